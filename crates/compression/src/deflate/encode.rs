@@ -0,0 +1,877 @@
+//! [DEFLATE](https://www.rfc-editor.org/rfc/rfc1951) compression
+//!
+//! The input is first turned into a sequence of literals and back-references to
+//! earlier data (LZ77), using hash chains to find repeated sequences. The sequence is then
+//! split into blocks and each block is written with whichever encoding is the smallest:
+//! Uncompressed, with the fixed huffman codes or with huffman codes built from the block itself.
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// The largest number of bytes in an uncompressed block
+const MAX_STORED_BLOCK_LENGTH: usize = u16::MAX as usize;
+
+/// The largest number of literals and back-references in a compressed block
+///
+/// Smaller blocks adapt faster to changes in the data, larger blocks spend
+/// less space on their huffman codes.
+const MAX_BLOCK_TOKENS: usize = 1 << 14;
+
+const MIN_MATCH_LENGTH: usize = 3;
+const MAX_MATCH_LENGTH: usize = 258;
+
+/// The largest distance that a back-reference can have
+const WINDOW_SIZE: usize = 1 << 15;
+
+const HASH_BITS: usize = 15;
+const HASH_SIZE: usize = 1 << HASH_BITS;
+
+/// Marks the end of a hash chain
+const NO_POSITION: usize = usize::MAX;
+
+const END_OF_BLOCK: usize = 256;
+const NUM_LITERAL_CODES: usize = 286;
+const NUM_DISTANCE_CODES: usize = 30;
+const NUM_CODE_LENGTH_CODES: usize = 19;
+
+const MAX_CODE_LENGTH: u8 = 15;
+const MAX_CODE_LENGTH_CODE_LENGTH: u8 = 7;
+
+/// The order in which the code lengths of the code length alphabet are stored
+const CODE_LENGTH_ORDER: [usize; NUM_CODE_LENGTH_CODES] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// The smallest length for each of the length codes `257..=285`
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+
+const LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// The smallest distance for each of the distance codes
+const DISTANCE_BASES: [u16; NUM_DISTANCE_CODES] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+const DISTANCE_EXTRA_BITS: [u8; NUM_DISTANCE_CODES] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// How much effort is spent on finding repeated sequences, from `0` (no compression) to `9`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CompressionLevel(u8);
+
+impl CompressionLevel {
+    /// Store the data without compressing it
+    pub const NONE: Self = Self(0);
+    pub const FASTEST: Self = Self(1);
+    pub const DEFAULT: Self = Self(6);
+    pub const BEST: Self = Self(9);
+
+    /// Levels above `9` are treated like `9`
+    #[inline]
+    #[must_use]
+    pub fn new(level: u8) -> Self {
+        Self(level.min(Self::BEST.0))
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn value(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns `None` for [CompressionLevel::NONE]
+    #[must_use]
+    fn match_parameters(&self) -> Option<MatchParameters> {
+        let (max_chain_length, nice_length, lazy_matching) = match self.0 {
+            0 => return None,
+            1 => (4, 8, false),
+            2 => (8, 16, false),
+            3 => (32, 32, false),
+            4 => (16, 32, true),
+            5 => (32, 64, true),
+            6 => (128, 128, true),
+            7 => (256, 128, true),
+            8 => (1024, MAX_MATCH_LENGTH, true),
+            _ => (4096, MAX_MATCH_LENGTH, true),
+        };
+
+        Some(MatchParameters {
+            max_chain_length,
+            nice_length,
+            lazy_matching,
+        })
+    }
+}
+
+impl Default for CompressionLevel {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct MatchParameters {
+    /// The maximum number of earlier positions that are compared with the current one
+    max_chain_length: usize,
+
+    /// Matches of at least this length are taken without looking for longer ones
+    nice_length: usize,
+
+    /// Whether to check if the next position has a longer match before emitting a match
+    lazy_matching: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    Literal(u8),
+    Match { length: u16, distance: u16 },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Match {
+    length: usize,
+    distance: usize,
+}
+
+/// Finds earlier occurences of the data at a position
+///
+/// Positions with the same hash of their first three bytes are linked together.
+struct MatchFinder<'a> {
+    data: &'a [u8],
+    parameters: MatchParameters,
+
+    /// The most recent position for every hash
+    head: Vec<usize>,
+
+    /// The previous position with the same hash, for every position
+    previous: Vec<usize>,
+}
+
+/// Writes individual bits, starting with the least significant bit of each byte
+#[derive(Debug, Default)]
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u64,
+    num_bits: u8,
+}
+
+/// The codes of a huffman alphabet, with their bits reversed so they can be written
+/// by a [BitWriter]
+struct HuffmanCodes {
+    codes: Vec<u16>,
+    lengths: Vec<u8>,
+}
+
+/// Compress data using DEFLATE
+#[must_use]
+pub fn encode(data: &[u8], level: CompressionLevel) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+
+    let Some(parameters) = level.match_parameters() else {
+        // Even empty inputs need a final block
+        let mut remaining = data;
+        loop {
+            let (block, rest) = remaining.split_at(remaining.len().min(MAX_STORED_BLOCK_LENGTH));
+            remaining = rest;
+
+            write_stored_block(&mut writer, block, remaining.is_empty());
+            if remaining.is_empty() {
+                return writer.finish();
+            }
+        }
+    };
+
+    let tokens = MatchFinder::new(data, parameters).tokens();
+
+    let mut tokens = &tokens[..];
+    let mut bytes = data;
+    loop {
+        // Every block must be small enough to be stored uncompressed
+        let mut num_tokens = 0;
+        let mut block_length = 0;
+        while num_tokens < tokens.len().min(MAX_BLOCK_TOKENS) {
+            let token_length = tokens[num_tokens].length();
+            if MAX_STORED_BLOCK_LENGTH < block_length + token_length {
+                break;
+            }
+
+            block_length += token_length;
+            num_tokens += 1;
+        }
+
+        let (block_tokens, remaining_tokens) = tokens.split_at(num_tokens);
+        let (block_bytes, remaining_bytes) = bytes.split_at(block_length);
+        tokens = remaining_tokens;
+        bytes = remaining_bytes;
+
+        write_block(&mut writer, block_tokens, block_bytes, tokens.is_empty());
+        if tokens.is_empty() {
+            return writer.finish();
+        }
+    }
+}
+
+/// Write a block with the encoding that produces the least amount of bits
+fn write_block(writer: &mut BitWriter, tokens: &[Token], bytes: &[u8], is_final: bool) {
+    let mut literal_frequencies = vec![0; NUM_LITERAL_CODES];
+    let mut distance_frequencies = vec![0; NUM_DISTANCE_CODES];
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => literal_frequencies[byte as usize] += 1,
+            Token::Match { length, distance } => {
+                literal_frequencies[length_code(length).0] += 1;
+                distance_frequencies[distance_code(distance).0] += 1;
+            },
+        }
+    }
+    literal_frequencies[END_OF_BLOCK] += 1;
+
+    let literal_lengths = code_lengths(&literal_frequencies, MAX_CODE_LENGTH);
+    let distance_lengths = code_lengths(&distance_frequencies, MAX_CODE_LENGTH);
+    let header = DynamicHeader::new(&literal_lengths, &distance_lengths);
+
+    let (fixed_literal_lengths, fixed_distance_lengths) = fixed_code_lengths();
+
+    let dynamic_size = 3
+        + header.size()
+        + encoded_size(
+            &literal_frequencies,
+            &distance_frequencies,
+            &literal_lengths,
+            &distance_lengths,
+        );
+    let fixed_size = 3 + encoded_size(
+        &literal_frequencies,
+        &distance_frequencies,
+        &fixed_literal_lengths,
+        &fixed_distance_lengths,
+    );
+
+    // Uncompressed blocks start at a byte boundary and store their length
+    let padding = (8 - (writer.num_bits as usize + 3) % 8) % 8;
+    let stored_size = 3 + padding + 32 + 8 * bytes.len();
+
+    if stored_size < dynamic_size.min(fixed_size) {
+        write_stored_block(writer, bytes, is_final);
+        return;
+    }
+
+    writer.write_bits(is_final as u32, 1);
+    let (literal_codes, distance_codes) = if fixed_size <= dynamic_size {
+        writer.write_bits(0b01, 2);
+
+        (
+            HuffmanCodes::new(fixed_literal_lengths),
+            HuffmanCodes::new(fixed_distance_lengths),
+        )
+    } else {
+        writer.write_bits(0b10, 2);
+        header.write(writer);
+
+        (
+            HuffmanCodes::new(literal_lengths),
+            HuffmanCodes::new(distance_lengths),
+        )
+    };
+
+    for token in tokens {
+        match *token {
+            Token::Literal(byte) => literal_codes.write(writer, byte as usize),
+            Token::Match { length, distance } => {
+                let (symbol, num_extra_bits, extra_bits) = length_code(length);
+                literal_codes.write(writer, symbol);
+                writer.write_bits(extra_bits as u32, num_extra_bits);
+
+                let (symbol, num_extra_bits, extra_bits) = distance_code(distance);
+                distance_codes.write(writer, symbol);
+                writer.write_bits(extra_bits as u32, num_extra_bits);
+            },
+        }
+    }
+
+    literal_codes.write(writer, END_OF_BLOCK);
+}
+
+fn write_stored_block(writer: &mut BitWriter, bytes: &[u8], is_final: bool) {
+    debug_assert!(bytes.len() <= MAX_STORED_BLOCK_LENGTH);
+
+    writer.write_bits(is_final as u32, 1);
+    writer.write_bits(0b00, 2);
+    writer.align_to_byte_boundary();
+
+    let length = bytes.len() as u16;
+    writer.output.extend_from_slice(&length.to_le_bytes());
+    writer.output.extend_from_slice(&(!length).to_le_bytes());
+    writer.output.extend_from_slice(bytes);
+}
+
+/// The number of bits needed to encode the symbols of a block with the given code lengths
+fn encoded_size(
+    literal_frequencies: &[u32],
+    distance_frequencies: &[u32],
+    literal_lengths: &[u8],
+    distance_lengths: &[u8],
+) -> usize {
+    let literal_size: usize = literal_frequencies
+        .iter()
+        .zip(literal_lengths)
+        .enumerate()
+        .map(|(symbol, (&frequency, &length))| {
+            let num_extra_bits = symbol
+                .checked_sub(END_OF_BLOCK + 1)
+                .map_or(0, |code| LENGTH_EXTRA_BITS[code]);
+            frequency as usize * (length + num_extra_bits) as usize
+        })
+        .sum();
+
+    let distance_size: usize = distance_frequencies
+        .iter()
+        .zip(distance_lengths)
+        .zip(DISTANCE_EXTRA_BITS)
+        .map(|((&frequency, &length), num_extra_bits)| {
+            frequency as usize * (length + num_extra_bits) as usize
+        })
+        .sum();
+
+    literal_size + distance_size
+}
+
+/// The code lengths of the fixed huffman codes, see <https://www.rfc-editor.org/rfc/rfc1951#section-3.2.6>
+fn fixed_code_lengths() -> (Vec<u8>, Vec<u8>) {
+    let mut literal_lengths = vec![8; 144];
+    literal_lengths.extend([9; 112]);
+    literal_lengths.extend([7; 24]);
+    literal_lengths.extend([8; 8]);
+
+    (literal_lengths, vec![5; 32])
+}
+
+/// Returns the length symbol, the number of extra bits and their value
+fn length_code(length: u16) -> (usize, u8, u16) {
+    let code = LENGTH_BASES
+        .iter()
+        .rposition(|&base| base <= length)
+        .expect("match is at least three bytes long");
+
+    (
+        END_OF_BLOCK + 1 + code,
+        LENGTH_EXTRA_BITS[code],
+        length - LENGTH_BASES[code],
+    )
+}
+
+/// Returns the distance symbol, the number of extra bits and their value
+fn distance_code(distance: u16) -> (usize, u8, u16) {
+    let code = DISTANCE_BASES
+        .iter()
+        .rposition(|&base| base <= distance)
+        .expect("distance is at least one");
+
+    (
+        code,
+        DISTANCE_EXTRA_BITS[code],
+        distance - DISTANCE_BASES[code],
+    )
+}
+
+/// The code lengths of a dynamic block, which are themselves huffman coded
+///
+/// See <https://www.rfc-editor.org/rfc/rfc1951#section-3.2.7>
+struct DynamicHeader {
+    num_literal_codes: usize,
+    num_distance_codes: usize,
+    num_code_length_codes: usize,
+
+    /// Code lengths and repetitions, along with the value of their extra bits
+    symbols: Vec<(usize, u8)>,
+    code_length_lengths: Vec<u8>,
+}
+
+impl DynamicHeader {
+    fn new(literal_lengths: &[u8], distance_lengths: &[u8]) -> Self {
+        // Trailing unused codes don't need to be stored
+        let num_literal_codes = literal_lengths
+            .iter()
+            .rposition(|&length| length != 0)
+            .map_or(0, |index| index + 1)
+            .max(END_OF_BLOCK + 1);
+        let num_distance_codes = distance_lengths
+            .iter()
+            .rposition(|&length| length != 0)
+            .map_or(0, |index| index + 1)
+            .max(1);
+
+        let lengths: Vec<u8> = literal_lengths[..num_literal_codes]
+            .iter()
+            .chain(&distance_lengths[..num_distance_codes])
+            .copied()
+            .collect();
+        let symbols = run_length_encode(&lengths);
+
+        let mut frequencies = vec![0; NUM_CODE_LENGTH_CODES];
+        for &(symbol, _) in &symbols {
+            frequencies[symbol] += 1;
+        }
+        let code_length_lengths = code_lengths(&frequencies, MAX_CODE_LENGTH_CODE_LENGTH);
+
+        let num_code_length_codes = CODE_LENGTH_ORDER
+            .iter()
+            .rposition(|&symbol| code_length_lengths[symbol] != 0)
+            .map_or(0, |index| index + 1)
+            .max(4);
+
+        Self {
+            num_literal_codes,
+            num_distance_codes,
+            num_code_length_codes,
+            symbols,
+            code_length_lengths,
+        }
+    }
+
+    /// The size of the header in bits
+    fn size(&self) -> usize {
+        let symbols_size: usize = self
+            .symbols
+            .iter()
+            .map(|&(symbol, _)| {
+                (self.code_length_lengths[symbol] + num_repeat_extra_bits(symbol)) as usize
+            })
+            .sum();
+
+        5 + 5 + 4 + 3 * self.num_code_length_codes + symbols_size
+    }
+
+    fn write(&self, writer: &mut BitWriter) {
+        writer.write_bits((self.num_literal_codes - 257) as u32, 5);
+        writer.write_bits((self.num_distance_codes - 1) as u32, 5);
+        writer.write_bits((self.num_code_length_codes - 4) as u32, 4);
+
+        for &symbol in &CODE_LENGTH_ORDER[..self.num_code_length_codes] {
+            writer.write_bits(self.code_length_lengths[symbol] as u32, 3);
+        }
+
+        let codes = HuffmanCodes::new(self.code_length_lengths.clone());
+        for &(symbol, extra_bits) in &self.symbols {
+            codes.write(writer, symbol);
+            writer.write_bits(extra_bits as u32, num_repeat_extra_bits(symbol));
+        }
+    }
+}
+
+fn num_repeat_extra_bits(symbol: usize) -> u8 {
+    match symbol {
+        16 => 2,
+        17 => 3,
+        18 => 7,
+        _ => 0,
+    }
+}
+
+/// Replace runs of equal code lengths with the repetition symbols `16`, `17` and `18`
+fn run_length_encode(lengths: &[u8]) -> Vec<(usize, u8)> {
+    let mut symbols = vec![];
+
+    let mut index = 0;
+    while index < lengths.len() {
+        let length = lengths[index];
+        let run_length = lengths[index..]
+            .iter()
+            .take_while(|&&other| other == length)
+            .count();
+
+        if length == 0 && 11 <= run_length {
+            let repeat = run_length.min(138);
+            symbols.push((18, (repeat - 11) as u8));
+            index += repeat;
+        } else if length == 0 && 3 <= run_length {
+            let repeat = run_length.min(10);
+            symbols.push((17, (repeat - 3) as u8));
+            index += repeat;
+        } else if length != 0 && 4 <= run_length {
+            // The first length has to be stored, the rest repeat it
+            let repeat = (run_length - 1).min(6);
+            symbols.push((length as usize, 0));
+            symbols.push((16, (repeat - 3) as u8));
+            index += 1 + repeat;
+        } else {
+            symbols.push((length as usize, 0));
+            index += 1;
+        }
+    }
+
+    symbols
+}
+
+/// Compute the lengths of a huffman code for symbols with the given frequencies,
+/// without exceeding the maximum length
+///
+/// At least two symbols are always assigned a code, since a single code of length one
+/// would not be complete.
+fn code_lengths(frequencies: &[u32], max_length: u8) -> Vec<u8> {
+    let mut frequencies = frequencies.to_vec();
+
+    loop {
+        let lengths = huffman_code_lengths(&frequencies);
+        if lengths.iter().all(|&length| length <= max_length) {
+            return lengths;
+        }
+
+        // Making the frequencies more similar makes the tree shallower.
+        // Eventually, all used symbols have the same frequency and the tree is balanced.
+        for frequency in &mut frequencies {
+            if *frequency != 0 {
+                *frequency = (*frequency / 2).max(1);
+            }
+        }
+    }
+}
+
+fn huffman_code_lengths(frequencies: &[u32]) -> Vec<u8> {
+    let mut lengths = vec![0; frequencies.len()];
+
+    let used_symbols: Vec<usize> = (0..frequencies.len())
+        .filter(|&symbol| frequencies[symbol] != 0)
+        .collect();
+
+    match used_symbols[..] {
+        [] => {
+            lengths[0] = 1;
+            lengths[1] = 1;
+            return lengths;
+        },
+        [symbol] => {
+            lengths[symbol] = 1;
+            lengths[if symbol == 0 { 1 } else { 0 }] = 1;
+            return lengths;
+        },
+        _ => {},
+    }
+
+    // The first nodes are the leaves, every node that is created later is the parent of
+    // two earlier nodes
+    let mut parents = vec![0; used_symbols.len()];
+    let mut queue: BinaryHeap<Reverse<(u32, usize)>> = used_symbols
+        .iter()
+        .enumerate()
+        .map(|(node, &symbol)| Reverse((frequencies[symbol], node)))
+        .collect();
+
+    while let (Some(Reverse((first_frequency, first))), Some(Reverse((second_frequency, second)))) =
+        (queue.pop(), queue.pop())
+    {
+        let parent = parents.len();
+        parents.push(parent);
+        parents[first] = parent;
+        parents[second] = parent;
+
+        queue.push(Reverse((first_frequency + second_frequency, parent)));
+    }
+
+    // Parents always come after their children, so the depths can be computed from the root downwards
+    let root = parents.len() - 1;
+    let mut depths = vec![0_usize; parents.len()];
+    for node in (0..root).rev() {
+        depths[node] = depths[parents[node]] + 1;
+    }
+
+    for (node, &symbol) in used_symbols.iter().enumerate() {
+        lengths[symbol] = u8::try_from(depths[node]).unwrap_or(u8::MAX);
+    }
+
+    lengths
+}
+
+impl HuffmanCodes {
+    /// Assign canonical codes to the symbols, see <https://www.rfc-editor.org/rfc/rfc1951#section-3.2.2>
+    fn new(lengths: Vec<u8>) -> Self {
+        let mut length_counts = [0_u16; MAX_CODE_LENGTH as usize + 1];
+        for &length in &lengths {
+            length_counts[length as usize] += 1;
+        }
+        length_counts[0] = 0;
+
+        let mut next_code = [0_u16; MAX_CODE_LENGTH as usize + 1];
+        let mut code = 0;
+        for bits in 1..=MAX_CODE_LENGTH as usize {
+            code = (code + length_counts[bits - 1]) << 1;
+            next_code[bits] = code;
+        }
+
+        let codes = lengths
+            .iter()
+            .map(|&length| {
+                if length == 0 {
+                    return 0;
+                }
+
+                let code = next_code[length as usize];
+                next_code[length as usize] += 1;
+
+                // Huffman codes are written starting with their most significant bit
+                code.reverse_bits() >> (16 - length)
+            })
+            .collect();
+
+        Self { codes, lengths }
+    }
+
+    fn write(&self, writer: &mut BitWriter, symbol: usize) {
+        debug_assert_ne!(self.lengths[symbol], 0, "symbol {symbol} has no code");
+
+        writer.write_bits(self.codes[symbol] as u32, self.lengths[symbol]);
+    }
+}
+
+impl<'a> MatchFinder<'a> {
+    fn new(data: &'a [u8], parameters: MatchParameters) -> Self {
+        Self {
+            data,
+            parameters,
+            head: vec![NO_POSITION; HASH_SIZE],
+            previous: vec![NO_POSITION; data.len()],
+        }
+    }
+
+    /// Split the data into literals and back-references
+    fn tokens(mut self) -> Vec<Token> {
+        let mut tokens = vec![];
+        let mut position = 0;
+
+        while position < self.data.len() {
+            let Some(mut longest_match) = self.longest_match(position) else {
+                self.insert(position);
+                tokens.push(Token::Literal(self.data[position]));
+                position += 1;
+                continue;
+            };
+            self.insert(position);
+
+            // Emitting a literal first is worth it if the next position has a longer match
+            while self.parameters.lazy_matching
+                && longest_match.length < self.parameters.nice_length
+                && position + 1 < self.data.len()
+            {
+                match self.longest_match(position + 1) {
+                    Some(next_match) if longest_match.length < next_match.length => {
+                        tokens.push(Token::Literal(self.data[position]));
+                        position += 1;
+                        self.insert(position);
+                        longest_match = next_match;
+                    },
+                    _ => break,
+                }
+            }
+
+            tokens.push(Token::Match {
+                length: longest_match.length as u16,
+                distance: longest_match.distance as u16,
+            });
+
+            for skipped_position in position + 1..position + longest_match.length {
+                self.insert(skipped_position);
+            }
+            position += longest_match.length;
+        }
+
+        tokens
+    }
+
+    fn hash(&self, position: usize) -> usize {
+        let bytes = &self.data[position..][..MIN_MATCH_LENGTH];
+        let hash = ((bytes[0] as usize) << 10) ^ ((bytes[1] as usize) << 5) ^ bytes[2] as usize;
+        hash & (HASH_SIZE - 1)
+    }
+
+    fn insert(&mut self, position: usize) {
+        if self.data.len() < position + MIN_MATCH_LENGTH {
+            return;
+        }
+
+        let hash = self.hash(position);
+        self.previous[position] = self.head[hash];
+        self.head[hash] = position;
+    }
+
+    /// Find the longest earlier occurence of the data at the given position
+    ///
+    /// The position itself must not have been inserted yet.
+    fn longest_match(&self, position: usize) -> Option<Match> {
+        if self.data.len() < position + MIN_MATCH_LENGTH {
+            return None;
+        }
+
+        let max_length = (self.data.len() - position).min(MAX_MATCH_LENGTH);
+        let current = &self.data[position..][..max_length];
+
+        let mut best_match: Option<Match> = None;
+        let mut candidate = self.head[self.hash(position)];
+        for _ in 0..self.parameters.max_chain_length {
+            if candidate == NO_POSITION || WINDOW_SIZE < position - candidate {
+                break;
+            }
+
+            let length = current
+                .iter()
+                .zip(&self.data[candidate..])
+                .take_while(|(a, b)| a == b)
+                .count();
+
+            if best_match.is_none_or(|best_match| best_match.length < length) {
+                best_match = Some(Match {
+                    length,
+                    distance: position - candidate,
+                });
+
+                if self.parameters.nice_length <= length {
+                    break;
+                }
+            }
+
+            candidate = self.previous[candidate];
+        }
+
+        best_match.filter(|best_match| MIN_MATCH_LENGTH <= best_match.length)
+    }
+}
+
+impl Token {
+    /// The number of bytes that the token stands for
+    fn length(&self) -> usize {
+        match self {
+            Self::Literal(_) => 1,
+            Self::Match { length, .. } => *length as usize,
+        }
+    }
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, bits: u32, num_bits: u8) {
+        debug_assert!(num_bits <= 32);
+
+        self.buffer |= (bits as u64) << self.num_bits;
+        self.num_bits += num_bits;
+
+        while 8 <= self.num_bits {
+            self.output.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.num_bits -= 8;
+        }
+    }
+
+    fn align_to_byte_boundary(&mut self) {
+        if self.num_bits != 0 {
+            self.output.push(self.buffer as u8);
+            self.buffer = 0;
+            self.num_bits = 0;
+        }
+    }
+
+    #[must_use]
+    fn finish(mut self) -> Vec<u8> {
+        self.align_to_byte_boundary();
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deflate::decompress;
+
+    fn round_trip(data: &[u8], level: CompressionLevel) -> Vec<u8> {
+        let compressed = encode(data, level);
+        let (decompressed, num_consumed_bytes) =
+            decompress(&compressed).expect("compressed data is valid");
+
+        assert_eq!(decompressed, data);
+        assert_eq!(num_consumed_bytes, compressed.len());
+        compressed
+    }
+
+    #[test]
+    fn empty_input() {
+        for level in 0..=9 {
+            round_trip(&[], CompressionLevel::new(level));
+        }
+    }
+
+    #[test]
+    fn repetitive_input() {
+        let data = b"Stormlicht Stormlicht Stormlicht, abcabcabcabcabcabc".repeat(100);
+
+        for level in 1..=9 {
+            let compressed = round_trip(&data, CompressionLevel::new(level));
+            assert!(compressed.len() < data.len() / 2);
+        }
+
+        let compressed = round_trip(&data, CompressionLevel::BEST);
+        assert!(compressed.len() < data.len() / 20);
+
+        let stored = round_trip(&data, CompressionLevel::NONE);
+        assert!(data.len() < stored.len());
+    }
+
+    #[test]
+    fn incompressible_input() {
+        // A simple xorshift generator, so the data has no patterns
+        let mut state: u32 = 0x1234_5678;
+        let data: Vec<u8> = (0..100_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+
+        let compressed = round_trip(&data, CompressionLevel::DEFAULT);
+
+        // The blocks are stored, so the overhead is small
+        assert!(compressed.len() < data.len() + 100);
+    }
+
+    #[test]
+    fn long_runs() {
+        let mut data = vec![0; 70_000];
+        data.extend(b"a".repeat(1000));
+        data.extend(0..=255);
+
+        for level in [
+            CompressionLevel::NONE,
+            CompressionLevel::FASTEST,
+            CompressionLevel::BEST,
+        ] {
+            round_trip(&data, level);
+        }
+    }
+
+    #[test]
+    fn code_lengths_are_limited() {
+        // Fibonacci frequencies produce the deepest possible huffman trees
+        let mut frequencies = vec![1_u32, 1];
+        while frequencies.len() < 30 {
+            frequencies
+                .push(frequencies[frequencies.len() - 1] + frequencies[frequencies.len() - 2]);
+        }
+
+        let lengths = code_lengths(&frequencies, MAX_CODE_LENGTH);
+        assert!(lengths
+            .iter()
+            .all(|&length| 0 < length && length <= MAX_CODE_LENGTH));
+
+        // The code must still be complete
+        let kraft_sum: f64 = lengths
+            .iter()
+            .map(|&length| 0.5_f64.powi(length as i32))
+            .sum();
+        assert_eq!(kraft_sum, 1.);
+    }
+}
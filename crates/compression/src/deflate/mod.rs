@@ -1,3 +1,5 @@
+mod encode;
+
 use crate::huffman::HuffmanTree;
 
 use error_derive::Error;
@@ -5,6 +7,8 @@ use sl_std::bitreader::{self, BitReader};
 
 use std::cmp::{min, Ordering};
 
+pub use encode::{encode, CompressionLevel};
+
 #[derive(Clone, Copy, Debug, Error)]
 pub enum Error {
     #[msg = "invalid compression scheme"]
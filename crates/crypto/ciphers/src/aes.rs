@@ -1,7 +1,14 @@
 //! [AES](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.197.pdf) implementation.
+//!
+//! On x86_64 CPUs that support it, the [AES-NI](https://en.wikipedia.org/wiki/AES_instruction_set)
+//! instruction set is used. Unlike the table-based software fallback, these instructions
+//! run in constant time.
 
 use crate::BlockCipher;
 
+#[cfg(target_arch = "x86_64")]
+use crate::aes_ni;
+
 const S_BOX: [u8; 256] = [
     0x63, 0x7C, 0x77, 0x7B, 0xF2, 0x6B, 0x6F, 0xC5, 0x30, 0x01, 0x67, 0x2B, 0xFE, 0xD7, 0xAB, 0x76,
     0xCA, 0x82, 0xC9, 0x7D, 0xFA, 0x59, 0x47, 0xF0, 0xAD, 0xD4, 0xA2, 0xAF, 0x9C, 0xA4, 0x72, 0xC0,
@@ -133,6 +140,8 @@ pub struct AesCipher<const S: usize, const R: usize> {
 }
 
 pub type Aes128Cipher = AesCipher<128, 11>;
+pub type Aes192Cipher = AesCipher<192, 13>;
+pub type Aes256Cipher = AesCipher<256, 15>;
 
 #[inline]
 fn rot_word(word_bytes: [u8; 4]) -> [u8; 4] {
@@ -156,44 +165,57 @@ fn xor_words(a: [u8; 4], b: [u8; 4]) -> [u8; 4] {
     [a[0] ^ b[0], a[1] ^ b[1], a[2] ^ b[2], a[3] ^ b[3]]
 }
 
-// expand a master key into B round keys
-fn key_expand<const A: usize, const B: usize>(master_key: [u8; A]) -> [[u8; A]; B] {
-    let mut round_keys = [[0; A]; B];
-    round_keys[0] = master_key;
+/// Expand a master key of `N` bytes into `R` round keys
+fn key_expand<const N: usize, const R: usize>(master_key: [u8; N]) -> [[u8; 16]; R] {
+    let words_per_key = N / 4;
 
-    let mut previous_word = master_key[A - 4..A].try_into().unwrap();
-    let mut previous_round_key = round_keys[0];
+    // AES-256 requires 60 words, more than any other key size
+    let mut words = [[0; 4]; 60];
+    for (word, key_bytes) in words.iter_mut().zip(master_key.chunks_exact(4)) {
+        word.copy_from_slice(key_bytes);
+    }
 
-    for (round_key_index, round_key) in round_keys.iter_mut().enumerate().skip(1) {
-        // First word in each round key is special
-        let tmp = xor_words(
-            sub_word(rot_word(previous_word)),
-            ROUND_CONSTANT[round_key_index],
-        );
-        let first_word_of_last_key = previous_round_key[..4].try_into().unwrap();
-        previous_word = xor_words(first_word_of_last_key, tmp);
-        round_key[0..4].copy_from_slice(&previous_word);
-
-        // The other ones are simpler
-        for (word, word_in_previous_round_key) in round_key
-            .chunks_exact_mut(4)
-            .zip(previous_round_key.chunks_exact(4))
-            .skip(1)
-        {
-            previous_word = xor_words(
-                word_in_previous_round_key.try_into().unwrap(),
-                previous_word,
-            );
-            word.copy_from_slice(&previous_word);
+    for i in words_per_key..4 * R {
+        let mut tmp = words[i - 1];
+        if i % words_per_key == 0 {
+            tmp = xor_words(sub_word(rot_word(tmp)), ROUND_CONSTANT[i / words_per_key]);
+        } else if words_per_key > 6 && i % words_per_key == 4 {
+            tmp = sub_word(tmp);
         }
-        previous_round_key = *round_key;
+        words[i] = xor_words(words[i - words_per_key], tmp);
     }
 
+    let mut round_keys = [[0; 16]; R];
+    for (round_key, round_key_words) in round_keys.iter_mut().zip(words.chunks_exact(4)) {
+        for (destination, word) in round_key.chunks_exact_mut(4).zip(round_key_words) {
+            destination.copy_from_slice(word);
+        }
+    }
     round_keys
 }
 
 impl<const A: usize, const B: usize> AesCipher<A, B> {
     pub fn encrypt_block(&mut self, input: [u8; 16]) -> [u8; 16] {
+        #[cfg(target_arch = "x86_64")]
+        if std::arch::is_x86_feature_detected!("aes") {
+            // SAFETY: We just checked that the CPU supports AES-NI
+            return unsafe { aes_ni::encrypt_block(&self.round_keys, input) };
+        }
+
+        self.encrypt_block_in_software(input)
+    }
+
+    pub fn decrypt_block(&mut self, input: [u8; 16]) -> [u8; 16] {
+        #[cfg(target_arch = "x86_64")]
+        if std::arch::is_x86_feature_detected!("aes") {
+            // SAFETY: We just checked that the CPU supports AES-NI
+            return unsafe { aes_ni::decrypt_block(&self.round_keys, input) };
+        }
+
+        self.decrypt_block_in_software(input)
+    }
+
+    fn encrypt_block_in_software(&mut self, input: [u8; 16]) -> [u8; 16] {
         self.set_state(input);
         self.add_round_key(0);
 
@@ -217,7 +239,7 @@ impl<const A: usize, const B: usize> AesCipher<A, B> {
         output
     }
 
-    pub fn decrypt_block(&mut self, input: [u8; 16]) -> [u8; 16] {
+    fn decrypt_block_in_software(&mut self, input: [u8; 16]) -> [u8; 16] {
         self.set_state(input);
         self.add_round_key(B - 1);
         self.inverse_shift_rows();
@@ -404,29 +426,37 @@ impl<const A: usize, const B: usize> AesCipher<A, B> {
     }
 }
 
-impl BlockCipher for AesCipher<128, 11> {
-    type Block = [u8; 16];
-    type Key = [u8; 16];
-
-    fn new(key: Self::Key) -> Self
-    where
-        Self: Sized,
-    {
-        Self {
-            round_keys: key_expand::<16, 11>(key),
-            state: [[0; 4]; 4],
-        }
-    }
+macro_rules! impl_block_cipher {
+    ($key_size: expr, $num_round_keys: expr) => {
+        impl BlockCipher for AesCipher<$key_size, $num_round_keys> {
+            type Block = [u8; 16];
+            type Key = [u8; $key_size / 8];
+
+            fn new(key: Self::Key) -> Self
+            where
+                Self: Sized,
+            {
+                Self {
+                    round_keys: key_expand::<{ $key_size / 8 }, $num_round_keys>(key),
+                    state: [[0; 4]; 4],
+                }
+            }
 
-    fn encrypt_block(&mut self, input: Self::Block) -> Self::Block {
-        self.encrypt_block(input)
-    }
+            fn encrypt_block(&mut self, input: Self::Block) -> Self::Block {
+                self.encrypt_block(input)
+            }
 
-    fn decrypt_block(&mut self, input: Self::Block) -> Self::Block {
-        self.decrypt_block(input)
-    }
+            fn decrypt_block(&mut self, input: Self::Block) -> Self::Block {
+                self.decrypt_block(input)
+            }
+        }
+    };
 }
 
+impl_block_cipher!(128, 11);
+impl_block_cipher!(192, 13);
+impl_block_cipher!(256, 15);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -445,7 +475,7 @@ mod tests {
 
     #[test]
     fn test_sub_bytes() {
-        let mut aes = AesCipher::new(*b"1234567890ABCDEF");
+        let mut aes = Aes128Cipher::new(*b"1234567890ABCDEF");
         let state = aes.state;
         aes.sub_bytes();
         aes.inverse_sub_bytes();
@@ -454,7 +484,7 @@ mod tests {
 
     #[test]
     fn test_expand_key() {
-        let aes = AesCipher::new(MASTER_KEY);
+        let aes = Aes128Cipher::new(MASTER_KEY);
         let expected_round_keys = [
             [
                 0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
@@ -516,7 +546,7 @@ mod tests {
             0x0b, 0x32,
         ];
 
-        let mut cipher = AesCipher::new(MASTER_KEY);
+        let mut cipher = Aes128Cipher::new(MASTER_KEY);
         let ciphertext = cipher.encrypt_block(plaintext_block);
         assert_eq!(ciphertext, expected_ciphertext);
     }
@@ -532,14 +562,68 @@ mod tests {
             0x0b, 0x32,
         ];
 
-        let mut cipher = AesCipher::new(MASTER_KEY);
+        let mut cipher = Aes128Cipher::new(MASTER_KEY);
         let plaintext = cipher.decrypt_block(ciphertext);
         assert_eq!(plaintext, expected_plaintext);
     }
 
+    #[test]
+    fn test_aes_key_sizes() {
+        // Example vectors from FIPS 197, Appendix C
+        let plaintext = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let key: [u8; 32] = std::array::from_fn(|i| i as u8);
+
+        let mut aes_128 = Aes128Cipher::new(key[..16].try_into().unwrap());
+        let ciphertext = aes_128.encrypt_block(plaintext);
+        assert_eq!(
+            ciphertext,
+            [
+                0x69, 0xc4, 0xe0, 0xd8, 0x6a, 0x7b, 0x04, 0x30, 0xd8, 0xcd, 0xb7, 0x80, 0x70, 0xb4,
+                0xc5, 0x5a
+            ]
+        );
+        assert_eq!(aes_128.decrypt_block(ciphertext), plaintext);
+
+        let mut aes_192 = Aes192Cipher::new(key[..24].try_into().unwrap());
+        let ciphertext = aes_192.encrypt_block(plaintext);
+        assert_eq!(
+            ciphertext,
+            [
+                0xdd, 0xa9, 0x7c, 0xa4, 0x86, 0x4c, 0xdf, 0xe0, 0x6e, 0xaf, 0x70, 0xa0, 0xec, 0x0d,
+                0x71, 0x91
+            ]
+        );
+        assert_eq!(aes_192.decrypt_block(ciphertext), plaintext);
+
+        let mut aes_256 = Aes256Cipher::new(key);
+        let ciphertext = aes_256.encrypt_block(plaintext);
+        assert_eq!(
+            ciphertext,
+            [
+                0x8e, 0xa2, 0xb7, 0xca, 0x51, 0x67, 0x45, 0xbf, 0xea, 0xfc, 0x49, 0x90, 0x4b, 0x49,
+                0x60, 0x89
+            ]
+        );
+        assert_eq!(aes_256.decrypt_block(ciphertext), plaintext);
+    }
+
+    #[test]
+    fn test_software_fallback() {
+        // Make sure the software implementation is tested even if AES-NI is available
+        let mut cipher = Aes256Cipher::new([0x42; 32]);
+        let plaintext = *b"sixteen bytes!!!";
+
+        let ciphertext = cipher.encrypt_block_in_software(plaintext);
+        assert_eq!(ciphertext, cipher.encrypt_block(plaintext));
+        assert_eq!(cipher.decrypt_block_in_software(ciphertext), plaintext);
+    }
+
     #[test]
     fn test_inverse_sub_bytes() {
-        let mut cipher = AesCipher::new(MASTER_KEY);
+        let mut cipher = Aes128Cipher::new(MASTER_KEY);
         cipher.set_state(MASTER_KEY); // Just some bogus data to init the state
         let state = cipher.state;
 
@@ -551,7 +635,7 @@ mod tests {
 
     #[test]
     fn test_inverse_mix_columns() {
-        let mut cipher = AesCipher::new(MASTER_KEY);
+        let mut cipher = Aes128Cipher::new(MASTER_KEY);
         cipher.set_state(MASTER_KEY); // Just some bogus data to init the state
         let state = cipher.state;
 
@@ -564,7 +648,7 @@ mod tests {
 
     #[test]
     fn test_shift_rows() {
-        let mut cipher = AesCipher::new(MASTER_KEY);
+        let mut cipher = Aes128Cipher::new(MASTER_KEY);
         cipher.set_state(MASTER_KEY); // Just some bogus data to init the state
         let state = cipher.state;
 
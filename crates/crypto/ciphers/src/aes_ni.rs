@@ -0,0 +1,58 @@
+//! Hardware accelerated AES using the AES-NI instruction set
+//!
+//! The round keys are expected in the same layout that the software implementation uses.
+
+use std::arch::x86_64::{
+    __m128i, _mm_aesdec_si128, _mm_aesdeclast_si128, _mm_aesenc_si128, _mm_aesenclast_si128,
+    _mm_aesimc_si128, _mm_loadu_si128, _mm_storeu_si128, _mm_xor_si128,
+};
+
+#[inline]
+fn load(bytes: &[u8; 16]) -> __m128i {
+    // SAFETY: The pointer is valid for 16 bytes and loadu does not require alignment
+    unsafe { _mm_loadu_si128(bytes.as_ptr().cast()) }
+}
+
+#[inline]
+fn store(block: __m128i) -> [u8; 16] {
+    let mut bytes = [0; 16];
+
+    // SAFETY: The pointer is valid for 16 bytes and storeu does not require alignment
+    unsafe { _mm_storeu_si128(bytes.as_mut_ptr().cast(), block) };
+
+    bytes
+}
+
+/// # Safety
+/// The CPU must support the `aes` target feature.
+#[target_feature(enable = "aes")]
+pub(crate) unsafe fn encrypt_block(round_keys: &[[u8; 16]], input: [u8; 16]) -> [u8; 16] {
+    let (first_key, remaining_keys) = round_keys.split_first().expect("no round keys");
+    let (last_key, middle_keys) = remaining_keys.split_last().expect("only one round key");
+
+    let mut state = _mm_xor_si128(load(&input), load(first_key));
+    for round_key in middle_keys {
+        state = _mm_aesenc_si128(state, load(round_key));
+    }
+    state = _mm_aesenclast_si128(state, load(last_key));
+
+    store(state)
+}
+
+/// # Safety
+/// The CPU must support the `aes` target feature.
+#[target_feature(enable = "aes")]
+pub(crate) unsafe fn decrypt_block(round_keys: &[[u8; 16]], input: [u8; 16]) -> [u8; 16] {
+    let (first_key, remaining_keys) = round_keys.split_first().expect("no round keys");
+    let (last_key, middle_keys) = remaining_keys.split_last().expect("only one round key");
+
+    // The equivalent inverse cipher requires InvMixColumns to be applied
+    // to all round keys except the first and the last one
+    let mut state = _mm_xor_si128(load(&input), load(last_key));
+    for round_key in middle_keys.iter().rev() {
+        state = _mm_aesdec_si128(state, _mm_aesimc_si128(load(round_key)));
+    }
+    state = _mm_aesdeclast_si128(state, load(first_key));
+
+    store(state)
+}
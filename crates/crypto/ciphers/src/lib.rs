@@ -1,7 +1,9 @@
 mod aes;
+#[cfg(target_arch = "x86_64")]
+mod aes_ni;
 mod chacha20;
 
-pub use aes::Aes128Cipher;
+pub use aes::{Aes128Cipher, Aes192Cipher, Aes256Cipher};
 pub use chacha20::ChaCha20;
 
 pub trait BlockCipher {
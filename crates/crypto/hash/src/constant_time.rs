@@ -0,0 +1,34 @@
+//! Helpers for handling secret values without leaking them through timing side channels
+
+/// Compare two byte slices in constant time
+///
+/// The runtime of this function only depends on the length of the inputs, not on their contents.
+/// Use this instead of `==` whenever secret values like message authentication codes are compared.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    // The length of a value is usually not secret
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let difference = a
+        .iter()
+        .zip(b)
+        .fold(0, |accumulator, (x, y)| accumulator | (x ^ y));
+
+    // Prevent the compiler from turning the fold into an early-exit comparison
+    std::hint::black_box(difference) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret!"));
+    }
+}
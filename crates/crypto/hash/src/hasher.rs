@@ -0,0 +1,72 @@
+use crate::{HashAlgorithm, Md5, Sha1, Sha224, Sha256, Sha384, Sha512};
+
+/// An incremental interface to any hash algorithm
+///
+/// Unlike [HashAlgorithm], this trait can be used as a trait object, which
+/// is useful when the algorithm is only known at runtime (for example
+/// during a TLS handshake or when checking subresource integrity metadata).
+///
+/// It is implemented for every cryptographic [HashAlgorithm].
+pub trait Hasher {
+    /// Feed more data into the hasher
+    fn update(&mut self, data: &[u8]);
+
+    /// Return the digest of all the data that was consumed so far
+    ///
+    /// This resets the hasher into its initial state, so it can be reused afterwards.
+    fn finalize(&mut self) -> Vec<u8>;
+
+    /// The length of the digest returned by [Hasher::finalize], in bytes
+    fn output_size(&self) -> usize;
+}
+
+// NOTE: A blanket implementation over all hash algorithms currently crashes the compiler
+//       (generic_const_exprs is incomplete), so we implement the trait for each algorithm instead.
+macro_rules! impl_hasher {
+    ($($algorithm: ty),*) => {
+        $(
+            impl Hasher for $algorithm {
+                fn update(&mut self, data: &[u8]) {
+                    HashAlgorithm::update(self, data);
+                }
+
+                fn finalize(&mut self) -> Vec<u8> {
+                    std::mem::take(self).finish().to_vec()
+                }
+
+                fn output_size(&self) -> usize {
+                    <Self as HashAlgorithm>::BLOCK_SIZE_OUT
+                }
+            }
+        )*
+    };
+}
+
+impl_hasher!(Md5, Sha1, Sha224, Sha256, Sha384, Sha512);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dynamic_hasher() {
+        let mut hashers: [Box<dyn Hasher>; 3] = [
+            Box::<Sha1>::default(),
+            Box::<Sha256>::default(),
+            Box::<Sha512>::default(),
+        ];
+
+        for hasher in &mut hashers {
+            hasher.update(b"a");
+            hasher.update(b"bc");
+        }
+
+        assert_eq!(hashers[0].finalize(), Sha1::hash(b"abc"));
+        assert_eq!(hashers[1].finalize(), Sha256::hash(b"abc"));
+        assert_eq!(hashers[2].finalize(), Sha512::hash(b"abc"));
+
+        // Finalizing resets the hasher
+        assert_eq!(hashers[1].output_size(), 32);
+        assert_eq!(hashers[1].finalize(), Sha256::hash(b""));
+    }
+}
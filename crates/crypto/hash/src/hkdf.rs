@@ -0,0 +1,93 @@
+//! HKDF ([RFC 5869](https://www.rfc-editor.org/rfc/rfc5869)) implementation
+
+use crate::CryptographicHashAlgorithm;
+
+pub(crate) fn extract<T: CryptographicHashAlgorithm>(
+    salt: &[u8],
+    input_key_material: &[u8],
+) -> [u8; T::BLOCK_SIZE_OUT]
+// Refer to the comment on the hmac function on why this trait bound is necessary
+where
+    [(); T::BLOCK_SIZE_IN]: Sized,
+{
+    // If no salt is provided, the spec uses a string of zeros with the length of the hash output.
+    // That is equivalent to an empty key, since hmac pads the key with zeros anyways.
+    T::hmac(salt, input_key_material)
+}
+
+pub(crate) fn expand<T: CryptographicHashAlgorithm>(
+    pseudo_random_key: &[u8],
+    info: &[u8],
+    output: &mut [u8],
+) where
+    [(); T::BLOCK_SIZE_IN]: Sized,
+    [(); T::BLOCK_SIZE_OUT]: Sized,
+{
+    assert!(
+        output.len() <= 255 * T::BLOCK_SIZE_OUT,
+        "HKDF cannot produce more than 255 blocks of output"
+    );
+
+    // T(i) = HMAC(PRK, T(i - 1) | info | i)
+    let mut previous_block: Vec<u8> = vec![];
+    for (index, chunk) in output.chunks_mut(T::BLOCK_SIZE_OUT).enumerate() {
+        let mut data = Vec::with_capacity(previous_block.len() + info.len() + 1);
+        data.extend_from_slice(&previous_block);
+        data.extend_from_slice(info);
+        data.push(index as u8 + 1);
+
+        let block = T::hmac(pseudo_random_key, &data);
+        chunk.copy_from_slice(&block[..chunk.len()]);
+        previous_block = block.to_vec();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+
+    #[test]
+    fn test_hkdf() {
+        // Test case 1 from RFC 5869, Appendix A
+        let input_key_material = [0x0b; 22];
+        let salt = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+        ];
+        let info = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+
+        let pseudo_random_key = extract::<Sha256>(&salt, &input_key_material);
+        assert_eq!(
+            pseudo_random_key,
+            [
+                0x07, 0x77, 0x09, 0x36, 0x2c, 0x2e, 0x32, 0xdf, 0x0d, 0xdc, 0x3f, 0x0d, 0xc4, 0x7b,
+                0xba, 0x63, 0x90, 0xb6, 0xc7, 0x3b, 0xb5, 0x0f, 0x9c, 0x31, 0x22, 0xec, 0x84, 0x4a,
+                0xd7, 0xc2, 0xb3, 0xe5
+            ]
+        );
+
+        let mut output_key_material = [0; 42];
+        expand::<Sha256>(&pseudo_random_key, &info, &mut output_key_material);
+        assert_eq!(
+            output_key_material,
+            [
+                0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36,
+                0x2f, 0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56,
+                0xec, 0xc4, 0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hkdf_without_salt() {
+        // Test case 3 from RFC 5869, Appendix A
+        assert_eq!(
+            extract::<Sha256>(&[], &[0x0b; 22]),
+            [
+                0x19, 0xef, 0x24, 0xa3, 0x2c, 0x71, 0x7b, 0x16, 0x7f, 0x33, 0xa9, 0x1d, 0x6f, 0x64,
+                0x8b, 0xdf, 0x96, 0x59, 0x67, 0x76, 0xaf, 0xdb, 0x63, 0x77, 0xac, 0x43, 0x4c, 0x1c,
+                0x29, 0x3c, 0xcb, 0x04
+            ]
+        );
+    }
+}
@@ -46,7 +46,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Md5;
+    use crate::{CryptographicHashAlgorithm, Md5, Sha384};
 
     #[test]
     fn test_hmac() {
@@ -58,4 +58,29 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_hmac_sha384() {
+        let tag = hmac::<Sha384>(b"key", b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            tag,
+            [
+                0xd7, 0xf4, 0x72, 0x7e, 0x2c, 0x0b, 0x39, 0xae, 0x0f, 0x1e, 0x40, 0xcc, 0x96, 0xf6,
+                0x02, 0x42, 0xd5, 0xb7, 0x80, 0x18, 0x41, 0xce, 0xa6, 0xfc, 0x59, 0x2c, 0x5d, 0x3e,
+                0x1a, 0xe5, 0x07, 0x00, 0x58, 0x2a, 0x96, 0xcf, 0x35, 0xe1, 0xe5, 0x54, 0x99, 0x5f,
+                0xe4, 0xe0, 0x33, 0x81, 0xc2, 0x37
+            ]
+        );
+
+        assert!(Sha384::verify_hmac(
+            b"key",
+            b"The quick brown fox jumps over the lazy dog",
+            &tag
+        ));
+        assert!(!Sha384::verify_hmac(
+            b"key",
+            b"The quick brown fox jumps over the lazy cat",
+            &tag
+        ));
+    }
 }
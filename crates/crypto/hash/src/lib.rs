@@ -2,14 +2,23 @@
 #![feature(cfg_match, generic_const_exprs)]
 
 mod adler32;
+mod constant_time;
 mod crc32;
+mod hasher;
+mod hkdf;
 mod hmac;
 mod md5;
 mod sha;
+mod sha1;
+mod sha512;
 
 pub use adler32::{adler32, Adler32Hasher};
+pub use constant_time::constant_time_eq;
+pub use hasher::Hasher;
 pub use md5::Md5;
 pub use sha::{Sha224, Sha256};
+pub use sha1::Sha1;
+pub use sha512::{Sha384, Sha512};
 pub use {crc32::crc32, crc32::Crc32Hasher};
 
 /// Something that is able to calculate a checksum over arbitrary bytes.
@@ -44,4 +53,37 @@ pub trait CryptographicHashAlgorithm: HashAlgorithm {
     {
         hmac::hmac::<Self>(key, data)
     }
+
+    /// Check whether `tag` is a valid HMAC for `data`
+    ///
+    /// The comparison runs in constant time.
+    fn verify_hmac(key: &[u8], data: &[u8], tag: &[u8]) -> bool
+    where
+        [(); Self::BLOCK_SIZE_IN]: Sized,
+        [(); Self::BLOCK_SIZE_OUT]: Sized,
+    {
+        constant_time_eq(&Self::hmac(key, data), tag)
+    }
+
+    /// Derive a pseudorandom key from some input keying material, as defined in
+    /// [RFC 5869](https://www.rfc-editor.org/rfc/rfc5869#section-2.2)
+    fn hkdf_extract(salt: &[u8], input_key_material: &[u8]) -> [u8; Self::BLOCK_SIZE_OUT]
+    where
+        [(); Self::BLOCK_SIZE_IN]: Sized,
+    {
+        hkdf::extract::<Self>(salt, input_key_material)
+    }
+
+    /// Expand a pseudorandom key into `output.len()` bytes of output keying material, as defined in
+    /// [RFC 5869](https://www.rfc-editor.org/rfc/rfc5869#section-2.3)
+    ///
+    /// # Panics
+    /// This function panics if more than `255 * BLOCK_SIZE_OUT` bytes of output are requested.
+    fn hkdf_expand(pseudo_random_key: &[u8], info: &[u8], output: &mut [u8])
+    where
+        [(); Self::BLOCK_SIZE_IN]: Sized,
+        [(); Self::BLOCK_SIZE_OUT]: Sized,
+    {
+        hkdf::expand::<Self>(pseudo_random_key, info, output)
+    }
 }
@@ -239,7 +239,7 @@ impl Sha256 {
         self.state[6] = self.state[6].wrapping_add(g);
         self.state[7] = self.state[7].wrapping_add(h);
 
-        self.num_bytes_consumed += 4;
+        self.num_bytes_consumed += 64;
         self.buffer_ptr = 0;
     }
 }
@@ -287,4 +287,17 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_sha256_multiple_blocks() {
+        // The encoded message length must account for every block that was processed
+        assert_eq!(
+            Sha256::hash(&[0x61; 1000]),
+            [
+                0x41, 0xed, 0xec, 0xe4, 0x2d, 0x63, 0xe8, 0xd9, 0xbf, 0x51, 0x5a, 0x9b, 0xa6, 0x93,
+                0x2e, 0x1c, 0x20, 0xcb, 0xc9, 0xf5, 0xa5, 0xd1, 0x34, 0x64, 0x5a, 0xdb, 0x5d, 0xb1,
+                0xb9, 0x73, 0x7e, 0xa3
+            ]
+        );
+    }
 }
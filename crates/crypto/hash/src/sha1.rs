@@ -0,0 +1,156 @@
+//! SHA-1 ([RFC 3174](https://www.rfc-editor.org/rfc/rfc3174)) hash implementation
+//!
+//! SHA-1 is considered broken and should not be used for anything security-related.
+//! It is only provided because some protocols (like WebSocket handshakes) still require it.
+
+use crate::{CryptographicHashAlgorithm, HashAlgorithm};
+
+const SHA1_INITIAL: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: [u8; 64],
+    buffer_ptr: usize,
+    num_bytes_consumed: u64,
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self {
+            state: SHA1_INITIAL,
+            buffer: [0; 64],
+            buffer_ptr: 0,
+            num_bytes_consumed: 0,
+        }
+    }
+}
+
+impl HashAlgorithm for Sha1 {
+    const BLOCK_SIZE_IN: usize = 64;
+    const BLOCK_SIZE_OUT: usize = 20;
+
+    fn update(&mut self, data: &[u8]) {
+        let bytes_to_fill = 64 - self.buffer_ptr;
+        if data.len() < bytes_to_fill {
+            self.buffer[self.buffer_ptr..self.buffer_ptr + data.len()].copy_from_slice(data);
+            self.buffer_ptr += data.len();
+            return;
+        }
+
+        self.buffer[self.buffer_ptr..].copy_from_slice(&data[..bytes_to_fill]);
+        self.step();
+
+        let chunks = data[bytes_to_fill..].chunks_exact(64);
+        let remaining_bytes = chunks.remainder();
+        for chunk in chunks {
+            self.buffer.copy_from_slice(chunk);
+            self.step();
+        }
+
+        self.buffer[..remaining_bytes.len()].copy_from_slice(remaining_bytes);
+        self.buffer_ptr = remaining_bytes.len();
+    }
+
+    fn finish(mut self) -> [u8; Self::BLOCK_SIZE_OUT] {
+        // Get the length (in bits) *before* we consume any padding
+        let length: u64 = (self.num_bytes_consumed + self.buffer_ptr as u64) * 8;
+
+        self.buffer[self.buffer_ptr] = 0x80;
+        self.buffer[self.buffer_ptr + 1..].fill(0);
+
+        // The last 8 bytes of the final block hold the message length.
+        // If they are already occupied, we need to process one more block.
+        if self.buffer_ptr >= 56 {
+            self.step();
+            self.buffer[..56].fill(0);
+        }
+
+        self.buffer[56..64].copy_from_slice(&length.to_be_bytes());
+        self.step();
+
+        let mut hash = [0; 20];
+        for (word, destination) in self.state.iter().zip(hash.chunks_exact_mut(4)) {
+            destination.copy_from_slice(&word.to_be_bytes());
+        }
+        hash
+    }
+}
+
+impl CryptographicHashAlgorithm for Sha1 {}
+
+impl Sha1 {
+    fn step(&mut self) {
+        let mut w = [0; 80];
+        for (index, word_bytes) in self.buffer.chunks_exact(4).enumerate() {
+            w[index] = u32::from_be_bytes(word_bytes.try_into().unwrap());
+        }
+
+        for t in 16..80 {
+            w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+
+        for (t, word) in w.iter().enumerate() {
+            let (f, k) = match t {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+
+            let tmp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(*word)
+                .wrapping_add(k);
+
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = tmp;
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+
+        self.num_bytes_consumed += 64;
+        self.buffer_ptr = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha1() {
+        assert_eq!(
+            Sha1::hash(b"abc"),
+            [
+                0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+                0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d
+            ]
+        );
+
+        assert_eq!(
+            Sha1::hash(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            [
+                0x84, 0x98, 0x3e, 0x44, 0x1c, 0x3b, 0xd2, 0x6e, 0xba, 0xae, 0x4a, 0xa1, 0xf9, 0x51,
+                0x29, 0xe5, 0xe5, 0x46, 0x70, 0xf1
+            ]
+        );
+
+        assert_eq!(
+            Sha1::hash(&[0x61; 1000]),
+            [
+                0x29, 0x1e, 0x9a, 0x6c, 0x66, 0x99, 0x49, 0x49, 0xb5, 0x7b, 0xa5, 0xe6, 0x50, 0x36,
+                0x1e, 0x98, 0xfc, 0x36, 0xb1, 0xba
+            ]
+        );
+    }
+}
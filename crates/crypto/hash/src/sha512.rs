@@ -0,0 +1,360 @@
+//! SHA-384 and SHA-512 ([RFC 6234](https://www.rfc-editor.org/rfc/rfc6234)) hash implementation
+//!
+//! Like the SHA-256 implementation, this only supports hashing data with a length that
+//! is a multiple of 8 bits.
+
+use crate::{CryptographicHashAlgorithm, HashAlgorithm};
+
+const K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+const SHA384_INITIAL: [u64; 8] = [
+    0xcbbb9d5dc1059ed8,
+    0x629a292a367cd507,
+    0x9159015a3070dd17,
+    0x152fecd8f70e5939,
+    0x67332667ffc00b31,
+    0x8eb44a8768581511,
+    0xdb0c2e0d64f98fa7,
+    0x47b5481dbefa4fa4,
+];
+
+const SHA512_INITIAL: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+#[inline]
+#[must_use]
+fn ch(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) | (!x & z)
+}
+
+#[inline]
+#[must_use]
+fn maj(x: u64, y: u64, z: u64) -> u64 {
+    (x & y) ^ (x & z) ^ (y & z)
+}
+
+#[inline]
+#[must_use]
+fn bsig0(x: u64) -> u64 {
+    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39)
+}
+
+#[inline]
+#[must_use]
+fn bsig1(x: u64) -> u64 {
+    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41)
+}
+
+#[inline]
+#[must_use]
+fn ssig0(x: u64) -> u64 {
+    x.rotate_right(1) ^ x.rotate_right(8) ^ (x >> 7)
+}
+
+#[inline]
+#[must_use]
+fn ssig1(x: u64) -> u64 {
+    x.rotate_right(19) ^ x.rotate_right(61) ^ (x >> 6)
+}
+
+// NOTE: SHA-384 is a truncated SHA-512 with different initial values, so we
+// just wrap a SHA-512 hasher.
+#[derive(Clone, Copy, Debug)]
+/// SHA-384 Hasher
+pub struct Sha384(Sha512);
+
+impl Default for Sha384 {
+    fn default() -> Self {
+        Self(Sha512 {
+            state: SHA384_INITIAL,
+            buffer: [0; 128],
+            buffer_ptr: 0,
+            num_bytes_consumed: 0,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+/// SHA-512 Hasher
+pub struct Sha512 {
+    state: [u64; 8],
+    buffer: [u8; 128],
+    buffer_ptr: usize,
+    num_bytes_consumed: u128,
+}
+
+impl Default for Sha512 {
+    fn default() -> Self {
+        Self {
+            state: SHA512_INITIAL,
+            buffer: [0; 128],
+            buffer_ptr: 0,
+            num_bytes_consumed: 0,
+        }
+    }
+}
+
+impl HashAlgorithm for Sha384 {
+    const BLOCK_SIZE_IN: usize = 128;
+    const BLOCK_SIZE_OUT: usize = 48;
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finish(self) -> [u8; 48] {
+        let sha512_hash = self.0.finish();
+        sha512_hash[..48].try_into().expect("slice has length 48")
+    }
+}
+
+impl CryptographicHashAlgorithm for Sha384 {}
+
+impl HashAlgorithm for Sha512 {
+    const BLOCK_SIZE_IN: usize = 128;
+    const BLOCK_SIZE_OUT: usize = 64;
+
+    fn update(&mut self, data: &[u8]) {
+        let bytes_to_fill = 128 - self.buffer_ptr;
+        if data.len() < bytes_to_fill {
+            self.buffer[self.buffer_ptr..self.buffer_ptr + data.len()].copy_from_slice(data);
+            self.buffer_ptr += data.len();
+            return;
+        }
+
+        self.buffer[self.buffer_ptr..].copy_from_slice(&data[..bytes_to_fill]);
+        self.step();
+
+        let chunks = data[bytes_to_fill..].chunks_exact(128);
+        let remaining_bytes = chunks.remainder();
+        for chunk in chunks {
+            self.buffer.copy_from_slice(chunk);
+            self.step();
+        }
+
+        self.buffer[..remaining_bytes.len()].copy_from_slice(remaining_bytes);
+        self.buffer_ptr = remaining_bytes.len();
+    }
+
+    fn finish(mut self) -> [u8; Self::BLOCK_SIZE_OUT] {
+        // Get the length (in bits) *before* we consume any padding
+        let length: u128 = (self.num_bytes_consumed + self.buffer_ptr as u128) * 8;
+
+        self.buffer[self.buffer_ptr] = 0x80;
+        self.buffer[self.buffer_ptr + 1..].fill(0);
+
+        // The last 16 bytes of the final block hold the message length.
+        // If they are already occupied, we need to process one more block.
+        if self.buffer_ptr >= 112 {
+            self.step();
+            self.buffer[..112].fill(0);
+        }
+
+        self.buffer[112..128].copy_from_slice(&length.to_be_bytes());
+        self.step();
+
+        let mut hash = [0; 64];
+        for (word, destination) in self.state.iter().zip(hash.chunks_exact_mut(8)) {
+            destination.copy_from_slice(&word.to_be_bytes());
+        }
+        hash
+    }
+}
+
+impl CryptographicHashAlgorithm for Sha512 {}
+
+impl Sha512 {
+    fn step(&mut self) {
+        let mut w = [0; 80];
+        for (index, word_bytes) in self.buffer.chunks_exact(8).enumerate() {
+            w[index] = u64::from_be_bytes(word_bytes.try_into().unwrap());
+        }
+
+        for t in 16..80 {
+            w[t] = ssig1(w[t - 2])
+                .wrapping_add(w[t - 7])
+                .wrapping_add(ssig0(w[t - 15]))
+                .wrapping_add(w[t - 16]);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+
+        for t in 0..80 {
+            let t1 = h
+                .wrapping_add(bsig1(e))
+                .wrapping_add(ch(e, f, g))
+                .wrapping_add(K[t])
+                .wrapping_add(w[t]);
+            let t2 = bsig0(a).wrapping_add(maj(a, b, c));
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        for (state, value) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state = state.wrapping_add(value);
+        }
+
+        self.num_bytes_consumed += 128;
+        self.buffer_ptr = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha384() {
+        assert_eq!(
+            Sha384::hash(b"abc"),
+            [
+                0xcb, 0x00, 0x75, 0x3f, 0x45, 0xa3, 0x5e, 0x8b, 0xb5, 0xa0, 0x3d, 0x69, 0x9a, 0xc6,
+                0x50, 0x07, 0x27, 0x2c, 0x32, 0xab, 0x0e, 0xde, 0xd1, 0x63, 0x1a, 0x8b, 0x60, 0x5a,
+                0x43, 0xff, 0x5b, 0xed, 0x80, 0x86, 0x07, 0x2b, 0xa1, 0xe7, 0xcc, 0x23, 0x58, 0xba,
+                0xec, 0xa1, 0x34, 0xc8, 0x25, 0xa7
+            ]
+        );
+
+        assert_eq!(
+            Sha384::hash(b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"),
+            [0x09, 0x33, 0x0c, 0x33, 0xf7, 0x11, 0x47, 0xe8, 0x3d, 0x19, 0x2f, 0xc7, 0x82, 0xcd, 0x1b, 0x47, 0x53, 0x11, 0x1b, 0x17, 0x3b, 0x3b, 0x05, 0xd2, 0x2f, 0xa0, 0x80, 0x86, 0xe3, 0xb0, 0xf7, 0x12, 0xfc, 0xc7, 0xc7, 0x1a, 0x55, 0x7e, 0x2d, 0xb9, 0x66, 0xc3, 0xe9, 0xfa, 0x91, 0x74, 0x60, 0x39]
+        );
+    }
+
+    #[test]
+    fn test_sha512() {
+        assert_eq!(
+            Sha512::hash(b"abc"),
+            [
+                0xdd, 0xaf, 0x35, 0xa1, 0x93, 0x61, 0x7a, 0xba, 0xcc, 0x41, 0x73, 0x49, 0xae, 0x20,
+                0x41, 0x31, 0x12, 0xe6, 0xfa, 0x4e, 0x89, 0xa9, 0x7e, 0xa2, 0x0a, 0x9e, 0xee, 0xe6,
+                0x4b, 0x55, 0xd3, 0x9a, 0x21, 0x92, 0x99, 0x2a, 0x27, 0x4f, 0xc1, 0xa8, 0x36, 0xba,
+                0x3c, 0x23, 0xa3, 0xfe, 0xeb, 0xbd, 0x45, 0x4d, 0x44, 0x23, 0x64, 0x3c, 0xe8, 0x0e,
+                0x2a, 0x9a, 0xc9, 0x4f, 0xa5, 0x4c, 0xa4, 0x9f
+            ]
+        );
+
+        assert_eq!(
+            Sha512::hash(b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"),
+            [0x8e, 0x95, 0x9b, 0x75, 0xda, 0xe3, 0x13, 0xda, 0x8c, 0xf4, 0xf7, 0x28, 0x14, 0xfc, 0x14, 0x3f, 0x8f, 0x77, 0x79, 0xc6, 0xeb, 0x9f, 0x7f, 0xa1, 0x72, 0x99, 0xae, 0xad, 0xb6, 0x88, 0x90, 0x18, 0x50, 0x1d, 0x28, 0x9e, 0x49, 0x00, 0xf7, 0xe4, 0x33, 0x1b, 0x99, 0xde, 0xc4, 0xb5, 0x43, 0x3a, 0xc7, 0xd3, 0x29, 0xee, 0xb6, 0xdd, 0x26, 0x54, 0x5e, 0x96, 0xe5, 0x5b, 0x87, 0x4b, 0xe9, 0x09]
+        );
+    }
+
+    #[test]
+    fn test_sha512_incremental() {
+        let data = [0x61; 1000];
+
+        let mut hasher = Sha512::default();
+        for chunk in data.chunks(7) {
+            hasher.update(chunk);
+        }
+
+        assert_eq!(hasher.finish(), Sha512::hash(&data));
+        assert_eq!(
+            Sha512::hash(&data),
+            [
+                0x67, 0xba, 0x55, 0x35, 0xa4, 0x6e, 0x3f, 0x86, 0xdb, 0xfb, 0xed, 0x8c, 0xbb, 0xaf,
+                0x01, 0x25, 0xc7, 0x6e, 0xd5, 0x49, 0xff, 0x8b, 0x0b, 0x9e, 0x03, 0xe0, 0xc8, 0x8c,
+                0xf9, 0x0f, 0xa6, 0x34, 0xfa, 0x7b, 0x12, 0xb4, 0x7d, 0x77, 0xb6, 0x94, 0xde, 0x48,
+                0x8a, 0xce, 0x8d, 0x9a, 0x65, 0x96, 0x7d, 0xc9, 0x6d, 0xf5, 0x99, 0x72, 0x7d, 0x32,
+                0x92, 0xa8, 0xd9, 0xd4, 0x47, 0x70, 0x9c, 0x97
+            ]
+        );
+    }
+}
@@ -23,6 +23,16 @@ pub struct Cache {
 #[derive(Debug, Default)]
 pub struct LockedCache(HashMap<Domain, CacheEntry>);
 
+/// A snapshot of a cached domain, used for diagnostics
+#[derive(Clone, Debug)]
+pub struct CachedDomain {
+    pub domain: Domain,
+    pub ip: IpAddr,
+
+    /// Time until the entry expires
+    pub expires_in: Duration,
+}
+
 impl Cache {
     pub fn insert(&self, domain: Domain, ip: IpAddr, ttl: u32) {
         if ttl == 0 {
@@ -77,6 +87,33 @@ impl Cache {
             },
         }
     }
+
+    /// List all entries that have not expired yet
+    #[must_use]
+    pub fn entries(&self) -> Vec<CachedDomain> {
+        let now = Instant::now();
+
+        self.cache
+            .lock()
+            .expect("DNS Cache lock was poisoned")
+            .0
+            .iter()
+            .filter(|(_, entry)| now < entry.expires_at)
+            .map(|(domain, entry)| CachedDomain {
+                domain: domain.clone(),
+                ip: entry.ip,
+                expires_in: entry.expires_at - now,
+            })
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.cache
+            .lock()
+            .expect("DNS Cache lock was poisoned")
+            .0
+            .clear();
+    }
 }
 
 impl LockedCache {
@@ -132,3 +169,33 @@ pub struct CacheEntry {
     // In the future we might want to differentiate between IPv4 and IPv6 here
     ip: IpAddr,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    #[test]
+    fn list_and_clear_entries() {
+        let cache = Cache::default();
+        cache.insert(
+            Domain::new("example.com"),
+            IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)),
+            60,
+        );
+        cache.insert(
+            Domain::new("uncached.com"),
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            0,
+        );
+
+        let entries = cache.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].domain.to_string(), "example.com");
+        assert!(entries[0].expires_in <= Duration::from_secs(60));
+
+        cache.clear();
+        assert!(cache.entries().is_empty());
+    }
+}
@@ -24,6 +24,12 @@ impl fmt::Debug for Domain {
     }
 }
 
+impl fmt::Display for Domain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.join("."))
+    }
+}
+
 impl Domain {
     #[must_use]
     pub fn new(source: &str) -> Self {
@@ -7,7 +7,7 @@ mod reader;
 mod resource_type;
 
 use crate::resource_type::{ResourceRecord, ResourceRecordClass};
-pub use dns_cache::DNS_CACHE;
+pub use dns_cache::{CachedDomain, DNS_CACHE};
 pub use domain::Domain;
 use error_derive::Error;
 
@@ -1,6 +1,9 @@
 //! <https://datatracker.ietf.org/doc/html/rfc1035#section-4.1.1>
 
-use sl_std::{rand::RNG, read::ReadExt};
+use sl_std::{
+    rand::{ChaChaRng, Rng, RNG},
+    read::ReadExt,
+};
 
 use crate::{domain::Domain, reader::Reader, DNSError, ResourceRecord, ResourceRecordClass};
 use std::{fmt, net::IpAddr, vec};
@@ -84,11 +87,22 @@ pub enum ResponseCode {
     Reserved,
 }
 
+/// Transaction IDs need to be unpredictable to make cache poisoning attacks harder
+fn random_id() -> u16 {
+    match ChaChaRng::from_entropy() {
+        Ok(mut rng) => rng.next_u16(),
+        Err(error) => {
+            log::warn!("Failed to seed random number generator: {error}");
+            RNG::default().next_u16()
+        },
+    }
+}
+
 impl Header {
     #[must_use]
     pub fn new(num_questions: u16) -> Self {
         Self {
-            id: RNG::default().next_u16(),
+            id: random_id(),
             flags: Flags::default(),
             num_questions,
             num_answers: 0x0000,
@@ -1,5 +1,57 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenTree;
+use quote::{quote, ToTokens};
+
+/// Whether or not a type contains a (strong) reference to another DOM object
+fn contains_dom_ptr(tokens: proc_macro2::TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "DomPtr",
+        TokenTree::Group(group) => contains_dom_ptr(group.stream()),
+        _ => false,
+    })
+}
+
+/// Implement `gc::Trace` for a DOM object
+///
+/// Only fields whose type mentions `DomPtr` are traced. All other fields can't
+/// reference the gc heap, unless a `DomPtr` is hidden inside another type. Since
+/// such a pointer is never unrooted, it simply keeps its object alive, just like an `Rc` would.
+fn impl_trace(struct_declaration: &syn::ItemStruct) -> proc_macro2::TokenStream {
+    let traced_fields: Vec<syn::Member> = struct_declaration
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            field
+                .ident
+                .as_ref()
+                .is_some_and(|ident| ident == "__parent")
+                || contains_dom_ptr(field.ty.to_token_stream())
+        })
+        .map(|(index, field)| match &field.ident {
+            Some(ident) => syn::Member::Named(ident.clone()),
+            None => syn::Member::Unnamed(index.into()),
+        })
+        .collect();
+
+    let struct_ident = &struct_declaration.ident;
+    quote!(
+        #[automatically_derived]
+        unsafe impl ::gc::Trace for #struct_ident {
+            fn trace(&self) {
+                #(::gc::Trace::trace(&self.#traced_fields);)*
+            }
+
+            fn root(&self) {
+                #(::gc::Trace::root(&self.#traced_fields);)*
+            }
+
+            fn unroot(&self) {
+                #(::gc::Trace::unroot(&self.#traced_fields);)*
+            }
+        }
+    )
+}
 
 #[proc_macro_attribute]
 pub fn inherit(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -8,10 +60,13 @@ pub fn inherit(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     if attr.is_empty() {
         // This is a root object that does not inherit from anything
+        let trace_impl = impl_trace(&struct_declaration);
         return quote!(
             #[repr(C)]
             #[derive(Default)]
             #struct_declaration
+
+            #trace_impl
         )
         .into();
     }
@@ -38,11 +93,14 @@ pub fn inherit(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 
     let struct_ident = &struct_declaration.ident;
+    let trace_impl = impl_trace(&struct_declaration);
     quote!(
         #[repr(C)]
         #[derive(Default)]
         #struct_declaration
 
+        #trace_impl
+
         #[automatically_derived]
         impl ::std::ops::Deref for #struct_ident {
             type Target = #parent_type_ident;
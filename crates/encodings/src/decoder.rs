@@ -21,12 +21,49 @@ pub struct DecodeError {
 pub struct Context<'a> {
     bytes: &'a [u8],
     offset: usize,
+
+    /// Bytes that were restored to the front of the queue, in reverse order
+    restored: Vec<u8>,
 }
 
 impl<'a> Context<'a> {
+    #[must_use]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            offset: 0,
+            restored: Vec::new(),
+        }
+    }
+
+    /// Put the last byte that was read back into the queue
+    ///
+    /// Must only be used for bytes that were not [restored](Self::restore).
     pub fn go_back(&mut self) {
         self.offset -= 1;
     }
+
+    /// Put bytes in front of the queue, they will be read before any other bytes
+    ///
+    /// Unlike [go_back](Self::go_back), this also works for bytes that were read
+    /// while decoding a previous chunk of a stream.
+    ///
+    /// <https://encoding.spec.whatwg.org/#concept-stream-prepend>
+    pub fn restore(&mut self, bytes: &[u8]) {
+        self.restored.extend(bytes.iter().rev());
+    }
+
+    /// The number of input bytes that were read so far
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Whether all bytes have been read
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.restored.is_empty() && self.offset == self.bytes.len()
+    }
 }
 
 pub trait Decoder: Default {
@@ -50,10 +87,7 @@ pub trait Decoder: Default {
     fn fully_decode<P: AsRef<[u8]>>(bytes: P) -> Result<String, DecodeError> {
         let mut result = String::new();
         let mut decoder = Self::default();
-        let mut context = Context {
-            bytes: bytes.as_ref(),
-            offset: 0,
-        };
+        let mut context = Context::new(bytes.as_ref());
 
         while let Some(c) = decoder.next_char(&mut context)? {
             result.push(c);
@@ -67,6 +101,10 @@ impl<'a> Iterator for Context<'a> {
     type Item = u8;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(byte) = self.restored.pop() {
+            return Some(byte);
+        }
+
         let byte = self.bytes.get(self.offset)?;
         self.offset += 1;
 
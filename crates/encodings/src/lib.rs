@@ -5,6 +5,9 @@
 mod decoder;
 mod euc_jp;
 mod euc_kr;
+mod stream;
+mod utf_16;
+mod utf_8;
 
 mod encodings {
     include!(concat!(env!("OUT_DIR"), "/encodings.rs"));
@@ -15,9 +18,12 @@ mod index {
     include!(concat!(env!("OUT_DIR"), "/indexes.rs"));
 }
 
-pub use encodings::Encoding;
+pub use encodings::{Encoding, InvalidEncoding};
 
 pub use decoder::{Context, DecodeError, DecodeResult, Decoder};
+pub use stream::{ErrorMode, StreamDecoder, UnsupportedEncoding};
+pub use utf_16::{Utf16BeDecoder, Utf16LeDecoder};
+pub use utf_8::Utf8Decoder;
 
 ///<https://encoding.spec.whatwg.org/#bom-sniff>
 #[must_use]
@@ -39,7 +45,7 @@ pub fn bom_sniff(bytes: &[u8]) -> Option<Encoding> {
 }
 
 /// <https://encoding.spec.whatwg.org/#decode>
-pub fn decode(mut bytes: &[u8], mut encoding: Encoding) {
+pub fn decode(mut bytes: &[u8], mut encoding: Encoding) -> Result<String, UnsupportedEncoding> {
     // 1. Let BOMEncoding be the result of BOM sniffing ioQueue.
     let bom_encoding = bom_sniff(bytes);
 
@@ -57,5 +63,12 @@ pub fn decode(mut bytes: &[u8], mut encoding: Encoding) {
         }
     }
 
-    todo!()
+    // 3. Process a queue with an instance of encoding’s decoder, ioQueue, output, and "replacement".
+    let mut decoder = StreamDecoder::new(encoding, ErrorMode::Replacement, true)?;
+    let output = decoder
+        .decode(bytes, false)
+        .expect("decoding in replacement mode cannot fail");
+
+    // 4. Return output.
+    Ok(output)
 }
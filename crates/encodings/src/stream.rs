@@ -0,0 +1,259 @@
+//! Decoding of byte streams that arrive in multiple chunks
+//!
+//! <https://encoding.spec.whatwg.org/#interface-textdecoder>
+
+use super::{
+    euc_jp::EucJpDecoder,
+    euc_kr::EucKrDecoder,
+    utf_16::{Utf16BeDecoder, Utf16LeDecoder},
+    utf_8::Utf8Decoder,
+    Context, DecodeError, DecodeResult, Decoder, Encoding,
+};
+
+/// <https://encoding.spec.whatwg.org/#error-mode>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Invalid byte sequences are replaced with `U+FFFD`
+    #[default]
+    Replacement,
+
+    /// Decoding stops at the first invalid byte sequence
+    Fatal,
+}
+
+/// There is no decoder for the encoding yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsupportedEncoding;
+
+/// The decoder of an [Encoding] that is only known at runtime
+#[derive(Clone, Copy, Debug)]
+enum AnyDecoder {
+    Utf8(Utf8Decoder),
+    Utf16Be(Utf16BeDecoder),
+    Utf16Le(Utf16LeDecoder),
+    EucJp(EucJpDecoder),
+    EucKr(EucKrDecoder),
+}
+
+impl AnyDecoder {
+    fn new(encoding: Encoding) -> Result<Self, UnsupportedEncoding> {
+        // FIXME: Support the remaining encodings
+        let decoder = match encoding {
+            Encoding::UTF_8 => Self::Utf8(Utf8Decoder::default()),
+            Encoding::UTF_16BE => Self::Utf16Be(Utf16BeDecoder::default()),
+            Encoding::UTF_16LE => Self::Utf16Le(Utf16LeDecoder::default()),
+            Encoding::EUC_JP => Self::EucJp(EucJpDecoder::default()),
+            Encoding::EUC_KR => Self::EucKr(EucKrDecoder::default()),
+            _ => return Err(UnsupportedEncoding),
+        };
+
+        Ok(decoder)
+    }
+
+    fn eat_byte(&mut self, context: &mut Context<'_>) -> DecodeResult {
+        match self {
+            Self::Utf8(decoder) => decoder.eat_byte(context),
+            Self::Utf16Be(decoder) => decoder.eat_byte(context),
+            Self::Utf16Le(decoder) => decoder.eat_byte(context),
+            Self::EucJp(decoder) => decoder.eat_byte(context),
+            Self::EucKr(decoder) => decoder.eat_byte(context),
+        }
+    }
+}
+
+/// Decodes a sequence of byte chunks
+///
+/// The decoder keeps its state between chunks, so a character may be split
+/// across multiple calls to [decode](Self::decode).
+#[derive(Clone, Copy, Debug)]
+pub struct StreamDecoder {
+    encoding: Encoding,
+    decoder: AnyDecoder,
+    error_mode: ErrorMode,
+
+    /// <https://encoding.spec.whatwg.org/#textdecoder-ignore-bom-flag>
+    ignore_bom: bool,
+
+    /// <https://encoding.spec.whatwg.org/#textdecoder-bom-seen-flag>
+    bom_seen: bool,
+
+    /// <https://encoding.spec.whatwg.org/#textdecoder-do-not-flush-flag>
+    do_not_flush: bool,
+}
+
+impl StreamDecoder {
+    /// If `ignore_bom` is `false`, a leading byte order mark is removed from the output
+    pub fn new(
+        encoding: Encoding,
+        error_mode: ErrorMode,
+        ignore_bom: bool,
+    ) -> Result<Self, UnsupportedEncoding> {
+        let decoder = Self {
+            encoding,
+            decoder: AnyDecoder::new(encoding)?,
+            error_mode,
+            ignore_bom,
+            bom_seen: false,
+            do_not_flush: false,
+        };
+
+        Ok(decoder)
+    }
+
+    #[must_use]
+    pub const fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    #[must_use]
+    pub const fn error_mode(&self) -> ErrorMode {
+        self.error_mode
+    }
+
+    #[must_use]
+    pub const fn ignore_bom(&self) -> bool {
+        self.ignore_bom
+    }
+
+    /// Decode the next chunk of bytes
+    ///
+    /// If `stream` is `true` then more chunks are expected to follow, and incomplete
+    /// byte sequences at the end of the chunk are kept for the next call.
+    ///
+    /// [Specification](https://encoding.spec.whatwg.org/#dom-textdecoder-decode)
+    pub fn decode(&mut self, bytes: &[u8], stream: bool) -> Result<String, DecodeError> {
+        // 1. If this’s do not flush is false, then set this’s decoder to a new instance of this’s encoding’s decoder,
+        //    this’s I/O queue to the I/O queue of bytes « end-of-queue », and this’s BOM seen to false.
+        if !self.do_not_flush {
+            self.decoder = AnyDecoder::new(self.encoding)
+                .expect("encoding was supported when the decoder was created");
+            self.bom_seen = false;
+        }
+
+        // 2. Set this’s do not flush to options["stream"].
+        self.do_not_flush = stream;
+
+        // 3. If input is given, then push a copy of input to this’s I/O queue.
+        let mut context = Context::new(bytes);
+
+        // 4. Let output be the I/O queue of scalar values « end-of-queue ».
+        let mut output = String::new();
+
+        // 5. While true:
+        loop {
+            // 1. Let item be the result of reading from this’s I/O queue.
+            // 2. If item is end-of-queue and this’s do not flush is true, then return the result of running
+            //    serialize I/O queue with this and output.
+            if context.is_empty() && self.do_not_flush {
+                return Ok(output);
+            }
+
+            // 3. Otherwise:
+            //    1. Let result be the result of processing an item with item, this’s decoder,
+            //       this’s I/O queue, output, and this’s error mode.
+            let c = match self.decoder.eat_byte(&mut context) {
+                DecodeResult::Continue => continue,
+                DecodeResult::Item(c) => c,
+
+                // 2. If result is finished, then return the result of running serialize I/O queue with this and output.
+                DecodeResult::Finished => return Ok(output),
+
+                //  3. Otherwise, if result is error, throw a TypeError.
+                DecodeResult::Error => match self.error_mode {
+                    ErrorMode::Replacement => char::REPLACEMENT_CHARACTER,
+                    ErrorMode::Fatal => {
+                        return Err(DecodeError {
+                            byte_offset: context.offset(),
+                        })
+                    },
+                },
+            };
+
+            self.serialize(c, &mut output);
+        }
+    }
+
+    /// <https://encoding.spec.whatwg.org/#concept-td-serialize>
+    fn serialize(&mut self, c: char, output: &mut String) {
+        // 1. If encoding is UTF-8, UTF-16BE, or UTF-16LE, and ignore BOM and BOM seen are false, then:
+        if matches!(
+            self.encoding,
+            Encoding::UTF_8 | Encoding::UTF_16BE | Encoding::UTF_16LE
+        ) && !self.ignore_bom
+            && !self.bom_seen
+        {
+            // 1. If item is U+FEFF BOM, then set decoder’s BOM seen to true.
+            // 2. Otherwise, if item is not end-of-queue, then set decoder’s BOM seen to true and append item to output.
+            self.bom_seen = true;
+            if c == '\u{FEFF}' {
+                return;
+            }
+        }
+
+        // 2. Otherwise, append item to output.
+        output.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_chunks() {
+        let mut decoder = StreamDecoder::new(Encoding::UTF_8, ErrorMode::Fatal, false).unwrap();
+
+        // "€" is split across both chunks
+        assert_eq!(decoder.decode(b"\xEF\xBB\xBFa\xE2\x82", true).unwrap(), "a");
+        assert_eq!(decoder.decode(b"\xAC", false).unwrap(), "€");
+
+        // A new stream starts after the last chunk, so the second BOM is removed too
+        assert_eq!(decoder.decode(b"\xEF\xBB\xBFb", false).unwrap(), "b");
+    }
+
+    #[test]
+    fn ignore_bom() {
+        let mut decoder = StreamDecoder::new(Encoding::UTF_8, ErrorMode::Fatal, true).unwrap();
+        assert_eq!(
+            decoder.decode(b"\xEF\xBB\xBFa", false).unwrap(),
+            "\u{FEFF}a"
+        );
+    }
+
+    #[test]
+    fn error_modes() {
+        let mut fatal = StreamDecoder::new(Encoding::UTF_8, ErrorMode::Fatal, false).unwrap();
+        assert!(fatal.decode(b"a\xFFb", false).is_err());
+
+        // Incomplete sequences are only an error once the stream ends
+        assert!(fatal.decode(b"\xE2\x82", true).is_ok());
+        assert!(fatal.decode(b"", false).is_err());
+
+        let mut replacement =
+            StreamDecoder::new(Encoding::UTF_8, ErrorMode::Replacement, false).unwrap();
+        assert_eq!(replacement.decode(b"a\xFFb", false).unwrap(), "a\u{FFFD}b");
+        assert_eq!(
+            replacement.decode(b"\xE2\x82a", false).unwrap(),
+            "\u{FFFD}a"
+        );
+    }
+
+    #[test]
+    fn decode_utf16() {
+        let mut decoder =
+            StreamDecoder::new(Encoding::UTF_16LE, ErrorMode::Replacement, false).unwrap();
+
+        // U+1F600 as a surrogate pair, split in the middle of the trailing surrogate
+        assert_eq!(
+            decoder.decode(b"\xFF\xFEa\x00\x3D\xD8\x00", true).unwrap(),
+            "a"
+        );
+        assert_eq!(decoder.decode(b"\xDE", false).unwrap(), "😀");
+
+        // A leading surrogate that is not followed by a trailing surrogate
+        assert_eq!(
+            decoder.decode(b"\x3D\xD8b\x00", false).unwrap(),
+            "\u{FFFD}b"
+        );
+    }
+}
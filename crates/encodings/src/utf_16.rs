@@ -0,0 +1,97 @@
+//! <https://encoding.spec.whatwg.org/#utf-16be-le>
+
+use super::{Context, DecodeResult, Decoder};
+
+/// <https://encoding.spec.whatwg.org/#utf-16be-decoder>
+pub type Utf16BeDecoder = Utf16Decoder<true>;
+
+/// <https://encoding.spec.whatwg.org/#utf-16le-decoder>
+pub type Utf16LeDecoder = Utf16Decoder<false>;
+
+/// <https://encoding.spec.whatwg.org/#shared-utf-16-decoder>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Utf16Decoder<const IS_BIG_ENDIAN: bool> {
+    /// <https://encoding.spec.whatwg.org/#utf-16-leading-byte>
+    leading_byte: Option<u8>,
+
+    /// <https://encoding.spec.whatwg.org/#utf-16-leading-surrogate>
+    leading_surrogate: Option<u16>,
+}
+
+impl<const IS_BIG_ENDIAN: bool> Decoder for Utf16Decoder<IS_BIG_ENDIAN> {
+    // <https://encoding.spec.whatwg.org/#shared-utf-16-decoder>
+    fn eat_byte(&mut self, context: &mut Context<'_>) -> DecodeResult {
+        let byte = context.next();
+
+        // 1. If byte is end-of-queue and either UTF-16 leading byte or UTF-16 leading surrogate is non-null,
+        //    set UTF-16 leading byte and UTF-16 leading surrogate to null, and return error.
+        // 2. If byte is end-of-queue and UTF-16 leading byte and UTF-16 leading surrogate are null, return finished.
+        let Some(byte) = byte else {
+            if self.leading_byte.is_some() || self.leading_surrogate.is_some() {
+                self.leading_byte = None;
+                self.leading_surrogate = None;
+                return DecodeResult::Error;
+            } else {
+                return DecodeResult::Finished;
+            }
+        };
+
+        // 3. If UTF-16 leading byte is null, set UTF-16 leading byte to byte and return continue.
+        let Some(leading_byte) = self.leading_byte.take() else {
+            self.leading_byte = Some(byte);
+            return DecodeResult::Continue;
+        };
+
+        // 4. Let code unit be the result of:
+        //    is UTF-16BE decoder is true: (UTF-16 leading byte << 8) + byte.
+        //    is UTF-16BE decoder is false: (byte << 8) + UTF-16 leading byte.
+        //    Then set UTF-16 leading byte to null.
+        let code_unit = if IS_BIG_ENDIAN {
+            u16::from_be_bytes([leading_byte, byte])
+        } else {
+            u16::from_le_bytes([leading_byte, byte])
+        };
+
+        // 5. If UTF-16 leading surrogate is non-null, let leading surrogate be UTF-16 leading surrogate,
+        //    set UTF-16 leading surrogate to null, and then:
+        if let Some(leading_surrogate) = self.leading_surrogate.take() {
+            // 1. If code unit is in the range U+DC00 to U+DFFF, inclusive, return a code point whose value
+            //    is 0x10000 + ((leading surrogate − 0xD800) << 10) + (code unit − 0xDC00).
+            if (0xDC00..=0xDFFF).contains(&code_unit) {
+                let code_point = 0x10000
+                    + ((u32::from(leading_surrogate) - 0xD800) << 10)
+                    + (u32::from(code_unit) - 0xDC00);
+                let c = char::from_u32(code_point).expect("surrogate pairs are always valid");
+                return DecodeResult::Item(c);
+            }
+
+            // 2. Let byte1 be code unit >> 8.
+            // 3. Let byte2 be code unit & 0x00FF.
+            // 4. Let bytes be a list of bytes [byte1, byte2] if is UTF-16BE decoder is true;
+            //    otherwise [byte2, byte1].
+            let bytes = if IS_BIG_ENDIAN {
+                code_unit.to_be_bytes()
+            } else {
+                code_unit.to_le_bytes()
+            };
+
+            // 5. Restore bytes to ioQueue and return error.
+            context.restore(&bytes);
+            return DecodeResult::Error;
+        }
+
+        // 6. If code unit is in the range U+D800 to U+DBFF, inclusive,
+        //    set UTF-16 leading surrogate to code unit and return continue.
+        if (0xD800..=0xDBFF).contains(&code_unit) {
+            self.leading_surrogate = Some(code_unit);
+            return DecodeResult::Continue;
+        }
+
+        // 7. If code unit is in the range U+DC00 to U+DFFF, inclusive, return error.
+        // 8. Return code point code unit.
+        match char::from_u32(u32::from(code_unit)) {
+            Some(c) => DecodeResult::Item(c),
+            None => DecodeResult::Error,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! <https://encoding.spec.whatwg.org/#utf-8>
+
+use super::{Context, DecodeResult, Decoder};
+
+/// <https://encoding.spec.whatwg.org/#utf-8-decoder>
+#[derive(Clone, Copy, Debug)]
+pub struct Utf8Decoder {
+    /// <https://encoding.spec.whatwg.org/#utf-8-code-point>
+    code_point: u32,
+
+    /// <https://encoding.spec.whatwg.org/#utf-8-bytes-seen>
+    bytes_seen: u8,
+
+    /// <https://encoding.spec.whatwg.org/#utf-8-bytes-needed>
+    bytes_needed: u8,
+
+    /// <https://encoding.spec.whatwg.org/#utf-8-lower-boundary>
+    lower_boundary: u8,
+
+    /// <https://encoding.spec.whatwg.org/#utf-8-upper-boundary>
+    upper_boundary: u8,
+}
+
+impl Default for Utf8Decoder {
+    fn default() -> Self {
+        Self {
+            code_point: 0,
+            bytes_seen: 0,
+            bytes_needed: 0,
+            lower_boundary: 0x80,
+            upper_boundary: 0xBF,
+        }
+    }
+}
+
+impl Decoder for Utf8Decoder {
+    // <https://encoding.spec.whatwg.org/#utf-8-decoder>
+    fn eat_byte(&mut self, context: &mut Context<'_>) -> DecodeResult {
+        let byte = context.next();
+
+        // 1. If byte is end-of-queue and UTF-8 bytes needed is not 0, set UTF-8 bytes needed to 0 and return error.
+        // 2. If byte is end-of-queue, return finished.
+        let Some(byte) = byte else {
+            if self.bytes_needed != 0 {
+                self.bytes_needed = 0;
+                return DecodeResult::Error;
+            } else {
+                return DecodeResult::Finished;
+            }
+        };
+
+        // 3. If UTF-8 bytes needed is 0, based on byte:
+        if self.bytes_needed == 0 {
+            match byte {
+                0x00..=0x7F => {
+                    // Return a code point whose value is byte.
+                    return DecodeResult::Item(char::from(byte));
+                },
+                0xC2..=0xDF => {
+                    // 1. Set UTF-8 bytes needed to 1.
+                    self.bytes_needed = 1;
+
+                    // 2. Set UTF-8 code point to byte & 0x1F.
+                    self.code_point = u32::from(byte & 0x1F);
+                },
+                0xE0..=0xEF => {
+                    // 1. If byte is 0xE0, set UTF-8 lower boundary to 0xA0.
+                    if byte == 0xE0 {
+                        self.lower_boundary = 0xA0;
+                    }
+
+                    // 2. If byte is 0xED, set UTF-8 upper boundary to 0x9F.
+                    if byte == 0xED {
+                        self.upper_boundary = 0x9F;
+                    }
+
+                    // 3. Set UTF-8 bytes needed to 2.
+                    self.bytes_needed = 2;
+
+                    // 4. Set UTF-8 code point to byte & 0xF.
+                    self.code_point = u32::from(byte & 0xF);
+                },
+                0xF0..=0xF4 => {
+                    // 1. If byte is 0xF0, set UTF-8 lower boundary to 0x90.
+                    if byte == 0xF0 {
+                        self.lower_boundary = 0x90;
+                    }
+
+                    // 2. If byte is 0xF4, set UTF-8 upper boundary to 0x8F.
+                    if byte == 0xF4 {
+                        self.upper_boundary = 0x8F;
+                    }
+
+                    // 3. Set UTF-8 bytes needed to 3.
+                    self.bytes_needed = 3;
+
+                    // 4. Set UTF-8 code point to byte & 0x7.
+                    self.code_point = u32::from(byte & 0x7);
+                },
+                _ => {
+                    // Return error.
+                    return DecodeResult::Error;
+                },
+            }
+
+            // Return continue.
+            return DecodeResult::Continue;
+        }
+
+        // 4. If byte is not in the range UTF-8 lower boundary to UTF-8 upper boundary, inclusive, then:
+        if !(self.lower_boundary..=self.upper_boundary).contains(&byte) {
+            // 1. Set UTF-8 code point, UTF-8 bytes needed, and UTF-8 bytes seen to 0,
+            //    set UTF-8 lower boundary to 0x80, and set UTF-8 upper boundary to 0xBF.
+            *self = Self::default();
+
+            // 2. Restore byte to ioQueue.
+            context.go_back();
+
+            // 3. Return error.
+            return DecodeResult::Error;
+        }
+
+        // 5. Set UTF-8 lower boundary to 0x80 and UTF-8 upper boundary to 0xBF.
+        self.lower_boundary = 0x80;
+        self.upper_boundary = 0xBF;
+
+        // 6. Set UTF-8 code point to (UTF-8 code point << 6) | (byte & 0x3F)
+        self.code_point = (self.code_point << 6) | u32::from(byte & 0x3F);
+
+        // 7. Increase UTF-8 bytes seen by one.
+        self.bytes_seen += 1;
+
+        // 8. If UTF-8 bytes seen is not equal to UTF-8 bytes needed, return continue.
+        if self.bytes_seen != self.bytes_needed {
+            return DecodeResult::Continue;
+        }
+
+        // 9. Let code point be UTF-8 code point.
+        let code_point = self.code_point;
+
+        // 10. Set UTF-8 code point, UTF-8 bytes needed, and UTF-8 bytes seen to 0.
+        self.code_point = 0;
+        self.bytes_needed = 0;
+        self.bytes_seen = 0;
+
+        // 11. Return a code point whose value is code point.
+        // NOTE: The boundaries exclude surrogates and values above U+10FFFF
+        let c = char::from_u32(code_point).expect("utf-8 decoder produced an invalid code point");
+        DecodeResult::Item(c)
+    }
+}
@@ -19,6 +19,10 @@ use crate::Trace;
 /// Whenever the contained value is accessed (mutably), the cell and all its contained gc elements are rooted.
 /// This is necessary since they might be moved from the value onto the stack (becoming a root) without us noticing
 /// otherwise.
+///
+/// The layout of a `GcCell<T>` only differs from the layout of a `GcCell<U>` in the
+/// wrapped value, so a cell can be reinterpreted as a cell of a type that `T` starts with.
+#[repr(C)]
 pub struct GcCell<T: ?Sized> {
     borrow: Cell<BorrowFlag>,
 
@@ -700,12 +704,11 @@ impl Drop for BorrowRefMut<'_> {
 }
 
 #[must_not_suspend = "holding a RefMut across suspend points can cause BorrowErrors"]
-pub struct RefMut<'b, T: ?Sized> {
+pub struct RefMut<'b, T: ?Sized + Trace> {
     // NB: we use a pointer instead of `&'b mut T` to avoid `noalias` violations, because a
     // `RefMut` argument doesn't hold exclusivity for its whole scope, only until it drops.
     value: NonNull<T>,
 
-    #[allow(dead_code)]
     borrow: BorrowRefMut<'b>,
     // `NonNull` is covariant over `T`, so we need to reintroduce invariance.
     marker: PhantomData<&'b mut T>,
@@ -725,7 +728,7 @@ impl<'b> BorrowRefMut<'b> {
     }
 }
 
-impl<T: ?Sized> Deref for RefMut<'_, T> {
+impl<T: ?Sized + Trace> Deref for RefMut<'_, T> {
     type Target = T;
 
     #[inline]
@@ -735,7 +738,7 @@ impl<T: ?Sized> Deref for RefMut<'_, T> {
     }
 }
 
-impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+impl<T: ?Sized + Trace> DerefMut for RefMut<'_, T> {
     #[inline]
     fn deref_mut(&mut self) -> &mut T {
         // SAFETY: the value is accessible as long as we hold our borrow.
@@ -743,25 +746,34 @@ impl<T: ?Sized> DerefMut for RefMut<'_, T> {
     }
 }
 
-impl<T: ?Sized + fmt::Debug> fmt::Debug for RefMut<'_, T> {
+impl<T: ?Sized + Trace + fmt::Debug> fmt::Debug for RefMut<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         (**self).fmt(f)
     }
 }
 
-impl<T: ?Sized + fmt::Display> fmt::Display for RefMut<'_, T> {
+impl<T: ?Sized + Trace + fmt::Display> fmt::Display for RefMut<'_, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         (**self).fmt(f)
     }
 }
 
+impl<T: ?Sized + Trace> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        // The value was rooted when it was borrowed, now it is only reachable through the cell again
+        if !self.borrow.borrow.get().is_rooted() {
+            // SAFETY: the value is accessible as long as we hold our borrow.
+            unsafe { self.value.as_ref() }.unroot();
+        }
+    }
+}
+
 unsafe impl<T: ?Sized + Trace> Trace for GcCell<T> {
     fn trace(&self) {
-        // Don't need to trace if the value is borrowed mutably, since it is already rooted
-        // in that case
-        if self.borrow.get().borrow_state() != BorrowState::Writing {
-            unsafe { self.as_ptr().as_ref() }.trace()
-        }
+        // The value is traced even while it is borrowed mutably. The borrow only rooted the
+        // value as the type that it was borrowed as, which might be a prefix of the actual type.
+        // The remaining fields are only reachable through this cell.
+        unsafe { self.as_ptr().as_ref() }.trace()
     }
 
     fn root(&self) {
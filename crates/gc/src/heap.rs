@@ -2,8 +2,7 @@
 
 use std::{
     cell::{Cell, RefCell},
-    mem,
-    ptr::{self, NonNull},
+    ptr::NonNull,
 };
 
 use crate::{node::HeapNode, Trace};
@@ -15,6 +14,7 @@ thread_local! {
         bytes_allocated: 0,
         collect_if_memory_usage_above: COLLECT_IF_MEMORY_USAGE_ABOVE,
         head: None,
+        dead_nodes: vec![],
     });
 }
 
@@ -31,6 +31,10 @@ pub(crate) struct Heap {
 
     /// The most recently allocated gc node
     head: Option<NonNull<HeapNode<dyn Trace>>>,
+
+    /// Nodes whose value was dropped, but that can't be deallocated yet
+    /// because there are still [WeakGcs](crate::WeakGc) pointing to them
+    dead_nodes: Vec<NonNull<HeapNode<dyn Trace>>>,
 }
 
 impl Heap {
@@ -41,7 +45,7 @@ impl Heap {
         let old_head = self.head.replace(node);
         node.as_ref().next.set(old_head);
 
-        self.bytes_allocated += mem::size_of_val(&node);
+        self.bytes_allocated += node.as_ref().size();
 
         if self.bytes_allocated > self.collect_if_memory_usage_above {
             self.collect_garbage();
@@ -93,34 +97,61 @@ impl Heap {
             next = &node.next;
         }
 
-        println!("Found {:?} unmarked ndoes", unmarked_nodes.len());
-
         // Sweep Phase
+        // Remove the unmarked nodes from the linked list, in reverse order so that
+        // the "linked_by" references stay valid
         let mut total_freed_size = 0;
-        while let Some(mut unmarked_node) = unmarked_nodes.pop() {
-            total_freed_size += mem::size_of_val(&unmarked_node.node);
-
-            // Remove the unmarked node from the linked list
+        let mut garbage = Vec::with_capacity(unmarked_nodes.len());
+        while let Some(unmarked_node) = unmarked_nodes.pop() {
             // SAFETY: The node ptr is guaranteed to point to a valid node
-            let node_to_be_dropped = unsafe { unmarked_node.node.as_mut() };
-            unmarked_node.linked_by.set(node_to_be_dropped.next.get());
+            let node = unsafe { unmarked_node.node.as_ref() };
+            unmarked_node.linked_by.set(node.next.get());
+
+            // Weak references must not be upgraded while the values are dropped,
+            // since the values might refer to each other
+            node.mark_as_dead();
+            total_freed_size += node.size();
+            garbage.push(unmarked_node.node);
+        }
 
-            // SAFETY: The node ptr is guaranteed to point to a valid node
-            // The constructed box is immediately dropped
-            let _ = unsafe { Box::from_raw(ptr::from_mut(node_to_be_dropped)) };
+        for mut node in garbage.iter().copied() {
+            // SAFETY: The node ptr is guaranteed to point to a valid node and the value was
+            //         not dropped before, since the node was still part of the linked list
+            unsafe { node.as_mut().drop_value() };
         }
 
+        self.dead_nodes.extend(garbage);
+        self.free_dead_nodes();
+
         self.bytes_allocated -= total_freed_size;
+        self.collect_if_memory_usage_above =
+            COLLECT_IF_MEMORY_USAGE_ABOVE.max(2 * self.bytes_allocated);
         log::debug!("Freed 0x{total_freed_size:x} bytes during garbage collection");
 
         total_freed_size
     }
+
+    /// Deallocate all dead nodes that are no longer referenced by any weak reference
+    fn free_dead_nodes(&mut self) {
+        self.dead_nodes.retain(|node| {
+            // SAFETY: Dead nodes stay valid until they are deallocated here
+            if unsafe { node.as_ref() }.weak_count.get() != 0 {
+                return true;
+            }
+
+            // SAFETY: The value was already dropped and the node itself is not
+            //         referenced anymore. Since the value is wrapped in ManuallyDrop,
+            //         dropping the box does not drop it again.
+            let _ = unsafe { Box::from_raw(node.as_ptr()) };
+            false
+        });
+    }
 }
 
 impl Drop for Heap {
     fn drop(&mut self) {
         self.collect_garbage();
 
-        // Remaining memory is leaked
+        // Remaining memory (including dead nodes that are still weakly referenced) is leaked
     }
 }
@@ -7,7 +7,7 @@ mod heap;
 mod node;
 mod trace;
 
-pub use cell::{GcCell, Ref};
+pub use cell::{GcCell, Ref, RefMut};
 pub use heap::collect_garbage;
 use node::HeapNode;
 pub use trace::Trace;
@@ -20,6 +20,10 @@ use std::{cell::Cell, fmt, ops::Deref, ptr::NonNull};
 /// interfer with the methods on `T`.
 ///
 /// Cloning a [Gc] does not perform a deep copy.
+///
+/// The layout of a `Gc<T>` does not depend on `T`, so pointers to values that
+/// start with the same fields can be reinterpreted as one another.
+#[repr(C)]
 pub struct Gc<T>
 where
     T: 'static + ?Sized + Trace,
@@ -81,7 +85,7 @@ where
     }
 
     fn root(&self) {
-        Self::node(self).value().root();
+        // Only the pointer itself becomes a root, the value it points to stays on the heap
         Self::mark_as_root(self);
     }
 
@@ -141,6 +145,94 @@ where
     pub fn mark(value: &Self) {
         Self::node(value).mark()
     }
+
+    /// Create a [WeakGc] pointing to the same value
+    #[must_use]
+    pub fn downgrade(value: &Self) -> WeakGc<T> {
+        let node = Self::node(value);
+        node.weak_count.set(node.weak_count.get() + 1);
+
+        WeakGc {
+            referenced_node: value.referenced_node.get(),
+        }
+    }
+}
+
+/// A pointer to the gc heap that does not keep its value alive
+///
+/// Once the value was collected, the pointer can no longer be [upgraded](WeakGc::upgrade).
+#[repr(C)]
+pub struct WeakGc<T>
+where
+    T: 'static + ?Sized + Trace,
+{
+    /// The node that the value was allocated in
+    ///
+    /// The node is not deallocated while weak references to it exist, even if its value was dropped.
+    referenced_node: NonNull<HeapNode<T>>,
+}
+
+impl<T> WeakGc<T>
+where
+    T: 'static + ?Sized + Trace,
+{
+    /// Get a [Gc] to the value, if it was not collected yet
+    #[must_use]
+    pub fn upgrade(&self) -> Option<Gc<T>> {
+        let mut node = self.referenced_node;
+
+        // SAFETY: The node is kept alive by this reference
+        let node = unsafe { node.as_mut() };
+        if node.is_dead() {
+            return None;
+        }
+
+        // The new gc starts out on the stack
+        node.increment_root_count();
+        Some(Gc {
+            is_rooted: Cell::new(true),
+            referenced_node: Cell::new(self.referenced_node),
+        })
+    }
+}
+
+impl<T> Clone for WeakGc<T>
+where
+    T: 'static + ?Sized + Trace,
+{
+    fn clone(&self) -> Self {
+        // SAFETY: The node is kept alive by this reference
+        let node = unsafe { self.referenced_node.as_ref() };
+        node.weak_count.set(node.weak_count.get() + 1);
+
+        Self {
+            referenced_node: self.referenced_node,
+        }
+    }
+}
+
+impl<T> Drop for WeakGc<T>
+where
+    T: 'static + ?Sized + Trace,
+{
+    fn drop(&mut self) {
+        // SAFETY: The node is kept alive by this reference.
+        //         Dead nodes are deallocated by the heap once their weak count reaches zero.
+        let node = unsafe { self.referenced_node.as_ref() };
+        node.weak_count.set(node.weak_count.get() - 1);
+    }
+}
+
+// Weak references never keep anything alive, so there is nothing to trace
+unsafe impl<T> Trace for WeakGc<T>
+where
+    T: 'static + ?Sized + Trace,
+{
+    fn trace(&self) {}
+
+    fn root(&self) {}
+
+    fn unroot(&self) {}
 }
 
 impl<T> fmt::Debug for Gc<T>
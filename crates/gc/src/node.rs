@@ -1,21 +1,36 @@
-use std::{cell::Cell, ptr::NonNull};
+use std::{
+    cell::Cell,
+    mem::{self, ManuallyDrop},
+    ptr::NonNull,
+};
 
 use crate::{heap::HEAP, Trace};
 
 const MARKED_BIT: usize = 1 << (usize::BITS - 1);
-const ROOTS_MASK: usize = !MARKED_BIT;
+const DEAD_BIT: usize = 1 << (usize::BITS - 2);
+const ROOTS_MASK: usize = !(MARKED_BIT | DEAD_BIT);
 
+#[repr(C)]
 pub struct HeapNode<T: ?Sized> {
-    /// Contains root count and whether or not the node is marked
+    /// Contains root count, whether or not the node is marked and whether or not it is dead
     ///
-    /// Highest bit indicates mark state, lower bits are the root count.
+    /// Highest bit indicates mark state, the bit after that is set once the value was dropped
+    /// and the lower bits are the root count.
     pub(crate) flags: Cell<usize>,
 
+    /// The number of [WeakGcs](crate::WeakGc) pointing to this node
+    ///
+    /// Dead nodes are not deallocated while weak references to them exist.
+    pub(crate) weak_count: Cell<usize>,
+
     /// [HeapNodes](HeapNode) make up a linked list, to keep track of all allocated objects
     pub(crate) next: Cell<Option<NonNull<HeapNode<dyn Trace>>>>,
 
     /// The actual value allocated
-    pub(crate) value: T,
+    ///
+    /// The value is dropped by the heap when the node is swept, which might happen
+    /// before the node itself is deallocated.
+    pub(crate) value: ManuallyDrop<T>,
 }
 
 impl<T> HeapNode<T>
@@ -25,8 +40,9 @@ where
     pub fn new(value: T) -> NonNull<Self> {
         let node = Self {
             flags: Cell::new(0x1), // Not marked, one root
+            weak_count: Cell::new(0),
             next: Cell::new(None),
-            value,
+            value: ManuallyDrop::new(value),
         };
 
         let node = NonNull::from(Box::leak(Box::new(node)));
@@ -44,7 +60,9 @@ where
 {
     #[inline]
     #[must_use]
-    pub const fn value(&self) -> &T {
+    pub fn value(&self) -> &T {
+        debug_assert!(!self.is_dead(), "Accessing value of a dead gc node");
+
         &self.value
     }
 
@@ -54,13 +72,13 @@ where
             self.flags.set(self.flags.get() | MARKED_BIT);
 
             // Also mark all of the connected cells
-            self.value.trace();
+            self.value().trace();
         }
     }
 
     /// Unmarks the cell (but leaves the children untouched)
     pub fn unmark(&self) {
-        self.flags.set(self.flags.get() & ROOTS_MASK);
+        self.flags.set(self.flags.get() & !MARKED_BIT);
     }
 
     #[must_use]
@@ -73,6 +91,12 @@ where
         self.flags.get() & MARKED_BIT != 0
     }
 
+    /// Whether the value of the node was dropped already
+    #[must_use]
+    pub fn is_dead(&self) -> bool {
+        self.flags.get() & DEAD_BIT != 0
+    }
+
     pub fn decrement_root_count(&mut self) {
         self.flags.set(self.flags.get() - 1);
     }
@@ -84,4 +108,25 @@ where
 
         self.flags.set(self.flags.get() + 1);
     }
+
+    /// The number of bytes occupied by the node, including the value
+    #[must_use]
+    pub(crate) fn size(&self) -> usize {
+        mem::size_of_val(self)
+    }
+
+    /// Prevent the node from being accessed through weak references, because it is about to be dropped
+    pub(crate) fn mark_as_dead(&self) {
+        self.flags.set(self.flags.get() | DEAD_BIT);
+    }
+
+    /// Drop the value of the node without deallocating the node itself
+    ///
+    /// # Safety
+    /// The node must be [dead](Self::mark_as_dead) and its value must not have been dropped before.
+    pub(crate) unsafe fn drop_value(&mut self) {
+        debug_assert!(self.is_dead());
+
+        ManuallyDrop::drop(&mut self.value);
+    }
 }
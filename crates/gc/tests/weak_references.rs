@@ -0,0 +1,64 @@
+use gc::{Gc, GcCell, Trace, WeakGc};
+
+struct Node {
+    children: Vec<Gc<GcCell<Node>>>,
+    parent: Option<WeakGc<GcCell<Node>>>,
+}
+
+unsafe impl Trace for Node {
+    fn trace(&self) {
+        self.children.trace();
+    }
+
+    fn root(&self) {
+        self.children.root();
+    }
+
+    fn unroot(&self) {
+        self.children.unroot();
+    }
+}
+
+fn append_child(parent: &Gc<GcCell<Node>>) -> Gc<GcCell<Node>> {
+    let child = Gc::new(GcCell::new(Node {
+        children: vec![],
+        parent: Some(Gc::downgrade(parent)),
+    }));
+
+    parent.borrow_mut().children.push(child.clone());
+    child
+}
+
+#[test]
+fn weak_references_do_not_keep_values_alive() {
+    let root = Gc::new(GcCell::new(Node {
+        children: vec![],
+        parent: None,
+    }));
+    let child = append_child(&root);
+    drop(root);
+
+    // The child only refers to the root through a weak reference, so the root is collected
+    gc::collect_garbage();
+    assert!(child.borrow().parent.as_ref().unwrap().upgrade().is_none());
+}
+
+#[test]
+fn collect_cycles() {
+    let a = Gc::new(GcCell::new(Node {
+        children: vec![],
+        parent: None,
+    }));
+    let b = append_child(&a);
+
+    // a -> b -> a
+    b.borrow_mut().children.push(a.clone());
+    let weak_a = Gc::downgrade(&a);
+    assert!(weak_a.upgrade().is_some());
+
+    drop(a);
+    drop(b);
+
+    assert_ne!(gc::collect_garbage(), 0);
+    assert!(weak_a.upgrade().is_none());
+}
@@ -1,6 +1,13 @@
 //! Manages available system fonts
 
-use std::{fs, io, path::PathBuf, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+};
+
+use sl_std::memory::{MemoryReport, MemoryReporter};
 
 use crate::{
     sources::{FontStore, SystemSource},
@@ -25,6 +32,17 @@ pub struct SystemFont {
 
 pub struct FontManager {
     system_fonts: Vec<SystemFont>,
+
+    /// Fonts that were loaded previously, by their path
+    loaded_fonts: Mutex<HashMap<PathBuf, LoadedFont>>,
+}
+
+struct LoadedFont {
+    name: String,
+    font: Font,
+
+    /// The size of the font file
+    size: usize,
 }
 
 impl FontManager {
@@ -35,7 +53,10 @@ impl FontManager {
         log::info!("Loading system fonts from store {:?}", S::NAME);
         let system_fonts = S::enumerate_system_fonts();
         log::info!("Loaded {} system fonts", system_fonts.len());
-        Self { system_fonts }
+        Self {
+            system_fonts,
+            loaded_fonts: Mutex::default(),
+        }
     }
 
     pub fn lookup(&self, family: Family, properties: Properties) -> &SystemFont {
@@ -52,6 +73,48 @@ impl FontManager {
 
         best_fit
     }
+
+    /// Load the font that best matches the given requirements
+    ///
+    /// Fonts are only read from disk the first time they are requested.
+    pub fn load(&self, family: Family, properties: Properties) -> Result<Font, FontLoadError> {
+        let system_font = self.lookup(family, properties);
+
+        let mut loaded_fonts = self
+            .loaded_fonts
+            .lock()
+            .expect("font cache lock was poisoned");
+
+        if let Some(loaded_font) = loaded_fonts.get(&system_font.path) {
+            return Ok(loaded_font.font.clone());
+        }
+
+        let bytes = fs::read(&system_font.path)?;
+        let font = Font::new(&bytes)?;
+        loaded_fonts.insert(
+            system_font.path.clone(),
+            LoadedFont {
+                name: system_font.name.clone(),
+                font: font.clone(),
+                size: bytes.len(),
+            },
+        );
+
+        Ok(font)
+    }
+}
+
+impl MemoryReporter for FontManager {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        let loaded_fonts = self
+            .loaded_fonts
+            .lock()
+            .expect("font cache lock was poisoned");
+
+        for loaded_font in loaded_fonts.values() {
+            report.record(&format!("fonts/{}", loaded_font.name), loaded_font.size);
+        }
+    }
 }
 
 impl SystemFont {
@@ -20,6 +20,11 @@ impl<'a> Stream<'a> {
     pub fn skip_bytes(&mut self, num_bytes: usize) {
         self.ptr += num_bytes;
     }
+
+    /// The bytes that were not read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.bytes[self.ptr..]
+    }
 }
 
 /// Trait for things that can be read from a byte stream
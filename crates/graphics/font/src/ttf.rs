@@ -5,15 +5,20 @@
 //! * <https://formats.kaitai.io/ttf/index.html>
 //! * <https://handmade.network/forums/articles/t/7330-implementing_a_font_reader_and_rasterizer_from_scratch%252C_part_1__ttf_font_reader>
 
-use std::{fmt, iter};
+use std::{fmt, iter, vec};
 
 use crate::{
     hinting::Interpreter,
     path::{Operation, PathConsumer, PathReader},
     ttf_tables::{
+        avar::AvarTable,
         cmap::{self, GlyphID},
-        glyf::{self, CompoundGlyph, Glyph, GlyphPointIterator, Metrics},
-        head, hhea, hmtx, loca, maxp, name,
+        fvar::{FvarTable, VariationAxis},
+        glyf::{self, CompoundGlyph, Glyph, GlyphPoint, Metrics},
+        gvar::{GvarTable, Outline},
+        head, hhea, hmtx, loca,
+        math::MathConstants,
+        maxp, name,
         offset::OffsetTable,
     },
 };
@@ -23,18 +28,28 @@ const DEFAULT_FONT: &[u8; 168644] = include_bytes!(concat!(
     "/fonts/roboto/Roboto-Medium.ttf"
 ));
 
+const AVAR_TAG: u32 = u32::from_be_bytes(*b"avar");
 const CMAP_TAG: u32 = u32::from_be_bytes(*b"cmap");
+const FVAR_TAG: u32 = u32::from_be_bytes(*b"fvar");
+const GVAR_TAG: u32 = u32::from_be_bytes(*b"gvar");
 const HEAD_TAG: u32 = u32::from_be_bytes(*b"head");
 const LOCA_TAG: u32 = u32::from_be_bytes(*b"loca");
 const GLYF_TAG: u32 = u32::from_be_bytes(*b"glyf");
 const HHEA_TAG: u32 = u32::from_be_bytes(*b"hhea");
 const HMTX_TAG: u32 = u32::from_be_bytes(*b"hmtx");
+const MATH_TAG: u32 = u32::from_be_bytes(*b"MATH");
 const MAXP_TAG: u32 = u32::from_be_bytes(*b"maxp");
 const NAME_TAG: u32 = u32::from_be_bytes(*b"name");
 const _VHEA_TAG: u32 = u32::from_be_bytes(*b"vhea");
 const PREP_TAG: u32 = u32::from_be_bytes(*b"prep");
 const FPGM_TAG: u32 = u32::from_be_bytes(*b"fpgm");
 
+/// The number of horizontal positions within a pixel that glyphs are placed at
+///
+/// Glyphs move smoothly instead of jumping from pixel to pixel, while glyphs
+/// with the same fractional position still produce identical outlines.
+const SUBPIXEL_POSITIONS: f32 = 4.;
+
 #[derive(Clone, Copy, Debug)]
 pub enum TTFParseError {
     UnexpectedEOF,
@@ -52,6 +67,19 @@ pub struct Font {
     maxp_table: maxp::MaxPTable,
     name_table: name::NameTable,
 
+    /// Only fonts that were designed for typesetting formulas have these
+    math_constants: Option<MathConstants>,
+
+    /// The design axes of a variable font
+    variation_axes: Option<FvarTable>,
+    axis_variations: Option<AvarTable>,
+    glyph_variations: Option<GvarTable>,
+
+    /// The normalized position along each of the [variation axes](Self::variation_axes)
+    ///
+    /// All zeros select the default instance of the font.
+    variation_coordinates: Vec<f32>,
+
     /// A program that is run once the font is loaded and whenever its environment changes
     ///
     /// Stored inside the `prep` table
@@ -123,6 +151,35 @@ impl Font {
             .ok_or(TTFParseError::MissingTable)?;
         let name_table = name::NameTable::new(&data[name_entry.offset()..]).unwrap();
 
+        let math_constants = offset_table.get_table(MATH_TAG).and_then(|math_entry| {
+            MathConstants::new(&data[math_entry.offset()..][..math_entry.length()])
+                .inspect_err(|error| log::warn!("Failed to parse MATH table: {error:?}"))
+                .ok()
+        });
+
+        let variation_axes = offset_table.get_table(FVAR_TAG).and_then(|fvar_entry| {
+            FvarTable::new(&data[fvar_entry.offset()..][..fvar_entry.length()])
+                .inspect_err(|error| log::warn!("Failed to parse fvar table: {error:?}"))
+                .ok()
+        });
+
+        let axis_variations = offset_table.get_table(AVAR_TAG).and_then(|avar_entry| {
+            AvarTable::new(&data[avar_entry.offset()..][..avar_entry.length()])
+                .inspect_err(|error| log::warn!("Failed to parse avar table: {error:?}"))
+                .ok()
+        });
+
+        let glyph_variations = offset_table.get_table(GVAR_TAG).and_then(|gvar_entry| {
+            GvarTable::new(&data[gvar_entry.offset()..][..gvar_entry.length()])
+                .inspect_err(|error| log::warn!("Failed to parse gvar table: {error:?}"))
+                .ok()
+        });
+
+        let variation_coordinates = variation_axes
+            .as_ref()
+            .map(|fvar| vec![0.; fvar.axes().len()])
+            .unwrap_or_default();
+
         let mut interpreter = Interpreter::new(
             maxp_table.max_storage as usize,
             maxp_table.max_function_defs as usize,
@@ -166,6 +223,11 @@ impl Font {
             hmtx_table,
             maxp_table,
             name_table,
+            math_constants,
+            variation_axes,
+            axis_variations,
+            glyph_variations,
+            variation_coordinates,
             control_value_program,
             interpreter,
             is_instructed,
@@ -214,6 +276,132 @@ impl Font {
         &self.offset_table
     }
 
+    /// The constants for laying out formulas, if the font has a `MATH` table
+    #[must_use]
+    pub fn math_constants(&self) -> Option<&MathConstants> {
+        self.math_constants.as_ref()
+    }
+
+    /// The axes along which the glyphs of the font can vary, empty if this is not a variable font
+    #[must_use]
+    pub fn variation_axes(&self) -> &[VariationAxis] {
+        self.variation_axes
+            .as_ref()
+            .map(FvarTable::axes)
+            .unwrap_or_default()
+    }
+
+    /// Select an instance of a variable font by the values of its axes
+    ///
+    /// Axes that are not mentioned in `settings` use their default value. If an axis
+    /// is mentioned more than once, the last value is used.
+    pub fn set_variations(&mut self, settings: &[([u8; 4], f32)]) {
+        let Some(fvar) = &self.variation_axes else {
+            return;
+        };
+
+        self.variation_coordinates = fvar
+            .axes()
+            .iter()
+            .enumerate()
+            .map(|(index, axis)| {
+                let value = settings
+                    .iter()
+                    .rev()
+                    .find(|(tag, _)| *tag == axis.tag)
+                    .map_or(axis.default_value, |&(_, value)| value);
+
+                let normalized = axis.normalize(value);
+                let normalized = self
+                    .axis_variations
+                    .as_ref()
+                    .map_or(normalized, |avar| avar.map(index, normalized));
+                normalized.clamp(-1., 1.)
+            })
+            .collect();
+    }
+
+    #[must_use]
+    fn has_variations(&self) -> bool {
+        self.glyph_variations.is_some() && self.variation_coordinates.iter().any(|&c| c != 0.)
+    }
+
+    /// Compute the deltas of a glyph at the current variation coordinates
+    ///
+    /// Returns `None` if the glyph does not change.
+    #[must_use]
+    fn glyph_deltas(
+        &self,
+        glyph_id: GlyphID,
+        num_points: usize,
+        outline: Option<Outline<'_>>,
+    ) -> Option<Vec<math::Vec2D<f32>>> {
+        if !self.has_variations() {
+            return None;
+        }
+
+        self.glyph_variations
+            .as_ref()?
+            .glyph_deltas(glyph_id, &self.variation_coordinates, num_points, outline)
+            .inspect_err(|error| log::warn!("Failed to apply glyph variations: {error:?}"))
+            .ok()
+    }
+
+    /// Move the points of a simple glyph to the current instance of a variable font
+    fn apply_variations(&self, glyph_id: GlyphID, points: &mut [GlyphPoint]) {
+        if !self.has_variations() {
+            return;
+        }
+
+        let coordinates: Vec<math::Vec2D<i32>> =
+            points.iter().map(|point| point.coordinates).collect();
+        let contour_end_points: Vec<usize> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| point.is_last_point_of_contour)
+            .map(|(index, _)| index)
+            .collect();
+        let outline = Outline {
+            points: &coordinates,
+            contour_end_points: &contour_end_points,
+        };
+
+        let Some(deltas) = self.glyph_deltas(glyph_id, points.len(), Some(outline)) else {
+            return;
+        };
+
+        // The first phantom point is the origin of the glyph, the outline is positioned relative to it
+        let origin = deltas[points.len()];
+        for (point, delta) in points.iter_mut().zip(deltas) {
+            point.coordinates.x += (delta.x - origin.x).round() as i32;
+            point.coordinates.y += delta.y.round() as i32;
+        }
+    }
+
+    /// The advance width of a glyph, including the changes made by the current variation coordinates
+    #[must_use]
+    fn advance_width(&self, glyph_id: GlyphID) -> i32 {
+        let advance_width = self.hmtx_table.get_metric_for(glyph_id).advance_width() as i32;
+
+        if !self.has_variations() {
+            return advance_width;
+        }
+
+        let num_points = match self.glyph_table.get_glyph(glyph_id) {
+            Glyph::Empty => 0,
+            Glyph::Simple(simple_glyph) => simple_glyph.num_points,
+            Glyph::Compound(compound_glyph) => compound_glyph.count(),
+        };
+
+        let Some(deltas) = self.glyph_deltas(glyph_id, num_points, None) else {
+            return advance_width;
+        };
+
+        // The second phantom point marks the advance
+        let advance_delta = deltas[num_points + 1].x - deltas[num_points].x;
+        advance_width + advance_delta.round() as i32
+    }
+
     /// Get the Glyph index for a given codepoint
     pub fn get_glyph_id(&self, codepoint: u16) -> Option<GlyphID> {
         self.format4.get_glyph_id(codepoint)
@@ -277,6 +465,11 @@ impl Font {
                 y: font_size - (glyph_point.y as f32 * font_size) / self.units_per_em(),
             };
 
+            // Quantize the horizontal position of the glyph to a fraction of a pixel
+            let origin = scale_point(glyph.position).x + text_offset.x;
+            let quantized_origin = (origin * SUBPIXEL_POSITIONS).round() / SUBPIXEL_POSITIONS;
+            let text_offset = text_offset + math::Vec2D::new(quantized_origin - origin, 0.);
+
             // Draw the outlines of the glyph on the rasterizer buffer
             // Note: all the coordinates in the path operations are relative to the glyph positiont;
             for path_op in glyph.path_operations {
@@ -307,7 +500,7 @@ impl Font {
 
         let mut symbols = Vec::with_capacity(text.len());
         let mut symbol_positions = Vec::with_capacity(text.len());
-        let path_objects: Vec<RenderedGlyph<'_>> = RenderedGlyphIterator::new(self, text).collect();
+        let path_objects: Vec<RenderedGlyph> = RenderedGlyphIterator::new(self, text).collect();
 
         // SVG uses a different coordinate space than our font renderer
         // We therefore have to create run two passes over the text:
@@ -391,7 +584,10 @@ pub fn read_i16_at(data: &[u8], offset: usize) -> i16 {
 
 pub struct RenderedGlyphIterator<'a, 'b> {
     glyphs: GlyphPositionIterator<'a, 'b>,
-    current_compound_glyphs: Vec<CompoundGlyph<'a>>,
+
+    /// The compound glyphs whose components are currently being emitted, along with
+    /// the variation deltas of their component offsets
+    current_compound_glyphs: Vec<(CompoundGlyph<'a>, vec::IntoIter<math::Vec2D<f32>>)>,
 
     /// The x coordinate that any compound glyph components positions are relative to
     x: i32,
@@ -414,10 +610,10 @@ struct PositionedGlyph {
     id: GlyphID,
 }
 
-pub struct RenderedGlyph<'a> {
+pub struct RenderedGlyph {
     metrics: Metrics,
     position: math::Vec2D<i32>,
-    path_operations: PathReader<GlyphPointIterator<'a>>,
+    path_operations: PathReader<vec::IntoIter<GlyphPoint>>,
 }
 
 impl<'font, 'text> Iterator for GlyphPositionIterator<'font, 'text> {
@@ -435,7 +631,7 @@ impl<'font, 'text> Iterator for GlyphPositionIterator<'font, 'text> {
         let x = self.x + horizontal_metrics.left_side_bearing() as i32;
         let y = self.y;
 
-        self.x += horizontal_metrics.advance_width() as i32;
+        self.x += self.font.advance_width(id);
 
         Some(PositionedGlyph { x, y, id })
     }
@@ -448,28 +644,29 @@ impl<'font, 'text> Iterator for GlyphPositionIterator<'font, 'text> {
 impl iter::FusedIterator for GlyphPositionIterator<'_, '_> {}
 
 impl<'a, 'b> Iterator for RenderedGlyphIterator<'a, 'b> {
-    type Item = RenderedGlyph<'a>;
+    type Item = RenderedGlyph;
 
     fn next(&mut self) -> Option<Self::Item> {
         // Determine which glyph we should render and where we should render it to.
         // If we are currently in the process of emitting the components of some compound glyph, continue doing that
         // else, read the next character and emit that
-        let positioned_glyph = if let Some(current_glyph) = self.current_compound_glyphs.last_mut()
-        {
-            if let Some(component) = current_glyph.next() {
-                PositionedGlyph {
-                    id: component.glyph_id,
-                    x: self.x + component.x_offset as i32,
-                    y: self.y + component.y_offset as i32,
+        let positioned_glyph =
+            if let Some((current_glyph, deltas)) = self.current_compound_glyphs.last_mut() {
+                if let Some(component) = current_glyph.next() {
+                    let delta = deltas.next().unwrap_or_default();
+                    PositionedGlyph {
+                        id: component.glyph_id,
+                        x: self.x + component.x_offset as i32 + delta.x.round() as i32,
+                        y: self.y + component.y_offset as i32 + delta.y.round() as i32,
+                    }
+                } else {
+                    // We are done emitting all parts of the current component glyph, pop it from the stack and start again
+                    self.current_compound_glyphs.pop();
+                    return self.next();
                 }
             } else {
-                // We are done emitting all parts of the current component glyph, pop it from the stack and start again
-                self.current_compound_glyphs.pop();
-                return self.next();
-            }
-        } else {
-            self.glyphs.next()?
-        };
+                self.glyphs.next()?
+            };
 
         let glyph = self
             .glyphs
@@ -483,7 +680,12 @@ impl<'a, 'b> Iterator for RenderedGlyphIterator<'a, 'b> {
                 self.next()
             },
             Glyph::Simple(simple_glyph) => {
-                let path_operations = PathReader::new(simple_glyph.into_iter());
+                let mut points: Vec<GlyphPoint> = simple_glyph.into_iter().collect();
+                self.glyphs
+                    .font
+                    .apply_variations(positioned_glyph.id, &mut points);
+
+                let path_operations = PathReader::new(points.into_iter());
                 Some(RenderedGlyph {
                     metrics: simple_glyph.metrics,
                     position: math::Vec2D::new(positioned_glyph.x, positioned_glyph.y),
@@ -493,7 +695,15 @@ impl<'a, 'b> Iterator for RenderedGlyphIterator<'a, 'b> {
             Glyph::Compound(compound_glyph) => {
                 self.x = positioned_glyph.x;
                 self.y = positioned_glyph.y;
-                self.current_compound_glyphs.push(compound_glyph);
+
+                // Each component offset is varied like a single point
+                let deltas = self
+                    .glyphs
+                    .font
+                    .glyph_deltas(positioned_glyph.id, compound_glyph.count(), None)
+                    .unwrap_or_default();
+                self.current_compound_glyphs
+                    .push((compound_glyph, deltas.into_iter()));
                 self.next()
             },
         }
@@ -0,0 +1,91 @@
+//! [avar](https://learn.microsoft.com/en-us/typography/opentype/spec/avar) table implementation
+//!
+//! Lets fonts modify the normalized value of each axis, so that the design of the glyphs
+//! does not have to change linearly along the axis.
+
+use crate::ttf::{read_i16_at, read_u16_at, TTFParseError};
+
+/// The size of the `avar` header in bytes
+const HEADER_SIZE: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct AvarTable {
+    /// For every axis, pairs of normalized values and what they are mapped to,
+    /// sorted by the former
+    segment_maps: Vec<Vec<(f32, f32)>>,
+}
+
+impl AvarTable {
+    pub fn new(data: &[u8]) -> Result<Self, TTFParseError> {
+        if data.len() < HEADER_SIZE {
+            return Err(TTFParseError::UnexpectedEOF);
+        }
+
+        if read_u16_at(data, 0) != 1 {
+            return Err(TTFParseError::UnsupportedFormat);
+        }
+
+        let axis_count = read_u16_at(data, 6) as usize;
+
+        let mut offset = HEADER_SIZE;
+        let mut segment_maps = Vec::with_capacity(axis_count);
+        for _ in 0..axis_count {
+            if data.len() < offset + 2 {
+                return Err(TTFParseError::UnexpectedEOF);
+            }
+            let position_map_count = read_u16_at(data, offset) as usize;
+            offset += 2;
+
+            let maps = data
+                .get(offset..offset + position_map_count * 4)
+                .ok_or(TTFParseError::UnexpectedEOF)?;
+            offset += position_map_count * 4;
+
+            let segment_map = maps
+                .array_chunks::<4>()
+                .map(|map| (read_f2dot14_at(map, 0), read_f2dot14_at(map, 2)))
+                .collect();
+            segment_maps.push(segment_map);
+        }
+
+        Ok(Self { segment_maps })
+    }
+
+    /// Modify the normalized value of the axis with the given index
+    #[must_use]
+    pub fn map(&self, axis_index: usize, value: f32) -> f32 {
+        let Some(segment_map) = self.segment_maps.get(axis_index) else {
+            return value;
+        };
+
+        let Some(&(first_from, first_to)) = segment_map.first() else {
+            return value;
+        };
+
+        if value <= first_from {
+            return first_to;
+        }
+
+        for window in segment_map.windows(2) {
+            let (start_from, start_to) = window[0];
+            let (end_from, end_to) = window[1];
+
+            if value <= end_from {
+                if end_from == start_from {
+                    return end_to;
+                }
+
+                let t = (value - start_from) / (end_from - start_from);
+                return start_to + t * (end_to - start_to);
+            }
+        }
+
+        // The value is larger than all the values in the map
+        segment_map.last().map_or(value, |&(_, to)| to)
+    }
+}
+
+/// Read a 2.14 fixed point number
+pub(crate) fn read_f2dot14_at(data: &[u8], offset: usize) -> f32 {
+    read_i16_at(data, offset) as f32 / 16384.
+}
@@ -0,0 +1,153 @@
+//! [fvar](https://learn.microsoft.com/en-us/typography/opentype/spec/fvar) table implementation
+//!
+//! Lists the design axes of a variable font. Named instances are not parsed, since
+//! they can always be selected by their axis values instead.
+
+use crate::ttf::{read_u16_at, read_u32_at, TTFParseError};
+
+/// The size of the `fvar` header in bytes
+const HEADER_SIZE: usize = 16;
+
+/// The minimum size of a `VariationAxisRecord` in bytes
+const AXIS_RECORD_SIZE: usize = 20;
+
+/// A design axis along which the glyphs of a variable font can change, like their weight
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VariationAxis {
+    /// Identifies the axis, like `wght` for the weight
+    pub tag: [u8; 4],
+    pub min_value: f32,
+    pub default_value: f32,
+    pub max_value: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct FvarTable {
+    axes: Vec<VariationAxis>,
+}
+
+impl FvarTable {
+    pub fn new(data: &[u8]) -> Result<Self, TTFParseError> {
+        if data.len() < HEADER_SIZE {
+            return Err(TTFParseError::UnexpectedEOF);
+        }
+
+        if read_u16_at(data, 0) != 1 {
+            return Err(TTFParseError::UnsupportedFormat);
+        }
+
+        let axes_offset = read_u16_at(data, 4) as usize;
+        let axis_count = read_u16_at(data, 8) as usize;
+        let axis_size = read_u16_at(data, 10) as usize;
+
+        if axis_size < AXIS_RECORD_SIZE {
+            return Err(TTFParseError::UnsupportedFormat);
+        }
+
+        let axes = (0..axis_count)
+            .map(|index| {
+                let record = data
+                    .get(axes_offset + index * axis_size..)
+                    .and_then(|record| record.get(..AXIS_RECORD_SIZE))
+                    .ok_or(TTFParseError::UnexpectedEOF)?;
+
+                let axis = VariationAxis {
+                    tag: record[..4].try_into().unwrap(),
+                    min_value: read_fixed_at(record, 4),
+                    default_value: read_fixed_at(record, 8),
+                    max_value: read_fixed_at(record, 12),
+                };
+
+                // Glyph variations refer to axes by their index, so a malformed axis
+                // can't just be skipped
+                if !(axis.min_value <= axis.default_value && axis.default_value <= axis.max_value) {
+                    return Err(TTFParseError::UnsupportedFormat);
+                }
+
+                Ok(axis)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { axes })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn axes(&self) -> &[VariationAxis] {
+        &self.axes
+    }
+}
+
+impl VariationAxis {
+    /// Map a value on the axis to the range `-1..=1`, with the default value at zero
+    ///
+    /// See <https://learn.microsoft.com/en-us/typography/opentype/spec/otvaroverview#coordinate-scales-and-normalization>
+    #[must_use]
+    pub fn normalize(&self, value: f32) -> f32 {
+        let value = value.clamp(self.min_value, self.max_value);
+
+        if value < self.default_value {
+            -(self.default_value - value) / (self.default_value - self.min_value)
+        } else if self.default_value < value {
+            (value - self.default_value) / (self.max_value - self.default_value)
+        } else {
+            0.
+        }
+    }
+}
+
+/// Read a 16.16 fixed point number
+fn read_fixed_at(data: &[u8], offset: usize) -> f32 {
+    read_u32_at(data, offset) as i32 as f32 / 65536.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fvar_with_axis(min: i32, default: i32, max: i32) -> Vec<u8> {
+        let mut data = vec![];
+
+        // Header with a single axis, directly after the header
+        for value in [
+            1_u16,
+            0,
+            HEADER_SIZE as u16,
+            2,
+            1,
+            AXIS_RECORD_SIZE as u16,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+
+        data.extend_from_slice(b"wght");
+        for value in [min, default, max] {
+            data.extend_from_slice(&(value << 16).to_be_bytes());
+        }
+        data.extend_from_slice(&[0; 4]);
+        data
+    }
+
+    #[test]
+    fn parse_axis() {
+        let fvar = FvarTable::new(&fvar_with_axis(100, 400, 900)).unwrap();
+        assert_eq!(
+            fvar.axes(),
+            [VariationAxis {
+                tag: *b"wght",
+                min_value: 100.,
+                default_value: 400.,
+                max_value: 900.,
+            }]
+        );
+        assert_eq!(fvar.axes()[0].normalize(650.), 0.5);
+    }
+
+    #[test]
+    fn reject_axis_with_invalid_range() {
+        assert!(FvarTable::new(&fvar_with_axis(900, 400, 100)).is_err());
+        assert!(FvarTable::new(&fvar_with_axis(500, 400, 900)).is_err());
+    }
+}
@@ -0,0 +1,466 @@
+//! [gvar](https://learn.microsoft.com/en-us/typography/opentype/spec/gvar) table implementation
+//!
+//! Stores how far each point of a glyph moves when the axis coordinates of a variable
+//! font change. Each glyph has a number of *tuple variations*, which each apply to a region
+//! of the design space and are scaled depending on how close the current coordinates are
+//! to the peak of that region.
+//!
+//! Besides the points of its outline, every glyph has four *phantom points*. The first two
+//! of them mark the horizontal origin and advance of the glyph, which lets the deltas change
+//! the advance width as well.
+
+use math::Vec2D;
+
+use super::{avar::read_f2dot14_at, cmap::GlyphID};
+use crate::{
+    ttf::{read_u16_at, read_u32_at, TTFParseError},
+    Stream,
+};
+
+/// The number of points that are appended to the outline of every glyph
+pub const NUM_PHANTOM_POINTS: usize = 4;
+
+/// The size of the `gvar` header in bytes
+const HEADER_SIZE: usize = 20;
+
+/// Set in the `flags` of the header if the glyph data offsets are 32 bit wide
+const LONG_OFFSETS: u16 = 1;
+
+/// Set in the tuple variation count if the glyph has point numbers that are shared by all tuples
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const COUNT_MASK: u16 = 0x0FFF;
+
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+const INTERMEDIATE_REGION: u16 = 0x4000;
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+const TUPLE_INDEX_MASK: u16 = 0x0FFF;
+
+const POINTS_ARE_WORDS: u8 = 0x80;
+const POINT_RUN_COUNT_MASK: u8 = 0x7F;
+
+const DELTAS_ARE_ZERO: u8 = 0x80;
+const DELTAS_ARE_WORDS: u8 = 0x40;
+const DELTA_RUN_COUNT_MASK: u8 = 0x3F;
+
+#[derive(Clone, Debug)]
+pub struct GvarTable {
+    data: Vec<u8>,
+    axis_count: usize,
+
+    /// Peak coordinates that can be referenced by the tuple variations of all glyphs
+    shared_tuples: Vec<Vec<f32>>,
+
+    /// The range of bytes describing the variations of each glyph
+    glyph_data_offsets: Vec<usize>,
+}
+
+/// The outline of a simple glyph, before any variations are applied
+///
+/// Tuple variations only need to specify the deltas of some points, the
+/// deltas of the remaining points on each contour are inferred from their neighbours.
+#[derive(Clone, Copy, Debug)]
+pub struct Outline<'a> {
+    pub points: &'a [Vec2D<i32>],
+
+    /// The index of the last point of every contour
+    pub contour_end_points: &'a [usize],
+}
+
+impl GvarTable {
+    pub fn new(data: &[u8]) -> Result<Self, TTFParseError> {
+        if data.len() < HEADER_SIZE {
+            return Err(TTFParseError::UnexpectedEOF);
+        }
+
+        if read_u16_at(data, 0) != 1 {
+            return Err(TTFParseError::UnsupportedFormat);
+        }
+
+        let axis_count = read_u16_at(data, 4) as usize;
+        let shared_tuple_count = read_u16_at(data, 6) as usize;
+        let shared_tuples_offset = read_u32_at(data, 8) as usize;
+        let glyph_count = read_u16_at(data, 12) as usize;
+        let flags = read_u16_at(data, 14);
+        let glyph_data_array_offset = read_u32_at(data, 16) as usize;
+
+        let shared_tuples = (0..shared_tuple_count)
+            .map(|index| {
+                let tuple_offset = shared_tuples_offset + index * axis_count * 2;
+                let tuple = data
+                    .get(tuple_offset..tuple_offset + axis_count * 2)
+                    .ok_or(TTFParseError::UnexpectedEOF)?;
+                Ok(read_tuple(tuple, axis_count))
+            })
+            .collect::<Result<_, _>>()?;
+
+        let offset_size = if flags & LONG_OFFSETS == 0 { 2 } else { 4 };
+        let offsets = data
+            .get(HEADER_SIZE..HEADER_SIZE + (glyph_count + 1) * offset_size)
+            .ok_or(TTFParseError::UnexpectedEOF)?;
+        let glyph_data_offsets = (0..=glyph_count)
+            .map(|index| {
+                // Short offsets are stored divided by two
+                let offset = if offset_size == 2 {
+                    read_u16_at(offsets, index * 2) as usize * 2
+                } else {
+                    read_u32_at(offsets, index * 4) as usize
+                };
+                glyph_data_array_offset + offset
+            })
+            .collect();
+
+        Ok(Self {
+            data: data.to_vec(),
+            axis_count,
+            shared_tuples,
+            glyph_data_offsets,
+        })
+    }
+
+    /// Compute how far each point of a glyph moves at the given normalized coordinates
+    ///
+    /// `num_points` is the number of points in the outline of a simple glyph, or the
+    /// number of components of a compound glyph. The returned deltas include the
+    /// [phantom points](NUM_PHANTOM_POINTS) after those.
+    ///
+    /// Without an `outline`, points whose deltas are not specified don't move.
+    pub fn glyph_deltas(
+        &self,
+        glyph_id: GlyphID,
+        coordinates: &[f32],
+        num_points: usize,
+        outline: Option<Outline<'_>>,
+    ) -> Result<Vec<Vec2D<f32>>, TTFParseError> {
+        let total_points = num_points + NUM_PHANTOM_POINTS;
+        let mut deltas = vec![Vec2D::new(0., 0.); total_points];
+
+        let index = glyph_id.numeric() as usize;
+        let (Some(&start), Some(&end)) = (
+            self.glyph_data_offsets.get(index),
+            self.glyph_data_offsets.get(index + 1),
+        ) else {
+            return Ok(deltas);
+        };
+
+        if end <= start {
+            // The glyph does not vary
+            return Ok(deltas);
+        }
+
+        let glyph_data = self
+            .data
+            .get(start..end)
+            .ok_or(TTFParseError::UnexpectedEOF)?;
+        if glyph_data.len() < 4 {
+            return Err(TTFParseError::UnexpectedEOF);
+        }
+
+        let tuple_variation_count = read_u16_at(glyph_data, 0);
+        let serialized_data = glyph_data
+            .get(read_u16_at(glyph_data, 2) as usize..)
+            .ok_or(TTFParseError::UnexpectedEOF)?;
+
+        let mut serialized_data = Stream::new(serialized_data);
+        let shared_point_numbers = if tuple_variation_count & SHARED_POINT_NUMBERS != 0 {
+            read_packed_point_numbers(&mut serialized_data)?
+        } else {
+            None
+        };
+        let mut serialized_data = serialized_data.remaining();
+
+        let mut headers = Stream::new(&glyph_data[4..]);
+        for _ in 0..tuple_variation_count & COUNT_MASK {
+            let variation_data_size = headers.read::<u16>()? as usize;
+            let tuple_index = headers.read::<u16>()?;
+
+            let peak = if tuple_index & EMBEDDED_PEAK_TUPLE != 0 {
+                self.read_tuple(&mut headers)?
+            } else {
+                self.shared_tuples
+                    .get((tuple_index & TUPLE_INDEX_MASK) as usize)
+                    .ok_or(TTFParseError::UnexpectedEOF)?
+                    .clone()
+            };
+
+            let intermediate_region = if tuple_index & INTERMEDIATE_REGION != 0 {
+                Some((
+                    self.read_tuple(&mut headers)?,
+                    self.read_tuple(&mut headers)?,
+                ))
+            } else {
+                None
+            };
+
+            // The data of each tuple follows directly after the data of the previous one,
+            // so it has to be skipped even if the tuple does not apply
+            let tuple_data = serialized_data
+                .get(..variation_data_size)
+                .ok_or(TTFParseError::UnexpectedEOF)?;
+            serialized_data = &serialized_data[variation_data_size..];
+            let mut tuple_data = Stream::new(tuple_data);
+
+            let scalar = tuple_scalar(
+                coordinates,
+                &peak,
+                intermediate_region
+                    .as_ref()
+                    .map(|(start, end)| (start.as_slice(), end.as_slice())),
+            );
+            if scalar == 0. {
+                continue;
+            }
+
+            let point_numbers = if tuple_index & PRIVATE_POINT_NUMBERS != 0 {
+                read_packed_point_numbers(&mut tuple_data)?
+            } else {
+                shared_point_numbers.clone()
+            };
+
+            let Some(point_numbers) = point_numbers else {
+                // The tuple specifies deltas for all points
+                let x_deltas = read_packed_deltas(&mut tuple_data, total_points)?;
+                let y_deltas = read_packed_deltas(&mut tuple_data, total_points)?;
+
+                for ((delta, x), y) in deltas.iter_mut().zip(x_deltas).zip(y_deltas) {
+                    delta.x += scalar * x as f32;
+                    delta.y += scalar * y as f32;
+                }
+                continue;
+            };
+
+            let x_deltas = read_packed_deltas(&mut tuple_data, point_numbers.len())?;
+            let y_deltas = read_packed_deltas(&mut tuple_data, point_numbers.len())?;
+
+            let mut tuple_deltas = vec![None; total_points];
+            for ((point, x), y) in point_numbers.into_iter().zip(x_deltas).zip(y_deltas) {
+                if let Some(delta) = tuple_deltas.get_mut(point as usize) {
+                    *delta = Some(Vec2D::new(x as f32, y as f32));
+                }
+            }
+
+            if let Some(outline) = outline {
+                infer_deltas(outline, &mut tuple_deltas);
+            }
+
+            for (delta, tuple_delta) in deltas.iter_mut().zip(tuple_deltas) {
+                if let Some(tuple_delta) = tuple_delta {
+                    delta.x += scalar * tuple_delta.x;
+                    delta.y += scalar * tuple_delta.y;
+                }
+            }
+        }
+
+        Ok(deltas)
+    }
+
+    fn read_tuple(&self, stream: &mut Stream<'_>) -> Result<Vec<f32>, TTFParseError> {
+        (0..self.axis_count)
+            .map(|_| Ok(stream.read::<i16>()? as f32 / 16384.))
+            .collect()
+    }
+}
+
+fn read_tuple(data: &[u8], axis_count: usize) -> Vec<f32> {
+    (0..axis_count)
+        .map(|axis| read_f2dot14_at(data, axis * 2))
+        .collect()
+}
+
+/// Compute how much a tuple variation applies at the given coordinates
+///
+/// See <https://learn.microsoft.com/en-us/typography/opentype/spec/otvaroverview#algorithm-for-interpolation-of-instance-values>
+fn tuple_scalar(
+    coordinates: &[f32],
+    peak: &[f32],
+    intermediate_region: Option<(&[f32], &[f32])>,
+) -> f32 {
+    let mut scalar = 1.;
+
+    for (axis, &peak) in peak.iter().enumerate() {
+        let coordinate = coordinates.get(axis).copied().unwrap_or_default();
+
+        if peak == 0. || coordinate == peak {
+            continue;
+        }
+
+        if let Some((start, end)) = intermediate_region {
+            let (start, end) = (start[axis], end[axis]);
+
+            // Invalid regions are ignored
+            if peak < start || end < peak || (start < 0. && 0. < end) {
+                continue;
+            }
+
+            if coordinate < start || end < coordinate {
+                return 0.;
+            }
+
+            if coordinate < peak {
+                scalar *= (coordinate - start) / (peak - start);
+            } else {
+                scalar *= (end - coordinate) / (end - peak);
+            }
+        } else {
+            // The region reaches from zero to the peak
+            if coordinate == 0.
+                || coordinate.signum() != peak.signum()
+                || peak.abs() < coordinate.abs()
+            {
+                return 0.;
+            }
+
+            scalar *= coordinate / peak;
+        }
+    }
+
+    scalar
+}
+
+/// Returns `None` if the data refers to all points of the glyph
+///
+/// See <https://learn.microsoft.com/en-us/typography/opentype/spec/otvarcommonformats#packed-point-numbers>
+fn read_packed_point_numbers(stream: &mut Stream<'_>) -> Result<Option<Vec<u16>>, TTFParseError> {
+    let first_byte = stream.read::<u8>()?;
+    if first_byte == 0 {
+        return Ok(None);
+    }
+
+    let count = if first_byte & POINTS_ARE_WORDS == 0 {
+        first_byte as usize
+    } else {
+        (((first_byte & POINT_RUN_COUNT_MASK) as usize) << 8) | stream.read::<u8>()? as usize
+    };
+
+    // Point numbers are stored as the difference to the previous one
+    let mut point_numbers = Vec::with_capacity(count);
+    let mut point_number: u16 = 0;
+    while point_numbers.len() < count {
+        let control = stream.read::<u8>()?;
+        let run_length = (control & POINT_RUN_COUNT_MASK) as usize + 1;
+
+        for _ in 0..run_length.min(count - point_numbers.len()) {
+            let difference = if control & POINTS_ARE_WORDS == 0 {
+                stream.read::<u8>()? as u16
+            } else {
+                stream.read::<u16>()?
+            };
+
+            point_number = point_number.wrapping_add(difference);
+            point_numbers.push(point_number);
+        }
+    }
+
+    Ok(Some(point_numbers))
+}
+
+/// See <https://learn.microsoft.com/en-us/typography/opentype/spec/otvarcommonformats#packed-deltas>
+fn read_packed_deltas(stream: &mut Stream<'_>, count: usize) -> Result<Vec<i16>, TTFParseError> {
+    let mut deltas = Vec::with_capacity(count);
+
+    while deltas.len() < count {
+        let control = stream.read::<u8>()?;
+        let run_length = (control & DELTA_RUN_COUNT_MASK) as usize + 1;
+
+        for _ in 0..run_length.min(count - deltas.len()) {
+            let delta = if control & DELTAS_ARE_ZERO != 0 {
+                0
+            } else if control & DELTAS_ARE_WORDS != 0 {
+                stream.read::<i16>()?
+            } else {
+                stream.read::<i8>()? as i16
+            };
+
+            deltas.push(delta);
+        }
+    }
+
+    Ok(deltas)
+}
+
+/// Interpolate the deltas of points that a tuple variation does not specify
+///
+/// See <https://learn.microsoft.com/en-us/typography/opentype/spec/gvar#inferred-deltas-for-un-referenced-point-numbers>
+fn infer_deltas(outline: Outline<'_>, deltas: &mut [Option<Vec2D<f32>>]) {
+    let mut contour_start = 0;
+
+    for &contour_end in outline.contour_end_points {
+        let contour = contour_start..=contour_end;
+        contour_start = contour_end + 1;
+
+        if outline.points.len() <= contour_end || deltas.len() <= contour_end {
+            break;
+        }
+
+        let referenced_points: Vec<usize> = contour
+            .clone()
+            .filter(|&point| deltas[point].is_some())
+            .collect();
+
+        // Contours without any referenced points don't move
+        let (Some(&first_referenced), Some(&last_referenced)) =
+            (referenced_points.first(), referenced_points.last())
+        else {
+            continue;
+        };
+
+        for point in contour {
+            if deltas[point].is_some() {
+                continue;
+            }
+
+            // The neighbours wrap around at the ends of the contour
+            let previous = referenced_points
+                .iter()
+                .rev()
+                .find(|&&referenced| referenced < point)
+                .copied()
+                .unwrap_or(last_referenced);
+            let next = referenced_points
+                .iter()
+                .find(|&&referenced| point < referenced)
+                .copied()
+                .unwrap_or(first_referenced);
+
+            let (Some(previous_delta), Some(next_delta)) = (deltas[previous], deltas[next]) else {
+                continue;
+            };
+
+            let position = outline.points[point];
+            let previous_position = outline.points[previous];
+            let next_position = outline.points[next];
+
+            deltas[point] = Some(Vec2D::new(
+                infer_delta(
+                    position.x,
+                    (previous_position.x, previous_delta.x),
+                    (next_position.x, next_delta.x),
+                ),
+                infer_delta(
+                    position.y,
+                    (previous_position.y, previous_delta.y),
+                    (next_position.y, next_delta.y),
+                ),
+            ));
+        }
+    }
+}
+
+/// Infer the delta of a single coordinate from the two referenced points around it
+fn infer_delta(coordinate: i32, a: (i32, f32), b: (i32, f32)) -> f32 {
+    let ((low, low_delta), (high, high_delta)) = if a.0 <= b.0 { (a, b) } else { (b, a) };
+
+    if low == high {
+        if low_delta == high_delta {
+            low_delta
+        } else {
+            0.
+        }
+    } else if coordinate <= low {
+        low_delta
+    } else if high <= coordinate {
+        high_delta
+    } else {
+        let t = (coordinate - low) as f32 / (high - low) as f32;
+        low_delta + t * (high_delta - low_delta)
+    }
+}
@@ -0,0 +1,90 @@
+//! [MATH](https://learn.microsoft.com/en-us/typography/opentype/spec/math) table implementation
+//!
+//! Only the global layout constants are parsed, glyph variants for stretchy operators
+//! are not supported yet.
+
+use crate::ttf::{read_i16_at, read_u16_at, TTFParseError};
+
+/// The size of the `MathConstants` table in bytes
+const CONSTANTS_SIZE: usize = 214;
+
+/// Constants that control the placement of the parts of a formula, in font units
+///
+/// See <https://learn.microsoft.com/en-us/typography/opentype/spec/math#mathconstants-table>
+/// for the meaning of each field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MathConstants {
+    /// The size of first-level scripts relative to the base, in percent
+    pub script_percent_scale_down: i16,
+
+    /// The size of second-level scripts relative to the base, in percent
+    pub script_script_percent_scale_down: i16,
+
+    /// The height of the mathematical axis above the baseline
+    pub axis_height: i16,
+    pub subscript_shift_down: i16,
+    pub subscript_top_max: i16,
+    pub superscript_shift_up: i16,
+    pub superscript_bottom_min: i16,
+    pub sub_superscript_gap_min: i16,
+    pub space_after_script: i16,
+    pub fraction_numerator_shift_up: i16,
+    pub fraction_numerator_display_style_shift_up: i16,
+    pub fraction_denominator_shift_down: i16,
+    pub fraction_denominator_display_style_shift_down: i16,
+    pub fraction_numerator_gap_min: i16,
+    pub fraction_numerator_display_style_gap_min: i16,
+    pub fraction_rule_thickness: i16,
+    pub fraction_denominator_gap_min: i16,
+    pub fraction_denominator_display_style_gap_min: i16,
+    pub radical_vertical_gap: i16,
+    pub radical_display_style_vertical_gap: i16,
+    pub radical_rule_thickness: i16,
+    pub radical_extra_ascender: i16,
+}
+
+impl MathConstants {
+    /// Parse the constants from a `MATH` table
+    pub fn new(data: &[u8]) -> Result<Self, TTFParseError> {
+        if data.len() < 10 {
+            return Err(TTFParseError::UnexpectedEOF);
+        }
+
+        if read_u16_at(data, 0) != 1 {
+            return Err(TTFParseError::UnsupportedFormat);
+        }
+
+        let constants_offset = read_u16_at(data, 4) as usize;
+        let constants = data
+            .get(constants_offset..constants_offset + CONSTANTS_SIZE)
+            .ok_or(TTFParseError::UnexpectedEOF)?;
+
+        // Most constants are MathValueRecords, whose device table offset we ignore
+        let value_at = |offset| read_i16_at(constants, offset);
+
+        Ok(Self {
+            script_percent_scale_down: value_at(0),
+            script_script_percent_scale_down: value_at(2),
+            axis_height: value_at(12),
+            subscript_shift_down: value_at(24),
+            subscript_top_max: value_at(28),
+            superscript_shift_up: value_at(36),
+            superscript_bottom_min: value_at(44),
+            sub_superscript_gap_min: value_at(52),
+            space_after_script: value_at(60),
+            fraction_numerator_shift_up: value_at(120),
+            fraction_numerator_display_style_shift_up: value_at(124),
+            fraction_denominator_shift_down: value_at(128),
+            fraction_denominator_display_style_shift_down: value_at(132),
+            fraction_numerator_gap_min: value_at(136),
+            fraction_numerator_display_style_gap_min: value_at(140),
+            fraction_rule_thickness: value_at(144),
+            fraction_denominator_gap_min: value_at(148),
+            fraction_denominator_display_style_gap_min: value_at(152),
+            radical_vertical_gap: value_at(188),
+            radical_display_style_vertical_gap: value_at(192),
+            radical_rule_thickness: value_at(196),
+            radical_extra_ascender: value_at(200),
+        })
+    }
+}
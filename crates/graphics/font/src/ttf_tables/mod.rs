@@ -1,11 +1,15 @@
 //! The tables commonly found in a TrueType/OpenType font.
 
+pub mod avar;
 pub mod cmap;
+pub mod fvar;
 pub mod glyf;
+pub mod gvar;
 pub mod head;
 pub mod hhea;
 pub mod hmtx;
 pub mod loca;
+pub mod math;
 pub mod maxp;
 pub mod name;
 pub mod offset;
@@ -1,6 +1,6 @@
 use crate::{bmp, jpeg, png};
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Rgbaf32 {
     channels: [f32; 4],
 }
@@ -94,7 +94,7 @@ impl Rgbaf32 {
     }
 }
 /// A texture that holds visual content
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Texture {
     width: usize,
     height: usize,
@@ -168,6 +168,12 @@ impl Texture {
         &self.data
     }
 
+    /// The number of bytes occupied by the pixels of the texture
+    #[must_use]
+    pub fn size_in_bytes(&self) -> usize {
+        self.data.capacity() * std::mem::size_of::<Rgbaf32>()
+    }
+
     #[must_use]
     pub const fn width(&self) -> usize {
         self.width
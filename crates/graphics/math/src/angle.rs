@@ -16,6 +16,18 @@ impl Angle {
         Self(radians)
     }
 
+    #[inline]
+    #[must_use]
+    pub fn from_degrees(degrees: f32) -> Self {
+        Self(degrees.to_radians())
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn to_radians(self) -> f32 {
+        self.0
+    }
+
     #[inline]
     #[must_use]
     pub fn diff(&self, other: &Self) -> Self {
@@ -39,6 +51,12 @@ impl Angle {
     pub fn cos(&self) -> f32 {
         self.0.cos()
     }
+
+    #[inline]
+    #[must_use]
+    pub fn tan(&self) -> f32 {
+        self.0.tan()
+    }
 }
 
 impl PartialEq for Angle {
@@ -0,0 +1,280 @@
+//! Easing functions, which map the progress of an animation to the progress of its effect
+//!
+//! See <https://drafts.csswg.org/css-easing/> for more information.
+
+/// Maps an input progress value to an output progress value
+///
+/// <https://drafts.csswg.org/css-easing/#easing-functions>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EasingFunction {
+    /// <https://drafts.csswg.org/css-easing/#linear-easing-function>
+    Linear,
+
+    /// A cubic bezier curve from `(0, 0)` to `(1, 1)` with the two control points
+    /// `(x1, y1)` and `(x2, y2)`
+    ///
+    /// <https://drafts.csswg.org/css-easing/#cubic-bezier-easing-functions>
+    CubicBezier { x1: f32, y1: f32, x2: f32, y2: f32 },
+
+    /// <https://drafts.csswg.org/css-easing/#step-easing-functions>
+    Steps { count: u32, position: StepPosition },
+}
+
+/// Where the jumps of a [step easing function](EasingFunction::Steps) occur
+///
+/// <https://drafts.csswg.org/css-easing/#step-position>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StepPosition {
+    /// The first jump happens when the animation begins
+    Start,
+
+    /// The last jump happens when the animation ends
+    #[default]
+    End,
+
+    /// Neither at the beginning nor the end of the animation
+    None,
+
+    /// Both at the beginning and the end of the animation
+    Both,
+}
+
+impl EasingFunction {
+    /// <https://drafts.csswg.org/css-easing/#valdef-cubic-bezier-easing-function-ease>
+    pub const EASE: Self = Self::cubic_bezier(0.25, 0.1, 0.25, 1.);
+
+    /// <https://drafts.csswg.org/css-easing/#valdef-cubic-bezier-easing-function-ease-in>
+    pub const EASE_IN: Self = Self::cubic_bezier(0.42, 0., 1., 1.);
+
+    /// <https://drafts.csswg.org/css-easing/#valdef-cubic-bezier-easing-function-ease-out>
+    pub const EASE_OUT: Self = Self::cubic_bezier(0., 0., 0.58, 1.);
+
+    /// <https://drafts.csswg.org/css-easing/#valdef-cubic-bezier-easing-function-ease-in-out>
+    pub const EASE_IN_OUT: Self = Self::cubic_bezier(0.42, 0., 0.58, 1.);
+
+    /// <https://drafts.csswg.org/css-easing/#valdef-step-easing-function-step-start>
+    pub const STEP_START: Self = Self::Steps {
+        count: 1,
+        position: StepPosition::Start,
+    };
+
+    /// <https://drafts.csswg.org/css-easing/#valdef-step-easing-function-step-end>
+    pub const STEP_END: Self = Self::Steps {
+        count: 1,
+        position: StepPosition::End,
+    };
+
+    #[inline]
+    #[must_use]
+    pub const fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self::CubicBezier { x1, y1, x2, y2 }
+    }
+
+    /// Compute the output progress for a given input progress
+    ///
+    /// The output of cubic bezier curves may lie outside of `0..=1`, even
+    /// if the input does not.
+    #[must_use]
+    pub fn evaluate(&self, input_progress: f32) -> f32 {
+        self.evaluate_with_before_flag(input_progress, false)
+    }
+
+    /// Like [evaluate](Self::evaluate), but respects the
+    /// [before flag](https://drafts.csswg.org/css-easing/#before-flag)
+    ///
+    /// The before flag should be set if the animation is in its before phase.
+    /// It only affects step easing functions.
+    #[must_use]
+    pub fn evaluate_with_before_flag(&self, input_progress: f32, before_flag: bool) -> f32 {
+        match *self {
+            Self::Linear => input_progress,
+            Self::CubicBezier { x1, y1, x2, y2 } => {
+                evaluate_cubic_bezier(x1, y1, x2, y2, input_progress)
+            },
+            Self::Steps { count, position } => {
+                evaluate_steps(count, position, input_progress, before_flag)
+            },
+        }
+    }
+}
+
+impl Default for EasingFunction {
+    fn default() -> Self {
+        Self::EASE
+    }
+}
+
+/// Evaluate the y coordinate of a cubic bezier curve from `(0, 0)` to `(1, 1)` at the given x coordinate
+///
+/// <https://drafts.csswg.org/css-easing/#cubic-bezier-algo>
+fn evaluate_cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, x: f32) -> f32 {
+    // For input progress values outside the range [0, 1], the curve is extended
+    // infinitely using tangent of the curve at the closest endpoint
+    if x < 0. {
+        let slope = if x1 > 0. {
+            y1 / x1
+        } else if x2 > 0. {
+            y2 / x2
+        } else {
+            0.
+        };
+        return slope * x;
+    }
+
+    if x > 1. {
+        let slope = if x2 < 1. {
+            (1. - y2) / (1. - x2)
+        } else if x1 < 1. {
+            (1. - y1) / (1. - x1)
+        } else {
+            0.
+        };
+        return slope.mul_add(x - 1., 1.);
+    }
+
+    let bezier = |t: f32, p1: f32, p2: f32| {
+        let u = 1. - t;
+        3. * u * u * t * p1 + 3. * u * t * t * p2 + t * t * t
+    };
+    let bezier_derivative = |t: f32, p1: f32, p2: f32| {
+        let u = 1. - t;
+        3. * u * u * p1 + 6. * u * t * (p2 - p1) + 3. * t * t * (1. - p2)
+    };
+
+    // Find the parameter t for which the curve has the given x coordinate. Newton's method
+    // converges quickly in most cases, bisection is used as a fallback.
+    const EPSILON: f32 = 1e-6;
+    let mut t = x;
+    for _ in 0..8 {
+        let error = bezier(t, x1, x2) - x;
+        if error.abs() < EPSILON {
+            return bezier(t, y1, y2);
+        }
+
+        let derivative = bezier_derivative(t, x1, x2);
+        if derivative.abs() < EPSILON {
+            break;
+        }
+        t -= error / derivative;
+    }
+
+    let (mut low, mut high) = (0., 1.);
+    t = x;
+    while high - low > EPSILON {
+        if bezier(t, x1, x2) < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.;
+    }
+
+    bezier(t, y1, y2)
+}
+
+/// <https://drafts.csswg.org/css-easing/#step-easing-algo>
+fn evaluate_steps(
+    count: u32,
+    position: StepPosition,
+    input_progress: f32,
+    before_flag: bool,
+) -> f32 {
+    let steps = count as f32;
+
+    // 1. Calculate the current step as floor(input progress value × steps).
+    let mut current_step = (input_progress * steps).floor();
+
+    // 2. If the step position property is one of jump-start or jump-both,
+    //    increment current step by one.
+    if matches!(position, StepPosition::Start | StepPosition::Both) {
+        current_step += 1.;
+    }
+
+    // 3. If both of the following conditions are true:
+    //    * the before flag is set, and
+    //    * input progress value × steps mod 1 equals zero (that is, if input progress value × steps is integral),
+    //    then decrement current step by one.
+    if before_flag && (input_progress * steps).fract() == 0. {
+        current_step -= 1.;
+    }
+
+    // 4. If input progress value ≥ 0 and current step < 0, let current step be zero.
+    if input_progress >= 0. && current_step < 0. {
+        current_step = 0.;
+    }
+
+    // 5. Calculate jumps based on the step position
+    let jumps = match position {
+        StepPosition::Start | StepPosition::End => steps,
+        StepPosition::None => steps - 1.,
+        StepPosition::Both => steps + 1.,
+    };
+
+    // 6. If input progress value ≤ 1 and current step > jumps, let current step be jumps.
+    if input_progress <= 1. && current_step > jumps {
+        current_step = jumps;
+    }
+
+    // 7. The output progress value is current step / jumps.
+    current_step / jumps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EasingFunction, StepPosition};
+
+    fn close_to(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn cubic_bezier() {
+        assert_eq!(EasingFunction::Linear.evaluate(0.3), 0.3);
+        assert!(close_to(EasingFunction::EASE.evaluate(0.5), 0.8024));
+        assert!(close_to(EasingFunction::EASE_IN_OUT.evaluate(0.5), 0.5));
+        assert_eq!(EasingFunction::EASE.evaluate(0.), 0.);
+        assert_eq!(EasingFunction::EASE.evaluate(1.), 1.);
+    }
+
+    #[test]
+    fn cubic_bezier_outside_of_unit_interval() {
+        // The curve continues along its tangents
+        let bezier = EasingFunction::cubic_bezier(0.5, 1., 0.5, 0.);
+        assert!(close_to(bezier.evaluate(-1.), -2.));
+        assert!(close_to(bezier.evaluate(2.), 3.));
+
+        // ease-in starts with a horizontal tangent
+        assert_eq!(EasingFunction::EASE_IN.evaluate(-0.5), 0.);
+    }
+
+    #[test]
+    fn steps() {
+        let steps = EasingFunction::Steps {
+            count: 4,
+            position: StepPosition::End,
+        };
+        assert_eq!(steps.evaluate(0.3), 0.25);
+        assert_eq!(steps.evaluate(1.), 1.);
+
+        let steps = EasingFunction::Steps {
+            count: 2,
+            position: StepPosition::Start,
+        };
+        assert_eq!(steps.evaluate(0.), 0.5);
+        assert_eq!(steps.evaluate_with_before_flag(0., true), 0.);
+
+        let steps = EasingFunction::Steps {
+            count: 3,
+            position: StepPosition::None,
+        };
+        assert_eq!(steps.evaluate(0.5), 0.5);
+        assert_eq!(steps.evaluate(0.9), 1.);
+
+        let steps = EasingFunction::Steps {
+            count: 3,
+            position: StepPosition::Both,
+        };
+        assert_eq!(steps.evaluate(0.), 0.25);
+        assert_eq!(steps.evaluate(1.), 1.);
+    }
+}
@@ -2,12 +2,14 @@
 
 mod angle;
 mod color;
+mod easing;
 mod rect;
 mod transform;
 mod vec2d;
 
 pub use angle::Angle;
 pub use color::Color;
+pub use easing::{EasingFunction, StepPosition};
 pub use rect::Rectangle;
 pub use transform::AffineTransform;
 pub use vec2d::Vec2D;
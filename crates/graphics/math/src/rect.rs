@@ -1,6 +1,6 @@
 use super::Vec2D;
 
-use std::{cmp, ops};
+use std::ops;
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct Rectangle<T = f32> {
@@ -9,7 +9,7 @@ pub struct Rectangle<T = f32> {
 }
 
 impl<T> Rectangle<T> {
-    pub fn from_corners(top_left: Vec2D<T>, bottom_right: Vec2D<T>) -> Self {
+    pub const fn from_corners(top_left: Vec2D<T>, bottom_right: Vec2D<T>) -> Self {
         Self {
             top_left,
             bottom_right,
@@ -98,8 +98,15 @@ where
 
 impl<T> Rectangle<T>
 where
-    T: Ord + Copy,
+    T: PartialOrd + Copy,
 {
+    /// Returns `true` if the rectangle does not cover any area
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bottom_right.x <= self.top_left.x || self.bottom_right.y <= self.top_left.y
+    }
+
     #[inline]
     #[must_use]
     pub fn contains(&self, other: Self) -> bool {
@@ -113,20 +120,120 @@ where
             && (self.top_left.y..=self.bottom_right.y).contains(&point.y)
     }
 
+    /// Returns `true` if the two rectangles share some area
+    ///
+    /// Rectangles that only touch at their edges do not intersect.
+    #[inline]
+    #[must_use]
+    pub fn intersects(&self, other: Self) -> bool {
+        self.top_left.x < other.bottom_right.x
+            && other.top_left.x < self.bottom_right.x
+            && self.top_left.y < other.bottom_right.y
+            && other.top_left.y < self.bottom_right.y
+    }
+
+    /// Compute the area that is covered by both rectangles
+    ///
+    /// Returns `None` if the rectangles do not intersect.
+    #[must_use]
+    pub fn intersection(&self, other: Self) -> Option<Self> {
+        if !self.intersects(other) {
+            return None;
+        }
+
+        let top_left = Vec2D::new(
+            partial_max(self.top_left.x, other.top_left.x),
+            partial_max(self.top_left.y, other.top_left.y),
+        );
+        let bottom_right = Vec2D::new(
+            partial_min(self.bottom_right.x, other.bottom_right.x),
+            partial_min(self.bottom_right.y, other.bottom_right.y),
+        );
+
+        Some(Self::from_corners(top_left, bottom_right))
+    }
+
+    /// Compute the smallest rectangle that contains both rectangles
+    #[inline]
+    #[must_use]
+    pub fn union(&self, other: Self) -> Self {
+        let mut union = *self;
+        union.grow_to_contain(other);
+        union
+    }
+
     #[inline]
     pub fn grow_to_contain(&mut self, other: Self) {
-        // Like Ord::min/Ord::max except they only require T to implement
-        // PartialOrd, not Ord
-        self.top_left.x = cmp::min(self.top_left.x, other.top_left.x);
-        self.top_left.y = cmp::min(self.top_left.y, other.top_left.y);
-        self.bottom_right.x = cmp::max(self.bottom_right.x, other.bottom_right.x);
-        self.bottom_right.y = cmp::max(self.bottom_right.y, other.bottom_right.y);
+        self.top_left.x = partial_min(self.top_left.x, other.top_left.x);
+        self.top_left.y = partial_min(self.top_left.y, other.top_left.y);
+        self.bottom_right.x = partial_max(self.bottom_right.x, other.bottom_right.x);
+        self.bottom_right.y = partial_max(self.bottom_right.y, other.bottom_right.y);
     }
 
     pub fn grow_to_contain_point(&mut self, point: Vec2D<T>) {
-        self.top_left.x = cmp::min(self.top_left.x, point.x);
-        self.top_left.y = cmp::min(self.top_left.y, point.y);
-        self.bottom_right.x = cmp::max(self.bottom_right.x, point.x);
-        self.bottom_right.y = cmp::max(self.bottom_right.y, point.y);
+        self.top_left.x = partial_min(self.top_left.x, point.x);
+        self.top_left.y = partial_min(self.top_left.y, point.y);
+        self.bottom_right.x = partial_max(self.bottom_right.x, point.x);
+        self.bottom_right.y = partial_max(self.bottom_right.y, point.y);
+    }
+}
+
+// Like Ord::min/Ord::max except they only require T to implement
+// PartialOrd, not Ord
+#[inline]
+fn partial_min<T: PartialOrd>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+#[inline]
+fn partial_max<T: PartialOrd>(a: T, b: T) -> T {
+    if a < b {
+        b
+    } else {
+        a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rectangle;
+    use crate::Vec2D;
+
+    fn rect(x0: i32, y0: i32, x1: i32, y1: i32) -> Rectangle<i32> {
+        Rectangle::from_corners(Vec2D::new(x0, y0), Vec2D::new(x1, y1))
+    }
+
+    #[test]
+    fn intersection() {
+        let a = rect(0, 0, 10, 10);
+        let b = rect(5, -5, 15, 5);
+
+        assert_eq!(a.intersection(b), Some(rect(5, 0, 10, 5)));
+        assert_eq!(b.intersection(a), Some(rect(5, 0, 10, 5)));
+
+        // Touching edges
+        assert_eq!(a.intersection(rect(10, 0, 20, 10)), None);
+    }
+
+    #[test]
+    fn union() {
+        let a = rect(0, 0, 10, 10);
+        let b = rect(5, -5, 15, 5);
+
+        assert_eq!(a.union(b), rect(0, -5, 15, 10));
+    }
+
+    #[test]
+    fn contains() {
+        let a = Rectangle::from_corners(Vec2D::new(0., 0.), Vec2D::new(10., 10.));
+        let b = Rectangle::from_corners(Vec2D::new(2., 2.), Vec2D::new(4., 4.));
+
+        assert!(a.contains(b));
+        assert!(!b.contains(a));
+        assert!(a.contains_point(Vec2D::new(10., 0.)));
     }
 }
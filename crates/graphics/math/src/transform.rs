@@ -1,10 +1,13 @@
-use crate::{Angle, Vec2D};
+use std::ops;
+
+use crate::{Angle, Rectangle, Vec2D};
 
 /// A 2-dimensional transformation.
 ///
 /// Each [AffineTransform] is a `3x3` matrix that transforms a 2 dimensional vector `x`, `y`.
+/// The last row is always `0 0 1` and is therefore not stored.
 /// See [Wikipedia](https://en.wikipedia.org/wiki/Affine_transformation) for more information.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct AffineTransform([[f32; 3]; 2]);
 
 impl AffineTransform {
@@ -14,6 +17,26 @@ impl AffineTransform {
         Self([[1., 0., 0.], [0., 1., 0.]])
     }
 
+    /// Create a transformation from the first two rows of its matrix
+    #[inline]
+    #[must_use]
+    pub const fn from_matrix(matrix: [[f32; 3]; 2]) -> Self {
+        Self(matrix)
+    }
+
+    /// The first two rows of the transformation matrix
+    #[inline]
+    #[must_use]
+    pub const fn matrix(&self) -> [[f32; 3]; 2] {
+        self.0
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+
     /// Create transformation that shifts every point by a fixed offset
     #[inline]
     #[must_use]
@@ -39,6 +62,13 @@ impl AffineTransform {
         ])
     }
 
+    /// Create a transformation that skews points along the X and Y axis
+    #[inline]
+    #[must_use]
+    pub fn skew(x_angle: Angle, y_angle: Angle) -> Self {
+        Self([[1., x_angle.tan(), 0.], [y_angle.tan(), 1., 0.]])
+    }
+
     /// Apply this transform to a provided vector
     #[inline]
     #[must_use]
@@ -53,6 +83,54 @@ impl AffineTransform {
         }
     }
 
+    /// Apply this transform to a rectangle
+    ///
+    /// Since the transformed rectangle is not necessarily axis-aligned, the
+    /// smallest rectangle containing all four transformed corners is returned.
+    #[must_use]
+    pub fn apply_to_rect(self, rect: Rectangle) -> Rectangle {
+        let top_left = self.apply_to(rect.top_left());
+        let mut result = Rectangle::from_corners(top_left, top_left);
+        result.grow_to_contain_point(self.apply_to(rect.top_right()));
+        result.grow_to_contain_point(self.apply_to(rect.bottom_left()));
+        result.grow_to_contain_point(self.apply_to(rect.bottom_right()));
+        result
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn determinant(&self) -> f32 {
+        self.0[0][0] * self.0[1][1] - self.0[0][1] * self.0[1][0]
+    }
+
+    /// Compute the transform that undoes this transform
+    ///
+    /// Returns `None` if the transform is not invertible, for example because
+    /// it scales everything down to a single point.
+    #[must_use]
+    pub fn invert(&self) -> Option<Self> {
+        let determinant = self.determinant();
+        if determinant.abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let [[a, b, c], [d, e, f]] = self.0;
+        let inverse_determinant = determinant.recip();
+
+        Some(Self([
+            [
+                e * inverse_determinant,
+                -b * inverse_determinant,
+                (b * f - c * e) * inverse_determinant,
+            ],
+            [
+                -d * inverse_determinant,
+                a * inverse_determinant,
+                (c * d - a * f) * inverse_determinant,
+            ],
+        ]))
+    }
+
     /// Combine two transforms together into a single one
     #[inline]
     #[must_use]
@@ -79,6 +157,15 @@ impl AffineTransform {
     }
 }
 
+impl ops::Mul for AffineTransform {
+    type Output = Self;
+
+    /// Equivalent to [AffineTransform::chain]: `a * b` first applies `a` and then `b`
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.chain(rhs)
+    }
+}
+
 impl Default for AffineTransform {
     fn default() -> Self {
         Self::identity()
@@ -87,7 +174,7 @@ impl Default for AffineTransform {
 
 #[cfg(test)]
 mod tests {
-    use crate::{AffineTransform, Vec2D};
+    use crate::{AffineTransform, Angle, Rectangle, Vec2D};
 
     #[test]
     fn test_identity() {
@@ -119,4 +206,30 @@ mod tests {
         dbg!(translate, scale, chained);
         assert_eq!(chained.apply_to(p), Vec2D::new(-2., 12.));
     }
+
+    #[test]
+    fn test_invert() {
+        let transform = AffineTransform::translate(Vec2D::new(1., 2.))
+            .chain(AffineTransform::scale(2., 4.))
+            .chain(AffineTransform::translate(Vec2D::new(-3., 0.5)));
+        let inverse = transform.invert().unwrap();
+
+        let p = Vec2D::new(3., -5.);
+        assert_eq!(inverse.apply_to(transform.apply_to(p)), p);
+        assert!((transform * inverse).is_identity());
+
+        assert!(AffineTransform::scale(0., 1.).invert().is_none());
+    }
+
+    #[test]
+    fn test_apply_to_rect() {
+        let rect = Rectangle::from_corners(Vec2D::new(0., 0.), Vec2D::new(2., 1.));
+        let transform = AffineTransform::rotate(Angle::from_degrees(90.));
+        let transformed = transform.apply_to_rect(rect);
+
+        assert!((transformed.top_left().x - -1.).abs() < 1e-6);
+        assert!((transformed.top_left().y - 0.).abs() < 1e-6);
+        assert!((transformed.bottom_right().x - 0.).abs() < 1e-6);
+        assert!((transformed.bottom_right().y - 2.).abs() < 1e-6);
+    }
 }
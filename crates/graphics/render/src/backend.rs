@@ -0,0 +1,57 @@
+//! Backends that turn a [Composition] into pixels
+
+use image::Texture;
+
+use crate::Composition;
+
+/// Draws [Compositions](Composition) to a texture
+///
+/// The software rasterizer is always available, other backends might
+/// fail to initialize at runtime, in which case [create_backend] falls back
+/// to software rendering.
+pub trait Backend {
+    /// Draw the composition into the given texture
+    fn render(&mut self, composition: &mut Composition, target: &mut Texture);
+
+    /// A human readable name for the backend, used for diagnostics
+    fn name(&self) -> &'static str;
+}
+
+/// Renders on the CPU, only redrawing the parts of the texture that changed
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SoftwareBackend;
+
+impl Backend for SoftwareBackend {
+    fn render(&mut self, composition: &mut Composition, target: &mut Texture) {
+        let _span = trace::span!(Paint, "rasterize");
+        composition.render_to(target);
+    }
+
+    fn name(&self) -> &'static str {
+        "software"
+    }
+}
+
+/// Create the most capable backend that is available
+///
+/// If `prefer_gpu` is set but no GPU backend can be created, the software
+/// rasterizer is used instead.
+#[must_use]
+pub fn create_backend(prefer_gpu: bool) -> Box<dyn Backend> {
+    #[cfg(feature = "gpu")]
+    if prefer_gpu {
+        match crate::gpu::GpuBackend::new() {
+            Ok(backend) => return Box::new(backend),
+            Err(error) => {
+                log::warn!("Failed to initialize GPU backend ({error}), falling back to software rendering")
+            },
+        }
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    if prefer_gpu {
+        log::warn!("Compiled without GPU support, falling back to software rendering");
+    }
+
+    Box::new(SoftwareBackend)
+}
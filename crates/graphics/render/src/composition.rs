@@ -2,9 +2,13 @@
 
 use std::collections::{hash_map::Iter, HashMap};
 
-use image::Texture;
+use image::{Rgbaf32, Texture};
+use math::{Rectangle, Vec2D};
 
-use crate::Layer;
+use crate::{
+    tiles::{PreparedLayer, TileCache},
+    Damage, Layer,
+};
 
 /// Manages all the different [Layers](Layer) that should be rendered.
 ///
@@ -13,6 +17,22 @@ use crate::Layer;
 pub struct Composition {
     dpi: (f32, f32),
     layers: HashMap<u16, Layer>,
+
+    /// The offset of the root scroll frame of the most recently composited [LayerTree](crate::LayerTree)
+    scroll_offset: Vec2D,
+
+    /// The color that dirty tiles are cleared to before the layers are drawn
+    background: Rgbaf32,
+
+    /// The tiles of the render target that need to be redrawn
+    damage: Damage,
+
+    /// The tiles that were redrawn during the previous frame, which the other render
+    /// target is missing when alternating between two of them
+    previous_damage: Damage,
+
+    /// What was drawn to the render target, and tiles that might be drawn again
+    tiles: TileCache,
 }
 
 impl Default for Composition {
@@ -20,6 +40,11 @@ impl Default for Composition {
         Self {
             dpi: (1., 1.),
             layers: HashMap::default(),
+            scroll_offset: Vec2D::ORIGIN,
+            background: Rgbaf32::rgb(1., 1., 1.),
+            damage: Damage::default(),
+            previous_damage: Damage::default(),
+            tiles: TileCache::default(),
         }
     }
 }
@@ -37,32 +62,137 @@ impl Composition {
         })
     }
 
+    /// Insert a [Layer] at the given index, replacing any existing layer
+    ///
+    /// The layer is scaled by the dpi of the composition.
+    pub fn insert_layer(&mut self, at_index: u16, mut layer: Layer) {
+        layer.scale(self.dpi.0, self.dpi.1);
+        self.layers.insert(at_index, layer);
+    }
+
     pub fn layers(&self) -> Iter<'_, u16, Layer> {
         self.layers.iter()
     }
 
     #[inline]
     pub fn set_dpi(&mut self, dpi: (f32, f32)) {
+        if self.dpi != dpi {
+            self.damage.invalidate_all();
+        }
         self.dpi = dpi;
     }
 
+    /// Scrolling moves everything on the screen, so changing the offset invalidates the whole composition
+    #[inline]
+    pub fn set_scroll_offset(&mut self, scroll_offset: Vec2D) {
+        if self.scroll_offset != scroll_offset {
+            self.damage.invalidate_all();
+        }
+        self.scroll_offset = scroll_offset;
+    }
+
+    #[inline]
+    pub fn set_background(&mut self, background: Rgbaf32) {
+        self.background = background;
+        self.damage.invalidate_all();
+    }
+
+    /// Remove all layers from the composition
+    ///
+    /// This does not affect the damaged area, so the previous contents
+    /// of the render target are retained where nothing changed.
     #[inline]
     pub fn clear(&mut self) {
         self.layers.clear();
     }
 
-    pub fn render_to(&mut self, texture: &mut Texture) {
-        // Draw all the layers, in order
-        let mut keys: Vec<u16> = self.layers.keys().copied().collect();
-        keys.sort();
+    /// Mark an area (in display points, not pixels) as needing to be redrawn
+    pub fn invalidate(&mut self, area: Rectangle) {
+        let top_left = area.top_left();
+        let bottom_right = area.bottom_right();
+
+        let area_in_pixels = Rectangle::from_corners(
+            math::Vec2D::new(top_left.x * self.dpi.0, top_left.y * self.dpi.1),
+            math::Vec2D::new(bottom_right.x * self.dpi.0, bottom_right.y * self.dpi.1),
+        );
+        self.damage.invalidate(area_in_pixels);
+    }
 
-        for key in keys {
-            let layer = self
-                .layers
-                .get_mut(&key)
-                .expect("Every key returned by layers.keys() should be valid");
+    /// Mark the whole render target as needing to be redrawn
+    #[inline]
+    pub fn invalidate_all(&mut self) {
+        self.damage.invalidate_all();
+    }
 
-            layer.render_to(texture);
+    #[cfg(feature = "gpu")]
+    #[inline]
+    #[must_use]
+    pub(crate) fn background(&self) -> Rgbaf32 {
+        self.background
+    }
+
+    #[cfg(feature = "gpu")]
+    /// All layers, sorted by the order in which they should be drawn
+    pub(crate) fn layers_in_paint_order(&mut self) -> Vec<&mut Layer> {
+        let mut layers: Vec<(&u16, &mut Layer)> = self.layers.iter_mut().collect();
+        layers.sort_by_key(|(key, _)| **key);
+        layers.into_iter().map(|(_, layer)| layer).collect()
+    }
+
+    /// Render to the texture that was used before the current one from now on
+    ///
+    /// This allows double buffering: Alternating between two textures only redraws
+    /// what changed since a texture was last rendered to. If `retained` is `false`,
+    /// the next texture is a new one and is redrawn completely.
+    pub fn swap_targets(&mut self, retained: bool) {
+        self.tiles.swap_targets(retained);
+
+        if retained {
+            self.damage.include(&self.previous_damage);
+        } else {
+            self.damage.invalidate_all();
         }
     }
+
+    #[cfg(feature = "gpu")]
+    /// Signal that the render target was fully redrawn by a [Backend](crate::Backend)
+    #[inline]
+    pub(crate) fn mark_rendered(&mut self) {
+        self.previous_damage.clone_from(&self.damage);
+        self.damage.clear();
+    }
+
+    /// Draw all layers to the parts of the texture that have been invalidated
+    /// since the last call to this function.
+    ///
+    /// Tiles are rasterized in parallel. Tiles whose contents did not actually change
+    /// are left untouched, so the same texture should be passed every time (or
+    /// [Self::swap_targets] must be called before passing a different one).
+    /// If the size of the texture changed, the whole texture is redrawn.
+    pub fn render_to(&mut self, texture: &mut Texture) {
+        if self.damage.size() != (texture.width(), texture.height()) {
+            self.damage = Damage::new(texture.width(), texture.height());
+            self.tiles.reset(texture.width(), texture.height());
+        }
+
+        self.previous_damage.clone_from(&self.damage);
+        if self.damage.is_empty() {
+            return;
+        }
+
+        let dirty_tiles: Vec<Rectangle<usize>> = self.damage.dirty_tiles().collect();
+
+        // Flatten all the layers, in paint order
+        let mut layers: Vec<(&u16, &mut Layer)> = self.layers.iter_mut().collect();
+        layers.sort_by_key(|(key, _)| **key);
+        let layers: Vec<PreparedLayer<'_>> = layers
+            .into_iter()
+            .filter_map(|(_, layer)| PreparedLayer::new(layer))
+            .collect();
+
+        self.tiles
+            .render(texture, &dirty_tiles, &layers, self.background);
+
+        self.damage.clear();
+    }
 }
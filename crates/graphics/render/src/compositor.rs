@@ -0,0 +1,396 @@
+//! Compositing on a separate thread
+//!
+//! The thread that runs layout and scripts paints [LayerTrees](LayerTree) and commits them
+//! to the [Compositor], which rasterizes them on its own thread. Finished frames are handed
+//! back with [Compositor::take_frame].
+//!
+//! Scrolling the root scroll frame of the tree is handled entirely by the compositor, so it
+//! keeps up with the input device even while the main thread is busy. The main thread
+//! learns about the distance that was scrolled with [Compositor::take_scroll_delta].
+//!
+//! Frames are double buffered: The compositor renders into one texture while the main
+//! thread displays the other one, so frames are handed over without copying them.
+
+use std::{
+    collections::VecDeque,
+    io, mem,
+    sync::{mpsc, Arc, Mutex, MutexGuard},
+    thread,
+};
+
+use image::Texture;
+use math::Vec2D;
+
+use crate::{create_backend, Backend, Composition, LayerTree};
+
+/// A rendered [LayerTree]
+#[derive(Clone, Debug)]
+pub struct Frame {
+    /// The compositor renders into the texture again once the frame and all its
+    /// clones were dropped, so frames should not be kept around for longer than necessary
+    pub texture: Arc<Texture>,
+
+    /// The offset of the root scroll frame that the tree was rendered with
+    pub scroll_offset: Vec2D,
+}
+
+enum Message {
+    Commit {
+        tree: LayerTree,
+
+        /// The size of the frame, in pixels
+        size: (usize, usize),
+
+        /// The number of scroll deltas that the main thread had taken when painting the tree
+        generation: u64,
+    },
+    ScrollBy(Vec2D),
+    Shutdown,
+}
+
+/// State that is shared between the compositor thread and the main thread
+#[derive(Debug, Default)]
+struct Shared {
+    /// The distance that the compositor scrolled, that was not taken by the main thread yet
+    unsynced_scroll: Vec2D,
+
+    /// Scroll deltas that were taken by the main thread, but are not part of a committed tree yet
+    in_flight_scroll: VecDeque<(u64, Vec2D)>,
+
+    /// The most recent frame that was not taken yet
+    frame: Option<Frame>,
+}
+
+impl Shared {
+    /// The scroll distance that the most recently committed tree does not include yet
+    fn pending_scroll(&self) -> Vec2D {
+        self.in_flight_scroll
+            .iter()
+            .fold(self.unsynced_scroll, |total, (_, delta)| total + *delta)
+    }
+}
+
+type FrameCallback = Box<dyn Fn() + Send>;
+
+/// Rasterizes [LayerTrees](LayerTree) on a separate thread
+pub struct Compositor {
+    messages: mpsc::Sender<Message>,
+    shared: Arc<Mutex<Shared>>,
+
+    /// The number of times that [Compositor::take_scroll_delta] was called
+    generation: u64,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Compositor {
+    /// Start the compositor thread
+    ///
+    /// `on_frame` is called on the compositor thread whenever a new frame is ready to be taken.
+    pub fn spawn<F>(prefer_gpu: bool, on_frame: F) -> io::Result<Self>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let (messages, receiver) = mpsc::channel();
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let thread_shared = shared.clone();
+        let thread = thread::Builder::new()
+            .name("Compositor".to_string())
+            .spawn(move || {
+                // Backends might not be sendable, so they are created on the thread that uses them
+                let mut compositor_thread = CompositorThread {
+                    shared: thread_shared,
+                    on_frame: Box::new(on_frame),
+                    backend: create_backend(prefer_gpu),
+                    composition: Composition::default(),
+                    size: (0, 0),
+                    frames: VecDeque::new(),
+                    tree: None,
+                };
+                compositor_thread.run(&receiver);
+            })?;
+
+        Ok(Self {
+            messages,
+            shared,
+            generation: 0,
+            thread: Some(thread),
+        })
+    }
+
+    fn shared(&self) -> MutexGuard<'_, Shared> {
+        self.shared.lock().expect("compositor lock is poisoned")
+    }
+
+    /// Replace the tree that is being displayed
+    ///
+    /// The offset of the root scroll frame should include all deltas returned from
+    /// [Compositor::take_scroll_delta]. Scrolling that happened after the last call to
+    /// [Compositor::take_scroll_delta] is applied on top of it.
+    pub fn commit(&self, tree: LayerTree, size: (usize, usize)) {
+        let message = Message::Commit {
+            tree,
+            size,
+            generation: self.generation,
+        };
+        _ = self.messages.send(message);
+    }
+
+    /// Scroll the root scroll frame of the current tree
+    ///
+    /// The offset is clamped to the size of the scroll frame. This does not block.
+    pub fn scroll_by(&self, delta: Vec2D) {
+        _ = self.messages.send(Message::ScrollBy(delta));
+    }
+
+    /// The distance that the compositor scrolled since the previous call to this function
+    ///
+    /// The main thread should apply the delta to its own scroll position before
+    /// painting the next tree.
+    #[must_use]
+    pub fn take_scroll_delta(&mut self) -> Vec2D {
+        self.generation += 1;
+
+        let mut shared = self.shared();
+        let delta = mem::take(&mut shared.unsynced_scroll);
+        if !delta.is_origin() {
+            shared.in_flight_scroll.push_back((self.generation, delta));
+        }
+        delta
+    }
+
+    /// The most recently rendered frame, if there was a new one since the previous call
+    #[must_use]
+    pub fn take_frame(&self) -> Option<Frame> {
+        self.shared().frame.take()
+    }
+}
+
+impl Drop for Compositor {
+    fn drop(&mut self) {
+        _ = self.messages.send(Message::Shutdown);
+
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                log::error!("Compositor thread panicked");
+            }
+        }
+    }
+}
+
+struct CompositorThread {
+    shared: Arc<Mutex<Shared>>,
+    on_frame: FrameCallback,
+    backend: Box<dyn Backend>,
+    composition: Composition,
+
+    /// The size of the frames that are rendered, in pixels
+    size: (usize, usize),
+
+    /// The two most recently rendered frames, oldest first
+    ///
+    /// Once the main thread released the older one, it is rendered into again
+    /// and only the parts that changed since then are redrawn.
+    frames: VecDeque<Arc<Texture>>,
+
+    /// The most recently committed tree, with the scroll offset that is currently displayed
+    tree: Option<LayerTree>,
+}
+
+impl CompositorThread {
+    fn run(&mut self, receiver: &mpsc::Receiver<Message>) {
+        while let Ok(message) = receiver.recv() {
+            // Catch up with everything that happened while the previous frame was rendered,
+            // but only render the final state
+            let mut needs_render = false;
+            for message in std::iter::once(message).chain(receiver.try_iter()) {
+                match message {
+                    Message::Commit {
+                        tree,
+                        size,
+                        generation,
+                    } => {
+                        // The damage of a tree that was never rendered is lost
+                        if needs_render {
+                            self.composition.invalidate_all();
+                        }
+
+                        self.commit(tree, size, generation);
+                        needs_render = true;
+                    },
+                    Message::ScrollBy(delta) => needs_render |= self.scroll_by(delta),
+                    Message::Shutdown => return,
+                }
+            }
+
+            if needs_render {
+                self.render();
+            }
+        }
+    }
+
+    fn shared(&self) -> MutexGuard<'_, Shared> {
+        self.shared.lock().expect("compositor lock is poisoned")
+    }
+
+    fn commit(&mut self, mut tree: LayerTree, size: (usize, usize), generation: u64) {
+        let pending_scroll = {
+            let mut shared = self.shared();
+
+            // The tree already includes all deltas that the main thread took before painting it
+            shared
+                .in_flight_scroll
+                .retain(|(delta_generation, _)| *delta_generation > generation);
+            shared.pending_scroll()
+        };
+
+        if let Some(scroll_frame) = tree.root_scroll_frame_mut() {
+            scroll_frame.offset = scroll_frame.clamp(scroll_frame.offset + pending_scroll);
+        }
+
+        self.size = size;
+        self.tree = Some(tree);
+    }
+
+    /// Returns `false` if the scroll offset did not change
+    fn scroll_by(&mut self, delta: Vec2D) -> bool {
+        let Some(scroll_frame) = self
+            .tree
+            .as_mut()
+            .and_then(LayerTree::root_scroll_frame_mut)
+        else {
+            return false;
+        };
+
+        let previous_offset = scroll_frame.offset;
+        scroll_frame.offset = scroll_frame.clamp(previous_offset + delta);
+        if scroll_frame.offset == previous_offset {
+            return false;
+        }
+
+        let scrolled_distance = scroll_frame.offset - previous_offset;
+        let mut shared = self.shared();
+        shared.unsynced_scroll = shared.unsynced_scroll + scrolled_distance;
+        true
+    }
+
+    fn render(&mut self) {
+        let _span = trace::span!(Paint, "composite");
+
+        let Some(tree) = &self.tree else {
+            return;
+        };
+
+        tree.composite(&mut self.composition);
+        let scroll_offset = tree.scroll_offset().unwrap_or(Vec2D::ORIGIN);
+
+        let mut target = self.next_target();
+        self.backend.render(&mut self.composition, &mut target);
+
+        let texture = Arc::new(target);
+        self.frames.push_back(texture.clone());
+        self.shared().frame = Some(Frame {
+            texture,
+            scroll_offset,
+        });
+        (self.on_frame)();
+    }
+
+    /// The texture that the next frame is rendered into
+    fn next_target(&mut self) -> Texture {
+        // The most recent frame stays on the screen until the next one is ready
+        let reusable_frame = if self.frames.len() == 2 {
+            self.frames
+                .pop_front()
+                .and_then(|frame| Arc::try_unwrap(frame).ok())
+                .filter(|frame| (frame.width(), frame.height()) == self.size)
+        } else {
+            None
+        };
+
+        // The first frame is rendered into a new texture without swapping, because there
+        // is nothing to swap with
+        if !self.frames.is_empty() {
+            self.composition.swap_targets(reusable_frame.is_some());
+        }
+
+        reusable_frame.unwrap_or_else(|| Texture::new(self.size.0, self.size.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{LayerNode, ScrollFrame};
+
+    fn scrolling_tree(offset: Vec2D) -> LayerTree {
+        let mut tree = LayerTree::default();
+        tree.root.children.push(LayerNode::scrolling(ScrollFrame {
+            offset,
+            content_size: Vec2D::new(10., 100.),
+            viewport_size: Vec2D::new(10., 10.),
+        }));
+        tree
+    }
+
+    fn wait_for_frame(compositor: &Compositor, frames: &mpsc::Receiver<()>) -> Frame {
+        frames
+            .recv_timeout(Duration::from_secs(10))
+            .expect("compositor did not render a frame");
+        compositor.take_frame().expect("frame was taken already")
+    }
+
+    #[test]
+    fn scroll_without_main_thread() {
+        let (frame_sender, frames) = mpsc::channel();
+        let mut compositor = Compositor::spawn(false, move || {
+            _ = frame_sender.send(());
+        })
+        .unwrap();
+
+        compositor.commit(scrolling_tree(Vec2D::ORIGIN), (10, 10));
+        let frame = wait_for_frame(&compositor, &frames);
+        assert_eq!((frame.texture.width(), frame.texture.height()), (10, 10));
+        assert_eq!(frame.scroll_offset, Vec2D::ORIGIN);
+
+        // Scrolling is clamped to the content of the scroll frame
+        compositor.scroll_by(Vec2D::new(0., 200.));
+        let frame = wait_for_frame(&compositor, &frames);
+        assert_eq!(frame.scroll_offset, Vec2D::new(0., 90.));
+
+        // A tree that was painted after the main thread saw the scroll delta keeps the offset
+        let delta = compositor.take_scroll_delta();
+        assert_eq!(delta, Vec2D::new(0., 90.));
+        compositor.scroll_by(Vec2D::new(0., -30.));
+        compositor.commit(scrolling_tree(delta), (10, 10));
+
+        // The scroll and the commit might be rendered as separate frames, but they agree on the offset
+        let frame = wait_for_frame(&compositor, &frames);
+        assert_eq!(frame.scroll_offset, Vec2D::new(0., 60.));
+        assert_eq!(compositor.take_scroll_delta(), Vec2D::new(0., -30.));
+    }
+
+    #[test]
+    fn reuse_released_frames() {
+        let (frame_sender, frames) = mpsc::channel();
+        let compositor = Compositor::spawn(false, move || {
+            _ = frame_sender.send(());
+        })
+        .unwrap();
+
+        compositor.commit(scrolling_tree(Vec2D::ORIGIN), (10, 10));
+        let first_frame = Arc::as_ptr(&wait_for_frame(&compositor, &frames).texture);
+
+        // The first frame is still displayed while the second one is rendered
+        compositor.scroll_by(Vec2D::new(0., 10.));
+        let second_frame = wait_for_frame(&compositor, &frames);
+        assert_ne!(Arc::as_ptr(&second_frame.texture), first_frame);
+
+        compositor.scroll_by(Vec2D::new(0., 10.));
+        let third_frame = wait_for_frame(&compositor, &frames);
+        assert_eq!(Arc::as_ptr(&third_frame.texture), first_frame);
+        assert_eq!(third_frame.scroll_offset, Vec2D::new(0., 20.));
+    }
+}
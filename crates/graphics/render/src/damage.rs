@@ -0,0 +1,122 @@
+//! Tracks which parts of a render target need to be re-rasterized
+
+use math::{Rectangle, Vec2D};
+
+/// The width and height of a single tile, in device pixels
+pub const TILE_SIZE: usize = 64;
+
+/// Divides a render target into square tiles and remembers which of them are out of date.
+///
+/// Only the dirty tiles are re-rasterized by [Composition::render_to](crate::Composition::render_to),
+/// the contents of all other tiles are retained from the previous frame.
+#[derive(Clone, Debug, Default)]
+pub struct Damage {
+    width: usize,
+    height: usize,
+    width_in_tiles: usize,
+    height_in_tiles: usize,
+    dirty: Vec<bool>,
+}
+
+impl Damage {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        let width_in_tiles = width.div_ceil(TILE_SIZE);
+        let height_in_tiles = height.div_ceil(TILE_SIZE);
+
+        Self {
+            width,
+            height,
+            width_in_tiles,
+            height_in_tiles,
+            dirty: vec![true; width_in_tiles * height_in_tiles],
+        }
+    }
+
+    /// Returns the size of the area that damage is tracked for
+    #[inline]
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Mark every tile as dirty
+    pub fn invalidate_all(&mut self) {
+        self.dirty.fill(true);
+    }
+
+    /// Mark all tiles that overlap the given area (in device pixels) as dirty
+    pub fn invalidate(&mut self, area: Rectangle) {
+        if self.dirty.is_empty() {
+            return;
+        }
+
+        // Grow the area slightly to account for anti-aliased edges that bleed into neighbouring pixels
+        let top_left = area
+            .top_left()
+            .map(|value| (value.floor() - 1.).max(0.) as usize);
+        let bottom_right = area
+            .bottom_right()
+            .map(|value| (value.ceil() + 1.).max(0.) as usize);
+
+        if top_left.x >= self.width || top_left.y >= self.height {
+            return;
+        }
+
+        let first_tile = Vec2D::new(top_left.x / TILE_SIZE, top_left.y / TILE_SIZE);
+        let last_tile = Vec2D::new(
+            (bottom_right.x / TILE_SIZE).min(self.width_in_tiles - 1),
+            (bottom_right.y / TILE_SIZE).min(self.height_in_tiles - 1),
+        );
+
+        for y in first_tile.y..=last_tile.y {
+            for x in first_tile.x..=last_tile.x {
+                self.dirty[y * self.width_in_tiles + x] = true;
+            }
+        }
+    }
+
+    /// Mark all tiles that are dirty in `other` as dirty
+    ///
+    /// If `other` tracks an area of a different size, every tile is marked as dirty.
+    pub fn include(&mut self, other: &Self) {
+        if other.size() != self.size() {
+            self.invalidate_all();
+            return;
+        }
+
+        for (is_dirty, other_is_dirty) in self.dirty.iter_mut().zip(&other.dirty) {
+            *is_dirty |= *other_is_dirty;
+        }
+    }
+
+    /// Returns `true` if no tile needs to be re-rasterized
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        !self.dirty.contains(&true)
+    }
+
+    /// Mark all tiles as up to date
+    pub fn clear(&mut self) {
+        self.dirty.fill(false);
+    }
+
+    /// Iterate over the areas (in device pixels) of all dirty tiles
+    pub fn dirty_tiles(&self) -> impl Iterator<Item = Rectangle<usize>> + '_ {
+        self.dirty
+            .iter()
+            .enumerate()
+            .filter(|(_, is_dirty)| **is_dirty)
+            .map(|(index, _)| {
+                let top_left = Vec2D::new(
+                    (index % self.width_in_tiles) * TILE_SIZE,
+                    (index / self.width_in_tiles) * TILE_SIZE,
+                );
+                let bottom_right = Vec2D::new(
+                    (top_left.x + TILE_SIZE).min(self.width),
+                    (top_left.y + TILE_SIZE).min(self.height),
+                );
+                Rectangle::from_corners(top_left, bottom_right)
+            })
+    }
+}
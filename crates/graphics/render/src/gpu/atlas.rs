@@ -0,0 +1,236 @@
+//! Packs many small images into a single texture
+
+use std::collections::HashMap;
+
+use math::{Rectangle, Vec2D};
+
+/// Empty space that is left around each entry, so that
+/// neighbouring entries don't bleed into each other
+const PADDING: usize = 1;
+
+/// A horizontal strip of the atlas that entries are placed into from left to right
+#[derive(Clone, Copy, Debug)]
+struct Shelf {
+    y: usize,
+    height: usize,
+    used_width: usize,
+}
+
+/// A RGBA texture atlas using a simple shelf packing strategy
+///
+/// Entries are identified by a caller-provided key. The atlas is never
+/// compacted, if it runs out of space the caller has to [clear](TextureAtlas::clear) it.
+/// Entries are never evicted implicitly, because quads that were already
+/// drawn might still refer to them.
+#[derive(Clone, Debug)]
+pub struct TextureAtlas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 4]>,
+    shelves: Vec<Shelf>,
+    entries: HashMap<u64, Rectangle<usize>>,
+
+    /// The area whose pixels changed since the atlas was last uploaded, if any
+    dirty_area: Option<Rectangle<usize>>,
+}
+
+impl TextureAtlas {
+    /// The area of an opaque white pixel, used for drawing solid colors
+    pub const WHITE_PIXEL: Rectangle<usize> =
+        Rectangle::from_corners(Vec2D::new(0, 0), Vec2D::new(1, 1));
+
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        let mut atlas = Self {
+            width,
+            height,
+            pixels: vec![[0; 4]; width * height],
+            shelves: vec![],
+            entries: HashMap::default(),
+            dirty_area: None,
+        };
+        atlas.clear();
+        atlas
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> &[[u8; 4]] {
+        &self.pixels
+    }
+
+    /// The area that changed since the last call to [TextureAtlas::mark_uploaded], if any
+    #[inline]
+    #[must_use]
+    pub const fn dirty_area(&self) -> Option<Rectangle<usize>> {
+        self.dirty_area
+    }
+
+    #[inline]
+    pub fn mark_uploaded(&mut self) {
+        self.dirty_area = None;
+    }
+
+    /// Evict all entries
+    pub fn clear(&mut self) {
+        self.pixels.fill([0; 4]);
+        self.shelves.clear();
+        self.entries.clear();
+        self.dirty_area = Some(Rectangle::from_position_and_size(
+            Vec2D::new(0, 0),
+            self.width,
+            self.height,
+        ));
+
+        // Reserve the white pixel
+        self.pixels[0] = [u8::MAX; 4];
+        self.shelves.push(Shelf {
+            y: 0,
+            height: 1,
+            used_width: 1 + PADDING,
+        });
+    }
+
+    /// Look up the area of the entry with the given key
+    #[must_use]
+    pub fn get(&self, key: u64) -> Option<Rectangle<usize>> {
+        self.entries.get(&key).copied()
+    }
+
+    /// Allocate space for a new entry and fill it with the pixels produced by `fill`
+    ///
+    /// `fill` is called with the `x` and `y` coordinates relative to the entry and
+    /// must return the color for that pixel.
+    ///
+    /// Returns `None` if there is not enough space left in the atlas.
+    pub fn insert<F>(
+        &mut self,
+        key: u64,
+        width: usize,
+        height: usize,
+        fill: F,
+    ) -> Option<Rectangle<usize>>
+    where
+        F: Fn(usize, usize) -> [u8; 4],
+    {
+        let top_left = self.allocate(width, height)?;
+
+        for y in 0..height {
+            let row_start = (top_left.y + y) * self.width + top_left.x;
+            for (x, pixel) in self.pixels[row_start..row_start + width]
+                .iter_mut()
+                .enumerate()
+            {
+                *pixel = fill(x, y);
+            }
+        }
+
+        let area = Rectangle::from_position_and_size(top_left, width, height);
+        self.entries.insert(key, area);
+        self.dirty_area = Some(
+            self.dirty_area
+                .map_or(area, |dirty_area| dirty_area.union(area)),
+        );
+        Some(area)
+    }
+
+    fn allocate(&mut self, width: usize, height: usize) -> Option<Vec2D<usize>> {
+        if self.width < width + PADDING {
+            return None;
+        }
+
+        // Find the shelf that wastes the least amount of vertical space
+        let best_shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| {
+                height <= shelf.height && width + PADDING <= self.width - shelf.used_width
+            })
+            .min_by_key(|shelf| shelf.height - height);
+
+        if let Some(shelf) = best_shelf {
+            let top_left = Vec2D::new(shelf.used_width, shelf.y);
+            shelf.used_width += width + PADDING;
+            return Some(top_left);
+        }
+
+        // Open a new shelf below the existing ones
+        let y = self
+            .shelves
+            .last()
+            .map(|shelf| shelf.y + shelf.height + PADDING)
+            .unwrap_or_default();
+
+        if self.height < y + height {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height,
+            used_width: width + PADDING,
+        });
+
+        Some(Vec2D::new(0, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextureAtlas;
+
+    #[test]
+    fn reuse_existing_entry() {
+        let mut atlas = TextureAtlas::new(16, 16);
+        assert!(atlas.get(1).is_none());
+
+        let inserted = atlas.insert(1, 4, 4, |_, _| [1; 4]);
+        assert!(inserted.is_some());
+        assert_eq!(atlas.get(1), inserted);
+    }
+
+    #[test]
+    fn entries_do_not_overlap() {
+        let mut atlas = TextureAtlas::new(16, 16);
+        let a = atlas.insert(1, 4, 4, |_, _| [1; 4]).unwrap();
+        let b = atlas.insert(2, 4, 4, |_, _| [2; 4]).unwrap();
+
+        assert!(!a.contains_point(b.top_left()));
+        assert!(!b.contains_point(a.top_left()));
+        assert!(!a.contains_point(TextureAtlas::WHITE_PIXEL.top_left()));
+    }
+
+    #[test]
+    fn entries_are_not_evicted_when_full() {
+        let mut atlas = TextureAtlas::new(8, 8);
+        let first = atlas.insert(1, 6, 6, |_, _| [1; 4]);
+        assert!(first.is_some());
+        assert!(atlas.insert(2, 6, 6, |_, _| [2; 4]).is_none());
+        assert_eq!(atlas.get(1), first);
+
+        // Once the atlas is cleared, there is room for the second entry
+        atlas.clear();
+        assert!(atlas.get(1).is_none());
+        assert!(atlas.insert(2, 6, 6, |_, _| [2; 4]).is_some());
+        assert_eq!(atlas.pixels()[0], [u8::MAX; 4]);
+        assert!(atlas.insert(3, 16, 16, |_, _| [3; 4]).is_none());
+    }
+
+    #[test]
+    fn only_changed_area_is_dirty() {
+        let mut atlas = TextureAtlas::new(16, 16);
+        assert_eq!(atlas.dirty_area().map(|area| area.width()), Some(16));
+        atlas.mark_uploaded();
+
+        let area = atlas.insert(1, 4, 4, |_, _| [1; 4]).unwrap();
+        assert_eq!(atlas.dirty_area(), Some(area));
+        atlas.mark_uploaded();
+        assert!(atlas.dirty_area().is_none());
+    }
+}
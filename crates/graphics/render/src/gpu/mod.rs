@@ -0,0 +1,638 @@
+//! A [Backend] that draws on the GPU using [wgpu](https://wgpu.rs)
+//!
+//! Every layer is drawn as a single textured quad:
+//! * Axis-aligned rectangles with a solid color sample a white pixel
+//! * Other outlines (like text) are rasterized on the CPU once and cached in a [TextureAtlas]
+//! * Images are copied into the atlas as-is
+//!
+//! All quads of a frame are batched into a single draw call. If the atlas runs out of
+//! space during a frame, the quads so far are drawn before the atlas is cleared.
+
+mod atlas;
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::{mpsc, Arc},
+};
+
+use error_derive::Error;
+use image::{Rgbaf32, Texture};
+use math::{Color, Rectangle};
+use sl_std::safe_casts::cast_slice;
+use wgpu::util::DeviceExt;
+
+use crate::{Backend, Composition, FlattenedPathPoint, Layer, Source};
+
+use atlas::TextureAtlas;
+
+const ATLAS_SIZE: usize = 2048;
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// `position` (2), `uv` (2), `color` (4)
+type Vertex = [f32; 8];
+
+#[derive(Debug, Error)]
+pub enum GpuError {
+    #[msg = "no suitable graphics adapter found"]
+    NoSuitableAdapter,
+
+    #[msg = "failed to request graphics device"]
+    RequestDevice(wgpu::RequestDeviceError),
+}
+
+/// The render target and the buffer that its contents are copied into
+struct Target {
+    width: usize,
+    height: usize,
+    texture: wgpu::Texture,
+    readback_buffer: wgpu::Buffer,
+}
+
+pub struct GpuBackend {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    viewport_buffer: wgpu::Buffer,
+    atlas_texture: wgpu::Texture,
+    atlas: TextureAtlas,
+
+    /// The textures that were copied into the atlas
+    ///
+    /// Their entries are identified by the address of the texture, so they are
+    /// kept alive until the atlas is cleared.
+    atlas_textures: Vec<Arc<Texture>>,
+    target: Option<Target>,
+}
+
+impl GpuBackend {
+    pub fn new() -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .ok_or(GpuError::NoSuitableAdapter)?;
+
+        log::info!("Using GPU adapter {:?}", adapter.get_info().name);
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("stormlicht"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults(),
+            },
+            None,
+        ))
+        .map_err(GpuError::RequestDevice)?;
+
+        let shader = device.create_shader_module(wgpu::include_wgsl!("shader.wgsl"));
+
+        let atlas = TextureAtlas::new(ATLAS_SIZE, ATLAS_SIZE);
+        let atlas_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("atlas"),
+            size: wgpu::Extent3d {
+                width: ATLAS_SIZE as u32,
+                height: ATLAS_SIZE as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let atlas_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("atlas sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let viewport_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("viewport"),
+            size: 4 * std::mem::size_of::<f32>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("quad bind group layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let atlas_view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("quad bind group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: viewport_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&atlas_sampler),
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("quad pipeline layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("quad pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Vertex>() as u64,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x2,
+                        1 => Float32x2,
+                        2 => Float32x4
+                    ],
+                }],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: TARGET_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            bind_group,
+            viewport_buffer,
+            atlas_texture,
+            atlas,
+            atlas_textures: vec![],
+            target: None,
+        })
+    }
+
+    /// Make sure the render target has the given size
+    fn prepare_target(&mut self, width: usize, height: usize) {
+        if self
+            .target
+            .as_ref()
+            .is_some_and(|target| target.width == width && target.height == height)
+        {
+            return;
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("render target"),
+            size: wgpu::Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback buffer"),
+            size: (padded_bytes_per_row(width) * height) as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        self.target = Some(Target {
+            width,
+            height,
+            texture,
+            readback_buffer,
+        });
+    }
+
+    /// Create the vertices for a single layer
+    ///
+    /// Returns `false` if there is not enough space left in the atlas for the layer,
+    /// in which case no vertices are created.
+    fn push_layer(&mut self, layer: &mut Layer, vertices: &mut Vec<Vertex>) -> bool {
+        let Some(extent) = layer.prepare() else {
+            return true;
+        };
+
+        let pixel_extent = extent.snap_to_grid();
+        let (width, height) = (pixel_extent.width(), pixel_extent.height());
+        if width == 0 || height == 0 {
+            return true;
+        }
+
+        // Layers are only rasterized if they are not in the atlas already
+        let (area, color) = match &layer.source {
+            Source::Solid(color)
+                if is_axis_aligned_rectangle(layer.flattened_outline(), extent) =>
+            {
+                push_quad(vertices, extent, TextureAtlas::WHITE_PIXEL, *color);
+                return true;
+            },
+            Source::Solid(color) => {
+                let key = hash_outline(layer.flattened_outline(), extent);
+                let area = self.atlas.get(key).or_else(|| {
+                    let mask = layer.rasterize(extent);
+                    self.atlas.insert(key, width, height, |x, y| {
+                        [u8::MAX, u8::MAX, u8::MAX, mask.coverage_at(x, y)]
+                    })
+                });
+                (area, *color)
+            },
+            Source::Texture { texture, .. } => {
+                let key = hash_texture(texture, width, height);
+                let area = self.atlas.get(key).or_else(|| {
+                    let resized = texture.resize(width, height);
+                    let area = self
+                        .atlas
+                        .insert(key, width, height, |x, y| to_rgba8(resized.get_pixel(x, y)))?;
+                    self.atlas_textures.push(texture.clone());
+                    Some(area)
+                });
+                (area, Color::WHITE)
+            },
+        };
+
+        let Some(area) = area else {
+            return false;
+        };
+        push_quad(vertices, as_f32(pixel_extent), area, color);
+        true
+    }
+
+    /// Upload the parts of the atlas that changed since the last upload
+    fn upload_atlas(&mut self) {
+        let Some(dirty_area) = self.atlas.dirty_area() else {
+            return;
+        };
+
+        let (width, _) = self.atlas.size();
+        let top_left = dirty_area.top_left();
+        self.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.atlas_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: top_left.x as u32,
+                    y: top_left.y as u32,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            cast_slice(self.atlas.pixels()),
+            wgpu::ImageDataLayout {
+                offset: (4 * (top_left.y * width + top_left.x)) as u64,
+                bytes_per_row: Some(4 * width as u32),
+                rows_per_image: Some(dirty_area.height() as u32),
+            },
+            wgpu::Extent3d {
+                width: dirty_area.width() as u32,
+                height: dirty_area.height() as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.atlas.mark_uploaded();
+    }
+
+    /// Draw the given quads onto the render target, with the current contents of the atlas
+    fn draw_batch(&mut self, vertices: &[Vertex], load: wgpu::LoadOp<wgpu::Color>) {
+        self.upload_atlas();
+
+        let vertex_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("quad vertices"),
+                contents: cast_slice(vertices),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+
+        let render_target = self
+            .target
+            .as_ref()
+            .expect("render target was not prepared");
+        let target_view = render_target
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("quads"),
+            });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("quads"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.draw(0..vertices.len() as u32, 0..1);
+        }
+
+        // Writes to the atlas are performed before the next submission, so the quads must
+        // be submitted before the atlas can change again
+        self.queue.submit(Some(encoder.finish()));
+    }
+}
+
+impl Backend for GpuBackend {
+    fn render(&mut self, composition: &mut Composition, target: &mut Texture) {
+        let _span = trace::span!(Paint, "render on gpu");
+        let (width, height) = (target.width(), target.height());
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        self.prepare_target(width, height);
+
+        let viewport: [f32; 4] = [width as f32, height as f32, 0., 0.];
+        self.queue
+            .write_buffer(&self.viewport_buffer, 0, cast_slice(&viewport));
+
+        let background = composition.background();
+        let mut load = wgpu::LoadOp::Clear(wgpu::Color {
+            r: background.red() as f64,
+            g: background.green() as f64,
+            b: background.blue() as f64,
+            a: background.alpha() as f64,
+        });
+
+        let mut vertices = vec![];
+        for layer in composition.layers_in_paint_order() {
+            if self.push_layer(layer, &mut vertices) {
+                continue;
+            }
+
+            // The atlas is full. The quads so far still sample its current contents,
+            // so they are drawn before the atlas is cleared
+            self.draw_batch(&vertices, load);
+            load = wgpu::LoadOp::Load;
+            vertices.clear();
+            self.atlas.clear();
+            self.atlas_textures.clear();
+
+            if !self.push_layer(layer, &mut vertices) {
+                log::warn!("Layer is too large for the texture atlas, skipping");
+            }
+        }
+        self.draw_batch(&vertices, load);
+
+        let render_target = self
+            .target
+            .as_ref()
+            .expect("render target was created above");
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("readback"),
+            });
+
+        let bytes_per_row = padded_bytes_per_row(width);
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &render_target.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &render_target.readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row as u32),
+                    rows_per_image: Some(height as u32),
+                },
+            },
+            wgpu::Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue.submit(Some(encoder.finish()));
+
+        // Copy the rendered frame back into the target texture
+        let buffer_slice = render_target.readback_buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        match receiver.recv() {
+            Ok(Ok(())) => {
+                let data = buffer_slice.get_mapped_range();
+                for (y, row) in data.chunks_exact(bytes_per_row).enumerate() {
+                    for (x, pixel) in row[..4 * width].chunks_exact(4).enumerate() {
+                        let color = Rgbaf32::rgba(
+                            pixel[0] as f32 / 255.,
+                            pixel[1] as f32 / 255.,
+                            pixel[2] as f32 / 255.,
+                            pixel[3] as f32 / 255.,
+                        );
+                        target.set_pixel(x, y, color);
+                    }
+                }
+                drop(data);
+                render_target.readback_buffer.unmap();
+            },
+            _ => log::error!("Failed to read back rendered frame from the GPU"),
+        }
+
+        // The whole texture is redrawn every frame, so there is no damage left
+        composition.mark_rendered();
+    }
+
+    fn name(&self) -> &'static str {
+        "gpu"
+    }
+}
+
+/// Rows in buffers that textures are copied into must be aligned
+fn padded_bytes_per_row(width: usize) -> usize {
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+    (4 * width).div_ceil(alignment) * alignment
+}
+
+fn push_quad(
+    vertices: &mut Vec<Vertex>,
+    area: Rectangle,
+    atlas_area: Rectangle<usize>,
+    color: Color,
+) {
+    let atlas_size = ATLAS_SIZE as f32;
+    let uv_top_left = atlas_area.top_left().map(|value| value as f32 / atlas_size);
+    let uv_bottom_right = atlas_area
+        .bottom_right()
+        .map(|value| value as f32 / atlas_size);
+
+    let color = [
+        color.red() as f32 / 255.,
+        color.green() as f32 / 255.,
+        color.blue() as f32 / 255.,
+        1.,
+    ];
+
+    let vertex = |x: f32, y: f32, u: f32, v: f32| -> Vertex {
+        [x, y, u, v, color[0], color[1], color[2], color[3]]
+    };
+
+    let top_left = vertex(
+        area.top_left().x,
+        area.top_left().y,
+        uv_top_left.x,
+        uv_top_left.y,
+    );
+    let top_right = vertex(
+        area.bottom_right().x,
+        area.top_left().y,
+        uv_bottom_right.x,
+        uv_top_left.y,
+    );
+    let bottom_left = vertex(
+        area.top_left().x,
+        area.bottom_right().y,
+        uv_top_left.x,
+        uv_bottom_right.y,
+    );
+    let bottom_right = vertex(
+        area.bottom_right().x,
+        area.bottom_right().y,
+        uv_bottom_right.x,
+        uv_bottom_right.y,
+    );
+
+    vertices.extend_from_slice(&[
+        top_left,
+        top_right,
+        bottom_left,
+        bottom_left,
+        top_right,
+        bottom_right,
+    ]);
+}
+
+/// Returns `true` if every point of the outline is a corner of its extent,
+/// in which case the outline can be drawn without rasterizing it first
+fn is_axis_aligned_rectangle(outline: &[FlattenedPathPoint], extent: Rectangle) -> bool {
+    outline.len() <= 5
+        && outline.iter().all(|point| {
+            let is_on_x_edge = point.coordinates.x == extent.top_left().x
+                || point.coordinates.x == extent.bottom_right().x;
+            let is_on_y_edge = point.coordinates.y == extent.top_left().y
+                || point.coordinates.y == extent.bottom_right().y;
+            is_on_x_edge && is_on_y_edge
+        })
+}
+
+/// Hash an outline relative to its extent, so that identical shapes at
+/// different positions share an atlas entry if they are aligned the same
+/// way relative to the pixel grid
+fn hash_outline(outline: &[FlattenedPathPoint], extent: Rectangle) -> u64 {
+    let origin = extent.snap_to_grid().top_left().map(|value| value as f32);
+
+    let mut hasher = DefaultHasher::new();
+    "outline".hash(&mut hasher);
+    for point in outline {
+        let relative = point.coordinates - origin;
+        relative.x.to_bits().hash(&mut hasher);
+        relative.y.to_bits().hash(&mut hasher);
+        point.connected.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Hash a texture along with the size that it is drawn at, so the texture only needs
+/// to be resized when it is not in the atlas yet
+///
+/// Textures are identified by their address. Shared textures are never modified,
+/// changing them creates a copy at a different address.
+fn hash_texture(texture: &Arc<Texture>, width: usize, height: usize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    "texture".hash(&mut hasher);
+    (width, height).hash(&mut hasher);
+    Arc::as_ptr(texture).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn to_rgba8(color: Rgbaf32) -> [u8; 4] {
+    [color.red(), color.green(), color.blue(), color.alpha()]
+        .map(|channel| (channel.clamp(0., 1.) * 255.).round() as u8)
+}
+
+fn as_f32(rectangle: Rectangle<usize>) -> Rectangle {
+    Rectangle::from_corners(
+        rectangle.top_left().map(|value| value as f32),
+        rectangle.bottom_right().map(|value| value as f32),
+    )
+}
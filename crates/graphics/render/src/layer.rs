@@ -1,7 +1,10 @@
-use image::{AccessMode, Rgbaf32, Texture};
+use std::sync::Arc;
+
+use font::path::PathConsumer;
+use image::{AccessMode, Texture};
 use math::{AffineTransform, Angle, Color, Rectangle, Vec2D};
 
-use crate::{FlattenedPathPoint, Mask, Path, Rasterizer};
+use crate::{Antialiasing, FlattenedPathPoint, Mask, Path, Rasterizer, Stroke};
 
 #[derive(Clone, Debug)]
 pub enum Source {
@@ -9,7 +12,7 @@ pub enum Source {
     Solid(Color),
 
     Texture {
-        texture: Texture,
+        texture: Arc<Texture>,
         access_mode: AccessMode,
     },
 }
@@ -25,6 +28,12 @@ pub struct Layer {
     pub outline: Path,
     pub source: Source,
 
+    /// If set, the outline is stroked instead of filled
+    pub stroke: Option<Stroke>,
+
+    /// How the edges of the outline are smoothed
+    pub antialiasing: Antialiasing,
+
     /// A common transformation applied to all elements in the layer
     transform: AffineTransform,
 
@@ -64,6 +73,67 @@ impl Layer {
         self
     }
 
+    /// Draw vertical text to the layer. This replaces any existing paths within
+    /// the layer
+    ///
+    /// The text runs from top to bottom within a column of width `column_width` whose
+    /// top left corner is at `offset`. Characters for which `is_upright` returns `true`
+    /// are drawn upright, all others are rotated by 90 degrees clockwise.
+    ///
+    /// Vertical font metrics are not supported, so every character advances by the
+    /// width it would take up in horizontal text.
+    pub fn vertical_text<F>(
+        &mut self,
+        text: &str,
+        fontface: font::Font,
+        font_size: f32,
+        offset: Vec2D,
+        column_width: f32,
+        is_upright: F,
+    ) -> &mut Self
+    where
+        F: Fn(char) -> bool,
+    {
+        self.outline = Path::new(Vec2D::new(0., 0.));
+
+        let mut advance = 0.;
+        let mut remaining_text = text;
+        while let Some(first_char) = remaining_text.chars().next() {
+            // Split off a segment of characters that share the same orientation
+            let upright = is_upright(first_char);
+            let segment_length = remaining_text
+                .find(|c| is_upright(c) != upright)
+                .unwrap_or(remaining_text.len());
+            let (segment, rest) = remaining_text.split_at(segment_length);
+            remaining_text = rest;
+
+            if upright {
+                for (index, c) in segment.char_indices() {
+                    let glyph = &segment[index..index + c.len_utf8()];
+                    let width = fontface.compute_rendered_width(glyph, font_size);
+                    let position = offset + Vec2D::new((column_width - width) / 2., advance);
+                    fontface.render(glyph, &mut self.outline, font_size, position);
+                    advance += width;
+                }
+            } else {
+                // Rotate the segment so that the top of the glyphs faces the
+                // right edge of the column
+                let transform = AffineTransform::from_matrix([
+                    [0., -1., offset.x + column_width],
+                    [1., 0., offset.y + advance],
+                ]);
+                let mut rotated_outline = TransformedPath {
+                    path: &mut self.outline,
+                    transform,
+                };
+                fontface.render(segment, &mut rotated_outline, font_size, Vec2D::new(0., 0.));
+                advance += fontface.compute_rendered_width(segment, font_size);
+            }
+        }
+
+        self
+    }
+
     /// Set the color source of the elements within the [Layer]
     #[inline]
     pub fn with_source(&mut self, source: Source) -> &mut Self {
@@ -78,6 +148,21 @@ impl Layer {
         self
     }
 
+    /// Stroke the outline of the layer instead of filling it
+    #[inline]
+    pub fn with_stroke(&mut self, stroke: Stroke) -> &mut Self {
+        self.stroke = Some(stroke);
+        self.needs_flattening = true;
+        self
+    }
+
+    /// Set how the edges of the outline are smoothed
+    #[inline]
+    pub fn with_antialiasing(&mut self, antialiasing: Antialiasing) -> &mut Self {
+        self.antialiasing = antialiasing;
+        self
+    }
+
     /// Rotate the layer by a fixed angle
     ///
     /// This operation does not cause the Bézier curves to be re-flattened
@@ -119,7 +204,12 @@ impl Layer {
         if self.needs_flattening {
             self.flattened_outline.clear();
             self.outline
-                .flatten(FLATTEN_TOLERANCE, &mut self.flattened_outline)
+                .flatten(FLATTEN_TOLERANCE, &mut self.flattened_outline);
+
+            if let Some(stroke) = &self.stroke {
+                let center_line = std::mem::take(&mut self.flattened_outline);
+                stroke.outline(&center_line, &mut self.flattened_outline);
+            }
         }
     }
 
@@ -130,51 +220,44 @@ impl Layer {
             .for_each(|p| p.coordinates = self.transform.apply_to(p.coordinates));
 
         // Compute extents of the transformed outline
-        self.flattened_outline
-            .iter()
-            .map(|p| p.coordinates)
-            .fold(None, |extent, point| {
-                extent
-                    .map(|extent| {
-                        let top_left = extent.top_left();
-                        let bottom_right = extent.bottom_right();
-
-                        Rectangle::from_corners(
-                            Vec2D::new(
-                                f32::min(top_left.x, point.x),
-                                f32::min(top_left.y, point.y),
-                            ),
-                            Vec2D::new(
-                                f32::max(bottom_right.x, point.x),
-                                f32::max(bottom_right.y, point.y),
-                            ),
-                        )
-                    })
-                    .or(Some(Rectangle::from_corners(point, point)))
-            })
-    }
-
-    pub(crate) fn render_to(&mut self, texture: &mut Texture) {
-        self.flatten_if_necessary();
-
-        if let Some(outline_extent) = self.apply_transform() {
-            // Compute a mask for the layer.
-            // This mask determines which pixels in the bitmap should be
-            // colored and which should not be.
-            let outline_offset = outline_extent.top_left();
-            let outline_extent = outline_extent.snap_to_grid();
+        let (first, rest) = self.flattened_outline.split_first()?;
+        let mut extent = Rectangle::from_corners(first.coordinates, first.coordinates);
+        for point in rest {
+            extent.grow_to_contain_point(point.coordinates);
+        }
+        Some(extent)
+    }
 
-            let mut rasterizer = Rasterizer::new(outline_extent, outline_offset);
-            rasterizer.fill(&self.flattened_outline);
-            let mask = rasterizer.into_mask();
+    /// Flatten and transform the outline of the layer
+    ///
+    /// Returns the area covered by the transformed outline, or `None` if the layer
+    /// has no outline at all.
+    pub(crate) fn prepare(&mut self) -> Option<Rectangle> {
+        self.flatten_if_necessary();
+        self.apply_transform()
+    }
 
-            let resized_source = self
-                .source
-                .resize(outline_extent.width(), outline_extent.height());
+    /// The outline of the layer, as computed by [Layer::prepare]
+    #[inline]
+    #[must_use]
+    pub(crate) fn flattened_outline(&self) -> &[FlattenedPathPoint] {
+        &self.flattened_outline
+    }
 
-            // Compose the mask onto the buffer
-            compose(texture, mask, &resized_source, outline_extent.top_left());
-        }
+    /// Compute a mask for the (prepared) layer.
+    ///
+    /// This mask determines which pixels in the bitmap should be
+    /// colored and which should not be.
+    #[must_use]
+    pub(crate) fn rasterize(&self, outline_extent: Rectangle) -> Mask {
+        let pixel_extent = outline_extent.snap_to_grid();
+        let mut rasterizer = Rasterizer::new(
+            pixel_extent,
+            pixel_extent.top_left().map(|value| value as f32),
+        )
+        .with_antialiasing(self.antialiasing);
+        rasterizer.fill(&self.flattened_outline);
+        rasterizer.into_mask()
     }
 }
 
@@ -183,6 +266,8 @@ impl Default for Layer {
         Self {
             outline: Path::empty(),
             source: Source::default(),
+            stroke: None,
+            antialiasing: Antialiasing::default(),
             transform: AffineTransform::identity(),
             is_enabled: true,
             needs_flattening: true,
@@ -192,64 +277,39 @@ impl Default for Layer {
 }
 
 impl Source {
-    fn resize(&self, width: usize, height: usize) -> Self {
+    pub(crate) fn resize(&self, width: usize, height: usize) -> Self {
         match self {
             Self::Solid(color) => Self::Solid(*color),
             Self::Texture {
                 texture,
                 access_mode,
             } => Self::Texture {
-                texture: texture.resize(width, height),
+                texture: Arc::new(texture.resize(width, height)),
                 access_mode: *access_mode,
             },
         }
     }
 }
-fn compose(destination: &mut Texture, mask: Mask, source: &Source, offset: Vec2D<usize>) {
-    if offset.x < destination.width() && offset.y < destination.height() {
-        // Don't draw out of bounds
-        let available_space = Vec2D::new(
-            destination.width() - offset.x,
-            destination.height() - offset.y,
+/// Applies a transformation to all points before adding them to a [Path]
+struct TransformedPath<'a> {
+    path: &'a mut Path,
+    transform: AffineTransform,
+}
+
+impl PathConsumer for TransformedPath<'_> {
+    fn move_to(&mut self, p: Vec2D) {
+        PathConsumer::move_to(self.path, self.transform.apply_to(p));
+    }
+
+    fn line_to(&mut self, p: Vec2D) {
+        PathConsumer::line_to(self.path, self.transform.apply_to(p));
+    }
+
+    fn quad_bez_to(&mut self, p1: Vec2D, p2: Vec2D) {
+        PathConsumer::quad_bez_to(
+            self.path,
+            self.transform.apply_to(p1),
+            self.transform.apply_to(p2),
         );
-        match source {
-            Source::Solid(color) => {
-                for x in 0..mask.width().min(available_space.x) {
-                    for y in 0..mask.height().min(available_space.y) {
-                        let opacity = mask.opacity_at(x, y).abs().min(1.);
-
-                        let color = Rgbaf32::rgba(
-                            color.red() as f32 / 255.,
-                            color.green() as f32 / 255.,
-                            color.blue() as f32 / 255.,
-                            opacity,
-                        );
-
-                        let previous_color = destination.get_pixel(x + offset.x, y + offset.y);
-                        let computed_color = previous_color.blend(color);
-                        destination.set_pixel(x + offset.x, y + offset.y, computed_color);
-                    }
-                }
-            },
-            Source::Texture {
-                texture,
-                access_mode,
-            } => {
-                for x in 0..mask.width().min(available_space.x) {
-                    for y in 0..mask.height().min(available_space.y) {
-                        let opacity = mask.opacity_at(x, y).abs().min(1.);
-                        let mut texture_pixel = texture.get(x, y, *access_mode);
-
-                        // Adjust the alpha value of the texture according to the mask
-                        let texture_alpha = texture_pixel.alpha();
-                        texture_pixel.set_alpha(texture_alpha * opacity);
-
-                        let previous_color = destination.get_pixel(x + offset.x, y + offset.y);
-                        let computed_color = previous_color.blend(texture_pixel);
-                        destination.set_pixel(x + offset.x, y + offset.y, computed_color);
-                    }
-                }
-            },
-        }
     }
 }
@@ -0,0 +1,240 @@
+//! A tree of [Layers](Layer) that can be composited independently of whoever built it
+//!
+//! Nodes can be scrolled, which moves their own layers and those of their children.
+//! Changing the scroll offset of a node does not require the tree to be rebuilt, which
+//! allows a [Compositor](crate::Compositor) to scroll without waiting for layout.
+
+use math::{Rectangle, Vec2D};
+
+use crate::{Composition, Layer};
+
+/// A node whose contents can be scrolled
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrollFrame {
+    /// The top left corner of the visible area within the contents of the node
+    pub offset: Vec2D,
+
+    /// The size of the area that can be scrolled within
+    pub content_size: Vec2D,
+
+    /// The size of the visible area
+    pub viewport_size: Vec2D,
+}
+
+impl ScrollFrame {
+    /// Keep an offset within the contents of the frame
+    ///
+    /// Frames whose contents are smaller than the visible area cannot be scrolled at all.
+    #[must_use]
+    pub fn clamp(&self, offset: Vec2D) -> Vec2D {
+        let max_x = (self.content_size.x - self.viewport_size.x).max(0.);
+        let max_y = (self.content_size.y - self.viewport_size.y).max(0.);
+
+        Vec2D::new(offset.x.clamp(0., max_x), offset.y.clamp(0., max_y))
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LayerNode {
+    /// Drawn in order, below the layers of all children
+    pub layers: Vec<Layer>,
+
+    /// Children are drawn in order, on top of each other
+    pub children: Vec<LayerNode>,
+
+    /// If set, the layers and children of the node are moved by the scroll offset
+    pub scroll_frame: Option<ScrollFrame>,
+
+    /// Areas (in the coordinate space of the node) that changed since the previous tree was composited
+    damage: Vec<Rectangle>,
+
+    /// Whether everything changed since the previous tree was composited
+    is_fully_damaged: bool,
+}
+
+impl LayerNode {
+    /// Create a node whose contents can be scrolled
+    #[must_use]
+    pub fn scrolling(scroll_frame: ScrollFrame) -> Self {
+        Self {
+            scroll_frame: Some(scroll_frame),
+            ..Self::default()
+        }
+    }
+
+    /// Append a new layer that is drawn on top of all previous layers of this node
+    pub fn push_layer(&mut self) -> &mut Layer {
+        self.layers.push(Layer::default());
+        self.layers.last_mut().expect("a layer was just added")
+    }
+
+    /// Mark an area (in the coordinate space of the node) as needing to be redrawn
+    pub fn invalidate(&mut self, area: Rectangle) {
+        self.damage.push(area);
+    }
+
+    /// Mark the whole node as needing to be redrawn
+    pub fn invalidate_all(&mut self) {
+        self.is_fully_damaged = true;
+    }
+
+    /// The offset of the contents of this node, relative to its parent
+    fn offset(&self) -> Vec2D {
+        self.scroll_frame
+            .map_or(Vec2D::ORIGIN, |scroll_frame| scroll_frame.offset)
+    }
+
+    fn first_scroll_frame(&self) -> Option<&ScrollFrame> {
+        self.scroll_frame
+            .as_ref()
+            .or_else(|| self.children.iter().find_map(LayerNode::first_scroll_frame))
+    }
+
+    fn first_scroll_frame_mut(&mut self) -> Option<&mut ScrollFrame> {
+        if self.scroll_frame.is_some() {
+            return self.scroll_frame.as_mut();
+        }
+
+        self.children
+            .iter_mut()
+            .find_map(LayerNode::first_scroll_frame_mut)
+    }
+
+    /// Add all layers of this node and its children to the composition, starting at `next_index`
+    ///
+    /// Returns `true` if the node or one of its children is fully damaged.
+    fn composite(
+        &self,
+        composition: &mut Composition,
+        parent_offset: Vec2D,
+        next_index: &mut u16,
+    ) -> bool {
+        let offset = parent_offset + self.offset();
+
+        for layer in &self.layers {
+            let mut layer = layer.clone();
+            layer.translate(Vec2D::ORIGIN - offset);
+            composition.insert_layer(*next_index, layer);
+            *next_index += 1;
+        }
+
+        for area in &self.damage {
+            composition.invalidate(area.offset_by(Vec2D::ORIGIN - offset));
+        }
+
+        let mut is_fully_damaged = self.is_fully_damaged;
+        for child in &self.children {
+            is_fully_damaged |= child.composite(composition, offset, next_index);
+        }
+        is_fully_damaged
+    }
+}
+
+/// The result of painting a page, ready to be composited
+#[derive(Clone, Debug)]
+pub struct LayerTree {
+    pub root: LayerNode,
+
+    /// The scale factor between the coordinates of the layers and pixels
+    pub dpi: (f32, f32),
+}
+
+impl Default for LayerTree {
+    fn default() -> Self {
+        Self {
+            root: LayerNode::default(),
+            dpi: (1., 1.),
+        }
+    }
+}
+
+impl LayerTree {
+    /// The scroll frame that is moved by scrolling the whole tree, which is the first one in tree order
+    #[must_use]
+    pub fn root_scroll_frame_mut(&mut self) -> Option<&mut ScrollFrame> {
+        self.root.first_scroll_frame_mut()
+    }
+
+    /// The scroll offset of [LayerTree::root_scroll_frame_mut], if there is a scroll frame
+    #[must_use]
+    pub fn scroll_offset(&self) -> Option<Vec2D> {
+        self.root
+            .first_scroll_frame()
+            .map(|scroll_frame| scroll_frame.offset)
+    }
+
+    /// Replace the layers of the composition with the layers of the tree and invalidate
+    /// everything that was damaged since the previous tree
+    ///
+    /// Layers of scrolled nodes are moved by the current scroll offset.
+    /// Scroll frames do not clip their contents.
+    pub fn composite(&self, composition: &mut Composition) {
+        composition.set_dpi(self.dpi);
+        composition.set_scroll_offset(self.scroll_offset().unwrap_or(Vec2D::ORIGIN));
+        composition.clear();
+
+        let mut next_index = 0;
+        let is_fully_damaged = self
+            .root
+            .composite(composition, Vec2D::ORIGIN, &mut next_index);
+
+        if is_fully_damaged {
+            composition.invalidate_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scroll_frame(offset: Vec2D) -> ScrollFrame {
+        ScrollFrame {
+            offset,
+            content_size: Vec2D::new(100., 1000.),
+            viewport_size: Vec2D::new(100., 100.),
+        }
+    }
+
+    #[test]
+    fn clamp_scroll_offset() {
+        let frame = scroll_frame(Vec2D::ORIGIN);
+        assert_eq!(frame.clamp(Vec2D::new(20., -5.)), Vec2D::ORIGIN);
+        assert_eq!(frame.clamp(Vec2D::new(0., 5000.)), Vec2D::new(0., 900.));
+    }
+
+    #[test]
+    fn find_root_scroll_frame() {
+        let mut tree = LayerTree::default();
+        assert!(tree.scroll_offset().is_none());
+
+        let mut child = LayerNode::default();
+        child
+            .children
+            .push(LayerNode::scrolling(scroll_frame(Vec2D::new(0., 10.))));
+        child
+            .children
+            .push(LayerNode::scrolling(scroll_frame(Vec2D::new(0., 20.))));
+        tree.root.children.push(child);
+
+        assert_eq!(tree.scroll_offset(), Some(Vec2D::new(0., 10.)));
+    }
+
+    #[test]
+    fn composite_layers_in_tree_order() {
+        let mut tree = LayerTree::default();
+        tree.root.push_layer();
+
+        let mut scrolled = LayerNode::scrolling(scroll_frame(Vec2D::new(0., 50.)));
+        scrolled.push_layer();
+        scrolled.push_layer();
+        tree.root.children.push(scrolled);
+
+        let mut composition = Composition::default();
+        tree.composite(&mut composition);
+
+        let mut indices: Vec<u16> = composition.layers().map(|(index, _)| *index).collect();
+        indices.sort();
+        assert_eq!(indices, [0, 1, 2]);
+    }
+}
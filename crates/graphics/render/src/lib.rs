@@ -14,12 +14,28 @@
 #![feature(array_windows)]
 #![feature(portable_simd)]
 
+mod backend;
 mod composition;
+mod compositor;
+mod damage;
+#[cfg(feature = "gpu")]
+mod gpu;
 mod layer;
+mod layer_tree;
 mod path;
+mod pdf;
 mod rasterizer;
+mod stroke;
+mod tiles;
+mod workers;
 
+pub use backend::{create_backend, Backend, SoftwareBackend};
 pub use composition::Composition;
+pub use compositor::{Compositor, Frame};
+pub use damage::{Damage, TILE_SIZE};
 pub use layer::{Layer, Source};
+pub use layer_tree::{LayerNode, LayerTree, ScrollFrame};
 pub use path::{FlattenedPathPoint, Path};
-pub use rasterizer::{Mask, Rasterizer};
+pub use pdf::PdfDocument;
+pub use rasterizer::{Antialiasing, Mask, Rasterizer};
+pub use stroke::{LineCap, LineJoin, Stroke};
@@ -133,6 +133,14 @@ impl Path {
 
         let mut current_point = self.start;
 
+        if self
+            .commands
+            .first()
+            .is_some_and(|command| !matches!(command, PathCommand::Move(_)))
+        {
+            flattened_path.push(FlattenedPathPoint::new(self.start, false));
+        }
+
         for &command in &self.commands {
             match command {
                 PathCommand::Move(point) => {
@@ -0,0 +1,333 @@
+//! Writes [LayerNodes](LayerNode) to PDF documents
+//!
+//! Every layer becomes a filled path, painted either in a solid color or with its texture,
+//! which is embedded as an image. Text is drawn as outlines too, so it cannot be selected
+//! in the resulting document.
+//!
+//! Coordinates of the layers are CSS pixels (`1/96in`), which are converted to PDF points (`1/72in`).
+//!
+//! <https://opensource.adobe.com/dc-acrobat-sdk-docs/pdfstandards/PDF32000_2008.pdf>
+
+use std::fmt::{self, Write};
+
+use image::Texture;
+use math::Vec2D;
+
+use crate::{Layer, LayerNode, Source};
+
+/// The number of PDF points per CSS pixel
+const POINTS_PER_PIXEL: f32 = 72. / 96.;
+
+/// The object that describes the document as a whole, see section 7.7.2
+const CATALOG: ObjectId = ObjectId(1);
+
+/// The object that lists all pages, see section 7.7.3.2
+const PAGE_TREE: ObjectId = ObjectId(2);
+
+/// Refers to an indirect object within a [PdfDocument]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ObjectId(usize);
+
+/// A PDF document that is built one page at a time
+#[derive(Clone, Debug)]
+pub struct PdfDocument {
+    /// The serialized objects of the document, the object with id `n` is at index `n - 1`
+    objects: Vec<Vec<u8>>,
+    pages: Vec<ObjectId>,
+}
+
+/// The content stream of a page that is being written, together with the images it refers to
+#[derive(Debug, Default)]
+struct PageWriter {
+    content: String,
+    images: Vec<ObjectId>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        // The catalog and the page tree are written once all pages are known
+        Self {
+            objects: vec![vec![], vec![]],
+            pages: vec![],
+        }
+    }
+}
+
+impl PdfDocument {
+    /// The number of pages that were added so far
+    #[must_use]
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Append a page of the given size (in CSS pixels) that shows `contents`
+    ///
+    /// Unlike during composition, the contents of scroll frames are clipped to their viewport,
+    /// whose top left corner is at the origin of the parent node.
+    pub fn add_page(&mut self, size: Vec2D, contents: &LayerNode) {
+        let mut page = PageWriter::default();
+
+        // PDF places the origin at the bottom left corner of the page, with the y axis pointing up
+        writeln!(
+            page.content,
+            "{} 0 0 {} 0 {} cm",
+            Number(POINTS_PER_PIXEL),
+            Number(-POINTS_PER_PIXEL),
+            Number(size.y * POINTS_PER_PIXEL)
+        )
+        .expect("Writing to a string cannot fail");
+        page.write_node(self, contents);
+
+        let content = self.add_stream("", page.content.as_bytes());
+
+        let mut images = String::new();
+        for (index, image) in page.images.iter().enumerate() {
+            write!(images, "/Im{index} {image} ").expect("Writing to a string cannot fail");
+        }
+
+        let page = format!(
+            "<< /Type /Page /Parent {PAGE_TREE} /MediaBox [0 0 {} {}] /Resources << /XObject << {images}>> >> /Contents {content} >>",
+            Number(size.x * POINTS_PER_PIXEL),
+            Number(size.y * POINTS_PER_PIXEL),
+        );
+        let page = self.add_object(page.into_bytes());
+        self.pages.push(page);
+    }
+
+    /// Serialize the document
+    #[must_use]
+    pub fn finish(mut self) -> Vec<u8> {
+        let kids: Vec<String> = self.pages.iter().map(ObjectId::to_string).collect();
+        self.objects[PAGE_TREE.0 - 1] = format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            self.pages.len()
+        )
+        .into_bytes();
+        self.objects[CATALOG.0 - 1] =
+            format!("<< /Type /Catalog /Pages {PAGE_TREE} >>").into_bytes();
+
+        // The comment with non-ASCII bytes marks the file as binary, see section 7.5.2
+        let mut output = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (index, object) in self.objects.iter().enumerate() {
+            offsets.push(output.len());
+            output.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            output.extend_from_slice(object);
+            output.extend_from_slice(b"\nendobj\n");
+        }
+
+        // Every entry of the cross-reference table is exactly 20 bytes long, see section 7.5.4
+        let cross_reference_table_offset = output.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", self.objects.len() + 1);
+        for offset in offsets {
+            writeln!(trailer, "{offset:010} 00000 n ").expect("Writing to a string cannot fail");
+        }
+        write!(
+            trailer,
+            "trailer\n<< /Size {} /Root {CATALOG} >>\nstartxref\n{cross_reference_table_offset}\n%%EOF\n",
+            self.objects.len() + 1
+        )
+        .expect("Writing to a string cannot fail");
+        output.extend_from_slice(trailer.as_bytes());
+
+        output
+    }
+
+    fn add_object(&mut self, object: Vec<u8>) -> ObjectId {
+        self.objects.push(object);
+        ObjectId(self.objects.len())
+    }
+
+    fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> ObjectId {
+        let mut object =
+            format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+        object.extend_from_slice(data);
+        object.extend_from_slice(b"\nendstream");
+        self.add_object(object)
+    }
+
+    /// Embed a texture as an image, with its alpha channel as a soft mask
+    fn add_image(&mut self, texture: &Texture) -> ObjectId {
+        let to_byte = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+
+        let mut colors = Vec::with_capacity(texture.data().len() * 3);
+        let mut alpha = Vec::with_capacity(texture.data().len());
+        for pixel in texture.data() {
+            colors.extend([
+                to_byte(pixel.red()),
+                to_byte(pixel.green()),
+                to_byte(pixel.blue()),
+            ]);
+            alpha.push(to_byte(pixel.alpha()));
+        }
+
+        let size = format!("/Width {} /Height {}", texture.width(), texture.height());
+        let mask = self.add_stream(
+            &format!(
+                "/Type /XObject /Subtype /Image {size} /ColorSpace /DeviceGray /BitsPerComponent 8"
+            ),
+            &alpha,
+        );
+        self.add_stream(
+            &format!("/Type /XObject /Subtype /Image {size} /ColorSpace /DeviceRGB /BitsPerComponent 8 /SMask {mask}"),
+            &colors,
+        )
+    }
+}
+
+impl PageWriter {
+    fn write_node(&mut self, document: &mut PdfDocument, node: &LayerNode) {
+        self.content.push_str("q\n");
+
+        if let Some(scroll_frame) = node.scroll_frame {
+            writeln!(
+                self.content,
+                "0 0 {} {} re W n\n1 0 0 1 {} {} cm",
+                Number(scroll_frame.viewport_size.x),
+                Number(scroll_frame.viewport_size.y),
+                Number(-scroll_frame.offset.x),
+                Number(-scroll_frame.offset.y)
+            )
+            .expect("Writing to a string cannot fail");
+        }
+
+        for layer in node.layers.iter().filter(|layer| layer.is_enabled) {
+            self.write_layer(document, layer);
+        }
+
+        for child in &node.children {
+            self.write_node(document, child);
+        }
+
+        self.content.push_str("Q\n");
+    }
+
+    fn write_layer(&mut self, document: &mut PdfDocument, layer: &Layer) {
+        // Preparing the layer transforms its outline in place, so the original layer is left untouched
+        let mut layer = layer.clone();
+        let Some(extent) = layer.prepare() else {
+            return;
+        };
+
+        let mut path = String::new();
+        for point in layer.flattened_outline() {
+            let operator = if point.connected { 'l' } else { 'm' };
+            writeln!(
+                path,
+                "{} {} {operator}",
+                Number(point.coordinates.x),
+                Number(point.coordinates.y)
+            )
+            .expect("Writing to a string cannot fail");
+        }
+
+        match &layer.source {
+            Source::Solid(color) => {
+                writeln!(
+                    self.content,
+                    "{} {} {} rg\n{path}f",
+                    Number(f32::from(color.red()) / 255.),
+                    Number(f32::from(color.green()) / 255.),
+                    Number(f32::from(color.blue()) / 255.),
+                )
+                .expect("Writing to a string cannot fail");
+            },
+            Source::Texture { texture, .. } => {
+                let image = document.add_image(texture);
+                let index = self.images.len();
+                self.images.push(image);
+
+                // Images fill the unit square, with their first row at the top. The texture
+                // is stretched across the extent of the outline, like during rasterization.
+                let top_left = extent.top_left();
+                writeln!(
+                    self.content,
+                    "q\n{path}W n\n{} 0 0 {} {} {} cm\n/Im{index} Do\nQ",
+                    Number(extent.width()),
+                    Number(-extent.height()),
+                    Number(top_left.x),
+                    Number(top_left.y + extent.height()),
+                )
+                .expect("Writing to a string cannot fail");
+            },
+        }
+    }
+}
+
+impl fmt::Display for ObjectId {
+    /// Formats a reference to the object
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 0 R", self.0)
+    }
+}
+
+/// Formats a number in a way that PDF readers understand, which excludes exponents
+struct Number(f32);
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = if self.0.is_finite() { self.0 } else { 0. };
+        let formatted = format!("{value:.3}");
+        let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+
+        match formatted {
+            "-0" | "" => f.write_str("0"),
+            _ => f.write_str(formatted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math::Color;
+
+    use super::*;
+    use crate::Path;
+
+    #[test]
+    fn format_numbers() {
+        assert_eq!(Number(1.).to_string(), "1");
+        assert_eq!(Number(0.75).to_string(), "0.75");
+        assert_eq!(Number(-0.0001).to_string(), "0");
+        assert_eq!(Number(1e-10).to_string(), "0");
+        assert_eq!(Number(12345678.).to_string(), "12345678");
+    }
+
+    #[test]
+    fn write_document() {
+        let mut contents = LayerNode::default();
+        contents
+            .push_layer()
+            .with_outline(Path::rect(Vec2D::new(10., 10.), Vec2D::new(50., 50.)))
+            .with_source(Source::Solid(Color::RED));
+
+        let mut document = PdfDocument::default();
+        document.add_page(Vec2D::new(96., 192.), &contents);
+        document.add_page(Vec2D::new(96., 192.), &LayerNode::default());
+        assert_eq!(document.page_count(), 2);
+
+        let output = document.finish();
+        let text = String::from_utf8_lossy(&output);
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/MediaBox [0 0 72 144]"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("1 0 0 rg"));
+
+        // Every entry of the cross-reference table points at the start of its object
+        let table_offset: usize = text
+            .lines()
+            .rev()
+            .nth(1)
+            .and_then(|line| line.parse().ok())
+            .expect("startxref should be followed by an offset");
+        let table = std::str::from_utf8(&output[table_offset..]).unwrap();
+        let entries = table.lines().skip(3);
+        for (index, entry) in entries.take_while(|line| line.ends_with(" n ")).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(output[offset..].starts_with(format!("{} 0 obj\n", index + 1).as_bytes()));
+        }
+    }
+}
@@ -1,12 +1,105 @@
+//! Anti-aliased scanline rasterization of flattened paths
+//!
+//! The rasterizer keeps an *active edge table*: edges are sorted by their
+//! top coordinate once, and while sweeping over the scanlines from top to bottom,
+//! edges are moved into (and out of) the set of active edges.
+//! Only the active edges contribute to a scanline, which keeps the cost proportional
+//! to the complexity of the shape instead of the size of the mask.
+//!
+//! Within a scanline, the exact area covered by each edge is accumulated, similar to
+//! [font-rs](https://github.com/raphlinus/font-rs/blob/master/src/raster.rs).
+//! The resulting coverage is quantized to 256 levels.
+//!
+//! With [Antialiasing::Lcd], the path is rasterized at three times the horizontal resolution,
+//! one sample for each of the red, green and blue subpixels of a pixel. The coverage of
+//! neighbouring subpixels is then blended, to keep color fringes at the edges low.
+
 use crate::FlattenedPathPoint;
 use math::{Rectangle, Vec2D};
 
+/// How the edges of shapes are smoothed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Antialiasing {
+    /// A single coverage value for every pixel
+    #[default]
+    Grayscale,
+
+    /// A coverage value for each of the red, green and blue subpixels of every pixel
+    ///
+    /// This assumes that the subpixels of the screen are arranged horizontally,
+    /// in `RGB` order.
+    Lcd,
+}
+
+impl Antialiasing {
+    /// The number of coverage values per pixel
+    #[inline]
+    #[must_use]
+    pub const fn subpixels(&self) -> usize {
+        match self {
+            Self::Grayscale => 1,
+            Self::Lcd => 3,
+        }
+    }
+}
+
+/// The weights of the filter that spreads the coverage of a subpixel to its neighbours
+///
+/// The weights sum up to `9`.
+const LCD_FILTER: [u16; 5] = [1, 2, 3, 2, 1];
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    /// The upper end of the edge
+    top: Vec2D,
+
+    /// The lower end of the edge
+    bottom: Vec2D,
+
+    /// The change in `x` per unit of `y`
+    slope: f32,
+
+    /// `1` if the edge goes downwards, `-1` otherwise
+    direction: f32,
+}
+
+impl Edge {
+    fn new(from: Vec2D, to: Vec2D) -> Option<Self> {
+        // Horizontal edges don't contribute any coverage
+        if (from.y - to.y).abs() <= f32::EPSILON {
+            return None;
+        }
+
+        let (direction, top, bottom) = if from.y < to.y {
+            (1., from, to)
+        } else {
+            (-1., to, from)
+        };
+
+        Some(Self {
+            top,
+            bottom,
+            slope: (bottom.x - top.x) / (bottom.y - top.y),
+            direction,
+        })
+    }
+
+    #[inline]
+    fn x_at(&self, y: f32) -> f32 {
+        self.top.x + (y - self.top.y) * self.slope
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Rasterizer {
     width: usize,
     height: usize,
     offset: Vec2D,
-    buffer: Vec<f32>,
+    antialiasing: Antialiasing,
+    edges: Vec<Edge>,
+
+    /// The coverage of every sample, which are subpixels for [Antialiasing::Lcd]
+    coverage: Vec<u8>,
 }
 
 impl Rasterizer {
@@ -18,118 +111,238 @@ impl Rasterizer {
             width,
             height,
             offset,
-            buffer: vec![0.; width * height],
+            antialiasing: Antialiasing::default(),
+            edges: vec![],
+            coverage: vec![0; width * height],
         }
     }
 
+    /// Change how the edges of the filled paths are smoothed
+    ///
+    /// This must be called before anything is drawn.
+    #[must_use]
+    pub fn with_antialiasing(mut self, antialiasing: Antialiasing) -> Self {
+        self.antialiasing = antialiasing;
+        self.coverage = vec![0; self.samples_per_row() * self.height];
+        self
+    }
+
+    #[inline]
+    fn samples_per_row(&self) -> usize {
+        self.width * self.antialiasing.subpixels()
+    }
+
     #[must_use]
     pub fn into_mask(self) -> Mask {
+        let coverage = match self.antialiasing {
+            Antialiasing::Grayscale => self.coverage,
+            Antialiasing::Lcd => filter_subpixels(&self.coverage, self.samples_per_row()),
+        };
+
         Mask {
             width: self.width,
             height: self.height,
-            mask: self.buffer,
+            antialiasing: self.antialiasing,
+            coverage,
         }
     }
 
-    /// Rasterize a 2D Line.
-    /// **Greatly** inspired by <https://github.com/raphlinus/font-rs/blob/master/src/raster.rs#L44>
+    /// Add a 2D Line to the set of edges that will be filled
     pub fn draw_line(&mut self, from: Vec2D, to: Vec2D) {
-        // The rasterizer does not draw horizontal lines, those are covered by the fill
-        // algorithm
-        if (from.y - to.y).abs() <= f32::EPSILON {
-            return;
+        // Edges are stored in sample coordinates
+        let subpixels = self.antialiasing.subpixels() as f32;
+        let to_samples = |point: Vec2D| Vec2D::new(point.x * subpixels, point.y);
+
+        if let Some(edge) = Edge::new(to_samples(from), to_samples(to)) {
+            self.edges.push(edge);
         }
+    }
 
-        // Make sure to always go from the lower point to the higher one
-        let (direction, from, to) = if from.y < to.y {
-            (1.0, from, to)
-        } else {
-            (-1.0, to, from)
-        };
+    /// Fill the area enclosed by the path, using the nonzero winding rule
+    pub fn fill(&mut self, path: &[FlattenedPathPoint]) {
+        for line in path.array_windows::<2>() {
+            if line[1].connected {
+                self.draw_line(
+                    line[0].coordinates - self.offset,
+                    line[1].coordinates - self.offset,
+                );
+            }
+        }
+
+        self.fill_edges();
+    }
 
-        let line_slope = (to.x - from.x) / (to.y - from.y);
+    fn fill_edges(&mut self) {
+        let mut edges = std::mem::take(&mut self.edges);
+        edges.sort_unstable_by(|a, b| a.top.y.total_cmp(&b.top.y));
 
-        let mut x = from.x;
-        let y_start = from.y as usize;
-        if from.y.is_sign_negative() {
-            x -= from.y * line_slope;
-        }
+        let width = self.samples_per_row();
 
-        for y in y_start..self.height.min(to.y.ceil() as usize) {
-            let linestart = y * self.width;
+        // Accumulated area for the current scanline. Two extra cells are required because
+        // an edge at the very right border of the mask spills over into the next cells.
+        let mut accumulation_buffer = vec![0.; width + 2];
+        let max_x = width as f32;
 
-            // The y-delta covered by this line segment.
-            // Will usually be zero, except for the first and last segments
-            let dy = ((y + 1) as f32).min(to.y) - (y as f32).max(from.y);
+        let mut active_edges: Vec<Edge> = vec![];
+        let mut next_edge = 0;
 
-            // The x coordinate where this line segment will end
-            let xnext = x + line_slope * dy;
+        let mut y = edges
+            .first()
+            .map(|edge| edge.top.y.max(0.) as usize)
+            .unwrap_or(self.height);
 
-            let d = dy * direction;
+        while y < self.height {
+            let scanline_top = y as f32;
+            let scanline_bottom = scanline_top + 1.;
 
-            let (x0, x1) = if x < xnext { (x, xnext) } else { (xnext, x) };
+            // Update the active edge table
+            while next_edge < edges.len() && edges[next_edge].top.y < scanline_bottom {
+                active_edges.push(edges[next_edge]);
+                next_edge += 1;
+            }
+            active_edges.retain(|edge| scanline_top < edge.bottom.y);
 
-            let x0floor = x0.floor();
-            let x0i = x0floor as i32;
-            let x1ceil = x1.ceil();
-            let x1i = x1ceil as i32;
-            if x1i <= x0i + 1 {
-                let xmf = 0.5 * (x + xnext) - x0floor;
-                let linestart_x0i = linestart as isize + x0i as isize;
-                if linestart_x0i.is_negative() {
-                    continue;
+            if active_edges.is_empty() {
+                // Skip ahead to the next edge
+                match edges.get(next_edge) {
+                    Some(edge) => {
+                        y = (edge.top.y as usize).max(y + 1);
+                        continue;
+                    },
+                    None => break,
                 }
-                self.buffer[linestart_x0i as usize] += d - d * xmf;
-                self.buffer[linestart_x0i as usize] += d * xmf;
-            } else {
-                let s = (x1 - x0).recip();
-                let x0f = x0 - x0floor;
-                let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
-                let x1f = x1 - x1ceil + 1.0;
-                let am = 0.5 * s * x1f * x1f;
-                let linestart_x0i = linestart as isize + x0i as isize;
-                if linestart_x0i.is_negative() {
+            }
+
+            // Accumulate the area covered by each edge
+            let mut span_start = width + 1;
+            let mut span_end = 0;
+            for edge in &active_edges {
+                let top = edge.top.y.max(scanline_top);
+                let bottom = edge.bottom.y.min(scanline_bottom);
+                if bottom <= top {
                     continue;
                 }
-                self.buffer[linestart_x0i as usize + 1] += d * a0;
-                if x1i == x0i + 2 {
-                    self.buffer[linestart_x0i as usize + 1] += d * (1.0 - a0 - am);
-                } else {
-                    let a1 = s * (1.5 - x0f);
-                    self.buffer[linestart_x0i as usize + 1] += d * (a1 - a0);
-                    for xi in x0i + 2..x1i - 1 {
-                        self.buffer[linestart + xi as usize] += d * s;
-                    }
-                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
-                    self.buffer[linestart + (x1i - 1) as usize] += d * (1.0 - a2 - am);
+
+                // Clamping to the left border preserves the coverage to the right of the edge,
+                // anything that is clamped to the right border is not visible anyways
+                let x_top = edge.x_at(top).clamp(0., max_x);
+                let x_bottom = edge.x_at(bottom).clamp(0., max_x);
+
+                span_start = span_start.min(x_top.min(x_bottom) as usize);
+                span_end = span_end.max(x_top.max(x_bottom).ceil() as usize + 1);
+
+                accumulate_line(
+                    &mut accumulation_buffer,
+                    x_top,
+                    x_bottom,
+                    (bottom - top) * edge.direction,
+                );
+            }
+
+            if span_end < span_start {
+                // None of the active edges intersect this scanline
+                y += 1;
+                continue;
+            }
+
+            // Sum up the accumulated area to compute the coverage of each pixel
+            let row = &mut self.coverage[y * width..(y + 1) * width];
+            let mut accumulator = 0.;
+            let span_end = span_end.min(width + 1);
+            for (x, area) in accumulation_buffer[span_start..=span_end]
+                .iter_mut()
+                .enumerate()
+            {
+                accumulator += *area;
+                *area = 0.;
+
+                if let Some(pixel) = row.get_mut(span_start + x) {
+                    *pixel = pixel.saturating_add(quantize(accumulator));
                 }
+            }
 
-                self.buffer[linestart + x1i as usize] += d * am;
+            // Pixels to the right of all edges in this scanline have the same coverage
+            let remaining_coverage = quantize(accumulator);
+            if remaining_coverage != 0 {
+                for pixel in row.iter_mut().skip(span_end + 1) {
+                    *pixel = pixel.saturating_add(remaining_coverage);
+                }
             }
-            x = xnext;
+
+            y += 1;
         }
     }
+}
 
-    pub fn fill(&mut self, path: &[FlattenedPathPoint]) {
-        // Draw the outlines of the shape
-        for line in path.array_windows::<2>() {
-            if line[1].connected {
-                self.draw_line(
-                    line[0].coordinates - self.offset,
-                    line[1].coordinates - self.offset,
-                );
-            }
-        }
+/// Convert an accumulated (signed) area into one of 256 coverage levels
+#[inline]
+fn quantize(area: f32) -> u8 {
+    (area.abs().min(1.) * 255.).round() as u8
+}
+
+/// Spread the coverage of every subpixel across its neighbours, using the [LCD_FILTER]
+///
+/// Without filtering, the edges of shapes have strong color fringes.
+fn filter_subpixels(coverage: &[u8], row_width: usize) -> Vec<u8> {
+    let mut filtered = Vec::with_capacity(coverage.len());
+    for row in coverage.chunks_exact(row_width) {
+        for x in 0..row_width {
+            let sum: u16 = LCD_FILTER
+                .iter()
+                .enumerate()
+                .filter_map(|(index, weight)| {
+                    let neighbour = (x + index).checked_sub(LCD_FILTER.len() / 2)?;
+                    Some(weight * u16::from(*row.get(neighbour)?))
+                })
+                .sum();
 
-        self.fill_outline()
+            // Divide by the sum of the weights, rounding to the nearest value
+            filtered.push(((sum + 4) / 9) as u8);
+        }
     }
+    filtered
+}
 
-    fn fill_outline(&mut self) {
-        let mut accumulator = 0.;
-        for elem in &mut self.buffer {
-            accumulator += *elem;
-            *elem = accumulator;
+/// Accumulate the area of a line segment that spans at most one scanline.
+///
+/// `d` is the (signed) height of the segment.
+fn accumulate_line(buffer: &mut [f32], x_top: f32, x_bottom: f32, d: f32) {
+    let (x0, x1) = if x_top < x_bottom {
+        (x_top, x_bottom)
+    } else {
+        (x_bottom, x_top)
+    };
+
+    let x0_floor = x0.floor();
+    let x0i = x0_floor as usize;
+    let x1_ceil = x1.ceil();
+    let x1i = x1_ceil as usize;
+
+    if x1i <= x0i + 1 {
+        // The segment is contained within a single pixel
+        let x_mid = 0.5 * (x_top + x_bottom) - x0_floor;
+        buffer[x0i] += d - d * x_mid;
+        buffer[x0i + 1] += d * x_mid;
+    } else {
+        let s = (x1 - x0).recip();
+        let x0f = x0 - x0_floor;
+        let a0 = 0.5 * s * (1. - x0f) * (1. - x0f);
+        let x1f = x1 - x1_ceil + 1.;
+        let am = 0.5 * s * x1f * x1f;
+
+        buffer[x0i] += d * a0;
+        if x1i == x0i + 2 {
+            buffer[x0i + 1] += d * (1. - a0 - am);
+        } else {
+            let a1 = s * (1.5 - x0f);
+            buffer[x0i + 1] += d * (a1 - a0);
+            for cell in &mut buffer[x0i + 2..x1i - 1] {
+                *cell += d * s;
+            }
+            let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+            buffer[x1i - 1] += d * (1. - a2 - am);
         }
+        buffer[x1i] += d * am;
     }
 }
 
@@ -137,7 +350,10 @@ impl Rasterizer {
 pub struct Mask {
     width: usize,
     height: usize,
-    mask: Vec<f32>,
+    antialiasing: Antialiasing,
+
+    /// [Antialiasing::subpixels] coverage values for every pixel
+    coverage: Vec<u8>,
 }
 
 impl Mask {
@@ -151,8 +367,161 @@ impl Mask {
         self.height
     }
 
+    #[must_use]
+    pub const fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    /// The coverage of a pixel, between `0` (not covered) and `255` (fully covered)
+    ///
+    /// For [Antialiasing::Lcd], this is the average coverage of the subpixels.
+    #[must_use]
+    pub fn coverage_at(&self, x: usize, y: usize) -> u8 {
+        match self.antialiasing {
+            Antialiasing::Grayscale => self.coverage[y * self.width + x],
+            Antialiasing::Lcd => {
+                let [red, green, blue] = self.subpixel_coverage_at(x, y).map(u16::from);
+                ((red + green + blue + 1) / 3) as u8
+            },
+        }
+    }
+
+    /// The coverage of the red, green and blue subpixels of a pixel
+    ///
+    /// For [Antialiasing::Grayscale], all subpixels have the same coverage.
+    #[must_use]
+    pub fn subpixel_coverage_at(&self, x: usize, y: usize) -> [u8; 3] {
+        match self.antialiasing {
+            Antialiasing::Grayscale => [self.coverage_at(x, y); 3],
+            Antialiasing::Lcd => {
+                let index = (y * self.width + x) * 3;
+                [
+                    self.coverage[index],
+                    self.coverage[index + 1],
+                    self.coverage[index + 2],
+                ]
+            },
+        }
+    }
+
+    /// The coverage of a pixel, between `0.` (not covered) and `1.` (fully covered)
     #[must_use]
     pub fn opacity_at(&self, x: usize, y: usize) -> f32 {
-        self.mask[y * self.width + x]
+        self.coverage_at(x, y) as f32 / 255.
+    }
+
+    /// The coverage of the red, green and blue subpixels of a pixel, between `0.` and `1.`
+    #[must_use]
+    pub fn subpixel_opacity_at(&self, x: usize, y: usize) -> [f32; 3] {
+        self.subpixel_coverage_at(x, y)
+            .map(|coverage| coverage as f32 / 255.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math::{Rectangle, Vec2D};
+
+    use super::{Antialiasing, Rasterizer};
+    use crate::{FlattenedPathPoint, Path};
+
+    fn rasterize(path: Path, size: usize) -> super::Mask {
+        rasterize_with(path, size, Antialiasing::Grayscale)
+    }
+
+    fn rasterize_with(path: Path, size: usize, antialiasing: Antialiasing) -> super::Mask {
+        let mut points = vec![];
+        path.flatten(0.01, &mut points);
+
+        let area = Rectangle::from_corners(Vec2D::new(0, 0), Vec2D::new(size, size));
+        let mut rasterizer =
+            Rasterizer::new(area, Vec2D::new(0., 0.)).with_antialiasing(antialiasing);
+        rasterizer.fill(&points);
+        rasterizer.into_mask()
+    }
+
+    #[test]
+    fn pixel_aligned_rectangle() {
+        let mask = rasterize(Path::rect(Vec2D::new(2., 2.), Vec2D::new(6., 6.)), 8);
+
+        for y in 0..mask.height() {
+            for x in 0..mask.width() {
+                let expected = if (2..6).contains(&x) && (2..6).contains(&y) {
+                    255
+                } else {
+                    0
+                };
+                assert_eq!(mask.coverage_at(x, y), expected, "at ({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn partially_covered_pixels() {
+        let mask = rasterize(Path::rect(Vec2D::new(1.5, 1.), Vec2D::new(3.5, 2.)), 4);
+
+        assert_eq!(mask.coverage_at(1, 1), 128);
+        assert_eq!(mask.coverage_at(2, 1), 255);
+        assert_eq!(mask.coverage_at(3, 1), 128);
+        assert_eq!(mask.coverage_at(0, 1), 0);
+        assert_eq!(mask.coverage_at(2, 2), 0);
+    }
+
+    #[test]
+    fn winding_direction_does_not_matter() {
+        let clockwise = [
+            FlattenedPathPoint::new(Vec2D::new(1., 1.), false),
+            FlattenedPathPoint::new(Vec2D::new(3., 1.), true),
+            FlattenedPathPoint::new(Vec2D::new(3., 3.), true),
+            FlattenedPathPoint::new(Vec2D::new(1., 3.), true),
+            FlattenedPathPoint::new(Vec2D::new(1., 1.), true),
+        ];
+        let mut counter_clockwise = clockwise;
+        counter_clockwise.reverse();
+        counter_clockwise[0].connected = false;
+        counter_clockwise[4].connected = true;
+
+        let area = Rectangle::from_corners(Vec2D::new(0, 0), Vec2D::new(4, 4));
+
+        let mut rasterizer = Rasterizer::new(area, Vec2D::new(0., 0.));
+        rasterizer.fill(&clockwise);
+        let a = rasterizer.into_mask();
+
+        let mut rasterizer = Rasterizer::new(area, Vec2D::new(0., 0.));
+        rasterizer.fill(&counter_clockwise);
+        let b = rasterizer.into_mask();
+
+        assert_eq!(a.coverage, b.coverage);
+        assert_eq!(a.coverage_at(2, 2), 255);
+    }
+
+    #[test]
+    fn lcd_subpixels() {
+        let mask = rasterize_with(
+            Path::rect(Vec2D::new(2., 2.), Vec2D::new(6., 6.)),
+            8,
+            Antialiasing::Lcd,
+        );
+
+        // Fully covered pixels are not affected by the filter
+        assert_eq!(mask.subpixel_coverage_at(3, 3), [255; 3]);
+        assert_eq!(mask.coverage_at(3, 3), 255);
+
+        // The coverage is spread to the neighbouring subpixels
+        assert_eq!(mask.subpixel_coverage_at(1, 3), [0, 28, 85]);
+        assert_eq!(mask.subpixel_coverage_at(2, 3), [170, 227, 255]);
+        assert_eq!(mask.subpixel_coverage_at(5, 3), [255, 227, 170]);
+        assert_eq!(mask.subpixel_coverage_at(6, 3), [85, 28, 0]);
+        assert_eq!(mask.subpixel_coverage_at(0, 3), [0; 3]);
+        assert_eq!(mask.subpixel_coverage_at(3, 1), [0; 3]);
+
+        // A shape that only covers the red subpixel
+        let mask = rasterize_with(
+            Path::rect(Vec2D::new(2., 2.), Vec2D::new(2. + 1. / 3., 3.)),
+            4,
+            Antialiasing::Lcd,
+        );
+        let [red, green, blue] = mask.subpixel_coverage_at(2, 2);
+        assert!(red > green && green > blue);
     }
 }
@@ -0,0 +1,479 @@
+//! Convert paths into outlines that can be filled to draw a stroke
+//!
+//! Every segment, join and cap of the stroke is emitted as its own
+//! polygon, all with the same winding direction. Filling the result with the nonzero
+//! winding rule then produces the union of all the polygons.
+
+use std::f32::consts::PI;
+
+use math::Vec2D;
+
+use crate::FlattenedPathPoint;
+
+/// The shape at the end of open subpaths
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// The stroke ends exactly at the end of the path
+    #[default]
+    Butt,
+
+    /// The stroke is extended by a half circle
+    Round,
+
+    /// The stroke is extended by half of its width
+    Square,
+}
+
+/// The shape where two segments of a path meet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges of the segments are extended until they meet
+    ///
+    /// If the resulting spike would be longer than the miter limit, a
+    /// [LineJoin::Bevel] is used instead.
+    #[default]
+    Miter,
+
+    /// The segments are connected by a circular arc
+    Round,
+
+    /// The outer corners of the segments are connected by a straight line
+    Bevel,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+
+    /// The maximum ratio of miter length to stroke width
+    pub miter_limit: f32,
+
+    /// Alternating lengths of dashes and gaps, an empty pattern draws a solid line
+    pub dash_pattern: Vec<f32>,
+
+    /// How far into the dash pattern the stroke starts
+    pub dash_offset: f32,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self {
+            width: 1.,
+            cap: LineCap::default(),
+            join: LineJoin::default(),
+            miter_limit: 4.,
+            dash_pattern: vec![],
+            dash_offset: 0.,
+        }
+    }
+}
+
+/// A sequence of connected points
+#[derive(Clone, Debug, Default)]
+struct Polyline {
+    points: Vec<Vec2D>,
+    is_closed: bool,
+}
+
+impl Stroke {
+    #[must_use]
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    #[must_use]
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    #[must_use]
+    pub fn with_dashes(mut self, dash_pattern: Vec<f32>, dash_offset: f32) -> Self {
+        self.dash_pattern = dash_pattern;
+        self.dash_offset = dash_offset;
+        self
+    }
+
+    /// Compute the outline of the stroke along `path`
+    ///
+    /// The result is appended to `outline` and must be filled using the nonzero winding rule.
+    pub fn outline(&self, path: &[FlattenedPathPoint], outline: &mut Vec<FlattenedPathPoint>) {
+        if self.width <= 0. {
+            return;
+        }
+
+        for polyline in split_into_polylines(path) {
+            for polyline in self.apply_dashes(polyline) {
+                self.stroke_polyline(&polyline, outline);
+            }
+        }
+    }
+
+    fn apply_dashes(&self, polyline: Polyline) -> Vec<Polyline> {
+        let mut pattern = self.dash_pattern.clone();
+        if pattern.len() % 2 == 1 {
+            // An odd number of values is repeated to yield an even number of values
+            pattern.extend_from_within(..);
+        }
+
+        let pattern_length: f32 = pattern.iter().sum();
+        if pattern.is_empty()
+            || pattern_length <= 0.
+            || pattern.iter().any(|value| value.is_sign_negative())
+        {
+            return vec![polyline];
+        }
+
+        let mut points = polyline.points;
+        if polyline.is_closed {
+            points.push(points[0]);
+        }
+
+        // Find the position in the pattern where the stroke starts
+        let mut dash_index = 0;
+        let mut remaining_in_dash = pattern[0];
+        let mut offset = self.dash_offset.rem_euclid(pattern_length);
+        while remaining_in_dash <= offset {
+            offset -= remaining_in_dash;
+            dash_index = (dash_index + 1) % pattern.len();
+            remaining_in_dash = pattern[dash_index];
+        }
+        remaining_in_dash -= offset;
+
+        // Even indices in the pattern are dashes, odd ones are gaps
+        let is_dash = |index: usize| index & 1 == 0;
+
+        let mut dashes = vec![];
+        let mut current = Polyline::default();
+        if is_dash(dash_index) {
+            current.points.push(points[0]);
+        }
+
+        for segment in points.array_windows::<2>() {
+            let [mut start, end] = *segment;
+            let mut segment_length = (end - start).magnitude();
+
+            while remaining_in_dash < segment_length {
+                let split_point = start.lerp(end, remaining_in_dash / segment_length);
+
+                // Either ends the current dash or starts a new one
+                current.points.push(split_point);
+                if is_dash(dash_index) {
+                    dashes.push(std::mem::take(&mut current));
+                }
+
+                segment_length -= remaining_in_dash;
+                start = split_point;
+                dash_index = (dash_index + 1) % pattern.len();
+                remaining_in_dash = pattern[dash_index];
+            }
+
+            remaining_in_dash -= segment_length;
+            if is_dash(dash_index) {
+                current.points.push(end);
+            }
+        }
+
+        if is_dash(dash_index) && !current.points.is_empty() {
+            dashes.push(current);
+        }
+
+        dashes
+    }
+
+    fn stroke_polyline(&self, polyline: &Polyline, outline: &mut Vec<FlattenedPathPoint>) {
+        let half_width = self.width / 2.;
+        let points = &polyline.points;
+
+        let Some(&first) = points.first() else {
+            return;
+        };
+
+        if points.len() == 1 {
+            // Zero-length subpaths are only visible if they have a cap
+            match self.cap {
+                LineCap::Butt => {},
+                LineCap::Round => emit_circle(first, half_width, outline),
+                LineCap::Square => {
+                    let (x, y) = (Vec2D::new(half_width, 0.), Vec2D::new(0., half_width));
+                    emit_polygon(
+                        &[first - x - y, first + x - y, first + x + y, first - x + y],
+                        outline,
+                    );
+                },
+            }
+            return;
+        }
+
+        // The body of the stroke
+        for segment in points.array_windows::<2>() {
+            let normal = normal(segment[0], segment[1]) * half_width;
+            emit_polygon(
+                &[
+                    segment[0] + normal,
+                    segment[1] + normal,
+                    segment[1] - normal,
+                    segment[0] - normal,
+                ],
+                outline,
+            );
+        }
+
+        if polyline.is_closed {
+            let last = points[points.len() - 1];
+            let normal = normal(last, first) * half_width;
+            emit_polygon(
+                &[last + normal, first + normal, first - normal, last - normal],
+                outline,
+            );
+        }
+
+        // Joins between segments
+        for window in points.array_windows::<3>() {
+            self.emit_join(window[0], window[1], window[2], outline);
+        }
+
+        if polyline.is_closed {
+            let n = points.len();
+            self.emit_join(points[n - 2], points[n - 1], points[0], outline);
+            self.emit_join(points[n - 1], points[0], points[1], outline);
+        } else {
+            self.emit_cap(points[1], points[0], outline);
+            self.emit_cap(points[points.len() - 2], points[points.len() - 1], outline);
+        }
+    }
+
+    fn emit_join(
+        &self,
+        previous: Vec2D,
+        point: Vec2D,
+        next: Vec2D,
+        outline: &mut Vec<FlattenedPathPoint>,
+    ) {
+        let half_width = self.width / 2.;
+        let incoming = direction(previous, point);
+        let outgoing = direction(point, next);
+
+        let cross = incoming.cross_product(outgoing);
+        if cross.abs() <= f32::EPSILON && incoming.dot(outgoing) > 0. {
+            // The segments are collinear, there is nothing to join
+            return;
+        }
+
+        if self.join == LineJoin::Round {
+            emit_circle(point, half_width, outline);
+            return;
+        }
+
+        // The join is on the outer side of the corner
+        let side = if cross > 0. { -1. } else { 1. };
+        let incoming_normal = normal(previous, point) * side;
+        let outgoing_normal = normal(point, next) * side;
+
+        let a = point + incoming_normal * half_width;
+        let b = point + outgoing_normal * half_width;
+
+        if self.join == LineJoin::Miter {
+            let bisector = incoming_normal + outgoing_normal;
+            let bisector_length = bisector.magnitude();
+
+            if bisector_length > f32::EPSILON {
+                let bisector = bisector / bisector_length;
+
+                // Ratio between the length of the miter and the stroke width
+                let miter_ratio = bisector.dot(incoming_normal).recip();
+
+                if miter_ratio <= self.miter_limit {
+                    let tip = point + bisector * (half_width * miter_ratio);
+                    emit_polygon(&[point, a, tip, b], outline);
+                    return;
+                }
+            }
+        }
+
+        emit_polygon(&[point, a, b], outline);
+    }
+
+    /// Emit the cap at `end`, for a segment that is coming from `start`
+    fn emit_cap(&self, start: Vec2D, end: Vec2D, outline: &mut Vec<FlattenedPathPoint>) {
+        let half_width = self.width / 2.;
+
+        match self.cap {
+            LineCap::Butt => {},
+            LineCap::Round => emit_circle(end, half_width, outline),
+            LineCap::Square => {
+                let normal = normal(start, end) * half_width;
+                let extension = direction(start, end) * half_width;
+                emit_polygon(
+                    &[
+                        end + normal,
+                        end + normal + extension,
+                        end - normal + extension,
+                        end - normal,
+                    ],
+                    outline,
+                );
+            },
+        }
+    }
+}
+
+/// Split a flattened path at every point that is not connected to its predecessor
+fn split_into_polylines(path: &[FlattenedPathPoint]) -> Vec<Polyline> {
+    let mut polylines = vec![];
+    let mut current = Polyline::default();
+
+    for point in path {
+        if !point.connected && !current.points.is_empty() {
+            polylines.push(std::mem::take(&mut current));
+        }
+
+        // Skip degenerate segments
+        if current.points.last() != Some(&point.coordinates) {
+            current.points.push(point.coordinates);
+        }
+    }
+
+    if !current.points.is_empty() {
+        polylines.push(current);
+    }
+
+    for polyline in &mut polylines {
+        if polyline.points.len() > 2 && polyline.points.first() == polyline.points.last() {
+            polyline.points.pop();
+            polyline.is_closed = true;
+        }
+    }
+
+    polylines
+}
+
+/// The unit vector pointing from `from` to `to`
+#[inline]
+fn direction(from: Vec2D, to: Vec2D) -> Vec2D {
+    let delta = to - from;
+    delta / delta.magnitude()
+}
+
+/// The unit normal of the line from `from` to `to`
+#[inline]
+fn normal(from: Vec2D, to: Vec2D) -> Vec2D {
+    let direction = direction(from, to);
+    Vec2D::new(-direction.y, direction.x)
+}
+
+fn emit_circle(center: Vec2D, radius: f32, outline: &mut Vec<FlattenedPathPoint>) {
+    let num_segments = ((radius * 4.).ceil() as usize).clamp(8, 64);
+    let points: Vec<Vec2D> = (0..num_segments)
+        .map(|i| {
+            let angle = 2. * PI * i as f32 / num_segments as f32;
+            center + Vec2D::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect();
+    emit_polygon(&points, outline);
+}
+
+/// Append a closed polygon to the outline, making sure that it is oriented clockwise
+fn emit_polygon(points: &[Vec2D], outline: &mut Vec<FlattenedPathPoint>) {
+    let signed_area: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.cross_product(*b))
+        .sum();
+
+    let mut emit = |point: Vec2D, connected| {
+        outline.push(FlattenedPathPoint::new(point, connected));
+    };
+
+    if signed_area < 0. {
+        emit(points[points.len() - 1], false);
+        for &point in points.iter().rev().skip(1) {
+            emit(point, true);
+        }
+        emit(points[points.len() - 1], true);
+    } else {
+        emit(points[0], false);
+        for &point in &points[1..] {
+            emit(point, true);
+        }
+        emit(points[0], true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math::Vec2D;
+
+    use super::{split_into_polylines, LineCap, Stroke};
+    use crate::FlattenedPathPoint;
+
+    fn line(points: &[(f32, f32)]) -> Vec<FlattenedPathPoint> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| FlattenedPathPoint::new(Vec2D::new(x, y), i != 0))
+            .collect()
+    }
+
+    #[test]
+    fn detect_closed_polylines() {
+        let path = line(&[(0., 0.), (1., 0.), (1., 1.), (0., 0.)]);
+        let polylines = split_into_polylines(&path);
+
+        assert_eq!(polylines.len(), 1);
+        assert!(polylines[0].is_closed);
+        assert_eq!(polylines[0].points.len(), 3);
+    }
+
+    #[test]
+    fn dashes() {
+        let stroke = Stroke::new(1.).with_dashes(vec![2., 1.], 0.);
+        let polylines = split_into_polylines(&line(&[(0., 0.), (10., 0.)]));
+        let dashes = stroke.apply_dashes(polylines[0].clone());
+
+        // Dashes at 0-2, 3-5, 6-8 and 9-10
+        assert_eq!(dashes.len(), 4);
+        assert_eq!(
+            dashes[1].points,
+            vec![Vec2D::new(3., 0.), Vec2D::new(5., 0.)]
+        );
+        assert_eq!(
+            dashes[3].points,
+            vec![Vec2D::new(9., 0.), Vec2D::new(10., 0.)]
+        );
+    }
+
+    #[test]
+    fn square_caps_extend_the_stroke() {
+        let path = line(&[(2., 2.), (6., 2.)]);
+
+        let mut butt = vec![];
+        Stroke::new(2.).outline(&path, &mut butt);
+
+        let mut square = vec![];
+        Stroke::new(2.)
+            .with_cap(LineCap::Square)
+            .outline(&path, &mut square);
+
+        let min_x = |outline: &[FlattenedPathPoint]| {
+            outline
+                .iter()
+                .map(|point| point.coordinates.x)
+                .fold(f32::INFINITY, f32::min)
+        };
+
+        assert_eq!(min_x(&butt), 2.);
+        assert_eq!(min_x(&square), 1.);
+    }
+}
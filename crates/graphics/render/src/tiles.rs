@@ -0,0 +1,523 @@
+//! Rasterizing a [Composition](crate::Composition) tile by tile, on all available cores
+//!
+//! Every tile is identified by a hash of everything that is drawn onto it. Tiles whose
+//! hash did not change since they were last drawn are skipped, tiles whose contents
+//! were rasterized before (like empty parts of a page, or content that moved by a
+//! multiple of [TILE_SIZE]) are copied from a cache. Only the remaining tiles are rasterized.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    mem,
+    sync::Arc,
+};
+
+use image::{Rgbaf32, Texture};
+use math::{Color, Rectangle, Vec2D};
+use sl_std::safe_casts::cast_slice;
+
+use crate::{workers::parallel_map, Antialiasing, Layer, Mask, Source, TILE_SIZE};
+
+/// The number of rasterized tiles that are kept around, in case their contents show up again
+const MAX_CACHED_TILES: usize = 256;
+
+/// A [Layer] whose outline was flattened and transformed
+pub(crate) struct PreparedLayer<'a> {
+    layer: &'a Layer,
+
+    /// The area covered by the outline, in device pixels
+    outline_extent: Rectangle,
+
+    /// The pixels that the layer might draw to
+    pixel_area: Rectangle<usize>,
+
+    /// A hash of the appearance of the layer, independent of its position
+    content_hash: u64,
+}
+
+impl<'a> PreparedLayer<'a> {
+    /// Returns `None` if the layer has no outline
+    pub(crate) fn new(layer: &'a mut Layer) -> Option<Self> {
+        let outline_extent = layer.prepare()?;
+        let layer = &*layer;
+
+        let pixel_extent = outline_extent.snap_to_grid();
+
+        // The mask includes the pixels at the bottom right edge of the extent
+        let pixel_area = Rectangle::from_corners(
+            pixel_extent.top_left(),
+            pixel_extent.bottom_right().map(|value| value + 1),
+        );
+
+        let mut hasher = DefaultHasher::new();
+        hash_source(&layer.source, &mut hasher);
+        layer.antialiasing.hash(&mut hasher);
+
+        // Points are relative to the pixel grid, so moving the layer by whole pixels keeps the hash
+        let origin = pixel_extent.top_left().map(|value| value as f32);
+        for point in layer.flattened_outline() {
+            let coordinates = point.coordinates - origin;
+            coordinates.x.to_bits().hash(&mut hasher);
+            coordinates.y.to_bits().hash(&mut hasher);
+            point.connected.hash(&mut hasher);
+        }
+
+        Some(Self {
+            layer,
+            outline_extent,
+            pixel_area,
+            content_hash: hasher.finish(),
+        })
+    }
+}
+
+/// Textures are identified by their address, which is only unique while the texture is alive
+///
+/// Shared textures are never modified, changing them creates a copy at a different address.
+fn hash_source(source: &Source, hasher: &mut DefaultHasher) {
+    match source {
+        Source::Solid(color) => color.0.hash(hasher),
+        Source::Texture {
+            texture,
+            access_mode,
+        } => {
+            Arc::as_ptr(texture).hash(hasher);
+            matches!(access_mode, image::AccessMode::Zero).hash(hasher);
+        },
+    }
+}
+
+/// The mask of a layer, ready to be drawn onto tiles
+struct RasterizedLayer {
+    mask: Mask,
+    source: Source,
+
+    /// The position of the top left corner of the mask, in device pixels
+    offset: Vec2D<usize>,
+}
+
+impl RasterizedLayer {
+    fn new(prepared: &PreparedLayer<'_>) -> Self {
+        let pixel_extent = prepared.outline_extent.snap_to_grid();
+
+        Self {
+            mask: prepared.layer.rasterize(prepared.outline_extent),
+            source: prepared
+                .layer
+                .source
+                .resize(pixel_extent.width(), pixel_extent.height()),
+            offset: pixel_extent.top_left(),
+        }
+    }
+
+    /// Blend the layer onto a tile, where `tile` is the area of the render target that `destination` covers
+    fn compose_onto(&self, destination: &mut Texture, tile: Rectangle<usize>) {
+        let mask_area = Rectangle::from_corners(
+            self.offset,
+            self.offset + Vec2D::new(self.mask.width(), self.mask.height()),
+        );
+        let Some(visible_area) = tile.intersection(mask_area) else {
+            return;
+        };
+
+        let start = visible_area.top_left();
+        let end = visible_area.bottom_right();
+        for y in start.y..end.y {
+            for x in start.x..end.x {
+                let (tile_x, tile_y) = (x - tile.top_left().x, y - tile.top_left().y);
+                let previous_color = destination.get_pixel(tile_x, tile_y);
+
+                if let (Source::Solid(color), Antialiasing::Lcd) =
+                    (&self.source, self.mask.antialiasing())
+                {
+                    let opacity = self
+                        .mask
+                        .subpixel_opacity_at(x - self.offset.x, y - self.offset.y);
+                    destination.set_pixel(
+                        tile_x,
+                        tile_y,
+                        blend_subpixels(previous_color, *color, opacity),
+                    );
+                    continue;
+                }
+
+                let opacity = self.mask.opacity_at(x - self.offset.x, y - self.offset.y);
+                let color = match &self.source {
+                    Source::Solid(color) => Rgbaf32::rgba(
+                        color.red() as f32 / 255.,
+                        color.green() as f32 / 255.,
+                        color.blue() as f32 / 255.,
+                        opacity,
+                    ),
+                    Source::Texture {
+                        texture,
+                        access_mode,
+                    } => {
+                        let mut texture_pixel =
+                            texture.get(x - self.offset.x, y - self.offset.y, *access_mode);
+
+                        // Adjust the alpha value of the texture according to the mask
+                        let texture_alpha = texture_pixel.alpha();
+                        texture_pixel.set_alpha(texture_alpha * opacity);
+                        texture_pixel
+                    },
+                };
+
+                destination.set_pixel(tile_x, tile_y, previous_color.blend(color));
+            }
+        }
+    }
+}
+
+/// Blend a solid color on top of a pixel, with a separate opacity for each
+/// of the red, green and blue subpixels
+fn blend_subpixels(previous: Rgbaf32, color: Color, opacity: [f32; 3]) -> Rgbaf32 {
+    let [red, green, blue] = opacity;
+    let average_opacity = (red + green + blue) / 3.;
+    let mix = |previous: f32, channel: u8, opacity: f32| {
+        (channel as f32 / 255.) * opacity + previous * (1. - opacity)
+    };
+
+    Rgbaf32::rgba(
+        mix(previous.red(), color.red(), red),
+        mix(previous.green(), color.green(), green),
+        mix(previous.blue(), color.blue(), blue),
+        previous.alpha() + average_opacity - previous.alpha() * average_opacity,
+    )
+}
+
+/// Identifies what is drawn onto a tile
+#[derive(Clone, Debug)]
+struct TileContents {
+    hash: u64,
+
+    /// The textures that are drawn onto the tile
+    ///
+    /// They are part of the hash by their address, so they are kept alive for as long
+    /// as the hash is in use.
+    #[allow(dead_code)] // Never read, only kept alive
+    textures: Vec<Arc<Texture>>,
+}
+
+/// A tile that needs to be rasterized
+struct TileJob {
+    /// The area of the tile, in device pixels
+    area: Rectangle<usize>,
+    contents: TileContents,
+
+    /// The indices of the layers that overlap the tile, in paint order
+    layers: Vec<usize>,
+}
+
+impl TileJob {
+    fn rasterize(&self, layers: &[Option<RasterizedLayer>], background: Rgbaf32) -> Texture {
+        let mut pixels = Texture::new(self.area.width(), self.area.height());
+        pixels.clear(background);
+
+        for layer in self
+            .layers
+            .iter()
+            .filter_map(|&index| layers[index].as_ref())
+        {
+            layer.compose_onto(&mut pixels, self.area);
+        }
+
+        pixels
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CachedTile {
+    pixels: Texture,
+    contents: TileContents,
+
+    /// The most recent frame in which the tile was used
+    last_used: u64,
+}
+
+/// Remembers what was drawn to the tiles of a render target
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TileCache {
+    /// The contents of every tile of the render target as it was last drawn, in row-major order
+    drawn: Vec<Option<TileContents>>,
+
+    /// Like [Self::drawn], for the render target that was used before the current one
+    previously_drawn: Vec<Option<TileContents>>,
+    width_in_tiles: usize,
+
+    /// Recently rasterized tiles, by their hash
+    tiles: HashMap<u64, CachedTile>,
+
+    /// Incremented on every render, used to find the least recently used tiles
+    frame: u64,
+}
+
+impl TileCache {
+    /// Forget what was drawn to the render target, because its size changed
+    pub(crate) fn reset(&mut self, width: usize, height: usize) {
+        self.width_in_tiles = width.div_ceil(TILE_SIZE);
+        self.drawn = vec![None; self.width_in_tiles * height.div_ceil(TILE_SIZE)];
+        self.previously_drawn = self.drawn.clone();
+    }
+
+    /// Continue with the render target that was used before the current one
+    ///
+    /// If `retained` is `false`, the new render target is a different texture whose
+    /// contents are unknown.
+    pub(crate) fn swap_targets(&mut self, retained: bool) {
+        mem::swap(&mut self.drawn, &mut self.previously_drawn);
+
+        if !retained {
+            self.drawn.fill(None);
+        }
+    }
+
+    fn index_of(&self, tile: Rectangle<usize>) -> usize {
+        (tile.top_left().y / TILE_SIZE) * self.width_in_tiles + tile.top_left().x / TILE_SIZE
+    }
+
+    /// Redraw the given tiles of the render target, if their contents changed since they were last drawn
+    ///
+    /// `layers` must be in paint order.
+    pub(crate) fn render(
+        &mut self,
+        target: &mut Texture,
+        dirty_tiles: &[Rectangle<usize>],
+        layers: &[PreparedLayer<'_>],
+        background: Rgbaf32,
+    ) {
+        self.frame += 1;
+
+        let mut jobs = vec![];
+        for &tile in dirty_tiles {
+            let visible_layers: Vec<usize> = layers
+                .iter()
+                .enumerate()
+                .filter(|(_, layer)| layer.pixel_area.intersects(tile))
+                .map(|(index, _)| index)
+                .collect();
+
+            let mut hasher = DefaultHasher::new();
+            tile.width().hash(&mut hasher);
+            tile.height().hash(&mut hasher);
+            hasher.write(cast_slice(&[background]));
+            for &index in &visible_layers {
+                let layer = &layers[index];
+                layer.content_hash.hash(&mut hasher);
+                (layer.pixel_area.top_left().x as isize - tile.top_left().x as isize)
+                    .hash(&mut hasher);
+                (layer.pixel_area.top_left().y as isize - tile.top_left().y as isize)
+                    .hash(&mut hasher);
+            }
+            let hash = hasher.finish();
+
+            let index = self.index_of(tile);
+            if self.drawn[index]
+                .as_ref()
+                .is_some_and(|drawn| drawn.hash == hash)
+            {
+                continue;
+            }
+
+            if let Some(cached) = self.tiles.get_mut(&hash) {
+                cached.last_used = self.frame;
+                copy_tile(target, tile, &cached.pixels);
+                self.drawn[index] = Some(cached.contents.clone());
+                continue;
+            }
+
+            let textures = visible_layers
+                .iter()
+                .filter_map(|&index| match &layers[index].layer.source {
+                    Source::Solid(_) => None,
+                    Source::Texture { texture, .. } => Some(texture.clone()),
+                })
+                .collect();
+            jobs.push(TileJob {
+                area: tile,
+                contents: TileContents { hash, textures },
+                layers: visible_layers,
+            });
+        }
+
+        if jobs.is_empty() {
+            return;
+        }
+
+        // Every layer is rasterized once, even if it spans multiple tiles
+        let mut is_needed = vec![false; layers.len()];
+        for job in &jobs {
+            for &index in &job.layers {
+                is_needed[index] = true;
+            }
+        }
+        let needed_layers: Vec<usize> = (0..layers.len())
+            .filter(|&index| is_needed[index])
+            .collect();
+
+        let mut rasterized_layers: Vec<Option<RasterizedLayer>> =
+            layers.iter().map(|_| None).collect();
+        let masks = parallel_map(&needed_layers, |&index| {
+            RasterizedLayer::new(&layers[index])
+        });
+        for (index, mask) in needed_layers.into_iter().zip(masks) {
+            rasterized_layers[index] = Some(mask);
+        }
+
+        let tiles = parallel_map(&jobs, |job| job.rasterize(&rasterized_layers, background));
+        for (job, pixels) in jobs.into_iter().zip(tiles) {
+            copy_tile(target, job.area, &pixels);
+
+            let index = self.index_of(job.area);
+            self.drawn[index] = Some(job.contents.clone());
+            self.tiles.insert(
+                job.contents.hash,
+                CachedTile {
+                    pixels,
+                    contents: job.contents,
+                    last_used: self.frame,
+                },
+            );
+        }
+
+        self.evict_unused_tiles();
+    }
+
+    fn evict_unused_tiles(&mut self) {
+        if self.tiles.len() <= MAX_CACHED_TILES {
+            return;
+        }
+
+        let mut by_age: Vec<(u64, u64)> = self
+            .tiles
+            .iter()
+            .map(|(hash, tile)| (tile.last_used, *hash))
+            .collect();
+        by_age.sort_unstable();
+
+        let excess = self.tiles.len() - MAX_CACHED_TILES;
+        for (_, hash) in &by_age[..excess] {
+            self.tiles.remove(hash);
+        }
+    }
+}
+
+fn copy_tile(target: &mut Texture, area: Rectangle<usize>, pixels: &Texture) {
+    for y in 0..area.height() {
+        for x in 0..area.width() {
+            target.set_pixel(
+                area.top_left().x + x,
+                area.top_left().y + y,
+                pixels.get_pixel(x, y),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Path;
+
+    fn square(position: Vec2D, color: Color) -> Layer {
+        let mut layer = Layer::default();
+        layer
+            .with_source(Source::Solid(color))
+            .with_outline(Path::rect(position, position + Vec2D::new(10., 10.)));
+        layer
+    }
+
+    fn render(cache: &mut TileCache, target: &mut Texture, layers: &mut [Layer]) {
+        let tiles = [
+            Rectangle::from_corners(Vec2D::new(0, 0), Vec2D::new(TILE_SIZE, TILE_SIZE)),
+            Rectangle::from_corners(
+                Vec2D::new(TILE_SIZE, 0),
+                Vec2D::new(2 * TILE_SIZE, TILE_SIZE),
+            ),
+        ];
+        let layers: Vec<PreparedLayer<'_>> =
+            layers.iter_mut().filter_map(PreparedLayer::new).collect();
+        cache.render(target, &tiles, &layers, Rgbaf32::rgb(1., 1., 1.));
+    }
+
+    #[test]
+    fn reuse_tiles_with_same_contents() {
+        let mut cache = TileCache::default();
+        cache.reset(2 * TILE_SIZE, TILE_SIZE);
+        let mut target = Texture::new(2 * TILE_SIZE, TILE_SIZE);
+
+        let red = Rgbaf32::rgb(1., 0., 0.);
+        let mut layers = [square(Vec2D::new(10., 10.), Color::RED)];
+        render(&mut cache, &mut target, &mut layers);
+        assert_eq!(target.get_pixel(15, 15), red);
+        assert_eq!(cache.tiles.len(), 2);
+
+        // Moving the square by exactly one tile produces the same tiles, in a different order
+        let mut layers = [square(Vec2D::new(TILE_SIZE as f32 + 10., 10.), Color::RED)];
+        render(&mut cache, &mut target, &mut layers);
+        assert_eq!(target.get_pixel(15, 15), Rgbaf32::rgb(1., 1., 1.));
+        assert_eq!(target.get_pixel(TILE_SIZE + 15, 15), red);
+        assert_eq!(cache.tiles.len(), 2);
+
+        // New contents are rasterized
+        let mut layers = [square(Vec2D::new(10., 10.), Color::BLUE)];
+        render(&mut cache, &mut target, &mut layers);
+        assert_eq!(target.get_pixel(15, 15), Rgbaf32::rgb(0., 0., 1.));
+        assert_eq!(cache.tiles.len(), 3);
+    }
+
+    #[test]
+    fn identify_textures_by_address() {
+        let mut cache = TileCache::default();
+        cache.reset(2 * TILE_SIZE, TILE_SIZE);
+        let mut target = Texture::new(2 * TILE_SIZE, TILE_SIZE);
+
+        let mut pixels = Texture::new(1, 1);
+        pixels.clear(Rgbaf32::rgb(0., 1., 0.));
+        let texture = Arc::new(pixels);
+        let mut layer = square(Vec2D::new(10., 10.), Color::RED);
+        layer.with_source(Source::Texture {
+            texture: texture.clone(),
+            access_mode: image::AccessMode::Clamp,
+        });
+        render(&mut cache, &mut target, &mut [layer.clone()]);
+        assert_eq!(target.get_pixel(15, 15), Rgbaf32::rgb(0., 1., 0.));
+
+        // The texture is kept alive, so its address cannot be reused by another texture
+        assert!(Arc::strong_count(&texture) > 2);
+
+        // Modifying the texture creates a copy, which is drawn again
+        let mut modified = texture.clone();
+        Arc::make_mut(&mut modified).clear(Rgbaf32::rgb(0., 0., 1.));
+        layer.with_source(Source::Texture {
+            texture: modified,
+            access_mode: image::AccessMode::Clamp,
+        });
+        render(&mut cache, &mut target, &mut [layer]);
+        assert_eq!(target.get_pixel(15, 15), Rgbaf32::rgb(0., 0., 1.));
+    }
+
+    #[test]
+    fn alternate_between_targets() {
+        let mut cache = TileCache::default();
+        cache.reset(2 * TILE_SIZE, TILE_SIZE);
+        let mut first_target = Texture::new(2 * TILE_SIZE, TILE_SIZE);
+        let mut second_target = Texture::new(2 * TILE_SIZE, TILE_SIZE);
+
+        let (red, white) = (Rgbaf32::rgb(1., 0., 0.), Rgbaf32::rgb(1., 1., 1.));
+        let mut layers = [square(Vec2D::new(10., 10.), Color::RED)];
+        render(&mut cache, &mut first_target, &mut layers);
+
+        cache.swap_targets(false);
+        let mut layers = [square(Vec2D::new(TILE_SIZE as f32 + 10., 10.), Color::RED)];
+        render(&mut cache, &mut second_target, &mut layers);
+        assert_eq!(second_target.get_pixel(15, 15), white);
+        assert_eq!(second_target.get_pixel(TILE_SIZE + 15, 15), red);
+
+        // The first target still shows the square at its old position
+        cache.swap_targets(true);
+        render(&mut cache, &mut first_target, &mut layers);
+        assert_eq!(first_target.get_pixel(15, 15), white);
+        assert_eq!(first_target.get_pixel(TILE_SIZE + 15, 15), red);
+    }
+}
@@ -0,0 +1,147 @@
+//! Threads that rasterize in parallel
+//!
+//! The threads are started once and shared by every render, instead of
+//! spawning new threads for each frame.
+
+use std::{
+    iter, mem,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, LazyLock, Mutex},
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+static WORKERS: LazyLock<Workers> = LazyLock::new(Workers::start);
+
+struct Workers {
+    /// Jobs are picked up by whichever thread is idle
+    sender: mpsc::Sender<Job>,
+
+    /// The number of threads that were started
+    num_threads: usize,
+}
+
+impl Workers {
+    fn start() -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        // The thread that hands out the work takes part in it as well
+        let parallelism = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let mut num_threads = 0;
+        for index in 1..parallelism {
+            let receiver = receiver.clone();
+            let result = thread::Builder::new()
+                .name(format!("Rasterizer {index}"))
+                .spawn(move || loop {
+                    let job = receiver.lock().expect("job queue lock is poisoned").recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(mpsc::RecvError) => break,
+                    }
+                });
+
+            if let Err(error) = result {
+                log::warn!("Failed to start rasterizer thread: {error}");
+                break;
+            }
+            num_threads += 1;
+        }
+
+        Self {
+            sender,
+            num_threads,
+        }
+    }
+}
+
+/// Call `f` for every item, spread across all available cores
+///
+/// The results are in the same order as the items.
+pub(crate) fn parallel_map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    let workers = &*WORKERS;
+    if workers.num_threads == 0 || items.len() <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk_size = items.len().div_ceil(workers.num_threads + 1);
+    let mut chunks = items.chunks(chunk_size);
+    let own_chunk = chunks.next().expect("there is at least one item");
+
+    let f = &f;
+    let (sender, receiver) = mpsc::channel();
+    let mut num_jobs = 0;
+    for (index, chunk) in chunks.enumerate() {
+        let sender = sender.clone();
+        let job: Box<dyn FnOnce() + Send + '_> = Box::new(move || {
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| chunk.iter().map(f).collect::<Vec<U>>()));
+            _ = sender.send((index, result));
+        });
+
+        // SAFETY: The job borrows `items` and `f`. This function neither returns nor unwinds
+        //         before every job reported its result, and jobs catch panics so they always
+        //         report one. Therefore, the borrows outlive the job.
+        let job: Job = unsafe { mem::transmute(job) };
+        if let Err(mpsc::SendError(job)) = workers.sender.send(job) {
+            job();
+        }
+        num_jobs += 1;
+    }
+    drop(sender);
+
+    let own_result = panic::catch_unwind(AssertUnwindSafe(|| {
+        own_chunk.iter().map(f).collect::<Vec<U>>()
+    }));
+
+    let mut results: Vec<_> = iter::repeat_with(|| None).take(num_jobs).collect();
+    for _ in 0..num_jobs {
+        let (index, result) = receiver.recv().expect("every job reports its result");
+        results[index] = Some(result);
+    }
+
+    // All jobs are done, so panics can be propagated
+    iter::once(own_result)
+        .chain(results.into_iter().flatten())
+        .flat_map(|result| result.unwrap_or_else(|payload| panic::resume_unwind(payload)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_in_order() {
+        let items: Vec<usize> = (0..1000).collect();
+
+        // The same threads are used again
+        for _ in 0..3 {
+            let squares = parallel_map(&items, |item| item * item);
+            assert!(squares
+                .iter()
+                .enumerate()
+                .all(|(item, &square)| square == item * item));
+        }
+    }
+
+    #[test]
+    fn propagate_panics() {
+        let items: Vec<usize> = (0..100).collect();
+
+        let result = panic::catch_unwind(|| {
+            parallel_map(&items, |&item| {
+                assert_ne!(item, 99, "last item");
+                item
+            })
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,493 @@
+//! HTTP authentication
+//!
+//! Supports the [Basic](https://www.rfc-editor.org/rfc/rfc7617) and
+//! [Digest](https://www.rfc-editor.org/rfc/rfc7616) schemes.
+
+use std::{fmt, fmt::Write, sync::Arc};
+
+use hash::{Hasher, Md5, Sha256};
+use sl_std::{
+    base64,
+    rand::{ChaChaRng, Rng},
+};
+use url::URL;
+
+/// Whether we are authenticating with the origin server or with a proxy
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AuthenticationTarget {
+    /// Challenges are sent in `WWW-Authenticate`
+    Server,
+
+    /// Challenges are sent in `Proxy-Authenticate`
+    Proxy,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Information that can be displayed to the user when asking for [Credentials]
+#[derive(Clone, Debug)]
+pub struct AuthenticationRequest {
+    pub url: URL,
+    pub target: AuthenticationTarget,
+
+    /// Describes the protection space, as chosen by the server
+    pub realm: Option<String>,
+
+    /// Whether credentials were already sent with this request and rejected
+    pub previous_attempt_failed: bool,
+}
+
+/// Asks the user for [Credentials]
+///
+/// Returning `None` cancels the authentication.
+#[derive(Clone)]
+pub struct CredentialPrompt(Arc<PromptFn>);
+
+type PromptFn = dyn Fn(&AuthenticationRequest) -> Option<Credentials> + Send + Sync;
+
+/// A single challenge from a `WWW-Authenticate` or `Proxy-Authenticate` header
+///
+/// <https://httpwg.org/specs/rfc9110.html#challenge.and.response>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Challenge {
+    scheme: String,
+    parameters: Vec<(String, String)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DigestAlgorithm {
+    Md5,
+    Sha256,
+}
+
+impl Credentials {
+    #[must_use]
+    pub fn new(username: String, password: String) -> Self {
+        Self { username, password }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Don't leak the password into logs
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CredentialPrompt {
+    pub fn new<F>(prompt: F) -> Self
+    where
+        F: Fn(&AuthenticationRequest) -> Option<Credentials> + Send + Sync + 'static,
+    {
+        Self(Arc::new(prompt))
+    }
+
+    #[must_use]
+    pub fn ask(&self, request: &AuthenticationRequest) -> Option<Credentials> {
+        (self.0)(request)
+    }
+}
+
+impl fmt::Debug for CredentialPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CredentialPrompt").finish_non_exhaustive()
+    }
+}
+
+impl DigestAlgorithm {
+    fn from_name(name: &str) -> Option<(Self, bool)> {
+        let (name, is_session_variant) = match name.len().checked_sub(5) {
+            Some(i) if name.is_char_boundary(i) && name[i..].eq_ignore_ascii_case("-sess") => {
+                (&name[..i], true)
+            },
+            _ => (name, false),
+        };
+
+        let algorithm = if name.eq_ignore_ascii_case("MD5") {
+            Self::Md5
+        } else if name.eq_ignore_ascii_case("SHA-256") {
+            Self::Sha256
+        } else {
+            return None;
+        };
+
+        Some((algorithm, is_session_variant))
+    }
+
+    fn hash_to_hex(&self, data: &str) -> String {
+        let mut hasher: Box<dyn Hasher> = match self {
+            Self::Md5 => Box::<Md5>::default(),
+            Self::Sha256 => Box::<Sha256>::default(),
+        };
+        hasher.update(data.as_bytes());
+
+        let mut hex = String::with_capacity(2 * hasher.output_size());
+        for byte in hasher.finalize() {
+            write!(hex, "{byte:02x}").expect("writing to a string cannot fail");
+        }
+        hex
+    }
+}
+
+impl Challenge {
+    #[must_use]
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Return the value of the parameter with the given name
+    ///
+    /// Parameter names are case-insensitive.
+    #[must_use]
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[must_use]
+    pub fn realm(&self) -> Option<&str> {
+        self.parameter("realm")
+    }
+
+    /// Higher values indicate more secure schemes, `None` if the scheme is not supported
+    fn strength(&self) -> Option<u8> {
+        if self.scheme.eq_ignore_ascii_case("Basic") {
+            return Some(0);
+        }
+
+        if self.scheme.eq_ignore_ascii_case("Digest") {
+            let algorithm = self.parameter("algorithm").unwrap_or("MD5");
+            return match DigestAlgorithm::from_name(algorithm)?.0 {
+                DigestAlgorithm::Md5 => Some(1),
+                DigestAlgorithm::Sha256 => Some(2),
+            };
+        }
+
+        None
+    }
+
+    /// Compute the value of the `Authorization` (or `Proxy-Authorization`) header
+    /// that answers this challenge
+    ///
+    /// `uri` is the request target, usually the path and query of the url.
+    /// Returns `None` if the challenge uses an unsupported scheme or is malformed.
+    #[must_use]
+    pub fn respond(&self, credentials: &Credentials, method: &str, uri: &str) -> Option<String> {
+        if self.scheme.eq_ignore_ascii_case("Basic") {
+            return Some(basic_authorization(credentials));
+        }
+
+        if self.scheme.eq_ignore_ascii_case("Digest") {
+            let cnonce = generate_cnonce()?;
+            return self.digest_authorization(credentials, method, uri, &cnonce);
+        }
+
+        None
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc7616#section-3.4>
+    fn digest_authorization(
+        &self,
+        credentials: &Credentials,
+        method: &str,
+        uri: &str,
+        cnonce: &str,
+    ) -> Option<String> {
+        // We only ever send a single request per nonce
+        const NONCE_COUNT: &str = "00000001";
+
+        let realm = self.realm()?;
+        let nonce = self.parameter("nonce")?;
+        let algorithm_name = self.parameter("algorithm").unwrap_or("MD5");
+        let (algorithm, is_session_variant) = DigestAlgorithm::from_name(algorithm_name)?;
+
+        // Only "auth" is supported, "auth-int" would require hashing the request body
+        let qop = match self.parameter("qop") {
+            Some(qop) => {
+                if !qop.split(',').any(|option| option.trim() == "auth") {
+                    log::warn!("Unsupported digest quality of protection: {qop:?}");
+                    return None;
+                }
+                Some("auth")
+            },
+            None => None,
+        };
+
+        let username = &credentials.username;
+        let password = &credentials.password;
+        let mut ha1 = algorithm.hash_to_hex(&format!("{username}:{realm}:{password}"));
+        if is_session_variant {
+            ha1 = algorithm.hash_to_hex(&format!("{ha1}:{nonce}:{cnonce}"));
+        }
+
+        let ha2 = algorithm.hash_to_hex(&format!("{method}:{uri}"));
+
+        let response = match qop {
+            Some(qop) => {
+                algorithm.hash_to_hex(&format!("{ha1}:{nonce}:{NONCE_COUNT}:{cnonce}:{qop}:{ha2}"))
+            },
+            None => algorithm.hash_to_hex(&format!("{ha1}:{nonce}:{ha2}")),
+        };
+
+        let mut authorization = format!(
+            "Digest username={}, realm={}, uri={}, algorithm={algorithm_name}, nonce={}, response=\"{response}\"",
+            quote(username),
+            quote(realm),
+            quote(uri),
+            quote(nonce),
+        );
+
+        if let Some(qop) = qop {
+            write!(
+                authorization,
+                ", qop={qop}, nc={NONCE_COUNT}, cnonce={}",
+                quote(cnonce)
+            )
+            .expect("writing to a string cannot fail");
+        }
+
+        if let Some(opaque) = self.parameter("opaque") {
+            write!(authorization, ", opaque={}", quote(opaque))
+                .expect("writing to a string cannot fail");
+        }
+
+        Some(authorization)
+    }
+}
+
+/// Choose the most secure challenge that we support
+#[must_use]
+pub fn select_challenge(challenges: &[Challenge]) -> Option<&Challenge> {
+    challenges
+        .iter()
+        .filter_map(|challenge| Some((challenge.strength()?, challenge)))
+        .max_by_key(|(strength, _)| *strength)
+        .map(|(_, challenge)| challenge)
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc7617#section-2>
+fn basic_authorization(credentials: &Credentials) -> String {
+    let user_pass = format!("{}:{}", credentials.username, credentials.password);
+    format!("Basic {}", base64::b64encode(user_pass.as_bytes()))
+}
+
+fn generate_cnonce() -> Option<String> {
+    let mut rng = match ChaChaRng::from_entropy() {
+        Ok(rng) => rng,
+        Err(error) => {
+            log::error!("Failed to seed random number generator: {error}");
+            return None;
+        },
+    };
+
+    let mut bytes = [0; 16];
+    rng.fill_bytes(&mut bytes);
+    Some(base64::b64encode(&bytes).to_string())
+}
+
+/// Serialize a string as a `quoted-string`
+///
+/// <https://httpwg.org/specs/rfc9110.html#quoted.strings>
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// <https://httpwg.org/specs/rfc9110.html#tokens>
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+/// Parse the value of a `WWW-Authenticate` or `Proxy-Authenticate` header
+///
+/// Challenges in `token68` form are ignored, since none of the supported schemes use them.
+#[must_use]
+pub fn parse_challenges(value: &str) -> Vec<Challenge> {
+    let mut parser = ChallengeParser { remaining: value };
+    let mut challenges = vec![];
+
+    loop {
+        parser.skip_separators();
+        let scheme = parser.token();
+        if scheme.is_empty() {
+            break;
+        }
+
+        let mut challenge = Challenge {
+            scheme: scheme.to_owned(),
+            parameters: vec![],
+        };
+
+        loop {
+            let before_parameter = parser.remaining;
+            parser.skip_separators();
+
+            let name = parser.token();
+            parser.skip_whitespace();
+
+            if name.is_empty() || !parser.consume('=') {
+                // This is the start of the next challenge
+                parser.remaining = before_parameter;
+                break;
+            }
+
+            parser.skip_whitespace();
+            let Some(value) = parser.value() else {
+                // The header is malformed, return everything that we could parse
+                challenges.push(challenge);
+                return challenges;
+            };
+
+            challenge.parameters.push((name.to_owned(), value));
+        }
+
+        challenges.push(challenge);
+    }
+
+    challenges
+}
+
+struct ChallengeParser<'a> {
+    remaining: &'a str,
+}
+
+impl<'a> ChallengeParser<'a> {
+    fn skip_whitespace(&mut self) {
+        self.remaining = self.remaining.trim_start_matches([' ', '\t']);
+    }
+
+    fn skip_separators(&mut self) {
+        self.remaining = self.remaining.trim_start_matches([' ', '\t', ',']);
+    }
+
+    fn consume(&mut self, c: char) -> bool {
+        match self.remaining.strip_prefix(c) {
+            Some(remaining) => {
+                self.remaining = remaining;
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn token(&mut self) -> &'a str {
+        let length = self
+            .remaining
+            .find(|c| !is_tchar(c))
+            .unwrap_or(self.remaining.len());
+        let (token, remaining) = self.remaining.split_at(length);
+        self.remaining = remaining;
+        token
+    }
+
+    /// Parse either a token or a quoted string
+    fn value(&mut self) -> Option<String> {
+        if !self.consume('"') {
+            let token = self.token();
+            return (!token.is_empty()).then(|| token.to_owned());
+        }
+
+        let mut value = String::new();
+        let mut chars = self.remaining.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.remaining = &self.remaining[i + 1..];
+                    return Some(value);
+                },
+                '\\' => value.push(chars.next()?.1),
+                _ => value.push(c),
+            }
+        }
+
+        // Unterminated quoted string
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_multiple_challenges() {
+        let challenges = parse_challenges(
+            r#"Newauth realm="apps", type=1, title="Login to \"apps\"", Basic realm="simple""#,
+        );
+
+        assert_eq!(challenges.len(), 2);
+        assert_eq!(challenges[0].scheme(), "Newauth");
+        assert_eq!(challenges[0].realm(), Some("apps"));
+        assert_eq!(challenges[0].parameter("TYPE"), Some("1"));
+        assert_eq!(challenges[0].parameter("title"), Some(r#"Login to "apps""#));
+        assert_eq!(challenges[1].scheme(), "Basic");
+        assert_eq!(challenges[1].realm(), Some("simple"));
+
+        assert_eq!(select_challenge(&challenges), Some(&challenges[1]));
+    }
+
+    #[test]
+    fn basic() {
+        // https://www.rfc-editor.org/rfc/rfc7617#section-2
+        let credentials = Credentials::new("Aladdin".to_string(), "open sesame".to_string());
+        assert_eq!(
+            basic_authorization(&credentials),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="
+        );
+    }
+
+    #[test]
+    fn digest() {
+        // https://www.rfc-editor.org/rfc/rfc7616#section-3.9.1
+        let header = r#"Digest
+            realm="http-auth@example.org",
+            qop="auth, auth-int",
+            algorithm=SHA-256,
+            nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+            opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS",
+        Digest
+            realm="http-auth@example.org",
+            qop="auth, auth-int",
+            algorithm=MD5,
+            nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+            opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#
+            .replace('\n', " ");
+        let challenges = parse_challenges(&header);
+        assert_eq!(challenges.len(), 2);
+        assert_eq!(select_challenge(&challenges), Some(&challenges[0]));
+
+        let credentials = Credentials::new("Mufasa".to_string(), "Circle of Life".to_string());
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+        let sha256 = challenges[0]
+            .digest_authorization(&credentials, "GET", "/dir/index.html", cnonce)
+            .unwrap();
+        assert!(sha256.contains(
+            r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#
+        ));
+
+        let md5 = challenges[1]
+            .digest_authorization(&credentials, "GET", "/dir/index.html", cnonce)
+            .unwrap();
+        assert_eq!(
+            md5,
+            r#"Digest username="Mufasa", realm="http-auth@example.org", uri="/dir/index.html", algorithm=MD5, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", response="8ca523f5e9506fed4657c9700eebdbec", qop=auth, nc=00000001, cnonce="f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#
+        );
+    }
+}
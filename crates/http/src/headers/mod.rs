@@ -36,6 +36,10 @@ impl Headers {
         self.internal.insert(header, value);
     }
 
+    pub fn remove(&mut self, header: Header) -> Option<String> {
+        self.internal.remove(&header)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&Header, &str)> {
         self.internal
             .iter()
@@ -0,0 +1,388 @@
+//! HTTP Strict Transport Security ([RFC 6797](https://www.rfc-editor.org/rfc/rfc6797))
+//!
+//! Hosts can ask to only ever be contacted over `https` by sending a `Strict-Transport-Security`
+//! header. Requests to `http` urls on such hosts are upgraded before they are sent.
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs, io,
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use serialize::{Deserialize, Serialize};
+use serialize_json::{JsonDeserializer, JsonSerializer};
+use url::{Host, URL};
+
+/// The hosts that are known to require `https`
+pub static HSTS: LazyLock<HstsStore> = LazyLock::new(HstsStore::default);
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Hosts that require `https` even if they never sent a `Strict-Transport-Security` header
+///
+/// This is a small subset of the list at <https://hstspreload.org>. All entries
+/// apply to their subdomains too.
+const PRELOADED_HOSTS: &[&str] = &["app", "dev", "page", "github.com"];
+
+/// The directives of a `Strict-Transport-Security` header
+///
+/// <https://www.rfc-editor.org/rfc/rfc6797#section-6.1>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrictTransportSecurity {
+    /// How long the host should be remembered, in seconds
+    pub max_age: u64,
+
+    pub include_subdomains: bool,
+}
+
+impl StrictTransportSecurity {
+    /// Parse the value of a `Strict-Transport-Security` header
+    ///
+    /// Returns `None` if the header is invalid, in which case it must be ignored entirely.
+    #[must_use]
+    pub fn parse(header_value: &str) -> Option<Self> {
+        let mut max_age = None;
+        let mut include_subdomains = false;
+
+        for directive in header_value.split(';') {
+            let directive = directive.trim();
+            if directive.is_empty() {
+                continue;
+            }
+
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+
+            // Directives must not appear more than once
+            if name.eq_ignore_ascii_case("max-age") {
+                if max_age.is_some() {
+                    return None;
+                }
+                max_age = Some(value?.parse().ok()?);
+            } else if name.eq_ignore_ascii_case("includesubdomains") {
+                if include_subdomains || value.is_some() {
+                    return None;
+                }
+                include_subdomains = true;
+            }
+
+            // Unknown directives (like "preload") are ignored
+        }
+
+        Some(Self {
+            max_age: max_age?,
+            include_subdomains,
+        })
+    }
+}
+
+/// A host that sent a `Strict-Transport-Security` header
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct KnownHost {
+    /// Time at which the policy expires, in seconds since the unix epoch
+    expires: u64,
+
+    include_subdomains: bool,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    known_hosts: HashMap<String, KnownHost>,
+
+    /// Where the known hosts are stored, `None` if they are not persisted
+    file: Option<PathBuf>,
+}
+
+/// The set of hosts that must only be contacted over `https`
+#[derive(Debug, Default)]
+pub struct HstsStore {
+    state: Mutex<State>,
+}
+
+impl HstsStore {
+    /// Read the known hosts from a file and persist all future changes in it
+    ///
+    /// A missing file is not an error, it is created once the first host is remembered.
+    pub fn load(&self, file: PathBuf) -> io::Result<()> {
+        let known_hosts = match fs::read_to_string(&file) {
+            Ok(contents) => {
+                let mut deserializer = JsonDeserializer::new(&contents);
+                HashMap::<String, KnownHost>::deserialize(&mut deserializer)
+                    .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(error) => return Err(error),
+        };
+
+        let mut state = self.state.lock().expect("hsts lock was poisoned");
+        state.known_hosts = known_hosts;
+        state.file = Some(file);
+
+        Ok(())
+    }
+
+    /// Whether the given host must only be contacted over `https`
+    ///
+    /// <https://www.rfc-editor.org/rfc/rfc6797#section-8.2>
+    #[must_use]
+    pub fn is_known_host(&self, host: &str) -> bool {
+        self.is_known_host_at(host, now())
+    }
+
+    fn is_known_host_at(&self, host: &str, now: u64) -> bool {
+        let host = normalize(host);
+        let state = self.state.lock().expect("hsts lock was poisoned");
+
+        // The host itself and all of its superdomains, a policy for a superdomain
+        // only applies if it includes subdomains
+        let mut domain = host.as_str();
+        let mut is_superdomain = false;
+        loop {
+            let is_known = state.known_hosts.get(domain).is_some_and(|known_host| {
+                known_host.expires > now && (!is_superdomain || known_host.include_subdomains)
+            });
+
+            if is_known || PRELOADED_HOSTS.contains(&domain) {
+                return true;
+            }
+
+            let Some((_, superdomain)) = domain.split_once('.') else {
+                return false;
+            };
+            domain = superdomain;
+            is_superdomain = true;
+        }
+    }
+
+    /// Return the `https` equivalent of an `http` url if its host is known to require `https`
+    ///
+    /// Returns `None` if the url does not need to be upgraded.
+    #[must_use]
+    pub fn upgrade(&self, url: &URL) -> Option<URL> {
+        if url.scheme().as_str() != "http" {
+            return None;
+        }
+
+        let Some(Host::Domain(host)) = url.host() else {
+            return None;
+        };
+
+        if !self.is_known_host(host.as_str()) {
+            return None;
+        }
+
+        // https://www.rfc-editor.org/rfc/rfc6797#section-8.3
+        // An explicit port 80 is changed to 443 (the default port for https), other ports are kept
+        let mut upgraded = String::from("https://");
+        if url.includes_credentials() {
+            let _ = write!(upgraded, "{}:{}@", url.username(), url.password());
+        }
+        let _ = write!(upgraded, "{host}");
+        if let Some(port) = url.port().filter(|&port| port != 80) {
+            let _ = write!(upgraded, ":{port}");
+        }
+        upgraded.push_str(url.path().as_str());
+        if let Some(query) = url.query() {
+            let _ = write!(upgraded, "?{query}");
+        }
+        if let Some(fragment) = url.fragment() {
+            let _ = write!(upgraded, "#{fragment}");
+        }
+        let upgraded: URL = upgraded.parse().ok()?;
+
+        log::info!(
+            "Upgrading {} to https, the host requires strict transport security",
+            url.serialize(url::ExcludeFragment::Yes)
+        );
+        Some(upgraded)
+    }
+
+    /// Remember the policy from a `Strict-Transport-Security` header that was received
+    /// from the given host
+    ///
+    /// The header must only be processed if it was received over a secure connection without
+    /// any certificate errors.
+    ///
+    /// <https://www.rfc-editor.org/rfc/rfc6797#section-8.1>
+    pub fn process_header(&self, host: &Host, header_value: &str) {
+        self.process_header_at(host, header_value, now());
+    }
+
+    fn process_header_at(&self, host: &Host, header_value: &str, now: u64) {
+        // Policies cannot be set for ip addresses
+        let Host::Domain(host) = host else {
+            return;
+        };
+
+        let Some(policy) = StrictTransportSecurity::parse(header_value) else {
+            log::warn!("Ignoring invalid Strict-Transport-Security header: {header_value:?}");
+            return;
+        };
+
+        let host = normalize(host.as_str());
+        let mut state = self.state.lock().expect("hsts lock was poisoned");
+
+        let previous = state.known_hosts.remove(&host);
+        let current = (policy.max_age != 0).then(|| KnownHost {
+            expires: now.saturating_add(policy.max_age),
+            include_subdomains: policy.include_subdomains,
+        });
+        if let Some(known_host) = current {
+            state.known_hosts.insert(host, known_host);
+        }
+
+        // Most responses repeat the same policy, which only moves the expiry date a little.
+        // There is no need to rewrite the file every time that happens.
+        let is_unchanged = match (previous, current) {
+            (Some(previous), Some(current)) => {
+                previous.include_subdomains == current.include_subdomains
+                    && current.expires.abs_diff(previous.expires) < SECONDS_PER_DAY
+            },
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !is_unchanged {
+            state
+                .known_hosts
+                .retain(|_, known_host| known_host.expires > now);
+
+            if let Err(error) = state.save() {
+                log::error!("Failed to save strict transport security policies: {error}");
+            }
+        }
+    }
+}
+
+impl State {
+    fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+
+        let serialized = JsonSerializer::serialize_to_string(self.known_hosts.clone())
+            .expect("Writing to a string cannot fail");
+
+        if let Some(directory) = file.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(file, serialized)
+    }
+}
+
+fn normalize(host: &str) -> String {
+    host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn domain(host: &str) -> Host {
+        Host::Domain(host.try_into().unwrap())
+    }
+
+    #[test]
+    fn parse_header() {
+        assert_eq!(
+            StrictTransportSecurity::parse("max-age=31536000; includeSubDomains; preload"),
+            Some(StrictTransportSecurity {
+                max_age: 31536000,
+                include_subdomains: true
+            })
+        );
+        assert_eq!(
+            StrictTransportSecurity::parse(" MAX-AGE=\"60\" "),
+            Some(StrictTransportSecurity {
+                max_age: 60,
+                include_subdomains: false
+            })
+        );
+
+        // max-age is required and directives must be unique
+        assert!(StrictTransportSecurity::parse("includeSubDomains").is_none());
+        assert!(StrictTransportSecurity::parse("max-age=1; max-age=2").is_none());
+        assert!(StrictTransportSecurity::parse("max-age=soon").is_none());
+    }
+
+    #[test]
+    fn known_hosts() {
+        let store = HstsStore::default();
+
+        store.process_header_at(&domain("example.com"), "max-age=100", 0);
+        store.process_header_at(&domain("secure.org"), "max-age=100; includeSubDomains", 0);
+        store.process_header_at(&Host::Ip([127, 0, 0, 1].into()), "max-age=100", 0);
+
+        assert!(store.is_known_host_at("Example.com.", 50));
+        assert!(!store.is_known_host_at("www.example.com", 50));
+        assert!(store.is_known_host_at("www.secure.org", 50));
+        assert!(!store.is_known_host_at("127.0.0.1", 50));
+
+        // Policies expire
+        assert!(!store.is_known_host_at("example.com", 100));
+
+        // A max-age of zero removes the policy
+        store.process_header_at(&domain("secure.org"), "max-age=0", 50);
+        assert!(!store.is_known_host_at("secure.org", 50));
+
+        // Preloaded hosts do not need to send a header
+        assert!(store.is_known_host_at("docs.github.com", 0));
+        assert!(store.is_known_host_at("web.dev", 0));
+    }
+
+    #[test]
+    fn upgrade_url() {
+        let store = HstsStore::default();
+        let upgrade = |url: &str| {
+            store
+                .upgrade(&url.parse().unwrap())
+                .map(|url| url.serialize(url::ExcludeFragment::No).to_string())
+        };
+
+        assert_eq!(
+            upgrade("http://github.com/search?q=http:").as_deref(),
+            Some("https://github.com/search?q=http:")
+        );
+        assert_eq!(
+            upgrade("http://github.com:80/").as_deref(),
+            Some("https://github.com/")
+        );
+        assert_eq!(upgrade("https://github.com/"), None);
+        assert_eq!(upgrade("http://example.com/"), None);
+
+        let upgraded = store
+            .upgrade(&"http://github.com:8080/".parse().unwrap())
+            .unwrap();
+        assert_eq!(upgraded.scheme().as_str(), "https");
+        assert_eq!(upgraded.port(), Some(8080));
+    }
+
+    #[test]
+    fn persist_known_hosts() {
+        let file = env::temp_dir().join(format!("stormlicht-hsts-{}.json", std::process::id()));
+
+        let store = HstsStore::default();
+        store.load(file.clone()).unwrap();
+        store.process_header(&domain("example.com"), "max-age=100");
+
+        let loaded = HstsStore::default();
+        loaded.load(file.clone()).unwrap();
+        assert!(loaded.is_known_host("example.com"));
+
+        fs::remove_file(file).unwrap();
+    }
+}
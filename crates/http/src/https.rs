@@ -1,13 +1,119 @@
 use std::{
+    collections::BTreeSet,
+    fmt, io,
     net::TcpStream,
-    sync::{Arc, OnceLock},
+    sync::{Arc, Mutex, OnceLock},
 };
 
-use crate::request::HTTPError;
+use rustls::{
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        WebPkiServerVerifier,
+    },
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    DigitallySignedStruct, SignatureScheme,
+};
+
+use crate::{request::HTTPError, HSTS};
 
 static CERTIFICATE_STORE: OnceLock<Arc<rustls::RootCertStore>> = OnceLock::new();
+static CERTIFICATE_VERIFIER: OnceLock<Arc<WebPkiServerVerifier>> = OnceLock::new();
+
+/// Hosts whose certificates are accepted even if they are invalid
+///
+/// Exceptions are only added after the user explicitly chose to ignore a certificate error
+/// and are not persisted.
+pub static CERTIFICATE_EXCEPTIONS: CertificateExceptions = CertificateExceptions {
+    hosts: Mutex::new(BTreeSet::new()),
+};
+
+pub(crate) const TLS_PORT: u16 = 443;
+
+/// A set of hosts for which certificate validation errors are ignored
+#[derive(Debug)]
+pub struct CertificateExceptions {
+    hosts: Mutex<BTreeSet<String>>,
+}
+
+impl CertificateExceptions {
+    /// Accept invalid certificates from the given host for the rest of the session
+    pub fn add(&self, host: &str) {
+        log::warn!("Adding certificate exception for {host}");
+
+        self.hosts
+            .lock()
+            .expect("certificate exceptions lock was poisoned")
+            .insert(host.to_ascii_lowercase());
+    }
+
+    #[must_use]
+    pub fn contains(&self, host: &str) -> bool {
+        self.hosts
+            .lock()
+            .expect("certificate exceptions lock was poisoned")
+            .contains(&host.to_ascii_lowercase())
+    }
+}
+
+/// Describes why the certificate of a server was rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CertificateProblem {
+    Expired,
+    NotValidYet,
+
+    /// The certificate does not belong to the host that was contacted
+    HostnameMismatch,
+
+    /// The certificate was not issued by a trusted certificate authority
+    UnknownIssuer,
+    Revoked,
+
+    /// Any other problem, like an invalid signature or encoding
+    Invalid,
+}
+
+/// The certificate presented by a server could not be verified
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateError {
+    pub host: String,
+    pub problem: CertificateProblem,
+}
+
+impl From<&rustls::CertificateError> for CertificateProblem {
+    fn from(value: &rustls::CertificateError) -> Self {
+        match value {
+            rustls::CertificateError::Expired => Self::Expired,
+            rustls::CertificateError::NotValidYet => Self::NotValidYet,
+            rustls::CertificateError::NotValidForName => Self::HostnameMismatch,
+            rustls::CertificateError::UnknownIssuer => Self::UnknownIssuer,
+            rustls::CertificateError::Revoked => Self::Revoked,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+impl fmt::Display for CertificateProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Self::Expired => "certificate has expired",
+            Self::NotValidYet => "certificate is not valid yet",
+            Self::HostnameMismatch => "certificate is not valid for this host",
+            Self::UnknownIssuer => "certificate was issued by an unknown authority",
+            Self::Revoked => "certificate has been revoked",
+            Self::Invalid => "certificate is invalid",
+        };
+
+        description.fmt(f)
+    }
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.problem, self.host)
+    }
+}
 
-const TLS_PORT: u16 = 443;
+impl std::error::Error for CertificateError {}
 
 fn root_certificates() -> Arc<rustls::RootCertStore> {
     CERTIFICATE_STORE
@@ -20,18 +126,164 @@ fn root_certificates() -> Arc<rustls::RootCertStore> {
         .clone()
 }
 
+fn webpki_verifier() -> Arc<WebPkiServerVerifier> {
+    CERTIFICATE_VERIFIER
+        .get_or_init(|| {
+            WebPkiServerVerifier::builder(root_certificates())
+                .build()
+                .expect("root certificate store is not empty")
+        })
+        .clone()
+}
+
+/// Verifies certificates like [WebPkiServerVerifier], but can be told to accept
+/// invalid certificates
+///
+/// Handshake signatures are always verified.
+#[derive(Debug)]
+struct Verifier {
+    inner: Arc<WebPkiServerVerifier>,
+    accept_invalid_certificates: bool,
+}
+
+impl ServerCertVerifier for Verifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let result = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        );
+
+        match result {
+            Err(rustls::Error::InvalidCertificate(error)) if self.accept_invalid_certificates => {
+                log::warn!("Ignoring invalid certificate for {server_name:?}: {error:?}");
+                Ok(ServerCertVerified::assertion())
+            },
+            other => other,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
+
+/// Perform a TLS handshake over an existing connection
+///
+/// The connection may either go directly to the server or through a proxy tunnel.
 pub(crate) fn establish_connection(
+    socket: TcpStream,
     domain_name: String,
-    port: Option<u16>,
 ) -> Result<rustls::StreamOwned<rustls::ClientConnection, TcpStream>, HTTPError> {
-    let socket = TcpStream::connect((domain_name.as_str(), port.unwrap_or(TLS_PORT)))?;
-    let server_name = rustls::pki_types::ServerName::try_from(domain_name).expect("invalid domain");
+    // Certificate errors on hosts that require strict transport security cannot be ignored
+    // https://www.rfc-editor.org/rfc/rfc6797#section-8.4
+    let verifier = Verifier {
+        inner: webpki_verifier(),
+        accept_invalid_certificates: CERTIFICATE_EXCEPTIONS.contains(&domain_name)
+            && !HSTS.is_known_host(&domain_name),
+    };
 
     let config = rustls::ClientConfig::builder()
-        .with_root_certificates(root_certificates())
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
         .with_no_client_auth();
 
+    let server_name = ServerName::try_from(domain_name.clone()).expect("invalid domain");
     let client = rustls::ClientConnection::new(Arc::new(config), server_name)?;
-    let stream = rustls::StreamOwned::new(client, socket);
+    let mut stream = rustls::StreamOwned::new(client, socket);
+
+    // Complete the handshake right away, so certificate problems are not reported as generic io errors
+    while stream.conn.is_handshaking() {
+        stream
+            .conn
+            .complete_io(&mut stream.sock)
+            .map_err(|error| handshake_error(error, &domain_name))?;
+    }
+
     Ok(stream)
 }
+
+fn handshake_error(error: io::Error, host: &str) -> HTTPError {
+    let tls_error = error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<rustls::Error>());
+
+    match tls_error {
+        Some(rustls::Error::InvalidCertificate(certificate_error)) => {
+            HTTPError::Certificate(CertificateError {
+                host: host.to_string(),
+                problem: certificate_error.into(),
+            })
+        },
+        Some(tls_error) => HTTPError::Tls(tls_error.clone()),
+        None => HTTPError::IO(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_exceptions() {
+        let exceptions = CertificateExceptions {
+            hosts: Mutex::new(BTreeSet::new()),
+        };
+
+        assert!(!exceptions.contains("example.com"));
+        exceptions.add("Example.com");
+        assert!(exceptions.contains("example.com"));
+        assert!(!exceptions.contains("www.example.com"));
+    }
+
+    #[test]
+    fn classify_handshake_error() {
+        let error = io::Error::new(
+            io::ErrorKind::InvalidData,
+            rustls::Error::InvalidCertificate(rustls::CertificateError::NotValidForName),
+        );
+
+        let HTTPError::Certificate(certificate_error) = handshake_error(error, "example.com")
+        else {
+            panic!("expected a certificate error");
+        };
+        assert_eq!(certificate_error.host, "example.com");
+        assert_eq!(
+            certificate_error.problem,
+            CertificateProblem::HostnameMismatch
+        );
+
+        let error = io::Error::from(io::ErrorKind::ConnectionReset);
+        assert!(matches!(
+            handshake_error(error, "example.com"),
+            HTTPError::IO(_)
+        ));
+    }
+}
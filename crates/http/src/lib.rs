@@ -2,13 +2,21 @@
 //!
 //! [Specifications](https://developer.mozilla.org/en-US/docs/Web/HTTP/Resources_and_specifications)
 
+pub mod authentication;
 mod headers;
+pub mod hsts;
 mod https;
+pub mod proxy;
 pub mod request;
 mod response;
 mod status_code;
 
 pub use headers::{Header, Headers};
+pub use hsts::HSTS;
+pub use https::{
+    CertificateError, CertificateExceptions, CertificateProblem, CERTIFICATE_EXCEPTIONS,
+};
+pub use proxy::ProxyConfig;
 pub use request::Request;
-pub use response::Response;
+pub use response::{BodySink, Response};
 pub use status_code::StatusCode;
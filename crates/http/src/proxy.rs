@@ -0,0 +1,282 @@
+//! Proxy configuration and `CONNECT` tunneling
+//!
+//! Environment variables are interpreted like [curl](https://everything.curl.dev/usingcurl/proxies/env.html) does.
+
+use std::{
+    env,
+    io::{self, BufReader, Write},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream},
+};
+
+use url::{Host, URL};
+
+use crate::{
+    request::{HTTPError, HTTP_NEWLINE, USER_AGENT},
+    response, Header, Headers, StatusCode,
+};
+
+/// The port used by proxies if none is specified
+const DEFAULT_PROXY_PORT: u16 = 1080;
+
+/// Describes which proxy should be used for a request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// Proxy for `http` requests
+    pub http: Option<SocketAddr>,
+
+    /// Proxy for `https` requests, which are tunneled through it with `CONNECT`
+    pub https: Option<SocketAddr>,
+
+    /// Hosts that should always be contacted directly
+    ///
+    /// An entry matches the host itself and all of its subdomains.
+    /// The special entry `*` matches all hosts.
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Connect to all hosts directly
+    pub const DIRECT: Self = Self {
+        http: None,
+        https: None,
+        no_proxy: Vec::new(),
+    };
+
+    /// Use the same proxy for all requests
+    #[must_use]
+    pub fn for_all_schemes(proxy: SocketAddr) -> Self {
+        Self {
+            http: Some(proxy),
+            https: Some(proxy),
+            no_proxy: vec![],
+        }
+    }
+
+    /// Read the configuration from the `http_proxy`, `https_proxy` and `no_proxy`
+    /// environment variables
+    ///
+    /// Like curl, only the lowercase version of `http_proxy` is respected, since
+    /// `HTTP_PROXY` may be set by CGI scripts.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let read_variable = |names: &[&str]| names.iter().find_map(|name| env::var(name).ok());
+        let read_proxy = |names: &[&str]| {
+            let value = read_variable(names)?;
+            let proxy = parse_proxy_address(&value);
+
+            if proxy.is_none() {
+                log::warn!("Ignoring invalid proxy {value:?} from the environment");
+            }
+
+            proxy
+        };
+
+        Self {
+            http: read_proxy(&["http_proxy"]),
+            https: read_proxy(&["https_proxy", "HTTPS_PROXY"]),
+            no_proxy: read_variable(&["no_proxy", "NO_PROXY"])
+                .map(|value| parse_no_proxy(&value))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Find the proxy that should be used to load the given [URL]
+    #[must_use]
+    pub fn proxy_for(&self, url: &URL) -> Option<SocketAddr> {
+        let proxy = match url.scheme().as_str() {
+            "http" => self.http?,
+            "https" => self.https?,
+            _ => return None,
+        };
+
+        if url.host().is_some_and(|host| self.bypasses(host)) {
+            return None;
+        }
+
+        Some(proxy)
+    }
+
+    fn bypasses(&self, host: &Host) -> bool {
+        let host = host.to_string();
+
+        self.no_proxy.iter().any(|entry| {
+            if entry == "*" {
+                return true;
+            }
+
+            let entry = entry.strip_prefix('.').unwrap_or(entry);
+            host.eq_ignore_ascii_case(entry)
+                || (host.len() > entry.len()
+                    && host.as_bytes()[host.len() - entry.len() - 1] == b'.'
+                    && host[host.len() - entry.len()..].eq_ignore_ascii_case(entry))
+        })
+    }
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self::DIRECT
+    }
+}
+
+/// Split a comma-separated list of hosts, like the value of `no_proxy`
+#[must_use]
+pub fn parse_no_proxy(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Parse a proxy address like `127.0.0.1:8080` or `http://proxy.example:3128`
+///
+/// Domain names are resolved immediately.
+#[must_use]
+pub fn parse_proxy_address(proxy: &str) -> Option<SocketAddr> {
+    let proxy = proxy.trim();
+    let url: URL = if proxy.contains("://") {
+        proxy.parse().ok()?
+    } else {
+        format!("http://{proxy}").parse().ok()?
+    };
+
+    if url.scheme().as_str() != "http" {
+        log::warn!("Unsupported proxy scheme: {}", url.scheme());
+        return None;
+    }
+
+    let ip = match url.host()? {
+        Host::Ip(ip) => *ip,
+        Host::Domain(domain) if domain.as_str() == "localhost" => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        Host::Domain(domain) => dns::Domain::new(domain.as_str()).lookup().ok()?,
+        Host::OpaqueHost(_) | Host::EmptyHost => return None,
+    };
+
+    Some(SocketAddr::new(
+        ip,
+        url.port().unwrap_or(DEFAULT_PROXY_PORT),
+    ))
+}
+
+pub(crate) enum Tunnel {
+    Established(TcpStream),
+
+    /// The proxy requires authentication, contains the response headers
+    AuthenticationRequired(Headers),
+}
+
+/// Ask the proxy to open a tunnel to the given host
+///
+/// <https://httpwg.org/specs/rfc9110.html#CONNECT>
+pub(crate) fn establish_tunnel(
+    proxy: SocketAddr,
+    host: &Host,
+    port: u16,
+    proxy_authorization: Option<&str>,
+) -> Result<Tunnel, HTTPError> {
+    log::info!("Tunneling connection to {host}:{port} via {proxy}");
+
+    let mut stream = TcpStream::connect(proxy)?;
+    write_connect_request(&mut stream, host, port, proxy_authorization)?;
+
+    // The server won't send anything until we start the TLS handshake, so the reader
+    // cannot buffer any data beyond the response head
+    let mut reader = BufReader::new(stream);
+    let (status, headers) = response::receive_head(&mut reader)?;
+
+    if status == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+        return Ok(Tunnel::AuthenticationRequired(headers));
+    }
+
+    if !status.is_success() {
+        log::warn!("Proxy refused to open a tunnel: {status:?}");
+        return Err(HTTPError::TunnelRefused);
+    }
+
+    Ok(Tunnel::Established(reader.into_inner()))
+}
+
+fn write_connect_request<W: Write>(
+    mut writer: W,
+    host: &Host,
+    port: u16,
+    proxy_authorization: Option<&str>,
+) -> io::Result<()> {
+    write!(writer, "CONNECT {host}:{port} HTTP/1.1{HTTP_NEWLINE}")?;
+    write!(
+        writer,
+        "{}: {host}:{port}{HTTP_NEWLINE}",
+        Header::HOST.as_str()
+    )?;
+    write!(
+        writer,
+        "{}: {USER_AGENT}{HTTP_NEWLINE}",
+        Header::USER_AGENT.as_str()
+    )?;
+    if let Some(proxy_authorization) = proxy_authorization {
+        write!(
+            writer,
+            "{}: {proxy_authorization}{HTTP_NEWLINE}",
+            Header::PROXY_AUTHORIZATION.as_str()
+        )?;
+    }
+    write!(writer, "{HTTP_NEWLINE}")?;
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proxy() {
+        let expected = Some(SocketAddr::from(([127, 0, 0, 1], 8080)));
+
+        assert_eq!(parse_proxy_address("127.0.0.1:8080"), expected);
+        assert_eq!(parse_proxy_address("http://127.0.0.1:8080/"), expected);
+        assert_eq!(
+            parse_proxy_address("localhost"),
+            Some(SocketAddr::from(([127, 0, 0, 1], DEFAULT_PROXY_PORT)))
+        );
+        assert_eq!(parse_proxy_address("socks5://127.0.0.1:8080"), None);
+    }
+
+    #[test]
+    fn proxy_for_url() {
+        let proxy = SocketAddr::from(([127, 0, 0, 1], 8080));
+        let config = ProxyConfig {
+            http: Some(proxy),
+            https: None,
+            no_proxy: parse_no_proxy("example.com, .internal"),
+        };
+
+        let proxy_for = |url: &str| config.proxy_for(&url.parse().unwrap());
+
+        assert_eq!(proxy_for("http://stormlicht.rs"), Some(proxy));
+        assert_eq!(proxy_for("https://stormlicht.rs"), None);
+        assert_eq!(proxy_for("http://example.com/foo"), None);
+        assert_eq!(proxy_for("http://www.example.com"), None);
+        assert_eq!(proxy_for("http://notexample.com"), Some(proxy));
+        assert_eq!(proxy_for("http://foo.internal"), None);
+    }
+
+    #[test]
+    fn connect_request() {
+        let mut request = vec![];
+        write_connect_request(
+            &mut request,
+            &Host::Domain("example.com".try_into().unwrap()),
+            443,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(request).unwrap(),
+            "CONNECT example.com:443 HTTP/1.1\r\nhost: example.com:443\r\nuser-agent: Stormlicht\r\n\r\n"
+        );
+    }
+}
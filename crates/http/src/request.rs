@@ -8,13 +8,23 @@ use dns::DNSError;
 use error_derive::Error;
 use url::{Host, URL};
 
-use crate::{https, response::Response, Header, Headers, StatusCode};
+use crate::{
+    authentication::{self, AuthenticationRequest, AuthenticationTarget, CredentialPrompt},
+    https,
+    proxy::{self, Tunnel},
+    response::{self, BodySink, Response},
+    Header, Headers, ProxyConfig, StatusCode, CERTIFICATE_EXCEPTIONS, HSTS,
+};
 
-const USER_AGENT: &str = "Stormlicht";
+/// Sent in the `User-Agent` header of every request
+pub const USER_AGENT: &str = "Stormlicht";
 pub(crate) const HTTP_NEWLINE: &str = "\r\n";
 
 const MAX_REDIRECTS: usize = 32;
 
+/// How often the user is asked for credentials before giving up
+const MAX_AUTHENTICATION_ATTEMPTS: usize = 3;
+
 #[derive(Debug, Error)]
 pub enum HTTPError {
     #[msg = "invalid response"]
@@ -41,6 +51,12 @@ pub enum HTTPError {
     #[msg = "tls communication failed"]
     Tls(rustls::Error),
 
+    #[msg = "invalid server certificate"]
+    Certificate(https::CertificateError),
+
+    #[msg = "proxy refused to open a tunnel"]
+    TunnelRefused,
+
     #[msg = "too many redirections"]
     RedirectLoop,
 
@@ -49,6 +65,9 @@ pub enum HTTPError {
 
     #[msg = "request to non-http url"]
     NonHTTPURl,
+
+    #[msg = "url does not have a host"]
+    MissingHost,
 }
 
 #[derive(Clone, Debug)]
@@ -60,7 +79,19 @@ pub struct Context {
     /// The [URL] that is currently being loaded
     pub url: URL,
 
-    pub proxy: Option<SocketAddr>,
+    pub proxy: ProxyConfig,
+
+    /// Asks the user for credentials when the server requires authentication
+    pub credential_prompt: Option<CredentialPrompt>,
+
+    /// The number of times we answered an authentication challenge
+    pub num_authentication_attempts: usize,
+
+    /// The value of the `Proxy-Authorization` header for the given proxy
+    ///
+    /// This is not part of the request headers, because the credentials must never
+    /// be sent to anyone but the proxy.
+    pub proxy_authorization: Option<(SocketAddr, String)>,
 }
 
 /// HTTP Request Method
@@ -111,12 +142,33 @@ impl Context {
         Self {
             num_redirections: 0,
             url,
-            proxy: None,
+            proxy: ProxyConfig::DIRECT,
+            credential_prompt: None,
+            num_authentication_attempts: 0,
+            proxy_authorization: None,
         }
     }
 
+    /// Send all requests through the given proxy
     pub fn set_proxy(&mut self, proxy: SocketAddr) {
-        self.proxy = Some(proxy);
+        self.proxy = ProxyConfig::for_all_schemes(proxy);
+    }
+
+    pub fn set_proxy_config(&mut self, config: ProxyConfig) {
+        self.proxy = config;
+    }
+
+    pub fn set_credential_prompt(&mut self, prompt: CredentialPrompt) {
+        self.credential_prompt = Some(prompt);
+    }
+
+    /// The `Proxy-Authorization` header that should be sent to the given proxy, if any
+    #[must_use]
+    pub fn proxy_authorization_for(&self, proxy: SocketAddr) -> Option<&str> {
+        self.proxy_authorization
+            .as_ref()
+            .filter(|(authorized_proxy, _)| *authorized_proxy == proxy)
+            .map(|(_, authorization)| authorization.as_str())
     }
 }
 
@@ -169,10 +221,23 @@ impl Request {
         }
     }
 
+    /// Send the request (and all redirections) through the given proxy
     pub fn set_proxy(&mut self, proxy: SocketAddr) {
         self.context.set_proxy(proxy);
     }
 
+    /// Choose a proxy for the request (and all redirections) according to the given config
+    pub fn set_proxy_config(&mut self, config: ProxyConfig) {
+        self.context.set_proxy_config(config);
+    }
+
+    /// Set the callback that is used when a server or proxy asks for credentials
+    ///
+    /// Without a prompt, authentication challenges are treated as errors.
+    pub fn set_credential_prompt(&mut self, prompt: CredentialPrompt) {
+        self.context.set_credential_prompt(prompt);
+    }
+
     #[must_use]
     pub fn headers(&self) -> &Headers {
         &self.headers
@@ -189,24 +254,48 @@ impl Request {
         W: io::Write,
     {
         // Send request header
-        let path = if self.context.proxy.is_none() {
-            self.context.url.path()
+        write!(writer, "{method} ", method = self.method.as_str())?;
+
+        // Requests that are tunneled through a proxy look exactly like direct requests
+        let forwarding_proxy = if self.context.url.scheme().as_str() == "http" {
+            self.context.proxy.proxy_for(&self.context.url)
         } else {
-            self.context.url.serialize(url::ExcludeFragment::Yes)
+            None
         };
 
-        write!(
-            writer,
-            "{method} {path} HTTP/1.1{HTTP_NEWLINE}",
-            method = self.method.as_str(),
-            path = path,
-        )?;
+        if forwarding_proxy.is_none() {
+            // The query is already percent-encoded by the URL parser
+            write!(writer, "{path}", path = self.context.url.path())?;
+            if let Some(query) = self.context.url.query() {
+                write!(writer, "?{query}")?;
+            }
+        } else {
+            // Proxies expect the absolute form
+            write!(
+                writer,
+                "{url}",
+                url = self.context.url.serialize(url::ExcludeFragment::Yes)
+            )?;
+        }
+
+        write!(writer, " HTTP/1.1{HTTP_NEWLINE}")?;
 
         // Send headers
         for (header, value) in self.headers.iter() {
             write!(writer, "{}: {value}{HTTP_NEWLINE}", header.as_str())?;
         }
 
+        // Tunneled requests are authorized with the proxy in the CONNECT request instead
+        if let Some(proxy_authorization) =
+            forwarding_proxy.and_then(|proxy| self.context.proxy_authorization_for(proxy))
+        {
+            write!(
+                writer,
+                "{}: {proxy_authorization}{HTTP_NEWLINE}",
+                Header::PROXY_AUTHORIZATION.as_str()
+            )?;
+        }
+
         // Finish request with an extra newline
         write!(writer, "{HTTP_NEWLINE}")?;
 
@@ -215,38 +304,81 @@ impl Request {
     }
 
     pub fn send(&mut self) -> Result<Response, HTTPError> {
-        if let Some(proxy) = self.context.proxy {
-            log::info!("Proxying http connection via {proxy}");
-            let stream = TcpStream::connect(proxy)?;
-            return self.send_on_stream(stream);
+        self.send_with_sink(None)
+    }
+
+    /// Send the request and pass the body of the final response to the given [BodySink]
+    ///
+    /// The body of the returned [Response] is empty. Responses that are not successful
+    /// are not passed to the sink.
+    pub fn send_to(&mut self, sink: &mut dyn BodySink) -> Result<Response, HTTPError> {
+        self.send_with_sink(Some(sink))
+    }
+
+    fn send_with_sink(&mut self, sink: Option<&mut dyn BodySink>) -> Result<Response, HTTPError> {
+        // Hosts that require strict transport security are never contacted over plain http,
+        // this includes redirections
+        if let Some(upgraded_url) = HSTS.upgrade(&self.context.url) {
+            self.context.url = upgraded_url;
         }
 
         // Establish a connection with the host
-        let host = self.context.url.host().expect("url does not have a host");
+        let host = self.context.url.host().ok_or(HTTPError::MissingHost)?;
         let port = self.context.url.port();
+        let proxy = self.context.proxy.proxy_for(&self.context.url);
 
         match self.context.url.scheme().as_str() {
             "http" => {
+                if let Some(proxy) = proxy {
+                    log::info!("Proxying http connection via {proxy}");
+                    let stream = TcpStream::connect(proxy)?;
+                    return self.send_on_stream(stream, sink);
+                }
+
                 // Resolve the hostname
                 let ip = match &host {
                     Host::Domain(host) | Host::OpaqueHost(host) => dns::Domain::new(host.as_str())
                         .lookup()
                         .map_err(HTTPError::DNS)?,
-                    Host::Ip(_ip) => todo!(),
-                    Host::EmptyHost => todo!(),
+                    Host::Ip(ip) => *ip,
+                    Host::EmptyHost => return Err(HTTPError::MissingHost),
                 };
 
                 let stream = TcpStream::connect(SocketAddr::new(ip, port.unwrap_or(80)))?;
-                self.send_on_stream(stream)
+                self.send_on_stream(stream, sink)
             },
             "https" => {
-                let stream = match host {
-                    Host::Domain(host) | Host::OpaqueHost(host) => {
-                        https::establish_connection(host.to_string(), port)?
+                let port = port.unwrap_or(https::TLS_PORT);
+                let socket = match proxy {
+                    Some(proxy) => {
+                        let proxy_authorization = self.context.proxy_authorization_for(proxy);
+                        match proxy::establish_tunnel(proxy, host, port, proxy_authorization)? {
+                            Tunnel::Established(socket) => socket,
+                            Tunnel::AuthenticationRequired(headers) => {
+                                if self.authenticate(AuthenticationTarget::Proxy, &headers) {
+                                    return self.send_with_sink(sink);
+                                }
+                                return Err(HTTPError::TunnelRefused);
+                            },
+                        }
+                    },
+                    None => match host {
+                        Host::Domain(host) | Host::OpaqueHost(host) => {
+                            TcpStream::connect((host.as_str(), port))?
+                        },
+                        Host::Ip(ip) => TcpStream::connect(SocketAddr::new(*ip, port))?,
+                        Host::EmptyHost => return Err(HTTPError::MissingHost),
                     },
-                    _ => todo!(),
                 };
-                self.send_on_stream(stream)
+
+                // Certificates can be issued for ip addresses too
+                let server_name = match host {
+                    Host::Domain(host) | Host::OpaqueHost(host) => host.to_string(),
+                    Host::Ip(ip) => ip.to_string(),
+                    Host::EmptyHost => return Err(HTTPError::MissingHost),
+                };
+                let stream = https::establish_connection(socket, server_name)?;
+                self.send_on_stream(stream, sink)
             },
             _ => Err(HTTPError::NonHTTPURl),
         }
@@ -255,13 +387,37 @@ impl Request {
     fn send_on_stream<S: io::Read + io::Write>(
         &mut self,
         mut stream: S,
+        sink: Option<&mut dyn BodySink>,
     ) -> Result<Response, HTTPError> {
         // Send our request
         self.write_to(&mut stream)?;
 
         // Parse the response
         let mut reader = BufReader::new(stream);
-        let response = Response::receive(&mut reader, self.context.clone())?;
+        let (status, headers) = response::receive_head(&mut reader)?;
+
+        // Only the body of the final response is interesting to the sink
+        let (sink, remaining_sink) = if status.is_success() {
+            (sink, None)
+        } else {
+            (None, sink)
+        };
+        let response =
+            Response::receive_body(&mut reader, status, headers, self.context.clone(), sink)?;
+
+        self.process_strict_transport_security(response.headers());
+
+        let authentication_target = match response.status() {
+            StatusCode::UNAUTHORIZED => Some(AuthenticationTarget::Server),
+            StatusCode::PROXY_AUTHENTICATION_REQUIRED => Some(AuthenticationTarget::Proxy),
+            _ => None,
+        };
+
+        if let Some(target) = authentication_target {
+            if self.authenticate(target, response.headers()) {
+                return self.send_with_sink(remaining_sink);
+            }
+        }
 
         if response.status().is_error() {
             log::warn!("HTTP Request failed: {:?}", response.status());
@@ -294,6 +450,11 @@ impl Request {
 
                 self.context.num_redirections += 1;
 
+                // Don't leak credentials to other hosts
+                if relocation.host() != self.context.url.host() {
+                    self.headers.remove(Header::AUTHORIZATION);
+                }
+
                 if self.context.num_redirections >= MAX_REDIRECTS {
                     log::warn!("Too many HTTP redirections ({MAX_REDIRECTS}), stopping");
                     return Err(HTTPError::RedirectLoop);
@@ -307,7 +468,7 @@ impl Request {
                         .to_string(),
                 );
                 self.context.url = relocation;
-                return self.send();
+                return self.send_with_sink(remaining_sink);
             } else {
                 log::warn!("HTTP response indicates redirection, but no new URL could be found");
             }
@@ -315,4 +476,124 @@ impl Request {
 
         Ok(response)
     }
+
+    /// Remember the strict transport security policy that the host sent, if any
+    ///
+    /// <https://www.rfc-editor.org/rfc/rfc6797#section-8.1>
+    fn process_strict_transport_security(&self, response_headers: &Headers) {
+        let Some(header_value) = response_headers.get(Header::STRICT_TRANSPORT_SECURITY) else {
+            return;
+        };
+
+        // The header is only meaningful if it was received over a secure connection
+        // without certificate errors
+        if self.context.url.scheme().as_str() != "https" {
+            return;
+        }
+
+        let host = self.context.url.host().expect("url does not have a host");
+        if CERTIFICATE_EXCEPTIONS.contains(&host.to_string()) {
+            return;
+        }
+
+        HSTS.process_header(host, header_value);
+    }
+
+    /// Try to answer an authentication challenge from the response headers
+    ///
+    /// Returns `true` if the request should be sent again.
+    fn authenticate(&mut self, target: AuthenticationTarget, response_headers: &Headers) -> bool {
+        let (challenge_header, proxy) = match target {
+            AuthenticationTarget::Server => (Header::WWW_AUTHENTICATE, None),
+            AuthenticationTarget::Proxy => {
+                // Only the proxy that we are talking to can ask for credentials
+                let Some(proxy) = self.context.proxy.proxy_for(&self.context.url) else {
+                    return false;
+                };
+                (Header::PROXY_AUTHENTICATE, Some(proxy))
+            },
+        };
+
+        let Some(prompt) = self.context.credential_prompt.clone() else {
+            return false;
+        };
+
+        if self.context.num_authentication_attempts >= MAX_AUTHENTICATION_ATTEMPTS {
+            log::warn!("Authentication failed {MAX_AUTHENTICATION_ATTEMPTS} times, giving up");
+            return false;
+        }
+
+        let challenges = authentication::parse_challenges(
+            response_headers.get(challenge_header).unwrap_or_default(),
+        );
+        let Some(challenge) = authentication::select_challenge(&challenges) else {
+            log::warn!("No supported authentication scheme in {challenges:?}");
+            return false;
+        };
+
+        let request = AuthenticationRequest {
+            url: self.context.url.clone(),
+            target,
+            realm: challenge.realm().map(str::to_owned),
+            previous_attempt_failed: match proxy {
+                Some(proxy) => self.context.proxy_authorization_for(proxy).is_some(),
+                None => self.headers.get(Header::AUTHORIZATION).is_some(),
+            },
+        };
+        let Some(credentials) = prompt.ask(&request) else {
+            log::info!("Authentication was cancelled");
+            return false;
+        };
+
+        let mut uri = self.context.url.path().to_string();
+        if let Some(query) = self.context.url.query() {
+            uri.push('?');
+            uri.push_str(query.as_str());
+        }
+
+        let Some(authorization) = challenge.respond(&credentials, self.method.as_str(), &uri)
+        else {
+            return false;
+        };
+
+        self.context.num_authentication_attempts += 1;
+        match proxy {
+            Some(proxy) => self.context.proxy_authorization = Some((proxy, authorization)),
+            None => self.headers.set(Header::AUTHORIZATION, authorization),
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serialize(request: &Request) -> String {
+        let mut bytes = vec![];
+        request.write_to(&mut bytes).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn proxy_credentials_are_only_sent_to_the_proxy() {
+        let proxy = SocketAddr::from(([127, 0, 0, 1], 8080));
+        let other_proxy = SocketAddr::from(([127, 0, 0, 1], 8081));
+
+        // Plain http requests are sent to the proxy itself
+        let mut request = Request::get(&"http://example.com/".parse().unwrap());
+        request.set_proxy(proxy);
+        request.context.proxy_authorization = Some((proxy, "Basic Zm9vOmJhcg==".to_string()));
+        assert!(serialize(&request).contains("proxy-authorization: Basic Zm9vOmJhcg==\r\n"));
+
+        // Credentials for one proxy are not sent to another one
+        request.set_proxy(other_proxy);
+        assert!(!serialize(&request).contains("proxy-authorization"));
+
+        // Https requests are sent to the origin through the tunnel
+        let mut request = Request::get(&"https://example.com/".parse().unwrap());
+        request.set_proxy(proxy);
+        request.context.proxy_authorization = Some((proxy, "Basic Zm9vOmJhcg==".to_string()));
+        assert!(!serialize(&request).contains("proxy-authorization"));
+    }
 }
@@ -1,6 +1,6 @@
 //! HTTP/1.1 response parser
 
-use std::io::{BufRead, BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read, Write};
 
 use compression::{brotli, gzip, zlib};
 use sl_std::{ascii, iter::MultiElementSplit};
@@ -82,159 +82,296 @@ impl Response {
         reader: &mut BufReader<R>,
         context: Context,
     ) -> Result<Self, HTTPError> {
-        // TODO all of this is very insecure - we blindly trust the size in Transfer-Encoding: chunked,
-        // no timeouts, stuff like that.
-        let needle = b"\r\n\r\n";
-        let header_bytes = read_until(reader, needle)?;
+        let (status, headers) = receive_head(reader)?;
+        Self::receive_body(reader, status, headers, context, None)
+    }
 
-        let mut response_lines =
-            MultiElementSplit::new(&header_bytes, |w: &[u8; 2]| w == HTTP_NEWLINE.as_bytes());
+    /// Read the body of a response whose head was already received
+    ///
+    /// If a [BodySink] is given, the body is passed to it instead of being stored
+    /// in the [Response].
+    pub(crate) fn receive_body<R: std::io::Read>(
+        reader: &mut BufReader<R>,
+        status: StatusCode,
+        headers: Headers,
+        context: Context,
+        sink: Option<&mut dyn BodySink>,
+    ) -> Result<Self, HTTPError> {
+        let mut response = Self {
+            status,
+            headers,
+            body: vec![],
+            context,
+        };
 
-        let mut status_line_words = response_lines
-            .next()
-            .ok_or(HTTPError::InvalidResponse)?
-            .split(|&b| b == b' ')
-            .filter(|word| !word.is_empty());
+        if !status.allowed_to_have_body() {
+            return Ok(response);
+        }
+
+        let is_encoded = response
+            .headers
+            .get(Header::CONTENT_ENCODING)
+            .is_some_and(|encoding| encoding != "identity");
 
-        if !matches!(status_line_words.next(), Some(b"HTTP/1.1")) {
-            return Err(HTTPError::InvalidResponse);
+        match sink {
+            // Encoded bodies need to be decompressed as a whole before they can be passed on
+            Some(sink) if !is_encoded => {
+                sink.start(&response)?;
+                read_body(reader, &response.headers, &mut SinkWriter(sink))?;
+            },
+            Some(sink) => {
+                sink.start(&response)?;
+                let mut body = vec![];
+                read_body(reader, &response.headers, &mut body)?;
+                sink.write(&decode_body(&response.headers, body)?)?;
+            },
+            None => {
+                let mut body = vec![];
+                read_body(reader, &response.headers, &mut body)?;
+                response.body = decode_body(&response.headers, body)?;
+            },
         }
 
-        // Parse status code
-        let status: StatusCode =
-            std::str::from_utf8(status_line_words.next().ok_or(HTTPError::InvalidResponse)?)
+        Ok(response)
+    }
+}
+
+/// Receives the body of a response while it is being transferred, instead
+/// of buffering all of it in memory
+pub trait BodySink {
+    /// Called once the head of the response was received, before any part of the body
+    ///
+    /// The body of the response passed to this method is always empty.
+    fn start(&mut self, response: &Response) -> io::Result<()>;
+
+    /// Called for each part of the body, in order
+    ///
+    /// Returning an error aborts the transfer.
+    fn write(&mut self, data: &[u8]) -> io::Result<()>;
+}
+
+struct SinkWriter<'a>(&'a mut dyn BodySink);
+
+impl<'a> Write for SinkWriter<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Read the (transfer-decoded) response body into the given [Writer](Write)
+fn read_body<R: Read, W: Write>(
+    reader: &mut BufReader<R>,
+    headers: &Headers,
+    writer: &mut W,
+) -> Result<(), HTTPError> {
+    // TODO all of this is very insecure - we blindly trust the size in Transfer-Encoding: chunked,
+    // no timeouts, stuff like that.
+
+    // Anything after the headers is the actual response body
+    // The length of the body depends on the headers that were sent
+    if let Some(transfer_encoding) = headers.get(Header::TRANSFER_ENCODING) {
+        match transfer_encoding {
+            "chunked" => {
+                // https://datatracker.ietf.org/doc/html/rfc9112#name-chunked-transfer-coding
+                loop {
+                    let size_bytes_with_newline = read_until(reader, HTTP_NEWLINE.as_bytes())?;
+                    let size_bytes = &size_bytes_with_newline
+                        [..size_bytes_with_newline.len() - HTTP_NEWLINE.len()];
+
+                    let size =
+                        std::str::from_utf8(size_bytes).map_err(|_| HTTPError::InvalidResponse)?;
+                    let size =
+                        u64::from_str_radix(size, 16).map_err(|_| HTTPError::InvalidResponse)?;
+
+                    if size == 0 {
+                        // > The chunked transfer coding is complete when a chunk with a chunk-size of zero is received.
+                        break;
+                    }
+
+                    // Pass the chunk on to the writer
+                    copy_exact(reader, writer, size)?;
+
+                    // Chunks are followed by a CRLF sequence
+                    let mut c = [0; 2];
+                    reader.read_exact(&mut c)?;
+
+                    if c != HTTP_NEWLINE.as_bytes() {
+                        log::warn!("Http chunk not followed by CRLF");
+                        return Err(HTTPError::InvalidResponse);
+                    }
+                }
+            },
+            _ => {
+                log::warn!("Unknown transfer encoding: {transfer_encoding}");
+                return Err(HTTPError::InvalidResponse);
+            },
+        }
+    } else if let Some(content_length) = headers.get(Header::CONTENT_LENGTH) {
+        let content_length: u64 =
+            str::parse(content_length).map_err(|_| HTTPError::InvalidResponse)?;
+
+        copy_exact(reader, writer, content_length)?;
+    } else {
+        log::warn!("Neither Transfer-Encoding nor Content-Length were provided, we don't know how to decode the body!");
+        return Err(HTTPError::InvalidResponse);
+    }
+
+    Ok(())
+}
+
+/// Copy exactly `length` bytes from the reader to the writer
+fn copy_exact<R: Read, W: Write>(reader: &mut R, writer: &mut W, length: u64) -> io::Result<()> {
+    let copied = io::copy(&mut reader.take(length), writer)?;
+
+    if copied != length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(())
+}
+
+/// Take care of response compressions
+fn decode_body(headers: &Headers, body: Vec<u8>) -> Result<Vec<u8>, HTTPError> {
+    let Some(compression_algorithm) = headers.get(Header::CONTENT_ENCODING) else {
+        return Ok(body);
+    };
+
+    // See https://www.rfc-editor.org/rfc/rfc2616#section-3.5
+    let body = match compression_algorithm {
+        "gzip" => gzip::decompress(&body)?,
+        "brotli" => brotli::decompress(&body)?,
+        "deflate" => {
+            // The deflate encoding actually isn't just deflate, but also contains a zlib wrapper
+            zlib::decompress(&body)?
+        },
+        "identity" => body,
+        _ => {
+            log::error!("Unknown HTTP Content-Encoding: {:?}", compression_algorithm);
+            body
+        },
+    };
+
+    Ok(body)
+}
+
+/// Read the status line and the headers of a response
+///
+/// The reader is left at the start of the response body.
+pub(crate) fn receive_head<R: std::io::Read>(
+    reader: &mut BufReader<R>,
+) -> Result<(StatusCode, Headers), HTTPError> {
+    let needle = b"\r\n\r\n";
+    let header_bytes = read_until(reader, needle)?;
+
+    let mut response_lines =
+        MultiElementSplit::new(&header_bytes, |w: &[u8; 2]| w == HTTP_NEWLINE.as_bytes());
+
+    let mut status_line_words = response_lines
+        .next()
+        .ok_or(HTTPError::InvalidResponse)?
+        .split(|&b| b == b' ')
+        .filter(|word| !word.is_empty());
+
+    if !matches!(status_line_words.next(), Some(b"HTTP/1.1")) {
+        return Err(HTTPError::InvalidResponse);
+    }
+
+    // Parse status code
+    let status: StatusCode =
+        std::str::from_utf8(status_line_words.next().ok_or(HTTPError::InvalidResponse)?)
+            .map_err(|_| HTTPError::InvalidResponse)?
+            .parse()
+            .map_err(|_| HTTPError::InvalidResponse)?;
+
+    // What follows is a textual description of the error code ("OK" for 200) - we don't care about that
+
+    // Parse the response headers
+    let mut headers = Headers::default();
+    for header_line in response_lines {
+        // An empty header indicates the end of the list of headers
+        if header_line.is_empty() {
+            break;
+        }
+
+        let separator = header_line
+            .iter()
+            .position(|&elem| elem == b':')
+            .ok_or(HTTPError::InvalidResponse)?;
+
+        let key = &header_line[..separator];
+        let value = &header_line[separator + 1..];
+
+        // FIXME: Find a way not to clone the header here
+        let header_name = ascii::Str::from_bytes(key)
+            .ok_or(HTTPError::InvalidResponse)?
+            .trim()
+            .to_lowercase();
+        let header = Header::from_lowercase_str(&header_name);
+        headers.set(
+            header,
+            std::str::from_utf8(value)
                 .map_err(|_| HTTPError::InvalidResponse)?
-                .parse()
-                .map_err(|_| HTTPError::InvalidResponse)?;
-
-        // What follows is a textual description of the error code ("OK" for 200) - we don't care about that
-
-        // Parse the response headers
-        let mut headers = Headers::default();
-        for header_line in response_lines {
-            // An empty header indicates the end of the list of headers
-            if header_line.is_empty() {
-                break;
-            }
-
-            let separator = header_line
-                .iter()
-                .position(|&elem| elem == b':')
-                .ok_or(HTTPError::InvalidResponse)?;
-
-            let key = &header_line[..separator];
-            let value = &header_line[separator + 1..];
-
-            // FIXME: Find a way not to clone the header here
-            let header_name = ascii::Str::from_bytes(key)
-                .ok_or(HTTPError::InvalidResponse)?
                 .trim()
-                .to_lowercase();
-            let header = Header::from_lowercase_str(&header_name);
-            headers.set(
-                header,
-                std::str::from_utf8(value)
-                    .map_err(|_| HTTPError::InvalidResponse)?
-                    .trim()
-                    .to_owned(),
-            );
+                .to_owned(),
+        );
+    }
+
+    Ok((status, headers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct CollectingSink {
+        status: Option<StatusCode>,
+        parts: Vec<Vec<u8>>,
+    }
+
+    impl BodySink for CollectingSink {
+        fn start(&mut self, response: &Response) -> io::Result<()> {
+            assert!(response.body().is_empty());
+            self.status = Some(response.status());
+            Ok(())
         }
 
-        if !status.allowed_to_have_body() {
-            return Ok(Self {
-                status,
-                headers,
-                body: vec![],
-                context,
-            });
+        fn write(&mut self, data: &[u8]) -> io::Result<()> {
+            self.parts.push(data.to_vec());
+            Ok(())
         }
+    }
 
-        // Anything after the headers is the actual response body
-        // The length of the body depends on the headers that were sent
-        let mut body: Vec<u8> = if let Some(transfer_encoding) =
-            headers.get(Header::TRANSFER_ENCODING)
-        {
-            match transfer_encoding {
-                "chunked" => {
-                    // https://datatracker.ietf.org/doc/html/rfc9112#name-chunked-transfer-coding
-                    let mut buffer = vec![];
-                    loop {
-                        let size_bytes_with_newline = read_until(reader, HTTP_NEWLINE.as_bytes())?;
-                        let size_bytes = &size_bytes_with_newline
-                            [..size_bytes_with_newline.len() - HTTP_NEWLINE.len()];
-
-                        let size = std::str::from_utf8(size_bytes)
-                            .map_err(|_| HTTPError::InvalidResponse)?;
-                        let size = usize::from_str_radix(size, 16)
-                            .map_err(|_| HTTPError::InvalidResponse)?;
-
-                        if size == 0 {
-                            // > The chunked transfer coding is complete when a chunk with a chunk-size of zero is received.
-                            break;
-                        }
-
-                        // Reserve enough space in the response buffer for this chunk
-                        let current_buffer_len = buffer.len();
-                        buffer.resize(current_buffer_len + size, 0);
-
-                        // Read the chunk into the response buffer
-                        reader.read_exact(&mut buffer[current_buffer_len..])?;
-
-                        // Chunks are followed by a CRLF sequence
-                        let mut c = [0; 2];
-                        reader.read_exact(&mut c)?;
-
-                        if c != HTTP_NEWLINE.as_bytes() {
-                            log::warn!("Http chunk not followed by CRLF");
-                            return Err(HTTPError::InvalidResponse);
-                        }
-                    }
-                    buffer
-                },
-                _ => {
-                    log::warn!("Unknown transfer encoding: {transfer_encoding}");
-                    return Err(HTTPError::InvalidResponse);
-                },
-            }
-        } else if let Some(content_length) = headers.get(Header::CONTENT_LENGTH) {
-            // Reserve enough space for the content inside the response body
-            let content_length: usize =
-                str::parse(content_length).map_err(|_| HTTPError::InvalidResponse)?;
-            let mut buffer = vec![0; content_length];
-
-            reader.read_exact(&mut buffer)?;
-            buffer
-        } else {
-            log::warn!("Neither Transfer-Encoding nor Content-Length were provided, we don't know how to decode the body!");
-            return Err(HTTPError::InvalidResponse);
-        };
+    #[test]
+    fn stream_chunked_body() {
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n7\r\n, World\r\n0\r\n\r\n";
+        let mut reader = BufReader::new(&response[..]);
+        let context = Context::new("http://example.com".parse().unwrap());
 
-        // Take care of response compressions
-        if let Some(compression_algorithm) = headers.get(Header::CONTENT_ENCODING) {
-            // See https://www.rfc-editor.org/rfc/rfc2616#section-3.5
-            match compression_algorithm {
-                "gzip" => {
-                    body = gzip::decompress(&body)?;
-                },
-                "brotli" => {
-                    body = brotli::decompress(&body)?;
-                },
-                "deflate" => {
-                    // The deflate encoding actually isn't just deflate, but also contains a zlib wrapper
-                    body = zlib::decompress(&body)?;
-                },
-                "identity" => {},
-                _ => {
-                    log::error!("Unknown HTTP Content-Encoding: {:?}", compression_algorithm);
-                },
-            }
-        }
+        let (status, headers) = receive_head(&mut reader).unwrap();
+        let mut sink = CollectingSink::default();
+        let response =
+            Response::receive_body(&mut reader, status, headers, context, Some(&mut sink)).unwrap();
 
-        Ok(Self {
-            status,
-            headers,
-            body,
-            context,
-        })
+        assert!(response.body().is_empty());
+        assert_eq!(sink.status, Some(StatusCode::OK));
+        assert_eq!(sink.parts.concat(), b"Hello, World");
+    }
+
+    #[test]
+    fn truncated_body() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nHello";
+        let mut reader = BufReader::new(&response[..]);
+        let context = Context::new("http://example.com".parse().unwrap());
+
+        assert!(matches!(
+            Response::receive(&mut reader, context),
+            Err(HTTPError::IO(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
     }
 }
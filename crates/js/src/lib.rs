@@ -2,8 +2,11 @@
 
 mod compiler;
 mod parser;
+pub mod regexp;
 mod runtime;
 mod value;
 
-pub use runtime::{Executable, Vm};
-pub use value::{Number, Value};
+pub use parser::SyntaxError;
+pub use regexp::RegExp;
+pub use runtime::{Exception, Executable, InterruptHandle, ThrowCompletionOr, Vm};
+pub use value::{ArrayBuffer, DataView, ElementType, Number, Symbol, TypedArray, Value};
@@ -0,0 +1,516 @@
+//! A backtracking matcher for parsed patterns
+//!
+//! The matcher follows the structure of the specification: every node is matched
+//! with a continuation that decides whether the rest of the pattern matches after it.
+//! Instead of closures that call each other (which would recurse once per matched
+//! character), continuations are linked lists of the steps that are left to do, and
+//! the alternatives that were not taken yet are kept on an explicit backtracking stack.
+//!
+//! <https://262.ecma-international.org/14.0/#sec-pattern-semantics>
+
+use std::{ops::Range, rc::Rc};
+
+use super::{
+    parser::{CharacterClass, ClassEscape, ClassItem, Node},
+    Flags,
+};
+
+/// The capture groups of a (partial) match, in character indices
+///
+/// Group `0` is the whole match.
+pub(super) type Captures = Vec<Option<(usize, usize)>>;
+
+/// The steps that are left to do after the current one, `None` once the pattern matched
+type Continuation<'a> = Option<Rc<Step<'a>>>;
+
+struct Step<'a> {
+    action: Action<'a>,
+    next: Continuation<'a>,
+}
+
+enum Action<'a> {
+    Match(&'a Node),
+
+    /// Set the capture group to the range from `start` to the current position
+    CloseGroup {
+        index: usize,
+        start: usize,
+    },
+
+    /// Reset the capture groups inside a repeated node before an iteration
+    ResetCaptures(&'a Range<usize>),
+
+    /// Called after one iteration of a repetition that started at `start`
+    FinishIteration {
+        repetition: Repetition<'a>,
+        start: usize,
+    },
+}
+
+/// The remaining iterations of a [Node::Repeat]
+#[derive(Clone)]
+struct Repetition<'a> {
+    node: &'a Node,
+    min: u32,
+    max: Option<u32>,
+    greedy: bool,
+    captures: &'a Range<usize>,
+}
+
+enum Backtrack<'a> {
+    /// An alternative that can be tried if everything after it fails
+    Branch {
+        continuation: Continuation<'a>,
+        position: usize,
+    },
+
+    /// Undo a change to a capture group
+    Restore {
+        index: usize,
+        capture: Option<(usize, usize)>,
+    },
+}
+
+pub(super) struct Matcher<'a> {
+    input: &'a [char],
+    flags: Flags,
+}
+
+impl<'a> Matcher<'a> {
+    pub(super) fn new(input: &'a [char], flags: Flags) -> Self {
+        Self { input, flags }
+    }
+
+    /// Try to match the pattern starting exactly at `start`
+    pub(super) fn match_at(
+        &self,
+        root: &Node,
+        capture_count: usize,
+        start: usize,
+    ) -> Option<Captures> {
+        let mut captures = vec![None; capture_count + 1];
+        let end = self.run(root, start, None, &mut captures)?;
+
+        captures[0] = Some((start, end));
+        Some(captures)
+    }
+
+    /// Match `node` starting at `position` and return the position after the first match
+    ///
+    /// If `required_end` is set, only matches that end there are accepted.
+    /// When there is no match, `captures` are left unchanged.
+    fn run<'p>(
+        &self,
+        node: &'p Node,
+        mut position: usize,
+        required_end: Option<usize>,
+        captures: &mut Captures,
+    ) -> Option<usize> {
+        let mut backtrack_stack: Vec<Backtrack<'p>> = vec![];
+        let mut continuation = then(Action::Match(node), None);
+
+        loop {
+            let Some(step) = continuation else {
+                if required_end.is_none_or(|end| end == position) {
+                    return Some(position);
+                }
+
+                continuation = backtrack(&mut backtrack_stack, &mut position, captures)?;
+                continue;
+            };
+
+            continuation = step.next.clone();
+            let succeeded = self.step(
+                &step.action,
+                &mut continuation,
+                &mut position,
+                captures,
+                &mut backtrack_stack,
+            );
+
+            if !succeeded {
+                continuation = backtrack(&mut backtrack_stack, &mut position, captures)?;
+            }
+        }
+    }
+
+    /// Perform a single action, returning `false` if it failed
+    ///
+    /// `continuation` is what happens afterwards, actions can prepend steps to it.
+    fn step<'p>(
+        &self,
+        action: &Action<'p>,
+        continuation: &mut Continuation<'p>,
+        position: &mut usize,
+        captures: &mut Captures,
+        backtrack_stack: &mut Vec<Backtrack<'p>>,
+    ) -> bool {
+        let node = match action {
+            Action::Match(node) => *node,
+            Action::CloseGroup { index, start } => {
+                set_capture(captures, backtrack_stack, *index, Some((*start, *position)));
+                return true;
+            },
+            Action::ResetCaptures(range) => {
+                for index in (*range).clone() {
+                    set_capture(captures, backtrack_stack, index, None);
+                }
+                return true;
+            },
+            Action::FinishIteration { repetition, start } => {
+                // 2.a. If min = 0 and y's endIndex = x's endIndex, return failure.
+                if repetition.min == 0 && *position == *start {
+                    return false;
+                }
+
+                // 2.b-d. Continue with the remaining iterations
+                let remaining = Repetition {
+                    min: repetition.min.saturating_sub(1),
+                    max: repetition.max.map(|max| max - 1),
+                    ..repetition.clone()
+                };
+                return self.repeat(&remaining, continuation, *position, backtrack_stack);
+            },
+        };
+
+        match node {
+            Node::Empty => true,
+            Node::Character(expected) => {
+                let expected = self.canonicalize(*expected);
+                self.match_character(position, |c| self.canonicalize(c) == expected)
+            },
+            Node::AnyCharacter => {
+                self.match_character(position, |c| self.flags.dot_all || !is_line_terminator(c))
+            },
+            Node::Class(class) => self.match_character(position, |c| self.class_contains(class, c)),
+            Node::LineStart => {
+                *position == 0
+                    || (self.flags.multiline && is_line_terminator(self.input[*position - 1]))
+            },
+            Node::LineEnd => {
+                *position == self.input.len()
+                    || (self.flags.multiline && is_line_terminator(self.input[*position]))
+            },
+            Node::WordBoundary { negated } => {
+                let a = *position != 0 && self.is_word_character(self.input[*position - 1]);
+                let b =
+                    *position != self.input.len() && self.is_word_character(self.input[*position]);
+
+                (a != b) != *negated
+            },
+            Node::Group { capture, node } => {
+                if let Some(index) = *capture {
+                    let close = Action::CloseGroup {
+                        index,
+                        start: *position,
+                    };
+                    *continuation = then(close, continuation.take());
+                }
+
+                *continuation = then(Action::Match(node), continuation.take());
+                true
+            },
+            Node::Lookaround {
+                is_lookbehind,
+                negated,
+                node,
+            } => self.match_lookaround(
+                node,
+                *is_lookbehind,
+                *negated,
+                *position,
+                captures,
+                backtrack_stack,
+            ),
+            Node::Backreference(index) => {
+                let Some((start, end)) = captures[*index] else {
+                    // References to groups that did not participate always succeed
+                    return true;
+                };
+
+                let length = end - start;
+                let Some(candidate) = self.input.get(*position..*position + length) else {
+                    return false;
+                };
+
+                let is_equal = self.input[start..end]
+                    .iter()
+                    .zip(candidate)
+                    .all(|(&a, &b)| self.canonicalize(a) == self.canonicalize(b));
+
+                if is_equal {
+                    *position += length;
+                }
+                is_equal
+            },
+            Node::Sequence(nodes) => {
+                for node in nodes.iter().rev() {
+                    *continuation = then(Action::Match(node), continuation.take());
+                }
+                true
+            },
+            Node::Alternation(alternatives) => {
+                let Some((first, rest)) = alternatives.split_first() else {
+                    return false;
+                };
+
+                // Later alternatives are only tried if the earlier ones fail
+                for alternative in rest.iter().rev() {
+                    backtrack_stack.push(Backtrack::Branch {
+                        continuation: then(Action::Match(alternative), continuation.clone()),
+                        position: *position,
+                    });
+                }
+
+                *continuation = then(Action::Match(first), continuation.take());
+                true
+            },
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+                captures: repeated_captures,
+            } => {
+                let repetition = Repetition {
+                    node,
+                    min: *min,
+                    max: *max,
+                    greedy: *greedy,
+                    captures: repeated_captures,
+                };
+                self.repeat(&repetition, continuation, *position, backtrack_stack)
+            },
+        }
+    }
+
+    fn match_character(&self, position: &mut usize, predicate: impl Fn(char) -> bool) -> bool {
+        match self.input.get(*position) {
+            Some(&c) if predicate(c) => {
+                *position += 1;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-compileassertion>
+    fn match_lookaround<'p>(
+        &self,
+        node: &Node,
+        is_lookbehind: bool,
+        negated: bool,
+        position: usize,
+        captures: &mut Captures,
+        backtrack_stack: &mut Vec<Backtrack<'p>>,
+    ) -> bool {
+        // Lookarounds are atomic, the first way the inner node matches is the one that is used
+        let mut inner_captures = captures.clone();
+        let matched = if is_lookbehind {
+            // FIXME: Lookbehinds should be matched backwards. Instead we search for the
+            //        earliest start position from which the node ends exactly here, which
+            //        differs from the specification when there are captures inside
+            (0..=position).any(|start| {
+                self.run(node, start, Some(position), &mut inner_captures)
+                    .is_some()
+            })
+        } else {
+            self.run(node, position, None, &mut inner_captures)
+                .is_some()
+        };
+
+        if negated {
+            return !matched;
+        }
+
+        if !matched {
+            return false;
+        }
+
+        // Captures from inside a positive lookaround are visible to the rest of the pattern
+        for (index, capture) in inner_captures.into_iter().enumerate() {
+            if captures[index] != capture {
+                set_capture(captures, backtrack_stack, index, capture);
+            }
+        }
+
+        true
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-runtime-semantics-repeatmatcher-abstract-operation>
+    fn repeat<'p>(
+        &self,
+        repetition: &Repetition<'p>,
+        continuation: &mut Continuation<'p>,
+        position: usize,
+        backtrack_stack: &mut Vec<Backtrack<'p>>,
+    ) -> bool {
+        // 1. If max = 0, return c(x).
+        if repetition.max == Some(0) {
+            return true;
+        }
+
+        let finish = Action::FinishIteration {
+            repetition: repetition.clone(),
+            start: position,
+        };
+        let mut iteration = then(
+            Action::Match(repetition.node),
+            then(finish, continuation.clone()),
+        );
+
+        // 4. Let cap be a copy of x's captures List.
+        // 5. For each integer k in the inclusive interval from parenIndex + 1 to parenIndex + parenCount, set cap[k] to undefined.
+        if !repetition.captures.is_empty() {
+            iteration = then(Action::ResetCaptures(repetition.captures), iteration);
+        }
+
+        // 11. If min ≠ 0, return m(xr, d).
+        if repetition.min != 0 {
+            *continuation = iteration;
+            return true;
+        }
+
+        if repetition.greedy {
+            // 13-15. Otherwise try m(xr, d) first, then c(x)
+            backtrack_stack.push(Backtrack::Branch {
+                continuation: continuation.take(),
+                position,
+            });
+            *continuation = iteration;
+        } else {
+            // 12. If greedy is false, then try c(x) first, then m(xr, d)
+            backtrack_stack.push(Backtrack::Branch {
+                continuation: iteration,
+                position,
+            });
+        }
+
+        true
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-runtime-semantics-canonicalize-ch>
+    fn canonicalize(&self, c: char) -> char {
+        if !self.flags.ignore_case {
+            return c;
+        }
+
+        if self.flags.unicode {
+            // FIXME: This should use simple case folding instead of lowercasing
+            return single_char(c.to_lowercase()).unwrap_or(c);
+        }
+
+        match single_char(c.to_uppercase()) {
+            // Characters outside of ASCII may not be mapped into ASCII
+            Some(upper) if c.is_ascii() || !upper.is_ascii() => upper,
+            _ => c,
+        }
+    }
+
+    fn class_contains(&self, class: &CharacterClass, c: char) -> bool {
+        let candidates = if self.flags.ignore_case {
+            [
+                c,
+                single_char(c.to_lowercase()).unwrap_or(c),
+                single_char(c.to_uppercase()).unwrap_or(c),
+            ]
+        } else {
+            [c; 3]
+        };
+
+        let contains = class.items.iter().any(|item| {
+            candidates
+                .iter()
+                .any(|&candidate| self.item_contains(*item, candidate))
+        });
+
+        contains != class.negated
+    }
+
+    fn item_contains(&self, item: ClassItem, c: char) -> bool {
+        match item {
+            ClassItem::Range(low, high) => (low..=high).contains(&c),
+            ClassItem::Escape { kind, negated } => {
+                let contains = match kind {
+                    ClassEscape::Digit => c.is_ascii_digit(),
+                    ClassEscape::Word => self.is_word_character(c),
+                    ClassEscape::Whitespace => is_whitespace(c),
+                };
+
+                contains != negated
+            },
+        }
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-iswordchar>
+    fn is_word_character(&self, c: char) -> bool {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            return true;
+        }
+
+        // With both the i and u flags, characters that case fold into the basic word
+        // characters (like the Kelvin sign) are word characters too
+        self.flags.ignore_case && self.flags.unicode && {
+            let folded = self.canonicalize(c);
+            folded.is_ascii_alphanumeric() || folded == '_'
+        }
+    }
+}
+
+/// Undo everything up to the most recent branch and continue there
+///
+/// Returns `None` if there are no branches left, in which case all changes were undone.
+fn backtrack<'a>(
+    backtrack_stack: &mut Vec<Backtrack<'a>>,
+    position: &mut usize,
+    captures: &mut Captures,
+) -> Option<Continuation<'a>> {
+    loop {
+        match backtrack_stack.pop()? {
+            Backtrack::Branch {
+                continuation,
+                position: branch_position,
+            } => {
+                *position = branch_position;
+                return Some(continuation);
+            },
+            Backtrack::Restore { index, capture } => captures[index] = capture,
+        }
+    }
+}
+
+/// Prepend a step to a continuation
+fn then<'a>(action: Action<'a>, next: Continuation<'a>) -> Continuation<'a> {
+    Some(Rc::new(Step { action, next }))
+}
+
+/// Change a capture group, remembering how to undo the change when backtracking
+fn set_capture(
+    captures: &mut Captures,
+    backtrack_stack: &mut Vec<Backtrack<'_>>,
+    index: usize,
+    capture: Option<(usize, usize)>,
+) {
+    let previous = std::mem::replace(&mut captures[index], capture);
+    if previous != capture {
+        backtrack_stack.push(Backtrack::Restore {
+            index,
+            capture: previous,
+        });
+    }
+}
+
+fn single_char(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+/// <https://262.ecma-international.org/14.0/#prod-LineTerminator>
+fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+/// <https://262.ecma-international.org/14.0/#prod-WhiteSpace> and <https://262.ecma-international.org/14.0/#prod-LineTerminator>
+fn is_whitespace(c: char) -> bool {
+    c == '\u{feff}' || (c.is_whitespace() && c != '\u{85}')
+}
@@ -0,0 +1,759 @@
+//! Regular expressions
+//!
+//! Patterns are parsed into a tree and matched by a backtracking matcher.
+//! All indices exposed by this module are byte offsets into the input string.
+//!
+//! <https://262.ecma-international.org/14.0/#sec-regexp-regular-expression-objects>
+
+mod matcher;
+mod parser;
+
+use std::{fmt, ops::Range};
+
+use crate::parser::SyntaxError;
+
+use self::{
+    matcher::{Captures, Matcher},
+    parser::{Parser, Pattern},
+};
+
+/// <https://262.ecma-international.org/14.0/#sec-get-regexp.prototype.flags>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flags {
+    /// `d`
+    pub has_indices: bool,
+
+    /// `g`
+    pub global: bool,
+
+    /// `i`
+    pub ignore_case: bool,
+
+    /// `m`
+    pub multiline: bool,
+
+    /// `s`
+    pub dot_all: bool,
+
+    /// `u`
+    pub unicode: bool,
+
+    /// `y`
+    pub sticky: bool,
+}
+
+impl Flags {
+    /// Parse a flags string like `"gi"`, failing on unknown or repeated flags
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexpinitialize)
+    pub fn parse(flags: &str) -> Result<Self, SyntaxError> {
+        let mut result = Self::default();
+
+        for (position, c) in flags.char_indices() {
+            let flag = match c {
+                'd' => &mut result.has_indices,
+                'g' => &mut result.global,
+                'i' => &mut result.ignore_case,
+                'm' => &mut result.multiline,
+                's' => &mut result.dot_all,
+                'u' => &mut result.unicode,
+                'y' => &mut result.sticky,
+                _ => {
+                    return Err(SyntaxError::new(
+                        position,
+                        format!("Invalid regular expression flag {c:?}"),
+                    ))
+                },
+            };
+
+            if *flag {
+                return Err(SyntaxError::new(
+                    position,
+                    format!("Duplicate regular expression flag {c:?}"),
+                ));
+            }
+            *flag = true;
+        }
+
+        Ok(result)
+    }
+}
+
+impl fmt::Display for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (is_set, flag) in [
+            (self.has_indices, 'd'),
+            (self.global, 'g'),
+            (self.ignore_case, 'i'),
+            (self.multiline, 'm'),
+            (self.dot_all, 's'),
+            (self.unicode, 'u'),
+            (self.sticky, 'y'),
+        ] {
+            if is_set {
+                write!(f, "{flag}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A compiled regular expression together with its `lastIndex`
+///
+/// [Specification](https://262.ecma-international.org/14.0/#sec-properties-of-regexp-instances)
+#[derive(Clone, Debug)]
+pub struct RegExp {
+    source: String,
+    flags: Flags,
+    pattern: Pattern,
+
+    /// The byte offset where the next search of a global or sticky expression starts
+    last_index: usize,
+}
+
+/// The result of a successful match
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// Byte ranges of the capture groups, group `0` is the whole match
+    captures: Vec<Option<Range<usize>>>,
+    group_names: Vec<(String, usize)>,
+}
+
+/// The input string split into characters, since patterns match code points
+/// but the public interface uses byte offsets
+struct Input<'a> {
+    string: &'a str,
+    chars: Vec<char>,
+
+    /// The byte offset of each character, followed by the length of the string
+    offsets: Vec<usize>,
+}
+
+impl RegExp {
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexpinitialize)
+    pub fn new(source: &str, flags: &str) -> Result<Self, SyntaxError> {
+        let flags = Flags::parse(flags)?;
+        let pattern = Parser::parse(source, flags.unicode)?;
+
+        let regexp = Self {
+            source: source.to_string(),
+            flags,
+            pattern,
+            last_index: 0,
+        };
+
+        Ok(regexp)
+    }
+
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    #[must_use]
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    #[must_use]
+    pub fn last_index(&self) -> usize {
+        self.last_index
+    }
+
+    pub fn set_last_index(&mut self, last_index: usize) {
+        self.last_index = last_index;
+    }
+
+    /// The number of capture groups, not including the whole match
+    #[must_use]
+    pub fn capture_count(&self) -> usize {
+        self.pattern.capture_count
+    }
+
+    /// Search for the next match, honoring and updating `lastIndex` for global and sticky expressions
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexpbuiltinexec)
+    pub fn exec(&mut self, string: &str) -> Option<Match> {
+        let input = Input::new(string);
+
+        // 4-6. Let global be ..., sticky be ..., hasIndices be ...
+        // 7. If global is false and sticky is false, set lastIndex to 0.
+        let last_index = if self.flags.global || self.flags.sticky {
+            self.last_index
+        } else {
+            0
+        };
+
+        // 12. Repeat, while matchSucceeded is false,
+        let mut position = input.char_index_at_or_after(last_index);
+        let captures = loop {
+            // a. If lastIndex > length, then
+            if position > input.chars.len() {
+                // i. If global is true or sticky is true, then set lastIndex to 0
+                if self.flags.global || self.flags.sticky {
+                    self.last_index = 0;
+                }
+
+                // ii. Return null.
+                return None;
+            }
+
+            // c. Let r be matcher(input, inputIndex).
+            match self.match_at(&input, position) {
+                Some(captures) => break captures,
+                None => {
+                    // d.i.1. If sticky is true, then set lastIndex to 0 and return null
+                    if self.flags.sticky {
+                        self.last_index = 0;
+                        return None;
+                    }
+
+                    // d.i.2. Set lastIndex to AdvanceStringIndex(S, lastIndex, fullUnicode).
+                    position += 1;
+                },
+            }
+        };
+
+        let found = input.to_match(&captures, &self.pattern.group_names);
+
+        // 15. If global is true or sticky is true, then set lastIndex to e
+        if self.flags.global || self.flags.sticky {
+            self.last_index = found.range().end;
+        }
+
+        Some(found)
+    }
+
+    /// Return whether the expression matches anywhere in the string
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexp.prototype.test)
+    pub fn test(&mut self, string: &str) -> bool {
+        self.exec(string).is_some()
+    }
+
+    /// Find the first match, or all matches if the expression is global
+    ///
+    /// This is the behaviour of `String.prototype.match`.
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexp.prototype-@@match)
+    pub fn match_string(&mut self, string: &str) -> Vec<Match> {
+        // 5. If flags does not contain "g", then
+        if !self.flags.global {
+            // a. Return ? RegExpExec(rx, S).
+            return self.exec(string).into_iter().collect();
+        }
+
+        // 6.c. Perform ? Set(rx, "lastIndex", +0𝔽, true).
+        self.last_index = 0;
+
+        // 6.e. Repeat,
+        let mut matches = vec![];
+        while let Some(found) = self.exec(string) {
+            // iii.4. If matchStr is the empty String, advance lastIndex so we make progress
+            if found.range().is_empty() {
+                self.last_index = advance_string_index(string, self.last_index);
+            }
+
+            matches.push(found);
+        }
+
+        matches
+    }
+
+    /// Replace the first match, or all matches if the expression is global
+    ///
+    /// `$` patterns in the replacement are expanded, see [Match::expand].
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexp.prototype-@@replace)
+    pub fn replace(&mut self, string: &str, replacement: &str) -> String {
+        let matches = self.match_string(string);
+
+        // 12. Let accumulatedResult be the empty String.
+        // 13. Let nextSourcePosition be 0.
+        let mut result = String::new();
+        let mut next_source_position = 0;
+
+        // 14. For each element result of results, do
+        for found in matches {
+            let range = found.range();
+
+            // p. If position ≥ nextSourcePosition, then append the skipped part and the replacement
+            if range.start >= next_source_position {
+                result.push_str(&string[next_source_position..range.start]);
+                result.push_str(&found.expand(string, replacement));
+                next_source_position = range.end;
+            }
+        }
+
+        // 16. Return the string-concatenation of accumulatedResult and the substring of S from nextSourcePosition.
+        result.push_str(&string[next_source_position..]);
+        result
+    }
+
+    /// Split the string at every match, up to `limit` parts
+    ///
+    /// The captures of each match are included between the parts, with `None`
+    /// for groups that did not participate in the match.
+    ///
+    /// This is the behaviour of `String.prototype.split`.
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexp.prototype-@@split)
+    #[must_use]
+    pub fn split(&self, string: &str, limit: Option<usize>) -> Vec<Option<String>> {
+        let input = Input::new(string);
+        let limit = limit.unwrap_or(usize::MAX);
+
+        // 14. If lim = 0, return A.
+        let mut parts = vec![];
+        if limit == 0 {
+            return parts;
+        }
+
+        // 16. If size = 0, then
+        if input.chars.is_empty() {
+            // c. If z is not null, return A.
+            if self.match_at(&input, 0).is_none() {
+                // e. Perform ! CreateDataPropertyOrThrow(A, "0", S).
+                parts.push(Some(string.to_string()));
+            }
+            return parts;
+        }
+
+        // 17. Let p be 0.
+        // 18. Let q be p.
+        let mut p = 0;
+        let mut q = 0;
+
+        // 19. Repeat, while q < size,
+        while q < input.chars.len() {
+            let Some(captures) = self.match_at(&input, q) else {
+                // d. If z is null, set q to AdvanceStringIndex(S, q, unicodeMatching).
+                q += 1;
+                continue;
+            };
+
+            // e.ii. Let e be ℝ(? ToLength(? Get(splitter, "lastIndex"))).
+            // e.iii. Set e to min(e, size).
+            let (_, end) = captures[0].expect("group 0 always participates in a match");
+
+            // e.iv. If e = p, set q to AdvanceStringIndex(S, q, unicodeMatching).
+            if end == p {
+                q += 1;
+                continue;
+            }
+
+            // e.v.1. Let T be the substring of S from p to q.
+            parts.push(Some(string[input.offsets[p]..input.offsets[q]].to_string()));
+
+            // e.v.4. If lengthA = lim, return A.
+            if parts.len() == limit {
+                return parts;
+            }
+
+            // e.v.5. Set p to e.
+            p = end;
+
+            // e.v.10. Repeat, while i ≤ numberOfCaptures,
+            for capture in &captures[1..] {
+                let capture = capture.map(|(start, end)| {
+                    string[input.offsets[start]..input.offsets[end]].to_string()
+                });
+                parts.push(capture);
+
+                if parts.len() == limit {
+                    return parts;
+                }
+            }
+
+            // e.v.11. Set q to p.
+            q = p;
+        }
+
+        // 20. Let T be the substring of S from p to size.
+        parts.push(Some(string[input.offsets[p]..].to_string()));
+        parts
+    }
+
+    fn match_at(&self, input: &Input<'_>, position: usize) -> Option<Captures> {
+        Matcher::new(&input.chars, self.flags).match_at(
+            &self.pattern.root,
+            self.pattern.capture_count,
+            position,
+        )
+    }
+}
+
+impl fmt::Display for RegExp {
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-regexp.prototype.tostring)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "/{}/{}", self.source, self.flags)
+    }
+}
+
+impl Match {
+    /// The byte range of the whole match
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.captures[0]
+            .clone()
+            .expect("group 0 always participates in a match")
+    }
+
+    /// The byte range of a capture group, or `None` if the group did not participate in the match
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Range<usize>> {
+        self.captures.get(index).cloned().flatten()
+    }
+
+    /// The byte range of a named capture group
+    #[must_use]
+    pub fn name(&self, name: &str) -> Option<Range<usize>> {
+        let (_, index) = self
+            .group_names
+            .iter()
+            .find(|(group_name, _)| group_name == name)?;
+
+        self.get(*index)
+    }
+
+    /// The number of groups, including the whole match
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.captures.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.captures.is_empty()
+    }
+
+    /// Expand the `$` patterns in a replacement string
+    ///
+    /// `string` must be the string that was matched.
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-getsubstitution)
+    #[must_use]
+    pub fn expand(&self, string: &str, replacement: &str) -> String {
+        let range = self.range();
+        let capture_count = self.captures.len() - 1;
+        let mut result = String::new();
+
+        let mut remaining = replacement;
+        while let Some(dollar) = remaining.find('$') {
+            result.push_str(&remaining[..dollar]);
+            remaining = &remaining[dollar..];
+
+            let bytes = remaining.as_bytes();
+            let consumed = match bytes.get(1) {
+                Some(b'$') => {
+                    result.push('$');
+                    2
+                },
+                Some(b'&') => {
+                    result.push_str(&string[range.clone()]);
+                    2
+                },
+                Some(b'`') => {
+                    result.push_str(&string[..range.start]);
+                    2
+                },
+                Some(b'\'') => {
+                    result.push_str(&string[range.end..]);
+                    2
+                },
+                Some(digit @ b'0'..=b'9') => {
+                    let one_digit = usize::from(digit - b'0');
+                    let two_digits = bytes
+                        .get(2)
+                        .filter(|c| c.is_ascii_digit())
+                        .map(|c| one_digit * 10 + usize::from(c - b'0'));
+
+                    // Prefer two digit references if they refer to an existing group
+                    let (index, consumed) = match two_digits {
+                        Some(index) if (1..=capture_count).contains(&index) => (index, 3),
+                        _ => (one_digit, 2),
+                    };
+
+                    if (1..=capture_count).contains(&index) {
+                        if let Some(capture) = self.get(index) {
+                            result.push_str(&string[capture]);
+                        }
+                        consumed
+                    } else {
+                        result.push('$');
+                        1
+                    }
+                },
+                Some(b'<') if !self.group_names.is_empty() => match remaining.find('>') {
+                    Some(close) => {
+                        if let Some(capture) = self.name(&remaining[2..close]) {
+                            result.push_str(&string[capture]);
+                        }
+                        close + 1
+                    },
+                    None => {
+                        result.push('$');
+                        1
+                    },
+                },
+                _ => {
+                    result.push('$');
+                    1
+                },
+            };
+
+            remaining = &remaining[consumed..];
+        }
+
+        result.push_str(remaining);
+        result
+    }
+}
+
+impl<'a> Input<'a> {
+    fn new(string: &'a str) -> Self {
+        let (offsets, chars) = string.char_indices().unzip();
+        let mut input = Self {
+            string,
+            chars,
+            offsets,
+        };
+        input.offsets.push(string.len());
+        input
+    }
+
+    /// Convert a byte offset into a character index, rounding up if the offset
+    /// is not on a character boundary
+    fn char_index_at_or_after(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.string.len() {
+            return self.chars.len() + 1;
+        }
+
+        self.offsets.partition_point(|&offset| offset < byte_offset)
+    }
+
+    fn to_match(&self, captures: &Captures, group_names: &[(String, usize)]) -> Match {
+        let captures = captures
+            .iter()
+            .map(|capture| capture.map(|(start, end)| self.offsets[start]..self.offsets[end]))
+            .collect();
+
+        Match {
+            captures,
+            group_names: group_names.to_vec(),
+        }
+    }
+}
+
+/// <https://262.ecma-international.org/14.0/#sec-advancestringindex>
+fn advance_string_index(string: &str, index: usize) -> usize {
+    string[index..]
+        .chars()
+        .next()
+        .map_or(index + 1, |c| index + c.len_utf8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(pattern: &str, flags: &str, string: &str) -> Option<Vec<Option<String>>> {
+        let found = RegExp::new(pattern, flags).unwrap().exec(string)?;
+
+        let captures = (0..found.len())
+            .map(|index| found.get(index).map(|range| string[range].to_string()))
+            .collect();
+        Some(captures)
+    }
+
+    fn groups(groups: &[Option<&str>]) -> Option<Vec<Option<String>>> {
+        Some(
+            groups
+                .iter()
+                .map(|group| group.map(str::to_string))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn flags() {
+        let flags = Flags::parse("yigd").unwrap();
+        assert_eq!(flags.to_string(), "dgiy");
+
+        assert!(Flags::parse("gg").is_err());
+        assert!(Flags::parse("x").is_err());
+    }
+
+    #[test]
+    fn backtracking() {
+        assert_eq!(
+            find("(a+)(a+)b", "", "xaaab"),
+            groups(&[Some("aaab"), Some("aa"), Some("a")])
+        );
+        assert_eq!(
+            find("(a+?)(a*)", "", "aaa"),
+            groups(&[Some("aaa"), Some("a"), Some("aa")])
+        );
+        assert_eq!(find("a|ab|abc", "", "abc"), groups(&[Some("a")]));
+        assert_eq!(find("^b", "", "ab"), None);
+    }
+
+    #[test]
+    fn captures_are_reset_between_iterations() {
+        assert_eq!(
+            find("(z)((a+)?(b+)?(c))*", "", "zaacbbbcac"),
+            groups(&[
+                Some("zaacbbbcac"),
+                Some("z"),
+                Some("ac"),
+                Some("a"),
+                None,
+                Some("c")
+            ])
+        );
+    }
+
+    #[test]
+    fn empty_iterations_terminate() {
+        assert_eq!(find("(a*)*", "", "b"), groups(&[Some(""), None]));
+        assert_eq!(find("(?:a?)+b", "", "aab"), groups(&[Some("aab")]));
+    }
+
+    #[test]
+    fn backreferences() {
+        assert_eq!(
+            find("(?<quote>['\"]).*?\\k<quote>", "", "say \"hi\"!"),
+            groups(&[Some("\"hi\""), Some("\"")])
+        );
+        assert_eq!(find("(a)\\1", "i", "aA"), groups(&[Some("aA"), Some("a")]));
+        assert_eq!(find("\\1(a)", "", "a"), groups(&[Some("a"), Some("a")]));
+    }
+
+    #[test]
+    fn assertions() {
+        assert_eq!(find("\\bfoo\\b", "", "a foo b"), groups(&[Some("foo")]));
+        assert_eq!(
+            find("foo(?=bar)", "", "foobaz foobar"),
+            groups(&[Some("foo")])
+        );
+        assert_eq!(find("(?<!\\$)\\d+", "", "$4 5"), groups(&[Some("5")]));
+        assert_eq!(find("(?<=\\$)\\d+", "", "4 $5"), groups(&[Some("5")]));
+        assert_eq!(find("^b$", "m", "a\nb\nc"), groups(&[Some("b")]));
+        assert_eq!(find("^b$", "", "a\nb\nc"), None);
+    }
+
+    #[test]
+    fn dot_and_classes() {
+        assert_eq!(find("a.c", "", "a\nc"), None);
+        assert_eq!(find("a.c", "s", "a\nc"), groups(&[Some("a\nc")]));
+        assert_eq!(find("[^\\s\\d]+", "", " 12ab c"), groups(&[Some("ab")]));
+        assert_eq!(find("[a-z]+", "i", "123ABC"), groups(&[Some("ABC")]));
+    }
+
+    #[test]
+    fn unicode() {
+        // Byte offsets are reported even though patterns match code points
+        let mut regexp = RegExp::new("\\u{1F600}+", "gu").unwrap();
+        let found = regexp.exec("a😀😀b").unwrap();
+        assert_eq!(found.range(), 1..9);
+        assert_eq!(regexp.last_index(), 9);
+
+        assert_eq!(find("^.$", "u", "ä"), groups(&[Some("ä")]));
+    }
+
+    #[test]
+    fn global_and_sticky() {
+        let mut regexp = RegExp::new("a", "g").unwrap();
+        assert_eq!(regexp.exec("aba").unwrap().range(), 0..1);
+        assert_eq!(regexp.exec("aba").unwrap().range(), 2..3);
+        assert!(regexp.exec("aba").is_none());
+        assert_eq!(regexp.last_index(), 0);
+
+        let mut regexp = RegExp::new("a", "y").unwrap();
+        regexp.set_last_index(1);
+        assert!(regexp.exec("aba").is_none());
+        assert_eq!(regexp.last_index(), 0);
+    }
+
+    #[test]
+    fn match_string() {
+        let mut regexp = RegExp::new("\\d*", "g").unwrap();
+        let matches: Vec<_> = regexp
+            .match_string("1a22")
+            .iter()
+            .map(Match::range)
+            .collect();
+        assert_eq!(matches, [0..1, 1..1, 2..4, 4..4]);
+    }
+
+    #[test]
+    fn replace() {
+        let mut regexp = RegExp::new("(?<first>\\w+) (\\w+)", "").unwrap();
+        assert_eq!(
+            regexp.replace("hello world!", "$2 $<first> [$&] $$ $3 $'"),
+            "world hello [hello world] $ $3 !!"
+        );
+
+        let mut regexp = RegExp::new("o", "g").unwrap();
+        assert_eq!(regexp.replace("foo boo", "0"), "f00 b00");
+
+        let mut regexp = RegExp::new("x*", "g").unwrap();
+        assert_eq!(regexp.replace("abc", "-"), "-a-b-c-");
+    }
+
+    #[test]
+    fn split() {
+        let split = |pattern: &str, string: &str, limit: Option<usize>| {
+            RegExp::new(pattern, "").unwrap().split(string, limit)
+        };
+        let parts = |parts: &[Option<&str>]| -> Vec<Option<String>> {
+            parts.iter().map(|part| part.map(str::to_string)).collect()
+        };
+
+        assert_eq!(
+            split(",\\s*", "a, b,c", None),
+            parts(&[Some("a"), Some("b"), Some("c")])
+        );
+        assert_eq!(
+            split("(-)|(\\+)", "1-2+3", None),
+            parts(&[
+                Some("1"),
+                Some("-"),
+                None,
+                Some("2"),
+                None,
+                Some("+"),
+                Some("3")
+            ])
+        );
+        assert_eq!(split("", "abc", Some(2)), parts(&[Some("a"), Some("b")]));
+        assert_eq!(split("a*", "", None), parts(&[]));
+        assert_eq!(split("b", "", None), parts(&[Some("")]));
+    }
+
+    #[test]
+    fn long_inputs() {
+        // Every repetition used to be another level of recursion in the matcher
+        let long_string = "ab".repeat(50_000);
+
+        let mut regexp = RegExp::new("^(?:a|b)*$", "").unwrap();
+        assert_eq!(regexp.exec(&long_string).unwrap().range(), 0..100_000);
+
+        let mut regexp = RegExp::new("(ab)*", "").unwrap();
+        let found = regexp.exec(&long_string).unwrap();
+        assert_eq!(found.range(), 0..100_000);
+        assert_eq!(found.get(1), Some(99_998..100_000));
+
+        let mut regexp = RegExp::new("[ab]*?$", "").unwrap();
+        assert_eq!(regexp.exec(&long_string).unwrap().range(), 0..100_000);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(RegExp::new("(", "").is_err());
+        assert!(RegExp::new("a**", "").is_err());
+        assert!(RegExp::new("\\p{L}", "u").is_err());
+        assert!(RegExp::new("a", "gq").is_err());
+    }
+}
@@ -0,0 +1,845 @@
+//! Parses regular expression patterns into a tree of [Node]s
+//!
+//! Patterns without the `u` flag accept the additional syntax from
+//! [Annex B](https://262.ecma-international.org/14.0/#sec-regular-expressions-patterns),
+//! like octal escapes and unescaped braces.
+//!
+//! <https://262.ecma-international.org/14.0/#sec-patterns>
+
+use std::{collections::HashMap, ops::Range};
+
+use crate::parser::SyntaxError;
+
+/// Characters that can always be escaped with a backslash
+///
+/// <https://262.ecma-international.org/14.0/#prod-SyntaxCharacter>
+const SYNTAX_CHARACTERS: &str = "^$\\.*+?()[]{}|/";
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Node {
+    Empty,
+    Character(char),
+
+    /// `.`, which matches anything but line terminators unless the `s` flag is set
+    AnyCharacter,
+    Class(CharacterClass),
+
+    /// `^`
+    LineStart,
+
+    /// `$`
+    LineEnd,
+
+    /// `\b` or `\B`
+    WordBoundary {
+        negated: bool,
+    },
+    Group {
+        /// The index of the capture group, if the group captures
+        capture: Option<usize>,
+        node: Box<Node>,
+    },
+    Lookaround {
+        is_lookbehind: bool,
+        negated: bool,
+        node: Box<Node>,
+    },
+    Backreference(usize),
+    Sequence(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: u32,
+        max: Option<u32>,
+        greedy: bool,
+
+        /// The capture groups inside the repeated node, which are reset on every iteration
+        captures: Range<usize>,
+    },
+}
+
+/// `[...]` or one of the class escapes like `\d`
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct CharacterClass {
+    pub negated: bool,
+    pub items: Vec<ClassItem>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum ClassItem {
+    /// An inclusive range of characters, single characters are ranges of length one
+    Range(char, char),
+    Escape {
+        kind: ClassEscape,
+        negated: bool,
+    },
+}
+
+/// <https://262.ecma-international.org/14.0/#prod-CharacterClassEscape>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ClassEscape {
+    /// `\d`
+    Digit,
+
+    /// `\w`
+    Word,
+
+    /// `\s`
+    Whitespace,
+}
+
+/// A parsed pattern
+#[derive(Clone, Debug)]
+pub(super) struct Pattern {
+    pub root: Node,
+    pub capture_count: usize,
+    pub group_names: Vec<(String, usize)>,
+}
+
+pub(super) struct Parser<'a> {
+    pattern: &'a str,
+
+    /// Byte offset into the pattern
+    position: usize,
+    unicode: bool,
+
+    /// The number of capture groups that have been opened so far
+    opened_groups: usize,
+
+    /// Found by scanning the pattern before parsing, since backreferences can refer to later groups
+    total_groups: usize,
+    group_names: HashMap<String, usize>,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn parse(pattern: &'a str, unicode: bool) -> Result<Pattern, SyntaxError> {
+        let (total_groups, group_names) = scan_capture_groups(pattern);
+
+        let mut parser = Self {
+            pattern,
+            position: 0,
+            unicode,
+            opened_groups: 0,
+            total_groups,
+            group_names,
+        };
+
+        let root = parser.parse_disjunction()?;
+        if parser.peek().is_some() {
+            // The only thing that stops a disjunction early is a closing parenthesis
+            return Err(parser.error("unmatched ')'"));
+        }
+
+        let mut group_names: Vec<(String, usize)> = parser.group_names.into_iter().collect();
+        group_names.sort_by_key(|(_, index)| *index);
+
+        Ok(Pattern {
+            root,
+            capture_count: total_groups,
+            group_names,
+        })
+    }
+
+    fn error(&self, message: &str) -> SyntaxError {
+        SyntaxError::new(
+            self.position,
+            format!("Invalid regular expression: {message}"),
+        )
+    }
+
+    fn remaining(&self) -> &'a str {
+        &self.pattern[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.remaining().starts_with(expected) {
+            self.position += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-Disjunction>
+    fn parse_disjunction(&mut self) -> Result<Node, SyntaxError> {
+        let mut alternatives = vec![self.parse_alternative()?];
+
+        while self.eat("|") {
+            alternatives.push(self.parse_alternative()?);
+        }
+
+        if alternatives.len() == 1 {
+            Ok(alternatives
+                .pop()
+                .expect("there is exactly one alternative"))
+        } else {
+            Ok(Node::Alternation(alternatives))
+        }
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-Alternative>
+    fn parse_alternative(&mut self) -> Result<Node, SyntaxError> {
+        let mut terms = vec![];
+
+        while self.peek().is_some_and(|c| c != '|' && c != ')') {
+            terms.push(self.parse_term()?);
+        }
+
+        let node = match terms.len() {
+            0 => Node::Empty,
+            1 => terms.pop().expect("there is exactly one term"),
+            _ => Node::Sequence(terms),
+        };
+
+        Ok(node)
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-Term>
+    fn parse_term(&mut self) -> Result<Node, SyntaxError> {
+        // Assertions can't be quantified
+        if self.eat("^") {
+            return Ok(Node::LineStart);
+        }
+        if self.eat("$") {
+            return Ok(Node::LineEnd);
+        }
+        if self.eat("\\b") {
+            return Ok(Node::WordBoundary { negated: false });
+        }
+        if self.eat("\\B") {
+            return Ok(Node::WordBoundary { negated: true });
+        }
+
+        for (prefix, is_lookbehind, negated) in [
+            ("(?=", false, false),
+            ("(?!", false, true),
+            ("(?<=", true, false),
+            ("(?<!", true, true),
+        ] {
+            if self.eat(prefix) {
+                let node = self.parse_disjunction()?;
+                if !self.eat(")") {
+                    return Err(self.error("unterminated group"));
+                }
+
+                return Ok(Node::Lookaround {
+                    is_lookbehind,
+                    negated,
+                    node: Box::new(node),
+                });
+            }
+        }
+
+        let groups_before_atom = self.opened_groups;
+        let atom = self.parse_atom()?;
+
+        let Some((min, max)) = self.parse_quantifier()? else {
+            return Ok(atom);
+        };
+
+        let greedy = !self.eat("?");
+
+        Ok(Node::Repeat {
+            node: Box::new(atom),
+            min,
+            max,
+            greedy,
+            captures: groups_before_atom + 1..self.opened_groups + 1,
+        })
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-Quantifier>
+    fn parse_quantifier(&mut self) -> Result<Option<(u32, Option<u32>)>, SyntaxError> {
+        let quantifier = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                let start = self.position;
+                match self.parse_braced_quantifier() {
+                    Some(quantifier) => return self.validate_quantifier(quantifier),
+                    None if self.unicode => return Err(self.error("incomplete quantifier")),
+                    None => {
+                        // Annex B: The brace is a literal character instead
+                        self.position = start;
+                        return Ok(None);
+                    },
+                }
+            },
+            _ => return Ok(None),
+        };
+
+        self.next();
+        Ok(Some(quantifier))
+    }
+
+    fn validate_quantifier(
+        &self,
+        quantifier: (u32, Option<u32>),
+    ) -> Result<Option<(u32, Option<u32>)>, SyntaxError> {
+        if quantifier.1.is_some_and(|max| max < quantifier.0) {
+            return Err(self.error("numbers out of order in {} quantifier"));
+        }
+
+        Ok(Some(quantifier))
+    }
+
+    /// Parse `{n}`, `{n,}` or `{n,m}`, returns `None` without consuming anything if the input is
+    /// not a valid quantifier
+    fn parse_braced_quantifier(&mut self) -> Option<(u32, Option<u32>)> {
+        let start = self.position;
+
+        let quantifier = (|| {
+            self.eat("{").then_some(())?;
+            let min = self.parse_decimal_digits()?;
+
+            let max = if self.eat(",") {
+                if self.peek() == Some('}') {
+                    None
+                } else {
+                    Some(self.parse_decimal_digits()?)
+                }
+            } else {
+                Some(min)
+            };
+
+            self.eat("}").then_some((min, max))
+        })();
+
+        if quantifier.is_none() {
+            self.position = start;
+        }
+
+        quantifier
+    }
+
+    /// Values that don't fit into a `u32` saturate, which makes no difference when matching
+    fn parse_decimal_digits(&mut self) -> Option<u32> {
+        let digits = self
+            .remaining()
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.remaining().len());
+
+        if digits == 0 {
+            return None;
+        }
+
+        let value = self.remaining()[..digits]
+            .bytes()
+            .fold(0_u32, |value, digit| {
+                value
+                    .saturating_mul(10)
+                    .saturating_add(u32::from(digit - b'0'))
+            });
+
+        self.position += digits;
+        Some(value)
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-Atom>
+    fn parse_atom(&mut self) -> Result<Node, SyntaxError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error("unexpected end of pattern"));
+        };
+
+        match c {
+            '.' => {
+                self.next();
+                Ok(Node::AnyCharacter)
+            },
+            '(' => self.parse_group(),
+            '[' => {
+                self.next();
+                Ok(Node::Class(self.parse_class()?))
+            },
+            '\\' => {
+                self.next();
+                self.parse_atom_escape()
+            },
+            '*' | '+' | '?' => Err(self.error("nothing to repeat")),
+            '{' if self.unicode || self.parse_braced_quantifier().is_some() => {
+                Err(self.error("nothing to repeat"))
+            },
+            ']' | '}' if self.unicode => Err(self.error("lone quantifier brackets")),
+            _ => {
+                self.next();
+                Ok(Node::Character(c))
+            },
+        }
+    }
+
+    fn parse_group(&mut self) -> Result<Node, SyntaxError> {
+        let capture = if self.eat("(?:") {
+            None
+        } else if self.eat("(?<") {
+            // The names were already collected while scanning the pattern
+            let name_length = self
+                .remaining()
+                .find('>')
+                .ok_or_else(|| self.error("invalid capture group name"))?;
+            if !is_valid_group_name(&self.remaining()[..name_length]) {
+                return Err(self.error("invalid capture group name"));
+            }
+            self.position += name_length + 1;
+
+            self.opened_groups += 1;
+            Some(self.opened_groups)
+        } else if self.eat("(?") {
+            return Err(self.error("invalid group"));
+        } else {
+            self.eat("(");
+            self.opened_groups += 1;
+            Some(self.opened_groups)
+        };
+
+        let node = self.parse_disjunction()?;
+        if !self.eat(")") {
+            return Err(self.error("unterminated group"));
+        }
+
+        Ok(Node::Group {
+            capture,
+            node: Box::new(node),
+        })
+    }
+
+    /// Parse the part of an escape sequence outside of a character class after the backslash
+    ///
+    /// <https://262.ecma-international.org/14.0/#prod-AtomEscape>
+    fn parse_atom_escape(&mut self) -> Result<Node, SyntaxError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error("\\ at end of pattern"));
+        };
+
+        match c {
+            '1'..='9' => {
+                let start = self.position;
+                let index = self
+                    .parse_decimal_digits()
+                    .expect("there is at least one digit");
+
+                if index as usize <= self.total_groups {
+                    return Ok(Node::Backreference(index as usize));
+                }
+
+                if self.unicode {
+                    return Err(self.error("invalid escape"));
+                }
+
+                // Annex B: Treat the digits as an octal escape instead
+                self.position = start;
+                Ok(Node::Character(self.parse_legacy_octal_escape()))
+            },
+            'k' if self.unicode || !self.group_names.is_empty() => {
+                self.next();
+
+                let name = self
+                    .remaining()
+                    .strip_prefix('<')
+                    .and_then(|remaining| remaining.split_once('>'))
+                    .map(|(name, _)| name)
+                    .ok_or_else(|| self.error("invalid named reference"))?;
+                let index = *self
+                    .group_names
+                    .get(name)
+                    .ok_or_else(|| self.error("invalid named capture referenced"))?;
+
+                self.position += name.len() + 2;
+                Ok(Node::Backreference(index))
+            },
+            _ => {
+                if let Some(item) = self.parse_class_escape() {
+                    return Ok(Node::Class(CharacterClass {
+                        negated: false,
+                        items: vec![item],
+                    }));
+                }
+
+                Ok(Node::Character(self.parse_character_escape(false)?))
+            },
+        }
+    }
+
+    /// Parse `\d`, `\D`, `\w`, `\W`, `\s` or `\S` (without the backslash)
+    ///
+    /// <https://262.ecma-international.org/14.0/#prod-CharacterClassEscape>
+    fn parse_class_escape(&mut self) -> Option<ClassItem> {
+        let (kind, negated) = match self.peek()? {
+            'd' => (ClassEscape::Digit, false),
+            'D' => (ClassEscape::Digit, true),
+            'w' => (ClassEscape::Word, false),
+            'W' => (ClassEscape::Word, true),
+            's' => (ClassEscape::Whitespace, false),
+            'S' => (ClassEscape::Whitespace, true),
+            _ => return None,
+        };
+
+        self.next();
+        Some(ClassItem::Escape { kind, negated })
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-CharacterEscape>
+    fn parse_character_escape(&mut self, in_class: bool) -> Result<char, SyntaxError> {
+        let start = self.position;
+        let c = self
+            .next()
+            .ok_or_else(|| self.error("\\ at end of pattern"))?;
+
+        let escaped = match c {
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\x0b',
+            'c' => match self.peek() {
+                Some(letter) if letter.is_ascii_alphabetic() => {
+                    self.next();
+                    char::from(letter as u8 % 32)
+                },
+                Some(c) if in_class && !self.unicode && (c.is_ascii_digit() || c == '_') => {
+                    // Annex B: ClassControlLetter
+                    self.next();
+                    char::from(c as u8 % 32)
+                },
+                _ if self.unicode => return Err(self.error("invalid unicode escape")),
+                _ => {
+                    // Annex B: The backslash is a literal character
+                    self.position = start;
+                    '\\'
+                },
+            },
+            '0' if !self.peek().is_some_and(|c| c.is_ascii_digit()) => '\0',
+            '0'..='7' if !self.unicode => {
+                self.position = start;
+                self.parse_legacy_octal_escape()
+            },
+            'x' => match self.parse_hex_digits(2) {
+                Some(value) => char::from_u32(value).expect("two hex digits are a valid char"),
+                None if self.unicode => return Err(self.error("invalid escape")),
+                None => 'x',
+            },
+            'u' => match self.parse_unicode_escape() {
+                Some(c) => c,
+                None if self.unicode => return Err(self.error("invalid unicode escape")),
+                None => 'u',
+            },
+            '-' if self.unicode && in_class => '-',
+            c if self.unicode => {
+                if !SYNTAX_CHARACTERS.contains(c) {
+                    return Err(self.error("invalid escape"));
+                }
+                c
+            },
+            c => c,
+        };
+
+        Ok(escaped)
+    }
+
+    /// Annex B: <https://262.ecma-international.org/14.0/#prod-annexB-LegacyOctalEscapeSequence>
+    ///
+    /// Digits that are not octal are treated as literal characters.
+    fn parse_legacy_octal_escape(&mut self) -> char {
+        let Some(first) = self.next() else {
+            return '\\';
+        };
+
+        let Some(mut value) = first.to_digit(8) else {
+            return first;
+        };
+
+        // Up to three digits, as long as the value fits into a byte
+        for _ in 0..2 {
+            match self.peek().and_then(|c| c.to_digit(8)) {
+                Some(digit) if value * 8 + digit <= 0o377 => {
+                    value = value * 8 + digit;
+                    self.next();
+                },
+                _ => break,
+            }
+        }
+
+        char::from_u32(value).expect("octal escapes are always valid chars")
+    }
+
+    fn parse_hex_digits(&mut self, count: usize) -> Option<u32> {
+        let digits = self.remaining().get(..count)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        self.position += count;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Parse the part of a `\u` escape after the `u`
+    ///
+    /// <https://262.ecma-international.org/14.0/#prod-RegExpUnicodeEscapeSequence>
+    fn parse_unicode_escape(&mut self) -> Option<char> {
+        let start = self.position;
+
+        if self.unicode && self.eat("{") {
+            let digits = self.remaining().find('}')?;
+            let value = u32::from_str_radix(&self.remaining()[..digits], 16).ok();
+
+            match value.and_then(char::from_u32) {
+                Some(c) => {
+                    self.position += digits + 1;
+                    return Some(c);
+                },
+                None => {
+                    self.position = start;
+                    return None;
+                },
+            }
+        }
+
+        let value = self.parse_hex_digits(4)?;
+
+        // In unicode mode, surrogate pairs are combined into a single code point
+        if self.unicode && (0xD800..0xDC00).contains(&value) {
+            let before_trail = self.position;
+            let trail = self
+                .eat("\\u")
+                .then(|| self.parse_hex_digits(4))
+                .flatten()
+                .filter(|trail| (0xDC00..0xE000).contains(trail));
+
+            if let Some(trail) = trail {
+                let code_point = 0x10000 + ((value - 0xD800) << 10) + (trail - 0xDC00);
+                return char::from_u32(code_point);
+            }
+            self.position = before_trail;
+        }
+
+        // FIXME: Lone surrogates can't be represented in a rust string, so they never match anything
+        Some(char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Parse a character class after the opening bracket
+    ///
+    /// <https://262.ecma-international.org/14.0/#prod-CharacterClass>
+    fn parse_class(&mut self) -> Result<CharacterClass, SyntaxError> {
+        let negated = self.eat("^");
+        let mut items = vec![];
+
+        loop {
+            if self.eat("]") {
+                break;
+            }
+
+            let first = self.parse_class_atom()?;
+
+            // A dash at the end of the class is a literal character
+            if self.remaining().starts_with('-') && !self.remaining().starts_with("-]") {
+                self.next();
+                let second = self.parse_class_atom()?;
+
+                match (first, second) {
+                    (ClassItem::Range(low, _), ClassItem::Range(high, _)) => {
+                        if high < low {
+                            return Err(self.error("range out of order in character class"));
+                        }
+                        items.push(ClassItem::Range(low, high));
+                    },
+                    _ if self.unicode => return Err(self.error("invalid character class")),
+                    _ => {
+                        // Annex B: Ranges involving class escapes are interpreted literally
+                        items.extend([first, ClassItem::Range('-', '-'), second]);
+                    },
+                }
+            } else {
+                items.push(first);
+            }
+        }
+
+        Ok(CharacterClass { negated, items })
+    }
+
+    /// <https://262.ecma-international.org/14.0/#prod-ClassAtom>
+    fn parse_class_atom(&mut self) -> Result<ClassItem, SyntaxError> {
+        let c = self
+            .next()
+            .ok_or_else(|| self.error("unterminated character class"))?;
+
+        if c != '\\' {
+            return Ok(ClassItem::Range(c, c));
+        }
+
+        if let Some(item) = self.parse_class_escape() {
+            return Ok(item);
+        }
+
+        // Inside a class, \b means backspace
+        if self.eat("b") {
+            return Ok(ClassItem::Range('\x08', '\x08'));
+        }
+
+        // Annex B: Decimal escapes are octal or literal characters inside classes
+        if !self.unicode && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let c = self.parse_legacy_octal_escape();
+            return Ok(ClassItem::Range(c, c));
+        }
+
+        let c = self.parse_character_escape(true)?;
+        Ok(ClassItem::Range(c, c))
+    }
+}
+
+/// Count the capture groups in a pattern and find the names of named groups
+fn scan_capture_groups(pattern: &str) -> (usize, HashMap<String, usize>) {
+    let mut count = 0;
+    let mut names = HashMap::new();
+    let mut in_class = false;
+
+    let mut chars = pattern.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            },
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '(' if !in_class => {
+                let rest = &pattern[index + 1..];
+                if !rest.starts_with('?') {
+                    count += 1;
+                } else if let Some(name) = rest
+                    .strip_prefix("?<")
+                    .filter(|name| !name.starts_with('=') && !name.starts_with('!'))
+                {
+                    count += 1;
+                    if let Some((name, _)) = name.split_once('>') {
+                        names.insert(name.to_string(), count);
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    (count, names)
+}
+
+/// <https://262.ecma-international.org/14.0/#prod-RegExpIdentifierName>
+fn is_valid_group_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '$' || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '$' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(pattern: &str) -> Node {
+        Parser::parse(pattern, false).unwrap().root
+    }
+
+    #[test]
+    fn quantifiers() {
+        assert_eq!(
+            parse("a{2,3}?"),
+            Node::Repeat {
+                node: Box::new(Node::Character('a')),
+                min: 2,
+                max: Some(3),
+                greedy: false,
+                captures: 1..1,
+            }
+        );
+
+        // Annex B allows braces that don't form a quantifier
+        assert_eq!(
+            parse("a{,"),
+            Node::Sequence(vec![
+                Node::Character('a'),
+                Node::Character('{'),
+                Node::Character(',')
+            ])
+        );
+        assert!(Parser::parse("a{,", true).is_err());
+        assert!(Parser::parse("a{3,2}", false).is_err());
+        assert!(Parser::parse("*a", false).is_err());
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(parse("\\x41"), Node::Character('A'));
+        assert_eq!(
+            parse("\\u{41}"),
+            Node::Repeat {
+                node: Box::new(Node::Character('u')),
+                min: 41,
+                max: Some(41),
+                greedy: true,
+                captures: 1..1,
+            }
+        );
+        assert_eq!(
+            Parser::parse("\\u{1F600}", true).unwrap().root,
+            Node::Character('😀')
+        );
+        assert_eq!(
+            Parser::parse("\\ud83d\\ude00", true).unwrap().root,
+            Node::Character('😀')
+        );
+        assert_eq!(parse("\\101"), Node::Character('A'));
+        assert_eq!(parse("\\cJ"), Node::Character('\n'));
+        assert!(Parser::parse("\\q", true).is_err());
+    }
+
+    #[test]
+    fn groups_and_backreferences() {
+        let pattern = Parser::parse("\\k<b>(?<a>x)(?:y)(?<b>z)\\1", false).unwrap();
+        assert_eq!(pattern.capture_count, 2);
+        assert_eq!(
+            pattern.group_names,
+            [("a".to_string(), 1), ("b".to_string(), 2)]
+        );
+
+        let Node::Sequence(terms) = pattern.root else {
+            panic!("expected a sequence");
+        };
+        assert_eq!(terms[0], Node::Backreference(2));
+        assert_eq!(terms[4], Node::Backreference(1));
+
+        assert!(Parser::parse("(a", false).is_err());
+        assert!(Parser::parse("a)", false).is_err());
+        assert!(Parser::parse("(?<1a>x)", false).is_err());
+    }
+
+    #[test]
+    fn character_classes() {
+        assert_eq!(
+            parse("[^a-c\\d-]"),
+            Node::Class(CharacterClass {
+                negated: true,
+                items: vec![
+                    ClassItem::Range('a', 'c'),
+                    ClassItem::Escape {
+                        kind: ClassEscape::Digit,
+                        negated: false
+                    },
+                    ClassItem::Range('-', '-'),
+                ]
+            })
+        );
+        assert!(Parser::parse("[z-a]", false).is_err());
+        assert!(Parser::parse("[a", false).is_err());
+        assert!(Parser::parse("[\\d-z]", true).is_err());
+    }
+}
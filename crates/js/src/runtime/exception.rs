@@ -26,4 +26,13 @@ impl Exception {
             value: "TypeError".to_string().into(),
         }
     }
+
+    #[must_use]
+    pub fn range_error() -> Self {
+        // FIXME: This should be a "rangeerror" object, but we don't
+        //        really support objects yet
+        Self {
+            value: "RangeError".to_string().into(),
+        }
+    }
 }
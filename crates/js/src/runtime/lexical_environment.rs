@@ -28,4 +28,11 @@ impl LexicalEnvironment {
 
         &mut environment.variables[binding.index]
     }
+
+    /// The number of variables in this environment and all of its outer environments
+    #[must_use]
+    pub fn num_variables(&self) -> usize {
+        let outer_variables = self.outer.as_ref().map_or(0, |outer| outer.num_variables());
+        self.variables.len() + outer_variables
+    }
 }
@@ -8,4 +8,4 @@ pub use exception::{Exception, ThrowCompletionOr};
 pub use executable::Executable;
 pub use lexical_environment::LexicalEnvironment;
 pub use opcode::OpCode;
-pub use vm::Vm;
+pub use vm::{InterruptHandle, Vm};
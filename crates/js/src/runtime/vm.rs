@@ -1,3 +1,13 @@
+use std::{
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use sl_std::memory::{MemoryReport, MemoryReporter};
+
 use crate::{compiler, value::StringOrNumericBinaryOperator, Value};
 
 use super::{Executable, LexicalEnvironment, OpCode};
@@ -7,14 +17,52 @@ pub struct Vm {
     program_counter: usize,
     stack: Vec<Value>,
     lexical_environment: LexicalEnvironment,
+    interrupt: InterruptHandle,
+}
+
+/// Stops a [Vm] from another thread
+///
+/// Once interrupted, the vm stops before its next instruction and does not execute
+/// anything anymore.
+#[derive(Clone, Debug, Default)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_interrupted(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 impl Vm {
+    /// Create a vm that can be stopped through `interrupt`
+    #[must_use]
+    pub fn with_interrupt_handle(interrupt: InterruptHandle) -> Self {
+        Self {
+            interrupt,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    /// Run the executable until it finishes or the vm is interrupted
     pub fn execute(&mut self, executable: Executable) {
         self.lexical_environment
             .reserve_variables(executable.num_variables);
 
         while let Some(instruction) = executable.fetch_instruction(self.program_counter) {
+            if self.interrupt.is_interrupted() {
+                return;
+            }
+
             self.program_counter += 1;
 
             match instruction {
@@ -87,3 +135,39 @@ impl Vm {
         *variable = value;
     }
 }
+
+impl MemoryReporter for Vm {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        report.record("js/stack", self.stack.capacity() * mem::size_of::<Value>());
+        report.record(
+            "js/variables",
+            self.lexical_environment.num_variables() * mem::size_of::<Value>(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn interrupt_infinite_loop() {
+        let executable = Executable {
+            num_variables: 0,
+            constants: compiler::ConstantStore::default(),
+            bytecode: vec![OpCode::Jump(0)],
+        };
+
+        let mut vm = Vm::default();
+        let interrupt = vm.interrupt_handle();
+        let interrupter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            interrupt.interrupt();
+        });
+
+        vm.execute(executable);
+        interrupter.join().unwrap();
+    }
+}
@@ -0,0 +1,359 @@
+//! <https://262.ecma-international.org/14.0/#sec-arraybuffer-objects>
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use crate::{
+    runtime::{Exception, ThrowCompletionOr},
+    Number, Value,
+};
+
+/// The type of the elements in a [TypedArray](super::TypedArray) or a value in a [DataView](super::DataView)
+///
+/// [Specification](https://262.ecma-international.org/14.0/#table-the-typedarray-constructors)
+///
+/// NOTE: `BigInt64` and `BigUint64` are missing because the runtime does not support bigints yet
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementType {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+}
+
+impl ElementType {
+    /// The size of a single element, in bytes
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        match self {
+            Self::Int8 | Self::Uint8 | Self::Uint8Clamped => 1,
+            Self::Int16 | Self::Uint16 => 2,
+            Self::Int32 | Self::Uint32 | Self::Float32 => 4,
+            Self::Float64 => 8,
+        }
+    }
+}
+
+/// A fixed-length block of raw binary data
+///
+/// Clones of an [ArrayBuffer] share the same data, like references to the same object would.
+///
+/// [Specification](https://262.ecma-international.org/14.0/#sec-arraybuffer-constructor)
+#[derive(Clone, Default)]
+pub struct ArrayBuffer {
+    /// The `[[ArrayBufferData]]` slot, which is `None` if the buffer is detached
+    data: Rc<RefCell<Option<Vec<u8>>>>,
+}
+
+impl ArrayBuffer {
+    /// <https://262.ecma-international.org/14.0/#sec-allocatearraybuffer>
+    pub fn allocate(byte_length: usize) -> ThrowCompletionOr<Self> {
+        // 2. Let block be ? CreateByteDataBlock(byteLength).
+        let block = create_byte_data_block(byte_length)?;
+
+        // 3. Set obj.[[ArrayBufferData]] to block.
+        // 4. Set obj.[[ArrayBufferByteLength]] to byteLength.
+        let buffer = Self {
+            data: Rc::new(RefCell::new(Some(block))),
+        };
+
+        // 5. Return obj.
+        Ok(buffer)
+    }
+
+    /// Create a buffer that holds the given bytes
+    #[must_use]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            data: Rc::new(RefCell::new(Some(bytes))),
+        }
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-isdetachedbuffer>
+    #[must_use]
+    pub fn is_detached(&self) -> bool {
+        self.data.borrow().is_none()
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-detacharraybuffer>
+    pub fn detach(&self) {
+        *self.data.borrow_mut() = None;
+    }
+
+    /// The length of the buffer in bytes, which is zero for detached buffers
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-get-arraybuffer.prototype.bytelength)
+    #[must_use]
+    pub fn byte_length(&self) -> usize {
+        self.data.borrow().as_ref().map_or(0, Vec::len)
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-getvaluefrombuffer>
+    ///
+    /// # Panics
+    /// This function panics if the buffer is detached or if the value is out of bounds.
+    #[must_use]
+    pub fn get_value(
+        &self,
+        byte_index: usize,
+        element_type: ElementType,
+        is_little_endian: bool,
+    ) -> Value {
+        // 1. Assert: IsDetachedBuffer(arrayBuffer) is false.
+        // 2. Assert: There are sufficient bytes in arrayBuffer starting at byteIndex to represent a value of type.
+        // 3. Let block be arrayBuffer.[[ArrayBufferData]].
+        let data = self.data.borrow();
+        let block = data.as_ref().expect("buffer must not be detached");
+
+        // 4. Let elementSize be the Element Size value specified in Table 71 for Element Type type.
+        // 5-8. Let rawValue be a List of elementSize containing, in order, the elementSize sequence
+        //      of bytes starting with block[byteIndex].
+        let raw_value = &block[byte_index..byte_index + element_type.size()];
+
+        // 9. Return RawBytesToNumeric(type, rawValue, isLittleEndian).
+        raw_bytes_to_numeric(element_type, raw_value, is_little_endian).into()
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-setvalueinbuffer>
+    ///
+    /// # Panics
+    /// This function panics if the buffer is detached or if the value is out of bounds.
+    pub fn set_value(
+        &self,
+        byte_index: usize,
+        element_type: ElementType,
+        value: Number,
+        is_little_endian: bool,
+    ) {
+        // 1. Assert: IsDetachedBuffer(arrayBuffer) is false.
+        // 2. Assert: There are sufficient bytes in arrayBuffer starting at byteIndex to represent a value of type.
+        // 4. Let block be arrayBuffer.[[ArrayBufferData]].
+        let mut data = self.data.borrow_mut();
+        let block = data.as_mut().expect("buffer must not be detached");
+
+        // 6. Let rawBytes be NumericToRawBytes(type, value, isLittleEndian).
+        let raw_bytes = numeric_to_raw_bytes(element_type, value, is_little_endian);
+
+        // 7-8. Store the individual bytes of rawBytes into block, starting at block[byteIndex].
+        block[byte_index..byte_index + raw_bytes.len()].copy_from_slice(&raw_bytes);
+    }
+
+    /// Copy `byte_length` bytes, starting at `byte_index`, out of the buffer
+    ///
+    /// Detached buffers are treated as if they were empty.
+    ///
+    /// # Panics
+    /// This function panics if the range is out of bounds.
+    #[must_use]
+    pub fn copy_bytes(&self, byte_index: usize, byte_length: usize) -> Vec<u8> {
+        self.data.borrow().as_ref().map_or_else(Vec::new, |block| {
+            block[byte_index..byte_index + byte_length].to_vec()
+        })
+    }
+
+    /// Overwrite the bytes starting at `byte_index`
+    ///
+    /// # Panics
+    /// This function panics if the buffer is detached or if the bytes are out of bounds.
+    pub fn write_bytes(&self, byte_index: usize, bytes: &[u8]) {
+        let mut data = self.data.borrow_mut();
+        let block = data.as_mut().expect("buffer must not be detached");
+
+        block[byte_index..byte_index + bytes.len()].copy_from_slice(bytes);
+    }
+}
+
+impl PartialEq for ArrayBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        // Buffers are compared by identity
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl fmt::Debug for ArrayBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrayBuffer")
+            .field("byte_length", &self.byte_length())
+            .field("detached", &self.is_detached())
+            .finish()
+    }
+}
+
+/// <https://262.ecma-international.org/14.0/#sec-createbytedatablock>
+fn create_byte_data_block(size: usize) -> ThrowCompletionOr<Vec<u8>> {
+    // 1. If size > 2^53 - 1, throw a RangeError exception.
+    if size as u64 > 2_u64.pow(53) - 1 {
+        return Err(Exception::range_error());
+    }
+
+    // 2. Let db be a new Data Block value consisting of size bytes.
+    //    If it is impossible to create such a Data Block, throw a RangeError exception.
+    let mut block = vec![];
+    block
+        .try_reserve_exact(size)
+        .map_err(|_| Exception::range_error())?;
+
+    // 3. Set all of the bytes of db to 0.
+    block.resize(size, 0);
+
+    // 4. Return db.
+    Ok(block)
+}
+
+/// <https://262.ecma-international.org/14.0/#sec-rawbytestonumeric>
+fn raw_bytes_to_numeric(
+    element_type: ElementType,
+    raw_bytes: &[u8],
+    is_little_endian: bool,
+) -> Number {
+    // 2. If isLittleEndian is false, reverse the order of the elements of rawBytes.
+    let mut bytes = [0; 8];
+    bytes[..raw_bytes.len()].copy_from_slice(raw_bytes);
+    if !is_little_endian {
+        bytes[..raw_bytes.len()].reverse();
+    }
+
+    let value = match element_type {
+        // 3. If type is Float32, then
+        //    a. Let value be the byte elements of rawBytes concatenated and interpreted as a
+        //       little-endian bit string encoding of an IEEE 754-2019 binary32 value.
+        //    b. If value is an IEEE 754-2019 binary32 NaN value, return the NaN Number value.
+        //    c. Return the Number value that corresponds to value.
+        ElementType::Float32 => {
+            f64::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        },
+
+        // 4. If type is Float64, then
+        //    [...]
+        ElementType::Float64 => f64::from_le_bytes(bytes),
+
+        // 5-9. Interpret the bytes as a little-endian (un)signed integer
+        ElementType::Int8 => f64::from(bytes[0] as i8),
+        ElementType::Uint8 | ElementType::Uint8Clamped => f64::from(bytes[0]),
+        ElementType::Int16 => f64::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+        ElementType::Uint16 => f64::from(u16::from_le_bytes([bytes[0], bytes[1]])),
+        ElementType::Int32 => {
+            f64::from(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        },
+        ElementType::Uint32 => {
+            f64::from(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        },
+    };
+
+    // NOTE: NaN values are canonicalized implicitly, since Number does not expose its bits
+    Number::new(value)
+}
+
+/// <https://262.ecma-international.org/14.0/#sec-numerictorawbytes>
+fn numeric_to_raw_bytes(
+    element_type: ElementType,
+    value: Number,
+    is_little_endian: bool,
+) -> Vec<u8> {
+    let mut raw_bytes = match element_type {
+        // 1. If type is Float32, then
+        //    a. Let rawBytes be a List whose elements are the 4 bytes that are the result of
+        //       converting value to IEEE 754-2019 binary32 format using roundTiesToEven mode.
+        ElementType::Float32 => (value.to_f64() as f32).to_le_bytes().to_vec(),
+
+        // 2. Else if type is Float64, then
+        //    [...]
+        ElementType::Float64 => value.to_f64().to_le_bytes().to_vec(),
+
+        // 3. Else,
+        //    a. Let n be the Element Size value specified in Table 71 for Element Type type.
+        //    b. Let convOp be the abstract operation named in the Conversion Operation column in Table 71 for Element Type type.
+        //    c. Let intValue be ℝ(convOp(value)).
+        //    d. If intValue ≥ 0, then
+        //       i. Let rawBytes be a List whose elements are the n-byte binary encoding of intValue.
+        //    e. Else,
+        //       i. Let rawBytes be a List whose elements are the n-byte binary two's complement encoding of intValue.
+        ElementType::Int8 => value.to_int8().to_le_bytes().to_vec(),
+        ElementType::Uint8 => value.to_uint8().to_le_bytes().to_vec(),
+        ElementType::Uint8Clamped => value.to_uint8_clamp().to_le_bytes().to_vec(),
+        ElementType::Int16 => value.to_int16().to_le_bytes().to_vec(),
+        ElementType::Uint16 => value.to_uint16().to_le_bytes().to_vec(),
+        ElementType::Int32 => value.to_int32().to_le_bytes().to_vec(),
+        ElementType::Uint32 => value.to_uint32().to_le_bytes().to_vec(),
+    };
+
+    // 4. If isLittleEndian is false, reverse the order of the elements of rawBytes.
+    if !is_little_endian {
+        raw_bytes.reverse();
+    }
+
+    // 5. Return rawBytes.
+    raw_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_order() {
+        let buffer = ArrayBuffer::allocate(4).unwrap();
+
+        buffer.set_value(
+            0,
+            ElementType::Uint32,
+            Number::new(0x01020304 as f64),
+            false,
+        );
+        assert_eq!(
+            buffer.get_value(0, ElementType::Uint32, false),
+            Number::new(0x01020304 as f64).into()
+        );
+        assert_eq!(
+            buffer.get_value(0, ElementType::Uint8, false),
+            Number::new(1.).into()
+        );
+        assert_eq!(
+            buffer.get_value(0, ElementType::Uint16, true),
+            Number::new(0x0201 as f64).into()
+        );
+    }
+
+    #[test]
+    fn element_conversions() {
+        let buffer = ArrayBuffer::allocate(8).unwrap();
+        let roundtrip = |element_type, value: f64| {
+            buffer.set_value(0, element_type, Number::new(value), true);
+            buffer.get_value(0, element_type, true)
+        };
+
+        assert_eq!(roundtrip(ElementType::Int8, 255.), Number::new(-1.).into());
+        assert_eq!(roundtrip(ElementType::Uint8, -1.), Number::new(255.).into());
+        assert_eq!(
+            roundtrip(ElementType::Uint8Clamped, -1.),
+            Number::new(0.).into()
+        );
+        assert_eq!(
+            roundtrip(ElementType::Int16, 40000.),
+            Number::new(-25536.).into()
+        );
+        assert_eq!(
+            roundtrip(ElementType::Float32, 0.1),
+            Number::new(f64::from(0.1_f32)).into()
+        );
+        assert_eq!(
+            roundtrip(ElementType::Float64, 0.1),
+            Number::new(0.1).into()
+        );
+    }
+
+    #[test]
+    fn detach() {
+        let buffer = ArrayBuffer::allocate(16).unwrap();
+        let same_buffer = buffer.clone();
+        assert_eq!(buffer.byte_length(), 16);
+
+        same_buffer.detach();
+        assert!(buffer.is_detached());
+        assert_eq!(buffer.byte_length(), 0);
+    }
+}
@@ -0,0 +1,184 @@
+//! <https://262.ecma-international.org/14.0/#sec-dataview-objects>
+
+use crate::{
+    runtime::{Exception, ThrowCompletionOr},
+    Value,
+};
+
+use super::{ArrayBuffer, ElementType};
+
+/// A view of an [ArrayBuffer] that reads and writes values of any type at any offset,
+/// with explicit byte order
+///
+/// [Specification](https://262.ecma-international.org/14.0/#sec-dataview-constructor)
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataView {
+    buffer: ArrayBuffer,
+    byte_offset: usize,
+    byte_length: usize,
+}
+
+impl DataView {
+    /// Create a view of (part of) a buffer
+    ///
+    /// If no length is given then the view extends to the end of the buffer.
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-dataview-buffer-byteoffset-bytelength)
+    pub fn new(
+        buffer: ArrayBuffer,
+        byte_offset: usize,
+        byte_length: Option<usize>,
+    ) -> ThrowCompletionOr<Self> {
+        // 4. If IsDetachedBuffer(buffer) is true, throw a TypeError exception.
+        if buffer.is_detached() {
+            return Err(Exception::type_error());
+        }
+
+        // 5. Let bufferByteLength be buffer.[[ArrayBufferByteLength]].
+        let buffer_byte_length = buffer.byte_length();
+
+        // 6. If offset > bufferByteLength, throw a RangeError exception.
+        if byte_offset > buffer_byte_length {
+            return Err(Exception::range_error());
+        }
+
+        let view_byte_length = match byte_length {
+            // 7. If byteLength is undefined, then
+            //    a. Let viewByteLength be bufferByteLength - offset.
+            None => buffer_byte_length - byte_offset,
+
+            // 8. Else,
+            //    a. Let viewByteLength be ? ToIndex(byteLength).
+            //    b. If offset + viewByteLength > bufferByteLength, throw a RangeError exception.
+            Some(view_byte_length) => {
+                if byte_offset.saturating_add(view_byte_length) > buffer_byte_length {
+                    return Err(Exception::range_error());
+                }
+                view_byte_length
+            },
+        };
+
+        let data_view = Self {
+            buffer,
+            byte_offset,
+            byte_length: view_byte_length,
+        };
+
+        Ok(data_view)
+    }
+
+    #[must_use]
+    pub fn buffer(&self) -> &ArrayBuffer {
+        &self.buffer
+    }
+
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-get-dataview.prototype.bytelength)
+    pub fn byte_length(&self) -> ThrowCompletionOr<usize> {
+        // 4. If IsViewOutOfBounds(viewRecord) is true, throw a TypeError exception.
+        if self.buffer.is_detached() {
+            return Err(Exception::type_error());
+        }
+
+        Ok(self.byte_length)
+    }
+
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-get-dataview.prototype.byteoffset)
+    pub fn byte_offset(&self) -> ThrowCompletionOr<usize> {
+        if self.buffer.is_detached() {
+            return Err(Exception::type_error());
+        }
+
+        Ok(self.byte_offset)
+    }
+
+    /// Compute the index of a value inside the buffer, throwing if the view can't hold it
+    fn buffer_index(
+        &self,
+        request_index: usize,
+        element_type: ElementType,
+    ) -> ThrowCompletionOr<usize> {
+        // 6. If IsViewOutOfBounds(viewRecord) is true, throw a TypeError exception.
+        if self.buffer.is_detached() {
+            return Err(Exception::type_error());
+        }
+
+        // 8. Let elementSize be the Element Size value specified in Table 71 for Element Type type.
+        // 9. If getIndex + elementSize > viewSize, throw a RangeError exception.
+        if request_index.saturating_add(element_type.size()) > self.byte_length {
+            return Err(Exception::range_error());
+        }
+
+        // 10. Let bufferIndex be getIndex + viewOffset.
+        Ok(request_index + self.byte_offset)
+    }
+
+    /// Read a value at the given offset within the view
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-getviewvalue)
+    pub fn get_value(
+        &self,
+        request_index: usize,
+        is_little_endian: bool,
+        element_type: ElementType,
+    ) -> ThrowCompletionOr<Value> {
+        let buffer_index = self.buffer_index(request_index, element_type)?;
+
+        // 11. Return GetValueFromBuffer(view.[[ViewedArrayBuffer]], bufferIndex, type, false, Unordered, isLittleEndian).
+        Ok(self
+            .buffer
+            .get_value(buffer_index, element_type, is_little_endian))
+    }
+
+    /// Write a value at the given offset within the view
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-setviewvalue)
+    pub fn set_value(
+        &self,
+        request_index: usize,
+        is_little_endian: bool,
+        element_type: ElementType,
+        value: &Value,
+    ) -> ThrowCompletionOr<()> {
+        // 4. If IsBigIntElementType(type) is true, let numberValue be ? ToBigInt(value).
+        // 5. Otherwise, let numberValue be ? ToNumber(value).
+        let number_value = value.to_number()?;
+
+        let buffer_index = self.buffer_index(request_index, element_type)?;
+
+        // 12. Perform SetValueInBuffer(view.[[ViewedArrayBuffer]], bufferIndex, type, numberValue, false, Unordered, isLittleEndian).
+        self.buffer
+            .set_value(buffer_index, element_type, number_value, is_little_endian);
+
+        // 13. Return undefined.
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Number;
+
+    #[test]
+    fn read_and_write() {
+        let buffer = ArrayBuffer::allocate(8).unwrap();
+        let view = DataView::new(buffer.clone(), 2, Some(4)).unwrap();
+
+        view.set_value(0, false, ElementType::Int16, &Number::new(-2.).into())
+            .unwrap();
+        assert_eq!(
+            view.get_value(0, true, ElementType::Uint16).unwrap(),
+            Number::new(0xfeff as f64).into()
+        );
+        assert_eq!(
+            buffer.get_value(2, ElementType::Uint8, true),
+            Number::new(0xff as f64).into()
+        );
+
+        // Reads past the end of the view fail, even if the buffer is large enough
+        assert!(view.get_value(2, true, ElementType::Float32).is_err());
+
+        buffer.detach();
+        assert!(view.get_value(0, true, ElementType::Int8).is_err());
+    }
+}
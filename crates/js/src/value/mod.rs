@@ -1,12 +1,18 @@
+mod array_buffer;
+mod data_view;
 mod number;
 pub mod object;
 mod reference_record;
 mod symbol;
+mod typed_array;
 
+pub use array_buffer::{ArrayBuffer, ElementType};
+pub use data_view::DataView;
 pub use number::Number;
 pub use object::Object;
 pub use reference_record::{ReferenceRecord, ValueOrReference};
 pub use symbol::Symbol;
+pub use typed_array::TypedArray;
 
 use crate::{
     parser::Identifier,
@@ -16,6 +16,11 @@ impl Number {
         Self(value)
     }
 
+    #[must_use]
+    pub const fn to_f64(&self) -> f64 {
+        self.0
+    }
+
     #[must_use]
     pub fn is_nan(&self) -> bool {
         self.0.is_nan()
@@ -39,6 +44,67 @@ impl Number {
         todo!()
     }
 
+    /// Steps 1-3 of the integer conversion operations like [ToInt32](Self::to_int32)
+    fn truncate_modulo(&self, modulus: f64) -> f64 {
+        // 1. If number is not finite or number is either +0𝔽 or -0𝔽, return +0𝔽.
+        if !self.0.is_finite() || self.is_zero() {
+            return 0.;
+        }
+
+        // 2. Let int be truncate(ℝ(number)).
+        // 3. Let intNbit be int modulo 2^N.
+        self.0.trunc().rem_euclid(modulus)
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-toint32>
+    #[must_use]
+    pub fn to_int32(&self) -> i32 {
+        // 4. If int32bit ≥ 2^31, return 𝔽(int32bit - 2^32); otherwise return 𝔽(int32bit).
+        self.to_uint32() as i32
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-touint32>
+    #[must_use]
+    pub fn to_uint32(&self) -> u32 {
+        self.truncate_modulo(2_f64.powi(32)) as u32
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-toint16>
+    #[must_use]
+    pub fn to_int16(&self) -> i16 {
+        self.to_uint16() as i16
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-touint16>
+    #[must_use]
+    pub fn to_uint16(&self) -> u16 {
+        self.truncate_modulo(2_f64.powi(16)) as u16
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-toint8>
+    #[must_use]
+    pub fn to_int8(&self) -> i8 {
+        self.to_uint8() as i8
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-touint8>
+    #[must_use]
+    pub fn to_uint8(&self) -> u8 {
+        self.truncate_modulo(2_f64.powi(8)) as u8
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-touint8clamp>
+    #[must_use]
+    pub fn to_uint8_clamp(&self) -> u8 {
+        // 2. If number is NaN, return +0𝔽.
+        // 3. Let mv be the extended mathematical value of number.
+        // 4. Let clamped be the result of clamping mv between 0 and 255.
+        // 5. Let f be floor(clamped).
+        // 6-8. Round to the nearest integer, ties go to the even one
+        // NOTE: Rust's float to int casts saturate and map NaN to zero
+        self.0.round_ties_even() as u8
+    }
+
     /// <https://262.ecma-international.org/14.0/#sec-numeric-types-number-lessThan>
     #[must_use]
     pub fn less_than(x: Self, y: Self) -> Value {
@@ -167,11 +233,11 @@ impl Number {
     /// <https://262.ecma-international.org/14.0/#sec-numeric-types-number-leftShift>
     #[must_use]
     pub fn shift_left(&self, other: Self) -> Self {
-        // 1. FIXME: Let lnum be ! ToInt32(x).
-        let lnum = self.0 as i32;
+        // 1. Let lnum be ! ToInt32(x).
+        let lnum = self.to_int32();
 
         // 2. Let rnum be ! ToUint32(y).
-        let rnum = other.0 as u32;
+        let rnum = other.to_uint32();
 
         // 3. Let shiftCount be ℝ(rnum) modulo 32.
         let shift_count = rnum % 32;
@@ -277,4 +343,21 @@ mod tests {
 
         assert_eq!(Number::NEG_ZERO.add(Number::NEG_ZERO), Number::NEG_ZERO);
     }
+
+    #[test]
+    fn integer_conversions() {
+        assert_eq!(Number::new(-1.).to_uint32(), u32::MAX);
+        assert_eq!(Number::new(2_f64.powi(31)).to_int32(), i32::MIN);
+        assert_eq!(Number::new(4294967297.5).to_int32(), 1);
+        assert_eq!(Number::NAN.to_int32(), 0);
+        assert_eq!(Number::INFINITY.to_uint16(), 0);
+        assert_eq!(Number::new(-129.9).to_int8(), 127);
+        assert_eq!(Number::new(300.).to_uint8(), 44);
+
+        assert_eq!(Number::new(300.).to_uint8_clamp(), 255);
+        assert_eq!(Number::new(-3.).to_uint8_clamp(), 0);
+        assert_eq!(Number::new(2.5).to_uint8_clamp(), 2);
+        assert_eq!(Number::new(3.5).to_uint8_clamp(), 4);
+        assert_eq!(Number::NAN.to_uint8_clamp(), 0);
+    }
 }
@@ -0,0 +1,281 @@
+//! <https://262.ecma-international.org/14.0/#sec-typedarray-objects>
+
+use crate::{
+    runtime::{Exception, ThrowCompletionOr},
+    Number, Value,
+};
+
+use super::{ArrayBuffer, ElementType};
+
+/// An array-like view of an [ArrayBuffer], whose elements all have the same [ElementType]
+///
+/// [Specification](https://262.ecma-international.org/14.0/#sec-typedarray-exotic-objects)
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedArray {
+    buffer: ArrayBuffer,
+    element_type: ElementType,
+    byte_offset: usize,
+    array_length: usize,
+}
+
+impl TypedArray {
+    /// Create a typed array with a new buffer that holds `length` elements
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-allocatetypedarraybuffer)
+    pub fn new(element_type: ElementType, length: usize) -> ThrowCompletionOr<Self> {
+        // 3. Let byteLength be elementSize × length.
+        let byte_length = element_type
+            .size()
+            .checked_mul(length)
+            .ok_or_else(Exception::range_error)?;
+
+        // 4. Let data be ? AllocateArrayBuffer(%ArrayBuffer%, byteLength).
+        let buffer = ArrayBuffer::allocate(byte_length)?;
+
+        // 5-8. Set O.[[ViewedArrayBuffer]] to data, O.[[ByteLength]] to byteLength,
+        //      O.[[ByteOffset]] to 0 and O.[[ArrayLength]] to length.
+        let typed_array = Self {
+            buffer,
+            element_type,
+            byte_offset: 0,
+            array_length: length,
+        };
+
+        Ok(typed_array)
+    }
+
+    /// Create a typed array that views (part of) an existing buffer
+    ///
+    /// If no length is given then the typed array extends to the end of the buffer.
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-initializetypedarrayfromarraybuffer)
+    pub fn with_buffer(
+        buffer: ArrayBuffer,
+        element_type: ElementType,
+        byte_offset: usize,
+        length: Option<usize>,
+    ) -> ThrowCompletionOr<Self> {
+        // 1. Let elementSize be TypedArrayElementSize(O).
+        let element_size = element_type.size();
+
+        // 3. If offset modulo elementSize ≠ 0, throw a RangeError exception.
+        if byte_offset % element_size != 0 {
+            return Err(Exception::range_error());
+        }
+
+        // 5. If IsDetachedBuffer(buffer) is true, throw a TypeError exception.
+        if buffer.is_detached() {
+            return Err(Exception::type_error());
+        }
+
+        // 6. Let bufferByteLength be buffer.[[ArrayBufferByteLength]].
+        let buffer_byte_length = buffer.byte_length();
+
+        let new_byte_length = match length {
+            // 7. If length is undefined, then
+            None => {
+                // a. If bufferByteLength modulo elementSize ≠ 0, throw a RangeError exception.
+                if buffer_byte_length % element_size != 0 {
+                    return Err(Exception::range_error());
+                }
+
+                // b. Let newByteLength be bufferByteLength - offset.
+                // c. If newByteLength < 0, throw a RangeError exception.
+                buffer_byte_length
+                    .checked_sub(byte_offset)
+                    .ok_or_else(Exception::range_error)?
+            },
+            // 8. Else,
+            Some(new_length) => {
+                // a. Let newByteLength be newLength × elementSize.
+                let new_byte_length = new_length
+                    .checked_mul(element_size)
+                    .ok_or_else(Exception::range_error)?;
+
+                // b. If offset + newByteLength > bufferByteLength, throw a RangeError exception.
+                if byte_offset.saturating_add(new_byte_length) > buffer_byte_length {
+                    return Err(Exception::range_error());
+                }
+
+                new_byte_length
+            },
+        };
+
+        // 9-12. Set O.[[ViewedArrayBuffer]] to buffer, O.[[ByteLength]] to newByteLength,
+        //       O.[[ByteOffset]] to offset and O.[[ArrayLength]] to newByteLength / elementSize.
+        let typed_array = Self {
+            buffer,
+            element_type,
+            byte_offset,
+            array_length: new_byte_length / element_size,
+        };
+
+        Ok(typed_array)
+    }
+
+    #[must_use]
+    pub fn buffer(&self) -> &ArrayBuffer {
+        &self.buffer
+    }
+
+    #[must_use]
+    pub fn element_type(&self) -> ElementType {
+        self.element_type
+    }
+
+    /// The number of elements, which is zero if the buffer was detached
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-typedarraylength)
+    #[must_use]
+    pub fn length(&self) -> usize {
+        if self.buffer.is_detached() {
+            return 0;
+        }
+
+        self.array_length
+    }
+
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-get-%typedarray%.prototype.bytelength)
+    #[must_use]
+    pub fn byte_length(&self) -> usize {
+        self.length() * self.element_type.size()
+    }
+
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-get-%typedarray%.prototype.byteoffset)
+    #[must_use]
+    pub fn byte_offset(&self) -> usize {
+        if self.buffer.is_detached() {
+            return 0;
+        }
+
+        self.byte_offset
+    }
+
+    /// <https://262.ecma-international.org/14.0/#sec-isvalidintegerindex>
+    fn valid_integer_index(&self, index: Number) -> Option<usize> {
+        // 1. If IsDetachedBuffer(O.[[ViewedArrayBuffer]]) is true, return false.
+        if self.buffer.is_detached() {
+            return None;
+        }
+
+        // 2. If IsIntegralNumber(index) is false, return false.
+        let index = index.to_f64();
+        if !index.is_finite() || index.trunc() != index {
+            return None;
+        }
+
+        // 3. If index is -0𝔽, return false.
+        if index == 0. && index.is_sign_negative() {
+            return None;
+        }
+
+        // 6. If ℝ(index) < 0 or ℝ(index) ≥ length, return false.
+        if index < 0. || index >= self.array_length as f64 {
+            return None;
+        }
+
+        // 7. Return true.
+        Some(index as usize)
+    }
+
+    /// Read the element at the given index, or `undefined` if there is no such element
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-typedarraygetelement)
+    #[must_use]
+    pub fn get(&self, index: Number) -> Value {
+        // 1. If IsValidIntegerIndex(O, index) is false, return undefined.
+        let Some(index) = self.valid_integer_index(index) else {
+            return Value::Undefined;
+        };
+
+        // 2. Let offset be O.[[ByteOffset]].
+        // 3. Let elementSize be TypedArrayElementSize(O).
+        // 4. Let byteIndexInBuffer be (ℝ(index) × elementSize) + offset.
+        let byte_index = index * self.element_type.size() + self.byte_offset;
+
+        // 5. Let elementType be TypedArrayElementType(O).
+        // 6. Return GetValueFromBuffer(O.[[ViewedArrayBuffer]], byteIndexInBuffer, elementType, true, Unordered).
+        self.buffer.get_value(byte_index, self.element_type, true)
+    }
+
+    /// Write the element at the given index, writes to invalid indices are ignored
+    ///
+    /// [Specification](https://262.ecma-international.org/14.0/#sec-typedarraysetelement)
+    pub fn set(&self, index: Number, value: &Value) -> ThrowCompletionOr<()> {
+        // 1. If O.[[ContentType]] is BigInt, let numValue be ? ToBigInt(value).
+        // 2. Otherwise, let numValue be ? ToNumber(value).
+        let num_value = value.to_number()?;
+
+        // 3. If IsValidIntegerIndex(O, index) is true, then
+        if let Some(index) = self.valid_integer_index(index) {
+            // a. Let offset be O.[[ByteOffset]].
+            // b. Let elementSize be TypedArrayElementSize(O).
+            // c. Let byteIndexInBuffer be (ℝ(index) × elementSize) + offset.
+            let byte_index = index * self.element_type.size() + self.byte_offset;
+
+            // d. Let elementType be TypedArrayElementType(O).
+            // e. Perform SetValueInBuffer(O.[[ViewedArrayBuffer]], byteIndexInBuffer, elementType, numValue, true, Unordered).
+            self.buffer
+                .set_value(byte_index, self.element_type, num_value, true);
+        }
+
+        // 4. Return unused.
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn element_access() {
+        let array = TypedArray::new(ElementType::Uint8Clamped, 4).unwrap();
+        assert_eq!(array.length(), 4);
+
+        array
+            .set(Number::new(1.), &Number::new(300.).into())
+            .unwrap();
+        assert_eq!(array.get(Number::new(1.)), Number::new(255.).into());
+
+        // Invalid indices are ignored
+        array.set(Number::new(4.), &Number::ONE.into()).unwrap();
+        array.set(Number::new(0.5), &Number::ONE.into()).unwrap();
+        assert_eq!(array.get(Number::new(4.)), Value::Undefined);
+        assert_eq!(array.get(Number::NEG_ZERO), Value::Undefined);
+        assert_eq!(array.get(Number::new(-1.)), Value::Undefined);
+    }
+
+    #[test]
+    fn shared_buffer() {
+        let buffer = ArrayBuffer::allocate(8).unwrap();
+        let bytes = TypedArray::with_buffer(buffer.clone(), ElementType::Uint8, 0, None).unwrap();
+        let words =
+            TypedArray::with_buffer(buffer.clone(), ElementType::Uint16, 2, Some(2)).unwrap();
+        assert_eq!(words.byte_length(), 4);
+
+        words
+            .set(Number::ZERO, &Number::new(0x0102 as f64).into())
+            .unwrap();
+        assert_eq!(bytes.get(Number::new(2.)), Number::new(2.).into());
+        assert_eq!(bytes.get(Number::new(3.)), Number::new(1.).into());
+
+        buffer.detach();
+        assert_eq!(bytes.length(), 0);
+        assert_eq!(bytes.get(Number::ZERO), Value::Undefined);
+    }
+
+    #[test]
+    fn invalid_views() {
+        let buffer = ArrayBuffer::allocate(6).unwrap();
+
+        // Unaligned offset
+        assert!(TypedArray::with_buffer(buffer.clone(), ElementType::Uint16, 1, None).is_err());
+
+        // Buffer length is not a multiple of the element size
+        assert!(TypedArray::with_buffer(buffer.clone(), ElementType::Uint32, 0, None).is_err());
+
+        // View exceeds the buffer
+        assert!(TypedArray::with_buffer(buffer, ElementType::Uint16, 2, Some(3)).is_err());
+    }
+}
@@ -0,0 +1,916 @@
+//! Saves resources that cannot be displayed to disk
+//!
+//! Downloads run on their own thread and write the response body to disk as it arrives.
+//! While a download is in progress, its data is stored in a `.part` file next to its final
+//! location. Cancelled or failed downloads can be resumed using `Range` requests.
+
+use std::{
+    env, fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread, time,
+};
+
+use http::{request::HTTPError, BodySink, Header, Headers, ProxyConfig, Response, StatusCode};
+use settings::SETTINGS;
+use sl_std::{ascii, percent_encode};
+use url::URL;
+
+use crate::{Resource, ResourceLoadError};
+
+/// Name used if no file name can be derived from the response
+const FALLBACK_FILE_NAME: &str = "download";
+
+/// Suffix for files that are still being downloaded
+const PARTIAL_FILE_SUFFIX: &str = ".part";
+
+/// Listeners are notified at most this often while data is being received
+const NOTIFICATION_INTERVAL: time::Duration = time::Duration::from_millis(100);
+
+pub type DownloadId = usize;
+
+type DownloadListener = Box<dyn Fn(&Arc<Download>) + Send + Sync>;
+
+type Listeners = Mutex<Vec<DownloadListener>>;
+
+/// Keeps track of all the downloads in the current session
+pub struct DownloadManager {
+    /// Where downloaded files are saved
+    directory: PathBuf,
+
+    downloads: Mutex<Vec<Arc<Download>>>,
+
+    /// Callbacks that are invoked whenever a download makes progress or changes its status
+    listeners: Arc<Listeners>,
+
+    next_id: AtomicUsize,
+}
+
+/// A single file that is being downloaded
+pub struct Download {
+    id: DownloadId,
+    url: URL,
+    directory: PathBuf,
+    proxy: ProxyConfig,
+    state: Mutex<DownloadState>,
+
+    /// Set when the user cancels the download, checked whenever data is received
+    cancelled: AtomicBool,
+
+    listeners: Arc<Listeners>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownloadStatus {
+    InProgress,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// A snapshot of the progress of a [Download]
+#[derive(Clone, Debug)]
+pub struct DownloadState {
+    pub status: DownloadStatus,
+
+    /// The name of the file inside the download directory, `None` until the
+    /// server responded
+    pub file_name: Option<String>,
+
+    /// The number of bytes that were written to disk
+    pub received: u64,
+
+    /// The size of the file, if the server told us
+    pub total: Option<u64>,
+
+    /// `ETag` or `Last-Modified` of the response, used to make sure that resumed
+    /// downloads continue with the same file
+    validator: Option<String>,
+}
+
+impl DownloadManager {
+    #[must_use]
+    pub fn new(directory: PathBuf) -> Self {
+        Self {
+            directory,
+            downloads: Mutex::default(),
+            listeners: Arc::default(),
+            next_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// The users download directory
+    ///
+    /// This is `$XDG_DOWNLOAD_DIR` if set, `$HOME/Downloads` otherwise.
+    #[must_use]
+    pub fn default_directory() -> PathBuf {
+        env::var_os("XDG_DOWNLOAD_DIR")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join("Downloads")))
+            .unwrap_or_else(env::temp_dir)
+    }
+
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Start downloading the given [URL] in the background
+    pub fn start(&self, url: URL) -> Arc<Download> {
+        let download = self.register(url);
+        download.spawn();
+        download
+    }
+
+    /// Save a resource that was already loaded, without fetching it again
+    pub fn save(&self, url: URL, resource: Arc<Resource>) -> Arc<Download> {
+        let download = self.register(url);
+        download.spawn_with(move |download| download.write_resource(&resource));
+        download
+    }
+
+    /// Create a new download without starting it
+    ///
+    /// Used for responses that are already being received, their body is passed
+    /// to a [DownloadSink].
+    pub(crate) fn register(&self, url: URL) -> Arc<Download> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let download = Arc::new(Download::new(
+            id,
+            url,
+            self.directory.clone(),
+            SETTINGS.proxy_config(),
+            self.listeners.clone(),
+        ));
+
+        self.downloads().push(download.clone());
+        download
+    }
+
+    /// Return all downloads in the order in which they were started
+    #[must_use]
+    pub fn all(&self) -> Vec<Arc<Download>> {
+        self.downloads().clone()
+    }
+
+    #[must_use]
+    pub fn get(&self, id: DownloadId) -> Option<Arc<Download>> {
+        self.downloads()
+            .iter()
+            .find(|download| download.id == id)
+            .cloned()
+    }
+
+    /// Register a callback that is invoked whenever a download changes
+    ///
+    /// The callback is called from the download thread.
+    pub fn on_update<F>(&self, listener: F)
+    where
+        F: Fn(&Arc<Download>) + Send + Sync + 'static,
+    {
+        self.listeners
+            .lock()
+            .expect("listener lock is poisoned")
+            .push(Box::new(listener));
+    }
+
+    fn downloads(&self) -> MutexGuard<'_, Vec<Arc<Download>>> {
+        self.downloads.lock().expect("download lock is poisoned")
+    }
+}
+
+impl Download {
+    fn new(
+        id: DownloadId,
+        url: URL,
+        directory: PathBuf,
+        proxy: ProxyConfig,
+        listeners: Arc<Listeners>,
+    ) -> Self {
+        Self {
+            id,
+            url,
+            directory,
+            proxy,
+            state: Mutex::new(DownloadState {
+                status: DownloadStatus::InProgress,
+                file_name: None,
+                received: 0,
+                total: None,
+                validator: None,
+            }),
+            cancelled: AtomicBool::new(false),
+            listeners,
+        }
+    }
+
+    #[must_use]
+    pub fn id(&self) -> DownloadId {
+        self.id
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &URL {
+        &self.url
+    }
+
+    #[must_use]
+    pub fn state(&self) -> DownloadState {
+        self.lock_state().clone()
+    }
+
+    /// The location of the file once the download is complete
+    #[must_use]
+    pub fn path(&self) -> Option<PathBuf> {
+        let file_name = self.lock_state().file_name.clone()?;
+        Some(self.directory.join(file_name))
+    }
+
+    /// Stop the download, keeping the data that was received so far
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Continue a download that was cancelled or failed
+    ///
+    /// Returns `false` if the download cannot be resumed because it is
+    /// still in progress or already complete.
+    pub fn resume(self: &Arc<Self>) -> bool {
+        {
+            let mut state = self.lock_state();
+            if !matches!(
+                state.status,
+                DownloadStatus::Cancelled | DownloadStatus::Failed
+            ) {
+                return false;
+            }
+            state.status = DownloadStatus::InProgress;
+        }
+
+        self.cancelled.store(false, Ordering::Relaxed);
+        self.notify();
+        self.spawn();
+        true
+    }
+
+    fn spawn(self: &Arc<Self>) {
+        self.spawn_with(|download| download.fetch());
+    }
+
+    fn spawn_with<F>(self: &Arc<Self>, job: F)
+    where
+        F: FnOnce(&Arc<Self>) -> Result<(), ResourceLoadError> + Send + 'static,
+    {
+        let download = self.clone();
+        let spawned = thread::Builder::new()
+            .name(format!("Download {}", self.id))
+            .spawn(move || {
+                let result = job(&download);
+                download.conclude(result);
+            });
+
+        if let Err(error) = spawned {
+            log::error!("Failed to spawn download thread: {error}");
+            self.finish(DownloadStatus::Failed);
+        }
+    }
+
+    fn fetch(self: &Arc<Self>) -> Result<(), ResourceLoadError> {
+        match self.url.scheme().as_str() {
+            "http" | "https" => self.fetch_http(),
+            _ => self.fetch_other(),
+        }
+    }
+
+    /// Move the received data to its final location and report the outcome of the transfer
+    pub(crate) fn conclude(self: &Arc<Self>, result: Result<(), ResourceLoadError>) {
+        let status = match result.and_then(|()| self.complete()) {
+            Ok(()) => {
+                log::info!(
+                    "Downloaded {} to {:?}",
+                    self.url.serialize(url::ExcludeFragment::Yes),
+                    self.path()
+                );
+                DownloadStatus::Completed
+            },
+            Err(_) if self.cancelled.load(Ordering::Relaxed) => {
+                log::info!(
+                    "Download of {} was cancelled",
+                    self.url.serialize(url::ExcludeFragment::Yes)
+                );
+                DownloadStatus::Cancelled
+            },
+            Err(error) => {
+                log::error!(
+                    "Failed to download {}: {error}",
+                    self.url.serialize(url::ExcludeFragment::Yes)
+                );
+                DownloadStatus::Failed
+            },
+        };
+
+        self.finish(status);
+    }
+
+    fn fetch_http(self: &Arc<Self>) -> Result<(), ResourceLoadError> {
+        let mut request = http::Request::get(&self.url);
+        request.set_proxy_config(self.proxy.clone());
+        if let Some(prompt) = crate::CREDENTIAL_PROMPT.get() {
+            request.set_credential_prompt(prompt.clone());
+        }
+
+        // Compressed bodies cannot be written to disk as they arrive
+        request
+            .headers_mut()
+            .set(Header::ACCEPT_ENCODING, "identity".to_string());
+
+        let state = self.state();
+        if state.received != 0 {
+            request
+                .headers_mut()
+                .set(Header::RANGE, format!("bytes={}-", state.received));
+
+            // If the file changed in the meantime, the server sends all of it again
+            if let Some(validator) = state.validator {
+                request.headers_mut().set(Header::IF_RANGE, validator);
+            }
+        }
+
+        let mut sink = DownloadSink::new(self.clone());
+        match request.send_to(&mut sink) {
+            Ok(_) => {},
+            Err(HTTPError::Status(StatusCode::RANGE_NOT_SATISFIABLE)) if state.received != 0 => {
+                // The partial file is probably larger than the resource, start over
+                log::warn!("Server refused to resume download, restarting");
+                self.lock_state().received = 0;
+                return self.fetch_http();
+            },
+            Err(error) => return Err(error.into()),
+        }
+
+        sink.finish()
+    }
+
+    /// Download resources that are not transferred over http
+    ///
+    /// These are loaded in one go by their [ProtocolHandler](crate::protocol::ProtocolHandler).
+    fn fetch_other(&self) -> Result<(), ResourceLoadError> {
+        let resource = Resource::load(&self.url)?;
+        self.write_resource(&resource)
+    }
+
+    fn write_resource(&self, resource: &Resource) -> Result<(), ResourceLoadError> {
+        self.choose_file_name(resource.http_headers());
+        fs::write(self.partial_path(), resource.data())?;
+
+        let mut state = self.lock_state();
+        state.received = resource.data().len() as u64;
+        state.total = Some(state.received);
+
+        Ok(())
+    }
+
+    /// Move the partial file to its final location
+    fn complete(&self) -> Result<(), ResourceLoadError> {
+        let path = self.path().expect("file name was chosen before completion");
+        fs::rename(self.partial_path(), path)?;
+        Ok(())
+    }
+
+    fn finish(self: &Arc<Self>, status: DownloadStatus) {
+        self.lock_state().status = status;
+        self.notify();
+    }
+
+    fn choose_file_name(&self, headers: Option<&Headers>) {
+        let mut state = self.lock_state();
+        if state.file_name.is_none() {
+            let file_name = file_name_for(&self.url, headers);
+            state.file_name = Some(unique_file_name(&self.directory, &file_name));
+        }
+    }
+
+    fn partial_path(&self) -> PathBuf {
+        let file_name = self
+            .lock_state()
+            .file_name
+            .clone()
+            .expect("file name was chosen before writing data");
+        self.directory
+            .join(format!("{file_name}{PARTIAL_FILE_SUFFIX}"))
+    }
+
+    fn notify(self: &Arc<Self>) {
+        for listener in self
+            .listeners
+            .lock()
+            .expect("listener lock is poisoned")
+            .iter()
+        {
+            listener(self);
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, DownloadState> {
+        self.state.lock().expect("download state lock is poisoned")
+    }
+}
+
+impl fmt::Debug for Download {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Download")
+            .field("id", &self.id)
+            .field("url", &self.url)
+            .field("directory", &self.directory)
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Writes the response body of a [Download] to disk
+pub(crate) struct DownloadSink {
+    download: Arc<Download>,
+    file: Option<File>,
+    last_notification: time::Instant,
+}
+
+impl DownloadSink {
+    pub(crate) fn new(download: Arc<Download>) -> Self {
+        Self {
+            download,
+            file: None,
+            last_notification: time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn download(&self) -> &Arc<Download> {
+        &self.download
+    }
+
+    /// Called once the transfer is complete
+    pub(crate) fn finish(&self) -> Result<(), ResourceLoadError> {
+        // Responses without a body still produce an (empty) file
+        if self.file.is_none() {
+            self.download.choose_file_name(None);
+            File::create(self.download.partial_path())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BodySink for DownloadSink {
+    fn start(&mut self, response: &Response) -> io::Result<()> {
+        let headers = response.headers();
+        self.download.choose_file_name(Some(headers));
+
+        // The length of compressed bodies says nothing about the size of the file
+        let is_encoded = headers
+            .get(Header::CONTENT_ENCODING)
+            .is_some_and(|encoding| encoding != "identity");
+        let content_length = headers
+            .get(Header::CONTENT_LENGTH)
+            .and_then(|length| length.parse::<u64>().ok())
+            .filter(|_| !is_encoded);
+
+        let (offset, total) = if response.status() == StatusCode::PARTIAL_CONTENT {
+            let (start, total) = headers
+                .get(Header::CONTENT_RANGE)
+                .and_then(parse_content_range)
+                .ok_or_else(|| io::Error::other("invalid Content-Range"))?;
+
+            if start != self.download.state().received {
+                return Err(io::Error::other("server resumed at the wrong offset"));
+            }
+
+            (start, total.or(content_length.map(|length| start + length)))
+        } else {
+            (0, content_length)
+        };
+
+        let file = if offset == 0 {
+            File::create(self.download.partial_path())?
+        } else {
+            OpenOptions::new()
+                .append(true)
+                .open(self.download.partial_path())?
+        };
+        self.file = Some(file);
+
+        {
+            let mut state = self.download.lock_state();
+            state.received = offset;
+            state.total = total;
+            state.validator = headers
+                .get(Header::ETAG)
+                .or_else(|| headers.get(Header::LAST_MODIFIED))
+                .map(str::to_owned);
+        }
+
+        self.download.notify();
+        Ok(())
+    }
+
+    fn write(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.download.cancelled.load(Ordering::Relaxed) {
+            return Err(io::Error::other("download was cancelled"));
+        }
+
+        self.file
+            .as_mut()
+            .expect("start was called before write")
+            .write_all(data)?;
+        self.download.lock_state().received += data.len() as u64;
+
+        if self.last_notification.elapsed() >= NOTIFICATION_INTERVAL {
+            self.last_notification = time::Instant::now();
+            self.download.notify();
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether the `Content-Disposition` header asks for the response to be downloaded
+/// instead of being displayed
+///
+/// <https://httpwg.org/specs/rfc6266.html#header.field.definition>
+#[must_use]
+pub fn is_attachment(headers: &Headers) -> bool {
+    headers
+        .get(Header::CONTENT_DISPOSITION)
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|disposition_type| disposition_type.trim().eq_ignore_ascii_case("attachment"))
+}
+
+/// Choose a name for a downloaded file
+///
+/// The name is taken from the `Content-Disposition` header if possible, otherwise
+/// from the last segment of the URL path.
+#[must_use]
+pub fn file_name_for(url: &URL, headers: Option<&Headers>) -> String {
+    let from_headers = headers
+        .and_then(|headers| headers.get(Header::CONTENT_DISPOSITION))
+        .and_then(file_name_from_content_disposition);
+
+    let from_url = || {
+        // The path of data URLs contains the data itself
+        if url.has_opaque_path() || url.scheme().as_str() == "data" {
+            return None;
+        }
+
+        let segment = url
+            .path_segments()
+            .filter(|segment| !segment.is_empty())
+            .last()?;
+        percent_encode::percent_decode_utf8(segment, percent_encode::Utf8Validation::Lossy).ok()
+    };
+
+    from_headers
+        .as_deref()
+        .and_then(sanitize_file_name)
+        .or_else(|| from_url().as_deref().and_then(sanitize_file_name))
+        .unwrap_or_else(|| FALLBACK_FILE_NAME.to_string())
+}
+
+/// Extract the `filename*` or `filename` parameter, preferring the former
+///
+/// <https://httpwg.org/specs/rfc6266.html#disposition.parameter.filename>
+fn file_name_from_content_disposition(value: &str) -> Option<String> {
+    let mut file_name = None;
+
+    for (name, value) in content_disposition_parameters(value) {
+        if name.eq_ignore_ascii_case("filename*") {
+            if let Some(decoded) = decode_extended_value(&value) {
+                return Some(decoded);
+            }
+        } else if name.eq_ignore_ascii_case("filename") && file_name.is_none() {
+            file_name = Some(value);
+        }
+    }
+
+    file_name
+}
+
+/// Split the parameters of a `Content-Disposition` header, unquoting their values
+fn content_disposition_parameters(value: &str) -> Vec<(String, String)> {
+    let mut parameters = vec![];
+
+    // Skip the disposition type
+    let Some((_, mut remaining)) = value.split_once(';') else {
+        return parameters;
+    };
+
+    loop {
+        remaining = remaining.trim_start_matches([' ', '\t', ';']);
+        let Some((name, rest)) = remaining.split_once('=') else {
+            break;
+        };
+        let name = name.trim().to_string();
+        let rest = rest.trim_start();
+
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = quoted.char_indices();
+            let mut end = quoted.len();
+
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next().map(|(_, escaped)| escaped)),
+                    '"' => {
+                        end = index + 1;
+                        break;
+                    },
+                    other => value.push(other),
+                }
+            }
+
+            remaining = &quoted[end..];
+            value
+        } else {
+            let (value, rest) = rest.split_once(';').unwrap_or((rest, ""));
+            remaining = rest;
+            value.trim_end().to_string()
+        };
+
+        parameters.push((name, value));
+    }
+
+    parameters
+}
+
+/// Decode a value like `UTF-8''na%C3%AFve.txt`
+///
+/// <https://httpwg.org/specs/rfc8187.html#parameter.value.syntax>
+fn decode_extended_value(value: &str) -> Option<String> {
+    let (charset, rest) = value.split_once('\'')?;
+    let (_language, encoded) = rest.split_once('\'')?;
+    let encoded = ascii::Str::from_bytes(encoded.as_bytes())?;
+
+    if charset.eq_ignore_ascii_case("utf-8") {
+        percent_encode::percent_decode_utf8(encoded, percent_encode::Utf8Validation::Strict).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(
+            percent_encode::percent_decode(encoded)
+                .into_iter()
+                .map(char::from)
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+
+/// Make sure a file name suggested by the server cannot escape the download directory
+fn sanitize_file_name(file_name: &str) -> Option<String> {
+    let file_name = file_name
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(file_name)
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+
+    // Leading dots would create hidden files (or refer to the parent directory)
+    let file_name = file_name.trim().trim_start_matches('.');
+
+    if file_name.is_empty() {
+        return None;
+    }
+
+    Some(file_name.to_string())
+}
+
+/// Append a number to the file name if a file with that name already exists
+fn unique_file_name(directory: &Path, file_name: &str) -> String {
+    let is_taken = |name: &str| {
+        directory.join(name).exists()
+            || directory
+                .join(format!("{name}{PARTIAL_FILE_SUFFIX}"))
+                .exists()
+    };
+
+    if !is_taken(file_name) {
+        return file_name.to_string();
+    }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (file_name, None),
+    };
+
+    (1..)
+        .map(|n| match extension {
+            Some(extension) => format!("{stem} ({n}).{extension}"),
+            None => format!("{stem} ({n})"),
+        })
+        .find(|name| !is_taken(name))
+        .expect("there are infinitely many candidate names")
+}
+
+/// Parse the start offset and the total length from a `Content-Range` header
+/// like `bytes 100-199/1000`
+///
+/// <https://httpwg.org/specs/rfc9110.html#field.content-range>
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (range, total) = range.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+
+    let start = start.trim().parse().ok()?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+
+    Some((start, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    fn temporary_directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("stormlicht-{name}-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn file_name_from_headers() {
+        let url: URL = "https://example.com/files/report%20final.pdf?version=2"
+            .parse()
+            .unwrap();
+        let file_name = |content_disposition: &str| {
+            let mut headers = Headers::default();
+            headers.set(Header::CONTENT_DISPOSITION, content_disposition.to_string());
+            file_name_for(&url, Some(&headers))
+        };
+
+        assert_eq!(file_name_for(&url, None), "report final.pdf");
+        assert_eq!(file_name("attachment"), "report final.pdf");
+        assert_eq!(file_name("attachment; filename=data.csv"), "data.csv");
+        assert_eq!(
+            file_name(r#"attachment; filename="quoted \"name\"; with semicolon.txt""#),
+            "quoted \"name\"; with semicolon.txt"
+        );
+        assert_eq!(
+            file_name("attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve.txt"),
+            "naïve.txt"
+        );
+        assert_eq!(
+            file_name("attachment; filename=\"../../.bashrc\""),
+            "bashrc"
+        );
+        assert_eq!(file_name("attachment; filename=\"..\""), "report final.pdf");
+
+        let url: URL = "https://example.com/".parse().unwrap();
+        assert_eq!(file_name_for(&url, None), FALLBACK_FILE_NAME);
+    }
+
+    #[test]
+    fn attachment() {
+        let mut headers = Headers::default();
+        assert!(!is_attachment(&headers));
+
+        headers.set(Header::CONTENT_DISPOSITION, "inline".to_string());
+        assert!(!is_attachment(&headers));
+
+        headers.set(
+            Header::CONTENT_DISPOSITION,
+            "Attachment; filename=foo.zip".to_string(),
+        );
+        assert!(is_attachment(&headers));
+    }
+
+    #[test]
+    fn content_range() {
+        assert_eq!(parse_content_range("bytes 5-11/12"), Some((5, Some(12))));
+        assert_eq!(parse_content_range("bytes 5-11/*"), Some((5, None)));
+        assert_eq!(parse_content_range("bytes */12"), None);
+    }
+
+    #[test]
+    fn unique_names() {
+        let directory = temporary_directory("unique-names");
+
+        assert_eq!(unique_file_name(&directory, "file.txt"), "file.txt");
+
+        fs::write(directory.join("file.txt"), "").unwrap();
+        fs::write(directory.join("file (1).txt.part"), "").unwrap();
+        assert_eq!(unique_file_name(&directory, "file.txt"), "file (2).txt");
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    /// Answer a single HTTP request with the given response, returning the
+    /// (lowercase) request headers
+    fn serve_once(response: &'static [u8]) -> (u16, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request_lines = BufReader::new(stream.try_clone().unwrap())
+                .lines()
+                .map(Result::unwrap)
+                .take_while(|line| !line.is_empty())
+                .map(|line| line.to_ascii_lowercase())
+                .collect();
+
+            stream.write_all(response).unwrap();
+            request_lines
+        });
+
+        (port, server)
+    }
+
+    #[test]
+    fn download_attachment() {
+        let (port, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nContent-Disposition: attachment; filename=\"greeting.txt\"\r\nContent-Length: 12\r\n\r\nHello, World",
+        );
+
+        let directory = temporary_directory("download-attachment");
+        let download = Arc::new(Download::new(
+            0,
+            format!("http://127.0.0.1:{port}/download?id=1")
+                .parse()
+                .unwrap(),
+            directory.clone(),
+            ProxyConfig::DIRECT,
+            Arc::default(),
+        ));
+        download.conclude(download.fetch());
+
+        let request_lines = server.join().unwrap();
+        assert!(request_lines.contains(&"accept-encoding: identity".to_string()));
+        assert!(!request_lines.iter().any(|line| line.starts_with("range:")));
+
+        let state = download.state();
+        assert_eq!(state.status, DownloadStatus::Completed);
+        assert_eq!((state.received, state.total), (12, Some(12)));
+        assert_eq!(download.path(), Some(directory.join("greeting.txt")));
+        assert_eq!(
+            fs::read_to_string(directory.join("greeting.txt")).unwrap(),
+            "Hello, World"
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn resume_download() {
+        let (port, server) = serve_once(
+            b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 5-11/12\r\nContent-Length: 7\r\nETag: \"v1\"\r\n\r\n, World",
+        );
+
+        let directory = temporary_directory("resume-download");
+        let download = Arc::new(Download::new(
+            0,
+            format!("http://127.0.0.1:{port}/hello.txt")
+                .parse()
+                .unwrap(),
+            directory.clone(),
+            ProxyConfig::DIRECT,
+            Arc::default(),
+        ));
+
+        // Pretend that the first five bytes were received before the download was cancelled
+        fs::write(directory.join("hello.txt.part"), "Hello").unwrap();
+        *download.lock_state() = DownloadState {
+            status: DownloadStatus::Cancelled,
+            file_name: Some("hello.txt".to_string()),
+            received: 5,
+            total: Some(12),
+            validator: Some("\"v1\"".to_string()),
+        };
+
+        download.conclude(download.fetch());
+
+        let request_lines = server.join().unwrap();
+        assert!(request_lines.contains(&"range: bytes=5-".to_string()));
+        assert!(request_lines.contains(&"if-range: \"v1\"".to_string()));
+
+        let state = download.state();
+        assert_eq!(state.status, DownloadStatus::Completed);
+        assert_eq!((state.received, state.total), (12, Some(12)));
+        assert_eq!(
+            fs::read_to_string(directory.join("hello.txt")).unwrap(),
+            "Hello, World"
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
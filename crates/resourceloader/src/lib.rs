@@ -1,35 +1,63 @@
 #![feature(ascii_char, ascii_char_variants)]
 
+pub mod download;
 mod loader;
+pub mod navigation;
+pub mod protocol;
 mod resource;
+mod scheduler;
 
-use loader::{LoadCompletion, ResourceLoadRequest, ResourceLoader};
+use download::DownloadManager;
+use http::authentication::CredentialPrompt;
+pub use loader::ResourceCache;
+use loader::{LoadCompletion, LoaderMessage, ResourceLoadRequest, ResourceLoader};
 pub use resource::{Resource, ResourceLoadError};
+pub use scheduler::Priority;
 use sl_std::oneshot;
 
 use std::{
     fmt,
-    sync::{mpsc, LazyLock},
+    sync::{mpsc, Arc, LazyLock, OnceLock},
     thread,
 };
 use url::URL;
 
 pub static RESOURCE_LOADER: LazyLock<ResourceThreadHandle> = LazyLock::new(|| {
     let (tx, rx) = mpsc::channel();
+    let cache = Arc::new(ResourceCache::default());
 
+    let thread_cache = cache.clone();
+    let thread_sender = tx.clone();
     let thread_handle = thread::Builder::new()
         .name("ResourceLoader".to_string())
-        .spawn(|| ResourceLoader::start(rx))
+        .spawn(|| ResourceLoader::start(rx, thread_sender, thread_cache))
         .expect("Failed to spawn ResourceLoader thread");
 
     let resource_loader = ResourceThreadHandle {
         thread_handle,
         sender: tx,
+        cache,
     };
 
     resource_loader
 });
 
+/// Saves resources that cannot be displayed to the users download directory
+pub static DOWNLOAD_MANAGER: LazyLock<DownloadManager> =
+    LazyLock::new(|| DownloadManager::new(DownloadManager::default_directory()));
+
+/// Asks the user for credentials when a server requires authentication
+static CREDENTIAL_PROMPT: OnceLock<CredentialPrompt> = OnceLock::new();
+
+/// Register the callback that the browser uses to ask the user for credentials
+///
+/// The prompt is called from the threads that load resources. It can only be set once.
+pub fn set_credential_prompt(prompt: CredentialPrompt) {
+    if CREDENTIAL_PROMPT.set(prompt).is_err() {
+        log::warn!("Credential prompt was already set, ignoring");
+    }
+}
+
 /// A handle held by the main thread to communicate
 /// with the resource thread
 pub struct ResourceThreadHandle {
@@ -37,7 +65,9 @@ pub struct ResourceThreadHandle {
     thread_handle: thread::JoinHandle<()>,
 
     /// Channel to forward incoming requests to the ResourceLoader
-    sender: mpsc::Sender<ResourceLoadRequest>,
+    sender: mpsc::Sender<LoaderMessage>,
+
+    cache: Arc<ResourceCache>,
 }
 
 /// Indicates that a message could not be sent because the resource thread
@@ -50,6 +80,12 @@ pub struct PendingLoad {
 }
 
 impl PendingLoad {
+    /// Whether the load finished, in which case [block](Self::block) returns immediately
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.receiver.is_ready()
+    }
+
     #[must_use]
     pub fn block(self) -> LoadCompletion {
         // Don't propagate the error to the user because this is never
@@ -66,15 +102,25 @@ impl ResourceThreadHandle {
         &self.thread_handle
     }
 
-    pub fn try_schedule_load(&self, url: URL) -> Result<PendingLoad, ResourceLoaderDisconnected> {
+    /// The resources that were loaded previously
+    #[must_use]
+    pub fn cache(&self) -> &ResourceCache {
+        &self.cache
+    }
+
+    pub fn try_schedule_load(
+        &self,
+        url: URL,
+        priority: Priority,
+    ) -> Result<PendingLoad, ResourceLoaderDisconnected> {
         let (sender, receiver) = oneshot::Channel::create();
 
-        let client = ResourceLoadRequest::new(url, sender);
+        let client = ResourceLoadRequest::new(url, sender, priority);
 
         // We ignore the send error and propagate an opaque ResourceLoaderDisconnected since
         // the error only contains the request itself, which we don't care about from the outside.
         self.sender
-            .send(client)
+            .send(LoaderMessage::Request(client))
             .map_err(|_| ResourceLoaderDisconnected)?;
 
         let load_handle = PendingLoad { receiver };
@@ -82,9 +128,22 @@ impl ResourceThreadHandle {
         Ok(load_handle)
     }
 
+    /// Load a resource into the cache without waiting for it
+    ///
+    /// Later requests for the same url are served from the cache, or merged with
+    /// the preload if it is still in progress.
+    pub fn preload(&self, url: URL, priority: Priority) -> Result<(), ResourceLoaderDisconnected> {
+        self.sender
+            .send(LoaderMessage::Request(ResourceLoadRequest::preload(
+                url, priority,
+            )))
+            .map_err(|_| ResourceLoaderDisconnected)
+    }
+
     /// Request a resource to be loaded
     ///
-    /// Called from the main thread.
+    /// Called from the main thread. The resource is treated as [render-blocking](Priority::RenderBlocking),
+    /// use [Self::schedule_load_with_priority] for resources that are less urgent.
     ///
     /// # Panics
     ///
@@ -92,7 +151,17 @@ impl ResourceThreadHandle {
     /// If you want to handle the error gracefully instead, use [Self::try_schedule_load].
     #[must_use]
     pub fn schedule_load(&self, url: URL) -> PendingLoad {
-        self.try_schedule_load(url)
+        self.schedule_load_with_priority(url, Priority::RenderBlocking)
+    }
+
+    /// Request a resource to be loaded, more urgent resources are loaded first
+    ///
+    /// # Panics
+    ///
+    /// Panics if the communication with the resource thread failed.
+    #[must_use]
+    pub fn schedule_load_with_priority(&self, url: URL, priority: Priority) -> PendingLoad {
+        self.try_schedule_load(url, priority)
             .expect("Failed to schedule load request")
     }
 }
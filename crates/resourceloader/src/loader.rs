@@ -1,18 +1,104 @@
 use std::{
     collections::HashMap,
-    mem,
-    sync::{mpsc, Arc},
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
 };
 
-use sl_std::oneshot;
+use sl_std::{
+    memory::{MemoryReport, MemoryReporter},
+    oneshot,
+};
 use url::URL;
 
-use crate::{resource::ResourceLoadError, Resource};
+use crate::{
+    resource::ResourceLoadError,
+    scheduler::{Priority, Scheduler},
+    Resource,
+};
 
 pub struct ResourceLoader {
-    receiver: mpsc::Receiver<ResourceLoadRequest>,
-    cache: HashMap<URL, Arc<Resource>>,
-    pending_loads: Vec<ResourceLoadRequest>,
+    receiver: mpsc::Receiver<LoaderMessage>,
+
+    /// Handed to the threads that load resources, so they can report back
+    sender: mpsc::Sender<LoaderMessage>,
+    cache: Arc<ResourceCache>,
+
+    /// Requested resources that were not loaded yet, along with everyone waiting for them
+    ///
+    /// The list of waiting requests is empty if the resource was only preloaded.
+    scheduler: Scheduler<Vec<oneshot::Sender<LoadCompletion>>>,
+
+    /// Loads that are currently running, along with everyone waiting for them
+    active_loads: HashMap<URL, (Priority, Vec<oneshot::Sender<LoadCompletion>>)>,
+}
+
+/// Messages that are received by the resource thread
+pub enum LoaderMessage {
+    Request(ResourceLoadRequest),
+
+    /// A resource finished loading
+    Completed(URL, LoadCompletion),
+}
+
+/// Resources that were loaded previously
+///
+/// The cache is shared between the resource thread and the main thread, so it
+/// can be inspected without waiting for pending loads.
+#[derive(Debug, Default)]
+pub struct ResourceCache {
+    resources: Mutex<HashMap<URL, Arc<Resource>>>,
+}
+
+impl ResourceCache {
+    #[must_use]
+    pub fn get(&self, url: &URL) -> Option<Arc<Resource>> {
+        self.resources
+            .lock()
+            .expect("resource cache lock was poisoned")
+            .get(url)
+            .cloned()
+    }
+
+    pub(crate) fn insert(&self, url: URL, resource: Arc<Resource>) {
+        self.resources
+            .lock()
+            .expect("resource cache lock was poisoned")
+            .insert(url, resource);
+    }
+
+    /// List all cached resources
+    #[must_use]
+    pub fn entries(&self) -> Vec<(URL, Arc<Resource>)> {
+        self.resources
+            .lock()
+            .expect("resource cache lock was poisoned")
+            .iter()
+            .map(|(url, resource)| (url.clone(), resource.clone()))
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.resources
+            .lock()
+            .expect("resource cache lock was poisoned")
+            .clear();
+    }
+}
+
+impl MemoryReporter for ResourceCache {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        let resources = self
+            .resources
+            .lock()
+            .expect("resource cache lock was poisoned");
+
+        let total_size = resources
+            .values()
+            .map(|resource| resource.data().len())
+            .sum();
+        report.record("resources/cache", total_size);
+    }
 }
 
 /// A handle to a resource being fetched
@@ -25,27 +111,50 @@ pub struct ResourceLoadRequest {
     /// The location of the resource that should be loaded
     pub url: URL,
 
-    pub sender: oneshot::Sender<LoadCompletion>,
+    /// `None` if nobody is waiting for the resource, it is only loaded into the cache
+    pub sender: Option<oneshot::Sender<LoadCompletion>>,
+
+    pub priority: Priority,
 }
 
 pub type LoadCompletion = Result<Arc<Resource>, ResourceLoadError>;
 
 impl ResourceLoadRequest {
     #[must_use]
-    pub fn new(url: URL, sender: oneshot::Sender<LoadCompletion>) -> Self {
-        Self { url, sender }
+    pub fn new(url: URL, sender: oneshot::Sender<LoadCompletion>, priority: Priority) -> Self {
+        Self {
+            url,
+            sender: Some(sender),
+            priority,
+        }
+    }
+
+    /// A request that only loads the resource into the cache
+    #[must_use]
+    pub fn preload(url: URL, priority: Priority) -> Self {
+        Self {
+            url,
+            sender: None,
+            priority,
+        }
     }
 }
 
 impl ResourceLoader {
     /// Starts a [ResourceLoader] instance on the current thread
-    pub fn start(receiver: mpsc::Receiver<ResourceLoadRequest>) {
+    pub fn start(
+        receiver: mpsc::Receiver<LoaderMessage>,
+        sender: mpsc::Sender<LoaderMessage>,
+        cache: Arc<ResourceCache>,
+    ) {
         log::info!("Starting ResourceLoader thread");
 
         let mut loader = Self {
             receiver,
-            cache: HashMap::default(),
-            pending_loads: Vec::default(),
+            sender,
+            cache,
+            scheduler: Scheduler::default(),
+            active_loads: HashMap::default(),
         };
 
         loader.run();
@@ -53,54 +162,192 @@ impl ResourceLoader {
 
     fn run(&mut self) {
         loop {
-            if self.pending_loads.is_empty() {
-                self.wait_for_request();
+            // The loader holds a sender itself, so the channel is never disconnected
+            let message = self.receiver.recv().expect("Resource channel disconnected");
+            self.handle_message(message);
+
+            // Handle everything that arrived in the meantime before deciding what to load next
+            while let Ok(message) = self.receiver.try_recv() {
+                self.handle_message(message);
             }
 
-            // FIXME: make this concurrent
-            self.handle_pending_loads();
+            self.start_loads();
         }
     }
 
-    fn wait_for_request(&mut self) {
-        let request = self.receiver.recv().expect("Main thread disconnected");
-        self.handle_incoming_request(request);
-
-        loop {
-            match self.receiver.try_recv() {
-                Ok(request) => {
-                    self.handle_incoming_request(request);
-                },
-                Err(mpsc::TryRecvError::Empty) => break,
-                Err(mpsc::TryRecvError::Disconnected) => panic!("Main thread disconnected"),
-            }
+    fn handle_message(&mut self, message: LoaderMessage) {
+        match message {
+            LoaderMessage::Request(request) => self.handle_incoming_request(request),
+            LoaderMessage::Completed(url, completion) => {
+                self.handle_completed_load(url, completion)
+            },
         }
     }
 
-    /// Looks up a cache entry for a given request and adds it to the list of
-    /// pending loads if no cache entry is present.
+    /// Looks up a cache entry for a given request and schedules a load
+    /// if no cache entry is present.
     fn handle_incoming_request(&mut self, request: ResourceLoadRequest) {
         if let Some(cached_resource) = self.cache.get(&request.url) {
-            let response = Ok(cached_resource.clone());
-            let was_sent = request.sender.send(response).is_ok();
-            assert!(was_sent, "Receiver disconnected");
+            if let Some(sender) = request.sender {
+                // The requester might not be interested in the resource anymore
+                _ = sender.send(Ok(cached_resource));
+            }
+            return;
+        }
+
+        // The same resource might already be on its way, in which case it is only loaded once
+        if let Some((_, senders)) = self.active_loads.get_mut(&request.url) {
+            senders.extend(request.sender);
             return;
         }
 
-        // This request is not in the cache, create a new handle for it
-        self.pending_loads.push(request);
+        if let Some((priority, senders)) = self.scheduler.get_mut(&request.url) {
+            senders.extend(request.sender);
+            *priority = (*priority).max(request.priority);
+            return;
+        }
+
+        self.scheduler.push(
+            request.url,
+            request.priority,
+            request.sender.into_iter().collect(),
+        );
     }
 
-    fn handle_pending_loads(&mut self) {
-        for pending_load in mem::take(&mut self.pending_loads) {
-            let completion = Resource::load(&pending_load.url).map(Arc::new);
+    /// Start as many of the scheduled loads as possible, each on its own thread
+    fn start_loads(&mut self) {
+        while let Some((url, priority, senders)) = self.scheduler.next() {
+            self.active_loads.insert(url.clone(), (priority, senders));
 
-            if let Ok(resource) = &completion {
-                self.cache.insert(pending_load.url, resource.clone());
-            }
+            let sender = self.sender.clone();
+            let spawned = thread::Builder::new()
+                .name("ResourceLoad".to_string())
+                .spawn(move || {
+                    // A panicking protocol handler must not leave everyone waiting for the resource forever
+                    let completion = panic::catch_unwind(AssertUnwindSafe(|| Resource::load(&url)))
+                        .unwrap_or(Err(ResourceLoadError::Panicked))
+                        .map(Arc::new);
+
+                    // The resource thread never exits
+                    _ = sender.send(LoaderMessage::Completed(url, completion));
+                });
+
+            spawned.expect("Failed to spawn thread for resource load");
+        }
+    }
+
+    fn handle_completed_load(&mut self, url: URL, completion: LoadCompletion) {
+        let bytes_received = completion
+            .as_ref()
+            .map_or(0, |resource| resource.data().len());
+        self.scheduler.finish(&url, bytes_received);
+
+        let (priority, mut senders) = self
+            .active_loads
+            .remove(&url)
+            .expect("completed load was not active");
 
-            let was_sent = pending_load.sender.send(completion).is_ok();
-            assert!(was_sent, "Receiver disconnected");
+        match &completion {
+            // Blob urls can be revoked, after which loading them must fail
+            Ok(_) if url.scheme().as_str() == "blob" => {},
+            Ok(resource) => self.cache.insert(url.clone(), resource.clone()),
+            Err(error) if senders.is_empty() => {
+                log::warn!(
+                    "Failed to preload {}: {error}",
+                    url.serialize(url::ExcludeFragment::Yes)
+                );
+            },
+            Err(_) => {},
         }
+
+        let Some(first_sender) = senders.pop() else {
+            return;
+        };
+
+        // Requesters drop their receiver when they are no longer interested in the resource,
+        // for example because the user navigated away. Failing to send is therefore expected.
+        match &completion {
+            Ok(resource) => {
+                for sender in senders {
+                    _ = sender.send(Ok(resource.clone()));
+                }
+            },
+            Err(_) => {
+                // Errors cannot be shared, so every other request makes its own attempt
+                if !senders.is_empty() {
+                    self.scheduler.push(url, priority, senders);
+                }
+            },
+        }
+
+        _ = first_sender.send(completion);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::{
+        protocol::{self, ProtocolHandler},
+        RESOURCE_LOADER,
+    };
+
+    use super::*;
+
+    static NUM_LOADS: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountingHandler;
+
+    impl ProtocolHandler for CountingHandler {
+        fn load(&self, _url: &URL) -> Result<Resource, ResourceLoadError> {
+            NUM_LOADS.fetch_add(1, Ordering::SeqCst);
+            Ok(Resource::new(b"preloaded".to_vec(), None))
+        }
+    }
+
+    struct PanickingHandler;
+
+    impl ProtocolHandler for PanickingHandler {
+        fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+            if url.path().as_str() == "panic" {
+                panic!("protocol handler panicked");
+            }
+            Ok(Resource::new(b"loaded".to_vec(), None))
+        }
+    }
+
+    #[test]
+    fn loads_survive_dropped_receivers_and_panics() {
+        protocol::register_protocol_handler("panicking", PanickingHandler);
+
+        // Nobody is interested in the result of this load anymore
+        drop(RESOURCE_LOADER.schedule_load("panicking:dropped".parse().unwrap()));
+
+        let result = RESOURCE_LOADER
+            .schedule_load("panicking:panic".parse().unwrap())
+            .block();
+        assert!(matches!(result, Err(ResourceLoadError::Panicked)));
+
+        // The resource thread is still alive
+        let resource = RESOURCE_LOADER
+            .schedule_load("panicking:after-panic".parse().unwrap())
+            .block()
+            .unwrap();
+        assert_eq!(resource.data(), b"loaded");
+    }
+
+    #[test]
+    fn preloaded_resources_are_loaded_once() {
+        protocol::register_protocol_handler("counting", CountingHandler);
+        let url: URL = "counting:resource".parse().unwrap();
+
+        RESOURCE_LOADER
+            .preload(url.clone(), Priority::Prefetch)
+            .unwrap_or_else(|_| panic!("resource thread disconnected"));
+        let resource = RESOURCE_LOADER.schedule_load(url).block().unwrap();
+
+        assert_eq!(resource.data(), b"preloaded");
+        assert_eq!(NUM_LOADS.load(Ordering::SeqCst), 1);
     }
 }
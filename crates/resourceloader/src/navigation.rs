@@ -0,0 +1,313 @@
+//! Loads the documents that the user navigates to
+//!
+//! Whether a response is displayed or saved to disk is decided as soon as enough of it
+//! arrived to determine its type. Downloads continue with the same response instead of
+//! requesting the resource a second time.
+
+use std::{io, mem, sync::Arc, thread};
+
+use http::{BodySink, Response};
+use settings::SETTINGS;
+use sl_std::oneshot;
+use url::URL;
+
+use crate::{
+    download::{Download, DownloadManager, DownloadSink},
+    Resource, ResourceLoadError, DOWNLOAD_MANAGER, RESOURCE_LOADER,
+};
+
+/// Decides whether a resource can be displayed
+///
+/// The data of the resource passed to the callback might only be the beginning
+/// of the response body, it is long enough to sniff the type of the resource.
+pub type IsDisplayable = fn(&Resource) -> bool;
+
+#[derive(Debug)]
+pub enum Navigation {
+    /// The resource should be displayed
+    Display(Arc<Resource>),
+
+    /// The resource cannot be displayed and is saved to disk instead
+    Download(Arc<Download>),
+}
+
+/// Load the resource at the given [URL], saving it to disk if it cannot be displayed
+pub fn navigate(url: &URL, is_displayable: IsDisplayable) -> Result<Navigation, ResourceLoadError> {
+    navigate_with(url, is_displayable, &DOWNLOAD_MANAGER)
+}
+
+fn navigate_with(
+    url: &URL,
+    is_displayable: IsDisplayable,
+    download_manager: &'static DownloadManager,
+) -> Result<Navigation, ResourceLoadError> {
+    let is_http = matches!(url.scheme().as_str(), "http" | "https");
+    let cached_resource = RESOURCE_LOADER.cache().get(url);
+
+    if !is_http || cached_resource.is_some() {
+        // Everything else is loaded in one go anyways
+        let resource = match cached_resource {
+            Some(resource) => resource,
+            None => RESOURCE_LOADER.schedule_load(url.clone()).block()?,
+        };
+
+        if is_displayable(&resource) {
+            return Ok(Navigation::Display(resource));
+        }
+
+        return Ok(Navigation::Download(
+            download_manager.save(url.clone(), resource),
+        ));
+    }
+
+    let (sender, receiver) = oneshot::Channel::create();
+    let thread_url = url.clone();
+    thread::Builder::new()
+        .name("Navigation".to_string())
+        .spawn(move || {
+            let mut sink = NavigationSink {
+                url: thread_url,
+                is_displayable,
+                download_manager,
+                sender: Some(sender),
+                state: State::Waiting,
+            };
+            sink.run();
+        })?;
+
+    // The sender is only dropped without a message if the navigation thread panicked
+    receiver
+        .receive_blocking()
+        .unwrap_or(Err(ResourceLoadError::Panicked))
+}
+
+/// Receives the response to a navigation request
+struct NavigationSink {
+    url: URL,
+    is_displayable: IsDisplayable,
+    download_manager: &'static DownloadManager,
+
+    /// `None` once the navigation was answered
+    sender: Option<oneshot::Sender<Result<Navigation, ResourceLoadError>>>,
+
+    state: State,
+}
+
+enum State {
+    /// The head of the response was not received yet
+    Waiting,
+
+    /// Not enough of the body was received to decide what to do with the response
+    Sniffing {
+        response: Response,
+        data: Vec<u8>,
+    },
+
+    Displaying {
+        response: Response,
+        data: Vec<u8>,
+    },
+
+    Downloading(DownloadSink),
+}
+
+impl NavigationSink {
+    fn run(&mut self) {
+        let mut request = http::Request::get(&self.url);
+        request.set_proxy_config(SETTINGS.proxy_config());
+        if let Some(prompt) = crate::CREDENTIAL_PROMPT.get() {
+            request.set_credential_prompt(prompt.clone());
+        }
+
+        let result = request
+            .send_to(self)
+            .map_err(ResourceLoadError::from)
+            .and_then(|response| {
+                // Responses that may not have a body are never passed to the sink
+                if matches!(self.state, State::Waiting) {
+                    self.start(&response)?;
+                }
+
+                if matches!(self.state, State::Sniffing { .. }) {
+                    self.decide()?;
+                }
+                Ok(())
+            });
+
+        match mem::replace(&mut self.state, State::Waiting) {
+            State::Downloading(sink) => {
+                let download = sink.download().clone();
+                download.conclude(result.and_then(|()| sink.finish()));
+            },
+            State::Displaying { response, data } => {
+                let completion = result.map(|()| {
+                    let resource = Arc::new(Resource::new_for_http_request(data, response.headers));
+                    RESOURCE_LOADER
+                        .cache()
+                        .insert(self.url.clone(), resource.clone());
+                    Navigation::Display(resource)
+                });
+                self.answer(completion);
+            },
+            State::Waiting | State::Sniffing { .. } => {
+                let error = result.expect_err("a decision is made once the response was received");
+                self.answer(Err(error));
+            },
+        }
+    }
+
+    /// Decide whether the response should be displayed, based on the data that was received so far
+    fn decide(&mut self) -> io::Result<()> {
+        let State::Sniffing { response, data } = mem::replace(&mut self.state, State::Waiting)
+        else {
+            unreachable!("response is not being sniffed");
+        };
+
+        let resource = Resource::new_for_http_request(data.clone(), response.headers.clone());
+        if (self.is_displayable)(&resource) {
+            self.state = State::Displaying { response, data };
+            return Ok(());
+        }
+
+        let download = self.download_manager.register(self.url.clone());
+        self.answer(Ok(Navigation::Download(download.clone())));
+
+        let mut sink = DownloadSink::new(download);
+        let result = sink.start(&response).and_then(|()| sink.write(&data));
+        self.state = State::Downloading(sink);
+        result
+    }
+
+    fn answer(&mut self, navigation: Result<Navigation, ResourceLoadError>) {
+        if let Some(sender) = self.sender.take() {
+            // The user might not be waiting for the navigation anymore
+            _ = sender.send(navigation);
+        }
+    }
+}
+
+impl BodySink for NavigationSink {
+    fn start(&mut self, response: &Response) -> io::Result<()> {
+        self.state = State::Sniffing {
+            response: response.clone(),
+            data: vec![],
+        };
+        Ok(())
+    }
+
+    fn write(&mut self, buffer: &[u8]) -> io::Result<()> {
+        match &mut self.state {
+            State::Waiting => unreachable!("start was called before write"),
+            State::Sniffing { data, .. } => {
+                data.extend_from_slice(buffer);
+                if data.len() >= mime::RESOURCE_HEADER_SIZE {
+                    self.decide()?;
+                }
+                Ok(())
+            },
+            State::Displaying { data, .. } => {
+                data.extend_from_slice(buffer);
+                Ok(())
+            },
+            State::Downloading(sink) => sink.write(buffer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, fs,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        time,
+    };
+
+    use crate::download::DownloadStatus;
+
+    use super::*;
+
+    fn is_html(resource: &Resource) -> bool {
+        resource.mime_metadata().computed_mime_type.is_html()
+    }
+
+    /// Answer every request with the given response, returning the number of requests
+    fn serve(response: &'static [u8]) -> (URL, thread::JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            listener.set_nonblocking(true).unwrap();
+            let deadline = time::Instant::now() + time::Duration::from_millis(500);
+            let mut num_requests = 0;
+
+            while time::Instant::now() < deadline {
+                let Ok((mut stream, _)) = listener.accept() else {
+                    thread::sleep(time::Duration::from_millis(10));
+                    continue;
+                };
+
+                stream.set_nonblocking(false).unwrap();
+                BufReader::new(stream.try_clone().unwrap())
+                    .lines()
+                    .map(Result::unwrap)
+                    .take_while(|line| !line.is_empty())
+                    .for_each(drop);
+                stream.write_all(response).unwrap();
+                num_requests += 1;
+            }
+
+            num_requests
+        });
+
+        let url = format!("http://127.0.0.1:{port}/file").parse().unwrap();
+        (url, server)
+    }
+
+    #[test]
+    fn display_html() {
+        let (url, server) = serve(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: 13\r\n\r\n<p>Hello</p>\n",
+        );
+        let directory = env::temp_dir().join(format!("stormlicht-display-{}", std::process::id()));
+        let download_manager = Box::leak(Box::new(DownloadManager::new(directory.clone())));
+
+        let Ok(Navigation::Display(resource)) = navigate_with(&url, is_html, download_manager)
+        else {
+            panic!("html was not displayed");
+        };
+        assert_eq!(resource.data(), b"<p>Hello</p>\n");
+        assert!(download_manager.all().is_empty());
+        assert_eq!(server.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn download_without_second_request() {
+        let (url, server) = serve(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/zip\r\nContent-Disposition: attachment; filename=\"archive.zip\"\r\nContent-Length: 4\r\n\r\nPK\x03\x04",
+        );
+        let directory =
+            env::temp_dir().join(format!("stormlicht-navigation-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let download_manager = Box::leak(Box::new(DownloadManager::new(directory.clone())));
+
+        let Ok(Navigation::Download(download)) = navigate_with(&url, is_html, download_manager)
+        else {
+            panic!("zip file was not downloaded");
+        };
+
+        // The request is only sent once
+        assert_eq!(server.join().unwrap(), 1);
+
+        while download.state().status == DownloadStatus::InProgress {
+            thread::sleep(time::Duration::from_millis(10));
+        }
+        assert_eq!(download.state().status, DownloadStatus::Completed);
+        assert_eq!(
+            fs::read(directory.join("archive.zip")).unwrap(),
+            b"PK\x03\x04"
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+//! <https://w3c.github.io/FileAPI/#url>
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+use sl_std::rand::{ChaChaRng, Rng, RNG};
+use url::{Origin, URL};
+
+use crate::{Resource, ResourceLoadError};
+
+use super::ProtocolHandler;
+
+/// The blobs that scripts created object urls for
+pub static BLOB_URL_STORE: LazyLock<BlobURLStore> = LazyLock::new(BlobURLStore::default);
+
+/// Loads blobs that were registered in the [BLOB_URL_STORE]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BlobHandler;
+
+/// <https://w3c.github.io/FileAPI/#blob-url-entry>
+#[derive(Clone, Debug)]
+pub struct BlobURLEntry {
+    /// The bytes of the blob
+    pub data: Arc<[u8]>,
+
+    /// The `type` of the blob, which might be the empty string
+    pub content_type: String,
+
+    /// The origin of the environment that created the url
+    pub origin: Origin,
+}
+
+/// <https://w3c.github.io/FileAPI/#BlobURLStore>
+#[derive(Debug, Default)]
+pub struct BlobURLStore {
+    /// Maps urls, serialized without their fragment, to the blob that they refer to
+    entries: RwLock<HashMap<String, BlobURLEntry>>,
+}
+
+impl BlobURLStore {
+    /// Register the blob under a new, unique url
+    ///
+    /// <https://w3c.github.io/FileAPI/#add-an-entry>
+    pub fn add(&self, entry: BlobURLEntry) -> URL {
+        // 2. Let url be the result of generating a new blob URL.
+        let url = generate_blob_url(&entry.origin);
+
+        // 3. Let entry be a new blob URL entry consisting of object and environment.
+        // 4. Set store[url] to entry.
+        self.entries
+            .write()
+            .expect("blob url store was poisoned")
+            .insert(url.serialize(url::ExcludeFragment::Yes).to_string(), entry);
+
+        // 5. Return url.
+        url
+    }
+
+    /// Forget about the blob that the url refers to, subsequent loads of the url fail
+    ///
+    /// Urls that were created by a different origin are ignored.
+    ///
+    /// <https://w3c.github.io/FileAPI/#dfn-revokeObjectURL>
+    pub fn revoke(&self, url: &URL, origin: &Origin) {
+        let key = url.serialize(url::ExcludeFragment::Yes).to_string();
+        let mut entries = self.entries.write().expect("blob url store was poisoned");
+
+        // 2. If url’s origin is not same origin with the current settings object’s origin, return.
+        // NOTE: The origin of a blob url is the origin of the environment that created it
+        let Some(entry) = entries.get(&key) else {
+            return;
+        };
+        if !entry.origin.is_same_origin(origin) {
+            log::warn!("Refusing to revoke {key}, it was created by a different origin");
+            return;
+        }
+
+        // 3. Remove an entry from the Blob URL Store for url.
+        entries.remove(&key);
+    }
+
+    /// <https://w3c.github.io/FileAPI/#blob-url-resolve>
+    #[must_use]
+    pub fn resolve(&self, url: &URL) -> Option<BlobURLEntry> {
+        // 1. Assert: url’s scheme is "blob".
+        debug_assert_eq!(url.scheme().as_str(), "blob");
+
+        // 3. Let urlString be the result of serializing url with the exclude fragment flag set.
+        // 4. If store[urlString] exists, then return store[urlString]; otherwise return failure.
+        self.entries
+            .read()
+            .expect("blob url store was poisoned")
+            .get(url.serialize(url::ExcludeFragment::Yes).as_str())
+            .cloned()
+    }
+}
+
+/// <https://w3c.github.io/FileAPI/#unicodeBlobURL>
+fn generate_blob_url(origin: &Origin) -> URL {
+    // 1. Let result be the empty string.
+    // 2. Append the string "blob:" to result.
+    // 3. Let settings be the current settings object
+    // 4. Let origin be settings’s origin.
+    // 5. Let serialized be the ASCII serialization of origin.
+    // 6. If serialized is "null", set it to an implementation-defined value.
+    // 7. Append serialized to result.
+    // 8. Append U+0024 SOLIDUS (/) to result.
+    // 9. Generate a UUID [RFC4122] as a string and append it to result.
+    // 10. Return result.
+    format!("blob:{origin}/{}", generate_uuid())
+        .parse()
+        .expect("blob urls are always valid")
+}
+
+/// Generates a random (version 4) UUID, like `1b4db7eb-4057-5ddf-91e0-36dec72071f5`
+///
+/// <https://www.rfc-editor.org/rfc/rfc4122#section-4.4>
+fn generate_uuid() -> String {
+    let mut bytes = [0; 16];
+    match ChaChaRng::from_entropy() {
+        Ok(mut rng) => rng.fill_bytes(&mut bytes),
+        Err(error) => {
+            log::warn!("Failed to seed random number generator: {error}");
+            RNG::default().fill_bytes(&mut bytes);
+        },
+    }
+
+    // Set the version to 4 and the variant to RFC4122
+    bytes[6] = (bytes[6] & 0x0F) | 0x40;
+    bytes[8] = (bytes[8] & 0x3F) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+impl ProtocolHandler for BlobHandler {
+    /// <https://fetch.spec.whatwg.org/#scheme-fetch>
+    fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+        // 1. Let blobURLEntry be request’s current URL’s blob URL entry.
+        // 2. If request’s method is not `GET` or blobURLEntry is null, then return a network error.
+        let Some(entry) = BLOB_URL_STORE.resolve(url) else {
+            log::error!(
+                "Failed to load {}: The url does not refer to a blob",
+                url.serialize(url::ExcludeFragment::Yes)
+            );
+            return Err(ResourceLoadError::UnknownBlobURL);
+        };
+
+        // 9. Let type be blob’s type.
+        // 10. If request’s header list does not contain `Range`:
+        //     5. Set response’s header list to (`Content-Length`, serializedFullLength), (`Content-Type`, type).
+        // FIXME: Support range requests
+        let mime_type = entry.content_type.parse().ok();
+
+        Ok(Resource::new(entry.data.to_vec(), mime_type))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(origin: &Origin) -> BlobURLEntry {
+        BlobURLEntry {
+            data: Arc::from(b"hello".as_slice()),
+            content_type: "text/plain".to_string(),
+            origin: origin.clone(),
+        }
+    }
+
+    #[test]
+    fn object_urls() {
+        let origin = "https://example.com/index.html"
+            .parse::<URL>()
+            .unwrap()
+            .origin();
+        let url = BLOB_URL_STORE.add(entry(&origin));
+
+        assert!(url
+            .serialize(url::ExcludeFragment::Yes)
+            .as_str()
+            .starts_with("blob:https://example.com/"));
+        assert!(url.origin().is_same_origin(&origin));
+
+        // Fragments are ignored when resolving the url
+        let mut with_fragment = url.serialize(url::ExcludeFragment::Yes).to_string();
+        with_fragment.push_str("#fragment");
+        let resource = Resource::load(&with_fragment.parse().unwrap()).unwrap();
+        assert_eq!(resource.data(), b"hello");
+
+        // Only the origin that created the url can revoke it
+        BLOB_URL_STORE.revoke(&url, &Origin::new_opaque());
+        assert!(BLOB_URL_STORE.resolve(&url).is_some());
+
+        BLOB_URL_STORE.revoke(&url, &origin);
+        assert!(BLOB_URL_STORE.resolve(&url).is_none());
+        assert!(matches!(
+            Resource::load(&url),
+            Err(ResourceLoadError::UnknownBlobURL)
+        ));
+    }
+
+    #[test]
+    fn unique_urls() {
+        let origin = Origin::new_opaque();
+        let first = BLOB_URL_STORE.add(entry(&origin));
+        let second = BLOB_URL_STORE.add(entry(&origin));
+
+        assert_ne!(first, second);
+        assert!(first
+            .serialize(url::ExcludeFragment::Yes)
+            .as_str()
+            .starts_with("blob:null/"));
+    }
+}
@@ -0,0 +1,208 @@
+//! <https://fetch.spec.whatwg.org/#data-urls>
+
+use sl_std::{ascii, base64, percent_encode};
+use url::URL;
+
+use crate::{Resource, ResourceLoadError};
+
+use super::ProtocolHandler;
+
+/// Loads data that is encoded directly in the url
+///
+/// <https://www.rfc-editor.org/rfc/rfc2397#section-2>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DataHandler;
+
+/// The contents of a `data:` url
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataURL {
+    /// The media type from the url, `text/plain;charset=US-ASCII` if it was omitted or invalid
+    ///
+    /// Parameters like `charset` are preserved so that textual payloads can be decoded correctly.
+    pub mime_type: mime::MIMEType,
+
+    pub body: Vec<u8>,
+}
+
+impl DataURL {
+    /// <https://fetch.spec.whatwg.org/#data-url-processor>
+    pub fn process(url: &URL) -> Result<Self, ResourceLoadError> {
+        // 1. Assert: dataURL’s scheme is "data".
+        debug_assert_eq!(url.scheme().as_str(), "data");
+
+        // 2. Let input be the result of running the URL serializer on dataURL with exclude fragment set to true.
+        // 3. Remove the leading "data:" from input.
+        let input = url.serialize(url::ExcludeFragment::Yes);
+        let input = &input[b"data:".len()..];
+
+        // 4. Let position point at the start of input.
+        // 5. Let mimeType be the result of collecting a sequence of code points that are not equal to U+002C (,),
+        //    given position.
+        // 7. If position is past the end of input, then return failure.
+        let Some((mime_type, encoded_body)) = input.split_once(ascii::Char::Comma) else {
+            return Err(ResourceLoadError::InvalidDataURL);
+        };
+
+        // 6. Strip leading and trailing ASCII whitespace from mimeType.
+        let mut mime_type = mime_type.trim().as_str();
+
+        // 8. Advance position by 1.
+        // 9. Let encodedBody be the remainder of input.
+        // 10. Let body be the percent-decoding of encodedBody.
+        let mut body = percent_encode::percent_decode(encoded_body);
+
+        // 11. If mimeType ends with U+003B (;), followed by zero or more U+0020 SPACE, followed by an
+        //     ASCII case-insensitive match for "base64", then:
+        if let Some(without_base64) = strip_base64_suffix(mime_type) {
+            // 1. Let stringBody be the isomorphic decode of body.
+            // 2. Set body to the forgiving-base64 decode of stringBody.
+            // 3. If body is failure, then return failure.
+            body = forgiving_base64_decode(&body)?;
+
+            // 4. Remove the last 6 code points from mimeType.
+            // 5. Remove trailing U+0020 SPACE code points from mimeType, if any.
+            // 6. Remove the last U+003B (;) from mimeType.
+            mime_type = without_base64;
+        }
+
+        // 12. If mimeType starts with ";", then prepend "text/plain" to mimeType.
+        // 13. Let mimeTypeRecord be the result of parsing mimeType.
+        let mime_type = if mime_type.starts_with(';') {
+            format!("text/plain{mime_type}").parse()
+        } else {
+            mime_type.parse()
+        };
+
+        // 14. If mimeTypeRecord is failure, then set mimeTypeRecord to text/plain;charset=US-ASCII.
+        let mime_type = mime_type.unwrap_or_else(|_| {
+            let mut mime_type = mime::MIMEType::new("text", "plain");
+            mime_type.set_parameter("charset", "US-ASCII");
+            mime_type
+        });
+
+        // 15. Return a new data: URL struct whose MIME type is mimeTypeRecord and body is body.
+        Ok(Self { mime_type, body })
+    }
+}
+
+/// Returns the media type without the `;base64` marker, or `None` if the data is not base64-encoded
+fn strip_base64_suffix(mime_type: &str) -> Option<&str> {
+    let suffix_start = mime_type.len().checked_sub(b"base64".len())?;
+    if !mime_type.is_char_boundary(suffix_start)
+        || !mime_type[suffix_start..].eq_ignore_ascii_case("base64")
+    {
+        return None;
+    }
+
+    mime_type[..suffix_start]
+        .trim_end_matches(' ')
+        .strip_suffix(';')
+}
+
+/// <https://infra.spec.whatwg.org/#forgiving-base64-decode>
+fn forgiving_base64_decode(data: &[u8]) -> Result<Vec<u8>, ResourceLoadError> {
+    // 1. Remove all ASCII whitespace from data.
+    let mut data: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+
+    // 2. If data’s code point length divides by 4 leaving no remainder, then:
+    //    1. If data ends with one or two U+003D (=) code points, then remove them from data.
+    if data.len() % 4 == 0 {
+        for _ in 0..2 {
+            if data.last() == Some(&b'=') {
+                data.pop();
+            }
+        }
+    }
+
+    // 3. If data’s code point length divides by 4 leaving a remainder of 1, return failure.
+    // 4. If data contains a code point that is not one of U+002B (+), U+002F (/), ASCII alphanumeric,
+    //    then return failure.
+    // NOTE: Padding was already removed, so the decoder rejects any leftover "=", just like
+    //       it rejects data with an invalid length.
+    let Some(data) = ascii::String::from_bytes(data) else {
+        return Err(base64::Error::IllegalCharacter.into());
+    };
+
+    // 5-9. Decode the remaining symbols
+    let data = base64::STANDARD_NO_PAD.decode(&data)?;
+
+    Ok(data)
+}
+
+impl ProtocolHandler for DataHandler {
+    fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+        let data_url = DataURL::process(url).inspect_err(|_| {
+            log::error!(
+                "Failed to load {}: malformed data URL",
+                url.serialize(url::ExcludeFragment::Yes)
+            );
+        })?;
+
+        Ok(Resource::new(data_url.body, Some(data_url.mime_type)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(url: &str) -> Result<DataURL, ResourceLoadError> {
+        DataURL::process(&url.parse().unwrap())
+    }
+
+    #[test]
+    fn percent_encoded_body() {
+        let data_url = process("data:text/css,a%20%7B%7D#fragment").unwrap();
+        assert_eq!(data_url.mime_type, mime::MIMEType::new("text", "css"));
+        assert_eq!(data_url.body, b"a {}");
+
+        // The query is part of the body
+        let data_url = process("data:,a?b").unwrap();
+        assert_eq!(data_url.body, b"a?b");
+
+        assert!(process("data:text/plain").is_err());
+    }
+
+    #[test]
+    fn base64_body() {
+        let data_url = process("data:image/png;base64,iVBORw0K").unwrap();
+        assert_eq!(data_url.mime_type, mime::MIMEType::new("image", "png"));
+        assert_eq!(data_url.body, b"\x89PNG\r\n");
+
+        // The marker is case-insensitive, padding is optional and whitespace is ignored
+        let data_url = process("data:font/woff2; BASE64,d09G%20Mg==").unwrap();
+        assert_eq!(data_url.mime_type, mime::MIMEType::new("font", "woff2"));
+        assert_eq!(data_url.body, b"wOF2");
+        assert_eq!(process("data:;base64,d09GMg").unwrap().body, b"wOF2");
+
+        assert!(process("data:;base64,d09GM").is_err());
+        assert!(process("data:;base64,d0=9GMg").is_err());
+
+        // Without the marker, the body is not decoded
+        assert_eq!(process("data:base64,d09GMg").unwrap().body, b"d09GMg");
+    }
+
+    #[test]
+    fn media_type() {
+        let default = "text/plain;charset=US-ASCII";
+        assert_eq!(process("data:,").unwrap().mime_type.to_string(), default);
+        assert_eq!(
+            process("data:invalid,").unwrap().mime_type.to_string(),
+            default
+        );
+
+        // The charset of the payload is preserved
+        let data_url = process("data:;charset=utf-8,%C3%A4").unwrap();
+        assert_eq!(data_url.mime_type.to_string(), "text/plain;charset=utf-8");
+        assert_eq!(data_url.mime_type.charset(), Some("utf-8"));
+        assert_eq!(data_url.body, "ä".as_bytes());
+
+        let data_url = process("data:text/html;charset=windows-1252;base64,5A==").unwrap();
+        assert_eq!(data_url.mime_type.charset(), Some("windows-1252"));
+        assert_eq!(data_url.body, b"\xE4");
+    }
+}
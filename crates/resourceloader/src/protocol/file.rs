@@ -0,0 +1,28 @@
+use std::fs;
+
+use url::URL;
+
+use crate::{Resource, ResourceLoadError};
+
+use super::ProtocolHandler;
+
+/// Reads files from the local filesystem
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileHandler;
+
+impl ProtocolHandler for FileHandler {
+    fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+        let data = match url.as_file_path() {
+            Ok(path) => fs::read(path)?,
+            Err(_) => {
+                log::error!(
+                    "Failed to load {}: Invalid file path for current platform",
+                    url.serialize(url::ExcludeFragment::Yes)
+                );
+                return Err(ResourceLoadError::InvalidFilePath);
+            },
+        };
+
+        Ok(Resource::new(data, None))
+    }
+}
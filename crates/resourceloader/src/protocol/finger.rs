@@ -0,0 +1,112 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use sl_std::{ascii, percent_encode};
+use url::URL;
+
+use crate::{Resource, ResourceLoadError};
+
+use super::ProtocolHandler;
+
+/// The port that finger servers listen on
+const FINGER_PORT: u16 = 79;
+
+/// Servers that take longer than this to respond are given up on
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Responses are cut off after this many bytes
+const MAX_RESPONSE_SIZE: u64 = 1024 * 1024;
+
+/// Queries information about users from a finger server
+///
+/// A url like `finger://example.com/alice` sends the query `alice` to `example.com`,
+/// an empty path lists all users that are logged in. The response is displayed as plain text.
+///
+/// This is mostly meant as an example of how to add support for a new protocol.
+///
+/// The handler is not registered for subresources, because it would allow any page to send
+/// arbitrary lines to any host. The browser only uses it for navigations that the user started.
+///
+/// <https://www.rfc-editor.org/rfc/rfc1288>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FingerHandler;
+
+impl ProtocolHandler for FingerHandler {
+    fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+        let Some(host) = url.host() else {
+            return Err(ResourceLoadError::MissingHost);
+        };
+
+        // Other ports belong to other protocols, which must not receive finger queries
+        if url.port().is_some_and(|port| port != FINGER_PORT) {
+            return Err(ResourceLoadError::InvalidFingerURL);
+        }
+        let query = query(url).ok_or(ResourceLoadError::InvalidFingerURL)?;
+
+        let mut stream = TcpStream::connect((host.to_string().as_str(), FINGER_PORT))?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        stream.write_all(&query)?;
+
+        // The server closes the connection once the response is complete
+        let mut response = vec![];
+        stream.take(MAX_RESPONSE_SIZE).read_to_end(&mut response)?;
+
+        let mime_type = mime::MIMEType::new("text", "plain");
+        Ok(Resource::new(response, Some(mime_type)))
+    }
+}
+
+/// The query line that is sent to the server, including the terminating CRLF
+///
+/// Returns `None` if the query contains control characters, which could be used to
+/// send additional lines to the server.
+#[must_use]
+fn query(url: &URL) -> Option<Vec<u8>> {
+    let path = url.path();
+    let user = path.strip_prefix(ascii::Char::Solidus).unwrap_or(path);
+
+    let mut query = percent_encode::percent_decode(user);
+    if query.iter().any(u8::is_ascii_control) {
+        return None;
+    }
+
+    query.extend_from_slice(b"\r\n");
+    Some(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_query() {
+        let url: URL = "finger://example.com/alice%20smith".parse().unwrap();
+        assert_eq!(query(&url).unwrap(), b"alice smith\r\n");
+
+        let url: URL = "finger://example.com".parse().unwrap();
+        assert_eq!(query(&url).unwrap(), b"\r\n");
+    }
+
+    #[test]
+    fn reject_control_characters() {
+        let url: URL = "finger://127.0.0.1/x%0D%0AFLUSHALL%0D%0A".parse().unwrap();
+        assert!(query(&url).is_none());
+
+        let url: URL = "finger://example.com/alice%00".parse().unwrap();
+        assert!(query(&url).is_none());
+    }
+
+    #[test]
+    fn reject_other_ports() {
+        let url: URL = "finger://127.0.0.1:6379/alice".parse().unwrap();
+        assert!(matches!(
+            FingerHandler.load(&url),
+            Err(ResourceLoadError::InvalidFingerURL)
+        ));
+    }
+}
@@ -0,0 +1,28 @@
+use settings::SETTINGS;
+use url::URL;
+
+use crate::{Resource, ResourceLoadError};
+
+use super::ProtocolHandler;
+
+/// Fetches `http` and `https` urls
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HttpHandler;
+
+impl ProtocolHandler for HttpHandler {
+    fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+        let mut request = http::request::Request::get(url);
+
+        request.set_proxy_config(SETTINGS.proxy_config());
+        if let Some(prompt) = crate::CREDENTIAL_PROMPT.get() {
+            request.set_credential_prompt(prompt.clone());
+        }
+
+        let response = request.send()?;
+
+        Ok(Resource::new_for_http_request(
+            response.body,
+            response.headers,
+        ))
+    }
+}
@@ -0,0 +1,100 @@
+//! Loading resources for the different url schemes
+//!
+//! Every scheme that can be loaded has a [ProtocolHandler] in a global registry. Additional
+//! protocols can be supported by registering a handler with [register_protocol_handler],
+//! without touching the rest of the resource loader.
+
+mod blob;
+mod data;
+mod file;
+mod finger;
+mod http;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+use url::URL;
+
+use crate::{Resource, ResourceLoadError};
+
+pub use blob::{BlobHandler, BlobURLEntry, BlobURLStore, BLOB_URL_STORE};
+pub use data::{DataHandler, DataURL};
+pub use file::FileHandler;
+pub use finger::FingerHandler;
+pub use http::HttpHandler;
+
+/// Loads resources for one or more url schemes
+///
+/// Handlers are called from the threads that load resources, possibly multiple at the same time.
+pub trait ProtocolHandler: Send + Sync {
+    /// Fetch the resource that the url points to
+    ///
+    /// The scheme of the url is always one of the schemes that the handler was registered for.
+    fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError>;
+}
+
+type Registry = HashMap<String, Arc<dyn ProtocolHandler>>;
+
+static PROTOCOL_HANDLERS: LazyLock<RwLock<Registry>> = LazyLock::new(|| {
+    let http = Arc::new(HttpHandler);
+
+    let mut handlers: Registry = HashMap::new();
+    handlers.insert("http".to_string(), http.clone());
+    handlers.insert("https".to_string(), http);
+    handlers.insert("file".to_string(), Arc::new(FileHandler));
+    handlers.insert("data".to_string(), Arc::new(DataHandler));
+    handlers.insert("blob".to_string(), Arc::new(BlobHandler));
+
+    RwLock::new(handlers)
+});
+
+/// Make a handler responsible for loading urls with the given scheme
+///
+/// A handler that was previously registered for the scheme is replaced.
+pub fn register_protocol_handler<H>(scheme: &str, handler: H)
+where
+    H: ProtocolHandler + 'static,
+{
+    let scheme = scheme.to_ascii_lowercase();
+    log::info!("Registering protocol handler for {scheme:?}");
+
+    PROTOCOL_HANDLERS
+        .write()
+        .expect("protocol handler registry was poisoned")
+        .insert(scheme, Arc::new(handler));
+}
+
+/// Find the handler that loads urls with the given scheme
+#[must_use]
+pub fn handler_for(scheme: &str) -> Option<Arc<dyn ProtocolHandler>> {
+    PROTOCOL_HANDLERS
+        .read()
+        .expect("protocol handler registry was poisoned")
+        .get(scheme)
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoHandler;
+
+    impl ProtocolHandler for EchoHandler {
+        fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+            Ok(Resource::new(url.path().as_bytes().to_vec(), None))
+        }
+    }
+
+    #[test]
+    fn custom_protocol() {
+        assert!(handler_for("echo").is_none());
+        register_protocol_handler("ECHO", EchoHandler);
+
+        let url: URL = "echo:hello".parse().unwrap();
+        let resource = Resource::load(&url).unwrap();
+        assert_eq!(resource.data(), b"hello");
+    }
+}
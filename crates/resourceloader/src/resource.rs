@@ -1,10 +1,11 @@
 use error_derive::Error;
 use http::request::HTTPError;
-use settings::SETTINGS;
-use sl_std::{ascii, base64};
-use std::{fs, io};
+use sl_std::base64;
+use std::io;
 use url::URL;
 
+use crate::protocol;
+
 #[derive(Clone, Debug)]
 pub struct Resource {
     data: Vec<u8>,
@@ -37,6 +38,18 @@ pub enum ResourceLoadError {
     #[msg = "invalid data url"]
     InvalidDataURL,
 
+    #[msg = "blob url does not refer to a blob"]
+    UnknownBlobURL,
+
+    #[msg = "url does not have a host"]
+    MissingHost,
+
+    #[msg = "invalid finger url"]
+    InvalidFingerURL,
+
+    #[msg = "protocol handler panicked"]
+    Panicked,
+
     #[msg = "io error"]
     IO(io::Error),
 }
@@ -44,7 +57,8 @@ pub enum ResourceLoadError {
 impl Resource {
     #[must_use]
     pub fn new_for_http_request(data: Vec<u8>, headers: http::Headers) -> Self {
-        let mime_metadata = mime::Metadata::for_http_request(&data, &headers, mime::NoSniff::No);
+        let no_sniff = mime::NoSniff::from_headers(&headers);
+        let mime_metadata = mime::Metadata::for_http_request(&data, &headers, no_sniff);
 
         Self {
             data,
@@ -86,87 +100,24 @@ impl Resource {
     }
 
     pub fn load(url: &URL) -> Result<Resource, ResourceLoadError> {
+        let _span = trace::span!(Net, "load {}", url.serialize(url::ExcludeFragment::Yes));
+
         log::info!(
             "Starting load of {}",
             url.serialize(url::ExcludeFragment::Yes)
         );
 
-        let resource = match url.scheme().as_str() {
-            "http" | "https" => {
-                // Fetch the file via http
-                let mut request = http::request::Request::get(url);
-
-                if let Some(proxy) = dbg!(SETTINGS.proxy) {
-                    request.set_proxy(proxy);
-                }
-
-                let response = request.send()?;
-
-                Self::new_for_http_request(response.body, response.headers)
-            },
-            "file" => {
-                // Fetch the file from the local filesystem
-                let data = match url.as_file_path() {
-                    Ok(path) => fs::read(path)?,
-                    Err(_) => {
-                        log::error!(
-                            "Failed to load {}: Invalid file path for current platform",
-                            url.serialize(url::ExcludeFragment::Yes)
-                        );
-                        return Err(ResourceLoadError::InvalidFilePath);
-                    },
-                };
-
-                Self::new(data, None)
-            },
-            "data" => {
-                // Load data encoded directly in the URL
-                // https://www.rfc-editor.org/rfc/rfc2397#section-2
-                if !url.has_opaque_path() {
-                    log::error!(
-                        "Failed to load {}: data URLs need to have an opaque path",
-                        url.serialize(url::ExcludeFragment::Yes)
-                    );
-                    return Err(ResourceLoadError::InvalidDataURL);
-                }
-
-                let opaque_path = &url.path();
-                let (mut before_data, data) = match opaque_path.split_once(ascii::Char::Comma) {
-                    Some(segments) => segments,
-                    None => return Err(ResourceLoadError::InvalidDataURL),
-                };
-
-                let is_b64 = if before_data.as_bytes().ends_with(b";base64") {
-                    before_data = &before_data[..before_data.len() - b";base64".len()];
-                    true
-                } else {
-                    false
-                };
-
-                let mut supplied_mime_type = None;
-                if !before_data.is_empty() {
-                    // We treat parse errors in the provided mime type as if no mime type
-                    // had been provided
-                    supplied_mime_type = before_data.as_str().parse().ok();
-                }
-
-                let data = if is_b64 {
-                    base64::b64decode(data)?
-                } else {
-                    url::percent_decode(data).to_vec()
-                };
-
-                Self::new(data, supplied_mime_type)
-            },
-            other => {
-                log::error!(
-                    "Failed to load unknown url scheme: {other} from {}",
-                    url.serialize(url::ExcludeFragment::Yes)
-                );
-                return Err(ResourceLoadError::UnsupportedScheme);
-            },
+        let Some(handler) = protocol::handler_for(url.scheme().as_str()) else {
+            log::error!(
+                "Failed to load unknown url scheme: {} from {}",
+                url.scheme(),
+                url.serialize(url::ExcludeFragment::Yes)
+            );
+            return Err(ResourceLoadError::UnsupportedScheme);
         };
 
+        let resource = handler.load(url)?;
+
         log::info!(
             "Successfully loaded {}",
             url.serialize(url::ExcludeFragment::Yes)
@@ -0,0 +1,235 @@
+//! Decides the order in which requested resources are loaded
+//!
+//! Resources are loaded in order of their [Priority]. The number of loads that
+//! run at the same time is limited, both in total and for every single host.
+//! Among loads of the same priority, hosts that received less data so far go first,
+//! so a single host with large resources cannot starve all others.
+
+use std::{cmp, collections::HashMap};
+
+use url::URL;
+
+/// The maximum number of resources that are loaded at the same time
+pub const MAX_CONCURRENT_LOADS: usize = 16;
+
+/// The maximum number of resources that are loaded from a single host at the same time
+pub const MAX_CONNECTIONS_PER_HOST: usize = 6;
+
+/// How urgently a resource is needed, more urgent resources are loaded first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Resources that might be needed by a future navigation
+    Prefetch,
+
+    /// Resources that are not needed to render the page, like images outside the viewport
+    Normal,
+
+    /// Scripts that do not block the parser
+    AsyncScript,
+
+    /// Images inside the viewport
+    VisibleImage,
+
+    Font,
+
+    /// Documents and stylesheets, nothing can be displayed until they are loaded
+    RenderBlocking,
+}
+
+impl Priority {
+    /// The weight of a HTTP/2 stream that loads a resource with this priority
+    ///
+    /// Streams receive bandwidth proportional to their weight, which ranges from 1 to 256.
+    ///
+    /// [Specification](https://www.rfc-editor.org/rfc/rfc9113#section-5.3.2)
+    #[must_use]
+    pub const fn http2_weight(&self) -> u16 {
+        match self {
+            Self::Prefetch => 1,
+            Self::Normal => 16,
+            Self::AsyncScript => 32,
+            Self::VisibleImage => 64,
+            Self::Font => 128,
+            Self::RenderBlocking => 256,
+        }
+    }
+}
+
+/// A load that is waiting to be started
+#[derive(Debug)]
+struct QueuedLoad<T> {
+    url: URL,
+    priority: Priority,
+    payload: T,
+}
+
+/// Keeps track of pending loads and the loads that are currently running
+///
+/// Every load carries a payload, which is handed back when the load is started.
+#[derive(Debug)]
+pub(crate) struct Scheduler<T> {
+    /// Loads that were not started yet, in the order they were requested
+    queue: Vec<QueuedLoad<T>>,
+
+    /// The number of running loads for every host
+    active_loads: HashMap<String, usize>,
+
+    /// The total number of running loads
+    num_active_loads: usize,
+
+    /// The number of bytes received from each host so far
+    bytes_received: HashMap<String, usize>,
+}
+
+impl<T> Default for Scheduler<T> {
+    fn default() -> Self {
+        Self {
+            queue: Vec::new(),
+            active_loads: HashMap::new(),
+            num_active_loads: 0,
+            bytes_received: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Scheduler<T> {
+    pub(crate) fn push(&mut self, url: URL, priority: Priority, payload: T) {
+        self.queue.push(QueuedLoad {
+            url,
+            priority,
+            payload,
+        });
+    }
+
+    /// Find a load that was not started yet
+    ///
+    /// The priority of the load may be raised, for example because a more urgent
+    /// request for the same resource was made.
+    pub(crate) fn get_mut(&mut self, url: &URL) -> Option<(&mut Priority, &mut T)> {
+        self.queue
+            .iter_mut()
+            .find(|queued_load| queued_load.url == *url)
+            .map(|queued_load| (&mut queued_load.priority, &mut queued_load.payload))
+    }
+
+    /// Start the most important load that is allowed to run right now
+    ///
+    /// Every started load must later be marked as [finished](Self::finish).
+    pub(crate) fn next(&mut self) -> Option<(URL, Priority, T)> {
+        if self.num_active_loads >= MAX_CONCURRENT_LOADS {
+            return None;
+        }
+
+        let (index, _) = self
+            .queue
+            .iter()
+            .enumerate()
+            .filter(|(_, queued_load)| {
+                let host = host_of(&queued_load.url);
+
+                // Resources without a host, like files, don't occupy any connections
+                host.is_empty()
+                    || self.active_loads.get(&host).copied().unwrap_or_default()
+                        < MAX_CONNECTIONS_PER_HOST
+            })
+            .min_by_key(|(index, queued_load)| {
+                let bytes_received = self
+                    .bytes_received
+                    .get(&host_of(&queued_load.url))
+                    .copied()
+                    .unwrap_or_default();
+
+                (cmp::Reverse(queued_load.priority), bytes_received, *index)
+            })?;
+
+        let queued_load = self.queue.remove(index);
+        *self
+            .active_loads
+            .entry(host_of(&queued_load.url))
+            .or_default() += 1;
+        self.num_active_loads += 1;
+
+        Some((queued_load.url, queued_load.priority, queued_load.payload))
+    }
+
+    /// Mark a load that was started by [next](Self::next) as finished
+    pub(crate) fn finish(&mut self, url: &URL, bytes_received: usize) {
+        let host = host_of(url);
+
+        if let Some(active_loads) = self.active_loads.get_mut(&host) {
+            *active_loads -= 1;
+            if *active_loads == 0 {
+                self.active_loads.remove(&host);
+            }
+        }
+        self.num_active_loads -= 1;
+
+        *self.bytes_received.entry(host).or_default() += bytes_received;
+    }
+}
+
+fn host_of(url: &URL) -> String {
+    url.host().map(ToString::to_string).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> URL {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn most_important_load_first() {
+        let mut scheduler = Scheduler::default();
+        scheduler.push(url("https://a.com/image"), Priority::VisibleImage, 0);
+        scheduler.push(url("https://a.com/prefetch"), Priority::Prefetch, 1);
+        scheduler.push(url("https://a.com/style"), Priority::RenderBlocking, 2);
+        scheduler.push(url("https://a.com/font"), Priority::Font, 3);
+        scheduler.push(
+            url("https://a.com/other-style"),
+            Priority::RenderBlocking,
+            4,
+        );
+
+        let order: Vec<_> = std::iter::from_fn(|| scheduler.next())
+            .map(|(_, _, payload)| payload)
+            .collect();
+        assert_eq!(order, [2, 4, 3, 0, 1]);
+    }
+
+    #[test]
+    fn connections_per_host_are_limited() {
+        let mut scheduler = Scheduler::default();
+        for i in 0..MAX_CONNECTIONS_PER_HOST + 1 {
+            scheduler.push(url("https://a.com/"), Priority::Normal, i);
+        }
+        scheduler.push(url("https://b.com/"), Priority::Prefetch, 100);
+
+        // Once a.com is saturated, loads from other hosts are started even if they are less important
+        for _ in 0..MAX_CONNECTIONS_PER_HOST {
+            assert_eq!(scheduler.next().unwrap().1, Priority::Normal);
+        }
+        assert_eq!(scheduler.next().unwrap().2, 100);
+        assert!(scheduler.next().is_none());
+
+        scheduler.finish(&url("https://a.com/"), 0);
+        assert_eq!(scheduler.next().unwrap().2, MAX_CONNECTIONS_PER_HOST);
+    }
+
+    #[test]
+    fn hosts_share_bandwidth() {
+        let mut scheduler = Scheduler::default();
+        scheduler.push(url("https://a.com/"), Priority::Normal, 0);
+        scheduler.push(url("https://b.com/"), Priority::Normal, 1);
+
+        // a.com already sent a lot of data, so b.com goes first
+        let (a, _, _) = scheduler.next().unwrap();
+        scheduler.finish(&a, 1024);
+        scheduler.push(url("https://a.com/"), Priority::Normal, 2);
+
+        assert_eq!(scheduler.next().unwrap().2, 1);
+        assert_eq!(scheduler.next().unwrap().2, 2);
+    }
+}
@@ -1,7 +1,7 @@
-use std::net;
+use std::{net, path::PathBuf};
 use url::URL;
 
-use crate::Settings;
+use crate::{DeviceEmulation, Profile, Settings};
 
 #[derive(clap::Parser, Debug)]
 #[command(name = "Stormlicht", version, about="A modern browser engine", long_about = None)]
@@ -20,11 +20,49 @@ pub struct Arguments {
     /// Proxy for http requests
     #[arg(long, value_parser = parse_socketaddr)]
     proxy: Option<net::SocketAddr>,
+
+    /// Render web pages on the GPU, if possible
+    #[clap(
+        long,
+        action = clap::ArgAction::SetTrue,
+    )]
+    gpu: bool,
+
+    /// Emulate a device with the given screen size and pixel ratio, like 375x667@2
+    #[arg(long, value_name = "WIDTHxHEIGHT[@RATIO]", value_parser = parse_device_emulation)]
+    emulate_device: Option<DeviceEmulation>,
+
+    /// Directory to store user data in, instead of the default location
+    #[arg(long, value_hint = clap::ValueHint::DirPath)]
+    profile: Option<PathBuf>,
+
+    /// Accept remote control commands on the given unix socket instead of opening a window
+    #[arg(long, value_name = "SOCKET", value_hint = clap::ValueHint::FilePath)]
+    remote_control: Option<PathBuf>,
+
+    /// Save the page as a PDF document to the given file instead of opening a window
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    print_to_pdf: Option<PathBuf>,
+
+    /// Record where time is spent and save it to the given file, in the chrome trace format
+    #[arg(long, value_name = "FILE", value_hint = clap::ValueHint::FilePath)]
+    trace: Option<PathBuf>,
+
+    /// Log how much memory each subsystem uses once a page is first laid out
+    #[clap(
+        long,
+        action = clap::ArgAction::SetTrue,
+    )]
+    report_memory: bool,
 }
 
 impl Arguments {
+    pub(crate) fn profile(&self) -> Option<Profile> {
+        self.profile.clone().map(Profile::new)
+    }
+
     pub(crate) fn update_settings(self, settings: &mut Settings) {
-        settings.disable_javascript = settings.disable_javascript;
+        settings.disable_javascript = self.disable_javascript;
 
         if let Some(url) = self.url {
             settings.url = url;
@@ -33,6 +71,13 @@ impl Arguments {
         if let Some(proxy) = self.proxy {
             settings.proxy = Some(proxy);
         }
+
+        settings.use_gpu = self.gpu;
+        settings.device_emulation = self.emulate_device;
+        settings.remote_control = self.remote_control;
+        settings.print_to_pdf = self.print_to_pdf;
+        settings.trace_file = self.trace;
+        settings.report_memory = self.report_memory;
     }
 }
 
@@ -40,6 +85,10 @@ fn parse_url(s: &str) -> Result<URL, String> {
     s.parse().map_err(|e: url::Error| format!("{e:?}"))
 }
 
+fn parse_device_emulation(s: &str) -> Result<DeviceEmulation, String> {
+    s.parse().map_err(|e| format!("{e}"))
+}
+
 fn parse_socketaddr(s: &str) -> Result<net::SocketAddr, String> {
     s.parse()
         .map_err(|e: <net::SocketAddr as std::str::FromStr>::Err| format!("{e}"))
@@ -0,0 +1,111 @@
+use std::str::FromStr;
+
+use error_derive::Error;
+
+/// Describes a device whose screen should be emulated instead of using the window size
+///
+/// This is useful for testing how pages behave on small (mobile) screens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeviceEmulation {
+    /// Width of the screen, in display points
+    pub width: u16,
+
+    /// Height of the screen, in display points
+    pub height: u16,
+
+    /// The number of device pixels per display point
+    pub device_pixel_ratio: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum InvalidDeviceEmulation {
+    #[msg = "expected a size like 375x667, optionally followed by @ and a pixel ratio"]
+    InvalidFormat,
+
+    #[msg = "device size must not be zero"]
+    EmptySize,
+
+    #[msg = "device pixel ratio must be positive"]
+    InvalidPixelRatio,
+}
+
+impl FromStr for DeviceEmulation {
+    type Err = InvalidDeviceEmulation;
+
+    /// Parse a device description like `375x667@2`
+    ///
+    /// The device pixel ratio is optional and defaults to `1`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (size, device_pixel_ratio) = match s.split_once('@') {
+            Some((size, ratio)) => {
+                let ratio: f32 = ratio
+                    .parse()
+                    .map_err(|_| InvalidDeviceEmulation::InvalidFormat)?;
+                (size, ratio)
+            },
+            None => (s, 1.),
+        };
+
+        let (width, height) = size
+            .split_once('x')
+            .ok_or(InvalidDeviceEmulation::InvalidFormat)?;
+        let width: u16 = width
+            .parse()
+            .map_err(|_| InvalidDeviceEmulation::InvalidFormat)?;
+        let height: u16 = height
+            .parse()
+            .map_err(|_| InvalidDeviceEmulation::InvalidFormat)?;
+
+        if width == 0 || height == 0 {
+            return Err(InvalidDeviceEmulation::EmptySize);
+        }
+
+        if !device_pixel_ratio.is_finite() || device_pixel_ratio <= 0. {
+            return Err(InvalidDeviceEmulation::InvalidPixelRatio);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            device_pixel_ratio,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_device_emulation() {
+        assert_eq!(
+            "375x667@2".parse(),
+            Ok(DeviceEmulation {
+                width: 375,
+                height: 667,
+                device_pixel_ratio: 2.,
+            })
+        );
+        assert_eq!(
+            "800x600".parse(),
+            Ok(DeviceEmulation {
+                width: 800,
+                height: 600,
+                device_pixel_ratio: 1.,
+            })
+        );
+
+        assert_eq!(
+            "375".parse::<DeviceEmulation>(),
+            Err(InvalidDeviceEmulation::InvalidFormat)
+        );
+        assert_eq!(
+            "0x600".parse::<DeviceEmulation>(),
+            Err(InvalidDeviceEmulation::EmptySize)
+        );
+        assert_eq!(
+            "375x667@0".parse::<DeviceEmulation>(),
+            Err(InvalidDeviceEmulation::InvalidPixelRatio)
+        );
+    }
+}
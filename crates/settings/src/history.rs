@@ -0,0 +1,456 @@
+//! Records which pages were visited, for the url bar autocompletion and `about:history`
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{RwLock, RwLockReadGuard},
+    time::{Duration, SystemTime},
+};
+
+use serialize::{Deserialize, Serialize};
+use serialize_json::{JsonDeserializer, JsonSerializer};
+use url::URL;
+
+use crate::{Profile, ProfileError};
+
+/// Older entries are forgotten once the history grows larger than this
+const MAX_ENTRIES: usize = 10_000;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// A single url in the browsing history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub url: String,
+
+    /// Title of the page during the most recent visit, may be empty
+    pub title: String,
+
+    pub visit_count: usize,
+
+    /// How often the url was entered in the url bar
+    pub typed_count: usize,
+
+    /// Time of the most recent visit, in seconds since the unix epoch
+    pub last_visit: u64,
+}
+
+/// The browsing history, persisted in the users [Profile]
+#[derive(Debug)]
+pub struct History {
+    /// Where the history is stored, `None` if it is not persisted
+    file: Option<PathBuf>,
+
+    /// Entries, ordered from least to most recently visited
+    entries: RwLock<Vec<HistoryEntry>>,
+}
+
+impl History {
+    /// Create a history that only lives in memory
+    #[must_use]
+    pub fn in_memory() -> Self {
+        Self {
+            file: None,
+            entries: RwLock::default(),
+        }
+    }
+
+    /// Read the history from the profile
+    ///
+    /// A profile without history yields an empty history.
+    pub fn load(profile: &Profile) -> Result<Self, ProfileError> {
+        let file = profile.history_file();
+
+        let entries = match fs::read_to_string(&file) {
+            Ok(contents) => {
+                let mut deserializer = JsonDeserializer::new(&contents);
+                Vec::<HistoryEntry>::deserialize(&mut deserializer)
+                    .map_err(|_| ProfileError::MalformedHistory)?
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self {
+            file: Some(file),
+            entries: RwLock::new(entries),
+        })
+    }
+
+    /// All entries, ordered from least to most recently visited
+    pub fn entries(&self) -> RwLockReadGuard<'_, Vec<HistoryEntry>> {
+        self.entries.read().expect("history lock is poisoned")
+    }
+
+    /// Record a visit to the given url
+    pub fn record_visit(&self, url: &URL, title: &str) -> Result<(), ProfileError> {
+        self.update(url, now(), |entry| {
+            entry.visit_count += 1;
+            entry.title = title.to_owned();
+        })
+    }
+
+    /// Remember that the user typed the url into the url bar
+    ///
+    /// Typed urls are ranked higher by [History::query]. This does not count as a visit.
+    pub fn record_typed(&self, url: &URL) -> Result<(), ProfileError> {
+        self.update(url, now(), |entry| entry.typed_count += 1)
+    }
+
+    /// Remove a url from the history, returns `true` if the url was part of the history
+    pub fn remove(&self, url: &str) -> Result<bool, ProfileError> {
+        let was_removed = {
+            let mut entries = self.entries.write().expect("history lock is poisoned");
+            let length_before = entries.len();
+            entries.retain(|entry| entry.url != url);
+            entries.len() != length_before
+        };
+
+        if was_removed {
+            self.save()?;
+        }
+
+        Ok(was_removed)
+    }
+
+    pub fn clear(&self) -> Result<(), ProfileError> {
+        self.entries
+            .write()
+            .expect("history lock is poisoned")
+            .clear();
+
+        self.save()
+    }
+
+    /// Find the entries that best match what the user typed so far, best match first
+    ///
+    /// Urls whose host starts with the input are preferred over urls or titles that contain
+    /// it, which in turn are preferred over urls that only contain the characters of the input
+    /// in the same order. Frequently typed and recently visited urls are ranked higher.
+    #[must_use]
+    pub fn query(&self, input: &str, limit: usize) -> Vec<HistoryEntry> {
+        self.query_at(input, limit, now())
+    }
+
+    fn query_at(&self, input: &str, limit: usize, now: u64) -> Vec<HistoryEntry> {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return vec![];
+        }
+
+        let entries = self.entries();
+        let mut matches: Vec<(f64, &HistoryEntry)> = entries
+            .iter()
+            .filter_map(|entry| {
+                let quality = match_quality(&input, entry)?;
+                Some((quality * frecency(entry, now), entry))
+            })
+            .collect();
+
+        matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    /// Complete what the user typed so far to the best matching url in the history
+    ///
+    /// The completion starts with the input, so that the rest of the url can be suggested
+    /// inline. Urls match if they start with the input, with or without their scheme and `www.`.
+    ///
+    /// Returns `None` if no url can complete the input.
+    #[must_use]
+    pub fn complete(&self, input: &str) -> Option<String> {
+        self.complete_at(input, now())
+    }
+
+    fn complete_at(&self, input: &str, now: u64) -> Option<String> {
+        // The user is typing search terms, not a url
+        if input.is_empty() || input.contains(char::is_whitespace) {
+            return None;
+        }
+
+        self.query_at(input, usize::MAX, now)
+            .into_iter()
+            .find_map(|entry| complete_url(input, &entry.url))
+    }
+
+    fn update<F>(&self, url: &URL, now: u64, update: F) -> Result<(), ProfileError>
+    where
+        F: FnOnce(&mut HistoryEntry),
+    {
+        let url = url.serialize(url::ExcludeFragment::Yes).to_string();
+
+        {
+            let mut entries = self.entries.write().expect("history lock is poisoned");
+
+            let mut entry = match entries.iter().position(|entry| entry.url == url) {
+                Some(index) => entries.remove(index),
+                None => HistoryEntry {
+                    url,
+                    title: String::new(),
+                    visit_count: 0,
+                    typed_count: 0,
+                    last_visit: now,
+                },
+            };
+
+            entry.last_visit = now;
+            update(&mut entry);
+            entries.push(entry);
+
+            if entries.len() > MAX_ENTRIES {
+                let excess = entries.len() - MAX_ENTRIES;
+                entries.drain(..excess);
+            }
+        }
+
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), ProfileError> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+
+        let serialized = JsonSerializer::serialize_to_string(self.entries().clone())
+            .expect("Writing to a string cannot fail");
+
+        if let Some(directory) = file.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(file, serialized)?;
+
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+/// Strip the parts of a url that users usually don't type
+fn strip_url_prefix(url: &str) -> &str {
+    let url = url
+        .split_once("://")
+        .map_or(url, |(_, after_scheme)| after_scheme);
+    url.strip_prefix("www.").unwrap_or(url)
+}
+
+/// Complete the input to the given url, keeping the part that was typed by the user
+fn complete_url(input: &str, url: &str) -> Option<String> {
+    let after_scheme = url.split_once("://").map(|(_, after_scheme)| after_scheme);
+    let after_www = after_scheme.and_then(|url| url.strip_prefix("www."));
+
+    [Some(url), after_scheme, after_www]
+        .into_iter()
+        .flatten()
+        .find_map(|candidate| {
+            let typed = candidate.get(..input.len())?;
+            let rest = &candidate[input.len()..];
+            if !typed.eq_ignore_ascii_case(input) || rest.is_empty() {
+                return None;
+            }
+
+            Some(format!("{input}{rest}"))
+        })
+}
+
+/// How well the (lowercase) input matches the entry, or `None` if it does not match at all
+fn match_quality(input: &str, entry: &HistoryEntry) -> Option<f64> {
+    let url = entry.url.to_lowercase();
+    let title = entry.title.to_lowercase();
+
+    if strip_url_prefix(&url).starts_with(strip_url_prefix(input)) {
+        Some(4.)
+    } else if url.contains(input) || title.contains(input) {
+        Some(2.)
+    } else if is_subsequence(input, &url) || is_subsequence(input, &title) {
+        Some(1.)
+    } else {
+        None
+    }
+}
+
+/// Whether all characters of `needle` appear in `haystack` in the same order
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|other| other == c))
+}
+
+/// Weighs how often and how recently an entry was visited
+fn frecency(entry: &HistoryEntry, now: u64) -> f64 {
+    let age_in_days = now.saturating_sub(entry.last_visit) / SECONDS_PER_DAY;
+    let recency = match age_in_days {
+        0..=3 => 1.,
+        4..=14 => 0.7,
+        15..=31 => 0.5,
+        _ => 0.3,
+    };
+
+    // Typing a url is a much stronger signal than following a link
+    let popularity = 1 + entry.visit_count + 4 * entry.typed_count;
+
+    popularity as f64 * recency
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn url(url: &str) -> URL {
+        url.parse().unwrap()
+    }
+
+    #[test]
+    fn record_visits() {
+        let history = History::in_memory();
+
+        history
+            .record_visit(&url("https://example.com/#top"), "Example")
+            .unwrap();
+        history
+            .record_visit(&url("https://stormlicht.rs"), "Stormlicht")
+            .unwrap();
+        history
+            .record_visit(&url("https://example.com"), "Example Domain")
+            .unwrap();
+
+        let entries = history.entries().clone();
+        assert_eq!(entries.len(), 2);
+
+        // Most recent visits are at the end
+        assert_eq!(entries[1].url, "https://example.com/");
+        assert_eq!(entries[1].title, "Example Domain");
+        assert_eq!(entries[1].visit_count, 2);
+
+        assert!(history.remove("https://stormlicht.rs/").unwrap());
+        assert!(!history.remove("https://stormlicht.rs/").unwrap());
+        assert_eq!(history.entries().len(), 1);
+    }
+
+    #[test]
+    fn query_ranking() {
+        let history = History::in_memory();
+        let now = 100 * SECONDS_PER_DAY;
+
+        let entry = |url: &str, title: &str, visit_count, typed_count, age_in_days| HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            visit_count,
+            typed_count,
+            last_visit: now - age_in_days * SECONDS_PER_DAY,
+        };
+        *history.entries.write().unwrap() = vec![
+            entry("https://www.example.com/", "Example Domain", 1, 0, 0),
+            entry("https://examples.org/", "", 3, 2, 0),
+            entry("https://docs.rs/", "Examining crates", 2, 0, 0),
+            entry("https://old.example.net/", "", 1, 0, 60),
+            entry("https://unrelated.com/", "", 100, 0, 0),
+        ];
+
+        let query = |input: &str| -> Vec<String> {
+            history
+                .query_at(input, 10, now)
+                .into_iter()
+                .map(|entry| entry.url)
+                .collect()
+        };
+
+        // Typed urls win over urls that were only visited
+        assert_eq!(
+            query("exa"),
+            [
+                "https://examples.org/",
+                "https://www.example.com/",
+                "https://docs.rs/",
+                "https://old.example.net/"
+            ]
+        );
+
+        // Prefix matches ignore the scheme and "www."
+        assert_eq!(
+            query("https://www.example.c")[0],
+            "https://www.example.com/"
+        );
+
+        // Fuzzy matches
+        assert_eq!(query("unrltd"), ["https://unrelated.com/"]);
+        assert!(query("   ").is_empty());
+    }
+
+    #[test]
+    fn complete_urls() {
+        let history = History::in_memory();
+        let now = 100 * SECONDS_PER_DAY;
+
+        let entry = |url: &str, typed_count| HistoryEntry {
+            url: url.to_string(),
+            title: String::new(),
+            visit_count: 1,
+            typed_count,
+            last_visit: now,
+        };
+        *history.entries.write().unwrap() = vec![
+            entry("https://www.example.com/", 0),
+            entry("https://examples.org/", 1),
+            entry("https://docs.rs/", 3),
+        ];
+
+        // Urls that only contain the input cannot be completed inline
+        assert_eq!(
+            history.complete_at("exa", now).as_deref(),
+            Some("examples.org/")
+        );
+        assert_eq!(
+            history.complete_at("www.exa", now).as_deref(),
+            Some("www.example.com/")
+        );
+
+        // The input is kept as it was typed
+        assert_eq!(
+            history.complete_at("HTTPS://d", now).as_deref(),
+            Some("HTTPS://docs.rs/")
+        );
+
+        assert_eq!(history.complete_at("docs.rs/", now), None);
+        assert_eq!(history.complete_at("rust docs", now), None);
+        assert_eq!(history.complete_at("", now), None);
+    }
+
+    #[test]
+    fn persist_history() {
+        let directory = env::temp_dir().join(format!("stormlicht-history-{}", std::process::id()));
+        let profile = Profile::new(directory.clone());
+
+        let history = History::load(&profile).unwrap();
+        assert!(history.entries().is_empty());
+
+        history
+            .record_visit(&url("https://example.com"), "Example \"Domain\"")
+            .unwrap();
+        history.record_typed(&url("https://example.com")).unwrap();
+
+        let loaded = History::load(&profile).unwrap();
+        assert_eq!(*loaded.entries(), *history.entries());
+        assert_eq!(loaded.entries()[0].typed_count, 1);
+
+        loaded.clear().unwrap();
+        assert!(History::load(&profile).unwrap().entries().is_empty());
+
+        fs::write(profile.history_file(), "[").unwrap();
+        assert!(matches!(
+            History::load(&profile),
+            Err(ProfileError::MalformedHistory)
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+//! Keyboard shortcuts for the commands of the browser interface
+//!
+//! Every [Command] has default accelerators which can be overridden with the `keybindings`
+//! preference. Its textual form is a comma separated list of `<command>=<accelerators>`
+//! entries, like `reload=<Primary>r F5,quit=`. Accelerators use the GTK syntax and are
+//! separated by spaces, an empty list unbinds the command.
+
+use std::collections::HashMap;
+
+use crate::PreferenceError;
+
+/// An action of the browser interface that can be triggered with a keyboard shortcut
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Command {
+    Reload,
+    Back,
+    Forward,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    ToggleReaderMode,
+    FocusAddressBar,
+    OpenFile,
+    PrintToPdf,
+    ShowShortcuts,
+    Quit,
+}
+
+impl Command {
+    /// All commands, in the order they should be listed to the user
+    pub const ALL: [Self; 12] = [
+        Self::Reload,
+        Self::Back,
+        Self::Forward,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::ResetZoom,
+        Self::ToggleReaderMode,
+        Self::FocusAddressBar,
+        Self::OpenFile,
+        Self::PrintToPdf,
+        Self::ShowShortcuts,
+        Self::Quit,
+    ];
+
+    /// The name of the command, as used in the `keybindings` preference
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Reload => "reload",
+            Self::Back => "back",
+            Self::Forward => "forward",
+            Self::ZoomIn => "zoom-in",
+            Self::ZoomOut => "zoom-out",
+            Self::ResetZoom => "reset-zoom",
+            Self::ToggleReaderMode => "toggle-reader-mode",
+            Self::FocusAddressBar => "focus-address-bar",
+            Self::OpenFile => "open-file",
+            Self::PrintToPdf => "print-to-pdf",
+            Self::ShowShortcuts => "show-shortcuts",
+            Self::Quit => "quit",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|command| command.name() == name)
+    }
+
+    /// Describes the command to the user
+    #[must_use]
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Self::Reload => "Reload the page",
+            Self::Back => "Go back",
+            Self::Forward => "Go forward",
+            Self::ZoomIn => "Zoom in",
+            Self::ZoomOut => "Zoom out",
+            Self::ResetZoom => "Reset the zoom level",
+            Self::ToggleReaderMode => "Toggle reader mode",
+            Self::FocusAddressBar => "Focus the address bar",
+            Self::OpenFile => "Open a file",
+            Self::PrintToPdf => "Print the page to a PDF file",
+            Self::ShowShortcuts => "Show keyboard shortcuts",
+            Self::Quit => "Quit",
+        }
+    }
+
+    /// The accelerators that trigger the command unless the user changed them
+    #[must_use]
+    pub const fn default_accelerators(&self) -> &'static [&'static str] {
+        match self {
+            Self::Reload => &["<Primary>r", "F5"],
+            Self::Back => &["<Alt>Left"],
+            Self::Forward => &["<Alt>Right"],
+            Self::ZoomIn => &["<Primary>plus", "<Primary>equal"],
+            Self::ZoomOut => &["<Primary>minus"],
+            Self::ResetZoom => &["<Primary>0"],
+            Self::ToggleReaderMode => &["<Primary><Alt>r"],
+            Self::FocusAddressBar => &["<Primary>l", "F6"],
+            Self::OpenFile => &["<Primary>o"],
+            Self::PrintToPdf => &["<Primary>p"],
+            Self::ShowShortcuts => &["<Primary>question"],
+            Self::Quit => &["<Primary>q"],
+        }
+    }
+}
+
+/// The accelerators of every [Command], with the overrides of the user applied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keybindings {
+    accelerators: HashMap<Command, Vec<String>>,
+}
+
+impl Keybindings {
+    /// Apply the given overrides, as stored in the `keybindings` preference, to the defaults
+    ///
+    /// Invalid overrides are ignored.
+    #[must_use]
+    pub fn with_overrides(overrides: &[String]) -> Self {
+        let mut accelerators: HashMap<Command, Vec<String>> = Command::ALL
+            .into_iter()
+            .map(|command| {
+                let defaults = command
+                    .default_accelerators()
+                    .iter()
+                    .map(|accelerator| accelerator.to_string())
+                    .collect();
+                (command, defaults)
+            })
+            .collect();
+
+        for entry in overrides {
+            match parse_override(entry) {
+                Ok((command, overridden)) => {
+                    accelerators.insert(command, overridden);
+                },
+                Err(_) => log::warn!("Ignoring invalid keybinding: {entry:?}"),
+            }
+        }
+
+        Self { accelerators }
+    }
+
+    /// The accelerators that trigger the given command, which might be empty
+    #[must_use]
+    pub fn accelerators(&self, command: Command) -> &[String] {
+        self.accelerators
+            .get(&command)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        Self::with_overrides(&[])
+    }
+}
+
+/// Parse a single `<command>=<accelerators>` entry of the `keybindings` preference
+pub(crate) fn parse_override(entry: &str) -> Result<(Command, Vec<String>), PreferenceError> {
+    let (name, accelerators) = entry.split_once('=').ok_or(PreferenceError::InvalidValue)?;
+    let command = Command::from_name(name.trim()).ok_or(PreferenceError::InvalidValue)?;
+    let accelerators = accelerators
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+
+    Ok((command, accelerators))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_replace_defaults() {
+        let keybindings = Keybindings::with_overrides(&[
+            "reload=F5".to_string(),
+            "quit=".to_string(),
+            "does-not-exist=F1".to_string(),
+        ]);
+
+        assert_eq!(keybindings.accelerators(Command::Reload), ["F5"]);
+        assert!(keybindings.accelerators(Command::Quit).is_empty());
+        assert_eq!(keybindings.accelerators(Command::Back), ["<Alt>Left"]);
+    }
+
+    #[test]
+    fn parse_entries() {
+        let (command, accelerators) =
+            parse_override(" zoom-in = <Primary>plus  <Primary>equal").unwrap();
+        assert_eq!(command, Command::ZoomIn);
+        assert_eq!(accelerators, ["<Primary>plus", "<Primary>equal"]);
+
+        assert!(parse_override("reload").is_err());
+        assert!(parse_override("unknown=F1").is_err());
+    }
+}
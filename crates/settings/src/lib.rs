@@ -1,52 +1,473 @@
 //! Responsible for creating and managing the global stormlicht settings
+//!
+//! Settings come from two sources: [Preferences] that are persisted in the users [Profile]
+//! and command line arguments, which only apply to the current session.
 
 mod cli;
+mod device_emulation;
+mod history;
+mod keybindings;
+mod permissions;
+mod preferences;
+mod profile;
+mod session;
 
-use std::{net, sync::LazyLock};
+pub use device_emulation::{DeviceEmulation, InvalidDeviceEmulation};
+pub use history::{History, HistoryEntry};
+pub use keybindings::{Command, Keybindings};
+pub use permissions::{
+    PermissionEntry, PermissionName, PermissionRequest, PermissionState, Permissions,
+};
+pub use preferences::{PreferenceError, Preferences, TextAntialiasing, Theme};
+pub use profile::{Profile, ProfileError};
+pub use session::{Session, SessionPage};
+
+use std::{
+    fmt, net,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, Mutex, RwLock, RwLockReadGuard,
+    },
+};
 
 use clap::Parser;
+use http::{proxy, ProxyConfig};
 use url::URL;
 
 /// The global settings singleton
 pub static SETTINGS: LazyLock<Settings> = LazyLock::new(Settings::init);
 
+/// The browsing history of the profile in [SETTINGS]
+pub static HISTORY: LazyLock<History> = LazyLock::new(|| {
+    let Some(profile) = SETTINGS.profile() else {
+        return History::in_memory();
+    };
+
+    History::load(profile).unwrap_or_else(|error| {
+        log::error!(
+            "Failed to load history from {}: {error}",
+            profile.history_file().display()
+        );
+        History::in_memory()
+    })
+});
+
+/// The permissions that the user granted to websites, persisted in the profile in [SETTINGS]
+pub static PERMISSIONS: LazyLock<Permissions> = LazyLock::new(|| {
+    let Some(profile) = SETTINGS.profile() else {
+        return Permissions::in_memory();
+    };
+
+    Permissions::load(profile).unwrap_or_else(|error| {
+        log::error!(
+            "Failed to load permissions from {}: {error}",
+            profile.permissions_file().display()
+        );
+        Permissions::in_memory()
+    })
+});
+
+/// The pages that are open in the browser, persisted in the profile in [SETTINGS]
+pub static SESSION: LazyLock<Session> = LazyLock::new(|| {
+    let Some(profile) = SETTINGS.profile() else {
+        return Session::in_memory();
+    };
+
+    Session::start(profile).unwrap_or_else(|error| {
+        log::error!(
+            "Failed to restore session from {}: {error}",
+            profile.session_file().display()
+        );
+        Session::in_memory()
+    })
+});
+
 const WELCOME_PAGE: &str = concat!(
     "file://localhost/",
     env!("CARGO_MANIFEST_DIR"),
     "/../pages/welcome.html"
 );
 
+type ChangeListener = Arc<dyn Fn(&Preferences) + Send + Sync>;
+
 /// Holds all the configurable information for a stormlicht instance
-#[derive(Debug)]
 pub struct Settings {
-    pub disable_javascript: bool,
-
     /// URL to load initially
     pub url: URL,
 
-    /// Proxy for networking
-    pub proxy: Option<net::SocketAddr>,
+    /// Whether or not to render on the GPU instead of the CPU
+    pub use_gpu: bool,
+
+    /// The device whose screen is emulated, set by `--emulate-device`
+    pub device_emulation: Option<DeviceEmulation>,
+
+    /// The socket to accept remote control commands on, set by `--remote-control`
+    pub remote_control: Option<PathBuf>,
+
+    /// Where to save the initial page as a PDF document instead of opening a window, set by `--print-to-pdf`
+    pub print_to_pdf: Option<PathBuf>,
+
+    /// Where to save the recorded trace on exit, set by `--trace`
+    pub trace_file: Option<PathBuf>,
+
+    /// Whether to log a memory report after the first layout of a page, set by `--report-memory`
+    pub report_memory: bool,
+
+    /// Where preferences are stored, `None` if they are not persisted
+    profile: Option<Profile>,
+
+    preferences: RwLock<Preferences>,
+
+    /// Callbacks that are invoked whenever the preferences change
+    listeners: Mutex<Vec<ChangeListener>>,
+
+    /// Set by `--disable-javascript`, takes precedence over the preferences
+    disable_javascript: bool,
+
+    /// Set by `--proxy`, takes precedence over the preferences
+    proxy: Option<net::SocketAddr>,
+
+    /// Proxy configuration from environment variables, used if the preferences
+    /// don't specify a proxy
+    environment_proxy: ProxyConfig,
+
+    /// The proxies that are used for networking, resolved from `proxy`, the preferences
+    /// and `environment_proxy` whenever one of them changes
+    proxy_config: RwLock<ProxyConfig>,
+
+    /// Whether the desktop environment uses a dark color scheme, reported by the chrome
+    system_prefers_dark: AtomicBool,
 }
 
 impl Settings {
     #[must_use]
     pub fn init() -> Self {
-        let mut settings = Self::default();
-
         let args = cli::Arguments::parse();
 
+        let profile = args.profile().or_else(Profile::locate_default);
+        let preferences = profile
+            .as_ref()
+            .map(Self::load_preferences)
+            .unwrap_or_default();
+
+        if let Some(profile) = &profile {
+            if let Err(error) = http::HSTS.load(profile.hsts_file()) {
+                log::error!(
+                    "Failed to load strict transport security policies from {}: {error}",
+                    profile.hsts_file().display()
+                );
+            }
+        }
+
+        let mut settings = Self::new(profile, preferences);
+        settings.environment_proxy = ProxyConfig::from_env();
         args.update_settings(&mut settings);
+        settings.update_proxy_config();
 
         settings
     }
-}
 
-impl Default for Settings {
-    fn default() -> Self {
-        Self {
+    #[must_use]
+    pub fn new(profile: Option<Profile>, preferences: Preferences) -> Self {
+        let url = preferences.homepage().unwrap_or_else(|| {
+            log::warn!("Invalid homepage: {:?}", preferences.homepage);
+            WELCOME_PAGE.parse().expect("welcome page is a valid url")
+        });
+
+        let settings = Self {
+            url,
+            use_gpu: false,
+            device_emulation: None,
+            remote_control: None,
+            print_to_pdf: None,
+            trace_file: None,
+            report_memory: false,
+            profile,
+            preferences: RwLock::new(preferences),
+            listeners: Mutex::default(),
             disable_javascript: false,
-            url: WELCOME_PAGE.parse().expect("welcome page is a valid url"),
             proxy: None,
+            environment_proxy: ProxyConfig::DIRECT,
+            proxy_config: RwLock::new(ProxyConfig::DIRECT),
+            system_prefers_dark: AtomicBool::new(false),
+        };
+        settings.update_proxy_config();
+
+        settings
+    }
+
+    fn load_preferences(profile: &Profile) -> Preferences {
+        match profile.load_preferences() {
+            Ok(Some(preferences)) => preferences,
+            Ok(None) => Preferences::default(),
+            Err(error) => {
+                log::error!(
+                    "Failed to load preferences from {}: {error}",
+                    profile.preferences_file().display()
+                );
+                Preferences::default()
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    pub fn preferences(&self) -> RwLockReadGuard<'_, Preferences> {
+        self.preferences
+            .read()
+            .expect("preferences lock is poisoned")
+    }
+
+    /// Modify the preferences, persist them in the profile and notify all listeners
+    ///
+    /// Listeners are notified even if the preferences could not be saved.
+    pub fn update_preferences<F>(&self, update: F) -> Result<(), ProfileError>
+    where
+        F: FnOnce(&mut Preferences),
+    {
+        let preferences = {
+            let mut preferences = self
+                .preferences
+                .write()
+                .expect("preferences lock is poisoned");
+            update(&mut preferences);
+
+            // Updated while the preferences are locked, so that concurrent updates are
+            // applied in the same order
+            *self
+                .proxy_config
+                .write()
+                .expect("proxy config lock is poisoned") = self.resolve_proxy_config(&preferences);
+
+            preferences.clone()
+        };
+
+        // Listeners may access the settings themselves, so they are not called while
+        // the lock is held
+        let listeners = self
+            .listeners
+            .lock()
+            .expect("listener lock is poisoned")
+            .clone();
+        for listener in listeners {
+            listener(&preferences);
         }
+
+        match &self.profile {
+            Some(profile) => profile.save_preferences(&preferences),
+            None => Ok(()),
+        }
+    }
+
+    /// Register a callback that is invoked whenever the preferences change
+    pub fn on_change<F>(&self, listener: F)
+    where
+        F: Fn(&Preferences) + Send + Sync + 'static,
+    {
+        self.listeners
+            .lock()
+            .expect("listener lock is poisoned")
+            .push(Arc::new(listener));
+    }
+
+    #[must_use]
+    pub fn javascript_enabled(&self) -> bool {
+        !self.disable_javascript && self.preferences().javascript_enabled
+    }
+
+    /// Whether web pages should be displayed with a dark color scheme
+    ///
+    /// This is exposed to web pages through `prefers-color-scheme`. If the theme
+    /// follows the system, the color scheme of the desktop environment is used.
+    #[must_use]
+    pub fn prefers_dark_color_scheme(&self) -> bool {
+        match self.preferences().theme {
+            Theme::System => self.system_prefers_dark.load(Ordering::Relaxed),
+            Theme::Light => false,
+            Theme::Dark => true,
+        }
+    }
+
+    /// Report the color scheme of the desktop environment
+    pub fn set_system_prefers_dark(&self, prefers_dark: bool) {
+        self.system_prefers_dark
+            .store(prefers_dark, Ordering::Relaxed);
+    }
+
+    /// The proxies that should be used for networking
+    ///
+    /// The `--proxy` argument takes precedence over the preferences, which in turn
+    /// take precedence over the environment.
+    #[must_use]
+    pub fn proxy_config(&self) -> ProxyConfig {
+        self.proxy_config
+            .read()
+            .expect("proxy config lock is poisoned")
+            .clone()
+    }
+
+    /// Resolve the proxy configuration again, after one of its sources changed
+    fn update_proxy_config(&self) {
+        let proxy_config = self.resolve_proxy_config(&self.preferences());
+        *self
+            .proxy_config
+            .write()
+            .expect("proxy config lock is poisoned") = proxy_config;
+    }
+
+    fn resolve_proxy_config(&self, preferences: &Preferences) -> ProxyConfig {
+        if let Some(proxy) = self.proxy {
+            return ProxyConfig::for_all_schemes(proxy);
+        }
+
+        let parse = |proxy: &Option<String>| {
+            let proxy = proxy.as_deref()?;
+            let address = proxy::parse_proxy_address(proxy);
+
+            if address.is_none() {
+                log::warn!("Ignoring invalid proxy: {proxy:?}");
+            }
+
+            address
+        };
+
+        ProxyConfig {
+            http: parse(&preferences.http_proxy).or(self.environment_proxy.http),
+            https: parse(&preferences.https_proxy).or(self.environment_proxy.https),
+            no_proxy: preferences
+                .no_proxy
+                .iter()
+                .chain(&self.environment_proxy.no_proxy)
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::new(None, Preferences::default())
+    }
+}
+
+impl fmt::Debug for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Settings")
+            .field("url", &self.url)
+            .field("use_gpu", &self.use_gpu)
+            .field("device_emulation", &self.device_emulation)
+            .field("remote_control", &self.remote_control)
+            .field("print_to_pdf", &self.print_to_pdf)
+            .field("trace_file", &self.trace_file)
+            .field("report_memory", &self.report_memory)
+            .field("profile", &self.profile)
+            .field("preferences", &self.preferences)
+            .field("disable_javascript", &self.disable_javascript)
+            .field("proxy", &self.proxy)
+            .field("environment_proxy", &self.environment_proxy)
+            .field("proxy_config", &self.proxy_config)
+            .field("system_prefers_dark", &self.system_prefers_dark)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn change_listeners() {
+        let settings = Settings::default();
+        assert!(settings.javascript_enabled());
+
+        let was_notified = Arc::new(AtomicBool::new(false));
+        let flag = was_notified.clone();
+        settings.on_change(move |preferences| {
+            assert!(!preferences.javascript_enabled);
+            flag.store(true, Ordering::Relaxed);
+        });
+
+        settings
+            .update_preferences(|preferences| preferences.javascript_enabled = false)
+            .unwrap();
+
+        assert!(was_notified.load(Ordering::Relaxed));
+        assert!(!settings.javascript_enabled());
+    }
+
+    #[test]
+    fn listeners_can_access_settings() {
+        static SETTINGS: LazyLock<Settings> = LazyLock::new(Settings::default);
+        static NUM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        SETTINGS.on_change(|_| {
+            assert!(!SETTINGS.javascript_enabled());
+            SETTINGS.on_change(|_| {
+                NUM_CALLS.fetch_add(1, Ordering::Relaxed);
+            });
+        });
+
+        SETTINGS
+            .update_preferences(|preferences| preferences.javascript_enabled = false)
+            .unwrap();
+        SETTINGS
+            .update_preferences(|preferences| preferences.theme = Theme::Dark)
+            .unwrap();
+
+        // Only the listener registered during the first update was called
+        assert_eq!(NUM_CALLS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn color_scheme() {
+        let settings = Settings::default();
+        settings.set_system_prefers_dark(true);
+        assert!(settings.prefers_dark_color_scheme());
+
+        settings
+            .update_preferences(|preferences| preferences.theme = Theme::Light)
+            .unwrap();
+        assert!(!settings.prefers_dark_color_scheme());
+    }
+
+    #[test]
+    fn proxy_precedence() {
+        let mut settings = Settings {
+            environment_proxy: ProxyConfig {
+                http: Some(net::SocketAddr::from(([127, 0, 0, 1], 1))),
+                https: Some(net::SocketAddr::from(([127, 0, 0, 1], 1))),
+                no_proxy: vec!["localhost".to_string()],
+            },
+            ..Settings::default()
+        };
+        settings
+            .update_preferences(|preferences| {
+                preferences.https_proxy = Some("127.0.0.1:2".to_string());
+            })
+            .unwrap();
+
+        let config = settings.proxy_config();
+        assert_eq!(
+            config.http,
+            Some(net::SocketAddr::from(([127, 0, 0, 1], 1)))
+        );
+        assert_eq!(
+            config.https,
+            Some(net::SocketAddr::from(([127, 0, 0, 1], 2)))
+        );
+        assert_eq!(config.no_proxy, ["localhost"]);
+
+        settings.proxy = Some(net::SocketAddr::from(([127, 0, 0, 1], 3)));
+        settings.update_proxy_config();
+        assert_eq!(
+            settings.proxy_config(),
+            ProxyConfig::for_all_schemes(net::SocketAddr::from(([127, 0, 0, 1], 3)))
+        );
     }
 }
@@ -0,0 +1,413 @@
+//! Decisions of the user about which origins may use powerful features, like geolocation
+//!
+//! <https://w3c.github.io/permissions/>
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{Mutex, RwLock, RwLockReadGuard},
+};
+
+use serialize::{Deserialize, Serialize};
+use serialize_json::{JsonDeserializer, JsonSerializer};
+use url::Origin;
+
+use crate::{Profile, ProfileError};
+
+/// A feature that requires the permission of the user
+///
+/// <https://w3c.github.io/permissions/#dfn-powerful-feature>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PermissionName {
+    Geolocation,
+    Notifications,
+    ClipboardRead,
+    ClipboardWrite,
+}
+
+impl PermissionName {
+    pub const ALL: [Self; 4] = [
+        Self::Geolocation,
+        Self::Notifications,
+        Self::ClipboardRead,
+        Self::ClipboardWrite,
+    ];
+
+    /// <https://w3c.github.io/permissions/#dfn-name>
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Geolocation => "geolocation",
+            Self::Notifications => "notifications",
+            Self::ClipboardRead => "clipboard-read",
+            Self::ClipboardWrite => "clipboard-write",
+        }
+    }
+
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.name() == name)
+    }
+
+    /// Describes the feature to the user, to complete the sentence "... wants to ..."
+    #[must_use]
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Self::Geolocation => "know your location",
+            Self::Notifications => "show notifications",
+            Self::ClipboardRead => "see text and images copied to the clipboard",
+            Self::ClipboardWrite => "modify the clipboard",
+        }
+    }
+}
+
+/// <https://w3c.github.io/permissions/#dom-permissionstate>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PermissionState {
+    Granted,
+    Denied,
+
+    /// The user has not decided yet, using the feature asks them
+    #[default]
+    Prompt,
+}
+
+impl PermissionState {
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Granted => "granted",
+            Self::Denied => "denied",
+            Self::Prompt => "prompt",
+        }
+    }
+}
+
+/// A decision of the user about a feature for a single origin
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PermissionEntry {
+    /// The serialized origin that the decision applies to
+    pub origin: String,
+
+    pub name: PermissionName,
+    pub state: PermissionState,
+}
+
+/// A page asking the user whether it may use a feature
+#[derive(Clone, Debug, PartialEq)]
+pub struct PermissionRequest {
+    pub origin: Origin,
+    pub name: PermissionName,
+}
+
+type PromptHandler = Box<dyn Fn(&PermissionRequest) + Send + Sync>;
+
+/// The permissions that the user granted or denied, persisted in the users [Profile]
+pub struct Permissions {
+    /// Where the permissions are stored, `None` if they are not persisted
+    file: Option<PathBuf>,
+
+    entries: RwLock<Vec<PermissionEntry>>,
+
+    /// Asks the user for permission, registered by the browser UI
+    prompt_handler: Mutex<Option<PromptHandler>>,
+}
+
+impl Permissions {
+    /// Create a set of permissions that only lives in memory
+    #[must_use]
+    pub fn in_memory() -> Self {
+        Self {
+            file: None,
+            entries: RwLock::default(),
+            prompt_handler: Mutex::default(),
+        }
+    }
+
+    /// Read the permissions from the profile
+    ///
+    /// A profile without permissions yields an empty set of permissions.
+    pub fn load(profile: &Profile) -> Result<Self, ProfileError> {
+        let file = profile.permissions_file();
+
+        let entries = match fs::read_to_string(&file) {
+            Ok(contents) => {
+                let mut deserializer = JsonDeserializer::new(&contents);
+                Vec::<PermissionEntry>::deserialize(&mut deserializer)
+                    .map_err(|_| ProfileError::MalformedPermissions)?
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error.into()),
+        };
+
+        Ok(Self {
+            file: Some(file),
+            entries: RwLock::new(entries),
+            prompt_handler: Mutex::default(),
+        })
+    }
+
+    /// All decisions of the user, ordered from oldest to newest
+    pub fn entries(&self) -> RwLockReadGuard<'_, Vec<PermissionEntry>> {
+        self.entries.read().expect("permission lock is poisoned")
+    }
+
+    /// <https://w3c.github.io/permissions/#dfn-permission-state>
+    #[must_use]
+    pub fn state(&self, origin: &Origin, name: PermissionName) -> PermissionState {
+        // Opaque origins can't be told apart after a reload, so they can never be granted a permission
+        if origin.is_opaque() {
+            return PermissionState::Denied;
+        }
+
+        let origin = origin.to_string();
+        self.entries()
+            .iter()
+            .find(|entry| entry.origin == origin && entry.name == name)
+            .map(|entry| entry.state)
+            .unwrap_or_default()
+    }
+
+    /// Remember the decision of the user
+    ///
+    /// Setting the state to [PermissionState::Prompt] forgets the previous decision.
+    pub fn set(
+        &self,
+        origin: &Origin,
+        name: PermissionName,
+        state: PermissionState,
+    ) -> Result<(), ProfileError> {
+        if origin.is_opaque() {
+            return Ok(());
+        }
+
+        let origin = origin.to_string();
+        {
+            let mut entries = self.entries.write().expect("permission lock is poisoned");
+            entries.retain(|entry| entry.origin != origin || entry.name != name);
+
+            if state != PermissionState::Prompt {
+                entries.push(PermissionEntry {
+                    origin,
+                    name,
+                    state,
+                });
+            }
+        }
+
+        self.save()
+    }
+
+    /// Forget all decisions about the given origin
+    pub fn reset(&self, origin: &Origin) -> Result<(), ProfileError> {
+        let origin = origin.to_string();
+        self.entries
+            .write()
+            .expect("permission lock is poisoned")
+            .retain(|entry| entry.origin != origin);
+
+        self.save()
+    }
+
+    /// Register the callback that asks the user whether to grant a permission
+    ///
+    /// The handler must not block. Once the user decided, the browser UI stores the
+    /// decision with [Permissions::set].
+    pub fn on_prompt<F>(&self, handler: F)
+    where
+        F: Fn(&PermissionRequest) + Send + Sync + 'static,
+    {
+        *self
+            .prompt_handler
+            .lock()
+            .expect("prompt handler lock is poisoned") = Some(Box::new(handler));
+    }
+
+    /// Ask the user for permission to use a feature, unless they already decided
+    ///
+    /// Returns the current state, which stays [PermissionState::Prompt] until the user answered.
+    ///
+    /// <https://w3c.github.io/permissions/#dfn-request-permission-to-use>
+    pub fn request(&self, origin: &Origin, name: PermissionName) -> PermissionState {
+        // 1. Let current state be the descriptor's permission state.
+        // 2. If current state is not "prompt", return current state and abort these steps.
+        let current_state = self.state(origin, name);
+        if current_state != PermissionState::Prompt {
+            return current_state;
+        }
+
+        // 3. Ask the user for express permission for the calling algorithm to use the powerful feature
+        //    described by descriptor.
+        let request = PermissionRequest {
+            origin: origin.clone(),
+            name,
+        };
+        match &*self
+            .prompt_handler
+            .lock()
+            .expect("prompt handler lock is poisoned")
+        {
+            Some(handler) => handler(&request),
+            None => log::warn!(
+                "Cannot ask whether {} may {}, there is no prompt handler",
+                request.origin,
+                name.description()
+            ),
+        }
+
+        current_state
+    }
+
+    fn save(&self) -> Result<(), ProfileError> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+
+        let serialized = JsonSerializer::serialize_to_string(self.entries().clone())
+            .expect("Writing to a string cannot fail");
+
+        if let Some(directory) = file.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(file, serialized)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env,
+        sync::{Arc, Mutex},
+    };
+
+    use url::URL;
+
+    use super::*;
+
+    fn origin(url: &str) -> Origin {
+        url.parse::<URL>().unwrap().origin()
+    }
+
+    #[test]
+    fn grant_and_deny() {
+        let permissions = Permissions::in_memory();
+        let example = origin("https://example.com/page");
+
+        assert_eq!(
+            permissions.state(&example, PermissionName::Geolocation),
+            PermissionState::Prompt
+        );
+
+        permissions
+            .set(
+                &example,
+                PermissionName::Geolocation,
+                PermissionState::Granted,
+            )
+            .unwrap();
+        permissions
+            .set(
+                &example,
+                PermissionName::Notifications,
+                PermissionState::Denied,
+            )
+            .unwrap();
+
+        assert_eq!(
+            permissions.state(
+                &origin("https://example.com:443"),
+                PermissionName::Geolocation
+            ),
+            PermissionState::Granted
+        );
+        assert_eq!(
+            permissions.state(&example, PermissionName::Notifications),
+            PermissionState::Denied
+        );
+
+        // Permissions are not shared between origins
+        assert_eq!(
+            permissions.state(&origin("http://example.com"), PermissionName::Geolocation),
+            PermissionState::Prompt
+        );
+
+        // Opaque origins are always denied
+        let file = origin("file:///index.html");
+        permissions
+            .set(&file, PermissionName::Geolocation, PermissionState::Granted)
+            .unwrap();
+        assert_eq!(
+            permissions.state(&file, PermissionName::Geolocation),
+            PermissionState::Denied
+        );
+
+        permissions.reset(&example).unwrap();
+        assert!(permissions.entries().is_empty());
+    }
+
+    #[test]
+    fn prompt_only_if_undecided() {
+        let permissions = Permissions::in_memory();
+        let requests = Arc::new(Mutex::new(vec![]));
+
+        let requests_clone = requests.clone();
+        permissions.on_prompt(move |request| requests_clone.lock().unwrap().push(request.clone()));
+
+        let example = origin("https://example.com");
+        assert_eq!(
+            permissions.request(&example, PermissionName::ClipboardRead),
+            PermissionState::Prompt
+        );
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [PermissionRequest {
+                origin: example.clone(),
+                name: PermissionName::ClipboardRead,
+            }]
+        );
+
+        permissions
+            .set(
+                &example,
+                PermissionName::ClipboardRead,
+                PermissionState::Denied,
+            )
+            .unwrap();
+        assert_eq!(
+            permissions.request(&example, PermissionName::ClipboardRead),
+            PermissionState::Denied
+        );
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn persist_permissions() {
+        let directory =
+            env::temp_dir().join(format!("stormlicht-permissions-{}", std::process::id()));
+        let profile = Profile::new(directory.clone());
+
+        let permissions = Permissions::load(&profile).unwrap();
+        permissions
+            .set(
+                &origin("https://example.com"),
+                PermissionName::Notifications,
+                PermissionState::Granted,
+            )
+            .unwrap();
+
+        let loaded = Permissions::load(&profile).unwrap();
+        assert_eq!(*loaded.entries(), *permissions.entries());
+
+        fs::write(profile.permissions_file(), "[").unwrap();
+        assert!(matches!(
+            Permissions::load(&profile),
+            Err(ProfileError::MalformedPermissions)
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,333 @@
+use error_derive::Error;
+use http::proxy;
+use serialize::{Deserialize, Serialize};
+use sl_std::percent_encode::{percent_encode, EncodeSet};
+use url::URL;
+
+use crate::{keybindings, Keybindings, WELCOME_PAGE};
+
+#[derive(Debug, Error)]
+pub enum PreferenceError {
+    #[msg = "unknown preference"]
+    UnknownPreference,
+
+    #[msg = "invalid value for preference"]
+    InvalidValue,
+}
+
+/// User preferences that are persisted in the [Profile](crate::Profile)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Preferences {
+    /// URL that is loaded when no other URL is specified
+    pub homepage: String,
+
+    /// Search URL template, `%s` is replaced with the search terms
+    pub search_engine: String,
+
+    /// Proxy for `http` requests, like `127.0.0.1:8080`
+    pub http_proxy: Option<String>,
+
+    /// Proxy for `https` requests, like `127.0.0.1:8080`
+    pub https_proxy: Option<String>,
+
+    /// Hosts that should be contacted without a proxy
+    pub no_proxy: Vec<String>,
+
+    /// Default font size in pixels
+    pub font_size: usize,
+
+    /// Font size for monospace text in pixels
+    pub monospace_font_size: usize,
+
+    pub javascript_enabled: bool,
+
+    pub theme: Theme,
+
+    /// How the edges of text are smoothed
+    pub text_antialiasing: TextAntialiasing,
+
+    /// Whether web pages should minimize non-essential motion,
+    /// exposed to them through `prefers-reduced-motion`
+    pub reduced_motion: bool,
+
+    /// Display the default styles of web pages (including form controls)
+    /// with dark colors, regardless of the theme
+    pub force_dark_mode: bool,
+
+    /// The preferred language of the user, as a language tag like `en-US`
+    pub language: String,
+
+    /// Keyboard shortcuts that differ from the defaults, like `reload=<Primary>r F5`
+    pub keybindings: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    /// Follow the system preference
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    /// All themes, in the order they should be offered to the user
+    pub const ALL: [Self; 3] = [Self::System, Self::Light, Self::Dark];
+
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::Light => "light",
+            Self::Dark => "dark",
+        }
+    }
+}
+
+/// How the edges of text are smoothed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextAntialiasing {
+    /// Every pixel is covered by a single shade of the text color
+    #[default]
+    Grayscale,
+
+    /// The red, green and blue subpixels of LCD screens are covered individually,
+    /// which makes text sharper at the cost of slight color fringes
+    Lcd,
+}
+
+impl TextAntialiasing {
+    /// All antialiasing modes, in the order they should be offered to the user
+    pub const ALL: [Self; 2] = [Self::Grayscale, Self::Lcd];
+
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Grayscale => "grayscale",
+            Self::Lcd => "lcd",
+        }
+    }
+}
+
+impl Preferences {
+    /// The names of all preferences, as used by [Preferences::get] and [Preferences::set]
+    pub const NAMES: [&'static str; 14] = [
+        "homepage",
+        "search_engine",
+        "http_proxy",
+        "https_proxy",
+        "no_proxy",
+        "font_size",
+        "monospace_font_size",
+        "javascript_enabled",
+        "theme",
+        "text_antialiasing",
+        "reduced_motion",
+        "force_dark_mode",
+        "language",
+        "keybindings",
+    ];
+
+    /// Return the homepage, if it is a valid URL
+    #[must_use]
+    pub fn homepage(&self) -> Option<URL> {
+        self.homepage.parse().ok()
+    }
+
+    /// The URL that searches for the given terms with the [search engine](Self::search_engine)
+    #[must_use]
+    pub fn search_url(&self, terms: &str) -> Option<URL> {
+        let mut encoded_terms = String::new();
+        percent_encode(
+            terms.as_bytes(),
+            EncodeSet::FORM_URLENCODED,
+            &mut encoded_terms,
+        );
+
+        self.search_engine
+            .replace("%s", &encoded_terms)
+            .parse()
+            .ok()
+    }
+
+    /// The keyboard shortcuts of the browser interface, with the overrides of the user applied
+    #[must_use]
+    pub fn keybindings(&self) -> Keybindings {
+        Keybindings::with_overrides(&self.keybindings)
+    }
+
+    /// Return the value of a preference in textual form
+    ///
+    /// Unset values are represented by an empty string, lists are separated by commas.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "homepage" => self.homepage.clone(),
+            "search_engine" => self.search_engine.clone(),
+            "http_proxy" => self.http_proxy.clone().unwrap_or_default(),
+            "https_proxy" => self.https_proxy.clone().unwrap_or_default(),
+            "no_proxy" => self.no_proxy.join(","),
+            "font_size" => self.font_size.to_string(),
+            "monospace_font_size" => self.monospace_font_size.to_string(),
+            "javascript_enabled" => self.javascript_enabled.to_string(),
+            "theme" => self.theme.name().to_string(),
+            "text_antialiasing" => self.text_antialiasing.name().to_string(),
+            "reduced_motion" => self.reduced_motion.to_string(),
+            "force_dark_mode" => self.force_dark_mode.to_string(),
+            "language" => self.language.clone(),
+            "keybindings" => self.keybindings.join(","),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    /// Change a preference from its textual form, as returned by [Preferences::get]
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), PreferenceError> {
+        let value = value.trim();
+        let optional = |value: &str| (!value.is_empty()).then(|| value.to_string());
+        let boolean = |value: &str| {
+            value
+                .parse::<bool>()
+                .map_err(|_| PreferenceError::InvalidValue)
+        };
+        let number = |value: &str| {
+            value
+                .parse::<usize>()
+                .ok()
+                .filter(|&size| size != 0)
+                .ok_or(PreferenceError::InvalidValue)
+        };
+
+        match name {
+            "homepage" => self.homepage = value.to_string(),
+            "search_engine" => self.search_engine = value.to_string(),
+            "http_proxy" => self.http_proxy = optional(value),
+            "https_proxy" => self.https_proxy = optional(value),
+            "no_proxy" => self.no_proxy = proxy::parse_no_proxy(value),
+            "font_size" => self.font_size = number(value)?,
+            "monospace_font_size" => self.monospace_font_size = number(value)?,
+            "javascript_enabled" => self.javascript_enabled = boolean(value)?,
+            "theme" => {
+                self.theme = Theme::ALL
+                    .into_iter()
+                    .find(|theme| theme.name().eq_ignore_ascii_case(value))
+                    .ok_or(PreferenceError::InvalidValue)?;
+            },
+            "text_antialiasing" => {
+                self.text_antialiasing = TextAntialiasing::ALL
+                    .into_iter()
+                    .find(|mode| mode.name().eq_ignore_ascii_case(value))
+                    .ok_or(PreferenceError::InvalidValue)?;
+            },
+            "reduced_motion" => self.reduced_motion = boolean(value)?,
+            "force_dark_mode" => self.force_dark_mode = boolean(value)?,
+            "language" => self.language = optional(value).ok_or(PreferenceError::InvalidValue)?,
+            "keybindings" => {
+                let entries: Vec<String> = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|entry| !entry.is_empty())
+                    .map(str::to_string)
+                    .collect();
+
+                for entry in &entries {
+                    keybindings::parse_override(entry)?;
+                }
+                self.keybindings = entries;
+            },
+            _ => return Err(PreferenceError::UnknownPreference),
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            homepage: WELCOME_PAGE.to_string(),
+            search_engine: "https://duckduckgo.com/?q=%s".to_string(),
+            http_proxy: None,
+            https_proxy: None,
+            no_proxy: vec![],
+            font_size: 16,
+            monospace_font_size: 13,
+            javascript_enabled: true,
+            theme: Theme::default(),
+            text_antialiasing: TextAntialiasing::default(),
+            reduced_motion: false,
+            force_dark_mode: false,
+            language: "en-US".to_string(),
+            keybindings: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_set_by_name() {
+        let mut preferences = Preferences::default();
+
+        for name in Preferences::NAMES {
+            let value = preferences.get(name).unwrap();
+            preferences.set(name, &value).unwrap();
+        }
+        assert_eq!(preferences, Preferences::default());
+
+        preferences.set("https_proxy", "127.0.0.1:8080").unwrap();
+        preferences.set("no_proxy", "localhost, .internal").unwrap();
+        preferences.set("theme", "Dark").unwrap();
+        preferences.set("font_size", "20").unwrap();
+        preferences.set("force_dark_mode", "true").unwrap();
+        preferences.set("text_antialiasing", "LCD").unwrap();
+        preferences.set("keybindings", "reload=F5, quit=").unwrap();
+
+        assert_eq!(preferences.https_proxy.as_deref(), Some("127.0.0.1:8080"));
+        assert_eq!(preferences.get("no_proxy").unwrap(), "localhost,.internal");
+        assert_eq!(preferences.theme, Theme::Dark);
+        assert_eq!(preferences.font_size, 20);
+        assert!(preferences.force_dark_mode);
+        assert_eq!(preferences.text_antialiasing, TextAntialiasing::Lcd);
+        assert_eq!(preferences.get("keybindings").unwrap(), "reload=F5,quit=");
+
+        preferences.set("https_proxy", "").unwrap();
+        assert_eq!(preferences.https_proxy, None);
+
+        assert!(matches!(
+            preferences.set("font_size", "0"),
+            Err(PreferenceError::InvalidValue)
+        ));
+        assert!(matches!(
+            preferences.set("javascript_enabled", "maybe"),
+            Err(PreferenceError::InvalidValue)
+        ));
+        assert!(matches!(
+            preferences.set("keybindings", "reload"),
+            Err(PreferenceError::InvalidValue)
+        ));
+        assert!(matches!(
+            preferences.set("does_not_exist", ""),
+            Err(PreferenceError::UnknownPreference)
+        ));
+    }
+
+    #[test]
+    fn search_url() {
+        let preferences = Preferences {
+            search_engine: "https://search.example/?q=%s&lang=en".to_string(),
+            ..Preferences::default()
+        };
+
+        assert_eq!(
+            preferences
+                .search_url("rust & web browsers")
+                .unwrap()
+                .to_string(),
+            "https://search.example/?q=rust%20%26%20web%20browsers&lang=en"
+        );
+    }
+}
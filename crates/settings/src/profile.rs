@@ -0,0 +1,172 @@
+use std::{env, fs, io, path::PathBuf};
+
+use error_derive::Error;
+use serialize::Deserialize;
+use serialize_json::{JsonDeserializer, JsonSerializer};
+
+use crate::Preferences;
+
+/// Name of the file inside the profile directory that stores the [Preferences]
+const PREFERENCES_FILE: &str = "settings.json";
+
+/// Name of the file inside the profile directory that stores the [History](crate::History)
+const HISTORY_FILE: &str = "history.json";
+
+/// Name of the file inside the profile directory that stores the [Permissions](crate::Permissions)
+const PERMISSIONS_FILE: &str = "permissions.json";
+
+/// Name of the file inside the profile directory that stores the pages of the [Session](crate::Session)
+const SESSION_FILE: &str = "session.json";
+
+/// Name of the file inside the profile directory that exists while a [Session](crate::Session) is running
+const SESSION_MARKER_FILE: &str = "session.running";
+
+/// Name of the directory inside the profile directory that contains user stylesheets and scripts
+const USER_CONTENT_DIRECTORY: &str = "user-content";
+
+/// Name of the file inside the profile directory that stores the hosts that require strict transport security
+const HSTS_FILE: &str = "hsts.json";
+
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[msg = "io error"]
+    IO(io::Error),
+
+    #[msg = "malformed settings file"]
+    MalformedSettings,
+
+    #[msg = "malformed history file"]
+    MalformedHistory,
+
+    #[msg = "malformed permissions file"]
+    MalformedPermissions,
+
+    #[msg = "malformed session file"]
+    MalformedSession,
+}
+
+/// A directory that holds all persistent user data
+#[derive(Clone, Debug)]
+pub struct Profile {
+    directory: PathBuf,
+}
+
+impl Profile {
+    #[must_use]
+    pub fn new(directory: PathBuf) -> Self {
+        Self { directory }
+    }
+
+    /// Locate the default profile according to the
+    /// [XDG Base Directory Specification](https://specifications.freedesktop.org/basedir-spec/latest/)
+    ///
+    /// Returns `None` if neither `$XDG_CONFIG_HOME` nor `$HOME` is set.
+    #[must_use]
+    pub fn locate_default() -> Option<Self> {
+        // Relative paths in $XDG_CONFIG_HOME are invalid and should be ignored
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        Some(Self::new(config_home.join("stormlicht")))
+    }
+
+    #[must_use]
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+
+    #[must_use]
+    pub fn preferences_file(&self) -> PathBuf {
+        self.directory.join(PREFERENCES_FILE)
+    }
+
+    #[must_use]
+    pub fn history_file(&self) -> PathBuf {
+        self.directory.join(HISTORY_FILE)
+    }
+
+    #[must_use]
+    pub fn permissions_file(&self) -> PathBuf {
+        self.directory.join(PERMISSIONS_FILE)
+    }
+
+    #[must_use]
+    pub fn session_file(&self) -> PathBuf {
+        self.directory.join(SESSION_FILE)
+    }
+
+    #[must_use]
+    pub fn session_marker_file(&self) -> PathBuf {
+        self.directory.join(SESSION_MARKER_FILE)
+    }
+
+    /// The directory that contains the stylesheets and scripts that the user added to websites
+    #[must_use]
+    pub fn user_content_directory(&self) -> PathBuf {
+        self.directory.join(USER_CONTENT_DIRECTORY)
+    }
+
+    #[must_use]
+    pub fn hsts_file(&self) -> PathBuf {
+        self.directory.join(HSTS_FILE)
+    }
+
+    /// Read the preferences from disk
+    ///
+    /// Returns `Ok(None)` if the profile does not contain any preferences yet.
+    pub fn load_preferences(&self) -> Result<Option<Preferences>, ProfileError> {
+        let contents = match fs::read_to_string(self.preferences_file()) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut deserializer = JsonDeserializer::new(&contents);
+        let preferences = Preferences::deserialize(&mut deserializer)
+            .map_err(|_| ProfileError::MalformedSettings)?;
+
+        Ok(Some(preferences))
+    }
+
+    /// Write the preferences to disk, creating the profile directory if necessary
+    pub fn save_preferences(&self, preferences: &Preferences) -> Result<(), ProfileError> {
+        let serialized = JsonSerializer::serialize_to_string_pretty(preferences.clone())
+            .expect("Writing to a string cannot fail");
+
+        fs::create_dir_all(&self.directory)?;
+        fs::write(self.preferences_file(), serialized)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_and_load_preferences() {
+        let directory = env::temp_dir().join(format!("stormlicht-profile-{}", std::process::id()));
+        let profile = Profile::new(directory.clone());
+
+        assert!(profile.load_preferences().unwrap().is_none());
+
+        let preferences = Preferences {
+            javascript_enabled: false,
+            https_proxy: Some("127.0.0.1:8080".to_string()),
+            ..Preferences::default()
+        };
+        profile.save_preferences(&preferences).unwrap();
+        assert_eq!(profile.load_preferences().unwrap(), Some(preferences));
+
+        fs::write(profile.preferences_file(), "{").unwrap();
+        assert!(matches!(
+            profile.load_preferences(),
+            Err(ProfileError::MalformedSettings)
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,194 @@
+//! Remembers which pages are open, so they can be restored after a restart or a crash
+//!
+//! While the browser is running, the profile contains a marker file that is removed
+//! when the session ends regularly. If the marker still exists when the next session
+//! starts, the browser must have crashed.
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{Mutex, RwLock},
+};
+
+use serialize::{Deserialize, Serialize};
+use serialize_json::{JsonDeserializer, JsonSerializer};
+
+use crate::{Profile, ProfileError};
+
+/// A page that was open when the session was saved
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SessionPage {
+    pub url: String,
+
+    /// Horizontal scroll position, in CSS pixels
+    pub scroll_x: f32,
+
+    /// Vertical scroll position, in CSS pixels
+    pub scroll_y: f32,
+}
+
+/// The pages that are open in the browser, persisted in the users [Profile]
+#[derive(Debug)]
+pub struct Session {
+    /// Where the open pages are stored, `None` if they are not persisted
+    file: Option<PathBuf>,
+
+    /// Exists while the session is running
+    marker: Option<PathBuf>,
+
+    /// The pages that were open at the end of the previous session
+    previous_pages: Mutex<Vec<SessionPage>>,
+
+    /// Whether the previous session ended without removing its marker
+    previous_session_crashed: bool,
+
+    /// The most recently saved pages, used to skip writes if nothing changed
+    saved_pages: RwLock<Vec<SessionPage>>,
+}
+
+impl Session {
+    /// Create a session that is not persisted
+    #[must_use]
+    pub fn in_memory() -> Self {
+        Self {
+            file: None,
+            marker: None,
+            previous_pages: Mutex::default(),
+            previous_session_crashed: false,
+            saved_pages: RwLock::default(),
+        }
+    }
+
+    /// Read the previous session from the profile and mark the new session as running
+    pub fn start(profile: &Profile) -> Result<Self, ProfileError> {
+        let file = profile.session_file();
+        let marker = profile.session_marker_file();
+
+        let previous_pages = match fs::read_to_string(&file) {
+            Ok(contents) => {
+                let mut deserializer = JsonDeserializer::new(&contents);
+                Vec::<SessionPage>::deserialize(&mut deserializer)
+                    .map_err(|_| ProfileError::MalformedSession)?
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound => vec![],
+            Err(error) => return Err(error.into()),
+        };
+
+        let previous_session_crashed = marker.exists();
+        fs::create_dir_all(profile.directory())?;
+        fs::write(&marker, "")?;
+
+        Ok(Self {
+            file: Some(file),
+            marker: Some(marker),
+            previous_pages: Mutex::new(previous_pages),
+            previous_session_crashed,
+            saved_pages: RwLock::default(),
+        })
+    }
+
+    /// Whether the previous session ended unexpectedly, for example because the browser crashed
+    #[must_use]
+    pub fn previous_session_crashed(&self) -> bool {
+        self.previous_session_crashed
+    }
+
+    /// Take the pages that were open at the end of the previous session
+    ///
+    /// Subsequent calls return no pages.
+    #[must_use]
+    pub fn take_previous_pages(&self) -> Vec<SessionPage> {
+        std::mem::take(
+            &mut *self
+                .previous_pages
+                .lock()
+                .expect("session lock is poisoned"),
+        )
+    }
+
+    /// Remember the pages that are currently open
+    pub fn save(&self, pages: Vec<SessionPage>) -> Result<(), ProfileError> {
+        if *self.saved_pages.read().expect("session lock is poisoned") == pages {
+            return Ok(());
+        }
+
+        if let Some(file) = &self.file {
+            let serialized = JsonSerializer::serialize_to_string(pages.clone())
+                .expect("Writing to a string cannot fail");
+
+            if let Some(directory) = file.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            fs::write(file, serialized)?;
+        }
+
+        *self.saved_pages.write().expect("session lock is poisoned") = pages;
+        Ok(())
+    }
+
+    /// Mark the session as having ended regularly
+    ///
+    /// The saved pages are kept, so they can be restored in the next session.
+    pub fn end(&self) -> Result<(), ProfileError> {
+        let Some(marker) = &self.marker else {
+            return Ok(());
+        };
+
+        match fs::remove_file(marker) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn page(url: &str, scroll_y: f32) -> SessionPage {
+        SessionPage {
+            url: url.to_string(),
+            scroll_x: 0.,
+            scroll_y,
+        }
+    }
+
+    #[test]
+    fn restore_after_regular_end_and_crash() {
+        let directory = env::temp_dir().join(format!("stormlicht-session-{}", std::process::id()));
+        let profile = Profile::new(directory.clone());
+
+        let session = Session::start(&profile).unwrap();
+        assert!(!session.previous_session_crashed());
+        assert!(session.take_previous_pages().is_empty());
+
+        let pages = vec![
+            page("https://example.com/", 120.),
+            page("about:history", 0.),
+        ];
+        session.save(pages.clone()).unwrap();
+        session.end().unwrap();
+
+        // The previous session ended regularly
+        let session = Session::start(&profile).unwrap();
+        assert!(!session.previous_session_crashed());
+        assert_eq!(session.take_previous_pages(), pages);
+        assert!(session.take_previous_pages().is_empty());
+
+        // The session above never ended, as if the browser crashed
+        let session = Session::start(&profile).unwrap();
+        assert!(session.previous_session_crashed());
+        assert_eq!(session.take_previous_pages(), pages);
+
+        fs::write(profile.session_file(), "[").unwrap();
+        assert!(matches!(
+            Session::start(&profile),
+            Err(ProfileError::MalformedSession)
+        ));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
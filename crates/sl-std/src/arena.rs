@@ -0,0 +1,174 @@
+//! A typed bump allocator
+//!
+//! An [Arena] hands out references to values that live as long as the arena itself.
+//! Values are placed next to each other in large chunks, which makes allocation cheap
+//! and keeps related values close together in memory. Nothing is freed until the whole
+//! arena is dropped.
+
+use std::{cell::RefCell, cmp, mem, slice};
+
+/// The number of values that fit into the first chunk of an arena
+const INITIAL_CHUNK_CAPACITY: usize = 64;
+
+/// Allocates values of type `T` that live as long as the arena
+///
+/// # Examples
+///
+/// ```rust
+/// # use sl_std::arena::Arena;
+/// let arena = Arena::default();
+///
+/// let a = arena.alloc(1);
+/// let b = arena.alloc_extend([2, 3, 4]);
+/// *a += 1;
+///
+/// assert_eq!(*a, 2);
+/// assert_eq!(b, &[2, 3, 4]);
+/// assert_eq!(arena.len(), 4);
+/// ```
+#[derive(Debug)]
+pub struct Arena<T> {
+    chunks: RefCell<Chunks<T>>,
+}
+
+#[derive(Debug)]
+struct Chunks<T> {
+    /// The chunk that new values are pushed into
+    ///
+    /// This vector is never pushed to beyond its capacity, so its elements never move.
+    current: Vec<T>,
+
+    /// Chunks that are full, they are only kept around to be dropped together with the arena
+    full: Vec<Vec<T>>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self {
+            chunks: RefCell::new(Chunks {
+                current: Vec::with_capacity(INITIAL_CHUNK_CAPACITY),
+                full: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl<T> Arena<T> {
+    /// Move a value into the arena
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.reserve(1);
+        chunks.current.push(value);
+        let allocated = chunks
+            .current
+            .last_mut()
+            .expect("value was just pushed to the chunk");
+
+        // SAFETY: See alloc_extend
+        unsafe { &mut *(allocated as *mut T) }
+    }
+
+    /// Move all values from an iterator into the arena, next to each other
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend<I: IntoIterator<Item = T>>(&self, values: I) -> &mut [T] {
+        // The iterator might allocate in this arena too, so it must be consumed before
+        // the chunks are borrowed
+        let values: Vec<T> = values.into_iter().collect();
+        if values.is_empty() {
+            return &mut [];
+        }
+
+        let mut chunks = self.chunks.borrow_mut();
+        chunks.reserve(values.len());
+
+        let start = chunks.current.len();
+        chunks.current.extend(values);
+        let allocated = &mut chunks.current[start..];
+
+        // SAFETY: The values are never moved, because the chunk is never grown beyond its capacity.
+        //         They are also never dropped or handed out a second time until the arena itself is dropped,
+        //         which can only happen once all references into it are gone.
+        unsafe { slice::from_raw_parts_mut(allocated.as_mut_ptr(), allocated.len()) }
+    }
+
+    /// The number of values allocated in the arena
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        chunks.current.len() + chunks.full.iter().map(Vec::len).sum::<usize>()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of bytes reserved by the arena, including space that is not used yet
+    #[must_use]
+    pub fn size_in_bytes(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        let capacity =
+            chunks.current.capacity() + chunks.full.iter().map(Vec::capacity).sum::<usize>();
+
+        capacity * mem::size_of::<T>()
+    }
+}
+
+impl<T> Chunks<T> {
+    /// Make sure that `additional` values fit into the current chunk
+    fn reserve(&mut self, additional: usize) {
+        if self.current.capacity() - self.current.len() >= additional {
+            return;
+        }
+
+        // Chunks grow exponentially, so the number of chunks stays small
+        let capacity = cmp::max(self.current.capacity() * 2, additional);
+        let full_chunk = mem::replace(&mut self.current, Vec::with_capacity(capacity));
+        self.full.push(full_chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_do_not_move() {
+        let arena = Arena::default();
+        let first = arena.alloc(String::from("first"));
+
+        // Fill up a couple of chunks
+        let values: Vec<&mut String> = (0..1000).map(|i| arena.alloc(i.to_string())).collect();
+        let slice = arena.alloc_extend((0..INITIAL_CHUNK_CAPACITY * 3).map(|i| i.to_string()));
+
+        assert_eq!(first, "first");
+        assert!(values
+            .iter()
+            .enumerate()
+            .all(|(i, value)| **value == i.to_string()));
+        assert_eq!(slice.len(), INITIAL_CHUNK_CAPACITY * 3);
+        assert_eq!(arena.len(), 1001 + INITIAL_CHUNK_CAPACITY * 3);
+    }
+
+    #[test]
+    fn values_may_reference_the_arena() {
+        struct Node<'a> {
+            value: usize,
+            children: &'a [Node<'a>],
+        }
+
+        let arena = Arena::default();
+        let leaves = arena.alloc_extend((0..3).map(|value| Node {
+            value,
+            children: &[],
+        }));
+        let root = arena.alloc(Node {
+            value: 3,
+            children: leaves,
+        });
+
+        let sum: usize = root.children.iter().map(|child| child.value).sum();
+        assert_eq!(sum + root.value, 6);
+    }
+}
@@ -242,6 +242,67 @@ impl Str {
         pattern.is_prefix_of(self)
     }
 
+    pub fn ends_with<'a, P>(&'a self, pattern: P) -> bool
+    where
+        P: super::Pattern<'a>,
+        P::Searcher: ReverseSearcher<'a>,
+    {
+        pattern.is_suffix_of(self)
+    }
+
+    /// Returns `true` if the pattern matches anywhere in the string
+    ///
+    /// # Examples
+    /// ```
+    /// #![feature(ascii_char_variants, ascii_char)]
+    /// # use sl_std::ascii;
+    ///
+    /// let haystack: &ascii::Str = "abcdef".try_into().unwrap();
+    ///
+    /// assert!(haystack.contains("cde"));
+    /// assert!(!haystack.contains(ascii::Char::SmallX));
+    /// ```
+    pub fn contains<'a, P>(&'a self, pattern: P) -> bool
+    where
+        P: super::Pattern<'a>,
+    {
+        pattern.is_contained_in(self)
+    }
+
+    /// Returns the string with the prefix removed, if the pattern matches at the start
+    ///
+    /// # Examples
+    /// ```
+    /// # use sl_std::ascii;
+    /// let s: &ascii::Str = "Bearer token".try_into().unwrap();
+    ///
+    /// assert_eq!(s.strip_prefix("Bearer ").map(ascii::Str::as_str), Some("token"));
+    /// assert_eq!(s.strip_prefix("Basic "), None);
+    /// ```
+    #[must_use = "this returns the remaining substring as a new slice, without modifying the original"]
+    pub fn strip_prefix<'a, P>(&'a self, prefix: P) -> Option<&'a Self>
+    where
+        P: super::Pattern<'a>,
+    {
+        match prefix.into_searcher(self).next() {
+            super::SearchStep::Match(0, end) => Some(&self[end..]),
+            _ => None,
+        }
+    }
+
+    /// Returns the string with the suffix removed, if the pattern matches at the end
+    #[must_use = "this returns the remaining substring as a new slice, without modifying the original"]
+    pub fn strip_suffix<'a, P>(&'a self, suffix: P) -> Option<&'a Self>
+    where
+        P: super::Pattern<'a>,
+        P::Searcher: ReverseSearcher<'a>,
+    {
+        match suffix.into_searcher(self).next_back() {
+            super::SearchStep::Match(start, end) if end == self.len() => Some(&self[..start]),
+            _ => None,
+        }
+    }
+
     /// Returns a string slice with leading and trailing whitespace removed.
     ///
     /// 'Whitespace' is defined according to the terms of the [WhatWG spec](https://infra.spec.whatwg.org/#ascii-whitespace).
@@ -380,6 +441,30 @@ impl Str {
         }
     }
 
+    /// Split the string at the occurences of a pattern, returning at most `n` segments
+    ///
+    /// The last segment contains the remainder of the string.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sl_std::ascii;
+    /// let haystack: &ascii::Str = "key=value=more".try_into().unwrap();
+    /// let mut splits = haystack.splitn(2, "=");
+    /// assert_eq!(splits.next().map(ascii::Str::as_str), Some("key"));
+    /// assert_eq!(splits.next().map(ascii::Str::as_str), Some("value=more"));
+    /// assert!(splits.next().is_none())
+    /// ```
+    pub fn splitn<'a, P: super::Pattern<'a>>(
+        &'a self,
+        n: usize,
+        pattern: P,
+    ) -> SplitNIterator<'a, P> {
+        SplitNIterator {
+            iter: self.split(pattern),
+            count: n,
+        }
+    }
+
     /// Find a pattern in the string
     ///
     /// # Examples
@@ -424,6 +509,34 @@ impl Str {
         result
     }
 
+    /// Replace the first `count` occurences of a pattern with another string
+    ///
+    /// # Examples
+    /// Basic Usage:
+    /// ```
+    /// # use sl_std::ascii;
+    /// let haystack: &ascii::Str = "a-b-c-d".try_into().unwrap();
+    ///
+    /// assert_eq!("a+b+c-d", haystack.replacen("-", "+".try_into().unwrap(), 2).as_str());
+    /// ````
+    pub fn replacen<'a, P: super::Pattern<'a>>(
+        &'a self,
+        pattern: P,
+        replace_with: &Self,
+        count: usize,
+    ) -> String {
+        let mut result = String::with_capacity(self.len());
+
+        let mut last_match_end = 0;
+        for (start, end) in self.match_indices(pattern).take(count) {
+            result.push_str(&self[last_match_end..start]);
+            result.push_str(replace_with);
+            last_match_end = end;
+        }
+        result.push_str(&self[last_match_end..]);
+        result
+    }
+
     #[inline]
     pub fn match_indices<'a, P: super::Pattern<'a>>(
         &'a self,
@@ -511,9 +624,7 @@ impl ToOwned for Str {
     type Owned = String;
 
     fn to_owned(&self) -> Self::Owned {
-        String {
-            chars: self.chars.to_owned(),
-        }
+        String::from(self)
     }
 }
 
@@ -716,3 +827,55 @@ where
 }
 
 impl<'a, P> FusedIterator for SplitIterator<'a, P> where P: super::Pattern<'a> {}
+
+impl<'a, P> SplitIterator<'a, P>
+where
+    P: super::Pattern<'a>,
+{
+    /// Return the part of the string that has not been split yet
+    fn remainder(&mut self) -> Option<&'a Str> {
+        if self.is_done {
+            return None;
+        }
+
+        self.is_done = true;
+        Some(&self.searcher.haystack()[self.start..self.end])
+    }
+}
+
+/// An iterator over a limited number of segments of a string slice.
+///
+///
+/// This struct is created by the [`splitn`](Str::splitn) method on [`ascii::Str`](Str).
+/// See its documentation for more.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct SplitNIterator<'a, P>
+where
+    P: super::Pattern<'a>,
+{
+    iter: SplitIterator<'a, P>,
+    count: usize,
+}
+
+impl<'a, P> Iterator for SplitNIterator<'a, P>
+where
+    P: super::Pattern<'a>,
+{
+    type Item = &'a Str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.count {
+            0 => None,
+            1 => {
+                self.count = 0;
+                self.iter.remainder()
+            },
+            _ => {
+                self.count -= 1;
+                self.iter.next()
+            },
+        }
+    }
+}
+
+impl<'a, P> FusedIterator for SplitNIterator<'a, P> where P: super::Pattern<'a> {}
@@ -1,7 +1,7 @@
 use std::{
     ascii::Char,
-    borrow::Borrow,
-    fmt, mem,
+    borrow::{Borrow, Cow},
+    fmt, hash, mem,
     ops::{Deref, DerefMut},
     string::String as Utf8String,
 };
@@ -24,6 +24,9 @@ pub struct NotAscii;
 /// Don't import this directly, instead import it's parent module and
 /// use it as `ascii::String`.
 ///
+/// Strings that are no longer than 23 characters are stored inline,
+/// without allocating any memory on the heap.
+///
 /// # Example
 /// ```
 /// # use sl_std::ascii;
@@ -42,13 +45,78 @@ pub struct NotAscii;
 /// foo.push_str("abcde".try_into().unwrap());
 /// assert_eq!(foo.len(), 5);
 /// ```
-#[cfg_attr(
-    feature = "serialize",
-    derive(serialize::Serialize, serialize::Deserialize)
-)]
-#[derive(Clone, Default, PartialEq, Eq, Hash)]
+#[derive(Clone, Default)]
 pub struct String {
-    pub(super) chars: Vec<Char>,
+    repr: Repr,
+}
+
+/// Strings up to this length don't require a heap allocation
+const INLINE_CAPACITY: usize = 23;
+
+#[derive(Clone)]
+enum Repr {
+    Inline {
+        len: u8,
+        chars: [Char; INLINE_CAPACITY],
+    },
+    Heap(Vec<Char>),
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Self::Inline {
+            len: 0,
+            chars: [Char::Null; INLINE_CAPACITY],
+        }
+    }
+}
+
+impl Repr {
+    #[inline]
+    fn as_slice(&self) -> &[Char] {
+        match self {
+            Self::Inline { len, chars } => &chars[..*len as usize],
+            Self::Heap(chars) => chars,
+        }
+    }
+
+    #[inline]
+    fn as_mut_slice(&mut self) -> &mut [Char] {
+        match self {
+            Self::Inline { len, chars } => &mut chars[..*len as usize],
+            Self::Heap(chars) => chars,
+        }
+    }
+
+    fn from_slice(slice: &[Char]) -> Self {
+        if slice.len() <= INLINE_CAPACITY {
+            let mut chars = [Char::Null; INLINE_CAPACITY];
+            chars[..slice.len()].copy_from_slice(slice);
+            Self::Inline {
+                len: slice.len() as u8,
+                chars,
+            }
+        } else {
+            Self::Heap(slice.to_vec())
+        }
+    }
+
+    /// Make sure there is space for at least `additional` more characters,
+    /// moving the data to the heap if necessary
+    fn reserve(&mut self, additional: usize) {
+        match self {
+            Self::Inline { len, chars } => {
+                let required_capacity = *len as usize + additional;
+                if INLINE_CAPACITY < required_capacity {
+                    let mut heap_chars =
+                        Vec::with_capacity(required_capacity.max(2 * INLINE_CAPACITY));
+                    heap_chars.extend_from_slice(&chars[..*len as usize]);
+                    *self = Self::Heap(heap_chars);
+                }
+            },
+            Self::Heap(chars) => chars.reserve(additional),
+        }
+    }
 }
 
 impl String {
@@ -67,7 +135,7 @@ impl String {
     /// let s = ascii::String::new();
     /// ```
     pub fn new() -> Self {
-        Self { chars: Vec::new() }
+        Self::default()
     }
 
     /// Creates a new empty `String` with at least the specified capacity.
@@ -106,9 +174,9 @@ impl String {
     #[inline]
     #[must_use]
     pub fn with_capacity(capacity: usize) -> Self {
-        Self {
-            chars: Vec::with_capacity(capacity),
-        }
+        let mut repr = Repr::default();
+        repr.reserve(capacity);
+        Self { repr }
     }
 
     /// Returns this `String`'s capacity, in bytes.
@@ -124,7 +192,23 @@ impl String {
     #[inline]
     #[must_use]
     pub fn capacity(&self) -> usize {
-        self.chars.capacity()
+        match &self.repr {
+            Repr::Inline { .. } => INLINE_CAPACITY,
+            Repr::Heap(chars) => chars.capacity(),
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more characters
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.repr.reserve(additional);
+    }
+
+    /// Whether or not the string is currently stored without a heap allocation
+    #[inline]
+    #[must_use]
+    pub fn is_inline(&self) -> bool {
+        matches!(self.repr, Repr::Inline { .. })
     }
 
     /// Appends the given [`Char`] to the end of this `String`.
@@ -143,15 +227,32 @@ impl String {
     /// assert_eq!("abc123", s.as_str());
     /// ```
     pub fn push(&mut self, c: Char) {
-        self.chars.push(c);
+        self.repr.reserve(1);
+
+        match &mut self.repr {
+            Repr::Inline { len, chars } => {
+                chars[*len as usize] = c;
+                *len += 1;
+            },
+            Repr::Heap(chars) => chars.push(c),
+        }
     }
 
     pub fn push_str(&mut self, s: &Str) {
-        self.chars.extend_from_slice(s.chars())
+        self.repr.reserve(s.len());
+
+        match &mut self.repr {
+            Repr::Inline { len, chars } => {
+                let start = *len as usize;
+                chars[start..start + s.len()].copy_from_slice(s.chars());
+                *len += s.len() as u8;
+            },
+            Repr::Heap(chars) => chars.extend_from_slice(s.chars()),
+        }
     }
 
     pub fn clear(&mut self) {
-        self.chars.clear()
+        self.truncate(0);
     }
 
     pub fn from_utf8_punycode(utf8_string: &str) -> Result<Self, punycode::PunyCodeError> {
@@ -196,7 +297,7 @@ impl String {
                     wrapped_bytes.capacity(),
                 )
             };
-            Some(Self { chars })
+            Some(Self::from_chars(chars))
         } else {
             None
         }
@@ -209,8 +310,13 @@ impl String {
     }
 
     pub fn into_bytes(self) -> Vec<u8> {
+        let chars = match self.repr {
+            Repr::Inline { len, chars } => chars[..len as usize].to_vec(),
+            Repr::Heap(chars) => chars,
+        };
+
         // Ensure the original vector is not dropped.
-        let mut wrapped_chars = mem::ManuallyDrop::new(self.chars);
+        let mut wrapped_chars = mem::ManuallyDrop::new(chars);
 
         // SAFETY: Vec<u8> has the same layout as Vec<ascii::Char>
         unsafe {
@@ -225,7 +331,9 @@ impl String {
     #[inline]
     #[must_use]
     pub const fn from_chars(chars: Vec<Char>) -> Self {
-        Self { chars }
+        Self {
+            repr: Repr::Heap(chars),
+        }
     }
 
     /// Shortens this `String` to the specified length.
@@ -247,7 +355,14 @@ impl String {
     /// assert_eq!(s, "he");
     /// ```
     pub fn truncate(&mut self, new_len: usize) {
-        self.chars.truncate(new_len);
+        match &mut self.repr {
+            Repr::Inline { len, .. } => {
+                if new_len < *len as usize {
+                    *len = new_len as u8;
+                }
+            },
+            Repr::Heap(chars) => chars.truncate(new_len),
+        }
     }
 }
 
@@ -255,13 +370,13 @@ impl Deref for String {
     type Target = Str;
 
     fn deref(&self) -> &Self::Target {
-        Str::from_ascii_chars(self.chars.as_slice())
+        Str::from_ascii_chars(self.repr.as_slice())
     }
 }
 
 impl DerefMut for String {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        Str::from_ascii_chars_mut(self.chars.as_mut_slice())
+        Str::from_ascii_chars_mut(self.repr.as_mut_slice())
     }
 }
 
@@ -293,11 +408,30 @@ impl Borrow<Str> for String {
     }
 }
 
+impl PartialEq for String {
+    fn eq(&self, other: &Self) -> bool {
+        self.deref().eq(other.deref())
+    }
+}
+
+impl Eq for String {}
+
+impl hash::Hash for String {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        // This has to be consistent with the hash of ascii::Str, since we implement Borrow<Str>
+        self.deref().hash(state)
+    }
+}
+
 impl FromIterator<Char> for String {
     fn from_iter<T: IntoIterator<Item = Char>>(iter: T) -> Self {
-        Self {
-            chars: iter.into_iter().collect(),
+        let mut result = Self::new();
+
+        for c in iter {
+            result.push(c);
         }
+
+        result
     }
 }
 
@@ -313,13 +447,27 @@ impl<'a> FromIterator<&'a Str> for String {
     }
 }
 
+impl From<&Str> for String {
+    fn from(value: &Str) -> Self {
+        Self {
+            repr: Repr::from_slice(value.chars()),
+        }
+    }
+}
+
 impl TryFrom<&str> for String {
     type Error = NotAscii;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Str::from_bytes(value.as_bytes())
-            .map(Str::to_owned)
-            .ok_or(NotAscii)
+        Self::try_from(value.as_bytes())
+    }
+}
+
+impl TryFrom<&[u8]> for String {
+    type Error = NotAscii;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Str::from_bytes(value).map(Self::from).ok_or(NotAscii)
     }
 }
 
@@ -352,3 +500,109 @@ impl From<String> for Utf8String {
         unsafe { Utf8String::from_utf8_unchecked(ascii_bytes) }
     }
 }
+
+impl<'a> From<&'a Str> for Cow<'a, Str> {
+    fn from(value: &'a Str) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl<'a> From<&'a String> for Cow<'a, Str> {
+    fn from(value: &'a String) -> Self {
+        Self::Borrowed(value.deref())
+    }
+}
+
+impl<'a> From<String> for Cow<'a, Str> {
+    fn from(value: String) -> Self {
+        Self::Owned(value)
+    }
+}
+
+impl<'a> From<Cow<'a, Str>> for String {
+    fn from(value: Cow<'a, Str>) -> Self {
+        value.into_owned()
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl serialize::Serialize for String {
+    fn serialize_to<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+    where
+        S: serialize::Serializer,
+    {
+        serializer.serialize_string(self.as_str())
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl serialize::Deserialize for String {
+    fn deserialize<D: serialize::Deserializer>(deserializer: D) -> Result<Self, D::Error> {
+        use serialize::deserialization::Error;
+
+        let utf8 = Utf8String::deserialize(deserializer)?;
+        Self::try_from(utf8).map_err(|_| D::Error::expected("an ascii string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn small_strings_are_inline() {
+        let mut s = String::try_from("short").unwrap();
+        assert!(s.is_inline());
+
+        s.push_str("and still inline!!".try_into().unwrap());
+        assert_eq!(s.len(), INLINE_CAPACITY);
+        assert!(s.is_inline());
+
+        s.push(Char::ExclamationMark);
+        assert!(!s.is_inline());
+        assert_eq!(s, "shortand still inline!!!");
+    }
+
+    #[test]
+    fn inline_and_heap_strings_are_equal() {
+        let inline = String::try_from("foo").unwrap();
+        let heap = String::from_chars("foo".as_ascii().unwrap().to_vec());
+
+        assert!(inline.is_inline());
+        assert!(!heap.is_inline());
+        assert_eq!(inline, heap);
+
+        // Required for using ascii::Str to look up ascii::String keys in hashmaps
+        let state = std::collections::hash_map::RandomState::new();
+        assert_eq!(state.hash_one(&inline), state.hash_one(&heap));
+        assert_eq!(
+            state.hash_one(&inline),
+            state.hash_one(inline.as_ascii_str())
+        );
+    }
+
+    #[test]
+    fn truncate_and_clear() {
+        let mut s = String::try_from("hello world").unwrap();
+        s.truncate(5);
+        assert_eq!(s, "hello");
+
+        s.truncate(10);
+        assert_eq!(s, "hello");
+
+        s.clear();
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn cow_conversions() {
+        let borrowed: &Str = "borrowed".try_into().unwrap();
+        assert!(matches!(Cow::from(borrowed), Cow::Borrowed(_)));
+
+        let owned = String::try_from("owned").unwrap();
+        let cow = Cow::from(owned.clone());
+        assert!(matches!(cow, Cow::Owned(_)));
+        assert_eq!(String::from(cow), owned);
+    }
+}
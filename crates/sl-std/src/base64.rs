@@ -1,8 +1,15 @@
+//! Base64 encoding and decoding, as defined in [RFC 4648](https://datatracker.ietf.org/doc/html/rfc4648)
+//!
+//! Both the standard and the URL-safe alphabet are supported, with configurable
+//! padding. [Encoder] and [Decoder] can be used to process data as a stream.
+
+use std::io;
+
 use error_derive::Error;
 
 use crate::ascii;
 
-const BASE64_CHARS: [ascii::Char; 64] = [
+const STANDARD_CHARS: [ascii::Char; 64] = [
     ascii::Char::CapitalA,
     ascii::Char::CapitalB,
     ascii::Char::CapitalC,
@@ -81,66 +88,356 @@ pub enum Error {
     InvalidPadding,
 }
 
-pub fn b64decode(base64: &ascii::Str) -> Result<Vec<u8>, Error> {
-    if base64.len() % 4 != 0 {
-        return Err(Error::InvalidLength);
+/// The set of characters that encode the 64 possible values of a symbol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Uses `+` and `/` for the last two values
+    Standard,
+
+    /// Uses `-` and `_` for the last two values, which makes the output safe to
+    /// use in URLs and file names
+    UrlSafe,
+}
+
+/// How padding (`=`) at the end of the encoded data is treated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    /// Padding is emitted when encoding and must be present when decoding
+    Required,
+
+    /// Padding is emitted when encoding but may be omitted when decoding
+    Optional,
+
+    /// Padding is never emitted and rejected when decoding
+    Forbidden,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    alphabet: Alphabet,
+    padding: Padding,
+}
+
+/// The standard alphabet with padding
+pub const STANDARD: Config = Config::new(Alphabet::Standard, Padding::Required);
+
+/// The standard alphabet without padding
+pub const STANDARD_NO_PAD: Config = Config::new(Alphabet::Standard, Padding::Forbidden);
+
+/// The URL-safe alphabet with padding
+pub const URL_SAFE: Config = Config::new(Alphabet::UrlSafe, Padding::Required);
+
+/// The URL-safe alphabet without padding
+pub const URL_SAFE_NO_PAD: Config = Config::new(Alphabet::UrlSafe, Padding::Forbidden);
+
+impl Alphabet {
+    #[must_use]
+    const fn encode_symbol(self, value: u8) -> ascii::Char {
+        match (self, value) {
+            (Self::UrlSafe, 62) => ascii::Char::HyphenMinus,
+            (Self::UrlSafe, 63) => ascii::Char::LowLine,
+            _ => STANDARD_CHARS[value as usize],
+        }
     }
 
-    // Let's not worry about this edge case later on
-    if base64.is_empty() {
-        return Ok(vec![]);
+    #[must_use]
+    const fn decode_symbol(self, symbol: u8) -> Option<u8> {
+        let value = match (self, symbol) {
+            (_, b'A'..=b'Z') => symbol - b'A',
+            (_, b'a'..=b'z') => symbol - b'a' + 26,
+            (_, b'0'..=b'9') => symbol - b'0' + 52,
+            (Self::Standard, b'+') | (Self::UrlSafe, b'-') => 62,
+            (Self::Standard, b'/') | (Self::UrlSafe, b'_') => 63,
+            _ => return None,
+        };
+
+        Some(value)
     }
+}
 
-    let padding = if base64[base64.len() - 1] == ascii::Char::EqualsSign {
-        if base64[base64.len() - 2] == ascii::Char::EqualsSign {
-            2
-        } else {
-            1
+impl Config {
+    #[must_use]
+    pub const fn new(alphabet: Alphabet, padding: Padding) -> Self {
+        Self { alphabet, padding }
+    }
+
+    #[must_use]
+    pub const fn with_padding(self, padding: Padding) -> Self {
+        Self { padding, ..self }
+    }
+
+    #[must_use]
+    pub const fn alphabet(&self) -> Alphabet {
+        self.alphabet
+    }
+
+    #[must_use]
+    pub const fn padding(&self) -> Padding {
+        self.padding
+    }
+
+    #[must_use]
+    pub fn encode(self, data: &[u8]) -> ascii::String {
+        let mut chars = Vec::with_capacity(data.len().div_ceil(3) * 4);
+        self.encode_into(data, &mut chars);
+        ascii::String::from_chars(chars)
+    }
+
+    pub fn decode(self, base64: &ascii::Str) -> Result<Vec<u8>, Error> {
+        let mut state = DecoderState::default();
+        let mut data = Vec::with_capacity(base64.len() / 4 * 3 + 2);
+
+        for &symbol in base64.as_bytes() {
+            state.push(symbol, self, &mut data)?;
         }
-    } else {
-        0
-    };
-
-    let mut data = Vec::with_capacity((base64.len() / 4) * 3 - padding);
-    let mut buffer: u32 = 0;
-    let mut iter = 0;
-
-    for &symbol in base64.chars() {
-        if symbol == ascii::Char::EqualsSign {
-            // End of data
-            match iter {
-                2 => {
-                    data.push((buffer >> 4) as u8);
-                },
-                3 => {
-                    data.push((buffer >> 10) as u8);
-                    data.push((buffer >> 2) as u8);
-                },
-                _ => return Err(Error::InvalidPadding),
+        state.finish(self, &mut data)?;
+
+        Ok(data)
+    }
+
+    /// Encodes the data, padding the last block if necessary
+    fn encode_into(self, data: &[u8], output: &mut Vec<ascii::Char>) {
+        for block in data.chunks(3) {
+            let mut bytes = [0; 3];
+            bytes[..block.len()].copy_from_slice(block);
+            let buffer = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+
+            // n bytes of input produce n + 1 symbols
+            for i in 0..=block.len() {
+                let value = (buffer >> (18 - 6 * i)) as u8 & 0b111111;
+                output.push(self.alphabet.encode_symbol(value));
+            }
+
+            if self.padding != Padding::Forbidden {
+                for _ in block.len()..3 {
+                    output.push(ascii::Char::EqualsSign);
+                }
+            }
+        }
+    }
+}
+
+/// Decodes base64 data using the [STANDARD] configuration
+pub fn b64decode(base64: &ascii::Str) -> Result<Vec<u8>, Error> {
+    STANDARD.decode(base64)
+}
+
+/// Encodes data using the [STANDARD] configuration
+#[must_use]
+pub fn b64encode(data: &[u8]) -> ascii::String {
+    STANDARD.encode(data)
+}
+
+/// Incremental decoding of base64 symbols
+#[derive(Clone, Copy, Debug, Default)]
+struct DecoderState {
+    buffer: u32,
+    num_symbols: usize,
+    num_padding: usize,
+}
+
+impl DecoderState {
+    fn push(&mut self, symbol: u8, config: Config, output: &mut Vec<u8>) -> Result<(), Error> {
+        if symbol == b'=' {
+            // Padding can only complete a block that already contains at least two symbols
+            if config.padding == Padding::Forbidden
+                || self.num_symbols < 2
+                || self.num_symbols + self.num_padding == 4
+            {
+                return Err(Error::InvalidPadding);
             }
-            break;
+
+            self.num_padding += 1;
+            return Ok(());
+        }
+
+        if self.num_padding != 0 {
+            // Padding marks the end of the data
+            return Err(Error::InvalidPadding);
+        }
+
+        let value = config
+            .alphabet
+            .decode_symbol(symbol)
+            .ok_or(Error::IllegalCharacter)?;
+        self.buffer = (self.buffer << 6) | u32::from(value);
+        self.num_symbols += 1;
+
+        if self.num_symbols == 4 {
+            output.extend_from_slice(&self.buffer.to_be_bytes()[1..]);
+            self.buffer = 0;
+            self.num_symbols = 0;
+        }
+
+        Ok(())
+    }
+
+    fn finish(self, config: Config, output: &mut Vec<u8>) -> Result<(), Error> {
+        if self.num_padding != 0 {
+            if self.num_symbols + self.num_padding != 4 {
+                return Err(Error::InvalidPadding);
+            }
+        } else if self.num_symbols != 0 && config.padding == Padding::Required {
+            return Err(Error::InvalidLength);
+        }
+
+        match self.num_symbols {
+            0 => {},
+            2 => output.push((self.buffer >> 4) as u8),
+            3 => {
+                output.push((self.buffer >> 10) as u8);
+                output.push((self.buffer >> 2) as u8);
+            },
+            _ => return Err(Error::InvalidLength),
         }
 
-        let index = BASE64_CHARS
-            .iter()
-            .position(|&c| c == symbol)
-            .ok_or(Error::IllegalCharacter)? as u32;
-        buffer = (buffer << 6) | index;
-        iter += 1;
-
-        if iter == 4 {
-            data.extend_from_slice(&buffer.to_be_bytes().as_slice()[1..]);
-            iter = 0;
-            buffer = 0;
+        Ok(())
+    }
+}
+
+/// Encodes everything that is written to it and forwards the result to the inner writer
+///
+/// Since the last block can only be encoded once all data is known, [Encoder::finish]
+/// must be called once all data was written. Dropping the encoder without doing so
+/// discards up to two bytes of input.
+#[derive(Debug)]
+pub struct Encoder<W: io::Write> {
+    writer: W,
+    config: Config,
+    pending: [u8; 2],
+    num_pending: usize,
+    encoded: Vec<ascii::Char>,
+}
+
+impl<W: io::Write> Encoder<W> {
+    #[must_use]
+    pub fn new(writer: W, config: Config) -> Self {
+        Self {
+            writer,
+            config,
+            pending: [0; 2],
+            num_pending: 0,
+            encoded: vec![],
         }
     }
 
-    Ok(data)
+    /// Encodes the remaining data and returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.encoded.clear();
+        self.config
+            .encode_into(&self.pending[..self.num_pending], &mut self.encoded);
+        self.writer.write_all(self.encoded.as_bytes())?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: io::Write> io::Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Only complete blocks are encoded, the rest is kept until more data arrives
+        let available = self.num_pending + buf.len();
+        if available < 3 {
+            self.pending[self.num_pending..available].copy_from_slice(buf);
+            self.num_pending = available;
+            return Ok(buf.len());
+        }
+
+        let mut block = [0; 3];
+        let from_buf = 3 - self.num_pending;
+        block[..self.num_pending].copy_from_slice(&self.pending[..self.num_pending]);
+        block[self.num_pending..].copy_from_slice(&buf[..from_buf]);
+
+        let remaining = &buf[from_buf..];
+        let complete_len = remaining.len() - remaining.len() % 3;
+
+        self.encoded.clear();
+        self.config.encode_into(&block, &mut self.encoded);
+        self.config
+            .encode_into(&remaining[..complete_len], &mut self.encoded);
+        self.writer.write_all(self.encoded.as_bytes())?;
+
+        let rest = &remaining[complete_len..];
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.num_pending = rest.len();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Decodes base64 data that is read from the inner reader
+///
+/// Errors in the encoded data are reported as [io::ErrorKind::InvalidData].
+#[derive(Debug)]
+pub struct Decoder<R: io::Read> {
+    reader: R,
+    config: Config,
+    state: DecoderState,
+    decoded: Vec<u8>,
+    decoded_ptr: usize,
+    is_done: bool,
+}
+
+impl<R: io::Read> Decoder<R> {
+    #[must_use]
+    pub fn new(reader: R, config: Config) -> Self {
+        Self {
+            reader,
+            config,
+            state: DecoderState::default(),
+            decoded: vec![],
+            decoded_ptr: 0,
+            is_done: false,
+        }
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn decode_next_chunk(&mut self) -> io::Result<()> {
+        self.decoded.clear();
+        self.decoded_ptr = 0;
+
+        let mut input = [0; 1024];
+        let n = self.reader.read(&mut input)?;
+
+        let result = if n == 0 {
+            self.is_done = true;
+            self.state.finish(self.config, &mut self.decoded)
+        } else {
+            input[..n]
+                .iter()
+                .try_for_each(|&symbol| self.state.push(symbol, self.config, &mut self.decoded))
+        };
+
+        result.map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+}
+
+impl<R: io::Read> io::Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.decoded_ptr == self.decoded.len() && !self.is_done {
+            self.decode_next_chunk()?;
+        }
+
+        let available = &self.decoded[self.decoded_ptr..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.decoded_ptr += n;
+
+        Ok(n)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::b64decode;
+    use std::io::{Read, Write};
+
+    use super::*;
 
     #[test]
     fn decode() {
@@ -149,4 +446,103 @@ mod tests {
 
         assert_eq!(b64decode(encoded).unwrap(), decoded);
     }
+
+    #[test]
+    fn rfc_test_vectors() {
+        // https://datatracker.ietf.org/doc/html/rfc4648#section-10
+        let cases: &[(&str, &str)] = &[
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+
+        for &(plain, encoded) in cases {
+            assert_eq!(b64encode(plain.as_bytes()), encoded);
+            assert_eq!(
+                b64decode(encoded.try_into().unwrap()).unwrap(),
+                plain.as_bytes()
+            );
+
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(STANDARD_NO_PAD.encode(plain.as_bytes()), unpadded);
+            assert_eq!(
+                STANDARD_NO_PAD
+                    .decode(unpadded.try_into().unwrap())
+                    .unwrap(),
+                plain.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn url_safe_alphabet() {
+        let data = [0xfb, 0xff, 0xbf];
+
+        assert_eq!(b64encode(&data), "+/+/");
+        assert_eq!(URL_SAFE.encode(&data), "-_-_");
+        assert_eq!(URL_SAFE.decode("-_-_".try_into().unwrap()).unwrap(), data);
+        assert!(matches!(
+            URL_SAFE.decode("+/+/".try_into().unwrap()),
+            Err(Error::IllegalCharacter)
+        ));
+    }
+
+    #[test]
+    fn padding() {
+        let decode = |config: Config, base64: &str| config.decode(base64.try_into().unwrap());
+
+        assert!(matches!(decode(STANDARD, "Zg"), Err(Error::InvalidLength)));
+        assert!(matches!(
+            decode(STANDARD_NO_PAD, "Zg=="),
+            Err(Error::InvalidPadding)
+        ));
+        assert!(matches!(
+            decode(STANDARD, "Zg="),
+            Err(Error::InvalidPadding)
+        ));
+        assert!(matches!(
+            decode(STANDARD, "Z==="),
+            Err(Error::InvalidPadding)
+        ));
+        assert!(matches!(
+            decode(STANDARD, "Zg==Zg=="),
+            Err(Error::InvalidPadding)
+        ));
+        assert!(matches!(
+            decode(STANDARD, "Zm9vY"),
+            Err(Error::InvalidLength)
+        ));
+
+        let optional = STANDARD.with_padding(Padding::Optional);
+        assert_eq!(decode(optional, "Zg").unwrap(), b"f");
+        assert_eq!(decode(optional, "Zg==").unwrap(), b"f");
+        assert_eq!(optional.encode(b"f"), "Zg==");
+    }
+
+    #[test]
+    fn streaming() {
+        let data: Vec<u8> = (0..=255).cycle().take(5000).collect();
+
+        let mut encoder = Encoder::new(vec![], URL_SAFE);
+        for chunk in data.chunks(7) {
+            encoder.write_all(chunk).unwrap();
+        }
+        let encoded = encoder.finish().unwrap();
+        assert_eq!(encoded, URL_SAFE.encode(&data).as_bytes());
+
+        let mut decoded = vec![];
+        Decoder::new(encoded.as_slice(), URL_SAFE)
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, data);
+
+        let error = Decoder::new(b"Zm9v!".as_slice(), STANDARD)
+            .read_to_end(&mut vec![])
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
 }
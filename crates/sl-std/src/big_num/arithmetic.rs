@@ -0,0 +1,271 @@
+//! Arithmetic on little-endian digit slices
+//!
+//! None of these functions require their inputs to be compacted, leading zeros
+//! are ignored where necessary.
+
+use std::cmp::Ordering;
+
+use super::{BigDigit, Digit};
+
+/// Below this number of digits, multiplication falls back to the schoolbook algorithm
+const KARATSUBA_THRESHOLD: usize = 32;
+
+/// Remove leading zeros
+#[inline]
+#[must_use]
+pub(super) fn trim(digits: &[Digit]) -> &[Digit] {
+    let significant_len = digits
+        .iter()
+        .rposition(|&digit| digit != 0)
+        .map_or(0, |index| index + 1);
+    &digits[..significant_len]
+}
+
+#[must_use]
+pub(super) fn compare(a: &[Digit], b: &[Digit]) -> Ordering {
+    let a = trim(a);
+    let b = trim(b);
+
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+/// Computes `a + b * c + carry`, returning the low and high digit of the result
+#[inline]
+#[must_use]
+pub(super) fn multiply_add(a: Digit, b: Digit, c: Digit, carry: Digit) -> (Digit, Digit) {
+    let result = BigDigit::from(a) + BigDigit::from(b) * BigDigit::from(c) + BigDigit::from(carry);
+    (result as Digit, (result >> Digit::BITS) as Digit)
+}
+
+#[must_use]
+pub(super) fn add(a: &[Digit], b: &[Digit]) -> Vec<Digit> {
+    let (longer, shorter) = if a.len() < b.len() { (b, a) } else { (a, b) };
+
+    let mut result = Vec::with_capacity(longer.len() + 1);
+    result.extend_from_slice(longer);
+    result.push(0);
+    add_assign_at(&mut result, shorter, 0);
+    result
+}
+
+/// Adds `b * BASE^offset` to `a`
+///
+/// # Panics
+/// This function panics if the result does not fit into `a`.
+pub(super) fn add_assign_at(a: &mut [Digit], b: &[Digit], offset: usize) {
+    let b = trim(b);
+    assert!(offset + b.len() <= a.len());
+
+    let mut carry = false;
+    for (d1, &d2) in a[offset..].iter_mut().zip(b) {
+        (*d1, carry) = d1.carrying_add(d2, carry);
+    }
+
+    let mut index = offset + b.len();
+    while carry {
+        (a[index], carry) = a[index].overflowing_add(1);
+        index += 1;
+    }
+}
+
+/// Subtracts `b` from `a` in place, returning `true` if the result is negative
+/// (in which case `a` contains the two's complement of the difference)
+pub(super) fn sub_assign(a: &mut [Digit], b: &[Digit]) -> bool {
+    let b = trim(b);
+    assert!(b.len() <= a.len());
+
+    let mut borrow = false;
+    for (d1, &d2) in a.iter_mut().zip(b) {
+        (*d1, borrow) = d1.borrowing_sub(d2, borrow);
+    }
+
+    for digit in &mut a[b.len()..] {
+        if !borrow {
+            break;
+        }
+        (*digit, borrow) = digit.overflowing_sub(1);
+    }
+
+    borrow
+}
+
+#[must_use]
+pub(super) fn multiply(a: &[Digit], b: &[Digit]) -> Vec<Digit> {
+    let a = trim(a);
+    let b = trim(b);
+
+    if a.len() < KARATSUBA_THRESHOLD || b.len() < KARATSUBA_THRESHOLD {
+        return multiply_schoolbook(a, b);
+    }
+
+    let split = a.len().max(b.len()) / 2;
+    if a.len() <= split || b.len() <= split {
+        // The operands are too unbalanced for karatsuba to be worth it
+        return multiply_schoolbook(a, b);
+    }
+
+    // a * b = z2 * BASE^(2 * split) + z1 * BASE^split + z0
+    let (a0, a1) = a.split_at(split);
+    let (b0, b1) = b.split_at(split);
+
+    let z0 = multiply(a0, b0);
+    let z2 = multiply(a1, b1);
+
+    let mut z1 = multiply(&add(a0, a1), &add(b0, b1));
+    sub_assign(&mut z1, &z0);
+    sub_assign(&mut z1, &z2);
+
+    let mut result = vec![0; a.len() + b.len() + 1];
+    add_assign_at(&mut result, &z0, 0);
+    add_assign_at(&mut result, &z1, split);
+    add_assign_at(&mut result, &z2, 2 * split);
+    result
+}
+
+#[must_use]
+pub(super) fn multiply_schoolbook(a: &[Digit], b: &[Digit]) -> Vec<Digit> {
+    let mut result = vec![0; a.len() + b.len()];
+
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, &y) in b.iter().enumerate() {
+            (result[i + j], carry) = multiply_add(result[i + j], x, y, carry);
+        }
+        result[i + b.len()] = carry;
+    }
+
+    result
+}
+
+/// Shift the digits left by less than a single digit, without growing the number
+fn shift_left_within(digits: &mut [Digit], shift: u32) -> Digit {
+    if shift == 0 {
+        return 0;
+    }
+
+    let mut carry = 0;
+    for digit in digits {
+        let new_carry = *digit >> (Digit::BITS - shift);
+        *digit = (*digit << shift) | carry;
+        carry = new_carry;
+    }
+    carry
+}
+
+/// Shift the digits right by less than a single digit
+pub(super) fn shift_right_within(digits: &mut [Digit], shift: u32) {
+    if shift == 0 {
+        return;
+    }
+
+    let mut carry = 0;
+    for digit in digits.iter_mut().rev() {
+        let new_carry = *digit << (Digit::BITS - shift);
+        *digit = (*digit >> shift) | carry;
+        carry = new_carry;
+    }
+}
+
+/// Divide by a single digit, returning the quotient and the remainder
+fn div_rem_digit(dividend: &[Digit], divisor: Digit) -> (Vec<Digit>, Digit) {
+    let mut quotient = vec![0; dividend.len()];
+    let mut remainder: BigDigit = 0;
+
+    for (q, &digit) in quotient.iter_mut().zip(dividend).rev() {
+        let current = (remainder << Digit::BITS) | BigDigit::from(digit);
+        *q = (current / BigDigit::from(divisor)) as Digit;
+        remainder = current % BigDigit::from(divisor);
+    }
+
+    (quotient, remainder as Digit)
+}
+
+/// Compute the quotient and remainder of the division.
+///
+/// Uses Algorithm D from Knuth's "The Art of Computer Programming, Vol. 2", Section 4.3.1.
+///
+/// # Panics
+/// This function panics if the divisor is zero.
+#[must_use]
+pub(super) fn div_rem(dividend: &[Digit], divisor: &[Digit]) -> (Vec<Digit>, Vec<Digit>) {
+    let dividend = trim(dividend);
+    let divisor = trim(divisor);
+
+    assert!(!divisor.is_empty(), "attempt to divide by zero");
+
+    if compare(dividend, divisor) == Ordering::Less {
+        return (vec![0], dividend.to_vec());
+    }
+
+    if let [divisor] = divisor {
+        let (quotient, remainder) = div_rem_digit(dividend, *divisor);
+        return (quotient, vec![remainder]);
+    }
+
+    // Normalize the divisor such that its most significant bit is set,
+    // this ensures that the estimated quotient digits are off by at most two
+    let shift = divisor[divisor.len() - 1].leading_zeros();
+
+    let mut v = divisor.to_vec();
+    shift_left_within(&mut v, shift);
+
+    let mut u = dividend.to_vec();
+    let carry = shift_left_within(&mut u, shift);
+    u.push(carry);
+
+    let n = v.len();
+    let m = u.len() - n;
+    let mut quotient = vec![0; m];
+
+    let base = BigDigit::from(Digit::MAX) + 1;
+    let v_top = BigDigit::from(v[n - 1]);
+    let v_second = BigDigit::from(v[n - 2]);
+
+    for j in (0..m).rev() {
+        // Estimate the next digit of the quotient
+        let numerator = (BigDigit::from(u[j + n]) << Digit::BITS) | BigDigit::from(u[j + n - 1]);
+        let mut q_hat = numerator / v_top;
+        let mut r_hat = numerator % v_top;
+
+        while q_hat >= base
+            || q_hat * v_second > ((r_hat << Digit::BITS) | BigDigit::from(u[j + n - 2]))
+        {
+            q_hat -= 1;
+            r_hat += v_top;
+            if r_hat >= base {
+                break;
+            }
+        }
+
+        // Multiply and subtract
+        let mut carry = 0;
+        let mut borrow = false;
+        for i in 0..n {
+            let product;
+            (product, carry) = multiply_add(0, q_hat as Digit, v[i], carry);
+            (u[i + j], borrow) = u[i + j].borrowing_sub(product, borrow);
+        }
+        (u[j + n], borrow) = u[j + n].borrowing_sub(carry, borrow);
+
+        if borrow {
+            // The estimate was one too large, add the divisor back
+            q_hat -= 1;
+
+            let mut carry = false;
+            for i in 0..n {
+                (u[i + j], carry) = u[i + j].carrying_add(v[i], carry);
+            }
+            u[j + n] = u[j + n].wrapping_add(Digit::from(carry));
+        }
+
+        quotient[j] = q_hat as Digit;
+    }
+
+    // Undo the normalization
+    u.truncate(n);
+    shift_right_within(&mut u, shift);
+
+    (quotient, u)
+}
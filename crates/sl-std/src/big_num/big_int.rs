@@ -0,0 +1,329 @@
+//! Signed arbitrary sized integers
+
+use std::{cmp::Ordering, ops};
+
+use super::BigNum;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sign {
+    Positive,
+    Negative,
+}
+
+impl ops::Neg for Sign {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        match self {
+            Self::Positive => Self::Negative,
+            Self::Negative => Self::Positive,
+        }
+    }
+}
+
+impl ops::Mul for Sign {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        if self == other {
+            Self::Positive
+        } else {
+            Self::Negative
+        }
+    }
+}
+
+/// A dynamically sized signed integer type
+///
+/// The number is stored as a sign and a [BigNum] magnitude.
+/// Zero is always positive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigInt {
+    sign: Sign,
+    magnitude: BigNum,
+}
+
+impl BigInt {
+    #[must_use]
+    pub fn new(sign: Sign, mut magnitude: BigNum) -> Self {
+        magnitude.compact();
+
+        // There is no negative zero
+        let sign = if magnitude.is_zero() {
+            Sign::Positive
+        } else {
+            sign
+        };
+
+        Self { sign, magnitude }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn zero() -> Self {
+        Self::new(Sign::Positive, BigNum::zero())
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn sign(&self) -> Sign {
+        self.sign
+    }
+
+    /// The absolute value of the number
+    #[inline]
+    #[must_use]
+    pub const fn magnitude(&self) -> &BigNum {
+        &self.magnitude
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_magnitude(self) -> BigNum {
+        self.magnitude
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.sign == Sign::Negative
+    }
+
+    /// Parse a big-endian two's complement representation, like the one used
+    /// in DER-encoded `INTEGER` values.
+    ///
+    /// An empty slice represents zero.
+    #[must_use]
+    pub fn from_signed_be_bytes(bytes: &[u8]) -> Self {
+        match bytes.first() {
+            Some(first) if first & 0x80 != 0 => {
+                // For negative numbers, the magnitude is the inverted value plus one
+                let inverted: Vec<u8> = bytes.iter().map(|byte| !byte).collect();
+                Self::new(Sign::Negative, BigNum::from_be_bytes(&inverted) + 1)
+            },
+            _ => Self::new(Sign::Positive, BigNum::from_be_bytes(bytes)),
+        }
+    }
+
+    /// Convert to the shortest big-endian two's complement representation
+    ///
+    /// The result always contains at least one byte.
+    #[must_use]
+    pub fn to_signed_be_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.magnitude.to_be_bytes();
+
+        match self.sign {
+            Sign::Positive => {
+                // Make sure that the sign bit is not set
+                if bytes.is_empty() || bytes[0] & 0x80 != 0 {
+                    bytes.insert(0, 0);
+                }
+                bytes
+            },
+            Sign::Negative => {
+                // The smallest negative number that fits into n bytes is -2^(8n - 1)
+                let mut length = bytes.len();
+                let min_value = &BigNum::from(1) << (8 * length - 1);
+                if min_value < self.magnitude {
+                    length += 1;
+                }
+
+                let two_complement = &(&BigNum::from(1) << (8 * length)) - &self.magnitude;
+                two_complement
+                    .to_be_bytes_with_length(length)
+                    .expect("two's complement fits into the computed length")
+            },
+        }
+    }
+
+    /// Computes the quotient and the remainder of `self / divisor`
+    ///
+    /// Like the division of primitive integers, the quotient is rounded towards zero and
+    /// the remainder has the same sign as `self`.
+    ///
+    /// # Panics
+    /// This function panics if `divisor` is zero.
+    #[must_use]
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let (quotient, remainder) = self.magnitude.div_rem(&divisor.magnitude);
+        (
+            Self::new(self.sign * divisor.sign, quotient),
+            Self::new(self.sign, remainder),
+        )
+    }
+}
+
+impl From<BigNum> for BigInt {
+    fn from(value: BigNum) -> Self {
+        Self::new(Sign::Positive, value)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        let sign = if value < 0 {
+            Sign::Negative
+        } else {
+            Sign::Positive
+        };
+
+        Self::new(
+            sign,
+            BigNum::from_be_bytes(&value.unsigned_abs().to_be_bytes()),
+        )
+    }
+}
+
+impl ops::Neg for BigInt {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.sign, self.magnitude)
+    }
+}
+
+impl ops::Add<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> Self::Output {
+        if self.sign == other.sign {
+            return BigInt::new(self.sign, &self.magnitude + &other.magnitude);
+        }
+
+        // The signs differ, subtract the smaller magnitude from the larger one
+        match self.magnitude.cmp(&other.magnitude) {
+            Ordering::Less => BigInt::new(other.sign, &other.magnitude - &self.magnitude),
+            Ordering::Equal => BigInt::zero(),
+            Ordering::Greater => BigInt::new(self.sign, &self.magnitude - &other.magnitude),
+        }
+    }
+}
+
+impl ops::Sub<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> Self::Output {
+        self + &(-other.clone())
+    }
+}
+
+impl ops::Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> Self::Output {
+        BigInt::new(self.sign * other.sign, &self.magnitude * &other.magnitude)
+    }
+}
+
+impl ops::Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn div(self, other: &BigInt) -> Self::Output {
+        self.div_rem(other).0
+    }
+}
+
+impl ops::Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+
+    fn rem(self, other: &BigInt) -> Self::Output {
+        self.div_rem(other).1
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.sign, other.sign) {
+            (Sign::Positive, Sign::Negative) => Ordering::Greater,
+            (Sign::Negative, Sign::Positive) => Ordering::Less,
+            (Sign::Positive, Sign::Positive) => self.magnitude.cmp(&other.magnitude),
+            (Sign::Negative, Sign::Negative) => other.magnitude.cmp(&self.magnitude),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BigInt, Sign};
+    use crate::bignum;
+
+    #[test]
+    fn arithmetic() {
+        let a = BigInt::from(-7);
+        let b = BigInt::from(3);
+
+        assert_eq!(&a + &b, BigInt::from(-4));
+        assert_eq!(&b - &a, BigInt::from(10));
+        assert_eq!(&a - &a, BigInt::zero());
+        assert_eq!(&a * &b, BigInt::from(-21));
+        assert_eq!(&a * &a, BigInt::from(49));
+
+        // Same semantics as primitive integers
+        assert_eq!(&a / &b, BigInt::from(-7 / 3));
+        assert_eq!(&a % &b, BigInt::from(-7 % 3));
+    }
+
+    #[test]
+    fn no_negative_zero() {
+        let zero = -BigInt::zero();
+        assert_eq!(zero.sign(), Sign::Positive);
+        assert_eq!(zero, BigInt::zero());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(BigInt::from(-8) < BigInt::from(-7));
+        assert!(BigInt::from(-1) < BigInt::from(0));
+        assert!(BigInt::from(2) > BigInt::from(1));
+    }
+
+    #[test]
+    fn signed_be_bytes() {
+        let cases: &[(i64, &[u8])] = &[
+            (0, &[0x00]),
+            (127, &[0x7f]),
+            (128, &[0x00, 0x80]),
+            (256, &[0x01, 0x00]),
+            (-1, &[0xff]),
+            (-128, &[0x80]),
+            (-129, &[0xff, 0x7f]),
+            (-256, &[0xff, 0x00]),
+        ];
+
+        for &(value, bytes) in cases {
+            assert_eq!(BigInt::from(value).to_signed_be_bytes(), bytes);
+            assert_eq!(BigInt::from_signed_be_bytes(bytes), BigInt::from(value));
+        }
+
+        // Leading sign bytes are redundant, but valid
+        assert_eq!(
+            BigInt::from_signed_be_bytes(&[0xff, 0xff, 0x7f]),
+            BigInt::from(-129)
+        );
+        assert_eq!(BigInt::from_signed_be_bytes(&[]), BigInt::zero());
+
+        // Serial numbers of X509 certificates are often 20 bytes long
+        let serial = [
+            0x00, 0x82, 0x10, 0xcf, 0xb0, 0xd2, 0x40, 0xe3, 0x59, 0x44, 0x63, 0xe0, 0xbb, 0x63,
+            0x82, 0x8b, 0x00,
+        ];
+        let parsed = BigInt::from_signed_be_bytes(&serial);
+        assert_eq!(
+            parsed,
+            BigInt::from(bignum!(0x8210cfb0d240e3594463e0bb63828b00))
+        );
+        assert_eq!(parsed.to_signed_be_bytes(), serial);
+    }
+}
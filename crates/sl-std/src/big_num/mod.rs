@@ -1,4 +1,11 @@
-use std::{iter, mem, ops};
+mod arithmetic;
+mod big_int;
+mod montgomery;
+
+use std::{cmp::Ordering, iter, mem, ops};
+
+pub use big_int::{BigInt, Sign};
+use montgomery::Montgomery;
 
 cfg_match! {
     cfg(target_pointer_width = "64") => {
@@ -121,7 +128,10 @@ impl BigNum {
 
             assert_eq!(carry, 0);
 
-            result = result + Digit::from(chunk.iter().fold(0, |acc, &digit| acc * radix + digit));
+            result = result
+                + chunk.iter().fold(0, |acc, &digit| {
+                    acc * Digit::from(radix) + Digit::from(digit)
+                });
         }
 
         result
@@ -131,7 +141,37 @@ impl BigNum {
     /// deallocating unused capacity and removing leading zeros.
     #[inline]
     pub fn compact(&mut self) {
-        self.digits.truncate(self.last_nonzero_digit() + 1);
+        let significant_len = arithmetic::trim(&self.digits).len();
+        self.digits.truncate(significant_len.max(1));
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_zero(&self) -> bool {
+        self.nonzero_digits().is_empty()
+    }
+
+    /// The number of bits required to represent the number
+    #[must_use]
+    pub fn bits(&self) -> usize {
+        match self.nonzero_digits().last() {
+            Some(most_significant) => {
+                self.nonzero_digits().len() * Digit::BITS as usize
+                    - most_significant.leading_zeros() as usize
+            },
+            None => 0,
+        }
+    }
+
+    /// Returns `true` if the bit at the given index (starting at the least significant bit) is set
+    #[must_use]
+    pub fn bit(&self, index: usize) -> bool {
+        let digit_index = index / Digit::BITS as usize;
+        let bit_index = index % Digit::BITS as usize;
+
+        self.digits
+            .get(digit_index)
+            .is_some_and(|digit| (digit >> bit_index) & 1 == 1)
     }
 
     #[inline]
@@ -141,10 +181,12 @@ impl BigNum {
     }
 
     /// List of digits, with leading zeros removed
+    ///
+    /// The digits of zero are an empty slice.
     #[inline]
     #[must_use]
     fn nonzero_digits(&self) -> &[Digit] {
-        &self.digits()[..=self.last_nonzero_digit()]
+        arithmetic::trim(self.digits())
     }
 
     #[inline]
@@ -153,23 +195,6 @@ impl BigNum {
         &mut self.digits
     }
 
-    fn last_nonzero_digit(&self) -> usize {
-        self.digits()
-            .iter()
-            .enumerate()
-            .rev()
-            .find_map(
-                |(index, digit)| {
-                    if *digit == 0 {
-                        None
-                    } else {
-                        Some(index)
-                    }
-                },
-            )
-            .unwrap_or_default()
-    }
-
     pub fn from_be_bytes(bytes: &[u8]) -> Self {
         let skip_from_start = bytes.len() % BYTES_PER_DIGIT;
         let n_digits = (bytes.len() + BYTES_PER_DIGIT - 1) / BYTES_PER_DIGIT;
@@ -194,6 +219,129 @@ impl BigNum {
 
         Self::from_digits(digits)
     }
+
+    /// Convert the number to its big-endian byte representation, without leading zeros
+    ///
+    /// The representation of zero is an empty vector.
+    #[must_use]
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        let bytes: Vec<u8> = self
+            .nonzero_digits()
+            .iter()
+            .rev()
+            .flat_map(|digit| digit.to_be_bytes())
+            .skip_while(|&byte| byte == 0)
+            .collect();
+        bytes
+    }
+
+    /// Convert the number to a big-endian byte representation of exactly `length` bytes,
+    /// padded with leading zeros.
+    ///
+    /// Returns `None` if the number is too large to fit into `length` bytes.
+    #[must_use]
+    pub fn to_be_bytes_with_length(&self, length: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_be_bytes();
+        let padding = length.checked_sub(bytes.len())?;
+
+        let mut result = vec![0; padding];
+        result.extend_from_slice(&bytes);
+        Some(result)
+    }
+
+    /// Computes `self - other`, returning `None` if the result would be negative
+    #[must_use]
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let mut digits = self.digits.clone();
+        if digits.len() < other.nonzero_digits().len() {
+            return None;
+        }
+
+        if arithmetic::sub_assign(&mut digits, other.digits()) {
+            return None;
+        }
+
+        let mut result = Self::from_digits(digits);
+        result.compact();
+        Some(result)
+    }
+
+    /// Computes the quotient and the remainder of `self / divisor`
+    ///
+    /// # Panics
+    /// This function panics if `divisor` is zero.
+    #[must_use]
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let (quotient, remainder) = arithmetic::div_rem(self.digits(), divisor.digits());
+
+        let mut quotient = Self::from_digits(quotient);
+        quotient.compact();
+
+        let mut remainder = Self::from_digits(remainder);
+        remainder.compact();
+
+        (quotient, remainder)
+    }
+
+    /// Computes `self^exponent mod modulus`
+    ///
+    /// For odd moduli (like the ones used in RSA) this uses montgomery multiplication.
+    ///
+    /// # Panics
+    /// This function panics if `modulus` is zero.
+    #[must_use]
+    pub fn modpow(&self, exponent: &Self, modulus: &Self) -> Self {
+        assert!(
+            !modulus.is_zero(),
+            "attempt to calculate the remainder with a divisor of zero"
+        );
+
+        if modulus.nonzero_digits() == [1] {
+            return Self::zero();
+        }
+
+        if modulus.nonzero_digits()[0] & 1 == 0 {
+            return self.modpow_without_montgomery(exponent, modulus);
+        }
+
+        let montgomery = Montgomery::new(modulus.digits());
+        let base = montgomery.enter(&(self % modulus));
+        let mut result = montgomery.one();
+
+        // Left-to-right binary exponentiation
+        for bit_index in (0..exponent.bits()).rev() {
+            result = montgomery.multiply(&result, &result);
+
+            if exponent.bit(bit_index) {
+                result = montgomery.multiply(&result, &base);
+            }
+        }
+
+        let mut result = montgomery.leave(&result);
+        result.compact();
+        result
+    }
+
+    fn modpow_without_montgomery(&self, exponent: &Self, modulus: &Self) -> Self {
+        let base = self % modulus;
+        let mut result = Self::from_digits(vec![1]);
+
+        for bit_index in (0..exponent.bits()).rev() {
+            result = &(&result * &result) % modulus;
+
+            if exponent.bit(bit_index) {
+                result = &(&result * &base) % modulus;
+            }
+        }
+
+        result
+    }
+}
+
+impl From<Digit> for BigNum {
+    fn from(value: Digit) -> Self {
+        Self::from_digits(vec![value])
+    }
 }
 
 impl ops::Add for BigNum {
@@ -235,27 +383,71 @@ impl ops::Add for BigNum {
     }
 }
 
+impl ops::Add<&BigNum> for &BigNum {
+    type Output = BigNum;
+
+    fn add(self, other: &BigNum) -> Self::Output {
+        let mut sum = BigNum::from_digits(arithmetic::add(self.digits(), other.digits()));
+        sum.compact();
+        sum
+    }
+}
+
 impl ops::Add<Digit> for BigNum {
     type Output = Self;
 
     fn add(mut self, other: Digit) -> Self::Output {
-        let mut carry = false;
+        let mut to_add = other;
         for digit in self.digits.iter_mut() {
-            (*digit, carry) = digit.carrying_add(other, carry);
+            let carry;
+            (*digit, carry) = digit.overflowing_add(to_add);
 
             if !carry {
                 return self;
             }
-        }
 
-        if carry {
-            self.digits.push(1);
+            to_add = 1;
         }
 
+        self.digits.push(to_add);
         self
     }
 }
 
+macro_rules! impl_binary_op {
+    ($trait: ident, $method: ident, $implementation: expr) => {
+        impl ops::$trait<&BigNum> for &BigNum {
+            type Output = BigNum;
+
+            fn $method(self, other: &BigNum) -> Self::Output {
+                $implementation(self, other)
+            }
+        }
+
+        impl ops::$trait for BigNum {
+            type Output = Self;
+
+            fn $method(self, other: Self) -> Self::Output {
+                $implementation(&self, &other)
+            }
+        }
+    };
+}
+
+impl_binary_op!(Sub, sub, |a: &BigNum, b: &BigNum| {
+    a.checked_sub(b).expect("attempt to subtract with overflow")
+});
+
+impl_binary_op!(Mul, mul, |a: &BigNum, b: &BigNum| {
+    let mut product = BigNum::from_digits(arithmetic::multiply(a.digits(), b.digits()));
+    product.compact();
+    product
+});
+
+impl_binary_op!(Div, div, |a: &BigNum, b: &BigNum| a.div_rem(b).0);
+
+impl_binary_op!(Rem, rem, |a: &BigNum, b: &BigNum| a.div_rem(b).1);
+
 impl ops::Shl<usize> for &BigNum {
     type Output = BigNum;
 
@@ -284,12 +476,46 @@ impl ops::Shl<usize> for &BigNum {
     }
 }
 
+impl ops::Shr<usize> for &BigNum {
+    type Output = BigNum;
+
+    fn shr(self, rhs: usize) -> Self::Output {
+        // First shift by digits
+        let n_digits_to_remove = rhs / Digit::BITS as usize;
+        if self.nonzero_digits().len() <= n_digits_to_remove {
+            return BigNum::zero();
+        }
+        let mut digits = self.nonzero_digits()[n_digits_to_remove..].to_vec();
+
+        // Then, shift by the remainder (which will always be less than the size of a digit)
+        arithmetic::shift_right_within(&mut digits, (rhs % Digit::BITS as usize) as u32);
+
+        let mut result = BigNum::from_digits(digits);
+        result.compact();
+        result
+    }
+}
+
 impl PartialEq for BigNum {
     fn eq(&self, other: &Self) -> bool {
         self.nonzero_digits() == other.nonzero_digits()
     }
 }
 
+impl Eq for BigNum {}
+
+impl PartialOrd for BigNum {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigNum {
+    fn cmp(&self, other: &Self) -> Ordering {
+        arithmetic::compare(self.digits(), other.digits())
+    }
+}
+
 // Takes an ascii string and converts it to a sequence of digits in the given
 // radix and removes leading zeros So `"01_23F"` in base 16 becomes `[1, 2, 3, 15]`.
 //
@@ -307,6 +533,7 @@ fn to_radix(number_with_leading_zeros: &str, base: u32) -> Vec<u32> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rand::{Rng, RNG};
 
     #[test]
     fn test_equal() {
@@ -349,6 +576,142 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_sub() {
+        let a = bignum!(57900000000000000000000000000000000000001455);
+        let b = bignum!(12300000000000000000000000000000000000000456);
+        let d = bignum!(45600000000000000000000000000000000000000999);
+
+        assert_eq!(a.clone() - b.clone(), d);
+        assert_eq!(&a - &a, BigNum::zero());
+        assert!(b.checked_sub(&a).is_none());
+    }
+
+    #[test]
+    fn test_mul() {
+        let a = bignum!(0xffffffffffffffffffffffffffffffff);
+        let b = bignum!(0x10000000000000000);
+        assert_eq!(
+            &a * &b,
+            bignum!(0xffffffffffffffffffffffffffffffff0000000000000000)
+        );
+
+        assert_eq!(
+            bignum!(123456789123456789123456789) * bignum!(987654321987654321),
+            bignum!(121932631356500531469135800347203169112635269)
+        );
+
+        assert_eq!(a * BigNum::zero(), BigNum::zero());
+    }
+
+    fn random_bignum(rng: &mut impl Rng, num_digits: usize) -> BigNum {
+        let mut digits = Vec::with_capacity(num_digits);
+        for _ in 0..num_digits {
+            digits.push(rng.next_u64() as Digit);
+        }
+        BigNum::from_digits(digits)
+    }
+
+    #[test]
+    fn test_karatsuba_matches_schoolbook() {
+        let mut rng = RNG::default();
+
+        for (a_len, b_len) in [(40, 40), (64, 33), (100, 70)] {
+            let a = random_bignum(&mut rng, a_len);
+            let b = random_bignum(&mut rng, b_len);
+
+            let mut schoolbook =
+                BigNum::from_digits(arithmetic::multiply_schoolbook(a.digits(), b.digits()));
+            schoolbook.compact();
+
+            assert_eq!(&a * &b, schoolbook);
+        }
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let (q, r) = bignum!(121932631356500531469135800347203169112635270)
+            .div_rem(&bignum!(987654321987654321));
+        assert_eq!(q, bignum!(123456789123456789123456789));
+        assert_eq!(r, bignum!(1));
+
+        let mut rng = RNG::default();
+        for (dividend_len, divisor_len) in [(1, 1), (4, 1), (8, 3), (20, 7), (5, 5), (3, 6)] {
+            let dividend = random_bignum(&mut rng, dividend_len);
+            let divisor = random_bignum(&mut rng, divisor_len);
+
+            let (quotient, remainder) = dividend.div_rem(&divisor);
+            assert!(remainder < divisor);
+            assert_eq!(&(&quotient * &divisor) + &remainder, dividend);
+        }
+    }
+
+    #[test]
+    fn test_ord() {
+        assert!(bignum!(123) < bignum!(124));
+        assert!(bignum!(0x10000000000000000) > bignum!(0xffffffffffffffff));
+        assert_eq!(
+            BigNum::from_digits(vec![5, 0, 0]).cmp(&bignum!(5)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_shr() {
+        assert_eq!(
+            &bignum!(0x100000000000000000000000000000000) >> 128,
+            bignum!(1)
+        );
+        assert_eq!(
+            &bignum!(0x6f56df778000000000000000) >> 63,
+            bignum!(0xdeadbeef)
+        );
+        assert_eq!(&bignum!(1) >> 1, BigNum::zero());
+    }
+
+    #[test]
+    fn test_modpow() {
+        assert_eq!(bignum!(4).modpow(&bignum!(13), &bignum!(497)), bignum!(445));
+
+        // Textbook RSA example
+        let n = bignum!(3233);
+        let ciphertext = bignum!(65).modpow(&bignum!(17), &n);
+        assert_eq!(ciphertext, bignum!(2790));
+        assert_eq!(ciphertext.modpow(&bignum!(413), &n), bignum!(65));
+
+        // Fermat's little theorem, using the mersenne prime 2^127 - 1
+        let p = bignum!(170141183460469231731687303715884105727);
+        let p_minus_one = &p - &bignum!(1);
+        assert_eq!(bignum!(3).modpow(&p_minus_one, &p), bignum!(1));
+
+        // Even modulus
+        assert_eq!(
+            bignum!(3).modpow(&bignum!(200), &bignum!(0x10000000000000000)),
+            bignum!(0x5bfaff1eaaf8b0a1)
+        );
+
+        assert_eq!(bignum!(3).modpow(&bignum!(0), &bignum!(7)), bignum!(1));
+        assert_eq!(bignum!(3).modpow(&bignum!(5), &bignum!(1)), bignum!(0));
+    }
+
+    #[test]
+    fn test_to_be_bytes() {
+        assert_eq!(
+            bignum!(0xdeadbeef).to_be_bytes(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+        assert_eq!(
+            bignum!(0x1deadbeefcafebabe00).to_be_bytes(),
+            vec![0x01, 0xde, 0xad, 0xbe, 0xef, 0xca, 0xfe, 0xba, 0xbe, 0x00]
+        );
+        assert!(BigNum::zero().to_be_bytes().is_empty());
+        assert_eq!(
+            bignum!(0xbeef).to_be_bytes_with_length(4),
+            Some(vec![0, 0, 0xbe, 0xef])
+        );
+        assert_eq!(bignum!(0xbeef).to_be_bytes_with_length(1), None);
+    }
+
     #[test]
     fn test_from_be_bytes() {
         assert_eq!(
@@ -0,0 +1,115 @@
+//! [Montgomery multiplication](https://en.wikipedia.org/wiki/Montgomery_modular_multiplication)
+//!
+//! Numbers are represented as `x * R mod N` with `R = BASE^k`, where `k` is the number of digits in
+//! the modulus. This allows computing modular products without any divisions, which makes
+//! repeated multiplications (like in modular exponentiation) much faster.
+
+use super::{
+    arithmetic::{self, multiply_add},
+    BigNum, Digit,
+};
+
+pub(super) struct Montgomery<'a> {
+    /// The (odd) modulus, without leading zeros
+    modulus: &'a [Digit],
+
+    /// `-modulus^(-1) mod BASE`
+    modulus_inverse: Digit,
+}
+
+impl<'a> Montgomery<'a> {
+    /// # Panics
+    /// This function panics if the modulus is even.
+    #[must_use]
+    pub(super) fn new(modulus: &'a [Digit]) -> Self {
+        let modulus = arithmetic::trim(modulus);
+        assert!(
+            modulus.first().is_some_and(|digit| digit & 1 == 1),
+            "Montgomery reduction requires an odd modulus"
+        );
+
+        // Compute the inverse using newton iteration, every iteration doubles
+        // the number of correct bits. Since n * n = 1 mod 8 for all odd n, we start with 3 correct bits.
+        let mut inverse = modulus[0];
+        for _ in 0..5 {
+            inverse =
+                inverse.wrapping_mul((2 as Digit).wrapping_sub(modulus[0].wrapping_mul(inverse)));
+        }
+        debug_assert_eq!(modulus[0].wrapping_mul(inverse), 1);
+
+        Self {
+            modulus,
+            modulus_inverse: inverse.wrapping_neg(),
+        }
+    }
+
+    #[inline]
+    fn num_digits(&self) -> usize {
+        self.modulus.len()
+    }
+
+    /// Convert a number into montgomery form
+    #[must_use]
+    pub(super) fn enter(&self, value: &BigNum) -> Vec<Digit> {
+        let shifted = value << (self.num_digits() * Digit::BITS as usize);
+        let (_, mut remainder) = arithmetic::div_rem(shifted.digits(), self.modulus);
+        remainder.resize(self.num_digits(), 0);
+        remainder
+    }
+
+    /// Convert a number out of montgomery form
+    #[must_use]
+    pub(super) fn leave(&self, value: &[Digit]) -> BigNum {
+        let mut one = vec![0; self.num_digits()];
+        one[0] = 1;
+        BigNum::from_digits(self.multiply(value, &one))
+    }
+
+    /// The number `1` in montgomery form
+    #[must_use]
+    pub(super) fn one(&self) -> Vec<Digit> {
+        self.enter(&BigNum::from_digits(vec![1]))
+    }
+
+    /// Compute `a * b * R^(-1) mod N`
+    ///
+    /// Both operands must be in montgomery form. Uses the
+    /// *Coarsely Integrated Operand Scanning* method.
+    #[must_use]
+    pub(super) fn multiply(&self, a: &[Digit], b: &[Digit]) -> Vec<Digit> {
+        let k = self.num_digits();
+        let n = self.modulus;
+        debug_assert_eq!(a.len(), k);
+        debug_assert_eq!(b.len(), k);
+
+        let mut t = vec![0; k + 2];
+        for &a_i in a {
+            // t += a_i * b
+            let mut carry = 0;
+            for (t_j, &b_j) in t.iter_mut().zip(b) {
+                (*t_j, carry) = multiply_add(*t_j, a_i, b_j, carry);
+            }
+            let overflow;
+            (t[k], overflow) = t[k].overflowing_add(carry);
+            t[k + 1] = Digit::from(overflow);
+
+            // t = (t + m * N) / BASE, with m chosen such that the division is exact
+            let m = t[0].wrapping_mul(self.modulus_inverse);
+            let (_, mut carry) = multiply_add(t[0], m, n[0], 0);
+            for j in 1..k {
+                (t[j - 1], carry) = multiply_add(t[j], m, n[j], carry);
+            }
+            let overflow;
+            (t[k - 1], overflow) = t[k].overflowing_add(carry);
+            t[k] = t[k + 1] + Digit::from(overflow);
+        }
+
+        // The result is less than 2N
+        t.truncate(k + 1);
+        if arithmetic::compare(&t, n).is_ge() {
+            arithmetic::sub_assign(&mut t, n);
+        }
+        t.truncate(k);
+        t
+    }
+}
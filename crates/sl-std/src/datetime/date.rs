@@ -37,6 +37,10 @@ impl Year {
         Self(year)
     }
 
+    pub const fn value(&self) -> YearRange {
+        self.0
+    }
+
     /// <https://howardhinnant.github.io/date_algorithms.html#is_leap>
     pub const fn is_leap_year(&self) -> bool {
         self.0 % 4 == 0 && (self.0 % 100 != 0 || self.0 % 400 == 0)
@@ -66,6 +70,25 @@ impl Month {
         Self(index)
     }
 
+    /// Construct a month from its abbreviated english name, like `Jan`
+    ///
+    /// The comparison is case-insensitive.
+    pub fn from_name(name: &str) -> Option<Self> {
+        const NAMES: [&str; consts::MONTHS_PER_YEAR] = [
+            "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+        ];
+
+        NAMES
+            .iter()
+            .position(|month_name| month_name.eq_ignore_ascii_case(name))
+            .map(|index| Self(index as u8))
+    }
+
+    /// The numeric index of the month (0 based)
+    pub const fn index(&self) -> u8 {
+        self.0
+    }
+
     /// Return the number of days in the month
     pub const fn num_days(&self, is_leap_year: bool) -> u64 {
         if is_leap_year {
@@ -94,7 +117,7 @@ impl Month {
     }
 
     const fn from_internal(internal: u8) -> Self {
-        let civil_month_index = if internal < 11 {
+        let civil_month_index = if internal < 10 {
             internal + 2
         } else {
             internal - 10
@@ -118,6 +141,11 @@ impl Weekday {
         Self(index)
     }
 
+    /// The numeric index of the day within the week (0 based, starting at sunday)
+    pub const fn index(&self) -> u8 {
+        self.0
+    }
+
     pub const fn name(&self) -> &'static str {
         match self.0 {
             0 => "Sun",
@@ -166,19 +194,19 @@ impl Date {
 
     /// Return number of days since [Date::UNIX].
     ///
+    /// Dates before [Date::UNIX] produce a negative result.
+    ///
     /// <https://howardhinnant.github.io/date_algorithms.html#days_from_civil>
-    pub const fn days_since_unix(&self) -> u64 {
-        let era = self.year.div_euclid(400) as usize;
+    pub const fn days_since_unix(&self) -> i64 {
+        let era = self.year.div_euclid(400) as i64;
 
         // Offset into the 400 year cycle, in years
-        let era_year = self.year.rem_euclid(400) as usize;
+        let era_year = self.year.rem_euclid(400) as i64;
 
-        let day_of_year = (153 * self.month as usize + 2) / 5 + self.day as usize - 1;
+        let day_of_year = (153 * self.month as i64 + 2) / 5 + self.day as i64 - 1;
         let day_of_era = era_year * 365 + era_year / 4 - era_year / 100 + day_of_year;
 
-        let days_since_unix = era * consts::DAYS_PER_400_YEARS + day_of_era - 719468;
-
-        days_since_unix as u64
+        era * consts::DAYS_PER_400_YEARS as i64 + day_of_era - 719468
     }
 
     pub const fn year(&self) -> Year {
@@ -195,6 +223,11 @@ impl Date {
         Month::from_internal(self.month)
     }
 
+    /// The day within the month, starting at `1`
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
     pub const fn weekday(&self) -> Weekday {
         let days_since_unix = self.days_since_unix();
         let index = (days_since_unix + 4).rem_euclid(7);
@@ -226,7 +259,7 @@ const fn month_from_day_of_year(day: i32) -> i32 {
 const fn days_in_month_leap_year(month: u8) -> u64 {
     const N_DAYS_IN_MONTH_LEAP_YEAR: [u64; consts::MONTHS_PER_YEAR] =
         [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    N_DAYS_IN_MONTH_LEAP_YEAR[(month - 1) as usize]
+    N_DAYS_IN_MONTH_LEAP_YEAR[month as usize]
 }
 
 /// <https://howardhinnant.github.io/date_algorithms.html#last_day_of_month_common_year>
@@ -234,7 +267,7 @@ const fn days_in_month_leap_year(month: u8) -> u64 {
 const fn days_in_month_common_year(month: u8) -> u64 {
     const N_DAYS_IN_MONTH_COMMON_YEAR: [u64; consts::MONTHS_PER_YEAR] =
         [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
-    N_DAYS_IN_MONTH_COMMON_YEAR[(month - 1) as usize]
+    N_DAYS_IN_MONTH_COMMON_YEAR[month as usize]
 }
 #[cfg(test)]
 mod tests {
@@ -250,6 +283,44 @@ mod tests {
         assert_eq!(Date::UNIX.days_since_unix(), 0);
     }
 
+    #[test]
+    fn before_unix() {
+        let date = Date::from_ymd(Year(1969), Month::DECEMBER, 31);
+        assert_eq!(date.days_since_unix(), -1);
+        assert_eq!(Date::new_from_days_since_unix(-1), date);
+        assert_eq!(date.weekday(), Weekday::WEDNESDAY);
+    }
+
+    #[test]
+    fn negative_years() {
+        let date = Date::from_ymd(Year(-44), Month::MARCH, 15);
+        assert_eq!(
+            Date::new_from_days_since_unix(date.days_since_unix() as i32),
+            date
+        );
+        assert_eq!(date.year(), Year(-44));
+        assert_eq!(date.month().index(), 2);
+        assert_eq!(date.weekday().index(), 4);
+    }
+
+    #[test]
+    fn civil_month() {
+        assert_eq!(Date::UNIX.month(), Month::JANUARY);
+        assert_eq!(Date::UNIX.year(), Year(1970));
+
+        let date = Date::from_ymd(Year(2023), Month::DECEMBER, 1);
+        assert_eq!(date.month(), Month::DECEMBER);
+        assert_eq!(date.year(), Year(2023));
+    }
+
+    #[test]
+    fn num_days() {
+        assert_eq!(Month::JANUARY.num_days(false), 31);
+        assert_eq!(Month::FEBRUARY.num_days(false), 28);
+        assert_eq!(Month::FEBRUARY.num_days(true), 29);
+        assert_eq!(Month::DECEMBER.num_days(false), 31);
+    }
+
     #[test]
     fn weekday() {
         assert_eq!(Date::UNIX.weekday(), Weekday::THURSDAY);
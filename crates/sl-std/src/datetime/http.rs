@@ -0,0 +1,425 @@
+//! Parsing and formatting of the date formats used by
+//! [HTTP](https://www.rfc-editor.org/rfc/rfc7231#section-7.1.1.1) and
+//! [cookies](https://www.rfc-editor.org/rfc/rfc6265#section-5.1.1)
+
+use super::{
+    consts,
+    date::{Month, Year, YearRange},
+    Date, DateTime, ParseError, Time, Weekday,
+};
+
+impl DateTime {
+    /// Parse a date in any of the formats that HTTP recipients are required to accept
+    ///
+    /// These are:
+    /// * IMF-fixdate, like `Sun, 06 Nov 1994 08:49:37 GMT`
+    /// * the obsolete RFC 850 format, like `Sunday, 06-Nov-94 08:49:37 GMT`
+    /// * ANSI C's `asctime()` format, like `Sun Nov  6 08:49:37 1994`
+    ///
+    /// Numeric timezone offsets (like `+0200`) are accepted in place of `GMT`,
+    /// the result is always converted to UTC.
+    pub fn from_http_date(input: &str) -> Result<Self, ParseError> {
+        match input.split_once(',') {
+            Some((weekday, rest)) if weekday.len() == 3 => {
+                Self::from_imf_fixdate(Weekday::from_rfc822(weekday)?, rest)
+            },
+            Some((weekday, rest)) => Self::from_rfc850(Weekday::from_full_name(weekday)?, rest),
+            None => Self::from_asctime(input),
+        }
+    }
+
+    /// Format the date as an IMF-fixdate, like `Sun, 06 Nov 1994 08:49:37 GMT`
+    ///
+    /// This is the preferred format for dates in HTTP headers.
+    #[must_use]
+    pub fn to_http_date(self) -> String {
+        format!(
+            "{weekday}, {day:02} {month} {year:04} {hour:02}:{minute:02}:{second:02} GMT",
+            weekday = self.date.weekday().name(),
+            day = self.date.day(),
+            month = self.date.month().name(),
+            year = self.date.year().value(),
+            hour = self.time.hour(),
+            minute = self.time.minute(),
+            second = self.time.second(),
+        )
+    }
+
+    /// Parse a cookie date using the (very lenient) algorithm from
+    /// [RFC 6265](https://www.rfc-editor.org/rfc/rfc6265#section-5.1.1)
+    pub fn from_cookie_date(input: &str) -> Result<Self, ParseError> {
+        let mut time = None;
+        let mut day = None;
+        let mut month = None;
+        let mut year = None;
+
+        for token in input.split(is_cookie_date_delimiter) {
+            if token.is_empty() {
+                continue;
+            }
+
+            if time.is_none() {
+                if let Some(parsed_time) = parse_cookie_time(token) {
+                    time = Some(parsed_time);
+                    continue;
+                }
+            }
+
+            if day.is_none() {
+                if let Some(parsed_day) = leading_digits(token, 1, 2) {
+                    day = Some(parsed_day);
+                    continue;
+                }
+            }
+
+            if month.is_none() {
+                if let Some(parsed_month) = token.get(..3).and_then(Month::from_name) {
+                    month = Some(parsed_month);
+                    continue;
+                }
+            }
+
+            if year.is_none() {
+                if let Some(parsed_year) = leading_digits(token, 2, 4) {
+                    year = Some(parsed_year);
+                    continue;
+                }
+            }
+        }
+
+        let (hour, minute, second) = time.ok_or(ParseError::MissingTime)?;
+        let day = day.ok_or(ParseError::MissingDay)?;
+        let month = month.ok_or(ParseError::MissingMonth)?;
+        let year = expand_two_digit_year(year.ok_or(ParseError::MissingYear)?);
+
+        if year < 1601 {
+            return Err(ParseError::InvalidYear);
+        }
+
+        let time = time_from_hms(hour, minute, second)?;
+        build_datetime(year as YearRange, month, day, time, None)
+    }
+
+    fn from_imf_fixdate(weekday: Weekday, rest: &str) -> Result<Self, ParseError> {
+        let mut parts = rest.split_ascii_whitespace();
+
+        let day = parse_day(parts.next().ok_or(ParseError::MissingDay)?)?;
+        let month = parse_month(parts.next().ok_or(ParseError::MissingMonth)?)?;
+        let year = parse_year(parts.next().ok_or(ParseError::MissingYear)?)?;
+        let time = parse_time(parts.next().ok_or(ParseError::MissingTime)?)?;
+        let offset = parse_timezone(parts.next().ok_or(ParseError::MissingTimezone)?)?;
+
+        let local_time = build_datetime(year, month, day, time, Some(weekday))?;
+        Ok(local_time.to_utc(offset))
+    }
+
+    fn from_rfc850(weekday: Weekday, rest: &str) -> Result<Self, ParseError> {
+        let mut parts = rest.split_ascii_whitespace();
+
+        let mut date = parts.next().ok_or(ParseError::MissingDay)?.split('-');
+        let day = parse_day(date.next().ok_or(ParseError::MissingDay)?)?;
+        let month = parse_month(date.next().ok_or(ParseError::MissingMonth)?)?;
+        let year = parse_year(date.next().ok_or(ParseError::MissingYear)?)?;
+
+        let time = parse_time(parts.next().ok_or(ParseError::MissingTime)?)?;
+        let offset = parse_timezone(parts.next().ok_or(ParseError::MissingTimezone)?)?;
+
+        let local_time = build_datetime(year, month, day, time, Some(weekday))?;
+        Ok(local_time.to_utc(offset))
+    }
+
+    fn from_asctime(input: &str) -> Result<Self, ParseError> {
+        let mut parts = input.split_ascii_whitespace();
+
+        let weekday = Weekday::from_rfc822(parts.next().ok_or(ParseError::InvalidWeekday)?)?;
+        let month = parse_month(parts.next().ok_or(ParseError::MissingMonth)?)?;
+        let day = parse_day(parts.next().ok_or(ParseError::MissingDay)?)?;
+        let time = parse_time(parts.next().ok_or(ParseError::MissingTime)?)?;
+        let year = parse_year(parts.next().ok_or(ParseError::MissingYear)?)?;
+
+        // asctime dates are always in UTC
+        build_datetime(year, month, day, time, Some(weekday))
+    }
+
+    /// Convert a local time with the given offset (in seconds) to UTC
+    #[must_use]
+    fn to_utc(self, offset: i64) -> Self {
+        Self::from_signed_unix_timestamp(self.unix_timestamp() - offset)
+    }
+}
+
+fn build_datetime(
+    year: YearRange,
+    month: Month,
+    day: u8,
+    time: Time,
+    weekday: Option<Weekday>,
+) -> Result<DateTime, ParseError> {
+    let is_leap_year = Year::new(year).is_leap_year();
+    if day == 0 || u64::from(day) > month.num_days(is_leap_year) {
+        return Err(ParseError::InvalidDay);
+    }
+
+    let date = Date::from_ymd(Year::new(year), month, day);
+
+    if weekday.is_some_and(|weekday| weekday != Weekday::of(date)) {
+        return Err(ParseError::IncorrectWeekday);
+    }
+
+    Ok(DateTime { date, time })
+}
+
+fn parse_day(day: &str) -> Result<u8, ParseError> {
+    if !(1..=2).contains(&day.len()) {
+        return Err(ParseError::InvalidDay);
+    }
+
+    day.parse().map_err(|_| ParseError::InvalidDay)
+}
+
+fn parse_month(month: &str) -> Result<Month, ParseError> {
+    // Unlike cookie dates, HTTP dates are case-sensitive
+    Month::from_name(month)
+        .filter(|parsed_month| parsed_month.name() == month)
+        .ok_or(ParseError::InvalidMonth)
+}
+
+fn parse_year(year: &str) -> Result<YearRange, ParseError> {
+    if !year.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(ParseError::InvalidYear);
+    }
+
+    match year.len() {
+        2 | 4 => {
+            let year = year.parse().map_err(|_| ParseError::InvalidYear)?;
+            Ok(expand_two_digit_year(year) as YearRange)
+        },
+        _ => Err(ParseError::InvalidYear),
+    }
+}
+
+/// Parse a time in the format `hh:mm:ss`
+fn parse_time(time: &str) -> Result<Time, ParseError> {
+    let mut parts = time.split(':');
+
+    let mut next_component = |error| {
+        parts
+            .next()
+            .filter(|part| part.len() == 2)
+            .and_then(|part| part.parse().ok())
+            .ok_or(error)
+    };
+
+    let hour = next_component(ParseError::InvalidHour)?;
+    let minute = next_component(ParseError::InvalidMinute)?;
+    let second = next_component(ParseError::InvalidSecond)?;
+
+    time_from_hms(hour, minute, second)
+}
+
+fn time_from_hms(hour: u64, minute: u64, second: u64) -> Result<Time, ParseError> {
+    if 23 < hour {
+        return Err(ParseError::InvalidHour);
+    }
+    if 59 < minute {
+        return Err(ParseError::InvalidMinute);
+    }
+
+    // Leap seconds are not representable
+    Time::from_hms(hour, minute, second).ok_or(ParseError::InvalidSecond)
+}
+
+/// Parse a timezone and return its offset from UTC in seconds
+///
+/// Only numeric offsets and the timezones that denote UTC are supported.
+fn parse_timezone(timezone: &str) -> Result<i64, ParseError> {
+    if matches!(timezone, "GMT" | "UTC" | "UT" | "Z") {
+        return Ok(0);
+    }
+
+    let (sign, digits) = match timezone.as_bytes().first() {
+        Some(b'+') => (1, &timezone[1..]),
+        Some(b'-') => (-1, &timezone[1..]),
+        _ => return Err(ParseError::InvalidTimezone),
+    };
+
+    if digits.len() != 4 || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(ParseError::InvalidTimezone);
+    }
+
+    let hours: i64 = digits[..2]
+        .parse()
+        .map_err(|_| ParseError::InvalidTimezone)?;
+    let minutes: i64 = digits[2..]
+        .parse()
+        .map_err(|_| ParseError::InvalidTimezone)?;
+
+    if 59 < minutes {
+        return Err(ParseError::InvalidTimezone);
+    }
+
+    Ok(sign
+        * (hours * consts::SECONDS_PER_HOUR as i64 + minutes * consts::SECONDS_PER_MINUTE as i64))
+}
+
+/// Two digit years in the range `70..=99` are in the 20th century,
+/// the ones below that are in the 21st century.
+fn expand_two_digit_year(year: u64) -> u64 {
+    match year {
+        0..=69 => year + 2000,
+        70..=99 => year + 1900,
+        _ => year,
+    }
+}
+
+fn is_cookie_date_delimiter(c: char) -> bool {
+    matches!(c, '\x09' | '\x20'..='\x2F' | '\x3B'..='\x40' | '\x5B'..='\x60' | '\x7B'..='\x7E')
+}
+
+/// Parse between `min` and `max` leading digits, which may be followed by
+/// anything that is not a digit.
+fn leading_digits<T: std::str::FromStr>(token: &str, min: usize, max: usize) -> Option<T> {
+    let num_digits = token
+        .bytes()
+        .position(|byte| !byte.is_ascii_digit())
+        .unwrap_or(token.len());
+
+    if !(min..=max).contains(&num_digits) {
+        return None;
+    }
+
+    token[..num_digits].parse().ok()
+}
+
+/// Parse a time in the format `h:m:s`, where each component is one or two digits long
+fn parse_cookie_time(token: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = token.splitn(3, ':');
+
+    let hour = parts.next()?;
+    let minute = parts.next()?;
+    let second = parts.next()?;
+
+    // Only the seconds may be followed by trailing characters
+    if !(1..=2).contains(&hour.len()) || !(1..=2).contains(&minute.len()) {
+        return None;
+    }
+
+    Some((
+        leading_digits(hour, 1, 2)?,
+        leading_digits(minute, 1, 2)?,
+        leading_digits(second, 1, 2)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sunday_6th_of_november() -> DateTime {
+        DateTime::from_ymd_hms(1994, 10, 6, 8, 49, 37).unwrap()
+    }
+
+    #[test]
+    fn http_date_formats() {
+        let expected = sunday_6th_of_november();
+
+        assert_eq!(
+            DateTime::from_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Ok(expected)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            Ok(expected)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sun Nov  6 08:49:37 1994"),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn invalid_http_dates() {
+        assert_eq!(
+            DateTime::from_http_date("Mon, 06 Nov 1994 08:49:37 GMT"),
+            Err(ParseError::IncorrectWeekday)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sun, 31 Nov 1994 08:49:37 GMT"),
+            Err(ParseError::InvalidDay)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sun, 06 nov 1994 08:49:37 GMT"),
+            Err(ParseError::InvalidMonth)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sun, 06 Nov 1994 24:49:37 GMT"),
+            Err(ParseError::InvalidHour)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sun, 06 Nov 1994 08:49:37"),
+            Err(ParseError::MissingTimezone)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sun, 06 Nov 1994 08:49:37 CEST"),
+            Err(ParseError::InvalidTimezone)
+        );
+    }
+
+    #[test]
+    fn timezone_offsets() {
+        let expected = sunday_6th_of_november();
+
+        assert_eq!(
+            DateTime::from_http_date("Sun, 06 Nov 1994 10:19:37 +0130"),
+            Ok(expected)
+        );
+        assert_eq!(
+            DateTime::from_http_date("Sat, 05 Nov 1994 23:49:37 -0900"),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn format_http_date() {
+        assert_eq!(
+            sunday_6th_of_november().to_http_date(),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(
+            DateTime::from_unix_timestamp(0).to_http_date(),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+    }
+
+    #[test]
+    fn cookie_dates() {
+        let expected = sunday_6th_of_november();
+
+        for input in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+            "6 november 94 8:49:37",
+            "08:49:37 1994-nov-06",
+        ] {
+            assert_eq!(DateTime::from_cookie_date(input), Ok(expected), "{input}");
+        }
+
+        assert_eq!(
+            DateTime::from_cookie_date("Thu, 01-Jan-1970 00:00:01"),
+            Ok(DateTime::from_unix_timestamp(1))
+        );
+        assert_eq!(
+            DateTime::from_cookie_date("Sun, 06 Nov 08:49:37 GMT"),
+            Err(ParseError::MissingYear)
+        );
+        assert_eq!(
+            DateTime::from_cookie_date("Fri, 30 Feb 2024 08:49:37 GMT"),
+            Err(ParseError::InvalidDay)
+        );
+        assert_eq!(
+            DateTime::from_cookie_date("Mon, 06 Nov 1600 08:49:37 GMT"),
+            Err(ParseError::InvalidYear)
+        );
+    }
+}
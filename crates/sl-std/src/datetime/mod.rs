@@ -1,9 +1,13 @@
 //! Provides various Date and Time utilities
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    ops,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub mod consts;
 mod date;
+mod http;
 mod time;
 
 pub use date::Date;
@@ -37,6 +41,8 @@ pub enum ParseError {
     MissingTime,
     MissingHour,
     MissingMinute,
+    MissingTimezone,
+    InvalidTimezone,
     IncorrectWeekday,
 }
 
@@ -54,6 +60,25 @@ impl Weekday {
             _ => Err(ParseError::InvalidWeekday),
         }
     }
+
+    /// Parse the full english name of a [Weekday], like `Monday`
+    pub fn from_full_name(s: &str) -> Result<Self, ParseError> {
+        match s {
+            "Monday" => Ok(Self::Monday),
+            "Tuesday" => Ok(Self::Tuesday),
+            "Wednesday" => Ok(Self::Wednesday),
+            "Thursday" => Ok(Self::Thursday),
+            "Friday" => Ok(Self::Friday),
+            "Saturday" => Ok(Self::Saturday),
+            "Sunday" => Ok(Self::Sunday),
+            _ => Err(ParseError::InvalidWeekday),
+        }
+    }
+
+    #[must_use]
+    pub fn of(date: Date) -> Self {
+        Self::from_rfc822(date.weekday().name()).expect("weekday names are valid")
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -77,15 +102,41 @@ impl DateTime {
 
     #[must_use]
     pub const fn from_unix_timestamp(seconds: u64) -> Self {
-        let days = seconds / consts::SECONDS_PER_DAY as u64;
-        let seconds = seconds % consts::SECONDS_PER_DAY as u64;
+        Self::from_signed_unix_timestamp(seconds as i64)
+    }
+
+    /// Like [DateTime::from_unix_timestamp], but also allows points in time before [UNIX_EPOCH]
+    #[must_use]
+    pub const fn from_signed_unix_timestamp(seconds: i64) -> Self {
+        let days = seconds.div_euclid(consts::SECONDS_PER_DAY as i64);
+        let seconds = seconds.rem_euclid(consts::SECONDS_PER_DAY as i64);
 
         let date = Date::new_from_days_since_unix(days as i32);
-        let time = Time::new_from_n_seconds_since_midnight(seconds);
+        let time = Time::new_from_n_seconds_since_midnight(seconds as u64);
 
         Self { date, time }
     }
 
+    /// Return the number of seconds since [UNIX_EPOCH]
+    ///
+    /// Points in time before [UNIX_EPOCH] produce a negative result.
+    #[must_use]
+    pub const fn unix_timestamp(&self) -> i64 {
+        self.date.days_since_unix() * consts::SECONDS_PER_DAY as i64
+            + self.time.seconds_since_midnight() as i64
+    }
+
+    /// Return the amount of time that passed between `earlier` and `self`
+    ///
+    /// Returns `None` if `earlier` is actually later than `self`.
+    #[must_use]
+    pub fn duration_since(&self, earlier: Self) -> Option<Duration> {
+        let seconds = self
+            .unix_timestamp()
+            .checked_sub(earlier.unix_timestamp())?;
+        Some(Duration::from_secs(u64::try_from(seconds).ok()?))
+    }
+
     pub fn from_ymd_hms(
         year: u64,
         month: u8,
@@ -108,3 +159,69 @@ impl DateTime {
         self.time
     }
 }
+
+impl ops::Add<Duration> for DateTime {
+    type Output = Self;
+
+    /// Sub-second precision is discarded.
+    fn add(self, duration: Duration) -> Self::Output {
+        let seconds = i64::try_from(duration.as_secs()).expect("duration too large");
+        Self::from_signed_unix_timestamp(self.unix_timestamp() + seconds)
+    }
+}
+
+impl ops::Sub<Duration> for DateTime {
+    type Output = Self;
+
+    /// Sub-second precision is discarded.
+    fn sub(self, duration: Duration) -> Self::Output {
+        let seconds = i64::try_from(duration.as_secs()).expect("duration too large");
+        Self::from_signed_unix_timestamp(self.unix_timestamp() - seconds)
+    }
+}
+
+impl ops::AddAssign<Duration> for DateTime {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl ops::SubAssign<Duration> for DateTime {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_timestamp() {
+        for timestamp in [0, 1, 784111777, -1, -86401] {
+            assert_eq!(
+                DateTime::from_signed_unix_timestamp(timestamp).unix_timestamp(),
+                timestamp
+            );
+        }
+    }
+
+    #[test]
+    fn duration_arithmetic() {
+        let datetime = DateTime::from_ymd_hms(1994, 10, 6, 23, 59, 30).unwrap();
+        let later = datetime + Duration::from_secs(45);
+
+        assert_eq!(later.date().day(), 7);
+        assert_eq!(later.time(), Time::from_hms(0, 0, 15).unwrap());
+        assert_eq!(later - Duration::from_secs(45), datetime);
+
+        assert_eq!(
+            later.duration_since(datetime),
+            Some(Duration::from_secs(45))
+        );
+        assert_eq!(datetime.duration_since(later), None);
+
+        let before_unix = DateTime::from_unix_timestamp(10) - Duration::from_secs(20);
+        assert_eq!(before_unix.unix_timestamp(), -10);
+    }
+}
@@ -16,6 +16,7 @@
     assert_matches
 )]
 
+pub mod arena;
 pub mod ascii;
 pub mod assert;
 pub mod base64;
@@ -26,7 +27,9 @@ pub mod chars;
 pub mod datetime;
 pub mod fixed;
 pub mod iter;
+pub mod memory;
 pub mod oneshot;
+pub mod percent_encode;
 pub mod punycode;
 pub mod rand;
 pub mod range;
@@ -0,0 +1,132 @@
+//! Accounting of how much memory the different parts of the browser use
+//!
+//! Every subsystem that holds on to significant amounts of memory implements [MemoryReporter].
+//! The numbers are estimates - they include the buffers that a subsystem owns, but not
+//! the bookkeeping overhead of the allocator.
+
+use std::{collections::BTreeMap, fmt};
+
+/// Something that can describe how much memory it uses
+pub trait MemoryReporter {
+    /// Add the memory used by `self` to the report
+    fn report_memory(&self, report: &mut MemoryReport);
+}
+
+/// A collection of memory measurements
+///
+/// Each measurement is identified by a path like `"images/decoded"`, where the first
+/// segment names the subsystem that the memory belongs to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    entries: BTreeMap<String, usize>,
+}
+
+impl MemoryReport {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `bytes` to the measurement at `path`
+    ///
+    /// Recording the same path multiple times accumulates the sizes.
+    pub fn record(&mut self, path: &str, bytes: usize) {
+        *self.entries.entry(path.to_owned()).or_default() += bytes;
+    }
+
+    /// All measurements, sorted by their path
+    pub fn entries(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.entries
+            .iter()
+            .map(|(path, bytes)| (path.as_str(), *bytes))
+    }
+
+    /// The total memory used by each subsystem, sorted by name
+    #[must_use]
+    pub fn subsystems(&self) -> Vec<(&str, usize)> {
+        let mut subsystems: Vec<(&str, usize)> = vec![];
+
+        for (path, bytes) in self.entries() {
+            let subsystem = path
+                .split_once('/')
+                .map_or(path, |(subsystem, _)| subsystem);
+
+            match subsystems.last_mut() {
+                Some((name, total)) if *name == subsystem => *total += bytes,
+                _ => subsystems.push((subsystem, bytes)),
+            }
+        }
+
+        subsystems
+    }
+
+    /// The memory used by all subsystems combined
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.entries.values().sum()
+    }
+}
+
+impl fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Total: {}", format_size(self.total()))?;
+
+        for (path, bytes) in self.entries() {
+            writeln!(f, "  {path}: {}", format_size(bytes))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Describe a number of bytes in a human-readable way, like `"1.5 KiB"`
+#[must_use]
+pub fn format_size(bytes: usize) -> String {
+    const KIB: usize = 1024;
+    const MIB: usize = 1024 * KIB;
+
+    if bytes < KIB {
+        format!("{bytes} bytes")
+    } else if bytes < MIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(format_size(12), "12 bytes");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn aggregate_subsystems() {
+        let mut report = MemoryReport::new();
+        report.record("images/decoded", 100);
+        report.record("dom/elements", 20);
+        report.record("images/canvas", 50);
+        report.record("images/decoded", 10);
+        report.record("resources", 5);
+
+        assert_eq!(
+            report.entries().collect::<Vec<_>>(),
+            [
+                ("dom/elements", 20),
+                ("images/canvas", 50),
+                ("images/decoded", 110),
+                ("resources", 5)
+            ]
+        );
+        assert_eq!(
+            report.subsystems(),
+            [("dom", 20), ("images", 160), ("resources", 5)]
+        );
+        assert_eq!(report.total(), 185);
+    }
+}
@@ -179,6 +179,21 @@ impl<T> Drop for Sender<T> {
 }
 
 impl<T> Receiver<T> {
+    /// Whether [receive_blocking](Self::receive_blocking) would return immediately
+    ///
+    /// This is the case if a message was sent or the sender was dropped.
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        // SAFETY: The Sender is never going to deallocate the channel while
+        //         we're alive.
+        let channel = unsafe { self.channel.as_ref() };
+
+        matches!(
+            channel.state.load(Ordering::Acquire),
+            WAITING_FOR_RECEIVER | DONE
+        )
+    }
+
     pub fn receive_blocking(self) -> Result<T, ReceiveError> {
         // SAFETY: The Sender is never going to deallocate the channel while
         //         we're alive.
@@ -357,6 +372,16 @@ mod tests {
         assert_eq!(msg, 42);
     }
 
+    #[test]
+    fn poll_before_receiving() {
+        let (sender, receiver) = Channel::create();
+        assert!(!receiver.is_ready());
+
+        sender.send(42u8).unwrap();
+        assert!(receiver.is_ready());
+        assert_eq!(receiver.receive_blocking().unwrap(), 42);
+    }
+
     #[test]
     fn drop_receiver() {
         let (sender, receiver) = Channel::create();
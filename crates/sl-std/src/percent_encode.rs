@@ -0,0 +1,293 @@
+//! Percent-encoding as defined in the [URL Standard](https://url.spec.whatwg.org/#percent-encoded-bytes)
+//!
+//! Which bytes get encoded is controlled by an [EncodeSet]. Bytes outside of the
+//! ASCII range are always encoded.
+
+use error_derive::Error;
+
+use crate::ascii;
+
+/// A set of ASCII bytes that need to be percent-encoded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodeSet {
+    /// Bit `n` is set if the byte `n` is part of the set
+    bits: u128,
+}
+
+impl EncodeSet {
+    pub const EMPTY: Self = Self { bits: 0 };
+
+    /// <https://infra.spec.whatwg.org/#c0-control>
+    pub const C0_CONTROL: Self = Self::EMPTY.add_range(0x00, 0x1F);
+
+    /// <https://url.spec.whatwg.org/#c0-control-percent-encode-set>
+    pub const C0_CONTROL_PERCENT_ENCODE_SET: Self = Self::C0_CONTROL.add(0x7F);
+
+    /// <https://url.spec.whatwg.org/#fragment-percent-encode-set>
+    pub const FRAGMENT: Self = Self::C0_CONTROL_PERCENT_ENCODE_SET
+        .add(b' ')
+        .add(b'"')
+        .add(b'<')
+        .add(b'>')
+        .add(b'`');
+
+    /// <https://url.spec.whatwg.org/#query-percent-encode-set>
+    pub const QUERY: Self = Self::C0_CONTROL_PERCENT_ENCODE_SET
+        .add(b' ')
+        .add(b'"')
+        .add(b'#')
+        .add(b'<')
+        .add(b'>');
+
+    /// <https://url.spec.whatwg.org/#special-query-percent-encode-set>
+    pub const SPECIAL_QUERY: Self = Self::QUERY.add(b'\'');
+
+    /// <https://url.spec.whatwg.org/#path-percent-encode-set>
+    pub const PATH: Self = Self::QUERY.add(b'?').add(b'`').add(b'{').add(b'}');
+
+    /// <https://url.spec.whatwg.org/#userinfo-percent-encode-set>
+    pub const USERINFO: Self = Self::PATH
+        .add(b'/')
+        .add(b':')
+        .add(b';')
+        .add(b'=')
+        .add(b'@')
+        .add_range(b'[', b'^')
+        .add(b'|');
+
+    /// <https://url.spec.whatwg.org/#component-percent-encode-set>
+    pub const COMPONENT: Self = Self::USERINFO.add_range(b'$', b'&').add(b'+').add(b',');
+
+    /// <https://url.spec.whatwg.org/#application-x-www-form-urlencoded-percent-encode-set>
+    pub const FORM_URLENCODED: Self = Self::COMPONENT.add(b'!').add_range(b'\'', b')').add(b'~');
+
+    /// Add a single byte to the set
+    ///
+    /// # Panics
+    /// This function panics if the byte is not ASCII.
+    #[must_use]
+    pub const fn add(self, byte: u8) -> Self {
+        assert!(byte.is_ascii());
+
+        Self {
+            bits: self.bits | (1 << byte),
+        }
+    }
+
+    /// Add all bytes from `start` to `end` (inclusive) to the set
+    #[must_use]
+    pub const fn add_range(mut self, start: u8, end: u8) -> Self {
+        let mut byte = start;
+        while byte <= end {
+            self = self.add(byte);
+            byte += 1;
+        }
+
+        self
+    }
+
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    /// Whether or not the byte needs to be percent-encoded
+    #[inline]
+    #[must_use]
+    pub const fn contains(&self, byte: u8) -> bool {
+        !byte.is_ascii() || self.bits & (1 << byte) != 0
+    }
+}
+
+/// Controls how [percent_decode_utf8] treats decoded data that is not valid UTF-8
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Utf8Validation {
+    /// Invalid UTF-8 is an error
+    Strict,
+
+    /// Invalid sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`
+    Lossy,
+}
+
+#[derive(Clone, Copy, Debug, Error)]
+pub enum DecodeError {
+    #[msg = "decoded data is not valid utf-8"]
+    InvalidUtf8,
+}
+
+/// <https://url.spec.whatwg.org/#string-percent-encode-after-encoding>
+pub fn percent_encode<W: ascii::Write>(input: &[u8], encode_set: EncodeSet, writer: &mut W) {
+    for &byte in input {
+        match ascii::Char::from_u8(byte) {
+            Some(c) if !encode_set.contains(byte) => writer.write_char(c),
+            _ => percent_encode_byte(byte, writer),
+        }
+    }
+}
+
+/// <https://url.spec.whatwg.org/#percent-encode>
+#[inline]
+fn percent_encode_byte<W: ascii::Write>(byte: u8, writer: &mut W) {
+    const HEX_DIGITS: [ascii::Char; 16] = [
+        ascii::Char::Digit0,
+        ascii::Char::Digit1,
+        ascii::Char::Digit2,
+        ascii::Char::Digit3,
+        ascii::Char::Digit4,
+        ascii::Char::Digit5,
+        ascii::Char::Digit6,
+        ascii::Char::Digit7,
+        ascii::Char::Digit8,
+        ascii::Char::Digit9,
+        ascii::Char::CapitalA,
+        ascii::Char::CapitalB,
+        ascii::Char::CapitalC,
+        ascii::Char::CapitalD,
+        ascii::Char::CapitalE,
+        ascii::Char::CapitalF,
+    ];
+
+    let chars = &[
+        ascii::Char::PercentSign,
+        HEX_DIGITS[(byte / 16) as usize],
+        HEX_DIGITS[(byte % 16) as usize],
+    ];
+    writer.write_str(ascii::Str::from_ascii_chars(chars));
+}
+
+/// <https://url.spec.whatwg.org/#percent-decode>
+#[must_use]
+pub fn percent_decode(encoded: &ascii::Str) -> Vec<u8> {
+    let decode = |first: ascii::Char, second: ascii::Char| {
+        let value = first.to_char().to_digit(16)? * 16 + second.to_char().to_digit(16)?;
+
+        // Truncating to a u8 is safe here because we only read two hex digits -> 0xFF max
+        Some(value as u8)
+    };
+
+    // 1. Let output be an empty byte sequence.
+    let mut result = Vec::with_capacity(encoded.len());
+
+    // 2. For each byte byte in input:
+    let chars = encoded.chars();
+    let mut i = 0;
+    while i < chars.len() {
+        // If byte is 0x25 (%) and followed by two hex digits, append the byte they encode.
+        // Otherwise, append byte to output.
+        let decoded = if chars[i] == ascii::Char::PercentSign && i + 2 < chars.len() {
+            decode(chars[i + 1], chars[i + 2])
+        } else {
+            None
+        };
+
+        if let Some(c) = decoded {
+            result.push(c);
+            i += 2;
+        } else {
+            result.push(chars[i].to_u8());
+        }
+        i += 1;
+    }
+
+    result
+}
+
+/// Percent-decodes the input and interprets the result as UTF-8
+pub fn percent_decode_utf8(
+    encoded: &ascii::Str,
+    validation: Utf8Validation,
+) -> Result<String, DecodeError> {
+    let decoded = percent_decode(encoded);
+
+    match validation {
+        Utf8Validation::Strict => String::from_utf8(decoded).map_err(|_| DecodeError::InvalidUtf8),
+        Utf8Validation::Lossy => Ok(String::from_utf8_lossy(&decoded).into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_encode_byte() {
+        // Examples from
+        // https://url.spec.whatwg.org/#example-percent-encode-operations
+
+        let mut buffer = ascii::String::default();
+        percent_encode_byte(0x23, &mut buffer);
+        assert_eq!(buffer.as_str(), "%23");
+
+        buffer.clear();
+        percent_encode_byte(0x7F, &mut buffer);
+        assert_eq!(buffer.as_str(), "%7F");
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        // Examples from
+        // https://url.spec.whatwg.org/#example-percent-encode-operations
+        let mut buffer = ascii::String::default();
+        percent_encode("≡".as_bytes(), EncodeSet::USERINFO, &mut buffer);
+        assert_eq!(buffer, "%E2%89%A1");
+
+        buffer.clear();
+        percent_encode("‽".as_bytes(), EncodeSet::USERINFO, &mut buffer);
+        assert_eq!(buffer, "%E2%80%BD");
+
+        buffer.clear();
+        percent_encode("Say what‽".as_bytes(), EncodeSet::USERINFO, &mut buffer);
+        assert_eq!(buffer, "Say%20what%E2%80%BD");
+
+        buffer.clear();
+        percent_encode(b"a+b c~", EncodeSet::FORM_URLENCODED, &mut buffer);
+        assert_eq!(buffer, "a%2Bb%20c%7E");
+    }
+
+    #[test]
+    fn encode_sets() {
+        assert!(EncodeSet::C0_CONTROL.contains(0x00));
+        assert!(!EncodeSet::C0_CONTROL.contains(0x7F));
+        assert!(EncodeSet::C0_CONTROL_PERCENT_ENCODE_SET.contains(0x7F));
+        assert!(EncodeSet::C0_CONTROL.contains(0x80));
+
+        assert!(EncodeSet::QUERY.contains(b'#'));
+        assert!(!EncodeSet::QUERY.contains(b'\''));
+        assert!(EncodeSet::SPECIAL_QUERY.contains(b'\''));
+        assert!(EncodeSet::FRAGMENT.contains(b'`'));
+        assert!(!EncodeSet::PATH.contains(b'/'));
+        assert!(EncodeSet::USERINFO.contains(b'/'));
+
+        let set = EncodeSet::EMPTY.add(b'a').union(EncodeSet::EMPTY.add(b'b'));
+        assert!(set.contains(b'a'));
+        assert!(set.contains(b'b'));
+        assert!(!set.contains(b'c'));
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        // Examples from
+        // https://url.spec.whatwg.org/#example-percent-encode-operations
+        let encoded = "%25%s%1G".try_into().unwrap();
+        let decoded = percent_decode(encoded);
+        assert_eq!(decoded, b"%%s%1G");
+    }
+
+    #[test]
+    fn decode_utf8() {
+        let encoded = "%E2%80%BD".try_into().unwrap();
+        assert_eq!(
+            percent_decode_utf8(encoded, Utf8Validation::Strict).unwrap(),
+            "‽"
+        );
+
+        let invalid = "a%FF".try_into().unwrap();
+        assert!(percent_decode_utf8(invalid, Utf8Validation::Strict).is_err());
+        assert_eq!(
+            percent_decode_utf8(invalid, Utf8Validation::Lossy).unwrap(),
+            "a\u{FFFD}"
+        );
+    }
+}
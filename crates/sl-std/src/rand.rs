@@ -1,4 +1,42 @@
 //! Random Number Generation
+//!
+//! There are two kinds of generators available:
+//! * [ChaChaRng], a cryptographically secure generator that can be seeded from the operating system
+//! * [RNG], a fast generator with a fixed seed. Its output is reproducible, which makes it useful
+//!   for tests, but it must never be used for anything security-related.
+
+use std::io;
+
+use ciphers::ChaCha20;
+
+/// A source of random numbers
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() & u32::MAX as u64) as u32
+    }
+
+    fn next_u16(&mut self) -> u16 {
+        (self.next_u64() & u16::MAX as u64) as u16
+    }
+
+    fn next_u8(&mut self) -> u8 {
+        (self.next_u64() & u8::MAX as u64) as u8
+    }
+
+    fn fill_bytes(&mut self, buffer: &mut [u8]) {
+        for chunk in buffer.chunks_mut(8) {
+            let random_bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&random_bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// A random number generator that is suitable for cryptographic use
+///
+/// This is a marker trait, no functionality beyond [Rng] is required.
+pub trait CryptoRng: Rng {}
 
 /// Implements the [XOR-Shift Algorithm](https://en.wikipedia.org/wiki/Xorshift)
 pub struct RNG {
@@ -6,30 +44,169 @@ pub struct RNG {
 }
 
 impl RNG {
-    pub fn next_u64(&mut self) -> u64 {
+    /// Create a generator that produces a reproducible sequence of numbers
+    #[must_use]
+    pub fn with_seed(seed: u64) -> Self {
+        // XOR-Shift gets stuck when the state is zero
+        if seed == 0 {
+            return Self::default();
+        }
+
+        Self { state: seed }
+    }
+}
+
+impl Rng for RNG {
+    fn next_u64(&mut self) -> u64 {
         self.state ^= self.state << 13;
         self.state ^= self.state >> 7;
         self.state ^= self.state << 17;
         self.state
     }
+}
 
-    pub fn next_u32(&mut self) -> u32 {
-        (self.next_u64() & u32::MAX as u64) as u32
+impl Default for RNG {
+    fn default() -> Self {
+        Self {
+            state: 0xcafebabedeadbeef,
+        }
     }
+}
 
-    pub fn next_u16(&mut self) -> u16 {
-        (self.next_u64() & u16::MAX as u64) as u16
+/// A cryptographically secure random number generator based on the
+/// [ChaCha20](https://datatracker.ietf.org/doc/html/rfc7539) stream cipher
+pub struct ChaChaRng {
+    key: [u8; 32],
+    block_count: u32,
+    buffer: [u8; 64],
+    buffer_ptr: usize,
+}
+
+impl ChaChaRng {
+    /// Create a generator from a fixed seed
+    ///
+    /// The generated sequence is fully determined by the seed, so the seed must be kept secret.
+    #[must_use]
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self {
+            key: seed,
+            block_count: 0,
+            buffer: [0; 64],
+            buffer_ptr: 64,
+        }
     }
 
-    pub fn next_u8(&mut self) -> u8 {
-        (self.next_u64() & u8::MAX as u64) as u8
+    /// Create a generator that is seeded from the operating system's entropy source
+    pub fn from_entropy() -> io::Result<Self> {
+        let mut seed = [0; 32];
+        fill_from_os(&mut seed)?;
+        Ok(Self::from_seed(seed))
+    }
+
+    fn refill_buffer(&mut self) {
+        if self.block_count == u32::MAX {
+            // The counter is about to wrap around, derive a fresh key before any
+            // block could be repeated
+            let block = ChaCha20::block(self.key, [0; 12], self.block_count);
+            self.key.copy_from_slice(&block[..32]);
+            self.block_count = 0;
+        }
+
+        self.buffer = ChaCha20::block(self.key, [0; 12], self.block_count);
+        self.block_count += 1;
+        self.buffer_ptr = 0;
     }
 }
 
-impl Default for RNG {
-    fn default() -> Self {
-        Self {
-            state: 0xcafebabedeadbeef,
+impl Rng for ChaChaRng {
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, mut buffer: &mut [u8]) {
+        while !buffer.is_empty() {
+            if self.buffer_ptr == self.buffer.len() {
+                self.refill_buffer();
+            }
+
+            let available = &self.buffer[self.buffer_ptr..];
+            let n = available.len().min(buffer.len());
+            buffer[..n].copy_from_slice(&available[..n]);
+
+            self.buffer_ptr += n;
+            buffer = &mut buffer[n..];
+        }
+    }
+}
+
+impl CryptoRng for ChaChaRng {}
+
+cfg_match! {
+    cfg(unix) => {
+        fn fill_from_os(buffer: &mut [u8]) -> io::Result<()> {
+            use std::io::Read;
+
+            std::fs::File::open("/dev/urandom")?.read_exact(buffer)
         }
     }
+    _ => {
+        fn fill_from_os(buffer: &mut [u8]) -> io::Result<()> {
+            use std::{
+                collections::hash_map::RandomState,
+                hash::BuildHasher,
+            };
+
+            // The standard library seeds the keys of its hashers using the
+            // operating system's random number generator
+            let state = RandomState::new();
+            for (index, chunk) in buffer.chunks_mut(8).enumerate() {
+                let random_bytes = state.hash_one(index).to_le_bytes();
+                chunk.copy_from_slice(&random_bytes[..chunk.len()]);
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic_rng() {
+        let mut a = RNG::with_seed(42);
+        let mut b = RNG::with_seed(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+
+        // A zero seed would only ever produce zeros
+        assert_ne!(RNG::with_seed(0).next_u64(), 0);
+    }
+
+    #[test]
+    fn chacha_rng_keystream() {
+        // The output must match the ChaCha20 keystream with a zero nonce
+        let mut rng = ChaChaRng::from_seed([0x42; 32]);
+
+        let mut output = [0; 100];
+        rng.fill_bytes(&mut output[..3]);
+        rng.fill_bytes(&mut output[3..]);
+
+        let mut expected = ChaCha20::block([0x42; 32], [0; 12], 0).to_vec();
+        expected.extend_from_slice(&ChaCha20::block([0x42; 32], [0; 12], 1));
+        assert_eq!(output, expected[..100]);
+    }
+
+    #[test]
+    fn chacha_rng_from_entropy() {
+        let mut a = ChaChaRng::from_entropy().unwrap();
+        let mut b = ChaChaRng::from_entropy().unwrap();
+
+        assert_ne!(a.next_u64(), b.next_u64());
+    }
 }
@@ -1,10 +1,13 @@
 use std::{fmt, net};
 
-use sl_std::{ascii, punycode};
+use sl_std::{
+    ascii,
+    percent_encode::{percent_encode, EncodeSet},
+    punycode,
+};
 
 use crate::{
     ip::{ipv4_parse, ipv6_parse},
-    percent_encode::{is_c0_control, percent_encode, C0_CONTROL},
     AsciiSet, IPParseError,
 };
 
@@ -30,7 +33,7 @@ const FORBIDDEN_HOST_CODE_POINTS: AsciiSet = AsciiSet::EMPTY
 
 /// <https://url.spec.whatwg.org/#forbidden-domain-code-point>
 const FORBIDDEN_DOMAIN_CODE_POINTS: AsciiSet = FORBIDDEN_HOST_CODE_POINTS
-    .merge(C0_CONTROL)
+    .merge(AsciiSet::C0_CONTROL)
     .add(ascii::Char::PercentSign)
     .add(ascii::Char::Delete);
 
@@ -162,6 +165,10 @@ fn opaque_host_parse(input: &str) -> Result<ascii::String, HostParseError> {
     // Return the result of running UTF-8 percent-encode on input
     // using the C0 control percent-encode set.
     let mut percent_encoded = ascii::String::with_capacity(input.len());
-    percent_encode(input.as_bytes(), is_c0_control, &mut percent_encoded);
+    percent_encode(
+        input.as_bytes(),
+        EncodeSet::C0_CONTROL_PERCENT_ENCODE_SET,
+        &mut percent_encoded,
+    );
     Ok(percent_encoded)
 }
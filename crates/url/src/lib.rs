@@ -21,16 +21,17 @@
 
 mod host;
 mod ip;
+mod origin;
 mod parser;
 mod path;
-mod percent_encode;
 mod set;
+mod setters;
 mod url;
 mod util;
 
 pub use crate::ip::IPParseError;
 pub use crate::url::*;
 pub use host::Host;
+pub use origin::{OpaqueOrigin, Origin};
 pub use path::PathSegments;
-pub use percent_encode::{percent_decode, percent_encode};
 use set::AsciiSet;
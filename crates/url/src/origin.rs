@@ -0,0 +1,192 @@
+//! <https://html.spec.whatwg.org/multipage/browsers.html#origin>
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use sl_std::ascii;
+
+use crate::{Host, Port, URL};
+
+/// Used to tell opaque origins apart
+static NEXT_OPAQUE_ORIGIN_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The security context that a [URL] belongs to
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/browsers.html#concept-origin)
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Origin {
+    /// An internal value that is only same-origin with itself (and its clones)
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/browsers.html#concept-origin-opaque)
+    Opaque(OpaqueOrigin),
+
+    /// [Specification](https://html.spec.whatwg.org/multipage/browsers.html#concept-origin-tuple)
+    Tuple {
+        scheme: ascii::String,
+        host: Host,
+        port: Option<Port>,
+    },
+}
+
+/// A unique identifier for an opaque [Origin]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OpaqueOrigin(usize);
+
+impl OpaqueOrigin {
+    /// Create an opaque origin that is different from all other opaque origins
+    #[must_use]
+    pub fn new() -> Self {
+        Self(NEXT_OPAQUE_ORIGIN_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for OpaqueOrigin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Origin {
+    #[must_use]
+    pub fn new_opaque() -> Self {
+        Self::Opaque(OpaqueOrigin::new())
+    }
+
+    #[must_use]
+    pub fn is_opaque(&self) -> bool {
+        matches!(self, Self::Opaque(_))
+    }
+
+    /// [Specification](https://html.spec.whatwg.org/multipage/browsers.html#same-origin)
+    #[must_use]
+    pub fn is_same_origin(&self, other: &Self) -> bool {
+        // Two origins, A and B, are said to be same origin if either
+        // 1. A and B are the same opaque origin, or
+        // 2. A and B are both tuple origins and their schemes, hosts, and port are identical.
+        self == other
+    }
+}
+
+impl URL {
+    /// [Specification](https://url.spec.whatwg.org/#concept-url-origin)
+    #[must_use]
+    pub fn origin(&self) -> Origin {
+        match self.scheme().as_str() {
+            "blob" => {
+                // FIXME: If url’s blob URL entry is non-null, then return url’s blob URL entry’s environment’s origin.
+
+                // 2. Let pathURL be the result of parsing the result of URL path serializing url.
+                // 3. If pathURL is failure, then return a new opaque origin.
+                let Ok(path_url) = self.path().as_str().parse::<Self>() else {
+                    return Origin::new_opaque();
+                };
+
+                // 4. If pathURL’s scheme is "http", "https", or "file", then return pathURL’s origin.
+                // NOTE: file urls have opaque origins, so there is no need to special-case them
+                if matches!(path_url.scheme().as_str(), "http" | "https") {
+                    return path_url.origin();
+                }
+
+                // 5. Return a new opaque origin.
+                Origin::new_opaque()
+            },
+            "ftp" | "http" | "https" | "ws" | "wss" => {
+                // NOTE: The parser does not remove explicitly specified default ports yet,
+                //       which would make "https://a.com:443" cross-origin with "https://a.com"
+                let port = self
+                    .port()
+                    .filter(|&port| Some(port) != self.default_port());
+
+                // Return the tuple origin (url’s scheme, url’s host, url’s port, null).
+                Origin::Tuple {
+                    scheme: self.scheme().to_owned(),
+                    host: self.host().cloned().unwrap_or(Host::EmptyHost),
+                    port,
+                }
+            },
+            _ => {
+                // NOTE: The spec leaves the origin of "file" urls up to the implementation,
+                //       we make them opaque like most other browsers do.
+                Origin::new_opaque()
+            },
+        }
+    }
+}
+
+impl fmt::Display for Origin {
+    // <https://html.spec.whatwg.org/multipage/browsers.html#ascii-serialisation-of-an-origin>
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Opaque(_) => {
+                // 1. If origin is an opaque origin, then return "null".
+                "null".fmt(f)
+            },
+            Self::Tuple { scheme, host, port } => {
+                // 2. Otherwise, let result be origin's scheme.
+                // 3. Append "://" to result.
+                // 4. Append origin's host, serialized, to result.
+                write!(f, "{scheme}://{host}")?;
+
+                // 5. If origin's port is non-null, append a U+003A COLON character (:), and origin's port, serialized, to result.
+                if let Some(port) = port {
+                    write!(f, ":{port}")?;
+                }
+
+                // 6. Return result.
+                Ok(())
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(url: &str) -> Origin {
+        url.parse::<URL>().unwrap().origin()
+    }
+
+    #[test]
+    fn tuple_origins() {
+        assert!(
+            origin("https://example.com/a?b#c").is_same_origin(&origin("https://example.com/d"))
+        );
+        assert!(origin("https://example.com:443/").is_same_origin(&origin("https://example.com/")));
+        assert!(!origin("https://example.com/").is_same_origin(&origin("http://example.com/")));
+        assert!(!origin("https://example.com/").is_same_origin(&origin("https://a.example.com/")));
+        assert!(!origin("http://example.com:8080/").is_same_origin(&origin("http://example.com/")));
+    }
+
+    #[test]
+    fn opaque_origins() {
+        let opaque = origin("data:text/plain,hello");
+        assert!(opaque.is_opaque());
+        assert!(opaque.is_same_origin(&opaque.clone()));
+        assert!(!opaque.is_same_origin(&origin("data:text/plain,hello")));
+        assert!(origin("file:///tmp/index.html").is_opaque());
+    }
+
+    #[test]
+    fn blob_origins() {
+        assert!(
+            origin("blob:https://example.com/uuid").is_same_origin(&origin("https://example.com/"))
+        );
+        assert!(origin("blob:ftp://example.com/uuid").is_opaque());
+    }
+
+    #[test]
+    fn serialize() {
+        assert_eq!(
+            origin("https://example.com/path").to_string(),
+            "https://example.com"
+        );
+        assert_eq!(
+            origin("http://[::1]:8080/path").to_string(),
+            "http://[::1]:8080"
+        );
+        assert_eq!(origin("about:blank").to_string(), "null");
+    }
+}
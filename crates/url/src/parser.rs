@@ -3,15 +3,15 @@
 //! The spec defines a complicated state machine that unfortunately doesn't translate
 //! well into actual code, which is why we don't adhere to the spec as closely here.
 
-use sl_std::{ascii, chars::ReversibleCharIterator};
+use sl_std::{
+    ascii,
+    chars::ReversibleCharIterator,
+    percent_encode::{percent_encode, EncodeSet},
+};
 
 use crate::{
     host::{self, HostParseError},
     is_special_scheme,
-    percent_encode::{
-        is_c0_percent_encode_set, is_fragment_percent_encode_set, is_query_percent_encode_set,
-        is_special_query_percent_encode_set, is_userinfo_percent_encode_set, percent_encode,
-    },
     util::{is_double_dot_path_segment, is_single_dot_path_segment, is_windows_drive_letter},
     URL,
 };
@@ -152,7 +152,7 @@ impl<'a> Parser<'a> {
                 c.encode_utf8(&mut buffer);
                 percent_encode(
                     &buffer[..c.len_utf8()],
-                    is_userinfo_percent_encode_set,
+                    EncodeSet::USERINFO,
                     &mut self.url.serialization,
                 );
             }
@@ -233,13 +233,18 @@ impl<'a> Parser<'a> {
         let port_str = &self.input.remaining()[..end_of_port];
 
         let port: u16 = port_str.parse().map_err(|_| Error::InvalidPort)?;
-        self.url.port = Some(port);
 
-        let port_str: &ascii::Str = port_str
-            .try_into()
-            .expect("port numbers are always valid ascii");
+        // The default port of the scheme is omitted
+        if self.url.default_port() != Some(port) {
+            self.url.port = Some(port);
+
+            let port_str: &ascii::Str = port_str
+                .try_into()
+                .expect("port numbers are always valid ascii");
 
-        self.url.serialization.push_str(port_str);
+            self.url.serialization.push(ascii::Char::Colon);
+            self.url.serialization.push_str(port_str);
+        }
 
         self.input
             .set_position(self.input.position() + port_str.len());
@@ -308,7 +313,7 @@ impl<'a> Parser<'a> {
                 c.encode_utf8(&mut buffer);
                 percent_encode(
                     &buffer[..c.len_utf8()],
-                    is_userinfo_percent_encode_set,
+                    EncodeSet::PATH,
                     &mut self.url.serialization,
                 );
             }
@@ -365,7 +370,7 @@ impl<'a> Parser<'a> {
                 c.encode_utf8(&mut buffer);
                 percent_encode(
                     &buffer[..c.len_utf8()],
-                    is_c0_percent_encode_set,
+                    EncodeSet::C0_CONTROL_PERCENT_ENCODE_SET,
                     &mut self.url.serialization,
                 );
             }
@@ -392,15 +397,13 @@ impl<'a> Parser<'a> {
             let base_fragment_start = base
                 .offsets
                 .fragment_start
-                .unwrap_or(base.serialization.len());
+                .map_or(base.serialization.len(), |start| start - 1);
             self.url.serialization.clear();
             self.url
                 .serialization
                 .push_str(&base.serialization[..base_fragment_start]);
 
             self.url.offsets = base.offsets;
-            self.url.offsets.fragment_start = Some(self.url.serialization.len());
-            self.url.serialization.push(ascii::Char::NumberSign);
 
             // and set state to fragment state.
             return self.parse_fragment();
@@ -424,15 +427,15 @@ impl<'a> Parser<'a> {
         self.url.offsets.query_start = Some(self.url.serialization.len());
 
         let percent_encode_set = if self.url.is_special() {
-            is_special_query_percent_encode_set
+            EncodeSet::SPECIAL_QUERY
         } else {
-            is_query_percent_encode_set
+            EncodeSet::QUERY
         };
 
         let query_start = self.input.position();
         while let Some(c) = self.input.next() {
             if c == '#' {
-                let buffer = &self.input.source()[query_start..self.input.position()];
+                let buffer = &self.input.source()[query_start..self.input.position() - 1];
 
                 percent_encode(
                     buffer.as_bytes(),
@@ -466,7 +469,7 @@ impl<'a> Parser<'a> {
 
         percent_encode(
             buffer.as_bytes(),
-            is_fragment_percent_encode_set,
+            EncodeSet::FRAGMENT,
             &mut self.url.serialization,
         );
 
@@ -490,7 +493,7 @@ impl<'a> Parser<'a> {
         let base_fragment_end = base
             .offsets
             .fragment_start
-            .unwrap_or(base.serialization.len());
+            .map_or(base.serialization.len(), |start| start - 1);
         let username_to_query = &base.serialization[base.offsets.scheme_end + 1..base_fragment_end];
         self.url.serialization.push_str(username_to_query);
         self.url.host = base.host.clone();
@@ -500,14 +503,19 @@ impl<'a> Parser<'a> {
 
         match c {
             Some('?') => {
+                self.input.next();
                 if let Some(query_start) = self.url.offsets.query_start {
-                    self.url.serialization.truncate(query_start);
+                    self.url.serialization.truncate(query_start - 1);
                 }
 
                 self.parse_query()
             },
-            Some('#') => self.parse_fragment(),
+            Some('#') => {
+                self.input.next();
+                self.parse_fragment()
+            },
             Some(_) => {
+                self.url.offsets.query_start = None;
                 self.url.shorten_path();
                 self.url.offsets.path_start = self.url.serialization.len();
                 self.parse_path()
@@ -36,6 +36,10 @@ impl AsciiSet {
 
     pub const ALPHANUMERIC: Self = Self::ALPHA.merge(Self::NUMERIC);
 
+    /// <https://infra.spec.whatwg.org/#c0-control>
+    pub const C0_CONTROL: Self =
+        Self::from_range_inclusive(ascii::Char::Null, ascii::Char::InformationSeparatorOne);
+
     #[must_use]
     pub const fn from_range_inclusive(start: ascii::Char, end: ascii::Char) -> Self {
         let mut set = Self::EMPTY;
@@ -0,0 +1,277 @@
+//! Modify individual components of a [URL]
+//!
+//! The specification implements the setters by running the basic url parser with a state
+//! override. Our parser does not support state overrides, so instead the changed serialization
+//! is parsed again. Characters that would start a different component are escaped first,
+//! which makes the result the same in almost all cases.
+//!
+//! <https://url.spec.whatwg.org/#urlutils-members>
+
+use crate::{parser, Error, ExcludeFragment, URL};
+
+impl URL {
+    /// Replace the whole url, failing if `value` is not a valid url
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-href)
+    pub fn set_href(&mut self, value: &str) -> Result<(), Error> {
+        *self = value.parse()?;
+        Ok(())
+    }
+
+    /// Change the scheme, like `https` or `https:`
+    ///
+    /// Special schemes (like `http`) can't be replaced with non-special schemes and vice versa,
+    /// in which case the url does not change.
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-protocol)
+    pub fn set_protocol(&mut self, value: &str) -> Result<(), Error> {
+        let scheme = value.split(':').next().unwrap_or_default();
+
+        // https://url.spec.whatwg.org/#scheme-state
+        let mut chars = scheme.chars();
+        let is_valid_scheme = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !is_valid_scheme {
+            return Err(Error::Parser(parser::Error::Failure));
+        }
+
+        let scheme = scheme.to_ascii_lowercase();
+        if crate::url::is_special_scheme(&scheme) != self.is_special() {
+            return Ok(());
+        }
+
+        // Urls with credentials or a port can't be file urls
+        if scheme == "file" && (self.includes_credentials() || self.port().is_some()) {
+            return Ok(());
+        }
+
+        let rest = &self.serialization[self.offsets.scheme_end..];
+        self.reparse(&format!("{scheme}{rest}"));
+        Ok(())
+    }
+
+    /// Change the host and optionally the port, like `example.com:8080`
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-host)
+    pub fn set_host(&mut self, value: &str) {
+        if self.has_opaque_path() {
+            return;
+        }
+
+        let host_and_port = value
+            .split(['/', '?', '#', '\\'])
+            .next()
+            .unwrap_or_default();
+        if host_and_port.is_empty() && self.is_special() {
+            return;
+        }
+
+        let serialization = format!(
+            "{}{host_and_port}{}",
+            &self.serialization[..self.offsets.host_start],
+            &self.serialization[self.offsets.path_start..]
+        );
+        self.reparse(&serialization);
+    }
+
+    /// Change the host, without changing the port
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-hostname)
+    pub fn set_hostname(&mut self, value: &str) {
+        let hostname = value
+            .split(['/', '?', '#', '\\', ':'])
+            .next()
+            .unwrap_or_default();
+
+        match self.port() {
+            Some(port) => self.set_host(&format!("{hostname}:{port}")),
+            None => self.set_host(hostname),
+        }
+    }
+
+    /// Change the port, an empty value removes the port
+    ///
+    /// Anything after the leading digits is ignored.
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-port)
+    pub fn set_port(&mut self, value: &str) {
+        // 1. If this’s URL cannot have a username/password/port, then return.
+        let host = self.host().map(ToString::to_string).unwrap_or_default();
+        if host.is_empty() || self.scheme() == "file" {
+            return;
+        }
+
+        let digits_end = value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len());
+        let digits = &value[..digits_end];
+
+        // 2. If the given value is the empty string, then set this’s URL’s port to null.
+        // Values that do not start with a digit are ignored
+        if digits.is_empty() {
+            if value.is_empty() {
+                self.set_host(&host);
+            }
+            return;
+        }
+
+        self.set_host(&format!("{host}:{digits}"));
+    }
+
+    /// Change the path, like `/index.html`
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-pathname)
+    pub fn set_pathname(&mut self, value: &str) {
+        // 1. If this’s URL has an opaque path, then return.
+        if self.has_opaque_path() {
+            return;
+        }
+
+        // The path can't start a query or a fragment
+        let mut path = escape(value, &['?', '#']);
+        if self.host().is_some() && !path.starts_with(['/', '\\']) {
+            path.insert(0, '/');
+        }
+
+        let mut serialization = self.serialization[..self.offsets.path_start].to_string();
+        serialization.push_str(&path);
+        if let Some(query) = self.query() {
+            serialization.push('?');
+            serialization.push_str(query.as_str());
+        }
+        if let Some(fragment) = self.fragment() {
+            serialization.push('#');
+            serialization.push_str(fragment.as_str());
+        }
+
+        self.reparse(&serialization);
+    }
+
+    /// Change the query, with or without the leading `?`
+    ///
+    /// An empty value removes the query.
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-search)
+    pub fn set_search(&mut self, value: &str) {
+        let value = value.strip_prefix('?').unwrap_or(value);
+
+        let query_start = self
+            .offsets
+            .query_start
+            .or(self.offsets.fragment_start)
+            .map_or(self.serialization.len(), |start| start - 1);
+
+        let mut serialization = self.serialization[..query_start].to_string();
+        if !value.is_empty() {
+            serialization.push('?');
+            serialization.push_str(&escape(value, &['#']));
+        }
+        if let Some(fragment) = self.fragment() {
+            serialization.push('#');
+            serialization.push_str(fragment.as_str());
+        }
+
+        self.reparse(&serialization);
+    }
+
+    /// Change the fragment, with or without the leading `#`
+    ///
+    /// An empty value removes the fragment.
+    ///
+    /// [Specification](https://url.spec.whatwg.org/#dom-url-hash)
+    pub fn set_hash(&mut self, value: &str) {
+        let value = value.strip_prefix('#').unwrap_or(value);
+
+        let mut serialization = self.serialize(ExcludeFragment::Yes).to_string();
+        if !value.is_empty() {
+            serialization.push('#');
+            serialization.push_str(value);
+        }
+
+        self.reparse(&serialization);
+    }
+
+    /// Replace the url with the result of parsing `serialization`, if that is a valid url
+    fn reparse(&mut self, serialization: &str) {
+        match serialization.parse() {
+            Ok(url) => *self = url,
+            Err(error) => log::debug!("Ignoring invalid url component: {error:?}"),
+        }
+    }
+}
+
+/// Percent-encode the given characters, so the parser does not treat them as delimiters
+fn escape(value: &str, characters: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if characters.contains(&c) {
+            escaped.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(input: &str) -> URL {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn set_protocol() {
+        let mut url = url("http://example.com/path");
+        url.set_protocol("HTTPS:").unwrap();
+        assert_eq!(url.to_string(), "https://example.com/path");
+
+        // Special schemes can't become non-special
+        url.set_protocol("foo").unwrap();
+        assert_eq!(url.scheme(), "https");
+
+        assert!(url.set_protocol("1http").is_err());
+    }
+
+    #[test]
+    fn set_host_and_port() {
+        let mut url = url("https://example.com:8080/path?query#fragment");
+
+        url.set_hostname("example.org/ignored");
+        assert_eq!(
+            url.to_string(),
+            "https://example.org:8080/path?query#fragment"
+        );
+
+        url.set_port("9090abc");
+        assert_eq!(url.port(), Some(9090));
+
+        url.set_port("");
+        assert_eq!(url.port(), None);
+
+        url.set_host("localhost:1234");
+        assert_eq!(
+            url.to_string(),
+            "https://localhost:1234/path?query#fragment"
+        );
+    }
+
+    #[test]
+    fn set_path_query_and_fragment() {
+        let mut url = url("https://example.com/a?b#c");
+
+        url.set_pathname("new?path");
+        assert_eq!(url.to_string(), "https://example.com/new%3Fpath?b#c");
+
+        url.set_search("?x=#y");
+        assert_eq!(url.to_string(), "https://example.com/new%3Fpath?x=%23y#c");
+
+        url.set_hash("#top");
+        assert_eq!(url.to_string(), "https://example.com/new%3Fpath?x=%23y#top");
+
+        url.set_search("");
+        url.set_hash("");
+        assert_eq!(url.to_string(), "https://example.com/new%3Fpath");
+    }
+}
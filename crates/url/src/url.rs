@@ -7,12 +7,11 @@ use std::{
     str::FromStr,
 };
 
-use sl_std::{ascii, chars::ReversibleCharIterator};
+use sl_std::{ascii, chars::ReversibleCharIterator, percent_encode::percent_decode};
 
 use crate::{
     host::Host,
     parser::{self, Parser},
-    percent_encode::percent_decode,
     util::{self, is_normalized_windows_drive_letter},
     PathSegments,
 };
@@ -520,4 +519,26 @@ mod tests {
         assert_eq!(url.path(), "/style.css");
         assert_eq!(url.serialization, "https://soju.im/style.css");
     }
+
+    #[test]
+    fn query_and_fragment_with_base_url() {
+        let base: URL = "https://example.com:8080/page?query#top".parse().unwrap();
+        assert_eq!(base.port(), Some(8080));
+        assert_eq!(base.query().unwrap(), "query");
+        assert_eq!(base.fragment().unwrap(), "top");
+
+        let url = URL::parse_with_base("#bottom", Some(&base), None).unwrap();
+        assert_eq!(
+            url.serialization,
+            "https://example.com:8080/page?query#bottom"
+        );
+
+        let url = URL::parse_with_base("?other", Some(&base), None).unwrap();
+        assert_eq!(url.serialization, "https://example.com:8080/page?other");
+        assert_eq!(url.query().unwrap(), "other");
+
+        let url: URL = "https://example.com:443/".parse().unwrap();
+        assert_eq!(url.port(), None);
+        assert_eq!(url.serialization, "https://example.com/");
+    }
 }
@@ -10,5 +10,5 @@ mod mime_type;
 mod sniff;
 mod sniff_tables;
 
-pub use metadata::{Metadata, NoSniff};
+pub use metadata::{Metadata, NoSniff, SniffingContext, RESOURCE_HEADER_SIZE};
 pub use mime_type::{MIMEParseError, MIMEType};
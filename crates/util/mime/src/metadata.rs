@@ -3,12 +3,17 @@
 use std::str::FromStr;
 
 use crate::{
-    sniff::{self, identify_audio_or_video_type, identify_image_type},
+    sniff::{self, identify_audio_or_video_type, identify_font_type, identify_image_type},
     sniff_tables, MIMEType,
 };
 
 use http::Header;
 
+/// The maximum number of bytes that are inspected when sniffing a resource
+///
+/// <https://mimesniff.spec.whatwg.org/#reading-the-resource-header>
+pub const RESOURCE_HEADER_SIZE: usize = 1445;
+
 /// Whether or not the user agent should try to guess the computed [MIMEType] of a [Resource].
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum NoSniff {
@@ -16,6 +21,27 @@ pub enum NoSniff {
     No,
 }
 
+impl NoSniff {
+    /// <https://fetch.spec.whatwg.org/#determine-nosniff>
+    #[must_use]
+    pub fn from_headers(headers: &http::Headers) -> Self {
+        // 1. Let values be the result of getting, decoding, and splitting `X-Content-Type-Options` from headers.
+        // 2. If values is null, then return false.
+        let Some(values) = headers.get(Header::CONTENT_TYPE_OPTIONS) else {
+            return Self::No;
+        };
+
+        // 3. If values[0] is an ASCII case-insensitive match for "nosniff", then return true.
+        let first_value = values.split(',').next().unwrap_or_default().trim();
+        if first_value.eq_ignore_ascii_case("nosniff") {
+            return Self::Yes;
+        }
+
+        // 4. Return false.
+        Self::No
+    }
+}
+
 /// Whether or not the user agent should check for an [apache bug](https://issues.apache.org/bugzilla/show_bug.cgi?id=13986)
 /// that caused apache to send unexpected `Content-Type` HTTP Headers when serving files with an unknown
 /// MIME Type
@@ -40,6 +66,9 @@ pub struct Metadata {
     pub computed_mime_type: MIMEType,
     pub check_for_apache_bug: CheckForApacheBug,
     pub no_sniff: NoSniff,
+
+    /// The first [RESOURCE_HEADER_SIZE] bytes of the resource
+    pub resource_header: Vec<u8>,
 }
 
 impl Metadata {
@@ -70,11 +99,12 @@ impl Metadata {
             }
         }
 
+        let resource_header = &resource_data[..resource_data.len().min(RESOURCE_HEADER_SIZE)];
         let computed_mime_type = determine_computed_mimetype(
             supplied_mime_type.as_ref(),
             no_sniff,
             check_for_apache_bug,
-            resource_data,
+            resource_header,
         );
 
         Self {
@@ -82,6 +112,7 @@ impl Metadata {
             computed_mime_type,
             check_for_apache_bug,
             no_sniff,
+            resource_header: resource_header.to_vec(),
         }
     }
 
@@ -91,11 +122,12 @@ impl Metadata {
         supplied_mime_type: Option<MIMEType>,
         no_sniff: NoSniff,
     ) -> Self {
+        let resource_header = &resource_data[..resource_data.len().min(RESOURCE_HEADER_SIZE)];
         let computed_mime_type = determine_computed_mimetype(
             supplied_mime_type.as_ref(),
             no_sniff,
             CheckForApacheBug::No,
-            resource_data,
+            resource_header,
         );
 
         Self {
@@ -103,6 +135,7 @@ impl Metadata {
             computed_mime_type,
             check_for_apache_bug: CheckForApacheBug::No,
             no_sniff,
+            resource_header: resource_header.to_vec(),
         }
     }
 
@@ -113,6 +146,81 @@ impl Metadata {
     pub fn new(resource_data: &[u8], no_sniff: NoSniff) -> Self {
         Self::with_supplied_mime_type(resource_data, None, no_sniff)
     }
+
+    /// Determine the [MIMEType] of the resource, depending on what it is used for
+    ///
+    /// Returns `None` if the [MIMEType] is undefined in the given context.
+    ///
+    /// <https://mimesniff.spec.whatwg.org/#context-specific-sniffing>
+    #[must_use]
+    pub fn computed_mime_type_in(&self, context: SniffingContext) -> Option<MIMEType> {
+        let supplied_mime_type = self.supplied_mime_type.as_ref();
+        let sniff_with = |identify: fn(&[u8]) -> Option<MIMEType>| {
+            // 1. If the supplied MIME type is an XML MIME type, the computed MIME type is the supplied MIME type.
+            // Abort these steps.
+            if let Some(supplied_mime_type) = supplied_mime_type.filter(|m| m.is_xml()) {
+                return Some(supplied_mime_type.clone());
+            }
+
+            // 2. Let matched-type be the result of executing the <context> type pattern matching algorithm
+            // with the resource header as the byte sequence to be matched.
+            // 3. If matched-type is not undefined, the computed MIME type is matched-type.
+            // Abort these steps.
+            // 4. The computed MIME type is the supplied MIME type.
+            identify(&self.resource_header).or_else(|| supplied_mime_type.cloned())
+        };
+
+        match context {
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-browsing-context>
+            SniffingContext::Browsing => Some(self.computed_mime_type.clone()),
+
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-an-image-context>
+            SniffingContext::Image => sniff_with(identify_image_type),
+
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-an-audio-or-video-context>
+            SniffingContext::AudioOrVideo => sniff_with(identify_audio_or_video_type),
+
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-font-context>
+            SniffingContext::Font => sniff_with(identify_font_type),
+
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-plugin-context>
+            SniffingContext::Plugin => {
+                // 1. If the supplied MIME type is undefined, the computed MIME type is "application/octet-stream".
+                // 2. The computed MIME type is the supplied MIME type.
+                Some(
+                    supplied_mime_type
+                        .cloned()
+                        .unwrap_or_else(|| MIMEType::new("application", "octet-stream")),
+                )
+            },
+
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-style-context>
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-script-context>
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-text-track-context>
+            // <https://mimesniff.spec.whatwg.org/#sniffing-in-a-cache-manifest-context>
+            SniffingContext::Style
+            | SniffingContext::Script
+            | SniffingContext::TextTrack
+            | SniffingContext::CacheManifest => supplied_mime_type.cloned(),
+        }
+    }
+}
+
+/// What a resource is going to be used for
+///
+/// <https://mimesniff.spec.whatwg.org/#context-specific-sniffing>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SniffingContext {
+    /// The resource is going to be displayed as a document
+    Browsing,
+    Image,
+    AudioOrVideo,
+    Plugin,
+    Style,
+    Script,
+    Font,
+    TextTrack,
+    CacheManifest,
 }
 
 /// <https://mimesniff.spec.whatwg.org/#determining-the-computed-mime-type-of-a-resource>
@@ -318,7 +426,7 @@ pub fn determine_computed_mimetype(
                 // are equal to 0x72 0x73 0x73 ("rss"), the computed MIME type is "application/rss+xml".
                 // Abort these steps.
                 if sequence[s..].starts_with(b"rss") {
-                    return MIMEType::new("application", "rss");
+                    return MIMEType::new("application", "rss+xml");
                 }
 
                 // 6. If length is greater than or equal to s + 4 and the four bytes from sequence[s] to sequence[s + 3] are equal to 0x66 0x65 0x65 0x64 ("feed"), the computed MIME type is "application/atom+xml".
@@ -381,7 +489,7 @@ pub fn determine_computed_mimetype(
                                 // 2. If length is greater than or equal to s + 43 and the forty-three bytes from sequence[s] to sequence[s + 42] are
                                 // equal to "http://purl.org/rss/1.0/", the computed MIME type is "application/rss+xml".
                                 // Abort these steps.
-                                if sequence.starts_with(b"http://purl.org/rss/1.0/") {
+                                if sequence[s..].starts_with(b"http://purl.org/rss/1.0/") {
                                     return MIMEType::new("application", "rss+xml");
                                 }
 
@@ -433,3 +541,77 @@ pub fn determine_computed_mimetype(
     // 10. The computed MIME type is the supplied MIME type.
     supplied_mime_type.clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[test]
+    fn nosniff() {
+        let png = MIMEType::new("image", "png");
+        let supplied_mime_type = Some(MIMEType::new("image", "gif"));
+
+        let metadata =
+            Metadata::with_supplied_mime_type(PNG_HEADER, supplied_mime_type.clone(), NoSniff::No);
+        assert_eq!(metadata.computed_mime_type, png);
+
+        let metadata =
+            Metadata::with_supplied_mime_type(PNG_HEADER, supplied_mime_type, NoSniff::Yes);
+        assert!(metadata.computed_mime_type.has_essence("image/gif"));
+
+        // Scriptable types are never sniffed if the no-sniff flag is set
+        let html = b"<!DOCTYPE html><html></html>";
+        assert!(Metadata::new(html, NoSniff::No)
+            .computed_mime_type
+            .is_html());
+        assert!(!Metadata::new(html, NoSniff::Yes)
+            .computed_mime_type
+            .is_html());
+    }
+
+    #[test]
+    fn feed_or_html() {
+        let html = Some(MIMEType::new("text", "html"));
+        let sniff = |data: &[u8]| {
+            Metadata::with_supplied_mime_type(data, html.clone(), NoSniff::No).computed_mime_type
+        };
+
+        assert!(sniff(b"<!-- comment --> <rss version=\"2.0\">").has_essence("application/rss+xml"));
+        assert!(sniff(b"<?xml version=\"1.0\"?><feed>").has_essence("application/atom+xml"));
+        assert!(sniff(b"<html><body>rss</body></html>").is_html());
+    }
+
+    #[test]
+    fn context_specific_sniffing() {
+        let metadata = Metadata::new(PNG_HEADER, NoSniff::No);
+        assert_eq!(
+            metadata.computed_mime_type_in(SniffingContext::Image),
+            Some(MIMEType::new("image", "png"))
+        );
+        assert_eq!(
+            metadata.computed_mime_type_in(SniffingContext::Script),
+            None
+        );
+        assert_eq!(
+            metadata.computed_mime_type_in(SniffingContext::Plugin),
+            Some(MIMEType::new("application", "octet-stream"))
+        );
+
+        let metadata = Metadata::new(b"wOF2\x00\x01", NoSniff::No);
+        assert_eq!(
+            metadata.computed_mime_type_in(SniffingContext::Font),
+            Some(MIMEType::new("font", "woff2"))
+        );
+    }
+
+    #[test]
+    fn nosniff_header() {
+        let mut headers = http::Headers::default();
+        assert_eq!(NoSniff::from_headers(&headers), NoSniff::No);
+
+        headers.set(Header::CONTENT_TYPE_OPTIONS, "NoSniff, foo".to_string());
+        assert_eq!(NoSniff::from_headers(&headers), NoSniff::Yes);
+    }
+}
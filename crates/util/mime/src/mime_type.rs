@@ -1,4 +1,4 @@
-use std::{collections::HashMap, fmt, str::FromStr};
+use std::{fmt, str::FromStr};
 
 /// <https://mimesniff.spec.whatwg.org/#http-token-code-point>
 #[inline]
@@ -6,6 +6,59 @@ fn is_http_token_code_point(c: char) -> bool {
     matches!(c, '!' | '#' | '$' | '%' | '&' | '\'' | '*' | '+' | '-' | '.' | '^' | '_' | '`' | '|' | '~' | 'a'..='z' | 'A'..='Z' | '0'..='9')
 }
 
+/// <https://mimesniff.spec.whatwg.org/#http-quoted-string-token-code-point>
+#[inline]
+fn is_http_quoted_string_token_code_point(c: char) -> bool {
+    matches!(c, '\t' | ' '..='~' | '\u{80}'..='\u{FF}')
+}
+
+/// <https://fetch.spec.whatwg.org/#collect-an-http-quoted-string>, with the extract-value flag set
+///
+/// `input` must start with a U+0022 (") code point. Returns the value and the remaining input.
+fn collect_http_quoted_string(input: &str) -> (String, &str) {
+    // 1. Let positionStart be position.
+    // 2. Let value be the empty string.
+    let mut value = String::new();
+
+    // 3. Assert: the code point at position within input is U+0022 (").
+    debug_assert!(input.starts_with('"'));
+
+    // 4. Advance position by 1.
+    let mut chars = input[1..].char_indices();
+
+    // 5. While true:
+    while let Some((index, c)) = chars.next() {
+        match c {
+            // 2. If position is past the end of input, then break.
+            // 3. Let quoteOrBackslash be the code point at position within input.
+            // 4. Advance position by 1.
+            // 5. If quoteOrBackslash is U+005C (\), then:
+            '\\' => match chars.next() {
+                // 2. Append the code point at position within input to value.
+                // 3. Advance position by 1.
+                Some((_, escaped)) => value.push(escaped),
+
+                // 1. If position is past the end of input, then append U+005C (\) to value and break.
+                None => {
+                    value.push('\\');
+                    break;
+                },
+            },
+            // 6. Otherwise:
+            //    1. Assert: quoteOrBackslash is U+0022 (").
+            //    2. Break.
+            '"' => return (value, &input[1 + index + 1..]),
+
+            // 1. Append the result of collecting a sequence of code points that are not U+0022 (")
+            //    or U+005C (\) from input, given position, to value.
+            other => value.push(other),
+        }
+    }
+
+    // 6. If the extract-value flag is set, then return value.
+    (value, "")
+}
+
 /// <https://mimesniff.spec.whatwg.org/#mime-type>
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct MIMEType {
@@ -15,9 +68,10 @@ pub struct MIMEType {
     /// <https://mimesniff.spec.whatwg.org/#subtype>
     pub mime_subtype: String,
 
-    // TODO: ordering
     /// <https://mimesniff.spec.whatwg.org/#parameters>
-    pub parameters: HashMap<String, String>,
+    ///
+    /// Parameters are stored in the order in which they were parsed, their names are always lowercase.
+    pub parameters: Vec<(String, String)>,
 }
 
 impl MIMEType {
@@ -27,15 +81,58 @@ impl MIMEType {
         Self {
             mime_type: mime_type.into(),
             mime_subtype: mime_subtype.into(),
-            parameters: HashMap::default(),
+            parameters: Vec::new(),
         }
     }
 
+    /// <https://mimesniff.spec.whatwg.org/#mime-type-essence>
     #[must_use]
     pub fn essence(&self) -> String {
         format!("{}/{}", self.mime_type, self.mime_subtype)
     }
 
+    /// Compare the essence of `self` with `essence`, ignoring ASCII case
+    ///
+    /// Unlike `self.essence() == essence`, this does not allocate.
+    #[must_use]
+    pub fn has_essence(&self, essence: &str) -> bool {
+        essence
+            .split_once('/')
+            .is_some_and(|(mime_type, mime_subtype)| {
+                self.mime_type.eq_ignore_ascii_case(mime_type)
+                    && self.mime_subtype.eq_ignore_ascii_case(mime_subtype)
+            })
+    }
+
+    /// Return the value of the parameter with the given (lowercase) name
+    #[must_use]
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(parameter_name, _)| parameter_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set the value of a parameter, replacing the previous value if there was one
+    pub fn set_parameter(&mut self, name: &str, value: &str) {
+        let name = name.to_ascii_lowercase();
+
+        match self
+            .parameters
+            .iter_mut()
+            .find(|(parameter_name, _)| *parameter_name == name)
+        {
+            Some((_, old_value)) => *old_value = value.to_string(),
+            None => self.parameters.push((name, value.to_string())),
+        }
+    }
+
+    /// The value of the `charset` parameter, if any
+    #[must_use]
+    pub fn charset(&self) -> Option<&str> {
+        self.parameter("charset")
+    }
+
     /// <https://mimesniff.spec.whatwg.org/#image-mime-type>
     #[inline]
     #[must_use]
@@ -142,8 +239,39 @@ impl MIMEType {
 }
 
 impl fmt::Display for MIMEType {
+    /// <https://mimesniff.spec.whatwg.org/#serialize-a-mime-type>
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&self.essence())
+        // 1. Let serialization be the concatenation of mimeType’s type, U+002F (/), and mimeType’s subtype.
+        write!(f, "{}/{}", self.mime_type, self.mime_subtype)?;
+
+        // 2. For each name → value of mimeType’s parameters:
+        for (name, value) in &self.parameters {
+            // 1. Append U+003B (;) to serialization.
+            // 2. Append name to serialization.
+            // 3. Append U+003D (=) to serialization.
+            write!(f, ";{name}=")?;
+
+            // 4. If value does not solely contain HTTP token code points or value is the empty string, then:
+            if value.is_empty() || !value.chars().all(is_http_token_code_point) {
+                // 1. Precede each occurrence of U+0022 (") or U+005C (\) in value with U+005C (\).
+                // 2. Prepend U+0022 (") to value.
+                // 3. Append U+0022 (") to value.
+                f.write_str("\"")?;
+                for c in value.chars() {
+                    if matches!(c, '"' | '\\') {
+                        f.write_str("\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                f.write_str("\"")?;
+            } else {
+                // 5. Append value to serialization.
+                f.write_str(value)?;
+            }
+        }
+
+        // 3. Return serialization.
+        Ok(())
     }
 }
 
@@ -224,7 +352,8 @@ impl FromStr for MIMEType {
             let parameter_name = match parameter_name_end {
                 Some(index) => {
                     //      1. If the code point at position within input is U+003B (;), then continue.
-                    if remaining_input.chars().nth(index) == Some(';') {
+                    if remaining_input.as_bytes()[index] == b';' {
+                        remaining_input = &remaining_input[index + 1..];
                         continue;
                     }
 
@@ -232,7 +361,7 @@ impl FromStr for MIMEType {
                     let parameter_name = remaining_input[..index].to_ascii_lowercase();
 
                     //      2. Advance position by 1. (This skips past U+003D (=).)
-                    remaining_input = &remaining_input[1..];
+                    remaining_input = &remaining_input[index + 1..];
 
                     parameter_name
                 },
@@ -246,12 +375,8 @@ impl FromStr for MIMEType {
             // 8. If the code point at position within input is U+0022 ("), then:
             let parameter_value = if remaining_input.starts_with('"') {
                 // 1. Set parameterValue to the result of collecting an HTTP quoted string from input, given position and the extract-value flag.
-                // NOTE: this is not entirely correct, wait for <https://fetch.spec.whatwg.org> to be implemented
-                let parts = remaining_input[1..]
-                    .split_once('"')
-                    .unwrap_or((&remaining_input[1..], ""));
-                let parameter_value = parts.0;
-                remaining_input = parts.1;
+                let (parameter_value, rest) = collect_http_quoted_string(remaining_input);
+                remaining_input = rest;
 
                 // 2. Collect a sequence of code points that are not U+003B (;) from input, given position.
                 remaining_input = remaining_input.split_once(';').map(|a| a.1).unwrap_or("");
@@ -275,7 +400,7 @@ impl FromStr for MIMEType {
                     continue;
                 }
 
-                parameter_value
+                parameter_value.to_string()
             };
 
             // 10. If all of the following are true
@@ -285,12 +410,13 @@ impl FromStr for MIMEType {
             // * mimeType’s parameters[parameterName] does not exist
             if !parameter_name.is_empty()
                 && parameter_name.chars().all(is_http_token_code_point)
-                && parameter_value.chars().all(is_http_token_code_point)
+                && parameter_value
+                    .chars()
+                    .all(is_http_quoted_string_token_code_point)
+                && mime.parameter(&parameter_name).is_none()
             {
                 // then set mimeType’s parameters[parameterName] to parameterValue.
-                mime.parameters
-                    .entry(parameter_name)
-                    .or_insert(parameter_value.to_string());
+                mime.parameters.push((parameter_name, parameter_value));
             }
         }
 
@@ -332,6 +458,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parameters() {
+        let mime_type =
+            MIMEType::from_str("Text/HTML;Charset=\"utf-8\"; foo; bar=\"a\\\"b\" ;charset=ascii")
+                .unwrap();
+
+        assert!(mime_type.has_essence("text/html"));
+        assert_eq!(mime_type.charset(), Some("utf-8"));
+        assert_eq!(mime_type.parameter("bar"), Some("a\"b"));
+        assert_eq!(mime_type.parameters.len(), 2);
+
+        // Parameters with empty values are ignored unless they are quoted
+        let mime_type = MIMEType::from_str("text/plain;foo=;bar=\"\"").unwrap();
+        assert_eq!(mime_type.parameters, [("bar".to_string(), String::new())]);
+    }
+
+    #[test]
+    fn serialize() {
+        let mut mime_type = MIMEType::new("text", "plain");
+        mime_type.set_parameter("Charset", "utf-8");
+        mime_type.set_parameter("name", "my \"file\"");
+        mime_type.set_parameter("empty", "");
+
+        let serialized = mime_type.to_string();
+        assert_eq!(
+            serialized,
+            "text/plain;charset=utf-8;name=\"my \\\"file\\\"\";empty=\"\""
+        );
+        assert_eq!(MIMEType::from_str(&serialized), Ok(mime_type));
+    }
+
     #[test]
     fn valid_mime_type() {
         // Simple MIME type
@@ -76,12 +76,12 @@ pub fn identify_audio_or_video_type(resource_header: &[u8]) -> Option<MIMEType>
 
     // 2. If input matches the signature for MP4, return "video/mp4".
     if matches_mp4_signature(resource_header) {
-        return Some(MIMEType::new("audio", "mp4"));
+        return Some(MIMEType::new("video", "mp4"));
     }
 
     // 3. If input matches the signature for WebM, return "video/webm".
     if matches_webm_signature(resource_header) {
-        return Some(MIMEType::new("audio", "mp4"));
+        return Some(MIMEType::new("video", "webm"));
     }
 
     // 4. If input matches the signature for MP3 without ID3, return "audio/mpeg".
@@ -93,6 +93,17 @@ pub fn identify_audio_or_video_type(resource_header: &[u8]) -> Option<MIMEType>
     None
 }
 
+/// <https://mimesniff.spec.whatwg.org/#font-type-pattern-matching-algorithm>
+pub fn identify_font_type(resource_header: &[u8]) -> Option<MIMEType> {
+    // 1. Execute the following steps for each row row in the following table:
+    if let Some(matched_mime_type) = sniff_tables::FONT_MIME_TYPES_TABLE.lookup(resource_header) {
+        return Some(matched_mime_type);
+    }
+
+    // 2. Return undefined.
+    None
+}
+
 /// <https://mimesniff.spec.whatwg.org/#archive-type-pattern-matching-algorithm>
 fn identify_archive_type(resource_header: &[u8]) -> Option<MIMEType> {
     // 1. Execute the following steps for each row row in the following table:
@@ -433,7 +433,60 @@ pub const ARCHIVE_MIME_TYPES_TABLE: MIMESniffTable<3> = MIMESniffTable([
         pattern: &[0x52, 0x61, 0x72, 0x20, 0x1A, 0x07, 0x00],
         mask: &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
         ignore: &[],
-        computed_mime: "application/zip",
+        computed_mime: "application/x-rar-compressed",
+    },
+]);
+
+pub const FONT_MIME_TYPES_TABLE: MIMESniffTable<6> = MIMESniffTable([
+    // 34 bytes followed by the string "LP", the Embedded OpenType signature.
+    MIMESniffPattern {
+        pattern: &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x4C, 0x50,
+        ],
+        mask: &[
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0xFF,
+        ],
+        ignore: &[],
+        computed_mime: "application/vnd.ms-fontobject",
+    },
+    // 4 bytes representing the version number 1.0, a TrueType signature.
+    MIMESniffPattern {
+        pattern: &[0x00, 0x01, 0x00, 0x00],
+        mask: &[0xFF, 0xFF, 0xFF, 0xFF],
+        ignore: &[],
+        computed_mime: "font/ttf",
+    },
+    // The string "OTTO", the OpenType signature.
+    MIMESniffPattern {
+        pattern: &[0x4F, 0x54, 0x54, 0x4F],
+        mask: &[0xFF, 0xFF, 0xFF, 0xFF],
+        ignore: &[],
+        computed_mime: "font/otf",
+    },
+    // The string "ttcf", the TrueType Collection signature.
+    MIMESniffPattern {
+        pattern: &[0x74, 0x74, 0x63, 0x66],
+        mask: &[0xFF, 0xFF, 0xFF, 0xFF],
+        ignore: &[],
+        computed_mime: "font/collection",
+    },
+    // The string "wOFF", the Web Open Font Format 1.0 signature.
+    MIMESniffPattern {
+        pattern: &[0x77, 0x4F, 0x46, 0x46],
+        mask: &[0xFF, 0xFF, 0xFF, 0xFF],
+        ignore: &[],
+        computed_mime: "font/woff",
+    },
+    // The string "wOF2", the Web Open Font Format 2.0 signature.
+    MIMESniffPattern {
+        pattern: &[0x77, 0x4F, 0x46, 0x32],
+        mask: &[0xFF, 0xFF, 0xFF, 0xFF],
+        ignore: &[],
+        computed_mime: "font/woff2",
     },
 ]);
 
@@ -479,8 +532,13 @@ impl MIMESniffPattern {
 
         // 6. While p < pattern’s length:
         while p < self.pattern.len() {
+            // NOTE: The spec does not account for ignored bytes pushing the pattern past the end of the input
+            let Some(&byte) = input.get(s) else {
+                return false;
+            };
+
             // 1. Let maskedData be the result of applying the bitwise AND operator to input[s] and mask[p].
-            let masked_data = input[s] & self.mask[p];
+            let masked_data = byte & self.mask[p];
 
             // 2. If maskedData is not equal to pattern[p], return false.
             if masked_data != self.pattern[p] {
@@ -0,0 +1,361 @@
+use serialize::{
+    deserialization::{EnumAccess, EnumVariantAccess, Error, MapAccess, SequentialAccess},
+    Deserialize, Deserializer, Visitor,
+};
+
+use crate::{tag, FORMAT_VERSION, MAGIC};
+
+#[derive(Clone, Debug)]
+pub enum BinaryError {
+    Expected(&'static str),
+    UnknownField(String),
+    UnknownVariant(String),
+    MissingField(&'static str),
+
+    /// The data does not start with a valid header
+    InvalidHeader,
+
+    /// The data was written with an incompatible version of the format
+    UnsupportedFormatVersion(u8),
+    UnexpectedEndOfInput,
+    UnexpectedTag(u8),
+    InvalidUtf8,
+    IntegerOverflow,
+}
+
+impl Error for BinaryError {
+    fn expected(expectation: &'static str) -> Self {
+        Self::Expected(expectation)
+    }
+
+    fn unknown_field(field: String) -> Self {
+        Self::UnknownField(field)
+    }
+
+    fn unknown_variant(name: String) -> Self {
+        Self::UnknownVariant(name)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::MissingField(field)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BinaryDeserializer<'a> {
+    bytes: &'a [u8],
+    version: u32,
+}
+
+impl<'a> BinaryDeserializer<'a> {
+    /// Create a new deserializer, after verifying the header
+    pub fn new(bytes: &'a [u8]) -> Result<Self, BinaryError> {
+        let mut deserializer = Self { bytes, version: 0 };
+
+        let magic = deserializer
+            .read_bytes(MAGIC.len())
+            .map_err(|_| BinaryError::InvalidHeader)?;
+        if magic != MAGIC {
+            return Err(BinaryError::InvalidHeader);
+        }
+
+        let format_version = deserializer.read_byte()?;
+        if format_version != FORMAT_VERSION {
+            return Err(BinaryError::UnsupportedFormatVersion(format_version));
+        }
+
+        deserializer.version = deserializer
+            .read_leb128()?
+            .try_into()
+            .map_err(|_| BinaryError::InvalidHeader)?;
+
+        Ok(deserializer)
+    }
+
+    /// The schema version that was passed to the [BinarySerializer](crate::BinarySerializer)
+    #[must_use]
+    pub const fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Whether all data has been consumed
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_byte(&mut self) -> Result<u8, BinaryError> {
+        let (&byte, remaining) = self
+            .bytes
+            .split_first()
+            .ok_or(BinaryError::UnexpectedEndOfInput)?;
+        self.bytes = remaining;
+        Ok(byte)
+    }
+
+    fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], BinaryError> {
+        if self.bytes.len() < n {
+            return Err(BinaryError::UnexpectedEndOfInput);
+        }
+
+        let (bytes, remaining) = self.bytes.split_at(n);
+        self.bytes = remaining;
+        Ok(bytes)
+    }
+
+    fn read_leb128(&mut self) -> Result<u64, BinaryError> {
+        let mut value: u64 = 0;
+        let mut shift = 0;
+
+        loop {
+            let byte = self.read_byte()?;
+
+            if shift >= u64::BITS || (shift == 63 && byte > 1) {
+                return Err(BinaryError::IntegerOverflow);
+            }
+            value |= u64::from(byte & 0x7F) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn peek_tag(&self) -> Result<u8, BinaryError> {
+        self.bytes
+            .first()
+            .copied()
+            .ok_or(BinaryError::UnexpectedEndOfInput)
+    }
+
+    fn expect_tag(&mut self, expected: u8) -> Result<(), BinaryError> {
+        let tag = self.read_byte()?;
+        if tag == expected {
+            Ok(())
+        } else {
+            Err(BinaryError::UnexpectedTag(tag))
+        }
+    }
+
+    /// Consume the next byte if it is [tag::END]
+    fn consume_end_tag(&mut self) -> Result<bool, BinaryError> {
+        let is_end = self.peek_tag()? == tag::END;
+        if is_end {
+            self.bytes = &self.bytes[1..];
+        }
+        Ok(is_end)
+    }
+}
+
+impl<'a> Deserializer for &mut BinaryDeserializer<'a> {
+    type Error = BinaryError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        match self.peek_tag()? {
+            tag::NULL => self.deserialize_option(visitor),
+            tag::FALSE | tag::TRUE => self.deserialize_bool(visitor),
+            tag::UNSIGNED => self.deserialize_usize(visitor),
+            tag::FLOAT => self.deserialize_f64(visitor),
+            tag::STRING => self.deserialize_string(visitor),
+            tag::SEQUENCE => self.deserialize_sequence(visitor),
+            tag::MAP | tag::STRUCT => {
+                // Structs can be read like any other map
+                self.bytes = &self.bytes[1..];
+                visitor.visit_map(BinaryMap { deserializer: self })
+            },
+            tag::ENUM => self.deserialize_enum(visitor),
+            other => Err(BinaryError::UnexpectedTag(other)),
+        }
+    }
+
+    fn deserialize_sequence<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::SEQUENCE)?;
+        visitor.visit_sequence(BinarySequence { deserializer: self })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::MAP)?;
+        visitor.visit_map(BinaryMap { deserializer: self })
+    }
+
+    fn deserialize_struct<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::STRUCT)?;
+        visitor.visit_map(BinaryMap { deserializer: self })
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        match self.read_byte()? {
+            tag::TRUE => visitor.visit_bool(true),
+            tag::FALSE => visitor.visit_bool(false),
+            other => Err(BinaryError::UnexpectedTag(other)),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::STRING)?;
+
+        let length = self
+            .read_leb128()?
+            .try_into()
+            .map_err(|_| BinaryError::IntegerOverflow)?;
+        let bytes = self.read_bytes(length)?;
+        let string = std::str::from_utf8(bytes).map_err(|_| BinaryError::InvalidUtf8)?;
+
+        visitor.visit_string(string.to_owned())
+    }
+
+    fn deserialize_usize<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::UNSIGNED)?;
+
+        let value = self
+            .read_leb128()?
+            .try_into()
+            .map_err(|_| BinaryError::IntegerOverflow)?;
+        visitor.visit_usize(value)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::FLOAT)?;
+
+        let bytes = self.read_bytes(8)?;
+        let value = f64::from_le_bytes(bytes.try_into().expect("read exactly 8 bytes"));
+        visitor.visit_f64(value)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        if self.peek_tag()? == tag::NULL {
+            self.bytes = &self.bytes[1..];
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_enum<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        self.expect_tag(tag::ENUM)?;
+        visitor.visit_enum(BinaryEnum { deserializer: self })
+    }
+
+    fn allows_unknown_fields(&self) -> bool {
+        // Data might have been written by a newer version of the program
+        true
+    }
+}
+
+struct BinarySequence<'a, 'b> {
+    deserializer: &'a mut BinaryDeserializer<'b>,
+}
+
+impl<'a, 'b> SequentialAccess for BinarySequence<'a, 'b> {
+    type Error = BinaryError;
+
+    fn next_element<T>(&mut self) -> Result<Option<T>, Self::Error>
+    where
+        T: Deserialize,
+    {
+        if self.deserializer.consume_end_tag()? {
+            return Ok(None);
+        }
+
+        T::deserialize(&mut *self.deserializer).map(Some)
+    }
+}
+
+struct BinaryMap<'a, 'b> {
+    deserializer: &'a mut BinaryDeserializer<'b>,
+}
+
+impl<'a, 'b> MapAccess for BinaryMap<'a, 'b> {
+    type Error = BinaryError;
+
+    fn next_key<K>(&mut self) -> Result<Option<K>, Self::Error>
+    where
+        K: Deserialize,
+    {
+        if self.deserializer.consume_end_tag()? {
+            return Ok(None);
+        }
+
+        K::deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value<V>(&mut self) -> Result<V, Self::Error>
+    where
+        V: Deserialize,
+    {
+        V::deserialize(&mut *self.deserializer)
+    }
+}
+
+struct BinaryEnum<'a, 'b> {
+    deserializer: &'a mut BinaryDeserializer<'b>,
+}
+
+impl<'a, 'b> EnumAccess for BinaryEnum<'a, 'b> {
+    type Error = BinaryError;
+    type Variant = Self;
+
+    fn variant<V>(self) -> Result<(V, Self::Variant), Self::Error>
+    where
+        V: Deserialize,
+    {
+        let variant = V::deserialize(&mut *self.deserializer)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'b> EnumVariantAccess for BinaryEnum<'a, 'b> {
+    type Error = BinaryError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        // Unit variants are serialized as empty tuple variants
+        self.deserializer.expect_tag(tag::SEQUENCE)?;
+        self.deserializer.expect_tag(tag::END)
+    }
+
+    fn tuple_variant<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserializer.deserialize_sequence(visitor)
+    }
+
+    fn struct_variant<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserializer.deserialize_struct(visitor)
+    }
+
+    fn newtype_variant<T>(self) -> Result<T, Self::Error>
+    where
+        T: Deserialize,
+    {
+        T::deserialize(&mut *self.deserializer)
+    }
+}
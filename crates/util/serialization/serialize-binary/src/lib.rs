@@ -0,0 +1,163 @@
+//! A compact, self-describing binary format for the [serialize] framework
+//!
+//! Every value is prefixed with a one-byte tag that describes its type, which allows
+//! the deserializer to skip over values it does not know about. Struct fields are
+//! stored together with their names, so data written by a newer version of a type
+//! (with additional fields) can still be read by an older one.
+//!
+//! Serialized data starts with a header that contains a user-defined schema version.
+//! Applications can use it to migrate data that was written by an older version of
+//! their types.
+
+mod deserializer;
+mod serializer;
+
+pub use deserializer::{BinaryDeserializer, BinaryError};
+pub use serializer::BinarySerializer;
+
+/// Identifies data written by this crate
+const MAGIC: [u8; 4] = *b"SLBN";
+
+/// Version of the encoding itself, incremented on incompatible changes to the format
+const FORMAT_VERSION: u8 = 1;
+
+/// Tags that precede every value
+mod tag {
+    /// Marks the end of a sequence, map or struct
+    pub const END: u8 = 0x00;
+    pub const NULL: u8 = 0x01;
+    pub const FALSE: u8 = 0x02;
+    pub const TRUE: u8 = 0x03;
+
+    /// Followed by a LEB128-encoded integer
+    pub const UNSIGNED: u8 = 0x04;
+
+    /// Followed by 8 bytes of little-endian IEEE 754 data
+    pub const FLOAT: u8 = 0x05;
+
+    /// Followed by the LEB128-encoded length and the UTF-8 bytes
+    pub const STRING: u8 = 0x06;
+
+    /// Followed by any number of values and [END]
+    pub const SEQUENCE: u8 = 0x07;
+
+    /// Followed by any number of key-value pairs and [END]
+    pub const MAP: u8 = 0x08;
+
+    /// Followed by any number of field name and value pairs and [END]
+    pub const STRUCT: u8 = 0x09;
+
+    /// Followed by the variant name and the variant data
+    pub const ENUM: u8 = 0x0A;
+}
+
+#[cfg(test)]
+mod tests {
+    use serialize::{Deserialize, Serialize};
+
+    use super::*;
+
+    fn serialize_deserialize<T>(value: T) -> T
+    where
+        T: Serialize + Deserialize,
+    {
+        let serialized = BinarySerializer::serialize_to_vec(value, 0).unwrap();
+        let mut deserializer = BinaryDeserializer::new(&serialized).unwrap();
+        let deserialized = T::deserialize(&mut deserializer).unwrap();
+
+        assert!(deserializer.is_empty());
+        deserialized
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct CookieV1 {
+        name: String,
+        value: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct CookieV2 {
+        name: String,
+        value: String,
+        expires: Option<usize>,
+        flags: Vec<Flag>,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    enum Flag {
+        Secure,
+        SameSite(String),
+        Partitioned { key: String },
+    }
+
+    #[test]
+    fn primitives() {
+        assert!(serialize_deserialize(true));
+        assert_eq!(serialize_deserialize(usize::MAX), usize::MAX);
+        assert_eq!(serialize_deserialize(0.1), 0.1);
+        assert_eq!(serialize_deserialize("ü".to_string()), "ü");
+        assert_eq!(serialize_deserialize(Some(3_u8)), Some(3));
+        assert_eq!(serialize_deserialize(None::<u8>), None);
+        assert_eq!(serialize_deserialize(vec![1_usize, 2, 3]), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn derived_types() {
+        let cookie = CookieV2 {
+            name: "session".to_string(),
+            value: "1234".to_string(),
+            expires: Some(1_700_000_000),
+            flags: vec![
+                Flag::Secure,
+                Flag::SameSite("Lax".to_string()),
+                Flag::Partitioned {
+                    key: "https://example.com".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(serialize_deserialize(cookie.clone()), cookie);
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped() {
+        let cookie = CookieV2 {
+            name: "session".to_string(),
+            value: "1234".to_string(),
+            expires: None,
+            flags: vec![Flag::Partitioned {
+                key: "https://example.com".to_string(),
+            }],
+        };
+
+        let serialized = BinarySerializer::serialize_to_vec(cookie, 2).unwrap();
+        let mut deserializer = BinaryDeserializer::new(&serialized).unwrap();
+        assert_eq!(deserializer.version(), 2);
+
+        let old_cookie = CookieV1::deserialize(&mut deserializer).unwrap();
+        assert_eq!(
+            old_cookie,
+            CookieV1 {
+                name: "session".to_string(),
+                value: "1234".to_string(),
+            }
+        );
+        assert!(deserializer.is_empty());
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert!(matches!(
+            BinaryDeserializer::new(b"JSON"),
+            Err(BinaryError::InvalidHeader)
+        ));
+
+        let mut serialized = BinarySerializer::serialize_to_vec("foo", 0).unwrap();
+        serialized.pop();
+        let mut deserializer = BinaryDeserializer::new(&serialized).unwrap();
+        assert!(matches!(
+            String::deserialize(&mut deserializer),
+            Err(BinaryError::UnexpectedEndOfInput)
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use std::io::{self, Write};
+
+use serialize::{
+    serialization::{
+        SerializeMap, SerializeSequence, SerializeStruct, SerializeStructVariant,
+        SerializeTupleVariant, Serializer,
+    },
+    Serialize,
+};
+
+use crate::{tag, FORMAT_VERSION, MAGIC};
+
+pub struct BinarySerializer<W> {
+    writer: W,
+}
+
+impl BinarySerializer<Vec<u8>> {
+    pub fn serialize_to_vec<T>(value: T, version: u32) -> Result<Vec<u8>, io::Error>
+    where
+        T: Serialize,
+    {
+        let mut serializer = Self::new(vec![], version)?;
+        value.serialize_to(&mut serializer)?;
+
+        Ok(serializer.writer)
+    }
+}
+
+impl<W> BinarySerializer<W>
+where
+    W: Write,
+{
+    /// Create a new serializer and write the header
+    ///
+    /// The `version` describes the schema of the serialized data and can be retrieved
+    /// with [BinaryDeserializer::version](crate::BinaryDeserializer::version).
+    pub fn new(mut writer: W, version: u32) -> Result<Self, io::Error> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        write_leb128(&mut writer, u64::from(version))?;
+
+        Ok(Self { writer })
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_tag(&mut self, tag: u8) -> Result<(), io::Error> {
+        self.writer.write_all(&[tag])
+    }
+}
+
+fn write_leb128<W: Write>(writer: &mut W, mut value: u64) -> Result<(), io::Error> {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+impl<W> Serializer for BinarySerializer<W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    type SequenceSerializer<'a>
+        = CompoundSerializer<'a, W>
+    where
+        Self: 'a;
+
+    type MapSerializer<'a>
+        = CompoundSerializer<'a, W>
+    where
+        Self: 'a;
+
+    type StructSerializer<'a>
+        = CompoundSerializer<'a, W>
+    where
+        Self: 'a;
+
+    type StructVariantSerializer<'a>
+        = CompoundSerializer<'a, W>
+    where
+        Self: 'a;
+
+    type TupleVariantSerializer<'a>
+        = CompoundSerializer<'a, W>
+    where
+        Self: 'a;
+
+    fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
+        if value {
+            self.write_tag(tag::TRUE)
+        } else {
+            self.write_tag(tag::FALSE)
+        }
+    }
+
+    fn serialize_string(&mut self, value: &str) -> Result<(), Self::Error> {
+        self.write_tag(tag::STRING)?;
+        write_leb128(&mut self.writer, value.len() as u64)?;
+        self.writer.write_all(value.as_bytes())
+    }
+
+    fn serialize_usize(&mut self, value: usize) -> Result<(), Self::Error> {
+        self.write_tag(tag::UNSIGNED)?;
+        write_leb128(&mut self.writer, value as u64)
+    }
+
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        self.write_tag(tag::FLOAT)?;
+        self.writer.write_all(&value.to_le_bytes())
+    }
+
+    fn serialize_option<T>(&mut self, value: &Option<T>) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        match value {
+            Some(value) => value.serialize_to(self),
+            None => self.write_tag(tag::NULL),
+        }
+    }
+
+    fn serialize_sequence(&mut self) -> Result<Self::SequenceSerializer<'_>, Self::Error> {
+        self.write_tag(tag::SEQUENCE)?;
+        Ok(CompoundSerializer { serializer: self })
+    }
+
+    fn serialize_map(&mut self) -> Result<Self::MapSerializer<'_>, Self::Error> {
+        self.write_tag(tag::MAP)?;
+        Ok(CompoundSerializer { serializer: self })
+    }
+
+    fn serialize_struct(&mut self) -> Result<Self::StructSerializer<'_>, Self::Error> {
+        self.write_tag(tag::STRUCT)?;
+        Ok(CompoundSerializer { serializer: self })
+    }
+
+    fn serialize_tuple_enum<'a>(
+        &'a mut self,
+        variant_name: &str,
+    ) -> Result<Self::TupleVariantSerializer<'a>, Self::Error> {
+        self.write_tag(tag::ENUM)?;
+        self.serialize_string(variant_name)?;
+        self.serialize_sequence()
+    }
+
+    fn serialize_struct_enum<'a>(
+        &'a mut self,
+        variant_name: &str,
+    ) -> Result<Self::StructVariantSerializer<'a>, Self::Error> {
+        self.write_tag(tag::ENUM)?;
+        self.serialize_string(variant_name)?;
+        self.serialize_struct()
+    }
+
+    fn serialize_newtype_variant<T>(
+        &mut self,
+        variant_name: &str,
+        value: &T,
+    ) -> Result<(), Self::Error>
+    where
+        T: Serialize,
+    {
+        self.write_tag(tag::ENUM)?;
+        self.serialize_string(variant_name)?;
+        value.serialize_to(self)
+    }
+}
+
+/// Serializes the contents of sequences, maps and structs, which are all
+/// terminated by [tag::END]
+pub struct CompoundSerializer<'a, W> {
+    serializer: &'a mut BinarySerializer<W>,
+}
+
+impl<'a, W> CompoundSerializer<'a, W>
+where
+    W: Write,
+{
+    fn finish(self) -> Result<(), io::Error> {
+        self.serializer.write_tag(tag::END)
+    }
+}
+
+impl<'a, W> SerializeSequence for CompoundSerializer<'a, W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn serialize_element<T>(&mut self, element: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        element.serialize_to(self.serializer)
+    }
+
+    fn finish(self) -> Result<(), Self::Error> {
+        CompoundSerializer::finish(self)
+    }
+}
+
+impl<'a, W> SerializeTupleVariant for CompoundSerializer<'a, W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn serialize_element<T>(&mut self, element: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        element.serialize_to(self.serializer)
+    }
+
+    fn finish(self) -> Result<(), Self::Error> {
+        CompoundSerializer::finish(self)
+    }
+}
+
+impl<'a, W> SerializeMap for CompoundSerializer<'a, W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn serialize_key_value_pair<K, V>(&mut self, key: &K, value: &V) -> Result<(), Self::Error>
+    where
+        K: ?Sized + Serialize,
+        V: ?Sized + Serialize,
+    {
+        key.serialize_to(self.serializer)?;
+        value.serialize_to(self.serializer)
+    }
+
+    fn finish(self) -> Result<(), Self::Error> {
+        CompoundSerializer::finish(self)
+    }
+}
+
+impl<'a, W> SerializeStruct for CompoundSerializer<'a, W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn serialize_field<T>(&mut self, name: &str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serializer.serialize_string(name)?;
+        value.serialize_to(self.serializer)
+    }
+
+    fn finish(self) -> Result<(), Self::Error> {
+        CompoundSerializer::finish(self)
+    }
+}
+
+impl<'a, W> SerializeStructVariant for CompoundSerializer<'a, W>
+where
+    W: Write,
+{
+    type Error = io::Error;
+
+    fn serialize_field<T>(&mut self, name: &str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serializer.serialize_string(name)?;
+        value.serialize_to(self.serializer)
+    }
+
+    fn finish(self) -> Result<(), Self::Error> {
+        CompoundSerializer::finish(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::write_leb128;
+
+    #[test]
+    fn leb128() {
+        let mut buffer = vec![];
+        write_leb128(&mut buffer, 624485).unwrap();
+        assert_eq!(buffer, [0xE5, 0x8E, 0x26]);
+
+        buffer.clear();
+        write_leb128(&mut buffer, 0).unwrap();
+        assert_eq!(buffer, [0x00]);
+    }
+}
@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use proc_macro2::{Ident, Span};
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
 use quote::quote;
 
 pub(crate) fn deserialize_struct(input: syn::ItemStruct) -> TokenStream {
@@ -15,87 +15,115 @@ pub(crate) fn deserialize_struct(input: syn::ItemStruct) -> TokenStream {
         .map(|field| field.ident.as_ref().expect("struct field without ident"))
         .collect();
 
-    let expecting = format!("Any of: {:?}", idents);
+    let visitor = struct_visitor(quote!(#struct_ident), quote!(#struct_ident), &idents);
 
     quote!(
         #[automatically_derived]
         impl ::serialize::Deserialize for #struct_ident {
             fn deserialize<T: ::serialize::Deserializer>(deserializer: T) -> Result<Self, T::Error> {
-                use ::serialize::deserialization::Error;
+                #visitor
 
-                #[allow(non_camel_case_types)]
-                enum Field {
-                    #(#idents,)*
-                }
+                deserializer.deserialize_struct(StructVisitor)
+            }
+        }
+    )
+    .into()
+}
 
-                impl ::serialize::Deserialize for Field {
-                    fn deserialize<T: ::serialize::Deserializer>(deserializer: T) -> Result<Self, T::Error> {
-                        struct FieldVisitor;
+/// Generates a `StructVisitor` that builds `constructor { fields... }` from a map
+///
+/// This is shared between structs and enum variants with named fields.
+fn struct_visitor(
+    value_type: TokenStream2,
+    constructor: TokenStream2,
+    idents: &[&Ident],
+) -> TokenStream2 {
+    let expecting = format!("Any of: {:?}", idents);
 
-                        impl ::serialize::Visitor for FieldVisitor {
-                            type Value = Field;
+    quote!(
+        #[allow(non_camel_case_types)]
+        enum Field {
+            #(#idents,)*
 
-                            const EXPECTS: &'static str = #expecting;
+            /// A field that is not part of the type, only produced if the
+            /// deserializer allows unknown fields
+            __unknown,
+        }
 
-                            fn visit_string<E>(&self, value: ::std::string::String) -> Result<Self::Value, E>
-                            where
-                                E: ::serialize::deserialization::Error,
-                            {
-                                let field = match value.as_str() {
-                                    #(
-                                        stringify!(#idents) => Field::#idents,
-                                    )*
-                                    _ => return Err(E::unknown_field(value)),
-                                };
-                                Ok(field)
-                            }
-                        }
+        impl ::serialize::Deserialize for Field {
+            fn deserialize<T: ::serialize::Deserializer>(deserializer: T) -> Result<Self, T::Error> {
+                struct FieldVisitor {
+                    allows_unknown_fields: bool,
+                }
 
-                        deserializer.deserialize_string(FieldVisitor)
+                impl ::serialize::Visitor for FieldVisitor {
+                    type Value = Field;
+
+                    const EXPECTS: &'static str = #expecting;
+
+                    fn visit_string<E>(&self, value: ::std::string::String) -> Result<Self::Value, E>
+                    where
+                        E: ::serialize::deserialization::Error,
+                    {
+                        let field = match value.as_str() {
+                            #(
+                                stringify!(#idents) => Field::#idents,
+                            )*
+                            _ if self.allows_unknown_fields => Field::__unknown,
+                            _ => return Err(E::unknown_field(value)),
+                        };
+                        Ok(field)
                     }
                 }
 
-                struct StructVisitor;
+                let visitor = FieldVisitor {
+                    allows_unknown_fields: deserializer.allows_unknown_fields(),
+                };
+                deserializer.deserialize_string(visitor)
+            }
+        }
 
-                impl ::serialize::Visitor for StructVisitor {
-                    type Value = #struct_ident;
+        struct StructVisitor;
 
-                    const EXPECTS: &'static str = "a map";
+        impl ::serialize::Visitor for StructVisitor {
+            type Value = #value_type;
 
-                    fn visit_map<M>(&self, mut value: M) -> Result<Self::Value, M::Error>
-                    where M: ::serialize::deserialization::MapAccess {
-                        #(
-                            let mut #idents = None;
-                        )*
+            const EXPECTS: &'static str = "a map";
 
+            fn visit_map<M>(&self, mut __map: M) -> Result<Self::Value, M::Error>
+            where M: ::serialize::deserialization::MapAccess {
+                use ::serialize::deserialization::Error;
 
-                        loop {
-                            let Some(key) = value.next_key()? else {
-                                break;
-                            };
+                #(
+                    let mut #idents = None;
+                )*
 
-                            match key {
-                                #(
-                                    Field::#idents => #idents = Some(value.next_value()?),
-                                )*
-                            }
-                        }
 
-                        let instance = Self::Value {
-                            #(
-                                #idents: #idents.ok_or(Error::missing_field(stringify!(#idents)))?,
-                            )*
-                        };
+                loop {
+                    let Some(__key) = __map.next_key()? else {
+                        break;
+                    };
 
-                        Ok(instance)
+                    match __key {
+                        #(
+                            Field::#idents => #idents = Some(__map.next_value()?),
+                        )*
+                        Field::__unknown => {
+                            __map.next_value::<::serialize::deserialization::IgnoredAny>()?;
+                        },
                     }
                 }
 
-                deserializer.deserialize_struct(StructVisitor)
+                let instance = #constructor {
+                    #(
+                        #idents: #idents.ok_or(Error::missing_field(stringify!(#idents)))?,
+                    )*
+                };
+
+                Ok(instance)
             }
         }
     )
-    .into()
 }
 
 pub(crate) fn deserialize_enum(input: syn::ItemEnum) -> TokenStream {
@@ -120,8 +148,22 @@ pub(crate) fn deserialize_enum(input: syn::ItemEnum) -> TokenStream {
                 variant_arms.push(code);
             },
             syn::Fields::Named(named_fields) => {
-                _ = named_fields;
-                todo!("implement deserializing enum struct fields");
+                let field_names: Vec<&Ident> =
+                    named_fields.named.iter().flat_map(|f| &f.ident).collect();
+                let visitor = struct_visitor(
+                    quote!(#enum_ident),
+                    quote!(#enum_ident::#ident),
+                    &field_names,
+                );
+
+                let code = quote! {
+                    (Variant::#ident, variant_data) => {
+                        #visitor
+
+                        variant_data.struct_variant(StructVisitor)?
+                    }
+                };
+                variant_arms.push(code);
             },
             syn::Fields::Unnamed(unnamed_fields) => {
                 // Generate some field names so we can refer to them in code
@@ -146,6 +188,11 @@ pub(crate) fn deserialize_enum(input: syn::ItemEnum) -> TokenStream {
                                     let #field_names = value.next_element()?.ok_or(S::Error::expected("at least one more value"))?;
                                 )*
 
+                                // Consume the end of the sequence
+                                if value.next_element::<::serialize::deserialization::IgnoredAny>()?.is_some() {
+                                    return Err(S::Error::expected("no more values"));
+                                }
+
                                 let parsed_value = #enum_ident::#ident(
                                     #(
                                         #field_names,
@@ -32,10 +32,15 @@ impl Error for JsonError {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     String(String),
-    Numeric(u32),
+
+    /// A non-negative integer
+    Numeric(usize),
+
+    /// Any other number
+    Float(f64),
     True,
     False,
     Null,
@@ -83,6 +88,66 @@ impl<'a> JsonDeserializer<'a> {
         Some(value)
     }
 
+    /// <https://www.rfc-editor.org/rfc/rfc8259#section-6>
+    #[must_use]
+    fn consume_number(&mut self) -> Option<Token> {
+        let remaining = self.chars.remaining();
+        let bytes = remaining.as_bytes();
+
+        let consume_digits = |i: &mut usize| {
+            let start = *i;
+            while bytes.get(*i).is_some_and(u8::is_ascii_digit) {
+                *i += 1;
+            }
+            *i != start
+        };
+
+        let mut length = 0;
+        let mut is_integer = true;
+
+        if bytes.first() == Some(&b'-') {
+            is_integer = false;
+            length += 1;
+        }
+
+        if !consume_digits(&mut length) {
+            return None;
+        }
+
+        if bytes.get(length) == Some(&b'.') {
+            is_integer = false;
+            length += 1;
+
+            if !consume_digits(&mut length) {
+                return None;
+            }
+        }
+
+        if matches!(bytes.get(length), Some(b'e' | b'E')) {
+            is_integer = false;
+            length += 1;
+
+            if matches!(bytes.get(length), Some(b'+' | b'-')) {
+                length += 1;
+            }
+
+            if !consume_digits(&mut length) {
+                return None;
+            }
+        }
+
+        let number = &remaining[..length];
+
+        // Integers that are too large for a usize are treated as floats instead
+        let token = match number.parse() {
+            Ok(value) if is_integer => Token::Numeric(value),
+            _ => Token::Float(number.parse().ok()?),
+        };
+
+        _ = self.chars.advance_by(length);
+        Some(token)
+    }
+
     #[must_use]
     fn next_token(&mut self) -> Option<Token> {
         loop {
@@ -174,22 +239,7 @@ impl<'a> JsonDeserializer<'a> {
                     // whitespace is skipped
                     self.chars.next();
                 },
-                '0'..='9' => {
-                    // Parse a numeric value
-                    let mut num = 0;
-
-                    while let Some(c) = self.chars.current() {
-                        if let Some(digit) = c.to_digit(10) {
-                            num *= 10;
-                            num += digit;
-                            self.chars.next();
-                        } else {
-                            break;
-                        }
-                    }
-
-                    return Some(Token::Numeric(num));
-                },
+                '0'..='9' | '-' => return self.consume_number(),
                 't' | 'f' | 'n' => {
                     // Parse an identifier (true, false or null)
                     let remaining = self.chars.remaining();
@@ -235,6 +285,7 @@ impl<'a> Deserializer for &mut JsonDeserializer<'a> {
             Some(Token::CurlyBraceOpen) => self.deserialize_map(visitor),
             Some(Token::String(_)) => self.deserialize_string(visitor),
             Some(Token::Numeric(_)) => self.deserialize_usize(visitor),
+            Some(Token::Float(_)) => self.deserialize_f64(visitor),
             Some(Token::True | Token::False) => self.deserialize_bool(visitor),
             Some(Token::Null) => self.deserialize_option(visitor),
             _ => return Err(Error::expected("any valid json value")),
@@ -295,7 +346,18 @@ impl<'a> Deserializer for &mut JsonDeserializer<'a> {
             return Err(JsonError::UnexpectedToken);
         };
 
-        visitor.visit_usize(num as usize)
+        visitor.visit_usize(num)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor,
+    {
+        match self.next_token() {
+            Some(Token::Float(num)) => visitor.visit_f64(num),
+            Some(Token::Numeric(num)) => visitor.visit_f64(num as f64),
+            _ => Err(JsonError::UnexpectedToken),
+        }
     }
 
     fn deserialize_enum<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error> {
@@ -338,12 +400,14 @@ impl<'a, 'b> SequentialAccess for JsonSequence<'a, 'b> {
     where
         T: Deserialize,
     {
+        // The closing token might already have been consumed after the previous element
+        if self.done {
+            return Ok(None);
+        }
+
         if self.deserializer.peek_token() == Some(Token::BracketClose) {
             let _ = self.deserializer.next_token();
             self.done = true;
-        }
-
-        if self.done {
             return Ok(None);
         }
 
@@ -379,12 +443,14 @@ impl<'a, 'b> MapAccess for JsonMap<'a, 'b> {
     where
         K: Deserialize,
     {
+        // The closing token might already have been consumed after the previous element
+        if self.done {
+            return Ok(None);
+        }
+
         if self.deserializer.peek_token() == Some(Token::CurlyBraceClose) {
             let _ = self.deserializer.next_token();
             self.done = true;
-        }
-
-        if self.done {
             return Ok(None);
         }
 
@@ -513,4 +579,26 @@ mod tests {
         assert_eq!(tokens.next_token(), Some(Token::String("☂\n".to_string())));
         assert_eq!(tokens.next_token(), None);
     }
+
+    #[test]
+    fn numbers() {
+        let json = "[0, 42, -3, 1.5, 2e3, -0.25E-2, 1., 01]";
+        let mut tokens = JsonDeserializer::new(json);
+        assert_eq!(tokens.next_token(), Some(Token::BracketOpen));
+        assert_eq!(tokens.next_token(), Some(Token::Numeric(0)));
+        assert_eq!(tokens.next_token(), Some(Token::Comma));
+        assert_eq!(tokens.next_token(), Some(Token::Numeric(42)));
+        assert_eq!(tokens.next_token(), Some(Token::Comma));
+        assert_eq!(tokens.next_token(), Some(Token::Float(-3.)));
+        assert_eq!(tokens.next_token(), Some(Token::Comma));
+        assert_eq!(tokens.next_token(), Some(Token::Float(1.5)));
+        assert_eq!(tokens.next_token(), Some(Token::Comma));
+        assert_eq!(tokens.next_token(), Some(Token::Float(2000.)));
+        assert_eq!(tokens.next_token(), Some(Token::Comma));
+        assert_eq!(tokens.next_token(), Some(Token::Float(-0.0025)));
+        assert_eq!(tokens.next_token(), Some(Token::Comma));
+
+        // Digits after the decimal point are required
+        assert_eq!(tokens.next_token(), None);
+    }
 }
@@ -37,4 +37,80 @@ mod tests {
 
         assert_eq!(serialize_deserialize(value), value)
     }
+
+    #[test]
+    fn serialize_deserialize_float() {
+        for value in [0.0, 1.0, -2.5, 1e300, 1.0e-7] {
+            assert_eq!(serialize_deserialize(value), value);
+        }
+
+        assert_eq!(
+            JsonSerializer::serialize_to_string(f64::NAN).unwrap(),
+            "null"
+        );
+    }
+
+    #[test]
+    fn escape_strings() {
+        let s = "\"quoted\"\\\n\u{1}ü".to_string();
+        let serialized = JsonSerializer::serialize_to_string(s.as_str()).unwrap();
+
+        assert_eq!(serialized, r#""\"quoted\"\\\n\u0001ü""#);
+        assert_eq!(serialize_deserialize(s.clone()), s);
+    }
+
+    #[test]
+    fn pretty_print() {
+        let value = Value::Map(
+            [(
+                "list".to_string(),
+                Value::List(vec![Value::Integer(1), Value::List(vec![])]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+
+        assert_eq!(
+            JsonSerializer::serialize_to_string_pretty(value).unwrap(),
+            "{\n    \"list\": [\n        1,\n        []\n    ]\n}"
+        );
+    }
+
+    #[test]
+    fn serialize_deserialize_derived() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Bookmark {
+            title: String,
+            visits: usize,
+            tags: Vec<String>,
+        }
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        enum Entry {
+            Separator,
+            Folder(String, Vec<Bookmark>),
+            Bookmark { bookmark: Bookmark, pinned: bool },
+        }
+
+        let bookmark = || Bookmark {
+            title: "Stormlicht".to_string(),
+            visits: 3,
+            tags: vec!["browser".to_string()],
+        };
+
+        let entries = vec![
+            Entry::Separator,
+            Entry::Folder("Rust".to_string(), vec![bookmark()]),
+            Entry::Bookmark {
+                bookmark: bookmark(),
+                pinned: true,
+            },
+        ];
+
+        assert_eq!(serialize_deserialize(bookmark()), bookmark());
+        for entry in entries {
+            let expected = format!("{entry:?}");
+            assert_eq!(format!("{:?}", serialize_deserialize(entry)), expected);
+        }
+    }
 }
@@ -8,8 +8,15 @@ use serialize::{
     Serialize,
 };
 
+/// Number of spaces per indentation level in pretty-printed output
+const INDENT_WIDTH: usize = 4;
+
 pub struct JsonSerializer<W> {
     writer: W,
+    is_pretty: bool,
+
+    /// Number of containers that are currently open
+    depth: usize,
 }
 
 impl JsonSerializer<String> {
@@ -17,30 +24,123 @@ impl JsonSerializer<String> {
     where
         T: Serialize,
     {
-        let mut serializer = Self {
-            writer: String::new(),
-        };
+        let mut serializer = Self::new(String::new());
+        value.serialize_to(&mut serializer)?;
+
+        Ok(serializer.writer)
+    }
+
+    /// Like [serialize_to_string](Self::serialize_to_string), but puts every element of
+    /// a sequence or map on its own, indented line
+    pub fn serialize_to_string_pretty<T>(value: T) -> Result<String, fmt::Error>
+    where
+        T: Serialize,
+    {
+        let mut serializer = Self::pretty(String::new());
         value.serialize_to(&mut serializer)?;
 
         Ok(serializer.writer)
     }
 }
 
+impl<W> JsonSerializer<W>
+where
+    W: Write,
+{
+    /// Create a serializer that produces compact output without any whitespace
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            is_pretty: false,
+            depth: 0,
+        }
+    }
+
+    /// Create a serializer that produces human-readable output
+    #[must_use]
+    pub fn pretty(writer: W) -> Self {
+        Self {
+            writer,
+            is_pretty: true,
+            depth: 0,
+        }
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_newline(&mut self) -> Result<(), fmt::Error> {
+        if self.is_pretty {
+            write!(self.writer, "\n{:1$}", "", self.depth * INDENT_WIDTH)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_key_value_separator(&mut self) -> Result<(), fmt::Error> {
+        if self.is_pretty {
+            write!(self.writer, ": ")
+        } else {
+            write!(self.writer, ":")
+        }
+    }
+
+    /// <https://www.rfc-editor.org/rfc/rfc8259#section-7>
+    fn write_escaped_string(&mut self, value: &str) -> Result<(), fmt::Error> {
+        self.writer.write_char('"')?;
+
+        for c in value.chars() {
+            match c {
+                '"' => self.writer.write_str("\\\"")?,
+                '\\' => self.writer.write_str("\\\\")?,
+                '\n' => self.writer.write_str("\\n")?,
+                '\r' => self.writer.write_str("\\r")?,
+                '\t' => self.writer.write_str("\\t")?,
+                '\x08' => self.writer.write_str("\\b")?,
+                '\x0c' => self.writer.write_str("\\f")?,
+                '\x00'..='\x1f' => write!(self.writer, "\\u{:04x}", c as u32)?,
+                other => self.writer.write_char(other)?,
+            }
+        }
+
+        self.writer.write_char('"')
+    }
+}
+
 impl<W> Serializer for JsonSerializer<W>
 where
     W: Write,
 {
     type Error = fmt::Error;
 
-    type SequenceSerializer<'a> = SequenceSerializer<'a, W> where Self: 'a, W: 'a;
+    type SequenceSerializer<'a>
+        = SequenceSerializer<'a, W>
+    where
+        Self: 'a,
+        W: 'a;
 
-    type MapSerializer<'a> = MapSerializer<'a, W> where Self: 'a;
+    type MapSerializer<'a>
+        = MapSerializer<'a, W>
+    where
+        Self: 'a;
 
-    type StructSerializer<'a> = StructSerializer<'a, W> where Self: 'a;
+    type StructSerializer<'a>
+        = StructSerializer<'a, W>
+    where
+        Self: 'a;
 
-    type StructVariantSerializer<'a> = StructVariantSerializer<'a, W> where Self: 'a;
+    type StructVariantSerializer<'a>
+        = StructVariantSerializer<'a, W>
+    where
+        Self: 'a;
 
-    type TupleVariantSerializer<'a> = TupleVariantSerializer<'a, W> where Self: 'a;
+    type TupleVariantSerializer<'a>
+        = TupleVariantSerializer<'a, W>
+    where
+        Self: 'a;
 
     fn serialize_bool(&mut self, value: bool) -> Result<(), Self::Error> {
         match value {
@@ -52,13 +152,24 @@ where
     }
 
     fn serialize_string(&mut self, value: &str) -> Result<(), Self::Error> {
-        write!(self.writer, "{value:?}")
+        self.write_escaped_string(value)
     }
 
     fn serialize_usize(&mut self, value: usize) -> Result<(), Self::Error> {
         write!(self.writer, "{value}")
     }
 
+    fn serialize_f64(&mut self, value: f64) -> Result<(), Self::Error> {
+        if value.is_finite() {
+            // The debug representation always contains a decimal point or exponent,
+            // which makes the value round-trip as a float
+            write!(self.writer, "{value:?}")
+        } else {
+            // JSON cannot represent NaN or infinity, javascript serializes them as null too
+            write!(self.writer, "null")
+        }
+    }
+
     fn serialize_option<T>(&mut self, value: &Option<T>) -> Result<(), Self::Error>
     where
         T: Serialize,
@@ -94,7 +205,10 @@ where
         &'a mut self,
         variant_name: &str,
     ) -> Result<Self::StructVariantSerializer<'a>, Self::Error> {
-        write!(self.writer, "{{{variant_name:?}:{{")?;
+        write!(self.writer, "{{")?;
+        self.write_escaped_string(variant_name)?;
+        self.write_key_value_separator()?;
+        write!(self.writer, "{{")?;
 
         let struct_variant_serializer = StructVariantSerializer(CommaSeparatedSequence::new(self));
 
@@ -105,7 +219,10 @@ where
         &'a mut self,
         variant_name: &str,
     ) -> Result<Self::TupleVariantSerializer<'a>, Self::Error> {
-        write!(self.writer, "{{{variant_name:?}:[")?;
+        write!(self.writer, "{{")?;
+        self.write_escaped_string(variant_name)?;
+        self.write_key_value_separator()?;
+        write!(self.writer, "[")?;
 
         let struct_variant_serializer = TupleVariantSerializer(CommaSeparatedSequence::new(self));
 
@@ -120,7 +237,9 @@ where
     where
         T: Serialize,
     {
-        write!(self.writer, "{{{variant_name:?}:")?;
+        write!(self.writer, "{{")?;
+        self.write_escaped_string(variant_name)?;
+        self.write_key_value_separator()?;
         value.serialize_to(self)?;
         write!(self.writer, "}}")?;
 
@@ -159,8 +278,8 @@ where
         Ok(())
     }
 
-    fn finish(mut self) -> Result<(), Self::Error> {
-        write!(self.0.writer(), "]")
+    fn finish(self) -> Result<(), Self::Error> {
+        self.0.finish("]")
     }
 }
 
@@ -178,13 +297,13 @@ where
         self.0.start_element()?;
 
         key.serialize_to(self.0.serializer)?;
-        write!(self.0.writer(), ":")?;
+        self.0.serializer.write_key_value_separator()?;
         value.serialize_to(self.0.serializer)?;
         Ok(())
     }
 
-    fn finish(mut self) -> Result<(), Self::Error> {
-        write!(self.0.writer(), "}}")
+    fn finish(self) -> Result<(), Self::Error> {
+        self.0.finish("}")
     }
 }
 
@@ -218,14 +337,14 @@ where
     {
         self.0.start_element()?;
         name.serialize_to(self.0.serializer)?;
-        write!(self.0.serializer.writer, ":")?;
+        self.0.serializer.write_key_value_separator()?;
         value.serialize_to(self.0.serializer)?;
 
         Ok(())
     }
 
     fn finish(self) -> Result<(), Self::Error> {
-        write!(self.0.serializer.writer, "}}}}")
+        self.0.finish("}}")
     }
 }
 
@@ -246,7 +365,7 @@ where
     }
 
     fn finish(self) -> Result<(), Self::Error> {
-        write!(self.0.serializer.writer, "]}}")
+        self.0.finish("]}")
     }
 }
 
@@ -256,6 +375,8 @@ where
 {
     #[must_use]
     fn new(serializer: &'a mut JsonSerializer<W>) -> Self {
+        serializer.depth += 1;
+
         Self {
             serializer,
             is_first_element: true,
@@ -266,15 +387,21 @@ where
         if self.is_first_element {
             // There is no comma before the first element
             self.is_first_element = false;
-            Ok(())
         } else {
-            write!(self.serializer.writer, ",")
+            write!(self.serializer.writer, ",")?;
         }
+
+        self.serializer.write_newline()
     }
 
-    /// Convenience function to access the underlying writer
-    #[must_use]
-    fn writer(&mut self) -> &mut W {
-        &mut self.serializer.writer
+    fn finish(self, closing: &str) -> Result<(), <JsonSerializer<W> as Serializer>::Error> {
+        self.serializer.depth -= 1;
+
+        // Empty containers are kept on a single line
+        if !self.is_first_element {
+            self.serializer.write_newline()?;
+        }
+
+        write!(self.serializer.writer, "{closing}")
     }
 }
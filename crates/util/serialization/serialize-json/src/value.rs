@@ -10,6 +10,7 @@ use serialize::{
 pub enum Value {
     String(String),
     Integer(usize),
+    Float(f64),
     Boolean(bool),
     List(Vec<Value>),
     Map(HashMap<String, Value>),
@@ -24,6 +25,7 @@ impl Serialize for Value {
         match self {
             Self::String(s) => serializer.serialize_string(s),
             Self::Integer(int) => serializer.serialize_usize(*int),
+            Self::Float(float) => serializer.serialize_f64(*float),
             Self::Boolean(boolean) => serializer.serialize_bool(*boolean),
             Self::List(list) => {
                 let mut sequence = serializer.serialize_sequence()?;
@@ -77,6 +79,13 @@ impl Deserialize for Value {
                 Ok(Value::Integer(value))
             }
 
+            fn visit_f64<E>(&self, value: f64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(Value::Float(value))
+            }
+
             fn visit_map<M>(&self, mut value: M) -> Result<Self::Value, M::Error>
             where
                 M: MapAccess,
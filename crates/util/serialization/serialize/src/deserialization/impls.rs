@@ -1,10 +1,95 @@
 use std::{ascii, collections::HashMap, hash::Hash, marker::PhantomData, net};
 
 use crate::{
-    deserialization::{EnumAccess, EnumVariantAccess, Error, MapAccess, SequentialAccess},
+    deserialization::{
+        EnumAccess, EnumVariantAccess, Error, IgnoredAny, MapAccess, SequentialAccess,
+    },
     Deserialize, Deserializer, Visitor,
 };
 
+impl Deserialize for IgnoredAny {
+    fn deserialize<D: Deserializer>(deserializer: D) -> Result<Self, D::Error> {
+        struct IgnoredAnyVisitor;
+
+        impl Visitor for IgnoredAnyVisitor {
+            type Value = IgnoredAny;
+
+            const EXPECTS: &'static str = "any value";
+
+            fn visit_bool<E>(&self, _value: bool) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(IgnoredAny)
+            }
+
+            fn visit_usize<E>(&self, _value: usize) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(IgnoredAny)
+            }
+
+            fn visit_f64<E>(&self, _value: f64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(IgnoredAny)
+            }
+
+            fn visit_none<E>(&self) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(IgnoredAny)
+            }
+
+            fn visit_some<D>(&self, value: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer,
+            {
+                IgnoredAny::deserialize(value)
+            }
+
+            fn visit_string<E>(&self, _value: String) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(IgnoredAny)
+            }
+
+            fn visit_sequence<S>(&self, mut value: S) -> Result<Self::Value, S::Error>
+            where
+                S: SequentialAccess,
+            {
+                while value.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(IgnoredAny)
+            }
+
+            fn visit_map<M>(&self, mut value: M) -> Result<Self::Value, M::Error>
+            where
+                M: MapAccess,
+            {
+                while value.next_key::<IgnoredAny>()?.is_some() {
+                    value.next_value::<IgnoredAny>()?;
+                }
+                Ok(IgnoredAny)
+            }
+
+            fn visit_enum<E>(&self, value: E) -> Result<Self::Value, E::Error>
+            where
+                E: EnumAccess,
+            {
+                // The variant data can be consumed like any other value
+                let (IgnoredAny, variant_data) = value.variant()?;
+                variant_data.newtype_variant::<IgnoredAny>()
+            }
+        }
+
+        deserializer.deserialize_any(IgnoredAnyVisitor)
+    }
+}
+
 impl Deserialize for String {
     fn deserialize<D: Deserializer>(deserializer: D) -> Result<Self, D::Error> {
         struct StringVisitor;
@@ -157,6 +242,40 @@ impl Deserialize for usize {
     }
 }
 
+impl Deserialize for f64 {
+    fn deserialize<D: Deserializer>(deserializer: D) -> Result<Self, D::Error> {
+        struct F64Visitor;
+
+        impl Visitor for F64Visitor {
+            type Value = f64;
+
+            const EXPECTS: &'static str = "a number";
+
+            fn visit_f64<E>(&self, value: f64) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(value)
+            }
+
+            fn visit_usize<E>(&self, value: usize) -> Result<Self::Value, E>
+            where
+                E: Error,
+            {
+                Ok(value as f64)
+            }
+        }
+
+        deserializer.deserialize_f64(F64Visitor)
+    }
+}
+
+impl Deserialize for f32 {
+    fn deserialize<D: Deserializer>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(|value| value as f32)
+    }
+}
+
 impl<T: Deserialize> Deserialize for Vec<T> {
     fn deserialize<D: Deserializer>(deserializer: D) -> Result<Self, D::Error> {
         struct VecVisitor<T> {
@@ -38,11 +38,28 @@ pub trait Deserializer {
 
     fn deserialize_usize<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error>;
 
+    fn deserialize_f64<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error>;
+
     fn deserialize_option<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error>;
 
     fn deserialize_enum<V: Visitor>(self, visitor: V) -> Result<V::Value, Self::Error>;
+
+    /// Whether struct fields that are not known to the target type should be skipped
+    /// instead of causing an error
+    ///
+    /// Formats that are used for persistent storage can enable this to stay compatible with
+    /// data written by newer versions of a type.
+    fn allows_unknown_fields(&self) -> bool {
+        false
+    }
 }
 
+/// Consumes any value and discards it
+///
+/// This is useful to skip over data that the caller is not interested in.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IgnoredAny;
+
 pub trait SequentialAccess {
     type Error: Error;
 
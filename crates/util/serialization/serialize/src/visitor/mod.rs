@@ -24,6 +24,14 @@ pub trait Visitor {
         Err(E::expected(Self::EXPECTS))
     }
 
+    fn visit_f64<E>(&self, value: f64) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        _ = value;
+        Err(E::expected(Self::EXPECTS))
+    }
+
     fn visit_none<E>(&self) -> Result<Self::Value, E>
     where
         E: Error,
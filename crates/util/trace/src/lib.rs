@@ -0,0 +1,429 @@
+//! Lightweight instrumentation to find out where the browser spends its time
+//!
+//! Interesting sections of code are wrapped in spans:
+//! ```
+//! let _span = trace::span!(Layout, "compute fragments");
+//! // ... everything until the end of the scope is part of the span
+//! ```
+//!
+//! Recording is disabled by default, in which case spans cost no more than an atomic load.
+//! Once [enabled](enable), the recorded events can be exported in the
+//! [Trace Event Format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+//! which can be viewed in `chrome://tracing` or <https://ui.perfetto.dev>.
+
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::HashMap,
+    fmt, fs, io,
+    path::Path,
+    process,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serialize::Serialize;
+use serialize_json::JsonSerializer;
+
+/// Records the events of the whole process
+static RECORDER: Recorder = Recorder::new();
+
+/// The names of all threads that recorded events, by their id
+static THREAD_NAMES: Mutex<Vec<(u64, String)>> = Mutex::new(Vec::new());
+
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD_ID: Cell<Option<u64>> = const { Cell::new(None) };
+}
+
+/// What kind of work a span describes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    /// Parsing of HTML and CSS
+    Parse,
+
+    /// Computing the style of elements
+    Style,
+
+    /// Arranging boxes on the page
+    Layout,
+
+    /// Drawing the page
+    Paint,
+
+    /// Loading resources
+    Net,
+}
+
+impl Category {
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Parse => "parse",
+            Self::Style => "style",
+            Self::Layout => "layout",
+            Self::Paint => "paint",
+            Self::Net => "net",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Begin,
+    End,
+}
+
+#[derive(Clone, Debug)]
+struct Event {
+    category: Category,
+    name: Cow<'static, str>,
+    phase: Phase,
+
+    /// Time since recording started
+    timestamp: Duration,
+    thread: u64,
+}
+
+/// Collects events while recording is enabled
+#[derive(Debug)]
+struct Recorder {
+    is_enabled: AtomicBool,
+    start: OnceLock<Instant>,
+    events: Mutex<Vec<Event>>,
+}
+
+/// A single entry in a chrome trace file
+///
+/// The field names are given by the file format.
+#[derive(Serialize)]
+struct TraceEvent {
+    name: String,
+    cat: String,
+    ph: String,
+
+    /// Timestamp in microseconds
+    ts: u64,
+    pid: u32,
+    tid: u64,
+    args: HashMap<String, String>,
+}
+
+impl Recorder {
+    const fn new() -> Self {
+        Self {
+            is_enabled: AtomicBool::new(false),
+            start: OnceLock::new(),
+            events: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn enable(&self) {
+        self.start.get_or_init(Instant::now);
+        self.is_enabled.store(true, Ordering::Relaxed);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.is_enabled.load(Ordering::Relaxed)
+    }
+
+    fn record(&self, category: Category, name: Cow<'static, str>, phase: Phase) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let start = self.start.get_or_init(Instant::now);
+        let event = Event {
+            category,
+            name,
+            phase,
+            timestamp: start.elapsed(),
+            thread: current_thread_id(),
+        };
+
+        self.events
+            .lock()
+            .expect("trace event lock was poisoned")
+            .push(event);
+    }
+
+    fn to_chrome_trace(&self) -> String {
+        let events = self.events.lock().expect("trace event lock was poisoned");
+
+        let mut trace_events = vec![];
+
+        // Metadata events give names to the threads in the trace
+        for (thread, thread_name) in THREAD_NAMES
+            .lock()
+            .expect("thread name lock was poisoned")
+            .iter()
+        {
+            if events.iter().any(|event| event.thread == *thread) {
+                trace_events.push(TraceEvent {
+                    name: "thread_name".to_string(),
+                    cat: String::new(),
+                    ph: "M".to_string(),
+                    ts: 0,
+                    pid: process::id(),
+                    tid: *thread,
+                    args: HashMap::from([("name".to_string(), thread_name.clone())]),
+                });
+            }
+        }
+
+        trace_events.extend(events.iter().map(|event| {
+            TraceEvent {
+                name: event.name.to_string(),
+                cat: event.category.name().to_string(),
+                ph: match event.phase {
+                    Phase::Begin => "B",
+                    Phase::End => "E",
+                }
+                .to_string(),
+                ts: event.timestamp.as_micros() as u64,
+                pid: process::id(),
+                tid: event.thread,
+                args: HashMap::new(),
+            }
+        }));
+
+        JsonSerializer::serialize_to_string(HashMap::from([("traceEvents", trace_events)]))
+            .expect("Writing to a string cannot fail")
+    }
+}
+
+/// Start recording events
+pub fn enable() {
+    RECORDER.enable();
+}
+
+/// Whether events are currently being recorded
+#[must_use]
+pub fn is_enabled() -> bool {
+    RECORDER.is_enabled()
+}
+
+/// Record the beginning of a span, prefer [begin!] or [span!] over calling this directly
+pub fn begin(category: Category, name: impl Into<Cow<'static, str>>) {
+    RECORDER.record(category, name.into(), Phase::Begin);
+}
+
+/// Record the end of a span, prefer [end!] or [span!] over calling this directly
+pub fn end(category: Category, name: impl Into<Cow<'static, str>>) {
+    RECORDER.record(category, name.into(), Phase::End);
+}
+
+/// All events that were recorded so far, in the chrome trace event format
+#[must_use]
+pub fn to_chrome_trace() -> String {
+    RECORDER.to_chrome_trace()
+}
+
+/// Write all events that were recorded so far to a file, in the chrome trace event format
+pub fn save(path: &Path) -> io::Result<()> {
+    fs::write(path, to_chrome_trace())
+}
+
+/// Turn the arguments of a tracing macro into the name of an event
+///
+/// Names without any formatting arguments don't need to be allocated.
+#[doc(hidden)]
+#[must_use]
+pub fn event_name(arguments: fmt::Arguments<'_>) -> Cow<'static, str> {
+    match arguments.as_str() {
+        Some(name) => Cow::Borrowed(name),
+        None => Cow::Owned(arguments.to_string()),
+    }
+}
+
+fn current_thread_id() -> u64 {
+    THREAD_ID.with(|id| {
+        if let Some(id) = id.get() {
+            return id;
+        }
+
+        let new_id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        id.set(Some(new_id));
+
+        let thread = thread::current();
+        let thread_name = thread
+            .name()
+            .map_or_else(|| format!("Thread {new_id}"), ToOwned::to_owned);
+        THREAD_NAMES
+            .lock()
+            .expect("thread name lock was poisoned")
+            .push((new_id, thread_name));
+
+        new_id
+    })
+}
+
+/// A span that ends once it is dropped, created by [span!]
+#[must_use = "The span ends as soon as it is dropped"]
+#[derive(Debug)]
+pub struct Span {
+    /// `None` if recording was disabled when the span began
+    event: Option<(Category, Cow<'static, str>)>,
+}
+
+impl Span {
+    pub fn new(category: Category, name: impl Into<Cow<'static, str>>) -> Self {
+        if !is_enabled() {
+            return Self::disabled();
+        }
+
+        let name = name.into();
+        begin(category, name.clone());
+
+        Self {
+            event: Some((category, name)),
+        }
+    }
+
+    /// A span that does not record anything
+    pub const fn disabled() -> Self {
+        Self { event: None }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some((category, name)) = self.event.take() {
+            end(category, name);
+        }
+    }
+}
+
+/// Begin a span that ends when the returned [Span] is dropped
+///
+/// The first argument is a [Category], the remaining arguments are formatted
+/// like [format!] to produce the name of the span. Names are only formatted
+/// if recording is enabled.
+///
+/// ```
+/// let url = "https://example.com";
+/// let _span = trace::span!(Net, "load {url}");
+/// ```
+#[macro_export]
+macro_rules! span {
+    ($category: ident, $($name: tt)+) => {
+        if $crate::is_enabled() {
+            $crate::Span::new(
+                $crate::Category::$category,
+                $crate::event_name(::std::format_args!($($name)+)),
+            )
+        } else {
+            $crate::Span::disabled()
+        }
+    };
+}
+
+/// Record the beginning of a span, which must be ended with [end!] on the same thread
+///
+/// Takes the same arguments as [span!].
+#[macro_export]
+macro_rules! begin {
+    ($category: ident, $($name: tt)+) => {
+        if $crate::is_enabled() {
+            $crate::begin(
+                $crate::Category::$category,
+                $crate::event_name(::std::format_args!($($name)+)),
+            );
+        }
+    };
+}
+
+/// Record the end of a span that was started with [begin!]
+#[macro_export]
+macro_rules! end {
+    ($category: ident, $($name: tt)+) => {
+        if $crate::is_enabled() {
+            $crate::end(
+                $crate::Category::$category,
+                $crate::event_name(::std::format_args!($($name)+)),
+            );
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serialize::Deserialize;
+    use serialize_json::{JsonDeserializer, Value};
+
+    use super::*;
+
+    #[test]
+    fn disabled_recorder() {
+        let recorder = Recorder::new();
+        recorder.record(Category::Parse, "html".into(), Phase::Begin);
+
+        assert!(recorder.events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn chrome_trace_format() {
+        let recorder = Recorder::new();
+        recorder.enable();
+        recorder.record(Category::Layout, "layout".into(), Phase::Begin);
+        recorder.record(Category::Layout, "layout".into(), Phase::End);
+
+        let trace = recorder.to_chrome_trace();
+        let mut deserializer = JsonDeserializer::new(&trace);
+        let trace = Value::deserialize(&mut deserializer).unwrap();
+
+        let events: Vec<_> = trace.as_map().unwrap()["traceEvents"]
+            .as_list()
+            .unwrap()
+            .map(|event| event.as_map().unwrap())
+            .collect();
+
+        // The current thread is named, followed by the two events
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["ph"].as_str(), Some("M"));
+        assert_eq!(events[1]["ph"].as_str(), Some("B"));
+        assert_eq!(events[1]["cat"].as_str(), Some("layout"));
+        assert_eq!(events[1]["name"].as_str(), Some("layout"));
+        assert_eq!(events[2]["ph"].as_str(), Some("E"));
+    }
+
+    #[test]
+    fn spans() {
+        enable();
+
+        let document = "index.html";
+        {
+            let _span = span!(Parse, "parse {document}");
+            begin!(Style, "compute styles");
+            end!(Style, "compute styles");
+        }
+
+        let events = RECORDER.events.lock().unwrap();
+        let events: Vec<_> = events
+            .iter()
+            .filter(|event| event.thread == current_thread_id())
+            .collect();
+        let names: Vec<_> = events
+            .iter()
+            .map(|event| (event.name.as_ref(), event.phase))
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                ("parse index.html", Phase::Begin),
+                ("compute styles", Phase::Begin),
+                ("compute styles", Phase::End),
+                ("parse index.html", Phase::End),
+            ]
+        );
+
+        // Names without arguments are not allocated
+        assert!(matches!(events[1].name, Cow::Borrowed(_)));
+    }
+}
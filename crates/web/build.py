@@ -5,6 +5,8 @@ import os
 import pathlib
 
 SIDES = ["top", "right", "bottom", "left"]
+LOGICAL_SIDES = ["block-start", "block-end", "inline-start", "inline-end"]
+LOGICAL_AXES = ["block", "inline"]
 
 
 def to_camel_case(text):
@@ -32,13 +34,76 @@ def build_identifiers(env, target_dir):
     with open("identifiers.json", "r") as infile:
         identifiers = json.load(infile)
 
+    # The lowercase version of every identifier is part of the static set too,
+    # so case-insensitive comparisons never need to intern a new string
+    mixed_case = {
+        identifier: identifier.lower()
+        for identifier in identifiers
+        if identifier != identifier.lower()
+    }
+    identifiers = list(dict.fromkeys(identifiers + list(mixed_case.values())))
+
     template = env.get_template("identifiers.rs.jinja")
-    autogenerated_code = template.render(identifiers=identifiers)
+    autogenerated_code = template.render(identifiers=identifiers, mixed_case=mixed_case)
 
     with open(target_dir / "identifiers.rs", "w") as outfile:
         outfile.write(autogenerated_code)
 
 
+def expand_longhands(properties):
+    """Flatten the list of properties into the longhands that are stored in a computed style"""
+    longhands = []
+    for p in properties:
+        if "longhands" in p:
+            template = p["longhands"]
+            for side in SIDES:
+                longhands.append(
+                    {
+                        "name": template["name"].replace("SIDE", side),
+                        "specification": template["specification"].replace("SIDE", side),
+                        "value": template["value"],
+                        "initial": template["initial"],
+                        "inherited": p["inherited"],
+                        "group": p["group"],
+                        "animatable": p.get("animatable", False),
+                    }
+                )
+        else:
+            longhands.append(
+                {
+                    "name": p["name"],
+                    "specification": p["specification"],
+                    "value": p["value"],
+                    "initial": p["initial"],
+                    "inherited": p["inherited"],
+                    "group": p["group"],
+                    "animatable": p.get("animatable", False),
+                }
+            )
+
+    return longhands
+
+
+def group_longhands(longhands):
+    """Partition the longhands into the groups that share storage in a computed style"""
+    groups = {}
+    for longhand in longhands:
+        group = groups.setdefault(
+            longhand["group"], {"inherited": longhand["inherited"], "longhands": []}
+        )
+
+        # Groups are shared between styles as a whole, so they can't mix
+        # inherited and non-inherited properties
+        if group["inherited"] != longhand["inherited"]:
+            raise ValueError(
+                f"{longhand['name']} does not have the same inheritance as the other properties in group {longhand['group']!r}"
+            )
+
+        group["longhands"].append(longhand)
+
+    return groups
+
+
 def build_properties(env, target_dir):
     with open("properties.json", "r") as infile:
         properties = json.load(infile)
@@ -46,23 +111,40 @@ def build_properties(env, target_dir):
     # Build properties.rs
     template = env.get_template("properties.rs.jinja")
     autogenerated_code = template.render(
-        properties=properties, to_camel_case=to_camel_case, SIDES=SIDES
+        properties=properties,
+        longhands=expand_longhands(properties),
+        to_camel_case=to_camel_case,
+        SIDES=SIDES,
+        LOGICAL_SIDES=LOGICAL_SIDES,
+        LOGICAL_AXES=LOGICAL_AXES,
     )
 
     with open(target_dir / "properties.rs", "w") as outfile:
         outfile.write(autogenerated_code)
 
     # Build computed_style.rs
-    inherited_properties = [p for p in properties if p["inherited"]]
-    non_inherited_properties = [p for p in properties if not p["inherited"]]
+    longhands = expand_longhands(properties)
+    groups = group_longhands(longhands)
+
+    # Collect the shorthands whose longhands can be transitioned
+    animatable_shorthands = {}
+    for p in properties:
+        if p.get("animatable", False) and "longhands" in p:
+            animatable_shorthands[p["name"]] = [
+                p["longhands"]["name"].replace("SIDE", side) for side in SIDES
+            ]
 
     template = env.get_template("computed_style.rs.jinja")
     autogenerated_code = template.render(
-        inherited_properties=inherited_properties,
-        non_inherited_properties=non_inherited_properties,
+        properties=properties,
+        longhands=longhands,
+        groups=groups,
+        animatable_shorthands=animatable_shorthands,
         to_snake_case=to_snake_case,
         to_camel_case=to_camel_case,
         SIDES=SIDES,
+        LOGICAL_SIDES=LOGICAL_SIDES,
+        LOGICAL_AXES=LOGICAL_AXES,
     )
 
     with open(target_dir / "computed_style.rs", "w") as outfile:
@@ -161,6 +161,15 @@ pub fn generate() -> Result<(), io::Error> {
             output
         });
 
+    let domtype_mark_match_arms: String =
+        type_journal
+            .types
+            .iter()
+            .fold(String::new(), |mut output, typename| {
+                let _ = write!(output, "Self::{typename} => unsafe {{ ptr.mark_as::<{DOM_OBJECT_MODULE_PATH}::{typename}>() }},");
+                output
+            });
+
     // For every known type, find the list of its parent types
     let mut parents = HashMap::with_capacity(type_journal.types.len());
     for typename in type_journal.types.iter() {
@@ -230,9 +239,22 @@ pub fn generate() -> Result<(), io::Error> {
                     {cast_type_match_arms}
                 }}
             }}
+
+            /// Mark the object behind a [DomPtr](crate::dom::DomPtr) whose underlying type is `self`
+            ///
+            /// The pointer might point to any parent type, but the object must be traced
+            /// as its actual type, to not miss any references.
+            pub(crate) fn mark<T: DomTyped>(&self, ptr: &crate::dom::DomPtr<T>) {{
+                debug_assert_eq!(*self, ptr.underlying_type());
+
+                // SAFETY: self is the actual type of the object
+                match self {{
+                    {domtype_mark_match_arms}
+                }}
+            }}
         }}
 
-        pub trait DomTyped {{
+        pub trait DomTyped: ::gc::Trace + 'static {{
             fn as_type() -> DomType;
         }}
 
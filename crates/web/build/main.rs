@@ -3,7 +3,11 @@
 mod inheritance;
 
 use buildutils::PYTHON;
-use std::{env, path::PathBuf, process::Command};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 pub fn main() {
     let out_dir = env::var_os("OUT_DIR").unwrap();
@@ -28,4 +32,31 @@ pub fn main() {
         .expect("Failed to run python build script");
 
     inheritance::generate().expect("Generating inheritance code failed");
+
+    emit_commit_hash();
+}
+
+/// Make the current commit available to `about:version`
+///
+/// Builds outside of a git checkout don't know their commit, which is fine.
+fn emit_commit_hash() {
+    let git_head = Path::new("../../.git/HEAD");
+    if git_head.exists() {
+        println!("cargo:rerun-if-changed={}", git_head.display());
+    }
+
+    let Ok(output) = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+    else {
+        return;
+    };
+
+    if !output.status.success() {
+        return;
+    }
+
+    if let Ok(commit) = String::from_utf8(output.stdout) {
+        println!("cargo:rustc-env=STORMLICHT_COMMIT={}", commit.trim());
+    }
 }
@@ -0,0 +1,52 @@
+//! The `about:cache` page, which lists all resources in the memory cache
+//!
+//! The cache is cleared by navigating to `about:cache?clear`.
+
+use std::fmt::Write;
+
+use resourceloader::RESOURCE_LOADER;
+use sl_std::memory::format_size;
+use url::URL;
+
+use super::{document, has_parameter};
+use crate::html::escape_into;
+
+pub(super) fn load(url: &URL, allow_changes: bool) -> String {
+    let cache = RESOURCE_LOADER.cache();
+
+    if allow_changes && has_parameter(url, "clear") {
+        cache.clear();
+    }
+
+    let mut entries = cache.entries();
+    entries.sort_by_cached_key(|(url, _)| url.serialize(url::ExcludeFragment::Yes).to_string());
+
+    let mut html = String::new();
+    if entries.is_empty() {
+        html.push_str("<p>The cache is empty.</p>");
+    } else {
+        let total_size: usize = entries
+            .iter()
+            .map(|(_, resource)| resource.data().len())
+            .sum();
+        let _ = write!(
+            html,
+            "<p>{} resources, {}. <a class=\"clear\" href=\"about:cache?clear\">Clear cache</a></p>",
+            entries.len(),
+            format_size(total_size)
+        );
+    }
+
+    for (url, resource) in &entries {
+        html.push_str("<div class=\"entry\"><div class=\"name\">");
+        escape_into(&mut html, url.serialize(url::ExcludeFragment::Yes).as_str());
+        html.push_str("</div><div class=\"detail\">");
+        escape_into(
+            &mut html,
+            &resource.mime_metadata().computed_mime_type.to_string(),
+        );
+        let _ = write!(html, ", {}</div></div>", format_size(resource.data().len()));
+    }
+
+    document("Cache", &html)
+}
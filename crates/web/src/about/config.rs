@@ -0,0 +1,174 @@
+//! The `about:config` page, which lists all preferences and allows editing them
+//!
+//! A preference is changed by navigating to `about:config?<name>=<value>` and reset to its
+//! default value by navigating to `about:config?reset=<name>`. Changes take effect immediately.
+//! Other pages cannot change preferences by linking to these urls.
+
+use settings::{Preferences, TextAntialiasing, Theme, SETTINGS};
+use sl_std::percent_encode::{percent_encode, EncodeSet};
+use url::URL;
+
+use super::{document, query_parameters};
+use crate::html::escape_into;
+
+const CONFIG_URL: &str = "about:config";
+
+/// Feedback about a requested change, displayed above the list of preferences
+#[derive(Debug, PartialEq)]
+enum Message {
+    Changed(String),
+    Failed(String),
+}
+
+pub(super) fn load(url: &URL, allow_changes: bool) -> String {
+    let parameters = if allow_changes {
+        query_parameters(url)
+    } else {
+        vec![]
+    };
+
+    let mut preferences = SETTINGS.preferences().clone();
+    let messages = apply_changes(&mut preferences, &parameters);
+
+    // Updating the preferences notifies all listeners, which applies the changes right away
+    let has_changed = preferences != *SETTINGS.preferences();
+    if has_changed
+        && let Err(error) = SETTINGS.update_preferences(|current| *current = preferences.clone())
+    {
+        log::error!("Failed to save preferences: {error}");
+    }
+
+    render(&preferences, &messages)
+}
+
+fn apply_changes(preferences: &mut Preferences, parameters: &[(String, String)]) -> Vec<Message> {
+    let defaults = Preferences::default();
+
+    parameters
+        .iter()
+        .map(|(name, value)| {
+            let (name, value) = if name == "reset" {
+                let Some(default) = defaults.get(value) else {
+                    return Message::Failed(format!("There is no preference called {value:?}"));
+                };
+                (value, default)
+            } else {
+                (name, value.clone())
+            };
+
+            match preferences.set(name, &value) {
+                Ok(()) => Message::Changed(format!("{name} was set to {value:?}")),
+                Err(error) => {
+                    Message::Failed(format!("Could not set {name} to {value:?}: {error}"))
+                },
+            }
+        })
+        .collect()
+}
+
+fn render(preferences: &Preferences, messages: &[Message]) -> String {
+    let defaults = Preferences::default();
+    let mut html = String::new();
+
+    for message in messages {
+        let (class, text) = match message {
+            Message::Changed(text) => ("message", text),
+            Message::Failed(text) => ("error", text),
+        };
+
+        html.push_str("<div class=\"");
+        html.push_str(class);
+        html.push_str("\">");
+        escape_into(&mut html, text);
+        html.push_str("</div>");
+    }
+
+    for name in Preferences::NAMES {
+        let value = preferences.get(name).unwrap_or_default();
+        let default = defaults.get(name).unwrap_or_default();
+
+        html.push_str("<div class=\"entry\"><span class=\"name\">");
+        html.push_str(name);
+        html.push_str("</span> ");
+        if value.is_empty() {
+            html.push_str("<i>unset</i>");
+        } else {
+            escape_into(&mut html, &value);
+        }
+
+        // Offer shortcuts for preferences that only have a few possible values
+        match name {
+            "javascript_enabled" | "reduced_motion" | "force_dark_mode" => {
+                let toggled = if value == "true" { "false" } else { "true" };
+                write_action(&mut html, name, toggled, "Toggle");
+            },
+            "theme" => {
+                for theme in Theme::ALL {
+                    if theme.name() != value {
+                        write_action(&mut html, name, theme.name(), theme.name());
+                    }
+                }
+            },
+            "text_antialiasing" => {
+                for mode in TextAntialiasing::ALL {
+                    if mode.name() != value {
+                        write_action(&mut html, name, mode.name(), mode.name());
+                    }
+                }
+            },
+            _ => {},
+        }
+
+        if value != default {
+            write_action(&mut html, "reset", name, "Reset");
+        }
+
+        html.push_str("</div>");
+    }
+
+    document("Configuration", &html)
+}
+
+fn write_action(html: &mut String, name: &str, value: &str, label: &str) {
+    let mut href = format!("{CONFIG_URL}?{name}=");
+    percent_encode(value.as_bytes(), EncodeSet::COMPONENT, &mut href);
+
+    html.push_str("<a class=\"action\" href=\"");
+    escape_into(html, &href);
+    html.push_str("\">");
+    escape_into(html, label);
+    html.push_str("</a>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn change_preferences() {
+        let mut preferences = Preferences::default();
+        let parameters = |parameters: &[(&str, &str)]| -> Vec<(String, String)> {
+            parameters
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect()
+        };
+
+        let messages = apply_changes(
+            &mut preferences,
+            &parameters(&[("font_size", "20"), ("theme", "purple"), ("unknown", "1")]),
+        );
+        assert_eq!(preferences.font_size, 20);
+        assert_eq!(preferences.theme, Theme::default());
+        assert!(matches!(messages[0], Message::Changed(_)));
+        assert!(matches!(messages[1], Message::Failed(_)));
+        assert!(matches!(messages[2], Message::Failed(_)));
+
+        let html = render(&preferences, &messages);
+        assert!(html.contains("<a class=\"action\" href=\"about:config?reset=font_size\">"));
+        assert!(!html.contains("about:config?reset=theme"));
+
+        apply_changes(&mut preferences, &parameters(&[("reset", "font_size")]));
+        assert_eq!(preferences, Preferences::default());
+    }
+}
@@ -0,0 +1,41 @@
+//! The `about:dns` page, which lists all cached dns lookups
+//!
+//! The cache is cleared by navigating to `about:dns?clear`.
+
+use std::fmt::Write;
+
+use dns::DNS_CACHE;
+use url::URL;
+
+use super::{document, has_parameter};
+use crate::html::escape_into;
+
+pub(super) fn load(url: &URL, allow_changes: bool) -> String {
+    if allow_changes && has_parameter(url, "clear") {
+        DNS_CACHE.clear();
+    }
+
+    let mut entries = DNS_CACHE.entries();
+    entries.sort_by_cached_key(|entry| entry.domain.to_string());
+
+    let mut html = String::new();
+    if entries.is_empty() {
+        html.push_str("<p>No domains are cached.</p>");
+    } else {
+        html.push_str("<p><a class=\"clear\" href=\"about:dns?clear\">Clear dns cache</a></p>");
+    }
+
+    for entry in &entries {
+        html.push_str("<div class=\"entry\"><span class=\"name\">");
+        escape_into(&mut html, &entry.domain.to_string());
+        html.push_str("</span> ");
+        escape_into(&mut html, &entry.ip.to_string());
+        let _ = write!(
+            html,
+            "<div class=\"detail\">Expires in {} seconds</div></div>",
+            entry.expires_in.as_secs()
+        );
+    }
+
+    document("DNS", &html)
+}
@@ -0,0 +1,163 @@
+//! The `about:history` page, which lists all visited pages
+//!
+//! Entries are deleted by navigating to `about:history?delete=<url>`, the whole
+//! history is cleared by navigating to `about:history?clear`.
+
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use settings::{History, HISTORY};
+use sl_std::percent_encode::{percent_encode, EncodeSet};
+use url::URL;
+
+use super::{document, query_parameters};
+use crate::html::escape_into;
+
+/// The url of the history page
+const HISTORY_URL: &str = "about:history";
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Delete(String),
+    Clear,
+}
+
+/// Perform the action requested by the url (if any and if allowed) and render the history page
+#[must_use]
+pub(super) fn load(url: &URL, allow_changes: bool) -> String {
+    let history = &*HISTORY;
+
+    let action = requested_action(url).filter(|_| allow_changes);
+    let result = match action {
+        Some(Action::Delete(url)) => history.remove(&url).map(|_| ()),
+        Some(Action::Clear) => history.clear(),
+        None => Ok(()),
+    };
+
+    if let Err(error) = result {
+        log::error!("Failed to update history: {error}");
+    }
+
+    render(history, now())
+}
+
+fn requested_action(url: &URL) -> Option<Action> {
+    query_parameters(url)
+        .into_iter()
+        .find_map(|(name, value)| match name.as_str() {
+            "clear" => Some(Action::Clear),
+            "delete" => Some(Action::Delete(value)),
+            _ => None,
+        })
+}
+
+fn render(history: &History, now: u64) -> String {
+    let mut html = String::new();
+
+    let entries = history.entries();
+    if entries.is_empty() {
+        html.push_str("<p>You have not visited any pages yet.</p>");
+    } else {
+        let _ = write!(
+            html,
+            "<p><a class=\"clear\" href=\"{HISTORY_URL}?clear\">Clear history</a></p>"
+        );
+    }
+
+    // Show the most recently visited pages first
+    for entry in entries.iter().rev() {
+        let title = if entry.title.is_empty() {
+            &entry.url
+        } else {
+            &entry.title
+        };
+
+        let mut delete_url = format!("{HISTORY_URL}?delete=");
+        percent_encode(entry.url.as_bytes(), EncodeSet::COMPONENT, &mut delete_url);
+
+        html.push_str("<div class=\"entry\"><div><a href=\"");
+        escape_into(&mut html, &entry.url);
+        html.push_str("\">");
+        escape_into(&mut html, title);
+        html.push_str("</a><a class=\"action\" href=\"");
+        escape_into(&mut html, &delete_url);
+        html.push_str("\">Delete</a></div><div class=\"detail\">");
+        escape_into(&mut html, &entry.url);
+        let _ = write!(
+            html,
+            "</div><div class=\"detail\">{}, {}</div></div>",
+            describe_visit_count(entry.visit_count),
+            describe_age(now.saturating_sub(entry.last_visit))
+        );
+    }
+
+    document("History", &html)
+}
+
+fn describe_visit_count(visit_count: usize) -> String {
+    match visit_count {
+        0 => "Never visited".to_string(),
+        1 => "Visited once".to_string(),
+        n => format!("Visited {n} times"),
+    }
+}
+
+/// Describe how long ago something happened, given the age in seconds
+fn describe_age(age: u64) -> String {
+    match age / SECONDS_PER_DAY {
+        0 => "last visit today".to_string(),
+        1 => "last visit yesterday".to_string(),
+        days => format!("last visit {days} days ago"),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_action() {
+        let action = |url: &str| requested_action(&url.parse().unwrap());
+
+        assert_eq!(action("about:history"), None);
+        assert_eq!(action("about:history?clear"), Some(Action::Clear));
+        assert_eq!(
+            action("about:history?delete=https%3A%2F%2Fexample.com%2F%3Fa%3Db"),
+            Some(Action::Delete("https://example.com/?a=b".to_string()))
+        );
+    }
+
+    #[test]
+    fn render_history() {
+        let history = History::in_memory();
+        history
+            .record_visit(&"https://example.com/?a=b".parse().unwrap(), "<Example>")
+            .unwrap();
+        history
+            .record_visit(&"https://stormlicht.rs".parse().unwrap(), "")
+            .unwrap();
+
+        let html = render(&history, now() + 3 * SECONDS_PER_DAY);
+
+        assert!(html.contains("<a href=\"https://example.com/?a=b\">&lt;Example&gt;</a>"));
+        assert!(html.contains("about:history?delete=https%3A%2F%2Fexample.com%2F%3Fa%3Db"));
+        assert!(html.contains("Visited once, last visit 3 days ago"));
+
+        // Pages without a title are listed by their url, most recent visits first
+        let stormlicht = html
+            .find("<a href=\"https://stormlicht.rs/\">https://stormlicht.rs/</a>")
+            .unwrap();
+        assert!(stormlicht < html.find("&lt;Example&gt;").unwrap());
+    }
+}
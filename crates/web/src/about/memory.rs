@@ -0,0 +1,56 @@
+//! The `about:memory` page, which shows how much memory each subsystem uses
+//!
+//! The page that was displayed before navigating to `about:memory` is included in the report.
+
+use std::fmt::Write;
+
+use sl_std::memory::{format_size, MemoryReport, MemoryReporter};
+
+use super::document;
+use crate::{html::escape_into, BrowsingContext};
+
+pub(super) fn load(browsing_context: &BrowsingContext) -> String {
+    let mut report = MemoryReport::new();
+    browsing_context.report_memory(&mut report);
+
+    let mut html = String::new();
+    let _ = write!(html, "<p>{} in total.</p>", format_size(report.total()));
+
+    for (subsystem, total_size) in report.subsystems() {
+        html.push_str("<div class=\"entry\"><div class=\"name\">");
+        escape_into(&mut html, subsystem);
+        let _ = write!(html, " ({})</div>", format_size(total_size));
+
+        let prefix = format!("{subsystem}/");
+        for (path, size) in report.entries() {
+            let Some(name) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+
+            html.push_str("<div class=\"detail\">");
+            escape_into(&mut html, name);
+            let _ = write!(html, ": {}</div>", format_size(size));
+        }
+
+        html.push_str("</div>");
+    }
+
+    document("Memory", &html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_subsystems() {
+        let mut browsing_context = BrowsingContext::default();
+        browsing_context
+            .load(&"about:version".parse().unwrap())
+            .unwrap();
+
+        let html = load(&browsing_context);
+        assert!(html.contains("dom ("));
+        assert!(html.contains("elements: "));
+    }
+}
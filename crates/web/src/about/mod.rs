@@ -0,0 +1,193 @@
+//! Internal pages that are served from generated html instead of being loaded from the network
+//!
+//! Each page lives at an `about:` url, like `about:config`. Pages that modify state
+//! (clearing a cache, changing a preference) are controlled through the query of the url.
+//! Such changes are only made when the user opened the url, or followed a link on the
+//! internal page itself. Other pages cannot use these urls to change the browser.
+
+mod cache;
+mod config;
+mod dns;
+mod history;
+mod memory;
+mod version;
+
+use sl_std::{
+    ascii,
+    percent_encode::{percent_decode_utf8, Utf8Validation},
+};
+use url::URL;
+
+use crate::{
+    browsing_context::NavigationInitiator, html::escape_into, BrowsingContext, BrowsingContextError,
+};
+
+pub const ABOUT_SCHEME: &str = "about";
+
+const STYLESHEET: &str = "
+body { font-family: sans-serif; margin: 32px; }
+h1 { font-size: 2em; }
+.entry { margin-bottom: 12px; }
+.name { font-weight: bold; }
+.detail { color: #777777; font-size: 0.8em; }
+.action { color: #c01c28; font-size: 0.8em; margin-left: 12px; }
+.clear { color: #c01c28; }
+.message { background-color: #e8f5e9; padding: 8px; margin-bottom: 16px; }
+.error { background-color: #fdecea; padding: 8px; margin-bottom: 16px; }
+";
+
+/// Create the html for the internal page at `url`
+///
+/// Any action requested by the url is performed before the page is rendered, unless the
+/// navigation was started by another page.
+/// Pages that describe the state of the browser inspect the `browsing_context`
+/// that is navigating to them.
+pub fn load(
+    url: &URL,
+    initiator: NavigationInitiator,
+    browsing_context: &BrowsingContext,
+) -> Result<String, BrowsingContextError> {
+    debug_assert_eq!(url.scheme().as_str(), ABOUT_SCHEME);
+
+    let allow_changes = may_change_state(url, initiator, browsing_context.url().as_ref());
+
+    let html = match url.path().as_str() {
+        "blank" => String::new(),
+        "version" => version::load(),
+        "config" => config::load(url, allow_changes),
+        "cache" => cache::load(url, allow_changes),
+        "dns" => dns::load(url, allow_changes),
+        "history" => history::load(url, allow_changes),
+        "memory" => memory::load(browsing_context),
+        _ => return Err(BrowsingContextError::UnknownInternalPage),
+    };
+
+    Ok(html)
+}
+
+/// Whether a navigation to `url` may perform the action that the url requests
+///
+/// That is the case if the user opened the url, or if the page that navigates is
+/// the same internal page (and the user clicked one of its links).
+fn may_change_state(url: &URL, initiator: NavigationInitiator, current_url: Option<&URL>) -> bool {
+    match initiator {
+        NavigationInitiator::User => true,
+        NavigationInitiator::Page => current_url.is_some_and(|current_url| {
+            current_url.scheme() == url.scheme() && current_url.path() == url.path()
+        }),
+    }
+}
+
+/// Wrap the body of an internal page in a complete html document
+fn document(title: &str, body: &str) -> String {
+    let mut html = String::from("<!DOCTYPE html><html><head><title>");
+    escape_into(&mut html, title);
+    html.push_str("</title><style>");
+    html.push_str(STYLESHEET);
+    html.push_str("</style></head><body><h1>");
+    escape_into(&mut html, title);
+    html.push_str("</h1>");
+    html.push_str(body);
+    html.push_str("</body></html>");
+    html
+}
+
+/// The (percent-decoded) name-value pairs in the query of the url
+///
+/// Parameters without a value, like `?clear`, have an empty value.
+fn query_parameters(url: &URL) -> Vec<(String, String)> {
+    let Some(query) = url.query() else {
+        return vec![];
+    };
+
+    let decode = |value: &str| {
+        ascii::Str::from_bytes(value.as_bytes())
+            .and_then(|value| percent_decode_utf8(value, Utf8Validation::Lossy).ok())
+    };
+
+    query
+        .as_str()
+        .split('&')
+        .filter(|parameter| !parameter.is_empty())
+        .filter_map(|parameter| {
+            let (name, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            Some((decode(name)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// Whether the url contains a parameter with the given name
+fn has_parameter(url: &URL, name: &str) -> bool {
+    query_parameters(url)
+        .iter()
+        .any(|(parameter, _)| parameter == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_query_parameters() {
+        let parameters = |url: &str| query_parameters(&url.parse().unwrap());
+
+        assert!(parameters("about:config").is_empty());
+        assert_eq!(
+            parameters("about:config?font_size=20&clear&theme=dark%20mode"),
+            [
+                ("font_size".to_string(), "20".to_string()),
+                ("clear".to_string(), String::new()),
+                ("theme".to_string(), "dark mode".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_page() {
+        let browsing_context = BrowsingContext::default();
+        let load = |url: &str| {
+            load(
+                &url.parse().unwrap(),
+                NavigationInitiator::User,
+                &browsing_context,
+            )
+        };
+
+        assert!(load("about:blank").unwrap().is_empty());
+        assert!(matches!(
+            load("about:does-not-exist"),
+            Err(BrowsingContextError::UnknownInternalPage)
+        ));
+    }
+
+    #[test]
+    fn only_change_state_on_behalf_of_the_user() {
+        let url: URL = "about:config?http_proxy=example.com:8080".parse().unwrap();
+        let config: URL = "about:config".parse().unwrap();
+        let other_page: URL = "https://example.com".parse().unwrap();
+
+        assert!(may_change_state(
+            &url,
+            NavigationInitiator::User,
+            Some(&other_page)
+        ));
+        assert!(may_change_state(
+            &url,
+            NavigationInitiator::Page,
+            Some(&config)
+        ));
+        assert!(!may_change_state(
+            &url,
+            NavigationInitiator::Page,
+            Some(&other_page)
+        ));
+        assert!(!may_change_state(&url, NavigationInitiator::Page, None));
+
+        let history: URL = "about:history".parse().unwrap();
+        assert!(!may_change_state(
+            &url,
+            NavigationInitiator::Page,
+            Some(&history)
+        ));
+    }
+}
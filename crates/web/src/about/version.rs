@@ -0,0 +1,44 @@
+//! The `about:version` page, which describes the running build of stormlicht
+
+use std::env::consts;
+
+use settings::SETTINGS;
+
+use super::document;
+use crate::html::escape_into;
+
+/// The commit that stormlicht was built from, if the build happened inside a git checkout
+const COMMIT: Option<&str> = option_env!("STORMLICHT_COMMIT");
+
+pub(super) fn load() -> String {
+    let profile = SETTINGS.profile().map_or_else(
+        || "None (preferences are not persisted)".to_string(),
+        |profile| profile.directory().display().to_string(),
+    );
+
+    let build = if cfg!(debug_assertions) {
+        "Debug"
+    } else {
+        "Release"
+    };
+
+    let properties = [
+        ("Version", env!("CARGO_PKG_VERSION")),
+        ("Commit", COMMIT.unwrap_or("Unknown")),
+        ("Build", build),
+        ("Operating system", consts::OS),
+        ("Architecture", consts::ARCH),
+        ("Profile", &profile),
+    ];
+
+    let mut html = String::new();
+    for (name, value) in properties {
+        html.push_str("<div class=\"entry\"><span class=\"name\">");
+        html.push_str(name);
+        html.push_str(": </span>");
+        escape_into(&mut html, value);
+        html.push_str("</div>");
+    }
+
+    document("Stormlicht", &html)
+}
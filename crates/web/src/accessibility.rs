@@ -0,0 +1,365 @@
+//! The accessibility tree, which describes a page to assistive technologies like screen readers
+//!
+//! The tree is derived from the DOM and the layout of the page. Nodes without any semantic
+//! meaning (like most `<div>` and `<span>` elements) are left out and their children take
+//! their place.
+//!
+//! Roles and names follow the [HTML Accessibility API Mappings](https://www.w3.org/TR/html-aam-1.0/),
+//! though only a small subset of the mappings are implemented.
+
+use std::collections::HashMap;
+
+use crate::{
+    css::{fragment_tree::FragmentTree, layout::Pixels},
+    dom::{
+        dom_objects::{Document, Element, Node, Text},
+        DomPtr,
+    },
+    static_interned, InternedString,
+};
+
+/// Elements that are never rendered and therefore not part of the accessibility tree
+const HIDDEN_ELEMENTS: [&str; 7] = [
+    "head", "script", "style", "template", "noscript", "title", "meta",
+];
+
+/// <https://w3c.github.io/aria/#role_definitions>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Document,
+    Article,
+    Banner,
+    Button,
+    Complementary,
+    ContentInfo,
+    Form,
+
+    /// An element without any semantic meaning that was kept because it has a name
+    Generic,
+    Heading {
+        level: u8,
+    },
+    Image,
+    Link,
+    List,
+    ListItem,
+    Main,
+    Navigation,
+    Paragraph,
+    Table,
+
+    /// A run of text, whose content is the name of the node
+    Text,
+}
+
+/// Dynamic properties of an [AccessibleNode]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct States {
+    /// Whether the node can receive keyboard focus
+    pub focusable: bool,
+
+    /// Whether the node currently has keyboard focus
+    pub focused: bool,
+
+    /// Whether the mouse is currently over the node
+    pub hovered: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibleNode {
+    pub role: Role,
+
+    /// <https://w3c.github.io/accname/#dfn-accessible-name>
+    pub name: String,
+    pub states: States,
+
+    /// Where the node is displayed, in css pixels relative to the top left corner of the page
+    ///
+    /// Nodes that don't generate a box (like text) don't have bounds.
+    pub bounds: Option<math::Rectangle>,
+    pub children: Vec<AccessibleNode>,
+}
+
+/// The accessibility tree of a page
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityTree {
+    root: AccessibleNode,
+}
+
+/// Builds the accessibility tree, keeping track of the layout of each node
+struct TreeBuilder {
+    /// The union of the border areas of all fragments generated by a node
+    ///
+    /// Nodes are identified by their address.
+    bounds: HashMap<*const (), math::Rectangle<Pixels>>,
+}
+
+impl AccessibilityTree {
+    /// Create the accessibility tree for a document that has been laid out
+    #[must_use]
+    pub fn new(document: &DomPtr<Document>, fragment_tree: &FragmentTree) -> Self {
+        let mut bounds: HashMap<*const (), math::Rectangle<Pixels>> = HashMap::new();
+
+        fragment_tree.for_each_box(|fragment, area| {
+            let Some(node) = fragment.dom_node() else {
+                return;
+            };
+
+            // Inline elements can generate multiple fragments, for example if they span multiple lines
+            bounds
+                .entry(node_address(&node))
+                .and_modify(|bounds| bounds.grow_to_contain(area))
+                .or_insert(area);
+        });
+
+        let builder = TreeBuilder { bounds };
+
+        let mut root = AccessibleNode {
+            role: Role::Document,
+            name: document.borrow().title(),
+            states: States::default(),
+            bounds: None,
+            children: vec![],
+        };
+
+        for child in document.borrow().children() {
+            builder.build(child, &mut root.children);
+        }
+
+        Self { root }
+    }
+
+    #[must_use]
+    pub fn root(&self) -> &AccessibleNode {
+        &self.root
+    }
+}
+
+impl TreeBuilder {
+    /// Append the accessible nodes for `node` and its descendants to `parent_children`
+    fn build(&self, node: &DomPtr<Node>, parent_children: &mut Vec<AccessibleNode>) {
+        if let Some(text) = node.try_into_type::<Text>() {
+            let text = collapse_whitespace(text.borrow().content());
+            if !text.is_empty() {
+                parent_children.push(AccessibleNode {
+                    role: Role::Text,
+                    name: text,
+                    states: States::default(),
+                    bounds: None,
+                    children: vec![],
+                });
+            }
+            return;
+        }
+
+        let Some(element) = node.try_into_type::<Element>() else {
+            return;
+        };
+
+        if is_hidden(&element.borrow()) {
+            return;
+        }
+
+        let role = role_of(&element.borrow());
+        let label = attribute(&element.borrow(), "aria-label").filter(|label| !label.is_empty());
+
+        // Elements without meaning are replaced by their children
+        let Some(role) = role.or(label.is_some().then_some(Role::Generic)) else {
+            for child in node.borrow().children() {
+                self.build(child, parent_children);
+            }
+            return;
+        };
+
+        let mut children = vec![];
+        for child in node.borrow().children() {
+            self.build(child, &mut children);
+        }
+
+        let name = label.unwrap_or_else(|| match role {
+            Role::Image => attribute(&element.borrow(), "alt").unwrap_or_default(),
+            Role::Link | Role::Button | Role::Heading { .. } => {
+                // These take their name from their content, which makes text children redundant
+                let name = text_content(node);
+                children.retain(|child| child.role != Role::Text);
+                name
+            },
+            _ => String::new(),
+        });
+
+        let states = States {
+            focusable: matches!(role, Role::Link | Role::Button),
+            focused: element.borrow().is_focused(),
+            hovered: element.borrow().is_hovered(),
+        };
+
+        let bounds = self.bounds.get(&node_address(node)).map(|bounds| {
+            math::Rectangle::from_corners(
+                bounds.top_left().map(f32::from),
+                bounds.bottom_right().map(f32::from),
+            )
+        });
+
+        parent_children.push(AccessibleNode {
+            role,
+            name,
+            states,
+            bounds,
+            children,
+        });
+    }
+}
+
+/// <https://www.w3.org/TR/html-aam-1.0/#html-element-role-mappings>
+fn role_of(element: &Element) -> Option<Role> {
+    let role = match element.local_name() {
+        static_interned!("a") => {
+            // Anchors without a href are placeholders, not links
+            element.attributes().get(&static_interned!("href"))?;
+            Role::Link
+        },
+        static_interned!("article") => Role::Article,
+        static_interned!("aside") => Role::Complementary,
+        static_interned!("button") => Role::Button,
+        static_interned!("footer") => Role::ContentInfo,
+        static_interned!("form") => Role::Form,
+        static_interned!("h1") => Role::Heading { level: 1 },
+        static_interned!("h2") => Role::Heading { level: 2 },
+        static_interned!("h3") => Role::Heading { level: 3 },
+        static_interned!("h4") => Role::Heading { level: 4 },
+        static_interned!("h5") => Role::Heading { level: 5 },
+        static_interned!("h6") => Role::Heading { level: 6 },
+        static_interned!("header") => Role::Banner,
+        static_interned!("img") => Role::Image,
+        static_interned!("li") => Role::ListItem,
+        static_interned!("main") => Role::Main,
+        static_interned!("nav") => Role::Navigation,
+        static_interned!("ol") | static_interned!("ul") => Role::List,
+        static_interned!("p") => Role::Paragraph,
+        static_interned!("table") => Role::Table,
+        _ => return None,
+    };
+
+    Some(role)
+}
+
+fn is_hidden(element: &Element) -> bool {
+    let local_name = element.local_name().to_string();
+
+    HIDDEN_ELEMENTS.contains(&local_name.as_str())
+        || attribute(element, "hidden").is_some()
+        || attribute(element, "aria-hidden").is_some_and(|value| value == "true")
+}
+
+fn attribute(element: &Element, name: &str) -> Option<String> {
+    element
+        .attributes()
+        .get(&InternedString::from(name))
+        .map(ToString::to_string)
+}
+
+/// The text of all visible descendants of the node, with whitespace collapsed
+pub(crate) fn text_content(node: &DomPtr<Node>) -> String {
+    fn collect(node: &DomPtr<Node>, text: &mut String) {
+        if let Some(text_node) = node.try_into_type::<Text>() {
+            text.push_str(text_node.borrow().content());
+            text.push(' ');
+            return;
+        }
+
+        if let Some(element) = node.try_into_type::<Element>()
+            && is_hidden(&element.borrow())
+        {
+            return;
+        }
+
+        for child in node.borrow().children() {
+            collect(child, text);
+        }
+    }
+
+    let mut text = String::new();
+    collect(node, &mut text);
+    collapse_whitespace(&text)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_ascii_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn node_address(node: &DomPtr<Node>) -> *const () {
+    node.as_ptr().cast()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::{self, tokenization::IgnoreParseErrors};
+
+    fn build(source: &str) -> AccessibilityTree {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+
+        AccessibilityTree::new(&document, &FragmentTree::default())
+    }
+
+    fn node(role: Role, name: &str, children: Vec<AccessibleNode>) -> AccessibleNode {
+        AccessibleNode {
+            role,
+            name: name.to_string(),
+            states: States {
+                focusable: matches!(role, Role::Link | Role::Button),
+                focused: false,
+                hovered: false,
+            },
+            bounds: None,
+            children,
+        }
+    }
+
+    #[test]
+    fn roles_and_names() {
+        let tree = build(
+            "<html><head><title>Test</title><script>code()</script></head><body>
+            <div><h2>A <em>heading</em></h2></div>
+            <p>Some  text, <a href=\"/\">a link</a> <a>no link</a></p>
+            <ul><li><img src=\"a.png\" alt=\"An image\"></li><li hidden>Hidden</li></ul>
+            <span aria-label=\"Labeled\"><span aria-hidden=\"true\">x</span></span>
+            </body></html>",
+        );
+
+        let expected = node(
+            Role::Document,
+            "Test",
+            vec![
+                node(Role::Heading { level: 2 }, "A heading", vec![]),
+                node(
+                    Role::Paragraph,
+                    "",
+                    vec![
+                        node(Role::Text, "Some text,", vec![]),
+                        node(Role::Link, "a link", vec![]),
+                        node(Role::Text, "no link", vec![]),
+                    ],
+                ),
+                node(
+                    Role::List,
+                    "",
+                    vec![node(
+                        Role::ListItem,
+                        "",
+                        vec![node(Role::Image, "An image", vec![])],
+                    )],
+                ),
+                node(Role::Generic, "Labeled", vec![]),
+            ],
+        );
+
+        assert_eq!(tree.root(), &expected);
+    }
+}
@@ -1,38 +1,169 @@
-use std::time;
+use std::{cell::RefCell, mem, path::PathBuf, rc::Rc, time};
 
 use error_derive::Error;
-use render::Composition;
-use resourceloader::{ResourceLoadError, RESOURCE_LOADER};
+use render::{Composition, LayerNode, LayerTree, PdfDocument, ScrollFrame};
+use resourceloader::{
+    download,
+    navigation::{self, Navigation},
+    protocol::{FingerHandler, ProtocolHandler},
+    Resource, ResourceLoadError, RESOURCE_LOADER,
+};
+use settings::{HISTORY, SETTINGS};
+use sl_std::{
+    ascii,
+    memory::{MemoryReport, MemoryReporter},
+    percent_encode::{percent_decode_utf8, Utf8Validation},
+};
 use url::URL;
 
 use crate::{
+    about,
+    accessibility::AccessibilityTree,
     css::{
-        display_list::Painter,
-        fragment_tree::FragmentTree,
-        layout::{BoxTree, Pixels, Size},
-        StyleComputer, Stylesheet,
+        display_list::{DisplayList, Painter},
+        fragment_tree::{Fragment, FragmentTree},
+        layout::{textarea::TextAreaGeometry, BoxTree, BoxTreeArena, LayoutCache, Pixels, Size},
+        AnimationClock, MediaEnvironment, PageLayout, StyleComputer, Stylesheet, Transitions,
     },
     dom::{
-        dom_objects::{self, Document},
+        dom_objects::{
+            self, Document, DocumentReadyState, HtmlInputElement, HtmlOptionElement,
+            HtmlSelectElement, HtmlTextAreaElement,
+        },
         DomPtr,
     },
-    event,
+    drag_and_drop::{self, DragSession, DropEffect},
+    editing::Motion,
+    error_page, event, feed,
+    file_chooser::{self, FileChooserRequest},
+    focus::{self, FocusDirection},
+    geometry::{DomRect, ElementGeometry},
     html::{self, tokenization::IgnoreParseErrors},
+    image_cache::IMAGE_CACHE,
+    intersection_observer::IntersectionObserver,
+    location::Location,
+    messaging::{PostMessageError, PostedMessage},
+    permissions::Permissions,
+    reader_mode,
+    resize_observer::{self, Measurement, ResizeObserver},
+    scroll,
+    select_popup::{self, Movement, SelectPopup},
+    session_history::{History, HistoryHandling, SessionHistory},
+    spellcheck::SpellingCorrection,
+    static_interned,
+    user_content::USER_CONTENT,
+    view_source,
+    viewport::{Device, Viewport, ViewportDescription},
+    window::{Window, WindowEvent},
+    worker::structured_clone::SerializedValue,
+    xml,
 };
 
+const FINGER_SCHEME: &str = "finger";
+
 /// The Browsing Context takes care of coordinating loads, layout calculations and paints
 #[derive(Default)]
 pub struct BrowsingContext {
     /// The currently loaded web page, or none if no page is loaded
     current_page: Option<CurrentPage>,
+
+    /// The navigation that is currently blocked by a certificate error, if any
+    ///
+    /// The user may choose to proceed anyways from the error page.
+    blocked_by_certificate: Option<BlockedNavigation>,
+
+    /// The original page, while its article is displayed in reader mode
+    page_without_reader_mode: Option<CurrentPage>,
+
+    session_history: SessionHistory,
+}
+
+struct BlockedNavigation {
+    host: String,
+    location: URL,
+}
+
+/// Who started a navigation
+///
+/// Some urls have side effects or reach beyond the web, so they may only be
+/// opened by the user and not by the page that is currently displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NavigationInitiator {
+    /// The user, through the browser interface, like the address bar or the reload button
+    User,
+
+    /// The current page, through a link or a script
+    Page,
 }
 
 struct CurrentPage {
     document: DomPtr<dom_objects::Document>,
+
+    /// Computed once, so that documents with an opaque origin stay same origin with themselves
+    ///
+    /// <https://dom.spec.whatwg.org/#concept-document-origin>
+    origin: url::Origin,
     fragment_tree: FragmentTree,
+
+    /// The display list that was painted most recently, if any
+    display_list: Option<DisplayList>,
+
+    /// The display list of everything that does not scroll with the page, like the popup of a `<select>` element
+    overlay_display_list: Option<DisplayList>,
+
+    /// Rebuilt after every layout, so it reflects changes to the document
+    accessibility_tree: Option<AccessibilityTree>,
     stylesheets: Vec<Stylesheet>,
+
+    /// Taken from the viewport `<meta>` element of the document
+    viewport_description: ViewportDescription,
+
+    /// The viewport that the page was most recently laid out in
+    viewport: Option<Viewport>,
+
+    /// The environment that media queries were evaluated in during the most recent layout
+    media_environment: Option<MediaEnvironment>,
     hovered_element: Option<DomPtr<dom_objects::Element>>,
+
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#focused-area-of-the-document>
+    focused_element: Option<DomPtr<dom_objects::Element>>,
+
+    /// The options of a drop-down `<select>` element, while it is opened
+    select_popup: Option<SelectPopup>,
+
+    /// Set when a file upload control was clicked, until the embedder asks the user to pick files
+    file_chooser_request: Option<FileChooserRequest>,
+
+    /// Files that are currently dragged over the page, if any
+    drag_session: Option<DragSession>,
     needs_relayout: bool,
+
+    /// Layout results of the previous layout passes, reused for parts of the page that did not change
+    layout_cache: LayoutCache,
+
+    /// Advanced once per rendering update
+    animation_clock: AnimationClock,
+    transitions: RefCell<Transitions>,
+
+    /// Created when the page is painted for the first time
+    window: Option<Window>,
+
+    /// Messages that were posted to the window, delivered during the next rendering update
+    posted_messages: Vec<PostedMessage>,
+
+    /// The top left corner of the viewport within the page
+    scroll_position: math::Vec2D<Pixels>,
+
+    /// Whether the viewport was scrolled since the last rendering update
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#pending-scroll-event-targets>
+    has_pending_scroll_event: bool,
+
+    /// <https://w3c.github.io/IntersectionObserver/#document-intersectionobservertaskqueued>
+    intersection_observers: Vec<Rc<RefCell<IntersectionObserver>>>,
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-document-resizeobservers-slot>
+    resize_observers: Vec<Rc<RefCell<ResizeObserver>>>,
 }
 
 #[derive(Debug, Error)]
@@ -40,33 +171,356 @@ pub enum BrowsingContextError {
     #[msg = "failed to load resource"]
     Loading(ResourceLoadError),
 
-    #[msg = "unsupported MIME type"]
-    UnsupportedMIME,
+    #[msg = "invalid view-source url"]
+    InvalidViewSourceURL,
+
+    #[msg = "unknown internal page"]
+    UnknownInternalPage,
+
+    #[msg = "url can only be opened by the user"]
+    NavigationNotAllowed,
+
+    #[msg = "document is not well-formed xml"]
+    InvalidXml(xml::XmlError),
+
+    #[msg = "document is not a rss or atom feed"]
+    NotAFeed,
 }
 
 impl BrowsingContext {
+    /// Navigate to the given [URL] on behalf of the user
+    ///
+    /// If the navigation fails, an error page describing the failure is displayed
+    /// and the error is returned.
     pub fn load(&mut self, location: &URL) -> Result<(), BrowsingContextError> {
+        self.load_with_history_handling(location, HistoryHandling::Push, NavigationInitiator::User)
+    }
+
+    fn load_with_history_handling(
+        &mut self,
+        location: &URL,
+        history_handling: HistoryHandling,
+        initiator: NavigationInitiator,
+    ) -> Result<(), BrowsingContextError> {
+        let previous_document = self
+            .current_page
+            .as_ref()
+            .map(|current_page| current_page.document.clone());
+
+        let result = self.load_document(location, initiator);
+
+        // Downloads keep the current page, so they don't add an entry to the session history
+        let is_new_document = self.current_page.as_ref().is_some_and(|current_page| {
+            previous_document
+                .is_none_or(|previous_document| !previous_document.ptr_eq(&current_page.document))
+        });
+        if is_new_document && let Some(url) = self.url() {
+            self.session_history.add_document(url, history_handling);
+        }
+
+        result
+    }
+
+    /// Load the current url again, without changing the session history
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#reload>
+    pub fn reload(&mut self) -> Result<(), BrowsingContextError> {
+        let Some(url) = self.url() else {
+            return Ok(());
+        };
+
+        // Reloading always refetches the page, even if the url has a fragment
+        self.load_document(&url, NavigationInitiator::User)
+    }
+
+    fn load_document(
+        &mut self,
+        location: &URL,
+        initiator: NavigationInitiator,
+    ) -> Result<(), BrowsingContextError> {
+        let blocked_navigation = self.blocked_by_certificate.take();
+        self.page_without_reader_mode = None;
+
+        // This is only honored while the certificate error page is displayed, so other
+        // pages cannot use it to bypass certificate validation
+        if location.serialize(url::ExcludeFragment::Yes).as_str()
+            == error_page::PROCEED_UNSAFELY_URL
+            && let Some(blocked_navigation) = blocked_navigation
+        {
+            http::CERTIFICATE_EXCEPTIONS.add(&blocked_navigation.host);
+            return self.load_document(&blocked_navigation.location, initiator);
+        }
+
+        // FIXME: The user should be asked whether they really want to leave the page if a
+        //        beforeunload handler canceled the event
+        if let Some(current_page) = &mut self.current_page {
+            current_page.prompt_to_unload();
+        }
+
+        let result = self.try_load(location, initiator);
+
+        if let Err(error) = &result {
+            if let Some(certificate_error) = error_page::certificate_error(error) {
+                self.blocked_by_certificate = Some(BlockedNavigation {
+                    host: certificate_error.host.clone(),
+                    location: location.clone(),
+                });
+            }
+
+            self.display_html(location, &error_page::render(location, error));
+        }
+
+        result
+    }
+
+    /// Navigate to the given [URL] on behalf of the current page
+    ///
+    /// Unlike [load](Self::load), navigations that only change the fragment of the
+    /// current url do not refetch the page.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#navigate>
+    pub fn navigate(&mut self, location: &URL) -> Result<(), BrowsingContextError> {
+        self.navigate_with_history_handling(location, HistoryHandling::Push)
+    }
+
+    pub(crate) fn navigate_with_history_handling(
+        &mut self,
+        location: &URL,
+        history_handling: HistoryHandling,
+    ) -> Result<(), BrowsingContextError> {
+        if let Some(current_page) = &mut self.current_page {
+            let current_url = current_page.document.borrow().url().clone();
+
+            if location.fragment().is_some()
+                && location.serialize(url::ExcludeFragment::Yes)
+                    == current_url.serialize(url::ExcludeFragment::Yes)
+            {
+                current_page.navigate_to_fragment(&current_url, location);
+                self.session_history.add_same_document_entry(
+                    location.clone(),
+                    None,
+                    history_handling,
+                );
+                return Ok(());
+            }
+        }
+
+        self.load_with_history_handling(location, history_handling, NavigationInitiator::Page)
+    }
+
+    /// Change the url of the current page without loading a new document
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#url-and-history-update-steps>
+    pub(crate) fn update_url_and_history(
+        &mut self,
+        url: URL,
+        serialized_state: Option<SerializedValue>,
+        history_handling: HistoryHandling,
+    ) {
+        let Some(current_page) = &mut self.current_page else {
+            return;
+        };
+
+        current_page.document.borrow_mut().set_url(url.clone());
+        self.session_history
+            .add_same_document_entry(url, serialized_state, history_handling);
+    }
+
+    /// Move `delta` entries back (or forward) through the session history
+    ///
+    /// Entries that belong to the current document are restored without loading
+    /// the page again, all others are refetched.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#traverse-the-history-by-a-delta>
+    pub fn traverse_history(&mut self, delta: isize) {
+        let Some((previous_entry, target_entry)) = self.session_history.traverse(delta) else {
+            return;
+        };
+        let is_same_document = previous_entry.document_id == target_entry.document_id;
+        let target_url = target_entry.url.clone();
+
+        if is_same_document && let Some(current_page) = &mut self.current_page {
+            current_page.traverse_within_document(&target_url);
+            return;
+        }
+
+        if let Err(error) = self.load_document(&target_url, NavigationInitiator::User) {
+            log::error!("Failed to load {target_url}: {error}");
+        }
+    }
+
+    pub(crate) fn session_history(&self) -> &SessionHistory {
+        &self.session_history
+    }
+
+    fn try_load(
+        &mut self,
+        location: &URL,
+        initiator: NavigationInitiator,
+    ) -> Result<(), BrowsingContextError> {
+        if location.scheme().as_str() == view_source::VIEW_SOURCE_SCHEME {
+            return self.load_source(location);
+        }
+
+        if location.scheme().as_str() == about::ABOUT_SCHEME {
+            let html_source = about::load(location, initiator, self)?;
+            self.display_html(location, &html_source);
+            return Ok(());
+        }
+
+        if location.scheme().as_str() == FINGER_SCHEME {
+            return self.load_finger(location, initiator);
+        }
+
+        // Hosts that require strict transport security are always displayed with their https url
+        if let Some(upgraded_location) = http::HSTS.upgrade(location) {
+            return self.try_load(&upgraded_location, initiator);
+        }
+
         // Load the content at the given url
+        let navigation = navigation::navigate(location, is_displayable)
+            .map_err(BrowsingContextError::Loading)?;
+
+        // Anything that we cannot display is saved to disk instead, the current page stays visible
+        let resource = match navigation {
+            Navigation::Display(resource) => resource,
+            Navigation::Download(_) => {
+                log::info!(
+                    "Downloading {}",
+                    location.serialize(url::ExcludeFragment::Yes),
+                );
+                return Ok(());
+            },
+        };
+
+        let mime_type = &resource.mime_metadata().computed_mime_type;
+        let is_xhtml = mime_type.has_essence("application/xhtml+xml");
+        let is_feed = mime_type.has_essence("application/rss+xml")
+            || mime_type.has_essence("application/atom+xml");
+
+        // FIXME: resource might not be utf-8
+        let source = String::from_utf8_lossy(&resource.data());
+        if is_xhtml {
+            self.display_xml(location, &source)?;
+        } else if is_feed {
+            let document = setup_document(location.clone());
+            let (document, _) = xml::Parser::new(&source, document).parse()?;
+            let feed = feed::extract(&document).ok_or(BrowsingContextError::NotAFeed)?;
+            self.display_html(location, &feed::render(&feed));
+        } else {
+            self.display_html(location, &source);
+        }
+        self.record_visit(location);
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn is_reader_mode_active(&self) -> bool {
+        self.page_without_reader_mode.is_some()
+    }
+
+    /// Enable or disable reader mode, which displays only the article of the current page
+    ///
+    /// Returns whether reader mode is active afterwards. Reader mode cannot be enabled
+    /// if the current page does not contain an article.
+    pub fn set_reader_mode(&mut self, enabled: bool) -> bool {
+        if enabled == self.is_reader_mode_active() {
+            return enabled;
+        }
+
+        if let Some(mut original_page) = self.page_without_reader_mode.take() {
+            // The viewport might have been resized and everything on screen needs to be repainted
+            original_page.display_list = None;
+            original_page.overlay_display_list = None;
+            original_page.invalidate_layout();
+            self.current_page = Some(original_page);
+            return false;
+        }
+
+        let Some(current_page) = &self.current_page else {
+            return false;
+        };
+
+        let Some(article) = reader_mode::extract(&current_page.document) else {
+            log::info!("Not enabling reader mode, the page does not contain an article");
+            return false;
+        };
+
+        let location = current_page.document.borrow().url().clone();
+        let original_page = self.current_page.take();
+        self.display_html(&location, &reader_mode::render(&article));
+        self.page_without_reader_mode = original_page;
+
+        true
+    }
+
+    /// Add the current page to the browsing history
+    fn record_visit(&self, location: &URL) {
+        // Internal pages are not part of the history
+        if !matches!(location.scheme().as_str(), "http" | "https" | "file") {
+            return;
+        }
+
+        let Some(current_page) = &self.current_page else {
+            return;
+        };
+
+        let title = current_page.document.borrow().title();
+        if let Err(error) = HISTORY.record_visit(location, &title) {
+            log::error!("Failed to update history: {error}");
+        }
+    }
+
+    /// Display the source code of the document at the inner url of a `view-source:` url
+    fn load_source(&mut self, location: &URL) -> Result<(), BrowsingContextError> {
+        let source_url =
+            view_source::source_url(location).ok_or(BrowsingContextError::InvalidViewSourceURL)?;
+
         let resource = RESOURCE_LOADER
-            .schedule_load(location.clone())
+            .schedule_load(source_url)
             .block()
             .map_err(BrowsingContextError::Loading)?;
 
-        if !resource.mime_metadata().computed_mime_type.is_html() {
-            log::error!(
-                "Cannot display unknown MIME type: {}",
-                resource.mime_metadata().computed_mime_type
-            );
-            return Err(BrowsingContextError::UnsupportedMIME);
+        // FIXME: resource might not be utf-8
+        let source = String::from_utf8_lossy(&resource.data());
+        let html_source = view_source::render(location, &source);
+        self.display_html(location, &html_source);
+
+        Ok(())
+    }
+
+    /// Display the response of a finger server as plain text
+    ///
+    /// Pages cannot open finger urls, since that would allow them to send
+    /// queries to arbitrary hosts.
+    fn load_finger(
+        &mut self,
+        location: &URL,
+        initiator: NavigationInitiator,
+    ) -> Result<(), BrowsingContextError> {
+        if initiator != NavigationInitiator::User {
+            return Err(BrowsingContextError::NavigationNotAllowed);
         }
 
-        // FIXME: resource might not be utf-8
-        let html_source = String::from_utf8_lossy(&resource.data());
+        let resource = FingerHandler
+            .load(location)
+            .map_err(BrowsingContextError::Loading)?;
+
+        let mut html_source = String::from("<!DOCTYPE html><pre>");
+        html::escape_into(&mut html_source, &String::from_utf8_lossy(resource.data()));
+        html_source.push_str("</pre>");
+        self.display_html(location, &html_source);
+
+        Ok(())
+    }
 
+    fn display_html(&mut self, location: &URL, html_source: &str) {
         // Parse the data into a html document
         let document = setup_document(location.clone());
         let parse_start = time::Instant::now();
-        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(&html_source, document);
+        let parser: html::Parser<IgnoreParseErrors> =
+            html::Parser::new(html_source, document).with_script_execution();
         let (document, stylesheets) = parser.parse();
         let parse_end = time::Instant::now();
 
@@ -75,117 +529,1620 @@ impl BrowsingContext {
             parse_end.duration_since(parse_start).as_millis()
         );
 
+        self.display_document(document, stylesheets);
+    }
+
+    /// Display a xml document, like an `application/xhtml+xml` page
+    ///
+    /// Unlike html, xml documents that are not well-formed are not displayed at all.
+    fn display_xml(&mut self, location: &URL, xml_source: &str) -> Result<(), xml::XmlError> {
+        let document = setup_document(location.clone());
+        let parse_start = time::Instant::now();
+        let (document, stylesheets) = xml::Parser::new(xml_source, document).parse()?;
+        let parse_end = time::Instant::now();
+
+        log::info!(
+            "Parsed xml document in {}ms",
+            parse_end.duration_since(parse_start).as_millis()
+        );
+
+        self.display_document(document, stylesheets);
+        Ok(())
+    }
+
+    fn display_document(&mut self, document: DomPtr<Document>, mut stylesheets: Vec<Stylesheet>) {
+        if let Some(mut previous_page) = self.current_page.take() {
+            previous_page.unload();
+        }
+
+        let user_stylesheets =
+            USER_CONTENT.stylesheets_for(document.borrow().url(), stylesheets.len());
+        stylesheets.extend(user_stylesheets);
+
+        let viewport_description = ViewportDescription::from_document(&document);
+        let origin = document.borrow().url().origin();
+
         let current_page = CurrentPage {
             document,
+            origin,
             fragment_tree: FragmentTree::default(),
+            display_list: None,
+            overlay_display_list: None,
+            accessibility_tree: None,
             stylesheets,
+            viewport_description,
+            viewport: None,
+            media_environment: None,
             hovered_element: None,
+            focused_element: None,
+            select_popup: None,
+            file_chooser_request: None,
+            drag_session: None,
             needs_relayout: true,
+            layout_cache: LayoutCache::default(),
+            animation_clock: AnimationClock::new(),
+            transitions: RefCell::default(),
+            window: None,
+            posted_messages: vec![],
+            scroll_position: math::Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+            has_pending_scroll_event: false,
+            intersection_observers: vec![],
+            resize_observers: vec![],
         };
 
         self.current_page = Some(current_page);
+    }
 
-        Ok(())
+    /// Paint the current page and composite it at the current scroll position
+    pub fn paint(&mut self, to: &mut Composition, device: Device) {
+        self.paint_layers(device).composite(to);
     }
 
-    pub fn paint(&mut self, to: &mut Composition, viewport_size: (u16, u16)) {
+    /// Paint the current page into a [LayerTree]
+    ///
+    /// The root scroll frame of the tree is the viewport, it can be scrolled without
+    /// painting the page again (see [render::Compositor]).
+    pub fn paint_layers(&mut self, device: Device) -> LayerTree {
+        let _span = trace::span!(Paint, "paint");
+
+        let mut layer_tree = LayerTree::default();
         let Some(current_page) = &mut self.current_page else {
-            return;
+            return layer_tree;
         };
 
-        let viewport_size = Size {
-            width: Pixels(viewport_size.0 as f32),
-            height: Pixels(viewport_size.1 as f32),
+        let screen_size = Size {
+            width: Pixels(device.size.0 as f32),
+            height: Pixels(device.size.1 as f32),
         };
 
-        if current_page.needs_relayout {
-            current_page.layout(viewport_size);
+        // The page is zoomed by scaling everything that is drawn to the screen
+        let viewport = current_page.viewport_description.resolve(screen_size);
+        let scale = device.device_pixel_ratio * viewport.scale;
+        layer_tree.dpi = (scale, scale);
+
+        match &mut current_page.window {
+            Some(window) => window.update(device, viewport, current_page.scroll_position),
+            None => current_page.window = Some(Window::new(device, viewport)),
+        }
+
+        // Changes to the preferences of the user can change which media queries match
+        let media_environment = MediaEnvironment::from_settings(viewport.size);
+
+        current_page.update_animations();
+
+        let is_first_layout = current_page.viewport.is_none();
+        if current_page.needs_relayout
+            || current_page.viewport != Some(viewport)
+            || current_page.media_environment != Some(media_environment)
+        {
+            current_page.layout(viewport, media_environment);
+        }
+
+        // Pages that are loaded with a fragment start out scrolled to the indicated part
+        if is_first_layout {
+            let url = current_page.document.borrow().url().clone();
+            if let Some(fragment) = url.fragment() {
+                current_page.scroll_to_fragment(fragment);
+            }
         }
 
-        // Paint the fragment_tree to the screen
+        // Images are loaded during the first layout, so the document is complete afterwards
+        if is_first_layout {
+            current_page.complete_loading();
+        }
+
+        current_page.run_resize_observers(viewport, media_environment, device.device_pixel_ratio);
+        current_page.dispatch_transition_events();
+        current_page.dispatch_window_events();
+        current_page.deliver_posted_messages();
+        current_page.dispatch_scroll_events();
+        current_page.update_intersection_observations();
+
+        // Paint the fragment_tree in page coordinates, the page is scrolled during composition
+        let overflow = current_page.fragment_tree.scrollable_overflow();
+        let canvas = Size {
+            width: overflow.width.max(viewport.size.width),
+            height: overflow.height.max(viewport.size.height),
+        };
         let mut painter = Painter::default();
         current_page
             .fragment_tree
-            .fill_display_list(&mut painter, viewport_size);
+            .fill_display_list(&mut painter, canvas);
+        current_page.paint_focus_ring(&mut painter);
+        let display_list = painter.finish();
+
+        let mut page = LayerNode::scrolling(ScrollFrame {
+            offset: current_page.scroll_position.map(f32::from),
+            content_size: math::Vec2D::new(overflow.width.into(), overflow.height.into()),
+            viewport_size: math::Vec2D::new(
+                viewport.size.width.into(),
+                viewport.size.height.into(),
+            ),
+        });
+
+        // Only the parts of the page that changed since the last paint need to be redrawn
+        display_list.invalidate_damaged_areas(current_page.display_list.as_ref(), &mut page);
+        display_list.paint(&mut page);
+        current_page.display_list = Some(display_list);
+        layer_tree.root.children.push(page);
+
+        // The popup of an opened drop-down box covers the page and does not move with it
+        let mut painter = Painter::default();
+        if let Some(select_popup) = &current_page.select_popup {
+            select_popup.paint(&mut painter);
+        }
+        let overlay_display_list = painter.finish();
+
+        let mut overlay = LayerNode::default();
+        overlay_display_list
+            .invalidate_damaged_areas(current_page.overlay_display_list.as_ref(), &mut overlay);
+        overlay_display_list.paint(&mut overlay);
+        current_page.overlay_display_list = Some(overlay_display_list);
+        layer_tree.root.children.push(overlay);
+
+        if is_first_layout && SETTINGS.report_memory {
+            let mut report = MemoryReport::new();
+            self.report_memory(&mut report);
+            log::info!("Memory usage:\n{report}");
+        }
 
-        painter.paint(to);
+        layer_tree
     }
 
-    pub fn handle_mouse_event(&mut self, mouse_event: event::MouseEvent) {
+    /// Print the current page to a PDF document
+    ///
+    /// The size and margins of the pages are taken from the `@page` rules of the document.
+    /// Returns `None` if there is no page to print.
+    pub fn print_to_pdf(&mut self) -> Option<Vec<u8>> {
+        let _span = trace::span!(Paint, "print");
+
+        let current_page = self.current_page.as_mut()?;
+        Some(current_page.print().finish())
+    }
+
+    /// Whether the current page is animating, in which case it should be repainted
+    /// as soon as possible
+    #[must_use]
+    pub fn has_running_animations(&self) -> bool {
+        self.current_page
+            .as_ref()
+            .is_some_and(|current_page| current_page.transitions.borrow().has_running_transitions())
+    }
+
+    /// The accessibility tree of the current page
+    ///
+    /// The tree is only available once the page has been laid out.
+    #[must_use]
+    pub fn accessibility_tree(&self) -> Option<&AccessibilityTree> {
+        self.current_page.as_ref()?.accessibility_tree.as_ref()
+    }
+
+    /// The window of the current page
+    ///
+    /// The window is only available once the page has been painted.
+    #[must_use]
+    pub fn window(&self) -> Option<&Window> {
+        self.current_page.as_ref()?.window.as_ref()
+    }
+
+    /// The url of the current page, if any
+    #[must_use]
+    pub fn url(&self) -> Option<URL> {
+        Some(self.current_page.as_ref()?.document.borrow().url().clone())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location>
+    #[must_use]
+    pub fn location(&mut self) -> Location<'_> {
+        Location::new(self)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history>
+    #[must_use]
+    pub fn history(&mut self) -> History<'_> {
+        History::new(self)
+    }
+
+    /// <https://w3c.github.io/permissions/#navigator-and-workernavigator-extension>
+    #[must_use]
+    pub fn permissions(&self) -> Permissions {
+        let origin = self
+            .url()
+            .map_or_else(url::Origin::new_opaque, |url| url.origin());
+        Permissions::new(origin)
+    }
+
+    /// Post a message from the current page to its own window
+    ///
+    /// The message is delivered during the next rendering update.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/web-messaging.html#dom-window-postmessage>
+    pub fn post_message(
+        &mut self,
+        message: &js::Value,
+        target_origin: &str,
+    ) -> Result<(), PostMessageError> {
         let Some(current_page) = &mut self.current_page else {
-            return;
+            return Ok(());
         };
 
-        let mouse_position = mouse_event.position.map(|x| Pixels(x as f32));
+        // FIXME: Messages can only be posted by the page itself until there are nested browsing contexts
+        let message = PostedMessage::new(message, target_origin, current_page.origin.clone())?;
+        current_page.posted_messages.push(message);
+        Ok(())
+    }
 
-        let hovered_element: Option<DomPtr<dom_objects::Element>> = current_page
-            .fragment_tree
-            .hit_test(mouse_position)
-            .and_then(|fragment| fragment.dom_node())
-            .and_then(|node| node.try_into_type());
+    /// The position of the given element, as it was computed during the most recent layout
+    ///
+    /// Returns `None` if no page is loaded.
+    #[must_use]
+    pub fn geometry<'a>(
+        &'a self,
+        element: &'a DomPtr<dom_objects::Node>,
+    ) -> Option<ElementGeometry<'a>> {
+        let current_page = self.current_page.as_ref()?;
 
-        current_page.update_hovered_element(hovered_element);
+        Some(ElementGeometry::new(
+            &current_page.fragment_tree,
+            current_page.scroll_position,
+            element,
+        ))
     }
-}
 
-impl CurrentPage {
-    fn layout(&mut self, viewport_size: Size<Pixels>) {
-        let layout_start = time::Instant::now();
-        let style_computer = StyleComputer::new(&self.stylesheets, Pixels(16.), viewport_size);
+    /// The top left corner of the viewport within the page
+    #[must_use]
+    pub fn scroll_position(&self) -> math::Vec2D<Pixels> {
+        self.current_page
+            .as_ref()
+            .map_or(math::Vec2D::new(Pixels::ZERO, Pixels::ZERO), |page| {
+                page.scroll_position
+            })
+    }
 
-        // Build a box tree for the parsed document
-        let box_tree = BoxTree::new(self.document.clone(), style_computer);
-        log::info!("\n{:?}", box_tree);
+    /// Scroll the viewport to the given position, which is clamped to the size of the page
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-scrollto>
+    pub fn scroll_to(&mut self, position: math::Vec2D<Pixels>) {
+        if let Some(current_page) = &mut self.current_page {
+            current_page.scroll_to(position);
+        }
+    }
 
-        // Build a fragment tree by fragmenting the boxes
-        self.fragment_tree = box_tree.compute_fragments(viewport_size);
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-scrollby>
+    pub fn scroll_by(&mut self, delta: math::Vec2D<Pixels>) {
+        self.scroll_to(self.scroll_position() + delta);
+    }
 
-        let layout_end = time::Instant::now();
-        log::info!(
-            "Layout took {}ms",
-            layout_end.duration_since(layout_start).as_millis()
-        );
+    /// Catch up with scrolling that was handled by a [render::Compositor]
+    ///
+    /// The delta is given in the coordinates of the [LayerTree], which are CSS pixels.
+    pub fn apply_scroll_delta(&mut self, delta: math::Vec2D) {
+        self.scroll_by(delta.map(Pixels));
+    }
 
-        self.needs_relayout = false;
+    /// Let the observer track the elements of the current page
+    ///
+    /// Observations are updated during every paint, until the page is replaced.
+    pub fn add_intersection_observer(&mut self, observer: Rc<RefCell<IntersectionObserver>>) {
+        if let Some(current_page) = &mut self.current_page {
+            current_page.intersection_observers.push(observer);
+        }
     }
 
-    fn update_hovered_element(&mut self, hovered_element: Option<DomPtr<dom_objects::Element>>) {
-        // Update hover state and invalidate layout if necessary
-        match (hovered_element.clone(), self.hovered_element.clone()) {
-            (Some(new_element), Some(old_element)) => {
-                if new_element.ptr_eq(&old_element) {
-                    // Nothing to do
-                    return;
-                }
-                old_element.borrow_mut().set_hovered(false);
-                new_element.borrow_mut().set_hovered(true);
-            },
-            (Some(new_element), None) => {
-                new_element.borrow_mut().set_hovered(true);
-            },
-            (None, Some(old_element)) => {
-                old_element.borrow_mut().set_hovered(false);
-            },
-            (None, None) => return,
+    /// Let the observer track the size of elements on the current page
+    ///
+    /// Observations are gathered during every paint, until the page is replaced.
+    pub fn add_resize_observer(&mut self, observer: Rc<RefCell<ResizeObserver>>) {
+        if let Some(current_page) = &mut self.current_page {
+            current_page.resize_observers.push(observer);
         }
-        self.hovered_element = hovered_element;
+    }
 
-        // Changing the hovered element can change the CSS rules that apply (via the :hover pseudoclass)
-        // and therefore invalidates layout
-        self.invalidate_layout();
+    /// Move the focus to the next (or previous) element that can be reached with the keyboard
+    ///
+    /// Returns `false` if there is no such element, in which case the focus should move
+    /// to the browser interface.
+    pub fn move_focus(&mut self, direction: FocusDirection) -> bool {
+        let Some(current_page) = &mut self.current_page else {
+            return false;
+        };
+
+        let order =
+            focus::sequential_focus_order(&current_page.document, &current_page.fragment_tree);
+        let target =
+            focus::next_focus_target(&order, current_page.focused_element.as_ref(), direction);
+
+        current_page.update_focused_element(target);
+        current_page.focused_element.is_some()
     }
 
-    fn invalidate_layout(&mut self) {
-        self.needs_relayout = true;
+    /// The destination of the focused element, if it is a link
+    #[must_use]
+    pub fn focused_link(&self) -> Option<URL> {
+        let current_page = self.current_page.as_ref()?;
+        let focused_element = current_page.focused_element.as_ref()?.borrow();
+
+        if focused_element.local_name() != static_interned!("a") {
+            return None;
+        }
+
+        let href = focused_element
+            .attributes()
+            .get(&static_interned!("href"))?
+            .to_string();
+        let base = current_page.document.borrow().url().clone();
+
+        URL::parse_with_base(&href, Some(&base), None).ok()
     }
-}
 
-fn setup_document(location: URL) -> DomPtr<Document> {
-    let document = DomPtr::new(Document::default());
-    {
+    /// Whether the focused element accepts text, in which case key presses should be
+    /// passed through an input method first
+    #[must_use]
+    pub fn accepts_text_input(&self) -> bool {
+        self.current_page
+            .as_ref()
+            .and_then(CurrentPage::focused_text_area)
+            .is_some_and(|textarea| textarea.borrow().is_mutable())
+    }
+
+    /// The area of the focused element that accepts text, relative to the viewport
+    ///
+    /// Input methods display their candidates next to it.
+    #[must_use]
+    pub fn text_input_area(&self) -> Option<math::Rectangle> {
+        let current_page = self.current_page.as_ref()?;
+        let textarea = current_page.focused_text_area()?;
+        let area = current_page.area_of(&textarea.upcast())?;
+        let area = math::Rectangle::from_corners(
+            area.top_left().map(f32::from),
+            area.bottom_right().map(f32::from),
+        );
+
+        Some(area.offset_by(math::Vec2D::ORIGIN - current_page.scroll_position.map(f32::from)))
+    }
+
+    /// The document of the current page, if any
+    #[must_use]
+    pub(crate) fn document(&self) -> Option<DomPtr<Document>> {
+        Some(self.current_page.as_ref()?.document.clone())
+    }
+
+    /// The stylesheets of the current page, including the ones of the user agent
+    #[must_use]
+    pub(crate) fn stylesheets(&self) -> &[Stylesheet] {
+        self.current_page
+            .as_ref()
+            .map_or(&[], |page| &page.stylesheets)
+    }
+
+    /// The fragment tree of the current page, as it was most recently laid out
+    #[must_use]
+    pub(crate) fn fragment_tree(&self) -> Option<&FragmentTree> {
+        Some(&self.current_page.as_ref()?.fragment_tree)
+    }
+
+    /// The area covered by the boxes of the given node, in page coordinates
+    ///
+    /// This is `None` if the node did not generate any boxes during the most recent layout.
+    #[must_use]
+    pub(crate) fn area_of(
+        &self,
+        node: &DomPtr<dom_objects::Node>,
+    ) -> Option<math::Rectangle<Pixels>> {
+        self.current_page.as_ref()?.area_of(node)
+    }
+
+    /// Click on the center of the given element, as if the user did
+    ///
+    /// Returns `false` if the element is not displayed on the page.
+    pub(crate) fn click_element(&mut self, element: &DomPtr<dom_objects::Element>) -> bool {
+        let Some(current_page) = &mut self.current_page else {
+            return false;
+        };
+
+        let Some(area) = current_page.area_of(&element.clone().upcast()) else {
+            return false;
+        };
+        let center = area.top_left() + math::Vec2D::new(area.width() / 2., area.height() / 2.);
+
+        current_page.update_hovered_element(Some(element.clone()));
+        current_page.handle_click(center);
+        true
+    }
+
+    pub fn handle_mouse_event(&mut self, mouse_event: event::MouseEvent) {
+        let Some(current_page) = &mut self.current_page else {
+            return;
+        };
+
+        // Mouse positions are given in display points, which differ from
+        // CSS pixels if the page is zoomed
+        let scale = current_page.viewport.map_or(1., |viewport| viewport.scale);
+        let viewport_position = mouse_event.position.map(|x| Pixels(x as f32 / scale));
+        let mouse_position = viewport_position + current_page.scroll_position;
+
+        let hovered_element: Option<DomPtr<dom_objects::Element>> = current_page
+            .fragment_tree
+            .hit_test(mouse_position)
+            .and_then(|fragment| fragment.dom_node())
+            .and_then(|node| node.try_into_type());
+
+        current_page.update_hovered_element(hovered_element);
+
+        match mouse_event.kind {
+            event::MouseEventKind::Move => {
+                if let Some(select_popup) = &mut current_page.select_popup {
+                    select_popup.hover(viewport_position);
+                }
+            },
+            event::MouseEventKind::Down(event::MouseButton::Left) => {
+                current_page.handle_click(mouse_position);
+            },
+            _ => {},
+        }
+    }
+
+    /// Let the current page react to a key press
+    ///
+    /// Returns `false` if the key press was not handled by the page.
+    pub fn handle_key_press(&mut self, key_event: event::KeyEvent) -> bool {
+        let Some(current_page) = &mut self.current_page else {
+            return false;
+        };
+
+        // Keys that are not used by the focused element scroll the page
+        current_page.handle_key_press(key_event) || current_page.scroll_with_key(key_event.key)
+    }
+
+    /// Let the focused element react to text that is entered through an input method
+    ///
+    /// Returns `false` if the focused element does not accept text.
+    pub fn handle_composition_event(&mut self, composition_event: event::CompositionEvent) -> bool {
+        let Some(current_page) = &mut self.current_page else {
+            return false;
+        };
+
+        current_page.handle_composition_event(composition_event)
+    }
+
+    pub fn handle_wheel_event(&mut self, wheel_event: event::WheelEvent) {
+        self.scroll_by(wheel_event.distance().map(Pixels));
+    }
+
+    /// Let the page react to files that are dragged over it
+    ///
+    /// Returns the operation that the page performs if the files are dropped at the current position,
+    /// [DropEffect::None] if it does not accept them there.
+    pub fn handle_drag_event(&mut self, drag_event: event::DragEvent) -> DropEffect {
+        let Some(current_page) = &mut self.current_page else {
+            return DropEffect::None;
+        };
+
+        current_page.handle_drag_event(drag_event)
+    }
+
+    /// The file chooser that the page wants to be shown, if any
+    ///
+    /// The embedder should show a dialog and pass the picked files to [Self::complete_file_chooser].
+    #[must_use]
+    pub fn take_file_chooser_request(&mut self) -> Option<FileChooserRequest> {
+        self.current_page.as_mut()?.file_chooser_request.take()
+    }
+
+    /// Select the files that the user picked in a file chooser
+    ///
+    /// If `paths` is empty, the user closed the dialog without picking anything and the
+    /// selection does not change.
+    pub fn complete_file_chooser(&mut self, request: FileChooserRequest, paths: &[PathBuf]) {
+        let Some(current_page) = &mut self.current_page else {
+            return;
+        };
+
+        // The page might have navigated away while the dialog was open
+        let input = request.input().clone();
+        let is_on_page = input
+            .borrow()
+            .owning_document()
+            .is_some_and(|document| document.ptr_eq(&current_page.document));
+        if !is_on_page {
+            return;
+        }
+
+        if paths.is_empty() {
+            // If the user dismisses the prompt without changing their selection,
+            // then queue an element task on the user interaction task source given element to fire an event named cancel at element
+            // FIXME: Fire the cancel event once the DOM supports event listeners
+            log::debug!("cancel on file input");
+            return;
+        }
+
+        let files = file_chooser::read_files(paths);
+        input.borrow_mut().update_file_selection(files);
+        current_page.invalidate_layout_of(input.upcast());
+    }
+
+    /// The misspelled word below the given position on the screen, which is in display points
+    ///
+    /// Embedders should offer the suggestions of the correction in the context menu.
+    #[must_use]
+    pub fn spelling_correction_at(&self, position: math::Vec2D<i32>) -> Option<SpellingCorrection> {
+        self.current_page.as_ref()?.spelling_correction_at(position)
+    }
+
+    /// Replace a misspelled word with one of the suggestions of the correction
+    pub fn apply_spelling_correction(&mut self, correction: SpellingCorrection, suggestion: &str) {
+        let Some(current_page) = &mut self.current_page else {
+            return;
+        };
+
+        // The page might have navigated away or the text might have changed while the menu was open
+        let textarea = correction.textarea().clone();
+        let is_on_page = textarea
+            .borrow()
+            .owning_document()
+            .is_some_and(|document| document.ptr_eq(&current_page.document));
+        let is_unchanged = textarea
+            .borrow()
+            .editor()
+            .text()
+            .get(correction.range())
+            .is_some_and(|word| word == correction.word());
+        if !is_on_page || !is_unchanged || !textarea.borrow().is_mutable() {
+            return;
+        }
+
+        let range = correction.range();
+        textarea.borrow_mut().edit(|editor| {
+            editor.move_to(range.start, false);
+            editor.move_to(range.end, true);
+            editor.insert(suggestion);
+        });
+
+        // FIXME: Fire the input event once the DOM supports event listeners
+        log::debug!("input on textarea");
+        current_page.invalidate_layout_of(textarea.clone().upcast());
+        current_page.update_focused_element(Some(textarea.upcast()));
+    }
+}
+
+impl MemoryReporter for BrowsingContext {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        let pages = [&self.current_page, &self.page_without_reader_mode];
+        for page in pages.into_iter().flatten() {
+            page.report_memory(report);
+        }
+
+        RESOURCE_LOADER.cache().report_memory(report);
+        IMAGE_CACHE.report_memory(report);
+        font::SYSTEM_FONTS.report_memory(report);
+
+        // FIXME: Report the javascript heap once pages can run scripts
+    }
+}
+
+impl MemoryReporter for CurrentPage {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        self.document.report_memory(report);
+
+        for stylesheet in &self.stylesheets {
+            stylesheet.report_memory(report);
+        }
+    }
+}
+
+impl CurrentPage {
+    fn layout(&mut self, viewport: Viewport, media_environment: MediaEnvironment) {
+        let layout_start = time::Instant::now();
+
+        // The stylesheets never change while the page is displayed, but media queries and
+        // viewport units can change the style of any element
+        if self.media_environment != Some(media_environment) {
+            self.layout_cache.clear();
+        }
+
+        self.select_image_sources(&media_environment);
+        let style_computer = StyleComputer::new(
+            &self.stylesheets,
+            media_environment.font_sizes.proportional,
+            viewport.size,
+        )
+        .with_media_environment(media_environment)
+        .with_transitions(&self.transitions);
+
+        // Build a box tree for the parsed document
+        // The boxes are only needed during this layout pass, so they are allocated in an arena
+        let arena = BoxTreeArena::new(&self.layout_cache);
+        self.transitions.borrow_mut().begin_style_pass();
+        let box_tree = BoxTree::new(&arena, self.document.clone(), style_computer);
+        self.transitions.borrow_mut().end_style_pass();
+        log::info!("\n{:?}", box_tree);
+        log::debug!(
+            "Box tree contains {} boxes ({})",
+            arena.len(),
+            sl_std::memory::format_size(arena.size_in_bytes())
+        );
+
+        // Build a fragment tree by fragmenting the boxes
+        self.fragment_tree = box_tree.compute_fragments(viewport.size);
+        self.layout_cache.finish_pass();
+        self.accessibility_tree = Some(AccessibilityTree::new(&self.document, &self.fragment_tree));
+
+        let layout_end = time::Instant::now();
+        log::info!(
+            "Layout took {}ms",
+            layout_end.duration_since(layout_start).as_millis()
+        );
+
+        self.viewport = Some(viewport);
+        self.media_environment = Some(media_environment);
+        self.needs_relayout = false;
+
+        // The page might have become smaller than the area that was scrolled to
+        self.scroll_to(self.scroll_position);
+
+        // Media elements start loading once they are laid out
+        self.dispatch_media_events();
+    }
+
+    /// Lay the document out on pages and paint each of them
+    ///
+    /// The document is laid out again for the screen before it is painted next.
+    fn print(&mut self) -> PdfDocument {
+        let mut stylesheets: Vec<&Stylesheet> = self.stylesheets.iter().collect();
+        stylesheets.sort_by_key(|stylesheet| stylesheet.index());
+        let page_layout =
+            PageLayout::from_rules(stylesheets.into_iter().flat_map(Stylesheet::page_rules));
+
+        let content_size = page_layout.content_size();
+        let viewport = Viewport {
+            size: content_size,
+            scale: 1.,
+        };
+        let media_environment = MediaEnvironment::print(content_size);
+        let scroll_position = self.scroll_position;
+
+        // A page that was never painted has not finished loading yet
+        if self.viewport.is_none() {
+            self.layout(viewport, media_environment);
+            self.complete_loading();
+        }
+        self.layout(viewport, media_environment);
+
+        let overflow = self.fragment_tree.scrollable_overflow();
+        let canvas = Size {
+            width: overflow.width.max(content_size.width),
+            height: overflow.height.max(content_size.height),
+        };
+        let mut painter = Painter::default();
+        self.fragment_tree.fill_display_list(&mut painter, canvas);
+        let mut contents = LayerNode::default();
+        painter.finish().paint(&mut contents);
+
+        let page_size = math::Vec2D::new(
+            page_layout.size.width.into(),
+            page_layout.size.height.into(),
+        );
+        let page_breaks = self.fragment_tree.page_breaks(content_size.height);
+        let mut document = PdfDocument::default();
+        for (index, &page_start) in page_breaks.iter().enumerate() {
+            let page_end = page_breaks.get(index + 1).copied().unwrap_or(canvas.height);
+
+            // The part of the document that is shown on this page, within the page margins
+            let mut page_contents = LayerNode::scrolling(ScrollFrame {
+                offset: math::Vec2D::new(0., page_start.into()),
+                content_size: math::Vec2D::new(canvas.width.into(), canvas.height.into()),
+                viewport_size: math::Vec2D::new(
+                    content_size.width.into(),
+                    (page_end - page_start).into(),
+                ),
+            });
+            page_contents.children.push(contents.clone());
+
+            let mut page = LayerNode::scrolling(ScrollFrame {
+                offset: math::Vec2D::new(
+                    -f32::from(page_layout.margins.left),
+                    -f32::from(page_layout.margins.top),
+                ),
+                content_size: page_size,
+                viewport_size: page_size,
+            });
+            page.children.push(page_contents);
+
+            document.add_page(page_size, &page);
+        }
+
+        // Printing does not change where the user scrolled to
+        self.scroll_position = scroll_position;
+        self.needs_relayout = true;
+
+        document
+    }
+
+    /// Let images with a `srcset` attribute choose the source that fits the viewport best
+    ///
+    /// <https://html.spec.whatwg.org/multipage/images.html#reacting-to-environment-changes>
+    fn select_image_sources(&self, media_environment: &MediaEnvironment) {
+        fn visit(
+            node: &DomPtr<dom_objects::Node>,
+            media_environment: &MediaEnvironment,
+            device_pixel_ratio: f32,
+        ) {
+            if let Some(image) = node.try_into_type::<dom_objects::HtmlImageElement>() {
+                let source_changed = image
+                    .borrow_mut()
+                    .select_source(media_environment, device_pixel_ratio);
+                if source_changed {
+                    dom_objects::Element::mark_needs_layout(node.clone());
+                }
+            }
+
+            for child in node.borrow().children() {
+                visit(child, media_environment, device_pixel_ratio);
+            }
+        }
+
+        let device_pixel_ratio = self.window.as_ref().map_or(1., Window::device_pixel_ratio);
+        visit(
+            &self.document.clone().upcast(),
+            media_environment,
+            device_pixel_ratio,
+        );
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/webappapis.html#update-the-rendering>
+    fn update_animations(&mut self) {
+        self.animation_clock.update(time::Instant::now());
+
+        // Running transitions change the computed style of their elements
+        let had_running_transitions = self
+            .transitions
+            .get_mut()
+            .update(self.animation_clock.current_time());
+        if had_running_transitions {
+            self.invalidate_layout();
+        }
+    }
+
+    fn dispatch_transition_events(&mut self) {
+        for event in self.transitions.get_mut().take_events() {
+            // FIXME: Fire the event at its target once the DOM supports event listeners
+            log::debug!(
+                "{} on <{}> for {} (elapsed: {}s)",
+                event.kind.name(),
+                event.target.borrow().local_name(),
+                event.property_name,
+                event.elapsed_time
+            );
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#navigate-fragid>
+    fn navigate_to_fragment(&mut self, current_url: &URL, location: &URL) {
+        self.document.borrow_mut().set_url(location.clone());
+
+        if let Some(fragment) = location.fragment() {
+            self.scroll_to_fragment(fragment);
+        }
+
+        if location.fragment() != current_url.fragment()
+            && let Some(window) = &mut self.window
+        {
+            window.queue_event(WindowEvent::HashChange);
+        }
+    }
+
+    /// Display a different entry of the session history that belongs to this page
+    ///
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#update-document-for-history-step-application>
+    fn traverse_within_document(&mut self, location: &URL) {
+        let current_url = self.document.borrow().url().clone();
+        self.document.borrow_mut().set_url(location.clone());
+
+        if let Some(window) = &mut self.window {
+            window.queue_event(WindowEvent::PopState);
+
+            if location.fragment() != current_url.fragment() {
+                window.queue_event(WindowEvent::HashChange);
+            }
+        }
+
+        // FIXME: Restore the scroll position that the entry was left with
+        if let Some(fragment) = location.fragment() {
+            self.scroll_to_fragment(fragment);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#scroll-to-the-fragment-identifier>
+    fn scroll_to_fragment(&mut self, fragment: &ascii::Str) {
+        let fragment = percent_decode_utf8(fragment, Utf8Validation::Lossy).unwrap_or_default();
+
+        let indicated_element = find_indicated_element(&self.document.clone().upcast(), &fragment);
+        match indicated_element {
+            Some(element) => {
+                if let Some(area) = self.area_of(&element.upcast()) {
+                    self.scroll_to(area.top_left());
+                }
+            },
+            // The empty fragment and "top" indicate the top of the document
+            None if fragment.is_empty() || fragment.eq_ignore_ascii_case("top") => {
+                self.scroll_to(math::Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+            },
+            None => {},
+        }
+    }
+
+    /// Move the viewport to `position`, which is clamped to the scrollable overflow of the page
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#perform-a-scroll>
+    fn scroll_to(&mut self, position: math::Vec2D<Pixels>) {
+        let Some(viewport) = self.viewport else {
+            return;
+        };
+
+        let position = scroll::clamp(
+            position,
+            self.fragment_tree.scrollable_overflow(),
+            viewport.size,
+        );
+        if position == self.scroll_position {
+            return;
+        }
+
+        self.scroll_position = position;
+        self.has_pending_scroll_event = true;
+
+        // The popup of a drop-down box does not move with the page
+        self.select_popup = None;
+    }
+
+    /// Returns `false` if the key does not scroll the page
+    fn scroll_with_key(&mut self, key: event::Key) -> bool {
+        let Some(viewport) = self.viewport else {
+            return false;
+        };
+
+        let Some(position) = scroll::position_after_key_press(
+            key,
+            self.scroll_position,
+            self.fragment_tree.scrollable_overflow(),
+            viewport.size,
+        ) else {
+            return false;
+        };
+
+        self.scroll_to(position);
+        true
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#run-the-scroll-steps>
+    fn dispatch_scroll_events(&mut self) {
+        if mem::take(&mut self.has_pending_scroll_event) {
+            // FIXME: Fire the event at the document once the DOM supports event listeners
+            log::debug!("scroll on document");
+        }
+    }
+
+    /// The boxes of `element`, as computed during the most recent layout
+    fn measure(
+        &self,
+        element: &DomPtr<dom_objects::Element>,
+        device_pixel_ratio: f32,
+    ) -> Measurement {
+        let node = element.clone().upcast();
+        let geometry = ElementGeometry::new(&self.fragment_tree, self.scroll_position, &node);
+
+        Measurement {
+            border_box: Size {
+                width: geometry.offset_width(),
+                height: geometry.offset_height(),
+            },
+            content_rect: geometry.content_rect(),
+            device_pixel_ratio,
+        }
+    }
+
+    /// Deliver resize observations, laying out the page again whenever a callback ran
+    ///
+    /// Each iteration only reports elements that are deeper in the tree than the
+    /// shallowest element of the previous iteration, so the loop always terminates.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/webappapis.html#update-the-rendering>
+    fn run_resize_observers(
+        &mut self,
+        viewport: Viewport,
+        media_environment: MediaEnvironment,
+        device_pixel_ratio: f32,
+    ) {
+        if self.resize_observers.is_empty() {
+            return;
+        }
+
+        // 1. Let resizeObserverDepth be 0.
+        let mut depth = 0;
+
+        // 2. Gather active resize observations at depth resizeObserverDepth for doc.
+        let observers = self.resize_observers.clone();
+        resize_observer::gather_active_observations(&observers, depth, |element| {
+            self.measure(element, device_pixel_ratio)
+        });
+
+        // 3. While doc has active resize observations:
+        while resize_observer::has_active_observations(&observers) {
+            // 1. Set resizeObserverDepth to the result of broadcasting active resize observations given doc.
+            depth = resize_observer::broadcast_active_observations(&observers, |element| {
+                self.measure(element, device_pixel_ratio)
+            });
+
+            // 2. Recalculate styles and update layout for doc.
+            // FIXME: Only lay out the page again if a callback changed the document
+            self.layout(viewport, media_environment);
+
+            // 3. Gather active resize observations at depth resizeObserverDepth for doc.
+            resize_observer::gather_active_observations(&observers, depth, |element| {
+                self.measure(element, device_pixel_ratio)
+            });
+        }
+
+        // 4. If doc has skipped resize observations, then deliver resize loop error given doc.
+        if resize_observer::has_skipped_observations(&observers) {
+            // FIXME: Report the error at the window once the DOM supports event listeners
+            log::warn!("ResizeObserver loop completed with undelivered notifications");
+        }
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo>
+    fn update_intersection_observations(&mut self) {
+        let Some(viewport) = self.viewport else {
+            return;
+        };
+
+        // The time is relative to the time origin of the document
+        let time = f64::from(self.animation_clock.current_time()) * 1000.;
+
+        for observer in &self.intersection_observers {
+            observer
+                .borrow_mut()
+                .update_observations(viewport.size, time, |target| {
+                    let area = self.area_of(&target.clone().upcast())?;
+                    Some(DomRect::from_rectangle(area.offset_by(
+                        math::Vec2D::new(Pixels::ZERO, Pixels::ZERO) - self.scroll_position,
+                    )))
+                });
+        }
+    }
+
+    /// Run the final steps of loading the document, once all of its subresources were loaded
+    ///
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#the-end>
+    fn complete_loading(&mut self) {
+        // 8. Spin the event loop until there is nothing that delays the load event in the Document.
+        // 9. Queue a global task on the DOM manipulation task source given the Document's relevant global object
+        //    to run the following steps:
+        //    1. Update the current document readiness to "complete".
+        self.document
+            .borrow_mut()
+            .set_ready_state(DocumentReadyState::Complete);
+
+        //    2. If the Document object's browsing context is null, then abort these steps.
+        //    3. Let window be the Document's relevant global object.
+        //    ...
+        //    6. Fire an event named load at window, with legacy target override flag set.
+        if let Some(window) = &mut self.window {
+            window.queue_event(WindowEvent::Load);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#prompt-to-unload>
+    fn prompt_to_unload(&mut self) {
+        let Some(window) = &mut self.window else {
+            return;
+        };
+
+        window.queue_event(WindowEvent::BeforeUnload);
+        self.dispatch_window_events();
+    }
+
+    /// Called when a different document replaces this one
+    ///
+    /// <https://html.spec.whatwg.org/multipage/document-lifecycle.html#unload-a-document>
+    fn unload(&mut self) {
+        let Some(window) = &mut self.window else {
+            return;
+        };
+
+        // 7. If oldDocument's page showing is true:
+        //    2. Set oldDocument's page showing to false.
+        //    3. Fire a page transition event named pagehide at oldDocument's relevant global object with
+        //       oldDocument's salvageable state.
+        window.queue_event(WindowEvent::PageHide);
+
+        // 10. If oldDocument's salvageable state is false, fire an event named unload at
+        //     oldDocument's relevant global object, with legacy target override flag set.
+        window.queue_event(WindowEvent::Unload);
+
+        self.dispatch_window_events();
+    }
+
+    fn dispatch_window_events(&mut self) {
+        let Some(window) = &mut self.window else {
+            return;
+        };
+
+        for event in window.take_events() {
+            // FIXME: Fire the event at the window once the DOM supports event listeners
+            log::debug!("{} on window", event.name());
+        }
+    }
+
+    fn deliver_posted_messages(&mut self) {
+        for message in mem::take(&mut self.posted_messages) {
+            let Some(event) = message.deliver_to(&self.origin) else {
+                continue;
+            };
+
+            // FIXME: Fire the event at the window once the DOM supports event listeners
+            log::debug!("message on window from {}: {:?}", event.origin, event.data);
+        }
+    }
+
+    fn dispatch_media_events(&self) {
+        fn visit(node: &DomPtr<dom_objects::Node>) {
+            if let Some(media_element) = node.try_into_type::<dom_objects::HtmlMediaElement>() {
+                let mut media_element = media_element.borrow_mut();
+                for event in media_element.take_events() {
+                    // FIXME: Fire the event at its target once the DOM supports event listeners
+                    log::debug!("{} on <{}>", event.name(), media_element.local_name());
+                }
+            }
+
+            for child in node.borrow().children() {
+                visit(child);
+            }
+        }
+
+        visit(&self.document.clone().upcast());
+    }
+
+    fn update_hovered_element(&mut self, hovered_element: Option<DomPtr<dom_objects::Element>>) {
+        // Update hover state and invalidate layout if necessary
+        match (hovered_element.clone(), self.hovered_element.clone()) {
+            (Some(new_element), Some(old_element)) => {
+                if new_element.ptr_eq(&old_element) {
+                    // Nothing to do
+                    return;
+                }
+                old_element.borrow_mut().set_hovered(false);
+                new_element.borrow_mut().set_hovered(true);
+
+                // Changing the hovered element can change the CSS rules that apply (via the :hover pseudoclass)
+                // and therefore invalidates layout
+                self.invalidate_style_of(old_element);
+                self.invalidate_style_of(new_element);
+            },
+            (Some(new_element), None) => {
+                new_element.borrow_mut().set_hovered(true);
+                self.invalidate_style_of(new_element);
+            },
+            (None, Some(old_element)) => {
+                old_element.borrow_mut().set_hovered(false);
+                self.invalidate_style_of(old_element);
+            },
+            (None, None) => return,
+        }
+        self.hovered_element = hovered_element;
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#focus-update-steps>
+    fn update_focused_element(&mut self, focused_element: Option<DomPtr<dom_objects::Element>>) {
+        let is_unchanged = match (&focused_element, &self.focused_element) {
+            (Some(new_element), Some(old_element)) => new_element.ptr_eq(old_element),
+            (None, None) => true,
+            _ => false,
+        };
+        if is_unchanged {
+            return;
+        }
+
+        // FIXME: Fire "blur" and "focus" events at the elements once the DOM supports event listeners
+        // The focused element can be styled with the :focus pseudoclass and the focus ring
+        // needs to be repainted
+        if let Some(old_element) = self.focused_element.take() {
+            old_element.borrow_mut().set_focused(false);
+            self.invalidate_style_of(old_element);
+        }
+
+        if let Some(new_element) = &focused_element {
+            new_element.borrow_mut().set_focused(true);
+            self.invalidate_style_of(new_element.clone());
+        }
+        self.focused_element = focused_element;
+    }
+
+    /// The area covered by the boxes of the given node, in page coordinates
+    ///
+    /// Elements that span multiple lines produce multiple fragments, the area surrounds all of them.
+    fn area_of(&self, node: &DomPtr<dom_objects::Node>) -> Option<math::Rectangle<Pixels>> {
+        let mut area: Option<math::Rectangle<Pixels>> = None;
+        self.fragment_tree.for_each_box(|fragment, fragment_area| {
+            if fragment
+                .dom_node()
+                .is_some_and(|fragment_node| fragment_node.ptr_eq(node))
+            {
+                match &mut area {
+                    Some(area) => area.grow_to_contain(fragment_area),
+                    None => area = Some(fragment_area),
+                }
+            }
+        });
+
+        area
+    }
+
+    fn handle_click(&mut self, position: math::Vec2D<Pixels>) {
+        // Clicking anywhere closes an opened drop-down box, picking the option below the cursor (if any)
+        if let Some(select_popup) = self.select_popup.take() {
+            if let Some(index) = select_popup.option_at(position - self.scroll_position) {
+                select_popup.select().borrow().pick_option(index);
+                self.invalidate_layout_of(select_popup.select().clone().upcast());
+            }
+            return;
+        }
+
+        // The boxes that are visited last are painted on top
+        let mut clicked_node = None;
+        self.fragment_tree.for_each_box(|fragment, area| {
+            if area.contains_point(position)
+                && let Some(node) = fragment.dom_node()
+            {
+                clicked_node = Some(node);
+            }
+        });
+        let Some(clicked_node) = clicked_node else {
+            return;
+        };
+
+        // Options only generate boxes inside list boxes
+        if let Some(option) = clicked_node.try_into_type::<HtmlOptionElement>() {
+            let Some(select) = option.borrow().select_element() else {
+                return;
+            };
+            if select.borrow().is_disabled() {
+                return;
+            }
+
+            let index = select
+                .borrow()
+                .list_of_options()
+                .iter()
+                .position(|other| other.ptr_eq(&option));
+            if let Some(index) = index {
+                select.borrow().toggle_option(index);
+            }
+
+            self.invalidate_layout_of(select.clone().upcast());
+            self.update_focused_element(Some(select.upcast()));
+        } else if let Some(select) = clicked_node.try_into_type::<HtmlSelectElement>() {
+            if select.borrow().is_disabled() {
+                return;
+            }
+
+            self.update_focused_element(Some(select.clone().upcast()));
+            self.open_select_popup(select);
+        } else if let Some(textarea) = clicked_node.try_into_type::<HtmlTextAreaElement>() {
+            if textarea.borrow().is_disabled() {
+                return;
+            }
+
+            // Place the cursor at the clicked position
+            if let Some(geometry) = self.text_area_geometry(&textarea) {
+                let offset = geometry.offset_at(position);
+                textarea
+                    .borrow_mut()
+                    .edit(|editor| editor.move_to(offset, false));
+            }
+
+            self.invalidate_layout_of(textarea.clone().upcast());
+            self.update_focused_element(Some(textarea.upcast()));
+        } else if let Some(input) = clicked_node.try_into_type::<HtmlInputElement>() {
+            let is_enabled_file_upload = {
+                let input = input.borrow();
+                input.is_file_upload() && !input.is_disabled()
+            };
+            if !is_enabled_file_upload {
+                return;
+            }
+
+            // Activating a file upload control shows a file picker, which is up to the embedder
+            self.file_chooser_request = Some(FileChooserRequest::new(input.clone()));
+            self.update_focused_element(Some(input.upcast()));
+        }
+    }
+
+    fn handle_drag_event(&mut self, drag_event: event::DragEvent) -> DropEffect {
+        match drag_event {
+            event::DragEvent::Move(position) => {
+                let immediate_user_selection = self.element_at(position);
+                self.drag_session
+                    .get_or_insert_default()
+                    .update(immediate_user_selection)
+            },
+            event::DragEvent::Leave => {
+                if let Some(drag_session) = self.drag_session.take() {
+                    drag_session.leave();
+                }
+                DropEffect::None
+            },
+            event::DragEvent::Drop { position, paths } => {
+                let immediate_user_selection = self.element_at(position);
+                let mut drag_session = self.drag_session.take().unwrap_or_default();
+                let drop_effect = drag_session.update(immediate_user_selection);
+
+                // The files are only read if the page accepts them
+                let files = if drop_effect == DropEffect::None {
+                    vec![]
+                } else {
+                    file_chooser::read_files(&paths)
+                };
+
+                // Dropping files onto a file upload control selects them
+                if let Some(input) = drag_session.drop_files(files.clone()) {
+                    input.borrow_mut().update_file_selection(files);
+                    self.invalidate_layout_of(input.clone().upcast());
+                    self.update_focused_element(Some(input.upcast()));
+                }
+
+                drop_effect
+            },
+        }
+    }
+
+    /// Convert a position on the screen, which is in display points, to page coordinates
+    #[must_use]
+    fn page_position(&self, position: math::Vec2D<i32>) -> math::Vec2D<Pixels> {
+        let scale = self.viewport.map_or(1., |viewport| viewport.scale);
+        position.map(|x| Pixels(x as f32 / scale)) + self.scroll_position
+    }
+
+    /// The element below the given position on the screen, which is in display points
+    fn element_at(&self, position: math::Vec2D<i32>) -> Option<DomPtr<dom_objects::Element>> {
+        let node = self
+            .fragment_tree
+            .hit_test(self.page_position(position))
+            .and_then(|fragment| fragment.dom_node())?;
+        drag_and_drop::immediate_user_selection(node)
+    }
+
+    fn spelling_correction_at(&self, position: math::Vec2D<i32>) -> Option<SpellingCorrection> {
+        let position = self.page_position(position);
+
+        // Text fragments don't belong to any node, so look for the box of the textarea instead
+        let mut textarea = None;
+        self.fragment_tree.for_each_box(|fragment, area| {
+            if area.contains_point(position)
+                && let Some(node) = fragment.dom_node()
+            {
+                textarea = node.try_into_type::<HtmlTextAreaElement>();
+            }
+        });
+        let textarea = textarea?;
+
+        let offset = self.text_area_geometry(&textarea)?.offset_at(position);
+        SpellingCorrection::at_offset(textarea, offset)
+    }
+
+    /// The wrapped text of a `<textarea>`, as it was most recently laid out
+    fn text_area_geometry(
+        &self,
+        textarea: &DomPtr<HtmlTextAreaElement>,
+    ) -> Option<TextAreaGeometry> {
+        let node: DomPtr<dom_objects::Node> = textarea.clone().upcast();
+
+        let mut geometry = None;
+        self.fragment_tree.for_each_box(|fragment, area| {
+            if let Fragment::Box(box_fragment) = fragment
+                && fragment
+                    .dom_node()
+                    .is_some_and(|fragment_node| fragment_node.ptr_eq(&node))
+            {
+                geometry = Some(TextAreaGeometry::new(
+                    &textarea.borrow(),
+                    box_fragment.style(),
+                    area,
+                ));
+            }
+        });
+
+        geometry
+    }
+
+    fn open_select_popup(&mut self, select: DomPtr<HtmlSelectElement>) {
+        if !select.borrow().is_drop_down_box() {
+            return;
+        }
+
+        // The popup is positioned relative to the viewport
+        if let Some(anchor) = self.area_of(&select.clone().upcast()) {
+            let anchor = anchor
+                .offset_by(math::Vec2D::new(Pixels::ZERO, Pixels::ZERO) - self.scroll_position);
+            self.select_popup = Some(SelectPopup::open(select, anchor));
+        }
+    }
+
+    fn handle_key_press(&mut self, key_event: event::KeyEvent) -> bool {
+        let key = key_event.key;
+        let movement = match key {
+            event::Key::ArrowUp => Some(Movement::Previous),
+            event::Key::ArrowDown => Some(Movement::Next),
+            event::Key::Home => Some(Movement::First),
+            event::Key::End => Some(Movement::Last),
+            _ => None,
+        };
+
+        if let Some(select_popup) = &mut self.select_popup {
+            match (key, movement) {
+                (_, Some(movement)) => select_popup.move_highlight(movement),
+                (event::Key::Enter | event::Key::Space, _) => {
+                    if let Some(index) = select_popup.highlighted() {
+                        select_popup.select().borrow().pick_option(index);
+                    }
+                    let select = select_popup.select().clone();
+                    self.select_popup = None;
+                    self.invalidate_layout_of(select.upcast());
+                },
+                (event::Key::Escape, _) => self.select_popup = None,
+                _ => return false,
+            }
+            return true;
+        }
+
+        if let Some(textarea) = self.focused_text_area() {
+            return self.handle_text_area_key_press(textarea, key_event);
+        }
+
+        let Some(select) = self
+            .focused_element
+            .as_ref()
+            .and_then(|element| element.try_into_type::<HtmlSelectElement>())
+        else {
+            return false;
+        };
+        if select.borrow().is_disabled() {
+            return false;
+        }
+
+        match (key, movement) {
+            (_, Some(movement)) => {
+                // The arrow keys change the selection without opening the popup
+                let (is_disabled, selected_index): (Vec<bool>, _) = {
+                    let select = select.borrow();
+                    let is_disabled = select
+                        .list_of_options()
+                        .iter()
+                        .map(|option| option.borrow().is_disabled())
+                        .collect();
+                    (is_disabled, select.selected_index())
+                };
+
+                if let Some(index) =
+                    select_popup::move_within(&is_disabled, selected_index, movement)
+                {
+                    select.borrow().pick_option(index);
+                    self.invalidate_layout_of(select.upcast());
+                }
+                true
+            },
+            (event::Key::Enter | event::Key::Space, _) if select.borrow().is_drop_down_box() => {
+                self.open_select_popup(select);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn focused_text_area(&self) -> Option<DomPtr<HtmlTextAreaElement>> {
+        self.focused_element
+            .as_ref()
+            .and_then(|element| element.try_into_type::<HtmlTextAreaElement>())
+    }
+
+    fn handle_composition_event(&mut self, composition_event: event::CompositionEvent) -> bool {
+        let Some(textarea) = self.focused_text_area() else {
+            return false;
+        };
+        if !textarea.borrow().is_mutable() {
+            return false;
+        }
+
+        // FIXME: Fire the composition events at the textarea once the DOM supports event listeners
+        textarea
+            .borrow_mut()
+            .edit(|editor| match composition_event {
+                event::CompositionEvent::Start => log::debug!("compositionstart on textarea"),
+                event::CompositionEvent::Update(text) => {
+                    log::debug!("compositionupdate on textarea");
+                    editor.set_composition(&text);
+                },
+                event::CompositionEvent::End(text) => {
+                    log::debug!("compositionend on textarea");
+                    editor.commit_composition(&text);
+                },
+            });
+
+        self.invalidate_layout_of(textarea.upcast());
+        true
+    }
+
+    fn handle_text_area_key_press(
+        &mut self,
+        textarea: DomPtr<HtmlTextAreaElement>,
+        key_event: event::KeyEvent,
+    ) -> bool {
+        // Moving between lines depends on where the text wraps
+        let geometry = self.text_area_geometry(&textarea);
+        let is_mutable = textarea.borrow().is_mutable();
+        let extend_selection = key_event.shift;
+
+        let was_handled = textarea.borrow_mut().edit(|editor| {
+            match key_event.key {
+                event::Key::ArrowLeft => editor.move_cursor(Motion::Left, extend_selection),
+                event::Key::ArrowRight => editor.move_cursor(Motion::Right, extend_selection),
+                event::Key::ArrowUp | event::Key::ArrowDown => {
+                    let Some(geometry) = &geometry else {
+                        return false;
+                    };
+
+                    let lines = if key_event.key == event::Key::ArrowUp {
+                        -1
+                    } else {
+                        1
+                    };
+                    let target = geometry.move_vertically(editor.cursor(), lines);
+                    editor.move_to(target, extend_selection);
+                },
+                event::Key::Home | event::Key::End => {
+                    // Home and End move to the boundaries of the displayed line
+                    let is_home = key_event.key == event::Key::Home;
+                    match &geometry {
+                        Some(geometry) => {
+                            let line = geometry.line_boundaries(editor.cursor());
+                            let target = if is_home { line.start } else { line.end };
+                            editor.move_to(target, extend_selection);
+                        },
+                        None if is_home => editor.move_cursor(Motion::Start, extend_selection),
+                        None => editor.move_cursor(Motion::End, extend_selection),
+                    }
+                },
+                event::Key::Enter if is_mutable => editor.insert("\n"),
+                event::Key::Space if is_mutable => editor.insert(" "),
+                event::Key::Character(c) if is_mutable => {
+                    editor.insert(c.encode_utf8(&mut [0; 4]));
+                },
+                event::Key::Backspace if is_mutable => editor.delete_backward(),
+                event::Key::Delete if is_mutable => editor.delete_forward(),
+                _ => return false,
+            }
+            true
+        });
+
+        if was_handled {
+            self.invalidate_layout_of(textarea.upcast());
+        }
+        was_handled
+    }
+
+    fn paint_focus_ring(&self, painter: &mut Painter) {
+        let Some(focused_element) = &self.focused_element else {
+            return;
+        };
+        let focused_node: DomPtr<dom_objects::Node> = focused_element.clone().upcast();
+
+        if let Some(area) = self.area_of(&focused_node) {
+            painter.begin_fragment(Some(&focused_node));
+            focus::paint_focus_ring(painter, area);
+            painter.end_fragment();
+        }
+    }
+
+    /// Lay out the page again during the next paint
+    ///
+    /// Parts of the page that did not change since the previous layout pass reuse its results.
+    fn invalidate_layout(&mut self) {
+        self.needs_relayout = true;
+    }
+
+    /// Schedule a layout pass, because something about `node` changed
+    fn invalidate_layout_of(&mut self, node: DomPtr<dom_objects::Node>) {
+        dom_objects::Element::mark_needs_layout(node);
+        self.invalidate_layout();
+    }
+
+    /// Schedule a layout pass, because the pseudoclasses that match `element` changed
+    ///
+    /// Selectors like `:hover > p` and `:hover ~ p` can change the style of the
+    /// descendants and the siblings of the element too.
+    fn invalidate_style_of(&mut self, element: DomPtr<dom_objects::Element>) {
+        let node: DomPtr<dom_objects::Node> = element.upcast();
+        let parent = node.borrow().parent_node();
+        dom_objects::Element::mark_subtree_needs_layout(parent.unwrap_or(node));
+        self.invalidate_layout();
+    }
+}
+
+fn setup_document(location: URL) -> DomPtr<Document> {
+    let document = DomPtr::new(Document::default());
+    {
         let mut document_ref = document.borrow_mut();
         document_ref.set_owning_document(DomPtr::clone(&document).downgrade());
         document_ref.set_url(location.clone());
+        document_ref.set_ready_state(DocumentReadyState::Loading);
     }
     document
 }
+
+/// Whether a resource can be displayed as a page, everything else is downloaded
+fn is_displayable(resource: &Resource) -> bool {
+    if resource.http_headers().is_some_and(download::is_attachment) {
+        return false;
+    }
+
+    let mime_type = &resource.mime_metadata().computed_mime_type;
+    mime_type.is_html()
+        || mime_type.has_essence("application/xhtml+xml")
+        || mime_type.has_essence("application/rss+xml")
+        || mime_type.has_essence("application/atom+xml")
+}
+
+/// The element with the given id, or an `<a>` element with the given name
+///
+/// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#find-a-potential-indicated-element>
+fn find_indicated_element(
+    node: &DomPtr<dom_objects::Node>,
+    fragment: &str,
+) -> Option<DomPtr<dom_objects::Element>> {
+    if fragment.is_empty() {
+        return None;
+    }
+
+    if let Some(element) = node.try_into_type::<dom_objects::Element>() {
+        let is_indicated = {
+            let element = element.borrow();
+            let attributes = element.attributes();
+            attributes
+                .get(&static_interned!("id"))
+                .is_some_and(|id| id.as_str() == fragment)
+                || (element.local_name() == static_interned!("a")
+                    && attributes
+                        .get(&static_interned!("name"))
+                        .is_some_and(|name| name.as_str() == fragment))
+        };
+
+        if is_indicated {
+            return Some(element);
+        }
+    }
+
+    node.borrow()
+        .children()
+        .iter()
+        .find_map(|child| find_indicated_element(child, fragment))
+}
@@ -0,0 +1,1011 @@
+//! <https://html.spec.whatwg.org/multipage/canvas.html#canvasrenderingcontext2d>
+
+use std::sync::Arc;
+
+use image::{Rgbaf32, Texture};
+use math::{AffineTransform, Angle, Rectangle, Vec2D};
+use render::{FlattenedPathPoint, LineCap, LineJoin, Rasterizer, Stroke};
+
+use crate::css::{self, CSSParse};
+
+use super::{path::CanvasPath, CanvasError, ImageData};
+
+/// The font that is used if the `font` attribute was never set
+const DEFAULT_FONT: &str = "10px sans-serif";
+
+/// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-textalign>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    #[default]
+    Start,
+    End,
+    Left,
+    Right,
+    Center,
+}
+
+/// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-textbaseline>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextBaseline {
+    Top,
+    Hanging,
+    Middle,
+    #[default]
+    Alphabetic,
+    Ideographic,
+    Bottom,
+}
+
+/// <https://html.spec.whatwg.org/multipage/canvas.html#textmetrics>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+}
+
+/// The font used for drawing text, as set through the `font` attribute
+#[derive(Clone, Debug, PartialEq)]
+struct CanvasFont {
+    /// The value of the `font` attribute, as it was set
+    specified: String,
+    family: String,
+    size: f32,
+}
+
+/// <https://html.spec.whatwg.org/multipage/canvas.html#drawing-state>
+#[derive(Clone, Debug)]
+struct DrawingState {
+    transform: AffineTransform,
+
+    // FIXME: Support gradients and patterns
+    fill_style: Rgbaf32,
+    stroke_style: Rgbaf32,
+    global_alpha: f32,
+    line_width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+    font: CanvasFont,
+    text_align: TextAlign,
+    text_baseline: TextBaseline,
+}
+
+impl Default for DrawingState {
+    fn default() -> Self {
+        Self {
+            transform: AffineTransform::identity(),
+            fill_style: Rgbaf32::rgb(0., 0., 0.),
+            stroke_style: Rgbaf32::rgb(0., 0., 0.),
+            global_alpha: 1.,
+            line_width: 1.,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: 10.,
+            font: CanvasFont::parse(DEFAULT_FONT).expect("default font is valid"),
+            text_align: TextAlign::default(),
+            text_baseline: TextBaseline::default(),
+        }
+    }
+}
+
+/// Draws into the bitmap of a `<canvas>` element
+///
+/// The bitmap uses non-premultiplied colors and starts out as transparent black.
+/// It is shared with everyone that displays the canvas and only copied when it is
+/// drawn to while still being displayed.
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#canvasrenderingcontext2d)
+#[derive(Clone, Debug)]
+pub struct CanvasRenderingContext2D {
+    bitmap: Arc<Texture>,
+
+    /// Incremented whenever the bitmap is drawn to
+    generation: u64,
+
+    state: DrawingState,
+
+    /// States that were pushed by [save](Self::save)
+    state_stack: Vec<DrawingState>,
+    path: CanvasPath,
+}
+
+impl CanvasRenderingContext2D {
+    #[must_use]
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            bitmap: Arc::new(Texture::new(width, height)),
+            generation: 0,
+            state: DrawingState::default(),
+            state_stack: vec![],
+            path: CanvasPath::default(),
+        }
+    }
+
+    /// The pixels that were drawn so far
+    #[must_use]
+    pub fn bitmap(&self) -> &Arc<Texture> {
+        &self.bitmap
+    }
+
+    /// Changes whenever the [bitmap](Self::bitmap) is drawn to
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-save>
+    pub fn save(&mut self) {
+        self.state_stack.push(self.state.clone());
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-restore>
+    pub fn restore(&mut self) {
+        if let Some(state) = self.state_stack.pop() {
+            self.state = state;
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-reset>
+    pub fn reset(&mut self) {
+        self.bitmap_mut().clear(Rgbaf32::BLANK);
+        self.state = DrawingState::default();
+        self.state_stack.clear();
+        self.path = CanvasPath::default();
+    }
+
+    // Transformations
+
+    #[must_use]
+    pub fn get_transform(&self) -> AffineTransform {
+        self.state.transform
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-scale>
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.apply_transform(AffineTransform::scale(x, y));
+    }
+
+    /// Rotate clockwise by `angle` radians
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-rotate)
+    pub fn rotate(&mut self, angle: f32) {
+        // The y axis points downwards, so this is a clockwise rotation on screen
+        self.apply_transform(AffineTransform::rotate(Angle::from_radians(angle)));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-translate>
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.apply_transform(AffineTransform::translate(Vec2D::new(x, y)));
+    }
+
+    /// Multiply the current transformation matrix with the given matrix
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-transform)
+    pub fn transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
+        self.apply_transform(canvas_matrix(a, b, c, d, e, f));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-settransform>
+    pub fn set_transform(&mut self, a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) {
+        self.state.transform = canvas_matrix(a, b, c, d, e, f);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-resettransform>
+    pub fn reset_transform(&mut self) {
+        self.state.transform = AffineTransform::identity();
+    }
+
+    fn apply_transform(&mut self, transform: AffineTransform) {
+        // The new transform is applied to points before the existing one
+        self.state.transform = transform.chain(self.state.transform);
+    }
+
+    // Compositing and styles
+
+    #[must_use]
+    pub fn global_alpha(&self) -> f32 {
+        self.state.global_alpha
+    }
+
+    /// Values outside of `0..=1` are ignored
+    pub fn set_global_alpha(&mut self, global_alpha: f32) {
+        if (0. ..=1.).contains(&global_alpha) {
+            self.state.global_alpha = global_alpha;
+        }
+    }
+
+    #[must_use]
+    pub fn fill_style(&self) -> String {
+        serialize_color(self.state.fill_style)
+    }
+
+    /// Set the fill style to a CSS color, invalid colors are ignored
+    pub fn set_fill_style(&mut self, style: &str) {
+        if let Some(color) = parse_color(style) {
+            self.state.fill_style = color;
+        }
+    }
+
+    #[must_use]
+    pub fn stroke_style(&self) -> String {
+        serialize_color(self.state.stroke_style)
+    }
+
+    /// Set the stroke style to a CSS color, invalid colors are ignored
+    pub fn set_stroke_style(&mut self, style: &str) {
+        if let Some(color) = parse_color(style) {
+            self.state.stroke_style = color;
+        }
+    }
+
+    // Line styles
+
+    #[must_use]
+    pub fn line_width(&self) -> f32 {
+        self.state.line_width
+    }
+
+    /// Values that are not positive and finite are ignored
+    pub fn set_line_width(&mut self, line_width: f32) {
+        if line_width.is_finite() && line_width > 0. {
+            self.state.line_width = line_width;
+        }
+    }
+
+    #[must_use]
+    pub fn line_cap(&self) -> LineCap {
+        self.state.line_cap
+    }
+
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.state.line_cap = line_cap;
+    }
+
+    #[must_use]
+    pub fn line_join(&self) -> LineJoin {
+        self.state.line_join
+    }
+
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.state.line_join = line_join;
+    }
+
+    #[must_use]
+    pub fn miter_limit(&self) -> f32 {
+        self.state.miter_limit
+    }
+
+    /// Values that are not positive and finite are ignored
+    pub fn set_miter_limit(&mut self, miter_limit: f32) {
+        if miter_limit.is_finite() && miter_limit > 0. {
+            self.state.miter_limit = miter_limit;
+        }
+    }
+
+    // Text styles
+
+    #[must_use]
+    pub fn font(&self) -> &str {
+        &self.state.font.specified
+    }
+
+    /// Set the font used for drawing text, like `"bold 16px serif"`
+    ///
+    /// Values that can't be parsed are ignored.
+    pub fn set_font(&mut self, font: &str) {
+        if let Some(font) = CanvasFont::parse(font) {
+            self.state.font = font;
+        }
+    }
+
+    #[must_use]
+    pub fn text_align(&self) -> TextAlign {
+        self.state.text_align
+    }
+
+    pub fn set_text_align(&mut self, text_align: TextAlign) {
+        self.state.text_align = text_align;
+    }
+
+    #[must_use]
+    pub fn text_baseline(&self) -> TextBaseline {
+        self.state.text_baseline
+    }
+
+    pub fn set_text_baseline(&mut self, text_baseline: TextBaseline) {
+        self.state.text_baseline = text_baseline;
+    }
+
+    // Building paths
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-beginpath>
+    pub fn begin_path(&mut self) {
+        self.path = CanvasPath::default();
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-closepath>
+    pub fn close_path(&mut self) {
+        self.path.close_path();
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-moveto>
+    pub fn move_to(&mut self, x: f32, y: f32) {
+        let point = self.to_device(x, y);
+        self.path.move_to(point);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-lineto>
+    pub fn line_to(&mut self, x: f32, y: f32) {
+        let point = self.to_device(x, y);
+        self.path.line_to(point);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-quadraticcurveto>
+    pub fn quadratic_curve_to(&mut self, cpx: f32, cpy: f32, x: f32, y: f32) {
+        let control_point = self.to_device(cpx, cpy);
+        let end = self.to_device(x, y);
+        self.path.quadratic_curve_to(control_point, end);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-beziercurveto>
+    pub fn bezier_curve_to(&mut self, cp1x: f32, cp1y: f32, cp2x: f32, cp2y: f32, x: f32, y: f32) {
+        let control_point_1 = self.to_device(cp1x, cp1y);
+        let control_point_2 = self.to_device(cp2x, cp2y);
+        let end = self.to_device(x, y);
+        self.path
+            .bezier_curve_to(control_point_1, control_point_2, end);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-arc>
+    pub fn arc(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius: f32,
+        start_angle: f32,
+        end_angle: f32,
+        counterclockwise: bool,
+    ) -> Result<(), CanvasError> {
+        self.ellipse(
+            x,
+            y,
+            radius,
+            radius,
+            0.,
+            start_angle,
+            end_angle,
+            counterclockwise,
+        )
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-ellipse>
+    #[allow(clippy::too_many_arguments)]
+    pub fn ellipse(
+        &mut self,
+        x: f32,
+        y: f32,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        start_angle: f32,
+        end_angle: f32,
+        counterclockwise: bool,
+    ) -> Result<(), CanvasError> {
+        // 1. If any of the arguments are infinite or NaN, then return.
+        if ![x, y, radius_x, radius_y, rotation, start_angle, end_angle]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return Ok(());
+        }
+
+        // 2. If either radiusX or radiusY are negative, then throw an "IndexSizeError" DOMException.
+        if radius_x < 0. || radius_y < 0. {
+            return Err(CanvasError::IndexSize);
+        }
+
+        self.path.ellipse(
+            self.state.transform,
+            Vec2D::new(x, y),
+            radius_x,
+            radius_y,
+            rotation,
+            start_angle,
+            end_angle,
+            counterclockwise,
+        );
+
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-rect>
+    pub fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let corners = self.rect_corners(x, y, width, height);
+        self.path.rect(corners);
+    }
+
+    // Drawing paths
+
+    /// Fill the current path with the fill style
+    ///
+    /// FIXME: Support the "evenodd" fill rule
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-fill)
+    pub fn fill(&mut self) {
+        let path = std::mem::take(&mut self.path);
+        self.fill_path(&path);
+        self.path = path;
+    }
+
+    /// Stroke the current path with the stroke style
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-stroke)
+    pub fn stroke(&mut self) {
+        let path = std::mem::take(&mut self.path);
+        self.stroke_path(&path);
+        self.path = path;
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-fillrect>
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let mut path = CanvasPath::default();
+        path.rect(self.rect_corners(x, y, width, height));
+        self.fill_path(&path);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-strokerect>
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let mut path = CanvasPath::default();
+        path.rect(self.rect_corners(x, y, width, height));
+        self.stroke_path(&path);
+    }
+
+    /// Make the pixels inside the rectangle transparent black
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-clearrect)
+    pub fn clear_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        let mut path = CanvasPath::default();
+        path.rect(self.rect_corners(x, y, width, height));
+
+        let mut outline = vec![];
+        path.fill_outline(&mut outline);
+        self.composite(&outline, |pixel, _, coverage| {
+            if coverage == 1. {
+                *pixel = Rgbaf32::BLANK;
+            } else {
+                // Pixels on the edge of the rectangle are only partially cleared
+                pixel.set_alpha(pixel.alpha() * (1. - coverage));
+            }
+        });
+    }
+
+    fn fill_path(&mut self, path: &CanvasPath) {
+        let mut outline = vec![];
+        path.fill_outline(&mut outline);
+        self.paint_outline(&outline, self.state.fill_style);
+    }
+
+    fn stroke_path(&mut self, path: &CanvasPath) {
+        // The stroke is computed in user space, so that transformations also
+        // affect its width
+        let Some(inverse_transform) = self.state.transform.invert() else {
+            return;
+        };
+
+        let mut center_lines = vec![];
+        path.stroke_center_lines(inverse_transform, &mut center_lines);
+
+        let stroke = Stroke {
+            miter_limit: self.state.miter_limit,
+            ..Stroke::new(self.state.line_width)
+                .with_cap(self.state.line_cap)
+                .with_join(self.state.line_join)
+        };
+
+        let mut outline = vec![];
+        stroke.outline(&center_lines, &mut outline);
+        for point in &mut outline {
+            point.coordinates = self.state.transform.apply_to(point.coordinates);
+        }
+
+        self.paint_outline(&outline, self.state.stroke_style);
+    }
+
+    // Text
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-filltext>
+    pub fn fill_text(&mut self, text: &str, x: f32, y: f32) {
+        let outline = self.text_outline(text, x, y);
+        self.paint_outline(&outline, self.state.fill_style);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-stroketext>
+    pub fn stroke_text(&mut self, text: &str, x: f32, y: f32) {
+        let mut path = CanvasPath::default();
+        let outline = self.text_outline(text, x, y);
+        for point in outline {
+            if point.connected {
+                path.line_to(point.coordinates);
+            } else {
+                path.move_to(point.coordinates);
+            }
+        }
+
+        self.stroke_path(&path);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-measuretext>
+    #[must_use]
+    pub fn measure_text(&self, text: &str) -> TextMetrics {
+        let font = self.state.font.load();
+        TextMetrics {
+            width: font.compute_rendered_width(text, self.state.font.size),
+        }
+    }
+
+    /// Compute the (device space) outlines of the glyphs in `text`
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#text-preparation-algorithm)
+    fn text_outline(&self, text: &str, x: f32, y: f32) -> Vec<FlattenedPathPoint> {
+        const FLATTEN_TOLERANCE: f32 = 0.01;
+
+        let font = self.state.font.load();
+        let size = self.state.font.size;
+        let width = font.compute_rendered_width(text, size);
+
+        // FIXME: "start" and "end" depend on the direction of the text
+        let x_offset = match self.state.text_align {
+            TextAlign::Start | TextAlign::Left => 0.,
+            TextAlign::End | TextAlign::Right => -width,
+            TextAlign::Center => -width / 2.,
+        };
+
+        // Fonts are rendered with the top of the em box at the given offset, with the
+        // alphabetic baseline one em below that
+        // FIXME: Use the actual font metrics for the other baselines
+        let y_offset = match self.state.text_baseline {
+            TextBaseline::Top | TextBaseline::Hanging => 0.,
+            TextBaseline::Middle => -size / 2.,
+            TextBaseline::Alphabetic | TextBaseline::Ideographic | TextBaseline::Bottom => -size,
+        };
+
+        let mut path = render::Path::empty();
+        font.render(
+            text,
+            &mut path,
+            size,
+            Vec2D::new(x + x_offset, y + y_offset),
+        );
+
+        let mut outline = vec![];
+        path.flatten(FLATTEN_TOLERANCE, &mut outline);
+        for point in &mut outline {
+            point.coordinates = self.state.transform.apply_to(point.coordinates);
+        }
+        outline
+    }
+
+    // Images
+
+    /// Draw an image at its natural size
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-drawimage)
+    pub fn draw_image(&mut self, image: &Texture, dx: f32, dy: f32) {
+        let width = image.width() as f32;
+        let height = image.height() as f32;
+
+        self.draw_image_section(image, 0., 0., width, height, dx, dy, width, height);
+    }
+
+    /// Draw an image, scaled to the given size
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-drawimage)
+    pub fn draw_image_scaled(&mut self, image: &Texture, dx: f32, dy: f32, dw: f32, dh: f32) {
+        let width = image.width() as f32;
+        let height = image.height() as f32;
+
+        self.draw_image_section(image, 0., 0., width, height, dx, dy, dw, dh);
+    }
+
+    /// Draw a section of an image into a rectangle on the canvas
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-drawimage)
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_image_section(
+        &mut self,
+        image: &Texture,
+        sx: f32,
+        sy: f32,
+        sw: f32,
+        sh: f32,
+        dx: f32,
+        dy: f32,
+        dw: f32,
+        dh: f32,
+    ) {
+        // 5. If one of the sw or sh arguments is zero, then return. Nothing is painted.
+        if sw == 0. || sh == 0. || image.width() == 0 || image.height() == 0 {
+            return;
+        }
+
+        let Some(inverse_transform) = self.state.transform.invert() else {
+            return;
+        };
+
+        let mut path = CanvasPath::default();
+        path.rect(self.rect_corners(dx, dy, dw, dh));
+        let mut outline = vec![];
+        path.fill_outline(&mut outline);
+
+        let global_alpha = self.state.global_alpha;
+        self.composite(&outline, |pixel, position, coverage| {
+            // Sample the image at the center of the pixel
+            let user_point = inverse_transform.apply_to(position + Vec2D::new(0.5, 0.5));
+            let image_x = sx + (user_point.x - dx) / dw * sw;
+            let image_y = sy + (user_point.y - dy) / dh * sh;
+
+            let mut color = image.get_clamped(image_x.max(0.) as usize, image_y.max(0.) as usize);
+            color.set_alpha(color.alpha() * coverage * global_alpha);
+            *pixel = pixel.blend(color);
+        });
+    }
+
+    // Pixel manipulation
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-createimagedata>
+    pub fn create_image_data(&self, width: usize, height: usize) -> Result<ImageData, CanvasError> {
+        ImageData::new(width, height)
+    }
+
+    /// Read the pixels in a rectangle of the bitmap, pixels outside of the bitmap
+    /// are transparent black
+    ///
+    /// The current transformation is ignored.
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-getimagedata)
+    pub fn get_image_data(
+        &self,
+        sx: isize,
+        sy: isize,
+        width: usize,
+        height: usize,
+    ) -> Result<ImageData, CanvasError> {
+        let mut image_data = ImageData::new(width, height)?;
+
+        for y in 0..height {
+            for x in 0..width {
+                let source_x = sx + x as isize;
+                let source_y = sy + y as isize;
+
+                if let Some(pixel) = self.pixel_at(source_x, source_y) {
+                    image_data.set_pixel(x, y, pixel);
+                }
+            }
+        }
+
+        Ok(image_data)
+    }
+
+    /// Write pixels into the bitmap, without blending them with the existing content
+    ///
+    /// The current transformation and global alpha are ignored.
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-putimagedata)
+    pub fn put_image_data(&mut self, image_data: &ImageData, dx: isize, dy: isize) {
+        let (width, height) = (self.bitmap.width(), self.bitmap.height());
+        let bitmap = self.bitmap_mut();
+
+        for y in 0..image_data.height() {
+            for x in 0..image_data.width() {
+                let target_x = dx + x as isize;
+                let target_y = dy + y as isize;
+
+                let (Ok(target_x), Ok(target_y)) =
+                    (usize::try_from(target_x), usize::try_from(target_y))
+                else {
+                    continue;
+                };
+
+                if target_x < width && target_y < height {
+                    bitmap.set_pixel(target_x, target_y, image_data.pixel(x, y));
+                }
+            }
+        }
+    }
+
+    fn pixel_at(&self, x: isize, y: isize) -> Option<Rgbaf32> {
+        let x = usize::try_from(x).ok()?;
+        let y = usize::try_from(y).ok()?;
+
+        if x >= self.bitmap.width() || y >= self.bitmap.height() {
+            return None;
+        }
+
+        Some(self.bitmap.get_pixel(x, y))
+    }
+
+    // Helpers
+
+    /// Mutable access to the bitmap, copying it first if it is still displayed somewhere
+    fn bitmap_mut(&mut self) -> &mut Texture {
+        self.generation += 1;
+        Arc::make_mut(&mut self.bitmap)
+    }
+
+    fn to_device(&self, x: f32, y: f32) -> Vec2D {
+        self.state.transform.apply_to(Vec2D::new(x, y))
+    }
+
+    fn rect_corners(&self, x: f32, y: f32, width: f32, height: f32) -> [Vec2D; 4] {
+        [
+            self.to_device(x, y),
+            self.to_device(x + width, y),
+            self.to_device(x + width, y + height),
+            self.to_device(x, y + height),
+        ]
+    }
+
+    /// Blend a solid color onto every pixel that is covered by the outline
+    fn paint_outline(&mut self, outline: &[FlattenedPathPoint], color: Rgbaf32) {
+        let global_alpha = self.state.global_alpha;
+
+        self.composite(outline, |pixel, _, coverage| {
+            let mut color = color;
+            color.set_alpha(color.alpha() * coverage * global_alpha);
+            *pixel = pixel.blend(color);
+        });
+    }
+
+    /// Rasterize the outline (using the nonzero winding rule) and call `paint` for
+    /// every pixel that is at least partially covered
+    ///
+    /// `paint` receives the pixel, its position and how much of it is covered by the outline.
+    fn composite<F>(&mut self, outline: &[FlattenedPathPoint], mut paint: F)
+    where
+        F: FnMut(&mut Rgbaf32, Vec2D, f32),
+    {
+        let Some((first, rest)) = outline.split_first() else {
+            return;
+        };
+
+        let mut extent = Rectangle::from_corners(first.coordinates, first.coordinates);
+        for point in rest {
+            extent.grow_to_contain_point(point.coordinates);
+        }
+
+        // Only rasterize the part of the outline that is inside the bitmap
+        let clamp = |value: f32, max: usize| (value.max(0.) as usize).min(max);
+        let top_left = Vec2D::new(
+            clamp(extent.top_left().x.floor(), self.bitmap.width()),
+            clamp(extent.top_left().y.floor(), self.bitmap.height()),
+        );
+        let bottom_right = Vec2D::new(
+            clamp(extent.bottom_right().x.ceil(), self.bitmap.width()),
+            clamp(extent.bottom_right().y.ceil(), self.bitmap.height()),
+        );
+
+        if top_left.x == bottom_right.x || top_left.y == bottom_right.y {
+            return;
+        }
+
+        let mut rasterizer = Rasterizer::new(
+            Rectangle::from_corners(top_left, bottom_right),
+            top_left.map(|value| value as f32),
+        );
+        rasterizer.fill(outline);
+        let mask = rasterizer.into_mask();
+
+        let bitmap = self.bitmap_mut();
+
+        for y in top_left.y..bottom_right.y {
+            for x in top_left.x..bottom_right.x {
+                let coverage = mask.opacity_at(x - top_left.x, y - top_left.y);
+                if coverage == 0. {
+                    continue;
+                }
+
+                let position = Vec2D::new(x as f32, y as f32);
+                paint(bitmap.pixel_data_mut(x, y), position, coverage);
+            }
+        }
+    }
+}
+
+/// Create a transform from the matrix notation used by the canvas API
+///
+/// ```text
+/// a c e
+/// b d f
+/// 0 0 1
+/// ```
+fn canvas_matrix(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> AffineTransform {
+    AffineTransform::from_matrix([[a, c, e], [b, d, f]])
+}
+
+fn parse_color(source: &str) -> Option<Rgbaf32> {
+    let mut parser = css::Parser::new(source, css::Origin::Author);
+    let color = css::Color::parse_complete(&mut parser).ok()?;
+
+    Some(color.into())
+}
+
+/// <https://html.spec.whatwg.org/multipage/canvas.html#serialisation-of-a-color>
+fn serialize_color(color: Rgbaf32) -> String {
+    let to_byte = |channel: f32| (channel * 255.).round() as u8;
+    let (red, green, blue) = (
+        to_byte(color.red()),
+        to_byte(color.green()),
+        to_byte(color.blue()),
+    );
+
+    if color.alpha() == 1. {
+        format!("#{red:02x}{green:02x}{blue:02x}")
+    } else {
+        let alpha = f32::from(to_byte(color.alpha())) / 255.;
+        format!("rgba({red}, {green}, {blue}, {alpha})")
+    }
+}
+
+impl CanvasFont {
+    /// Parse a (simplified) CSS `font` shorthand
+    ///
+    /// FIXME: Use the CSS parser for this, currently only font sizes in pixels are understood
+    ///        and style, variant and weight are ignored
+    fn parse(specified: &str) -> Option<Self> {
+        let mut parts = specified.split_whitespace();
+
+        let size = parts
+            .by_ref()
+            .find_map(|part| part.strip_suffix("px")?.parse::<f32>().ok())?;
+
+        let family: Vec<&str> = parts.collect();
+        if family.is_empty() {
+            return None;
+        }
+
+        let family = family.join(" ");
+        let family = family
+            .split(',')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_string();
+
+        let font = Self {
+            specified: specified.to_string(),
+            family,
+            size,
+        };
+
+        Some(font)
+    }
+
+    fn load(&self) -> font::Font {
+        let family = match self.family.as_str() {
+            "serif" | "sans-serif" | "cursive" | "fantasy" | "monospace" => {
+                font::Family::Generic(self.family.clone())
+            },
+            _ => font::Family::Specific(self.family.clone()),
+        };
+
+        let properties = font::Properties {
+            style: font::Style::Normal,
+            weight: font::Weight::NORMAL,
+            language: font::Language::English,
+        };
+
+        font::SYSTEM_FONTS
+            .load(family, properties)
+            .unwrap_or_else(|error| {
+                log::warn!("Failed to load canvas font {:?}: {error:?}", self.family);
+                font::Font::fallback()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(context: &CanvasRenderingContext2D, x: usize, y: usize) -> Rgbaf32 {
+        context.bitmap().get_pixel(x, y)
+    }
+
+    #[test]
+    fn fill_rect_with_transform() {
+        let mut context = CanvasRenderingContext2D::new(20, 20);
+        context.set_fill_style("red");
+        context.translate(10., 0.);
+        context.scale(2., 1.);
+        context.fill_rect(0., 0., 2., 5.);
+
+        assert_eq!(pixel(&context, 9, 2), Rgbaf32::BLANK);
+        assert_eq!(pixel(&context, 10, 2), Rgbaf32::rgb(1., 0., 0.));
+        assert_eq!(pixel(&context, 13, 4), Rgbaf32::rgb(1., 0., 0.));
+        assert_eq!(pixel(&context, 14, 2), Rgbaf32::BLANK);
+        assert_eq!(pixel(&context, 10, 5), Rgbaf32::BLANK);
+    }
+
+    #[test]
+    fn stroke_path() {
+        let mut context = CanvasRenderingContext2D::new(20, 20);
+        context.set_line_width(2.);
+        context.move_to(2., 10.);
+        context.line_to(18., 10.);
+        context.stroke();
+
+        // The stroke covers one pixel above and below the line
+        assert_eq!(pixel(&context, 10, 9).alpha(), 1.);
+        assert_eq!(pixel(&context, 10, 10).alpha(), 1.);
+        assert_eq!(pixel(&context, 10, 8).alpha(), 0.);
+        assert_eq!(pixel(&context, 1, 10).alpha(), 0.);
+    }
+
+    #[test]
+    fn drawing_does_not_change_shared_bitmaps() {
+        let mut context = CanvasRenderingContext2D::new(4, 4);
+        let shared = context.bitmap().clone();
+        let generation = context.generation();
+
+        context.fill_rect(0., 0., 4., 4.);
+
+        assert_ne!(context.generation(), generation);
+        assert_eq!(shared.get_pixel(0, 0), Rgbaf32::BLANK);
+        assert_eq!(pixel(&context, 0, 0), Rgbaf32::rgb(0., 0., 0.));
+    }
+
+    #[test]
+    fn save_and_restore() {
+        let mut context = CanvasRenderingContext2D::new(1, 1);
+        context.set_fill_style("#00ff00");
+        context.save();
+        context.set_fill_style("#0000ff80");
+        context.set_fill_style("not a color");
+        assert_eq!(context.fill_style(), "rgba(0, 0, 255, 0.5019608)");
+
+        context.restore();
+        assert_eq!(context.fill_style(), "#00ff00");
+    }
+
+    #[test]
+    fn image_data_round_trip() {
+        let mut context = CanvasRenderingContext2D::new(4, 4);
+        context.set_fill_style("blue");
+        context.fill_rect(0., 0., 2., 2.);
+
+        let image_data = context.get_image_data(1, 1, 2, 2).unwrap();
+        assert_eq!(&image_data.data()[..4], &[0, 0, 255, 255]);
+        assert_eq!(&image_data.data()[4..8], &[0, 0, 0, 0]);
+
+        context.clear_rect(0., 0., 4., 4.);
+        context.put_image_data(&image_data, 3, 3);
+        assert_eq!(pixel(&context, 3, 3), Rgbaf32::rgb(0., 0., 1.));
+        assert_eq!(pixel(&context, 0, 0), Rgbaf32::BLANK);
+
+        assert!(context.get_image_data(0, 0, 0, 1).is_err());
+    }
+
+    #[test]
+    fn draw_image() {
+        let mut image = Texture::new(2, 1);
+        image.set_pixel(0, 0, Rgbaf32::rgb(1., 0., 0.));
+        image.set_pixel(1, 0, Rgbaf32::rgb(0., 1., 0.));
+
+        let mut context = CanvasRenderingContext2D::new(4, 4);
+        context.draw_image_scaled(&image, 0., 0., 4., 2.);
+
+        assert_eq!(pixel(&context, 1, 1), Rgbaf32::rgb(1., 0., 0.));
+        assert_eq!(pixel(&context, 2, 0), Rgbaf32::rgb(0., 1., 0.));
+        assert_eq!(pixel(&context, 2, 2), Rgbaf32::BLANK);
+    }
+
+    #[test]
+    fn parse_font() {
+        let font = CanvasFont::parse("bold 16px \"Open Sans\", serif").unwrap();
+        assert_eq!(font.size, 16.);
+        assert_eq!(font.family, "Open Sans");
+
+        assert!(CanvasFont::parse("16px").is_none());
+        assert!(CanvasFont::parse("serif").is_none());
+    }
+}
@@ -0,0 +1,96 @@
+//! Immediate mode 2D drawing for `<canvas>` elements
+//!
+//! The rendering context draws into a bitmap owned by the canvas, which is then
+//! painted like any other replaced content.
+//!
+//! <https://html.spec.whatwg.org/multipage/canvas.html>
+
+mod context_2d;
+mod path;
+
+use error_derive::Error;
+use image::Rgbaf32;
+
+pub use context_2d::{CanvasRenderingContext2D, TextAlign, TextBaseline, TextMetrics};
+pub use render::{LineCap, LineJoin};
+
+#[derive(Clone, Copy, Debug, Error)]
+pub enum CanvasError {
+    /// Corresponds to an `IndexSizeError` `DOMException`
+    #[msg = "index or size is negative or zero"]
+    IndexSize,
+}
+
+/// Raw pixel data of (a section of) a canvas
+///
+/// Pixels are stored row by row as non-premultiplied RGBA bytes.
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/imagebitmap-and-animations.html#imagedata)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageData {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+}
+
+impl ImageData {
+    /// Create transparent black image data
+    ///
+    /// Fails if either `width` or `height` are zero.
+    pub fn new(width: usize, height: usize) -> Result<Self, CanvasError> {
+        if width == 0 || height == 0 {
+            return Err(CanvasError::IndexSize);
+        }
+
+        Ok(Self {
+            width,
+            height,
+            data: vec![0; width * height * 4],
+        })
+    }
+
+    #[must_use]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    #[must_use]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[must_use]
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> Rgbaf32 {
+        let index = (y * self.width + x) * 4;
+        let channels = &self.data[index..index + 4];
+        let to_float = |byte: u8| f32::from(byte) / 255.;
+
+        Rgbaf32::rgba(
+            to_float(channels[0]),
+            to_float(channels[1]),
+            to_float(channels[2]),
+            to_float(channels[3]),
+        )
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, pixel: Rgbaf32) {
+        let index = (y * self.width + x) * 4;
+        let to_byte = |channel: f32| (channel.clamp(0., 1.) * 255.).round() as u8;
+
+        self.data[index..index + 4].copy_from_slice(&[
+            to_byte(pixel.red()),
+            to_byte(pixel.green()),
+            to_byte(pixel.blue()),
+            to_byte(pixel.alpha()),
+        ]);
+    }
+}
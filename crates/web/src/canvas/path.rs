@@ -0,0 +1,282 @@
+//! <https://html.spec.whatwg.org/multipage/canvas.html#building-paths>
+
+use std::f32::consts::{PI, TAU};
+
+use math::{AffineTransform, Vec2D};
+use render::FlattenedPathPoint;
+
+/// The maximum distance (in device pixels) between a curve and the line segments
+/// that approximate it
+const FLATTEN_TOLERANCE: f32 = 0.1;
+
+/// A sequence of connected points
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Subpath {
+    points: Vec<Vec2D>,
+    is_closed: bool,
+}
+
+/// The current default path of a rendering context
+///
+/// Points are transformed with the current transformation matrix as they are added,
+/// which means that the path is stored in device coordinates. Curves are flattened
+/// into line segments right away.
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#concept-path)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(super) struct CanvasPath {
+    subpaths: Vec<Subpath>,
+}
+
+impl CanvasPath {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.subpaths.is_empty()
+    }
+
+    /// The last point of the last subpath, in device coordinates
+    fn last_point(&self) -> Option<Vec2D> {
+        self.subpaths.last()?.points.last().copied()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#ensure-there-is-a-subpath>
+    fn ensure_subpath(&mut self, point: Vec2D) {
+        if self.subpaths.is_empty() {
+            self.move_to(point);
+        }
+    }
+
+    fn current_subpath(&mut self) -> &mut Subpath {
+        self.subpaths
+            .last_mut()
+            .expect("callers ensure that there is a subpath")
+    }
+
+    /// Start a new subpath at the given (device) point
+    pub fn move_to(&mut self, point: Vec2D) {
+        self.subpaths.push(Subpath {
+            points: vec![point],
+            is_closed: false,
+        });
+    }
+
+    pub fn line_to(&mut self, point: Vec2D) {
+        self.ensure_subpath(point);
+        self.current_subpath().points.push(point);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-closepath>
+    pub fn close_path(&mut self) {
+        let Some(subpath) = self.subpaths.last_mut() else {
+            return;
+        };
+
+        // Mark the subpath as closed and start a new one at the same point
+        subpath.is_closed = true;
+        let start = subpath.points[0];
+        self.move_to(start);
+    }
+
+    pub fn quadratic_curve_to(&mut self, control_point: Vec2D, end: Vec2D) {
+        self.ensure_subpath(control_point);
+        let start = self.last_point().expect("there is a subpath");
+
+        self.flatten_curve(start, end, |t| {
+            let u = 1. - t;
+            start * (u * u) + control_point * (2. * u * t) + end * (t * t)
+        });
+    }
+
+    pub fn bezier_curve_to(&mut self, control_point_1: Vec2D, control_point_2: Vec2D, end: Vec2D) {
+        self.ensure_subpath(control_point_1);
+        let start = self.last_point().expect("there is a subpath");
+
+        self.flatten_curve(start, end, |t| {
+            let u = 1. - t;
+            start * (u * u * u)
+                + control_point_1 * (3. * u * u * t)
+                + control_point_2 * (3. * u * t * t)
+                + end * (t * t * t)
+        });
+    }
+
+    /// Add an elliptical arc around `center`
+    ///
+    /// The arc is built in user space and every point is transformed with `transform`.
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-ellipse)
+    #[allow(clippy::too_many_arguments)]
+    pub fn ellipse(
+        &mut self,
+        transform: AffineTransform,
+        center: Vec2D,
+        radius_x: f32,
+        radius_y: f32,
+        rotation: f32,
+        start_angle: f32,
+        end_angle: f32,
+        counterclockwise: bool,
+    ) {
+        let sweep = arc_sweep(start_angle, end_angle, counterclockwise);
+
+        let point_at = |angle: f32| {
+            let unrotated = Vec2D::new(radius_x * angle.cos(), radius_y * angle.sin());
+            let rotated = Vec2D::new(
+                unrotated.x * rotation.cos() - unrotated.y * rotation.sin(),
+                unrotated.x * rotation.sin() + unrotated.y * rotation.cos(),
+            );
+            transform.apply_to(center + rotated)
+        };
+
+        // The arc is connected to the previous point with a straight line
+        let start = point_at(start_angle);
+        if self.is_empty() {
+            self.move_to(start);
+        } else {
+            self.line_to(start);
+        }
+
+        // Choose the number of segments such that the maximum deviation from the
+        // circle (r * (1 - cos(θ / 2))) stays below the tolerance
+        let scale = transform.determinant().abs().sqrt().max(f32::EPSILON);
+        let radius = radius_x.max(radius_y) * scale;
+        let max_step = if radius <= FLATTEN_TOLERANCE {
+            PI / 2.
+        } else {
+            2. * (1. - FLATTEN_TOLERANCE / radius).acos()
+        };
+        let segments = ((sweep.abs() / max_step).ceil() as usize).clamp(1, 1024);
+
+        for i in 1..=segments {
+            let angle = start_angle + sweep * (i as f32 / segments as f32);
+            self.current_subpath().points.push(point_at(angle));
+        }
+    }
+
+    /// Add a closed rectangle with the given (device) corners
+    pub fn rect(&mut self, corners: [Vec2D; 4]) {
+        self.subpaths.push(Subpath {
+            points: corners.to_vec(),
+            is_closed: true,
+        });
+
+        // Subsequent commands continue at the origin of the rectangle
+        self.move_to(corners[0]);
+    }
+
+    fn flatten_curve(&mut self, start: Vec2D, end: Vec2D, evaluate: impl Fn(f32) -> Vec2D) {
+        // The length of the control polygon is an upper bound for the length of the curve
+        let estimated_length =
+            (evaluate(0.5) - start).magnitude() + (end - evaluate(0.5)).magnitude();
+        let segments =
+            ((estimated_length / FLATTEN_TOLERANCE).sqrt().ceil() as usize).clamp(1, 256);
+
+        let subpath = self.current_subpath();
+        for i in 1..=segments {
+            subpath.points.push(evaluate(i as f32 / segments as f32));
+        }
+    }
+
+    /// Append the outline that should be filled, with every subpath implicitly closed
+    pub fn fill_outline(&self, outline: &mut Vec<FlattenedPathPoint>) {
+        for subpath in &self.subpaths {
+            let Some((&first, rest)) = subpath.points.split_first() else {
+                continue;
+            };
+
+            outline.push(FlattenedPathPoint::new(first, false));
+            outline.extend(
+                rest.iter()
+                    .map(|&point| FlattenedPathPoint::new(point, true)),
+            );
+            outline.push(FlattenedPathPoint::new(first, true));
+        }
+    }
+
+    /// Append the center lines of all subpaths, mapped through `transform`
+    ///
+    /// Closed subpaths end at their first point, which is how [render::Stroke]
+    /// recognizes them.
+    pub fn stroke_center_lines(
+        &self,
+        transform: AffineTransform,
+        lines: &mut Vec<FlattenedPathPoint>,
+    ) {
+        for subpath in &self.subpaths {
+            // Subpaths with a single point are not drawn
+            if subpath.points.len() < 2 {
+                continue;
+            }
+
+            for (index, &point) in subpath.points.iter().enumerate() {
+                lines.push(FlattenedPathPoint::new(
+                    transform.apply_to(point),
+                    index != 0,
+                ));
+            }
+
+            if subpath.is_closed {
+                lines.push(FlattenedPathPoint::new(
+                    transform.apply_to(subpath.points[0]),
+                    true,
+                ));
+            }
+        }
+    }
+}
+
+/// Compute the signed angle that an arc sweeps over
+///
+/// <https://html.spec.whatwg.org/multipage/canvas.html#dom-context-2d-arc>
+fn arc_sweep(start_angle: f32, end_angle: f32, counterclockwise: bool) -> f32 {
+    let difference = end_angle - start_angle;
+
+    // If the difference is at least a full turn then the arc is a whole ellipse
+    if !counterclockwise && difference >= TAU {
+        return TAU;
+    }
+    if counterclockwise && -difference >= TAU {
+        return -TAU;
+    }
+
+    let sweep = difference.rem_euclid(TAU);
+    if counterclockwise {
+        if sweep == 0. {
+            0.
+        } else {
+            sweep - TAU
+        }
+    } else {
+        sweep
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arc_directions() {
+        assert_eq!(arc_sweep(0., PI, false), PI);
+        assert_eq!(arc_sweep(0., PI / 2., true), -1.5 * PI);
+        assert_eq!(arc_sweep(0., 3. * TAU, false), TAU);
+        assert_eq!(arc_sweep(0., 0., true), 0.);
+    }
+
+    #[test]
+    fn close_path_starts_new_subpath() {
+        let mut path = CanvasPath::default();
+        path.move_to(Vec2D::new(0., 0.));
+        path.line_to(Vec2D::new(10., 0.));
+        path.line_to(Vec2D::new(10., 10.));
+        path.close_path();
+        path.line_to(Vec2D::new(0., 10.));
+
+        let mut lines = vec![];
+        path.stroke_center_lines(AffineTransform::identity(), &mut lines);
+
+        let connected: Vec<bool> = lines.iter().map(|point| point.connected).collect();
+        assert_eq!(connected, [false, true, true, true, false, true]);
+        assert_eq!(lines[3].coordinates, Vec2D::new(0., 0.));
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Instant;
+
+/// The timeline that all animations of a document are synchronized to
+///
+/// The clock only advances when it is explicitly updated, which happens once per
+/// rendering update. That way, all animations observe the same time during a frame.
+///
+/// <https://drafts.csswg.org/web-animations/#the-documents-default-timeline>
+#[derive(Clone, Copy, Debug)]
+pub struct AnimationClock {
+    /// The moment the clock was started
+    origin: Instant,
+
+    /// The time (in seconds since `origin`) at the most recent update
+    current_time: f32,
+}
+
+impl AnimationClock {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            current_time: 0.,
+        }
+    }
+
+    /// Advance the clock to the given point in time
+    ///
+    /// The clock never runs backwards.
+    pub fn update(&mut self, now: Instant) {
+        self.current_time = self
+            .current_time
+            .max(now.saturating_duration_since(self.origin).as_secs_f32());
+    }
+
+    /// The time (in seconds) since the clock was started, as of the most recent update
+    #[must_use]
+    pub const fn current_time(&self) -> f32 {
+        self.current_time
+    }
+}
+
+impl Default for AnimationClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
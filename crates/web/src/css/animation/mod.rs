@@ -0,0 +1,42 @@
+//! Animation of CSS values over time
+//!
+//! <https://drafts.csswg.org/web-animations/>
+
+mod clock;
+mod transform;
+mod transition;
+
+pub use clock::AnimationClock;
+pub use transition::Transitions;
+
+/// A value that can be interpolated between two end points
+///
+/// <https://drafts.csswg.org/css-values-4/#interpolation>
+pub trait Animate: Sized {
+    /// Compute the value at `progress` between `self` and `to`
+    ///
+    /// A progress of `0` corresponds to `self` and a progress of `1` to `to`,
+    /// values outside of that range extrapolate.
+    /// Returns `None` if the two values cannot be interpolated, for example
+    /// because one of them is `auto`.
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self>;
+}
+
+impl Animate for f32 {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some((to - self).mul_add(progress, *self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animate_number() {
+        assert_eq!(1_f32.animate(&3., 0.), Some(1.));
+        assert_eq!(1_f32.animate(&3., 0.5), Some(2.));
+        assert_eq!(1_f32.animate(&3., 1.), Some(3.));
+        assert_eq!(1_f32.animate(&3., 1.5), Some(4.));
+    }
+}
@@ -0,0 +1,214 @@
+//! <https://drafts.csswg.org/css-transforms/#interpolation-of-2d-matrices>
+
+use std::f32::consts::PI;
+
+use math::{AffineTransform, Angle};
+
+use super::Animate;
+
+/// A 2D transformation, split into components that can be interpolated individually
+///
+/// <https://drafts.csswg.org/css-transforms/#decomposing-a-2d-matrix>
+#[derive(Clone, Copy, Debug)]
+struct DecomposedTransform {
+    translate: [f32; 2],
+    scale: [f32; 2],
+
+    /// The rotation, in radians
+    angle: f32,
+
+    /// The remaining 2x2 matrix after translation, scale and rotation were removed
+    matrix: [[f32; 2]; 2],
+}
+
+impl DecomposedTransform {
+    fn decompose(transform: &AffineTransform) -> Option<Self> {
+        // NOTE: The specification uses the row-vector convention, so the rows
+        //       of its matrix are the columns of ours
+        let [[a, c, e], [b, d, f]] = transform.matrix();
+        let mut row0 = [a, b];
+        let mut row1 = [c, d];
+
+        let translate = [e, f];
+        let mut scale = [a.hypot(b), c.hypot(d)];
+
+        // If the determinant is negative, one axis was flipped
+        let determinant = transform.determinant();
+        if determinant < 0. {
+            if row0[0] < row1[1] {
+                scale[0] = -scale[0];
+            } else {
+                scale[1] = -scale[1];
+            }
+        }
+
+        // Renormalize the matrix to remove the scale
+        if scale[0] != 0. {
+            row0 = [row0[0] / scale[0], row0[1] / scale[0]];
+        }
+        if scale[1] != 0. {
+            row1 = [row1[0] / scale[1], row1[1] / scale[1]];
+        }
+
+        // Compute the rotation and renormalize the matrix
+        let angle = row0[1].atan2(row0[0]);
+        if angle != 0. {
+            let sin = -row0[1];
+            let cos = row0[0];
+            let [m11, m12] = row0;
+            let [m21, m22] = row1;
+
+            row0 = [cos.mul_add(m11, sin * m21), cos.mul_add(m12, sin * m22)];
+            row1 = [
+                (-sin).mul_add(m11, cos * m21),
+                (-sin).mul_add(m12, cos * m22),
+            ];
+        }
+
+        if !translate.iter().chain(&scale).all(|x| x.is_finite()) {
+            return None;
+        }
+
+        Some(Self {
+            translate,
+            scale,
+            angle,
+            matrix: [row0, row1],
+        })
+    }
+
+    /// <https://drafts.csswg.org/css-transforms/#recomposing-to-a-2d-matrix>
+    fn recompose(&self) -> AffineTransform {
+        let [row0, row1] = self.matrix;
+
+        // Rotate the matrix
+        let angle = Angle::from_radians(self.angle);
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let mut row0_rotated = [
+            cos.mul_add(row0[0], sin * row1[0]),
+            cos.mul_add(row0[1], sin * row1[1]),
+        ];
+        let mut row1_rotated = [
+            (-sin).mul_add(row0[0], cos * row1[0]),
+            (-sin).mul_add(row0[1], cos * row1[1]),
+        ];
+
+        // Scale the matrix
+        row0_rotated = row0_rotated.map(|x| x * self.scale[0]);
+        row1_rotated = row1_rotated.map(|x| x * self.scale[1]);
+
+        AffineTransform::from_matrix([
+            [row0_rotated[0], row1_rotated[0], self.translate[0]],
+            [row0_rotated[1], row1_rotated[1], self.translate[1]],
+        ])
+    }
+}
+
+impl Animate for [f32; 2] {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        Some([
+            self[0].animate(&to[0], progress)?,
+            self[1].animate(&to[1], progress)?,
+        ])
+    }
+}
+
+impl Animate for AffineTransform {
+    /// <https://drafts.csswg.org/css-transforms/#interpolation-of-decomposed-2d-matrix-values>
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let mut from = DecomposedTransform::decompose(self)?;
+        let mut to = DecomposedTransform::decompose(to)?;
+
+        // If x-axis of one is flipped, and y-axis of the other, convert to an unflipped
+        // rotation
+        if (from.scale[0] < 0. && to.scale[1] < 0.) || (from.scale[1] < 0. && to.scale[0] < 0.) {
+            from.scale = from.scale.map(|x| -x);
+            from.angle += if from.angle < 0. { PI } else { -PI };
+        }
+
+        // Don't rotate the long way around
+        if from.angle == 0. {
+            from.angle = 2. * PI;
+        }
+        if to.angle == 0. {
+            to.angle = 2. * PI;
+        }
+
+        if (from.angle - to.angle).abs() > PI {
+            if from.angle > to.angle {
+                from.angle -= 2. * PI;
+            } else {
+                to.angle -= 2. * PI;
+            }
+        }
+
+        let interpolated = DecomposedTransform {
+            translate: from.translate.animate(&to.translate, progress)?,
+            scale: from.scale.animate(&to.scale, progress)?,
+            angle: from.angle.animate(&to.angle, progress)?,
+            matrix: [
+                from.matrix[0].animate(&to.matrix[0], progress)?,
+                from.matrix[1].animate(&to.matrix[1], progress)?,
+            ],
+        };
+
+        Some(interpolated.recompose())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math::Vec2D;
+
+    use super::*;
+
+    fn assert_close(a: AffineTransform, b: AffineTransform) {
+        let close = a
+            .matrix()
+            .iter()
+            .flatten()
+            .zip(b.matrix().iter().flatten())
+            .all(|(a, b)| (a - b).abs() < 1e-4);
+
+        assert!(close, "{a:?} is not close to {b:?}");
+    }
+
+    #[test]
+    fn decompose_and_recompose() {
+        let transforms = [
+            AffineTransform::identity(),
+            AffineTransform::rotate(Angle::from_degrees(30.)),
+            AffineTransform::scale(2., -3.),
+            AffineTransform::skew(Angle::from_degrees(10.), Angle::from_degrees(0.)),
+            AffineTransform::scale(0.5, 2.)
+                .chain(AffineTransform::rotate(Angle::from_degrees(-120.)))
+                .chain(AffineTransform::translate(Vec2D::new(10., -4.))),
+        ];
+
+        for transform in transforms {
+            let decomposed = DecomposedTransform::decompose(&transform).unwrap();
+            assert_close(decomposed.recompose(), transform);
+        }
+    }
+
+    #[test]
+    fn animate_transform() {
+        let from = AffineTransform::translate(Vec2D::new(0., 10.));
+        let to = AffineTransform::translate(Vec2D::new(20., 30.));
+        assert_close(
+            from.animate(&to, 0.25).unwrap(),
+            AffineTransform::translate(Vec2D::new(5., 15.)),
+        );
+
+        // Rotations are interpolated by their angle, not component-wise
+        let from = AffineTransform::identity();
+        let to = AffineTransform::rotate(Angle::from_degrees(90.));
+        assert_close(
+            from.animate(&to, 0.5).unwrap(),
+            AffineTransform::rotate(Angle::from_degrees(45.)),
+        );
+
+        assert_close(from.animate(&to, 0.).unwrap(), from);
+        assert_close(from.animate(&to, 1.).unwrap(), to);
+    }
+}
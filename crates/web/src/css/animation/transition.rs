@@ -0,0 +1,738 @@
+//! <https://drafts.csswg.org/css-transitions/>
+
+use std::{collections::HashMap, fmt, mem};
+
+use crate::{
+    css::{
+        computed_style::{ComputedStyle, ANIMATABLE_PROPERTIES},
+        style::specified::SingleTransitionProperty,
+        values::EasingFunction,
+        PropertyId,
+    },
+    dom::{dom_objects::Element, DomPtr, WeakDomPtr},
+    InternedString,
+};
+
+/// Keeps track of all the transitions that are running within a document
+///
+/// Transitions are started from within style computation, whenever the computed value
+/// of a property on an element changes.
+#[derive(Default)]
+pub struct Transitions {
+    /// The transition state of all elements that were styled so far, keyed by their address
+    elements: HashMap<usize, ElementTransitions>,
+
+    /// Events that were generated since they were last taken
+    pending_events: Vec<TransitionEvent>,
+
+    /// The time (in seconds) as of the most recent update
+    current_time: f32,
+
+    /// Counts style passes, used to find elements that are no longer being styled
+    style_pass: usize,
+}
+
+struct ElementTransitions {
+    element: WeakDomPtr<Element>,
+
+    /// The after-change style of the most recent style change event, without any
+    /// transitions applied
+    previous_style: ComputedStyle,
+
+    running_transitions: Vec<RunningTransition>,
+
+    /// The most recent style pass that this element was styled in
+    last_style_pass: usize,
+}
+
+/// <https://drafts.csswg.org/css-transitions/#running-transition>
+#[derive(Clone, Debug)]
+struct RunningTransition {
+    property: PropertyId,
+
+    /// The time (in seconds) at which the transition was started
+    start_time: f32,
+
+    /// The delay (in seconds), which may be negative
+    delay: f32,
+
+    /// The duration (in seconds)
+    duration: f32,
+
+    timing_function: EasingFunction,
+
+    /// Contains the start value of the transitioned property
+    start_style: ComputedStyle,
+
+    /// Contains the end value of the transitioned property
+    end_style: ComputedStyle,
+
+    /// <https://drafts.csswg.org/css-transitions/#transition-reversing-adjusted-start-value>
+    reversing_adjusted_start_style: ComputedStyle,
+
+    /// <https://drafts.csswg.org/css-transitions/#transition-reversing-shortening-factor>
+    reversing_shortening_factor: f32,
+
+    phase: TransitionPhase,
+}
+
+/// <https://drafts.csswg.org/web-animations/#animation-effect-phases-and-states>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TransitionPhase {
+    /// The transition was not yet observed by an update
+    Idle,
+    Before,
+    Active,
+    After,
+}
+
+/// <https://drafts.csswg.org/css-transitions/#transition-events>
+#[derive(Clone)]
+pub struct TransitionEvent {
+    pub kind: TransitionEventKind,
+    pub target: DomPtr<Element>,
+    pub property_name: InternedString,
+
+    /// <https://drafts.csswg.org/css-transitions/#dom-transitionevent-elapsedtime>
+    pub elapsed_time: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransitionEventKind {
+    Run,
+    Start,
+    End,
+    Cancel,
+}
+
+/// The values of the `transition-*` properties that apply to a single property
+#[derive(Clone, Copy, Debug)]
+struct MatchingTransitionValue {
+    delay: f32,
+    duration: f32,
+    timing_function: EasingFunction,
+}
+
+impl TransitionEventKind {
+    /// The name of the event type
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Run => "transitionrun",
+            Self::Start => "transitionstart",
+            Self::End => "transitionend",
+            Self::Cancel => "transitioncancel",
+        }
+    }
+}
+
+impl fmt::Debug for TransitionEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransitionEvent")
+            .field("kind", &self.kind)
+            .field("target", &self.target.borrow().local_name())
+            .field("property_name", &self.property_name)
+            .field("elapsed_time", &self.elapsed_time)
+            .finish()
+    }
+}
+
+impl fmt::Debug for Transitions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let running_transitions: usize = self
+            .elements
+            .values()
+            .map(|element| element.running_transitions.len())
+            .sum();
+
+        f.debug_struct("Transitions")
+            .field("current_time", &self.current_time)
+            .field("running_transitions", &running_transitions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl MatchingTransitionValue {
+    /// <https://drafts.csswg.org/css-transitions/#combined-duration>
+    fn combined_duration(&self) -> f32 {
+        self.duration.max(0.) + self.delay
+    }
+
+    /// <https://drafts.csswg.org/css-transitions/#matching-transition-property-value>
+    fn find(style: &ComputedStyle, property: PropertyId) -> Option<Self> {
+        let properties = style.transition_property().properties();
+
+        // If there are multiple matching values, the last one wins
+        let index =
+            properties
+                .iter()
+                .rposition(|transition_property| match transition_property {
+                    SingleTransitionProperty::All => true,
+                    SingleTransitionProperty::Property(name) => {
+                        ComputedStyle::animatable_longhands(name.clone()).contains(&property)
+                    },
+                })?;
+
+        // The lists of durations, delays and timing functions are repeated as
+        // often as necessary
+        fn value_at<T: Copy>(values: &[T], index: usize) -> T {
+            values[index % values.len()]
+        }
+
+        let duration = value_at(style.transition_duration().durations(), index);
+        let delay = value_at(style.transition_delay().delays(), index);
+        let timing_function =
+            value_at(style.transition_timing_function().timing_functions(), index);
+
+        Some(Self {
+            delay: delay.as_seconds(),
+            duration: duration.as_seconds(),
+            timing_function,
+        })
+    }
+}
+
+impl RunningTransition {
+    fn new(
+        property: PropertyId,
+        start_time: f32,
+        value: MatchingTransitionValue,
+        start_style: ComputedStyle,
+        end_style: ComputedStyle,
+    ) -> Self {
+        Self {
+            property,
+            start_time,
+            delay: value.delay,
+            duration: value.duration,
+            timing_function: value.timing_function,
+            reversing_adjusted_start_style: start_style.clone(),
+            start_style,
+            end_style,
+            reversing_shortening_factor: 1.,
+            phase: TransitionPhase::Idle,
+        }
+    }
+
+    fn phase_at(&self, time: f32) -> TransitionPhase {
+        let local_time = time - self.start_time;
+
+        if local_time < self.delay {
+            TransitionPhase::Before
+        } else if local_time < self.delay + self.duration {
+            TransitionPhase::Active
+        } else {
+            TransitionPhase::After
+        }
+    }
+
+    /// The time (in seconds) that the transition has spent in its active phase
+    fn active_time(&self, time: f32) -> f32 {
+        (time - self.start_time - self.delay).clamp(0., self.duration)
+    }
+
+    /// The output of the timing function at the given time
+    fn progress_at(&self, time: f32) -> f32 {
+        let is_before = self.phase_at(time) == TransitionPhase::Before;
+        let input_progress = if is_before {
+            0.
+        } else if self.duration == 0. {
+            1.
+        } else {
+            self.active_time(time) / self.duration
+        };
+
+        self.timing_function
+            .evaluate_with_before_flag(input_progress, is_before)
+    }
+
+    /// Set the transitioned property in `style` to its value at the given time
+    ///
+    /// Returns `false` if the value could not be computed.
+    fn apply(&self, style: &mut ComputedStyle, time: f32) -> bool {
+        // Transitions fill backwards, so the start value is used during the delay
+        style.interpolate_property(
+            self.property,
+            &self.start_style,
+            &self.end_style,
+            self.progress_at(time),
+        )
+    }
+
+    fn end_value_equals(&self, style: &ComputedStyle) -> bool {
+        self.end_style.property_equals(style, self.property)
+    }
+
+    /// The elapsed time of the events that are fired when the transition starts
+    fn interval_start(&self) -> f32 {
+        (-self.delay).clamp(0., self.duration)
+    }
+}
+
+impl Transitions {
+    /// Advance all running transitions to the given time (in seconds)
+    ///
+    /// Returns `true` if any transition was running, in which case the styles
+    /// of the affected elements need to be recomputed.
+    pub fn update(&mut self, current_time: f32) -> bool {
+        self.current_time = current_time;
+
+        let mut had_running_transitions = false;
+        self.elements.retain(|_, element_transitions| {
+            let Some(element) = element_transitions.element.upgrade() else {
+                // Elements that were removed from the DOM can't be styled anymore
+                return false;
+            };
+
+            if !element_transitions.running_transitions.is_empty() {
+                // The style of the element changes with every frame
+                Element::mark_needs_layout(element.clone().upcast());
+                had_running_transitions = true;
+            }
+
+            element_transitions
+                .running_transitions
+                .retain_mut(|transition| {
+                    let new_phase = transition.phase_at(current_time);
+                    queue_phase_change_events(
+                        &mut self.pending_events,
+                        &element,
+                        transition,
+                        new_phase,
+                    );
+                    transition.phase = new_phase;
+
+                    // Finished transitions have no effect anymore
+                    new_phase != TransitionPhase::After
+                });
+
+            true
+        });
+
+        had_running_transitions
+    }
+
+    /// Whether there are any transitions that have not finished yet
+    #[must_use]
+    pub fn has_running_transitions(&self) -> bool {
+        self.elements
+            .values()
+            .any(|element| !element.running_transitions.is_empty())
+    }
+
+    /// Take all the events that were generated since the last call
+    pub fn take_events(&mut self) -> Vec<TransitionEvent> {
+        mem::take(&mut self.pending_events)
+    }
+
+    /// Must be called before the styles of the document are recomputed
+    pub fn begin_style_pass(&mut self) {
+        self.style_pass += 1;
+    }
+
+    /// Must be called after the styles of the document were recomputed
+    ///
+    /// Transitions on elements that were not styled during the pass (because they were
+    /// removed from the document or are inside a `display: none` subtree) are cancelled.
+    pub fn end_style_pass(&mut self) {
+        let style_pass = self.style_pass;
+        let current_time = self.current_time;
+
+        self.elements.retain(|_, element_transitions| {
+            if element_transitions.last_style_pass == style_pass {
+                return true;
+            }
+
+            if let Some(element) = element_transitions.element.upgrade() {
+                for transition in &element_transitions.running_transitions {
+                    queue_cancel_event(
+                        &mut self.pending_events,
+                        &element,
+                        transition,
+                        current_time,
+                    );
+                }
+            }
+
+            false
+        });
+    }
+
+    /// Start, update or cancel the transitions of an element after its style was recomputed
+    ///
+    /// `after_change_style` is the newly computed style of the element, without any transitions
+    /// applied. The returned style has the running transitions applied and should be used
+    /// for layout.
+    ///
+    /// <https://drafts.csswg.org/css-transitions/#starting>
+    pub fn process_style_change(
+        &mut self,
+        element: &DomPtr<Element>,
+        after_change_style: ComputedStyle,
+    ) -> ComputedStyle {
+        let key = element.as_ptr() as usize;
+        let current_time = self.current_time;
+
+        // Elements that are not rendered have no transitions
+        if after_change_style.display().is_none() {
+            if let Some(element_transitions) = self.elements.remove(&key) {
+                for transition in &element_transitions.running_transitions {
+                    queue_cancel_event(&mut self.pending_events, element, transition, current_time);
+                }
+            }
+
+            return after_change_style;
+        }
+
+        let Some(element_transitions) = self.elements.get_mut(&key) else {
+            // Elements that are styled for the first time don't have a before-change style
+            // and are never transitioned
+            self.elements.insert(
+                key,
+                ElementTransitions {
+                    element: element.downgrade(),
+                    previous_style: after_change_style.clone(),
+                    running_transitions: vec![],
+                    last_style_pass: self.style_pass,
+                },
+            );
+            return after_change_style;
+        };
+        element_transitions.last_style_pass = self.style_pass;
+
+        // The before-change style is the previous style, updated to the current time
+        let mut before_change_style = element_transitions.previous_style.clone();
+        for transition in &element_transitions.running_transitions {
+            transition.apply(&mut before_change_style, current_time);
+        }
+
+        for &property in ANIMATABLE_PROPERTIES {
+            let running_index = element_transitions
+                .running_transitions
+                .iter()
+                .position(|transition| transition.property == property);
+            let matching_value = MatchingTransitionValue::find(&after_change_style, property);
+
+            let Some(running_index) = running_index else {
+                // 1. If all of the following are true:
+                //    * the element does not have a running transition for the property,
+                //    * the before-change style is different from the after-change style for that property,
+                //      and the values for the property are transitionable,
+                //    * there is a matching transition-property value, and
+                //    * the combined duration is greater than 0s,
+                //    then implementations must start a transition.
+                let Some(matching_value) = matching_value else {
+                    continue;
+                };
+
+                if before_change_style.property_equals(&after_change_style, property)
+                    || matching_value.combined_duration() <= 0.
+                    || !is_transitionable(&before_change_style, &after_change_style, property)
+                {
+                    continue;
+                }
+
+                element_transitions
+                    .running_transitions
+                    .push(RunningTransition::new(
+                        property,
+                        current_time,
+                        matching_value,
+                        before_change_style.clone(),
+                        after_change_style.clone(),
+                    ));
+                continue;
+            };
+
+            // 2. NOTE: Completed transitions are discarded as soon as they finish
+
+            // 3. If the element has a running transition for the property, there is a matching
+            //    transition-property value, and the end value of the running transition is not
+            //    equal to the value of the property in the after-change style, then:
+            // 4. If the element has a running transition for the property, and there is not a
+            //    matching transition-property value, then implementations must cancel the running
+            //    transition
+            let running_transition = &element_transitions.running_transitions[running_index];
+            let Some(matching_value) = matching_value else {
+                queue_cancel_event(
+                    &mut self.pending_events,
+                    element,
+                    running_transition,
+                    current_time,
+                );
+                element_transitions
+                    .running_transitions
+                    .remove(running_index);
+                continue;
+            };
+
+            if running_transition.end_value_equals(&after_change_style) {
+                continue;
+            }
+
+            // The value of the property at the current time is the one in the before-change style
+            let current_value_style = &before_change_style;
+
+            // The running transition is replaced in any case
+            let running_transition = element_transitions
+                .running_transitions
+                .remove(running_index);
+            queue_cancel_event(
+                &mut self.pending_events,
+                element,
+                &running_transition,
+                current_time,
+            );
+
+            // 1. If the current value of the property in the running transition is equal to the value
+            //    of the property in the after-change style, or if these two values are not transitionable,
+            //    then implementations must cancel the running transition.
+            if current_value_style.property_equals(&after_change_style, property)
+                || !is_transitionable(current_value_style, &after_change_style, property)
+            {
+                continue;
+            }
+
+            let new_transition = if running_transition
+                .reversing_adjusted_start_style
+                .property_equals(&after_change_style, property)
+            {
+                // 2. Otherwise, if the reversing-adjusted start value of the running transition is the
+                //    same as the value of the property in the after-change style, implementations must
+                //    cancel the running transition and start a new transition whose:
+                //    * reversing-adjusted start value is the end value of the running transition,
+                //    * reversing shortening factor is the absolute value, clamped to the range [0, 1],
+                //      of the sum of:
+                //        1. the output of the timing function of the old transition at the time of the
+                //           style change event, times the reversing shortening factor of the old transition
+                //        2. 1 minus the reversing shortening factor of the old transition.
+                //    * start time is the time of the style change event plus:
+                //        1. if the matching transition delay is nonnegative, the matching transition delay, or
+                //        2. if the matching transition delay is negative, the product of the new transition's
+                //           reversing shortening factor and the matching transition delay,
+                //    * end time is the start time plus the product of the matching transition duration and
+                //      the new transition's reversing shortening factor,
+                //    * start value is the current value of the property in the running transition,
+                //    * end value is the value of the property in the after-change style
+                let reversing_shortening_factor = (running_transition.progress_at(current_time)
+                    * running_transition.reversing_shortening_factor
+                    + (1. - running_transition.reversing_shortening_factor))
+                    .abs()
+                    .clamp(0., 1.);
+
+                let delay = if matching_value.delay < 0. {
+                    reversing_shortening_factor * matching_value.delay
+                } else {
+                    matching_value.delay
+                };
+                let value = MatchingTransitionValue {
+                    delay,
+                    duration: matching_value.duration * reversing_shortening_factor,
+                    timing_function: matching_value.timing_function,
+                };
+
+                let mut new_transition = RunningTransition::new(
+                    property,
+                    current_time,
+                    value,
+                    current_value_style.clone(),
+                    after_change_style.clone(),
+                );
+                new_transition.reversing_adjusted_start_style = running_transition.end_style;
+                new_transition.reversing_shortening_factor = reversing_shortening_factor;
+                new_transition
+            } else {
+                // 3. Otherwise, implementations must cancel the running transition and if there is a
+                //    matching transition-property value whose combined duration is greater than 0s,
+                //    start a new transition whose start value is the current value of the property in
+                //    the running transition and whose end value is the value of the property in the
+                //    after-change style.
+                if matching_value.combined_duration() <= 0. {
+                    continue;
+                }
+
+                RunningTransition::new(
+                    property,
+                    current_time,
+                    matching_value,
+                    current_value_style.clone(),
+                    after_change_style.clone(),
+                )
+            };
+
+            element_transitions.running_transitions.push(new_transition);
+        }
+
+        // Apply the transitions to the new style
+        let mut style = after_change_style.clone();
+        element_transitions
+            .running_transitions
+            .retain(|transition| {
+                let could_apply = transition.apply(&mut style, current_time);
+                if !could_apply {
+                    log::warn!(
+                        "Failed to interpolate {:?} during transition, cancelling it",
+                        transition.property
+                    );
+                    queue_cancel_event(&mut self.pending_events, element, transition, current_time);
+                }
+                could_apply
+            });
+
+        element_transitions.previous_style = after_change_style;
+        style
+    }
+}
+
+/// <https://drafts.csswg.org/css-values-4/#interpolation>
+fn is_transitionable(from: &ComputedStyle, to: &ComputedStyle, property: PropertyId) -> bool {
+    from.clone().interpolate_property(property, from, to, 0.)
+}
+
+/// <https://drafts.csswg.org/css-transitions-2/#event-dispatch>
+fn queue_phase_change_events(
+    events: &mut Vec<TransitionEvent>,
+    element: &DomPtr<Element>,
+    transition: &RunningTransition,
+    new_phase: TransitionPhase,
+) {
+    use TransitionEventKind::*;
+    use TransitionPhase::*;
+
+    let interval_start = transition.interval_start();
+    let interval_end = transition.duration;
+
+    let events_to_fire: &[(TransitionEventKind, f32)] = match (transition.phase, new_phase) {
+        (Idle, Before) => &[(Run, interval_start)],
+        (Idle, Active) => &[(Run, interval_start), (Start, interval_start)],
+        (Idle, After) => &[
+            (Run, interval_start),
+            (Start, interval_start),
+            (End, interval_end),
+        ],
+        (Before, Active) => &[(Start, interval_start)],
+        (Before, After) => &[(Start, interval_start), (End, interval_end)],
+        (Active, After) => &[(End, interval_end)],
+        _ => &[],
+    };
+
+    events.extend(
+        events_to_fire
+            .iter()
+            .map(|&(kind, elapsed_time)| TransitionEvent {
+                kind,
+                target: element.clone(),
+                property_name: transition.property.name(),
+                elapsed_time,
+            }),
+    );
+}
+
+fn queue_cancel_event(
+    events: &mut Vec<TransitionEvent>,
+    element: &DomPtr<Element>,
+    transition: &RunningTransition,
+    current_time: f32,
+) {
+    // Transitions that never left the idle phase never fired a "transitionrun" event,
+    // so they don't fire a "transitioncancel" event either
+    if matches!(
+        transition.phase,
+        TransitionPhase::Idle | TransitionPhase::After
+    ) {
+        return;
+    }
+
+    events.push(TransitionEvent {
+        kind: TransitionEventKind::Cancel,
+        target: element.clone(),
+        property_name: transition.property.name(),
+        elapsed_time: transition.active_time(current_time),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::{
+        style::specified::{TransitionDuration, TransitionTimingFunction},
+        values::Color,
+        CSSParse,
+    };
+
+    fn style_with_color(color: Color) -> ComputedStyle {
+        let mut style = ComputedStyle::default();
+        style.set_transition_duration(TransitionDuration::parse_from_str("1s").unwrap());
+        style.set_transition_timing_function(
+            TransitionTimingFunction::parse_from_str("linear").unwrap(),
+        );
+        style.set_color(color);
+        style
+    }
+
+    fn event_kinds(transitions: &mut Transitions) -> Vec<TransitionEventKind> {
+        transitions
+            .take_events()
+            .iter()
+            .map(|event| event.kind)
+            .collect()
+    }
+
+    #[test]
+    fn transition_color() {
+        let element = DomPtr::new(Element::default());
+        let mut transitions = Transitions::default();
+        let black = style_with_color(Color::BLACK);
+        let white = style_with_color(Color::WHITE);
+
+        // Elements are not transitioned when they are styled for the first time
+        let style = transitions.process_style_change(&element, black);
+        assert_eq!(*style.color(), Color::BLACK);
+        assert!(!transitions.has_running_transitions());
+
+        let style = transitions.process_style_change(&element, white.clone());
+        assert_eq!(*style.color(), Color::BLACK);
+        assert!(transitions.has_running_transitions());
+
+        assert!(transitions.update(0.5));
+        let style = transitions.process_style_change(&element, white.clone());
+        assert_eq!(*style.color(), Color::rgb(128, 128, 128));
+        assert_eq!(
+            event_kinds(&mut transitions),
+            [TransitionEventKind::Run, TransitionEventKind::Start]
+        );
+
+        transitions.update(1.);
+        let style = transitions.process_style_change(&element, white);
+        assert_eq!(*style.color(), Color::WHITE);
+        assert_eq!(event_kinds(&mut transitions), [TransitionEventKind::End]);
+        assert!(!transitions.has_running_transitions());
+    }
+
+    #[test]
+    fn reverse_transition() {
+        let element = DomPtr::new(Element::default());
+        let mut transitions = Transitions::default();
+        let black = style_with_color(Color::BLACK);
+        let white = style_with_color(Color::WHITE);
+
+        transitions.process_style_change(&element, black.clone());
+        transitions.process_style_change(&element, white);
+        transitions.update(0.25);
+        _ = transitions.take_events();
+
+        // Reversing a transition that is a quarter done only takes a quarter of the time
+        let style = transitions.process_style_change(&element, black.clone());
+        assert_eq!(*style.color(), Color::rgb(64, 64, 64));
+        assert_eq!(event_kinds(&mut transitions), [TransitionEventKind::Cancel]);
+
+        transitions.update(0.375);
+        let style = transitions.process_style_change(&element, black.clone());
+        assert_eq!(*style.color(), Color::rgb(32, 32, 32));
+
+        transitions.update(0.5);
+        let style = transitions.process_style_change(&element, black);
+        assert_eq!(*style.color(), Color::BLACK);
+        assert!(!transitions.has_running_transitions());
+    }
+}
@@ -40,4 +40,77 @@ impl ComputedStyle {
             left,
         }
     }
+
+    /// Whether the box lays out its contents in columns
+    ///
+    /// <https://drafts.csswg.org/css-multicol/#multi-column-container>
+    #[must_use]
+    pub fn is_multi_column_container(&self) -> bool {
+        !self.column_count().is_auto() || !self.column_width().is_auto()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        css::{
+            layout::{Pixels, Size},
+            style::StyleContext,
+            values::{AutoOr, PercentageOr},
+            Origin, Parser, StyleProperty,
+        },
+        static_interned, InternedString,
+    };
+
+    fn set(style: &mut ComputedStyle, property: InternedString, value: &str) {
+        let context = StyleContext::new(Size {
+            width: Pixels(800.),
+            height: Pixels(600.),
+        });
+        let mut parser = Parser::new(value, Origin::Author);
+        let property = StyleProperty::parse_value(&mut parser, property).unwrap();
+        style.set_property(property, &context);
+    }
+
+    #[test]
+    fn inherit_style() {
+        let mut parent = ComputedStyle::default();
+        set(&mut parent, static_interned!("color"), "red");
+        set(&mut parent, static_interned!("margin-top"), "10px");
+
+        let child = parent.get_inherited();
+        assert_eq!(child.color(), parent.color());
+        assert_eq!(
+            *child.margin_top(),
+            AutoOr::NotAuto(PercentageOr::NotPercentage(Pixels::ZERO))
+        );
+    }
+
+    #[test]
+    fn flow_relative_properties() {
+        let margin = |pixels| AutoOr::NotAuto(PercentageOr::NotPercentage(Pixels(pixels)));
+
+        let mut style = ComputedStyle::default();
+        set(&mut style, static_interned!("margin-inline-start"), "10px");
+        set(&mut style, static_interned!("margin-block"), "5px 15px");
+        assert_eq!(*style.margin_left(), margin(10.));
+        assert_eq!(*style.margin_top(), margin(5.));
+        assert_eq!(*style.margin_bottom(), margin(15.));
+
+        let mut style = ComputedStyle::default();
+        set(&mut style, static_interned!("writing-mode"), "vertical-rl");
+        set(&mut style, static_interned!("margin-inline-start"), "10px");
+        set(&mut style, static_interned!("padding-block-start"), "20px");
+        set(&mut style, static_interned!("inline-size"), "30px");
+        assert_eq!(*style.margin_top(), margin(10.));
+        assert_eq!(
+            *style.padding_right(),
+            PercentageOr::NotPercentage(Pixels(20.))
+        );
+        assert_eq!(
+            *style.height(),
+            AutoOr::NotAuto(PercentageOr::NotPercentage(Pixels(30.)))
+        );
+    }
 }
@@ -1,15 +1,17 @@
+use std::sync::Arc;
+
 use image::Texture;
 
-use crate::css::{layout::Pixels, FontMetrics};
+use crate::css::{layout::Pixels, style::computed::TextOrientation, FontMetrics};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Command {
     Rect(RectCommand),
     Text(TextCommand),
     Image(ImageCommand),
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct RectCommand {
     pub area: math::Rectangle<Pixels>,
     pub color: math::Color,
@@ -17,14 +19,41 @@ pub struct RectCommand {
 
 #[derive(Clone, Debug)]
 pub struct TextCommand {
-    pub position: math::Vec2D<Pixels>,
+    pub area: math::Rectangle<Pixels>,
     pub text: String,
     pub font_metrics: FontMetrics,
     pub color: math::Color,
+
+    /// The orientation of the characters, if the text runs vertically
+    pub vertical: Option<TextOrientation>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ImageCommand {
     pub area: math::Rectangle<Pixels>,
-    pub texture: Texture,
+    pub texture: Arc<Texture>,
+}
+
+impl Command {
+    /// The area that is affected by this command
+    #[must_use]
+    pub fn area(&self) -> math::Rectangle<Pixels> {
+        match self {
+            Self::Rect(rect_command) => rect_command.area,
+            Self::Text(text_command) => text_command.area,
+            Self::Image(image_command) => image_command.area,
+        }
+    }
+}
+
+impl PartialEq for TextCommand {
+    fn eq(&self, other: &Self) -> bool {
+        // Fonts are not compared byte-by-byte, their name is good enough
+        self.area == other.area
+            && self.text == other.text
+            && self.color == other.color
+            && self.vertical == other.vertical
+            && self.font_metrics.size == other.font_metrics.size
+            && self.font_metrics.font_face.name() == other.font_metrics.font_face.name()
+    }
 }
@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+
+use image::AccessMode;
+use math::{Rectangle, Vec2D};
+use render::{Antialiasing, LayerNode, Path, Source};
+use settings::{TextAntialiasing, SETTINGS};
+
+use crate::css::layout::Pixels;
+
+use super::Command;
+
+/// Identifies the fragment that produced a [DisplayItem]
+///
+/// Keys are stable across relayouts as long as the DOM node that produced
+/// the fragment is alive, which allows display lists from different
+/// frames to be compared against each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ItemKey {
+    /// The address of the DOM node that produced the item
+    pub(super) owner: usize,
+
+    /// Distinguishes multiple items produced by the same fragment
+    pub(super) index: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct DisplayItem {
+    pub key: ItemKey,
+    pub command: Command,
+}
+
+/// A list of drawing commands, in paint order
+///
+/// Display lists are retained between repaints so only the areas of the
+/// screen that actually changed need to be re-rasterized.
+#[derive(Clone, Debug, Default)]
+pub struct DisplayList {
+    items: Vec<DisplayItem>,
+}
+
+impl DisplayList {
+    #[must_use]
+    pub fn new(items: Vec<DisplayItem>) -> Self {
+        Self { items }
+    }
+
+    /// Compute the areas that differ between `self` and a display list from a previous frame
+    ///
+    /// An item is considered to be damaged if it was added, removed or changed.
+    /// Changing the paint order of items also damages them, since that might
+    /// change which item ends up on top.
+    #[must_use]
+    pub fn damage_since(&self, previous: &Self) -> Vec<Rectangle<Pixels>> {
+        let previous_items: HashMap<ItemKey, (usize, &Command)> = previous
+            .items
+            .iter()
+            .enumerate()
+            .map(|(paint_order, item)| (item.key, (paint_order, &item.command)))
+            .collect();
+
+        let mut damage = vec![];
+        let mut seen_items = 0;
+        for (paint_order, item) in self.items.iter().enumerate() {
+            match previous_items.get(&item.key) {
+                Some((previous_paint_order, previous_command)) => {
+                    seen_items += 1;
+
+                    if *previous_paint_order != paint_order || *previous_command != &item.command {
+                        damage.push(previous_command.area());
+                        damage.push(item.command.area());
+                    }
+                },
+                None => damage.push(item.command.area()),
+            }
+        }
+
+        // Items that are no longer painted damage the area they previously covered
+        if seen_items != previous.items.len() {
+            let current_keys: HashSet<ItemKey> = self.items.iter().map(|item| item.key).collect();
+
+            damage.extend(
+                previous
+                    .items
+                    .iter()
+                    .filter(|item| !current_keys.contains(&item.key))
+                    .map(|item| item.command.area()),
+            );
+        }
+
+        damage
+    }
+
+    /// Invalidate the parts of the layer node that changed since `previous` was painted
+    ///
+    /// If there is no previous display list, the whole node is invalidated.
+    pub fn invalidate_damaged_areas(&self, previous: Option<&Self>, node: &mut LayerNode) {
+        let Some(previous) = previous else {
+            node.invalidate_all();
+            return;
+        };
+
+        for area in self.damage_since(previous) {
+            node.invalidate(Rectangle::from_corners(
+                to_points(area.top_left()),
+                to_points(area.bottom_right()),
+            ));
+        }
+    }
+
+    /// Add a layer for every item in the display list to the layer node
+    pub fn paint(&self, node: &mut LayerNode) {
+        let text_antialiasing = match SETTINGS.preferences().text_antialiasing {
+            TextAntialiasing::Grayscale => Antialiasing::Grayscale,
+            TextAntialiasing::Lcd => Antialiasing::Lcd,
+        };
+
+        for item in &self.items {
+            let layer = node.push_layer();
+
+            match &item.command {
+                Command::Rect(rect_cmd) => {
+                    layer
+                        .with_source(Source::Solid(rect_cmd.color))
+                        .with_outline(Path::rect(
+                            to_points(rect_cmd.area.top_left()),
+                            to_points(rect_cmd.area.bottom_right()),
+                        ));
+                },
+                Command::Text(text_command) => {
+                    let font_face = *text_command.font_metrics.font_face.clone();
+                    let font_size = text_command.font_metrics.size.into();
+                    let position = to_points(text_command.area.top_left());
+
+                    match text_command.vertical {
+                        Some(text_orientation) => layer.vertical_text(
+                            &text_command.text,
+                            font_face,
+                            font_size,
+                            position,
+                            text_command.area.width().into(),
+                            |c| text_orientation.is_upright(c),
+                        ),
+                        None => layer.text(&text_command.text, font_face, font_size, position),
+                    }
+                    .with_source(Source::Solid(text_command.color))
+                    .with_antialiasing(text_antialiasing);
+                },
+                Command::Image(image_command) => {
+                    let texture_source = Source::Texture {
+                        texture: image_command.texture.clone(),
+                        access_mode: AccessMode::Zero,
+                    };
+
+                    layer.with_source(texture_source).with_outline(Path::rect(
+                        to_points(image_command.area.top_left()),
+                        to_points(image_command.area.bottom_right()),
+                    ));
+                },
+            }
+        }
+    }
+}
+
+#[inline]
+fn to_points(position: Vec2D<Pixels>) -> Vec2D {
+    position.map(f32::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplayItem, DisplayList, ItemKey};
+    use crate::css::{
+        display_list::{command::RectCommand, Command},
+        layout::Pixels,
+    };
+
+    fn rect_item(owner: usize, x: f32, color: math::Color) -> DisplayItem {
+        let area = math::Rectangle::from_position_and_size(
+            math::Vec2D::new(Pixels(x), Pixels(0.)),
+            Pixels(10.),
+            Pixels(10.),
+        );
+
+        DisplayItem {
+            key: ItemKey { owner, index: 0 },
+            command: Command::Rect(RectCommand { area, color }),
+        }
+    }
+
+    #[test]
+    fn unchanged_list_has_no_damage() {
+        let list = DisplayList::new(vec![rect_item(1, 0., math::Color::RED)]);
+        assert!(list.damage_since(&list.clone()).is_empty());
+    }
+
+    #[test]
+    fn changed_item_damages_old_and_new_area() {
+        let previous = DisplayList::new(vec![rect_item(1, 0., math::Color::RED)]);
+        let current = DisplayList::new(vec![rect_item(1, 20., math::Color::RED)]);
+
+        let damage = current.damage_since(&previous);
+        assert_eq!(damage.len(), 2);
+        assert_eq!(damage[0].top_left().x, Pixels(0.));
+        assert_eq!(damage[1].top_left().x, Pixels(20.));
+    }
+
+    #[test]
+    fn removed_item_damages_old_area() {
+        let previous = DisplayList::new(vec![
+            rect_item(1, 0., math::Color::RED),
+            rect_item(2, 50., math::Color::BLUE),
+        ]);
+        let current = DisplayList::new(vec![rect_item(1, 0., math::Color::RED)]);
+
+        let damage = current.damage_since(&previous);
+        assert_eq!(damage.len(), 1);
+        assert_eq!(damage[0].top_left().x, Pixels(50.));
+    }
+}
@@ -1,7 +1,9 @@
 //! Provides drawing primitives that can be used to compose a rendered website
 
 mod command;
+mod list;
 mod painter;
 
 pub use command::Command;
+pub use list::{DisplayItem, DisplayList, ItemKey};
 pub use painter::Painter;
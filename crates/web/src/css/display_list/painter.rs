@@ -1,111 +1,134 @@
-use image::{AccessMode, Texture};
-use math::Vec2D;
-use render::{Composition, Path, Source};
-
-use crate::css::{
-    display_list::{
-        command::{RectCommand, TextCommand},
-        Command,
+use std::{collections::HashMap, sync::Arc};
+
+use image::Texture;
+
+use crate::{
+    css::{
+        display_list::{
+            command::{RectCommand, TextCommand},
+            Command, DisplayItem, DisplayList, ItemKey,
+        },
+        layout::{Pixels, Size},
+        style::computed::TextOrientation,
+        FontMetrics,
     },
-    layout::{Pixels, Size},
-    FontMetrics,
+    dom::{dom_objects, DomPtr},
 };
 
 use super::command::ImageCommand;
 
 #[derive(Clone, Debug, Default)]
 pub struct Painter {
-    commands: Vec<Command>,
+    items: Vec<DisplayItem>,
+
+    /// The fragments that are currently being painted, innermost last
+    ///
+    /// Each fragment is identified by the address of the DOM node that produced it.
+    owners: Vec<usize>,
+
+    /// The number of items emitted by each owner so far
+    items_per_owner: HashMap<usize, u32>,
 }
 
 impl Painter {
-    pub fn paint_magic_background(&mut self, viewport: Size<Pixels>, color: math::Color) {
-        let area = viewport.at_position(math::Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+    /// Start painting a fragment that was produced by the given DOM node
+    ///
+    /// Items emitted until the matching call to [Painter::end_fragment] are keyed by the node
+    /// so they can be matched against the previous display list.
+    /// Fragments without a DOM node share the identity of their parent.
+    pub fn begin_fragment(&mut self, dom_node: Option<&DomPtr<dom_objects::Node>>) {
+        let owner = dom_node
+            .map(|node| node.as_ptr() as usize)
+            .unwrap_or_else(|| self.current_owner());
+        self.owners.push(owner);
+    }
+
+    pub fn end_fragment(&mut self) {
+        self.owners.pop();
+    }
+
+    fn current_owner(&self) -> usize {
+        self.owners.last().copied().unwrap_or_default()
+    }
+
+    fn push(&mut self, command: Command) {
+        let key = self.next_key();
+        self.items.push(DisplayItem { key, command });
+    }
+
+    fn next_key(&mut self) -> ItemKey {
+        let owner = self.current_owner();
+        let index = self.items_per_owner.entry(owner).or_default();
+        let key = ItemKey {
+            owner,
+            index: *index,
+        };
+        *index += 1;
+        key
+    }
 
-        self.commands
-            .insert(0, Command::Rect(RectCommand { area, color }))
+    pub fn paint_magic_background(&mut self, canvas: Size<Pixels>, color: math::Color) {
+        let area = canvas.at_position(math::Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+
+        let key = self.next_key();
+        self.items.insert(
+            0,
+            DisplayItem {
+                key,
+                command: Command::Rect(RectCommand { area, color }),
+            },
+        )
     }
 
     pub fn rect(&mut self, area: math::Rectangle<Pixels>, color: math::Color) {
-        self.commands
-            .push(Command::Rect(RectCommand { area, color }))
+        self.push(Command::Rect(RectCommand { area, color }))
     }
 
-    pub fn image(&mut self, area: math::Rectangle<Pixels>, texture: Texture) {
-        self.commands
-            .push(Command::Image(ImageCommand { area, texture }))
+    pub fn image(&mut self, area: math::Rectangle<Pixels>, texture: Arc<Texture>) {
+        self.push(Command::Image(ImageCommand { area, texture }))
     }
 
     pub fn text(
         &mut self,
         text: String,
-        position: Vec2D<Pixels>,
+        area: math::Rectangle<Pixels>,
+        color: math::Color,
+        font_metrics: FontMetrics,
+    ) {
+        let text_command = TextCommand {
+            area,
+            text,
+            font_metrics,
+            color,
+            vertical: None,
+        };
+
+        self.push(Command::Text(text_command));
+    }
+
+    /// Paint text that runs from top to bottom
+    pub fn vertical_text(
+        &mut self,
+        text: String,
+        area: math::Rectangle<Pixels>,
         color: math::Color,
         font_metrics: FontMetrics,
+        text_orientation: TextOrientation,
     ) {
         let text_command = TextCommand {
-            position,
+            area,
             text,
             font_metrics,
             color,
+            vertical: Some(text_orientation),
         };
 
-        self.commands.push(Command::Text(text_command));
+        self.push(Command::Text(text_command));
     }
 
-    pub fn paint(self, composition: &mut Composition) {
-        for (index, command) in self.commands.into_iter().enumerate() {
-            match command {
-                Command::Rect(rect_cmd) => {
-                    composition
-                        .get_or_insert_layer(index as u16)
-                        .with_source(Source::Solid(rect_cmd.color))
-                        .with_outline(Path::rect(
-                            Vec2D {
-                                x: rect_cmd.area.top_left().x.0,
-                                y: rect_cmd.area.top_left().y.0,
-                            },
-                            Vec2D {
-                                x: rect_cmd.area.bottom_right().x.0,
-                                y: rect_cmd.area.bottom_right().y.0,
-                            },
-                        ));
-                },
-                Command::Text(text_command) => {
-                    composition
-                        .get_or_insert_layer(index as u16)
-                        .text(
-                            &text_command.text,
-                            *text_command.font_metrics.font_face.clone(),
-                            text_command.font_metrics.size.into(),
-                            Vec2D {
-                                x: text_command.position.x.0,
-                                y: text_command.position.y.0,
-                            },
-                        )
-                        .with_source(Source::Solid(text_command.color));
-                },
-                Command::Image(image_command) => {
-                    let texture_source = Source::Texture {
-                        texture: image_command.texture,
-                        access_mode: AccessMode::Zero,
-                    };
-
-                    composition
-                        .get_or_insert_layer(index as u16)
-                        .with_source(texture_source)
-                        .with_outline(Path::rect(
-                            Vec2D {
-                                x: image_command.area.top_left().x.0,
-                                y: image_command.area.top_left().y.0,
-                            },
-                            Vec2D {
-                                x: image_command.area.bottom_right().x.0,
-                                y: image_command.area.bottom_right().y.0,
-                            },
-                        ));
-                },
-            }
-        }
+    /// Finish painting and return the display list that was built
+    #[must_use]
+    pub fn finish(self) -> DisplayList {
+        DisplayList::new(self.items)
     }
 }
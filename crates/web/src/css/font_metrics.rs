@@ -1,11 +1,118 @@
 use font::Font;
+use settings::Preferences;
 
-use super::layout::Pixels;
+use super::{
+    layout::Pixels,
+    style::{
+        computed::{FontFamily, FontStyle},
+        specified::FontName,
+    },
+    ComputedStyle,
+};
 
-pub const DEFAULT_FONT_SIZE: Pixels = Pixels(16.0);
+/// The font sizes that the user chose for text whose size is not specified by the page
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DefaultFontSizes {
+    pub proportional: Pixels,
+    pub monospace: Pixels,
+}
+
+impl DefaultFontSizes {
+    #[must_use]
+    pub fn new(preferences: &Preferences) -> Self {
+        Self {
+            proportional: Pixels(preferences.font_size as f32),
+            monospace: Pixels(preferences.monospace_font_size as f32),
+        }
+    }
+
+    /// The size of `font-size: medium` for text in the given font family
+    #[must_use]
+    pub fn medium_for(&self, font_family: &FontFamily) -> Pixels {
+        if font_family.is_monospace() {
+            self.monospace
+        } else {
+            self.proportional
+        }
+    }
+}
+
+impl Default for DefaultFontSizes {
+    fn default() -> Self {
+        Self::new(&Preferences::default())
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct FontMetrics {
     pub font_face: Box<Font>,
     pub size: Pixels,
 }
+
+impl FontMetrics {
+    /// Load the font that text with the given style is rendered in
+    #[must_use]
+    pub fn for_style(style: &ComputedStyle) -> Self {
+        // FIXME: Consider more than just the first specified font
+        let family = match &style.font_family().fonts()[0] {
+            FontName::Family(name) => font::Family::Specific(name.to_string()),
+            FontName::Generic(name) => font::Family::Generic(name.to_string()),
+        };
+
+        let properties = font::Properties {
+            style: font::Style::Normal,
+            weight: font::Weight(style.font_weight().value().round() as u16),
+            language: font::Language::English,
+        };
+
+        let mut font = font::SYSTEM_FONTS
+            .load(family, properties)
+            .expect("Failed to load font");
+
+        if !font.variation_axes().is_empty() {
+            font.set_variations(&variation_settings(style));
+        }
+
+        Self {
+            font_face: Box::new(font),
+            size: *style.font_size(),
+        }
+    }
+
+    /// The width of the given text when rendered with this font
+    #[must_use]
+    pub fn width_of(&self, text: &str) -> Pixels {
+        Pixels(
+            self.font_face
+                .compute_rendered_width(text, self.size.into()),
+        )
+    }
+}
+
+/// The axis values that select the instance of a variable font matching the style
+///
+/// See <https://drafts.csswg.org/css-fonts-4/#font-feature-variation-resolution>
+#[must_use]
+fn variation_settings(style: &ComputedStyle) -> Vec<([u8; 4], f32)> {
+    let mut settings = vec![
+        (*b"wght", style.font_weight().value()),
+        (*b"wdth", style.font_stretch().as_fraction() * 100.),
+    ];
+
+    match style.font_style() {
+        FontStyle::Normal => {},
+        FontStyle::Italic => settings.push((*b"ital", 1.)),
+        FontStyle::Oblique(angle) => settings.push((*b"slnt", -angle.as_degrees())),
+    }
+
+    // Explicitly specified axis values take precedence
+    settings.extend(
+        style
+            .font_variation_settings()
+            .settings()
+            .iter()
+            .map(|setting| (setting.tag, setting.value)),
+    );
+
+    settings
+}
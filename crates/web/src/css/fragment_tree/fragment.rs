@@ -1,11 +1,13 @@
+use std::sync::Arc;
+
 use image::Texture;
 use math::Rectangle;
 
 use crate::{
     css::{
         display_list::Painter,
-        layout::{Pixels, Sides},
-        style::specified::BackgroundColor,
+        layout::{logical::LogicalMapping, Pixels, Sides, Size},
+        style::{computed::TextOrientation, specified::BackgroundColor},
         values::Color,
         ComputedStyle, FontMetrics,
     },
@@ -25,6 +27,9 @@ pub struct BoxFragment {
     padding_area: Rectangle<Pixels>,
     content_area: Rectangle<Pixels>,
     children: Vec<Fragment>,
+
+    /// The rules between the columns of a multi-column container, relative to the content area
+    column_rules: Vec<Rectangle<Pixels>>,
 }
 
 #[derive(Clone, Debug)]
@@ -33,11 +38,15 @@ pub struct TextFragment {
     area: Rectangle<Pixels>,
     color: Color,
     font_metrics: FontMetrics,
+    text_orientation: TextOrientation,
+
+    /// Whether the text runs from top to bottom
+    is_vertical: bool,
 }
 
 #[derive(Clone, Debug)]
 pub struct TextureFragment {
-    pub texture: Texture,
+    pub texture: Arc<Texture>,
     pub area: Rectangle<Pixels>,
 }
 
@@ -53,7 +62,7 @@ impl Fragment {
         match self {
             Self::Box(box_fragment) => box_fragment.fill_display_list(painter, state),
             Self::Text(text_fragment) => text_fragment.fill_display_list(painter, state),
-            Self::Image(image_fragment) => image_fragment.fill_display_list(painter),
+            Self::Image(image_fragment) => image_fragment.fill_display_list(painter, state),
         }
     }
 
@@ -100,6 +109,111 @@ impl Fragment {
         }
     }
 
+    /// Call `visitor` for this fragment and all descendant box fragments, together with
+    /// their border area (offset by `offset`)
+    pub(super) fn visit_boxes<'a, F>(&'a self, offset: math::Vec2D<Pixels>, visitor: &mut F)
+    where
+        F: FnMut(&'a Fragment, Rectangle<Pixels>),
+    {
+        let Self::Box(box_fragment) = self else {
+            return;
+        };
+
+        visitor(self, box_fragment.border_area().offset_by(offset));
+
+        // Children are positioned relative to the content area, like when painting
+        let offset = offset + box_fragment.content_area.top_left();
+        for child in box_fragment.children() {
+            child.visit_boxes(offset, visitor);
+        }
+    }
+
+    /// Collect the areas (offset by `offset`) of all text and images within this fragment
+    ///
+    /// These should not be split when the content is broken across pages or columns.
+    pub(super) fn collect_unbreakable_areas(
+        &self,
+        offset: math::Vec2D<Pixels>,
+        areas: &mut Vec<Rectangle<Pixels>>,
+    ) {
+        match self {
+            Self::Box(box_fragment) => {
+                let offset = offset + box_fragment.content_area.top_left();
+                for child in box_fragment.children() {
+                    child.collect_unbreakable_areas(offset, areas);
+                }
+            },
+            Self::Text(text_fragment) => areas.push(text_fragment.area.offset_by(offset)),
+            Self::Image(image_fragment) => areas.push(image_fragment.area.offset_by(offset)),
+        }
+    }
+
+    /// The part of this fragment between the vertical positions `start` and `end`
+    ///
+    /// Text and images are never split, they belong to the part that contains their top edge.
+    /// Boxes are sliced, without borders, padding or margins at the edges where they are cut.
+    /// Returns `None` if no part of the fragment lies within the range.
+    ///
+    /// <https://drafts.csswg.org/css-break/#valdef-box-decoration-break-slice>
+    #[must_use]
+    pub(crate) fn slice(&self, start: Pixels, end: Pixels) -> Option<Self> {
+        let contains_top_edge = |area: Rectangle<Pixels>| {
+            let top = area.top_left().y;
+            start <= top && top < end
+        };
+
+        match self {
+            Self::Box(box_fragment) => box_fragment.slice(start, end).map(Self::Box),
+            Self::Text(text_fragment) => {
+                contains_top_edge(text_fragment.area).then(|| self.clone())
+            },
+            Self::Image(image_fragment) => {
+                contains_top_edge(image_fragment.area).then(|| self.clone())
+            },
+        }
+    }
+
+    /// Move the fragment and everything within it by `offset`
+    pub(crate) fn translate(&mut self, offset: math::Vec2D<Pixels>) {
+        match self {
+            Self::Box(box_fragment) => {
+                box_fragment.margin_area = box_fragment.margin_area.offset_by(offset);
+                box_fragment.padding_area = box_fragment.padding_area.offset_by(offset);
+                box_fragment.content_area = box_fragment.content_area.offset_by(offset);
+            },
+            Self::Text(text_fragment) => text_fragment.area = text_fragment.area.offset_by(offset),
+            Self::Image(image_fragment) => {
+                image_fragment.area = image_fragment.area.offset_by(offset)
+            },
+        }
+    }
+
+    /// Convert a fragment that was laid out along the logical axes into physical coordinates
+    ///
+    /// `container` is the logical size of the area that the fragment is positioned in.
+    #[must_use]
+    pub(crate) fn into_physical(self, mapping: LogicalMapping, container: Size<Pixels>) -> Self {
+        match self {
+            Self::Box(box_fragment) => Self::Box(box_fragment.into_physical(mapping, container)),
+            Self::Text(mut text_fragment) => {
+                text_fragment.area = mapping.to_physical_rect(text_fragment.area, container);
+                text_fragment.is_vertical = mapping.is_vertical();
+                Self::Text(text_fragment)
+            },
+            Self::Image(mut image_fragment) => {
+                // FIXME: Replaced elements are laid out with their physical width on the inline axis,
+                //        so only their position is converted
+                let area = mapping.to_physical_rect(image_fragment.area, container);
+                image_fragment.area = Rectangle::from_position_and_size(
+                    area.top_left(),
+                    image_fragment.area.width(),
+                    image_fragment.area.height(),
+                );
+                Self::Image(image_fragment)
+            },
+        }
+    }
+
     /// Return the [Node](dom_objects::Node) associated with this fragment, if any
     pub fn dom_node(&self) -> Option<DomPtr<dom_objects::Node>> {
         match self {
@@ -124,9 +238,18 @@ impl TextFragment {
             area,
             color,
             font_metrics,
+            text_orientation: TextOrientation::default(),
+            is_vertical: false,
         }
     }
 
+    /// Set the orientation of the characters, in case the text ends up running vertically
+    #[must_use]
+    pub fn with_text_orientation(mut self, text_orientation: TextOrientation) -> Self {
+        self.text_orientation = text_orientation;
+        self
+    }
+
     #[inline]
     #[must_use]
     pub fn text(&self) -> &str {
@@ -137,12 +260,22 @@ impl TextFragment {
     pub(super) fn fill_display_list(&self, painter: &mut Painter, state: &DisplayState) {
         let color = math::Color::from(self.color);
 
-        painter.text(
-            self.text().to_owned(),
-            self.area.top_left() + state.offset,
-            color,
-            self.font_metrics.clone(),
-        );
+        if self.is_vertical {
+            painter.vertical_text(
+                self.text().to_owned(),
+                self.area.offset_by(state.offset),
+                color,
+                self.font_metrics.clone(),
+                self.text_orientation,
+            );
+        } else {
+            painter.text(
+                self.text().to_owned(),
+                self.area.offset_by(state.offset),
+                color,
+                self.font_metrics.clone(),
+            );
+        }
     }
 }
 
@@ -165,14 +298,52 @@ impl BoxFragment {
             padding_area,
             content_area,
             children,
+            column_rules: vec![],
         }
     }
 
+    /// Draw rules in the given areas, which are relative to the content area
+    #[must_use]
+    pub fn with_column_rules(mut self, column_rules: Vec<Rectangle<Pixels>>) -> Self {
+        self.column_rules = column_rules;
+        self
+    }
+
     #[must_use]
     pub fn style(&self) -> &ComputedStyle {
         &self.style
     }
 
+    #[must_use]
+    fn into_physical(self, mapping: LogicalMapping, container: Size<Pixels>) -> Self {
+        // Children are positioned relative to the content area
+        let content_size = Size {
+            width: self.content_area.width(),
+            height: self.content_area.height(),
+        };
+        let children = self
+            .children
+            .into_iter()
+            .map(|child| child.into_physical(mapping, content_size))
+            .collect();
+        let column_rules = self
+            .column_rules
+            .into_iter()
+            .map(|rule| mapping.to_physical_rect(rule, content_size))
+            .collect();
+
+        Self {
+            dom_node: self.dom_node,
+            style: self.style,
+            margin_area: mapping.to_physical_rect(self.margin_area, container),
+            borders: mapping.to_physical_sides(self.borders),
+            padding_area: mapping.to_physical_rect(self.padding_area, container),
+            content_area: mapping.to_physical_rect(self.content_area, container),
+            children,
+            column_rules,
+        }
+    }
+
     #[must_use]
     pub fn children(&self) -> &[Fragment] {
         &self.children
@@ -185,10 +356,89 @@ impl BoxFragment {
         self.margin_area
     }
 
+    #[inline]
+    #[must_use]
+    pub fn padding_area(&self) -> Rectangle<Pixels> {
+        self.padding_area
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn content_area(&self) -> Rectangle<Pixels> {
+        self.content_area
+    }
+
     pub fn border_area(&self) -> Rectangle<Pixels> {
         self.borders.surround(self.padding_area)
     }
 
+    /// See [Fragment::slice]
+    #[must_use]
+    fn slice(&self, start: Pixels, end: Pixels) -> Option<Self> {
+        let top = self.margin_area.top_left().y;
+        let bottom = self.margin_area.bottom_right().y;
+
+        // Empty boxes are kept as long as they start within the range
+        if end <= top || (bottom <= start && top < start) {
+            return None;
+        }
+
+        if start <= top && bottom <= end {
+            return Some(self.clone());
+        }
+
+        let clip = |area: Rectangle<Pixels>, start: Pixels, end: Pixels| {
+            let top_left = math::Vec2D::new(area.top_left().x, area.top_left().y.clamp(start, end));
+            let bottom_right = math::Vec2D::new(
+                area.bottom_right().x,
+                area.bottom_right().y.clamp(start, end),
+            );
+            Rectangle::from_corners(top_left, bottom_right)
+        };
+
+        let border_area = self.border_area();
+        let mut borders = self.borders;
+        if border_area.top_left().y < start {
+            borders.top = Pixels::ZERO;
+        }
+        if end < border_area.bottom_right().y {
+            borders.bottom = Pixels::ZERO;
+        }
+
+        // Children are positioned relative to the content area, whose top edge might move
+        let content_area = clip(self.content_area, start, end);
+        let content_top = self.content_area.top_left().y;
+        let shift = math::Vec2D::new(Pixels::ZERO, content_top - content_area.top_left().y);
+        let (child_start, child_end) = (start - content_top, end - content_top);
+
+        let children = self
+            .children
+            .iter()
+            .filter_map(|child| child.slice(child_start, child_end))
+            .map(|mut child| {
+                child.translate(shift);
+                child
+            })
+            .collect();
+        let column_rules = self
+            .column_rules
+            .iter()
+            .map(|rule| clip(*rule, child_start, child_end).offset_by(shift))
+            .filter(|rule| rule.height() != Pixels::ZERO)
+            .collect();
+
+        Some(Self {
+            dom_node: self.dom_node.clone(),
+            style: self.style.clone(),
+            margin_area: clip(self.margin_area, start, end),
+            borders,
+            padding_area: clip(self.padding_area, start, end),
+            content_area,
+            children,
+            column_rules,
+        })
+    }
+
     fn draw_background(&self, painter: &mut Painter, state: &mut DisplayState) {
         match *self.style().background_color() {
             BackgroundColor::Transparent => {
@@ -202,7 +452,7 @@ impl BoxFragment {
                     // (https://drafts.csswg.org/css2/#background)
                     // (https://drafts.csswg.org/css-backgrounds/#root-background)
                     state.has_seen_background_on_html_element = true;
-                    painter.paint_magic_background(state.viewport, color.into());
+                    painter.paint_magic_background(state.canvas, color.into());
                 } else if box_type == Some(dom::DomType::HtmlBodyElement)
                     && !state.has_seen_background_on_html_element
                 {
@@ -210,7 +460,7 @@ impl BoxFragment {
                     // applies to the body element
                     // (https://drafts.csswg.org/css2/#background)
                     // (https://drafts.csswg.org/css-backgrounds/#body-background)
-                    painter.paint_magic_background(state.viewport, color.into());
+                    painter.paint_magic_background(state.canvas, color.into());
                 } else {
                     painter.rect(self.padding_area.offset_by(state.offset), color.into());
                 }
@@ -219,6 +469,7 @@ impl BoxFragment {
     }
 
     fn fill_display_list(&self, painter: &mut Painter, state: &mut DisplayState) {
+        painter.begin_fragment(self.dom_node.as_ref());
         self.draw_background(painter, state);
 
         // Draw borders
@@ -281,16 +532,24 @@ impl BoxFragment {
         let old_offset = state.offset;
         state.offset = old_offset + self.content_area.top_left();
 
+        // Column rules are painted above the borders but below the contents of the box
+        let column_rule_color = *self.style().column_rule_color();
+        for rule in &self.column_rules {
+            painter.rect(rule.offset_by(state.offset), column_rule_color.into());
+        }
+
         for child in self.children() {
             child.fill_display_list(painter, state);
         }
         state.offset = old_offset;
+
+        painter.end_fragment();
     }
 }
 
 impl TextureFragment {
-    fn fill_display_list(&self, painter: &mut Painter) {
-        painter.image(self.area, self.texture.clone());
+    fn fill_display_list(&self, painter: &mut Painter, state: &DisplayState) {
+        painter.image(self.area.offset_by(state.offset), self.texture.clone());
     }
 }
 
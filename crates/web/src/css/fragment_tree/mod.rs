@@ -15,7 +15,9 @@ use super::{
 #[derive(Clone, Copy, Debug)]
 struct DisplayState {
     has_seen_background_on_html_element: bool,
-    viewport: Size<Pixels>,
+
+    /// The area that is covered by the background of the root element
+    canvas: Size<Pixels>,
     offset: math::Vec2D<Pixels>,
 }
 
@@ -39,10 +41,51 @@ impl FragmentTree {
             .next()
     }
 
-    pub fn fill_display_list(&self, painter: &mut Painter, viewport: Size<Pixels>) {
+    /// Call `visitor` for every box fragment, together with its border area in page coordinates
+    pub fn for_each_box<'a, F>(&'a self, mut visitor: F)
+    where
+        F: FnMut(&'a Fragment, math::Rectangle<Pixels>),
+    {
+        for fragment in &self.root_fragments {
+            fragment.visit_boxes(math::Vec2D::new(Pixels::ZERO, Pixels::ZERO), &mut visitor);
+        }
+    }
+
+    /// The size of the area covered by all boxes, which is the area that the viewport can be scrolled within
+    ///
+    /// <https://drafts.csswg.org/css-overflow/#scrollable-overflow-region>
+    #[must_use]
+    pub fn scrollable_overflow(&self) -> Size<Pixels> {
+        let mut overflow = Size {
+            width: Pixels::ZERO,
+            height: Pixels::ZERO,
+        };
+
+        self.for_each_box(|_, area| {
+            let bottom_right = area.bottom_right();
+            overflow.width = overflow.width.max(bottom_right.x);
+            overflow.height = overflow.height.max(bottom_right.y);
+        });
+
+        overflow
+    }
+
+    /// Choose where a document that is printed on pages of the given height continues on the next page
+    ///
+    /// Returns the vertical position at which each page starts, see [FragmentationContext::breaks].
+    #[must_use]
+    pub fn page_breaks(&self, page_height: Pixels) -> Vec<Pixels> {
+        FragmentationContext::new(&self.root_fragments, self.scrollable_overflow().height)
+            .breaks(page_height)
+    }
+
+    /// Paint the fragments in page coordinates
+    ///
+    /// The background of the root element covers the whole `canvas`.
+    pub fn fill_display_list(&self, painter: &mut Painter, canvas: Size<Pixels>) {
         let mut state = DisplayState {
             has_seen_background_on_html_element: false,
-            viewport,
+            canvas,
             offset: math::Vec2D::new(Pixels::ZERO, Pixels::ZERO),
         };
 
@@ -51,3 +94,167 @@ impl FragmentTree {
         }
     }
 }
+
+/// Content that was laid out in one piece and is split across a series of fragmentainers,
+/// like the pages of a printed document or the columns of a multi-column container
+///
+/// <https://drafts.csswg.org/css-break/#fragmentation-context>
+#[derive(Clone, Debug)]
+pub(crate) struct FragmentationContext {
+    /// The areas of all text and images, which should not be split
+    unbreakable_areas: Vec<math::Rectangle<Pixels>>,
+
+    content_height: Pixels,
+}
+
+impl FragmentationContext {
+    #[must_use]
+    pub fn new(fragments: &[Fragment], content_height: Pixels) -> Self {
+        let mut unbreakable_areas = vec![];
+        for fragment in fragments {
+            fragment.collect_unbreakable_areas(
+                math::Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+                &mut unbreakable_areas,
+            );
+        }
+
+        Self {
+            unbreakable_areas,
+            content_height,
+        }
+    }
+
+    /// Choose where the content continues in the next fragmentainer, if all of them have the given height
+    ///
+    /// Returns the vertical position at which each fragmentainer starts, the first one always starts at zero.
+    /// The content breaks between lines of text and images, unless one of them is taller than a fragmentainer.
+    /// Boxes are split wherever a fragmentainer ends.
+    ///
+    /// <https://drafts.csswg.org/css-break/#breaking-rules>
+    #[must_use]
+    pub fn breaks(&self, fragmentainer_height: Pixels) -> Vec<Pixels> {
+        // Every fragmentainer must make progress
+        let fragmentainer_height = fragmentainer_height.max(Pixels(1.));
+
+        let mut breaks = vec![Pixels::ZERO];
+        let mut start = Pixels::ZERO;
+        while start + fragmentainer_height < self.content_height {
+            let mut end = start + fragmentainer_height;
+
+            // Move the end of the fragmentainer up until it does not cut through any text or images.
+            // Areas that start at the top of the fragmentainer are cut anyways, they would not fit into any of them.
+            while let Some(top) = self
+                .unbreakable_areas
+                .iter()
+                .filter(|area| {
+                    start < area.top_left().y
+                        && area.top_left().y < end
+                        && end < area.bottom_right().y
+                })
+                .map(|area| area.top_left().y)
+                .min()
+            {
+                end = top;
+            }
+
+            breaks.push(end);
+            start = end;
+        }
+
+        breaks
+    }
+
+    /// Split the fragments at the given breaks, as returned by [breaks](Self::breaks)
+    ///
+    /// Each of the returned lists of fragments is positioned relative to the top of its fragmentainer.
+    /// Content above the first or below the last fragmentainer ends up in the first or last one respectively.
+    #[must_use]
+    pub fn split(fragments: &[Fragment], breaks: &[Pixels]) -> Vec<Vec<Fragment>> {
+        breaks
+            .iter()
+            .enumerate()
+            .map(|(index, &fragmentainer_start)| {
+                let start = if index == 0 {
+                    -Pixels::INFINITY
+                } else {
+                    fragmentainer_start
+                };
+                let end = breaks.get(index + 1).copied().unwrap_or(Pixels::INFINITY);
+                let offset = math::Vec2D::new(Pixels::ZERO, -fragmentainer_start);
+
+                fragments
+                    .iter()
+                    .filter_map(|fragment| fragment.slice(start, end))
+                    .map(|mut fragment| {
+                        fragment.translate(offset);
+                        fragment
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use image::Texture;
+    use math::{Rectangle, Vec2D};
+
+    use super::*;
+    use crate::css::{layout::Sides, ComputedStyle};
+
+    fn image(top: f32) -> Fragment {
+        TextureFragment {
+            texture: Arc::new(Texture::new(1, 1)),
+            area: Rectangle::from_position_and_size(
+                Vec2D::new(Pixels::ZERO, Pixels(top)),
+                Pixels(10.),
+                Pixels(30.),
+            ),
+        }
+        .into()
+    }
+
+    #[test]
+    fn split_between_images() {
+        // A box with 10px of padding around three images that are 30px tall
+        let content_area = Rectangle::from_position_and_size(
+            Vec2D::new(Pixels(10.), Pixels(10.)),
+            Pixels(10.),
+            Pixels(90.),
+        );
+        let padding_area = Sides::all(Pixels(10.)).surround(content_area);
+        let container = BoxFragment::new(
+            None,
+            ComputedStyle::default(),
+            padding_area,
+            Sides::all(Pixels::ZERO),
+            padding_area,
+            content_area,
+            vec![image(0.), image(30.), image(60.)],
+        );
+        let fragments = [Fragment::Box(container)];
+
+        // Fragmentainers end above the image they would cut through
+        let breaks = FragmentationContext::new(&fragments, Pixels(110.)).breaks(Pixels(50.));
+        assert_eq!(breaks, [Pixels::ZERO, Pixels(40.), Pixels(70.)]);
+
+        let fragmentainers = FragmentationContext::split(&fragments, &breaks);
+        assert_eq!(fragmentainers.len(), 3);
+
+        let heights: Vec<Pixels> = fragmentainers
+            .iter()
+            .map(|fragmentainer| {
+                let [Fragment::Box(box_fragment)] = fragmentainer.as_slice() else {
+                    panic!("every fragmentainer should contain a part of the box");
+                };
+                assert_eq!(box_fragment.margin_area().top_left().y, Pixels::ZERO);
+                assert_eq!(box_fragment.children().len(), 1);
+                box_fragment.margin_area().height()
+            })
+            .collect();
+        assert_eq!(heights, [Pixels(40.), Pixels(30.), Pixels(40.)]);
+    }
+}
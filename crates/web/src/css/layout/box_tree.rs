@@ -1,34 +1,128 @@
 use std::fmt;
 
 use math::Vec2D;
+use sl_std::arena::Arena;
 
 use crate::{
     css::{
-        computed_style::ComputedStyle,
         fragment_tree::FragmentTree,
-        layout::{ContainingBlock, Pixels, Size},
+        layout::{ContainingBlock, LayoutCache, Pixels, Size},
         StyleComputer,
     },
     dom::{dom_objects, DomPtr},
     TreeDebug, TreeFormatter,
 };
 
-use super::flow::{BlockContainerBuilder, BlockFormattingContext};
+use super::{
+    flow::{
+        BlockContainerBuilder, BlockFormattingContext, BlockLevelBox, InlineLevelBox, RubySegment,
+    },
+    mathml::MathBox,
+};
+
+/// Owns the boxes of a [BoxTree]
+///
+/// Layout creates thousands of small boxes, so instead of allocating each list of
+/// children on its own they are placed next to each other in an arena that lives
+/// for the duration of a single layout pass.
+///
+/// The fragment tree outlives the layout pass (it is needed for painting and hit testing),
+/// so fragments are not allocated in the arena.
+pub struct BoxTreeArena<'box_tree> {
+    block_level_boxes: Arena<BlockLevelBox<'box_tree>>,
+    inline_level_boxes: Arena<InlineLevelBox<'box_tree>>,
+    ruby_segments: Arena<RubySegment<'box_tree>>,
+    math_boxes: Arena<MathBox<'box_tree>>,
+
+    /// Results from previous layout passes, which outlive the arena
+    layout_cache: &'box_tree LayoutCache,
+}
+
+impl<'box_tree> BoxTreeArena<'box_tree> {
+    #[must_use]
+    pub fn new(layout_cache: &'box_tree LayoutCache) -> Self {
+        Self {
+            block_level_boxes: Arena::default(),
+            inline_level_boxes: Arena::default(),
+            ruby_segments: Arena::default(),
+            math_boxes: Arena::default(),
+            layout_cache,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn layout_cache(&self) -> &'box_tree LayoutCache {
+        self.layout_cache
+    }
+
+    pub(crate) fn alloc_block_level_boxes(
+        &'box_tree self,
+        boxes: Vec<BlockLevelBox<'box_tree>>,
+    ) -> &'box_tree [BlockLevelBox<'box_tree>] {
+        self.block_level_boxes.alloc_extend(boxes)
+    }
+
+    pub(crate) fn alloc_inline_level_boxes(
+        &'box_tree self,
+        boxes: Vec<InlineLevelBox<'box_tree>>,
+    ) -> &'box_tree [InlineLevelBox<'box_tree>] {
+        self.inline_level_boxes.alloc_extend(boxes)
+    }
+
+    pub(crate) fn alloc_ruby_segments(
+        &'box_tree self,
+        segments: Vec<RubySegment<'box_tree>>,
+    ) -> &'box_tree [RubySegment<'box_tree>] {
+        self.ruby_segments.alloc_extend(segments)
+    }
+
+    pub(crate) fn alloc_math_boxes(
+        &'box_tree self,
+        boxes: Vec<MathBox<'box_tree>>,
+    ) -> &'box_tree [MathBox<'box_tree>] {
+        self.math_boxes.alloc_extend(boxes)
+    }
+
+    /// The total number of boxes in the arena
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.block_level_boxes.len()
+            + self.inline_level_boxes.len()
+            + self.ruby_segments.len()
+            + self.math_boxes.len()
+    }
+
+    /// The number of bytes reserved by the arena
+    #[must_use]
+    pub fn size_in_bytes(&self) -> usize {
+        self.block_level_boxes.size_in_bytes()
+            + self.inline_level_boxes.size_in_bytes()
+            + self.ruby_segments.size_in_bytes()
+            + self.math_boxes.size_in_bytes()
+    }
+}
 
 #[derive(Clone)]
-pub struct BoxTree {
+pub struct BoxTree<'box_tree> {
     /// The root box acts like it's inside a [BlockFormattingContext](super::flow::BlockFormattingContext), except
     /// that the formatting context always only contains a single element (said root box) and the size of
     /// the root box is always equivalent to the viewport.
     ///
     /// There might be *no* root boxes if the root element has `display: none;`
     // FIXME: can there be more than one root element?
-    root: BlockFormattingContext,
+    root: BlockFormattingContext<'box_tree>,
 }
 
-impl BoxTree {
+impl<'box_tree> BoxTree<'box_tree> {
     #[must_use]
-    pub fn new(document: DomPtr<dom_objects::Document>, style_computer: StyleComputer<'_>) -> Self {
+    pub fn new(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
+        document: DomPtr<dom_objects::Document>,
+        style_computer: StyleComputer<'_>,
+    ) -> Self {
+        // Building the box tree is where elements get their computed style
+        let _span = trace::span!(Style, "build box tree");
+
         let html = document
             .borrow()
             .children()
@@ -37,10 +131,10 @@ impl BoxTree {
             .try_into_type::<dom_objects::HtmlHtmlElement>()
             .expect("expected root element to be html element");
 
-        let parent_style = ComputedStyle::default();
+        let parent_style = style_computer.initial_style();
         let element_style = style_computer.get_computed_style(html.clone().upcast(), &parent_style);
 
-        let mut container = BlockContainerBuilder::new(&parent_style, style_computer);
+        let mut container = BlockContainerBuilder::new(arena, &parent_style, style_computer);
 
         container.handle_element(html.upcast(), element_style);
 
@@ -52,6 +146,8 @@ impl BoxTree {
     }
 
     pub fn compute_fragments(&self, viewport: Size<Pixels>) -> FragmentTree {
+        let _span = trace::span!(Layout, "compute fragments");
+
         // The initial containing block always has the size of the viewport
         let initial_containing_block =
             ContainingBlock::new(viewport.width, Vec2D::new(Pixels::ZERO, Pixels::ZERO))
@@ -62,7 +158,7 @@ impl BoxTree {
     }
 }
 
-impl fmt::Debug for BoxTree {
+impl<'box_tree> fmt::Debug for BoxTree<'box_tree> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut tree_formatter = TreeFormatter::new(f);
         self.root.tree_fmt(&mut tree_formatter)
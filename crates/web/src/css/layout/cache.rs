@@ -0,0 +1,211 @@
+//! Layout results that are reused across layout passes
+//!
+//! The box tree is rebuilt during every layout pass, but laying out the contents of an
+//! independent formatting context is only repeated if something inside it changed.
+//! Results are discarded once the element [needs layout](dom_objects::Element::needs_layout),
+//! its computed style changes or it is laid out in a different containing block.
+//!
+//! Only the style of the element that established the formatting context is compared.
+//! Changes that affect the styles of its descendants without marking them as needing
+//! layout, like a different media environment, must [clear](LayoutCache::clear) the cache.
+//!
+//! Note that the style pass and the box tree are not incremental: every layout pass starts at
+//! the root element, only the layout of unchanged formatting contexts is skipped.
+
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use crate::{
+    css::ComputedStyle,
+    dom::{dom_objects, DomPtr},
+};
+
+use super::{flow::ContentLayoutInfo, sizing::ContentSizes, ContainingBlock};
+
+/// The cached layout results of all independent formatting contexts on a page
+#[derive(Default)]
+pub struct LayoutCache {
+    /// Keyed by the address of the element that established the formatting context
+    entries: RefCell<HashMap<usize, CacheEntry>>,
+}
+
+struct CacheEntry {
+    /// Keeps the element alive, so its address is not reused by another element
+    _element: DomPtr<dom_objects::Element>,
+
+    /// The style that the element had when the results were computed
+    style: ComputedStyle,
+
+    results: Rc<CachedLayout>,
+
+    /// Whether the element was part of the box tree during the current layout pass
+    is_in_use: bool,
+}
+
+/// The layout results of a single independent formatting context
+#[derive(Default)]
+pub(crate) struct CachedLayout {
+    content_sizes: OnceCell<ContentSizes>,
+
+    /// The most recent layout, together with the containing block it was computed in
+    layout: RefCell<Option<(ContainingBlock, ContentLayoutInfo)>>,
+}
+
+impl LayoutCache {
+    /// Get the results for the formatting context established by `element`
+    ///
+    /// If the results can't be reused, they are replaced by an empty set of results.
+    pub(crate) fn get(
+        &self,
+        element: &DomPtr<dom_objects::Element>,
+        style: &ComputedStyle,
+    ) -> Rc<CachedLayout> {
+        let key = element.as_ptr() as usize;
+        let needs_layout = element.borrow().needs_layout();
+
+        let mut entries = self.entries.borrow_mut();
+        match entries.get_mut(&key) {
+            Some(entry) if !needs_layout && entry.style == *style => {
+                entry.is_in_use = true;
+                entry.results.clone()
+            },
+            _ => {
+                let results = Rc::new(CachedLayout::default());
+                let entry = CacheEntry {
+                    _element: element.clone(),
+                    style: style.clone(),
+                    results: results.clone(),
+                    is_in_use: true,
+                };
+                entries.insert(key, entry);
+                results
+            },
+        }
+    }
+
+    /// Drop all results, because the styles of any element might have changed
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Drop the results of all formatting contexts that were not part of the most recent box tree
+    pub fn finish_pass(&self) {
+        self.entries.borrow_mut().retain(|_, entry| {
+            let is_in_use = entry.is_in_use;
+            entry.is_in_use = false;
+            is_in_use
+        });
+    }
+}
+
+impl CachedLayout {
+    pub(crate) fn content_sizes<F>(&self, compute: F) -> ContentSizes
+    where
+        F: FnOnce() -> ContentSizes,
+    {
+        *self.content_sizes.get_or_init(compute)
+    }
+
+    /// Lay out the formatting context, unless it was already laid out in the same containing block
+    pub(crate) fn layout<F>(
+        &self,
+        containing_block: ContainingBlock,
+        compute: F,
+    ) -> ContentLayoutInfo
+    where
+        F: FnOnce() -> ContentLayoutInfo,
+    {
+        if let Some((cached_containing_block, layout_info)) = &*self.layout.borrow()
+            && *cached_containing_block == containing_block
+        {
+            return layout_info.clone();
+        }
+
+        let layout_info = compute();
+        *self.layout.borrow_mut() = Some((containing_block, layout_info.clone()));
+        layout_info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use math::Vec2D;
+
+    use super::*;
+    use crate::css::layout::Pixels;
+
+    fn layout_with_height(height: f32) -> ContentLayoutInfo {
+        ContentLayoutInfo {
+            height: Pixels(height),
+            fragments: vec![],
+            has_in_flow_content: false,
+        }
+    }
+
+    #[test]
+    fn reuse_results_until_element_changes() {
+        let cache = LayoutCache::default();
+        let element = DomPtr::new(dom_objects::Element::default());
+        let style = ComputedStyle::default();
+        let containing_block =
+            ContainingBlock::new(Pixels(100.), Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+
+        let results = cache.get(&element, &style);
+        results.layout(containing_block, || layout_with_height(10.));
+        cache.finish_pass();
+
+        // Nothing changed, so the previous layout is reused
+        let results = cache.get(&element, &style);
+        let layout = results.layout(containing_block, || unreachable!());
+        assert_eq!(layout.height, Pixels(10.));
+        cache.finish_pass();
+
+        // The contents of the element changed
+        dom_objects::Element::mark_needs_layout(element.clone().upcast());
+        let results = cache.get(&element, &style);
+        let layout = results.layout(containing_block, || layout_with_height(20.));
+        assert_eq!(layout.height, Pixels(20.));
+
+        // The element is laid out in a different containing block
+        let wider_containing_block =
+            ContainingBlock::new(Pixels(200.), Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+        let layout = results.layout(wider_containing_block, || layout_with_height(5.));
+        assert_eq!(layout.height, Pixels(5.));
+    }
+
+    #[test]
+    fn drop_results_of_removed_elements() {
+        let cache = LayoutCache::default();
+        let element = DomPtr::new(dom_objects::Element::default());
+        cache.get(&element, &ComputedStyle::default());
+
+        cache.finish_pass();
+        assert_eq!(cache.entries.borrow().len(), 1);
+
+        // The element was not part of the box tree during this pass
+        cache.finish_pass();
+        assert!(cache.entries.borrow().is_empty());
+    }
+
+    #[test]
+    fn clearing_discards_results() {
+        let cache = LayoutCache::default();
+        let element = DomPtr::new(dom_objects::Element::default());
+        let style = ComputedStyle::default();
+        let containing_block =
+            ContainingBlock::new(Pixels(100.), Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+
+        let results = cache.get(&element, &style);
+        results.layout(containing_block, || layout_with_height(10.));
+        cache.finish_pass();
+
+        // The style of a descendant might have changed without the element noticing
+        cache.clear();
+        let results = cache.get(&element, &style);
+        let layout = results.layout(containing_block, || layout_with_height(20.));
+        assert_eq!(layout.height, Pixels(20.));
+    }
+}
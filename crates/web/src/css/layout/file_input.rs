@@ -0,0 +1,114 @@
+//! Rendering of `<input type=file>` elements
+//!
+//! File upload controls are rendered like replaced elements: A button that opens the file chooser,
+//! followed by a label that describes the selected files.
+//!
+//! <https://html.spec.whatwg.org/multipage/rendering.html#the-input-element-as-a-file-upload-control>
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment},
+        style::specified::BackgroundColor,
+        values::Color,
+        ComputedStyle,
+    },
+    dom::{dom_objects, DomPtr},
+};
+
+use super::{form_control::FormControl, replaced::IntrinsicSize, Pixels, Sides, Size};
+
+/// Space between the border of the button and its label, and between the button and the file name
+const PADDING: Pixels = Pixels(4.);
+
+const BUTTON_LABEL: &str = "Browse…";
+
+/// The background of the button that opens the file chooser
+const BUTTON_BACKGROUND: Color = Color::rgb(0xe9, 0xe9, 0xed);
+
+/// The content of a `<input type=file>` element, at the time that it was laid out
+#[derive(Clone, Debug)]
+pub(crate) struct FileInputControl {
+    control: FormControl,
+
+    /// Describes the selected files, like "No file selected."
+    label: String,
+}
+
+impl FileInputControl {
+    #[must_use]
+    pub fn new(input: DomPtr<dom_objects::HtmlInputElement>, style: ComputedStyle) -> Self {
+        let label = {
+            let input = input.borrow();
+            let files = input.files().cloned().unwrap_or_default();
+            match files.length() {
+                0 if input.is_multiple() => "No files selected.".to_string(),
+                0 => "No file selected.".to_string(),
+                1 => files.item(0).expect("list has one file").name().to_string(),
+                n => format!("{n} files selected."),
+            }
+        };
+
+        Self {
+            control: FormControl::new(input.upcast(), style),
+            label,
+        }
+    }
+
+    #[must_use]
+    fn button_width(&self) -> Pixels {
+        self.control.font_metrics.width_of(BUTTON_LABEL) + PADDING * 2.
+    }
+
+    /// The size of the control, including its borders
+    #[must_use]
+    pub fn intrinsic_size(&self) -> IntrinsicSize {
+        let content_width =
+            self.button_width() + PADDING + self.control.font_metrics.width_of(&self.label);
+        let content_height = self.control.line_height() + PADDING;
+
+        self.control.intrinsic_size(content_width, content_height)
+    }
+
+    /// Create a fragment for the control, the borders of the element are drawn inside the given area
+    #[must_use]
+    pub fn create_fragment(&self, position: Vec2D<Pixels>, size: Size<Pixels>) -> Fragment {
+        self.control
+            .create_fragment(position, size, |content_area| self.children(content_area))
+    }
+
+    /// The fragments inside the control, relative to its content area
+    #[must_use]
+    fn children(&self, content_area: Rectangle<Pixels>) -> Vec<Fragment> {
+        let color = *self.control.style.color();
+        let text_top = (content_area.height() - self.control.line_height()) / 2.;
+
+        // Clicking anywhere on the control opens the file chooser, so the button belongs to the element too
+        let mut button_style = ComputedStyle::default();
+        button_style.set_background_color(BackgroundColor::Color(BUTTON_BACKGROUND));
+        let button_area = Rectangle::from_position_and_size(
+            Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+            self.button_width(),
+            content_area.height(),
+        );
+        let button = BoxFragment::new(
+            Some(self.control.node.clone()),
+            button_style,
+            button_area,
+            Sides::all(Pixels::ZERO),
+            button_area,
+            button_area,
+            vec![self
+                .control
+                .text_fragment(BUTTON_LABEL, Vec2D::new(PADDING, text_top), color)],
+        );
+
+        let label_left = self.button_width() + PADDING;
+        vec![
+            button.into(),
+            self.control
+                .text_fragment(&self.label, Vec2D::new(label_left, text_top), color),
+        ]
+    }
+}
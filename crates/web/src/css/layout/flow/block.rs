@@ -1,11 +1,15 @@
-use std::{fmt, fmt::Write};
+use std::{cell::OnceCell, fmt, fmt::Write, rc::Rc};
 
 use math::{Rectangle, Vec2D};
 
 use crate::{
     css::{
         fragment_tree::{BoxFragment, Fragment},
-        layout::{replaced::ReplacedElement, ContainingBlock, Pixels, Sides},
+        layout::{
+            replaced::ReplacedElement,
+            sizing::{resolve_preferred_size, ContentSizes},
+            BoxTreeArena, CachedLayout, ContainingBlock, Pixels, Sides,
+        },
         style::{
             computed::{Clear, Margin, Padding},
             specified::DisplayInside,
@@ -18,8 +22,8 @@ use crate::{
 };
 
 use super::{
-    positioning::AbsolutelyPositionedBox, BlockContainerBuilder, FloatContext, FloatingBox,
-    InlineFormattingContext,
+    multicol::ColumnLayout, positioning::AbsolutelyPositionedBox, BlockContainerBuilder,
+    FloatContext, FloatingBox, InlineFormattingContext,
 };
 
 /// <https://drafts.csswg.org/css2/#block-formatting>
@@ -57,49 +61,73 @@ impl BlockFormattingContextState {
 }
 
 #[derive(Clone)]
-pub struct BlockFormattingContext {
-    contents: BlockContainer,
+pub struct BlockFormattingContext<'box_tree> {
+    contents: BlockContainer<'box_tree>,
+
+    /// Results of [content_sizes](Self::content_sizes) and [layout](Self::layout)
+    ///
+    /// For formatting contexts that are established by an element, these are reused
+    /// across layout passes until the element [needs layout](dom_objects::Element::needs_layout).
+    cached_layout: Rc<CachedLayout>,
 }
 
-impl BlockFormattingContext {
+impl<'box_tree> BlockFormattingContext<'box_tree> {
     #[must_use]
     pub fn build(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
         element: DomPtr<dom_objects::Element>,
         element_style: ComputedStyle,
         display_inside: DisplayInside,
         style_computer: StyleComputer<'_>,
     ) -> Self {
+        // This must happen before the contents are built, because building them
+        // clears the layout flag of the element
+        let cached_layout = arena.layout_cache().get(&element, &element_style);
+
         let contents = BlockContainerBuilder::build(
+            arena,
             element.upcast(),
             style_computer,
             &element_style,
             display_inside,
         );
 
-        Self { contents }
+        Self {
+            contents,
+            cached_layout,
+        }
     }
 
     #[must_use]
     pub fn layout(&self, containing_block: ContainingBlock) -> ContentLayoutInfo {
-        let mut formatting_context_state = BlockFormattingContextState::new(containing_block);
+        self.cached_layout.layout(containing_block, || {
+            let mut formatting_context_state = BlockFormattingContextState::new(containing_block);
 
-        self.contents
-            .layout(containing_block, &mut formatting_context_state)
+            self.contents
+                .layout(containing_block, &mut formatting_context_state)
+        })
+    }
+
+    /// <https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes>
+    #[must_use]
+    pub(crate) fn content_sizes(&self) -> ContentSizes {
+        self.cached_layout
+            .content_sizes(|| self.contents.content_sizes())
     }
 }
 
 /// A Box that participates in a [BlockFormattingContext]
 /// <https://drafts.csswg.org/css2/#block-level-boxes>
 #[derive(Clone)]
-pub(crate) enum BlockLevelBox {
-    Floating(FloatingBox),
-    InFlow(InFlowBlockBox),
-    AbsolutelyPositioned(AbsolutelyPositionedBox),
+pub(crate) enum BlockLevelBox<'box_tree> {
+    Floating(FloatingBox<'box_tree>),
+    InFlow(InFlowBlockBox<'box_tree>),
+    AbsolutelyPositioned(AbsolutelyPositionedBox<'box_tree>),
     Replaced(ReplacedElement),
 }
 
 #[derive(Clone)]
-pub struct InFlowBlockBox {
+pub struct InFlowBlockBox<'box_tree> {
     style: ComputedStyle,
 
     /// The DOM element that produced this box.
@@ -108,35 +136,41 @@ pub struct InFlowBlockBox {
     node: Option<DomPtr<dom_objects::Node>>,
 
     /// Boxes contained by this box
-    contents: BlockContainer,
+    contents: BlockContainer<'box_tree>,
+
+    /// Cached result of [content_sizes](Self::content_sizes)
+    content_sizes: OnceCell<ContentSizes>,
 }
 
 /// Elements contained in a [BlockLevelBox]
 ///
 /// <https://drafts.csswg.org/css2/#block-container-box>
+///
+/// The children are allocated in the [BoxTreeArena] of the current layout pass.
 #[derive(Clone)]
-pub enum BlockContainer {
-    BlockLevelBoxes(Vec<BlockLevelBox>),
-    InlineFormattingContext(InlineFormattingContext),
+pub enum BlockContainer<'box_tree> {
+    BlockLevelBoxes(&'box_tree [BlockLevelBox<'box_tree>]),
+    InlineFormattingContext(InlineFormattingContext<'box_tree>),
 }
 
-impl Default for BlockContainer {
+impl<'box_tree> Default for BlockContainer<'box_tree> {
     fn default() -> Self {
-        Self::InlineFormattingContext(vec![].into())
+        Self::InlineFormattingContext(InlineFormattingContext::default())
     }
 }
 
-impl InFlowBlockBox {
+impl<'box_tree> InFlowBlockBox<'box_tree> {
     #[must_use]
     pub const fn new(
         style: ComputedStyle,
         node: Option<DomPtr<dom_objects::Node>>,
-        contents: BlockContainer,
+        contents: BlockContainer<'box_tree>,
     ) -> Self {
         Self {
             style,
             node,
             contents,
+            content_sizes: OnceCell::new(),
         }
     }
 
@@ -147,12 +181,27 @@ impl InFlowBlockBox {
     }
 
     #[must_use]
-    pub fn create_anonymous_box(contents: BlockContainer, parent_style: &ComputedStyle) -> Self {
-        Self {
-            style: parent_style.get_inherited(),
-            node: None,
-            contents,
-        }
+    pub fn create_anonymous_box(
+        contents: BlockContainer<'box_tree>,
+        parent_style: &ComputedStyle,
+    ) -> Self {
+        Self::new(parent_style.get_inherited(), None, contents)
+    }
+
+    /// <https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes>
+    ///
+    /// The result is cached, as computing content sizes requires a traversal of the
+    /// entire subtree.
+    #[must_use]
+    fn content_sizes(&self) -> ContentSizes {
+        *self.content_sizes.get_or_init(|| {
+            let content_sizes = self.contents.content_sizes();
+            if self.style.is_multi_column_container() {
+                ColumnLayout::content_sizes(&self.style, content_sizes)
+            } else {
+                content_sizes
+            }
+        })
     }
 
     /// Compute layout for this block box, turning it into a fragment
@@ -165,7 +214,8 @@ impl InFlowBlockBox {
         containing_block: ContainingBlock,
         formatting_context: &mut BlockFormattingContextState,
     ) -> BoxFragment {
-        let mut dimensions = BlockDimensions::compute(self.style(), containing_block);
+        let mut dimensions =
+            BlockDimensions::compute(self.style(), containing_block, || self.content_sizes());
 
         // Possibly collapse top margin
         dimensions.margin.top = formatting_context.get_collapsed_margin(dimensions.margin.top);
@@ -187,10 +237,26 @@ impl InFlowBlockBox {
             .float_context
             .lower_float_ceiling(position_relative_to_formatting_context_root.y);
 
-        let content_info = self.contents.layout(
-            dimensions.as_containing_block(position_relative_to_formatting_context_root),
-            formatting_context,
-        );
+        let containing_block_for_contents =
+            dimensions.as_containing_block(position_relative_to_formatting_context_root);
+        let (content_info, column_rules) = if self.style.is_multi_column_container() {
+            let columns = ColumnLayout::compute(self.style(), dimensions.width);
+            let (content_info, column_rules) =
+                columns.layout(self.style(), &self.contents, containing_block_for_contents);
+
+            // The contents are in a formatting context of their own, so their margins
+            // never collapse with ours
+            if content_info.has_in_flow_content {
+                formatting_context.prevent_margin_collapse();
+            }
+
+            (content_info, column_rules)
+        } else {
+            let content_info = self
+                .contents
+                .layout(containing_block_for_contents, formatting_context);
+            (content_info, vec![])
+        };
 
         // If the content did not contain any in-flow elements *but* it has a nonzero
         // height anyways then it does prevent the top and bottom margins from collapsing
@@ -234,28 +300,29 @@ impl InFlowBlockBox {
             content_area,
             content_info.fragments,
         )
+        .with_column_rules(column_rules)
     }
 }
 
-impl From<FloatingBox> for BlockLevelBox {
-    fn from(value: FloatingBox) -> Self {
+impl<'box_tree> From<FloatingBox<'box_tree>> for BlockLevelBox<'box_tree> {
+    fn from(value: FloatingBox<'box_tree>) -> Self {
         Self::Floating(value)
     }
 }
 
-impl From<InFlowBlockBox> for BlockLevelBox {
-    fn from(value: InFlowBlockBox) -> Self {
+impl<'box_tree> From<InFlowBlockBox<'box_tree>> for BlockLevelBox<'box_tree> {
+    fn from(value: InFlowBlockBox<'box_tree>) -> Self {
         Self::InFlow(value)
     }
 }
 
-impl From<AbsolutelyPositionedBox> for BlockLevelBox {
-    fn from(value: AbsolutelyPositionedBox) -> Self {
+impl<'box_tree> From<AbsolutelyPositionedBox<'box_tree>> for BlockLevelBox<'box_tree> {
+    fn from(value: AbsolutelyPositionedBox<'box_tree>) -> Self {
         Self::AbsolutelyPositioned(value)
     }
 }
 
-impl From<ReplacedElement> for BlockLevelBox {
+impl<'box_tree> From<ReplacedElement> for BlockLevelBox<'box_tree> {
     fn from(value: ReplacedElement) -> Self {
         Self::Replaced(value)
     }
@@ -268,17 +335,53 @@ pub(crate) struct ContentLayoutInfo {
     pub has_in_flow_content: bool,
 }
 
-impl BlockContainer {
+impl<'box_tree> BlockLevelBox<'box_tree> {
+    /// The contribution of this box to the content sizes of its parent
+    ///
+    /// <https://drafts.csswg.org/css-sizing-3/#contributions>
+    #[must_use]
+    fn outer_content_sizes(&self) -> ContentSizes {
+        match self {
+            // FIXME: Floats can be placed next to each other, which is not considered here
+            Self::Floating(float_box) => float_box
+                .contents
+                .content_sizes()
+                .outer_contribution(&float_box.style),
+            Self::InFlow(in_flow_box) => in_flow_box
+                .content_sizes()
+                .outer_contribution(&in_flow_box.style),
+            // Absolutely positioned boxes are out-of-flow and don't affect the size of their parent
+            Self::AbsolutelyPositioned(_) => ContentSizes::default(),
+            Self::Replaced(replaced_element) => replaced_element.content_sizes(),
+        }
+    }
+}
+
+impl<'box_tree> BlockContainer<'box_tree> {
+    /// <https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes>
+    #[must_use]
+    pub(crate) fn content_sizes(&self) -> ContentSizes {
+        match self {
+            Self::BlockLevelBoxes(block_level_boxes) => block_level_boxes
+                .iter()
+                .map(BlockLevelBox::outer_content_sizes)
+                .fold(ContentSizes::default(), ContentSizes::max),
+            Self::InlineFormattingContext(inline_formatting_context) => {
+                inline_formatting_context.content_sizes()
+            },
+        }
+    }
+
     #[must_use]
     pub(crate) fn layout(
         &self,
         containing_block: ContainingBlock,
         formatting_context: &mut BlockFormattingContextState,
     ) -> ContentLayoutInfo {
-        match &self {
+        match self {
             Self::BlockLevelBoxes(block_level_boxes) => {
                 let mut state = BlockFlowState::new(containing_block, formatting_context);
-                for block_box in block_level_boxes {
+                for block_box in *block_level_boxes {
                     state.visit_block_box(block_box);
                 }
                 state.finish()
@@ -289,7 +392,8 @@ impl BlockContainer {
                 //        https://drafts.csswg.org/css2/#inline-formatting
                 formatting_context.prevent_margin_collapse();
 
-                let (fragments, height) = inline_formatting_context.layout(containing_block);
+                let (fragments, height) = inline_formatting_context
+                    .layout(containing_block, &formatting_context.float_context);
 
                 ContentLayoutInfo {
                     height,
@@ -312,7 +416,7 @@ pub struct BlockFlowState<'box_tree, 'formatting_context> {
 
 #[derive(Clone, Copy)]
 struct AbsoluteBoxRequiringLayout<'a> {
-    absolute_box: &'a AbsolutelyPositionedBox,
+    absolute_box: &'a AbsolutelyPositionedBox<'a>,
     static_position: Vec2D<Pixels>,
     index: usize,
 }
@@ -357,7 +461,7 @@ impl<'box_tree, 'formatting_context> BlockFlowState<'box_tree, 'formatting_conte
         }
     }
 
-    pub fn visit_block_box(&mut self, block_box: &'box_tree BlockLevelBox) {
+    pub fn visit_block_box(&mut self, block_box: &'box_tree BlockLevelBox<'box_tree>) {
         match block_box {
             BlockLevelBox::Floating(float_box) => {
                 self.respect_clearance(float_box.style.clear());
@@ -402,6 +506,9 @@ impl<'box_tree, 'formatting_context> BlockFlowState<'box_tree, 'formatting_conte
                 // To accomodate this, we keep track of the absolute boxes we found during the first
                 // pass and later insert the fragments at the correct position once the
                 // size of the containing block is known.
+                //
+                // The hypothetical box of an absolutely positioned box would be placed at
+                // the current cursor position if it was in-flow.
                 self.absolute_boxes_requiring_layout
                     .push(AbsoluteBoxRequiringLayout {
                         absolute_box,
@@ -420,6 +527,8 @@ impl<'box_tree, 'formatting_context> BlockFlowState<'box_tree, 'formatting_conte
         // elements.
         let height = self.cursor.y;
         let mut fragments = self.fragments_so_far;
+        // FIXME: The containing block of absolutely positioned boxes should be the padding box
+        //        of their nearest positioned ancestor, not the content box of their parent
         let definite_containing_block = self.containing_block.make_definite(height);
 
         for task in self.absolute_boxes_requiring_layout {
@@ -525,8 +634,16 @@ impl BlockDimensions {
     /// * https://drafts.csswg.org/css2/#normal-block
     ///
     /// This method does **not** layout the blocks contents nor does it perform margin-collapsing.
+    /// `content_sizes` is only called if the width of the box depends on its content.
     #[must_use]
-    fn compute(style: &ComputedStyle, containing_block: ContainingBlock) -> Self {
+    fn compute<F>(
+        style: &ComputedStyle,
+        containing_block: ContainingBlock,
+        content_sizes: F,
+    ) -> Self
+    where
+        F: FnOnce() -> ContentSizes,
+    {
         // NOTE: This is not a mistake, *both* horizontal and vertical margin percentages are calculated
         //       with respect to the *width* of the containing block.
         //       Refer to https://drafts.csswg.org/css2/#margin-properties
@@ -544,12 +661,22 @@ impl BlockDimensions {
 
         let border = style.used_border_widths();
 
-        // See https://drafts.csswg.org/css2/#blockwidth for a description of how the width is computed
-        let width = style.width().map(|p| p.resolve_against(available_length));
-
         let mut margin_left = resolve_margin(style.margin_left());
         let mut margin_right = resolve_margin(style.margin_right());
 
+        // See https://drafts.csswg.org/css2/#blockwidth for a description of how the width is computed
+        let available_width_for_content = containing_block.width()
+            - margin_left.unwrap_or_default()
+            - border.horizontal_sum()
+            - padding.horizontal_sum()
+            - margin_right.unwrap_or_default();
+        let width = resolve_preferred_size(
+            *style.width(),
+            available_length,
+            available_width_for_content,
+            content_sizes,
+        );
+
         // Margins are treated as zero if the total width exceeds the available width
         let total_width_is_more_than_available = |width: &Pixels| {
             let total_width = margin_left.unwrap_or_default()
@@ -631,6 +758,13 @@ impl BlockDimensions {
             }
         });
 
+        // Boxes with a preferred aspect ratio derive their automatic height from their width
+        // https://drafts.csswg.org/css-sizing-4/#aspect-ratio-automatic
+        let height = match (height, style.aspect_ratio().preferred_aspect_ratio(None)) {
+            (AutoOr::Auto, Some(aspect_ratio)) => AutoOr::NotAuto(width / aspect_ratio),
+            (height, _) => height,
+        };
+
         let margin = Sides {
             top: resolve_margin(style.margin_top()).unwrap_or_default(),
             right: margin_right,
@@ -670,7 +804,7 @@ impl BlockDimensions {
     }
 }
 
-impl TreeDebug for BlockLevelBox {
+impl<'box_tree> TreeDebug for BlockLevelBox<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
         match self {
             Self::Floating(float_box) => float_box.tree_fmt(formatter),
@@ -684,7 +818,7 @@ impl TreeDebug for BlockLevelBox {
     }
 }
 
-impl TreeDebug for InFlowBlockBox {
+impl<'box_tree> TreeDebug for InFlowBlockBox<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> std::fmt::Result {
         formatter.indent()?;
         write!(formatter, "Block Box")?;
@@ -701,11 +835,11 @@ impl TreeDebug for InFlowBlockBox {
     }
 }
 
-impl TreeDebug for BlockContainer {
+impl<'box_tree> TreeDebug for BlockContainer<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
-        match &self {
+        match self {
             Self::BlockLevelBoxes(block_level_boxes) => {
-                for block_box in block_level_boxes {
+                for block_box in *block_level_boxes {
                     block_box.tree_fmt(formatter)?;
                 }
                 Ok(())
@@ -717,14 +851,17 @@ impl TreeDebug for BlockContainer {
     }
 }
 
-impl TreeDebug for BlockFormattingContext {
+impl<'box_tree> TreeDebug for BlockFormattingContext<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
         self.contents.tree_fmt(formatter)
     }
 }
 
-impl From<BlockContainer> for BlockFormattingContext {
-    fn from(contents: BlockContainer) -> Self {
-        Self { contents }
+impl<'box_tree> From<BlockContainer<'box_tree>> for BlockFormattingContext<'box_tree> {
+    fn from(contents: BlockContainer<'box_tree>) -> Self {
+        Self {
+            contents,
+            cached_layout: Rc::default(),
+        }
     }
 }
@@ -12,60 +12,127 @@ use crate::{
                 InlineLevelBox,
             },
             formatting_context::IndependentFormattingContext,
+            mathml::{MathBox, MathFormula},
+            BoxTreeArena,
         },
-        style::specified::{Display, DisplayBox, DisplayInside, DisplayOutside},
+        style::specified::{Display, DisplayBox, DisplayInside, DisplayInternal, DisplayOutside},
         ComputedStyle, StyleComputer,
     },
     dom::{dom_objects, DomPtr},
 };
 
-use super::{float, positioning::AbsolutelyPositionedBox, TextRun};
+use super::{
+    float, positioning::AbsolutelyPositionedBox, RubyAnnotation, RubyContainer, RubySegment,
+    TextRun,
+};
 
 #[derive(Clone)]
-pub struct BlockContainerBuilder<'stylesheets, 'parent_style> {
+pub struct BlockContainerBuilder<'box_tree, 'stylesheets, 'parent_style> {
+    arena: &'box_tree BoxTreeArena<'box_tree>,
     style_computer: StyleComputer<'stylesheets>,
     style: &'parent_style ComputedStyle,
-    block_level_boxes: Vec<BlockLevelBox>,
-    current_inline_formatting_context: InlineFormattingContext,
-    inline_stack: Vec<InlineBox>,
+    block_level_boxes: Vec<BlockLevelBox<'box_tree>>,
+
+    /// The contents of the inline formatting context that is currently being built
+    current_inline_level_boxes: Vec<InlineLevelBox<'box_tree>>,
+    inline_stack: Vec<OpenInlineBox<'box_tree>>,
+}
+
+/// An [InlineBox] whose children are still being collected
+///
+/// Once the box is closed, its children are moved into the [BoxTreeArena].
+#[derive(Clone)]
+struct OpenInlineBox<'box_tree> {
+    node: DomPtr<dom_objects::Node>,
+    style: ComputedStyle,
+    contents: Vec<InlineLevelBox<'box_tree>>,
+}
+
+impl<'box_tree> OpenInlineBox<'box_tree> {
+    #[must_use]
+    fn new(node: DomPtr<dom_objects::Node>, style: ComputedStyle) -> Self {
+        Self {
+            node,
+            style,
+            contents: vec![],
+        }
+    }
+
+    fn push(&mut self, element: InlineLevelBox<'box_tree>) {
+        self.contents.push(element);
+    }
+
+    /// Create a inline box with the same style but no children
+    ///
+    /// This is necessary when an [InlineBox] needs to be split due to
+    /// a [BlockLevelBox] inside it.
+    #[must_use]
+    fn split_off(&self) -> Self {
+        Self::new(self.node.clone(), self.style.clone())
+    }
+
+    #[must_use]
+    fn finish(self, arena: &'box_tree BoxTreeArena<'box_tree>) -> InlineLevelBox<'box_tree> {
+        let contents = arena.alloc_inline_level_boxes(self.contents);
+        InlineLevelBox::InlineBox(InlineBox::new(self.node, self.style, contents))
+    }
 }
 
-impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_style> {
+impl<'box_tree, 'stylesheets, 'parent_style>
+    BlockContainerBuilder<'box_tree, 'stylesheets, 'parent_style>
+{
     #[must_use]
     pub fn new(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
         style: &'parent_style ComputedStyle,
         style_computer: StyleComputer<'stylesheets>,
     ) -> Self {
         Self {
+            arena,
             style_computer,
             style,
             block_level_boxes: Vec::new(),
-            current_inline_formatting_context: InlineFormattingContext::default(),
+            current_inline_level_boxes: Vec::new(),
             inline_stack: Vec::new(),
         }
     }
 
     #[must_use]
-    pub fn finish(mut self) -> BlockContainer {
-        if !self.current_inline_formatting_context.is_empty() {
+    pub fn finish(mut self) -> BlockContainer<'box_tree> {
+        if !self.current_inline_level_boxes.is_empty() {
             if self.block_level_boxes.is_empty() {
-                return BlockContainer::InlineFormattingContext(
-                    self.current_inline_formatting_context,
-                );
+                let elements = self
+                    .arena
+                    .alloc_inline_level_boxes(self.current_inline_level_boxes);
+                return BlockContainer::InlineFormattingContext(InlineFormattingContext::new(
+                    elements, self.style,
+                ));
             }
             self.end_inline_formatting_context();
         }
 
-        BlockContainer::BlockLevelBoxes(self.block_level_boxes)
+        BlockContainer::BlockLevelBoxes(self.arena.alloc_block_level_boxes(self.block_level_boxes))
+    }
+
+    /// Finish a builder whose contents are only allowed to be inline-level
+    #[must_use]
+    fn finish_inline(self) -> &'box_tree [InlineLevelBox<'box_tree>] {
+        if !self.block_level_boxes.is_empty() {
+            log::warn!("FIXME: Block-level boxes inside ruby are not supported");
+        }
+
+        self.arena
+            .alloc_inline_level_boxes(self.current_inline_level_boxes)
     }
 
     pub fn build(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
         node: DomPtr<dom_objects::Node>,
         style_computer: StyleComputer<'stylesheets>,
         style: &'parent_style ComputedStyle,
         display_inside: DisplayInside,
-    ) -> BlockContainer {
-        let mut builder = Self::new(style, style_computer);
+    ) -> BlockContainer<'box_tree> {
+        let mut builder = Self::new(arena, style, style_computer);
 
         if display_inside.has_list_item_flag() {
             if let Some(element) = node.try_into_type() {
@@ -88,20 +155,24 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
 
     fn traverse_subtree(&mut self, node: DomPtr<dom_objects::Node>, parent_style: &ComputedStyle) {
         for child in node.borrow().children() {
-            if let Some(element) = child.try_into_type::<dom_objects::Element>() {
-                let computed_style = self
-                    .style_computer
-                    .get_computed_style(element.clone(), parent_style);
-
-                self.handle_element(element, computed_style);
-            } else if let Some(text) = child.try_into_type::<dom_objects::Text>() {
-                // Content that would later be collapsed away according to the white-space property
-                // does not generate inline boxes
-                let text = text.borrow();
-                if text.content().contains(|c: char| !c.is_whitespace()) {
-                    let text_run = TextRun::new(text.content().to_owned(), parent_style.clone());
-                    self.push_text(text_run);
-                }
+            self.handle_child(child.clone(), parent_style);
+        }
+    }
+
+    fn handle_child(&mut self, child: DomPtr<dom_objects::Node>, parent_style: &ComputedStyle) {
+        if let Some(element) = child.try_into_type::<dom_objects::Element>() {
+            let computed_style = self
+                .style_computer
+                .get_computed_style(element.clone(), parent_style);
+
+            self.handle_element(element, computed_style);
+        } else if let Some(text) = child.try_into_type::<dom_objects::Text>() {
+            // Content that would later be collapsed away according to the white-space property
+            // does not generate inline boxes
+            let text = text.borrow();
+            if text.content().contains(|c: char| !c.is_whitespace()) {
+                let text_run = TextRun::new(text.content().to_owned(), parent_style.clone());
+                self.push_text(text_run);
             }
         }
     }
@@ -109,25 +180,47 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
     pub fn handle_element(&mut self, element: DomPtr<dom_objects::Element>, style: ComputedStyle) {
         let content = Content::for_element(element.clone(), style.clone());
 
+        // The boxes of the element are built below, so any cached results for it were either
+        // reused or replaced by now
+        let laid_out_element = element.clone();
+
         match *style.display() {
             Display::InsideOutside(inside_outside) => match inside_outside.outside {
                 DisplayOutside::RunIn | // FIXME: implement display: run-in
                 DisplayOutside::Block => self.push_block_box(element, style,inside_outside.inside, content),
+                DisplayOutside::Inline if inside_outside.inside == DisplayInside::Ruby => self.push_ruby_container(element, style),
+                DisplayOutside::Inline if inside_outside.inside == DisplayInside::Math => {
+                    let root = MathBox::build(self.arena, element, style, self.style_computer);
+                    self.push_inline_level_box(InlineLevelBox::Math(MathFormula::new(root, false)));
+                },
                 DisplayOutside::Inline => self.push_inline_box(element, style, content),
             },
+            // FIXME: Ruby-internal boxes outside of a ruby container should be wrapped in
+            //        an anonymous ruby container
+            Display::Internal(
+                DisplayInternal::RubyBase
+                | DisplayInternal::RubyText
+                | DisplayInternal::RubyBaseContainer
+                | DisplayInternal::RubyTextContainer,
+            ) => self.push_inline_box(element, style, content),
             Display::Internal(_) | // FIXME
             Display::Box(DisplayBox::None) => {
                 // This element does not generate a box
             },
-            Display::Box(DisplayBox::Contents) => self.traverse_subtree(element.upcast(), &style),
+            Display::Box(DisplayBox::Contents) => self.traverse_subtree(element.clone().upcast(), &style),
         }
+
+        laid_out_element.borrow_mut().clear_needs_layout();
     }
 
     /// Wrap the current inline formatting context in a block level box and push
     fn end_inline_formatting_context(&mut self) {
-        debug_assert!(!self.current_inline_formatting_context.is_empty());
+        debug_assert!(!self.current_inline_level_boxes.is_empty());
 
-        let formatting_context = std::mem::take(&mut self.current_inline_formatting_context);
+        let elements = self
+            .arena
+            .alloc_inline_level_boxes(std::mem::take(&mut self.current_inline_level_boxes));
+        let formatting_context = InlineFormattingContext::new(elements, self.style);
         self.block_level_boxes.push(
             InFlowBlockBox::create_anonymous_box(
                 BlockContainer::InlineFormattingContext(formatting_context),
@@ -144,7 +237,7 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
             top_box.push(text_box);
         } else {
             // inline box stack is empty
-            self.current_inline_formatting_context.push(text_box);
+            self.current_inline_level_boxes.push(text_box);
         }
     }
 
@@ -157,7 +250,7 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
         let inline_box = match content {
             Content::Element => {
                 // Create a new inline box and put it on the stack of open boxes
-                let inline_box = InlineBox::new(element.clone().upcast(), style.clone());
+                let inline_box = OpenInlineBox::new(element.clone().upcast(), style.clone());
                 self.inline_stack.push(inline_box);
 
                 // Traverse all children, they will be appended to the inline box we just created
@@ -166,11 +259,10 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
                 // Pop the inline box from the stack and append it to its parents list of children
                 // unless the stack of open inline boxes is empty, in which case this was a top level box
                 // and we append it to the ongoing inline formatting context instead
-                InlineLevelBox::InlineBox(
-                    self.inline_stack
-                        .pop()
-                        .expect("stack of open inline boxes should not be empty"),
-                )
+                self.inline_stack
+                    .pop()
+                    .expect("stack of open inline boxes should not be empty")
+                    .finish(self.arena)
             },
             Content::Replaced(replaced_element) => InlineLevelBox::Replaced(replaced_element),
             Content::PseudoElement(text) => {
@@ -179,12 +271,80 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
             },
         };
 
+        self.push_inline_level_box(inline_box);
+    }
+
+    fn push_inline_level_box(&mut self, inline_level_box: InlineLevelBox<'box_tree>) {
         if let Some(top_box) = self.inline_stack.last_mut() {
-            top_box.push(inline_box);
+            top_box.push(inline_level_box);
         } else {
             // inline box stack is empty
-            self.current_inline_formatting_context.push(inline_box);
+            self.current_inline_level_boxes.push(inline_level_box);
+        }
+    }
+
+    /// Split the children of a `display: ruby` element into ruby segments
+    ///
+    /// Every annotation (`display: ruby-text` or `ruby-text-container`) closes the segment
+    /// whose base is made up of the content before it.
+    ///
+    /// <https://drafts.csswg.org/css-ruby/#box-fixup>
+    fn push_ruby_container(&mut self, element: DomPtr<dom_objects::Element>, style: ComputedStyle) {
+        let mut segments = vec![];
+        let mut base = BlockContainerBuilder::new(self.arena, &style, self.style_computer);
+
+        for child in element.borrow().children() {
+            let Some(child_element) = child.try_into_type::<dom_objects::Element>() else {
+                base.handle_child(child.clone(), &style);
+                continue;
+            };
+
+            let child_style = self
+                .style_computer
+                .get_computed_style(child_element.clone(), &style);
+
+            match *child_style.display() {
+                Display::Internal(
+                    DisplayInternal::RubyText | DisplayInternal::RubyTextContainer,
+                ) => {
+                    let mut contents =
+                        BlockContainerBuilder::new(self.arena, &child_style, self.style_computer);
+                    contents.traverse_subtree(child_element.clone().upcast(), &child_style);
+                    child_element.borrow_mut().clear_needs_layout();
+                    let contents = contents.finish_inline();
+
+                    let annotation =
+                        RubyAnnotation::new(child_element.upcast(), child_style, contents);
+                    let finished_base = std::mem::replace(
+                        &mut base,
+                        BlockContainerBuilder::new(self.arena, &style, self.style_computer),
+                    );
+                    segments.push(RubySegment::new(
+                        finished_base.finish_inline(),
+                        Some(annotation),
+                    ));
+                },
+                Display::Internal(
+                    DisplayInternal::RubyBase | DisplayInternal::RubyBaseContainer,
+                ) => {
+                    // The base element itself does not generate a box, its contents become part
+                    // of the current base
+                    base.traverse_subtree(child_element.clone().upcast(), &child_style);
+                    child_element.borrow_mut().clear_needs_layout();
+                },
+                _ => base.handle_element(child_element, child_style),
+            }
+        }
+
+        // Content after the last annotation forms a segment without annotation
+        let trailing_base = base.finish_inline();
+        if !trailing_base.is_empty() {
+            segments.push(RubySegment::new(trailing_base, None));
         }
+
+        let segments = self.arena.alloc_ruby_segments(segments);
+        let ruby_container = RubyContainer::new(element.upcast(), style, segments);
+        self.push_inline_level_box(InlineLevelBox::Ruby(ruby_container));
     }
 
     fn push_block_box(
@@ -197,7 +357,11 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
         // Split all currently open inline boxes around the block box
         if !self.inline_stack.is_empty() {
             // Split each inline box - these will end up on the "right side" of the block box
-            let mut fragments = self.inline_stack.iter().map(InlineBox::split_off).collect();
+            let mut fragments = self
+                .inline_stack
+                .iter()
+                .map(OpenInlineBox::split_off)
+                .collect();
 
             std::mem::swap(&mut self.inline_stack, &mut fragments);
 
@@ -206,17 +370,17 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
             let left_side_box = fragments
                 .into_iter()
                 .reduce(|child_tree, mut parent| {
-                    parent.push(InlineLevelBox::InlineBox(child_tree));
+                    parent.push(child_tree.finish(self.arena));
                     parent
                 })
                 .expect("inline box stack cannot be empty");
-            self.current_inline_formatting_context
-                .push(InlineLevelBox::InlineBox(left_side_box));
+            self.current_inline_level_boxes
+                .push(left_side_box.finish(self.arena));
         }
 
         // End the current inline formatting context, as we've found a block box
         // that interrupts the sequence of inline boxes
-        if !self.current_inline_formatting_context.is_empty() {
+        if !self.current_inline_level_boxes.is_empty() {
             self.end_inline_formatting_context();
         }
 
@@ -227,6 +391,7 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
         let block_box = match (style.float().side(), is_absolutely_positioned) {
             (Some(side), _) => {
                 let content = IndependentFormattingContext::create(
+                    self.arena,
                     element.clone(),
                     self.style_computer,
                     style.clone(),
@@ -236,6 +401,7 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
             },
             (None, true) => {
                 let content = IndependentFormattingContext::create(
+                    self.arena,
                     element.clone(),
                     self.style_computer,
                     style.clone(),
@@ -250,8 +416,31 @@ impl<'stylesheets, 'parent_style> BlockContainerBuilder<'stylesheets, 'parent_st
                 .into()
             },
             (None, false) => match content {
+                Content::Element if display_inside == DisplayInside::Math => {
+                    // The formula sits on a line of its own. Its root box inherits from the
+                    // block instead of repeating its borders and backgrounds
+                    let root = MathBox::build(
+                        self.arena,
+                        element.clone(),
+                        style.get_inherited(),
+                        self.style_computer,
+                    );
+                    let formula = InlineLevelBox::Math(MathFormula::new(root, true));
+                    let contents = InlineFormattingContext::new(
+                        self.arena.alloc_inline_level_boxes(vec![formula]),
+                        &style,
+                    );
+
+                    InFlowBlockBox::new(
+                        style,
+                        Some(element.upcast()),
+                        BlockContainer::InlineFormattingContext(contents),
+                    )
+                    .into()
+                },
                 Content::Element => {
                     let content = BlockContainerBuilder::build(
+                        self.arena,
                         element.clone().upcast(),
                         self.style_computer,
                         &style,
@@ -9,7 +9,8 @@ use crate::{
         computed_style::ComputedStyle,
         fragment_tree::BoxFragment,
         layout::{
-            formatting_context::IndependentFormattingContext, ContainingBlock, Pixels, Sides, Size,
+            formatting_context::IndependentFormattingContext, sizing::resolve_preferred_size,
+            ContainingBlock, Pixels, Sides, Size,
         },
         style::{
             computed::{Margin, Padding},
@@ -21,23 +22,23 @@ use crate::{
     TreeDebug, TreeFormatter,
 };
 
-use std::{cmp, fmt, fmt::Write};
+use std::{fmt, fmt::Write};
 
 #[derive(Clone)]
-pub(crate) struct FloatingBox {
+pub(crate) struct FloatingBox<'box_tree> {
     pub node: DomPtr<dom_objects::Node>,
     pub style: ComputedStyle,
     pub side: FloatSide,
-    pub contents: IndependentFormattingContext,
+    pub contents: IndependentFormattingContext<'box_tree>,
 }
 
-impl FloatingBox {
+impl<'box_tree> FloatingBox<'box_tree> {
     #[must_use]
     pub fn new(
         node: DomPtr<dom_objects::Node>,
         style: ComputedStyle,
         side: FloatSide,
-        contents: IndependentFormattingContext,
+        contents: IndependentFormattingContext<'box_tree>,
     ) -> Self {
         Self {
             node,
@@ -77,13 +78,20 @@ impl FloatingBox {
 
         let border = self.style.used_border_widths();
 
-        let width = self
-            .style
-            .width()
-            .map(|p| p.resolve_against(available_width))
-            .unwrap_or_else(|| {
-                todo!("compute shrink-to-fit width");
-            });
+        // The width of floats is shrink-to-fit if it's not specified
+        // https://drafts.csswg.org/css2/#float-width
+        let available_width_for_content = available_width
+            - margin.horizontal_sum()
+            - border.horizontal_sum()
+            - padding.horizontal_sum();
+        let content_sizes = || self.contents.content_sizes();
+        let width = resolve_preferred_size(
+            *self.style.width(),
+            available_width,
+            available_width_for_content,
+            content_sizes,
+        )
+        .unwrap_or_else(|| content_sizes().shrink_to_fit(available_width_for_content));
 
         let height =
             self.style
@@ -170,6 +178,10 @@ pub struct FloatContext {
     containing_block: ContainingBlock,
 
     /// Describes how the available space is reduced by floating elements
+    ///
+    /// The bands are stacked vertically, so the y-coordinate of a band is the sum of the
+    /// heights of all bands above it. The last band always has infinite height and is not
+    /// occupied by any floats.
     content_bands: Vec<ContentBand>,
 
     lowest_float_left: Pixels,
@@ -215,150 +227,209 @@ impl FloatContext {
         self.float_ceiling = self.float_ceiling.max(new_ceiling)
     }
 
-    /// Place a float in a given position.
-    fn place_float(&mut self, margin_area: Size<Pixels>, side: FloatSide, placement: Placement) {
-        // Split the content band in up to three new bands
-        let old_content_band = self.content_bands.remove(placement.band_index);
-        let (new_inset_left, new_inset_right) = match side {
-            FloatSide::Left => {
-                let inset_left = Some(placement.position.x + margin_area.width);
-                (inset_left, old_content_band.inset_right)
-            },
-            FloatSide::Right => {
-                let inset_right = Some(self.containing_block.width() - placement.position.x);
-                (old_content_band.inset_left, inset_right)
-            },
-        };
+    /// Make sure that a content band starts at the given y-coordinate
+    ///
+    /// Returns the index of that band.
+    fn split_band_at(&mut self, y: Pixels) -> usize {
+        let mut band_top = Pixels::ZERO;
+        let mut index = 0;
+
+        loop {
+            let band = &mut self.content_bands[index];
+            if band_top == y {
+                return index;
+            }
 
-        let overhang = margin_area.height + placement.offset_in_band - old_content_band.height;
-        if overhang.value() < 0. {
-            // There will be a new content_band *below* the floating box that takes up the rest of
-            // vertical space from the old content band
-            let area_below = ContentBand {
-                height: old_content_band.height - placement.offset_in_band - margin_area.height,
-                inset_left: old_content_band.inset_left,
-                inset_right: old_content_band.inset_right,
-            };
-
-            self.content_bands.insert(placement.band_index, area_below);
-        } else if overhang.value() > 0. {
-            // Rule 2: Any bands *below* the current bands are reduced in size (or removed entirely if empty)
-            // since the element sticks out of the bottom of its band
-            // This also preserves the property that the y position of a band is the prefix sums of all the
-            // previous bands
-            let mut band_modify_index = placement.band_index;
-            while band_modify_index < self.content_bands.len() {
-                self.content_bands[band_modify_index].height -= overhang;
-
-                if self.content_bands[band_modify_index].height <= Pixels::ZERO {
-                    // Remove it
-                    self.content_bands.remove(band_modify_index);
-                } else {
-                    // move on to the next one
-                    band_modify_index += 1;
-                }
+            let band_bottom = band_top + band.height;
+            if y < band_bottom {
+                let band_below = ContentBand {
+                    height: band_bottom - y,
+                    ..*band
+                };
+                band.height = y - band_top;
+                self.content_bands.insert(index + 1, band_below);
+                return index + 1;
             }
-        }
 
-        let reduced_area = ContentBand {
-            height: margin_area.height,
-            inset_left: new_inset_left,
-            inset_right: new_inset_right,
-        };
-        self.content_bands
-            .insert(placement.band_index, reduced_area);
-
-        if placement.offset_in_band != Pixels::ZERO {
-            // There will be a new content band *above* the floating box
-            let area_above = ContentBand {
-                height: placement.offset_in_band,
-                inset_left: old_content_band.inset_left,
-                inset_right: old_content_band.inset_right,
-            };
-            self.content_bands.insert(placement.band_index, area_above);
+            band_top = band_bottom;
+            index += 1;
         }
+    }
 
-        // Lower the float ceiling: New floats may not appear above this box
-        self.lower_float_ceiling(placement.position.y);
+    /// Iterate over all content bands that intersect the vertical range `[top, top + height)`
+    ///
+    /// If `height` is zero then the band containing `top` is returned.
+    fn bands_in_range(&self, top: Pixels, height: Pixels) -> impl Iterator<Item = &ContentBand> {
+        let bottom = top + height;
 
-        match side {
-            FloatSide::Left => {
-                self.lowest_float_left = placement.position.y + margin_area.height;
-            },
-            FloatSide::Right => {
-                self.lowest_float_right = placement.position.y + margin_area.height;
+        self.content_bands
+            .iter()
+            .scan(Pixels::ZERO, |band_top, band| {
+                let position = *band_top;
+                *band_top += band.height;
+                Some((position, band))
+            })
+            .skip_while(move |(band_top, band)| *band_top + band.height <= top)
+            .take_while(move |(band_top, _)| *band_top < bottom || *band_top <= top)
+            .map(|(_, band)| band)
+    }
+
+    /// Compute the horizontal insets caused by floats within the vertical range `[top, top + height)`
+    ///
+    /// The left inset is the rightmost outer edge of any left-floating box, the right inset
+    /// is the distance of the leftmost outer edge of any right-floating box to the right edge
+    /// of the formatting context root. Either of them is `None` if there are no floats on that side.
+    fn insets_in_range(&self, top: Pixels, height: Pixels) -> (Option<Pixels>, Option<Pixels>) {
+        self.bands_in_range(top, height).fold(
+            (None, None),
+            |(inset_left, inset_right): (Option<Pixels>, Option<Pixels>), band| {
+                let inset_left = match (inset_left, band.inset_left) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+                let inset_right = match (inset_right, band.inset_right) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+                (inset_left, inset_right)
             },
-        }
+        )
     }
 
-    /// Computes a suitable position for a floating element
+    /// Compute the area between the floats within the vertical range `[top, top + height)`,
+    /// clamped to the given containing block
     ///
-    /// `containing_block_area` describes the position of the containing block for the float
-    /// relative to the root of the current formatting context that the float is positioned in.
-    fn find_position_for_float(
+    /// The edges of the area are relative to the formatting context root.
+    fn area_between_floats(
         &self,
-        float_width: Pixels,
-        side: FloatSide,
+        top: Pixels,
+        height: Pixels,
         containing_block: ContainingBlock,
-    ) -> Placement {
-        debug_assert!(!self.content_bands.is_empty());
-
-        // While the float is positioned relative to the formatting context root, it may not
-        // be placed outside its containing block
+    ) -> AreaBetweenFloats {
         let min_left = containing_block
             .position_relative_to_formatting_context_root
             .x;
         let max_right = min_left + containing_block.width();
 
-        // Search the first suitable band that the float can be placed in
-        let mut cursor = Pixels::ZERO;
-        let mut band_to_place_float_in = self.content_bands.len() - 1;
-        for (index, content_band) in self.content_bands[..self.content_bands.len() - 1]
-            .iter()
-            .enumerate()
-        {
-            if cursor + content_band.height <= self.float_ceiling {
-                // Cannot place floats above the float ceiling
-                cursor += content_band.height;
-                continue;
-            }
+        let (inset_left, inset_right) = self.insets_in_range(top, height);
+        let left_edge = inset_left.map_or(min_left, |inset| inset.max(min_left));
+        let right_edge = inset_right.map_or(max_right, |inset| {
+            (self.containing_block.width() - inset).min(max_right)
+        });
 
-            if !content_band.box_fits(
-                float_width,
-                side,
-                (min_left, max_right),
-                self.containing_block.width,
-            ) {
-                // The float does not fit here
-                cursor += content_band.height;
-                continue;
+        AreaBetweenFloats {
+            is_next_to_floats: inset_left.is_some() || inset_right.is_some(),
+            left_edge,
+            right_edge,
+        }
+    }
+
+    /// Returns the y-coordinate of the first band boundary below `y`
+    fn next_band_boundary_after(&self, y: Pixels) -> Pixels {
+        let mut band_bottom = Pixels::ZERO;
+        for band in &self.content_bands {
+            band_bottom += band.height;
+            if y < band_bottom {
+                break;
             }
+        }
+        band_bottom
+    }
 
-            // This the first suitable place to put the float
-            band_to_place_float_in = index;
-            break;
+    /// Compute the horizontal space that is not occupied by floats within a vertical range
+    ///
+    /// `top` is relative to the formatting context root. The returned offset and width
+    /// describe the free space relative to the given containing block.
+    /// If the floats leave no space at all then the width is zero.
+    #[must_use]
+    pub fn available_space(
+        &self,
+        top: Pixels,
+        height: Pixels,
+        containing_block: ContainingBlock,
+    ) -> (Pixels, Pixels) {
+        let area = self.area_between_floats(top, height, containing_block);
+        let containing_block_left = containing_block
+            .position_relative_to_formatting_context_root
+            .x;
+
+        (
+            area.left_edge - containing_block_left,
+            (area.right_edge - area.left_edge).max(Pixels::ZERO),
+        )
+    }
+
+    /// Place a float in a given position.
+    ///
+    /// `position` is relative to the formatting context root.
+    fn place_float(&mut self, position: Vec2D<Pixels>, margin_area: Size<Pixels>, side: FloatSide) {
+        if margin_area.height > Pixels::ZERO {
+            // Split the bands so that the float covers a contiguous range of them
+            let first_band = self.split_band_at(position.y);
+            let last_band = self.split_band_at(position.y + margin_area.height);
+
+            let width_of_formatting_context_root = self.containing_block.width();
+            for band in &mut self.content_bands[first_band..last_band] {
+                match side {
+                    FloatSide::Left => {
+                        let inset = position.x + margin_area.width;
+                        band.inset_left = Some(band.inset_left.map_or(inset, |i| i.max(inset)));
+                    },
+                    FloatSide::Right => {
+                        let inset = width_of_formatting_context_root - position.x;
+                        band.inset_right = Some(band.inset_right.map_or(inset, |i| i.max(inset)));
+                    },
+                }
+            }
         }
 
-        // The last fragment has infinite height. The float can always be placed here
-        // (but won't if theres any available space earlier)
-        let chosen_band = &self.content_bands[band_to_place_float_in];
-        let y_position = cmp::max(cursor, self.float_ceiling);
-        let x_position = match side {
-            FloatSide::Left => chosen_band.inset_left.unwrap_or_default().max(min_left),
+        // Lower the float ceiling: New floats may not appear above this box
+        self.lower_float_ceiling(position.y);
+
+        let bottom = position.y + margin_area.height;
+        match side {
+            FloatSide::Left => {
+                self.lowest_float_left = self.lowest_float_left.max(bottom);
+            },
             FloatSide::Right => {
-                let right_edge = (self.containing_block.width()
-                    - chosen_band.inset_right.unwrap_or_default())
-                .min(max_right);
-                right_edge - float_width
+                self.lowest_float_right = self.lowest_float_right.max(bottom);
             },
-        };
+        }
+    }
 
-        let position = Vec2D::new(x_position, y_position);
+    /// Computes a suitable position for a floating element
+    ///
+    /// The returned position is relative to the formatting context root.
+    fn find_position_for_float(
+        &self,
+        margin_area: Size<Pixels>,
+        side: FloatSide,
+        containing_block: ContainingBlock,
+    ) -> Vec2D<Pixels> {
+        // Floats may not be placed above the float ceiling or above their containing block
+        let mut y = self.float_ceiling.max(
+            containing_block
+                .position_relative_to_formatting_context_root
+                .y,
+        );
+
+        loop {
+            let area = self.area_between_floats(y, margin_area.height, containing_block);
+
+            // Rules 3 and 7: The float may not overlap floats on the other side and may only overflow
+            // its containing block if there are no other floats next to it
+            let fits = area.left_edge + margin_area.width <= area.right_edge;
+            if fits || !area.is_next_to_floats {
+                let x = match side {
+                    FloatSide::Left => area.left_edge,
+                    FloatSide::Right => area.right_edge - margin_area.width,
+                };
+                return Vec2D::new(x, y);
+            }
 
-        Placement {
-            position,
-            offset_in_band: y_position - cursor,
-            band_index: band_to_place_float_in,
+            // Rule 8: The float is placed as high as possible, so move down until the next
+            // band starts. The last band is not occupied by floats, so this terminates
+            y = self.next_band_boundary_after(y);
         }
     }
 
@@ -371,119 +442,133 @@ impl FloatContext {
         side: FloatSide,
         containing_block: ContainingBlock,
     ) -> Vec2D<Pixels> {
-        let placement = self.find_position_for_float(margin_area.width, side, containing_block);
-        self.place_float(margin_area, side, placement);
-        placement.position - containing_block.position_relative_to_formatting_context_root
+        let position = self.find_position_for_float(margin_area, side, containing_block);
+        self.place_float(position, margin_area, side);
+        position - containing_block.position_relative_to_formatting_context_root
     }
 }
 
 #[derive(Clone, Copy, Debug)]
-struct Placement {
-    position: Vec2D<Pixels>,
-    band_index: usize,
-    offset_in_band: Pixels,
+struct AreaBetweenFloats {
+    is_next_to_floats: bool,
+    left_edge: Pixels,
+    right_edge: Pixels,
 }
 
-/// A rectangular area where content may be placed
-#[derive(Clone, Debug)]
+/// A vertical range of the formatting context root where the available space is
+/// reduced by the same floats
+#[derive(Clone, Copy, Debug)]
 struct ContentBand {
     height: Pixels,
+
+    /// The right outer edge of the rightmost left-floating box in this band,
+    /// relative to the formatting context root
     inset_left: Option<Pixels>,
+
+    /// The distance between the left outer edge of the leftmost right-floating box
+    /// in this band and the right edge of the formatting context root
     inset_right: Option<Pixels>,
 }
 
-impl ContentBand {
-    /// When determining whether or not a float fits in a content band there are effectively
-    /// two constraints to consider:
-    /// * The box must be positioned inside the band (governed by float positioning rules)
-    /// * The box must be positioned
-    fn box_fits(
-        &self,
-        box_width: Pixels,
-        place_on_side: FloatSide,
-        (min_left, max_right): (Pixels, Pixels),
-        width_of_formatting_context_root: Pixels,
-    ) -> bool {
-        // The algorithm is the same for left- and right-floating boxes.
-        // So instead of duplicating code, we only implement it for left-floating boxes
-        // and mirror the two sides if we're right-floating
-
-        #[derive(Debug)]
-        struct OrientationIndependentFloatInfo {
-            has_floating_box_to_the_left: bool,
-
-            /// The horizontal position that the box will be placed at, assuming it fits
-            position: Pixels,
-            right_edge_of_containing_block: Pixels,
-            closest_floating_edge_on_the_right: Option<Pixels>,
-        }
+impl<'box_tree> TreeDebug for FloatingBox<'box_tree> {
+    fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
+        formatter.indent()?;
+        write!(formatter, "Block Box (floating)")?;
+        writeln!(formatter, " ({:?})", self.node.underlying_type())?;
 
-        let positioning_info = match place_on_side {
-            FloatSide::Left => OrientationIndependentFloatInfo {
-                has_floating_box_to_the_left: self.inset_left.is_some(),
-                position: self.left_edge().max(min_left),
-                right_edge_of_containing_block: max_right,
-                closest_floating_edge_on_the_right: self.inset_right,
-            },
-            FloatSide::Right => OrientationIndependentFloatInfo {
-                has_floating_box_to_the_left: self.inset_right.is_some(),
-                position: self
-                    .right_edge()
-                    .max(width_of_formatting_context_root - max_right),
-                right_edge_of_containing_block: width_of_formatting_context_root - min_left,
-                closest_floating_edge_on_the_right: self.inset_left,
-            },
-        };
+        formatter.increase_indent();
+        self.contents.tree_fmt(formatter)?;
+        formatter.decrease_indent();
+        Ok(())
+    }
+}
 
-        let right_box_edge = positioning_info.position + box_width;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // Rule 7:
-        // > A left-floating box that has another left-floating box to its left may not have its right
-        // > outer edge to the right of its containing block’s right edge.
-        // > An analogous rule holds for right-floating elements.
-        if positioning_info.has_floating_box_to_the_left
-            && positioning_info.right_edge_of_containing_block < right_box_edge
-        {
-            return false;
-        }
+    fn containing_block(width: f32) -> ContainingBlock {
+        ContainingBlock::new(Pixels(width), Vec2D::new(Pixels::ZERO, Pixels::ZERO))
+    }
 
-        // Rule 3:
-        // > The right outer edge of a left-floating box may not be to the right of the left outer edge
-        // > of any right-floating box that is next to it. Analogous rules hold for right-floating elements.
-        if positioning_info
-            .closest_floating_edge_on_the_right
-            .is_some_and(|inset| width_of_formatting_context_root - inset < right_box_edge)
-        {
-            return false;
+    fn size(width: f32, height: f32) -> Size<Pixels> {
+        Size {
+            width: Pixels(width),
+            height: Pixels(height),
         }
-
-        true
     }
 
-    /// Compute the inset of the left edge relative to the formatting context root
-    #[inline]
-    #[must_use]
-    fn left_edge(&self) -> Pixels {
-        self.inset_left.unwrap_or_default()
+    #[test]
+    fn floats_on_both_sides() {
+        let cb = containing_block(100.);
+        let mut float_context = FloatContext::new(cb);
+
+        let left =
+            float_context.find_position_and_place_float_box(size(30., 20.), FloatSide::Left, cb);
+        let right =
+            float_context.find_position_and_place_float_box(size(40., 10.), FloatSide::Right, cb);
+        assert_eq!(left, Vec2D::new(Pixels(0.), Pixels(0.)));
+        assert_eq!(right, Vec2D::new(Pixels(60.), Pixels(0.)));
+
+        // This float doesn't fit between the two, but it fits once the right float ends
+        let second_left =
+            float_context.find_position_and_place_float_box(size(50., 10.), FloatSide::Left, cb);
+        assert_eq!(second_left, Vec2D::new(Pixels(30.), Pixels(10.)));
+
+        assert_eq!(float_context.clear_left(), Pixels(20.));
+        assert_eq!(float_context.clear_right(), Pixels(10.));
     }
 
-    /// Compute the inset of the right edge relative to the formatting context root
-    #[inline]
-    #[must_use]
-    fn right_edge(&self) -> Pixels {
-        self.inset_right.unwrap_or_default()
+    #[test]
+    fn float_spanning_multiple_bands() {
+        let cb = containing_block(100.);
+        let mut float_context = FloatContext::new(cb);
+
+        // A tall float followed by a short one creates two bands next to the tall float
+        float_context.find_position_and_place_float_box(size(20., 50.), FloatSide::Left, cb);
+        float_context.find_position_and_place_float_box(size(20., 10.), FloatSide::Left, cb);
+
+        // This float does not fit next to the first two and must be placed below the
+        // tall float, not just below the short one
+        let position =
+            float_context.find_position_and_place_float_box(size(70., 10.), FloatSide::Left, cb);
+        assert_eq!(position, Vec2D::new(Pixels(20.), Pixels(10.)));
+
+        let position =
+            float_context.find_position_and_place_float_box(size(90., 10.), FloatSide::Left, cb);
+        assert_eq!(position, Vec2D::new(Pixels(0.), Pixels(50.)));
     }
-}
 
-impl TreeDebug for FloatingBox {
-    fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
-        formatter.indent()?;
-        write!(formatter, "Block Box (floating)")?;
-        writeln!(formatter, " ({:?})", self.node.underlying_type())?;
+    #[test]
+    fn available_space_for_lines() {
+        let cb = containing_block(100.);
+        let mut float_context = FloatContext::new(cb);
 
-        formatter.increase_indent();
-        self.contents.tree_fmt(formatter)?;
-        formatter.decrease_indent();
-        Ok(())
+        float_context.find_position_and_place_float_box(size(20., 30.), FloatSide::Left, cb);
+        float_context.find_position_and_place_float_box(size(30., 10.), FloatSide::Right, cb);
+
+        assert_eq!(
+            float_context.available_space(Pixels(0.), Pixels(5.), cb),
+            (Pixels(20.), Pixels(50.))
+        );
+        assert_eq!(
+            float_context.available_space(Pixels(15.), Pixels(5.), cb),
+            (Pixels(20.), Pixels(80.))
+        );
+        assert_eq!(
+            float_context.available_space(Pixels(5.), Pixels(10.), cb),
+            (Pixels(20.), Pixels(50.))
+        );
+        assert_eq!(
+            float_context.available_space(Pixels(30.), Pixels(10.), cb),
+            (Pixels(0.), Pixels(100.))
+        );
+
+        // Offsets are relative to the given containing block
+        let nested = ContainingBlock::new(Pixels(80.), Vec2D::new(Pixels(10.), Pixels(0.)));
+        assert_eq!(
+            float_context.available_space(Pixels(15.), Pixels(5.), nested),
+            (Pixels(10.), Pixels(70.))
+        );
     }
 }
@@ -7,20 +7,33 @@ use crate::{
     css::{
         font_metrics,
         fragment_tree::{BoxFragment, Fragment, TextFragment},
-        layout::{replaced::ReplacedElement, ContainingBlock, Pixels, Sides, Size},
-        style::{computed::VerticalAlign, specified::FontName},
+        layout::{
+            logical::LogicalMapping,
+            mathml::{MathFormula, MathItem},
+            replaced::ReplacedElement,
+            sizing::ContentSizes,
+            ContainingBlock, Pixels, Sides, Size,
+        },
+        style::computed::{Clear, VerticalAlign},
         ComputedStyle, LineBreakIterator,
     },
     dom::{dom_objects, DomPtr},
     TreeDebug, TreeFormatter,
 };
 
+use super::{
+    ruby::{RubyContainer, RubySegmentItem},
+    FloatContext,
+};
+
 /// <https://drafts.csswg.org/css2/#inline-level-boxes>
 #[derive(Clone, Debug)]
-pub enum InlineLevelBox {
-    InlineBox(InlineBox),
+pub enum InlineLevelBox<'box_tree> {
+    InlineBox(InlineBox<'box_tree>),
     TextRun(TextRun),
     Replaced(ReplacedElement),
+    Ruby(RubyContainer<'box_tree>),
+    Math(MathFormula<'box_tree>),
 }
 
 #[derive(Clone, Debug)]
@@ -31,16 +44,19 @@ pub struct TextRun {
 
 /// <https://drafts.csswg.org/css2/#inline-box>
 #[derive(Clone, Debug)]
-pub struct InlineBox {
+pub struct InlineBox<'box_tree> {
     node: DomPtr<dom_objects::Node>,
     style: ComputedStyle,
-    contents: Vec<InlineLevelBox>,
+    contents: &'box_tree [InlineLevelBox<'box_tree>],
 }
 
 /// <https://drafts.csswg.org/css2/#inline-formatting>
 #[derive(Clone, Debug, Default)]
-pub struct InlineFormattingContext {
-    elements: Vec<InlineLevelBox>,
+pub struct InlineFormattingContext<'box_tree> {
+    elements: &'box_tree [InlineLevelBox<'box_tree>],
+
+    /// The writing mode of the block container that establishes this formatting context
+    logical_mapping: LogicalMapping,
 }
 
 impl TextRun {
@@ -73,32 +89,35 @@ impl TextRun {
     }
 
     fn find_suitable_font(&self) -> FontMetrics {
-        // FIXME: Consider more than just the first specified font
-        let family = match self.style().font_family().fonts()[0] {
-            FontName::Family(name) => font::Family::Specific(name.to_string()),
-            FontName::Generic(name) => font::Family::Generic(name.to_string()),
-        };
+        FontMetrics::for_style(self.style())
+    }
 
-        let properties = font::Properties {
-            style: font::Style::Normal,
-            weight: font::Weight::NORMAL,
-            language: font::Language::English,
+    /// The max-content width is the width of the whole text, the min-content width is
+    /// the width of the longest word
+    #[must_use]
+    fn content_sizes(&self) -> ContentSizes {
+        let font_metrics = self.find_suitable_font();
+        let width_of = |text: &str| {
+            font_metrics
+                .font_face
+                .compute_rendered_width(text, font_metrics.size.into())
         };
 
-        let font = font::SYSTEM_FONTS
-            .lookup(family, properties)
-            .try_load()
-            .expect("Failed to load font");
+        let min_content = self
+            .text
+            .split_whitespace()
+            .map(width_of)
+            .fold(0., f32::max);
 
-        FontMetrics {
-            font_face: Box::new(font),
-            size: *self.style.font_size(),
+        ContentSizes {
+            min_content: Pixels(min_content),
+            max_content: Pixels(width_of(self.text.trim_start())),
         }
     }
 
     fn layout_into_line_items<'state, 'box_tree>(
         &self,
-        state: &'state mut InlineFormattingContextState<'box_tree>,
+        state: &'state mut InlineFormattingContextState<'box_tree, '_>,
     ) where
         'box_tree: 'state,
     {
@@ -135,27 +154,77 @@ impl TextRun {
     }
 }
 
-impl InlineFormattingContext {
-    #[inline]
-    pub fn elements(&self) -> &[InlineLevelBox] {
-        &self.elements
+impl<'box_tree> InlineFormattingContext<'box_tree> {
+    /// Create a formatting context for a block container with the given style
+    #[must_use]
+    pub fn new(elements: &'box_tree [InlineLevelBox<'box_tree>], style: &ComputedStyle) -> Self {
+        Self {
+            elements,
+            logical_mapping: LogicalMapping::from_style(style),
+        }
     }
 
     #[inline]
-    pub fn push(&mut self, inline_level_box: InlineLevelBox) {
-        self.elements.push(inline_level_box)
+    pub fn elements(&self) -> &'box_tree [InlineLevelBox<'box_tree>] {
+        self.elements
     }
 
-    /// Return true if there are no elements in the [InlineFormattingContext]
+    /// <https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes>
+    #[must_use]
+    pub(crate) fn content_sizes(&self) -> ContentSizes {
+        content_sizes_of_inline_level_boxes(self.elements())
+    }
+
+    /// Lay out the contents of the inline formatting context into line boxes
     ///
-    /// Note that a valid [InlineFormattingContext] should never be empty
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.elements.is_empty()
+    /// Line boxes are shortened to avoid the floats in `float_context`.
+    pub fn layout(
+        &self,
+        containing_block: ContainingBlock,
+        float_context: &FloatContext,
+    ) -> (Vec<Fragment>, Pixels) {
+        if self.logical_mapping.is_vertical() {
+            return self.layout_vertically(containing_block);
+        }
+
+        self.layout_line_boxes(containing_block, float_context)
     }
 
-    pub fn layout(&self, containing_block: ContainingBlock) -> (Vec<Fragment>, Pixels) {
-        let mut state = InlineFormattingContextState::new(containing_block);
+    /// Lay out the contents in a [vertical writing mode](https://drafts.csswg.org/css-writing-modes-4/#vertical-mode)
+    ///
+    /// The lines are laid out as if they were horizontal and then mapped onto the physical axes.
+    /// They are as long as the containing block is high, or, if its height is not known, as long
+    /// as the max-content size of the contents.
+    fn layout_vertically(&self, containing_block: ContainingBlock) -> (Vec<Fragment>, Pixels) {
+        let inline_size = containing_block
+            .height()
+            .unwrap_or_else(|| self.content_sizes().max_content);
+
+        // FIXME: Floats are not taken into account in vertical writing modes
+        let logical_containing_block =
+            ContainingBlock::new(inline_size, Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+        let float_context = FloatContext::new(logical_containing_block);
+        let (fragments, _) = self.layout_line_boxes(logical_containing_block, &float_context);
+
+        // Lines are stacked along the width of the containing block
+        let container = Size {
+            width: inline_size,
+            height: containing_block.width(),
+        };
+        let fragments = fragments
+            .into_iter()
+            .map(|fragment| fragment.into_physical(self.logical_mapping, container))
+            .collect();
+
+        (fragments, inline_size)
+    }
+
+    fn layout_line_boxes(
+        &self,
+        containing_block: ContainingBlock,
+        float_context: &FloatContext,
+    ) -> (Vec<Fragment>, Pixels) {
+        let mut state = InlineFormattingContextState::new(containing_block, float_context);
 
         state.traverse(self.elements());
 
@@ -169,6 +238,47 @@ impl InlineFormattingContext {
     }
 }
 
+/// Lay out a sequence of inline-level boxes on a single line, without any line breaks
+///
+/// Returns the fragments (relative to the start of the line) and the size of the line.
+#[must_use]
+pub(super) fn layout_on_single_line<'box_tree>(
+    elements: &'box_tree [InlineLevelBox<'box_tree>],
+) -> (Vec<Fragment>, Size<Pixels>) {
+    let containing_block =
+        ContainingBlock::new(Pixels::INFINITY, Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+    let float_context = FloatContext::new(containing_block);
+    let mut state = InlineFormattingContextState::new(containing_block, &float_context);
+
+    state.traverse(elements);
+    state.finish_current_line();
+
+    let size = Size {
+        width: content_sizes_of_inline_level_boxes(elements).max_content,
+        height: state.y_cursor,
+    };
+    (state.finished_fragments, size)
+}
+
+/// Compute the content sizes of a sequence of inline-level boxes that are placed on
+/// the same line
+#[must_use]
+pub(super) fn content_sizes_of_inline_level_boxes(elements: &[InlineLevelBox<'_>]) -> ContentSizes {
+    elements
+        .iter()
+        .map(|element| match element {
+            InlineLevelBox::InlineBox(inline_box) => {
+                // FIXME: respect margins/borders for inline boxes
+                content_sizes_of_inline_level_boxes(inline_box.contents)
+            },
+            InlineLevelBox::TextRun(text_run) => text_run.content_sizes(),
+            InlineLevelBox::Replaced(replaced_element) => replaced_element.content_sizes(),
+            InlineLevelBox::Ruby(ruby_container) => ruby_container.content_sizes(),
+            InlineLevelBox::Math(formula) => formula.content_sizes(),
+        })
+        .fold(ContentSizes::default(), ContentSizes::append)
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 struct LineBoxUnderConstruction {
     height: Pixels,
@@ -185,12 +295,12 @@ struct NestingLevelState<'box_tree> {
 
 #[derive(Clone, Debug)]
 struct InlineBoxContainerState<'box_tree> {
-    inline_box: &'box_tree InlineBox,
+    inline_box: &'box_tree InlineBox<'box_tree>,
     nesting_level_state: NestingLevelState<'box_tree>,
 }
 
 #[derive(Clone, Debug)]
-struct InlineFormattingContextState<'box_tree> {
+struct InlineFormattingContextState<'box_tree, 'floats> {
     /// Information about the line box currently being constructed
     line_box_under_construction: LineBoxUnderConstruction,
 
@@ -200,6 +310,18 @@ struct InlineFormattingContextState<'box_tree> {
     inline_box_stack: Vec<InlineBoxContainerState<'box_tree>>,
 
     containing_block: ContainingBlock,
+
+    /// The floats in the surrounding block formatting context
+    float_context: &'floats FloatContext,
+
+    /// The horizontal offset of the current line box from the containing block
+    ///
+    /// This is non-zero if there are left-floating boxes next to the line.
+    line_box_offset: Pixels,
+
+    /// The width that is available to the current line box
+    available_width_for_line_box: Pixels,
+
     finished_fragments: Vec<Fragment>,
     has_seen_relevant_content: bool,
 
@@ -217,6 +339,8 @@ enum LineItem<'box_tree> {
     TextRun(TextRunItem),
     InlineBox(InlineBoxItem<'box_tree>),
     Replaced(ReplacedItem<'box_tree>),
+    Ruby(RubySegmentItem),
+    Math(MathItem),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -322,17 +446,69 @@ impl<'box_tree> InlineBoxItem<'box_tree> {
     }
 }
 
-impl<'box_tree> InlineFormattingContextState<'box_tree> {
-    fn new(containing_block: ContainingBlock) -> Self {
-        Self {
+impl<'box_tree, 'floats> InlineFormattingContextState<'box_tree, 'floats> {
+    fn new(containing_block: ContainingBlock, float_context: &'floats FloatContext) -> Self {
+        let mut state = Self {
             line_box_under_construction: LineBoxUnderConstruction::default(),
             root_nesting_level_state: NestingLevelState::default(),
             inline_box_stack: Vec::new(),
             containing_block,
+            float_context,
+            line_box_offset: Pixels::ZERO,
+            available_width_for_line_box: containing_block.width(),
             finished_fragments: Vec::new(),
             has_seen_relevant_content: false,
             y_cursor: Pixels::ZERO,
             at_beginning_of_line: true,
+        };
+
+        state.find_space_for_line_box();
+        state
+    }
+
+    /// Query the float context for the horizontal space that the current line box may occupy
+    fn find_space_for_line_box(&mut self) {
+        // FIXME: The height of the line box is not known in advance, so only the floats at the top
+        //        of the line are considered. We should also move the line down if its content does
+        //        not fit next to the floats.
+        let top = self
+            .containing_block
+            .position_relative_to_formatting_context_root
+            .y
+            + self.y_cursor;
+        let (offset, width) =
+            self.float_context
+                .available_space(top, Pixels::ZERO, self.containing_block);
+
+        self.line_box_offset = offset;
+        self.available_width_for_line_box = width;
+    }
+
+    /// Move the next line box below floats if the `clear` property of an inline-level box requires it
+    ///
+    /// If the current line box is not empty, it is finished first.
+    fn respect_clearance(&mut self, clear: &Clear) {
+        let clear_to = match clear {
+            Clear::Left => self.float_context.clear_left(),
+            Clear::Right => self.float_context.clear_right(),
+            Clear::Both => self.float_context.clear_both(),
+            _ => return,
+        };
+
+        // The clear value is always relative to the formatting context root
+        let clear_to = clear_to
+            - self
+                .containing_block
+                .position_relative_to_formatting_context_root
+                .y;
+
+        if self.y_cursor < clear_to {
+            if !self.at_beginning_of_line {
+                self.finish_current_line();
+            }
+
+            self.y_cursor = self.y_cursor.max(clear_to);
+            self.find_space_for_line_box();
         }
     }
 
@@ -349,21 +525,26 @@ impl<'box_tree> InlineFormattingContextState<'box_tree> {
     }
 
     fn remaining_width_for_line_box(&self) -> Pixels {
-        self.containing_block.width() - self.line_box_under_construction.width
+        self.available_width_for_line_box - self.line_box_under_construction.width
     }
 
-    fn traverse<I: IntoIterator<Item = &'box_tree InlineLevelBox>>(&mut self, iterator: I) {
+    fn traverse<I: IntoIterator<Item = &'box_tree InlineLevelBox<'box_tree>>>(
+        &mut self,
+        iterator: I,
+    ) {
         for element in iterator {
             match element {
                 InlineLevelBox::InlineBox(inline_box) => {
+                    self.respect_clearance(inline_box.style.clear());
                     self.start_inline_box(inline_box);
-                    self.traverse(&inline_box.contents);
+                    self.traverse(inline_box.contents);
                     self.finish_inline_box();
                 },
                 InlineLevelBox::TextRun(text_run) => {
                     text_run.layout_into_line_items(self);
                 },
                 InlineLevelBox::Replaced(replaced_element) => {
+                    self.respect_clearance(replaced_element.style.clear());
                     let size = replaced_element.used_size_if_it_was_inline(self.containing_block);
                     let replaced_item = ReplacedItem {
                         replaced_element,
@@ -371,6 +552,30 @@ impl<'box_tree> InlineFormattingContextState<'box_tree> {
                     };
                     self.push_line_item(replaced_item.into(), size);
                 },
+                InlineLevelBox::Ruby(ruby_container) => {
+                    self.respect_clearance(ruby_container.style().clear());
+
+                    // Ruby segments are not broken across lines, but there may be a break between them
+                    for segment in ruby_container.layout_segments() {
+                        let size = segment.size();
+                        if !self.at_beginning_of_line
+                            && self.remaining_width_for_line_box() < size.width
+                        {
+                            self.finish_current_line();
+                        }
+                        self.push_line_item(segment.into(), size);
+                    }
+                },
+                InlineLevelBox::Math(formula) => {
+                    self.respect_clearance(formula.style().clear());
+
+                    let mut math_item = MathItem::new(formula);
+                    if math_item.is_display_style() {
+                        math_item.center_in(self.remaining_width_for_line_box());
+                    }
+                    let size = math_item.size();
+                    self.push_line_item(math_item.into(), size);
+                },
             }
         }
     }
@@ -396,7 +601,7 @@ impl<'box_tree> InlineFormattingContextState<'box_tree> {
         let items_on_this_line = mem::take(&mut self.root_nesting_level_state.line_items);
 
         let mut layout_state = LineItemLayoutState::new(
-            Vec2D::new(Pixels::ZERO, self.y_cursor),
+            Vec2D::new(self.line_box_offset, self.y_cursor),
             self.line_box_under_construction.height,
         );
         self.finished_fragments
@@ -411,6 +616,7 @@ impl<'box_tree> InlineFormattingContextState<'box_tree> {
         };
 
         self.at_beginning_of_line = true;
+        self.find_space_for_line_box();
     }
 
     fn current_insertion_point(&mut self) -> &mut NestingLevelState<'box_tree> {
@@ -420,7 +626,7 @@ impl<'box_tree> InlineFormattingContextState<'box_tree> {
         }
     }
 
-    fn start_inline_box(&mut self, inline_box: &'box_tree InlineBox) {
+    fn start_inline_box(&mut self, inline_box: &'box_tree InlineBox<'box_tree>) {
         self.inline_box_stack
             .push(InlineBoxContainerState::new(inline_box));
     }
@@ -466,6 +672,19 @@ impl<'box_tree> LineItemLayoutState {
                     let fragment = replaced_item.layout(self);
                     fragments.push(fragment);
                 },
+                LineItem::Ruby(segment) => {
+                    // The annotation sits on top of the base, so the line box grows to fit both
+                    let size = segment.size();
+                    let position = self.position_element(size.height, segment.style());
+                    self.push_item(size);
+                    fragments.push(segment.into_fragment(position));
+                },
+                LineItem::Math(math_item) => {
+                    let size = math_item.size();
+                    let position = self.position_element(size.height, math_item.style());
+                    self.push_item(size);
+                    fragments.push(math_item.into_fragment(position));
+                },
             }
         }
 
@@ -479,7 +698,7 @@ impl<'box_tree> LineItemLayoutState {
 
 impl<'box_tree> InlineBoxContainerState<'box_tree> {
     #[must_use]
-    fn new(inline_box: &'box_tree InlineBox) -> Self {
+    fn new(inline_box: &'box_tree InlineBox<'box_tree>) -> Self {
         Self {
             inline_box,
             nesting_level_state: NestingLevelState::default(),
@@ -525,52 +744,32 @@ impl TextRunItem {
         });
 
         TextFragment::new(self.text, area, *self.style.color(), self.metrics)
+            .with_text_orientation(*self.style.text_orientation())
     }
 }
 
-impl From<Vec<InlineLevelBox>> for InlineFormattingContext {
-    fn from(elements: Vec<InlineLevelBox>) -> Self {
-        Self { elements }
-    }
-}
-
-impl InlineBox {
+impl<'box_tree> InlineBox<'box_tree> {
     #[inline]
     #[must_use]
-    pub fn new(node: DomPtr<dom_objects::Node>, style: ComputedStyle) -> Self {
+    pub fn new(
+        node: DomPtr<dom_objects::Node>,
+        style: ComputedStyle,
+        contents: &'box_tree [InlineLevelBox<'box_tree>],
+    ) -> Self {
         Self {
             node,
             style,
-            contents: Vec::new(),
-        }
-    }
-
-    #[inline]
-    pub fn push(&mut self, element: InlineLevelBox) {
-        self.contents.push(element);
-    }
-
-    /// Create a inline box with the same style but no children
-    ///
-    /// This is necessary when an [InlineBox] needs to be split due to
-    /// a [BlockLevelBox](super::BlockLevelBox) inside it.
-    #[inline]
-    #[must_use]
-    pub fn split_off(&self) -> Self {
-        Self {
-            node: self.node.clone(),
-            style: self.style.clone(),
-            contents: vec![],
+            contents,
         }
     }
 }
 
-impl TreeDebug for InlineFormattingContext {
+impl<'box_tree> TreeDebug for InlineFormattingContext<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> std::fmt::Result {
         formatter.indent()?;
         writeln!(formatter, "Inline Formatting Context")?;
         formatter.increase_indent();
-        for child in &self.elements {
+        for child in self.elements {
             child.tree_fmt(formatter)?;
         }
         formatter.decrease_indent();
@@ -578,7 +777,7 @@ impl TreeDebug for InlineFormattingContext {
     }
 }
 
-impl TreeDebug for InlineLevelBox {
+impl<'box_tree> TreeDebug for InlineLevelBox<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> std::fmt::Result {
         match self {
             Self::TextRun(text_run) => {
@@ -594,7 +793,7 @@ impl TreeDebug for InlineLevelBox {
                     inline_box.node.underlying_type()
                 )?;
                 formatter.increase_indent();
-                for child in &inline_box.contents {
+                for child in inline_box.contents {
                     child.tree_fmt(formatter)?;
                     writeln!(formatter)?;
                 }
@@ -604,6 +803,40 @@ impl TreeDebug for InlineLevelBox {
                 formatter.indent()?;
                 writeln!(formatter, "Replaced Element")?;
             },
+            Self::Ruby(ruby_container) => {
+                formatter.indent()?;
+                writeln!(
+                    formatter,
+                    "Ruby Container ({:?})",
+                    ruby_container.node().underlying_type()
+                )?;
+                formatter.increase_indent();
+                for segment in ruby_container.segments() {
+                    formatter.indent()?;
+                    writeln!(formatter, "Ruby Base")?;
+                    formatter.increase_indent();
+                    for child in segment.base() {
+                        child.tree_fmt(formatter)?;
+                    }
+                    formatter.decrease_indent();
+
+                    if let Some(annotation) = segment.annotation() {
+                        formatter.indent()?;
+                        writeln!(
+                            formatter,
+                            "Ruby Annotation ({:?})",
+                            annotation.node().underlying_type()
+                        )?;
+                        formatter.increase_indent();
+                        for child in annotation.contents() {
+                            child.tree_fmt(formatter)?;
+                        }
+                        formatter.decrease_indent();
+                    }
+                }
+                formatter.decrease_indent();
+            },
+            Self::Math(formula) => formula.tree_fmt(formatter)?,
         }
         Ok(())
     }
@@ -626,3 +859,15 @@ impl<'box_tree> From<ReplacedItem<'box_tree>> for LineItem<'box_tree> {
         Self::Replaced(value)
     }
 }
+
+impl<'box_tree> From<RubySegmentItem> for LineItem<'box_tree> {
+    fn from(value: RubySegmentItem) -> Self {
+        Self::Ruby(value)
+    }
+}
+
+impl<'box_tree> From<MathItem> for LineItem<'box_tree> {
+    fn from(value: MathItem) -> Self {
+        Self::Math(value)
+    }
+}
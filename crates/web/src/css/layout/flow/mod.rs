@@ -2,9 +2,16 @@ mod block;
 mod builder;
 mod float;
 mod inline;
+mod multicol;
 mod positioning;
+mod ruby;
 
-pub(crate) use block::{BlockContainer, BlockFormattingContext, BlockLevelBox, InFlowBlockBox};
+pub(crate) use block::{
+    BlockContainer, BlockFormattingContext, BlockLevelBox, ContentLayoutInfo, InFlowBlockBox,
+};
 pub use builder::BlockContainerBuilder;
 use float::{FloatContext, FloatingBox};
-use inline::{InlineBox, InlineFormattingContext, InlineLevelBox, TextRun};
+pub(crate) use inline::InlineLevelBox;
+use inline::{InlineBox, InlineFormattingContext, TextRun};
+pub(crate) use ruby::RubySegment;
+use ruby::{RubyAnnotation, RubyContainer};
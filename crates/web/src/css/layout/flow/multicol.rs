@@ -0,0 +1,279 @@
+//! Multi-column layout
+//!
+//! The contents of a multi-column container are laid out in a single column first,
+//! which is then split into column boxes that are placed next to each other.
+//!
+//! <https://drafts.csswg.org/css-multicol/>
+
+use math::{Rectangle, Vec2D};
+
+use crate::css::{
+    fragment_tree::FragmentationContext,
+    layout::{sizing::ContentSizes, ContainingBlock, Pixels},
+    style::computed::{Direction, LineStyle},
+    values::{AutoOr, Gap},
+    ComputedStyle,
+};
+
+use super::{block::BlockFormattingContextState, BlockContainer, ContentLayoutInfo};
+
+/// How close balanced columns are to the shortest possible column height
+const BALANCING_PRECISION: Pixels = Pixels(0.5);
+
+/// The number and size of the column boxes of a multi-column container
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct ColumnLayout {
+    count: usize,
+    width: Pixels,
+    gap: Pixels,
+}
+
+impl ColumnLayout {
+    /// Determine the columns of a multi-column container whose content box has the given width
+    #[must_use]
+    pub fn compute(style: &ComputedStyle, available_width: Pixels) -> Self {
+        let count = style.column_count().map(|count| count.get() as usize);
+        let width = style.column_width().map(|width| width.max(Pixels::ZERO));
+        let gap = used_column_gap(style, available_width);
+
+        Self::new(count, width, gap, available_width)
+    }
+
+    /// <https://drafts.csswg.org/css-multicol/#pseudo-algorithm>
+    #[must_use]
+    fn new(
+        count: AutoOr<usize>,
+        width: AutoOr<Pixels>,
+        gap: Pixels,
+        available_width: Pixels,
+    ) -> Self {
+        let (count, width) = match (count, width) {
+            (AutoOr::NotAuto(count), AutoOr::Auto) => {
+                let width = (available_width - gap * (count - 1) as f32) / count as f32;
+                (count, width.max(Pixels::ZERO))
+            },
+            (count, AutoOr::NotAuto(width)) => {
+                // There are as many columns as fit into the container, but always at least one
+                let column_and_gap = (width + gap).max(Pixels(1.));
+                let fitting_columns =
+                    ((available_width + gap).value() / column_and_gap.value()).floor() as usize;
+                let count = match count {
+                    AutoOr::Auto => fitting_columns.max(1),
+                    AutoOr::NotAuto(count) => count.min(fitting_columns.max(1)),
+                };
+
+                let width = (available_width + gap) / count as f32 - gap;
+                (count, width.max(Pixels::ZERO))
+            },
+            (AutoOr::Auto, AutoOr::Auto) => (1, available_width),
+        };
+
+        Self { count, width, gap }
+    }
+
+    /// The content sizes of a multi-column container whose contents have the given content sizes
+    ///
+    /// <https://drafts.csswg.org/css-sizing-3/#multicol-intrinsic>
+    #[must_use]
+    pub fn content_sizes(style: &ComputedStyle, contents: ContentSizes) -> ContentSizes {
+        let count = style.column_count().map(|count| count.get()).unwrap_or(1) as f32;
+
+        // Percentage gaps are treated as zero, like all percentages while computing intrinsic sizes
+        let gaps = used_column_gap(style, Pixels::ZERO) * (count - 1.);
+
+        let min_content = match *style.column_width() {
+            AutoOr::NotAuto(width) => width.min(contents.min_content),
+            AutoOr::Auto => contents.min_content * count + gaps,
+        };
+        let column_width = style.column_width().unwrap_or_default();
+        let max_content = contents.max_content.max(column_width) * count + gaps;
+
+        ContentSizes {
+            min_content,
+            max_content: max_content.max(min_content),
+        }
+    }
+
+    /// Lay out the contents in columns of equal height
+    ///
+    /// The columns are balanced, so they are only as tall as necessary. If the container
+    /// has a definite height, the columns are never taller than that and the content that
+    /// does not fit into them overflows into additional columns.
+    ///
+    /// Returns the rules between the columns too, relative to the content area of the container.
+    #[must_use]
+    pub fn layout(
+        &self,
+        style: &ComputedStyle,
+        contents: &BlockContainer<'_>,
+        containing_block: ContainingBlock,
+    ) -> (ContentLayoutInfo, Vec<Rectangle<Pixels>>) {
+        // Multi-column containers establish a new formatting context for their contents
+        let column = ContainingBlock::new(self.width, Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+        let mut formatting_context = BlockFormattingContextState::new(column);
+        let content_info = contents.layout(column, &mut formatting_context);
+
+        let fragmentation = FragmentationContext::new(&content_info.fragments, content_info.height);
+        let balanced_height = self.balanced_height(&fragmentation, content_info.height);
+        let column_height = containing_block
+            .height()
+            .map_or(balanced_height, |height| balanced_height.min(height));
+        let breaks = fragmentation.breaks(column_height);
+        let columns = FragmentationContext::split(&content_info.fragments, &breaks);
+
+        let column_position = |index: usize| {
+            let offset = (self.width + self.gap) * index as f32;
+            if *style.direction() == Direction::Rtl {
+                containing_block.width() - self.width - offset
+            } else {
+                offset
+            }
+        };
+
+        let mut fragments = vec![];
+        for (index, column_fragments) in columns.iter().enumerate() {
+            let offset = Vec2D::new(column_position(index), Pixels::ZERO);
+            fragments.extend(column_fragments.iter().cloned().map(|mut fragment| {
+                fragment.translate(offset);
+                fragment
+            }));
+        }
+
+        // Rules are drawn in the middle of the gaps between columns that both have content
+        // https://drafts.csswg.org/css-multicol/#column-gaps-and-rules
+        let rule_width = used_column_rule_width(style);
+        let mut column_rules = vec![];
+        if rule_width != Pixels::ZERO {
+            for index in 1..columns.len() {
+                let gap_start = column_position(index - 1).min(column_position(index)) + self.width;
+                let left = gap_start + (self.gap - rule_width) / 2.;
+                column_rules.push(Rectangle::from_position_and_size(
+                    Vec2D::new(left, Pixels::ZERO),
+                    rule_width,
+                    column_height,
+                ));
+            }
+        }
+
+        let content_info = ContentLayoutInfo {
+            height: column_height,
+            fragments,
+            has_in_flow_content: content_info.has_in_flow_content,
+        };
+
+        (content_info, column_rules)
+    }
+
+    /// The smallest column height at which the content fits into the columns
+    ///
+    /// <https://drafts.csswg.org/css-multicol/#valdef-column-fill-balance>
+    #[must_use]
+    fn balanced_height(
+        &self,
+        fragmentation: &FragmentationContext,
+        content_height: Pixels,
+    ) -> Pixels {
+        let fits = |height| fragmentation.breaks(height).len() <= self.count;
+
+        // Columns that are shorter than this could never hold all of the content
+        let mut too_short = content_height / self.count as f32;
+        if fits(too_short) {
+            return too_short;
+        }
+
+        let mut tall_enough = content_height;
+        while tall_enough - too_short > BALANCING_PRECISION {
+            let height = (too_short + tall_enough) / 2.;
+            if fits(height) {
+                tall_enough = height;
+            } else {
+                too_short = height;
+            }
+        }
+
+        tall_enough
+    }
+}
+
+/// The space between columns, where `normal` is `1em`
+///
+/// <https://drafts.csswg.org/css-multicol/#column-gap>
+#[must_use]
+fn used_column_gap(style: &ComputedStyle, percentage_basis: Pixels) -> Pixels {
+    let gap = match *style.column_gap() {
+        Gap::Normal => *style.font_size(),
+        Gap::Length(gap) => gap.resolve_against(percentage_basis),
+    };
+
+    gap.max(Pixels::ZERO)
+}
+
+/// <https://drafts.csswg.org/css-multicol/#crw>
+#[must_use]
+fn used_column_rule_width(style: &ComputedStyle) -> Pixels {
+    match style.column_rule_style() {
+        LineStyle::None | LineStyle::Hidden => Pixels::ZERO,
+        _ => *style.column_rule_width(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AVAILABLE_WIDTH: Pixels = Pixels(100.);
+
+    #[test]
+    fn column_count() {
+        let columns = ColumnLayout::new(
+            AutoOr::NotAuto(3),
+            AutoOr::Auto,
+            Pixels(5.),
+            AVAILABLE_WIDTH,
+        );
+        assert_eq!(columns.count, 3);
+        assert_eq!(columns.width, Pixels(30.));
+
+        // Columns never have a negative width
+        let columns = ColumnLayout::new(
+            AutoOr::NotAuto(3),
+            AutoOr::Auto,
+            Pixels(80.),
+            AVAILABLE_WIDTH,
+        );
+        assert_eq!(columns.width, Pixels::ZERO);
+    }
+
+    #[test]
+    fn column_width() {
+        // As many columns as fit, which are stretched to fill the container
+        let columns = ColumnLayout::new(
+            AutoOr::Auto,
+            AutoOr::NotAuto(Pixels(40.)),
+            Pixels(5.),
+            AVAILABLE_WIDTH,
+        );
+        assert_eq!(columns.count, 2);
+        assert_eq!(columns.width, Pixels(47.5));
+
+        // The column count is the maximum number of columns
+        let columns = ColumnLayout::new(
+            AutoOr::NotAuto(2),
+            AutoOr::NotAuto(Pixels(10.)),
+            Pixels::ZERO,
+            AVAILABLE_WIDTH,
+        );
+        assert_eq!(columns.count, 2);
+        assert_eq!(columns.width, Pixels(50.));
+
+        // There is always at least one column, even if it is narrower than the column width
+        let columns = ColumnLayout::new(
+            AutoOr::Auto,
+            AutoOr::NotAuto(Pixels(200.)),
+            Pixels(5.),
+            AVAILABLE_WIDTH,
+        );
+        assert_eq!(columns.count, 1);
+        assert_eq!(columns.width, AVAILABLE_WIDTH);
+    }
+}
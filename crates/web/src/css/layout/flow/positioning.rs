@@ -1,17 +1,22 @@
 //! Implements functionality described in [CSS-Position Level 3](https://drafts.csswg.org/css-position)
+//!
+//! The size and position of absolutely positioned boxes are determined by the constraint equations
+//! from CSS2, see <https://drafts.csswg.org/css2/#abs-non-replaced-width> and
+//! <https://drafts.csswg.org/css2/#abs-non-replaced-height>.
 use std::{cmp, fmt, fmt::Write};
 
-use math::{Rectangle, Vec2D};
+use math::Vec2D;
 
 use crate::{
     css::{
         computed_style::ComputedStyle,
         fragment_tree::BoxFragment,
         layout::{
-            formatting_context::IndependentFormattingContext, ContainingBlock, Pixels, Sides, Size,
+            formatting_context::IndependentFormattingContext, sizing::resolve_preferred_size,
+            ContainingBlock, Pixels, Sides, Size,
         },
-        style::computed::Inset,
-        values::AutoOr,
+        style::computed::{Inset, Margin, Padding},
+        values::{AutoOr, PreferredSize},
     },
     dom::{dom_objects, DomPtr},
     TreeDebug, TreeFormatter,
@@ -19,137 +24,296 @@ use crate::{
 
 /// A block-level box with `position: absolute;`
 #[derive(Clone)]
-pub(crate) struct AbsolutelyPositionedBox {
+pub(crate) struct AbsolutelyPositionedBox<'box_tree> {
     pub node: DomPtr<dom_objects::Node>,
     pub style: ComputedStyle,
-    pub content: IndependentFormattingContext,
+    pub content: IndependentFormattingContext<'box_tree>,
 }
 
-/// Computes the used inset properties on a given axis
+/// The values that take part in the constraint equation of an absolutely positioned box on one axis
 ///
-/// `axis_end` is assumed to be the inset of the [end edge](https://drafts.csswg.org/css-writing-modes-4/#css-end),
-/// meaning that if the two insets take up more space than is available and are not `auto`,
-/// then `axis_end` is assumed to be the [weaker inset](https://drafts.csswg.org/css-position/#weaker-inset) and its
-/// value is changed (possibly becoming negative).
-#[must_use]
-fn resolve_inset_on_axis(
-    axis_start: AutoOr<Pixels>,
-    axis_end: AutoOr<Pixels>,
-    axis_size: Pixels,
+/// All values are expected to be resolved against the containing block already. The `start` and `end`
+/// values are in [flow-relative](https://drafts.csswg.org/css-writing-modes-4/#flow-relative-direction) order,
+/// meaning that `start` is `left`/`top` and `end` is `right`/`bottom`.
+#[derive(Clone, Copy, Debug)]
+struct AxisConstraints {
+    inset_start: AutoOr<Pixels>,
+    margin_start: AutoOr<Pixels>,
+    size: AutoOr<Pixels>,
+    margin_end: AutoOr<Pixels>,
+    inset_end: AutoOr<Pixels>,
+
+    /// The sum of the borders and padding on both sides of the box
+    borders_and_padding: Pixels,
+
+    /// The position of the start margin edge of the [hypothetical box](https://drafts.csswg.org/css2/#static-position),
+    /// relative to the containing block
     static_position: Pixels,
-) -> (Pixels, Pixels) {
-    // Informally, a value of "auto" for an inset property produces an inset such that the element is positioned
-    // where it was found in the flow
-    match (axis_start, axis_end) {
-        (AutoOr::NotAuto(l), AutoOr::NotAuto(r)) => (l, r),
-        (AutoOr::NotAuto(l), AutoOr::Auto) => {
-            let r = cmp::min(axis_size - l, Pixels::ZERO);
-            (l, r)
-        },
-        (AutoOr::Auto, AutoOr::NotAuto(r)) => {
-            let l = cmp::min(axis_size - r, Pixels::ZERO);
-            (l, r)
-        },
-        (AutoOr::Auto, AutoOr::Auto) => {
-            // FIXME: This is not correct, the correct results depends on the self-alignment for the axis
-            // We currently always assume a self-alignment of "self-start" (or equivalent)
-            (static_position, Pixels::ZERO)
-        },
-    }
 }
 
-/// <https://drafts.csswg.org/css-position/#inset-modified-containing-block>
-///
-/// The coordinates of the inset-modified containing block are relative to the actual containing block.
-#[must_use]
-pub fn inset_modified_containing_block(
-    containing_block: Size<Pixels>,
-    style: &ComputedStyle,
-    static_position: Vec2D<Pixels>,
-) -> Rectangle<Pixels> {
-    let resolve_inset_property = |p: &Inset, resolve_percentage_against| {
-        p.map(|p| p.resolve_against(resolve_percentage_against))
-    };
-
-    let width = containing_block.width;
-    let height = containing_block.height;
-
-    let left = resolve_inset_property(style.left(), width);
-    let right = resolve_inset_property(style.right(), width);
-    let top = resolve_inset_property(style.top(), height);
-    let bottom = resolve_inset_property(style.bottom(), height);
-
-    let (inset_left, inset_right) =
-        resolve_inset_on_axis(left, right, containing_block.width, static_position.x);
-    let (inset_top, inset_bottom) =
-        resolve_inset_on_axis(top, bottom, containing_block.height, static_position.y);
-
-    let top_left = Vec2D::new(inset_left, inset_top);
-    let bottom_right = Vec2D::new(containing_block.width, containing_block.height)
-        - Vec2D::new(inset_right, inset_bottom);
-
-    Rectangle::from_corners(top_left, bottom_right)
+/// The used values of an absolutely positioned box on one axis
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct AxisLayout {
+    inset_start: Pixels,
+    margin_start: Pixels,
+    size: Pixels,
+    margin_end: Pixels,
+    inset_end: Pixels,
+}
+
+impl AxisConstraints {
+    /// Solve the constraint equation
+    ///
+    /// `inset_start + margin_start + borders_and_padding + size + margin_end + inset_end = containing_block_size`
+    ///
+    /// `auto_size` is called if the size cannot be determined from the equation. It receives the space that is
+    /// available to the box and must return the size of its content box - for the inline axis, that is the
+    /// shrink-to-fit width and for the block axis, the height of the content.
+    ///
+    /// `is_inline_axis` is required because auto margins are resolved slightly differently on the two axes.
+    #[must_use]
+    fn solve(
+        self,
+        containing_block_size: Pixels,
+        is_inline_axis: bool,
+        auto_size: impl FnOnce(Pixels) -> Pixels,
+    ) -> AxisLayout {
+        if let (AutoOr::NotAuto(inset_start), AutoOr::NotAuto(size), AutoOr::NotAuto(inset_end)) =
+            (self.inset_start, self.size, self.inset_end)
+        {
+            let remaining_space =
+                containing_block_size - inset_start - self.borders_and_padding - size - inset_end;
+
+            let (margin_start, margin_end) = match (self.margin_start, self.margin_end) {
+                (AutoOr::Auto, AutoOr::Auto) => {
+                    // Both margins get equal values, unless this would make them negative, in which case
+                    // the start margin is zero (only on the inline axis)
+                    if is_inline_axis && remaining_space < Pixels::ZERO {
+                        (Pixels::ZERO, remaining_space)
+                    } else {
+                        (remaining_space / 2., remaining_space / 2.)
+                    }
+                },
+                (AutoOr::Auto, AutoOr::NotAuto(margin_end)) => {
+                    (remaining_space - margin_end, margin_end)
+                },
+                (AutoOr::NotAuto(margin_start), AutoOr::Auto) => {
+                    (margin_start, remaining_space - margin_start)
+                },
+                (AutoOr::NotAuto(margin_start), AutoOr::NotAuto(margin_end)) => {
+                    // The values are over-constrained, ignore the end inset and solve for it
+                    // FIXME: If the "direction" property is "rtl", we should ignore the start inset
+                    //        on the inline axis instead
+                    return AxisLayout {
+                        inset_start,
+                        margin_start,
+                        size,
+                        margin_end,
+                        inset_end: remaining_space + inset_end - margin_start - margin_end,
+                    };
+                },
+            };
+
+            return AxisLayout {
+                inset_start,
+                margin_start,
+                size,
+                margin_end,
+                inset_end,
+            };
+        }
+
+        // Otherwise, auto margins are zero and one of six rules applies
+        let margin_start = self.margin_start.unwrap_or_default();
+        let margin_end = self.margin_end.unwrap_or_default();
+
+        // The space that is shared by the insets and the size of the box
+        let available_space =
+            containing_block_size - margin_start - self.borders_and_padding - margin_end;
+
+        let (inset_start, size, inset_end) = match (self.inset_start, self.size, self.inset_end) {
+            (AutoOr::Auto, AutoOr::Auto, AutoOr::Auto) => {
+                // Set the start inset to the static position and apply rule 3
+                // FIXME: If the "direction" property is "rtl", we should set the end inset instead
+                let inset_start = self.static_position;
+                let size = auto_size(available_space - inset_start);
+                (inset_start, size, available_space - inset_start - size)
+            },
+            (AutoOr::Auto, AutoOr::Auto, AutoOr::NotAuto(inset_end)) => {
+                // Rule 1
+                let size = auto_size(available_space - inset_end);
+                (available_space - size - inset_end, size, inset_end)
+            },
+            (AutoOr::Auto, AutoOr::NotAuto(size), AutoOr::Auto) => {
+                // Rule 2
+                let inset_start = self.static_position;
+                (inset_start, size, available_space - inset_start - size)
+            },
+            (AutoOr::NotAuto(inset_start), AutoOr::Auto, AutoOr::Auto) => {
+                // Rule 3
+                let size = auto_size(available_space - inset_start);
+                (inset_start, size, available_space - inset_start - size)
+            },
+            (AutoOr::Auto, AutoOr::NotAuto(size), AutoOr::NotAuto(inset_end)) => {
+                // Rule 4
+                (available_space - size - inset_end, size, inset_end)
+            },
+            (AutoOr::NotAuto(inset_start), AutoOr::Auto, AutoOr::NotAuto(inset_end)) => {
+                // Rule 5
+                let size = cmp::max(available_space - inset_start - inset_end, Pixels::ZERO);
+                (inset_start, size, inset_end)
+            },
+            (AutoOr::NotAuto(inset_start), AutoOr::NotAuto(size), AutoOr::Auto) => {
+                // Rule 6
+                (inset_start, size, available_space - inset_start - size)
+            },
+            (AutoOr::NotAuto(_), AutoOr::NotAuto(_), AutoOr::NotAuto(_)) => {
+                unreachable!("handled above")
+            },
+        };
+
+        AxisLayout {
+            inset_start,
+            margin_start,
+            size,
+            margin_end,
+            inset_end,
+        }
+    }
 }
 
-impl AbsolutelyPositionedBox {
+impl<'box_tree> AbsolutelyPositionedBox<'box_tree> {
     /// <https://drafts.csswg.org/css-position/#abspos-layout>
     ///
     /// `containing_block` references the nearest [absolut positioning containing block](https://drafts.csswg.org/css-position/#absolute-positioning-containing-block) and
-    /// therefore always has a definite size
+    /// therefore always has a definite size.
+    /// `static_position` is the position of the top-left margin edge of the
+    /// [hypothetical box](https://drafts.csswg.org/css2/#static-position), relative to the containing block.
     pub fn layout(
         &self,
         containing_block: Size<Pixels>,
         static_position: Vec2D<Pixels>,
     ) -> BoxFragment {
-        // Calculate the available space for the abspos element
-        let inset_modified_block =
-            inset_modified_containing_block(containing_block, &self.style, static_position);
-
-        // Resolve its width and height
-        // https://drafts.csswg.org/css-position/#abspos-auto-size
-        // FIXME: We always assume stretch-fit-size in case of "auto", but depending
-        //        on the self-alignment property for the axis, it could be content-fit
-        let width = self
-            .style
-            .width()
-            .map(|p| p.resolve_against(containing_block.width.into()))
-            .unwrap_or(containing_block.width);
-
-        let height = self
-            .style
-            .height()
-            .map(|p| p.resolve_against(containing_block.height.into()))
-            .unwrap_or(containing_block.height);
-
-        let content_size = Size { width, height };
-
-        let borders = self.style.used_border_widths();
-
-        // Calculate the value of "auto" margins
-        // https://drafts.csswg.org/css-position/#abspos-margins
-        let margins =
-            calculate_used_margins(&self.style, inset_modified_block, content_size, borders);
-
-        // Align the elements margin box within the inset modified block
-        // FIXME: We don't do this yet
-        let content_offset = Vec2D::new(margins.left + borders.left, margins.top + borders.top);
-        let top_left = inset_modified_block.top_left() + content_offset;
-
-        // FIXME: Actually include padding here
-        let content_area = Rectangle::from_position_and_size(top_left, width, height);
-        let padding_area = borders.surround(content_area);
-        let margin_area = margins.surround(padding_area);
-
-        let containing_block =
-            ContainingBlock::new(width, Vec2D::new(Pixels::ZERO, Pixels::ZERO)).with_height(height);
+        // NOTE: This is not a mistake, *both* horizontal and vertical margin (and padding) percentages
+        //       are calculated with respect to the *width* of the containing block.
+        let resolve_margin =
+            |margin: &Margin| margin.map(|p| p.resolve_against(containing_block.width));
+        let resolve_padding = |padding: &Padding| padding.resolve_against(containing_block.width);
+        let resolve_inset = |inset: &Inset, resolve_percentage_against: Pixels| {
+            inset.map(|p| p.resolve_against(resolve_percentage_against))
+        };
+
+        // Replaced elements have their size determined as if they were inline and
+        // (like block-level replaced elements) currently don't have borders or padding
+        // https://drafts.csswg.org/css2/#abs-replaced-width
+        let (width, height, borders, padding) = match &self.content {
+            IndependentFormattingContext::Replaced(replaced_element) => {
+                let size = replaced_element.used_size_if_it_was_inline(
+                    ContainingBlock::new(
+                        containing_block.width,
+                        Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+                    )
+                    .with_height(containing_block.height),
+                );
+
+                (
+                    AutoOr::NotAuto(size.width),
+                    AutoOr::NotAuto(size.height),
+                    Sides::all(Pixels::ZERO),
+                    Sides::all(Pixels::ZERO),
+                )
+            },
+            IndependentFormattingContext::NonReplaced(_) => {
+                let padding = Sides {
+                    top: resolve_padding(self.style.padding_top()),
+                    right: resolve_padding(self.style.padding_right()),
+                    bottom: resolve_padding(self.style.padding_bottom()),
+                    left: resolve_padding(self.style.padding_left()),
+                };
+
+                // NOTE: For absolutely positioned boxes, "auto" already behaves like "fit-content"
+                let width = match *self.style.width() {
+                    PreferredSize::FitContent(None) => AutoOr::Auto,
+                    width => resolve_preferred_size(
+                        width,
+                        containing_block.width,
+                        containing_block.width,
+                        || self.content.content_sizes(),
+                    ),
+                };
+
+                (
+                    width,
+                    self.style
+                        .height()
+                        .map(|p| p.resolve_against(containing_block.height)),
+                    self.style.used_border_widths(),
+                    padding,
+                )
+            },
+        };
+
+        // Resolve the horizontal constraints first, as the height of the box might depend on its width
+        let horizontal = AxisConstraints {
+            inset_start: resolve_inset(self.style.left(), containing_block.width),
+            margin_start: resolve_margin(self.style.margin_left()),
+            size: width,
+            margin_end: resolve_margin(self.style.margin_right()),
+            inset_end: resolve_inset(self.style.right(), containing_block.width),
+            borders_and_padding: borders.horizontal_sum() + padding.horizontal_sum(),
+            static_position: static_position.x,
+        }
+        .solve(containing_block.width, true, |available_width| {
+            self.content.content_sizes().shrink_to_fit(available_width)
+        });
+
+        let vertical = AxisConstraints {
+            inset_start: resolve_inset(self.style.top(), containing_block.height),
+            margin_start: resolve_margin(self.style.margin_top()),
+            size: height,
+            margin_end: resolve_margin(self.style.margin_bottom()),
+            inset_end: resolve_inset(self.style.bottom(), containing_block.height),
+            borders_and_padding: borders.vertical_sum() + padding.vertical_sum(),
+            static_position: static_position.y,
+        }
+        .solve(containing_block.height, false, |_| {
+            self.content_height(horizontal.size)
+        });
+
+        let margins = Sides {
+            top: vertical.margin_start,
+            right: horizontal.margin_end,
+            bottom: vertical.margin_end,
+            left: horizontal.margin_start,
+        };
+
+        let top_left = Vec2D::new(
+            horizontal.inset_start + margins.left + borders.left + padding.left,
+            vertical.inset_start + margins.top + borders.top + padding.top,
+        );
+        let content_size = Size {
+            width: horizontal.size,
+            height: vertical.size,
+        };
+
+        let content_area = content_size.at_position(top_left);
+        let padding_area = padding.surround(content_area);
+        let margin_area = margins.surround(borders.surround(padding_area));
 
         // Absolute elements establish a new formatting context for their elements
         let fragments = match &self.content {
             IndependentFormattingContext::Replaced(replaced_element) => {
-                _ = replaced_element;
-                todo!("implement absolutely positioned replaced elements");
+                vec![replaced_element
+                    .content()
+                    .create_fragment(Vec2D::new(Pixels::ZERO, Pixels::ZERO), content_size)]
             },
             IndependentFormattingContext::NonReplaced(bfc) => {
+                let containing_block = ContainingBlock::new(
+                    content_size.width,
+                    Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+                )
+                .with_height(content_size.height);
+
                 bfc.layout(containing_block).fragments
             },
         };
@@ -164,90 +328,26 @@ impl AbsolutelyPositionedBox {
             fragments,
         )
     }
-}
 
-#[must_use]
-fn calculate_used_margins(
-    style: &ComputedStyle,
-    inset_modified_block: Rectangle<Pixels>,
-    content_size: Size<Pixels>,
-    borders: Sides<Pixels>,
-) -> Sides<Pixels> {
-    let margin_left = style
-        .margin_left()
-        .map(|p| p.resolve_against(inset_modified_block.width().into()));
-    let margin_right = style
-        .margin_right()
-        .map(|p| p.resolve_against(inset_modified_block.width().into()));
-    let margin_top = style
-        .margin_top()
-        .map(|p| p.resolve_against(inset_modified_block.height().into()));
-    let margin_bottom = style
-        .margin_bottom()
-        .map(|p| p.resolve_against(inset_modified_block.height().into()));
-
-    let (margin_left, margin_right) = calculate_margins_for_axis(
-        (*style.left(), *style.right()),
-        inset_modified_block.width(),
-        content_size.width + borders.left + borders.right, // FIXME: padding
-        (margin_left, margin_right),
-        true,
-    );
-    let (margin_top, margin_bottom) = calculate_margins_for_axis(
-        (*style.top(), *style.bottom()),
-        inset_modified_block.width(),
-        content_size.height + borders.top + borders.bottom, // FIXME: padding
-        (margin_top, margin_bottom),
-        false,
-    );
-
-    Sides {
-        top: margin_top,
-        right: margin_right,
-        bottom: margin_bottom,
-        left: margin_left,
-    }
-}
+    /// Compute the height of the box if it is determined by its content
+    ///
+    /// <https://drafts.csswg.org/css2/#root-height>
+    #[must_use]
+    fn content_height(&self, width: Pixels) -> Pixels {
+        let containing_block = ContainingBlock::new(width, Vec2D::new(Pixels::ZERO, Pixels::ZERO));
 
-/// Compute the used margins inside absolut layout
-///
-/// `axis_inset` and `margins` are expected to be passed in [flow-relative](https://drafts.csswg.org/css-writing-modes-4/#flow-relative-direction) order,
-/// meaning that the first value is on the [block-start](https://drafts.csswg.org/css-writing-modes-4/#block-start)/[inline-start](https://drafts.csswg.org/css-writing-modes-4/#inline-start)
-/// side and the second value is at the [block-end](https://drafts.csswg.org/css-writing-modes-4/#block-end)/[inline-end](https://drafts.csswg.org/css-writing-modes-4/#inline-end)
-#[must_use]
-fn calculate_margins_for_axis(
-    axis_inset: (Inset, Inset),
-    available_space: Pixels,
-    used_box_size: Pixels,
-    margins: (AutoOr<Pixels>, AutoOr<Pixels>),
-    is_inline_axis: bool,
-) -> (Pixels, Pixels) {
-    if axis_inset.0.is_auto() || axis_inset.1.is_auto() {
-        // If either inset is auto, then any auto margin resolves to zero
-        (
-            margins.0.unwrap_or(Pixels::ZERO),
-            margins.1.unwrap_or(Pixels::ZERO),
-        )
-    } else {
-        let remaining_space = available_space - used_box_size;
-
-        // The remaining space is distributed among any auto margins
-        match (margins.0, margins.1) {
-            (AutoOr::Auto, AutoOr::Auto) => {
-                if is_inline_axis && remaining_space.is_sign_negative() {
-                    (Pixels::ZERO, remaining_space)
-                } else {
-                    (remaining_space / 2., remaining_space / 2.)
-                }
+        match &self.content {
+            IndependentFormattingContext::Replaced(replaced_element) => {
+                replaced_element
+                    .used_size_if_it_was_inline(containing_block)
+                    .height
             },
-            (AutoOr::Auto, AutoOr::NotAuto(r)) => (remaining_space - r, r),
-            (AutoOr::NotAuto(l), AutoOr::Auto) => (l, remaining_space - l),
-            (AutoOr::NotAuto(l), AutoOr::NotAuto(r)) => (l, r),
+            IndependentFormattingContext::NonReplaced(bfc) => bfc.layout(containing_block).height,
         }
     }
 }
 
-impl TreeDebug for AbsolutelyPositionedBox {
+impl<'box_tree> TreeDebug for AbsolutelyPositionedBox<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
         formatter.indent()?;
         write!(formatter, "Block Box")?;
@@ -259,3 +359,116 @@ impl TreeDebug for AbsolutelyPositionedBox {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTAINING_BLOCK_SIZE: Pixels = Pixels(100.);
+
+    fn constraints(
+        inset_start: AutoOr<Pixels>,
+        size: AutoOr<Pixels>,
+        inset_end: AutoOr<Pixels>,
+    ) -> AxisConstraints {
+        AxisConstraints {
+            inset_start,
+            margin_start: AutoOr::NotAuto(Pixels::ZERO),
+            size,
+            margin_end: AutoOr::NotAuto(Pixels::ZERO),
+            inset_end,
+            borders_and_padding: Pixels(10.),
+            static_position: Pixels(5.),
+        }
+    }
+
+    #[test]
+    fn static_position_and_shrink_to_fit() {
+        let layout = constraints(AutoOr::Auto, AutoOr::Auto, AutoOr::Auto).solve(
+            CONTAINING_BLOCK_SIZE,
+            true,
+            |available| {
+                assert_eq!(available, Pixels(85.));
+                Pixels(20.)
+            },
+        );
+
+        assert_eq!(layout.inset_start, Pixels(5.));
+        assert_eq!(layout.size, Pixels(20.));
+        assert_eq!(layout.inset_end, Pixels(65.));
+    }
+
+    #[test]
+    fn solve_for_start_inset() {
+        // Rule 1
+        let layout = constraints(AutoOr::Auto, AutoOr::Auto, AutoOr::NotAuto(Pixels(30.))).solve(
+            CONTAINING_BLOCK_SIZE,
+            true,
+            |available| {
+                assert_eq!(available, Pixels(60.));
+                Pixels(20.)
+            },
+        );
+        assert_eq!(layout.inset_start, Pixels(40.));
+
+        // Rule 4
+        let layout = constraints(
+            AutoOr::Auto,
+            AutoOr::NotAuto(Pixels(50.)),
+            AutoOr::NotAuto(Pixels(30.)),
+        )
+        .solve(CONTAINING_BLOCK_SIZE, true, |_| unreachable!());
+        assert_eq!(layout.inset_start, Pixels(10.));
+    }
+
+    #[test]
+    fn solve_for_size() {
+        // Rule 5
+        let layout = constraints(
+            AutoOr::NotAuto(Pixels(20.)),
+            AutoOr::Auto,
+            AutoOr::NotAuto(Pixels(30.)),
+        )
+        .solve(CONTAINING_BLOCK_SIZE, true, |_| unreachable!());
+        assert_eq!(layout.size, Pixels(40.));
+    }
+
+    #[test]
+    fn auto_margins() {
+        let mut constraints = constraints(
+            AutoOr::NotAuto(Pixels(10.)),
+            AutoOr::NotAuto(Pixels(40.)),
+            AutoOr::NotAuto(Pixels(10.)),
+        );
+        constraints.margin_start = AutoOr::Auto;
+        constraints.margin_end = AutoOr::Auto;
+
+        let layout = constraints.solve(CONTAINING_BLOCK_SIZE, true, |_| unreachable!());
+        assert_eq!(layout.margin_start, Pixels(15.));
+        assert_eq!(layout.margin_end, Pixels(15.));
+
+        // Negative margins are only split evenly on the block axis
+        constraints.size = AutoOr::NotAuto(Pixels(90.));
+        let layout = constraints.solve(CONTAINING_BLOCK_SIZE, true, |_| unreachable!());
+        assert_eq!(layout.margin_start, Pixels::ZERO);
+        assert_eq!(layout.margin_end, Pixels(-20.));
+
+        let layout = constraints.solve(CONTAINING_BLOCK_SIZE, false, |_| unreachable!());
+        assert_eq!(layout.margin_start, Pixels(-10.));
+        assert_eq!(layout.margin_end, Pixels(-10.));
+    }
+
+    #[test]
+    fn over_constrained() {
+        // The end inset is ignored
+        let layout = constraints(
+            AutoOr::NotAuto(Pixels(10.)),
+            AutoOr::NotAuto(Pixels(50.)),
+            AutoOr::NotAuto(Pixels(10.)),
+        )
+        .solve(CONTAINING_BLOCK_SIZE, true, |_| unreachable!());
+
+        assert_eq!(layout.inset_start, Pixels(10.));
+        assert_eq!(layout.inset_end, Pixels(30.));
+    }
+}
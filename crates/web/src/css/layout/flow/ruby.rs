@@ -0,0 +1,273 @@
+//! Ruby annotations, like the readings that are written above kanji in Japanese text
+//!
+//! A ruby container is split into segments, each of which pairs a ruby base with the
+//! annotation that follows it. Segments are laid out like atomic inlines: The annotation
+//! is centered above the base and the line box grows to make room for it.
+//!
+//! Inline formatting contexts with a vertical writing mode are laid out as if they were
+//! horizontal and then rotated, so the annotation ends up beside the base on the block-start
+//! side (to the right in `vertical-rl`).
+//!
+//! <https://drafts.csswg.org/css-ruby/>
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment},
+        layout::{sizing::ContentSizes, Pixels, Sides, Size},
+        ComputedStyle,
+    },
+    dom::{dom_objects, DomPtr},
+};
+
+use super::inline::{content_sizes_of_inline_level_boxes, layout_on_single_line, InlineLevelBox};
+
+/// The box generated by an element with `display: ruby`
+///
+/// <https://drafts.csswg.org/css-ruby/#ruby-container>
+#[derive(Clone, Debug)]
+pub struct RubyContainer<'box_tree> {
+    node: DomPtr<dom_objects::Node>,
+    style: ComputedStyle,
+    segments: &'box_tree [RubySegment<'box_tree>],
+}
+
+/// A ruby base together with its annotation
+///
+/// <https://drafts.csswg.org/css-ruby/#ruby-segment>
+#[derive(Clone, Debug)]
+pub struct RubySegment<'box_tree> {
+    base: &'box_tree [InlineLevelBox<'box_tree>],
+
+    /// Bases without an annotation are laid out like regular inline content
+    annotation: Option<RubyAnnotation<'box_tree>>,
+}
+
+/// The box generated by an element with `display: ruby-text`
+#[derive(Clone, Debug)]
+pub struct RubyAnnotation<'box_tree> {
+    node: DomPtr<dom_objects::Node>,
+    style: ComputedStyle,
+    contents: &'box_tree [InlineLevelBox<'box_tree>],
+}
+
+/// A ruby segment that was laid out, but not yet placed on a line
+#[derive(Clone, Debug)]
+pub(super) struct RubySegmentItem {
+    style: ComputedStyle,
+    base: (Vec<Fragment>, Size<Pixels>),
+    annotation: Option<LaidOutAnnotation>,
+}
+
+#[derive(Clone, Debug)]
+struct LaidOutAnnotation {
+    node: DomPtr<dom_objects::Node>,
+    style: ComputedStyle,
+    fragments: Vec<Fragment>,
+    size: Size<Pixels>,
+}
+
+impl<'box_tree> RubyContainer<'box_tree> {
+    #[must_use]
+    pub fn new(
+        node: DomPtr<dom_objects::Node>,
+        style: ComputedStyle,
+        segments: &'box_tree [RubySegment<'box_tree>],
+    ) -> Self {
+        Self {
+            node,
+            style,
+            segments,
+        }
+    }
+
+    #[must_use]
+    pub fn node(&self) -> &DomPtr<dom_objects::Node> {
+        &self.node
+    }
+
+    #[must_use]
+    pub fn style(&self) -> &ComputedStyle {
+        &self.style
+    }
+
+    #[must_use]
+    pub fn segments(&self) -> &'box_tree [RubySegment<'box_tree>] {
+        self.segments
+    }
+
+    /// Line breaks are allowed between the segments, but not within them
+    #[must_use]
+    pub(crate) fn content_sizes(&self) -> ContentSizes {
+        self.segments
+            .iter()
+            .map(|segment| ContentSizes::fixed(segment.width()))
+            .fold(ContentSizes::default(), ContentSizes::append)
+    }
+
+    /// Lay out the base and annotation of every segment
+    #[must_use]
+    pub(super) fn layout_segments(&self) -> Vec<RubySegmentItem> {
+        self.segments
+            .iter()
+            .map(|segment| RubySegmentItem {
+                style: self.style.clone(),
+                base: layout_on_single_line(segment.base),
+                annotation: segment.annotation.as_ref().map(|annotation| {
+                    let (fragments, size) = layout_on_single_line(annotation.contents);
+                    LaidOutAnnotation {
+                        node: annotation.node.clone(),
+                        style: annotation.style.clone(),
+                        fragments,
+                        size,
+                    }
+                }),
+            })
+            .collect()
+    }
+}
+
+impl<'box_tree> RubySegment<'box_tree> {
+    #[must_use]
+    pub fn new(
+        base: &'box_tree [InlineLevelBox<'box_tree>],
+        annotation: Option<RubyAnnotation<'box_tree>>,
+    ) -> Self {
+        Self { base, annotation }
+    }
+
+    #[must_use]
+    pub fn base(&self) -> &'box_tree [InlineLevelBox<'box_tree>] {
+        self.base
+    }
+
+    #[must_use]
+    pub fn annotation(&self) -> Option<&RubyAnnotation<'box_tree>> {
+        self.annotation.as_ref()
+    }
+
+    /// The segment is as wide as the wider one of its base and annotation
+    #[must_use]
+    fn width(&self) -> Pixels {
+        let base_width = content_sizes_of_inline_level_boxes(self.base).max_content;
+        let annotation_width = self.annotation.as_ref().map_or(Pixels::ZERO, |annotation| {
+            content_sizes_of_inline_level_boxes(annotation.contents).max_content
+        });
+
+        base_width.max(annotation_width)
+    }
+}
+
+impl<'box_tree> RubyAnnotation<'box_tree> {
+    #[must_use]
+    pub fn new(
+        node: DomPtr<dom_objects::Node>,
+        style: ComputedStyle,
+        contents: &'box_tree [InlineLevelBox<'box_tree>],
+    ) -> Self {
+        Self {
+            node,
+            style,
+            contents,
+        }
+    }
+
+    #[must_use]
+    pub fn node(&self) -> &DomPtr<dom_objects::Node> {
+        &self.node
+    }
+
+    #[must_use]
+    pub fn contents(&self) -> &'box_tree [InlineLevelBox<'box_tree>] {
+        self.contents
+    }
+}
+
+impl RubySegmentItem {
+    #[must_use]
+    pub fn style(&self) -> &ComputedStyle {
+        &self.style
+    }
+
+    /// The space that the segment takes up on its line, including the annotation
+    #[must_use]
+    pub fn size(&self) -> Size<Pixels> {
+        let (_, base_size) = &self.base;
+        let annotation_size = self.annotation.as_ref().map_or(
+            Size {
+                width: Pixels::ZERO,
+                height: Pixels::ZERO,
+            },
+            |annotation| annotation.size,
+        );
+
+        Size {
+            width: base_size.width.max(annotation_size.width),
+            height: base_size.height + annotation_size.height,
+        }
+    }
+
+    /// Place the segment with its top left corner at `position`
+    ///
+    /// Base and annotation are both centered within the segment.
+    #[must_use]
+    pub fn into_fragment(self, position: Vec2D<Pixels>) -> Fragment {
+        let size = self.size();
+        let (base_fragments, base_size) = self.base;
+
+        let mut children = vec![];
+        let mut base_top = Pixels::ZERO;
+        if let Some(annotation) = self.annotation {
+            let area = Rectangle::from_position_and_size(
+                Vec2D::new((size.width - annotation.size.width) / 2., Pixels::ZERO),
+                annotation.size.width,
+                annotation.size.height,
+            );
+            children.push(wrap_fragments(
+                Some(annotation.node),
+                annotation.style,
+                area,
+                annotation.fragments,
+            ));
+            base_top = annotation.size.height;
+        }
+
+        let base_area = Rectangle::from_position_and_size(
+            Vec2D::new((size.width - base_size.width) / 2., base_top),
+            base_size.width,
+            base_size.height,
+        );
+        children.push(wrap_fragments(
+            None,
+            ComputedStyle::default(),
+            base_area,
+            base_fragments,
+        ));
+
+        let area = size.at_position(position);
+        wrap_fragments(None, self.style, area, children)
+    }
+}
+
+/// Create a box without borders that contains the given fragments
+///
+/// The positions of the fragments are relative to the top left corner of `area`.
+#[must_use]
+fn wrap_fragments(
+    node: Option<DomPtr<dom_objects::Node>>,
+    style: ComputedStyle,
+    area: Rectangle<Pixels>,
+    children: Vec<Fragment>,
+) -> Fragment {
+    BoxFragment::new(
+        node,
+        style,
+        area,
+        Sides::all(Pixels::ZERO),
+        area,
+        area,
+        children,
+    )
+    .into()
+}
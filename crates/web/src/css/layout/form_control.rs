@@ -0,0 +1,99 @@
+//! Layout that is shared between form controls that are rendered like replaced elements,
+//! like [select elements](super::select), [textareas](super::textarea) and
+//! [file upload controls](super::file_input)
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment, TextFragment},
+        values::Color,
+        ComputedStyle, FontMetrics,
+    },
+    dom::{dom_objects, DomPtr},
+};
+
+use super::{replaced::IntrinsicSize, Pixels, Size};
+
+/// The element and style of a form control, at the time that it was laid out
+#[derive(Clone, Debug)]
+pub(crate) struct FormControl {
+    pub node: DomPtr<dom_objects::Node>,
+    pub style: ComputedStyle,
+    pub font_metrics: FontMetrics,
+}
+
+impl FormControl {
+    #[must_use]
+    pub fn new(node: DomPtr<dom_objects::Node>, style: ComputedStyle) -> Self {
+        let font_metrics = FontMetrics::for_style(&style);
+
+        Self {
+            node,
+            style,
+            font_metrics,
+        }
+    }
+
+    /// The height of a single line of text within the control
+    #[must_use]
+    pub fn line_height(&self) -> Pixels {
+        self.style.line_height().used_value(*self.style.font_size())
+    }
+
+    /// The size of a control whose content has the given size, including its borders
+    #[must_use]
+    pub fn intrinsic_size(&self, content_width: Pixels, content_height: Pixels) -> IntrinsicSize {
+        let borders = self.style.used_border_widths();
+
+        IntrinsicSize {
+            width: Some(content_width + borders.horizontal_sum()),
+            height: Some(content_height + borders.vertical_sum()),
+            aspect_ratio: None,
+        }
+    }
+
+    /// A single line of text, `position` is relative to the content area of the control
+    #[must_use]
+    pub fn text_fragment(&self, text: &str, position: Vec2D<Pixels>, color: Color) -> Fragment {
+        let area = Rectangle::from_position_and_size(
+            position,
+            self.font_metrics.width_of(text),
+            self.line_height(),
+        );
+        TextFragment::new(text.to_owned(), area, color, self.font_metrics.clone()).into()
+    }
+
+    /// Create a fragment for the control, the borders of the element are drawn inside the given area
+    ///
+    /// `children` receives the content area of the control and creates the fragments inside it.
+    /// These are positioned relative to the content area.
+    #[must_use]
+    pub fn create_fragment<F>(
+        &self,
+        position: Vec2D<Pixels>,
+        size: Size<Pixels>,
+        children: F,
+    ) -> Fragment
+    where
+        F: FnOnce(Rectangle<Pixels>) -> Vec<Fragment>,
+    {
+        let area = size.at_position(position);
+        let borders = self.style.used_border_widths();
+        let content_area = Rectangle::from_corners(
+            area.top_left() + Vec2D::new(borders.left, borders.top),
+            area.bottom_right() - Vec2D::new(borders.right, borders.bottom),
+        );
+
+        BoxFragment::new(
+            Some(self.node.clone()),
+            self.style.clone(),
+            area,
+            borders,
+            content_area,
+            content_area,
+            children(content_area),
+        )
+        .into()
+    }
+}
@@ -9,30 +9,35 @@ use crate::{
 use super::{
     flow::{self, BlockFormattingContext},
     replaced::ReplacedElement,
+    sizing::ContentSizes,
+    BoxTreeArena,
 };
 
 /// <https://drafts.csswg.org/css-display/#independent-formatting-context>
 #[derive(Clone)]
-pub(crate) enum IndependentFormattingContext {
+pub(crate) enum IndependentFormattingContext<'box_tree> {
     Replaced(ReplacedElement),
-    NonReplaced(flow::BlockFormattingContext),
+    NonReplaced(flow::BlockFormattingContext<'box_tree>),
 }
 
-impl From<ReplacedElement> for IndependentFormattingContext {
+impl<'box_tree> From<ReplacedElement> for IndependentFormattingContext<'box_tree> {
     fn from(value: ReplacedElement) -> Self {
         Self::Replaced(value)
     }
 }
 
-impl From<flow::BlockFormattingContext> for IndependentFormattingContext {
-    fn from(value: flow::BlockFormattingContext) -> Self {
+impl<'box_tree> From<flow::BlockFormattingContext<'box_tree>>
+    for IndependentFormattingContext<'box_tree>
+{
+    fn from(value: flow::BlockFormattingContext<'box_tree>) -> Self {
         Self::NonReplaced(value)
     }
 }
 
-impl IndependentFormattingContext {
+impl<'box_tree> IndependentFormattingContext<'box_tree> {
     #[must_use]
     pub fn create(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
         element: DomPtr<dom_objects::Element>,
         style_computer: StyleComputer<'_>,
         element_style: ComputedStyle,
@@ -44,6 +49,7 @@ impl IndependentFormattingContext {
             Self::Replaced(replaced_element)
         } else {
             Self::NonReplaced(BlockFormattingContext::build(
+                arena,
                 element,
                 element_style,
                 display_inside,
@@ -51,9 +57,18 @@ impl IndependentFormattingContext {
             ))
         }
     }
+
+    /// <https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes>
+    #[must_use]
+    pub fn content_sizes(&self) -> ContentSizes {
+        match self {
+            Self::Replaced(replaced_element) => replaced_element.content_sizes(),
+            Self::NonReplaced(bfc) => bfc.content_sizes(),
+        }
+    }
 }
 
-impl TreeDebug for IndependentFormattingContext {
+impl<'box_tree> TreeDebug for IndependentFormattingContext<'box_tree> {
     fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
         match self {
             Self::NonReplaced(bfc) => bfc.tree_fmt(formatter),
@@ -0,0 +1,264 @@
+//! Maps [logical directions](https://drafts.csswg.org/css-writing-modes-4/#logical-directions)
+//! to physical ones
+
+use math::{Rectangle, Vec2D};
+
+use crate::css::{
+    computed_style::ComputedStyle,
+    style::computed::{Direction, WritingMode},
+};
+
+use super::{Pixels, Sides, Size};
+
+/// <https://drafts.csswg.org/css-writing-modes-4/#logical-directions>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogicalSide {
+    BlockStart,
+    BlockEnd,
+    InlineStart,
+    InlineEnd,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhysicalSide {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+/// Describes how the logical directions of a box map to physical directions
+///
+/// Logical sizes and positions are stored in [Sizes](Size) and [Vec2Ds](Vec2D)
+/// with the inline axis in place of the horizontal and the block axis in place of
+/// the vertical one, which is how they would be laid out in `horizontal-tb`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LogicalMapping {
+    writing_mode: WritingMode,
+    direction: Direction,
+}
+
+impl LogicalMapping {
+    #[must_use]
+    pub const fn new(writing_mode: WritingMode, direction: Direction) -> Self {
+        Self {
+            writing_mode,
+            direction,
+        }
+    }
+
+    #[must_use]
+    pub fn from_style(style: &ComputedStyle) -> Self {
+        Self::new(*style.writing_mode(), *style.direction())
+    }
+
+    /// Whether the inline axis is vertical
+    #[must_use]
+    pub const fn is_vertical(&self) -> bool {
+        self.writing_mode.is_vertical()
+    }
+
+    /// Whether the block axis points from right to left
+    #[must_use]
+    const fn is_right_to_left_block_flow(&self) -> bool {
+        matches!(
+            self.writing_mode,
+            WritingMode::VerticalRl | WritingMode::SidewaysRl
+        )
+    }
+
+    /// Whether the inline axis points towards the bottom or the right
+    #[must_use]
+    const fn is_inline_axis_reversed(&self) -> bool {
+        let is_rtl = matches!(self.direction, Direction::Rtl);
+
+        // In sideways-lr, text runs from bottom to top
+        if matches!(self.writing_mode, WritingMode::SidewaysLr) {
+            !is_rtl
+        } else {
+            is_rtl
+        }
+    }
+
+    /// <https://drafts.csswg.org/css-writing-modes-4/#logical-to-physical>
+    #[must_use]
+    pub const fn physical_side(&self, side: LogicalSide) -> PhysicalSide {
+        let (start, end) = match side {
+            LogicalSide::BlockStart | LogicalSide::BlockEnd => {
+                if !self.is_vertical() {
+                    (PhysicalSide::Top, PhysicalSide::Bottom)
+                } else if self.is_right_to_left_block_flow() {
+                    (PhysicalSide::Right, PhysicalSide::Left)
+                } else {
+                    (PhysicalSide::Left, PhysicalSide::Right)
+                }
+            },
+            LogicalSide::InlineStart | LogicalSide::InlineEnd => {
+                let (start, end) = if self.is_vertical() {
+                    (PhysicalSide::Top, PhysicalSide::Bottom)
+                } else {
+                    (PhysicalSide::Left, PhysicalSide::Right)
+                };
+
+                if self.is_inline_axis_reversed() {
+                    (end, start)
+                } else {
+                    (start, end)
+                }
+            },
+        };
+
+        match side {
+            LogicalSide::BlockStart | LogicalSide::InlineStart => start,
+            LogicalSide::BlockEnd | LogicalSide::InlineEnd => end,
+        }
+    }
+
+    /// Convert a logical size into a physical size
+    #[must_use]
+    pub fn to_physical_size<T>(self, size: Size<T>) -> Size<T> {
+        if self.is_vertical() {
+            Size {
+                width: size.height,
+                height: size.width,
+            }
+        } else {
+            size
+        }
+    }
+
+    /// Convert logical sides into physical ones
+    ///
+    /// The `top`, `right`, `bottom` and `left` fields of the input refer to the
+    /// block-start, inline-end, block-end and inline-start sides respectively.
+    #[must_use]
+    pub fn to_physical_sides<T: Copy>(self, sides: Sides<T>) -> Sides<T> {
+        let mut physical = sides;
+
+        for (logical_side, value) in [
+            (LogicalSide::BlockStart, sides.top),
+            (LogicalSide::InlineEnd, sides.right),
+            (LogicalSide::BlockEnd, sides.bottom),
+            (LogicalSide::InlineStart, sides.left),
+        ] {
+            match self.physical_side(logical_side) {
+                PhysicalSide::Top => physical.top = value,
+                PhysicalSide::Right => physical.right = value,
+                PhysicalSide::Bottom => physical.bottom = value,
+                PhysicalSide::Left => physical.left = value,
+            }
+        }
+
+        physical
+    }
+
+    /// Convert a logical rectangle within a container into physical coordinates
+    ///
+    /// `container` is the logical size of the area that the rectangle is positioned in.
+    #[must_use]
+    pub fn to_physical_rect(
+        self,
+        rect: Rectangle<Pixels>,
+        container: Size<Pixels>,
+    ) -> Rectangle<Pixels> {
+        let inline_position = if self.is_inline_axis_reversed() {
+            container.width - rect.top_left().x - rect.width()
+        } else {
+            rect.top_left().x
+        };
+
+        let block_position = if self.is_right_to_left_block_flow() {
+            container.height - rect.top_left().y - rect.height()
+        } else {
+            rect.top_left().y
+        };
+
+        let size = self.to_physical_size(Size {
+            width: rect.width(),
+            height: rect.height(),
+        });
+
+        let position = if self.is_vertical() {
+            Vec2D::new(block_position, inline_position)
+        } else {
+            Vec2D::new(inline_position, block_position)
+        };
+
+        size.at_position(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn physical_sides() {
+        let horizontal_rtl = LogicalMapping::new(WritingMode::HorizontalTb, Direction::Rtl);
+        assert_eq!(
+            horizontal_rtl.physical_side(LogicalSide::InlineStart),
+            PhysicalSide::Right
+        );
+        assert_eq!(
+            horizontal_rtl.physical_side(LogicalSide::BlockStart),
+            PhysicalSide::Top
+        );
+
+        let vertical_rl = LogicalMapping::new(WritingMode::VerticalRl, Direction::Ltr);
+        assert_eq!(
+            vertical_rl.physical_side(LogicalSide::BlockStart),
+            PhysicalSide::Right
+        );
+        assert_eq!(
+            vertical_rl.physical_side(LogicalSide::InlineEnd),
+            PhysicalSide::Bottom
+        );
+
+        let sideways_lr = LogicalMapping::new(WritingMode::SidewaysLr, Direction::Ltr);
+        assert_eq!(
+            sideways_lr.physical_side(LogicalSide::BlockStart),
+            PhysicalSide::Left
+        );
+        assert_eq!(
+            sideways_lr.physical_side(LogicalSide::InlineStart),
+            PhysicalSide::Bottom
+        );
+    }
+
+    #[test]
+    fn physical_rect() {
+        let container = Size {
+            width: Pixels(100.),
+            height: Pixels(50.),
+        };
+        let rect = Rectangle::from_position_and_size(
+            Vec2D::new(Pixels(10.), Pixels(5.)),
+            Pixels(30.),
+            Pixels(20.),
+        );
+
+        let horizontal = LogicalMapping::default();
+        assert_eq!(horizontal.to_physical_rect(rect, container), rect);
+
+        // The first line is at the right edge of the container
+        let vertical_rl = LogicalMapping::new(WritingMode::VerticalRl, Direction::Ltr);
+        assert_eq!(
+            vertical_rl.to_physical_rect(rect, container),
+            Rectangle::from_position_and_size(
+                Vec2D::new(Pixels(25.), Pixels(10.)),
+                Pixels(20.),
+                Pixels(30.)
+            )
+        );
+
+        let vertical_lr = LogicalMapping::new(WritingMode::VerticalLr, Direction::Rtl);
+        assert_eq!(
+            vertical_lr.to_physical_rect(rect, container),
+            Rectangle::from_position_and_size(
+                Vec2D::new(Pixels(5.), Pixels(60.)),
+                Pixels(20.),
+                Pixels(30.)
+            )
+        );
+    }
+}
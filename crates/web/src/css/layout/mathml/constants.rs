@@ -0,0 +1,130 @@
+//! Parameters that control the placement of the parts of a formula
+//!
+//! <https://w3c.github.io/mathml-core/#layout-constants-mathconstants>
+
+use font::ttf_tables::math::MathConstants as FontMathConstants;
+
+use crate::css::{layout::Pixels, FontMetrics};
+
+/// The units per em of [FALLBACK]
+const FALLBACK_UNITS_PER_EM: f32 = 1000.;
+
+/// Used for fonts without a `MATH` table, roughly following the values of Latin Modern Math
+const FALLBACK: FontMathConstants = FontMathConstants {
+    script_percent_scale_down: 70,
+    script_script_percent_scale_down: 50,
+    axis_height: 250,
+    subscript_shift_down: 200,
+    subscript_top_max: 344,
+    superscript_shift_up: 363,
+    superscript_bottom_min: 108,
+    sub_superscript_gap_min: 160,
+    space_after_script: 56,
+    fraction_numerator_shift_up: 394,
+    fraction_numerator_display_style_shift_up: 677,
+    fraction_denominator_shift_down: 345,
+    fraction_denominator_display_style_shift_down: 686,
+    fraction_numerator_gap_min: 40,
+    fraction_numerator_display_style_gap_min: 120,
+    fraction_rule_thickness: 40,
+    fraction_denominator_gap_min: 40,
+    fraction_denominator_display_style_gap_min: 120,
+    radical_vertical_gap: 50,
+    radical_display_style_vertical_gap: 148,
+    radical_rule_thickness: 40,
+    radical_extra_ascender: 40,
+};
+
+/// The [MATH table constants](FontMathConstants) of a font, scaled to its size
+#[derive(Clone, Copy, Debug)]
+pub(super) struct MathConstants {
+    /// The font size of scripts, relative to their base
+    pub script_scale: f32,
+
+    /// The font size of scripts within scripts, relative to the outermost base
+    pub script_script_scale: f32,
+
+    pub axis_height: Pixels,
+    pub subscript_shift_down: Pixels,
+    pub subscript_top_max: Pixels,
+    pub superscript_shift_up: Pixels,
+    pub superscript_bottom_min: Pixels,
+    pub sub_superscript_gap_min: Pixels,
+    pub space_after_script: Pixels,
+    pub fraction_numerator_shift_up: Pixels,
+    pub fraction_numerator_display_style_shift_up: Pixels,
+    pub fraction_denominator_shift_down: Pixels,
+    pub fraction_denominator_display_style_shift_down: Pixels,
+    pub fraction_numerator_gap_min: Pixels,
+    pub fraction_numerator_display_style_gap_min: Pixels,
+    pub fraction_rule_thickness: Pixels,
+    pub fraction_denominator_gap_min: Pixels,
+    pub fraction_denominator_display_style_gap_min: Pixels,
+    pub radical_vertical_gap: Pixels,
+    pub radical_display_style_vertical_gap: Pixels,
+    pub radical_rule_thickness: Pixels,
+    pub radical_extra_ascender: Pixels,
+}
+
+impl MathConstants {
+    #[must_use]
+    pub fn for_font(font_metrics: &FontMetrics) -> Self {
+        let font = &font_metrics.font_face;
+        let (constants, units_per_em) = match font.math_constants() {
+            Some(constants) => (*constants, font.units_per_em()),
+            None => (FALLBACK, FALLBACK_UNITS_PER_EM),
+        };
+
+        let length = |value: i16| font_metrics.size * (value as f32 / units_per_em);
+
+        // Some fonts leave the scale factors at zero, which would make scripts disappear
+        let percentage = |value: i16, fallback: i16| {
+            if 0 < value {
+                value as f32 / 100.
+            } else {
+                fallback as f32 / 100.
+            }
+        };
+
+        Self {
+            script_scale: percentage(
+                constants.script_percent_scale_down,
+                FALLBACK.script_percent_scale_down,
+            ),
+            script_script_scale: percentage(
+                constants.script_script_percent_scale_down,
+                FALLBACK.script_script_percent_scale_down,
+            ),
+            axis_height: length(constants.axis_height),
+            subscript_shift_down: length(constants.subscript_shift_down),
+            subscript_top_max: length(constants.subscript_top_max),
+            superscript_shift_up: length(constants.superscript_shift_up),
+            superscript_bottom_min: length(constants.superscript_bottom_min),
+            sub_superscript_gap_min: length(constants.sub_superscript_gap_min),
+            space_after_script: length(constants.space_after_script),
+            fraction_numerator_shift_up: length(constants.fraction_numerator_shift_up),
+            fraction_numerator_display_style_shift_up: length(
+                constants.fraction_numerator_display_style_shift_up,
+            ),
+            fraction_denominator_shift_down: length(constants.fraction_denominator_shift_down),
+            fraction_denominator_display_style_shift_down: length(
+                constants.fraction_denominator_display_style_shift_down,
+            ),
+            fraction_numerator_gap_min: length(constants.fraction_numerator_gap_min),
+            fraction_numerator_display_style_gap_min: length(
+                constants.fraction_numerator_display_style_gap_min,
+            ),
+            fraction_rule_thickness: length(constants.fraction_rule_thickness),
+            fraction_denominator_gap_min: length(constants.fraction_denominator_gap_min),
+            fraction_denominator_display_style_gap_min: length(
+                constants.fraction_denominator_display_style_gap_min,
+            ),
+            radical_vertical_gap: length(constants.radical_vertical_gap),
+            radical_display_style_vertical_gap: length(
+                constants.radical_display_style_vertical_gap,
+            ),
+            radical_rule_thickness: length(constants.radical_rule_thickness),
+            radical_extra_ascender: length(constants.radical_extra_ascender),
+        }
+    }
+}
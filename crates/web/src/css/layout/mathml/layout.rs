@@ -0,0 +1,580 @@
+//! Layout of the boxes within a formula
+//!
+//! Every box is laid out around its baseline: It has a width, an *ascent* (the height above
+//! the baseline) and a *descent* (the depth below it). Parents then position their children
+//! relative to their own baseline.
+//!
+//! <https://w3c.github.io/mathml-core/#box-model>
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment, TextFragment},
+        layout::{Pixels, Sides, Size},
+        style::specified::BackgroundColor,
+        values::Color,
+        ComputedStyle, FontMetrics,
+    },
+    dom::{dom_objects, DomPtr},
+};
+
+use super::{constants::MathConstants, MathBox, MathBoxKind, MathFormula};
+
+/// The space around operators is measured in multiples of `1/18em`
+const MATH_UNITS_PER_EM: f32 = 18.;
+
+/// The radical sign in front of a square root
+const RADICAL_SIGN: &str = "\u{221A}";
+
+/// <https://w3c.github.io/mathml-core/#the-math-style-property>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MathStyle {
+    /// Whether the box is typeset in display style (`math-style: normal`)
+    is_display_style: bool,
+
+    /// The nesting depth of scripts, which makes the text smaller
+    ///
+    /// <https://w3c.github.io/mathml-core/#the-math-script-level-property>
+    script_level: u8,
+}
+
+/// A laid out [MathBox]
+///
+/// The positions of the children are relative to the top left corner of the box.
+#[derive(Clone, Debug)]
+struct MathFragment {
+    node: DomPtr<dom_objects::Node>,
+    style: ComputedStyle,
+    children: Vec<Fragment>,
+    width: Pixels,
+    ascent: Pixels,
+    descent: Pixels,
+}
+
+/// A formula that was laid out, but not yet placed on a line
+#[derive(Clone, Debug)]
+pub(crate) struct MathItem {
+    fragment: MathFragment,
+    is_display_style: bool,
+
+    /// Display formulas are centered on their line
+    leading_space: Pixels,
+}
+
+impl MathStyle {
+    /// The style of the numerator and denominator of a fraction
+    ///
+    /// They are smaller than the fraction itself, unless that is in display style.
+    #[must_use]
+    fn fraction_part(self) -> Self {
+        let script_level = if self.is_display_style {
+            self.script_level
+        } else {
+            self.script_level.saturating_add(1)
+        };
+
+        Self {
+            is_display_style: false,
+            script_level,
+        }
+    }
+
+    /// The style of subscripts and superscripts
+    #[must_use]
+    fn script(self) -> Self {
+        Self {
+            is_display_style: false,
+            script_level: self.script_level.saturating_add(1),
+        }
+    }
+
+    /// The font for a box with the given style
+    ///
+    /// The constants of the base font control how much smaller scripts are.
+    #[must_use]
+    fn font_metrics(self, style: &ComputedStyle) -> FontMetrics {
+        let mut font_metrics = FontMetrics::for_style(style);
+
+        if self.script_level != 0 {
+            let constants = MathConstants::for_font(&font_metrics);
+            let scale = if self.script_level == 1 {
+                constants.script_scale
+            } else {
+                constants.script_script_scale
+            };
+            font_metrics.size = font_metrics.size * scale;
+        }
+
+        font_metrics
+    }
+}
+
+impl MathFragment {
+    #[must_use]
+    fn height(&self) -> Pixels {
+        self.ascent + self.descent
+    }
+
+    /// Place the box with its top left corner at `position`
+    #[must_use]
+    fn into_fragment(self, position: Vec2D<Pixels>) -> Fragment {
+        let area = Rectangle::from_position_and_size(position, self.width, self.height());
+
+        BoxFragment::new(
+            Some(self.node),
+            self.style,
+            area,
+            Sides::all(Pixels::ZERO),
+            area,
+            area,
+            self.children,
+        )
+        .into()
+    }
+}
+
+impl MathBox<'_> {
+    #[must_use]
+    fn layout(&self, math_style: MathStyle) -> MathFragment {
+        let (children, width, ascent, descent) = match &self.kind {
+            MathBoxKind::Row(children) => layout_row(children, math_style),
+            MathBoxKind::Token(text) | MathBoxKind::Operator(text) => {
+                self.layout_token(text, math_style)
+            },
+            MathBoxKind::Fraction {
+                numerator,
+                denominator,
+            } => self.layout_fraction(numerator, denominator, math_style),
+            MathBoxKind::SquareRoot(children) => self.layout_square_root(children, math_style),
+            MathBoxKind::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => self.layout_scripts(base, *subscript, *superscript, math_style),
+        };
+
+        MathFragment {
+            node: self.node.clone(),
+            style: self.style.clone(),
+            children,
+            width,
+            ascent,
+            descent,
+        }
+    }
+
+    /// <https://w3c.github.io/mathml-core/#token-elements>
+    #[must_use]
+    fn layout_token(
+        &self,
+        text: &str,
+        math_style: MathStyle,
+    ) -> (Vec<Fragment>, Pixels, Pixels, Pixels) {
+        let font_metrics = math_style.font_metrics(&self.style);
+        let size = font_metrics.size;
+        let width = font_metrics.width_of(text);
+
+        // FIXME: Use the ascent and descent of the font once the hhea table is exposed
+        let ascent = size * 0.8;
+        let descent = size * 0.2;
+
+        // Text is drawn with its baseline at the top of its area plus the font size
+        let area =
+            Rectangle::from_position_and_size(Vec2D::new(Pixels::ZERO, ascent - size), width, size);
+        let text = TextFragment::new(text.to_owned(), area, *self.style.color(), font_metrics);
+
+        (vec![text.into()], width, ascent, descent)
+    }
+
+    /// <https://w3c.github.io/mathml-core/#fraction-with-nonzero-line-thickness>
+    #[must_use]
+    fn layout_fraction(
+        &self,
+        numerator: &MathBox<'_>,
+        denominator: &MathBox<'_>,
+        math_style: MathStyle,
+    ) -> (Vec<Fragment>, Pixels, Pixels, Pixels) {
+        let constants = MathConstants::for_font(&math_style.font_metrics(&self.style));
+        let numerator = numerator.layout(math_style.fraction_part());
+        let denominator = denominator.layout(math_style.fraction_part());
+
+        let (numerator_shift, numerator_gap, denominator_shift, denominator_gap) =
+            if math_style.is_display_style {
+                (
+                    constants.fraction_numerator_display_style_shift_up,
+                    constants.fraction_numerator_display_style_gap_min,
+                    constants.fraction_denominator_display_style_shift_down,
+                    constants.fraction_denominator_display_style_gap_min,
+                )
+            } else {
+                (
+                    constants.fraction_numerator_shift_up,
+                    constants.fraction_numerator_gap_min,
+                    constants.fraction_denominator_shift_down,
+                    constants.fraction_denominator_gap_min,
+                )
+            };
+
+        // The fraction bar is centered on the math axis
+        let axis = constants.axis_height;
+        let half_thickness = constants.fraction_rule_thickness / 2.;
+
+        let numerator_shift =
+            numerator_shift.max(axis + half_thickness + numerator_gap + numerator.descent);
+        let denominator_shift =
+            denominator_shift.max(denominator_gap + half_thickness - axis + denominator.ascent);
+
+        let width = numerator.width.max(denominator.width);
+        let ascent = (numerator_shift + numerator.ascent).max(axis + half_thickness);
+        let descent = (denominator_shift + denominator.descent).max(half_thickness - axis);
+
+        let rule = Rectangle::from_position_and_size(
+            Vec2D::new(Pixels::ZERO, ascent - axis - half_thickness),
+            width,
+            constants.fraction_rule_thickness,
+        );
+        let numerator_position = Vec2D::new(
+            (width - numerator.width) / 2.,
+            ascent - numerator_shift - numerator.ascent,
+        );
+        let denominator_position = Vec2D::new(
+            (width - denominator.width) / 2.,
+            ascent + denominator_shift - denominator.ascent,
+        );
+
+        let children = vec![
+            numerator.into_fragment(numerator_position),
+            filled_rect(rule, *self.style.color()),
+            denominator.into_fragment(denominator_position),
+        ];
+
+        (children, width, ascent, descent)
+    }
+
+    /// <https://w3c.github.io/mathml-core/#radicals-msqrt-mroot>
+    #[must_use]
+    fn layout_square_root(
+        &self,
+        children: &[MathBox<'_>],
+        math_style: MathStyle,
+    ) -> (Vec<Fragment>, Pixels, Pixels, Pixels) {
+        let font_metrics = math_style.font_metrics(&self.style);
+        let constants = MathConstants::for_font(&font_metrics);
+        let gap = if math_style.is_display_style {
+            constants.radical_display_style_vertical_gap
+        } else {
+            constants.radical_vertical_gap
+        };
+        let thickness = constants.radical_rule_thickness;
+
+        let (radicand, radicand_width, radicand_ascent, descent) = layout_row(children, math_style);
+        let ascent = radicand_ascent + gap + thickness + constants.radical_extra_ascender;
+
+        // FIXME: Use the glyph variants and assemblies from the MATH table instead of
+        //        scaling the radical sign until it is as high as the radicand
+        let radical_metrics = FontMetrics {
+            size: ascent + descent,
+            ..font_metrics
+        };
+        let radical_width = radical_metrics.width_of(RADICAL_SIGN);
+        let radical_area = Rectangle::from_position_and_size(
+            Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+            radical_width,
+            radical_metrics.size,
+        );
+        let radical_sign = TextFragment::new(
+            RADICAL_SIGN.to_owned(),
+            radical_area,
+            *self.style.color(),
+            radical_metrics,
+        );
+
+        let overbar = Rectangle::from_position_and_size(
+            Vec2D::new(radical_width, constants.radical_extra_ascender),
+            radicand_width,
+            thickness,
+        );
+        let radicand_area = Rectangle::from_position_and_size(
+            Vec2D::new(radical_width, ascent - radicand_ascent),
+            radicand_width,
+            radicand_ascent + descent,
+        );
+
+        let children = vec![
+            radical_sign.into(),
+            filled_rect(overbar, *self.style.color()),
+            anonymous_box(radicand_area, radicand),
+        ];
+
+        (children, radical_width + radicand_width, ascent, descent)
+    }
+
+    /// <https://w3c.github.io/mathml-core/#base-with-subscript>
+    #[must_use]
+    fn layout_scripts(
+        &self,
+        base: &MathBox<'_>,
+        subscript: Option<&MathBox<'_>>,
+        superscript: Option<&MathBox<'_>>,
+        math_style: MathStyle,
+    ) -> (Vec<Fragment>, Pixels, Pixels, Pixels) {
+        let constants = MathConstants::for_font(&math_style.font_metrics(&self.style));
+        let base = base.layout(math_style);
+        let subscript = subscript.map(|subscript| subscript.layout(math_style.script()));
+        let superscript = superscript.map(|superscript| superscript.layout(math_style.script()));
+
+        let mut subscript_shift = subscript.as_ref().map(|subscript| {
+            constants
+                .subscript_shift_down
+                .max(subscript.ascent - constants.subscript_top_max)
+        });
+        let superscript_shift = superscript.as_ref().map(|superscript| {
+            constants
+                .superscript_shift_up
+                .max(superscript.descent + constants.superscript_bottom_min)
+        });
+
+        // Move the subscript down if it would collide with the superscript
+        if let (
+            Some(subscript),
+            Some(superscript),
+            Some(subscript_shift),
+            Some(superscript_shift),
+        ) = (
+            &subscript,
+            &superscript,
+            subscript_shift.as_mut(),
+            superscript_shift,
+        ) {
+            let gap =
+                (*subscript_shift - subscript.ascent) + (superscript_shift - superscript.descent);
+            if gap < constants.sub_superscript_gap_min {
+                *subscript_shift += constants.sub_superscript_gap_min - gap;
+            }
+        }
+
+        let mut ascent = base.ascent;
+        let mut descent = base.descent;
+        let mut scripts_width = Pixels::ZERO;
+        if let (Some(subscript), Some(shift)) = (&subscript, subscript_shift) {
+            ascent = ascent.max(subscript.ascent - shift);
+            descent = descent.max(shift + subscript.descent);
+            scripts_width = scripts_width.max(subscript.width);
+        }
+        if let (Some(superscript), Some(shift)) = (&superscript, superscript_shift) {
+            ascent = ascent.max(shift + superscript.ascent);
+            descent = descent.max(superscript.descent - shift);
+            scripts_width = scripts_width.max(superscript.width);
+        }
+
+        let base_width = base.width;
+        let base_position = Vec2D::new(Pixels::ZERO, ascent - base.ascent);
+        let mut children = vec![base.into_fragment(base_position)];
+
+        if let (Some(subscript), Some(shift)) = (subscript, subscript_shift) {
+            let position = Vec2D::new(base_width, ascent + shift - subscript.ascent);
+            children.push(subscript.into_fragment(position));
+        }
+        if let (Some(superscript), Some(shift)) = (superscript, superscript_shift) {
+            let position = Vec2D::new(base_width, ascent - shift - superscript.ascent);
+            children.push(superscript.into_fragment(position));
+        }
+
+        let width = base_width + scripts_width + constants.space_after_script;
+        (children, width, ascent, descent)
+    }
+}
+
+/// Place the children next to each other, with their baselines aligned
+///
+/// <https://w3c.github.io/mathml-core/#layout-of-mrow>
+#[must_use]
+fn layout_row(
+    children: &[MathBox<'_>],
+    math_style: MathStyle,
+) -> (Vec<Fragment>, Pixels, Pixels, Pixels) {
+    let laid_out_children: Vec<_> = children
+        .iter()
+        .enumerate()
+        .map(|(index, child)| {
+            // Operators at the start or end of a row are prefix or postfix operators,
+            // which have no space around them
+            // FIXME: Implement the full form inference algorithm
+            let spacing = match &child.kind {
+                MathBoxKind::Operator(operator) if index != 0 && index + 1 != children.len() => {
+                    let (leading, trailing) = operator_spacing(operator);
+                    let math_unit = math_style.font_metrics(&child.style).size / MATH_UNITS_PER_EM;
+                    (math_unit * leading as f32, math_unit * trailing as f32)
+                },
+                _ => (Pixels::ZERO, Pixels::ZERO),
+            };
+
+            (child.layout(math_style), spacing)
+        })
+        .collect();
+
+    let ascent = laid_out_children
+        .iter()
+        .map(|(child, _)| child.ascent)
+        .max()
+        .unwrap_or(Pixels::ZERO);
+    let descent = laid_out_children
+        .iter()
+        .map(|(child, _)| child.descent)
+        .max()
+        .unwrap_or(Pixels::ZERO);
+
+    let mut width = Pixels::ZERO;
+    let mut fragments = Vec::with_capacity(laid_out_children.len());
+    for (child, (leading_space, trailing_space)) in laid_out_children {
+        width += leading_space;
+        let child_width = child.width;
+        let position = Vec2D::new(width, ascent - child.ascent);
+        fragments.push(child.into_fragment(position));
+        width += child_width + trailing_space;
+    }
+
+    (fragments, width, ascent, descent)
+}
+
+/// The space before and after an infix operator, in multiples of `1/18em`
+///
+/// This is an excerpt of the [operator dictionary](https://w3c.github.io/mathml-core/#operator-dictionary-human).
+#[must_use]
+fn operator_spacing(operator: &str) -> (u8, u8) {
+    match operator {
+        // Relations
+        "=" | "<" | ">" | "\u{2260}" | "\u{2264}" | "\u{2265}" | "\u{2248}" | "\u{2261}"
+        | "\u{223C}" | "\u{2208}" | "\u{2209}" | "\u{2282}" | "\u{2286}" | "\u{2192}"
+        | "\u{2190}" | "\u{2194}" | "\u{21D2}" | "\u{21D4}" | ":=" => (5, 5),
+        // Binary operators
+        "+" | "-" | "\u{2212}" | "\u{00B1}" | "\u{2213}" | "*" | "\u{00D7}" | "\u{00F7}"
+        | "\u{00B7}" | "\u{22C5}" | "\u{2217}" | "/" | "\u{2229}" | "\u{222A}" | "\u{2227}"
+        | "\u{2228}" => (4, 4),
+        // Separators
+        "," | ";" => (0, 3),
+        // Fences
+        "(" | ")" | "[" | "]" | "{" | "}" | "|" | "\u{2016}" | "\u{27E8}" | "\u{27E9}" => (0, 0),
+        _ => (5, 5),
+    }
+}
+
+/// A box without borders that is filled with the given color, like the bar of a fraction
+#[must_use]
+fn filled_rect(area: Rectangle<Pixels>, color: Color) -> Fragment {
+    let mut style = ComputedStyle::default();
+    style.set_background_color(BackgroundColor::Color(color));
+
+    anonymous_box_with_style(area, style, vec![])
+}
+
+/// Group fragments that are not generated by a single element
+#[must_use]
+fn anonymous_box(area: Rectangle<Pixels>, children: Vec<Fragment>) -> Fragment {
+    anonymous_box_with_style(area, ComputedStyle::default(), children)
+}
+
+#[must_use]
+fn anonymous_box_with_style(
+    area: Rectangle<Pixels>,
+    style: ComputedStyle,
+    children: Vec<Fragment>,
+) -> Fragment {
+    BoxFragment::new(
+        None,
+        style,
+        area,
+        Sides::all(Pixels::ZERO),
+        area,
+        area,
+        children,
+    )
+    .into()
+}
+
+impl MathItem {
+    #[must_use]
+    pub fn new(formula: &MathFormula<'_>) -> Self {
+        let math_style = MathStyle {
+            is_display_style: formula.is_display_style,
+            script_level: 0,
+        };
+
+        Self {
+            fragment: formula.root.layout(math_style),
+            is_display_style: formula.is_display_style,
+            leading_space: Pixels::ZERO,
+        }
+    }
+
+    #[must_use]
+    pub fn style(&self) -> &ComputedStyle {
+        &self.fragment.style
+    }
+
+    #[must_use]
+    pub fn is_display_style(&self) -> bool {
+        self.is_display_style
+    }
+
+    /// Center the formula within a line of the given width
+    pub fn center_in(&mut self, width: Pixels) {
+        self.leading_space = ((width - self.fragment.width) / 2.).max(Pixels::ZERO);
+    }
+
+    /// The space that the formula takes up on its line
+    #[must_use]
+    pub fn size(&self) -> Size<Pixels> {
+        // FIXME: Line boxes don't have a baseline yet, inline content is aligned with the
+        //        bottom of the line instead. Letting the descent of inline formulas overflow
+        //        the line, like the descenders of text do, keeps their baseline in line with
+        //        the surrounding text.
+        let height = if self.is_display_style {
+            self.fragment.height()
+        } else {
+            self.fragment.ascent
+        };
+
+        Size {
+            width: self.leading_space * 2. + self.fragment.width,
+            height,
+        }
+    }
+
+    /// Place the formula with its top left corner at `position`
+    #[must_use]
+    pub fn into_fragment(self, position: Vec2D<Pixels>) -> Fragment {
+        self.fragment
+            .into_fragment(position + Vec2D::new(self.leading_space, Pixels::ZERO))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fraction_parts_are_smaller_in_inline_formulas() {
+        let display = MathStyle {
+            is_display_style: true,
+            script_level: 0,
+        };
+        let inline = MathStyle {
+            is_display_style: false,
+            script_level: 0,
+        };
+
+        assert_eq!(display.fraction_part(), inline);
+        assert_eq!(inline.fraction_part(), inline.script());
+        assert_eq!(inline.script().script_level, 1);
+    }
+
+    #[test]
+    fn operator_spacing_depends_on_the_operator() {
+        assert_eq!(operator_spacing("="), (5, 5));
+        assert_eq!(operator_spacing("+"), (4, 4));
+        assert_eq!(operator_spacing(","), (0, 3));
+        assert_eq!(operator_spacing("("), (0, 0));
+    }
+}
@@ -0,0 +1,268 @@
+//! Mathematical formulas, written in [MathML Core](https://w3c.github.io/mathml-core/)
+//!
+//! The boxes of a formula are built from the MathML elements below a `<math>` element.
+//! Their `display` value is ignored (unless it is `none`), every element is laid out according
+//! to its tag name instead. Elements that are not supported are laid out like an `<mrow>`.
+//!
+//! Each box is laid out around a baseline and the whole formula is placed on its line like
+//! an atomic inline.
+
+mod constants;
+mod layout;
+
+use std::fmt::{self, Write};
+
+use crate::{
+    css::{ComputedStyle, StyleComputer},
+    dom::{dom_objects, DomPtr},
+    static_interned, TreeDebug, TreeFormatter,
+};
+
+use super::{sizing::ContentSizes, BoxTreeArena};
+
+pub(crate) use layout::MathItem;
+
+/// The root of a formula, generated by a `<math>` element
+#[derive(Clone, Debug)]
+pub(crate) struct MathFormula<'box_tree> {
+    root: MathBox<'box_tree>,
+
+    /// Formulas that are displayed on their own line are typeset larger
+    ///
+    /// <https://w3c.github.io/mathml-core/#the-math-style-property>
+    is_display_style: bool,
+}
+
+/// The box generated by a single MathML element
+#[derive(Clone, Debug)]
+pub(crate) struct MathBox<'box_tree> {
+    node: DomPtr<dom_objects::Node>,
+    style: ComputedStyle,
+    kind: MathBoxKind<'box_tree>,
+}
+
+#[derive(Clone, Debug)]
+enum MathBoxKind<'box_tree> {
+    /// `<mrow>`, and every element that has no special layout
+    Row(&'box_tree [MathBox<'box_tree>]),
+
+    /// `<mi>`, `<mn>`, `<mtext>` and `<ms>`
+    Token(String),
+
+    /// `<mo>`, which is surrounded by some space depending on the operator
+    Operator(String),
+
+    /// `<mfrac>`
+    Fraction {
+        numerator: &'box_tree MathBox<'box_tree>,
+        denominator: &'box_tree MathBox<'box_tree>,
+    },
+
+    /// `<msqrt>`, whose children are laid out like an `<mrow>` below the radical sign
+    SquareRoot(&'box_tree [MathBox<'box_tree>]),
+
+    /// `<msub>`, `<msup>` and `<msubsup>`
+    Scripts {
+        base: &'box_tree MathBox<'box_tree>,
+        subscript: Option<&'box_tree MathBox<'box_tree>>,
+        superscript: Option<&'box_tree MathBox<'box_tree>>,
+    },
+}
+
+impl<'box_tree> MathFormula<'box_tree> {
+    #[must_use]
+    pub fn new(root: MathBox<'box_tree>, is_display_style: bool) -> Self {
+        Self {
+            root,
+            is_display_style,
+        }
+    }
+
+    #[must_use]
+    pub fn style(&self) -> &ComputedStyle {
+        &self.root.style
+    }
+
+    /// Formulas cannot be broken across lines
+    #[must_use]
+    pub(crate) fn content_sizes(&self) -> ContentSizes {
+        ContentSizes::fixed(MathItem::new(self).size().width)
+    }
+}
+
+impl<'box_tree> MathBox<'box_tree> {
+    /// Build the boxes for a MathML element and its descendants
+    #[must_use]
+    pub fn build(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
+        element: DomPtr<dom_objects::Element>,
+        style: ComputedStyle,
+        style_computer: StyleComputer<'_>,
+    ) -> Self {
+        let local_name = element.borrow().local_name();
+        let kind = match local_name {
+            static_interned!("mi")
+            | static_interned!("mn")
+            | static_interned!("mtext")
+            | static_interned!("ms") => MathBoxKind::Token(token_text(&element)),
+            static_interned!("mo") => MathBoxKind::Operator(token_text(&element)),
+            _ => {
+                let children = Self::build_children(arena, &element, &style, style_computer);
+                let children = arena.alloc_math_boxes(children);
+
+                // Elements with the wrong number of children are laid out like rows
+                match (local_name, children) {
+                    (static_interned!("mfrac"), [numerator, denominator]) => {
+                        MathBoxKind::Fraction {
+                            numerator,
+                            denominator,
+                        }
+                    },
+                    (static_interned!("msqrt"), _) => MathBoxKind::SquareRoot(children),
+                    (static_interned!("msub"), [base, subscript]) => MathBoxKind::Scripts {
+                        base,
+                        subscript: Some(subscript),
+                        superscript: None,
+                    },
+                    (static_interned!("msup"), [base, superscript]) => MathBoxKind::Scripts {
+                        base,
+                        subscript: None,
+                        superscript: Some(superscript),
+                    },
+                    (static_interned!("msubsup"), [base, subscript, superscript]) => {
+                        MathBoxKind::Scripts {
+                            base,
+                            subscript: Some(subscript),
+                            superscript: Some(superscript),
+                        }
+                    },
+                    _ => MathBoxKind::Row(children),
+                }
+            },
+        };
+
+        Self {
+            node: element.upcast(),
+            style,
+            kind,
+        }
+    }
+
+    /// Text between the children of non-token elements is not rendered
+    #[must_use]
+    fn build_children(
+        arena: &'box_tree BoxTreeArena<'box_tree>,
+        element: &DomPtr<dom_objects::Element>,
+        style: &ComputedStyle,
+        style_computer: StyleComputer<'_>,
+    ) -> Vec<Self> {
+        let mut children = vec![];
+
+        for child in element.borrow().children() {
+            let Some(child) = child.try_into_type::<dom_objects::Element>() else {
+                continue;
+            };
+
+            let child_style = style_computer.get_computed_style(child.clone(), style);
+            if !child_style.display().is_none() {
+                children.push(Self::build(
+                    arena,
+                    child.clone(),
+                    child_style,
+                    style_computer,
+                ));
+            }
+
+            child.borrow_mut().clear_needs_layout();
+        }
+
+        children
+    }
+}
+
+/// The text of a token element, with whitespace collapsed
+///
+/// <https://w3c.github.io/mathml-core/#token-elements>
+#[must_use]
+fn token_text(element: &DomPtr<dom_objects::Element>) -> String {
+    fn collect_text(node: &DomPtr<dom_objects::Node>, text: &mut String) {
+        if let Some(text_node) = node.try_into_type::<dom_objects::Text>() {
+            text.push_str(text_node.borrow().content());
+        }
+
+        for child in node.borrow().children() {
+            collect_text(child, text);
+        }
+    }
+
+    let mut text = String::new();
+    collect_text(&element.clone().upcast(), &mut text);
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+impl TreeDebug for MathFormula<'_> {
+    fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
+        formatter.indent()?;
+        if self.is_display_style {
+            writeln!(formatter, "Math Formula (display)")?;
+        } else {
+            writeln!(formatter, "Math Formula")?;
+        }
+
+        formatter.increase_indent();
+        self.root.tree_fmt(formatter)?;
+        formatter.decrease_indent();
+        Ok(())
+    }
+}
+
+impl TreeDebug for MathBox<'_> {
+    fn tree_fmt(&self, formatter: &mut TreeFormatter<'_, '_>) -> fmt::Result {
+        formatter.indent()?;
+
+        let children: Vec<&MathBox<'_>> = match &self.kind {
+            MathBoxKind::Row(children) => {
+                writeln!(formatter, "Row")?;
+                children.iter().collect()
+            },
+            MathBoxKind::Token(text) => {
+                writeln!(formatter, "Token {text:?}")?;
+                vec![]
+            },
+            MathBoxKind::Operator(text) => {
+                writeln!(formatter, "Operator {text:?}")?;
+                vec![]
+            },
+            MathBoxKind::Fraction {
+                numerator,
+                denominator,
+            } => {
+                writeln!(formatter, "Fraction")?;
+                vec![*numerator, *denominator]
+            },
+            MathBoxKind::SquareRoot(children) => {
+                writeln!(formatter, "Square Root")?;
+                children.iter().collect()
+            },
+            MathBoxKind::Scripts {
+                base,
+                subscript,
+                superscript,
+            } => {
+                writeln!(formatter, "Scripts")?;
+                [Some(*base), *subscript, *superscript]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            },
+        };
+
+        formatter.increase_indent();
+        for child in children {
+            child.tree_fmt(formatter)?;
+        }
+        formatter.decrease_indent();
+        Ok(())
+    }
+}
@@ -1,17 +1,27 @@
 mod box_tree;
+mod cache;
 mod content;
+mod file_input;
 pub mod flow;
+mod form_control;
 mod formatting_context;
+pub mod logical;
+mod mathml;
 mod pixels;
 mod replaced;
+pub(crate) mod select;
+mod sizing;
+pub(crate) mod textarea;
 
-pub use box_tree::BoxTree;
+pub use box_tree::{BoxTree, BoxTreeArena};
+use cache::CachedLayout;
+pub use cache::LayoutCache;
 pub use pixels::Pixels;
 
 use math::{Rectangle, Vec2D};
 
 use std::ops;
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Sides<T> {
     pub top: T,
     pub right: T,
@@ -81,7 +91,7 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Size<T> {
     pub width: T,
     pub height: T,
@@ -97,7 +107,7 @@ where
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ContainingBlock {
     width: Pixels,
 
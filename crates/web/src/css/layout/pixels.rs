@@ -1,6 +1,6 @@
 use std::ops::{self, Mul};
 
-use crate::css::values::Percentage;
+use crate::css::{animation::Animate, values::Percentage};
 
 /// The base unit of measurement within CSS
 ///
@@ -93,6 +93,14 @@ impl ops::SubAssign for Pixels {
     }
 }
 
+impl ops::Neg for Pixels {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
 impl Default for Pixels {
     fn default() -> Self {
         Self::ZERO
@@ -126,3 +134,9 @@ impl Mul<Percentage> for Pixels {
         Self(self.0 * rhs.as_fraction())
     }
 }
+
+impl Animate for Pixels {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        self.0.animate(&to.0, progress).map(Self)
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use image::Texture;
 use math::{Rectangle, Vec2D};
 
@@ -5,12 +7,15 @@ use crate::{
     css::{
         computed_style::ComputedStyle,
         fragment_tree::{Fragment, TextureFragment},
-        values::AutoOr,
+        values::{AutoOr, PercentageOr, PreferredSize},
     },
     dom::{dom_objects, DomPtr},
 };
 
-use super::{ContainingBlock, Pixels, Size};
+use super::{
+    file_input::FileInputControl, select::SelectControl, sizing::ContentSizes,
+    textarea::TextAreaControl, ContainingBlock, Pixels, Size,
+};
 
 /// <https://drafts.csswg.org/css2/#intrinsic>
 #[derive(Clone, Copy, Debug)]
@@ -49,7 +54,10 @@ impl IntrinsicSize {
 
 #[derive(Clone, Debug)]
 pub(crate) enum ReplacedContent {
-    Image(Texture),
+    Image(Arc<Texture>),
+    Select(SelectControl),
+    TextArea(TextAreaControl),
+    FileInput(FileInputControl),
 }
 
 /// <https://drafts.csswg.org/css-display/#replaced-element>
@@ -62,113 +70,100 @@ pub(crate) struct ReplacedElement {
 
 impl ReplacedElement {
     #[must_use]
-    const fn has_intrinsic_width(&self) -> bool {
-        self.intrinsic_size.width.is_some()
+    pub fn style(&self) -> ComputedStyle {
+        self.style.clone()
     }
 
     #[must_use]
-    const fn has_intrinsic_height(&self) -> bool {
-        self.intrinsic_size.height.is_some()
+    pub const fn content(&self) -> &ReplacedContent {
+        &self.content
     }
 
+    /// The aspect ratio used for sizing the element
+    ///
+    /// This is either the intrinsic aspect ratio of the content or the one given by the `aspect-ratio` property.
     #[must_use]
-    const fn has_intrinsic_aspect_ratio(&self) -> bool {
-        self.intrinsic_size.aspect_ratio.is_some()
+    fn preferred_aspect_ratio(&self) -> Option<f32> {
+        self.style
+            .aspect_ratio()
+            .preferred_aspect_ratio(self.intrinsic_size.aspect_ratio)
     }
 
+    /// The computed value of the `height` property, with percentages resolved against the containing block
+    ///
+    /// If the height of the containing block is not known, percentages are treated as `auto`.
+    /// (<https://drafts.csswg.org/css2/#the-height-property>)
     #[must_use]
-    pub fn style(&self) -> ComputedStyle {
-        self.style.clone()
-    }
-
-    #[must_use]
-    pub const fn content(&self) -> &ReplacedContent {
-        &self.content
+    fn resolved_height(&self, containing_block: ContainingBlock) -> AutoOr<Pixels> {
+        self.style
+            .height()
+            .flat_map(|percentage_or_length| match percentage_or_length {
+                PercentageOr::Percentage(percentage) => match containing_block.height() {
+                    Some(available_height) => AutoOr::NotAuto(available_height * percentage),
+                    None => AutoOr::Auto,
+                },
+                PercentageOr::NotPercentage(length) => AutoOr::NotAuto(length),
+            })
     }
 
     /// <https://drafts.csswg.org/css2/#inline-replaced-width>
-    fn used_inline_width(&self, containining_block: ContainingBlock) -> Pixels {
-        let computed_width = self.style.width();
-        let computed_height = self.style.height();
-
-        if let AutoOr::NotAuto(width) = computed_width {
-            width.resolve_against(containining_block.width())
-        } else if computed_height.is_auto()
-            && let Some(intrinsic_width) = self.intrinsic_size.width
-        {
-            intrinsic_width
-        } else if let Some(intrinsic_height) = self.intrinsic_size.height
-            && let Some(intrinsic_aspect_ratio) = self.intrinsic_size.aspect_ratio
-        {
-            intrinsic_height * intrinsic_aspect_ratio
-        } else if let AutoOr::NotAuto(height) = computed_height
-            && let Some(intrinsic_aspect_ratio) = self.intrinsic_size.aspect_ratio
-            && let Some(container_height) = containining_block.height()
-        {
-            // The spec doesn't explicitly state this, but to use the "used height" here,
-            // the height of the containing block is required to be known.
-            let used_height = height.resolve_against(container_height);
-
-            used_height * intrinsic_aspect_ratio
-        } else if computed_height.is_auto()
-            && !self.has_intrinsic_width()
-            && !self.has_intrinsic_height()
-            && self.has_intrinsic_aspect_ratio()
-        {
-            log::warn!("Computing width of replaced element with neither height nor width but an intrinsic aspect ratio, this is undefined in CSS2");
-            log::warn!("Falling back to 0.0 pixels");
-            Pixels::ZERO
-        } else if let Some(intrinsic_width) = self.intrinsic_size.width {
-            // NOTE: This is the same case as above, but without the condition that height is auto
-            intrinsic_width
-        } else {
-            // FIXME: Use the actual viewport size here
-            let viewport = Size {
-                width: Pixels(800.),
-                height: Pixels(600.),
-            };
+    #[must_use]
+    fn used_inline_width(&self, containing_block: ContainingBlock) -> Pixels {
+        // The content sizes of replaced elements are equal to their natural size, so the intrinsic
+        // size keywords behave like "auto"
+        let computed_width = match self.style.width() {
+            PreferredSize::Size(width) => {
+                AutoOr::NotAuto(width.resolve_against(containing_block.width()))
+            },
+            _ => AutoOr::Auto,
+        };
+        let aspect_ratio = self.preferred_aspect_ratio();
 
-            if viewport.width < Pixels(300.) {
-                // The width of the largest rectangle with a 2:1 aspect ratio that fits on the viewport
-                if viewport.width < viewport.height * 2. {
-                    viewport.width
+        match (computed_width, self.resolved_height(containing_block)) {
+            (AutoOr::NotAuto(width), _) => width,
+            (AutoOr::Auto, AutoOr::Auto) => {
+                if let Some(intrinsic_width) = self.intrinsic_size.width {
+                    intrinsic_width
+                } else if let Some(intrinsic_height) = self.intrinsic_size.height
+                    && let Some(aspect_ratio) = aspect_ratio
+                {
+                    intrinsic_height * aspect_ratio
+                } else if aspect_ratio.is_some() {
+                    // The width is undefined in CSS2 if there is only an aspect ratio, the suggestion is to
+                    // use the width that a block-level, non-replaced element would have
+                    containing_block.width()
                 } else {
-                    viewport.height / 2.
+                    fallback_width()
                 }
-            } else {
-                Pixels(300.)
-            }
+            },
+            (AutoOr::Auto, AutoOr::NotAuto(used_height)) => {
+                if let Some(aspect_ratio) = aspect_ratio {
+                    used_height * aspect_ratio
+                } else if let Some(intrinsic_width) = self.intrinsic_size.width {
+                    intrinsic_width
+                } else {
+                    fallback_width()
+                }
+            },
         }
     }
 
     /// <https://drafts.csswg.org/css2/#inline-replaced-height>
     #[must_use]
-    fn used_inline_height(&self, containining_block: ContainingBlock) -> Pixels {
-        let computed_width = self.style.width();
-        let computed_height = self.style.height();
-
-        if let AutoOr::NotAuto(height) = computed_height
-            && let Some(available_height) = containining_block.height()
-        {
-            height.resolve_against(available_height)
-        } else if computed_width.is_auto()
-            && let Some(intrinsic_height) = self.intrinsic_size.height
-        {
-            intrinsic_height
-        } else if let AutoOr::NotAuto(width) = computed_width
-            && let Some(intrinsic_aspect_ratio) = self.intrinsic_size.aspect_ratio
-        {
-            let used_width = width.resolve_against(containining_block.width());
+    fn used_inline_height(&self, containing_block: ContainingBlock, used_width: Pixels) -> Pixels {
+        if let AutoOr::NotAuto(height) = self.resolved_height(containing_block) {
+            return height;
+        }
 
-            used_width * intrinsic_aspect_ratio
+        // NOTE: If both width and height are "auto", then the used width is either the intrinsic
+        //       width or derived from the intrinsic height, so the aspect ratio yields
+        //       the intrinsic height
+        if let Some(aspect_ratio) = self.preferred_aspect_ratio() {
+            used_width / aspect_ratio
         } else if let Some(intrinsic_height) = self.intrinsic_size.height {
             intrinsic_height
         } else {
-            // The height of the largest rectangle that has a 2:1 ratio,
-            //  a height not greater than 150px, and has a width not greater than the device width.
-            // FIXME: Use the actual device width here
-            let device_width = Pixels(800.);
-            (device_width / 2.).min(Pixels(150.))
+            fallback_height()
         }
     }
 
@@ -176,33 +171,132 @@ impl ReplacedElement {
     ///
     /// See  <https://drafts.csswg.org/css2/#inline-replaced-width> and <https://drafts.csswg.org/css2/#inline-replaced-height>
     #[must_use]
-    pub fn used_size_if_it_was_inline(&self, containining_block: ContainingBlock) -> Size<Pixels> {
-        let width = self.used_inline_width(containining_block);
-        let height = self.used_inline_height(containining_block);
+    pub fn used_size_if_it_was_inline(&self, containing_block: ContainingBlock) -> Size<Pixels> {
+        let width = self.used_inline_width(containing_block);
+        let height = self.used_inline_height(containing_block, width);
         Size { width, height }
     }
 
+    /// Replaced elements cannot be broken across lines, so their min-content and max-content
+    /// widths are both equal to their used width
+    #[must_use]
+    pub fn content_sizes(&self) -> ContentSizes {
+        // Percentages are resolved against zero while computing intrinsic sizes
+        let containing_block =
+            ContainingBlock::new(Pixels::ZERO, Vec2D::new(Pixels::ZERO, Pixels::ZERO));
+        ContentSizes::fixed(self.used_inline_width(containing_block))
+    }
+
     #[must_use]
     pub fn try_from(
         element: DomPtr<dom_objects::Element>,
         element_style: ComputedStyle,
     ) -> Option<Self> {
         // Check if the element is replaced
-        // Currently the only replaced element supported is the <img> element
+        // Currently the only replaced elements supported are <img>, <canvas>, <video>, <select>, <textarea>
+        // and <input type=file>
+        if let Some(select) = element.try_into_type::<dom_objects::HtmlSelectElement>() {
+            let control = SelectControl::new(select, element_style.clone());
+
+            let replaced_select = ReplacedElement {
+                intrinsic_size: control.intrinsic_size(),
+                content: ReplacedContent::Select(control),
+                style: element_style,
+            };
+            return Some(replaced_select);
+        }
+
+        if let Some(textarea) = element.try_into_type::<dom_objects::HtmlTextAreaElement>() {
+            let control = TextAreaControl::new(textarea, element_style.clone());
+
+            let replaced_textarea = ReplacedElement {
+                intrinsic_size: control.intrinsic_size(),
+                content: ReplacedContent::TextArea(control),
+                style: element_style,
+            };
+            return Some(replaced_textarea);
+        }
+
+        if let Some(input) = element.try_into_type::<dom_objects::HtmlInputElement>()
+            && input.borrow().is_file_upload()
+        {
+            let control = FileInputControl::new(input, element_style.clone());
+
+            let replaced_file_input = ReplacedElement {
+                intrinsic_size: control.intrinsic_size(),
+                content: ReplacedContent::FileInput(control),
+                style: element_style,
+            };
+            return Some(replaced_file_input);
+        }
+
+        if let Some(video) = element.try_into_type::<dom_objects::HtmlVideoElement>() {
+            let mut video = video.borrow_mut();
+            video.ensure_resource_selected();
+
+            // The poster frame is shown until the first video frame is available
+            // https://html.spec.whatwg.org/multipage/rendering.html#video-object
+            let texture = video
+                .current_frame()
+                .map(Arc::new)
+                .or_else(|| video.poster_frame().cloned())
+                .unwrap_or_else(|| Arc::new(Texture::empty()));
+
+            // The natural size of the video takes precedence over the one of the poster frame
+            // https://html.spec.whatwg.org/multipage/media.html#concept-video-intrinsic-width
+            let intrinsic_size = match video.video_size() {
+                Some((width, height)) => {
+                    IntrinsicSize::new(Pixels(width as f32), Pixels(height as f32))
+                },
+                None if texture.width() != 0 && texture.height() != 0 => IntrinsicSize::new(
+                    Pixels(texture.width() as f32),
+                    Pixels(texture.height() as f32),
+                ),
+                None => IntrinsicSize::NONE,
+            };
+
+            let replaced_video = ReplacedElement {
+                intrinsic_size,
+                content: ReplacedContent::Image(texture),
+                style: element_style,
+            };
+            return Some(replaced_video);
+        }
+
+        if let Some(canvas) = element.try_into_type::<dom_objects::HtmlCanvasElement>() {
+            let canvas = canvas.borrow();
+
+            // A canvas that was never drawn to has no bitmap yet, but still has a size
+            let intrinsic_width = Pixels(canvas.width() as f32);
+            let intrinsic_height = Pixels(canvas.height() as f32);
+            let texture = canvas
+                .bitmap()
+                .unwrap_or_else(|| Arc::new(Texture::empty()));
+
+            let replaced_canvas = ReplacedElement {
+                intrinsic_size: IntrinsicSize::new(intrinsic_width, intrinsic_height),
+                content: ReplacedContent::Image(texture),
+                style: element_style,
+            };
+            return Some(replaced_canvas);
+        }
+
         if let Some(image) = element.try_into_type::<dom_objects::HtmlImageElement>() {
+            let density = image.borrow().current_pixel_density();
             let Some(texture) = image.borrow_mut().texture().cloned() else {
                 // Fallback to an empty image with no intrinsic size
                 let replaced_element = ReplacedElement {
                     intrinsic_size: IntrinsicSize::NONE,
-                    content: ReplacedContent::Image(Texture::empty()),
+                    content: ReplacedContent::Image(Arc::new(Texture::empty())),
                     style: element_style,
                 };
 
                 return Some(replaced_element);
             };
 
-            let intrinsic_width = Pixels(texture.width() as f32);
-            let intrinsic_height = Pixels(texture.height() as f32);
+            // High resolution images are displayed at a smaller size, so they appear sharper
+            let intrinsic_width = Pixels(texture.width() as f32 / density);
+            let intrinsic_height = Pixels(texture.height() as f32 / density);
 
             let intrinsic_size = IntrinsicSize::new(intrinsic_width, intrinsic_height);
             let replaced_image = ReplacedElement {
@@ -217,6 +311,41 @@ impl ReplacedElement {
     }
 }
 
+/// The width of replaced elements that have neither a specified nor an intrinsic width
+///
+/// This is 300px, unless that is too wide to fit the device, in which case it is
+/// the width of the largest rectangle with a 2:1 ratio that fits the device.
+#[must_use]
+fn fallback_width() -> Pixels {
+    // FIXME: Use the actual viewport size here
+    let viewport = Size {
+        width: Pixels(800.),
+        height: Pixels(600.),
+    };
+
+    if viewport.width < Pixels(300.) {
+        // The width of the largest rectangle with a 2:1 aspect ratio that fits on the viewport
+        if viewport.width < viewport.height * 2. {
+            viewport.width
+        } else {
+            viewport.height * 2.
+        }
+    } else {
+        Pixels(300.)
+    }
+}
+
+/// The height of replaced elements that have neither a specified nor an intrinsic height
+///
+/// This is the height of the largest rectangle that has a 2:1 ratio, a height not greater
+/// than 150px and a width not greater than the device width.
+#[must_use]
+fn fallback_height() -> Pixels {
+    // FIXME: Use the actual device width here
+    let device_width = Pixels(800.);
+    (device_width / 2.).min(Pixels(150.))
+}
+
 impl ReplacedContent {
     /// Create a fragment for the given position and size
     ///
@@ -231,6 +360,88 @@ impl ReplacedContent {
                 area: Rectangle::from_position_and_size(position, size.width, size.height),
             }
             .into(),
+            Self::Select(control) => control.create_fragment(position, size),
+            Self::TextArea(control) => control.create_fragment(position, size),
+            Self::FileInput(control) => control.create_fragment(position, size),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::css::{style::specified::AspectRatio, CSSParse};
+
+    use super::*;
+
+    fn replaced_element(intrinsic_size: IntrinsicSize, style: ComputedStyle) -> ReplacedElement {
+        ReplacedElement {
+            intrinsic_size,
+            content: ReplacedContent::Image(Arc::new(Texture::empty())),
+            style,
         }
     }
+
+    fn containing_block() -> ContainingBlock {
+        ContainingBlock::new(Pixels(500.), Vec2D::new(Pixels::ZERO, Pixels::ZERO))
+    }
+
+    fn assert_size(element: &ReplacedElement, width: f32, height: f32) {
+        let size = element.used_size_if_it_was_inline(containing_block());
+        assert_eq!(size.width, Pixels(width));
+        assert_eq!(size.height, Pixels(height));
+    }
+
+    #[test]
+    fn intrinsic_size() {
+        let element = replaced_element(
+            IntrinsicSize::new(Pixels(200.), Pixels(100.)),
+            ComputedStyle::default(),
+        );
+        assert_size(&element, 200., 100.);
+
+        // The height follows from the intrinsic aspect ratio
+        let mut style = ComputedStyle::default();
+        style.set_width(PreferredSize::Size(PercentageOr::NotPercentage(Pixels(
+            50.,
+        ))));
+        let element = replaced_element(IntrinsicSize::new(Pixels(200.), Pixels(100.)), style);
+        assert_size(&element, 50., 25.);
+    }
+
+    #[test]
+    fn fallback_size() {
+        let element = replaced_element(IntrinsicSize::NONE, ComputedStyle::default());
+        assert_size(&element, 300., 150.);
+    }
+
+    #[test]
+    fn aspect_ratio_only() {
+        let intrinsic_size = IntrinsicSize {
+            width: None,
+            height: None,
+            aspect_ratio: Some(2.),
+        };
+        let element = replaced_element(intrinsic_size, ComputedStyle::default());
+        assert_size(&element, 500., 250.);
+    }
+
+    #[test]
+    fn aspect_ratio_property() {
+        let mut style = ComputedStyle::default();
+        style.set_aspect_ratio(AspectRatio::parse_from_str("1").unwrap());
+        let element = replaced_element(IntrinsicSize::new(Pixels(200.), Pixels(100.)), style);
+        assert_size(&element, 200., 200.);
+
+        // With "auto", the intrinsic aspect ratio takes precedence
+        let mut style = ComputedStyle::default();
+        style.set_aspect_ratio(AspectRatio::parse_from_str("auto 1").unwrap());
+        let element = replaced_element(
+            IntrinsicSize::new(Pixels(200.), Pixels(100.)),
+            style.clone(),
+        );
+        assert_size(&element, 200., 100.);
+
+        let element = replaced_element(IntrinsicSize::NONE, style);
+        assert_size(&element, 500., 500.);
+    }
 }
@@ -0,0 +1,232 @@
+//! Rendering of `<select>` elements
+//!
+//! Select elements are rendered like replaced elements: Their options do not generate boxes,
+//! instead the element is drawn either as a drop-down box showing the current value or as
+//! a list box showing a number of options at once.
+//!
+//! <https://html.spec.whatwg.org/multipage/rendering.html#the-select-element-2>
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment},
+        style::specified::BackgroundColor,
+        values::Color,
+        ComputedStyle,
+    },
+    dom::{dom_objects, DomPtr},
+};
+
+use super::{form_control::FormControl, replaced::IntrinsicSize, Pixels, Sides, Size};
+
+/// Horizontal space between the border of the control and its text
+const PADDING: Pixels = Pixels(4.);
+
+/// Drawn at the end of drop-down boxes, to indicate that they can be opened
+const DROP_DOWN_ARROW: &str = "▾";
+
+/// The background of selected options
+pub(crate) const SELECTED_OPTION_BACKGROUND: Color = Color::rgb(0x1a, 0x5f, 0xb4);
+
+/// The text color of selected options
+pub(crate) const SELECTED_OPTION_COLOR: Color = Color::WHITE;
+
+/// The text color of options that cannot be selected
+pub(crate) const DISABLED_OPTION_COLOR: Color = Color::GRAY;
+
+#[derive(Clone, Debug)]
+struct OptionRow {
+    node: DomPtr<dom_objects::Node>,
+    label: String,
+    is_selected: bool,
+    is_disabled: bool,
+}
+
+#[derive(Clone, Debug)]
+enum Appearance {
+    /// Shows the label of the selected option, the other options are displayed in a popup
+    DropDownBox { label: String },
+
+    /// Shows `visible_rows` options, starting at `first_visible_row`
+    ListBox {
+        rows: Vec<OptionRow>,
+        first_visible_row: usize,
+        visible_rows: usize,
+    },
+}
+
+/// The content of a `<select>` element, at the time that it was laid out
+#[derive(Clone, Debug)]
+pub(crate) struct SelectControl {
+    control: FormControl,
+
+    /// The width of the widest option label
+    ///
+    /// The control is wide enough to display any of the options, not just
+    /// the ones that are currently visible.
+    label_width: Pixels,
+    appearance: Appearance,
+}
+
+impl SelectControl {
+    #[must_use]
+    pub fn new(select: DomPtr<dom_objects::HtmlSelectElement>, style: ComputedStyle) -> Self {
+        let control = FormControl::new(select.clone().upcast(), style);
+        let font_metrics = &control.font_metrics;
+
+        let select_ref = select.borrow();
+        let options = select_ref.list_of_options();
+        let selected_indices = select_ref.selected_indices();
+
+        let rows: Vec<OptionRow> = options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| {
+                let option_ref = option.borrow();
+                OptionRow {
+                    node: option.clone().upcast(),
+                    label: option_ref.label(),
+                    is_selected: selected_indices.contains(&index),
+                    is_disabled: option_ref.is_disabled(),
+                }
+            })
+            .collect();
+
+        let label_width = rows
+            .iter()
+            .map(|row| font_metrics.width_of(&row.label))
+            .fold(Pixels::ZERO, Pixels::max);
+
+        let appearance = if select_ref.is_drop_down_box() {
+            let label = selected_indices
+                .first()
+                .map(|&index| rows[index].label.clone())
+                .unwrap_or_default();
+            Appearance::DropDownBox { label }
+        } else {
+            // Scroll the first selected option into view
+            let visible_rows = select_ref.display_size();
+            let last_possible_first_row = rows.len().saturating_sub(visible_rows);
+            let first_visible_row = selected_indices
+                .first()
+                .map_or(0, |&index| (index + 1).saturating_sub(visible_rows))
+                .min(last_possible_first_row);
+
+            Appearance::ListBox {
+                rows,
+                first_visible_row,
+                visible_rows,
+            }
+        };
+
+        drop(select_ref);
+
+        Self {
+            control,
+            label_width,
+            appearance,
+        }
+    }
+
+    /// The size of the control, including its borders
+    ///
+    /// Select elements have no intrinsic aspect ratio, so changing their width
+    /// does not affect their height.
+    #[must_use]
+    pub fn intrinsic_size(&self) -> IntrinsicSize {
+        let row_height = self.control.line_height();
+
+        let (content_width, content_height) = match &self.appearance {
+            Appearance::DropDownBox { .. } => {
+                let arrow_width = self.control.font_metrics.width_of(DROP_DOWN_ARROW);
+                (self.label_width + arrow_width + PADDING * 3., row_height)
+            },
+            Appearance::ListBox { visible_rows, .. } => (
+                self.label_width + PADDING * 2.,
+                row_height * *visible_rows as f32,
+            ),
+        };
+
+        self.control.intrinsic_size(content_width, content_height)
+    }
+
+    /// Create a fragment for the control, the borders of the element are drawn inside the given area
+    #[must_use]
+    pub fn create_fragment(&self, position: Vec2D<Pixels>, size: Size<Pixels>) -> Fragment {
+        self.control
+            .create_fragment(position, size, |content_area| self.children(content_area))
+    }
+
+    /// The fragments inside the control, relative to its content area
+    #[must_use]
+    fn children(&self, content_area: Rectangle<Pixels>) -> Vec<Fragment> {
+        let row_height = self.control.line_height();
+        let color = *self.control.style.color();
+
+        match &self.appearance {
+            Appearance::DropDownBox { label } => {
+                let text_top = (content_area.height() - row_height) / 2.;
+                let arrow_left = content_area.width()
+                    - PADDING
+                    - self.control.font_metrics.width_of(DROP_DOWN_ARROW);
+
+                vec![
+                    self.control
+                        .text_fragment(label, Vec2D::new(PADDING, text_top), color),
+                    self.control.text_fragment(
+                        DROP_DOWN_ARROW,
+                        Vec2D::new(arrow_left, text_top),
+                        color,
+                    ),
+                ]
+            },
+            Appearance::ListBox {
+                rows,
+                first_visible_row,
+                visible_rows,
+            } => rows
+                .iter()
+                .skip(*first_visible_row)
+                .take(*visible_rows)
+                .enumerate()
+                .map(|(index, row)| {
+                    let row_area = Rectangle::from_position_and_size(
+                        Vec2D::new(Pixels::ZERO, row_height * index as f32),
+                        content_area.width(),
+                        row_height,
+                    );
+
+                    let mut row_style = ComputedStyle::default();
+                    let text_color = if row.is_selected {
+                        row_style.set_background_color(BackgroundColor::Color(
+                            SELECTED_OPTION_BACKGROUND,
+                        ));
+                        SELECTED_OPTION_COLOR
+                    } else if row.is_disabled {
+                        DISABLED_OPTION_COLOR
+                    } else {
+                        color
+                    };
+
+                    let label = self.control.text_fragment(
+                        &row.label,
+                        Vec2D::new(PADDING, Pixels::ZERO),
+                        text_color,
+                    );
+
+                    BoxFragment::new(
+                        Some(row.node.clone()),
+                        row_style,
+                        row_area,
+                        Sides::all(Pixels::ZERO),
+                        row_area,
+                        row_area,
+                        vec![label],
+                    )
+                    .into()
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! Implements [intrinsic sizing](https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes)
+
+use std::cmp;
+
+use crate::css::{
+    computed_style::ComputedStyle,
+    style::computed::{Margin, Padding},
+    values::{AutoOr, PercentageOr, PreferredSize},
+};
+
+use super::Pixels;
+
+/// The [min-content](https://drafts.csswg.org/css-sizing-3/#min-content) and
+/// [max-content](https://drafts.csswg.org/css-sizing-3/#max-content) inline sizes of a box
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct ContentSizes {
+    /// The narrowest width the box can take without overflowing its contents
+    ///
+    /// CSS2 calls this the "preferred minimum width".
+    pub min_content: Pixels,
+
+    /// The width the box would take if no line breaks were forced
+    ///
+    /// CSS2 calls this the "preferred width".
+    pub max_content: Pixels,
+}
+
+impl ContentSizes {
+    /// Content sizes of something that cannot be broken into multiple lines
+    #[must_use]
+    pub const fn fixed(size: Pixels) -> Self {
+        Self {
+            min_content: size,
+            max_content: size,
+        }
+    }
+
+    /// <https://drafts.csswg.org/css2/#shrink-to-fit-float>
+    ///
+    /// The result is `min(max(preferred minimum width, available width), preferred width)`.
+    #[must_use]
+    pub fn shrink_to_fit(&self, available_width: Pixels) -> Pixels {
+        cmp::min(
+            cmp::max(self.min_content, available_width),
+            self.max_content,
+        )
+    }
+
+    /// Content sizes of a box that can fit either `self` or `other`
+    #[must_use]
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            min_content: cmp::max(self.min_content, other.min_content),
+            max_content: cmp::max(self.max_content, other.max_content),
+        }
+    }
+
+    /// Content sizes of two boxes that are placed next to each other on the same line
+    ///
+    /// The min-content contribution is the maximum of the two, because a line break
+    /// may be inserted between them.
+    #[must_use]
+    pub fn append(self, other: Self) -> Self {
+        Self {
+            min_content: cmp::max(self.min_content, other.min_content),
+            max_content: self.max_content + other.max_content,
+        }
+    }
+
+    /// Add a fixed amount of space (for example borders) to both sizes
+    #[must_use]
+    pub fn grow_by(self, amount: Pixels) -> Self {
+        Self {
+            min_content: self.min_content + amount,
+            max_content: self.max_content + amount,
+        }
+    }
+
+    /// Compute the [outer size contribution](https://drafts.csswg.org/css-sizing-3/#contributions)
+    /// of a box with the given style and content sizes
+    ///
+    /// Percentages cannot be resolved while computing intrinsic sizes and are treated as zero
+    /// (or `auto`, in case of the `width` property).
+    #[must_use]
+    pub fn outer_contribution(self, style: &ComputedStyle) -> Self {
+        let inner = match *style.width() {
+            PreferredSize::Size(PercentageOr::NotPercentage(width)) => Self::fixed(width),
+            PreferredSize::MinContent => Self::fixed(self.min_content),
+            PreferredSize::MaxContent => Self::fixed(self.max_content),
+            PreferredSize::FitContent(Some(PercentageOr::NotPercentage(limit))) => Self {
+                min_content: self.min_content,
+                max_content: self.shrink_to_fit(limit),
+            },
+            _ => self,
+        };
+
+        let resolve_margin = |margin: &Margin| {
+            margin
+                .map(|p| p.resolve_against(Pixels::ZERO))
+                .unwrap_or_default()
+        };
+        let resolve_padding = |padding: &Padding| padding.resolve_against(Pixels::ZERO);
+
+        let borders = style.used_border_widths();
+        let margin_left = resolve_margin(style.margin_left());
+        let margin_right = resolve_margin(style.margin_right());
+        let padding_left = resolve_padding(style.padding_left());
+        let padding_right = resolve_padding(style.padding_right());
+
+        inner.grow_by(
+            margin_left + padding_left + borders.horizontal_sum() + padding_right + margin_right,
+        )
+    }
+}
+
+/// Resolve the value of a [preferred size property](https://drafts.csswg.org/css-sizing-3/#preferred-size-properties)
+/// like `width`
+///
+/// Percentages are resolved against `percentage_basis`. `available_space` is the space that the
+/// content box may take up, which is what `fit-content` is clamped by if no explicit limit is given.
+/// The content sizes of the box are only computed if the size depends on them.
+///
+/// Returns [AutoOr::Auto] if the size is `auto`.
+#[must_use]
+pub(crate) fn resolve_preferred_size<F>(
+    size: PreferredSize<PercentageOr<Pixels>>,
+    percentage_basis: Pixels,
+    available_space: Pixels,
+    content_sizes: F,
+) -> AutoOr<Pixels>
+where
+    F: FnOnce() -> ContentSizes,
+{
+    let used_size = match size {
+        PreferredSize::Auto => return AutoOr::Auto,
+        PreferredSize::Size(size) => size.resolve_against(percentage_basis),
+        PreferredSize::MinContent => content_sizes().min_content,
+        PreferredSize::MaxContent => content_sizes().max_content,
+        PreferredSize::FitContent(limit) => {
+            // https://drafts.csswg.org/css-sizing-3/#fit-content-size
+            let limit = limit.map_or(available_space, |limit| {
+                limit.resolve_against(percentage_basis)
+            });
+            content_sizes().shrink_to_fit(limit)
+        },
+    };
+
+    AutoOr::NotAuto(used_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::values::Percentage;
+
+    #[test]
+    fn shrink_to_fit() {
+        let sizes = ContentSizes {
+            min_content: Pixels(50.),
+            max_content: Pixels(200.),
+        };
+
+        assert_eq!(sizes.shrink_to_fit(Pixels(100.)), Pixels(100.));
+        assert_eq!(sizes.shrink_to_fit(Pixels(10.)), Pixels(50.));
+        assert_eq!(sizes.shrink_to_fit(Pixels(500.)), Pixels(200.));
+    }
+
+    #[test]
+    fn intrinsic_size_keywords() {
+        let content_sizes = || ContentSizes {
+            min_content: Pixels(50.),
+            max_content: Pixels(200.),
+        };
+        let resolve =
+            |size| resolve_preferred_size(size, Pixels(400.), Pixels(100.), content_sizes);
+
+        assert_eq!(resolve(PreferredSize::Auto), AutoOr::Auto);
+        assert_eq!(
+            resolve(PreferredSize::Size(PercentageOr::NotPercentage(Pixels(
+                10.
+            )))),
+            AutoOr::NotAuto(Pixels(10.))
+        );
+        assert_eq!(
+            resolve(PreferredSize::MinContent),
+            AutoOr::NotAuto(Pixels(50.))
+        );
+        assert_eq!(
+            resolve(PreferredSize::MaxContent),
+            AutoOr::NotAuto(Pixels(200.))
+        );
+        assert_eq!(
+            resolve(PreferredSize::FitContent(None)),
+            AutoOr::NotAuto(Pixels(100.))
+        );
+
+        // fit-content(25%) = fit-content(100px)
+        assert_eq!(
+            resolve(PreferredSize::FitContent(Some(PercentageOr::Percentage(
+                Percentage::from_fraction(0.25)
+            )))),
+            AutoOr::NotAuto(Pixels(100.))
+        );
+    }
+
+    #[test]
+    fn append_and_max() {
+        let a = ContentSizes {
+            min_content: Pixels(10.),
+            max_content: Pixels(30.),
+        };
+        let b = ContentSizes {
+            min_content: Pixels(20.),
+            max_content: Pixels(25.),
+        };
+
+        assert_eq!(
+            a.append(b),
+            ContentSizes {
+                min_content: Pixels(20.),
+                max_content: Pixels(55.),
+            }
+        );
+        assert_eq!(
+            a.max(b),
+            ContentSizes {
+                min_content: Pixels(20.),
+                max_content: Pixels(30.),
+            }
+        );
+    }
+}
@@ -0,0 +1,469 @@
+//! Rendering of `<textarea>` elements
+//!
+//! Like [select elements](super::select), textareas are rendered like replaced elements.
+//! Their value is wrapped to the width of the control and scrolled vertically such that
+//! the cursor is always visible.
+//!
+//! <https://html.spec.whatwg.org/multipage/rendering.html#the-textarea-element-2>
+
+use std::ops::Range;
+
+use math::{Rectangle, Vec2D};
+use settings::SETTINGS;
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment},
+        line_break::LineBreakIterator,
+        style::specified::BackgroundColor,
+        values::Color,
+        ComputedStyle, FontMetrics,
+    },
+    dom::{dom_objects, DomPtr},
+    editing::TextEditor,
+    spellcheck,
+};
+
+use super::{form_control::FormControl, replaced::IntrinsicSize, Pixels, Sides, Size};
+
+/// Space between the border of the control and its text
+const PADDING: Pixels = Pixels(2.);
+
+const CARET_WIDTH: Pixels = Pixels(1.);
+const COMPOSITION_UNDERLINE: Pixels = Pixels(1.);
+
+const SELECTION_BACKGROUND: Color = Color::rgb(0x1a, 0x5f, 0xb4);
+
+const SELECTION_COLOR: Color = Color::WHITE;
+
+const SPELLING_ERROR_COLOR: Color = Color::rgb(0xe0, 0x1b, 0x24);
+
+/// The width of a single segment of the wavy line below misspelled words
+const SQUIGGLE_SEGMENT_WIDTH: Pixels = Pixels(2.);
+
+/// The thickness of the wavy line, it spans twice this height
+const SQUIGGLE_THICKNESS: Pixels = Pixels(1.);
+
+/// The value of a textarea, broken into lines that fit into the control
+#[derive(Clone, Debug)]
+pub(crate) struct WrappedText {
+    text: String,
+
+    /// The byte range of each line within the text
+    ///
+    /// The whitespace at which lines are broken is not part of any line.
+    lines: Vec<Range<usize>>,
+    font_metrics: FontMetrics,
+}
+
+impl WrappedText {
+    #[must_use]
+    pub fn new(text: &str, font_metrics: FontMetrics, available_width: Pixels) -> Self {
+        let mut lines = vec![];
+
+        let mut paragraph_start = 0;
+        for paragraph in text.split('\n') {
+            let mut line_breaker =
+                LineBreakIterator::new(paragraph, font_metrics.clone(), available_width);
+
+            // Leading whitespace is preserved, but not the whitespace at which lines are broken
+            let mut is_at_beginning_of_paragraph = true;
+            while let Some(line) = line_breaker.next_line(!is_at_beginning_of_paragraph) {
+                let start =
+                    paragraph_start + (line.text.as_ptr() as usize - paragraph.as_ptr() as usize);
+                lines.push(start..start + line.text.len());
+                is_at_beginning_of_paragraph = false;
+            }
+
+            if is_at_beginning_of_paragraph {
+                // Empty paragraphs still occupy a line
+                lines.push(paragraph_start..paragraph_start);
+            }
+
+            paragraph_start += paragraph.len() + 1;
+        }
+
+        Self {
+            text: text.to_owned(),
+            lines,
+            font_metrics,
+        }
+    }
+
+    /// The number of lines, this is never zero
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    #[must_use]
+    pub fn line(&self, index: usize) -> Range<usize> {
+        self.lines[index].clone()
+    }
+
+    /// The index of the line that contains the given byte offset
+    #[must_use]
+    pub fn line_of(&self, offset: usize) -> usize {
+        self.lines
+            .partition_point(|line| line.start <= offset)
+            .saturating_sub(1)
+    }
+
+    /// The horizontal position of the given byte offset within its line
+    #[must_use]
+    pub fn x_of(&self, offset: usize) -> Pixels {
+        let line = self.line(self.line_of(offset));
+        let offset = offset.clamp(line.start, line.end);
+        self.font_metrics.width_of(&self.text[line.start..offset])
+    }
+
+    /// The byte offset in the given line that is closest to a horizontal position
+    #[must_use]
+    pub fn offset_at(&self, line: usize, x: Pixels) -> usize {
+        let line = self.line(line.min(self.len() - 1));
+
+        let mut previous_boundary = (line.start, Pixels::ZERO);
+        for (index, c) in self.text[line.clone()].char_indices() {
+            let boundary = line.start + index + c.len_utf8();
+            let width = self.font_metrics.width_of(&self.text[line.start..boundary]);
+
+            if x < (previous_boundary.1 + width) / 2. {
+                break;
+            }
+            previous_boundary = (boundary, width);
+        }
+
+        previous_boundary.0
+    }
+}
+
+/// The text of a textarea, as it is displayed inside a control with the given border box
+pub(crate) struct TextAreaGeometry {
+    wrapped_text: WrappedText,
+
+    /// The area that the text is displayed in, inside the borders and padding of the control
+    text_area: Rectangle<Pixels>,
+    line_height: Pixels,
+    first_visible_line: usize,
+}
+
+impl TextAreaGeometry {
+    #[must_use]
+    pub fn new(
+        textarea: &dom_objects::HtmlTextAreaElement,
+        style: &ComputedStyle,
+        border_area: Rectangle<Pixels>,
+    ) -> Self {
+        let borders = style.used_border_widths();
+        let text_area = Rectangle::from_corners(
+            border_area.top_left() + Vec2D::new(borders.left + PADDING, borders.top + PADDING),
+            border_area.bottom_right()
+                - Vec2D::new(borders.right + PADDING, borders.bottom + PADDING),
+        );
+
+        let font_metrics = FontMetrics::for_style(style);
+        let wrapped_text =
+            WrappedText::new(textarea.editor().text(), font_metrics, text_area.width());
+
+        Self {
+            wrapped_text,
+            text_area,
+            line_height: style.line_height().used_value(*style.font_size()),
+            first_visible_line: textarea.first_visible_line(),
+        }
+    }
+
+    /// The number of lines that fit into the control, at least one
+    #[must_use]
+    fn visible_lines(&self) -> usize {
+        ((self.text_area.height().0 / self.line_height.0).floor() as usize).max(1)
+    }
+
+    /// Scroll the minimal distance that makes the line containing `offset` visible
+    fn scroll_into_view(&mut self, offset: usize) {
+        let line = self.wrapped_text.line_of(offset);
+        let visible_lines = self.visible_lines();
+
+        if line < self.first_visible_line {
+            self.first_visible_line = line;
+        } else if line >= self.first_visible_line + visible_lines {
+            self.first_visible_line = line + 1 - visible_lines;
+        }
+
+        let last_possible_first_line = self.wrapped_text.len().saturating_sub(visible_lines);
+        self.first_visible_line = self.first_visible_line.min(last_possible_first_line);
+    }
+
+    /// The byte offset of the text that is closest to the given position
+    #[must_use]
+    pub fn offset_at(&self, position: Vec2D<Pixels>) -> usize {
+        let relative_position = position - self.text_area.top_left();
+        let line = (relative_position.y.0 / self.line_height.0).floor().max(0.) as usize
+            + self.first_visible_line;
+
+        self.wrapped_text.offset_at(line, relative_position.x)
+    }
+
+    /// The byte offset that is reached by moving `lines` lines up (negative) or down (positive) from `offset`
+    #[must_use]
+    pub fn move_vertically(&self, offset: usize, lines: isize) -> usize {
+        let line = self.wrapped_text.line_of(offset);
+        let target_line = line.saturating_add_signed(lines);
+
+        if target_line >= self.wrapped_text.len() {
+            return self.wrapped_text.text.len();
+        }
+        if lines < 0 && line == 0 {
+            return 0;
+        }
+
+        self.wrapped_text
+            .offset_at(target_line, self.wrapped_text.x_of(offset))
+    }
+
+    /// The byte offsets where the line containing `offset` starts and ends
+    #[must_use]
+    pub fn line_boundaries(&self, offset: usize) -> Range<usize> {
+        self.wrapped_text.line(self.wrapped_text.line_of(offset))
+    }
+}
+
+/// The content of a `<textarea>` element, at the time that it was laid out
+#[derive(Clone, Debug)]
+pub(crate) struct TextAreaControl {
+    control: FormControl,
+
+    /// The value of the `cols` attribute
+    cols: usize,
+
+    /// The value of the `rows` attribute
+    rows: usize,
+}
+
+impl TextAreaControl {
+    #[must_use]
+    pub fn new(textarea: DomPtr<dom_objects::HtmlTextAreaElement>, style: ComputedStyle) -> Self {
+        let (cols, rows) = {
+            let textarea = textarea.borrow();
+            (textarea.cols(), textarea.rows())
+        };
+
+        Self {
+            control: FormControl::new(textarea.upcast(), style),
+            cols,
+            rows,
+        }
+    }
+
+    #[must_use]
+    fn textarea(&self) -> DomPtr<dom_objects::HtmlTextAreaElement> {
+        self.control
+            .node
+            .try_into_type()
+            .expect("textarea control was not created for a textarea element")
+    }
+
+    /// The size of the control, including its borders
+    ///
+    /// This is determined by the `cols` and `rows` attributes, the value of the
+    /// element does not affect the size.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#attr-textarea-cols>
+    #[must_use]
+    pub fn intrinsic_size(&self) -> IntrinsicSize {
+        // FIXME: This should use the average character width of the font
+        let character_width = self.control.font_metrics.width_of("0");
+        let content_width = character_width * self.cols as f32 + PADDING * 2.;
+        let content_height = self.control.line_height() * self.rows as f32 + PADDING * 2.;
+
+        self.control.intrinsic_size(content_width, content_height)
+    }
+
+    /// A box that is drawn behind (or in front of) the text
+    #[must_use]
+    fn filled_rect(&self, area: Rectangle<Pixels>, color: Color) -> Fragment {
+        let mut style = ComputedStyle::default();
+        style.set_background_color(BackgroundColor::Color(color));
+
+        BoxFragment::new(
+            None,
+            style,
+            area,
+            Sides::all(Pixels::ZERO),
+            area,
+            area,
+            vec![],
+        )
+        .into()
+    }
+
+    /// A wavy line below the text between `start` and `end`, which are on the same line
+    fn push_squiggle(
+        &self,
+        start: Vec2D<Pixels>,
+        end: Vec2D<Pixels>,
+        children: &mut Vec<Fragment>,
+    ) {
+        let bottom = start.y + self.control.line_height();
+
+        let mut x = start.x;
+        let mut is_raised = false;
+        while x < end.x {
+            let width = SQUIGGLE_SEGMENT_WIDTH.min(end.x - x);
+            let top = if is_raised {
+                bottom - SQUIGGLE_THICKNESS * 2.
+            } else {
+                bottom - SQUIGGLE_THICKNESS
+            };
+
+            let segment =
+                Rectangle::from_position_and_size(Vec2D::new(x, top), width, SQUIGGLE_THICKNESS);
+            children.push(self.filled_rect(segment, SPELLING_ERROR_COLOR));
+
+            x += width;
+            is_raised = !is_raised;
+        }
+    }
+
+    /// The byte ranges of the misspelled words in the value of the textarea
+    ///
+    /// The word that is currently being typed is not reported until the caret leaves it.
+    #[must_use]
+    fn spelling_errors(
+        &self,
+        textarea: &dom_objects::HtmlTextAreaElement,
+        editor: &TextEditor,
+        is_focused: bool,
+    ) -> Vec<Range<usize>> {
+        if !textarea.is_mutable() || !spellcheck::is_enabled_for(&self.control.node) {
+            return vec![];
+        }
+
+        let language = SETTINGS.preferences().language.clone();
+        let Some(dictionary) = spellcheck::dictionary_for_language(&language) else {
+            return vec![];
+        };
+
+        let mut misspelled_words = spellcheck::misspelled_words(editor.text(), &dictionary);
+        if is_focused && !editor.has_selection() {
+            let cursor = editor.cursor();
+            misspelled_words.retain(|word| !(word.start..=word.end).contains(&cursor));
+        }
+
+        misspelled_words
+    }
+
+    /// Create a fragment for the control, the borders of the element are drawn inside the given area
+    #[must_use]
+    pub fn create_fragment(&self, position: Vec2D<Pixels>, size: Size<Pixels>) -> Fragment {
+        self.control
+            .create_fragment(position, size, |content_area| self.children(content_area))
+    }
+
+    /// The fragments inside the control, relative to its content area
+    #[must_use]
+    fn children(&self, content_area: Rectangle<Pixels>) -> Vec<Fragment> {
+        // Children are positioned relative to the content area
+        let borders = self.control.style.used_border_widths();
+        let relative_border_area = Rectangle::from_corners(
+            Vec2D::new(Pixels::ZERO - borders.left, Pixels::ZERO - borders.top),
+            Vec2D::new(
+                content_area.width() + borders.right,
+                content_area.height() + borders.bottom,
+            ),
+        );
+
+        let textarea = self.textarea();
+        let (editor, is_focused, spelling_errors) = {
+            let textarea = textarea.borrow();
+            let editor = textarea.editor();
+            let is_focused = textarea.is_focused();
+            let spelling_errors = self.spelling_errors(&textarea, &editor, is_focused);
+            (editor, is_focused, spelling_errors)
+        };
+
+        let mut geometry = TextAreaGeometry::new(
+            &textarea.borrow(),
+            &self.control.style,
+            relative_border_area,
+        );
+        geometry.scroll_into_view(editor.cursor());
+        textarea
+            .borrow_mut()
+            .set_first_visible_line(geometry.first_visible_line);
+
+        let wrapped_text = &geometry.wrapped_text;
+        let line_height = self.control.line_height();
+        let color = *self.control.style.color();
+        let selection = editor.selection();
+
+        let mut children = vec![];
+        let visible_lines = geometry.first_visible_line
+            ..(geometry.first_visible_line + geometry.visible_lines()).min(wrapped_text.len());
+        for (row, line_index) in visible_lines.enumerate() {
+            let line = wrapped_text.line(line_index);
+            let line_top_left =
+                geometry.text_area.top_left() + Vec2D::new(Pixels::ZERO, line_height * row as f32);
+            let x_of = |offset| line_top_left + Vec2D::new(wrapped_text.x_of(offset), Pixels::ZERO);
+
+            // Split the line into the parts before, inside and after the selection
+            let selection_start = selection.start.clamp(line.start, line.end);
+            let selection_end = selection.end.clamp(line.start, line.end);
+
+            if selection_start != selection_end {
+                let selection_area = Rectangle::from_corners(
+                    x_of(selection_start),
+                    x_of(selection_end) + Vec2D::new(Pixels::ZERO, line_height),
+                );
+                children.push(self.filled_rect(selection_area, SELECTION_BACKGROUND));
+            }
+
+            let parts = [
+                (line.start..selection_start, color),
+                (selection_start..selection_end, SELECTION_COLOR),
+                (selection_end..line.end, color),
+            ];
+            for (range, color) in parts {
+                if !range.is_empty() {
+                    let text = &editor.text()[range.clone()];
+                    children.push(self.control.text_fragment(text, x_of(range.start), color));
+                }
+            }
+
+            for word in &spelling_errors {
+                let start = word.start.clamp(line.start, line.end);
+                let end = word.end.clamp(line.start, line.end);
+                if start != end {
+                    self.push_squiggle(x_of(start), x_of(end), &mut children);
+                }
+            }
+
+            // Text that an input method is still composing is underlined
+            if let Some(composition) = editor.composition().filter(|_| is_focused) {
+                let start = composition.start.clamp(line.start, line.end);
+                let end = composition.end.clamp(line.start, line.end);
+                if start != end {
+                    let underline_area = Rectangle::from_corners(
+                        x_of(start) + Vec2D::new(Pixels::ZERO, line_height - COMPOSITION_UNDERLINE),
+                        x_of(end) + Vec2D::new(Pixels::ZERO, line_height),
+                    );
+                    children.push(self.filled_rect(underline_area, color));
+                }
+            }
+
+            let has_caret = is_focused
+                && !editor.has_selection()
+                && wrapped_text.line_of(editor.cursor()) == line_index;
+            if has_caret {
+                let caret_area = Rectangle::from_position_and_size(
+                    x_of(editor.cursor()),
+                    CARET_WIDTH,
+                    line_height,
+                );
+                children.push(self.filled_rect(caret_area, color));
+            }
+        }
+
+        children
+    }
+}
@@ -0,0 +1,615 @@
+//! <https://drafts.csswg.org/mediaqueries-5/>
+
+use settings::SETTINGS;
+
+use crate::{
+    css::{
+        font_metrics::DefaultFontSizes,
+        layout::{Pixels, Size},
+        serialize::serialize_comma_separated,
+        style::{specified::Length, StyleContext, ToComputedStyle},
+        syntax::Token,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned, InternedString,
+};
+
+/// The properties of the user agent and device that media queries are evaluated against
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct MediaEnvironment {
+    pub viewport: Size<Pixels>,
+
+    /// <https://drafts.csswg.org/mediaqueries-5/#prefers-color-scheme>
+    pub color_scheme: ColorScheme,
+
+    /// <https://drafts.csswg.org/mediaqueries-5/#prefers-reduced-motion>
+    pub prefers_reduced_motion: bool,
+
+    /// Whether the document is printed instead of being shown on a screen
+    pub is_printing: bool,
+
+    /// The font sizes chosen by the user
+    ///
+    /// Relative lengths in media queries refer to these instead of the font size of any element.
+    pub font_sizes: DefaultFontSizes,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ColorScheme {
+    #[default]
+    Light,
+    Dark,
+}
+
+/// <https://drafts.csswg.org/mediaqueries-5/#typedef-media-query-list>
+///
+/// The list matches if any of its queries match. An empty list always matches.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MediaQueryList {
+    queries: Vec<MediaQuery>,
+}
+
+/// <https://drafts.csswg.org/mediaqueries-5/#typedef-media-query>
+#[derive(Clone, Debug)]
+struct MediaQuery {
+    is_negated: bool,
+    media_type: MediaType,
+    condition: Option<MediaCondition>,
+}
+
+/// <https://drafts.csswg.org/mediaqueries-5/#media-types>
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum MediaType {
+    All,
+    Screen,
+    Print,
+
+    /// Media types that are not known to the user agent never match
+    Unknown(InternedString),
+}
+
+/// <https://drafts.csswg.org/mediaqueries-5/#typedef-media-condition>
+#[derive(Clone, Debug)]
+enum MediaCondition {
+    Feature(MediaFeature),
+    Not(Box<Self>),
+    And(Vec<Self>),
+    Or(Vec<Self>),
+}
+
+/// <https://drafts.csswg.org/mediaqueries-5/#mq-features>
+#[derive(Clone, Debug)]
+enum MediaFeature {
+    /// `(prefers-color-scheme)` always matches, since there is always a color scheme
+    PrefersColorScheme(Option<ColorScheme>),
+
+    /// Whether the user prefers reduced motion, `(prefers-reduced-motion)` is equivalent
+    /// to `(prefers-reduced-motion: reduce)`
+    PrefersReducedMotion(bool),
+    Width(Range, Length),
+    Height(Range, Length),
+
+    /// Features that are not known to the user agent never match
+    ///
+    /// Only the name of the feature is kept.
+    Unknown(InternedString),
+}
+
+/// How the value of a feature is compared to the value in a media query
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Range {
+    /// `min-` prefix
+    AtLeast,
+
+    /// `max-` prefix
+    AtMost,
+    Exactly,
+}
+
+impl MediaEnvironment {
+    /// An environment with the default preferences
+    #[must_use]
+    pub fn new(viewport: Size<Pixels>) -> Self {
+        Self {
+            viewport,
+            color_scheme: ColorScheme::default(),
+            prefers_reduced_motion: false,
+            is_printing: false,
+            font_sizes: DefaultFontSizes::default(),
+        }
+    }
+
+    /// An environment for printing the document on pages whose content area has the given size
+    ///
+    /// Printed documents always use the light color scheme.
+    #[must_use]
+    pub fn print(page_content_size: Size<Pixels>) -> Self {
+        Self {
+            is_printing: true,
+            ..Self::new(page_content_size)
+        }
+    }
+
+    /// An environment with the preferences of the user
+    #[must_use]
+    pub fn from_settings(viewport: Size<Pixels>) -> Self {
+        let color_scheme = if SETTINGS.prefers_dark_color_scheme() {
+            ColorScheme::Dark
+        } else {
+            ColorScheme::Light
+        };
+
+        let preferences = SETTINGS.preferences();
+
+        Self {
+            viewport,
+            color_scheme,
+            prefers_reduced_motion: preferences.reduced_motion,
+            is_printing: false,
+            font_sizes: DefaultFontSizes::new(&preferences),
+        }
+    }
+}
+
+impl MediaQueryList {
+    #[must_use]
+    pub fn matches(&self, environment: &MediaEnvironment) -> bool {
+        self.queries.is_empty() || self.queries.iter().any(|query| query.matches(environment))
+    }
+}
+
+impl MediaQuery {
+    /// The query that results from a query that failed to parse
+    const NOT_ALL: Self = Self {
+        is_negated: true,
+        media_type: MediaType::All,
+        condition: None,
+    };
+
+    fn matches(&self, environment: &MediaEnvironment) -> bool {
+        let matches_type = match self.media_type {
+            MediaType::All => true,
+            MediaType::Screen => !environment.is_printing,
+            MediaType::Print => environment.is_printing,
+            MediaType::Unknown(_) => false,
+        };
+
+        let matches_condition = self
+            .condition
+            .as_ref()
+            .is_none_or(|condition| condition.matches(environment));
+
+        (matches_type && matches_condition) != self.is_negated
+    }
+}
+
+impl MediaCondition {
+    fn matches(&self, environment: &MediaEnvironment) -> bool {
+        match self {
+            Self::Feature(feature) => feature.matches(environment),
+            Self::Not(condition) => !condition.matches(environment),
+            Self::And(conditions) => conditions
+                .iter()
+                .all(|condition| condition.matches(environment)),
+            Self::Or(conditions) => conditions
+                .iter()
+                .any(|condition| condition.matches(environment)),
+        }
+    }
+
+    /// Parse a condition, optionally disallowing `or` at the top level
+    ///
+    /// <https://drafts.csswg.org/mediaqueries-5/#typedef-media-condition>
+    fn parse_with_or(parser: &mut Parser<'_>, allow_or: bool) -> Result<Self, ParseError> {
+        if parser.peek_token_ignoring_whitespace(0) == Some(&Token::Ident(static_interned!("not")))
+        {
+            let _ = parser.next_token_ignoring_whitespace();
+            let condition = Self::parse_in_parens(parser)?;
+            return Ok(Self::Not(Box::new(condition)));
+        }
+
+        let first = Self::parse_in_parens(parser)?;
+
+        let combinator = match parser.peek_token_ignoring_whitespace(0) {
+            Some(Token::Ident(static_interned!("and"))) => static_interned!("and"),
+            Some(Token::Ident(static_interned!("or"))) if allow_or => static_interned!("or"),
+            _ => return Ok(first),
+        };
+
+        // "and" and "or" cannot be mixed without parentheses
+        let mut conditions = vec![first];
+        while parser.peek_token_ignoring_whitespace(0) == Some(&Token::Ident(combinator.clone())) {
+            let _ = parser.next_token_ignoring_whitespace();
+            conditions.push(Self::parse_in_parens(parser)?);
+        }
+
+        if combinator == static_interned!("and") {
+            Ok(Self::And(conditions))
+        } else {
+            Ok(Self::Or(conditions))
+        }
+    }
+
+    /// <https://drafts.csswg.org/mediaqueries-5/#typedef-media-in-parens>
+    fn parse_in_parens(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
+        parser.expect_token(Token::ParenthesisOpen)?;
+
+        let is_nested_condition = matches!(
+            parser.peek_token_ignoring_whitespace(0),
+            Some(Token::ParenthesisOpen | Token::Ident(static_interned!("not")))
+        );
+        let condition = if is_nested_condition {
+            Self::parse_with_or(parser, true)?
+        } else {
+            Self::Feature(MediaFeature::parse(parser)?)
+        };
+
+        parser.expect_token(Token::ParenthesisClose)?;
+        Ok(condition)
+    }
+}
+
+impl MediaFeature {
+    fn matches(&self, environment: &MediaEnvironment) -> bool {
+        let compare = |range: Range, actual: Pixels, length: &Length| {
+            let expected =
+                length.to_computed_style(&StyleContext::for_media_environment(environment));
+            match range {
+                Range::AtLeast => actual >= expected,
+                Range::AtMost => actual <= expected,
+                Range::Exactly => actual == expected,
+            }
+        };
+
+        match self {
+            Self::PrefersColorScheme(color_scheme) => {
+                color_scheme.is_none_or(|color_scheme| color_scheme == environment.color_scheme)
+            },
+            Self::PrefersReducedMotion(reduce) => *reduce == environment.prefers_reduced_motion,
+            Self::Width(range, length) => compare(*range, environment.viewport.width, length),
+            Self::Height(range, length) => compare(*range, environment.viewport.height, length),
+            Self::Unknown(_) => false,
+        }
+    }
+}
+
+impl<'a> CSSParse<'a> for MediaQueryList {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let mut queries = vec![];
+
+        loop {
+            match parser.peek_token_ignoring_whitespace(0) {
+                None | Some(Token::CurlyBraceOpen | Token::Semicolon) => break,
+                _ => {},
+            }
+
+            // Queries that fail to parse are replaced with "not all", without affecting
+            // the other queries in the list
+            let query = parser.parse().unwrap_or_else(|_| {
+                while !matches!(
+                    parser.peek_token_ignoring_whitespace(0),
+                    None | Some(Token::Comma | Token::CurlyBraceOpen | Token::Semicolon)
+                ) {
+                    let _ = parser.next_token_ignoring_whitespace();
+                }
+                MediaQuery::NOT_ALL
+            });
+            queries.push(query);
+
+            if parser.peek_token_ignoring_whitespace(0) == Some(&Token::Comma) {
+                let _ = parser.next_token_ignoring_whitespace();
+            } else if !matches!(
+                parser.peek_token_ignoring_whitespace(0),
+                None | Some(Token::CurlyBraceOpen | Token::Semicolon)
+            ) {
+                // Trailing garbage after a query
+                return Err(ParseError);
+            }
+        }
+
+        Ok(Self { queries })
+    }
+}
+
+impl<'a> CSSParse<'a> for MediaQuery {
+    // <https://drafts.csswg.org/mediaqueries-5/#typedef-media-query>
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        if parser.peek_token_ignoring_whitespace(0) == Some(&Token::ParenthesisOpen) {
+            let condition = MediaCondition::parse_with_or(parser, true)?;
+            return Ok(Self {
+                is_negated: false,
+                media_type: MediaType::All,
+                condition: Some(condition),
+            });
+        }
+
+        let is_negated = match parser.peek_token_ignoring_whitespace(0) {
+            Some(Token::Ident(static_interned!("not"))) => {
+                let _ = parser.next_token_ignoring_whitespace();
+                true
+            },
+            Some(Token::Ident(static_interned!("only"))) => {
+                let _ = parser.next_token_ignoring_whitespace();
+                false
+            },
+            _ => false,
+        };
+
+        let media_type = match parser.expect_identifier()? {
+            static_interned!("all") => MediaType::All,
+            static_interned!("screen") => MediaType::Screen,
+            static_interned!("print") => MediaType::Print,
+            static_interned!("not")
+            | static_interned!("only")
+            | static_interned!("and")
+            | static_interned!("or") => return Err(ParseError),
+            other => MediaType::Unknown(other),
+        };
+
+        let condition = if parser.peek_token_ignoring_whitespace(0)
+            == Some(&Token::Ident(static_interned!("and")))
+        {
+            let _ = parser.next_token_ignoring_whitespace();
+            Some(MediaCondition::parse_with_or(parser, false)?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            is_negated,
+            media_type,
+            condition,
+        })
+    }
+}
+
+impl<'a> CSSParse<'a> for MediaFeature {
+    // <https://drafts.csswg.org/mediaqueries-5/#typedef-media-feature>
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let name = parser.expect_identifier()?;
+
+        // Features without a value are evaluated in a boolean context
+        if parser.peek_token_ignoring_whitespace(0) != Some(&Token::Colon) {
+            let feature = match name {
+                static_interned!("prefers-color-scheme") => Self::PrefersColorScheme(None),
+                static_interned!("prefers-reduced-motion") => Self::PrefersReducedMotion(true),
+                _ => Self::Unknown(name),
+            };
+            return Ok(feature);
+        }
+        let _ = parser.next_token_ignoring_whitespace();
+
+        let feature = match name {
+            static_interned!("prefers-color-scheme") => {
+                let color_scheme = match parser.expect_identifier()? {
+                    static_interned!("light") => ColorScheme::Light,
+                    static_interned!("dark") => ColorScheme::Dark,
+                    _ => return Err(ParseError),
+                };
+                Self::PrefersColorScheme(Some(color_scheme))
+            },
+            static_interned!("prefers-reduced-motion") => {
+                let reduce = match parser.expect_identifier()? {
+                    static_interned!("reduce") => true,
+                    static_interned!("no-preference") => false,
+                    _ => return Err(ParseError),
+                };
+                Self::PrefersReducedMotion(reduce)
+            },
+            static_interned!("width") => Self::Width(Range::Exactly, parser.parse()?),
+            static_interned!("min-width") => Self::Width(Range::AtLeast, parser.parse()?),
+            static_interned!("max-width") => Self::Width(Range::AtMost, parser.parse()?),
+            static_interned!("height") => Self::Height(Range::Exactly, parser.parse()?),
+            static_interned!("min-height") => Self::Height(Range::AtLeast, parser.parse()?),
+            static_interned!("max-height") => Self::Height(Range::AtMost, parser.parse()?),
+            _ => {
+                // Skip the value of unknown features
+                while !matches!(
+                    parser.peek_token_ignoring_whitespace(0),
+                    None | Some(Token::ParenthesisClose)
+                ) {
+                    let _ = parser.next_token_ignoring_whitespace();
+                }
+                Self::Unknown(name)
+            },
+        };
+
+        Ok(feature)
+    }
+}
+
+impl CSSSerialize for MediaQueryList {
+    /// <https://drafts.csswg.org/cssom/#serialize-a-media-query-list>
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_comma_separated(&self.queries, dest);
+    }
+}
+
+impl CSSSerialize for MediaQuery {
+    /// <https://drafts.csswg.org/cssom/#serialize-a-media-query>
+    fn serialize_to(&self, dest: &mut String) {
+        // The media type can be omitted if it is "all" and there is a condition
+        let omit_media_type =
+            !self.is_negated && self.media_type == MediaType::All && self.condition.is_some();
+
+        if !omit_media_type {
+            if self.is_negated {
+                dest.push_str("not ");
+            }
+            self.media_type.serialize_to(dest);
+        }
+
+        if let Some(condition) = &self.condition {
+            if !omit_media_type {
+                dest.push_str(" and ");
+            }
+            condition.serialize_to(dest);
+        }
+    }
+}
+
+impl CSSSerialize for MediaType {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::All => dest.push_str("all"),
+            Self::Screen => dest.push_str("screen"),
+            Self::Print => dest.push_str("print"),
+            Self::Unknown(name) => name.serialize_to(dest),
+        }
+    }
+}
+
+impl MediaCondition {
+    fn serialize_in_parens(&self, dest: &mut String) {
+        dest.push('(');
+        match self {
+            Self::Feature(feature) => feature.serialize_to(dest),
+            _ => self.serialize_to(dest),
+        }
+        dest.push(')');
+    }
+}
+
+impl CSSSerialize for MediaCondition {
+    fn serialize_to(&self, dest: &mut String) {
+        let (conditions, combinator) = match self {
+            Self::Feature(_) => {
+                self.serialize_in_parens(dest);
+                return;
+            },
+            Self::Not(condition) => {
+                dest.push_str("not ");
+                condition.serialize_in_parens(dest);
+                return;
+            },
+            Self::And(conditions) => (conditions, " and "),
+            Self::Or(conditions) => (conditions, " or "),
+        };
+
+        for (index, condition) in conditions.iter().enumerate() {
+            if index != 0 {
+                dest.push_str(combinator);
+            }
+            condition.serialize_in_parens(dest);
+        }
+    }
+}
+
+impl CSSSerialize for MediaFeature {
+    /// Serializes the feature without the surrounding parentheses
+    fn serialize_to(&self, dest: &mut String) {
+        let range_prefix = |range: &Range| match range {
+            Range::AtLeast => "min-",
+            Range::AtMost => "max-",
+            Range::Exactly => "",
+        };
+
+        match self {
+            Self::PrefersColorScheme(None) => dest.push_str("prefers-color-scheme"),
+            Self::PrefersColorScheme(Some(ColorScheme::Light)) => {
+                dest.push_str("prefers-color-scheme: light");
+            },
+            Self::PrefersColorScheme(Some(ColorScheme::Dark)) => {
+                dest.push_str("prefers-color-scheme: dark");
+            },
+            Self::PrefersReducedMotion(true) => dest.push_str("prefers-reduced-motion: reduce"),
+            Self::PrefersReducedMotion(false) => {
+                dest.push_str("prefers-reduced-motion: no-preference");
+            },
+            Self::Width(range, length) => {
+                dest.push_str(range_prefix(range));
+                dest.push_str("width: ");
+                length.serialize_to(dest);
+            },
+            Self::Height(range, length) => {
+                dest.push_str(range_prefix(range));
+                dest.push_str("height: ");
+                length.serialize_to(dest);
+            },
+            Self::Unknown(name) => name.serialize_to(dest),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(query: &str, environment: &MediaEnvironment) -> bool {
+        MediaQueryList::parse_from_str(query)
+            .expect("media query list should parse")
+            .matches(environment)
+    }
+
+    #[test]
+    fn media_types() {
+        let environment = MediaEnvironment::new(Size {
+            width: Pixels(800.),
+            height: Pixels(600.),
+        });
+
+        assert!(matches("", &environment));
+        assert!(matches("screen", &environment));
+        assert!(matches("only screen", &environment));
+        assert!(!matches("print", &environment));
+        assert!(matches("not print", &environment));
+        assert!(matches("print, screen", &environment));
+        assert!(!matches("tv", &environment));
+
+        let environment = MediaEnvironment::print(Size {
+            width: Pixels(700.),
+            height: Pixels(1000.),
+        });
+        assert!(matches("print", &environment));
+        assert!(!matches("screen", &environment));
+        assert!(matches("not screen", &environment));
+        assert!(matches("print and (min-width: 600px)", &environment));
+    }
+
+    #[test]
+    fn user_preferences() {
+        let mut environment = MediaEnvironment::new(Size {
+            width: Pixels(800.),
+            height: Pixels(600.),
+        });
+
+        assert!(matches("(prefers-color-scheme: light)", &environment));
+        assert!(!matches("(prefers-color-scheme: dark)", &environment));
+        assert!(!matches("(prefers-reduced-motion)", &environment));
+
+        environment.color_scheme = ColorScheme::Dark;
+        environment.prefers_reduced_motion = true;
+        assert!(matches("(prefers-color-scheme: dark)", &environment));
+        assert!(matches(
+            "screen and (prefers-reduced-motion: reduce)",
+            &environment
+        ));
+        assert!(!matches(
+            "not all and (prefers-reduced-motion)",
+            &environment
+        ));
+    }
+
+    #[test]
+    fn viewport_size() {
+        let environment = MediaEnvironment::new(Size {
+            width: Pixels(800.),
+            height: Pixels(600.),
+        });
+
+        assert!(matches("(min-width: 600px)", &environment));
+        assert!(!matches("(max-width: 600px)", &environment));
+        assert!(matches(
+            "(max-width: 600px) or (min-height: 50vh)",
+            &environment
+        ));
+        assert!(!matches(
+            "(min-width: 600px) and (not (height: 600px))",
+            &environment
+        ));
+        assert!(!matches("(unknown-feature: 1)", &environment));
+
+        // A query that fails to parse does not affect the others
+        assert!(matches("screen and foo, (min-width: 1px)", &environment));
+    }
+}
@@ -1,23 +1,33 @@
 //! Cascading Style Sheets
 
+mod animation;
 mod computed_style;
 pub(crate) mod display_list;
 mod font_metrics;
 pub(crate) mod fragment_tree;
 pub(crate) mod layout;
 mod line_break;
+mod media_queries;
+mod page;
 mod properties;
 mod selectors;
+mod serialize;
 pub mod style;
 mod stylecomputer;
 mod stylesheet;
 pub(crate) mod syntax;
 mod values;
 
-use computed_style::ComputedStyle;
-use font_metrics::FontMetrics;
+pub(crate) use animation::{AnimationClock, Transitions};
+pub(crate) use computed_style::ComputedStyle;
+pub(crate) use font_metrics::FontMetrics;
 use line_break::LineBreakIterator;
-use properties::{StyleProperty, StylePropertyDeclaration};
+pub(crate) use media_queries::{MediaEnvironment, MediaQueryList};
+pub(crate) use page::{PageLayout, PageRule};
+use properties::{PropertyId, StyleProperty, StylePropertyDeclaration};
+pub(crate) use selectors::Selector;
+pub(crate) use serialize::CSSSerialize;
 pub(crate) use stylecomputer::StyleComputer;
 pub(crate) use stylesheet::{Origin, StyleRule, Stylesheet};
 pub(crate) use syntax::parser::{CSSParse, ParseError, Parser};
+pub(crate) use values::Color;
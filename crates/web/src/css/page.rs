@@ -0,0 +1,316 @@
+//! Page boxes, which a document is split into when it is printed
+//!
+//! <https://drafts.csswg.org/css-page-3/>
+
+use std::rc::Rc;
+
+use crate::{
+    css::{
+        layout::{Pixels, Sides, Size},
+        media_queries::{MediaEnvironment, MediaQueryList},
+        style::{
+            specified::{Length, Margin},
+            StyleContext, ToComputedStyle,
+        },
+        syntax::Token,
+        values::{AutoOr, PercentageOr},
+        CSSParse, ParseError, Parser, StyleProperty, StylePropertyDeclaration,
+    },
+    static_interned,
+};
+
+/// The size of a page if no `@page` rule specifies one, which is ISO A4
+const DEFAULT_PAGE_SIZE: Size<Pixels> = millimeters(210., 297.);
+
+/// The margins of a page if no `@page` rule specifies them (1cm)
+const DEFAULT_PAGE_MARGIN: Pixels = Pixels(96. / 2.54);
+
+/// An `@page` rule
+///
+/// <https://drafts.csswg.org/css-page-3/#at-page-rule>
+#[derive(Clone, Debug)]
+pub(crate) struct PageRule {
+    size: Option<PageSize>,
+
+    /// The declarations of the rule, except for `size`
+    declarations: Vec<StylePropertyDeclaration>,
+
+    /// The conditions of all the `@media` rules that this rule is nested in
+    media: Vec<Rc<MediaQueryList>>,
+}
+
+/// <https://drafts.csswg.org/css-page-3/#page-size-prop>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PageSize {
+    Auto,
+
+    /// The width and the height of the page
+    Explicit(Length, Length),
+
+    /// A standard paper size, or the default size if there is none, in the given orientation
+    Named {
+        size: Option<Size<Pixels>>,
+        orientation: Option<Orientation>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Orientation {
+    Portrait,
+    Landscape,
+}
+
+/// The size and margins of the pages that a document is printed on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct PageLayout {
+    pub size: Size<Pixels>,
+    pub margins: Sides<Pixels>,
+}
+
+impl PageRule {
+    #[must_use]
+    pub fn new(media: Vec<Rc<MediaQueryList>>) -> Self {
+        Self {
+            size: None,
+            declarations: vec![],
+            media,
+        }
+    }
+
+    pub fn set_size(&mut self, size: PageSize) {
+        self.size = Some(size);
+    }
+
+    pub fn push_declaration(&mut self, declaration: StylePropertyDeclaration) {
+        self.declarations.push(declaration);
+    }
+
+    /// Whether the conditions of all enclosing `@media` rules are true
+    #[must_use]
+    fn applies_in(&self, environment: &MediaEnvironment) -> bool {
+        self.media
+            .iter()
+            .all(|media_queries| media_queries.matches(environment))
+    }
+}
+
+impl PageLayout {
+    /// Determine the layout of the pages from the `@page` rules that apply when printing
+    ///
+    /// Later rules take precedence over earlier ones. Media queries of enclosing `@media`
+    /// rules are evaluated against pages of the default size. Margins may be specified as percentages,
+    /// which are relative to the width (for the left and right margins) or height (for the top
+    /// and bottom margins) of the page.
+    #[must_use]
+    pub fn from_rules<'a, I>(rules: I) -> Self
+    where
+        I: IntoIterator<Item = &'a PageRule>,
+    {
+        let environment = MediaEnvironment::print(DEFAULT_PAGE_SIZE);
+        let rules: Vec<&PageRule> = rules
+            .into_iter()
+            .filter(|rule| rule.applies_in(&environment))
+            .collect();
+
+        let size = rules
+            .iter()
+            .rev()
+            .find_map(|rule| rule.size)
+            .map_or(DEFAULT_PAGE_SIZE, PageSize::resolve);
+
+        let default_margin = AutoOr::NotAuto(PercentageOr::NotPercentage(Length::pixels(
+            DEFAULT_PAGE_MARGIN,
+        )));
+        let mut margins = Sides::all(default_margin);
+        for declaration in rules.iter().flat_map(|rule| &rule.declarations) {
+            match &declaration.value {
+                StyleProperty::Margin(sides) => margins = *sides,
+                StyleProperty::MarginTop(margin) => margins.top = *margin,
+                StyleProperty::MarginRight(margin) => margins.right = *margin,
+                StyleProperty::MarginBottom(margin) => margins.bottom = *margin,
+                StyleProperty::MarginLeft(margin) => margins.left = *margin,
+                _ => {},
+            }
+        }
+
+        // Automatic margins are only meaningful for the boxes in the page margins, which are not supported
+        let context = StyleContext::new(size);
+        let resolve = |margin: &Margin, percent_of: Pixels| match margin.to_computed_style(&context)
+        {
+            AutoOr::Auto => Pixels::ZERO,
+            AutoOr::NotAuto(margin) => margin.resolve_against(percent_of),
+        };
+        let margins = Sides {
+            top: resolve(&margins.top, size.height),
+            right: resolve(&margins.right, size.width),
+            bottom: resolve(&margins.bottom, size.height),
+            left: resolve(&margins.left, size.width),
+        };
+
+        Self { size, margins }
+    }
+
+    /// The area within the margins of a page, which the document is laid out in
+    #[must_use]
+    pub fn content_size(&self) -> Size<Pixels> {
+        Size {
+            width: (self.size.width - self.margins.left - self.margins.right).max(Pixels(1.)),
+            height: (self.size.height - self.margins.top - self.margins.bottom).max(Pixels(1.)),
+        }
+    }
+}
+
+impl PageSize {
+    #[must_use]
+    fn resolve(self) -> Size<Pixels> {
+        match self {
+            Self::Auto => DEFAULT_PAGE_SIZE,
+            Self::Explicit(width, height) => {
+                // Relative lengths are resolved against the initial font size
+                let context = StyleContext::new(DEFAULT_PAGE_SIZE);
+                Size {
+                    width: width.to_computed_style(&context),
+                    height: height.to_computed_style(&context),
+                }
+            },
+            Self::Named { size, orientation } => {
+                let size = size.unwrap_or(DEFAULT_PAGE_SIZE);
+                let is_landscape = size.width > size.height;
+                let is_rotated = match orientation {
+                    Some(Orientation::Portrait) => is_landscape,
+                    Some(Orientation::Landscape) => !is_landscape,
+                    None => false,
+                };
+
+                if is_rotated {
+                    Size {
+                        width: size.height,
+                        height: size.width,
+                    }
+                } else {
+                    size
+                }
+            },
+        }
+    }
+}
+
+/// <https://drafts.csswg.org/css-page-3/#typedef-page-size-page-size>
+fn parse_named_size(parser: &mut Parser<'_>) -> Result<Size<Pixels>, ParseError> {
+    let size = match parser.expect_identifier()? {
+        static_interned!("a5") => millimeters(148., 210.),
+        static_interned!("a4") => millimeters(210., 297.),
+        static_interned!("a3") => millimeters(297., 420.),
+        static_interned!("b5") => millimeters(176., 250.),
+        static_interned!("b4") => millimeters(250., 353.),
+        static_interned!("letter") => inches(8.5, 11.),
+        static_interned!("legal") => inches(8.5, 14.),
+        static_interned!("ledger") => inches(11., 17.),
+        _ => return Err(ParseError),
+    };
+
+    Ok(size)
+}
+
+impl<'a> CSSParse<'a> for Orientation {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        match parser.expect_identifier()? {
+            static_interned!("portrait") => Ok(Self::Portrait),
+            static_interned!("landscape") => Ok(Self::Landscape),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+impl<'a> CSSParse<'a> for PageSize {
+    /// `auto | <length [0,∞]>{1,2} | [ <page-size> || [ portrait | landscape ] ]`
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        if matches!(
+            parser.peek_token_ignoring_whitespace(0),
+            Some(Token::Ident(static_interned!("auto")))
+        ) {
+            _ = parser.next_token_ignoring_whitespace();
+            return Ok(Self::Auto);
+        }
+
+        if let Some(width) = parser.parse_optional::<Length>() {
+            let height = parser.parse_optional::<Length>().unwrap_or(width);
+            if width.is_negative() || height.is_negative() {
+                return Err(ParseError);
+            }
+            return Ok(Self::Explicit(width, height));
+        }
+
+        let mut size = parser.parse_optional_value(parse_named_size);
+        let orientation = parser.parse_optional::<Orientation>();
+        if size.is_none() {
+            size = parser.parse_optional_value(parse_named_size);
+        }
+
+        if size.is_none() && orientation.is_none() {
+            return Err(ParseError);
+        }
+
+        Ok(Self::Named { size, orientation })
+    }
+}
+
+const fn millimeters(width: f32, height: f32) -> Size<Pixels> {
+    Size {
+        width: Pixels(width * 96. / 25.4),
+        height: Pixels(height * 96. / 25.4),
+    }
+}
+
+const fn inches(width: f32, height: f32) -> Size<Pixels> {
+    Size {
+        width: Pixels(width * 96.),
+        height: Pixels(height * 96.),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::Origin;
+
+    fn page_layout(css: &str) -> PageLayout {
+        let stylesheet = Parser::new(css, Origin::Author).parse_stylesheet(0);
+        PageLayout::from_rules(stylesheet.page_rules())
+    }
+
+    #[test]
+    fn page_size() {
+        assert_eq!(page_layout("").size, DEFAULT_PAGE_SIZE);
+        assert_eq!(page_layout("@page { size: auto }").size, DEFAULT_PAGE_SIZE);
+        assert_eq!(page_layout("@page { size: letter }").size, inches(8.5, 11.));
+        assert_eq!(
+            page_layout("@page { size: landscape a5 }").size,
+            millimeters(210., 148.)
+        );
+        assert_eq!(page_layout("@page { size: 4in 6in }").size, inches(4., 6.));
+        assert_eq!(page_layout("@page { size: 5in }").size, inches(5., 5.));
+
+        // Invalid values and rules with page selectors are ignored
+        assert_eq!(
+            page_layout("@page { size: a4 a4 } @page :first { size: letter }").size,
+            DEFAULT_PAGE_SIZE
+        );
+    }
+
+    #[test]
+    fn page_margins() {
+        let layout = page_layout("@page { margin: 1in 10%; margin-bottom: 0 }");
+        assert_eq!(layout.margins.top, Pixels(96.));
+        assert_eq!(layout.margins.right, DEFAULT_PAGE_SIZE.width * 0.1);
+        assert_eq!(layout.margins.bottom, Pixels::ZERO);
+
+        // Only rules that apply to printed documents are considered
+        let layout = page_layout(
+            "@page { size: letter; margin: 0 } @media screen { @page { size: a3; margin: 1in } }",
+        );
+        assert_eq!(layout.size, inches(8.5, 11.));
+        assert_eq!(layout.margins, Sides::all(Pixels::ZERO));
+        assert_eq!(layout.content_size(), inches(8.5, 11.));
+    }
+}
@@ -1,2 +1,25 @@
 #![allow(clippy::all)]
 include!(concat!(env!("OUT_DIR"), "/properties.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn property_id_lookup() {
+        for &id in PropertyId::ALL {
+            assert_eq!(PropertyId::from_name(id.name()), Some(id));
+        }
+
+        assert_eq!(
+            PropertyId::from_name(static_interned!("margin-top")),
+            Some(PropertyId::MarginTop)
+        );
+        assert_eq!(PropertyId::from_name(static_interned!("margin")), None);
+
+        assert!(PropertyId::Color.is_inherited());
+        assert!(!PropertyId::MarginTop.is_inherited());
+        assert!(PropertyId::Width.is_animatable());
+        assert!(!PropertyId::Display.is_animatable());
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt;
 
 use super::CSSValidateSelector;
-use crate::css::{syntax::Token, CSSParse, ParseError, Parser};
+use crate::css::{syntax::Token, CSSParse, CSSSerialize, ParseError, Parser};
 
 /// <https://drafts.csswg.org/selectors-4/#typedef-attr-matcher>
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -49,9 +49,21 @@ impl<'a> CSSParse<'a> for AttributeMatcher {
 
 impl CSSValidateSelector for AttributeMatcher {
     fn is_valid(&self) -> bool {
-        // We don't support *any* attribute matchers
-        // As per spec, we therefore treat them as invalid
-        false
+        true
+    }
+}
+
+impl CSSSerialize for AttributeMatcher {
+    fn serialize_to(&self, dest: &mut String) {
+        let matcher = match self {
+            Self::WhiteSpaceSeperatedListContaining => "~=",
+            Self::HyphenSeperatedListBeginningWith => "|=",
+            Self::StartsWith => "^=",
+            Self::EndsWith => "$=",
+            Self::ContainsSubstring => "*=",
+            Self::EqualTo => "=",
+        };
+        dest.push_str(matcher);
     }
 }
 
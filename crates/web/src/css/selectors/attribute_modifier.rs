@@ -1,6 +1,6 @@
 use super::CSSValidateSelector;
 use crate::{
-    css::{syntax::Token, CSSParse, ParseError, Parser},
+    css::{syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
     static_interned,
 };
 
@@ -39,9 +39,16 @@ impl<'a> CSSParse<'a> for AttributeModifier {
 
 impl CSSValidateSelector for AttributeModifier {
     fn is_valid(&self) -> bool {
-        // We don't support *any* attribute modifiers
-        // As per spec, we therefore treat them as invalid
-        false
+        true
+    }
+}
+
+impl CSSSerialize for AttributeModifier {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::CaseInsensitive => dest.push('i'),
+            Self::CaseSensitive => dest.push('s'),
+        }
     }
 }
 
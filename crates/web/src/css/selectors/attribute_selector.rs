@@ -1,15 +1,17 @@
-use std::fmt;
+use std::{borrow::Cow, fmt};
 
 use crate::{
     css::{
         selectors::{
-            AttributeMatcher, AttributeModifier, CSSValidateSelector, Specificity,
-            WellQualifiedName,
+            AttributeMatcher, AttributeModifier, CSSValidateSelector, NamespacePrefix, Namespaces,
+            Specificity, WellQualifiedName,
         },
+        serialize::serialize_string,
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     dom::{dom_objects::Element, DomPtr},
+    infra::Namespace,
     InternedString,
 };
 
@@ -32,6 +34,13 @@ impl AttributeSelector {
     pub fn parse_without_leading_bracket(parser: &mut Parser<'_>) -> Result<Self, ParseError> {
         let attribute_name = WellQualifiedName::parse(parser)?;
 
+        // Selectors that use undeclared namespace prefixes are invalid
+        if let Some(NamespacePrefix::Ident(prefix)) = &attribute_name.prefix
+            && parser.namespaces().resolve(prefix).is_none()
+        {
+            return Err(ParseError);
+        }
+
         let selector = if matches!(
             parser.peek_token_ignoring_whitespace(0),
             Some(Token::BracketClose)
@@ -63,42 +72,54 @@ impl AttributeSelector {
         Ok(selector)
     }
 
-    pub fn matches(&self, element: &DomPtr<Element>) -> bool {
+    /// The name of the attribute that is matched
+    #[must_use]
+    pub fn attribute_name(&self) -> &WellQualifiedName {
         match self {
-            Self::Exists { attribute_name } => {
-                // FIXME: Don't consider attribute namespace
-                element
-                    .borrow()
-                    .attributes()
-                    .get(&attribute_name.ident)
-                    .is_some()
+            Self::Exists { attribute_name } | Self::Matches { attribute_name, .. } => {
+                attribute_name
             },
+        }
+    }
+
+    /// <https://drafts.csswg.org/selectors-4/#attribute-selectors>
+    #[must_use]
+    pub fn matches(&self, element: &DomPtr<Element>, namespaces: &Namespaces) -> bool {
+        let attribute_name = self.attribute_name();
+
+        // FIXME: The DOM does not support namespaced attributes yet, so every
+        //        attribute is considered to be in the null namespace
+        let matches_namespace = match &attribute_name.prefix {
+            None | Some(NamespacePrefix::Asterisk) => true,
+            Some(NamespacePrefix::Ident(prefix)) => namespaces
+                .resolve(prefix)
+                .is_some_and(|namespace| namespace.as_str().is_empty()),
+        };
+        if !matches_namespace {
+            return false;
+        }
+
+        let element = element.borrow();
+
+        // Attribute names of html elements are matched case-insensitively
+        let name = if element.namespace() == Namespace::HTML {
+            InternedString::from(attribute_name.ident.to_string().to_ascii_lowercase())
+        } else {
+            attribute_name.ident.clone()
+        };
+
+        let Some(attribute_value) = element.attributes().get(&name) else {
+            return false;
+        };
+
+        match self {
+            Self::Exists { .. } => true,
             Self::Matches {
-                attribute_name,
                 matcher,
-                value: selector_value,
+                value,
                 modifier,
-            } => {
-                let borrowed_elem = element.borrow();
-                let attribute_value = borrowed_elem.attributes().get(&attribute_name.ident);
-
-                match attribute_value {
-                    Some(interned_value) => {
-                        if modifier.is_case_insensitive() {
-                            matcher.are_matching(
-                                &selector_value.to_lowercase(),
-                                &interned_value.to_string().to_ascii_lowercase(),
-                            )
-                        } else {
-                            matcher.are_matching(
-                                selector_value,
-                                &interned_value.to_string().to_ascii_lowercase(),
-                            )
-                        }
-                    },
-                    None => false,
-                }
-            },
+                ..
+            } => matcher.are_matching(value, &attribute_value.to_string(), *modifier),
         }
     }
 }
@@ -106,7 +127,10 @@ impl AttributeSelector {
 impl<'a> CSSParse<'a> for AttributeSelector {
     // <https://drafts.csswg.org/selectors-4/#typedef-attribute-selector>
     fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
-        if !matches!(parser.next_token(), Some(Token::BracketOpen)) {
+        if !matches!(
+            parser.next_token_ignoring_whitespace(),
+            Some(Token::BracketOpen)
+        ) {
             return Err(ParseError);
         }
 
@@ -129,21 +153,157 @@ impl CSSValidateSelector for AttributeSelector {
 }
 
 impl AttributeMatcher {
-    fn are_matching(&self, selector_value: &str, attribute_value: &str) -> bool {
+    fn are_matching(
+        &self,
+        selector_value: &str,
+        attribute_value: &str,
+        modifier: AttributeModifier,
+    ) -> bool {
+        // Case-insensitive matching only folds ascii letters
+        let (selector_value, attribute_value): (Cow<'_, str>, Cow<'_, str>) =
+            if modifier.is_case_insensitive() {
+                (
+                    selector_value.to_ascii_lowercase().into(),
+                    attribute_value.to_ascii_lowercase().into(),
+                )
+            } else {
+                (selector_value.into(), attribute_value.into())
+            };
+        let (selector_value, attribute_value) = (&*selector_value, &*attribute_value);
+
         match self {
-            Self::ContainsSubstring => attribute_value.contains(selector_value),
-            Self::EndsWith => attribute_value.ends_with(selector_value),
-            Self::EqualTo => attribute_value.eq(selector_value),
+            Self::EqualTo => attribute_value == selector_value,
             Self::HyphenSeperatedListBeginningWith => {
-                let following_char = attribute_value.as_bytes().get(selector_value.len() + 1);
+                attribute_value == selector_value
+                    || attribute_value
+                        .strip_prefix(selector_value)
+                        .is_some_and(|remainder| remainder.starts_with('-'))
+            },
+            Self::WhiteSpaceSeperatedListContaining => {
+                // A value that is empty or contains whitespace can never be an item of the list
+                !selector_value.is_empty()
+                    && !selector_value.contains(|c: char| c.is_ascii_whitespace())
+                    && attribute_value
+                        .split_ascii_whitespace()
+                        .any(|item| item == selector_value)
+            },
 
-                attribute_value.starts_with(selector_value)
-                    && matches!(following_char, None | Some(b'-'))
+            // The substring matchers never match an empty value
+            Self::StartsWith => {
+                !selector_value.is_empty() && attribute_value.starts_with(selector_value)
+            },
+            Self::EndsWith => {
+                !selector_value.is_empty() && attribute_value.ends_with(selector_value)
+            },
+            Self::ContainsSubstring => {
+                !selector_value.is_empty() && attribute_value.contains(selector_value)
             },
-            Self::StartsWith => attribute_value.starts_with(selector_value),
-            Self::WhiteSpaceSeperatedListContaining => attribute_value
-                .split(|c: char| c.is_ascii_whitespace())
-                .any(|element| element == selector_value),
         }
     }
 }
+
+impl CSSSerialize for AttributeSelector {
+    /// <https://drafts.csswg.org/cssom/#serialize-a-simple-selector>
+    fn serialize_to(&self, dest: &mut String) {
+        dest.push('[');
+        match self {
+            Self::Exists { attribute_name } => attribute_name.serialize_to(dest),
+            Self::Matches {
+                attribute_name,
+                matcher,
+                value,
+                modifier,
+            } => {
+                attribute_name.serialize_to(dest);
+                matcher.serialize_to(dest);
+                serialize_string(value, dest);
+
+                // Attributes are matched case-sensitively by default
+                if modifier.is_case_insensitive() {
+                    dest.push(' ');
+                    modifier.serialize_to(dest);
+                }
+            },
+        }
+        dest.push(']');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        css::{CSSParse, Origin, Parser, Selector},
+        dom::{dom_objects::Element, DomPtr},
+    };
+
+    fn element_with_attribute(name: &str, value: &str) -> DomPtr<Element> {
+        let element = DomPtr::new(Element::default());
+        element
+            .borrow_mut()
+            .attributes_mut()
+            .insert(name.into(), value.into());
+        element
+    }
+
+    fn matches(selector: &str, element: &DomPtr<Element>) -> bool {
+        Selector::parse_from_str(selector)
+            .expect("selector is valid")
+            .matches(element)
+    }
+
+    #[test]
+    fn match_attribute_values() {
+        let element = element_with_attribute("lang", "en-US");
+
+        assert!(matches("[lang]", &element));
+        assert!(matches("[LANG]", &element));
+        assert!(!matches("[title]", &element));
+        assert!(matches("[lang=en-US]", &element));
+        assert!(!matches("[lang=en-us]", &element));
+        assert!(!matches("[lang=en-us s]", &element));
+        assert!(matches("[lang=en-us i]", &element));
+        assert!(matches("[lang|=en]", &element));
+        assert!(matches("[lang|=en-US]", &element));
+        assert!(!matches("[lang|=e]", &element));
+        assert!(matches("[lang^=en]", &element));
+        assert!(matches("[lang$=US]", &element));
+        assert!(matches("[lang*=\"n-U\"]", &element));
+        assert!(!matches("[lang^=\"\"]", &element));
+        assert!(!matches("[lang*=\"\"]", &element));
+    }
+
+    #[test]
+    fn match_whitespace_separated_list() {
+        let element = element_with_attribute("rel", "noopener  external\tnofollow");
+
+        assert!(matches("[rel~=external]", &element));
+        assert!(matches("[rel~=nofollow]", &element));
+        assert!(!matches("[rel~=extern]", &element));
+        assert!(!matches("[rel~=\"noopener external\"]", &element));
+        assert!(!matches("[rel~=\"\"]", &element));
+    }
+
+    #[test]
+    fn resolve_namespace_prefixes() {
+        let mut parser = Parser::new(
+            "@namespace empty \"\";
+            @namespace svg url(http://www.w3.org/2000/svg);
+            [empty|lang] { color: red }
+            [svg|lang] { color: red }
+            [*|lang] { color: red }
+            [|lang] { color: red }
+            [html|lang] { color: red }",
+            Origin::Author,
+        );
+        let stylesheet = parser.parse_stylesheet(0);
+
+        // The rule with the undeclared "html" prefix is invalid
+        let element = element_with_attribute("lang", "en");
+        let matching_rules: Vec<bool> = stylesheet
+            .rules()
+            .iter()
+            .map(|rule| rule.selectors()[0].matches(&element))
+            .collect();
+        assert_eq!(matching_rules, [true, false, true, true]);
+    }
+}
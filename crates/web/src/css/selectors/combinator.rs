@@ -1,6 +1,8 @@
 use std::fmt;
 
-use crate::css::{selectors::CSSValidateSelector, syntax::Token, CSSParse, ParseError, Parser};
+use crate::css::{
+    selectors::CSSValidateSelector, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser,
+};
 
 /// <https://drafts.csswg.org/selectors-4/#combinators>
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -128,6 +130,19 @@ impl CSSValidateSelector for Combinator {
     }
 }
 
+impl CSSSerialize for Combinator {
+    fn serialize_to(&self, dest: &mut String) {
+        let combinator = match self {
+            Self::Descendant => " ",
+            Self::Child => " > ",
+            Self::NextSibling => " + ",
+            Self::SubsequentSibling => " ~ ",
+            Self::Column => " || ",
+        };
+        dest.push_str(combinator);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Combinator;
@@ -18,7 +18,7 @@ pub use attribute_matcher::AttributeMatcher;
 pub use attribute_modifier::AttributeModifier;
 pub use attribute_selector::AttributeSelector;
 pub use combinator::Combinator;
-pub use namespace_prefix::NamespacePrefix;
+pub use namespace_prefix::{NamespacePrefix, Namespaces};
 pub use pseudo_class_selector::PseudoClassSelector;
 pub use qualified_name::WellQualifiedName;
 pub use specificity::Specificity;
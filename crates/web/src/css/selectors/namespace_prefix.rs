@@ -1,15 +1,57 @@
+use std::collections::HashMap;
+
 use crate::{
-    css::{selectors::CSSValidateSelector, syntax::Token, CSSParse, ParseError, Parser},
+    css::{
+        selectors::CSSValidateSelector, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser,
+    },
     InternedString,
 };
 
 /// <https://drafts.csswg.org/selectors-4/#typedef-ns-prefix>
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NamespacePrefix {
     Ident(InternedString),
     Asterisk,
 }
 
+/// The namespaces declared by the `@namespace` rules of a stylesheet
+///
+/// <https://drafts.csswg.org/css-namespaces/#declaration>
+#[derive(Clone, Debug, Default)]
+pub struct Namespaces {
+    /// The namespace of selectors without a [NamespacePrefix], if one was declared
+    default: Option<InternedString>,
+
+    prefixes: HashMap<InternedString, InternedString>,
+}
+
+impl Namespaces {
+    /// Declare a namespace, replacing any earlier declaration for the same prefix
+    ///
+    /// A `prefix` of `None` declares the default namespace.
+    pub fn declare(&mut self, prefix: Option<InternedString>, namespace: InternedString) {
+        match prefix {
+            Some(prefix) => {
+                self.prefixes.insert(prefix, namespace);
+            },
+            None => self.default = Some(namespace),
+        }
+    }
+
+    /// The namespace that a prefix was declared for
+    ///
+    /// An empty namespace means that the prefix refers to names without a namespace.
+    #[must_use]
+    pub fn resolve(&self, prefix: &InternedString) -> Option<&InternedString> {
+        self.prefixes.get(prefix)
+    }
+
+    #[must_use]
+    pub fn default_namespace(&self) -> Option<&InternedString> {
+        self.default.as_ref()
+    }
+}
+
 impl<'a> CSSParse<'a> for Option<NamespacePrefix> {
     /// <https://drafts.csswg.org/selectors-4/#typedef-ns-prefix>
     ///
@@ -34,9 +76,19 @@ impl<'a> CSSParse<'a> for Option<NamespacePrefix> {
 
 impl CSSValidateSelector for NamespacePrefix {
     fn is_valid(&self) -> bool {
-        // We don't support *any* namespace prefixes
-        // As per spec, we therefore treat them as invalid
-        false
+        // Selectors with undeclared prefixes are already rejected while parsing
+        true
+    }
+}
+
+impl CSSSerialize for NamespacePrefix {
+    /// Serializes the prefix, including the trailing `|`
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Ident(ident) => ident.serialize_to(dest),
+            Self::Asterisk => dest.push('*'),
+        }
+        dest.push('|');
     }
 }
 
@@ -1,7 +1,7 @@
 //! <https://drafts.csswg.org/selectors-4/#typedef-pseudo-class-selector>
 
 use crate::{
-    css::{syntax::Token, CSSParse, ParseError, Parser},
+    css::{syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
     InternedString,
 };
 
@@ -25,3 +25,16 @@ impl<'a> CSSParse<'a> for PseudoClassSelector {
         Ok(pseudo_class_selector)
     }
 }
+
+impl CSSSerialize for PseudoClassSelector {
+    fn serialize_to(&self, dest: &mut String) {
+        dest.push(':');
+        match self {
+            Self::Ident(ident) => ident.serialize_to(dest),
+            Self::Function => {
+                // FIXME: The arguments of functional pseudo classes are not kept around,
+                //        so they can't be serialized
+            },
+        }
+    }
+}
@@ -4,13 +4,13 @@ use crate::{
     css::{
         selectors::{CSSValidateSelector, NamespacePrefix},
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     InternedString,
 };
 
 /// <https://drafts.csswg.org/selectors-4/#typedef-wq-name>
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct WellQualifiedName {
     pub prefix: Option<NamespacePrefix>,
     pub ident: InternedString,
@@ -54,9 +54,13 @@ impl<'a> CSSParse<'a> for WellQualifiedName {
                 }
             },
             Some(Token::Ident(ident)) => {
+                // The '|' in an attribute selector like "[lang|=en]" is part of the matcher
                 if matches!(
                     parser.peek_token_ignoring_whitespace(0),
                     Some(Token::Delim('|'))
+                ) && !matches!(
+                    parser.peek_token_ignoring_whitespace(1),
+                    Some(Token::Delim('='))
                 ) {
                     _ = parser.next_token_ignoring_whitespace();
                     // The identifier was the namespace prefix
@@ -90,6 +94,15 @@ impl CSSValidateSelector for WellQualifiedName {
     }
 }
 
+impl CSSSerialize for WellQualifiedName {
+    fn serialize_to(&self, dest: &mut String) {
+        if let Some(prefix) = &self.prefix {
+            prefix.serialize_to(dest);
+        }
+        self.ident.serialize_to(dest);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::WellQualifiedName;
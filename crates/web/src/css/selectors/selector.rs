@@ -1,13 +1,13 @@
-use std::fmt;
+use std::{fmt, rc::Rc};
 
 use crate::{
-    css::{selectors::Specificity, syntax::Token, CSSParse, ParseError, Parser},
+    css::{selectors::Specificity, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
     dom::{dom_objects::Element, DomPtr},
     static_interned, InternedString,
 };
 
 use super::{
-    type_selector, AttributeSelector, Combinator, PseudoClassSelector, TypeSelector,
+    type_selector, AttributeSelector, Combinator, Namespaces, PseudoClassSelector, TypeSelector,
     WellQualifiedName,
 };
 
@@ -19,6 +19,9 @@ pub trait CSSValidateSelector {
 #[derive(Clone, Debug)]
 pub struct Selector {
     components: Box<[SelectorComponentOrCombinator]>,
+
+    /// The namespaces that were declared when the selector was parsed
+    namespaces: Rc<Namespaces>,
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +52,11 @@ pub enum SelectorComponent {
     /// <https://drafts.csswg.org/selectors/#the-hover-pseudo>
     Hover,
 
+    /// Match the focused element (`:focus`)
+    ///
+    /// <https://drafts.csswg.org/selectors/#the-focus-pseudo>
+    Focus,
+
     /// Matches an element on some other property
     ///
     /// <https://drafts.csswg.org/selectors-4/#typedef-pseudo-class-selector>
@@ -82,6 +90,7 @@ impl<'a> CSSParse<'a> for Selector {
 
         let selector = Self {
             components: components.into_boxed_slice(),
+            namespaces: parser.namespaces().clone(),
         };
 
         Ok(selector)
@@ -92,7 +101,7 @@ impl<'a> CSSParse<'a> for SelectorComponent {
     fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
         let component = match parser.peek_token_ignoring_whitespace(0) {
             Some(Token::Hash(ident, ..)) => {
-                let ident = *ident;
+                let ident = ident.clone();
                 _ = parser.next_token_ignoring_whitespace();
 
                 Self::Id(ident)
@@ -116,13 +125,10 @@ impl<'a> CSSParse<'a> for SelectorComponent {
             Some(Token::Colon) => {
                 let pseudo_class_selector = PseudoClassSelector::parse(parser)?;
 
-                if matches!(
-                    pseudo_class_selector,
-                    PseudoClassSelector::Ident(static_interned!("hover"))
-                ) {
-                    Self::Hover
-                } else {
-                    Self::PseudoClass(pseudo_class_selector)
+                match pseudo_class_selector {
+                    PseudoClassSelector::Ident(static_interned!("hover")) => Self::Hover,
+                    PseudoClassSelector::Ident(static_interned!("focus")) => Self::Focus,
+                    other => Self::PseudoClass(other),
                 }
             },
             Some(Token::Delim('*')) => {
@@ -185,7 +191,7 @@ impl Selector {
         let mut components = self.components();
 
         loop {
-            if components.all(|selector| selector.matches(element)) {
+            if components.all(|selector| selector.matches(element, &self.namespaces)) {
                 return true;
             }
 
@@ -208,13 +214,13 @@ impl SelectorComponent {
             Self::Id(_) => Specificity::new(1, 0, 0),
             Self::Class(_) => Specificity::new(0, 1, 0),
             Self::Attribute(_) => Specificity::new(0, 1, 0),
-            Self::PseudoClass(_) | Self::Hover => Specificity::new(0, 1, 0),
+            Self::PseudoClass(_) | Self::Hover | Self::Focus => Specificity::new(0, 1, 0),
             Self::Type(type_selector) => type_selector.specificity(),
         }
     }
 
     #[must_use]
-    pub fn matches(&self, element: &DomPtr<Element>) -> bool {
+    pub fn matches(&self, element: &DomPtr<Element>, namespaces: &Namespaces) -> bool {
         match self {
             Self::Id(id) => element
                 .borrow()
@@ -230,7 +236,8 @@ impl SelectorComponent {
                 false
             },
             Self::Hover => element.borrow().is_hovered(),
-            Self::Attribute(attribute_selector) => attribute_selector.matches(element),
+            Self::Focus => element.borrow().is_focused(),
+            Self::Attribute(attribute_selector) => attribute_selector.matches(element, namespaces),
             Self::Type(type_selector) => type_selector.matches(element),
         }
     }
@@ -280,6 +287,43 @@ impl From<Combinator> for SelectorComponentOrCombinator {
     }
 }
 
+impl CSSSerialize for Selector {
+    /// <https://drafts.csswg.org/cssom/#serialize-a-selector>
+    fn serialize_to(&self, dest: &mut String) {
+        for component in &self.components {
+            match component {
+                SelectorComponentOrCombinator::SelectorComponent(component) => {
+                    component.serialize_to(dest);
+                },
+                SelectorComponentOrCombinator::Combinator(combinator) => {
+                    combinator.serialize_to(dest);
+                },
+            }
+        }
+    }
+}
+
+impl CSSSerialize for SelectorComponent {
+    /// <https://drafts.csswg.org/cssom/#serialize-a-simple-selector>
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Id(id) => {
+                dest.push('#');
+                id.serialize_to(dest);
+            },
+            Self::Class(class) => {
+                dest.push('.');
+                class.serialize_to(dest);
+            },
+            Self::Attribute(attribute_selector) => attribute_selector.serialize_to(dest),
+            Self::Hover => dest.push_str(":hover"),
+            Self::Focus => dest.push_str(":focus"),
+            Self::PseudoClass(pseudo_class) => pseudo_class.serialize_to(dest),
+            Self::Type(type_selector) => type_selector.serialize_to(dest),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::css::selectors::{AttributeMatcher, AttributeModifier, NamespacePrefix};
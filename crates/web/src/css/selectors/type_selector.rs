@@ -4,7 +4,7 @@ use crate::{
     css::{
         selectors::{CSSValidateSelector, NamespacePrefix, Specificity, WellQualifiedName},
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     dom::{dom_objects::Element, DomPtr},
 };
@@ -101,3 +101,17 @@ impl TypeSelector {
         Specificity::new(0, 0, 1)
     }
 }
+
+impl CSSSerialize for TypeSelector {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Universal(prefix) => {
+                if let Some(prefix) = prefix {
+                    prefix.serialize_to(dest);
+                }
+                dest.push('*');
+            },
+            Self::Typename(type_name) => type_name.serialize_to(dest),
+        }
+    }
+}
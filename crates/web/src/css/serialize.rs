@@ -0,0 +1,225 @@
+//! Turning parsed CSS back into source code
+//!
+//! Parsing the serialization of a value yields the same value again (up to rounding),
+//! so serialized styles can be round-tripped.
+//!
+//! <https://drafts.csswg.org/cssom/#serializing-css-values>
+
+use crate::InternedString;
+
+use super::layout::Sides;
+
+/// Types that can be converted to CSS source code
+pub trait CSSSerialize {
+    fn serialize_to(&self, dest: &mut String);
+
+    #[must_use]
+    fn to_css_string(&self) -> String {
+        let mut dest = String::new();
+        self.serialize_to(&mut dest);
+        dest
+    }
+}
+
+/// The number of decimal places that floating point numbers are rounded to
+///
+/// This hides rounding errors that were introduced by unit conversions,
+/// like `0.3` turning into `0.29999998`.
+const DECIMAL_PLACES: i32 = 4;
+
+/// Serialize a number without any trailing zeros
+///
+/// <https://drafts.csswg.org/cssom/#serialize-a-css-component-value>
+pub fn serialize_float(value: f32, dest: &mut String) {
+    let scale = 10_f32.powi(DECIMAL_PLACES);
+    let rounded = (value * scale).round() / scale;
+
+    // Avoid serializing "-0"
+    let rounded = if rounded == 0. { 0. } else { rounded };
+    dest.push_str(&rounded.to_string());
+}
+
+/// <https://drafts.csswg.org/cssom/#serialize-an-identifier>
+pub fn serialize_identifier(identifier: &str, dest: &mut String) {
+    if identifier == "-" {
+        dest.push_str("\\-");
+        return;
+    }
+
+    let starts_with_hyphen = identifier.starts_with('-');
+    for (index, c) in identifier.chars().enumerate() {
+        let is_at_start = index == 0 || (index == 1 && starts_with_hyphen);
+
+        match c {
+            '\0' => dest.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => escape_as_code_point(c, dest),
+            '0'..='9' if is_at_start => escape_as_code_point(c, dest),
+            '-' | '_' | '0'..='9' | 'a'..='z' | 'A'..='Z' | '\u{80}'.. => dest.push(c),
+            _ => {
+                dest.push('\\');
+                dest.push(c);
+            },
+        }
+    }
+}
+
+/// <https://drafts.csswg.org/cssom/#serialize-a-string>
+pub fn serialize_string(value: &str, dest: &mut String) {
+    dest.push('"');
+
+    for c in value.chars() {
+        match c {
+            '\0' => dest.push('\u{FFFD}'),
+            '\u{1}'..='\u{1F}' | '\u{7F}' => escape_as_code_point(c, dest),
+            '"' | '\\' => {
+                dest.push('\\');
+                dest.push(c);
+            },
+            _ => dest.push(c),
+        }
+    }
+
+    dest.push('"');
+}
+
+/// <https://drafts.csswg.org/cssom/#serialize-a-url>
+pub fn serialize_url(url: &str, dest: &mut String) {
+    dest.push_str("url(");
+    serialize_string(url, dest);
+    dest.push(')');
+}
+
+/// Serialize a list of values, separated by commas
+pub fn serialize_comma_separated<T: CSSSerialize>(values: &[T], dest: &mut String) {
+    for (index, value) in values.iter().enumerate() {
+        if index != 0 {
+            dest.push_str(", ");
+        }
+        value.serialize_to(dest);
+    }
+}
+
+/// <https://drafts.csswg.org/cssom/#escape-a-character-as-code-point>
+fn escape_as_code_point(c: char, dest: &mut String) {
+    dest.push_str(&format!("\\{:x} ", c as u32));
+}
+
+impl CSSSerialize for InternedString {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_identifier(self.as_str(), dest);
+    }
+}
+
+/// Values for the start and end side of flow-relative shorthands like `margin-inline`
+///
+/// The second value is omitted if it equals the first one.
+impl<T: CSSSerialize + PartialEq> CSSSerialize for (T, T) {
+    fn serialize_to(&self, dest: &mut String) {
+        self.0.serialize_to(dest);
+
+        if self.1 != self.0 {
+            dest.push(' ');
+            self.1.serialize_to(dest);
+        }
+    }
+}
+
+/// Serializes the shortest list of values that expands to the same sides
+impl<T: CSSSerialize + PartialEq> CSSSerialize for Sides<T> {
+    fn serialize_to(&self, dest: &mut String) {
+        let values: &[&T] = if self.left != self.right {
+            &[&self.top, &self.right, &self.bottom, &self.left]
+        } else if self.top != self.bottom {
+            &[&self.top, &self.right, &self.bottom]
+        } else if self.top != self.right {
+            &[&self.top, &self.right]
+        } else {
+            &[&self.top]
+        };
+
+        for (index, value) in values.iter().enumerate() {
+            if index != 0 {
+                dest.push(' ');
+            }
+            value.serialize_to(dest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::{Origin, Parser};
+
+    fn serialize_stylesheet(source: &str) -> String {
+        Parser::new(source, Origin::Author)
+            .parse_stylesheet(0)
+            .to_css_string()
+    }
+
+    #[test]
+    fn escape_identifiers() {
+        let serialize = |identifier: &str| {
+            let mut dest = String::new();
+            serialize_identifier(identifier, &mut dest);
+            dest
+        };
+
+        assert_eq!(serialize("foo-bar"), "foo-bar");
+        assert_eq!(serialize("1st"), "\\31 st");
+        assert_eq!(serialize("-2"), "-\\32 ");
+        assert_eq!(serialize("a.b"), "a\\.b");
+        assert_eq!(serialize("-"), "\\-");
+    }
+
+    #[test]
+    fn escape_strings() {
+        let mut dest = String::new();
+        serialize_string("say \"hi\"\n", &mut dest);
+        assert_eq!(dest, "\"say \\\"hi\\\"\\a \"");
+    }
+
+    #[test]
+    fn serialize_floats() {
+        let serialize = |value: f32| {
+            let mut dest = String::new();
+            serialize_float(value, &mut dest);
+            dest
+        };
+
+        assert_eq!(serialize(1.), "1");
+        assert_eq!(serialize(0.1 + 0.2), "0.3");
+        assert_eq!(serialize(-0.00001), "0");
+    }
+
+    #[test]
+    fn serialize_rules() {
+        assert_eq!(
+            serialize_stylesheet("p , #d:hover{margin:1px 2px 1px 2px;color:red!important}"),
+            "p, #d:hover { margin: 1px 2px; color: rgb(255, 0, 0) !important; }"
+        );
+        assert_eq!(
+            serialize_stylesheet(
+                "@media screen and (min-width: 10em) { p { display: inline flow-root } }"
+            ),
+            "@media screen and (min-width: 10em) { p { display: inline-block; } }"
+        );
+    }
+
+    #[test]
+    fn round_trip_stylesheet() {
+        let source = r#"
+            p, .note, #main:focus { font-family: "Open Sans", serif; font-size: 120%; line-height: 1.5 }
+            div { transition: color 1s ease-in, margin-top 200ms steps(3, jump-none) 1s }
+            img { aspect-ratio: auto 16 / 9; background-image: none, url(bg.png); border: thin dotted #ff000080 }
+            @media not print, (prefers-color-scheme: dark) and (not (max-height: 100px)) {
+                li { list-style-type: "- "; padding-inline: 1px 2px; font-style: oblique 10deg }
+            }
+            h1 { justify-self: safe flex-end; width: fit-content(50%); vertical-align: -2px }
+        "#;
+
+        let serialized = serialize_stylesheet(source);
+        assert_eq!(serialized.lines().count(), 5, "all rules should be parsed");
+        assert_eq!(serialize_stylesheet(&serialized), serialized);
+    }
+}
@@ -0,0 +1,86 @@
+//! <https://drafts.csswg.org/css-fonts/#font-weight-prop>
+
+use crate::css::animation::Animate;
+
+/// The numeric weight of a font, between `1` and `1000`
+///
+/// <https://drafts.csswg.org/css-fonts/#font-weight-prop>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontWeight(f32);
+
+impl FontWeight {
+    pub const NORMAL: Self = Self(400.);
+    pub const BOLD: Self = Self(700.);
+
+    #[inline]
+    #[must_use]
+    pub const fn new(weight: f32) -> Self {
+        Self(weight)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn value(&self) -> f32 {
+        self.0
+    }
+
+    /// The weight of text that is `bolder` than text with this weight
+    ///
+    /// <https://drafts.csswg.org/css-fonts/#relative-weights>
+    #[must_use]
+    pub fn bolder(self) -> Self {
+        let weight = if self.0 < 350. {
+            400.
+        } else if self.0 < 550. {
+            700.
+        } else if self.0 < 900. {
+            900.
+        } else {
+            self.0
+        };
+
+        Self(weight)
+    }
+
+    /// The weight of text that is `lighter` than text with this weight
+    ///
+    /// <https://drafts.csswg.org/css-fonts/#relative-weights>
+    #[must_use]
+    pub fn lighter(self) -> Self {
+        let weight = if self.0 < 100. {
+            self.0
+        } else if self.0 < 550. {
+            100.
+        } else if self.0 < 750. {
+            400.
+        } else {
+            700.
+        };
+
+        Self(weight)
+    }
+}
+
+impl Animate for FontWeight {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        self.0.animate(&to.0, progress).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_weights() {
+        assert_eq!(FontWeight::new(100.).bolder(), FontWeight::NORMAL);
+        assert_eq!(FontWeight::NORMAL.bolder(), FontWeight::BOLD);
+        assert_eq!(FontWeight::BOLD.bolder(), FontWeight::new(900.));
+        assert_eq!(FontWeight::new(950.).bolder(), FontWeight::new(950.));
+
+        assert_eq!(FontWeight::new(50.).lighter(), FontWeight::new(50.));
+        assert_eq!(FontWeight::NORMAL.lighter(), FontWeight::new(100.));
+        assert_eq!(FontWeight::new(600.).lighter(), FontWeight::NORMAL);
+        assert_eq!(FontWeight::new(900.).lighter(), FontWeight::BOLD);
+    }
+}
@@ -9,7 +9,7 @@ use crate::css::{layout::Pixels, values::Number};
 const LINE_HEIGHT_NORMAL: f32 = 1.0;
 
 /// <https://drafts.csswg.org/css2/#propdef-line-height>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum LineHeight {
     Absolute(Pixels),
     Relative(Number),
@@ -1,16 +1,21 @@
 mod border;
+mod font_weight;
 mod line_height;
 
 use crate::css::{
     layout::Pixels,
-    values::{AutoOr, PercentageOr},
+    values::{AutoOr, Gap, Percentage, PercentageOr},
 };
 
 use super::specified;
 
 pub use border::Border;
+pub use font_weight::FontWeight;
 pub use line_height::LineHeight;
 
+/// <https://drafts.csswg.org/css-sizing-4/#aspect-ratio>
+pub type AspectRatio = specified::AspectRatio;
+
 /// </// <https://drafts.csswg.org/css-backgrounds/#background-color>>
 pub type BackgroundColor = specified::BackgroundColor;
 
@@ -26,9 +31,18 @@ pub type BorderWidth = Pixels;
 /// <https://drafts.csswg.org/css2/#propdef-clear>
 pub type Clear = specified::Clear;
 
+/// <https://drafts.csswg.org/css-multicol/#cc>
+pub type ColumnCount = specified::ColumnCount;
+
+/// <https://drafts.csswg.org/css-align/#column-row-gap>
+pub type ColumnGap = Gap<PercentageOr<Length>>;
+
 /// <https://drafts.csswg.org/css-ui/#cursor>
 pub type Cursor = specified::Cursor;
 
+/// <https://drafts.csswg.org/css-writing-modes-4/#direction>
+pub type Direction = specified::Direction;
+
 /// <https://drafts.csswg.org/css-display/#the-display-properties>
 pub type Display = specified::Display;
 
@@ -41,9 +55,15 @@ pub type FontFamily = specified::FontFamily;
 /// <https://drafts.csswg.org/css2/#font-size-props>
 pub type FontSize = Pixels;
 
+/// <https://drafts.csswg.org/css-fonts/#font-stretch-prop>
+pub type FontStretch = Percentage;
+
 /// <https://drafts.csswg.org/css-fonts/#font-style-prop>
 pub type FontStyle = specified::FontStyle;
 
+/// <https://drafts.csswg.org/css-fonts-4/#font-variation-settings-def>
+pub type FontVariationSettings = specified::FontVariationSettings;
+
 /// <https://drafts.csswg.org/css-position/#inset-properties>
 pub type Inset = AutoOr<PercentageOr<Length>>;
 
@@ -70,5 +90,23 @@ pub type Padding = PercentageOr<Length>;
 /// <https://drafts.csswg.org/css-position/#position-property>
 pub type Position = specified::Position;
 
+/// <https://drafts.csswg.org/css-writing-modes-4/#text-orientation>
+pub type TextOrientation = specified::TextOrientation;
+
+/// <https://drafts.csswg.org/css-transitions/#transition-delay-property>
+pub type TransitionDelay = specified::TransitionDelay;
+
+/// <https://drafts.csswg.org/css-transitions/#transition-duration-property>
+pub type TransitionDuration = specified::TransitionDuration;
+
+/// <https://drafts.csswg.org/css-transitions/#transition-property-property>
+pub type TransitionProperty = specified::TransitionProperty;
+
+/// <https://drafts.csswg.org/css-transitions/#transition-timing-function-property>
+pub type TransitionTimingFunction = specified::TransitionTimingFunction;
+
 /// <https://drafts.csswg.org/css2/#propdef-vertical-align>
 pub type VerticalAlign = specified::VerticalAlign;
+
+/// <https://drafts.csswg.org/css-writing-modes-4/#block-flow>
+pub type WritingMode = specified::WritingMode;
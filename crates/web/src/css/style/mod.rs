@@ -1,6 +1,7 @@
 use super::{
-    font_metrics::DEFAULT_FONT_SIZE,
+    font_metrics::DefaultFontSizes,
     layout::{Pixels, Size},
+    MediaEnvironment,
 };
 
 pub mod computed;
@@ -19,6 +20,16 @@ pub struct StyleContext {
 
     pub root_font_size: Pixels,
 
+    /// The size of `font-size: medium`
+    ///
+    /// This depends on whether the element uses a monospace font
+    pub medium_font_size: Pixels,
+
+    /// The font weight of the parent element
+    ///
+    /// Relative weights like `bolder` depend on this
+    pub inherited_font_weight: computed::FontWeight,
+
     /// The size of the viewport
     ///
     /// Viewport-relative units like `vw` depend on this
@@ -28,10 +39,29 @@ pub struct StyleContext {
 impl StyleContext {
     #[must_use]
     pub fn new(viewport: Size<Pixels>) -> Self {
+        let font_size = DefaultFontSizes::default().proportional;
+
         Self {
-            font_size: DEFAULT_FONT_SIZE,
-            root_font_size: DEFAULT_FONT_SIZE,
+            font_size,
+            root_font_size: font_size,
+            medium_font_size: font_size,
+            inherited_font_weight: computed::FontWeight::NORMAL,
             viewport,
         }
     }
+
+    /// The context that lengths in media queries are resolved in
+    ///
+    /// Relative lengths refer to the font size chosen by the user.
+    #[must_use]
+    pub(crate) fn for_media_environment(environment: &MediaEnvironment) -> Self {
+        let font_size = environment.font_sizes.proportional;
+
+        Self {
+            font_size,
+            root_font_size: font_size,
+            medium_font_size: font_size,
+            ..Self::new(environment.viewport)
+        }
+    }
 }
@@ -3,7 +3,7 @@ use crate::{
         self,
         style::{computed, StyleContext, ToComputedStyle},
         values::{AutoOr, PercentageOr},
-        CSSParse,
+        CSSParse, CSSSerialize,
     },
     static_interned, InternedString,
 };
@@ -125,3 +125,48 @@ impl ToComputedStyle for JustifySelf {
         *self
     }
 }
+
+impl CSSSerialize for OverflowPosition {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Safe => dest.push_str("safe"),
+            Self::Unsafe => dest.push_str("unsafe"),
+            Self::Unspecified => {},
+        }
+    }
+}
+
+impl CSSSerialize for JustifySelfPosition {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Center => "center",
+            Self::Start => "start",
+            Self::End => "end",
+            Self::SelfStart => "self-start",
+            Self::SelfEnd => "self-end",
+            Self::FlexStart => "flex-start",
+            Self::FlexEnd => "flex-end",
+            Self::Left => "left",
+            Self::Right => "right",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for JustifySelf {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Normal => dest.push_str("normal"),
+            Self::Stretch => dest.push_str("stretch"),
+            Self::FirstBaseline => dest.push_str("first baseline"),
+            Self::LastBaseline => dest.push_str("last baseline"),
+            Self::SelfPosition(overflow_position, position) => {
+                if *overflow_position != OverflowPosition::Unspecified {
+                    overflow_position.serialize_to(dest);
+                    dest.push(' ');
+                }
+                position.serialize_to(dest);
+            },
+        }
+    }
+}
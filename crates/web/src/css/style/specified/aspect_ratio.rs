@@ -0,0 +1,199 @@
+//! <https://drafts.csswg.org/css-sizing-4/#aspect-ratio>
+
+use crate::{
+    css::{
+        serialize::serialize_float,
+        style::{computed, StyleContext, ToComputedStyle},
+        syntax::Token,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// <https://drafts.csswg.org/css-sizing-4/#aspect-ratio>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AspectRatio {
+    /// Whether the natural aspect ratio of replaced elements takes precedence
+    /// over the specified ratio
+    auto: bool,
+
+    /// The specified ratio (`width / height`)
+    ///
+    /// This is `None` if no ratio was specified or if the ratio was
+    /// [degenerate](https://drafts.csswg.org/css-values-4/#degenerate-ratio).
+    ratio: Option<f32>,
+}
+
+impl AspectRatio {
+    pub const AUTO: Self = Self {
+        auto: true,
+        ratio: None,
+    };
+
+    /// Compute the [preferred aspect ratio](https://drafts.csswg.org/css-sizing-4/#preferred-aspect-ratio)
+    /// of a box with the given natural aspect ratio
+    #[must_use]
+    pub fn preferred_aspect_ratio(&self, natural_aspect_ratio: Option<f32>) -> Option<f32> {
+        if self.auto
+            && let Some(natural_aspect_ratio) = natural_aspect_ratio
+        {
+            return Some(natural_aspect_ratio);
+        }
+
+        self.ratio
+    }
+}
+
+impl Default for AspectRatio {
+    fn default() -> Self {
+        Self::AUTO
+    }
+}
+
+/// Parse a [`<ratio>`](https://drafts.csswg.org/css-values-4/#ratio-value)
+///
+/// Returns `None` if the ratio is degenerate.
+fn parse_ratio(parser: &mut Parser<'_>) -> Result<Option<f32>, ParseError> {
+    let parse_non_negative_number = |parser: &mut Parser<'_>| {
+        let number = f32::from(parser.expect_number()?);
+        if number.is_sign_negative() {
+            return Err(ParseError);
+        }
+        Ok(number)
+    };
+
+    let width = parse_non_negative_number(parser)?;
+
+    // The second value is optional and defaults to 1
+    let height = parser
+        .parse_optional_value(|parser| {
+            if parser.next_token_ignoring_whitespace() != Some(Token::Delim('/')) {
+                return Err(ParseError);
+            }
+            parse_non_negative_number(parser)
+        })
+        .unwrap_or(1.);
+
+    let ratio = width / height;
+    if ratio.is_normal() {
+        Ok(Some(ratio))
+    } else {
+        Ok(None)
+    }
+}
+
+impl<'a> CSSParse<'a> for AspectRatio {
+    // <https://drafts.csswg.org/css-sizing-4/#propdef-aspect-ratio>
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let parse_auto = |parser: &mut Parser<'a>| {
+            if parser.expect_identifier()? == static_interned!("auto") {
+                Ok(())
+            } else {
+                Err(ParseError)
+            }
+        };
+
+        // "auto" and the ratio may appear in any order
+        let mut auto = parser.parse_optional_value(parse_auto).is_some();
+        let ratio = parser.parse_optional_value(parse_ratio);
+        if !auto && ratio.is_some() {
+            auto = parser.parse_optional_value(parse_auto).is_some();
+        }
+
+        let Some(ratio) = ratio else {
+            if auto {
+                return Ok(Self::AUTO);
+            }
+            return Err(ParseError);
+        };
+
+        // A degenerate ratio behaves as "auto"
+        Ok(Self {
+            auto: auto || ratio.is_none(),
+            ratio,
+        })
+    }
+}
+
+impl ToComputedStyle for AspectRatio {
+    type Computed = computed::AspectRatio;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        *self
+    }
+}
+
+impl CSSSerialize for AspectRatio {
+    fn serialize_to(&self, dest: &mut String) {
+        if self.auto {
+            dest.push_str("auto");
+        }
+
+        if let Some(ratio) = self.ratio {
+            if self.auto {
+                dest.push(' ');
+            }
+            serialize_float(ratio, dest);
+            dest.push_str(" / 1");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_aspect_ratio() {
+        assert_eq!(AspectRatio::parse_from_str("auto"), Ok(AspectRatio::AUTO));
+        assert_eq!(
+            AspectRatio::parse_from_str("16 / 9"),
+            Ok(AspectRatio {
+                auto: false,
+                ratio: Some(16. / 9.)
+            })
+        );
+        assert_eq!(
+            AspectRatio::parse_from_str("2"),
+            Ok(AspectRatio {
+                auto: false,
+                ratio: Some(2.)
+            })
+        );
+        assert_eq!(
+            AspectRatio::parse_from_str("1/2 auto"),
+            Ok(AspectRatio {
+                auto: true,
+                ratio: Some(0.5)
+            })
+        );
+        assert_eq!(
+            AspectRatio::parse_from_str("auto 1/2"),
+            Ok(AspectRatio {
+                auto: true,
+                ratio: Some(0.5)
+            })
+        );
+
+        // Degenerate ratios behave as "auto"
+        assert_eq!(AspectRatio::parse_from_str("0 / 1"), Ok(AspectRatio::AUTO));
+
+        assert!(AspectRatio::parse_from_str("-1 / 2").is_err());
+        assert!(AspectRatio::parse_from_str("auto auto").is_err());
+        assert!(AspectRatio::parse_from_str("").is_err());
+    }
+
+    #[test]
+    fn preferred_aspect_ratio() {
+        let ratio = AspectRatio::parse_from_str("2").unwrap();
+        assert_eq!(ratio.preferred_aspect_ratio(Some(1.)), Some(2.));
+
+        let ratio = AspectRatio::parse_from_str("auto 2").unwrap();
+        assert_eq!(ratio.preferred_aspect_ratio(Some(1.)), Some(1.));
+        assert_eq!(ratio.preferred_aspect_ratio(None), Some(2.));
+
+        assert_eq!(AspectRatio::AUTO.preferred_aspect_ratio(None), None);
+    }
+}
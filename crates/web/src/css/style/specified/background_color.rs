@@ -2,16 +2,17 @@
 
 use crate::{
     css::{
+        animation::Animate,
         style::{StyleContext, ToComputedStyle},
         syntax::Token,
         values::Color,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
 
 /// <https://drafts.csswg.org/css-backgrounds/#background-color>
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum BackgroundColor {
     Color(Color),
     #[default]
@@ -40,8 +41,30 @@ impl ToComputedStyle for BackgroundColor {
     }
 }
 
+impl Animate for BackgroundColor {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let as_color = |background_color: &Self| match *background_color {
+            Self::Color(color) => color,
+            Self::Transparent => Color::rgba(0, 0, 0, 0),
+        };
+
+        as_color(self)
+            .animate(&as_color(to), progress)
+            .map(Self::Color)
+    }
+}
+
 impl From<Color> for BackgroundColor {
     fn from(value: Color) -> Self {
         Self::Color(value)
     }
 }
+
+impl CSSSerialize for BackgroundColor {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Color(color) => color.serialize_to(dest),
+            Self::Transparent => dest.push_str("transparent"),
+        }
+    }
+}
@@ -5,13 +5,13 @@ use crate::{
         style::{StyleContext, ToComputedStyle},
         syntax::Token,
         values::Url,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
 
 /// <https://drafts.csswg.org/css-backgrounds/#background-image>
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct BackgroundImage {
     // TODO: The spec explicitly treats the "none" layers as layers (that are not rendered).
     //       Is this necessary? Do we need to keep these around?
@@ -72,6 +72,21 @@ impl ToComputedStyle for BackgroundImage {
     }
 }
 
+impl CSSSerialize for BackgroundImage {
+    fn serialize_to(&self, dest: &mut String) {
+        for (index, layer) in self.layers.iter().enumerate() {
+            if index != 0 {
+                dest.push_str(", ");
+            }
+
+            match layer {
+                Some(url) => url.serialize_to(dest),
+                None => dest.push_str("none"),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,7 +4,7 @@ use crate::{
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
         values::Color,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned, InternedString,
 };
@@ -91,7 +91,7 @@ impl ToComputedStyle for LineStyle {
 }
 
 /// <https://drafts.csswg.org/css-backgrounds/#typedef-line-width>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct LineWidth(Length);
 
 impl Default for LineWidth {
@@ -127,7 +127,7 @@ impl<'a> CSSParse<'a> for LineWidth {
     fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
         match parser.peek_token_ignoring_whitespace(0) {
             Some(Token::Ident(name)) => {
-                let width = Self::from_name(*name)?;
+                let width = Self::from_name(name.clone())?;
                 let _ = parser.next_token_ignoring_whitespace();
 
                 Ok(width)
@@ -174,7 +174,7 @@ impl<'a> CSSParse<'a> for Border {
                     | static_interned!("medium")
                     | static_interned!("thick")),
                 )) => {
-                    border_width = Some(LineWidth::from_name(*name)?);
+                    border_width = Some(LineWidth::from_name(name.clone())?);
                     _ = parser.next_token_ignoring_whitespace();
                 },
                 Some(Token::Ident(
@@ -189,13 +189,13 @@ impl<'a> CSSParse<'a> for Border {
                     | static_interned!("inset")
                     | static_interned!("outset")),
                 )) => {
-                    let style = LineStyle::from_name(*name)?;
+                    let style = LineStyle::from_name(name.clone())?;
                     _ = parser.next_token_ignoring_whitespace();
 
                     border_style = Some(style);
                 },
                 Some(Token::Dimension(value, unit_name)) => {
-                    let length = Length::from_dimension(*value, *unit_name)?;
+                    let length = Length::from_dimension(*value, unit_name.clone())?;
                     _ = parser.next_token_ignoring_whitespace();
 
                     border_width = Some(length.into());
@@ -206,7 +206,7 @@ impl<'a> CSSParse<'a> for Border {
                     border_width = Some(Length::ZERO.into());
                 },
                 Some(Token::Ident(other)) => {
-                    border_color = Some(Color::from_name(*other)?);
+                    border_color = Some(Color::from_name(other.clone())?);
                     _ = parser.next_token_ignoring_whitespace();
                 },
                 _ => {
@@ -248,3 +248,37 @@ impl ToComputedStyle for Border {
         }
     }
 }
+
+impl CSSSerialize for LineStyle {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::None => "none",
+            Self::Hidden => "hidden",
+            Self::Dotted => "dotted",
+            Self::Dashed => "dashed",
+            Self::Solid => "solid",
+            Self::Double => "double",
+            Self::Groove => "groove",
+            Self::Ridge => "ridge",
+            Self::Inset => "inset",
+            Self::Outset => "outset",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for LineWidth {
+    fn serialize_to(&self, dest: &mut String) {
+        self.0.serialize_to(dest);
+    }
+}
+
+impl CSSSerialize for Border {
+    fn serialize_to(&self, dest: &mut String) {
+        self.width.serialize_to(dest);
+        dest.push(' ');
+        self.style.serialize_to(dest);
+        dest.push(' ');
+        self.color.serialize_to(dest);
+    }
+}
@@ -0,0 +1,133 @@
+//! <https://drafts.csswg.org/css-multicol/#the-number-and-width-of-columns>
+
+use crate::{
+    css::{
+        style::{computed, StyleContext, ToComputedStyle},
+        syntax::Token,
+        values::{AutoOr, Number},
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+use super::Length;
+
+/// The number of columns in a multi-column container, which is always at least one
+///
+/// <https://drafts.csswg.org/css-multicol/#cc>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnCount(u32);
+
+/// The value of the `columns` shorthand
+///
+/// <https://drafts.csswg.org/css-multicol/#columns>
+#[derive(Clone, Copy, Debug)]
+pub struct Columns {
+    pub width: AutoOr<Length>,
+    pub count: AutoOr<ColumnCount>,
+}
+
+impl ColumnCount {
+    #[inline]
+    #[must_use]
+    pub const fn get(&self) -> u32 {
+        self.0
+    }
+}
+
+impl<'a> CSSParse<'a> for ColumnCount {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        match parser.next_token_ignoring_whitespace() {
+            Some(Token::Number(Number::Integer(count))) if count >= 1 => Ok(Self(count as u32)),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+impl ToComputedStyle for ColumnCount {
+    type Computed = computed::ColumnCount;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        *self
+    }
+}
+
+impl CSSSerialize for ColumnCount {
+    fn serialize_to(&self, dest: &mut String) {
+        dest.push_str(&self.0.to_string());
+    }
+}
+
+impl<'a> CSSParse<'a> for Columns {
+    /// `<'column-width'> || <'column-count'>`
+    ///
+    /// Each of the two values may be `auto`, omitted values are `auto` as well.
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let mut width = None;
+        let mut count = None;
+        let mut has_auto = false;
+
+        for _ in 0..2 {
+            match parser.peek_token_ignoring_whitespace(0) {
+                Some(Token::Ident(static_interned!("auto"))) => {
+                    _ = parser.next_token_ignoring_whitespace();
+                    has_auto = true;
+                },
+                Some(Token::Number(Number::Integer(_))) if count.is_none() => {
+                    count = Some(parser.parse()?);
+                },
+                Some(_) if width.is_none() => {
+                    let Some(length) = parser.parse_optional::<Length>() else {
+                        break;
+                    };
+                    width = Some(length);
+                },
+                _ => break,
+            }
+        }
+
+        if width.is_none() && count.is_none() && !has_auto {
+            return Err(ParseError);
+        }
+
+        Ok(Self {
+            width: width.map_or(AutoOr::Auto, AutoOr::NotAuto),
+            count: count.map_or(AutoOr::Auto, AutoOr::NotAuto),
+        })
+    }
+}
+
+impl CSSSerialize for Columns {
+    fn serialize_to(&self, dest: &mut String) {
+        self.width.serialize_to(dest);
+        dest.push(' ');
+        self.count.serialize_to(dest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::layout::Pixels;
+
+    #[test]
+    fn parse_columns() {
+        let columns = Columns::parse_from_str("3 10px").unwrap();
+        assert_eq!(columns.count, AutoOr::NotAuto(ColumnCount(3)));
+        assert_eq!(columns.width, AutoOr::NotAuto(Length::pixels(Pixels(10.))));
+
+        let columns = Columns::parse_from_str("auto 2").unwrap();
+        assert_eq!(columns.count, AutoOr::NotAuto(ColumnCount(2)));
+        assert_eq!(columns.width, AutoOr::Auto);
+
+        let columns = Columns::parse_from_str("12em").unwrap();
+        assert_eq!(columns.count, AutoOr::Auto);
+        assert!(!columns.width.is_auto());
+
+        assert!(ColumnCount::parse_from_str("0").is_err());
+        assert!(ColumnCount::parse_from_str("1.5").is_err());
+        assert!(Columns::parse_from_str("2 3").is_err());
+    }
+}
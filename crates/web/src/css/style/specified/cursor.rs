@@ -3,7 +3,7 @@
 use crate::{
     css::{
         style::{computed, StyleContext, ToComputedStyle},
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
@@ -171,3 +171,46 @@ impl ToComputedStyle for Cursor {
         *self
     }
 }
+
+impl CSSSerialize for Cursor {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Default => "default",
+            Self::None => "none",
+            Self::ContextMenu => "context-menu",
+            Self::Help => "help",
+            Self::Pointer => "pointer",
+            Self::Progress => "progress",
+            Self::Wait => "wait",
+            Self::Cell => "cell",
+            Self::Crosshair => "crosshair",
+            Self::Text => "text",
+            Self::VerticalText => "vertical-text",
+            Self::Alias => "alias",
+            Self::Copy => "copy",
+            Self::Move => "move",
+            Self::NoDrop => "no-drop",
+            Self::NotAllowed => "not-allowed",
+            Self::Grab => "grab",
+            Self::Grabbing => "grabbing",
+            Self::ResizeEast => "e-resize",
+            Self::ResizeNorth => "n-resize",
+            Self::ResizeNorthEast => "ne-resize",
+            Self::ResizeNorthWest => "nw-resize",
+            Self::ResizeSouth => "s-resize",
+            Self::ResizeSouthEast => "se-resize",
+            Self::ResizeSouthWest => "sw-resize",
+            Self::ResizeWest => "w-resize",
+            Self::ResizeEastWest => "ew-resize",
+            Self::ResizeNorthSouth => "ns-resize",
+            Self::ResizeNorthEastSouthWest => "nesw-resize",
+            Self::ResizeNorthWestSouthEast => "nwse-resize",
+            Self::ResizeColumn => "col-resize",
+            Self::ResizeRow => "row-resize",
+            Self::AllScroll => "all-scroll",
+            Self::ZoomIn => "zoom-in",
+            Self::ZoomOut => "zoom-out",
+        };
+        dest.push_str(keyword);
+    }
+}
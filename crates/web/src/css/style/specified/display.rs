@@ -2,7 +2,7 @@ use crate::{
     css::{
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned, InternedString,
 };
@@ -28,6 +28,9 @@ pub enum DisplayInside {
     Flex,
     Grid,
     Ruby,
+
+    /// <https://w3c.github.io/mathml-core/#new-display-math-value>
+    Math,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -94,6 +97,7 @@ pub enum Short {
     Grid,
     InlineGrid,
     Ruby,
+    Math,
     Table,
     InlineTable,
 }
@@ -116,6 +120,7 @@ impl TryFrom<InternedString> for Short {
             static_interned!("grid") => Ok(Self::Grid),
             static_interned!("inline-grid") => Ok(Self::InlineGrid),
             static_interned!("ruby") => Ok(Self::Ruby),
+            static_interned!("math") => Ok(Self::Math),
             static_interned!("table") => Ok(Self::Table),
             static_interned!("inline-table") => Ok(Self::InlineTable),
             _ => Err(ParseError),
@@ -198,6 +203,11 @@ impl From<Short> for Display {
                 outside: DisplayOutside::Inline,
                 inside: DisplayInside::Ruby,
             }),
+            // Like ruby, math is inline if no outer display type is given
+            Short::Math => Self::InsideOutside(DisplayInsideOutside {
+                outside: DisplayOutside::Inline,
+                inside: DisplayInside::Math,
+            }),
             Short::Table => Self::InsideOutside(DisplayInsideOutside {
                 outside: DisplayOutside::Block,
                 inside: DisplayInside::Table,
@@ -217,7 +227,7 @@ impl<'a> CSSParse<'a> for Display {
         for _ in 0..3 {
             match parser.peek_token_ignoring_whitespace(0) {
                 Some(Token::Ident(ident)) => {
-                    let ident = *ident;
+                    let ident = ident.clone();
                     let _ = parser.next_token_ignoring_whitespace();
                     idents.push(ident)
                 },
@@ -227,7 +237,11 @@ impl<'a> CSSParse<'a> for Display {
         }
 
         if idents.len() == 1 {
-            let ident = idents[0];
+            let ident = idents[0].clone();
+            if let Some(internal) = DisplayInternal::from_ident(ident.clone()) {
+                return Ok(Self::Internal(internal));
+            }
+
             let short = Short::try_from(ident)?;
             Ok(Self::from(short))
         } else {
@@ -240,7 +254,7 @@ impl<'a> CSSParse<'a> for Display {
             for ident in idents {
                 if ident == static_interned!("list-item") {
                     has_list_item_flag = true;
-                } else if let Some(display_outside) = DisplayOutside::from_ident(ident) {
+                } else if let Some(display_outside) = DisplayOutside::from_ident(ident.clone()) {
                     outside = display_outside;
                 } else if let Some(display_inside) = DisplayInside::from_ident(ident) {
                     inside = display_inside;
@@ -305,6 +319,24 @@ impl DisplayInside {
             static_interned!("flex") => Some(Self::Flex),
             static_interned!("grid") => Some(Self::Grid),
             static_interned!("ruby") => Some(Self::Ruby),
+            static_interned!("math") => Some(Self::Math),
+            _ => None,
+        }
+    }
+}
+
+impl DisplayInternal {
+    /// Parse one of the layout-internal keywords
+    ///
+    /// FIXME: The table keywords are not accepted yet, because there is no table layout
+    ///        and table parts would not generate any boxes otherwise.
+    #[inline]
+    fn from_ident(ident: InternedString) -> Option<Self> {
+        match ident {
+            static_interned!("ruby-base") => Some(Self::RubyBase),
+            static_interned!("ruby-text") => Some(Self::RubyText),
+            static_interned!("ruby-base-container") => Some(Self::RubyBaseContainer),
+            static_interned!("ruby-text-container") => Some(Self::RubyTextContainer),
             _ => None,
         }
     }
@@ -319,3 +351,96 @@ impl ToComputedStyle for Display {
         *self
     }
 }
+
+impl CSSSerialize for DisplayOutside {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Block => "block",
+            Self::Inline => "inline",
+            Self::RunIn => "run-in",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for DisplayInside {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Flow { .. } => "flow",
+            Self::FlowRoot { .. } => "flow-root",
+            Self::Table => "table",
+            Self::Flex => "flex",
+            Self::Grid => "grid",
+            Self::Ruby => "ruby",
+            Self::Math => "math",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for DisplayInternal {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::TableRowGroup => "table-row-group",
+            Self::TableHeaderGroup => "table-header-group",
+            Self::TableFooterGroup => "table-footer-group",
+            Self::TableRow => "table-row",
+            Self::TableCell => "table-cell",
+            Self::TableColumnGroup => "table-column-group",
+            Self::TableColumn => "table-column",
+            Self::TableCaption => "table-caption",
+            Self::RubyBase => "ruby-base",
+            Self::RubyText => "ruby-text",
+            Self::RubyBaseContainer => "ruby-base-container",
+            Self::RubyTextContainer => "ruby-text-container",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for Display {
+    /// Uses the short form of the value, if there is one
+    ///
+    /// <https://drafts.csswg.org/css-display/#display-value-summary>
+    fn serialize_to(&self, dest: &mut String) {
+        let short_forms = [
+            (Short::None, "none"),
+            (Short::Contents, "contents"),
+            (Short::Block, "block"),
+            (Short::FlowRoot, "flow-root"),
+            (Short::Inline, "inline"),
+            (Short::InlineBlock, "inline-block"),
+            (Short::RunIn, "run-in"),
+            (Short::ListItem, "list-item"),
+            (Short::Flex, "flex"),
+            (Short::InlineFlex, "inline-flex"),
+            (Short::Grid, "grid"),
+            (Short::InlineGrid, "inline-grid"),
+            (Short::Ruby, "ruby"),
+            (Short::Math, "math"),
+            (Short::Table, "table"),
+            (Short::InlineTable, "inline-table"),
+        ];
+        if let Some((_, keyword)) = short_forms
+            .iter()
+            .find(|(short, _)| Self::from(*short) == *self)
+        {
+            dest.push_str(keyword);
+            return;
+        }
+
+        match self {
+            Self::InsideOutside(DisplayInsideOutside { outside, inside }) => {
+                outside.serialize_to(dest);
+                dest.push(' ');
+                inside.serialize_to(dest);
+
+                if inside.has_list_item_flag() {
+                    dest.push_str(" list-item");
+                }
+            },
+            Self::Internal(internal) => internal.serialize_to(dest),
+            Self::Box(_) => unreachable!("display box values have a short form"),
+        }
+    }
+}
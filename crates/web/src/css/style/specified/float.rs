@@ -1,13 +1,13 @@
 use crate::{
     css::{
         style::{computed, StyleContext, ToComputedStyle},
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
 
 /// <https://drafts.csswg.org/css2/#propdef-float>
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Float {
     side: Option<FloatSide>,
 }
@@ -48,7 +48,7 @@ impl ToComputedStyle for Float {
 }
 
 /// <https://drafts.csswg.org/css2/#propdef-clear>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Clear {
     None,
     Left,
@@ -78,3 +78,26 @@ impl ToComputedStyle for Clear {
         *self
     }
 }
+
+impl CSSSerialize for Float {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self.side {
+            Some(FloatSide::Left) => "left",
+            Some(FloatSide::Right) => "right",
+            None => "none",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for Clear {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::None => "none",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Both => "both",
+        };
+        dest.push_str(keyword);
+    }
+}
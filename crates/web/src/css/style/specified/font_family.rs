@@ -3,20 +3,21 @@ use std::fmt;
 use crate::{
     css::{
         self,
+        serialize::{serialize_comma_separated, serialize_string},
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
-        CSSParse,
+        CSSParse, CSSSerialize,
     },
     static_interned, InternedString,
 };
 
 /// <https://drafts.csswg.org/css-fonts/#font-family-prop>
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct FontFamily {
     fonts: Vec<FontName>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum FontName {
     /// <https://drafts.csswg.org/css-fonts/#family-name-syntax>
     Family(InternedString),
@@ -28,10 +29,16 @@ impl FontFamily {
     pub fn fonts(&self) -> &[FontName] {
         &self.fonts
     }
+
+    /// Whether text in this family is rendered in the generic monospace font
+    #[must_use]
+    pub fn is_monospace(&self) -> bool {
+        self.fonts.first() == Some(&FontName::Generic(GenericFontFamily::Monospace))
+    }
 }
 
 /// <https://drafts.csswg.org/css-fonts/#generic-family-value>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum GenericFontFamily {
     /// <https://drafts.csswg.org/css-fonts/#serif-def>
     Serif,
@@ -86,7 +93,14 @@ impl<'a> CSSParse<'a> for FontFamily {
     fn parse(parser: &mut css::Parser<'a>) -> Result<Self, css::ParseError> {
         let mut desired_fonts = vec![];
 
-        while let Some(desired_font) = parser.parse_optional_value(CSSParse::parse) {
+        while let Some(desired_font) = parser.parse_optional_value(|parser| {
+            if !desired_fonts.is_empty()
+                && parser.peek_token_ignoring_whitespace(0) == Some(&Token::Comma)
+            {
+                let _ = parser.next_token_ignoring_whitespace();
+            }
+            FontName::parse(parser)
+        }) {
             desired_fonts.push(desired_font);
         }
 
@@ -103,7 +117,7 @@ impl<'a> CSSParse<'a> for FontFamily {
 impl<'a> CSSParse<'a> for FontName {
     fn parse(parser: &mut css::Parser<'a>) -> Result<Self, css::ParseError> {
         if let Some(Token::String(name)) = parser.peek_token_ignoring_whitespace(0) {
-            let name = *name;
+            let name = name.clone();
             let _ = parser.next_token_ignoring_whitespace();
             Ok(Self::Family(name))
         } else {
@@ -115,7 +129,7 @@ impl<'a> CSSParse<'a> for FontName {
 
 impl<'a> CSSParse<'a> for GenericFontFamily {
     fn parse(parser: &mut css::Parser<'a>) -> Result<Self, css::ParseError> {
-        let parsed_value = match parser.next_token() {
+        let parsed_value = match parser.next_token_ignoring_whitespace() {
             Some(Token::Ident(static_interned!("serif"))) => Self::Serif,
             Some(Token::Ident(static_interned!("sans-serif"))) => Self::SansSerif,
             Some(Token::Ident(static_interned!("cursive"))) => Self::Cursive,
@@ -163,3 +177,18 @@ impl ToComputedStyle for FontFamily {
         self.clone()
     }
 }
+
+impl CSSSerialize for FontFamily {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_comma_separated(&self.fonts, dest);
+    }
+}
+
+impl CSSSerialize for FontName {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Family(name) => serialize_string(name.as_str(), dest),
+            Self::Generic(generic_family) => dest.push_str(&generic_family.to_string()),
+        }
+    }
+}
@@ -4,14 +4,11 @@ use crate::{
         style::{computed, specified::Length, StyleContext, ToComputedStyle},
         syntax::Token,
         values::PercentageOr,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
 
-/// The default font size.
-pub const FONT_MEDIUM_PX: Pixels = Pixels(16.0);
-
 /// Ratio applied for font-size: larger/smaller
 ///
 /// Take from https://github.com/servo/servo/blob/fd31da9102497cfaf5265bbab17df4424a8a1078/components/style/values/specified/font.rs#L779
@@ -71,12 +68,12 @@ impl AbsoluteSize {
     }
 
     #[must_use]
-    fn to_pixels(self) -> Pixels {
+    fn to_pixels(self, medium_font_size: Pixels) -> Pixels {
         /// Mapping from html size to scale factor, copied from
         /// https://github.com/servo/servo/blob/fd31da9102497cfaf5265bbab17df4424a8a1078/components/style/values/specified/font.rs#L869
         const FONT_SIZE_FACTORS: [f32; 8] = [0.6, 0.75, 0.89, 1.00, 1.20, 1.50, 2.00, 3.00];
 
-        FONT_MEDIUM_PX * FONT_SIZE_FACTORS[self.html_size() as usize]
+        medium_font_size * FONT_SIZE_FACTORS[self.html_size() as usize]
     }
 }
 
@@ -121,7 +118,7 @@ impl ToComputedStyle for FontSize {
 
     fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
         match self {
-            Self::Absolute(absolute_size) => absolute_size.to_pixels(),
+            Self::Absolute(absolute_size) => absolute_size.to_pixels(context.medium_font_size),
             Self::Relative(relative_size) => relative_size.to_pixels(context.font_size),
             Self::LengthPercentage(percentage_or_length) => {
                 let length =
@@ -132,11 +129,33 @@ impl ToComputedStyle for FontSize {
         }
     }
 }
+impl CSSSerialize for FontSize {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Absolute(AbsoluteSize::XXSmall) => "xx-small",
+            Self::Absolute(AbsoluteSize::XSmall) => "x-small",
+            Self::Absolute(AbsoluteSize::Small) => "small",
+            Self::Absolute(AbsoluteSize::Medium) => "medium",
+            Self::Absolute(AbsoluteSize::Large) => "large",
+            Self::Absolute(AbsoluteSize::XLarge) => "x-large",
+            Self::Absolute(AbsoluteSize::XXLarge) => "xx-large",
+            Self::Relative(RelativeSize::Smaller) => "smaller",
+            Self::Relative(RelativeSize::Larger) => "larger",
+            Self::LengthPercentage(length_percentage) => {
+                length_percentage.serialize_to(dest);
+                return;
+            },
+        };
+        dest.push_str(keyword);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::assert_matches::assert_matches;
 
     use super::*;
+    use crate::css::layout::Size;
 
     #[test]
     fn parse() {
@@ -195,4 +214,22 @@ mod tests {
             Ok(FontSize::LengthPercentage(_))
         );
     }
+
+    #[test]
+    fn absolute_sizes_depend_on_medium_size() {
+        let mut context = StyleContext::new(Size {
+            width: Pixels(800.),
+            height: Pixels(600.),
+        });
+        context.medium_font_size = Pixels(13.);
+
+        assert_eq!(
+            FontSize::Absolute(AbsoluteSize::Medium).to_computed_style(&context),
+            Pixels(13.)
+        );
+        assert_eq!(
+            FontSize::Absolute(AbsoluteSize::XXLarge).to_computed_style(&context),
+            Pixels(26.)
+        );
+    }
 }
@@ -0,0 +1,107 @@
+use crate::{
+    css::{
+        style::{computed, StyleContext, ToComputedStyle},
+        syntax::Token,
+        values::Percentage,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// <https://drafts.csswg.org/css-fonts/#font-stretch-prop>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FontStretch {
+    UltraCondensed,
+    ExtraCondensed,
+    Condensed,
+    SemiCondensed,
+    Normal,
+    SemiExpanded,
+    Expanded,
+    ExtraExpanded,
+    UltraExpanded,
+    Percentage(Percentage),
+}
+
+impl FontStretch {
+    /// The width of the font, relative to its normal width
+    ///
+    /// <https://drafts.csswg.org/css-fonts/#font-stretch-prop>
+    #[must_use]
+    pub const fn as_percentage(&self) -> Percentage {
+        let fraction = match self {
+            Self::UltraCondensed => 0.5,
+            Self::ExtraCondensed => 0.625,
+            Self::Condensed => 0.75,
+            Self::SemiCondensed => 0.875,
+            Self::Normal => 1.,
+            Self::SemiExpanded => 1.125,
+            Self::Expanded => 1.25,
+            Self::ExtraExpanded => 1.5,
+            Self::UltraExpanded => 2.,
+            Self::Percentage(percentage) => return *percentage,
+        };
+
+        Percentage::from_fraction(fraction)
+    }
+}
+
+impl<'a> CSSParse<'a> for FontStretch {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let font_stretch = match parser.next_token_ignoring_whitespace() {
+            Some(Token::Ident(static_interned!("ultra-condensed"))) => Self::UltraCondensed,
+            Some(Token::Ident(static_interned!("extra-condensed"))) => Self::ExtraCondensed,
+            Some(Token::Ident(static_interned!("condensed"))) => Self::Condensed,
+            Some(Token::Ident(static_interned!("semi-condensed"))) => Self::SemiCondensed,
+            Some(Token::Ident(static_interned!("normal"))) => Self::Normal,
+            Some(Token::Ident(static_interned!("semi-expanded"))) => Self::SemiExpanded,
+            Some(Token::Ident(static_interned!("expanded"))) => Self::Expanded,
+            Some(Token::Ident(static_interned!("extra-expanded"))) => Self::ExtraExpanded,
+            Some(Token::Ident(static_interned!("ultra-expanded"))) => Self::UltraExpanded,
+            Some(Token::Percentage(number)) => {
+                let percentage = Percentage::from_css_percentage(number);
+
+                // Negative widths are invalid
+                if percentage.as_fraction() < 0. {
+                    return Err(ParseError);
+                }
+
+                Self::Percentage(percentage)
+            },
+            _ => return Err(ParseError),
+        };
+
+        Ok(font_stretch)
+    }
+}
+
+impl ToComputedStyle for FontStretch {
+    type Computed = computed::FontStretch;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        self.as_percentage()
+    }
+}
+
+impl CSSSerialize for FontStretch {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::UltraCondensed => "ultra-condensed",
+            Self::ExtraCondensed => "extra-condensed",
+            Self::Condensed => "condensed",
+            Self::SemiCondensed => "semi-condensed",
+            Self::Normal => "normal",
+            Self::SemiExpanded => "semi-expanded",
+            Self::Expanded => "expanded",
+            Self::ExtraExpanded => "extra-expanded",
+            Self::UltraExpanded => "ultra-expanded",
+            Self::Percentage(percentage) => {
+                percentage.serialize_to(dest);
+                return;
+            },
+        };
+        dest.push_str(keyword);
+    }
+}
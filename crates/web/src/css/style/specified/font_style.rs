@@ -3,7 +3,7 @@ use crate::{
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
         values::Angle,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
@@ -27,7 +27,7 @@ impl<'a> CSSParse<'a> for FontStyle {
                 let angle = if let Some(Token::Dimension(value, dimension)) =
                     parser.peek_token_ignoring_whitespace(0)
                 {
-                    if let Ok(angle) = Angle::from_dimension(*value, *dimension) {
+                    if let Ok(angle) = Angle::from_dimension(*value, dimension.clone()) {
                         _ = parser.next_token_ignoring_whitespace();
                         angle
                     } else {
@@ -60,6 +60,23 @@ impl ToComputedStyle for FontStyle {
     }
 }
 
+impl CSSSerialize for FontStyle {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Normal => dest.push_str("normal"),
+            Self::Italic => dest.push_str("italic"),
+            Self::Oblique(angle) => {
+                dest.push_str("oblique");
+
+                if *angle != DEFAULT_OBLIQUE_ANGLE {
+                    dest.push(' ');
+                    angle.serialize_to(dest);
+                }
+            },
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
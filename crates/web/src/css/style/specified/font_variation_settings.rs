@@ -0,0 +1,138 @@
+use crate::{
+    css::{
+        serialize::{serialize_comma_separated, serialize_float, serialize_string},
+        style::{computed, StyleContext, ToComputedStyle},
+        syntax::Token,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// <https://drafts.csswg.org/css-fonts-4/#font-variation-settings-def>
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontVariationSettings {
+    /// Empty for `normal`
+    settings: Vec<VariationSetting>,
+}
+
+/// The value of a single axis of a variable font
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VariationSetting {
+    /// The four characters identifying the axis, like `wght`
+    pub tag: [u8; 4],
+    pub value: f32,
+}
+
+impl FontVariationSettings {
+    #[inline]
+    #[must_use]
+    pub fn settings(&self) -> &[VariationSetting] {
+        &self.settings
+    }
+}
+
+impl<'a> CSSParse<'a> for VariationSetting {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let Some(Token::String(tag)) = parser.next_token_ignoring_whitespace() else {
+            return Err(ParseError);
+        };
+
+        // Tags consist of exactly four printable ASCII characters
+        let tag: [u8; 4] = tag.as_str().as_bytes().try_into().map_err(|_| ParseError)?;
+        if !tag.iter().all(|byte| (0x20..=0x7E).contains(byte)) {
+            return Err(ParseError);
+        }
+
+        let value = parser.expect_number()?.into();
+
+        Ok(Self { tag, value })
+    }
+}
+
+impl<'a> CSSParse<'a> for FontVariationSettings {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        if matches!(
+            parser.peek_token_ignoring_whitespace(0),
+            Some(Token::Ident(static_interned!("normal")))
+        ) {
+            let _ = parser.next_token_ignoring_whitespace();
+            return Ok(Self::default());
+        }
+
+        let settings = parser.parse_comma_seperated_list(VariationSetting::parse);
+        if settings.is_empty() {
+            return Err(ParseError);
+        }
+
+        Ok(Self { settings })
+    }
+}
+
+impl ToComputedStyle for FontVariationSettings {
+    type Computed = computed::FontVariationSettings;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        self.clone()
+    }
+}
+
+impl CSSSerialize for VariationSetting {
+    fn serialize_to(&self, dest: &mut String) {
+        // Tags only contain ASCII characters
+        serialize_string(&String::from_utf8_lossy(&self.tag), dest);
+        dest.push(' ');
+        serialize_float(self.value, dest);
+    }
+}
+
+impl CSSSerialize for FontVariationSettings {
+    fn serialize_to(&self, dest: &mut String) {
+        if self.settings.is_empty() {
+            dest.push_str("normal");
+        } else {
+            serialize_comma_separated(&self.settings, dest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_font_variation_settings() {
+        assert_eq!(
+            FontVariationSettings::parse_from_str("normal"),
+            Ok(FontVariationSettings::default())
+        );
+
+        assert_eq!(
+            FontVariationSettings::parse_from_str("\"wght\" 650, \"wdth\" 75.5"),
+            Ok(FontVariationSettings {
+                settings: vec![
+                    VariationSetting {
+                        tag: *b"wght",
+                        value: 650.,
+                    },
+                    VariationSetting {
+                        tag: *b"wdth",
+                        value: 75.5,
+                    },
+                ]
+            })
+        );
+
+        // Tags must be exactly four characters long
+        assert!(FontVariationSettings::parse_from_str("\"wgh\" 650").is_err());
+        assert!(FontVariationSettings::parse_from_str("\"weight\" 650").is_err());
+        assert!(FontVariationSettings::parse_from_str("\"wght\"").is_err());
+    }
+
+    #[test]
+    fn serialize_font_variation_settings() {
+        let settings = FontVariationSettings::parse_from_str("\"wght\" 650, \"ital\" 1").unwrap();
+        assert_eq!(settings.to_css_string(), "\"wght\" 650, \"ital\" 1");
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    css::{
+        serialize::serialize_float,
+        style::{computed, StyleContext, ToComputedStyle},
+        syntax::Token,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// <https://drafts.csswg.org/css-fonts/#font-weight-prop>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FontWeight {
+    Normal,
+    Bold,
+
+    /// A weight between `1` and `1000`
+    Absolute(f32),
+
+    /// Bolder than the inherited weight
+    Bolder,
+
+    /// Lighter than the inherited weight
+    Lighter,
+}
+
+impl<'a> CSSParse<'a> for FontWeight {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let font_weight = match parser.next_token_ignoring_whitespace() {
+            Some(Token::Ident(static_interned!("normal"))) => Self::Normal,
+            Some(Token::Ident(static_interned!("bold"))) => Self::Bold,
+            Some(Token::Ident(static_interned!("bolder"))) => Self::Bolder,
+            Some(Token::Ident(static_interned!("lighter"))) => Self::Lighter,
+            Some(Token::Number(number)) => {
+                let weight = f32::from(number);
+                if !(1. ..=1000.).contains(&weight) {
+                    return Err(ParseError);
+                }
+
+                Self::Absolute(weight)
+            },
+            _ => return Err(ParseError),
+        };
+
+        Ok(font_weight)
+    }
+}
+
+impl ToComputedStyle for FontWeight {
+    type Computed = computed::FontWeight;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        match self {
+            Self::Normal => computed::FontWeight::NORMAL,
+            Self::Bold => computed::FontWeight::BOLD,
+            Self::Absolute(weight) => computed::FontWeight::new(*weight),
+            Self::Bolder => context.inherited_font_weight.bolder(),
+            Self::Lighter => context.inherited_font_weight.lighter(),
+        }
+    }
+}
+
+impl CSSSerialize for FontWeight {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Normal => dest.push_str("normal"),
+            Self::Bold => dest.push_str("bold"),
+            Self::Absolute(weight) => serialize_float(*weight, dest),
+            Self::Bolder => dest.push_str("bolder"),
+            Self::Lighter => dest.push_str("lighter"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_font_weight() {
+        assert_eq!(FontWeight::parse_from_str("normal"), Ok(FontWeight::Normal));
+        assert_eq!(FontWeight::parse_from_str("bold"), Ok(FontWeight::Bold));
+        assert_eq!(FontWeight::parse_from_str("bolder"), Ok(FontWeight::Bolder));
+        assert_eq!(
+            FontWeight::parse_from_str("lighter"),
+            Ok(FontWeight::Lighter)
+        );
+        assert_eq!(
+            FontWeight::parse_from_str("350"),
+            Ok(FontWeight::Absolute(350.))
+        );
+        assert_eq!(
+            FontWeight::parse_from_str("1000"),
+            Ok(FontWeight::Absolute(1000.))
+        );
+
+        assert!(FontWeight::parse_from_str("0").is_err());
+        assert!(FontWeight::parse_from_str("1001").is_err());
+        assert!(FontWeight::parse_from_str("heavy").is_err());
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
     css::{
         layout::Pixels,
+        serialize::serialize_float,
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
         values::{Number, Percentage},
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned, InternedString,
 };
@@ -12,7 +13,7 @@ use crate::{
 use std::ops::Mul;
 
 /// <https://www.w3.org/TR/css-values-4/#length-value>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Length {
     value: f32,
     unit: Unit,
@@ -127,6 +128,12 @@ impl Length {
         }
     }
 
+    #[inline]
+    #[must_use]
+    pub fn is_negative(&self) -> bool {
+        self.value < 0.
+    }
+
     pub fn from_dimension(value: Number, unit_name: InternedString) -> Result<Self, ParseError> {
         let length = Self {
             value: value.into(),
@@ -287,3 +294,61 @@ impl ToComputedStyle for Length {
         }
     }
 }
+
+impl Unit {
+    #[must_use]
+    const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Em => "em",
+            Self::Rem => "rem",
+            Self::Ex => "ex",
+            Self::Rex => "rex",
+            Self::Cap => "cap",
+            Self::Rcap => "rcap",
+            Self::Ch => "ch",
+            Self::Rch => "rch",
+            Self::Ic => "ic",
+            Self::Ric => "ric",
+            Self::Lh => "lh",
+            Self::Rlh => "rlh",
+            Self::Vw => "vw",
+            Self::Svw => "svw",
+            Self::Lvw => "lvw",
+            Self::Dvw => "dvw",
+            Self::Vh => "vh",
+            Self::Svh => "svh",
+            Self::Lvh => "lvh",
+            Self::Dvh => "dvh",
+            Self::Vi => "vi",
+            Self::Svi => "svi",
+            Self::Lvi => "lvi",
+            Self::Dvi => "dvi",
+            Self::Vb => "vb",
+            Self::Svb => "svb",
+            Self::Lvb => "lvb",
+            Self::Dvb => "dvb",
+            Self::Vmin => "vmin",
+            Self::Svmin => "svmin",
+            Self::Lvmin => "lvmin",
+            Self::Dvmin => "dvmin",
+            Self::Vmax => "vmax",
+            Self::Svmax => "svmax",
+            Self::Lvmax => "lvmax",
+            Self::Dvmax => "dvmax",
+            Self::Cm => "cm",
+            Self::Mm => "mm",
+            Self::Q => "q",
+            Self::In => "in",
+            Self::Pc => "pc",
+            Self::Pt => "pt",
+            Self::Px => "px",
+        }
+    }
+}
+
+impl CSSSerialize for Length {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_float(self.value, dest);
+        dest.push_str(self.unit.as_str());
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
         values::{Number, Percentage},
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
@@ -71,3 +71,14 @@ impl ToComputedStyle for LineHeight {
         }
     }
 }
+
+impl CSSSerialize for LineHeight {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Normal => dest.push_str("normal"),
+            Self::Number(number) => number.serialize_to(dest),
+            Self::Percentage(percentage) => percentage.serialize_to(dest),
+            Self::Length(length) => length.serialize_to(dest),
+        }
+    }
+}
@@ -2,16 +2,17 @@
 
 use crate::{
     css::{
+        serialize::serialize_string,
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
         values::CounterStyle,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned, InternedString,
 };
 
 /// <https://drafts.csswg.org/css-lists/#propdef-list-style-type>
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ListStyleType {
     CounterStyle(CounterStyle),
     String(InternedString),
@@ -63,3 +64,13 @@ impl ToComputedStyle for ListStyleType {
         self.clone()
     }
 }
+
+impl CSSSerialize for ListStyleType {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::CounterStyle(counter_style) => dest.push_str(&counter_style.as_str()),
+            Self::String(string) => serialize_string(string.as_str(), dest),
+            Self::None => dest.push_str("none"),
+        }
+    }
+}
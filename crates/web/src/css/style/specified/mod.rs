@@ -1,38 +1,60 @@
 //! Defines properties as defined by the stylesheet author
 
 mod alignment;
+mod aspect_ratio;
 mod background_color;
 mod background_image;
 mod border;
+mod columns;
 mod cursor;
 mod display;
 mod float;
 mod font_family;
 mod font_size;
+mod font_stretch;
 mod font_style;
+mod font_variation_settings;
+mod font_weight;
 mod length;
 mod line_height;
 mod list_style_type;
 mod position;
+mod transition;
 mod vertical_align;
+mod writing_mode;
 
 pub use alignment::{Inset, JustifySelf};
+pub use aspect_ratio::AspectRatio;
 pub use background_color::BackgroundColor;
 pub use background_image::BackgroundImage;
 pub use border::{Border, LineStyle, LineWidth};
+pub use columns::{ColumnCount, Columns};
 pub use cursor::Cursor;
-pub use display::{Display, DisplayBox, DisplayInside, DisplayInsideOutside, DisplayOutside};
+pub use display::{
+    Display, DisplayBox, DisplayInside, DisplayInsideOutside, DisplayInternal, DisplayOutside,
+};
 pub use float::{Clear, Float, FloatSide};
 pub use font_family::{FontFamily, FontName};
 pub use font_size::FontSize;
+pub use font_stretch::FontStretch;
 pub use font_style::FontStyle;
+pub use font_variation_settings::{FontVariationSettings, VariationSetting};
+pub use font_weight::FontWeight;
 pub use length::Length;
 pub use line_height::LineHeight;
 pub use list_style_type::ListStyleType;
 pub use position::Position;
+pub use transition::{
+    SingleTransitionProperty, Transition, TransitionDelay, TransitionDuration, TransitionProperty,
+    TransitionTimingFunction,
+};
 pub use vertical_align::VerticalAlign;
+pub use writing_mode::{Direction, TextOrientation, WritingMode};
 
-use crate::css::values::{AutoOr, PercentageOr};
+use crate::css::values::{AutoOr, Gap, PercentageOr};
+
+/// <https://drafts.csswg.org/css-align/#column-row-gap>
+pub type ColumnGap = Gap<PercentageOr<Length>>;
 
 /// <https://drafts.csswg.org/css2/#value-def-margin-width>
 pub type Margin = AutoOr<PercentageOr<Length>>;
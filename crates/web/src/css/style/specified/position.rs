@@ -2,7 +2,7 @@ use crate::{
     css::{
         style::{computed, StyleContext, ToComputedStyle},
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
@@ -61,3 +61,16 @@ impl ToComputedStyle for Position {
         *self
     }
 }
+
+impl CSSSerialize for Position {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Static => "static",
+            Self::Relative => "relative",
+            Self::Sticky => "sticky",
+            Self::Absolute => "absolute",
+            Self::Fixed => "fixed",
+        };
+        dest.push_str(keyword);
+    }
+}
@@ -0,0 +1,435 @@
+//! <https://drafts.csswg.org/css-transitions/#transitions>
+
+use crate::{
+    css::{
+        serialize::serialize_comma_separated,
+        style::{StyleContext, ToComputedStyle},
+        syntax::Token,
+        values::{EasingFunction, Time},
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned, InternedString,
+};
+
+/// <https://drafts.csswg.org/css-transitions/#single-transition-property>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SingleTransitionProperty {
+    All,
+    Property(InternedString),
+}
+
+/// <https://drafts.csswg.org/css-transitions/#transition-property-property>
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionProperty {
+    /// The properties that are transitioned, empty for `none`
+    properties: Vec<SingleTransitionProperty>,
+}
+
+/// <https://drafts.csswg.org/css-transitions/#transition-duration-property>
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionDuration {
+    durations: Vec<Time>,
+}
+
+/// <https://drafts.csswg.org/css-transitions/#transition-timing-function-property>
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionTimingFunction {
+    timing_functions: Vec<EasingFunction>,
+}
+
+/// <https://drafts.csswg.org/css-transitions/#transition-delay-property>
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransitionDelay {
+    delays: Vec<Time>,
+}
+
+/// The `transition` shorthand, which sets all the `transition-*` longhands at once
+///
+/// <https://drafts.csswg.org/css-transitions/#transition-shorthand-property>
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transition {
+    pub property: TransitionProperty,
+    pub duration: TransitionDuration,
+    pub timing_function: TransitionTimingFunction,
+    pub delay: TransitionDelay,
+}
+
+/// <https://drafts.csswg.org/css-transitions/#single-transition>
+#[derive(Clone, Debug)]
+struct SingleTransition {
+    /// `None` if the property was specified as `none`
+    property: Option<SingleTransitionProperty>,
+    duration: Time,
+    timing_function: EasingFunction,
+    delay: Time,
+}
+
+impl TransitionProperty {
+    #[must_use]
+    pub fn properties(&self) -> &[SingleTransitionProperty] {
+        &self.properties
+    }
+}
+
+impl TransitionDuration {
+    #[must_use]
+    pub fn durations(&self) -> &[Time] {
+        &self.durations
+    }
+}
+
+impl TransitionTimingFunction {
+    #[must_use]
+    pub fn timing_functions(&self) -> &[EasingFunction] {
+        &self.timing_functions
+    }
+}
+
+impl TransitionDelay {
+    #[must_use]
+    pub fn delays(&self) -> &[Time] {
+        &self.delays
+    }
+}
+
+impl Default for TransitionProperty {
+    fn default() -> Self {
+        Self {
+            properties: vec![SingleTransitionProperty::All],
+        }
+    }
+}
+
+impl Default for TransitionDuration {
+    fn default() -> Self {
+        Self {
+            durations: vec![Time::ZERO],
+        }
+    }
+}
+
+impl Default for TransitionTimingFunction {
+    fn default() -> Self {
+        Self {
+            timing_functions: vec![EasingFunction::EASE],
+        }
+    }
+}
+
+impl Default for TransitionDelay {
+    fn default() -> Self {
+        Self {
+            delays: vec![Time::ZERO],
+        }
+    }
+}
+
+impl<'a> CSSParse<'a> for SingleTransitionProperty {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        match parser.expect_identifier()? {
+            static_interned!("all") => Ok(Self::All),
+            static_interned!("none")
+            | static_interned!("initial")
+            | static_interned!("inherit")
+            | static_interned!("unset")
+            | static_interned!("default") => Err(ParseError),
+            property => Ok(Self::Property(property)),
+        }
+    }
+}
+
+impl<'a> CSSParse<'a> for TransitionProperty {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        if matches!(
+            parser.peek_token_ignoring_whitespace(0),
+            Some(Token::Ident(static_interned!("none")))
+        ) {
+            let _ = parser.next_token_ignoring_whitespace();
+            return Ok(Self { properties: vec![] });
+        }
+
+        let properties = parse_list(parser, SingleTransitionProperty::parse)?;
+        Ok(Self { properties })
+    }
+}
+
+impl<'a> CSSParse<'a> for TransitionDuration {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let durations = parse_list(parser, Time::parse)?;
+
+        // Negative durations are invalid
+        if durations.iter().any(|duration| *duration < Time::ZERO) {
+            return Err(ParseError);
+        }
+
+        Ok(Self { durations })
+    }
+}
+
+impl<'a> CSSParse<'a> for TransitionTimingFunction {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let timing_functions = parse_list(parser, EasingFunction::parse)?;
+        Ok(Self { timing_functions })
+    }
+}
+
+impl<'a> CSSParse<'a> for TransitionDelay {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let delays = parse_list(parser, Time::parse)?;
+        Ok(Self { delays })
+    }
+}
+
+impl<'a> CSSParse<'a> for SingleTransition {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let mut property = None;
+        let mut times = vec![];
+        let mut timing_function = None;
+
+        // The components may appear in any order, the first time is the duration
+        // and the second one is the delay
+        loop {
+            if timing_function.is_none()
+                && let Some(value) = parser.parse_optional::<EasingFunction>()
+            {
+                timing_function = Some(value);
+            } else if times.len() < 2
+                && let Some(time) = parser.parse_optional::<Time>()
+            {
+                times.push(time);
+            } else if property.is_none()
+                && let Some(value) = parser.parse_optional_value(parse_property_or_none)
+            {
+                property = Some(value);
+            } else {
+                break;
+            }
+        }
+
+        if property.is_none() && times.is_empty() && timing_function.is_none() {
+            return Err(ParseError);
+        }
+
+        let duration = times.first().copied().unwrap_or(Time::ZERO);
+        if duration < Time::ZERO {
+            return Err(ParseError);
+        }
+
+        Ok(Self {
+            property: property.unwrap_or(Some(SingleTransitionProperty::All)),
+            duration,
+            timing_function: timing_function.unwrap_or_default(),
+            delay: times.get(1).copied().unwrap_or(Time::ZERO),
+        })
+    }
+}
+
+/// Parse a non-empty, comma-separated list
+///
+/// Unlike [Parser::parse_comma_seperated_list], this fails if any of the
+/// elements is invalid.
+fn parse_list<'a, T>(
+    parser: &mut Parser<'a>,
+    parse_element: fn(&mut Parser<'a>) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    let mut elements = vec![parse_element(parser)?];
+
+    while matches!(parser.peek_token_ignoring_whitespace(0), Some(Token::Comma)) {
+        let _ = parser.next_token_ignoring_whitespace();
+        elements.push(parse_element(parser)?);
+    }
+
+    Ok(elements)
+}
+
+fn parse_property_or_none(
+    parser: &mut Parser<'_>,
+) -> Result<Option<SingleTransitionProperty>, ParseError> {
+    if matches!(
+        parser.peek_token_ignoring_whitespace(0),
+        Some(Token::Ident(static_interned!("none")))
+    ) {
+        let _ = parser.next_token_ignoring_whitespace();
+        return Ok(None);
+    }
+
+    parser.parse().map(Some)
+}
+
+impl<'a> CSSParse<'a> for Transition {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let transitions = parse_list(parser, SingleTransition::parse)?;
+
+        // "none" is only valid if it is the only transition
+        let properties: Option<Vec<_>> = transitions
+            .iter()
+            .map(|transition| transition.property.clone())
+            .collect();
+        let properties = match properties {
+            Some(properties) => properties,
+            None if transitions.len() == 1 => vec![],
+            None => return Err(ParseError),
+        };
+
+        Ok(Self {
+            property: TransitionProperty { properties },
+            duration: TransitionDuration {
+                durations: transitions.iter().map(|t| t.duration).collect(),
+            },
+            timing_function: TransitionTimingFunction {
+                timing_functions: transitions.iter().map(|t| t.timing_function).collect(),
+            },
+            delay: TransitionDelay {
+                delays: transitions.iter().map(|t| t.delay).collect(),
+            },
+        })
+    }
+}
+
+macro_rules! computed_as_specified {
+    ($($type: ty),*) => {
+        $(
+            impl ToComputedStyle for $type {
+                type Computed = Self;
+
+                fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+                    _ = context;
+
+                    self.clone()
+                }
+            }
+        )*
+    };
+}
+
+computed_as_specified!(
+    TransitionProperty,
+    TransitionDuration,
+    TransitionTimingFunction,
+    TransitionDelay,
+    Transition
+);
+
+impl CSSSerialize for SingleTransitionProperty {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::All => dest.push_str("all"),
+            Self::Property(property) => property.serialize_to(dest),
+        }
+    }
+}
+
+impl CSSSerialize for TransitionProperty {
+    fn serialize_to(&self, dest: &mut String) {
+        if self.properties.is_empty() {
+            dest.push_str("none");
+        } else {
+            serialize_comma_separated(&self.properties, dest);
+        }
+    }
+}
+
+impl CSSSerialize for TransitionDuration {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_comma_separated(&self.durations, dest);
+    }
+}
+
+impl CSSSerialize for TransitionTimingFunction {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_comma_separated(&self.timing_functions, dest);
+    }
+}
+
+impl CSSSerialize for TransitionDelay {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_comma_separated(&self.delays, dest);
+    }
+}
+
+impl CSSSerialize for Transition {
+    /// Serializes every single transition as `<property> <duration> <easing-function> <delay>`
+    fn serialize_to(&self, dest: &mut String) {
+        for (index, duration) in self.duration.durations.iter().enumerate() {
+            if index != 0 {
+                dest.push_str(", ");
+            }
+
+            match self.property.properties.get(index) {
+                Some(property) => property.serialize_to(dest),
+                None => dest.push_str("none"),
+            }
+            dest.push(' ');
+            duration.serialize_to(dest);
+
+            if let Some(timing_function) = self.timing_function.timing_functions.get(index) {
+                dest.push(' ');
+                timing_function.serialize_to(dest);
+            }
+
+            if let Some(delay) = self.delay.delays.get(index) {
+                dest.push(' ');
+                delay.serialize_to(dest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_transition_property() {
+        assert_eq!(
+            TransitionProperty::parse_from_str("none"),
+            Ok(TransitionProperty { properties: vec![] })
+        );
+        assert_eq!(
+            TransitionProperty::parse_from_str("color, all"),
+            Ok(TransitionProperty {
+                properties: vec![
+                    SingleTransitionProperty::Property(static_interned!("color")),
+                    SingleTransitionProperty::All
+                ]
+            })
+        );
+        assert!(TransitionProperty::parse_from_str("color, none").is_err());
+    }
+
+    #[test]
+    fn parse_transition_shorthand() {
+        let transition =
+            Transition::parse_from_str("color 1s ease-in, margin-top 200ms 1s").unwrap();
+
+        assert_eq!(
+            transition.property.properties(),
+            &[
+                SingleTransitionProperty::Property(static_interned!("color")),
+                SingleTransitionProperty::Property(static_interned!("margin-top")),
+            ]
+        );
+        assert_eq!(
+            transition.duration.durations(),
+            &[Time::from_seconds(1.), Time::from_milliseconds(200.)]
+        );
+        assert_eq!(
+            transition.timing_function.timing_functions(),
+            &[EasingFunction::EASE_IN, EasingFunction::EASE]
+        );
+        assert_eq!(
+            transition.delay.delays(),
+            &[Time::ZERO, Time::from_seconds(1.)]
+        );
+
+        // The property defaults to "all"
+        let transition = Transition::parse_from_str("2s linear").unwrap();
+        assert_eq!(
+            transition.property.properties(),
+            &[SingleTransitionProperty::All]
+        );
+
+        assert!(Transition::parse_from_str("none 1s, color 1s").is_err());
+        assert!(Transition::parse_from_str("color -1s").is_err());
+    }
+}
@@ -5,13 +5,13 @@ use crate::{
         style::{specified::Length, StyleContext, ToComputedStyle},
         syntax::Token,
         values::Percentage,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned, InternedString,
 };
 
 /// <https://drafts.csswg.org/css2/#propdef-vertical-align>
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum VerticalAlign {
     /// <https://drafts.csswg.org/css2/#valdef-vertical-align-baseline>
     Baseline,
@@ -41,7 +41,7 @@ pub enum VerticalAlign {
 }
 
 impl VerticalAlign {
-    pub const fn from_name(name: InternedString) -> Result<Self, ParseError> {
+    pub fn from_name(name: InternedString) -> Result<Self, ParseError> {
         let vertical_align = match name {
             static_interned!("baseline") => Self::Baseline,
             static_interned!("sub") => Self::Sub,
@@ -62,7 +62,7 @@ impl<'a> CSSParse<'a> for VerticalAlign {
     fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
         let vertical_align = match parser.peek_token_ignoring_whitespace(0) {
             Some(Token::Ident(ident)) => {
-                let align = Self::from_name(*ident)?;
+                let align = Self::from_name(ident.clone())?;
                 _ = parser.next_token_ignoring_whitespace();
                 align
             },
@@ -92,3 +92,27 @@ impl ToComputedStyle for VerticalAlign {
         *self
     }
 }
+
+impl CSSSerialize for VerticalAlign {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Baseline => "baseline",
+            Self::Sub => "sub",
+            Self::Super => "super",
+            Self::Top => "top",
+            Self::TextTop => "text-top",
+            Self::Middle => "middle",
+            Self::Bottom => "bottom",
+            Self::TextBottom => "text-bottom",
+            Self::Percentage(percentage) => {
+                percentage.serialize_to(dest);
+                return;
+            },
+            Self::Length(length) => {
+                length.serialize_to(dest);
+                return;
+            },
+        };
+        dest.push_str(keyword);
+    }
+}
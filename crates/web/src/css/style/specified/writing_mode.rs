@@ -0,0 +1,223 @@
+//! <https://drafts.csswg.org/css-writing-modes-4/>
+
+use crate::{
+    css::{
+        style::{computed, StyleContext, ToComputedStyle},
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// <https://drafts.csswg.org/css-writing-modes-4/#block-flow>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WritingMode {
+    /// Lines are horizontal and stacked from top to bottom
+    #[default]
+    HorizontalTb,
+
+    /// Lines are vertical and stacked from right to left
+    VerticalRl,
+
+    /// Lines are vertical and stacked from left to right
+    VerticalLr,
+
+    /// Like [WritingMode::VerticalRl], but all text is set sideways
+    SidewaysRl,
+
+    /// Like [WritingMode::VerticalLr], but all text is set sideways and
+    /// runs from bottom to top
+    SidewaysLr,
+}
+
+/// <https://drafts.csswg.org/css-writing-modes-4/#direction>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+/// <https://drafts.csswg.org/css-writing-modes-4/#text-orientation>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextOrientation {
+    /// Characters from horizontal-only scripts are set sideways, characters from
+    /// vertical scripts are set upright
+    #[default]
+    Mixed,
+
+    /// All characters are set upright
+    Upright,
+
+    /// All characters are set sideways
+    Sideways,
+}
+
+impl WritingMode {
+    /// Whether lines in this writing mode are vertical
+    #[must_use]
+    pub const fn is_vertical(&self) -> bool {
+        !matches!(self, Self::HorizontalTb)
+    }
+}
+
+impl TextOrientation {
+    /// Determine whether a character should be set upright within a vertical line
+    ///
+    /// Instead of the full [Unicode vertical orientation](https://www.unicode.org/reports/tr50/) data,
+    /// only the most common blocks of vertical scripts are set upright in mixed orientation.
+    #[must_use]
+    pub fn is_upright(&self, c: char) -> bool {
+        match self {
+            Self::Upright => true,
+            Self::Sideways => false,
+            Self::Mixed => matches!(c,
+                '\u{1100}'..='\u{11FF}' // Hangul Jamo
+                | '\u{2E80}'..='\u{2FDF}' // CJK Radicals
+                | '\u{3000}'..='\u{30FF}' // CJK Symbols and Punctuation, Hiragana, Katakana
+                | '\u{3100}'..='\u{31FF}' // Bopomofo, Hangul Compatibility Jamo, Katakana Extensions
+                | '\u{3400}'..='\u{4DBF}' // CJK Unified Ideographs Extension A
+                | '\u{4E00}'..='\u{9FFF}' // CJK Unified Ideographs
+                | '\u{AC00}'..='\u{D7AF}' // Hangul Syllables
+                | '\u{F900}'..='\u{FAFF}' // CJK Compatibility Ideographs
+                | '\u{FF00}'..='\u{FFEF}' // Halfwidth and Fullwidth Forms
+            ),
+        }
+    }
+}
+
+impl<'a> CSSParse<'a> for WritingMode {
+    // <https://drafts.csswg.org/css-writing-modes-4/#propdef-writing-mode>
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let value = match parser.expect_identifier()? {
+            static_interned!("horizontal-tb") => Self::HorizontalTb,
+            static_interned!("vertical-rl") => Self::VerticalRl,
+            static_interned!("vertical-lr") => Self::VerticalLr,
+            static_interned!("sideways-rl") => Self::SidewaysRl,
+            static_interned!("sideways-lr") => Self::SidewaysLr,
+            _ => return Err(ParseError),
+        };
+        Ok(value)
+    }
+}
+
+impl<'a> CSSParse<'a> for Direction {
+    // <https://drafts.csswg.org/css-writing-modes-4/#propdef-direction>
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let value = match parser.expect_identifier()? {
+            static_interned!("ltr") => Self::Ltr,
+            static_interned!("rtl") => Self::Rtl,
+            _ => return Err(ParseError),
+        };
+        Ok(value)
+    }
+}
+
+impl<'a> CSSParse<'a> for TextOrientation {
+    // <https://drafts.csswg.org/css-writing-modes-4/#propdef-text-orientation>
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let value = match parser.expect_identifier()? {
+            static_interned!("mixed") => Self::Mixed,
+            static_interned!("upright") => Self::Upright,
+            static_interned!("sideways") => Self::Sideways,
+            _ => return Err(ParseError),
+        };
+        Ok(value)
+    }
+}
+
+impl ToComputedStyle for WritingMode {
+    type Computed = computed::WritingMode;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        *self
+    }
+}
+
+impl ToComputedStyle for Direction {
+    type Computed = computed::Direction;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        *self
+    }
+}
+
+impl ToComputedStyle for TextOrientation {
+    type Computed = computed::TextOrientation;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        _ = context;
+
+        *self
+    }
+}
+
+impl CSSSerialize for WritingMode {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::HorizontalTb => "horizontal-tb",
+            Self::VerticalRl => "vertical-rl",
+            Self::VerticalLr => "vertical-lr",
+            Self::SidewaysRl => "sideways-rl",
+            Self::SidewaysLr => "sideways-lr",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for Direction {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Ltr => "ltr",
+            Self::Rtl => "rtl",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+impl CSSSerialize for TextOrientation {
+    fn serialize_to(&self, dest: &mut String) {
+        let keyword = match self {
+            Self::Mixed => "mixed",
+            Self::Upright => "upright",
+            Self::Sideways => "sideways",
+        };
+        dest.push_str(keyword);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_writing_mode() {
+        assert_eq!(
+            WritingMode::parse_from_str("vertical-rl"),
+            Ok(WritingMode::VerticalRl)
+        );
+        assert_eq!(
+            WritingMode::parse_from_str("sideways-lr"),
+            Ok(WritingMode::SidewaysLr)
+        );
+        assert!(WritingMode::parse_from_str("lr-tb").is_err());
+
+        assert_eq!(Direction::parse_from_str("rtl"), Ok(Direction::Rtl));
+        assert_eq!(
+            TextOrientation::parse_from_str("upright"),
+            Ok(TextOrientation::Upright)
+        );
+    }
+
+    #[test]
+    fn mixed_text_orientation() {
+        assert!(TextOrientation::Mixed.is_upright('漢'));
+        assert!(TextOrientation::Mixed.is_upright('あ'));
+        assert!(!TextOrientation::Mixed.is_upright('a'));
+        assert!(TextOrientation::Upright.is_upright('a'));
+        assert!(!TextOrientation::Sideways.is_upright('漢'));
+    }
+}
@@ -1,4 +1,4 @@
-use std::cmp;
+use std::{cell::RefCell, cmp};
 
 use crate::{
     css::{
@@ -6,14 +6,14 @@ use crate::{
         properties::Important,
         selectors::{Selector, Specificity},
         syntax::RuleParser,
-        Origin, Parser, StyleProperty, StylePropertyDeclaration, Stylesheet,
+        MediaEnvironment, Origin, Parser, StyleProperty, StylePropertyDeclaration, Stylesheet,
+        Transitions,
     },
     dom::{dom_objects::Element, DomPtr},
     static_interned,
 };
 
 use super::{
-    font_metrics::DEFAULT_FONT_SIZE,
     layout::{Pixels, Size},
     style::{StyleContext, ToComputedStyle},
 };
@@ -23,6 +23,12 @@ pub struct StyleComputer<'a> {
     stylesheets: &'a [Stylesheet],
     root_font_size: Pixels,
     viewport_size: Size<Pixels>,
+
+    /// Decides which `@media` rules apply
+    media_environment: MediaEnvironment,
+
+    /// The transitions of the document, if style changes should be animated
+    transitions: Option<&'a RefCell<Transitions>>,
 }
 
 #[derive(Clone, Debug)]
@@ -66,9 +72,20 @@ impl<'a> MatchingProperty<'a> {
         self.property.value.clone()
     }
 
+    /// Whether this property is needed to map flow-relative properties to physical ones
+    ///
+    /// <https://drafts.csswg.org/css-writing-modes-4/#logical-to-physical>
+    fn affects_writing_mode(&self) -> bool {
+        matches!(
+            self.property.value,
+            StyleProperty::WritingMode(_) | StyleProperty::Direction(_)
+        )
+    }
+
     fn origin_and_importance_group(&self) -> u8 {
         match (self.property.important, self.origin) {
-            // 1. FIXME: Transition declarations [css-transitions-1]
+            // 1. NOTE: Transition declarations [css-transitions-1] are applied after the cascade,
+            //          by the transitions of the document
 
             // 2. Important user agent declarations
             (Important::Yes, Origin::UserAgent) => 2,
@@ -125,16 +142,45 @@ impl<'a> StyleComputer<'a> {
             stylesheets,
             root_font_size,
             viewport_size,
+            media_environment: MediaEnvironment::new(viewport_size),
+            transitions: None,
         }
     }
 
+    #[must_use]
+    pub fn with_media_environment(mut self, media_environment: MediaEnvironment) -> Self {
+        self.media_environment = media_environment;
+        self
+    }
+
+    /// Start transitions whenever the computed style of an element changes
+    #[must_use]
+    pub fn with_transitions(mut self, transitions: &'a RefCell<Transitions>) -> Self {
+        self.transitions = Some(transitions);
+        self
+    }
+
+    /// The style that the root element inherits from
+    #[must_use]
+    pub fn initial_style(&self) -> ComputedStyle {
+        let mut style = ComputedStyle::default();
+        style.set_font_size(
+            self.media_environment
+                .font_sizes
+                .medium_for(style.font_family()),
+        );
+        style
+    }
+
     // Find all the [StyleRules](super::StyleRule) that apply to an [Element]
     fn collect_matched_properties(&self, element: DomPtr<Element>) -> Vec<MatchingProperty<'_>> {
         let mut matched_properties = vec![];
 
         for stylesheet in self.stylesheets {
             for (rule_index, rule) in stylesheet.rules().iter().enumerate() {
-                if rule.selectors().iter().any(|s| s.matches(&element)) {
+                if rule.applies_in(&self.media_environment)
+                    && rule.selectors().iter().any(|s| s.matches(&element))
+                {
                     let new_properties = rule.properties().iter().map(|prop| {
                         // FIXME: This should be the specificity of the most-specific matching selector,
                         //        not the sum
@@ -173,13 +219,27 @@ impl<'a> StyleComputer<'a> {
             MatchingProperty::new(property, Specificity::MAX, 0, 0, Origin::Author)
         });
 
-        let mut matched_properties = self.collect_matched_properties(element);
+        let mut matched_properties = self.collect_matched_properties(element.clone());
         matched_properties.extend(attribute_style);
 
         // Sort matching rules in cascade order, see
         // https://drafts.csswg.org/css-cascade-4/#cascade-sort for more info
         matched_properties.sort_unstable_by(MatchingProperty::compare_in_cascade_order);
 
+        // The size of "font-size: medium" depends on the font family of the element
+        let font_family = matched_properties
+            .iter()
+            .rev()
+            .find_map(|prop| {
+                if let StyleProperty::FontFamily(font_family) = prop.property() {
+                    Some(font_family)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_else(|| parent_style.font_family().clone());
+        let medium_font_size = self.media_environment.font_sizes.medium_for(&font_family);
+
         // Find the font size of the element
         // This is done seperately, as only the font-size property refers to the font size
         // of the parent instead of the element itself
@@ -197,17 +257,29 @@ impl<'a> StyleComputer<'a> {
                 let style_context = StyleContext {
                     font_size: *parent_style.font_size(),
                     root_font_size: self.root_font_size,
+                    medium_font_size,
+                    inherited_font_weight: *parent_style.font_weight(),
                     viewport: self.viewport_size,
                 };
 
                 font_size.to_computed_style(&style_context)
             })
             .next()
-            .unwrap_or(DEFAULT_FONT_SIZE);
+            .unwrap_or_else(|| {
+                // Text that switches between a monospace and a proportional font is scaled
+                // to the size the user chose for the new font
+                let parent_medium_font_size = self
+                    .media_environment
+                    .font_sizes
+                    .medium_for(parent_style.font_family());
+                *parent_style.font_size() * (medium_font_size.0 / parent_medium_font_size.0)
+            });
 
         let style_context = StyleContext {
             font_size,
             root_font_size: self.root_font_size,
+            medium_font_size,
+            inherited_font_weight: *parent_style.font_weight(),
             viewport: self.viewport_size,
         };
 
@@ -215,12 +287,24 @@ impl<'a> StyleComputer<'a> {
         // That way, more important rules can override less important ones
         let mut computed_style = parent_style.get_inherited();
 
-        for matched_property in matched_properties.into_iter() {
+        // The writing mode is determined first, since flow-relative properties
+        // like "margin-inline-start" depend on it
+        let (writing_mode_properties, other_properties): (Vec<_>, Vec<_>) = matched_properties
+            .into_iter()
+            .partition(MatchingProperty::affects_writing_mode);
+
+        for matched_property in writing_mode_properties.into_iter().chain(other_properties) {
             let property = matched_property.property();
             computed_style.set_property(property, &style_context);
         }
+        computed_style.set_font_size(font_size);
 
-        computed_style
+        match self.transitions {
+            Some(transitions) => transitions
+                .borrow_mut()
+                .process_style_change(&element, computed_style),
+            None => computed_style,
+        }
     }
 }
 
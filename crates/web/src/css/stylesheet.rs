@@ -1,4 +1,14 @@
-use super::{selectors::Selector, Parser, StylePropertyDeclaration};
+use std::{mem, rc::Rc};
+
+use sl_std::memory::{MemoryReport, MemoryReporter};
+
+use super::{
+    media_queries::{MediaEnvironment, MediaQueryList},
+    selectors::Selector,
+    serialize::serialize_comma_separated,
+    syntax::SyntaxError,
+    CSSSerialize, PageRule, Parser, StylePropertyDeclaration,
+};
 
 /// <https://drafts.csswg.org/css-cascade-4/#cascading-origins>
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -24,8 +34,14 @@ pub struct Stylesheet {
     /// The rules contained in the stylesheet
     rules: Vec<StyleRule>,
 
+    /// The `@page` rules of the stylesheet, which apply when the document is printed
+    page_rules: Vec<PageRule>,
+
     /// A number describing the order of appearance of different stylesheets
     index: usize,
+
+    /// Parts of the stylesheet that were ignored because they could not be parsed
+    errors: Vec<SyntaxError>,
 }
 
 impl Stylesheet {
@@ -35,15 +51,40 @@ impl Stylesheet {
         Self {
             origin,
             rules,
+            page_rules: vec![],
             index,
+            errors: vec![],
         }
     }
 
+    #[must_use]
+    pub(crate) fn with_errors(mut self, errors: Vec<SyntaxError>) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn with_page_rules(mut self, page_rules: Vec<PageRule>) -> Self {
+        self.page_rules = page_rules;
+        self
+    }
+
     #[inline]
     #[must_use]
     pub fn user_agent_rules() -> Self {
         let default_css = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/default.css"));
-        Parser::new(default_css, Origin::UserAgent).parse_stylesheet(usize::MAX)
+        Parser::new(default_css, Origin::UserAgent).parse_stylesheet(usize::MAX - 1)
+    }
+
+    /// Rules that give the default styles (including those of form controls) dark colors
+    ///
+    /// These are used in addition to [Stylesheet::user_agent_rules] if dark mode is forced,
+    /// and take precedence over them.
+    #[inline]
+    #[must_use]
+    pub fn forced_dark_user_agent_rules() -> Self {
+        let forced_dark_css = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/forced-dark.css"));
+        Parser::new(forced_dark_css, Origin::UserAgent).parse_stylesheet(usize::MAX)
     }
 
     #[inline]
@@ -58,15 +99,51 @@ impl Stylesheet {
         &self.rules
     }
 
+    #[inline]
+    #[must_use]
+    pub(crate) fn page_rules(&self) -> &[PageRule] {
+        &self.page_rules
+    }
+
     pub fn index(&self) -> usize {
         self.index
     }
+
+    #[inline]
+    #[must_use]
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct StyleRule {
     selectors: Vec<Selector>,
     properties: Vec<StylePropertyDeclaration>,
+
+    /// The conditions of all the `@media` rules that this rule is nested in
+    media: Vec<Rc<MediaQueryList>>,
+}
+
+impl MemoryReporter for Stylesheet {
+    /// The sizes are shallow, data that is owned by individual selectors or declarations is not included
+    fn report_memory(&self, report: &mut MemoryReport) {
+        report.record(
+            "style/rules",
+            self.rules.capacity() * mem::size_of::<StyleRule>(),
+        );
+
+        for rule in &self.rules {
+            report.record(
+                "style/selectors",
+                rule.selectors.capacity() * mem::size_of::<Selector>(),
+            );
+            report.record(
+                "style/declarations",
+                rule.properties.capacity() * mem::size_of::<StylePropertyDeclaration>(),
+            );
+        }
+    }
 }
 
 impl StyleRule {
@@ -74,9 +151,24 @@ impl StyleRule {
         Self {
             selectors,
             properties,
+            media: vec![],
         }
     }
 
+    #[must_use]
+    pub(crate) fn with_media(mut self, media: Vec<Rc<MediaQueryList>>) -> Self {
+        self.media = media;
+        self
+    }
+
+    /// Whether the conditions of all enclosing `@media` rules are true
+    #[must_use]
+    pub(crate) fn applies_in(&self, environment: &MediaEnvironment) -> bool {
+        self.media
+            .iter()
+            .all(|media_queries| media_queries.matches(environment))
+    }
+
     #[must_use]
     pub fn selectors(&self) -> &[Selector] {
         &self.selectors
@@ -87,3 +179,46 @@ impl StyleRule {
         &self.properties
     }
 }
+
+impl CSSSerialize for Stylesheet {
+    /// Serializes all rules of the stylesheet, one per line
+    ///
+    /// This is the equivalent of concatenating the
+    /// [cssText](https://drafts.csswg.org/cssom/#dom-cssrule-csstext) of all rules.
+    fn serialize_to(&self, dest: &mut String) {
+        for (index, rule) in self.rules.iter().enumerate() {
+            if index != 0 {
+                dest.push('\n');
+            }
+            rule.serialize_to(dest);
+        }
+    }
+}
+
+impl CSSSerialize for StyleRule {
+    /// <https://drafts.csswg.org/cssom/#serialize-a-css-rule>
+    ///
+    /// Rules that are nested in `@media` rules are serialized together with the enclosing
+    /// rules. Rules that were nested in the same `@media` rule are therefore serialized
+    /// as separate `@media` rules.
+    fn serialize_to(&self, dest: &mut String) {
+        for media_queries in &self.media {
+            dest.push_str("@media ");
+            media_queries.serialize_to(dest);
+            dest.push_str(" { ");
+        }
+
+        serialize_comma_separated(&self.selectors, dest);
+        dest.push_str(" {");
+        for declaration in &self.properties {
+            dest.push(' ');
+            declaration.serialize_to(dest);
+            dest.push(';');
+        }
+        dest.push_str(" }");
+
+        for _ in &self.media {
+            dest.push_str(" }");
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Problems that were found while parsing a stylesheet
+//!
+//! CSS parsers recover from errors by skipping the part of the stylesheet that
+//! they could not parse. That makes it hard for authors to find out why a rule
+//! has no effect, so the errors are collected and kept in the
+//! [Stylesheet](crate::css::Stylesheet) they belong to.
+
+use std::fmt;
+
+use crate::InternedString;
+
+use super::parser::SourceLocation;
+
+/// Something in a stylesheet that was ignored because it could not be parsed
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError {
+    /// Where the ignored part of the stylesheet starts
+    pub location: SourceLocation,
+    pub kind: SyntaxErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SyntaxErrorKind {
+    /// The selectors of a style rule are invalid, so the whole rule was dropped
+    InvalidSelector(String),
+
+    /// A declaration did not start with the name of a property
+    ExpectedPropertyName,
+
+    /// The name of a property was not followed by a colon
+    ExpectedColon {
+        property: InternedString,
+    },
+
+    UnknownProperty(InternedString),
+
+    /// The value is not valid for the property
+    InvalidValue {
+        property: InternedString,
+        value: String,
+    },
+
+    /// A `!` after the value of a property was not followed by `important`
+    InvalidImportant {
+        property: InternedString,
+    },
+
+    /// The prelude of a `@media` rule is not a valid media query list
+    InvalidMediaQuery(String),
+
+    /// The prelude of a `@namespace` rule is not a prefix followed by a string or url
+    InvalidNamespaceRule,
+
+    /// A `@namespace` rule appeared after other rules or inside a block
+    MisplacedNamespaceRule,
+
+    UnsupportedAtRule(InternedString),
+
+    /// A `@page` rule has a page selector, which is not supported
+    UnsupportedPageSelector(String),
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+impl fmt::Display for SyntaxErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSelector(selector) => {
+                write!(f, "Invalid selector '{selector}', the rule is ignored")
+            },
+            Self::ExpectedPropertyName => write!(f, "Expected the name of a property"),
+            Self::ExpectedColon { property } => {
+                write!(f, "Expected a colon after the property name '{property}'")
+            },
+            Self::UnknownProperty(property) => write!(f, "Unknown property '{property}'"),
+            Self::InvalidValue { property, value } => {
+                write!(f, "Invalid value '{value}' for property '{property}'")
+            },
+            Self::InvalidImportant { property } => {
+                write!(f, "Expected 'important' after '!' in property '{property}'")
+            },
+            Self::InvalidMediaQuery(media_query) => {
+                write!(
+                    f,
+                    "Invalid media query '{media_query}', the rule is ignored"
+                )
+            },
+            Self::InvalidNamespaceRule => write!(f, "Invalid @namespace rule, it is ignored"),
+            Self::MisplacedNamespaceRule => write!(
+                f,
+                "@namespace rules must precede all other rules, the rule is ignored"
+            ),
+            Self::UnsupportedAtRule(name) => write!(f, "Unsupported at-rule @{name}"),
+            Self::UnsupportedPageSelector(selector) => write!(
+                f,
+                "Unsupported page selector '{selector}', the rule is ignored"
+            ),
+        }
+    }
+}
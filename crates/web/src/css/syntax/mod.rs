@@ -1,8 +1,10 @@
 //! Implements the [CSS Syntax Module Level 3](https://drafts.csswg.org/css-syntax/) draft.
 
+mod error;
 pub mod parser;
 mod rule_parser;
 mod tokenizer;
 
+pub use error::{SyntaxError, SyntaxErrorKind};
 pub(crate) use rule_parser::RuleParser;
 pub use tokenizer::Token;
@@ -2,28 +2,29 @@
 //!
 //! This is the next parsing stage after [Tokenization](super::tokenizer).
 
-use sl_std::ring_buffer::RingBuffer;
-
 use super::{
+    error::{SyntaxError, SyntaxErrorKind},
     rule_parser::RuleParser,
-    tokenizer::{Token, Tokenizer},
+    tokenizer::{Token, TokenBuffer},
 };
 
 use crate::{
     css::{
-        layout::Sides, properties::Important, values::Number, Origin, StyleProperty,
-        StylePropertyDeclaration, StyleRule, Stylesheet,
+        layout::Sides,
+        media_queries::MediaQueryList,
+        page::{PageRule, PageSize},
+        properties::Important,
+        selectors::Namespaces,
+        values::{Number, Url},
+        Origin, StyleProperty, StylePropertyDeclaration, StyleRule, Stylesheet,
     },
     static_interned, InternedString,
 };
 
-use std::fmt::Debug;
+use std::{fmt, fmt::Debug, rc::Rc};
 
 const MAX_ITERATIONS: usize = 128;
 
-/// The maximum number of tokens that can be peeked ahead during parsing
-const MAX_LOOKAHEAD: usize = 16;
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum MixedWithDeclarations {
     Yes,
@@ -38,13 +39,53 @@ pub enum TopLevel {
     No,
 }
 
+/// Consumes the tokens of a source string
+///
+/// The source is tokenized once, up front. Cloning a [Parser] or resetting it to
+/// an earlier [state](Parser::state) is therefore cheap, which makes speculative parsing
+/// (like in [parse_optional_value](Parser::parse_optional_value)) cheap too.
 #[derive(Clone, Debug)]
 pub struct Parser<'a> {
-    tokenizer: Tokenizer<'a>,
-    queued_tokens: RingBuffer<Token, MAX_LOOKAHEAD>,
+    source: &'a str,
+    tokens: Rc<TokenBuffer>,
+
+    /// The index of the next token in [tokens](Self::tokens)
+    cursor: usize,
+
     origin: Origin,
+
+    /// Parts of the source that were skipped because they could not be parsed
+    errors: Vec<SyntaxError>,
+
+    /// The namespaces declared by `@namespace` rules so far
+    namespaces: Rc<Namespaces>,
+
+    /// The `@page` rules that were parsed so far
+    page_rules: Vec<PageRule>,
+}
+
+/// A point in the token stream that a [Parser] can be reset to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParserState {
+    cursor: usize,
 }
 
+/// A position in the source of a stylesheet
+///
+/// Lines and columns start at `1`, columns count characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A byte offset into the source of a stylesheet
+///
+/// Positions are cheap to obtain, they are only turned into a [SourceLocation]
+/// once an error is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourcePosition(usize);
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ParseError;
 
@@ -52,49 +93,122 @@ impl<'a> Parser<'a> {
     #[must_use]
     pub fn new(source: &'a str, origin: Origin) -> Self {
         Self {
-            tokenizer: Tokenizer::new(source),
-            queued_tokens: RingBuffer::default(),
+            source,
+            tokens: Rc::new(TokenBuffer::new(source)),
+            cursor: 0,
             origin,
+            errors: vec![],
+            namespaces: Rc::default(),
+            page_rules: vec![],
         }
     }
 
-    /// Make sure that there are at least `n` more tokens in the queue
+    /// Remember the current position in the token stream
     ///
-    /// This collapses sequences of whitespace tokens, since they have no semantic meaning.
+    /// The parser can later be reset to this position using [set_state](Self::set_state).
+    #[inline]
+    #[must_use]
+    pub fn state(&self) -> ParserState {
+        ParserState {
+            cursor: self.cursor,
+        }
+    }
+
+    #[inline]
+    pub fn set_state(&mut self, state: ParserState) {
+        self.cursor = state.cursor;
+    }
+
+    /// The position of the next token in the source
     ///
-    /// The queue might not contain `n` elements afterwards if the end of file was reached.
-    fn queue_tokens(&mut self, n: usize) {
-        if n <= self.queued_tokens.len() {
-            return;
+    /// If there are no more tokens, the position of the end of the source is returned.
+    #[inline]
+    #[must_use]
+    pub fn source_position(&self) -> SourcePosition {
+        SourcePosition(
+            self.tokens
+                .position(self.cursor)
+                .unwrap_or(self.source.len()),
+        )
+    }
+
+    /// The location of the next token in the source
+    #[must_use]
+    pub fn source_location(&self) -> SourceLocation {
+        self.location_of(self.source_position())
+    }
+
+    #[must_use]
+    fn location_of(&self, position: SourcePosition) -> SourceLocation {
+        let (line, line_start) = self.tokens.line_containing(position.0);
+        let column = self.source[line_start..position.0].chars().count() + 1;
+
+        SourceLocation {
+            line: line + 1,
+            column,
         }
+    }
 
-        let mut last_token_was_whitespace = self
-            .queued_tokens
-            .peek_back(0)
-            .is_some_and(Token::is_whitespace);
+    /// Remember that a part of the source, starting at `position`, was skipped
+    pub fn report_error(&mut self, position: SourcePosition, kind: SyntaxErrorKind) {
+        let location = self.location_of(position);
+        self.errors.push(SyntaxError { location, kind });
+    }
 
-        for _ in 0..n - self.queued_tokens.len() {
-            while let Some(token) = self.tokenizer.next_token() {
-                if token.is_whitespace() {
-                    if last_token_was_whitespace {
-                        continue;
-                    } else {
-                        last_token_was_whitespace = true;
-                        self.queued_tokens.push(token);
-                        break;
-                    }
-                } else {
-                    self.queued_tokens.push(token);
-                    break;
-                }
-            }
+    /// The errors that were reported so far
+    #[must_use]
+    pub fn errors(&self) -> &[SyntaxError] {
+        &self.errors
+    }
+
+    /// The namespaces that selectors can refer to
+    #[must_use]
+    pub fn namespaces(&self) -> &Rc<Namespaces> {
+        &self.namespaces
+    }
+
+    /// The source code of all tokens between `state` and the current position
+    #[must_use]
+    fn source_since(&self, state: ParserState) -> &'a str {
+        let start = self
+            .tokens
+            .position(state.cursor)
+            .unwrap_or(self.source.len());
+        let end = self
+            .tokens
+            .position(self.cursor)
+            .unwrap_or(self.source.len());
+
+        self.source[start..end.max(start)].trim()
+    }
+
+    /// The source code from the current position up to the end of the current declaration
+    ///
+    /// This does not advance the parser.
+    #[must_use]
+    fn source_of_remaining_declaration(&self) -> &'a str {
+        let mut end = self.clone();
+        while !matches!(
+            end.peek_token(0),
+            Some(Token::Semicolon | Token::CurlyBraceClose) | None
+        ) {
+            end.cursor += 1;
+        }
+
+        end.source_since(self.state())
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek_token(0).is_some_and(Token::is_whitespace) {
+            self.cursor += 1;
         }
     }
 
     #[must_use]
     pub fn next_token(&mut self) -> Option<Token> {
-        self.queue_tokens(1);
-        self.queued_tokens.pop_front()
+        let token = self.tokens.get(self.cursor)?.clone();
+        self.cursor += 1;
+        Some(token)
     }
 
     #[must_use]
@@ -108,30 +222,17 @@ impl<'a> Parser<'a> {
     }
 
     #[must_use]
-    pub fn peek_token(&mut self, n: usize) -> Option<&Token> {
-        self.queue_tokens(n + 1);
-        self.queued_tokens.peek_front(n)
+    pub fn peek_token(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.cursor + n)
     }
 
     #[inline]
     #[must_use]
-    pub fn peek_token_ignoring_whitespace(&mut self, n: usize) -> Option<&Token> {
-        self.queue_tokens(2 * n + 2);
-
-        let mut non_whitespace_tokens_seen = 0;
-        let mut tokens_seen = 0;
-
-        while let Some(token) = self.queued_tokens.peek_front(tokens_seen) {
-            if !token.is_whitespace() {
-                if non_whitespace_tokens_seen == n {
-                    return Some(token);
-                }
-                non_whitespace_tokens_seen += 1;
-            }
-            tokens_seen += 1;
-        }
-
-        None
+    pub fn peek_token_ignoring_whitespace(&self, n: usize) -> Option<&Token> {
+        (self.cursor..self.tokens.len())
+            .filter_map(|index| self.tokens.get(index))
+            .filter(|token| !token.is_whitespace())
+            .nth(n)
     }
 
     #[inline]
@@ -204,10 +305,27 @@ impl<'a> Parser<'a> {
     ) -> Result<StyleRule, ParseError> {
         _ = mixed_with_declarations;
 
+        self.skip_whitespace();
+        let position = self.source_position();
+        let prelude_start = self.state();
+
         // Parse the rule prelude (selectors)
-        let selectors = match rule_parser.parse_qualified_rule_prelude(self) {
+        let prelude = rule_parser
+            .parse_qualified_rule_prelude(self)
+            .and_then(|selectors| {
+                self.expect_token(Token::CurlyBraceOpen)?; // FIXME: this could be a semicolon
+                Ok(selectors)
+            });
+        let selectors = match prelude {
             Ok(selectors) => selectors,
             Err(error) => {
+                self.set_state(prelude_start);
+                while !matches!(self.peek_token(0), Some(Token::CurlyBraceOpen) | None) {
+                    _ = self.next_token();
+                }
+                let selector = self.source_since(prelude_start).to_owned();
+                self.report_error(position, SyntaxErrorKind::InvalidSelector(selector));
+
                 // See <https://drafts.csswg.org/selectors-4/#invalid>
                 // Skip this rule in its entirety, its never going to match
                 while !matches!(self.next_token(), Some(Token::CurlyBraceClose) | None) {}
@@ -216,8 +334,6 @@ impl<'a> Parser<'a> {
             },
         };
 
-        self.expect_token(Token::CurlyBraceOpen)?; // FIXME: this could be a semicolon
-
         // Parse the rule block
         let properties = rule_parser.parse_qualified_rule_block(self)?;
         let qualified_rule = StyleRule::new(selectors, properties);
@@ -240,17 +356,26 @@ impl<'a> Parser<'a> {
         // NOTE: We don't construct declarations like this.
         let mut important = Important::No;
 
+        self.skip_whitespace();
+        let position = self.source_position();
+
         // 1. If the next token is an <ident-token>, consume a token from input and set decl’s name to the token’s value.
         //    Otherwise, consume the remnants of a bad declaration from input, with nested, and return nothing.
-        let declaration_name =
-            if let Some(Token::Ident(name)) = self.peek_token_ignoring_whitespace(0) {
-                let name = *name;
-                let _ = self.next_token_ignoring_whitespace();
+        let declaration_name = match self.peek_token(0) {
+            Some(Token::Ident(name)) => {
+                let name = name.clone();
+                let _ = self.next_token();
                 name
-            } else {
+            },
+            token => {
+                // Empty declarations (like in "a { color: red;; }") are allowed
+                if !matches!(token, Some(Token::Semicolon)) {
+                    self.report_error(position, SyntaxErrorKind::ExpectedPropertyName);
+                }
                 self.consume_remnants_of_bad_declaration(nested);
                 return None;
-            };
+            },
+        };
 
         // 2. Discard whitespace from input.
         // 3. If the next token is a <colon-token>, discard a token from input.
@@ -258,16 +383,42 @@ impl<'a> Parser<'a> {
         if let Some(Token::Colon) = self.peek_token_ignoring_whitespace(0) {
             let _ = self.next_token_ignoring_whitespace();
         } else {
+            self.report_error(
+                position,
+                SyntaxErrorKind::ExpectedColon {
+                    property: declaration_name,
+                },
+            );
             self.consume_remnants_of_bad_declaration(nested);
             return None;
         }
 
         // 4. Discard whitespace from input.
+        self.skip_whitespace();
+        let value_start = self.state();
+        let num_errors = self.errors.len();
 
         // NOTE: At this point we deviate from the spec because the spec gets a little silly
-        let value = if let Ok(value) = StyleProperty::parse_value(self, declaration_name) {
+        let value = if let Ok(value) = StyleProperty::parse_value(self, declaration_name.clone()) {
             value
         } else {
+            if self.errors.len() > num_errors {
+                // The property is unknown, which is reported while parsing the value.
+                // The error should point to the property name instead of its value though.
+                let location = self.location_of(position);
+                self.errors[num_errors].location = location;
+            } else {
+                self.set_state(value_start);
+                let value = self.source_of_remaining_declaration().to_owned();
+                self.report_error(
+                    position,
+                    SyntaxErrorKind::InvalidValue {
+                        property: declaration_name,
+                        value,
+                    },
+                );
+            }
+
             self.consume_remnants_of_bad_declaration(nested);
             return None;
         };
@@ -285,6 +436,12 @@ impl<'a> Parser<'a> {
                     important = Important::Yes;
                 },
                 _ => {
+                    self.report_error(
+                        position,
+                        SyntaxErrorKind::InvalidImportant {
+                            property: declaration_name,
+                        },
+                    );
                     self.consume_remnants_of_bad_declaration(nested);
                     return None;
                 },
@@ -318,29 +475,280 @@ impl<'a> Parser<'a> {
     }
 
     pub fn parse_stylesheet(&mut self, index: usize) -> Stylesheet {
+        let _span = trace::span!(Parse, "parse stylesheet");
+
         // NOTE: The ruleparser shouldn't stay a unit struct
         #[allow(clippy::default_constructed_unit_structs)]
         let mut rule_parser = RuleParser::default();
 
+        let rules = self.consume_rules(&mut rule_parser, &[], TopLevel::Yes);
+
+        if self.origin == Origin::Author {
+            for error in &self.errors {
+                log::warn!(target: "css", "{error}");
+            }
+        }
+
+        Stylesheet::new(self.origin, rules, index)
+            .with_page_rules(std::mem::take(&mut self.page_rules))
+            .with_errors(std::mem::take(&mut self.errors))
+    }
+
+    /// Consume style rules until the end of the input or (if this is not the top level)
+    /// the end of the enclosing block
+    ///
+    /// `media` contains the conditions of all the `@media` rules that the rules are nested in.
+    fn consume_rules(
+        &mut self,
+        rule_parser: &mut RuleParser,
+        media: &[Rc<MediaQueryList>],
+        top_level: TopLevel,
+    ) -> Vec<StyleRule> {
         let mut rules = vec![];
 
-        while self.peek_token_ignoring_whitespace(0).is_some() {
-            let rule =
-                match self.consume_qualified_rule(&mut rule_parser, MixedWithDeclarations::No) {
-                    Ok(rule) => rule,
-                    Err(error) => {
-                        log::debug!("Failed to parse CSS rule: {error:?}");
+        // @namespace rules must precede all other rules of the stylesheet
+        let mut may_declare_namespaces = top_level == TopLevel::Yes;
+
+        loop {
+            self.skip_whitespace();
+            let position = self.source_position();
+
+            match self.peek_token(0) {
+                None => break,
+                Some(Token::CurlyBraceClose) if top_level == TopLevel::No => break,
+                Some(Token::AtKeyword(name)) => {
+                    let name = name.clone();
+                    let _ = self.next_token();
+
+                    if name == static_interned!("namespace") {
+                        if may_declare_namespaces {
+                            self.consume_namespace_rule(position);
+                        } else {
+                            self.report_error(position, SyntaxErrorKind::MisplacedNamespaceRule);
+                            self.consume_remnants_of_at_rule();
+                        }
                         continue;
-                    },
-                };
+                    }
 
-            // There's no point in caring about empty rules, so let's drop them
-            if !rule.properties().is_empty() {
-                rules.push(rule);
+                    may_declare_namespaces = false;
+                    if name == static_interned!("media") {
+                        rules.extend(self.consume_media_rule(rule_parser, media, position));
+                    } else if name == static_interned!("page") {
+                        self.consume_page_rule(media, position);
+                    } else {
+                        self.report_error(position, SyntaxErrorKind::UnsupportedAtRule(name));
+                        self.consume_remnants_of_at_rule();
+                    }
+                },
+                Some(_) => {
+                    may_declare_namespaces = false;
+                    let rule =
+                        match self.consume_qualified_rule(rule_parser, MixedWithDeclarations::No) {
+                            Ok(rule) => rule,
+                            Err(error) => {
+                                log::debug!(
+                                    "Failed to parse CSS rule at {}: {error:?}",
+                                    self.location_of(position)
+                                );
+                                continue;
+                            },
+                        };
+
+                    // There's no point in caring about empty rules, so let's drop them
+                    if !rule.properties().is_empty() {
+                        rules.push(rule.with_media(media.to_vec()));
+                    }
+                },
             }
         }
 
-        Stylesheet::new(self.origin, rules, index)
+        rules
+    }
+
+    /// Consume the prelude of a `@namespace` rule, after the at-keyword
+    ///
+    /// <https://drafts.csswg.org/css-namespaces/#syntax>
+    fn consume_namespace_rule(&mut self, position: SourcePosition) {
+        let prelude_start = self.state();
+
+        match self.parse_namespace_declaration() {
+            Ok((prefix, namespace)) => {
+                Rc::make_mut(&mut self.namespaces).declare(prefix, namespace)
+            },
+            Err(ParseError) => {
+                self.set_state(prelude_start);
+                self.report_error(position, SyntaxErrorKind::InvalidNamespaceRule);
+                self.consume_remnants_of_at_rule();
+            },
+        }
+    }
+
+    /// `<namespace-prefix>? [ <string> | <url> ] ;`
+    fn parse_namespace_declaration(
+        &mut self,
+    ) -> Result<(Option<InternedString>, InternedString), ParseError> {
+        let prefix = if let Some(Token::Ident(prefix)) = self.peek_token_ignoring_whitespace(0) {
+            let prefix = prefix.clone();
+            _ = self.next_token_ignoring_whitespace();
+            Some(prefix)
+        } else {
+            None
+        };
+
+        let namespace =
+            if let Some(Token::String(namespace)) = self.peek_token_ignoring_whitespace(0) {
+                let namespace = namespace.clone();
+                _ = self.next_token_ignoring_whitespace();
+                namespace
+            } else {
+                self.parse::<Url>()?.value()
+            };
+
+        match self.next_token_ignoring_whitespace() {
+            Some(Token::Semicolon) | None => Ok((prefix, namespace)),
+            _ => Err(ParseError),
+        }
+    }
+
+    /// Consume the prelude and block of a `@media` rule, after the at-keyword
+    ///
+    /// <https://drafts.csswg.org/css-conditional-3/#at-media>
+    fn consume_media_rule(
+        &mut self,
+        rule_parser: &mut RuleParser,
+        media: &[Rc<MediaQueryList>],
+        position: SourcePosition,
+    ) -> Vec<StyleRule> {
+        self.skip_whitespace();
+        let prelude_start = self.state();
+
+        let media_queries = self
+            .parse::<MediaQueryList>()
+            .ok()
+            .filter(|_| self.expect_token(Token::CurlyBraceOpen).is_ok());
+        let Some(media_queries) = media_queries else {
+            self.set_state(prelude_start);
+            while !matches!(
+                self.peek_token(0),
+                Some(Token::CurlyBraceOpen | Token::Semicolon) | None
+            ) {
+                _ = self.next_token();
+            }
+            let media_query = self.source_since(prelude_start).to_owned();
+            self.report_error(position, SyntaxErrorKind::InvalidMediaQuery(media_query));
+
+            self.consume_remnants_of_at_rule();
+            return vec![];
+        };
+
+        let mut nested_media = media.to_vec();
+        nested_media.push(Rc::new(media_queries));
+        let rules = self.consume_rules(rule_parser, &nested_media, TopLevel::No);
+
+        // The block may be closed implicitly by the end of the input
+        let _ = self.expect_token(Token::CurlyBraceClose);
+
+        rules
+    }
+
+    /// Consume the prelude and block of a `@page` rule, after the at-keyword
+    ///
+    /// Every page is printed the same way, so rules with a page selector (like `@page :first`)
+    /// are ignored.
+    ///
+    /// <https://drafts.csswg.org/css-page-3/#at-page-rule>
+    fn consume_page_rule(&mut self, media: &[Rc<MediaQueryList>], position: SourcePosition) {
+        self.skip_whitespace();
+        let prelude_start = self.state();
+
+        if self.expect_token(Token::CurlyBraceOpen).is_err() {
+            self.set_state(prelude_start);
+            while !matches!(
+                self.peek_token(0),
+                Some(Token::CurlyBraceOpen | Token::Semicolon) | None
+            ) {
+                _ = self.next_token();
+            }
+            let selector = self.source_since(prelude_start).to_owned();
+            self.report_error(position, SyntaxErrorKind::UnsupportedPageSelector(selector));
+
+            self.consume_remnants_of_at_rule();
+            return;
+        }
+
+        let mut page_rule = PageRule::new(media.to_vec());
+        loop {
+            match self.peek_token_ignoring_whitespace(0) {
+                Some(Token::CurlyBraceClose) | None => break,
+                Some(Token::Ident(name)) if *name == static_interned!("size") => {
+                    self.skip_whitespace();
+                    let position = self.source_position();
+                    _ = self.next_token();
+
+                    let value_start = self.state();
+                    let size = self
+                        .expect_token(Token::Colon)
+                        .and_then(|()| self.parse::<PageSize>())
+                        .ok()
+                        .filter(|_| {
+                            matches!(
+                                self.peek_token_ignoring_whitespace(0),
+                                Some(Token::Semicolon | Token::CurlyBraceClose) | None
+                            )
+                        });
+
+                    match size {
+                        Some(size) => page_rule.set_size(size),
+                        None => {
+                            self.set_state(value_start);
+                            let value = self.source_of_remaining_declaration().to_owned();
+                            self.report_error(
+                                position,
+                                SyntaxErrorKind::InvalidValue {
+                                    property: static_interned!("size"),
+                                    value,
+                                },
+                            );
+                        },
+                    }
+
+                    // Skip the semicolon after the declaration, as well as anything that was not parsed
+                    self.consume_remnants_of_bad_declaration(false);
+                },
+                Some(_) => {
+                    // Invalid declarations are skipped up to the next semicolon by consume_declaration
+                    if let Some(declaration) = self.consume_declaration() {
+                        page_rule.push_declaration(declaration);
+                        self.consume_remnants_of_bad_declaration(false);
+                    }
+                },
+            }
+        }
+
+        // The block may be closed implicitly by the end of the input
+        let _ = self.expect_token(Token::CurlyBraceClose);
+
+        self.page_rules.push(page_rule);
+    }
+
+    /// Skip an at-rule that is not supported, up to and including its
+    /// terminating semicolon or block
+    fn consume_remnants_of_at_rule(&mut self) {
+        let mut nesting_depth = 0_usize;
+
+        while let Some(token) = self.next_token() {
+            match token {
+                Token::Semicolon if nesting_depth == 0 => break,
+                Token::CurlyBraceOpen => nesting_depth += 1,
+                Token::CurlyBraceClose => {
+                    nesting_depth = nesting_depth.saturating_sub(1);
+                    if nesting_depth == 0 {
+                        break;
+                    }
+                },
+                _ => {},
+            }
+        }
     }
 
     /// Applies a parser as often as possible, seperating individual parser calls by
@@ -385,16 +793,13 @@ impl<'a> Parser<'a> {
         F: Fn(&mut Self) -> Result<T, ParseError>,
     {
         // Remember where we were at before we parsed a list
-        let position = self.tokenizer.get_position();
-        let n_buffered_tokens = self.queued_tokens.len();
+        let state = self.state();
 
         // Apply the parser
         let parsed_token = closure(self)?;
 
         // Fail if our reader was not advanced
-        if self.tokenizer.get_position() == position
-            && self.queued_tokens.len() == n_buffered_tokens
-        {
+        if self.state() == state {
             Err(ParseError)
         } else {
             Ok(parsed_token)
@@ -414,7 +819,7 @@ impl<'a> Parser<'a> {
         F: Fn(&mut Self) -> Result<T, ParseError>,
     {
         let mut parsed_tokens = vec![];
-        let mut state_before_end_token = self.clone();
+        let mut state_before_end_token = self.state();
         let mut iterations = 0;
 
         while let Ok(parsed_value) = closure(self) {
@@ -423,14 +828,14 @@ impl<'a> Parser<'a> {
                 break;
             }
 
-            state_before_end_token = self.clone();
+            state_before_end_token = self.state();
             parsed_tokens.push(parsed_value);
 
             iterations += 1;
         }
 
         // Reset to the last valid state to avoid accidentally consuming too many tokens
-        *self = state_before_end_token;
+        self.set_state(state_before_end_token);
 
         parsed_tokens
     }
@@ -445,12 +850,12 @@ impl<'a> Parser<'a> {
     where
         F: Fn(&mut Self) -> Result<T, ParseError>,
     {
-        let state = self.clone();
+        let state = self.state();
         let x = closure(self);
         match x {
             Ok(parsed_value) => Some(parsed_value),
             Err(_) => {
-                *self = state;
+                self.set_state(state);
                 None
             },
         }
@@ -501,6 +906,20 @@ impl<'a> Parser<'a> {
         self.parse_optional_value(T::parse)
     }
 
+    /// Parse the value of a flow-relative shorthand like `margin-inline`
+    ///
+    /// Returns the values for the start and the end side.
+    pub fn parse_two_sided_property<T: CSSParse<'a> + Copy>(
+        &mut self,
+    ) -> Result<(T, T), ParseError> {
+        let start: T = self.parse()?;
+
+        // If only one value is supplied, it is used for both sides
+        let end = self.parse_optional_value(T::parse).unwrap_or(start);
+
+        Ok((start, end))
+    }
+
     pub fn parse_four_sided_property<T: CSSParse<'a> + Copy>(
         &mut self,
     ) -> Result<Sides<T>, ParseError> {
@@ -542,6 +961,12 @@ impl<'a> Parser<'a> {
     }
 }
 
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
 /// Types that can be parsed from a [Parser]
 pub trait CSSParse<'a>: Sized {
     /// Try to parse an instance of the type from CSS source code.
@@ -582,3 +1007,120 @@ impl<'a, T: CSSParse<'a> + Debug> CSSParse<'a> for Option<T> {
         Ok(parser.parse_optional_value(T::parse))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Parser, SourceLocation};
+    use crate::css::{
+        syntax::{SyntaxErrorKind, Token},
+        Origin,
+    };
+
+    #[test]
+    fn rewind_to_earlier_state() {
+        let mut parser = Parser::new("a b c", Origin::Author);
+        let state = parser.state();
+
+        assert_eq!(
+            parser.next_token_ignoring_whitespace(),
+            Some(Token::Ident("a".into()))
+        );
+        assert_eq!(
+            parser.next_token_ignoring_whitespace(),
+            Some(Token::Ident("b".into()))
+        );
+
+        parser.set_state(state);
+        assert_eq!(
+            parser.peek_token_ignoring_whitespace(2),
+            Some(&Token::Ident("c".into()))
+        );
+        assert_eq!(parser.next_token(), Some(Token::Ident("a".into())));
+    }
+
+    #[test]
+    fn report_syntax_errors() {
+        let source = "a { color: red; colour: red }\n\
+            b ! { color: red }\n\
+            c { width: 10 apples; height: 1px !importan; color red }\n\
+            @media (foo {} \n\
+            @import 'foo.css';";
+        let stylesheet = Parser::new(source, Origin::Author).parse_stylesheet(0);
+        assert_eq!(stylesheet.rules().len(), 1);
+
+        let errors: Vec<(usize, usize, String)> = stylesheet
+            .errors()
+            .iter()
+            .map(|error| {
+                (
+                    error.location.line,
+                    error.location.column,
+                    error.kind.to_string(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            errors,
+            [
+                (1, 17, "Unknown property 'colour'".to_string()),
+                (
+                    2,
+                    1,
+                    "Invalid selector 'b !', the rule is ignored".to_string()
+                ),
+                (
+                    3,
+                    5,
+                    "Invalid value '10 apples' for property 'width'".to_string()
+                ),
+                (
+                    3,
+                    23,
+                    "Expected 'important' after '!' in property 'height'".to_string()
+                ),
+                (
+                    3,
+                    46,
+                    "Expected a colon after the property name 'color'".to_string()
+                ),
+                (
+                    4,
+                    1,
+                    "Invalid media query '(foo', the rule is ignored".to_string()
+                ),
+                (5, 1, "Unsupported at-rule @import".to_string()),
+            ]
+        );
+        assert!(matches!(
+            stylesheet.errors()[0].kind,
+            SyntaxErrorKind::UnknownProperty(_)
+        ));
+    }
+
+    #[test]
+    fn source_location_of_tokens() {
+        let mut parser = Parser::new("a {\n  /* ä */ color: red;\n}", Origin::Author);
+        assert_eq!(
+            parser.source_location(),
+            SourceLocation { line: 1, column: 1 }
+        );
+
+        // Skip to the "color" identifier, comments are not part of the whitespace token
+        while parser.next_token() != Some(Token::CurlyBraceOpen) {}
+        assert_eq!(parser.next_token(), Some(Token::Whitespace));
+        assert_eq!(
+            parser.source_location(),
+            SourceLocation {
+                line: 2,
+                column: 11
+            }
+        );
+
+        while parser.next_token().is_some() {}
+        assert_eq!(
+            parser.source_location(),
+            SourceLocation { line: 3, column: 2 }
+        );
+    }
+}
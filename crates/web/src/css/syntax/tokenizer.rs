@@ -2,7 +2,7 @@ use std::num;
 
 use sl_std::chars::ReversibleCharIterator;
 
-use crate::{css::values::Number, InternedString};
+use crate::{css::values::Number, static_interned, InternedString};
 
 // characters are sort of unreadable and should
 // be referenced via their name instead
@@ -81,12 +81,7 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    /// Get the current position of the [Tokenizer].
-    ///
-    /// This can later be used to "reset" it using [set_position](Tokenizer::set_position).
-    /// Note that this causes the same source string to be retokenized a second time.
-    /// Since this method is mostly used by the [Parser](crate::parser::Parser) to parse **small** optional tokens,
-    /// this is not expected to be a problem, but should be kept in mind anyways.
+    /// Get the current byte offset of the [Tokenizer] in the source
     #[inline]
     pub fn get_position(&self) -> usize {
         self.source.position()
@@ -538,7 +533,9 @@ impl<'a> Tokenizer<'a> {
         let ident = self.consume_ident_sequence(MakeAsciiLowercase::No);
 
         // If string’s value is an ASCII case-insensitive match for "url", and the next input code point is U+0028 LEFT PARENTHESIS (()
-        if ident.to_string().eq_ignore_ascii_case("url") && self.peek_codepoint(0) == Some('(') {
+        if ident.eq_ignore_ascii_case(&static_interned!("url"))
+            && self.peek_codepoint(0) == Some('(')
+        {
             // consume it
             self.advance(1);
 
@@ -926,6 +923,89 @@ impl<'a> Tokenizer<'a> {
     }
 }
 
+/// The tokens of a source string, together with the positions they start at
+///
+/// Tokenizing the whole source ahead of time allows the [Parser](super::parser::Parser)
+/// to move back to an earlier token without tokenizing the source again.
+///
+/// Sequences of whitespace tokens are collapsed into a single token, since they
+/// have no semantic meaning.
+#[derive(Clone, Debug, Default)]
+pub struct TokenBuffer {
+    tokens: Vec<Token>,
+
+    /// The byte offset in the source of every token in [tokens](Self::tokens)
+    positions: Vec<usize>,
+
+    /// The byte offset in the source at which each line starts
+    line_starts: Vec<usize>,
+}
+
+impl TokenBuffer {
+    #[must_use]
+    pub fn new(source: &str) -> Self {
+        let mut tokenizer = Tokenizer::new(source);
+        let mut buffer = Self {
+            line_starts: std::iter::once(0)
+                .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+                .collect(),
+            ..Self::default()
+        };
+
+        loop {
+            // Comments are not part of any token, so they shouldn't affect the positions either
+            tokenizer.consume_comments();
+            let position = tokenizer.get_position();
+
+            let Some(token) = tokenizer.next_token() else {
+                break;
+            };
+
+            let last_token_was_whitespace = buffer.tokens.last().is_some_and(Token::is_whitespace);
+            if token.is_whitespace() && last_token_was_whitespace {
+                continue;
+            }
+
+            buffer.tokens.push(token);
+            buffer.positions.push(position);
+        }
+
+        buffer
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Token> {
+        self.tokens.get(index)
+    }
+
+    /// The byte offset in the source at which the token at `index` starts
+    ///
+    /// Returns `None` if there is no such token.
+    #[inline]
+    #[must_use]
+    pub fn position(&self, index: usize) -> Option<usize> {
+        self.positions.get(index).copied()
+    }
+
+    /// The zero-based index of the line that contains the given byte offset,
+    /// together with the offset at which that line starts
+    #[must_use]
+    pub fn line_containing(&self, position: usize) -> (usize, usize) {
+        let line = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= position)
+            .saturating_sub(1);
+        (line, self.line_starts.get(line).copied().unwrap_or(0))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+}
+
 impl<'a> Iterator for Tokenizer<'a> {
     type Item = Token;
 
@@ -989,7 +1069,7 @@ fn is_valid_escape(c1: Option<char>, c2: Option<char>) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::{Token, Tokenizer};
+    use super::{Token, TokenBuffer, Tokenizer};
     use crate::css::values::Number;
 
     #[test]
@@ -1013,4 +1093,29 @@ mod tests {
         );
         assert!(tokenizer.next_token().is_none());
     }
+
+    #[test]
+    fn buffer_tokens_with_positions() {
+        let source = "a /* comment */  /**/ \n{";
+        let buffer = TokenBuffer::new(source);
+
+        // The whitespace around the comments is collapsed into a single token
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(buffer.get(0), Some(&Token::Ident("a".into())));
+        assert_eq!(buffer.get(1), Some(&Token::Whitespace));
+        assert_eq!(buffer.get(2), Some(&Token::CurlyBraceOpen));
+
+        assert_eq!(buffer.position(0), Some(0));
+        assert_eq!(buffer.position(1), Some(1));
+        assert_eq!(buffer.position(2), Some(source.len() - 1));
+        assert_eq!(buffer.position(3), None);
+
+        assert_eq!(buffer.line_containing(0), (0, 0));
+        assert_eq!(buffer.line_containing(source.len() - 2), (0, 0));
+        assert_eq!(
+            buffer.line_containing(source.len() - 1),
+            (1, source.len() - 1)
+        );
+        assert_eq!(buffer.line_containing(source.len()), (1, source.len() - 1));
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    css::{syntax::Token, CSSParse, ParseError, Parser},
+    css::{serialize::serialize_float, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
     static_interned, InternedString,
 };
 
@@ -90,3 +90,10 @@ impl fmt::Debug for Angle {
         write!(f, "{}°", self.as_degrees())
     }
 }
+
+impl CSSSerialize for Angle {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_float(self.as_degrees(), dest);
+        dest.push_str("deg");
+    }
+}
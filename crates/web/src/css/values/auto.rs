@@ -1,8 +1,9 @@
 use crate::{
     css::{
+        animation::Animate,
         style::{StyleContext, ToComputedStyle},
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned,
 };
@@ -228,6 +229,22 @@ where
     }
 }
 
+impl<T> Animate for AutoOr<T>
+where
+    T: Animate,
+{
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        // "auto" is not interpolable with anything else
+        match (self, to) {
+            (Self::Auto, Self::Auto) => Some(Self::Auto),
+            (Self::NotAuto(from), Self::NotAuto(to)) => {
+                from.animate(to, progress).map(Self::NotAuto)
+            },
+            _ => None,
+        }
+    }
+}
+
 impl<T> ToComputedStyle for AutoOr<T>
 where
     T: ToComputedStyle,
@@ -241,3 +258,12 @@ where
         }
     }
 }
+
+impl<T: CSSSerialize> CSSSerialize for AutoOr<T> {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Auto => dest.push_str("auto"),
+            Self::NotAuto(value) => value.serialize_to(dest),
+        }
+    }
+}
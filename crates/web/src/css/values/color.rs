@@ -2,9 +2,10 @@
 
 use crate::{
     css::{
+        animation::Animate,
         style::{StyleContext, ToComputedStyle},
         syntax::Token,
-        CSSParse, ParseError, Parser,
+        CSSParse, CSSSerialize, ParseError, Parser,
     },
     static_interned, InternedString,
 };
@@ -772,14 +773,14 @@ impl<'a> CSSParse<'a> for Color {
         match parser.peek_token_ignoring_whitespace(0) {
             Some(Token::Hash(hash, ..)) => {
                 // TODO: should we care about the hash flag here?
-                let hash = *hash;
+                let hash = hash.clone();
                 let _ = parser.next_token_ignoring_whitespace();
 
                 Self::from_hex_color(hash)
             },
             Some(Token::Function(_)) => Self::parse_rgb_function(parser),
             Some(Token::Ident(color_name)) => {
-                let color_name = *color_name;
+                let color_name = color_name.clone();
                 let _ = parser.next_token_ignoring_whitespace();
 
                 Self::from_name(color_name)
@@ -812,6 +813,49 @@ impl From<Color> for math::Color {
     }
 }
 
+impl From<Color> for image::Rgbaf32 {
+    fn from(value: Color) -> Self {
+        let to_float = |channel: u8| f32::from(channel) / 255.;
+
+        Self::rgba(
+            to_float(value.red),
+            to_float(value.green),
+            to_float(value.blue),
+            to_float(value.alpha),
+        )
+    }
+}
+
+impl Animate for Color {
+    /// Colors are interpolated in sRGB, with premultiplied alpha
+    ///
+    /// <https://drafts.csswg.org/css-color-4/#interpolation-alpha>
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        let to_float = |channel: u8| f32::from(channel) / 255.;
+
+        let from_alpha = to_float(self.alpha);
+        let to_alpha = to_float(to.alpha);
+        let alpha = from_alpha.animate(&to_alpha, progress)?.clamp(0., 1.);
+
+        if alpha == 0. {
+            return Some(Self::rgba(0, 0, 0, 0));
+        }
+
+        let interpolate_channel = |from: u8, to: u8| {
+            let premultiplied =
+                (to_float(from) * from_alpha).animate(&(to_float(to) * to_alpha), progress)?;
+            Some(((premultiplied / alpha).clamp(0., 1.) * 255.).round() as u8)
+        };
+
+        Some(Self::rgba(
+            interpolate_channel(self.red, to.red)?,
+            interpolate_channel(self.green, to.green)?,
+            interpolate_channel(self.blue, to.blue)?,
+            (alpha * 255.).round() as u8,
+        ))
+    }
+}
+
 impl ToComputedStyle for Color {
     type Computed = Color;
 
@@ -822,10 +866,27 @@ impl ToComputedStyle for Color {
     }
 }
 
+impl CSSSerialize for Color {
+    /// <https://drafts.csswg.org/css-color-4/#serializing-sRGB-values>
+    fn serialize_to(&self, dest: &mut String) {
+        if self.alpha == u8::MAX {
+            dest.push_str(&format!("rgb({}, {}, {})", self.red, self.green, self.blue));
+        } else {
+            // The specification uses rgba() with a fractional alpha value here, but
+            // the alpha value of legacy rgba() colors is parsed as an integer.
+            // Hex colors are the only way to describe the exact alpha value.
+            dest.push_str(&format!(
+                "#{:02x}{:02x}{:02x}{:02x}",
+                self.red, self.green, self.blue, self.alpha
+            ));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Color;
-    use crate::css::CSSParse;
+    use crate::css::{animation::Animate, CSSParse};
 
     #[test]
     fn parse_color_name() {
@@ -912,4 +973,17 @@ mod tests {
             Ok(Color::rgb(255, 128, 13))
         );
     }
+    #[test]
+    fn animate_color() {
+        let red = Color::rgb(255, 0, 0);
+        let blue = Color::rgb(0, 0, 255);
+        assert_eq!(red.animate(&blue, 0.5), Some(Color::rgb(128, 0, 128)));
+
+        // The color channels of a transparent color don't influence the result
+        let transparent = Color::rgba(0, 255, 0, 0);
+        assert_eq!(
+            red.animate(&transparent, 0.5),
+            Some(Color::rgba(255, 0, 0, 128))
+        );
+    }
 }
@@ -0,0 +1,186 @@
+//! <https://drafts.csswg.org/css-easing/>
+
+use math::{EasingFunction, StepPosition};
+
+use crate::{
+    css::{serialize::serialize_float, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
+    static_interned,
+};
+
+use super::Number;
+
+impl<'a> CSSParse<'a> for EasingFunction {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let easing_function = match parser.next_token_ignoring_whitespace() {
+            Some(Token::Ident(static_interned!("linear"))) => Self::Linear,
+            Some(Token::Ident(static_interned!("ease"))) => Self::EASE,
+            Some(Token::Ident(static_interned!("ease-in"))) => Self::EASE_IN,
+            Some(Token::Ident(static_interned!("ease-out"))) => Self::EASE_OUT,
+            Some(Token::Ident(static_interned!("ease-in-out"))) => Self::EASE_IN_OUT,
+            Some(Token::Ident(static_interned!("step-start"))) => Self::STEP_START,
+            Some(Token::Ident(static_interned!("step-end"))) => Self::STEP_END,
+            Some(Token::Function(static_interned!("cubic-bezier"))) => {
+                let easing_function = parse_cubic_bezier_arguments(parser)?;
+                parser.expect_token(Token::ParenthesisClose)?;
+                easing_function
+            },
+            Some(Token::Function(static_interned!("steps"))) => {
+                let easing_function = parse_steps_arguments(parser)?;
+                parser.expect_token(Token::ParenthesisClose)?;
+                easing_function
+            },
+            _ => return Err(ParseError),
+        };
+
+        Ok(easing_function)
+    }
+}
+
+/// <https://drafts.csswg.org/css-easing/#funcdef-cubic-bezier>
+fn parse_cubic_bezier_arguments(parser: &mut Parser<'_>) -> Result<EasingFunction, ParseError> {
+    let mut arguments = [0.; 4];
+    for (index, argument) in arguments.iter_mut().enumerate() {
+        if index != 0 {
+            parser.expect_token(Token::Comma)?;
+        }
+        *argument = f32::from(parser.expect_number()?);
+    }
+
+    let [x1, y1, x2, y2] = arguments;
+
+    // The x values must be in the range [0, 1] or the definition is invalid
+    if !(0. ..=1.).contains(&x1) || !(0. ..=1.).contains(&x2) {
+        return Err(ParseError);
+    }
+
+    Ok(EasingFunction::cubic_bezier(x1, y1, x2, y2))
+}
+
+/// <https://drafts.csswg.org/css-easing/#funcdef-step-easing-function-steps>
+fn parse_steps_arguments(parser: &mut Parser<'_>) -> Result<EasingFunction, ParseError> {
+    let Number::Integer(count) = parser.expect_number()? else {
+        return Err(ParseError);
+    };
+
+    let position = parser
+        .parse_optional_value(|parser| {
+            parser.expect_token(Token::Comma)?;
+            parser.parse()
+        })
+        .unwrap_or_default();
+
+    // jump-none needs at least two steps, everything else at least one
+    let min_count = if position == StepPosition::None { 2 } else { 1 };
+    if count < min_count {
+        return Err(ParseError);
+    }
+
+    Ok(EasingFunction::Steps {
+        count: count as u32,
+        position,
+    })
+}
+
+impl<'a> CSSParse<'a> for StepPosition {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let position = match parser.expect_identifier()? {
+            static_interned!("jump-start") | static_interned!("start") => Self::Start,
+            static_interned!("jump-end") | static_interned!("end") => Self::End,
+            static_interned!("jump-none") => Self::None,
+            static_interned!("jump-both") => Self::Both,
+            _ => return Err(ParseError),
+        };
+
+        Ok(position)
+    }
+}
+
+/// <https://drafts.csswg.org/css-easing/#serialization>
+impl CSSSerialize for EasingFunction {
+    fn serialize_to(&self, dest: &mut String) {
+        let keywords = [
+            (Self::EASE, "ease"),
+            (Self::EASE_IN, "ease-in"),
+            (Self::EASE_OUT, "ease-out"),
+            (Self::EASE_IN_OUT, "ease-in-out"),
+        ];
+        if let Some((_, keyword)) = keywords.iter().find(|(function, _)| function == self) {
+            dest.push_str(keyword);
+            return;
+        }
+
+        match *self {
+            Self::Linear => dest.push_str("linear"),
+            Self::CubicBezier { x1, y1, x2, y2 } => {
+                dest.push_str("cubic-bezier(");
+                for (index, value) in [x1, y1, x2, y2].into_iter().enumerate() {
+                    if index != 0 {
+                        dest.push_str(", ");
+                    }
+                    serialize_float(value, dest);
+                }
+                dest.push(')');
+            },
+            Self::Steps { count, position } => {
+                dest.push_str(&format!("steps({count}"));
+                if position != StepPosition::End {
+                    dest.push_str(", ");
+                    position.serialize_to(dest);
+                }
+                dest.push(')');
+            },
+        }
+    }
+}
+
+impl CSSSerialize for StepPosition {
+    fn serialize_to(&self, dest: &mut String) {
+        let position = match self {
+            Self::Start => "start",
+            Self::End => "end",
+            Self::None => "jump-none",
+            Self::Both => "jump-both",
+        };
+        dest.push_str(position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_easing_function() {
+        assert_eq!(
+            EasingFunction::parse_from_str("ease-in-out"),
+            Ok(EasingFunction::EASE_IN_OUT)
+        );
+        assert_eq!(
+            EasingFunction::parse_from_str("step-start"),
+            Ok(EasingFunction::STEP_START)
+        );
+        assert_eq!(
+            EasingFunction::parse_from_str("cubic-bezier(0.1, -0.5, 0.9, 1.5)"),
+            Ok(EasingFunction::cubic_bezier(0.1, -0.5, 0.9, 1.5))
+        );
+        assert_eq!(
+            EasingFunction::parse_from_str("steps(4, jump-both)"),
+            Ok(EasingFunction::Steps {
+                count: 4,
+                position: StepPosition::Both
+            })
+        );
+        assert_eq!(
+            EasingFunction::parse_from_str("steps(2)"),
+            Ok(EasingFunction::Steps {
+                count: 2,
+                position: StepPosition::End
+            })
+        );
+
+        // x values outside of [0, 1] are invalid
+        assert!(EasingFunction::parse_from_str("cubic-bezier(2, 0, 0, 1)").is_err());
+        assert!(EasingFunction::parse_from_str("steps(1, jump-none)").is_err());
+        assert!(EasingFunction::parse_from_str("steps(0)").is_err());
+    }
+}
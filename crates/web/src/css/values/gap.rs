@@ -0,0 +1,77 @@
+//! <https://drafts.csswg.org/css-align/#gaps>
+
+use crate::{
+    css::{
+        animation::Animate,
+        style::{StyleContext, ToComputedStyle},
+        syntax::Token,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// The value of a gap property like `column-gap`
+///
+/// <https://drafts.csswg.org/css-align/#column-row-gap>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Gap<T> {
+    /// A used value of `1em` in multi-column containers and `0px` everywhere else
+    ///
+    /// <https://drafts.csswg.org/css-align/#valdef-row-gap-normal>
+    #[default]
+    Normal,
+
+    /// An explicit gap, like `10px` or `5%`
+    Length(T),
+}
+
+impl<'a, T> CSSParse<'a> for Gap<T>
+where
+    T: CSSParse<'a>,
+{
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        match parser.peek_token_ignoring_whitespace(0) {
+            Some(Token::Ident(static_interned!("normal"))) => {
+                let _ = parser.next_token_ignoring_whitespace();
+                Ok(Self::Normal)
+            },
+            _ => Ok(Self::Length(T::parse(parser)?)),
+        }
+    }
+}
+
+impl<T> Animate for Gap<T>
+where
+    T: Animate,
+{
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        match (self, to) {
+            (Self::Normal, Self::Normal) => Some(Self::Normal),
+            (Self::Length(from), Self::Length(to)) => from.animate(to, progress).map(Self::Length),
+            _ => None,
+        }
+    }
+}
+
+impl<T> ToComputedStyle for Gap<T>
+where
+    T: ToComputedStyle,
+{
+    type Computed = Gap<T::Computed>;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        match self {
+            Self::Normal => Gap::Normal,
+            Self::Length(length) => Gap::Length(length.to_computed_style(context)),
+        }
+    }
+}
+
+impl<T: CSSSerialize> CSSSerialize for Gap<T> {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Normal => dest.push_str("normal"),
+            Self::Length(length) => length.serialize_to(dest),
+        }
+    }
+}
@@ -2,14 +2,22 @@ mod angle;
 mod auto;
 mod color;
 mod counter_style;
+mod easing;
+mod gap;
 mod number;
 mod percentage;
+mod preferred_size;
+mod time;
 mod url;
 
 pub use angle::Angle;
 pub use auto::AutoOr;
 pub use color::Color;
 pub use counter_style::CounterStyle;
+pub use gap::Gap;
+pub use math::EasingFunction;
 pub use number::Number;
 pub use percentage::{Percentage, PercentageOr};
+pub use preferred_size::PreferredSize;
+pub use time::Time;
 pub use url::Url;
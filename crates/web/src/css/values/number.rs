@@ -1,6 +1,8 @@
 use std::ops;
 
-use crate::css::{syntax::Token, CSSParse, ParseError, Parser};
+use crate::css::{
+    serialize::serialize_float, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser,
+};
 
 use super::Percentage;
 
@@ -57,3 +59,12 @@ impl<'a> CSSParse<'a> for Number {
         }
     }
 }
+
+impl CSSSerialize for Number {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Integer(i) => dest.push_str(&i.to_string()),
+            Self::Number(f) => serialize_float(*f, dest),
+        }
+    }
+}
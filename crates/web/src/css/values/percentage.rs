@@ -1,9 +1,11 @@
 use std::{fmt, ops::Mul};
 
 use crate::css::{
+    animation::Animate,
+    serialize::serialize_float,
     style::{StyleContext, ToComputedStyle},
     syntax::Token,
-    CSSParse, ParseError, Parser,
+    CSSParse, CSSSerialize, ParseError, Parser,
 };
 
 use super::Number;
@@ -99,6 +101,30 @@ impl From<Number> for Percentage {
     }
 }
 
+impl Animate for Percentage {
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        self.0.animate(&to.0, progress).map(Self)
+    }
+}
+
+impl<T> Animate for PercentageOr<T>
+where
+    T: Animate,
+{
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        // FIXME: Mixing percentages and other values requires calc()
+        match (self, to) {
+            (Self::Percentage(from), Self::Percentage(to)) => {
+                from.animate(to, progress).map(Self::Percentage)
+            },
+            (Self::NotPercentage(from), Self::NotPercentage(to)) => {
+                from.animate(to, progress).map(Self::NotPercentage)
+            },
+            _ => None,
+        }
+    }
+}
+
 impl<T> ToComputedStyle for PercentageOr<T>
 where
     T: ToComputedStyle,
@@ -114,3 +140,19 @@ where
         }
     }
 }
+
+impl CSSSerialize for Percentage {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_float(self.as_fraction() * 100., dest);
+        dest.push('%');
+    }
+}
+
+impl<T: CSSSerialize> CSSSerialize for PercentageOr<T> {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Percentage(percentage) => percentage.serialize_to(dest),
+            Self::NotPercentage(value) => value.serialize_to(dest),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! <https://drafts.csswg.org/css-sizing-3/#preferred-size-properties>
+
+use crate::{
+    css::{
+        animation::Animate,
+        style::{StyleContext, ToComputedStyle},
+        syntax::Token,
+        values::AutoOr,
+        CSSParse, CSSSerialize, ParseError, Parser,
+    },
+    static_interned,
+};
+
+/// The value of a [preferred size property](https://drafts.csswg.org/css-sizing-3/#preferred-size-properties)
+/// like `width`
+///
+/// In addition to `auto` and explicit sizes, the size may be given as an
+/// [intrinsic size](https://drafts.csswg.org/css-sizing-3/#intrinsic-sizes) keyword.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PreferredSize<T> {
+    /// <https://drafts.csswg.org/css-sizing-3/#valdef-width-auto>
+    #[default]
+    Auto,
+
+    /// An explicit size, like `10px` or `50%`
+    Size(T),
+
+    /// <https://drafts.csswg.org/css-sizing-3/#valdef-width-min-content>
+    MinContent,
+
+    /// <https://drafts.csswg.org/css-sizing-3/#valdef-width-max-content>
+    MaxContent,
+
+    /// `fit-content` or `fit-content(<length-percentage>)`
+    ///
+    /// If no argument is given, the size is clamped by the available space instead.
+    ///
+    /// <https://drafts.csswg.org/css-sizing-3/#valdef-width-fit-content-length-percentage>
+    FitContent(Option<T>),
+}
+
+impl<T> PreferredSize<T> {
+    /// The explicit size, if any
+    ///
+    /// Intrinsic size keywords are treated as `auto`.
+    #[must_use]
+    pub fn size_or_auto(self) -> AutoOr<T> {
+        match self {
+            Self::Size(size) => AutoOr::NotAuto(size),
+            _ => AutoOr::Auto,
+        }
+    }
+}
+
+impl<'a, T> CSSParse<'a> for PreferredSize<T>
+where
+    T: CSSParse<'a>,
+{
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let keyword = match parser.peek_token_ignoring_whitespace(0) {
+            Some(Token::Ident(static_interned!("auto"))) => Self::Auto,
+            Some(Token::Ident(static_interned!("min-content"))) => Self::MinContent,
+            Some(Token::Ident(static_interned!("max-content"))) => Self::MaxContent,
+            Some(Token::Ident(static_interned!("fit-content"))) => Self::FitContent(None),
+            Some(Token::Function(static_interned!("fit-content"))) => {
+                let _ = parser.next_token_ignoring_whitespace();
+                let limit = T::parse(parser)?;
+                parser.expect_token(Token::ParenthesisClose)?;
+                return Ok(Self::FitContent(Some(limit)));
+            },
+            _ => return Ok(Self::Size(T::parse(parser)?)),
+        };
+
+        let _ = parser.next_token_ignoring_whitespace();
+        Ok(keyword)
+    }
+}
+
+impl<T> Animate for PreferredSize<T>
+where
+    T: Animate,
+{
+    fn animate(&self, to: &Self, progress: f32) -> Option<Self> {
+        // Only explicit sizes can be interpolated, keywords are only
+        // interpolable with themselves
+        match (self, to) {
+            (Self::Size(from), Self::Size(to)) => from.animate(to, progress).map(Self::Size),
+            (Self::FitContent(Some(from)), Self::FitContent(Some(to))) => from
+                .animate(to, progress)
+                .map(|limit| Self::FitContent(Some(limit))),
+            (Self::Auto, Self::Auto) => Some(Self::Auto),
+            (Self::MinContent, Self::MinContent) => Some(Self::MinContent),
+            (Self::MaxContent, Self::MaxContent) => Some(Self::MaxContent),
+            (Self::FitContent(None), Self::FitContent(None)) => Some(Self::FitContent(None)),
+            _ => None,
+        }
+    }
+}
+
+impl<T> ToComputedStyle for PreferredSize<T>
+where
+    T: ToComputedStyle,
+{
+    type Computed = PreferredSize<T::Computed>;
+
+    fn to_computed_style(&self, context: &StyleContext) -> Self::Computed {
+        match self {
+            Self::Auto => PreferredSize::Auto,
+            Self::Size(size) => PreferredSize::Size(size.to_computed_style(context)),
+            Self::MinContent => PreferredSize::MinContent,
+            Self::MaxContent => PreferredSize::MaxContent,
+            Self::FitContent(limit) => PreferredSize::FitContent(
+                limit.as_ref().map(|limit| limit.to_computed_style(context)),
+            ),
+        }
+    }
+}
+
+impl<T: CSSSerialize> CSSSerialize for PreferredSize<T> {
+    fn serialize_to(&self, dest: &mut String) {
+        match self {
+            Self::Auto => dest.push_str("auto"),
+            Self::Size(size) => size.serialize_to(dest),
+            Self::MinContent => dest.push_str("min-content"),
+            Self::MaxContent => dest.push_str("max-content"),
+            Self::FitContent(None) => dest.push_str("fit-content"),
+            Self::FitContent(Some(limit)) => {
+                dest.push_str("fit-content(");
+                limit.serialize_to(dest);
+                dest.push(')');
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::{layout::Pixels, style::specified::Length, values::PercentageOr};
+
+    #[test]
+    fn parse_preferred_size() {
+        type Width = PreferredSize<PercentageOr<Length>>;
+
+        assert!(matches!(
+            Width::parse_from_str("auto"),
+            Ok(PreferredSize::Auto)
+        ));
+        assert!(matches!(
+            Width::parse_from_str("min-content"),
+            Ok(PreferredSize::MinContent)
+        ));
+        assert!(matches!(
+            Width::parse_from_str("max-content"),
+            Ok(PreferredSize::MaxContent)
+        ));
+        assert!(matches!(
+            Width::parse_from_str("fit-content"),
+            Ok(PreferredSize::FitContent(None))
+        ));
+        assert!(matches!(
+            Width::parse_from_str("fit-content(50%)"),
+            Ok(PreferredSize::FitContent(Some(PercentageOr::Percentage(_))))
+        ));
+        assert!(matches!(
+            Width::parse_from_str("10px"),
+            Ok(PreferredSize::Size(PercentageOr::NotPercentage(_)))
+        ));
+        assert!(Width::parse_from_str("fit-content()").is_err());
+    }
+
+    #[test]
+    fn animate_preferred_size() {
+        let from = PreferredSize::Size(Pixels(10.));
+        let to = PreferredSize::Size(Pixels(20.));
+        assert_eq!(
+            from.animate(&to, 0.5),
+            Some(PreferredSize::Size(Pixels(15.)))
+        );
+
+        assert_eq!(from.animate(&PreferredSize::MinContent, 0.5), None);
+    }
+}
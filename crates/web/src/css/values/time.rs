@@ -0,0 +1,99 @@
+use std::fmt;
+
+use crate::{
+    css::{serialize::serialize_float, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
+    static_interned, InternedString,
+};
+
+use super::Number;
+
+/// <https://drafts.csswg.org/css-values-4/#time>
+#[derive(Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Time {
+    seconds: f32,
+}
+
+impl Time {
+    pub const ZERO: Self = Self { seconds: 0. };
+
+    #[must_use]
+    pub const fn from_seconds(seconds: f32) -> Self {
+        Self { seconds }
+    }
+
+    #[must_use]
+    pub const fn from_milliseconds(milliseconds: f32) -> Self {
+        Self {
+            seconds: milliseconds / 1000.,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_seconds(&self) -> f32 {
+        self.seconds
+    }
+
+    pub fn from_dimension(value: Number, dimension: InternedString) -> Result<Self, ParseError> {
+        let value = f32::from(value);
+
+        let time = match dimension {
+            static_interned!("s") => {
+                // https://drafts.csswg.org/css-values-4/#s
+                Self::from_seconds(value)
+            },
+            static_interned!("ms") => {
+                // https://drafts.csswg.org/css-values-4/#ms
+                Self::from_milliseconds(value)
+            },
+            _ => {
+                // Unknown time unit
+                return Err(ParseError);
+            },
+        };
+
+        Ok(time)
+    }
+}
+
+impl<'a> CSSParse<'a> for Time {
+    fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
+        let Some(Token::Dimension(value, dimension)) = parser.next_token_ignoring_whitespace()
+        else {
+            return Err(ParseError);
+        };
+
+        Self::from_dimension(value, dimension)
+    }
+}
+
+impl fmt::Debug for Time {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}s", self.seconds)
+    }
+}
+
+impl CSSSerialize for Time {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_float(self.seconds, dest);
+        dest.push('s');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_time() {
+        assert_eq!(Time::parse_from_str("2s"), Ok(Time::from_seconds(2.)));
+        assert_eq!(
+            Time::parse_from_str("150ms"),
+            Ok(Time::from_milliseconds(150.))
+        );
+        assert_eq!(Time::parse_from_str("-1s"), Ok(Time::from_seconds(-1.)));
+
+        // Unitless zero is not a valid time
+        assert!(Time::parse_from_str("0").is_err());
+        assert!(Time::parse_from_str("3px").is_err());
+    }
+}
@@ -1,11 +1,11 @@
 //! <https://drafts.csswg.org/css-values-3/#urls>
 
 use crate::{
-    css::{syntax::Token, CSSParse, ParseError, Parser},
+    css::{serialize::serialize_url, syntax::Token, CSSParse, CSSSerialize, ParseError, Parser},
     static_interned, InternedString,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Url {
     value: InternedString,
 }
@@ -13,7 +13,7 @@ pub struct Url {
 impl Url {
     #[must_use]
     pub fn value(&self) -> InternedString {
-        self.value
+        self.value.clone()
     }
 }
 
@@ -21,7 +21,7 @@ impl<'a> CSSParse<'a> for Url {
     fn parse(parser: &mut Parser<'a>) -> Result<Self, ParseError> {
         let url = match parser.next_token_ignoring_whitespace() {
             Some(Token::Uri(url)) => url,
-            Some(Token::Function(f)) if f == static_interned!("url") => {
+            Some(Token::Function(static_interned!("url"))) => {
                 let Some(Token::String(url)) = parser.next_token_ignoring_whitespace() else {
                     return Err(ParseError);
                 };
@@ -37,6 +37,12 @@ impl<'a> CSSParse<'a> for Url {
     }
 }
 
+impl CSSSerialize for Url {
+    fn serialize_to(&self, dest: &mut String) {
+        serialize_url(self.value.as_str(), dest);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,43 @@
+use std::{
+    cell::{Ref, RefCell},
+    collections::HashMap,
+    sync::Arc,
+};
+
 use dom_derive::inherit;
+use image::Texture;
 use url::URL;
 
-use super::Node;
+use super::{HtmlScriptElement, HtmlTitleElement, Node, Text};
+use crate::{
+    dom::DomPtr,
+    image_cache::{ImageError, IMAGE_CACHE},
+};
+
+/// <https://html.spec.whatwg.org/multipage/dom.html#current-document-readiness>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DocumentReadyState {
+    /// The document is still being parsed
+    Loading,
+
+    /// The document was parsed, but its subresources are still loading
+    Interactive,
+
+    #[default]
+    Complete,
+}
+
+impl DocumentReadyState {
+    /// <https://html.spec.whatwg.org/multipage/dom.html#dom-document-readystate>
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Loading => "loading",
+            Self::Interactive => "interactive",
+            Self::Complete => "complete",
+        }
+    }
+}
 
 /// <https://dom.spec.whatwg.org/#interface-document>
 #[inherit(Node)]
@@ -10,6 +46,18 @@ pub struct Document {
     url: URL,
 
     charset: String,
+
+    /// The images used by this document
+    ///
+    /// They stay alive as long as the document does, even if they are evicted
+    /// from the shared image cache.
+    images: RefCell<HashMap<URL, Arc<Texture>>>,
+
+    /// <https://html.spec.whatwg.org/multipage/dom.html#dom-document-currentscript>
+    current_script: Option<DomPtr<HtmlScriptElement>>,
+
+    /// <https://html.spec.whatwg.org/multipage/dom.html#current-document-readiness>
+    ready_state: DocumentReadyState,
 }
 
 impl Document {
@@ -25,4 +73,87 @@ impl Document {
     pub fn set_url(&mut self, url: URL) {
         self.url = url;
     }
+
+    /// The script element that is currently running, if any
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dom.html#dom-document-currentscript>
+    #[must_use]
+    pub fn current_script(&self) -> Option<DomPtr<HtmlScriptElement>> {
+        self.current_script.clone()
+    }
+
+    pub fn set_current_script(&mut self, script: Option<DomPtr<HtmlScriptElement>>) {
+        self.current_script = script;
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/dom.html#dom-document-readystate>
+    #[must_use]
+    pub fn ready_state(&self) -> DocumentReadyState {
+        self.ready_state
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/dom.html#update-the-current-document-readiness>
+    pub fn set_ready_state(&mut self, ready_state: DocumentReadyState) {
+        // 1. If document's current document readiness equals readinessValue, then return.
+        if self.ready_state == ready_state {
+            return;
+        }
+
+        // 2. Set document's current document readiness to readinessValue.
+        self.ready_state = ready_state;
+
+        // 3. - 5. Mark the navigation timing entry
+        // 6. Fire an event named readystatechange at document.
+        // FIXME: Fire the event at the document once the DOM supports event listeners
+        log::debug!("readystatechange on document ({})", ready_state.as_str());
+    }
+
+    /// Get the decoded image at the given url
+    ///
+    /// All elements in the document that refer to the same url share the same image.
+    pub(crate) fn image(&self, url: &URL) -> Result<Arc<Texture>, ImageError> {
+        if let Some(texture) = self.images.borrow().get(url) {
+            return Ok(texture.clone());
+        }
+
+        let texture = IMAGE_CACHE.get(url)?;
+        self.images
+            .borrow_mut()
+            .insert(url.clone(), texture.clone());
+        Ok(texture)
+    }
+
+    /// The images used by this document, by their url
+    pub(crate) fn images(&self) -> Ref<'_, HashMap<URL, Arc<Texture>>> {
+        self.images.borrow()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/dom.html#document.title>
+    #[must_use]
+    pub fn title(&self) -> String {
+        fn find_title_element(node: &DomPtr<Node>) -> Option<DomPtr<Node>> {
+            if node.is_a::<HtmlTitleElement>() {
+                return Some(node.clone());
+            }
+
+            node.borrow().children().iter().find_map(find_title_element)
+        }
+
+        // The title element is the first title element in the document (in tree order), if there is one
+        let Some(title_element) = self.children().iter().find_map(find_title_element) else {
+            return String::new();
+        };
+
+        // Let value be the child text content of the title element
+        let value: String = title_element
+            .borrow()
+            .children()
+            .iter()
+            .filter_map(|child| child.try_into_type::<Text>())
+            .map(|text| text.borrow().content().to_owned())
+            .collect();
+
+        // Strip and collapse ASCII whitespace in value
+        value.split_ascii_whitespace().collect::<Vec<_>>().join(" ")
+    }
 }
@@ -2,7 +2,11 @@ use std::{collections::HashMap, fmt};
 
 use dom_derive::inherit;
 
-use crate::{dom::ElementCustomState, infra::Namespace, static_interned, InternedString};
+use crate::{
+    dom::{DomPtr, ElementCustomState},
+    infra::Namespace,
+    static_interned, InternedString,
+};
 
 use super::Node;
 
@@ -15,6 +19,10 @@ struct ElementFlags(u8);
 impl ElementFlags {
     // If you add fields here, update the Debug impl below
     const HOVER: u8 = 1;
+    const FOCUS: u8 = 1 << 1;
+
+    /// The element or one of its descendants changed since the last layout pass
+    const NEEDS_LAYOUT: u8 = 1 << 2;
 
     #[inline]
     fn set(&mut self, flag: u8) {
@@ -86,7 +94,7 @@ impl Element {
     #[inline]
     #[must_use]
     pub fn local_name(&self) -> InternedString {
-        self.local_name
+        self.local_name.clone()
     }
 
     #[inline]
@@ -97,7 +105,7 @@ impl Element {
 
     #[inline]
     pub fn id(&self) -> Option<InternedString> {
-        self.attributes.get(&static_interned!("id")).copied()
+        self.attributes.get(&static_interned!("id")).cloned()
     }
 
     pub fn is_replaced(&self) -> bool {
@@ -122,14 +130,87 @@ impl Element {
     pub fn is_hovered(&self) -> bool {
         self.flags.is_set(ElementFlags::HOVER)
     }
+
+    #[inline]
+    pub fn set_focused(&mut self, focused: bool) {
+        if focused {
+            self.flags.set(ElementFlags::FOCUS)
+        } else {
+            self.flags.unset(ElementFlags::FOCUS)
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_focused(&self) -> bool {
+        self.flags.is_set(ElementFlags::FOCUS)
+    }
+
+    /// Whether the element or one of its descendants changed since the last layout pass
+    ///
+    /// Layout results of elements that don't need layout can be reused.
+    #[inline]
+    #[must_use]
+    pub fn needs_layout(&self) -> bool {
+        self.flags.is_set(ElementFlags::NEEDS_LAYOUT)
+    }
+
+    /// Called by layout once the element was laid out
+    #[inline]
+    pub(crate) fn clear_needs_layout(&mut self) {
+        self.flags.unset(ElementFlags::NEEDS_LAYOUT)
+    }
+
+    /// Mark `node` and all of its ancestors as needing layout
+    ///
+    /// This must be called whenever something about the node changes that can affect
+    /// its layout, like its contents or attributes.
+    pub fn mark_needs_layout(node: DomPtr<Node>) {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            if let Some(element) = node.try_into_type::<Element>() {
+                element.borrow_mut().flags.set(ElementFlags::NEEDS_LAYOUT);
+            }
+            current = node.borrow().parent_node();
+        }
+    }
+
+    /// Like [mark_needs_layout](Self::mark_needs_layout), but also marks all descendants of `node`
+    ///
+    /// This is necessary when the styles of the descendants might have changed too,
+    /// for example because a pseudoclass like `:hover` now matches `node`.
+    pub fn mark_subtree_needs_layout(node: DomPtr<Node>) {
+        fn mark_descendants(node: &DomPtr<Node>) {
+            if let Some(element) = node.try_into_type::<Element>() {
+                element.borrow_mut().flags.set(ElementFlags::NEEDS_LAYOUT);
+            }
+
+            for child in node.borrow().children() {
+                mark_descendants(child);
+            }
+        }
+
+        mark_descendants(&node);
+        Self::mark_needs_layout(node);
+    }
 }
 
 impl fmt::Debug for ElementFlags {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.is_set(Self::HOVER) {
-            "HOVER".fmt(f)
-        } else {
+        let flags: Vec<&str> = [
+            (Self::HOVER, "HOVER"),
+            (Self::FOCUS, "FOCUS"),
+            (Self::NEEDS_LAYOUT, "NEEDS_LAYOUT"),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| self.is_set(*flag))
+        .map(|(_, name)| name)
+        .collect();
+
+        if flags.is_empty() {
             "(empty)".fmt(f)
+        } else {
+            flags.join(" | ").fmt(f)
         }
     }
 }
@@ -0,0 +1,15 @@
+use dom_derive::inherit;
+
+use super::HtmlMediaElement;
+
+/// <https://html.spec.whatwg.org/multipage/media.html#the-audio-element>
+#[inherit(HtmlMediaElement)]
+pub struct HtmlAudioElement {}
+
+impl HtmlAudioElement {
+    pub fn new(media_element: HtmlMediaElement) -> Self {
+        Self {
+            __parent: media_element,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use dom_derive::inherit;
+use image::Texture;
+use sl_std::memory::{MemoryReport, MemoryReporter};
+
+use crate::{canvas::CanvasRenderingContext2D, static_interned, InternedString};
+
+use super::HtmlElement;
+
+/// The width of a canvas without a (valid) `width` attribute
+const DEFAULT_WIDTH: usize = 300;
+
+/// The height of a canvas without a (valid) `height` attribute
+const DEFAULT_HEIGHT: usize = 150;
+
+/// The largest width or height of a canvas, larger values are clamped
+///
+/// A bitmap of this size takes up 256MiB.
+const MAX_DIMENSION: usize = 4096;
+
+/// <https://html.spec.whatwg.org/multipage/canvas.html#the-canvas-element>
+#[inherit(HtmlElement)]
+pub struct HtmlCanvasElement {
+    /// The rendering context, created by the first call to [get_context](Self::get_context)
+    ///
+    /// FIXME: Changing the width or height attributes should reset the bitmap
+    context: Option<CanvasRenderingContext2D>,
+}
+
+impl HtmlCanvasElement {
+    pub fn new(html_element: HtmlElement) -> Self {
+        Self {
+            __parent: html_element,
+            context: None,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-canvas-width>
+    ///
+    /// The width is at most [MAX_DIMENSION].
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.dimension(static_interned!("width"), DEFAULT_WIDTH)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/canvas.html#dom-canvas-height>
+    ///
+    /// The height is at most [MAX_DIMENSION].
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.dimension(static_interned!("height"), DEFAULT_HEIGHT)
+    }
+
+    fn dimension(&self, attribute: InternedString, default: usize) -> usize {
+        // FIXME: Use the rules for parsing non-negative integers
+        self.attributes()
+            .get(&attribute)
+            .and_then(|value| value.to_string().trim().parse().ok())
+            .unwrap_or(default)
+            .min(MAX_DIMENSION)
+    }
+
+    /// Get the rendering context for the given context id
+    ///
+    /// Only the `"2d"` context is supported.
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/canvas.html#dom-canvas-getcontext)
+    pub fn get_context(&mut self, context_id: &str) -> Option<&mut CanvasRenderingContext2D> {
+        if context_id != "2d" {
+            log::warn!("Unsupported canvas context: {context_id:?}");
+            return None;
+        }
+
+        if self.context.is_none() {
+            self.context = Some(CanvasRenderingContext2D::new(self.width(), self.height()));
+        }
+
+        self.context.as_mut()
+    }
+
+    /// The current content of the canvas, or `None` if no rendering context was created yet
+    ///
+    /// A canvas without a bitmap is transparent black. The bitmap is shared, drawing to
+    /// the canvas afterwards does not change it.
+    #[must_use]
+    pub fn bitmap(&self) -> Option<Arc<Texture>> {
+        self.context
+            .as_ref()
+            .map(|context| context.bitmap().clone())
+    }
+}
+
+impl MemoryReporter for HtmlCanvasElement {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        if let Some(context) = &self.context {
+            report.record("images/canvas", context.bitmap().size_in_bytes());
+        }
+    }
+}
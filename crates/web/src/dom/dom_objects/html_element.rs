@@ -1,5 +1,7 @@
 use dom_derive::inherit;
 
+use crate::static_interned;
+
 use super::Element;
 
 /// <https://html.spec.whatwg.org/multipage/dom.html#htmlelement>
@@ -10,4 +12,18 @@ impl HtmlElement {
     pub fn new(element: Element) -> Self {
         Self { __parent: element }
     }
+
+    /// The state of the `spellcheck` attribute, or `None` if the attribute is in the default state
+    ///
+    /// <https://html.spec.whatwg.org/multipage/interaction.html#attr-spellcheck>
+    #[must_use]
+    pub fn spellcheck_attribute(&self) -> Option<bool> {
+        let value = self.attributes().get(&static_interned!("spellcheck"))?;
+
+        match value.to_string().to_ascii_lowercase().as_str() {
+            "" | "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
 }
@@ -1,6 +1,23 @@
 use dom_derive::inherit;
 
-use super::HtmlElement;
+use crate::{dom::DomPtr, file_api::File};
+
+use super::{HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, Node};
+
+/// The value of an entry in the data of a form
+///
+/// <https://xhr.spec.whatwg.org/#formdataentryvalue>
+#[derive(Clone, Debug)]
+pub enum FormDataEntryValue {
+    String(String),
+    File(File),
+}
+
+impl From<String> for FormDataEntryValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
 
 /// <https://html.spec.whatwg.org/multipage/forms.html#the-form-element>
 #[inherit(HtmlElement)]
@@ -12,4 +29,51 @@ impl HtmlFormElement {
             __parent: html_element,
         }
     }
+
+    /// The name-value pairs that would be submitted with this form, in tree order
+    ///
+    /// Currently, only `<select>`, `<textarea>` and file upload `<input>` elements contribute to the entry list.
+    ///
+    /// FIXME: This should consider the form owner of each control instead of the descendants of the form
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#constructing-the-form-data-set>
+    #[must_use]
+    pub fn entry_list(&self) -> Vec<(String, FormDataEntryValue)> {
+        fn collect_entries(node: &DomPtr<Node>, entries: &mut Vec<(String, FormDataEntryValue)>) {
+            if let Some(select) = node.try_into_type::<HtmlSelectElement>() {
+                let select_entries = select.borrow().form_entries();
+                entries.extend(
+                    select_entries
+                        .into_iter()
+                        .map(|(name, value)| (name, value.into())),
+                );
+                return;
+            }
+
+            if let Some(textarea) = node.try_into_type::<HtmlTextAreaElement>() {
+                let textarea_entries = textarea.borrow().form_entries();
+                entries.extend(
+                    textarea_entries
+                        .into_iter()
+                        .map(|(name, value)| (name, value.into())),
+                );
+                return;
+            }
+
+            if let Some(input) = node.try_into_type::<HtmlInputElement>() {
+                entries.extend(input.borrow().form_entries());
+                return;
+            }
+
+            for child in node.borrow().children() {
+                collect_entries(child, entries);
+            }
+        }
+
+        let mut entries = vec![];
+        for child in self.children() {
+            collect_entries(child, &mut entries);
+        }
+        entries
+    }
 }
@@ -1,16 +1,27 @@
+use std::sync::Arc;
+
 use dom_derive::inherit;
 use image::Texture;
-use resourceloader::RESOURCE_LOADER;
 use url::URL;
 
-use crate::static_interned;
+use crate::{
+    css::MediaEnvironment,
+    html::srcset::{self, ImageSource},
+    image_cache::IMAGE_CACHE,
+    static_interned, InternedString,
+};
 
 use super::HtmlElement;
 
 /// <https://html.spec.whatwg.org/multipage/embedded-content.html#the-img-element>
 #[inherit(HtmlElement)]
 pub struct HtmlImageElement {
-    texture: Option<Option<Texture>>,
+    texture: Option<Option<Arc<Texture>>>,
+
+    /// The image that was chosen from the `src` and `srcset` attributes, if any
+    ///
+    /// <https://html.spec.whatwg.org/multipage/images.html#img-req-data>
+    current_source: Option<ImageSource>,
 }
 
 impl HtmlImageElement {
@@ -20,57 +31,102 @@ impl HtmlImageElement {
         Self {
             __parent: html_element,
             texture: None,
+            current_source: None,
         }
     }
 
     #[must_use]
-    pub fn texture(&mut self) -> Option<&Texture> {
+    pub fn texture(&mut self) -> Option<&Arc<Texture>> {
         let loaded_texture = self
             .texture
-            .get_or_insert_with(|| load_texture_for_img_element(&self.__parent));
+            .get_or_insert_with(|| match &self.current_source {
+                Some(source) => load_image_from(&self.__parent, &source.url),
+                None => load_image(&self.__parent, static_interned!("src")),
+            });
 
         loaded_texture.as_ref()
     }
+
+    /// The number of image pixels per css pixel
+    ///
+    /// <https://html.spec.whatwg.org/multipage/images.html#current-pixel-density>
+    #[must_use]
+    pub fn current_pixel_density(&self) -> f32 {
+        self.current_source
+            .as_ref()
+            .map_or(1., |source| source.density)
+    }
+
+    /// Choose the image that best fits the viewport and pixel density
+    ///
+    /// Returns `true` if a different image was chosen, in which case it is loaded
+    /// on the next call to [texture](Self::texture).
+    ///
+    /// <https://html.spec.whatwg.org/multipage/images.html#reacting-to-environment-changes>
+    pub(crate) fn select_source(
+        &mut self,
+        environment: &MediaEnvironment,
+        device_pixel_ratio: f32,
+    ) -> bool {
+        let attribute = |name| self.attributes().get(&name).map(ToString::to_string);
+        let source = srcset::select_image_source(
+            attribute(static_interned!("src")).as_deref(),
+            attribute(static_interned!("srcset")).as_deref(),
+            attribute(static_interned!("sizes")).as_deref(),
+            environment,
+            device_pixel_ratio,
+        );
+
+        if source == self.current_source {
+            return false;
+        }
+
+        self.current_source = source;
+        self.texture = None;
+        true
+    }
 }
 
+/// Load the image that the given attribute of an element points to
+///
+/// This is used for the `src` attribute of `<img>` elements as well as for the `poster` attribute of `<video>` elements.
 #[must_use]
-fn load_texture_for_img_element(html_element: &HtmlElement) -> Option<Texture> {
-    let Some(source_url) = html_element.attributes().get(&static_interned!("src")) else {
-        log::error!("Failed to load <img> content: No \"src\" attribute found");
+pub(super) fn load_image(
+    html_element: &HtmlElement,
+    attribute: InternedString,
+) -> Option<Arc<Texture>> {
+    let element_name = html_element.local_name();
+
+    let Some(source_url) = html_element.attributes().get(&attribute) else {
+        log::error!("Failed to load <{element_name}> content: No \"{attribute}\" attribute found");
         return None;
     };
 
-    let source_url = source_url.to_string();
-
-    let source_url: URL = source_url.parse()
-        .inspect_err(|error| {
-            log::error!("Failed to load <img> content: \"src\" attribute ({source_url}) cannot be parsed as a URL ({error:?}")
-        })
-        .ok()?;
+    load_image_from(html_element, &source_url.to_string())
+}
 
-    let resource_handle = RESOURCE_LOADER.schedule_load(source_url.clone()).block();
+/// Load the image at the given url, relative to the document of the element
+#[must_use]
+fn load_image_from(html_element: &HtmlElement, source_url: &str) -> Option<Arc<Texture>> {
+    let element_name = html_element.local_name();
+    let base = html_element
+        .owning_document()
+        .map(|document| document.borrow().url().clone());
 
-    let resource = resource_handle
+    let source_url = URL::parse_with_base(source_url, base.as_ref(), None)
         .inspect_err(|error| {
-            log::error!("Failed to load <img> content: {source_url} could not be loaded ({error:?}")
+            log::error!("Failed to load <{element_name}> content: {source_url} cannot be parsed as a URL ({error:?})")
         })
         .ok()?;
 
-    if !resource.mime_metadata().computed_mime_type.is_image() {
-        log::error!(
-            "Failed to load <img> content: Expected image, found {}",
-            resource.mime_metadata().computed_mime_type
-        );
-        return None;
-    }
+    let texture = match html_element.owning_document() {
+        Some(document) => document.borrow().image(&source_url),
+        None => IMAGE_CACHE.get(&source_url),
+    };
 
-    let texture = Texture::from_bytes(&resource.data())
+    texture
         .inspect_err(|error| {
-            log::error!(
-                "Failed to load <img> content: Failed to load {source_url} as an image ({error:?})",
-            )
+            log::error!("Failed to load <{element_name}> content: {source_url} could not be loaded as an image ({error})")
         })
-        .ok()?;
-
-    Some(texture)
+        .ok()
 }
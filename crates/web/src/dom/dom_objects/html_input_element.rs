@@ -0,0 +1,355 @@
+use dom_derive::inherit;
+
+use crate::{
+    file_api::{File, FileList, FilePropertyBag},
+    static_interned,
+};
+
+use super::{html_form_element::FormDataEntryValue, HtmlElement};
+
+/// The state of the `type` attribute, which decides how the element behaves
+///
+/// <https://html.spec.whatwg.org/multipage/input.html#attr-input-type>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputType {
+    Hidden,
+    #[default]
+    Text,
+    Search,
+    Telephone,
+    Url,
+    Email,
+    Password,
+    Date,
+    Month,
+    Week,
+    Time,
+    LocalDateAndTime,
+    Number,
+    Range,
+    Color,
+    Checkbox,
+    RadioButton,
+    FileUpload,
+    SubmitButton,
+    ImageButton,
+    ResetButton,
+    Button,
+}
+
+impl InputType {
+    /// The state for a value of the `type` attribute
+    ///
+    /// Missing and invalid values map to the [Text](Self::Text) state.
+    #[must_use]
+    pub fn from_keyword(keyword: &str) -> Self {
+        match keyword.to_ascii_lowercase().as_str() {
+            "hidden" => Self::Hidden,
+            "search" => Self::Search,
+            "tel" => Self::Telephone,
+            "url" => Self::Url,
+            "email" => Self::Email,
+            "password" => Self::Password,
+            "date" => Self::Date,
+            "month" => Self::Month,
+            "week" => Self::Week,
+            "time" => Self::Time,
+            "datetime-local" => Self::LocalDateAndTime,
+            "number" => Self::Number,
+            "range" => Self::Range,
+            "color" => Self::Color,
+            "checkbox" => Self::Checkbox,
+            "radio" => Self::RadioButton,
+            "file" => Self::FileUpload,
+            "submit" => Self::SubmitButton,
+            "image" => Self::ImageButton,
+            "reset" => Self::ResetButton,
+            "button" => Self::Button,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/input.html#the-input-element>
+#[inherit(HtmlElement)]
+pub struct HtmlInputElement {
+    /// The files that were picked by the user, only used in the [FileUpload](InputType::FileUpload) state
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#concept-input-type-file-selected>
+    selected_files: FileList,
+}
+
+impl HtmlInputElement {
+    pub fn new(html_element: HtmlElement) -> Self {
+        Self {
+            __parent: html_element,
+            selected_files: FileList::default(),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/input.html#dom-input-type>
+    #[must_use]
+    pub fn input_type(&self) -> InputType {
+        self.attributes()
+            .get(&static_interned!("type"))
+            .map_or(InputType::Text, |value| {
+                InputType::from_keyword(&value.to_string())
+            })
+    }
+
+    #[must_use]
+    pub fn is_file_upload(&self) -> bool {
+        self.input_type() == InputType::FileUpload
+    }
+
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("disabled"))
+    }
+
+    /// Whether the user may pick more than one file
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#attr-input-multiple>
+    #[must_use]
+    pub fn is_multiple(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("multiple"))
+    }
+
+    /// The file types that the user should pick from, as lowercase extensions (like `.png`)
+    /// or MIME types (like `image/*` or `text/plain`)
+    ///
+    /// These are only hints for the file chooser, the user may still pick any file.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#attr-input-accept>
+    #[must_use]
+    pub fn accepted_types(&self) -> Vec<String> {
+        let Some(accept) = self.attributes().get(&static_interned!("accept")) else {
+            return vec![];
+        };
+
+        // User agents may use the value of this attribute to display a more appropriate user interface than a generic file picker.
+        // Values are separated by commas, invalid tokens are ignored.
+        accept
+            .to_string()
+            .split(',')
+            .map(|token| token.trim().to_ascii_lowercase())
+            .filter(|token| is_valid_accept_token(token))
+            .collect()
+    }
+
+    /// The files that were picked by the user, or `None` if the element is not a file upload control
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#dom-input-files>
+    #[must_use]
+    pub fn files(&self) -> Option<&FileList> {
+        self.is_file_upload().then_some(&self.selected_files)
+    }
+
+    /// Replace the selected files with the ones that were picked by the user
+    ///
+    /// Only the first file is kept unless the element allows [multiple](Self::is_multiple) files.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#update-the-file-selection>
+    pub fn update_file_selection(&mut self, mut files: Vec<File>) {
+        if !self.is_multiple() {
+            files.truncate(1);
+        }
+
+        // 1. Queue an element task on the user interaction task source given element and the following steps:
+        //    1. Update element's selected files so that it represents the user's selection.
+        self.selected_files = FileList::from(files);
+
+        //    2. Fire an event named input at the input element, with the bubbles and composed attributes initialized to true.
+        //    3. Fire an event named change at the input element, with the bubbles attribute initialized to true.
+        // FIXME: Fire these events once the DOM supports event listeners
+        log::debug!("input and change on file input");
+    }
+
+    /// The value of the element, in the [filename](https://html.spec.whatwg.org/multipage/input.html#dom-input-value-filename) mode
+    ///
+    /// For compatibility, the name of the first selected file is prefixed with a fake path.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/input.html#dom-input-value>
+    #[must_use]
+    pub fn value(&self) -> String {
+        if !self.is_file_upload() {
+            // FIXME: Support the value of the other input types
+            return String::new();
+        }
+
+        // On getting, if the element's list of selected files is not empty, return the string
+        // "C:\fakepath\" followed by the name of the first file in the list of selected files, if any,
+        // or the empty string if the list is empty.
+        self.selected_files
+            .item(0)
+            .map(|file| format!("C:\\fakepath\\{}", file.name()))
+            .unwrap_or_default()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/input.html#the-input-element:concept-form-reset-control>
+    pub fn reset(&mut self) {
+        // The reset algorithm for input elements is to set the user validity, dirty value flag, and dirty checkedness flag
+        // back to false, set the value of the element to the value of the value content attribute, if there is one,
+        // or the empty string otherwise, ...
+        // FIXME: Reset the value of the other input types
+        self.selected_files = FileList::default();
+    }
+
+    /// The name-value pairs that this element contributes to the data of its form
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#constructing-the-form-data-set>
+    #[must_use]
+    pub fn form_entries(&self) -> Vec<(String, FormDataEntryValue)> {
+        let name = match self.attributes().get(&static_interned!("name")) {
+            Some(name) if !self.is_disabled() && !name.to_string().is_empty() => name.to_string(),
+            _ => return vec![],
+        };
+
+        if !self.is_file_upload() {
+            // FIXME: Support the other input types
+            return vec![];
+        }
+
+        // Otherwise, if the field element is an input element whose type attribute is in the File Upload state, then:
+        // 1. If there are no selected files, then create an entry with name and a new File object with an empty name,
+        //    application/octet-stream as type, and an empty body, and append it to entry list.
+        if self.selected_files.is_empty() {
+            let empty_file = File::new(
+                &[],
+                "",
+                FilePropertyBag {
+                    content_type: "application/octet-stream".to_string(),
+                    ..Default::default()
+                },
+            );
+            return vec![(name, FormDataEntryValue::File(empty_file))];
+        }
+
+        // 2. Otherwise, for each file in selected files, create an entry with name and a File object
+        //    representing the file, and append it to entry list.
+        self.selected_files
+            .iter()
+            .map(|file| (name.clone(), FormDataEntryValue::File(file.clone())))
+            .collect()
+    }
+}
+
+/// Whether `token` is one of the values allowed in the `accept` attribute
+///
+/// <https://html.spec.whatwg.org/multipage/input.html#attr-input-accept>
+#[must_use]
+fn is_valid_accept_token(token: &str) -> bool {
+    match token {
+        "audio/*" | "video/*" | "image/*" => true,
+        _ if token.starts_with('.') => token.len() > 1,
+
+        // A valid MIME type string with no parameters
+        _ => token
+            .parse::<mime::MIMEType>()
+            .is_ok_and(|mime_type| mime_type.essence() == token),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{
+            dom_objects::{Document, Node},
+            DomPtr,
+        },
+        file_api::BlobPart,
+        html::{self, tokenization::IgnoreParseErrors},
+    };
+
+    fn parse_input(source: &str) -> DomPtr<HtmlInputElement> {
+        fn find_input(node: &DomPtr<Node>) -> Option<DomPtr<HtmlInputElement>> {
+            node.try_into_type()
+                .or_else(|| node.borrow().children().iter().find_map(find_input))
+        }
+
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+        find_input(&document.upcast()).expect("document should contain an input element")
+    }
+
+    fn text_file(name: &str, text: &str) -> File {
+        File::new(
+            &[BlobPart::String(text.to_string())],
+            name,
+            FilePropertyBag::default(),
+        )
+    }
+
+    #[test]
+    fn input_type() {
+        assert_eq!(
+            parse_input("<input>").borrow().input_type(),
+            InputType::Text
+        );
+        assert_eq!(
+            parse_input("<input type=FILE>").borrow().input_type(),
+            InputType::FileUpload
+        );
+        assert_eq!(
+            parse_input("<input type=unknown>").borrow().input_type(),
+            InputType::Text
+        );
+        assert!(parse_input("<input type=text>").borrow().files().is_none());
+    }
+
+    #[test]
+    fn accepted_types() {
+        let input =
+            parse_input("<input type=file accept=\"image/*, .PDF,text/html;x=y, invalid,.\">");
+        assert_eq!(input.borrow().accepted_types(), ["image/*", ".pdf"]);
+    }
+
+    #[test]
+    fn selected_files() {
+        let input = parse_input("<input type=file name=upload>");
+        let mut input = input.borrow_mut();
+
+        // Forms submit an empty file if nothing was selected
+        let entries = input.form_entries();
+        assert_eq!(entries.len(), 1);
+        let FormDataEntryValue::File(file) = &entries[0].1 else {
+            panic!("file inputs should submit files");
+        };
+        assert_eq!(file.name(), "");
+        assert_eq!(file.content_type(), "application/octet-stream");
+
+        // Without the multiple attribute, only the first file is selected
+        input.update_file_selection(vec![text_file("a.txt", "a"), text_file("b.txt", "b")]);
+        assert_eq!(input.files().unwrap().length(), 1);
+        assert_eq!(input.value(), "C:\\fakepath\\a.txt");
+
+        input.reset();
+        assert!(input.files().unwrap().is_empty());
+        assert_eq!(input.value(), "");
+    }
+
+    #[test]
+    fn multiple_files() {
+        let input = parse_input("<input type=file name=upload multiple>");
+        let mut input = input.borrow_mut();
+
+        input.update_file_selection(vec![text_file("a.txt", "a"), text_file("b.txt", "b")]);
+        let names: Vec<_> = input
+            .form_entries()
+            .into_iter()
+            .map(|(name, value)| match value {
+                FormDataEntryValue::File(file) => format!("{name}={}", file.name()),
+                FormDataEntryValue::String(value) => format!("{name}={value}"),
+            })
+            .collect();
+        assert_eq!(names, ["upload=a.txt", "upload=b.txt"]);
+    }
+}
@@ -25,6 +25,27 @@ impl HtmlLinkElement {
             .unwrap_or(links::Relationship::Invalid)
     }
 
+    /// The value of the `integrity` attribute, or an empty string if there is none
+    ///
+    /// <https://html.spec.whatwg.org/multipage/semantics.html#attr-link-integrity>
+    #[must_use]
+    pub fn integrity(&self) -> String {
+        self.attributes()
+            .get(&static_interned!("integrity"))
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    }
+
+    /// The kind of resource that is preloaded, given by the `as` attribute
+    ///
+    /// <https://html.spec.whatwg.org/multipage/semantics.html#attr-link-as>
+    #[must_use]
+    pub fn destination(&self) -> Option<links::Destination> {
+        self.attributes()
+            .get(&static_interned!("as"))
+            .and_then(|value| links::Destination::from_as_attribute(&value.to_string()))
+    }
+
     #[must_use]
     pub fn url(&self) -> Option<URL> {
         let document = self.owning_document().expect("must have a document");
@@ -0,0 +1,545 @@
+use std::time::Duration;
+
+use dom_derive::inherit;
+use image::Texture;
+use resourceloader::{Priority, RESOURCE_LOADER};
+use url::URL;
+
+use crate::{
+    media::{
+        self, MediaErrorCode, MediaEvent, MediaMetadata, MediaPlayer, NetworkState, ReadyState,
+    },
+    static_interned,
+};
+
+use super::{Element, HtmlElement};
+
+/// The common base of `<audio>` and `<video>` elements
+///
+/// <https://html.spec.whatwg.org/multipage/media.html#htmlmediaelement>
+#[inherit(HtmlElement)]
+pub struct HtmlMediaElement {
+    network_state: NetworkState,
+    ready_state: ReadyState,
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-paused>
+    is_paused: bool,
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-error>
+    error: Option<MediaErrorCode>,
+    metadata: Option<MediaMetadata>,
+
+    /// The backend that decodes the current resource, if there is one
+    player: Option<Box<dyn MediaPlayer>>,
+
+    /// Whether the resource selection algorithm was run at least once
+    ///
+    /// The spec runs it when the element is inserted into the document, but the `src`
+    /// attribute is only set after the element was created. Instead, a resource is
+    /// selected the first time that the element is laid out.
+    has_selected_resource: bool,
+
+    /// Events that were fired at the element but not dispatched yet
+    pending_events: Vec<MediaEvent>,
+}
+
+impl HtmlMediaElement {
+    pub fn new(html_element: HtmlElement) -> Self {
+        Self {
+            __parent: html_element,
+            network_state: NetworkState::Empty,
+            ready_state: ReadyState::HaveNothing,
+            is_paused: true,
+            error: None,
+            metadata: None,
+            player: None,
+            has_selected_resource: false,
+            pending_events: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn network_state(&self) -> NetworkState {
+        self.network_state
+    }
+
+    #[must_use]
+    pub fn ready_state(&self) -> ReadyState {
+        self.ready_state
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    #[must_use]
+    pub fn error(&self) -> Option<MediaErrorCode> {
+        self.error
+    }
+
+    /// The length of the resource, `None` if it is not known (yet)
+    ///
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-duration>
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        self.metadata.and_then(|metadata| metadata.duration)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-currenttime>
+    #[must_use]
+    pub fn current_time(&self) -> Duration {
+        self.player
+            .as_ref()
+            .map_or(Duration::ZERO, |player| player.current_time())
+    }
+
+    /// The size of the video frames in pixels, if the resource contains a video
+    #[must_use]
+    pub fn video_size(&self) -> Option<(usize, usize)> {
+        self.metadata.and_then(|metadata| metadata.video_size)
+    }
+
+    /// The video frame at the current playback position
+    #[must_use]
+    pub fn current_frame(&self) -> Option<Texture> {
+        if self.ready_state < ReadyState::HaveCurrentData {
+            return None;
+        }
+
+        self.player.as_ref()?.current_frame()
+    }
+
+    /// The events that were fired at this element since the last call
+    pub fn take_events(&mut self) -> Vec<MediaEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    fn queue_event(&mut self, event: MediaEvent) {
+        self.pending_events.push(event);
+    }
+
+    /// Select a resource, unless that already happened before
+    pub fn ensure_resource_selected(&mut self) {
+        if !self.has_selected_resource {
+            self.select_resource();
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-load>
+    pub fn load(&mut self) {
+        // https://html.spec.whatwg.org/multipage/media.html#media-element-load-algorithm
+        // 4. If the media element's networkState is set to NETWORK_LOADING or NETWORK_IDLE,
+        //    queue a media element task given the media element to fire an event named abort at the media element.
+        if matches!(
+            self.network_state,
+            NetworkState::Loading | NetworkState::Idle
+        ) {
+            self.queue_event(MediaEvent::Abort);
+        }
+
+        // 5. If the media element's networkState is not set to NETWORK_EMPTY, then:
+        if self.network_state != NetworkState::Empty {
+            // 1. Queue a media element task given the media element to fire an event named emptied at the media element.
+            self.queue_event(MediaEvent::Emptied);
+
+            // 2. If a fetching process is in progress for the media element, the user agent should stop it.
+            self.player = None;
+            self.metadata = None;
+
+            // 3. Set the networkState attribute to NETWORK_EMPTY.
+            self.network_state = NetworkState::Empty;
+
+            // 6. If readyState is not set to HAVE_NOTHING, then set it to that state.
+            self.ready_state = ReadyState::HaveNothing;
+
+            // 7. If the paused attribute is false, then set it to true.
+            self.is_paused = true;
+        }
+
+        // 8. Set the error attribute to null.
+        self.error = None;
+
+        // 10. Invoke the media element's resource selection algorithm.
+        self.select_resource();
+    }
+
+    /// The URL of the first resource that the element could play
+    ///
+    /// This is either the `src` attribute of the element or of its first `<source>` child.
+    #[must_use]
+    fn source(&self) -> Option<String> {
+        if let Some(src) = self.attributes().get(&static_interned!("src")) {
+            return Some(src.to_string());
+        }
+
+        // FIXME: Try the other <source> elements if the first one cannot be played
+        self.children()
+            .iter()
+            .filter_map(|child| child.try_into_type::<Element>())
+            .filter(|child| child.borrow().local_name() == static_interned!("source"))
+            .find_map(|source| {
+                source
+                    .borrow()
+                    .attributes()
+                    .get(&static_interned!("src"))
+                    .map(ToString::to_string)
+            })
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#concept-media-load-algorithm>
+    fn select_resource(&mut self) {
+        self.has_selected_resource = true;
+
+        // 1. Set the element's networkState attribute to the NETWORK_NO_SOURCE value.
+        self.network_state = NetworkState::NoSource;
+
+        // 6. If the media element has an assigned media provider object, a src attribute or a source element child, then
+        //    the mode is object, attribute or children. Otherwise, the media element has no assigned media provider object
+        //    and has neither a src attribute nor a source element child:
+        let Some(source) = self.source() else {
+            // set the networkState to NETWORK_EMPTY and return
+            self.network_state = NetworkState::Empty;
+            return;
+        };
+
+        // 7. Set the media element's networkState to NETWORK_LOADING.
+        self.network_state = NetworkState::Loading;
+
+        // 8. Queue a media element task given the media element to fire an event named loadstart at the media element.
+        self.queue_event(MediaEvent::LoadStart);
+
+        let document = self.owning_document();
+        let base = document
+            .as_ref()
+            .map(|document| document.borrow().url().clone());
+        let Ok(url) = URL::parse_with_base(&source, base.as_ref(), None) else {
+            log::error!("Failed to load media: {source:?} cannot be parsed as a URL");
+            self.fail_to_select_source();
+            return;
+        };
+
+        // FIXME: Fetch the resource in the background, instead of blocking until the whole resource is loaded
+        let resource = match RESOURCE_LOADER
+            .schedule_load_with_priority(url.clone(), Priority::Normal)
+            .block()
+        {
+            Ok(resource) => resource,
+            Err(error) => {
+                log::error!("Failed to load media: {url} could not be loaded ({error:?})");
+                self.fail_to_select_source();
+                return;
+            },
+        };
+
+        let mime_type = resource
+            .mime_metadata()
+            .computed_mime_type_in(mime::SniffingContext::AudioOrVideo);
+        self.load_resource(mime_type.as_ref(), resource.data());
+    }
+
+    /// Hand the data of the selected resource to a backend that can play it
+    fn load_resource(&mut self, mime_type: Option<&mime::MIMEType>, data: &[u8]) {
+        let Some(mut player) = mime_type.and_then(media::create_player) else {
+            log::warn!("Failed to load media: There is no backend that can play {mime_type:?}");
+            self.fail_to_select_source();
+            return;
+        };
+
+        let Ok(metadata) = player.load(data) else {
+            log::error!("Failed to load media: The resource could not be decoded");
+            self.fail_to_select_source();
+            return;
+        };
+
+        // https://html.spec.whatwg.org/multipage/media.html#getting-media-metadata
+        self.metadata = Some(metadata);
+        self.player = Some(player);
+        self.ready_state = ReadyState::HaveMetadata;
+        self.queue_event(MediaEvent::DurationChange);
+        self.queue_event(MediaEvent::LoadedMetadata);
+
+        // The whole resource is available, so playback can go on without interruption
+        self.set_ready_state(ReadyState::HaveEnoughData);
+
+        // Once the entire media resource has been fetched, fire suspend and switch to NETWORK_IDLE
+        self.network_state = NetworkState::Idle;
+        self.queue_event(MediaEvent::Suspend);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dedicated-media-source-failure-steps>
+    fn fail_to_select_source(&mut self) {
+        // 1. Set the error attribute to the result of creating a MediaError with MEDIA_ERR_SRC_NOT_SUPPORTED.
+        self.error = Some(MediaErrorCode::SourceNotSupported);
+
+        // 3. Set the element's networkState attribute to the NETWORK_NO_SOURCE value.
+        self.network_state = NetworkState::NoSource;
+
+        // 5. Fire an event named error at the media element.
+        self.queue_event(MediaEvent::Error);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#ready-states>
+    fn set_ready_state(&mut self, ready_state: ReadyState) {
+        let previous_ready_state = self.ready_state;
+        self.ready_state = ready_state;
+
+        if previous_ready_state < ReadyState::HaveCurrentData
+            && ready_state >= ReadyState::HaveCurrentData
+        {
+            self.queue_event(MediaEvent::LoadedData);
+        }
+
+        if previous_ready_state < ReadyState::HaveFutureData
+            && ready_state >= ReadyState::HaveFutureData
+        {
+            self.queue_event(MediaEvent::CanPlay);
+
+            if !self.is_paused {
+                self.notify_about_playing();
+            }
+        }
+
+        if ready_state == ReadyState::HaveEnoughData {
+            // If the autoplaying flag is true and the element has an autoplay attribute,
+            // playback starts by itself
+            let autoplay = self
+                .attributes()
+                .contains_key(&static_interned!("autoplay"));
+            if self.is_paused && autoplay {
+                self.is_paused = false;
+                self.queue_event(MediaEvent::Play);
+                self.notify_about_playing();
+            }
+
+            self.queue_event(MediaEvent::CanPlayThrough);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#notify-about-playing>
+    fn notify_about_playing(&mut self) {
+        self.queue_event(MediaEvent::Playing);
+
+        if let Some(player) = &mut self.player {
+            player.play();
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-play>
+    pub fn play(&mut self) {
+        // 2. If the media element's error attribute is not null and its code is MEDIA_ERR_SRC_NOT_SUPPORTED,
+        //    return a promise rejected with a "NotSupportedError" DOMException.
+        if self.error == Some(MediaErrorCode::SourceNotSupported) {
+            return;
+        }
+
+        // https://html.spec.whatwg.org/multipage/media.html#internal-play-steps
+        // 1. If the media element's networkState attribute has the value NETWORK_EMPTY,
+        //    invoke the media element's resource selection algorithm.
+        if self.network_state == NetworkState::Empty {
+            self.select_resource();
+        }
+
+        // 4. If the media element's paused attribute is true, then:
+        if self.is_paused {
+            // 1. Change the value of paused to false.
+            self.is_paused = false;
+
+            // 3. Queue a media element task given the media element to fire an event named play at the element.
+            self.queue_event(MediaEvent::Play);
+
+            // 4. If the media element's readyState attribute has the value HAVE_NOTHING, HAVE_METADATA, or HAVE_CURRENT_DATA,
+            //    queue a media element task given the media element to fire an event named waiting at the element.
+            //    Otherwise, the media element's readyState attribute has the value HAVE_FUTURE_DATA or HAVE_ENOUGH_DATA:
+            //    notify about playing the media element.
+            if self.ready_state <= ReadyState::HaveCurrentData {
+                self.queue_event(MediaEvent::Waiting);
+            } else {
+                self.notify_about_playing();
+            }
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#dom-media-pause>
+    pub fn pause(&mut self) {
+        // 1. If the media element's networkState attribute has the value NETWORK_EMPTY,
+        //    invoke the media element's resource selection algorithm.
+        if self.network_state == NetworkState::Empty {
+            self.select_resource();
+        }
+
+        // https://html.spec.whatwg.org/multipage/media.html#internal-pause-steps
+        // 3. If the media element's paused attribute is false, run the following steps:
+        if !self.is_paused {
+            // 1. Change the value of paused to true.
+            self.is_paused = true;
+
+            // 3. Queue a media element task given the media element to fire an event named pause at the element.
+            self.queue_event(MediaEvent::Pause);
+
+            if let Some(player) = &mut self.player {
+                player.pause();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{
+            dom_objects::{Document, Node},
+            DomPtr,
+        },
+        html::{self, tokenization::IgnoreParseErrors},
+        media::DecodeError,
+    };
+
+    /// Pretends to decode a ten second long video
+    struct FakePlayer {
+        is_playing: bool,
+    }
+
+    impl MediaPlayer for FakePlayer {
+        fn load(&mut self, data: &[u8]) -> Result<MediaMetadata, DecodeError> {
+            if data != b"video" {
+                return Err(DecodeError);
+            }
+
+            Ok(MediaMetadata {
+                duration: Some(Duration::from_secs(10)),
+                video_size: Some((320, 240)),
+            })
+        }
+
+        fn play(&mut self) {
+            self.is_playing = true;
+        }
+
+        fn pause(&mut self) {
+            self.is_playing = false;
+        }
+
+        fn seek(&mut self, _position: Duration) {}
+
+        fn current_time(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        fn current_frame(&self) -> Option<Texture> {
+            Some(Texture::new(320, 240))
+        }
+
+        fn has_ended(&self) -> bool {
+            false
+        }
+    }
+
+    fn fake_backend(mime_type: &mime::MIMEType) -> Option<Box<dyn MediaPlayer>> {
+        (mime_type.essence() == "video/x-stormlicht-test")
+            .then(|| Box::new(FakePlayer { is_playing: false }) as Box<dyn MediaPlayer>)
+    }
+
+    fn parse_media_element(source: &str) -> DomPtr<HtmlMediaElement> {
+        fn find_media_element(node: &DomPtr<Node>) -> Option<DomPtr<HtmlMediaElement>> {
+            node.try_into_type()
+                .or_else(|| node.borrow().children().iter().find_map(find_media_element))
+        }
+
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+        find_media_element(&document.upcast()).expect("document should contain a media element")
+    }
+
+    #[test]
+    fn play_and_pause_without_source() {
+        let video = parse_media_element("<video></video>");
+        let mut video = video.borrow_mut();
+
+        assert_eq!(video.network_state(), NetworkState::Empty);
+        assert_eq!(video.ready_state(), ReadyState::HaveNothing);
+        assert!(video.is_paused());
+
+        video.play();
+        video.play();
+        video.pause();
+
+        assert_eq!(video.network_state(), NetworkState::Empty);
+        assert_eq!(
+            video.take_events(),
+            [MediaEvent::Play, MediaEvent::Waiting, MediaEvent::Pause]
+        );
+    }
+
+    #[test]
+    fn unsupported_source() {
+        let audio = parse_media_element("<audio><source src='data:audio/x-unknown,abc'></audio>");
+        let mut audio = audio.borrow_mut();
+        audio.ensure_resource_selected();
+
+        assert_eq!(audio.network_state(), NetworkState::NoSource);
+        assert_eq!(audio.error(), Some(MediaErrorCode::SourceNotSupported));
+        assert_eq!(
+            audio.take_events(),
+            [MediaEvent::LoadStart, MediaEvent::Error]
+        );
+
+        // Playing a resource that is not supported does nothing
+        audio.play();
+        assert!(audio.is_paused());
+        assert!(audio.take_events().is_empty());
+    }
+
+    #[test]
+    fn load_with_backend() {
+        media::register_backend(fake_backend);
+        let mime_type: mime::MIMEType = "video/x-stormlicht-test".parse().unwrap();
+
+        let video = parse_media_element("<video autoplay></video>");
+        let mut video = video.borrow_mut();
+
+        // Data that the backend does not understand
+        video.load_resource(Some(&mime_type), b"audio");
+        assert_eq!(video.error(), Some(MediaErrorCode::SourceNotSupported));
+        assert_eq!(video.take_events(), [MediaEvent::Error]);
+
+        video.load_resource(Some(&mime_type), b"video");
+        assert_eq!(video.network_state(), NetworkState::Idle);
+        assert_eq!(video.ready_state(), ReadyState::HaveEnoughData);
+        assert_eq!(video.duration(), Some(Duration::from_secs(10)));
+        assert_eq!(video.video_size(), Some((320, 240)));
+        assert!(video.current_frame().is_some());
+        assert!(!video.is_paused());
+        assert_eq!(
+            video.take_events(),
+            [
+                MediaEvent::DurationChange,
+                MediaEvent::LoadedMetadata,
+                MediaEvent::LoadedData,
+                MediaEvent::CanPlay,
+                MediaEvent::Play,
+                MediaEvent::Playing,
+                MediaEvent::CanPlayThrough,
+                MediaEvent::Suspend,
+            ]
+        );
+
+        // Reloading drops the resource, and there is no source to select a new one from
+        video.pause();
+        video.load();
+        assert!(video.is_paused());
+        assert_eq!(video.network_state(), NetworkState::Empty);
+        assert_eq!(video.ready_state(), ReadyState::HaveNothing);
+        assert_eq!(video.duration(), None);
+        assert_eq!(
+            video.take_events(),
+            [MediaEvent::Pause, MediaEvent::Abort, MediaEvent::Emptied]
+        );
+    }
+}
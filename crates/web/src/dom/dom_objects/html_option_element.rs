@@ -0,0 +1,120 @@
+use dom_derive::inherit;
+
+use crate::{dom::DomPtr, static_interned};
+
+use super::{Element, HtmlElement, HtmlSelectElement, Node, Text};
+
+/// <https://html.spec.whatwg.org/multipage/form-elements.html#the-option-element>
+#[inherit(HtmlElement)]
+pub struct HtmlOptionElement {
+    /// The selectedness of the option, once it was changed by the user
+    ///
+    /// Until then, the option is selected if it has a `selected` attribute. This
+    /// is what the spec calls the [dirtiness](https://html.spec.whatwg.org/multipage/form-elements.html#concept-option-dirtiness)
+    /// of the option.
+    selectedness: Option<bool>,
+}
+
+impl HtmlOptionElement {
+    pub fn new(html_element: HtmlElement) -> Self {
+        Self {
+            __parent: html_element,
+            selectedness: None,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-option-selectedness>
+    #[must_use]
+    pub fn selectedness(&self) -> bool {
+        self.selectedness.unwrap_or_else(|| {
+            self.attributes()
+                .contains_key(&static_interned!("selected"))
+        })
+    }
+
+    pub fn set_selectedness(&mut self, selectedness: bool) {
+        self.selectedness = Some(selectedness);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-option-disabled>
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        if self
+            .attributes()
+            .contains_key(&static_interned!("disabled"))
+        {
+            return true;
+        }
+
+        // Options inside a disabled optgroup are disabled too
+        self.parent_node()
+            .and_then(|parent| parent.try_into_type::<Element>())
+            .is_some_and(|parent| {
+                let parent = parent.borrow();
+                parent.local_name() == static_interned!("optgroup")
+                    && parent
+                        .attributes()
+                        .contains_key(&static_interned!("disabled"))
+            })
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-option-text>
+    #[must_use]
+    pub fn text(&self) -> String {
+        fn collect_text(node: &DomPtr<Node>, text: &mut String) {
+            if let Some(text_node) = node.try_into_type::<Text>() {
+                text.push_str(text_node.borrow().content());
+            }
+
+            for child in node.borrow().children() {
+                collect_text(child, text);
+            }
+        }
+
+        let mut text = String::new();
+        for child in self.children() {
+            collect_text(child, &mut text);
+        }
+
+        // Strip and collapse ASCII whitespace
+        text.split_ascii_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-option-label>
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self.attributes().get(&static_interned!("label")) {
+            Some(label) if !label.to_string().is_empty() => label.to_string(),
+            _ => self.text(),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-option-value>
+    #[must_use]
+    pub fn value(&self) -> String {
+        match self.attributes().get(&static_interned!("value")) {
+            Some(value) => value.to_string(),
+            None => self.text(),
+        }
+    }
+
+    /// The `<select>` element that this option belongs to, if any
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-select-option-list>
+    #[must_use]
+    pub fn select_element(&self) -> Option<DomPtr<HtmlSelectElement>> {
+        let parent = self.parent_node()?;
+        if parent.is_a::<HtmlSelectElement>() {
+            return parent.try_into_type();
+        }
+
+        let is_in_optgroup = parent
+            .try_into_type::<Element>()
+            .is_some_and(|parent| parent.borrow().local_name() == static_interned!("optgroup"));
+        if !is_in_optgroup {
+            return None;
+        }
+
+        parent.borrow().parent_node()?.try_into_type()
+    }
+}
@@ -1,15 +1,129 @@
 use dom_derive::inherit;
 
-use super::HtmlElement;
+use crate::static_interned;
+
+use super::{HtmlElement, Text};
 
 /// <https://html.spec.whatwg.org/multipage/scripting.html#the-script-element>
 #[inherit(HtmlElement)]
-pub struct HtmlScriptElement {}
+pub struct HtmlScriptElement {
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#already-started>
+    already_started: bool,
+
+    /// Whether the element was inserted by the parser, instead of a script
+    ///
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#parser-document>
+    is_parser_inserted: bool,
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#script-force-async>
+    force_async: bool,
+}
 
 impl HtmlScriptElement {
     pub fn new(html_element: HtmlElement) -> Self {
         Self {
             __parent: html_element,
+            already_started: false,
+            is_parser_inserted: false,
+            force_async: true,
         }
     }
+
+    /// The value of the `integrity` attribute, or an empty string if there is none
+    ///
+    /// Fetched scripts must match this metadata before they may be executed, see
+    /// [crate::integrity::bytes_match_metadata].
+    ///
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#attr-script-integrity>
+    #[must_use]
+    pub fn integrity(&self) -> String {
+        self.attributes()
+            .get(&static_interned!("integrity"))
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn already_started(&self) -> bool {
+        self.already_started
+    }
+
+    pub fn set_already_started(&mut self) {
+        self.already_started = true;
+    }
+
+    #[must_use]
+    pub fn is_parser_inserted(&self) -> bool {
+        self.is_parser_inserted
+    }
+
+    /// Called by the parser when it creates the element
+    ///
+    /// Parser-inserted scripts are not async unless they have an `async` attribute.
+    pub fn set_parser_inserted(&mut self) {
+        self.is_parser_inserted = true;
+        self.force_async = false;
+    }
+
+    #[must_use]
+    pub fn force_async(&self) -> bool {
+        self.force_async
+    }
+
+    /// The value of the `src` attribute, if any
+    ///
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#attr-script-src>
+    #[must_use]
+    pub fn src(&self) -> Option<String> {
+        self.attributes()
+            .get(&static_interned!("src"))
+            .map(ToString::to_string)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#attr-script-type>
+    #[must_use]
+    pub fn type_attribute(&self) -> Option<String> {
+        self.attributes()
+            .get(&static_interned!("type"))
+            .map(ToString::to_string)
+    }
+
+    /// The obsolete `language` attribute, which is still used to determine the type of the script
+    #[must_use]
+    pub fn language_attribute(&self) -> Option<String> {
+        self.attributes()
+            .get(&static_interned!("language"))
+            .map(ToString::to_string)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#attr-script-async>
+    #[must_use]
+    pub fn has_async_attribute(&self) -> bool {
+        self.attributes().contains_key(&static_interned!("async"))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#attr-script-defer>
+    #[must_use]
+    pub fn has_defer_attribute(&self) -> bool {
+        self.attributes().contains_key(&static_interned!("defer"))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#attr-script-nomodule>
+    #[must_use]
+    pub fn has_nomodule_attribute(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("nomodule"))
+    }
+
+    /// The inline source of the script
+    ///
+    /// <https://dom.spec.whatwg.org/#concept-child-text-content>
+    #[must_use]
+    pub fn child_text_content(&self) -> String {
+        self.children()
+            .iter()
+            .filter_map(|child| child.try_into_type::<Text>())
+            .map(|text| text.borrow().content().to_owned())
+            .collect()
+    }
 }
@@ -0,0 +1,284 @@
+use dom_derive::inherit;
+
+use crate::{dom::DomPtr, static_interned};
+
+use super::{Element, HtmlElement, HtmlOptionElement};
+
+/// <https://html.spec.whatwg.org/multipage/form-elements.html#the-select-element>
+#[inherit(HtmlElement)]
+pub struct HtmlSelectElement {}
+
+impl HtmlSelectElement {
+    pub fn new(html_element: HtmlElement) -> Self {
+        Self {
+            __parent: html_element,
+        }
+    }
+
+    #[must_use]
+    pub fn is_multiple(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("multiple"))
+    }
+
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("disabled"))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-select-size>
+    #[must_use]
+    pub fn display_size(&self) -> usize {
+        // FIXME: Use the rules for parsing non-negative integers
+        let size = self
+            .attributes()
+            .get(&static_interned!("size"))
+            .and_then(|value| value.to_string().trim().parse().ok())
+            .filter(|&size: &usize| size != 0);
+
+        match size {
+            Some(size) => size,
+            None if self.is_multiple() => 4,
+            None => 1,
+        }
+    }
+
+    /// Whether the element is displayed as a drop-down box instead of a list box
+    ///
+    /// <https://html.spec.whatwg.org/multipage/rendering.html#the-select-element-2>
+    #[must_use]
+    pub fn is_drop_down_box(&self) -> bool {
+        !self.is_multiple() && self.display_size() == 1
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-select-option-list>
+    #[must_use]
+    pub fn list_of_options(&self) -> Vec<DomPtr<HtmlOptionElement>> {
+        let mut options = vec![];
+
+        for child in self.children() {
+            if let Some(option) = child.try_into_type::<HtmlOptionElement>() {
+                options.push(option);
+                continue;
+            }
+
+            let is_optgroup = child
+                .try_into_type::<Element>()
+                .is_some_and(|child| child.borrow().local_name() == static_interned!("optgroup"));
+            if is_optgroup {
+                options.extend(
+                    child
+                        .borrow()
+                        .children()
+                        .iter()
+                        .filter_map(|grandchild| grandchild.try_into_type::<HtmlOptionElement>()),
+                );
+            }
+        }
+
+        options
+    }
+
+    /// The indices of all selected options in the [list of options](Self::list_of_options)
+    ///
+    /// This applies the [selectedness setting algorithm](https://html.spec.whatwg.org/multipage/form-elements.html#selectedness-setting-algorithm)
+    /// to the selectedness of the options, so a drop-down box always has exactly one selected option
+    /// (unless all of them are disabled).
+    #[must_use]
+    pub fn selected_indices(&self) -> Vec<usize> {
+        let options = self.list_of_options();
+        let mut selected = options
+            .iter()
+            .enumerate()
+            .filter(|(_, option)| option.borrow().selectedness())
+            .map(|(index, _)| index);
+
+        if self.is_multiple() {
+            return selected.collect();
+        }
+
+        // If more than one option is selected, the last one wins
+        if let Some(index) = selected.next_back() {
+            return vec![index];
+        }
+
+        if self.display_size() == 1 {
+            let first_enabled = options
+                .iter()
+                .position(|option| !option.borrow().is_disabled());
+            return first_enabled.into_iter().collect();
+        }
+
+        vec![]
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-select-selectedindex>
+    #[must_use]
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_indices().first().copied()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-select-value>
+    #[must_use]
+    pub fn value(&self) -> String {
+        self.selected_index()
+            .and_then(|index| self.list_of_options().get(index).cloned())
+            .map(|option| option.borrow().value())
+            .unwrap_or_default()
+    }
+
+    /// Make the option at the given index the only selected option
+    ///
+    /// This is what happens when the user picks an option from a drop-down box. Disabled options cannot be picked.
+    pub fn pick_option(&self, index: usize) {
+        let options = self.list_of_options();
+        if options
+            .get(index)
+            .is_none_or(|option| option.borrow().is_disabled())
+        {
+            return;
+        }
+
+        for (option_index, option) in options.iter().enumerate() {
+            option.borrow_mut().set_selectedness(option_index == index);
+        }
+    }
+
+    /// Flip the selectedness of the option at the given index, in a `<select multiple>`
+    ///
+    /// Without the `multiple` attribute, this is the same as [picking](Self::pick_option) the option.
+    pub fn toggle_option(&self, index: usize) {
+        if !self.is_multiple() {
+            self.pick_option(index);
+            return;
+        }
+
+        let Some(option) = self.list_of_options().get(index).cloned() else {
+            return;
+        };
+
+        let mut option = option.borrow_mut();
+        if !option.is_disabled() {
+            let selectedness = option.selectedness();
+            option.set_selectedness(!selectedness);
+        }
+    }
+
+    /// The name-value pairs that this element contributes to the data of its form
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#constructing-the-form-data-set>
+    #[must_use]
+    pub fn form_entries(&self) -> Vec<(String, String)> {
+        let name = match self.attributes().get(&static_interned!("name")) {
+            Some(name) if !self.is_disabled() && !name.to_string().is_empty() => name.to_string(),
+            _ => return vec![],
+        };
+
+        let options = self.list_of_options();
+        self.selected_indices()
+            .into_iter()
+            .map(|index| options[index].borrow())
+            .filter(|option| !option.is_disabled())
+            .map(|option| (name.clone(), option.value()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::dom_objects::{Document, Node},
+        html::{self, tokenization::IgnoreParseErrors},
+    };
+
+    fn parse_select(source: &str) -> DomPtr<HtmlSelectElement> {
+        fn find_select(node: &DomPtr<Node>) -> Option<DomPtr<HtmlSelectElement>> {
+            node.try_into_type()
+                .or_else(|| node.borrow().children().iter().find_map(find_select))
+        }
+
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+        find_select(&document.upcast()).expect("document should contain a select element")
+    }
+
+    #[test]
+    fn drop_down_box_selects_first_enabled_option() {
+        let select = parse_select(
+            "<select name=fruit>
+                <option disabled>Choose one</option>
+                <optgroup label=Citrus><option>Lemon</option><option value=orange>Orange</option></optgroup>
+            </select>",
+        );
+        let select = select.borrow();
+
+        assert!(select.is_drop_down_box());
+        assert_eq!(select.list_of_options().len(), 3);
+        assert_eq!(select.selected_index(), Some(1));
+        assert_eq!(select.value(), "Lemon");
+
+        select.pick_option(2);
+        assert_eq!(select.selected_indices(), [2]);
+        assert_eq!(
+            select.form_entries(),
+            [("fruit".to_string(), "orange".to_string())]
+        );
+
+        // Disabled options cannot be picked
+        select.pick_option(0);
+        assert_eq!(select.selected_indices(), [2]);
+    }
+
+    #[test]
+    fn last_selected_option_wins() {
+        let select = parse_select(
+            "<select><option selected>a</option><option selected>b</option><option>c</option></select>",
+        );
+        assert_eq!(select.borrow().selected_indices(), [1]);
+    }
+
+    #[test]
+    fn multiple_selection() {
+        let select = parse_select(
+            "<select name=toppings multiple>
+                <option selected>Cheese</option>
+                <option>Olives</option>
+                <option label=Peppers value=peppers selected></option>
+            </select>",
+        );
+        let select = select.borrow();
+
+        assert!(!select.is_drop_down_box());
+        assert_eq!(select.display_size(), 4);
+        assert_eq!(select.selected_indices(), [0, 2]);
+        assert_eq!(select.list_of_options()[2].borrow().label(), "Peppers");
+
+        select.toggle_option(0);
+        select.toggle_option(1);
+        assert_eq!(
+            select.form_entries(),
+            [
+                ("toppings".to_string(), "Olives".to_string()),
+                ("toppings".to_string(), "peppers".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn list_box_without_selection() {
+        let select = parse_select("<select size=3><option>a</option><option>b</option></select>");
+        let select = select.borrow();
+
+        assert!(!select.is_drop_down_box());
+        assert_eq!(select.display_size(), 3);
+        assert_eq!(select.selected_index(), None);
+        assert_eq!(select.value(), "");
+    }
+}
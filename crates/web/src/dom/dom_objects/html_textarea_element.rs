@@ -0,0 +1,249 @@
+use dom_derive::inherit;
+
+use crate::{
+    editing::{self, TextEditor},
+    static_interned,
+};
+
+use super::{HtmlElement, Text};
+
+/// <https://html.spec.whatwg.org/multipage/form-elements.html#the-textarea-element>
+#[inherit(HtmlElement)]
+pub struct HtmlTextAreaElement {
+    /// The text that the user is editing, including the cursor and selection
+    editor: TextEditor,
+
+    /// Whether the value was changed by the user or by script
+    ///
+    /// Until then, the [raw value](https://html.spec.whatwg.org/multipage/form-elements.html#concept-textarea-raw-value)
+    /// follows the [default value](Self::default_value).
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-textarea-dirty>
+    is_dirty: bool,
+
+    /// The first line that is visible inside the control
+    first_visible_line: usize,
+}
+
+impl HtmlTextAreaElement {
+    pub fn new(html_element: HtmlElement) -> Self {
+        Self {
+            __parent: html_element,
+            editor: TextEditor::default(),
+            is_dirty: false,
+            first_visible_line: 0,
+        }
+    }
+
+    /// The child text content of the element
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-textarea-defaultvalue>
+    #[must_use]
+    pub fn default_value(&self) -> String {
+        self.children()
+            .iter()
+            .filter_map(|child| child.try_into_type::<Text>())
+            .map(|text| text.borrow().content().to_owned())
+            .collect()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-fe-api-value>
+    #[must_use]
+    pub fn value(&self) -> String {
+        if self.is_dirty {
+            self.editor.text().to_owned()
+        } else {
+            editing::normalize_newlines(&self.default_value(), true)
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-textarea-value>
+    pub fn set_value(&mut self, value: &str) {
+        self.editor.set_text(value);
+        self.is_dirty = true;
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#the-textarea-element:concept-form-reset-control>
+    pub fn reset(&mut self) {
+        self.editor = TextEditor::new(&self.default_value(), true);
+        self.is_dirty = false;
+    }
+
+    /// The current state of the editor
+    ///
+    /// While the value is not dirty, this reflects changes to the default value.
+    #[must_use]
+    pub(crate) fn editor(&self) -> TextEditor {
+        if self.is_dirty {
+            return self.editor.clone();
+        }
+
+        let mut editor = TextEditor::new(&self.default_value(), true);
+        editor.move_to(self.editor.cursor(), false);
+        editor
+    }
+
+    /// Change the text or the cursor of the element
+    ///
+    /// The value becomes dirty once the text is changed.
+    pub(crate) fn edit<R>(&mut self, f: impl FnOnce(&mut TextEditor) -> R) -> R {
+        let mut editor = self.editor();
+        let result = f(&mut editor);
+
+        if editor.text() != self.value() {
+            self.is_dirty = true;
+        }
+        self.editor = editor;
+
+        result
+    }
+
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("disabled"))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#attr-textarea-readonly>
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.attributes()
+            .contains_key(&static_interned!("readonly"))
+    }
+
+    /// Whether the user can change the value of the element
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#concept-fe-mutable>
+    #[must_use]
+    pub fn is_mutable(&self) -> bool {
+        !self.is_disabled() && !self.is_read_only()
+    }
+
+    /// Parse a dimension attribute, falling back to the default if the value is not a
+    /// valid non-negative integer greater than zero
+    #[must_use]
+    fn dimension(&self, attribute: crate::InternedString, default: usize) -> usize {
+        // FIXME: Use the rules for parsing non-negative integers
+        self.attributes()
+            .get(&attribute)
+            .and_then(|value| value.to_string().trim().parse().ok())
+            .filter(|&value: &usize| value != 0)
+            .unwrap_or(default)
+    }
+
+    /// The number of lines that are visible without scrolling
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-textarea-rows>
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.dimension(static_interned!("rows"), 2)
+    }
+
+    /// The number of characters per line
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-elements.html#dom-textarea-cols>
+    #[must_use]
+    pub fn cols(&self) -> usize {
+        self.dimension(static_interned!("cols"), 20)
+    }
+
+    #[must_use]
+    pub fn first_visible_line(&self) -> usize {
+        self.first_visible_line
+    }
+
+    pub fn set_first_visible_line(&mut self, first_visible_line: usize) {
+        self.first_visible_line = first_visible_line;
+    }
+
+    /// The name-value pairs that this element contributes to the data of its form
+    ///
+    /// <https://html.spec.whatwg.org/multipage/form-control-infrastructure.html#constructing-the-form-data-set>
+    #[must_use]
+    pub fn form_entries(&self) -> Vec<(String, String)> {
+        match self.attributes().get(&static_interned!("name")) {
+            // FIXME: Insert the line breaks of hard-wrapped textareas
+            Some(name) if !self.is_disabled() && !name.to_string().is_empty() => {
+                vec![(name.to_string(), self.value())]
+            },
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::{
+            dom_objects::{Document, Node},
+            DomPtr,
+        },
+        editing::Motion,
+        html::{self, tokenization::IgnoreParseErrors},
+    };
+
+    fn parse_textarea(source: &str) -> DomPtr<HtmlTextAreaElement> {
+        fn find_textarea(node: &DomPtr<Node>) -> Option<DomPtr<HtmlTextAreaElement>> {
+            node.try_into_type()
+                .or_else(|| node.borrow().children().iter().find_map(find_textarea))
+        }
+
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+        find_textarea(&document.upcast()).expect("document should contain a textarea element")
+    }
+
+    #[test]
+    fn default_value_and_value() {
+        let textarea =
+            parse_textarea("<textarea name=comment>\nHello &amp; <b>welcome</b></textarea>");
+        let mut textarea = textarea.borrow_mut();
+
+        // The leading newline is ignored and the content is parsed as text
+        assert_eq!(textarea.default_value(), "Hello & <b>welcome</b>");
+        assert_eq!(textarea.value(), textarea.default_value());
+
+        textarea.edit(|editor| {
+            editor.move_cursor(Motion::End, false);
+            editor.insert("!");
+        });
+        assert_eq!(textarea.value(), "Hello & <b>welcome</b>!");
+        assert_eq!(textarea.default_value(), "Hello & <b>welcome</b>");
+        assert_eq!(
+            textarea.form_entries(),
+            [("comment".to_string(), "Hello & <b>welcome</b>!".to_string())]
+        );
+
+        textarea.reset();
+        assert_eq!(textarea.value(), "Hello & <b>welcome</b>");
+    }
+
+    #[test]
+    fn moving_the_cursor_does_not_make_the_value_dirty() {
+        let textarea = parse_textarea("<textarea>abc</textarea>");
+        let mut textarea = textarea.borrow_mut();
+
+        textarea.edit(|editor| editor.move_cursor(Motion::End, false));
+        assert_eq!(textarea.editor().cursor(), 3);
+        assert!(!textarea.is_dirty);
+
+        textarea.set_value("a\r\nb");
+        assert_eq!(textarea.value(), "a\nb");
+        assert_eq!(textarea.editor().cursor(), 3);
+    }
+
+    #[test]
+    fn dimensions() {
+        let textarea = parse_textarea("<textarea rows=5 cols=0></textarea>");
+        let textarea = textarea.borrow();
+
+        assert_eq!(textarea.rows(), 5);
+        assert_eq!(textarea.cols(), 20);
+    }
+}
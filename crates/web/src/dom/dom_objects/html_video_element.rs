@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use dom_derive::inherit;
+use image::Texture;
+
+use crate::static_interned;
+
+use super::{html_image_element, HtmlMediaElement};
+
+/// <https://html.spec.whatwg.org/multipage/media.html#the-video-element>
+#[inherit(HtmlMediaElement)]
+pub struct HtmlVideoElement {
+    /// The image that is shown until the first video frame is available
+    ///
+    /// Like the content of `<img>` elements, this is loaded once it is first needed.
+    poster: Option<Option<Arc<Texture>>>,
+}
+
+impl HtmlVideoElement {
+    pub fn new(media_element: HtmlMediaElement) -> Self {
+        Self {
+            __parent: media_element,
+            poster: None,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/media.html#poster-frame>
+    #[must_use]
+    pub fn poster_frame(&mut self) -> Option<&Arc<Texture>> {
+        if !self.attributes().contains_key(&static_interned!("poster")) {
+            return None;
+        }
+
+        self.poster
+            .get_or_insert_with(|| {
+                html_image_element::load_image(&self.__parent, static_interned!("poster"))
+            })
+            .as_ref()
+    }
+}
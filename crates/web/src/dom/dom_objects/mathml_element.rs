@@ -0,0 +1,13 @@
+use dom_derive::inherit;
+
+use super::Element;
+
+/// <https://w3c.github.io/mathml-core/#dom-and-javascript>
+#[inherit(Element)]
+pub struct MathMlElement {}
+
+impl MathMlElement {
+    pub fn new(element: Element) -> Self {
+        Self { __parent: element }
+    }
+}
@@ -4,8 +4,10 @@ mod document;
 mod document_type;
 mod element;
 mod html_anchor_element;
+mod html_audio_element;
 mod html_body_element;
 mod html_button_element;
+mod html_canvas_element;
 mod html_dd_element;
 mod html_div_element;
 mod html_dt_element;
@@ -15,45 +17,61 @@ mod html_head_element;
 mod html_heading_element;
 mod html_html_element;
 mod html_image_element;
+mod html_input_element;
 mod html_li_element;
 mod html_link_element;
+mod html_media_element;
 mod html_meta_element;
 mod html_noscript_element;
+mod html_option_element;
 mod html_paragraph_element;
 mod html_script_element;
+mod html_select_element;
 mod html_style_element;
 mod html_table_element;
 mod html_template_element;
+mod html_textarea_element;
 mod html_title_element;
+mod html_video_element;
+mod mathml_element;
 mod node;
 mod text;
 
 pub use character_data::CharacterData;
 pub use comment::Comment;
-pub use document::Document;
+pub use document::{Document, DocumentReadyState};
 pub use document_type::DocumentType;
 pub use element::Element;
 pub use html_anchor_element::HtmlAnchorElement;
+pub use html_audio_element::HtmlAudioElement;
 pub use html_body_element::HtmlBodyElement;
 pub use html_button_element::HtmlButtonElement;
+pub use html_canvas_element::HtmlCanvasElement;
 pub use html_dd_element::HtmlDdElement;
 pub use html_div_element::HtmlDivElement;
 pub use html_dt_element::HtmlDtElement;
 pub use html_element::HtmlElement;
-pub use html_form_element::HtmlFormElement;
+pub use html_form_element::{FormDataEntryValue, HtmlFormElement};
 pub use html_head_element::HtmlHeadElement;
 pub use html_heading_element::HtmlHeadingElement;
 pub use html_html_element::HtmlHtmlElement;
 pub use html_image_element::HtmlImageElement;
+pub use html_input_element::{HtmlInputElement, InputType};
 pub use html_li_element::HtmlLiElement;
 pub use html_link_element::HtmlLinkElement;
+pub use html_media_element::HtmlMediaElement;
 pub use html_meta_element::HtmlMetaElement;
 pub use html_noscript_element::HtmlNoscriptElement;
+pub use html_option_element::HtmlOptionElement;
 pub use html_paragraph_element::HtmlParagraphElement;
 pub use html_script_element::HtmlScriptElement;
+pub use html_select_element::HtmlSelectElement;
 pub use html_style_element::HtmlStyleElement;
 pub use html_table_element::HtmlTableElement;
 pub use html_template_element::HtmlTemplateElement;
+pub use html_textarea_element::HtmlTextAreaElement;
 pub use html_title_element::HtmlTitleElement;
+pub use html_video_element::HtmlVideoElement;
+pub use mathml_element::MathMlElement;
 pub use node::Node;
 pub use text::Text;
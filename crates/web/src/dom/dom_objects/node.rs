@@ -1,7 +1,7 @@
 use dom_derive::inherit;
 use std::fmt;
 
-use super::{Comment, Document, Text};
+use super::{Comment, Document, Element, Text};
 use crate::{
     dom::{DomPtr, DomType, WeakDomPtr},
     TreeDebug, TreeFormatter,
@@ -95,6 +95,8 @@ impl Node {
     pub fn append_child(parent: DomPtr<Node>, child: DomPtr<Node>) {
         child.borrow_mut().parent_node = Some(parent.downgrade());
         parent.borrow_mut().child_nodes.push(child);
+
+        Element::mark_needs_layout(parent);
     }
 
     pub fn owning_document(&self) -> Option<DomPtr<Document>> {
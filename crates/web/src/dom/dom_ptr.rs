@@ -1,9 +1,6 @@
-use std::{
-    cell::RefCell,
-    fmt::Write,
-    ops::Deref,
-    rc::{Rc, Weak},
-};
+use std::{fmt::Write, ops::Deref, ptr};
+
+use gc::{Gc, GcCell, Trace, WeakGc};
 
 use crate::TreeDebug;
 
@@ -16,31 +13,29 @@ use super::{
 /// Each [DomPtr] contains a pointer to an object of type `T`.
 /// `T` is either the actual type stored at the address or any
 /// of its supertypes.
-/// The internal objects are reference counted and inside a `RefCell`.
+/// The internal objects live on the garbage collected heap and inside a [GcCell],
+/// so reference cycles between DOM objects (and, eventually, script objects) are
+/// collected as well.
+///
+/// Pointers to different types are reinterpreted as one another, so the layout must not depend on `T`.
+#[repr(C)]
 pub struct DomPtr<T: DomTyped> {
-    inner: Rc<RefCell<T>>,
+    inner: Gc<GcCell<T>>,
 
     /// The actual type pointed to by inner.
     underlying_type: DomType,
 }
 
+#[repr(C)]
 pub struct WeakDomPtr<T: DomTyped> {
-    inner: Weak<RefCell<T>>,
+    inner: WeakGc<GcCell<T>>,
 
     /// The actual type pointed to by inner.
     underlying_type: DomType,
 }
 
 impl<T: DomTyped> Deref for DomPtr<T> {
-    type Target = Rc<RefCell<T>>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
-    }
-}
-
-impl<T: DomTyped> Deref for WeakDomPtr<T> {
-    type Target = Weak<RefCell<T>>;
+    type Target = GcCell<T>;
 
     fn deref(&self) -> &Self::Target {
         &self.inner
@@ -50,7 +45,7 @@ impl<T: DomTyped> Deref for WeakDomPtr<T> {
 impl<T: DomTyped> DomPtr<T> {
     pub fn new(inner: T) -> Self {
         Self {
-            inner: Rc::new(RefCell::new(inner)),
+            inner: Gc::new(GcCell::new(inner)),
             underlying_type: T::as_type(),
         }
     }
@@ -96,6 +91,19 @@ impl<T: DomTyped> DomPtr<T> {
         std::mem::transmute(self)
     }
 
+    /// Mark the object as reachable, treating it as an instance of `O`
+    ///
+    /// # Safety
+    /// `O` must be the [underlying type](Self::underlying_type) of the object.
+    pub(crate) unsafe fn mark_as<O: DomTyped>(&self) {
+        debug_assert_eq!(self.underlying_type, O::as_type());
+
+        // SAFETY: O is the actual type of the object, so this is the pointer that
+        //         the object was allocated with
+        let inner = unsafe { &*ptr::from_ref(&self.inner).cast::<Gc<GcCell<O>>>() };
+        Gc::mark(inner);
+    }
+
     /// Try to cast the object to another type and fail
     /// if the cast is invalid (ie the objects don't inherit from each other)
     pub fn try_into_type<O: DomTyped>(&self) -> Option<DomPtr<O>> {
@@ -108,18 +116,20 @@ impl<T: DomTyped> DomPtr<T> {
     }
 
     /// Check if two [DomPtr]'s point to the same object.
-    /// This is the equivalent `ptr_eq` on [Rc](std::rc::Rc).
-    /// Note that due to the constraints on [Rc], the two dom
-    /// pointers must point to the same type.
     pub fn ptr_eq<U: DomTyped>(&self, other: &DomPtr<U>) -> bool {
         // We don't care about the type information,
         // only if the two DOMPtrs point to the same underlying object
-        self.inner.as_ptr().cast::<U>() == other.as_ptr()
+        self.as_ptr().cast::<U>() == other.as_ptr()
+    }
+
+    /// The address of the object, which stays the same for as long as it lives
+    pub fn as_ptr(&self) -> *const T {
+        self.inner.as_ptr().as_ptr().cast_const()
     }
 
     pub fn downgrade(&self) -> WeakDomPtr<T> {
         WeakDomPtr {
-            inner: Rc::downgrade(&self.inner),
+            inner: Gc::downgrade(&self.inner),
             underlying_type: self.underlying_type,
         }
     }
@@ -144,6 +154,22 @@ impl<T: DomTyped> WeakDomPtr<T> {
     }
 }
 
+// Objects are traced as their actual type, because the pointer itself might only know
+// about one of the parent types
+unsafe impl<T: DomTyped> Trace for DomPtr<T> {
+    fn trace(&self) {
+        self.underlying_type.mark(self);
+    }
+
+    fn root(&self) {
+        self.inner.root();
+    }
+
+    fn unroot(&self) {
+        self.inner.unroot();
+    }
+}
+
 impl<T: DomTyped> Clone for DomPtr<T> {
     fn clone(&self) -> Self {
         Self {
@@ -194,3 +220,51 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dom::dom_objects::{Comment, Document, HtmlScriptElement, Node};
+
+    #[test]
+    fn collect_detached_subtrees() {
+        let parent = DomPtr::new(Node::default());
+        let mut comment = Comment::default();
+        comment.content_mut().push_str("child");
+        let child = DomPtr::new(comment);
+        Node::append_child(parent.clone(), child.clone().upcast());
+
+        let weak_child = child.downgrade();
+        drop(child);
+
+        // The child is only reachable through its parent, which only knows it as a "Node"
+        gc::collect_garbage();
+        let child = weak_child.upgrade().expect("child was collected");
+        assert_eq!(child.borrow().comment_data(), "child");
+        drop(child);
+
+        drop(parent);
+        gc::collect_garbage();
+        assert!(weak_child.upgrade().is_none());
+    }
+
+    #[test]
+    fn trace_mutably_borrowed_objects_as_their_actual_type() {
+        let document = DomPtr::new(Document::default());
+        let script = DomPtr::new(HtmlScriptElement::default());
+        document
+            .borrow_mut()
+            .set_current_script(Some(script.clone()));
+
+        let weak_script = script.downgrade();
+        drop(script);
+
+        // Borrowing the document as a "Node" only roots the fields of the node
+        let node: DomPtr<Node> = document.upcast();
+        let borrowed_node = node.borrow_mut();
+        gc::collect_garbage();
+        drop(borrowed_node);
+
+        assert!(weak_script.upgrade().is_some());
+    }
+}
@@ -0,0 +1,53 @@
+//! Estimates how much memory a document occupies
+
+use std::mem;
+
+use sl_std::memory::{MemoryReport, MemoryReporter};
+
+use crate::{image_cache::IMAGE_CACHE, InternedString};
+
+use super::{
+    dom_objects::{Document, Element, HtmlCanvasElement, Node, Text},
+    DomPtr,
+};
+
+impl MemoryReporter for DomPtr<Document> {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        report_node(&self.clone().upcast(), report);
+
+        // Images that are still in the shared cache are reported by the cache
+        for (url, texture) in self.borrow().images().iter() {
+            if !IMAGE_CACHE.contains(url, texture) {
+                report.record("images/evicted", texture.size_in_bytes());
+            }
+        }
+    }
+}
+
+fn report_node(node: &DomPtr<Node>, report: &mut MemoryReport) {
+    let children_size = mem::size_of_val(node.borrow().children());
+
+    if let Some(element) = node.try_into_type::<Element>() {
+        let attributes_size = element.borrow().attributes().capacity()
+            * mem::size_of::<(InternedString, InternedString)>();
+        report.record(
+            "dom/elements",
+            mem::size_of::<Element>() + attributes_size + children_size,
+        );
+    } else if let Some(text) = node.try_into_type::<Text>() {
+        report.record(
+            "dom/text",
+            mem::size_of::<Text>() + text.borrow().content().len(),
+        );
+    } else {
+        report.record("dom/other", mem::size_of::<Node>() + children_size);
+    }
+
+    if let Some(canvas) = node.try_into_type::<HtmlCanvasElement>() {
+        canvas.borrow().report_memory(report);
+    }
+
+    for child in node.borrow().children() {
+        report_node(child, report);
+    }
+}
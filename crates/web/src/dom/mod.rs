@@ -4,15 +4,17 @@ mod boundary_point;
 mod codegen;
 pub mod dom_objects;
 mod dom_ptr;
+mod memory;
 
 pub use boundary_point::{BoundaryPoint, RelativePosition};
 pub use codegen::{DomType, DomTyped, IsA};
 use dom_objects::{
-    Document, Element, HtmlAnchorElement, HtmlBodyElement, HtmlButtonElement, HtmlDdElement,
-    HtmlDivElement, HtmlDtElement, HtmlElement, HtmlFormElement, HtmlHeadElement,
-    HtmlHeadingElement, HtmlHtmlElement, HtmlLiElement, HtmlLinkElement, HtmlMetaElement,
-    HtmlNoscriptElement, HtmlParagraphElement, HtmlScriptElement, HtmlStyleElement,
-    HtmlTemplateElement, HtmlTitleElement,
+    Document, Element, HtmlAnchorElement, HtmlAudioElement, HtmlBodyElement, HtmlButtonElement,
+    HtmlCanvasElement, HtmlDdElement, HtmlDivElement, HtmlDtElement, HtmlElement, HtmlFormElement,
+    HtmlHeadElement, HtmlHeadingElement, HtmlHtmlElement, HtmlInputElement, HtmlLiElement,
+    HtmlLinkElement, HtmlMediaElement, HtmlMetaElement, HtmlNoscriptElement, HtmlOptionElement,
+    HtmlParagraphElement, HtmlScriptElement, HtmlSelectElement, HtmlStyleElement,
+    HtmlTemplateElement, HtmlTextAreaElement, HtmlTitleElement, HtmlVideoElement, MathMlElement,
 };
 pub use dom_ptr::{DomPtr, WeakDomPtr};
 
@@ -54,7 +56,7 @@ pub fn create_element(
     // let mut result = None;
 
     // 4. Let definition be the result of looking up a custom element definition given document, namespace, localName, and is.
-    let definition = lookup_custom_element_definition(namespace, local_name, is);
+    let definition = lookup_custom_element_definition(namespace, local_name.clone(), is.clone());
 
     let result = match definition {
         // 5. FIXME: If definition is non-null, and definition’s name is not equal to its local name (i.e., definition represents a customized built-in element), then:
@@ -76,7 +78,7 @@ pub fn create_element(
             let mut element_data = Element::new(
                 namespace,
                 prefix,
-                local_name,
+                local_name.clone(),
                 ElementCustomState::Uncustomized,
                 None,
                 is,
@@ -122,6 +124,17 @@ fn create_element_for_interface(
     namespace: Namespace,
     element_data: Element,
 ) -> DomPtr<Element> {
+    // There are no interfaces for elements in unknown namespaces
+    if namespace == Namespace::Other {
+        return DomPtr::new(element_data);
+    }
+
+    // All MathML elements share the same interface
+    // https://w3c.github.io/mathml-core/#dom-and-javascript
+    if namespace == Namespace::MathML {
+        return DomPtr::new(MathMlElement::new(element_data)).upcast();
+    }
+
     if namespace != Namespace::HTML {
         log::warn!(
             "Failed to create element for {namespace:?}:  {:?}",
@@ -135,12 +148,19 @@ fn create_element_for_interface(
         static_interned!("a") => {
             DomPtr::new(HtmlAnchorElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("audio") => DomPtr::new(HtmlAudioElement::new(HtmlMediaElement::new(
+            HtmlElement::new(element_data),
+        )))
+        .upcast(),
         static_interned!("body") => {
             DomPtr::new(HtmlBodyElement::new(HtmlElement::new(element_data))).upcast()
         },
         static_interned!("button") => {
             DomPtr::new(HtmlButtonElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("canvas") => {
+            DomPtr::new(HtmlCanvasElement::new(HtmlElement::new(element_data))).upcast()
+        },
         static_interned!("dd") => {
             DomPtr::new(HtmlDdElement::new(HtmlElement::new(element_data))).upcast()
         },
@@ -159,6 +179,9 @@ fn create_element_for_interface(
         static_interned!("img") => {
             DomPtr::new(HtmlImageElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("input") => {
+            DomPtr::new(HtmlInputElement::new(HtmlElement::new(element_data))).upcast()
+        },
         static_interned!("link") => {
             DomPtr::new(HtmlLinkElement::new(HtmlElement::new(element_data))).upcast()
         },
@@ -168,12 +191,18 @@ fn create_element_for_interface(
         static_interned!("noscript") => {
             DomPtr::new(HtmlNoscriptElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("option") => {
+            DomPtr::new(HtmlOptionElement::new(HtmlElement::new(element_data))).upcast()
+        },
         static_interned!("p") => {
             DomPtr::new(HtmlParagraphElement::new(HtmlElement::new(element_data))).upcast()
         },
         static_interned!("script") => {
             DomPtr::new(HtmlScriptElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("select") => {
+            DomPtr::new(HtmlSelectElement::new(HtmlElement::new(element_data))).upcast()
+        },
         static_interned!("style") => {
             DomPtr::new(HtmlStyleElement::new(HtmlElement::new(element_data))).upcast()
         },
@@ -183,9 +212,16 @@ fn create_element_for_interface(
         static_interned!("template") => {
             DomPtr::new(HtmlTemplateElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("textarea") => {
+            DomPtr::new(HtmlTextAreaElement::new(HtmlElement::new(element_data))).upcast()
+        },
         static_interned!("title") => {
             DomPtr::new(HtmlTitleElement::new(HtmlElement::new(element_data))).upcast()
         },
+        static_interned!("video") => DomPtr::new(HtmlVideoElement::new(HtmlMediaElement::new(
+            HtmlElement::new(element_data),
+        )))
+        .upcast(),
         static_interned!("h1")
         | static_interned!("h2")
         | static_interned!("h3")
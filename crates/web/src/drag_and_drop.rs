@@ -0,0 +1,351 @@
+//! Dragging files from other applications onto the page
+//!
+//! Only drags that start outside of the browser are supported, so the drag data store
+//! always contains files. Until the files are dropped, pages can only see that there are
+//! files, not what they contain.
+//!
+//! Drag events can't be canceled.
+//!
+//! <https://html.spec.whatwg.org/multipage/dnd.html>
+
+use crate::{
+    dom::{
+        dom_objects::{Element, HtmlInputElement, Node},
+        DomPtr,
+    },
+    file_api::{File, FileList},
+};
+
+/// The operation that is performed when the dragged data is dropped
+///
+/// <https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransfer-dropeffect>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DropEffect {
+    /// The data cannot be dropped at the current position
+    #[default]
+    None,
+    Copy,
+    Link,
+    Move,
+}
+
+impl DropEffect {
+    #[must_use]
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        let drop_effect = match keyword {
+            "none" => Self::None,
+            "copy" => Self::Copy,
+            "link" => Self::Link,
+            "move" => Self::Move,
+            _ => return None,
+        };
+
+        Some(drop_effect)
+    }
+
+    #[must_use]
+    pub const fn keyword(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Copy => "copy",
+            Self::Link => "link",
+            Self::Move => "move",
+        }
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/dnd.html#drag-data-store-mode>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DragDataStoreMode {
+    /// Used during `dragstart`, data can be added to the store
+    ReadWrite,
+
+    /// Used during `drop`, the data can be read
+    ReadOnly,
+
+    /// Used during all other events, only the types of the data can be read
+    Protected,
+}
+
+/// The events that are fired at the page while something is dragged over it
+///
+/// <https://html.spec.whatwg.org/multipage/dnd.html#dndevents>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DragEventType {
+    Enter,
+    Over,
+    Leave,
+    Drop,
+}
+
+impl DragEventType {
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Enter => "dragenter",
+            Self::Over => "dragover",
+            Self::Leave => "dragleave",
+            Self::Drop => "drop",
+        }
+    }
+
+    /// The mode of the drag data store while the event is dispatched
+    #[must_use]
+    pub const fn data_store_mode(&self) -> DragDataStoreMode {
+        match self {
+            Self::Drop => DragDataStoreMode::ReadOnly,
+            Self::Enter | Self::Over | Self::Leave => DragDataStoreMode::Protected,
+        }
+    }
+}
+
+/// The data that is being dragged
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/dnd.html#the-datatransfer-interface)
+#[derive(Clone, Debug)]
+pub struct DataTransfer {
+    mode: DragDataStoreMode,
+    drop_effect: DropEffect,
+
+    /// The dragged files, only known once they are dropped
+    files: FileList,
+}
+
+impl DataTransfer {
+    #[must_use]
+    pub(crate) fn new(event_type: DragEventType, files: FileList) -> Self {
+        // Initialize the dropEffect attribute to "none" for dragstart, drag, and dragleave events
+        // and to the value of the current drag operation for drop and dragend events.
+        // For dragenter and dragover, the user agent picks an initial value based on effectAllowed.
+        // Files from other applications allow any effect, they are copied by default.
+        let drop_effect = match event_type {
+            DragEventType::Leave => DropEffect::None,
+            DragEventType::Enter | DragEventType::Over | DragEventType::Drop => DropEffect::Copy,
+        };
+
+        Self {
+            mode: event_type.data_store_mode(),
+            drop_effect,
+            files,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransfer-dropeffect>
+    #[must_use]
+    pub const fn drop_effect(&self) -> DropEffect {
+        self.drop_effect
+    }
+
+    /// On setting, values that are not one of "none", "copy", "link" or "move" are ignored
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransfer-dropeffect>
+    pub fn set_drop_effect(&mut self, keyword: &str) {
+        if let Some(drop_effect) = DropEffect::from_keyword(keyword) {
+            self.drop_effect = drop_effect;
+        }
+    }
+
+    /// Data from other applications may be dropped with any effect
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransfer-effectallowed>
+    #[must_use]
+    pub const fn effect_allowed(&self) -> &'static str {
+        "all"
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransfer-types>
+    #[must_use]
+    pub fn types(&self) -> Vec<&'static str> {
+        // If there are any items in the drag data store item list whose kind is File,
+        // then add an entry to L consisting of the string "Files".
+        vec!["Files"]
+    }
+
+    /// The dropped files, empty unless the data transfer belongs to a `drop` event
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#dom-datatransfer-files>
+    #[must_use]
+    pub fn files(&self) -> FileList {
+        // 2. If the DataTransfer object is no longer associated with a drag data store, return the empty list L.
+        // 3. If the drag data store's mode is in the protected mode, return the empty list L.
+        if self.mode == DragDataStoreMode::Protected {
+            return FileList::default();
+        }
+
+        // 4. For each item in the drag data store item list whose kind is File, add the item's data to the list L.
+        self.files.clone()
+    }
+}
+
+/// The state of a drag that is currently over the page
+#[derive(Default)]
+pub(crate) struct DragSession {
+    /// The element that the `dragover` and `drop` events are fired at
+    ///
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#current-target-element>
+    current_target: Option<DomPtr<Element>>,
+
+    /// <https://html.spec.whatwg.org/multipage/dnd.html#current-drag-operation>
+    current_drag_operation: DropEffect,
+}
+
+impl DragSession {
+    /// Move the drag to the element below the cursor, firing the events that the move causes
+    ///
+    /// Returns the operation that would be performed if the data was dropped now.
+    pub fn update(&mut self, immediate_user_selection: Option<DomPtr<Element>>) -> DropEffect {
+        // If the user is indicating a different immediate user selection than during the last iteration
+        // (or if this is the first iteration), and if this immediate user selection is not the same as the
+        // current target element, then fire a DND event named dragenter at the immediate user selection.
+        let is_same_target = match (&self.current_target, &immediate_user_selection) {
+            (Some(current), Some(new)) => current.ptr_eq(new),
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !is_same_target {
+            // If the previous target element was not null, fire a DND event named dragleave at it.
+            if let Some(previous_target) = self.current_target.take() {
+                fire_drag_event(DragEventType::Leave, &previous_target, FileList::default());
+            }
+
+            // FIXME: Without event listeners, nothing can cancel the dragenter event. The specification
+            //        retargets uncanceled events to the body, but keeping the immediate user selection
+            //        lets file upload controls receive the drop.
+            if let Some(new_target) = immediate_user_selection {
+                fire_drag_event(DragEventType::Enter, &new_target, FileList::default());
+                self.current_target = Some(new_target);
+            }
+        }
+
+        // If the current target element is a DOM element, then fire a DND event named dragover at this current target element.
+        // If the dragover event is not canceled, the current drag operation depends on the default action.
+        self.current_drag_operation = match &self.current_target {
+            Some(target) => {
+                let data_transfer =
+                    fire_drag_event(DragEventType::Over, target, FileList::default());
+
+                if accepts_dropped_files(target) {
+                    data_transfer.drop_effect()
+                } else {
+                    DropEffect::None
+                }
+            },
+            None => DropEffect::None,
+        };
+
+        self.current_drag_operation
+    }
+
+    /// The user dragged the data away from the page
+    pub fn leave(self) {
+        if let Some(target) = &self.current_target {
+            fire_drag_event(DragEventType::Leave, target, FileList::default());
+        }
+    }
+
+    /// The user dropped the files, returns the file input that should receive them (if any)
+    pub fn drop_files(self, files: Vec<File>) -> Option<DomPtr<HtmlInputElement>> {
+        // Otherwise, if the user ended the drag-and-drop operation (e.g. by releasing the mouse button in a mouse-driven
+        // drag-and-drop interface), or if the drag event was canceled, then this will be the last iteration.
+        let target = self.current_target?;
+
+        // If the current drag operation is "none" (no drag operation), or, if the user ended the drag-and-drop
+        // operation by canceling it, or if the current target element is null, then the drag operation failed.
+        // If the current target element is a DOM element, fire a DND event named dragleave at it.
+        if self.current_drag_operation == DropEffect::None {
+            fire_drag_event(DragEventType::Leave, &target, FileList::default());
+            return None;
+        }
+
+        // Otherwise, the drag operation might be a success; fire a DND event named drop at the current target element.
+        fire_drag_event(DragEventType::Drop, &target, FileList::from(files));
+
+        // If the event is not canceled, perform an appropriate default action.
+        target.try_into_type::<HtmlInputElement>()
+    }
+}
+
+/// The element that receives the drag events when the cursor is over `node`
+///
+/// Text and other nodes that aren't elements are represented by their parent.
+#[must_use]
+pub(crate) fn immediate_user_selection(node: DomPtr<Node>) -> Option<DomPtr<Element>> {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if let Some(element) = node.try_into_type::<Element>() {
+            return Some(element);
+        }
+        current = node.borrow().parent_node();
+    }
+
+    None
+}
+
+/// Whether the user agent handles files that are dropped onto the element by itself
+///
+/// File upload controls are drop zones even without any event listeners.
+#[must_use]
+fn accepts_dropped_files(element: &DomPtr<Element>) -> bool {
+    element
+        .try_into_type::<HtmlInputElement>()
+        .is_some_and(|input| {
+            let input = input.borrow();
+            input.is_file_upload() && !input.is_disabled()
+        })
+}
+
+/// <https://html.spec.whatwg.org/multipage/dnd.html#fire-a-dnd-event>
+fn fire_drag_event(
+    event_type: DragEventType,
+    target: &DomPtr<Element>,
+    files: FileList,
+) -> DataTransfer {
+    let data_transfer = DataTransfer::new(event_type, files);
+
+    // FIXME: Dispatch the event at the target once the DOM supports event listeners
+    log::debug!(
+        "{} on <{}>",
+        event_type.name(),
+        target.borrow().local_name()
+    );
+
+    data_transfer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_api::{BlobPart, FilePropertyBag};
+
+    #[test]
+    fn files_are_protected_until_dropped() {
+        let file = File::new(
+            &[BlobPart::String("hello".to_string())],
+            "hello.txt",
+            FilePropertyBag::default(),
+        );
+        let files = FileList::from(vec![file]);
+
+        let data_transfer = DataTransfer::new(DragEventType::Over, files.clone());
+        assert_eq!(data_transfer.types(), ["Files"]);
+        assert!(data_transfer.files().is_empty());
+
+        let data_transfer = DataTransfer::new(DragEventType::Drop, files);
+        assert_eq!(data_transfer.files().length(), 1);
+        assert_eq!(data_transfer.files().item(0).unwrap().name(), "hello.txt");
+    }
+
+    #[test]
+    fn drop_effect() {
+        let mut data_transfer = DataTransfer::new(DragEventType::Leave, FileList::default());
+        assert_eq!(data_transfer.drop_effect(), DropEffect::None);
+
+        data_transfer.set_drop_effect("link");
+        assert_eq!(data_transfer.drop_effect(), DropEffect::Link);
+
+        // Invalid values are ignored
+        data_transfer.set_drop_effect("Copy");
+        assert_eq!(data_transfer.drop_effect().keyword(), "link");
+    }
+}
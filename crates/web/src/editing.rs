@@ -0,0 +1,302 @@
+//! Editing the text of form controls
+//!
+//! This is the part of text editing that does not depend on how the text is displayed,
+//! so it is shared between all controls that the user can type into. Movement between
+//! lines depends on where the text wraps and is therefore left to the control.
+
+use std::ops::Range;
+
+/// How the cursor moves in response to keyboard input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Motion {
+    /// One character backwards
+    Left,
+
+    /// One character forwards
+    Right,
+
+    /// The beginning of the text
+    Start,
+
+    /// The end of the text
+    End,
+}
+
+/// A text together with a cursor and a (possibly empty) selection
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TextEditor {
+    text: String,
+
+    /// Whether the text may contain line breaks
+    is_multiline: bool,
+
+    /// Byte offset of the cursor
+    cursor: usize,
+
+    /// Byte offset of the end of the selection that does not move with the cursor
+    ///
+    /// If this is equal to the cursor then nothing is selected.
+    anchor: usize,
+
+    /// Byte range of the text that an input method is composing
+    ///
+    /// The composed text is part of the text, but it is replaced as a whole
+    /// until the input method commits it.
+    composition: Option<Range<usize>>,
+}
+
+/// Normalize the line breaks of a string that is inserted into a control
+///
+/// Single line controls do not allow line breaks at all, multi line controls
+/// only use line feeds.
+///
+/// <https://infra.spec.whatwg.org/#normalize-newlines>
+#[must_use]
+pub(crate) fn normalize_newlines(text: &str, is_multiline: bool) -> String {
+    let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+
+    if is_multiline {
+        normalized
+    } else {
+        normalized.replace('\n', "")
+    }
+}
+
+impl TextEditor {
+    /// Create an editor with the cursor placed at the beginning of the text
+    #[must_use]
+    pub fn new(text: &str, is_multiline: bool) -> Self {
+        Self {
+            text: normalize_newlines(text, is_multiline),
+            is_multiline,
+            cursor: 0,
+            anchor: 0,
+            composition: None,
+        }
+    }
+
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the whole text, the cursor is moved to the end of the new text
+    pub fn set_text(&mut self, text: &str) {
+        self.text = normalize_newlines(text, self.is_multiline);
+        self.cursor = self.text.len();
+        self.anchor = self.cursor;
+    }
+
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The selected part of the text, as a byte range
+    #[must_use]
+    pub fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor)..self.cursor.max(self.anchor)
+    }
+
+    #[must_use]
+    pub fn has_selection(&self) -> bool {
+        self.cursor != self.anchor
+    }
+
+    /// The part of the text that is still being composed, as a byte range
+    #[must_use]
+    pub fn composition(&self) -> Option<Range<usize>> {
+        self.composition.clone()
+    }
+
+    /// Move the cursor to the given byte offset
+    ///
+    /// If `extend_selection` is `true` then the selection grows or shrinks
+    /// with the cursor, otherwise it is collapsed. Text that is being
+    /// composed is committed as it is.
+    pub fn move_to(&mut self, offset: usize, extend_selection: bool) {
+        self.composition = None;
+
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        self.cursor = offset;
+        if !extend_selection {
+            self.anchor = offset;
+        }
+    }
+
+    pub fn move_cursor(&mut self, motion: Motion, extend_selection: bool) {
+        // Without shift, moving left or right collapses the selection to one of its ends
+        if !extend_selection && self.has_selection() {
+            let selection = self.selection();
+            match motion {
+                Motion::Left => return self.move_to(selection.start, false),
+                Motion::Right => return self.move_to(selection.end, false),
+                _ => {},
+            }
+        }
+
+        let target = match motion {
+            Motion::Left => self.previous_boundary(),
+            Motion::Right => self.next_boundary(),
+            Motion::Start => 0,
+            Motion::End => self.text.len(),
+        };
+        self.move_to(target, extend_selection);
+    }
+
+    /// Replace the selection with the given text and place the cursor after it
+    pub fn insert(&mut self, text: &str) {
+        let text = normalize_newlines(text, self.is_multiline);
+        let selection = self.selection();
+
+        self.text.replace_range(selection.clone(), &text);
+        self.move_to(selection.start + text.len(), false);
+    }
+
+    /// Replace the text that is being composed (or, if there is none, the selection)
+    /// with a new preliminary text
+    ///
+    /// The cursor is placed after the composed text.
+    pub fn set_composition(&mut self, text: &str) {
+        let text = normalize_newlines(text, self.is_multiline);
+        let range = self.composition.take().unwrap_or_else(|| self.selection());
+
+        self.text.replace_range(range.clone(), &text);
+        self.move_to(range.start + text.len(), false);
+
+        if !text.is_empty() {
+            self.composition = Some(range.start..range.start + text.len());
+        }
+    }
+
+    /// Replace the text that is being composed (or, if there is none, the selection)
+    /// with its final version and stop composing
+    pub fn commit_composition(&mut self, text: &str) {
+        self.set_composition(text);
+        self.composition = None;
+    }
+
+    /// Delete the selection or, if there is none, the character before the cursor
+    pub fn delete_backward(&mut self) {
+        if !self.has_selection() {
+            self.anchor = self.previous_boundary();
+        }
+        self.insert("");
+    }
+
+    /// Delete the selection or, if there is none, the character after the cursor
+    pub fn delete_forward(&mut self) {
+        if !self.has_selection() {
+            self.anchor = self.next_boundary();
+        }
+        self.insert("");
+    }
+
+    #[must_use]
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(index, _)| index)
+    }
+
+    #[must_use]
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_delete() {
+        let mut editor = TextEditor::new("hllo", true);
+        editor.move_cursor(Motion::Right, false);
+        editor.insert("e");
+        assert_eq!(editor.text(), "hello");
+        assert_eq!(editor.cursor(), 2);
+
+        editor.move_cursor(Motion::End, false);
+        editor.insert("\r\nwörld");
+        assert_eq!(editor.text(), "hello\nwörld");
+
+        editor.move_cursor(Motion::Left, false);
+        editor.move_cursor(Motion::Left, false);
+        editor.delete_backward();
+        assert_eq!(editor.text(), "hello\nwöld");
+
+        editor.delete_backward();
+        editor.delete_forward();
+        assert_eq!(editor.text(), "hello\nwd");
+
+        editor.move_cursor(Motion::Start, false);
+        editor.delete_backward();
+        assert_eq!(editor.text(), "hello\nwd");
+    }
+
+    #[test]
+    fn selection() {
+        let mut editor = TextEditor::new("one two", false);
+        editor.move_to(3, false);
+        editor.move_cursor(Motion::End, true);
+        assert_eq!(&editor.text()[editor.selection()], " two");
+
+        // Moving without shift collapses the selection
+        editor.move_cursor(Motion::Left, false);
+        assert!(!editor.has_selection());
+        assert_eq!(editor.cursor(), 3);
+
+        editor.move_cursor(Motion::Start, true);
+        assert_eq!(&editor.text()[editor.selection()], "one");
+        editor.insert("1");
+        assert_eq!(editor.text(), "1 two");
+
+        editor.move_cursor(Motion::End, false);
+        editor.move_cursor(Motion::Start, true);
+        editor.delete_forward();
+        assert_eq!(editor.text(), "");
+    }
+
+    #[test]
+    fn compose_text() {
+        let mut editor = TextEditor::new("ab", false);
+        editor.move_cursor(Motion::Right, false);
+
+        editor.set_composition("n");
+        editor.set_composition("に");
+        assert_eq!(editor.text(), "aにb");
+        assert_eq!(editor.composition(), Some(1..4));
+        assert_eq!(editor.cursor(), 4);
+
+        editor.commit_composition("日本");
+        assert_eq!(editor.text(), "a日本b");
+        assert_eq!(editor.composition(), None);
+        assert_eq!(editor.cursor(), 7);
+
+        // Composing replaces the selection, cancelling the composition removes the composed text
+        editor.move_cursor(Motion::Start, true);
+        editor.set_composition("x");
+        assert_eq!(editor.text(), "xb");
+        editor.commit_composition("");
+        assert_eq!(editor.text(), "b");
+        assert_eq!(editor.cursor(), 0);
+    }
+
+    #[test]
+    fn single_line_editors_drop_line_breaks() {
+        let mut editor = TextEditor::new("a\nb", false);
+        assert_eq!(editor.text(), "ab");
+
+        editor.insert("c\r\nd");
+        assert_eq!(editor.text(), "cdab");
+    }
+}
@@ -0,0 +1,56 @@
+//! The javascript interface to the [encodings] crate
+//!
+//! <https://encoding.spec.whatwg.org/#api>
+
+mod text_decoder;
+mod text_encoder;
+
+pub use text_decoder::{TextDecodeOptions, TextDecoder, TextDecoderOptions};
+pub use text_encoder::{EncodeIntoResult, TextEncoder};
+
+/// <https://webidl.spec.whatwg.org/#BufferSource>
+#[derive(Clone, Debug)]
+pub enum BufferSource {
+    ArrayBuffer(js::ArrayBuffer),
+    TypedArray(js::TypedArray),
+    DataView(js::DataView),
+}
+
+impl BufferSource {
+    /// Detached buffers are treated as if they were empty
+    ///
+    /// <https://webidl.spec.whatwg.org/#dfn-get-buffer-source-copy>
+    #[must_use]
+    pub fn copy_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::ArrayBuffer(buffer) => buffer.copy_bytes(0, buffer.byte_length()),
+            Self::TypedArray(typed_array) => typed_array
+                .buffer()
+                .copy_bytes(typed_array.byte_offset(), typed_array.byte_length()),
+            Self::DataView(data_view) => match (data_view.byte_offset(), data_view.byte_length()) {
+                (Ok(byte_offset), Ok(byte_length)) => {
+                    data_view.buffer().copy_bytes(byte_offset, byte_length)
+                },
+                _ => vec![],
+            },
+        }
+    }
+}
+
+impl From<js::ArrayBuffer> for BufferSource {
+    fn from(value: js::ArrayBuffer) -> Self {
+        Self::ArrayBuffer(value)
+    }
+}
+
+impl From<js::TypedArray> for BufferSource {
+    fn from(value: js::TypedArray) -> Self {
+        Self::TypedArray(value)
+    }
+}
+
+impl From<js::DataView> for BufferSource {
+    fn from(value: js::DataView) -> Self {
+        Self::DataView(value)
+    }
+}
@@ -0,0 +1,147 @@
+use encodings::{Encoding, ErrorMode, StreamDecoder};
+use js::{Exception, ThrowCompletionOr};
+
+use super::BufferSource;
+
+/// <https://encoding.spec.whatwg.org/#textdecoderoptions>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TextDecoderOptions {
+    /// Throw on invalid input instead of inserting `U+FFFD`
+    pub fatal: bool,
+
+    /// Keep a leading byte order mark in the output
+    pub ignore_bom: bool,
+}
+
+/// <https://encoding.spec.whatwg.org/#textdecodeoptions>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TextDecodeOptions {
+    /// More input follows in another call to [TextDecoder::decode]
+    pub stream: bool,
+}
+
+/// <https://encoding.spec.whatwg.org/#interface-textdecoder>
+#[derive(Clone, Copy, Debug)]
+pub struct TextDecoder {
+    decoder: StreamDecoder,
+}
+
+impl TextDecoder {
+    /// Create a decoder for the encoding with the given label, like `"utf-8"` or `"latin1"`
+    ///
+    /// [Specification](https://encoding.spec.whatwg.org/#dom-textdecoder)
+    pub fn new(label: &str, options: TextDecoderOptions) -> ThrowCompletionOr<Self> {
+        // 1. Let encoding be the result of getting an encoding from label.
+        // 2. If encoding is failure or replacement, then throw a RangeError.
+        let encoding: Encoding = label.parse().map_err(|_| Exception::range_error())?;
+        if encoding == Encoding::replacement {
+            return Err(Exception::range_error());
+        }
+
+        // 4. If options["fatal"] is true, then set this’s error mode to "fatal".
+        let error_mode = if options.fatal {
+            ErrorMode::Fatal
+        } else {
+            ErrorMode::Replacement
+        };
+
+        // 3. Set this’s encoding to encoding.
+        // 5. Set this’s ignore BOM to options["ignoreBOM"].
+        // FIXME: Decoders for most legacy encodings are still missing. Throwing
+        //        allows scripts to fall back to their own implementation
+        let decoder = StreamDecoder::new(encoding, error_mode, options.ignore_bom)
+            .map_err(|_| Exception::range_error())?;
+
+        Ok(Self { decoder })
+    }
+
+    /// <https://encoding.spec.whatwg.org/#dom-textdecoder-encoding>
+    #[must_use]
+    pub fn encoding(&self) -> String {
+        self.decoder.encoding().name().to_ascii_lowercase()
+    }
+
+    /// <https://encoding.spec.whatwg.org/#dom-textdecoder-fatal>
+    #[must_use]
+    pub fn fatal(&self) -> bool {
+        self.decoder.error_mode() == ErrorMode::Fatal
+    }
+
+    /// <https://encoding.spec.whatwg.org/#dom-textdecoder-ignorebom>
+    #[must_use]
+    pub fn ignore_bom(&self) -> bool {
+        self.decoder.ignore_bom()
+    }
+
+    /// <https://encoding.spec.whatwg.org/#dom-textdecoder-decode>
+    pub fn decode(
+        &mut self,
+        input: Option<&BufferSource>,
+        options: TextDecodeOptions,
+    ) -> ThrowCompletionOr<String> {
+        let bytes = input.map(BufferSource::copy_bytes).unwrap_or_default();
+
+        self.decoder
+            .decode(&bytes, options.stream)
+            .map_err(|_| Exception::type_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use js::{ArrayBuffer, ElementType, TypedArray};
+
+    #[test]
+    fn labels() {
+        let decoder =
+            TextDecoder::new(" Unicode-1-1-UTF-8", TextDecoderOptions::default()).unwrap();
+        assert_eq!(decoder.encoding(), "utf-8");
+
+        let decoder = TextDecoder::new("utf-16", TextDecoderOptions::default()).unwrap();
+        assert_eq!(decoder.encoding(), "utf-16le");
+
+        assert!(TextDecoder::new("iso-2022-kr", TextDecoderOptions::default()).is_err());
+        assert!(TextDecoder::new("not an encoding", TextDecoderOptions::default()).is_err());
+    }
+
+    #[test]
+    fn decode_views() {
+        let buffer = ArrayBuffer::from_bytes(b"\xEF\xBB\xBFhello \xE2\x82\xAC".to_vec());
+        let mut decoder = TextDecoder::new("utf-8", TextDecoderOptions::default()).unwrap();
+
+        assert_eq!(
+            decoder
+                .decode(Some(&buffer.clone().into()), TextDecodeOptions::default())
+                .unwrap(),
+            "hello €"
+        );
+
+        // The view ends in the middle of the euro sign
+        let view = TypedArray::with_buffer(buffer, ElementType::Uint8, 3, Some(8)).unwrap();
+        let stream = TextDecodeOptions { stream: true };
+        assert_eq!(
+            decoder.decode(Some(&view.into()), stream).unwrap(),
+            "hello "
+        );
+        assert_eq!(
+            decoder.decode(None, TextDecodeOptions::default()).unwrap(),
+            "\u{FFFD}"
+        );
+    }
+
+    #[test]
+    fn fatal() {
+        let options = TextDecoderOptions {
+            fatal: true,
+            ignore_bom: false,
+        };
+        let mut decoder = TextDecoder::new("utf-8", options).unwrap();
+        assert!(decoder.fatal());
+
+        let invalid = ArrayBuffer::from_bytes(vec![b'a', 0xFF]).into();
+        assert!(decoder
+            .decode(Some(&invalid), TextDecodeOptions::default())
+            .is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use js::{ArrayBuffer, ElementType, Exception, ThrowCompletionOr, TypedArray};
+
+/// <https://encoding.spec.whatwg.org/#interface-textencoder>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TextEncoder;
+
+/// <https://encoding.spec.whatwg.org/#dictdef-textencoderencodeintoresult>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodeIntoResult {
+    /// The number of UTF-16 code units that were read from the source
+    pub read: usize,
+
+    /// The number of bytes that were written to the destination
+    pub written: usize,
+}
+
+impl TextEncoder {
+    /// <https://encoding.spec.whatwg.org/#dom-textencoderencoderstream-encoding>
+    #[must_use]
+    pub const fn encoding(&self) -> &'static str {
+        "utf-8"
+    }
+
+    /// Encode `input` as UTF-8 and return a new `Uint8Array` with the bytes
+    ///
+    /// [Specification](https://encoding.spec.whatwg.org/#dom-textencoder-encode)
+    #[must_use]
+    pub fn encode(&self, input: &str) -> TypedArray {
+        // NOTE: Rust strings are UTF-8 already
+        let buffer = ArrayBuffer::from_bytes(input.as_bytes().to_vec());
+
+        TypedArray::with_buffer(buffer, ElementType::Uint8, 0, None)
+            .expect("byte arrays can view any buffer")
+    }
+
+    /// Encode as much of `source` as fits into the `destination` Uint8Array
+    ///
+    /// Characters are never split, so there might be some space left in the destination.
+    ///
+    /// [Specification](https://encoding.spec.whatwg.org/#dom-textencoder-encodeinto)
+    pub fn encode_into(
+        &self,
+        source: &str,
+        destination: &TypedArray,
+    ) -> ThrowCompletionOr<EncodeIntoResult> {
+        if destination.element_type() != ElementType::Uint8 {
+            return Err(Exception::type_error());
+        }
+
+        // 1. Let read be 0.
+        // 2. Let written be 0.
+        let mut result = EncodeIntoResult::default();
+
+        // 3. Let encoder be an instance of the UTF-8 encoder.
+        // 4. Let unused be the I/O queue of scalar values « end-of-queue ».
+        // 5. Let encode be the result of converting source to an I/O queue of scalar values.
+        // 6. While true:
+        let available = destination.byte_length();
+        let mut buffer = [0; 4];
+        for c in source.chars() {
+            // 1. Let item be the result of reading from src.
+            // 2. Let result be the result of running encoder’s handler on unused and item.
+            let bytes = c.encode_utf8(&mut buffer).as_bytes();
+
+            // 4. Otherwise:
+            //    1. If destination’s byte length − written is greater than or equal to the number
+            //       of bytes in result, then:
+            if available - result.written < bytes.len() {
+                // 2. Otherwise, break.
+                break;
+            }
+
+            // 1. If item is greater than U+FFFF, then increment read by 2.
+            // 2. Otherwise, increment read by 1.
+            result.read += c.len_utf16();
+
+            // 3. Write the bytes in result into destination, with startingOffset set to written.
+            destination
+                .buffer()
+                .write_bytes(destination.byte_offset() + result.written, bytes);
+
+            // 4. Increment written by the number of bytes in result.
+            result.written += bytes.len();
+        }
+
+        // 3. If result is finished, then return «[ "read" → read, "written" → written ]».
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use js::{Number, Value};
+
+    #[test]
+    fn encode() {
+        let bytes = TextEncoder.encode("a€");
+        assert_eq!(bytes.length(), 4);
+        assert_eq!(
+            bytes.get(Number::new(1.)),
+            Value::from(Number::new(0xE2 as f64))
+        );
+    }
+
+    #[test]
+    fn encode_into() {
+        let destination = TypedArray::new(ElementType::Uint8, 6).unwrap();
+
+        // The emoji needs four bytes, but only three are left
+        let result = TextEncoder.encode_into("a€😀", &destination).unwrap();
+        assert_eq!(
+            result,
+            EncodeIntoResult {
+                read: 2,
+                written: 4
+            }
+        );
+        assert_eq!(
+            destination.get(Number::new(3.)),
+            Value::from(Number::new(0xAC as f64))
+        );
+
+        let words = TypedArray::new(ElementType::Uint16, 4).unwrap();
+        assert!(TextEncoder.encode_into("a", &words).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! Internal pages that are displayed when a navigation fails
+
+use std::{error::Error, fmt::Write, io};
+
+use http::{request::HTTPError, CertificateProblem};
+use resourceloader::ResourceLoadError;
+use url::URL;
+
+use crate::{html::escape_into, BrowsingContextError};
+
+/// Navigating to this url from a certificate error page adds an exception for the host
+/// and loads the page anyways
+pub const PROCEED_UNSAFELY_URL: &str = "about:proceed-unsafely";
+
+const TEMPLATE: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/error_page.html"));
+
+/// Describes why a page could not be displayed, in terms that make sense to the user
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Diagnosis {
+    title: String,
+    description: String,
+}
+
+/// Create a html document that explains why the page at `url` could not be loaded
+#[must_use]
+pub fn render(url: &URL, error: &BrowsingContextError) -> String {
+    let diagnosis = diagnose(url, error);
+
+    let mut html = String::with_capacity(TEMPLATE.len());
+    let mut remaining = TEMPLATE;
+
+    while let Some(start) = remaining.find("{{") {
+        html.push_str(&remaining[..start]);
+        remaining = &remaining[start + 2..];
+
+        let Some(end) = remaining.find("}}") else {
+            break;
+        };
+        let placeholder = &remaining[..end];
+        remaining = &remaining[end + 2..];
+
+        match placeholder {
+            "title" => escape_into(&mut html, &diagnosis.title),
+            "description" => escape_into(&mut html, &diagnosis.description),
+            "url" => escape_into(&mut html, url.serialize(url::ExcludeFragment::No).as_str()),
+            "details" => write_details(&mut html, error),
+            "actions" => write_actions(&mut html, url, error),
+            _ => log::warn!("Unknown placeholder in error page template: {placeholder:?}"),
+        }
+    }
+
+    html.push_str(remaining);
+    html
+}
+
+/// List the error and all of its causes, one per line
+fn write_details(html: &mut String, error: &BrowsingContextError) {
+    let mut current: Option<&dyn Error> = Some(error);
+
+    while let Some(error) = current {
+        html.push_str("<div>");
+        escape_into(html, &error.to_string());
+        html.push_str("</div>");
+
+        current = error.source();
+    }
+}
+
+fn write_actions(html: &mut String, url: &URL, error: &BrowsingContextError) {
+    let mut write_action = |href: &str, label: &str| {
+        html.push_str("<a href=\"");
+        escape_into(html, href);
+        html.push_str("\">");
+        escape_into(html, label);
+        html.push_str("</a>");
+    };
+
+    write_action(
+        url.serialize(url::ExcludeFragment::No).as_str(),
+        "Try again",
+    );
+
+    // Hosts that require strict transport security must not be visited with an invalid certificate
+    if let Some(certificate_error) = certificate_error(error)
+        && !http::HSTS.is_known_host(&certificate_error.host)
+    {
+        write_action(PROCEED_UNSAFELY_URL, "Proceed anyway (unsafe)");
+    }
+
+    // Sometimes only a single page is broken, offer to go to the start page instead
+    if matches!(url.scheme().as_str(), "http" | "https")
+        && url.path().as_str() != "/"
+        && let Some(host) = url.host()
+    {
+        let mut root = format!("{}://{host}", url.scheme());
+        if let Some(port) = url.port() {
+            let _ = write!(root, ":{port}");
+        }
+        root.push('/');
+
+        write_action(&root, "Go to start page");
+    }
+}
+
+/// Return the certificate error that caused the navigation to fail, if any
+#[must_use]
+pub fn certificate_error(error: &BrowsingContextError) -> Option<&http::CertificateError> {
+    match error {
+        BrowsingContextError::Loading(ResourceLoadError::HTTP(HTTPError::Certificate(
+            certificate_error,
+        ))) => Some(certificate_error),
+        _ => None,
+    }
+}
+
+fn diagnose(url: &URL, error: &BrowsingContextError) -> Diagnosis {
+    let diagnosis = |title: &str, description: String| Diagnosis {
+        title: title.to_string(),
+        description,
+    };
+
+    let host = url
+        .host()
+        .map(|host| host.to_string())
+        .unwrap_or_else(|| url.serialize(url::ExcludeFragment::Yes).to_string());
+
+    let load_error = match error {
+        BrowsingContextError::Loading(load_error) => load_error,
+        BrowsingContextError::InvalidViewSourceURL => {
+            return diagnosis(
+                "Invalid address",
+                "The address of the page whose source should be displayed is not valid."
+                    .to_string(),
+            );
+        },
+        BrowsingContextError::InvalidXml(xml_error) => {
+            return diagnosis(
+                "Broken document",
+                format!(
+                    "The page at {} cannot be displayed because it contains an error in line {}.",
+                    url.serialize(url::ExcludeFragment::Yes),
+                    xml_error.line
+                ),
+            );
+        },
+        BrowsingContextError::NotAFeed => {
+            return diagnosis(
+                "Broken feed",
+                format!(
+                    "The feed at {} cannot be displayed because its format is not supported.",
+                    url.serialize(url::ExcludeFragment::Yes)
+                ),
+            );
+        },
+        BrowsingContextError::NavigationNotAllowed => {
+            return diagnosis(
+                "Blocked address",
+                format!(
+                    "For your safety, {} can only be opened from the address bar.",
+                    url.serialize(url::ExcludeFragment::Yes)
+                ),
+            );
+        },
+        BrowsingContextError::UnknownInternalPage => {
+            return diagnosis(
+                "Page not found",
+                format!(
+                    "There is no internal page at {}.",
+                    url.serialize(url::ExcludeFragment::Yes)
+                ),
+            );
+        },
+    };
+
+    match load_error {
+        ResourceLoadError::HTTP(HTTPError::DNS(_)) => diagnosis(
+            "Server not found",
+            format!(
+                "Stormlicht could not find the server at {host}. Check the address for typing errors and make sure that you are connected to the internet."
+            ),
+        ),
+        ResourceLoadError::HTTP(HTTPError::Certificate(certificate_error)) => {
+            let reason = match certificate_error.problem {
+                CertificateProblem::Expired => format!("The certificate of {host} has expired."),
+                CertificateProblem::NotValidYet => format!(
+                    "The certificate of {host} is not valid yet. Make sure that your system clock is set correctly."
+                ),
+                CertificateProblem::HostnameMismatch => format!(
+                    "The certificate presented by {host} belongs to a different website."
+                ),
+                CertificateProblem::UnknownIssuer => format!(
+                    "The certificate of {host} was not issued by a trusted certificate authority."
+                ),
+                CertificateProblem::Revoked => {
+                    format!("The certificate of {host} has been revoked by its issuer.")
+                },
+                CertificateProblem::Invalid => format!("The certificate of {host} is invalid."),
+            };
+
+            diagnosis(
+                "Your connection is not secure",
+                format!(
+                    "{reason} Someone might be trying to impersonate the site or steal your information. If you proceed anyway, the data you send and receive may be visible to others."
+                ),
+            )
+        },
+        ResourceLoadError::HTTP(HTTPError::Tls(_)) => diagnosis(
+            "Secure connection failed",
+            format!(
+                "A secure connection to {host} could not be established. The page cannot be shown because the authenticity of the received data could not be verified."
+            ),
+        ),
+        ResourceLoadError::HTTP(HTTPError::Status(status)) => {
+            let description = if status.is_server_error() {
+                format!("The server at {host} encountered an error while processing the request.")
+            } else {
+                format!("The server at {host} could not fulfill the request.")
+            };
+            diagnosis(&format!("Error {status}"), description)
+        },
+        ResourceLoadError::HTTP(HTTPError::IO(_)) => diagnosis(
+            "Unable to connect",
+            format!("The connection to {host} failed or was interrupted."),
+        ),
+        ResourceLoadError::HTTP(HTTPError::TunnelRefused) => diagnosis(
+            "Proxy refused the connection",
+            format!("The configured proxy server refused to connect to {host}."),
+        ),
+        ResourceLoadError::HTTP(HTTPError::RedirectLoop) => diagnosis(
+            "Too many redirects",
+            format!("The server at {host} is redirecting the request in a way that will never complete."),
+        ),
+        ResourceLoadError::HTTP(_) => diagnosis(
+            "Invalid response",
+            format!("The server at {host} sent a response that could not be understood."),
+        ),
+        ResourceLoadError::UnsupportedScheme => diagnosis(
+            "Unsupported address",
+            format!(
+                "Stormlicht does not know how to open \"{}:\" addresses.",
+                url.scheme()
+            ),
+        ),
+        // Only files are read directly, other protocols that fail with io errors use the network
+        ResourceLoadError::IO(_) if url.scheme().as_str() != "file" => diagnosis(
+            "Unable to connect",
+            format!("The connection to {host} failed or was interrupted."),
+        ),
+        ResourceLoadError::IO(io_error) if io_error.kind() == io::ErrorKind::NotFound => diagnosis(
+            "File not found",
+            "The file does not exist. Check the file name for typing errors.".to_string(),
+        ),
+        ResourceLoadError::IO(_) => diagnosis(
+            "Unable to read file",
+            "The file exists but could not be read.".to_string(),
+        ),
+        ResourceLoadError::Panicked => diagnosis(
+            "Loading failed",
+            "Stormlicht ran into an internal error while loading the page.".to_string(),
+        ),
+        ResourceLoadError::UnknownBlobURL => diagnosis(
+            "Page no longer available",
+            "The address was revoked by the page that created it.".to_string(),
+        ),
+        ResourceLoadError::InvalidFilePath
+        | ResourceLoadError::InvalidDataURL
+        | ResourceLoadError::MissingHost
+        | ResourceLoadError::InvalidFingerURL
+        | ResourceLoadError::Base64(_) => diagnosis(
+            "Invalid address",
+            "The address is not valid and cannot be loaded.".to_string(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_error_page() {
+        let url: URL = "https://example.com/missing?a=<b>".parse().unwrap();
+        let error = BrowsingContextError::Loading(ResourceLoadError::HTTP(HTTPError::Status(
+            http::StatusCode::NOT_FOUND,
+        )));
+
+        assert_eq!(diagnose(&url, &error).title, "Error 404 (not found)");
+
+        let html = render(&url, &error);
+        assert!(!html.contains("{{"));
+        assert!(html.contains("<div>failed to load resource</div>"));
+        assert!(html.contains("<div>status code indicates error</div>"));
+        assert!(html.contains("<a href=\"https://example.com/\">Go to start page</a>"));
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn unsupported_scheme() {
+        let url: URL = "gopher://example.com".parse().unwrap();
+        let error = BrowsingContextError::Loading(ResourceLoadError::UnsupportedScheme);
+
+        let diagnosis = diagnose(&url, &error);
+        assert_eq!(diagnosis.title, "Unsupported address");
+        assert!(diagnosis.description.contains("\"gopher:\""));
+        assert!(!render(&url, &error).contains("Go to start page"));
+    }
+
+    #[test]
+    fn certificate_interstitial() {
+        let url: URL = "https://self-signed.example.com".parse().unwrap();
+        let error = BrowsingContextError::Loading(ResourceLoadError::HTTP(HTTPError::Certificate(
+            http::CertificateError {
+                host: "self-signed.example.com".to_string(),
+                problem: CertificateProblem::UnknownIssuer,
+            },
+        )));
+
+        let diagnosis = diagnose(&url, &error);
+        assert_eq!(diagnosis.title, "Your connection is not secure");
+        assert!(diagnosis
+            .description
+            .contains("not issued by a trusted certificate authority"));
+        assert!(render(&url, &error).contains(&format!(
+            "<a href=\"{PROCEED_UNSAFELY_URL}\">Proceed anyway (unsafe)</a>"
+        )));
+    }
+}
@@ -1,8 +1,14 @@
+use std::path::PathBuf;
+
 use math::Vec2D;
 
+use crate::scroll;
+
 #[derive(Clone, Copy, Debug)]
 pub enum Event {
     Mouse(MouseEvent),
+    Key(KeyEvent),
+    Wheel(WheelEvent),
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -25,6 +31,96 @@ pub enum MouseButton {
     Right,
 }
 
+/// Scrolling with a mouse wheel or a touchpad
+#[derive(Clone, Copy, Debug)]
+pub struct WheelEvent {
+    /// The distance to scroll, in lines
+    pub delta: Vec2D<f32>,
+}
+
+impl WheelEvent {
+    /// The distance to scroll, in CSS pixels
+    #[must_use]
+    pub fn distance(&self) -> Vec2D<f32> {
+        self.delta
+            .map(|lines| f32::from(scroll::LINE_HEIGHT) * lines)
+    }
+}
+
+/// Keys that web content reacts to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Home,
+    End,
+    Enter,
+    Space,
+    Escape,
+    Backspace,
+    Delete,
+
+    /// A key that produces text when typed into a form control
+    Character(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+
+    /// Whether the shift key was held down, which extends the selection in text controls
+    pub shift: bool,
+}
+
+impl KeyEvent {
+    #[must_use]
+    pub fn new(key: Key) -> Self {
+        Self { key, shift: false }
+    }
+
+    #[must_use]
+    pub fn with_shift(mut self, shift: bool) -> Self {
+        self.shift = shift;
+        self
+    }
+}
+
+/// Text that is entered through an input method over multiple key presses, like CJK characters
+///
+/// <https://w3c.github.io/uievents/#events-compositionevents>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompositionEvent {
+    /// The input method started composing text
+    Start,
+
+    /// The preliminary text changed
+    Update(String),
+
+    /// The input method committed the final text, which might be empty if the composition was cancelled
+    End(String),
+}
+
+/// Files that are dragged onto the page from another application
+///
+/// The browsing context turns these into the `dragenter`, `dragover`, `dragleave` and `drop`
+/// events of the [drag-and-drop processing model](https://html.spec.whatwg.org/multipage/dnd.html#drag-and-drop-processing-model).
+#[derive(Clone, Debug, PartialEq)]
+pub enum DragEvent {
+    /// The files entered the page or were moved to a new position
+    Move(Vec2D<i32>),
+
+    /// The files were dragged away from the page, or the drag was cancelled
+    Leave,
+
+    /// The files were dropped at the given position
+    Drop {
+        position: Vec2D<i32>,
+        paths: Vec<PathBuf>,
+    },
+}
+
 impl MouseEvent {
     /// Construct a event indicating cursor movement
     #[must_use]
@@ -34,4 +130,13 @@ impl MouseEvent {
             kind: MouseEventKind::Move,
         }
     }
+
+    /// Construct a event indicating that a mouse button was pressed
+    #[must_use]
+    pub fn button_down(position: Vec2D<i32>, button: MouseButton) -> Self {
+        Self {
+            position,
+            kind: MouseEventKind::Down(button),
+        }
+    }
 }
@@ -0,0 +1,352 @@
+//! Displays RSS and Atom feeds as a page that lists their entries
+//!
+//! Supports [RSS 2.0](https://www.rssboard.org/rss-specification), RSS 1.0
+//! and [Atom](https://www.rfc-editor.org/rfc/rfc4287). Feeds often embed html in
+//! their entries, which is [sanitized](crate::html::sanitizer) before it is displayed.
+
+use crate::{
+    dom::{
+        dom_objects::{Document, Element, Node, Text},
+        DomPtr,
+    },
+    html::{
+        escape_into,
+        sanitizer::{self, SanitizerConfig},
+    },
+    static_interned,
+};
+
+const STYLESHEET: &str = "
+body { background-color: #fafafa; color: #222222; font-family: sans-serif; margin: 48px; }
+header { border-bottom: 1px solid #dddddd; margin-bottom: 32px; max-width: 700px; }
+article { margin-bottom: 32px; max-width: 700px; }
+h2 { font-size: 1.25em; margin-bottom: 4px; }
+time { color: #666666; display: block; font-size: 0.875em; margin-bottom: 8px; }
+a { color: #1a5fb4; }
+img { max-width: 100%; }
+";
+
+/// The content of a RSS or Atom feed
+#[derive(Clone, Debug)]
+pub struct Feed {
+    pub title: String,
+    description: String,
+
+    /// The website that the feed belongs to
+    link: Option<String>,
+    entries: Vec<Entry>,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    title: String,
+    link: Option<String>,
+
+    /// As it appears in the feed, dates are not parsed
+    date: Option<String>,
+
+    /// The sanitized html content of the entry
+    summary: String,
+}
+
+/// Read the feed from a parsed xml document
+///
+/// Returns `None` if the document is not a feed.
+#[must_use]
+pub fn extract(document: &DomPtr<Document>) -> Option<Feed> {
+    let root = document
+        .borrow()
+        .children()
+        .iter()
+        .find_map(|child| child.try_into_type::<Element>())?;
+
+    let local_name = root.borrow().local_name();
+    match local_name.as_str() {
+        // RSS 2.0, where items are part of the channel
+        "rss" => {
+            let channel = child_element(&root, "channel")?;
+            Some(extract_rss(&channel, &channel))
+        },
+        // RSS 1.0, where items are siblings of the channel
+        "RDF" => {
+            let channel = child_element(&root, "channel")?;
+            Some(extract_rss(&channel, &root))
+        },
+        "feed" => Some(extract_atom(&root)),
+        _ => None,
+    }
+}
+
+/// Create a html document that displays the feed
+#[must_use]
+pub fn render(feed: &Feed) -> String {
+    let mut html = String::from("<!DOCTYPE html><html><head><title>");
+    escape_into(&mut html, &feed.title);
+    html.push_str("</title><style>");
+    html.push_str(STYLESHEET);
+    html.push_str("</style></head><body><header><h1>");
+    write_link(&mut html, &feed.title, feed.link.as_deref());
+    html.push_str("</h1>");
+
+    if !feed.description.is_empty() {
+        html.push_str("<p>");
+        escape_into(&mut html, &feed.description);
+        html.push_str("</p>");
+    }
+    html.push_str("</header>");
+
+    if feed.entries.is_empty() {
+        html.push_str("<p>This feed has no entries.</p>");
+    }
+
+    for entry in &feed.entries {
+        html.push_str("<article><h2>");
+        write_link(&mut html, &entry.title, entry.link.as_deref());
+        html.push_str("</h2>");
+
+        if let Some(date) = &entry.date {
+            html.push_str("<time>");
+            escape_into(&mut html, date);
+            html.push_str("</time>");
+        }
+
+        html.push_str("<div>");
+        html.push_str(&entry.summary);
+        html.push_str("</div></article>");
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// <https://www.rssboard.org/rss-specification>
+fn extract_rss(channel: &DomPtr<Element>, item_parent: &DomPtr<Element>) -> Feed {
+    let sanitizer = SanitizerConfig::default();
+
+    let entries = child_elements(item_parent)
+        .filter(|element| element.borrow().local_name().as_str() == "item")
+        .map(|item| Entry {
+            title: child_text(&item, "title").unwrap_or_default(),
+            link: child_text(&item, "link"),
+            date: child_text(&item, "pubDate").or_else(|| child_text(&item, "date")),
+
+            // The description is escaped html
+            summary: sanitizer.sanitize(&child_text(&item, "description").unwrap_or_default()),
+        })
+        .collect();
+
+    Feed {
+        title: child_text(channel, "title").unwrap_or_default(),
+        description: child_text(channel, "description").unwrap_or_default(),
+        link: child_text(channel, "link"),
+        entries,
+    }
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc4287#section-4.1>
+fn extract_atom(feed: &DomPtr<Element>) -> Feed {
+    let entries = child_elements(feed)
+        .filter(|element| element.borrow().local_name().as_str() == "entry")
+        .map(|entry| Entry {
+            title: child_text(&entry, "title").unwrap_or_default(),
+            link: atom_link(&entry),
+            date: child_text(&entry, "updated").or_else(|| child_text(&entry, "published")),
+            summary: child_element(&entry, "content")
+                .or_else(|| child_element(&entry, "summary"))
+                .map(|content| atom_text_construct(&content))
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    Feed {
+        title: child_text(feed, "title").unwrap_or_default(),
+        description: child_text(feed, "subtitle").unwrap_or_default(),
+        link: atom_link(feed),
+        entries,
+    }
+}
+
+/// The `href` of the first `<link>` that refers to an alternate version of the feed or entry
+///
+/// <https://www.rfc-editor.org/rfc/rfc4287#section-4.2.7>
+fn atom_link(element: &DomPtr<Element>) -> Option<String> {
+    child_elements(element)
+        .filter(|child| child.borrow().local_name() == static_interned!("link"))
+        .find(|link| {
+            link.borrow()
+                .attributes()
+                .get(&static_interned!("rel"))
+                .is_none_or(|rel| rel.as_str() == "alternate")
+        })
+        .and_then(|link| {
+            link.borrow()
+                .attributes()
+                .get(&static_interned!("href"))
+                .map(ToString::to_string)
+        })
+}
+
+/// Convert the content of an atom text construct to sanitized html
+///
+/// <https://www.rfc-editor.org/rfc/rfc4287#section-3.1>
+fn atom_text_construct(element: &DomPtr<Element>) -> String {
+    let content_type = element
+        .borrow()
+        .attributes()
+        .get(&static_interned!("type"))
+        .map(ToString::to_string);
+
+    let mut html = String::new();
+    match content_type.as_deref() {
+        Some("html") => html = SanitizerConfig::default().sanitize(&text_content(element)),
+        Some("xhtml") => {
+            // The content is wrapped in a xhtml <div>
+            let sanitizer = SanitizerConfig::default();
+            for div in child_elements(element) {
+                for child in div.borrow().children() {
+                    sanitizer.write_node(child, &mut html);
+                }
+            }
+        },
+        _ => escape_into(&mut html, &text_content(element)),
+    }
+    html
+}
+
+fn write_link(html: &mut String, text: &str, link: Option<&str>) {
+    let link =
+        link.filter(|link| sanitizer::has_allowed_scheme(link, &sanitizer::DEFAULT_URL_SCHEMES));
+
+    let Some(link) = link else {
+        escape_into(html, text);
+        return;
+    };
+
+    html.push_str("<a href=\"");
+    escape_into(html, link);
+    html.push_str("\">");
+    escape_into(html, text);
+    html.push_str("</a>");
+}
+
+fn child_elements(element: &DomPtr<Element>) -> impl Iterator<Item = DomPtr<Element>> {
+    element
+        .borrow()
+        .children()
+        .iter()
+        .filter_map(|child| child.try_into_type::<Element>())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+fn child_element(element: &DomPtr<Element>, local_name: &str) -> Option<DomPtr<Element>> {
+    child_elements(element).find(|child| child.borrow().local_name().as_str() == local_name)
+}
+
+/// The trimmed text content of a child element, if it exists and is not empty
+fn child_text(element: &DomPtr<Element>, local_name: &str) -> Option<String> {
+    let text = text_content(&child_element(element, local_name)?);
+    let text = text.trim();
+
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+fn text_content(element: &DomPtr<Element>) -> String {
+    fn visit(node: &DomPtr<Node>, text: &mut String) {
+        if let Some(text_node) = node.try_into_type::<Text>() {
+            text.push_str(text_node.borrow().content());
+            return;
+        }
+
+        for child in node.borrow().children() {
+            visit(child, text);
+        }
+    }
+
+    let mut text = String::new();
+    visit(&element.clone().upcast(), &mut text);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xml;
+
+    fn parse(source: &str) -> DomPtr<Document> {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        xml::Parser::new(source, document)
+            .parse()
+            .map(|(document, _)| document)
+            .expect("feed is not well-formed")
+    }
+
+    #[test]
+    fn extract_rss_feed() {
+        let source = "<?xml version=\"1.0\"?>
+            <rss version=\"2.0\"><channel>
+                <title>Example blog</title>
+                <link>https://example.com/</link>
+                <description>Posts about examples</description>
+                <item>
+                    <title>First post</title>
+                    <link>https://example.com/first</link>
+                    <pubDate>Mon, 06 Sep 2021 16:45:00 GMT</pubDate>
+                    <description>&lt;p onclick=\"steal()\"&gt;Hello &lt;b&gt;world&lt;/b&gt;&lt;/p&gt;</description>
+                </item>
+                <item><title>Second post</title><link>javascript:steal()</link></item>
+            </channel></rss>";
+
+        let feed = extract(&parse(source)).expect("document is a feed");
+        assert_eq!(feed.title, "Example blog");
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(feed.entries.len(), 2);
+        assert_eq!(feed.entries[0].summary, "<p>Hello <b>world</b></p>");
+
+        let html = render(&feed);
+        assert!(html.contains("<a href=\"https://example.com/first\">First post</a>"));
+        assert!(html.contains("<time>Mon, 06 Sep 2021 16:45:00 GMT</time>"));
+        assert!(html.contains("<h2>Second post</h2>"));
+        assert!(!html.contains("steal"));
+    }
+
+    #[test]
+    fn extract_atom_feed() {
+        let source = "<feed xmlns=\"http://www.w3.org/2005/Atom\">
+                <title>Example blog</title>
+                <link rel=\"self\" href=\"https://example.com/feed.atom\"/>
+                <link href=\"https://example.com/\"/>
+                <entry>
+                    <title>Text</title>
+                    <link href=\"https://example.com/text\"/>
+                    <updated>2021-09-06T16:45:00Z</updated>
+                    <summary>1 &lt; 2</summary>
+                </entry>
+                <entry>
+                    <title>Markup</title>
+                    <content type=\"xhtml\">
+                        <div xmlns=\"http://www.w3.org/1999/xhtml\"><p>Some <em>markup</em></p><script>steal()</script></div>
+                    </content>
+                </entry>
+            </feed>";
+
+        let feed = extract(&parse(source)).expect("document is a feed");
+        assert_eq!(feed.title, "Example blog");
+        assert_eq!(feed.link.as_deref(), Some("https://example.com/"));
+        assert_eq!(
+            feed.entries[0].link.as_deref(),
+            Some("https://example.com/text")
+        );
+        assert_eq!(feed.entries[0].summary, "1 &lt; 2");
+        assert_eq!(feed.entries[1].summary, "<p>Some <em>markup</em></p>");
+    }
+
+    #[test]
+    fn documents_without_feeds() {
+        assert!(extract(&parse("<html xmlns=\"http://www.w3.org/1999/xhtml\"/>")).is_none());
+    }
+}
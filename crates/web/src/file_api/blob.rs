@@ -0,0 +1,292 @@
+use std::{ops::Range, sync::Arc};
+
+use js::{ArrayBuffer, ElementType, TypedArray};
+
+use crate::encoding::BufferSource;
+
+use super::normalize_content_type;
+
+/// How line endings in strings are treated when they are added to a blob
+///
+/// <https://w3c.github.io/FileAPI/#enumdef-endingtype>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endings {
+    /// Strings are copied unchanged
+    #[default]
+    Transparent,
+
+    /// Line endings are converted to the ones used by the platform
+    Native,
+}
+
+/// <https://w3c.github.io/FileAPI/#dfn-BlobPropertyBag>
+#[derive(Clone, Debug, Default)]
+pub struct BlobPropertyBag {
+    /// The media type of the blob, like `image/png`
+    pub content_type: String,
+    pub endings: Endings,
+}
+
+/// The parts that a blob is constructed from
+///
+/// <https://w3c.github.io/FileAPI/#typedefdef-blobpart>
+#[derive(Clone, Debug)]
+pub enum BlobPart {
+    Buffer(BufferSource),
+    Blob(Blob),
+    String(String),
+}
+
+/// Immutable raw data
+///
+/// Slicing a blob does not copy any data, all slices share the bytes of the original blob.
+///
+/// [Specification](https://w3c.github.io/FileAPI/#blob-section)
+#[derive(Clone, Debug)]
+pub struct Blob {
+    bytes: Arc<[u8]>,
+
+    /// The part of `bytes` that belongs to this blob
+    range: Range<usize>,
+
+    /// Either the empty string or a lowercase, printable ascii string
+    content_type: String,
+}
+
+impl Blob {
+    /// [Specification](https://w3c.github.io/FileAPI/#constructorBlob)
+    #[must_use]
+    pub fn new(blob_parts: &[BlobPart], options: BlobPropertyBag) -> Self {
+        // 2. Let bytes be the result of processing blob parts given blobParts and options.
+        let bytes = process_blob_parts(blob_parts, options.endings);
+
+        // 3. If the type member of the options argument is not the empty string, run the following sub-steps:
+        let content_type = normalize_content_type(&options.content_type);
+
+        // 4. Return a Blob object referring to bytes as its associated byte sequence,
+        //    with its size set to the length of bytes, and its type set to the value of t
+        Self::from_bytes(bytes, content_type)
+    }
+
+    #[must_use]
+    pub(crate) fn from_bytes(bytes: Vec<u8>, content_type: String) -> Self {
+        Self {
+            range: 0..bytes.len(),
+            bytes: bytes.into(),
+            content_type,
+        }
+    }
+
+    /// The number of bytes in the blob
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-size)
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.range.len()
+    }
+
+    /// The media type of the blob, or the empty string if it is unknown
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-type)
+    #[must_use]
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[self.range.clone()]
+    }
+
+    /// The bytes of the blob in a buffer that can outlive it
+    ///
+    /// Bytes are only copied if the blob is a slice of a larger one.
+    #[must_use]
+    pub(crate) fn shared_bytes(&self) -> Arc<[u8]> {
+        if self.range == (0..self.bytes.len()) {
+            self.bytes.clone()
+        } else {
+            Arc::from(self.bytes())
+        }
+    }
+
+    /// A new blob that contains the bytes between `start` and `end`
+    ///
+    /// Negative offsets are relative to the end of the blob, like in [slice](https://tc39.es/ecma262/#sec-array.prototype.slice).
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-slice)
+    #[must_use]
+    pub fn slice(&self, start: Option<i64>, end: Option<i64>, content_type: Option<&str>) -> Self {
+        // 2. Let originalSize be blob’s size.
+        let original_size = self.size();
+
+        // 3. The start parameter is a value for the start point of a slice() call, and must be treated as a byte-order
+        //    position, with the zeroth position representing the first byte.
+        //    User agents must normalize start according to the following:
+        // 4. The end parameter is a value for the end point of a slice() call.
+        //    User agents must normalize end according to the following:
+        let relative_position = |position: i64| {
+            if position < 0 {
+                original_size.saturating_sub(position.unsigned_abs() as usize)
+            } else {
+                (position as usize).min(original_size)
+            }
+        };
+        let relative_start = start.map_or(0, relative_position);
+        let relative_end = end.map_or(original_size, relative_position);
+
+        // 5. The contentType parameter is used to set the ASCII-encoded string in lower case representing
+        //    the media type of the Blob.
+        let content_type = content_type.map(normalize_content_type).unwrap_or_default();
+
+        // 6. Let span be max((relativeEnd - relativeStart), 0).
+        let span = relative_end.saturating_sub(relative_start);
+
+        // 7. Return a new Blob object S with the following characteristics:
+        //    S refers to span consecutive bytes from blob’s associated byte sequence,
+        //    beginning with the byte at byte-order position relativeStart.
+        let start = self.range.start + relative_start;
+        Self {
+            bytes: self.bytes.clone(),
+            range: start..start + span,
+            content_type,
+        }
+    }
+
+    /// The contents of the blob, decoded as UTF-8
+    ///
+    /// This resolves the promise returned by `text()` once the blob was read.
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dom-blob-text)
+    #[must_use]
+    pub fn text(&self) -> String {
+        // 5. Return the result of transforming promise by a fulfillment handler that returns the result of
+        //    running UTF-8 decode on its first argument.
+        encodings::decode(self.bytes(), encodings::Encoding::UTF_8)
+            .expect("utf-8 is always supported")
+    }
+
+    /// This resolves the promise returned by `arrayBuffer()` once the blob was read
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dom-blob-arraybuffer)
+    #[must_use]
+    pub fn array_buffer(&self) -> ArrayBuffer {
+        ArrayBuffer::from_bytes(self.bytes().to_vec())
+    }
+
+    /// A `Uint8Array` with the contents of the blob
+    ///
+    /// This resolves the promise returned by `bytes()` once the blob was read.
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dom-blob-bytes)
+    #[must_use]
+    pub fn uint8_array(&self) -> TypedArray {
+        TypedArray::with_buffer(self.array_buffer(), ElementType::Uint8, 0, None)
+            .expect("byte arrays can view any buffer")
+    }
+}
+
+/// <https://w3c.github.io/FileAPI/#process-blob-parts>
+#[must_use]
+pub(super) fn process_blob_parts(parts: &[BlobPart], endings: Endings) -> Vec<u8> {
+    // 1. Let bytes be an empty sequence of bytes.
+    let mut bytes = vec![];
+
+    // 2. For each element in parts:
+    for element in parts {
+        match element {
+            // 1. If element is a USVString, run the following substeps:
+            BlobPart::String(string) => {
+                // 2. If the endings member of options is "native", set s to the result of converting line endings
+                //    to native of element.
+                // 3. Append the result of UTF-8 encoding s to bytes.
+                if endings == Endings::Native {
+                    bytes.extend_from_slice(convert_line_endings_to_native(string).as_bytes());
+                } else {
+                    bytes.extend_from_slice(string.as_bytes());
+                }
+            },
+
+            // 2. If element is a BufferSource, get a copy of the bytes held by the buffer source,
+            //    and append those bytes to bytes.
+            BlobPart::Buffer(buffer) => bytes.extend(buffer.copy_bytes()),
+
+            // 3. If element is a Blob, append the bytes it represents to bytes.
+            BlobPart::Blob(blob) => bytes.extend_from_slice(blob.bytes()),
+        }
+    }
+
+    // 3. Return bytes.
+    bytes
+}
+
+/// <https://w3c.github.io/FileAPI/#convert-line-endings-to-native>
+#[must_use]
+fn convert_line_endings_to_native(string: &str) -> String {
+    // 1. Let native line ending be the code point U+000A LF.
+    // 2. If the underlying platform’s conventions are to represent newlines as a carriage return and line feed
+    //    sequence, set native line ending to the code point U+000D CR followed by the code point U+000A LF.
+    let native_line_ending = if cfg!(windows) { "\r\n" } else { "\n" };
+
+    // 3-8. Replace every CRLF, CR and LF with native line ending
+    string
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('\n', native_line_ending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn construct_from_parts() {
+        let first = Blob::new(
+            &[BlobPart::String("a\r\nb\rc".to_string())],
+            BlobPropertyBag {
+                content_type: "Text/Plain".to_string(),
+                endings: Endings::Native,
+            },
+        );
+        assert_eq!(first.content_type(), "text/plain");
+        if cfg!(windows) {
+            assert_eq!(first.bytes(), b"a\r\nb\r\nc");
+        } else {
+            assert_eq!(first.bytes(), b"a\nb\nc");
+        }
+
+        let buffer = ArrayBuffer::from_bytes(vec![1, 2, 3]);
+        let second = Blob::new(
+            &[
+                BlobPart::Buffer(buffer.into()),
+                BlobPart::Blob(first.slice(Some(0), Some(1), None)),
+            ],
+            BlobPropertyBag {
+                content_type: "invalid\u{7F}".to_string(),
+                ..Default::default()
+            },
+        );
+        assert_eq!(second.bytes(), b"\x01\x02\x03a");
+        assert_eq!(second.content_type(), "");
+    }
+
+    #[test]
+    fn slice() {
+        let blob = Blob::new(
+            &[BlobPart::String("hello world".to_string())],
+            BlobPropertyBag::default(),
+        );
+
+        assert_eq!(blob.slice(Some(6), None, None).text(), "world");
+        assert_eq!(blob.slice(Some(-5), Some(-2), None).text(), "wor");
+        assert_eq!(blob.slice(Some(-100), Some(100), None).size(), 11);
+        assert_eq!(blob.slice(Some(8), Some(2), None).size(), 0);
+
+        // Slices of slices share the same bytes
+        let slice = blob.slice(Some(2), None, Some("TEXT/PLAIN"));
+        let nested = slice.slice(Some(1), Some(3), None);
+        assert_eq!(slice.content_type(), "text/plain");
+        assert_eq!(nested.text(), "lo");
+        assert!(Arc::ptr_eq(&blob.bytes, &nested.bytes));
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    fs, io,
+    ops::Deref,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{blob::process_blob_parts, normalize_content_type, Blob, BlobPart, Endings};
+
+/// <https://w3c.github.io/FileAPI/#dfn-FilePropertyBag>
+#[derive(Clone, Debug, Default)]
+pub struct FilePropertyBag {
+    /// The media type of the file, like `image/png`
+    pub content_type: String,
+    pub endings: Endings,
+
+    /// Milliseconds since the unix epoch, the current time if `None`
+    pub last_modified: Option<i64>,
+}
+
+/// A [Blob] with a name, usually one that was selected by the user
+///
+/// Files dereference to their blob, so they can be used wherever a blob is expected.
+///
+/// [Specification](https://w3c.github.io/FileAPI/#file-section)
+#[derive(Clone, Debug)]
+pub struct File {
+    blob: Blob,
+    name: String,
+    last_modified: i64,
+}
+
+impl File {
+    /// [Specification](https://w3c.github.io/FileAPI/#file-constructor)
+    #[must_use]
+    pub fn new(file_bits: &[BlobPart], file_name: &str, options: FilePropertyBag) -> Self {
+        // 1. Let bytes be the result of processing blob parts given fileBits and options.
+        let bytes = process_blob_parts(file_bits, options.endings);
+
+        // 2. Let n be the fileName argument to the constructor.
+        let name = file_name.to_string();
+
+        // 3. Process FilePropertyBag dictionary argument by running the following substeps:
+        //    1. If the type member is provided and is not the empty string, let t be set to the type dictionary member.
+        let content_type = normalize_content_type(&options.content_type);
+
+        //    3. If the lastModified member is provided, let d be set to the lastModified dictionary member.
+        //       If it is not provided, set d to the current date and time represented as the number of milliseconds
+        //       since the Unix Epoch.
+        let last_modified = options.last_modified.unwrap_or_else(now);
+
+        // 4. Return a new File object F such that ...
+        Self {
+            blob: Blob::from_bytes(bytes, content_type),
+            name,
+            last_modified,
+        }
+    }
+
+    /// Read a file from the local filesystem, like one that was picked by the user
+    ///
+    /// The type of the file is sniffed from its contents, since file extensions are not
+    /// a reliable indicator.
+    pub fn from_path(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let metadata = fs::metadata(path)?;
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let content_type = mime::Metadata::new(&bytes, mime::NoSniff::No)
+            .computed_mime_type_in(mime::SniffingContext::Browsing)
+            .map(|mime_type| mime_type.essence())
+            .unwrap_or_default();
+
+        let last_modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or_else(now, |duration| duration.as_millis() as i64);
+
+        Ok(Self {
+            blob: Blob::from_bytes(bytes, normalize_content_type(&content_type)),
+            name,
+            last_modified,
+        })
+    }
+
+    /// The name of the file, without any path
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-name)
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-lastModified)
+    #[must_use]
+    pub const fn last_modified(&self) -> i64 {
+        self.last_modified
+    }
+
+    #[must_use]
+    pub fn as_blob(&self) -> &Blob {
+        &self.blob
+    }
+}
+
+impl Deref for File {
+    type Target = Blob;
+
+    fn deref(&self) -> &Self::Target {
+        &self.blob
+    }
+}
+
+impl From<File> for Blob {
+    fn from(value: File) -> Self {
+        value.blob
+    }
+}
+
+/// The current time in milliseconds since the unix epoch
+#[must_use]
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_properties() {
+        let file = File::new(
+            &[BlobPart::String("{}".to_string())],
+            "data.json",
+            FilePropertyBag {
+                content_type: "application/JSON".to_string(),
+                last_modified: Some(42),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(file.name(), "data.json");
+        assert_eq!(file.last_modified(), 42);
+        assert_eq!(file.content_type(), "application/json");
+        assert_eq!(file.size(), 2);
+
+        // Slices of files are plain blobs
+        let slice: Blob = file.slice(Some(1), None, None);
+        assert_eq!(slice.text(), "}");
+
+        let file = File::new(&[], "empty", FilePropertyBag::default());
+        assert!(file.last_modified() > 0);
+    }
+
+    #[test]
+    fn read_from_disk() {
+        let name = format!("stormlicht-file-api-{}.html", std::process::id());
+        let path = std::env::temp_dir().join(&name);
+        fs::write(&path, "<!DOCTYPE html><p>hi</p>").unwrap();
+
+        let file = File::from_path(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(file.name(), name);
+        assert_eq!(file.content_type(), "text/html");
+        assert_eq!(file.text(), "<!DOCTYPE html><p>hi</p>");
+        assert!(file.last_modified() > 0);
+    }
+}
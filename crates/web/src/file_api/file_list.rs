@@ -0,0 +1,53 @@
+use std::slice;
+
+use super::File;
+
+/// The files that were selected in a `<input type=file>` element or dropped onto the page
+///
+/// [Specification](https://w3c.github.io/FileAPI/#filelist-section)
+#[derive(Clone, Debug, Default)]
+pub struct FileList {
+    files: Vec<File>,
+}
+
+impl FileList {
+    /// The number of files in the list
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-length)
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.files.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// The file at `index` in the list, or `None` if there is no such file
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-item)
+    #[must_use]
+    pub fn item(&self, index: usize) -> Option<&File> {
+        self.files.get(index)
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, File> {
+        self.files.iter()
+    }
+}
+
+impl From<Vec<File>> for FileList {
+    fn from(files: Vec<File>) -> Self {
+        Self { files }
+    }
+}
+
+impl<'a> IntoIterator for &'a FileList {
+    type Item = &'a File;
+    type IntoIter = slice::Iter<'a, File>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
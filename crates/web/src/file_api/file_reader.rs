@@ -0,0 +1,405 @@
+use encodings::Encoding;
+use error_derive::Error;
+use js::ArrayBuffer;
+use sl_std::base64;
+
+use super::Blob;
+
+/// The number of bytes that are read from a blob before a `progress` event is fired
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, Error)]
+pub enum FileReaderError {
+    /// Corresponds to an `InvalidStateError` `DOMException`
+    #[msg = "the reader is already reading a blob"]
+    InvalidState,
+}
+
+/// <https://w3c.github.io/FileAPI/#dom-filereader-readystate>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadyState {
+    #[default]
+    Empty,
+    Loading,
+    Done,
+}
+
+/// <https://w3c.github.io/FileAPI/#events>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileReaderEvent {
+    LoadStart,
+
+    /// Some bytes were read, `loaded` out of `total`
+    Progress {
+        loaded: usize,
+        total: usize,
+    },
+    Load,
+    Abort,
+    LoadEnd,
+}
+
+impl FileReaderEvent {
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::LoadStart => "loadstart",
+            Self::Progress { .. } => "progress",
+            Self::Load => "load",
+            Self::Abort => "abort",
+            Self::LoadEnd => "loadend",
+        }
+    }
+}
+
+/// <https://w3c.github.io/FileAPI/#dom-filereader-result>
+#[derive(Clone, Debug)]
+pub enum FileReaderResult {
+    String(String),
+    ArrayBuffer(ArrayBuffer),
+}
+
+/// What the bytes of the blob are converted into once they were read
+///
+/// <https://w3c.github.io/FileAPI/#blob-package-data>
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ReadType {
+    ArrayBuffer,
+    BinaryString,
+    Text { encoding: Option<String> },
+    DataURL,
+}
+
+/// A read that is still in progress
+#[derive(Clone, Debug)]
+struct ReadOperation {
+    blob: Blob,
+    read_type: ReadType,
+    bytes_read: usize,
+}
+
+/// Reads the contents of a blob asynchronously, reporting its progress through events
+///
+/// After starting a read with one of the `read_as_*` methods, the event loop calls
+/// [process_next_chunk](Self::process_next_chunk) until the read is complete. Events
+/// that should be fired at the reader are collected with [take_events](Self::take_events).
+///
+/// [Specification](https://w3c.github.io/FileAPI/#APIASynch)
+#[derive(Clone, Debug, Default)]
+pub struct FileReader {
+    ready_state: ReadyState,
+    result: Option<FileReaderResult>,
+    operation: Option<ReadOperation>,
+    pending_events: Vec<FileReaderEvent>,
+}
+
+impl FileReader {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// [Specification](https://w3c.github.io/FileAPI/#dom-filereader-readystate)
+    #[must_use]
+    pub const fn ready_state(&self) -> ReadyState {
+        self.ready_state
+    }
+
+    /// The contents of the blob, or `None` if the read did not complete
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dom-filereader-result)
+    #[must_use]
+    pub const fn result(&self) -> Option<&FileReaderResult> {
+        self.result.as_ref()
+    }
+
+    #[must_use]
+    pub fn take_events(&mut self) -> Vec<FileReaderEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-readAsArrayBuffer)
+    pub fn read_as_array_buffer(&mut self, blob: &Blob) -> Result<(), FileReaderError> {
+        self.start_read(blob, ReadType::ArrayBuffer)
+    }
+
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-readAsBinaryString)
+    pub fn read_as_binary_string(&mut self, blob: &Blob) -> Result<(), FileReaderError> {
+        self.start_read(blob, ReadType::BinaryString)
+    }
+
+    /// Read the blob as text in the given encoding
+    ///
+    /// If no encoding is given, the charset of the blob's type or UTF-8 is used.
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-readAsText)
+    pub fn read_as_text(
+        &mut self,
+        blob: &Blob,
+        encoding: Option<&str>,
+    ) -> Result<(), FileReaderError> {
+        let encoding = encoding.map(str::to_string);
+        self.start_read(blob, ReadType::Text { encoding })
+    }
+
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-readAsDataURL)
+    pub fn read_as_data_url(&mut self, blob: &Blob) -> Result<(), FileReaderError> {
+        self.start_read(blob, ReadType::DataURL)
+    }
+
+    /// <https://w3c.github.io/FileAPI/#readOperation>
+    fn start_read(&mut self, blob: &Blob, read_type: ReadType) -> Result<(), FileReaderError> {
+        // 1. If fr’s state is "loading", throw an InvalidStateError DOMException.
+        if self.ready_state == ReadyState::Loading {
+            return Err(FileReaderError::InvalidState);
+        }
+
+        // 2. Set fr’s state to "loading".
+        // 3. Set fr’s result to null.
+        // 4. Set fr’s error to null.
+        self.ready_state = ReadyState::Loading;
+        self.result = None;
+
+        // 5. Let stream be the result of calling get stream on blob.
+        // 6. Let reader be the result of getting a reader from stream.
+        // 7. Let bytes be an empty byte sequence.
+        self.operation = Some(ReadOperation {
+            blob: blob.clone(),
+            read_type,
+            bytes_read: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Read the next chunk of the blob
+    ///
+    /// Returns `true` while there are chunks left to read.
+    pub fn process_next_chunk(&mut self) -> bool {
+        let Some(operation) = &mut self.operation else {
+            return false;
+        };
+
+        // 11. In parallel, while true:
+        //     2. If chunkPromise is fulfilled, and isFirstChunk is true, queue a task to fire a progress event
+        //        called loadstart at fr.
+        if operation.bytes_read == 0 {
+            self.pending_events.push(FileReaderEvent::LoadStart);
+        }
+
+        //     4. If chunkPromise is fulfilled with an object whose done property is false and whose value property
+        //        is a Uint8Array object, run these steps:
+        let total = operation.blob.size();
+        if operation.bytes_read < total {
+            // 2. Append bs to bytes.
+            operation.bytes_read = (operation.bytes_read + CHUNK_SIZE).min(total);
+
+            // 3. If roughly 50ms have passed since these steps were last invoked, queue a task to fire a
+            //    progress event called progress at fr.
+            // NOTE: Progress is reported after every chunk instead
+            self.pending_events.push(FileReaderEvent::Progress {
+                loaded: operation.bytes_read,
+                total,
+            });
+            return true;
+        }
+
+        //     5. Otherwise, if chunkPromise is fulfilled with an object whose done property is true,
+        //        queue a task to run the following steps and abort this algorithm:
+        let operation = self.operation.take().expect("there is a read in progress");
+
+        // 1. Set fr’s state to "done".
+        self.ready_state = ReadyState::Done;
+
+        // 2. Let result be the result of package data given bytes, type, blob’s type, and encodingName.
+        // 4. Else: Set fr’s result to result. Fire a progress event called load at the fr.
+        self.result = Some(package_data(&operation.blob, &operation.read_type));
+        self.pending_events.push(FileReaderEvent::Load);
+
+        // 5. If fr’s state is not "loading", fire a progress event called loadend at the fr.
+        self.pending_events.push(FileReaderEvent::LoadEnd);
+
+        false
+    }
+
+    /// Cancel the read that is currently in progress
+    ///
+    /// [Specification](https://w3c.github.io/FileAPI/#dfn-abort)
+    pub fn abort(&mut self) {
+        // 1. If this's state is "empty" or if this's state is "done" set this's result to null and terminate
+        //    this algorithm.
+        if self.ready_state != ReadyState::Loading {
+            self.result = None;
+            return;
+        }
+
+        // 2. If this's state is "loading" set this's state to "done" and set this's result to null.
+        self.ready_state = ReadyState::Done;
+        self.result = None;
+
+        // 3. If there are any tasks from this on the file reading task source in an affiliated task queue,
+        //    then remove those tasks from that task queue.
+        self.pending_events.clear();
+
+        // 4. Terminate the algorithm for the read method being processed.
+        self.operation = None;
+
+        // 5. Fire a progress event called abort at this.
+        // 6. If this's state is not "loading", fire a progress event called loadend at this.
+        self.pending_events.push(FileReaderEvent::Abort);
+        self.pending_events.push(FileReaderEvent::LoadEnd);
+    }
+}
+
+/// <https://w3c.github.io/FileAPI/#blob-package-data>
+fn package_data(blob: &Blob, read_type: &ReadType) -> FileReaderResult {
+    let bytes = blob.bytes();
+
+    match read_type {
+        // Return bytes as a DataURL [RFC2397] subject to the considerations below:
+        // * Use mimeType as part of the Data URL if it is available in keeping with the Data URL specification [RFC2397].
+        // * If mimeType is not available return a Data URL without a media-type.
+        ReadType::DataURL => FileReaderResult::String(format!(
+            "data:{};base64,{}",
+            blob.content_type(),
+            base64::b64encode(bytes)
+        )),
+        ReadType::Text { encoding } => {
+            // 1. Let encoding be failure.
+            // 2. If the encodingName is present, set encoding to the result of getting an encoding from encodingName.
+            let mut encoding = encoding.as_deref().and_then(|label| label.parse().ok());
+
+            // 3. If encoding is failure, and mimeType is present:
+            //    1. Let type be the result of parse a MIME type given mimeType.
+            //    2. If type is not failure, set encoding to the result of getting an encoding from type’s parameters["charset"].
+            if encoding.is_none() {
+                encoding = blob
+                    .content_type()
+                    .parse::<mime::MIMEType>()
+                    .ok()
+                    .and_then(|mime_type| mime_type.charset()?.parse().ok());
+            }
+
+            // 4. If encoding is failure, then set encoding to UTF-8.
+            // 5. Decode bytes using fallback encoding encoding, and return the result.
+            // FIXME: Decoders for most legacy encodings are still missing, fall back to UTF-8 for those
+            let text = encodings::decode(bytes, encoding.unwrap_or(Encoding::UTF_8))
+                .or_else(|_| encodings::decode(bytes, Encoding::UTF_8))
+                .expect("utf-8 is always supported");
+            FileReaderResult::String(text)
+        },
+        ReadType::ArrayBuffer => {
+            FileReaderResult::ArrayBuffer(ArrayBuffer::from_bytes(bytes.to_vec()))
+        },
+
+        // Return bytes as a binary string, in which every byte is represented by a code unit of equal value [0..255].
+        ReadType::BinaryString => {
+            FileReaderResult::String(bytes.iter().map(|&byte| char::from(byte)).collect())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_api::{BlobPart, BlobPropertyBag};
+
+    fn blob(bytes: &[u8], content_type: &str) -> Blob {
+        let buffer = ArrayBuffer::from_bytes(bytes.to_vec());
+        Blob::new(
+            &[BlobPart::Buffer(buffer.into())],
+            BlobPropertyBag {
+                content_type: content_type.to_string(),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn read_to_end(reader: &mut FileReader) {
+        while reader.process_next_chunk() {}
+    }
+
+    fn result_string(reader: &FileReader) -> &str {
+        match reader.result() {
+            Some(FileReaderResult::String(string)) => string,
+            other => panic!("expected a string result, found {other:?}"),
+        }
+    }
+
+    #[test]
+    fn events() {
+        let data = vec![0; CHUNK_SIZE + 1];
+        let mut reader = FileReader::new();
+        reader.read_as_array_buffer(&blob(&data, "")).unwrap();
+        assert_eq!(reader.ready_state(), ReadyState::Loading);
+        assert!(reader.result().is_none());
+
+        // Only one read can be in progress at a time
+        assert!(reader.read_as_text(&blob(b"", ""), None).is_err());
+
+        read_to_end(&mut reader);
+        assert_eq!(reader.ready_state(), ReadyState::Done);
+        assert_eq!(
+            reader.take_events(),
+            [
+                FileReaderEvent::LoadStart,
+                FileReaderEvent::Progress {
+                    loaded: CHUNK_SIZE,
+                    total: CHUNK_SIZE + 1
+                },
+                FileReaderEvent::Progress {
+                    loaded: CHUNK_SIZE + 1,
+                    total: CHUNK_SIZE + 1
+                },
+                FileReaderEvent::Load,
+                FileReaderEvent::LoadEnd,
+            ]
+        );
+
+        let Some(FileReaderResult::ArrayBuffer(buffer)) = reader.result() else {
+            panic!("expected an array buffer");
+        };
+        assert_eq!(buffer.byte_length(), CHUNK_SIZE + 1);
+    }
+
+    #[test]
+    fn abort() {
+        let mut reader = FileReader::new();
+        reader.read_as_text(&blob(b"hello", ""), None).unwrap();
+        reader.process_next_chunk();
+        reader.abort();
+
+        assert_eq!(reader.ready_state(), ReadyState::Done);
+        assert!(reader.result().is_none());
+        assert!(!reader.process_next_chunk());
+        assert_eq!(
+            reader.take_events(),
+            [FileReaderEvent::Abort, FileReaderEvent::LoadEnd]
+        );
+    }
+
+    #[test]
+    fn package_results() {
+        let mut reader = FileReader::new();
+
+        reader.read_as_data_url(&blob(b"hi", "text/plain")).unwrap();
+        read_to_end(&mut reader);
+        assert_eq!(result_string(&reader), "data:text/plain;base64,aGk=");
+
+        reader.read_as_binary_string(&blob(b"\xE4", "")).unwrap();
+        read_to_end(&mut reader);
+        assert_eq!(result_string(&reader), "\u{E4}");
+
+        // The charset of the blob is used if no encoding is given
+        let utf16 = blob(b"h\x00i\x00", "text/plain;charset=utf-16le");
+        reader.read_as_text(&utf16, None).unwrap();
+        read_to_end(&mut reader);
+        assert_eq!(result_string(&reader), "hi");
+
+        reader
+            .read_as_text(&blob("ä".as_bytes(), ""), Some("utf-8"))
+            .unwrap();
+        read_to_end(&mut reader);
+        assert_eq!(result_string(&reader), "ä");
+    }
+}
@@ -0,0 +1,98 @@
+//! Blobs, files and the `blob:` urls that refer to them
+//!
+//! The bytes of a [Blob] are immutable and shared between all of its slices. Object urls
+//! register a blob in the [BLOB_URL_STORE], where the resource loader looks it up when
+//! the url is fetched.
+//!
+//! <https://w3c.github.io/FileAPI/>
+
+mod blob;
+mod file;
+mod file_list;
+mod file_reader;
+
+pub use blob::{Blob, BlobPart, BlobPropertyBag, Endings};
+pub use file::{File, FilePropertyBag};
+pub use file_list::FileList;
+pub use file_reader::{FileReader, FileReaderError, FileReaderEvent, FileReaderResult, ReadyState};
+
+use resourceloader::protocol::{BlobURLEntry, BLOB_URL_STORE};
+use url::{Origin, URL};
+
+/// Create a `blob:` url that loads the blob, until it is revoked
+///
+/// `origin` is the origin of the environment that calls the method, only that
+/// origin can revoke the url again.
+///
+/// [Specification](https://w3c.github.io/FileAPI/#dfn-createObjectURL)
+#[must_use]
+pub fn create_object_url(blob: &Blob, origin: &Origin) -> URL {
+    // 1. Return the result of adding an entry to the blob URL store for obj.
+    BLOB_URL_STORE.add(BlobURLEntry {
+        data: blob.shared_bytes(),
+        content_type: blob.content_type().to_string(),
+        origin: origin.clone(),
+    })
+}
+
+/// Release the blob that a url returned by [create_object_url] refers to
+///
+/// Invalid urls and urls created by a different origin are ignored.
+///
+/// [Specification](https://w3c.github.io/FileAPI/#dfn-revokeObjectURL)
+pub fn revoke_object_url(url: &str, origin: &Origin) {
+    // 1. Let url record be the result of parsing url.
+    // 2. If url record’s scheme is not "blob", return.
+    let Ok(url) = url.parse::<URL>() else {
+        return;
+    };
+    if url.scheme().as_str() != "blob" {
+        return;
+    }
+
+    // 3-5. If url record’s origin is not same origin with the current settings object’s origin, return.
+    //      Remove an entry from the Blob URL Store for url.
+    BLOB_URL_STORE.revoke(&url, origin);
+}
+
+/// Whether a `type` can be used for a blob, which requires it to be printable ascii
+///
+/// Valid types are converted to lowercase, invalid ones are replaced with the empty string.
+#[must_use]
+fn normalize_content_type(content_type: &str) -> String {
+    // If the type member of the options argument is not the empty string, run the following sub-steps:
+    // 1. Let t be the type dictionary member. If t contains any characters outside the range U+0020 to U+007E,
+    //    then set t to the empty string and return from these substeps.
+    if !content_type.chars().all(|c| matches!(c, ' '..='~')) {
+        return String::new();
+    }
+
+    // 2. Convert every character in t to ASCII lowercase.
+    content_type.to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use resourceloader::Resource;
+
+    use super::*;
+
+    #[test]
+    fn object_urls() {
+        let origin: Origin = "https://example.com/".parse::<URL>().unwrap().origin();
+        let blob = Blob::new(
+            &[BlobPart::String("<p>hello</p>".to_string())],
+            BlobPropertyBag {
+                content_type: "TEXT/HTML".to_string(),
+                ..Default::default()
+            },
+        );
+        let url = create_object_url(&blob.slice(Some(3), Some(-4), None), &origin);
+
+        let resource = Resource::load(&url).unwrap();
+        assert_eq!(resource.data(), b"hello");
+
+        revoke_object_url(url.serialize(url::ExcludeFragment::Yes).as_str(), &origin);
+        assert!(Resource::load(&url).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Picking files for `<input type=file>` elements
+//!
+//! Clicking a file upload control does not open a dialog by itself, the browsing context
+//! hands out a [FileChooserRequest] that the embedder should answer with the files that
+//! the user picked.
+
+use std::path::PathBuf;
+
+use crate::{
+    dom::{dom_objects::HtmlInputElement, DomPtr},
+    file_api::File,
+};
+
+/// A file upload control that wants the user to pick files
+///
+/// <https://html.spec.whatwg.org/multipage/input.html#file-upload-state-(type=file)>
+#[derive(Clone)]
+pub struct FileChooserRequest {
+    input: DomPtr<HtmlInputElement>,
+}
+
+impl FileChooserRequest {
+    #[must_use]
+    pub(crate) fn new(input: DomPtr<HtmlInputElement>) -> Self {
+        Self { input }
+    }
+
+    #[must_use]
+    pub(crate) fn input(&self) -> &DomPtr<HtmlInputElement> {
+        &self.input
+    }
+
+    /// Whether the user may pick more than one file
+    #[must_use]
+    pub fn allows_multiple_files(&self) -> bool {
+        self.input.borrow().is_multiple()
+    }
+
+    /// The file types that should be offered, as extensions (like `.png`) or MIME types (like `image/*`)
+    ///
+    /// If this is empty, any file can be picked.
+    #[must_use]
+    pub fn accepted_types(&self) -> Vec<String> {
+        self.input.borrow().accepted_types()
+    }
+}
+
+/// Read the files that the user picked or dropped onto the page
+///
+/// Files that can't be read are skipped.
+#[must_use]
+pub(crate) fn read_files(paths: &[PathBuf]) -> Vec<File> {
+    paths
+        .iter()
+        .filter_map(|path| match File::from_path(path) {
+            Ok(file) => Some(file),
+            Err(error) => {
+                log::error!("Failed to read {}: {error}", path.display());
+                None
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,260 @@
+//! Keyboard focus
+//!
+//! <https://html.spec.whatwg.org/multipage/interaction.html#focus>
+
+use std::collections::HashSet;
+
+use crate::{
+    css::{display_list::Painter, fragment_tree::FragmentTree, layout::Pixels},
+    dom::{
+        dom_objects::{Document, Element, Node},
+        DomPtr,
+    },
+    static_interned,
+};
+
+/// Width of the outline that is painted around the focused element
+const FOCUS_RING_WIDTH: Pixels = Pixels(2.);
+
+const FOCUS_RING_COLOR: math::Color = math::Color::rgb(0x1a, 0x5f, 0xb4);
+
+/// <https://html.spec.whatwg.org/multipage/interaction.html#sequential-navigation-search-algorithm>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusDirection {
+    Forward,
+    Backward,
+}
+
+/// Parse the value of a `tabindex` attribute
+///
+/// <https://html.spec.whatwg.org/multipage/common-microsyntaxes.html#rules-for-parsing-integers>
+fn parse_tabindex(value: &str) -> Option<i32> {
+    let value = value.trim_start_matches(|c: char| c.is_ascii_whitespace());
+
+    let (is_negative, value) = match value.as_bytes().first()? {
+        b'-' => (true, &value[1..]),
+        b'+' => (false, &value[1..]),
+        _ => (false, value),
+    };
+
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(value, |end| &value[..end]);
+    let number: i32 = digits.parse().ok()?;
+
+    Some(if is_negative { -number } else { number })
+}
+
+/// The tabindex value of the element, if it has a valid one
+///
+/// <https://html.spec.whatwg.org/multipage/interaction.html#attr-tabindex>
+fn tabindex(element: &Element) -> Option<i32> {
+    let value = element.attributes().get(&static_interned!("tabindex"))?;
+    parse_tabindex(&value.to_string())
+}
+
+/// Whether the element is focusable even if it does not have a `tabindex` attribute
+///
+/// <https://html.spec.whatwg.org/multipage/interaction.html#tabindex-value>
+fn is_focusable_by_default(element: &Element) -> bool {
+    let attributes = element.attributes();
+    let is_disabled = attributes.contains_key(&static_interned!("disabled"));
+
+    match element.local_name() {
+        static_interned!("a") => attributes.contains_key(&static_interned!("href")),
+        static_interned!("button") | static_interned!("select") | static_interned!("textarea") => {
+            !is_disabled
+        },
+        static_interned!("input") => {
+            let is_hidden = attributes
+                .get(&static_interned!("type"))
+                .is_some_and(|input_type| {
+                    input_type.eq_ignore_ascii_case(&static_interned!("hidden"))
+                });
+            !is_disabled && !is_hidden
+        },
+        _ => false,
+    }
+}
+
+/// The tabindex that determines the position of the element in the sequential focus navigation
+/// order, or `None` if the element cannot be reached with the keyboard
+fn sequential_tabindex(element: &Element) -> Option<i32> {
+    match tabindex(element) {
+        Some(tabindex) if tabindex < 0 => None,
+        Some(tabindex) => Some(tabindex),
+        None if is_focusable_by_default(element) => Some(0),
+        None => None,
+    }
+}
+
+/// All elements that can be focused with the keyboard, in the order in which they are visited
+///
+/// Elements with a positive tabindex come first, in order of their tabindex. All other
+/// elements follow in tree order. Elements that are not being rendered are skipped.
+///
+/// <https://html.spec.whatwg.org/multipage/interaction.html#sequential-focus-navigation-order>
+#[must_use]
+pub fn sequential_focus_order(
+    document: &DomPtr<Document>,
+    fragment_tree: &FragmentTree,
+) -> Vec<DomPtr<Element>> {
+    let mut rendered_nodes = HashSet::new();
+    fragment_tree.for_each_box(|fragment, _| {
+        if let Some(node) = fragment.dom_node() {
+            rendered_nodes.insert(node_address(&node));
+        }
+    });
+
+    focus_order(document, |node| {
+        rendered_nodes.contains(&node_address(node))
+    })
+}
+
+fn focus_order<F>(document: &DomPtr<Document>, is_rendered: F) -> Vec<DomPtr<Element>>
+where
+    F: Fn(&DomPtr<Node>) -> bool,
+{
+    fn collect<F>(node: &DomPtr<Node>, is_rendered: &F, elements: &mut Vec<(i32, DomPtr<Element>)>)
+    where
+        F: Fn(&DomPtr<Node>) -> bool,
+    {
+        if let Some(element) = node.try_into_type::<Element>()
+            && is_rendered(node)
+        {
+            let tabindex = sequential_tabindex(&element.borrow());
+            if let Some(tabindex) = tabindex {
+                elements.push((tabindex, element));
+            }
+        }
+
+        for child in node.borrow().children() {
+            collect(child, is_rendered, elements);
+        }
+    }
+
+    let mut elements = vec![];
+    for child in document.borrow().children() {
+        collect(child, &is_rendered, &mut elements);
+    }
+
+    // A tabindex of zero sorts after all positive values, the sort is stable so ties stay in tree order
+    elements.sort_by_key(|(tabindex, _)| match tabindex {
+        0 => i32::MAX,
+        other => *other,
+    });
+
+    elements.into_iter().map(|(_, element)| element).collect()
+}
+
+fn node_address(node: &DomPtr<Node>) -> *const () {
+    node.as_ptr().cast()
+}
+
+/// Find the element that should receive focus next
+///
+/// Returns `None` if the end of the document was reached, in which case focus should
+/// move back to the browser interface.
+#[must_use]
+pub fn next_focus_target(
+    order: &[DomPtr<Element>],
+    currently_focused: Option<&DomPtr<Element>>,
+    direction: FocusDirection,
+) -> Option<DomPtr<Element>> {
+    let position = currently_focused
+        .and_then(|focused| order.iter().position(|element| element.ptr_eq(focused)));
+
+    let index = match (position, direction) {
+        (None, FocusDirection::Forward) => 0,
+        (None, FocusDirection::Backward) => order.len().checked_sub(1)?,
+        (Some(position), FocusDirection::Forward) => position + 1,
+        (Some(position), FocusDirection::Backward) => position.checked_sub(1)?,
+    };
+
+    order.get(index).cloned()
+}
+
+/// Draw an outline around the given area
+pub fn paint_focus_ring(painter: &mut Painter, area: math::Rectangle<Pixels>) {
+    let width = FOCUS_RING_WIDTH;
+    let outer = math::Rectangle::from_corners(
+        area.top_left() - math::Vec2D::new(width, width),
+        area.bottom_right() + math::Vec2D::new(width, width),
+    );
+
+    let top = math::Rectangle::from_position_and_size(outer.top_left(), outer.width(), width);
+    let bottom = math::Rectangle::from_position_and_size(
+        outer.bottom_left() - math::Vec2D::new(Pixels::ZERO, width),
+        outer.width(),
+        width,
+    );
+    let left = math::Rectangle::from_position_and_size(outer.top_left(), width, outer.height());
+    let right = math::Rectangle::from_position_and_size(
+        outer.top_right() - math::Vec2D::new(width, Pixels::ZERO),
+        width,
+        outer.height(),
+    );
+
+    for side in [top, bottom, left, right] {
+        painter.rect(side, FOCUS_RING_COLOR);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::{self, tokenization::IgnoreParseErrors};
+
+    #[test]
+    fn parse_tabindex_values() {
+        assert_eq!(parse_tabindex("0"), Some(0));
+        assert_eq!(parse_tabindex("  12abc"), Some(12));
+        assert_eq!(parse_tabindex("-1"), Some(-1));
+        assert_eq!(parse_tabindex("+3"), Some(3));
+        assert_eq!(parse_tabindex(""), None);
+        assert_eq!(parse_tabindex("abc"), None);
+        assert_eq!(parse_tabindex("-"), None);
+    }
+
+    #[test]
+    fn focus_order_respects_tabindex() {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let source = "<a href=\"/\" id=\"a\">a</a>
+            <a id=\"not-a-link\">b</a>
+            <button id=\"c\" tabindex=\"2\">c</button>
+            <div id=\"d\" tabindex=\"0\">d</div>
+            <input id=\"e\" type=\"hidden\">
+            <button id=\"f\" disabled>f</button>
+            <a href=\"/\" id=\"g\" tabindex=\"-1\">g</a>
+            <input id=\"h\" tabindex=\"1\">";
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+
+        let ids: Vec<String> = focus_order(&document, |_| true)
+            .iter()
+            .map(|element| element.borrow().id().unwrap().to_string())
+            .collect();
+        assert_eq!(ids, ["h", "c", "a", "d"]);
+    }
+
+    #[test]
+    fn focus_traversal() {
+        let order: Vec<DomPtr<Element>> = (0..3).map(|_| DomPtr::new(Element::default())).collect();
+
+        let next = |focused: Option<usize>, direction| {
+            let focused = focused.map(|index| &order[index]);
+            next_focus_target(&order, focused, direction)
+                .map(|target| order.iter().position(|e| e.ptr_eq(&target)).unwrap())
+        };
+
+        assert_eq!(next(None, FocusDirection::Forward), Some(0));
+        assert_eq!(next(Some(0), FocusDirection::Forward), Some(1));
+        assert_eq!(next(Some(2), FocusDirection::Forward), None);
+        assert_eq!(next(None, FocusDirection::Backward), Some(2));
+        assert_eq!(next(Some(0), FocusDirection::Backward), None);
+    }
+}
@@ -0,0 +1,278 @@
+//! Where elements are positioned on the page, as computed during the most recent layout
+//!
+//! Scripts query these to find out whether (and where) an element is visible, for example
+//! to lazily load images once they are scrolled into view.
+//!
+//! <https://drafts.csswg.org/cssom-view/#extension-to-the-element-interface>
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        fragment_tree::{BoxFragment, Fragment, FragmentTree},
+        layout::{Pixels, Size},
+    },
+    dom::{dom_objects, DomPtr},
+};
+
+/// <https://drafts.fxtf.org/geometry/#domrect>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DomRect {
+    pub x: Pixels,
+    pub y: Pixels,
+    pub width: Pixels,
+    pub height: Pixels,
+}
+
+impl DomRect {
+    #[must_use]
+    pub fn from_rectangle(rectangle: Rectangle<Pixels>) -> Self {
+        let top_left = rectangle.top_left();
+
+        Self {
+            x: top_left.x,
+            y: top_left.y,
+            width: rectangle.width(),
+            height: rectangle.height(),
+        }
+    }
+
+    #[must_use]
+    pub fn to_rectangle(&self) -> Rectangle<Pixels> {
+        Rectangle::from_corners(
+            Vec2D::new(self.left(), self.top()),
+            Vec2D::new(self.right(), self.bottom()),
+        )
+    }
+
+    /// <https://drafts.fxtf.org/geometry/#dom-domrectreadonly-top>
+    #[must_use]
+    pub fn top(&self) -> Pixels {
+        self.y.min(self.y + self.height)
+    }
+
+    /// <https://drafts.fxtf.org/geometry/#dom-domrectreadonly-right>
+    #[must_use]
+    pub fn right(&self) -> Pixels {
+        self.x.max(self.x + self.width)
+    }
+
+    /// <https://drafts.fxtf.org/geometry/#dom-domrectreadonly-bottom>
+    #[must_use]
+    pub fn bottom(&self) -> Pixels {
+        self.y.max(self.y + self.height)
+    }
+
+    /// <https://drafts.fxtf.org/geometry/#dom-domrectreadonly-left>
+    #[must_use]
+    pub fn left(&self) -> Pixels {
+        self.x.min(self.x + self.width)
+    }
+
+    #[must_use]
+    pub fn area(&self) -> f32 {
+        self.width.0.abs() * self.height.0.abs()
+    }
+}
+
+/// The geometry of a single element
+///
+/// Elements that did not generate any boxes (like elements with `display: none`)
+/// have an empty geometry.
+#[derive(Clone, Copy)]
+pub struct ElementGeometry<'a> {
+    fragment_tree: &'a FragmentTree,
+    scroll_position: Vec2D<Pixels>,
+    node: &'a DomPtr<dom_objects::Node>,
+}
+
+impl<'a> ElementGeometry<'a> {
+    #[must_use]
+    pub(crate) fn new(
+        fragment_tree: &'a FragmentTree,
+        scroll_position: Vec2D<Pixels>,
+        node: &'a DomPtr<dom_objects::Node>,
+    ) -> Self {
+        Self {
+            fragment_tree,
+            scroll_position,
+            node,
+        }
+    }
+
+    /// Call `visitor` for every box of the element, together with its border area in page coordinates
+    fn for_each_box<F>(&self, mut visitor: F)
+    where
+        F: FnMut(&Fragment, Rectangle<Pixels>),
+    {
+        self.fragment_tree.for_each_box(|fragment, area| {
+            if fragment
+                .dom_node()
+                .is_some_and(|fragment_node| fragment_node.ptr_eq(self.node))
+            {
+                visitor(fragment, area);
+            }
+        });
+    }
+
+    /// The area covered by all boxes of the element, in page coordinates
+    fn border_box(&self) -> Option<Rectangle<Pixels>> {
+        let mut border_box: Option<Rectangle<Pixels>> = None;
+        self.for_each_box(|_, area| match &mut border_box {
+            Some(border_box) => border_box.grow_to_contain(area),
+            None => border_box = Some(area),
+        });
+        border_box
+    }
+
+    /// The border area of each box of the element, relative to the viewport
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-element-getclientrects>
+    #[must_use]
+    pub fn client_rects(&self) -> Vec<DomRect> {
+        let offset = Vec2D::new(Pixels::ZERO, Pixels::ZERO) - self.scroll_position;
+
+        let mut client_rects = vec![];
+        self.for_each_box(|_, area| {
+            client_rects.push(DomRect::from_rectangle(area.offset_by(offset)))
+        });
+        client_rects
+    }
+
+    /// The smallest rectangle that contains all boxes of the element, relative to the viewport
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-element-getboundingclientrect>
+    #[must_use]
+    pub fn bounding_client_rect(&self) -> DomRect {
+        let offset = Vec2D::new(Pixels::ZERO, Pixels::ZERO) - self.scroll_position;
+
+        self.border_box()
+            .map(|border_box| DomRect::from_rectangle(border_box.offset_by(offset)))
+            .unwrap_or_default()
+    }
+
+    /// The position of the element within the page
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsettop>
+    #[must_use]
+    pub fn offset_top(&self) -> Pixels {
+        // FIXME: This should be relative to the offset parent
+        self.border_box()
+            .map_or(Pixels::ZERO, |border_box| border_box.top_left().y)
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsetleft>
+    #[must_use]
+    pub fn offset_left(&self) -> Pixels {
+        // FIXME: This should be relative to the offset parent
+        self.border_box()
+            .map_or(Pixels::ZERO, |border_box| border_box.top_left().x)
+    }
+
+    /// The width of the element, including its padding and borders
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsetwidth>
+    #[must_use]
+    pub fn offset_width(&self) -> Pixels {
+        self.border_box()
+            .map_or(Pixels::ZERO, |border_box| border_box.width())
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-htmlelement-offsetheight>
+    #[must_use]
+    pub fn offset_height(&self) -> Pixels {
+        self.border_box()
+            .map_or(Pixels::ZERO, |border_box| border_box.height())
+    }
+
+    /// The first box of the element, if any
+    fn first_box(&self) -> Option<&'a BoxFragment> {
+        let mut first_box = None;
+        self.fragment_tree.for_each_box(|fragment, _| {
+            if let Fragment::Box(box_fragment) = fragment
+                && first_box.is_none()
+                && fragment
+                    .dom_node()
+                    .is_some_and(|fragment_node| fragment_node.ptr_eq(self.node))
+            {
+                first_box = Some(box_fragment);
+            }
+        });
+        first_box
+    }
+
+    /// The size of the padding area of the first box of the element
+    fn padding_size(&self) -> Size<Pixels> {
+        self.first_box().map_or(
+            Size {
+                width: Pixels::ZERO,
+                height: Pixels::ZERO,
+            },
+            |box_fragment| {
+                let padding_area = box_fragment.padding_area();
+                Size {
+                    width: padding_area.width(),
+                    height: padding_area.height(),
+                }
+            },
+        )
+    }
+
+    /// The content area of the first box of the element, relative to its padding area
+    ///
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-contentrect>
+    #[must_use]
+    pub fn content_rect(&self) -> DomRect {
+        let Some(box_fragment) = self.first_box() else {
+            return DomRect::default();
+        };
+
+        let offset =
+            Vec2D::new(Pixels::ZERO, Pixels::ZERO) - box_fragment.padding_area().top_left();
+        DomRect::from_rectangle(box_fragment.content_area().offset_by(offset))
+    }
+
+    /// The width of the element, including its padding but not its borders
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-element-clientwidth>
+    #[must_use]
+    pub fn client_width(&self) -> Pixels {
+        self.padding_size().width
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-element-clientheight>
+    #[must_use]
+    pub fn client_height(&self) -> Pixels {
+        self.padding_size().height
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_with_negative_size() {
+        let rect = DomRect {
+            x: Pixels(10.),
+            y: Pixels(20.),
+            width: Pixels(-5.),
+            height: Pixels(30.),
+        };
+
+        assert_eq!(rect.left(), Pixels(5.));
+        assert_eq!(rect.right(), Pixels(10.));
+        assert_eq!(rect.top(), Pixels(20.));
+        assert_eq!(rect.bottom(), Pixels(50.));
+        assert_eq!(rect.area(), 150.);
+        assert_eq!(
+            DomRect::from_rectangle(rect.to_rectangle()),
+            DomRect {
+                x: Pixels(5.),
+                y: Pixels(20.),
+                width: Pixels(5.),
+                height: Pixels(30.),
+            }
+        );
+    }
+}
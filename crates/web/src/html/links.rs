@@ -125,3 +125,52 @@ impl From<&str> for Relationship {
         }
     }
 }
+
+/// The kind of resource that a `<link rel=preload>` element fetches, given by its `as` attribute
+///
+/// <https://fetch.spec.whatwg.org/#concept-potential-destination>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Destination {
+    Audio,
+    Document,
+    Embed,
+    Fetch,
+    Font,
+    Image,
+    Json,
+    Manifest,
+    Object,
+    Script,
+    Style,
+    Track,
+    Video,
+    Worker,
+}
+
+impl Destination {
+    /// Parse the value of an `as` attribute, returns `None` for unknown destinations
+    ///
+    /// <https://html.spec.whatwg.org/multipage/links.html#translate-a-preload-destination>
+    #[must_use]
+    pub fn from_as_attribute(value: &str) -> Option<Self> {
+        let destination = match value.to_ascii_lowercase().as_str() {
+            "audio" => Self::Audio,
+            "document" => Self::Document,
+            "embed" => Self::Embed,
+            "fetch" => Self::Fetch,
+            "font" => Self::Font,
+            "image" => Self::Image,
+            "json" => Self::Json,
+            "manifest" => Self::Manifest,
+            "object" => Self::Object,
+            "script" => Self::Script,
+            "style" => Self::Style,
+            "track" => Self::Track,
+            "video" => Self::Video,
+            "worker" => Self::Worker,
+            _ => return None,
+        };
+
+        Some(destination)
+    }
+}
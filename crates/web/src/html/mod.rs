@@ -1,5 +1,24 @@
 pub mod links;
+mod resource_hints;
+pub mod sanitizer;
+pub mod scripting;
+pub mod srcset;
 pub mod tokenization;
 pub mod treebuilding;
 
 pub use treebuilding::parser::Parser;
+
+/// Append `text` to `output`, escaping all characters that have a special meaning in html
+///
+/// The result can be used both as text content and as a quoted attribute value.
+pub fn escape_into(output: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            other => output.push(other),
+        }
+    }
+}
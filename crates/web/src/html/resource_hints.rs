@@ -0,0 +1,108 @@
+//! Resource hints, which allow a page to tell the browser about resources it is going to need
+//!
+//! <https://html.spec.whatwg.org/multipage/links.html#link-type-dns-prefetch>
+//! <https://html.spec.whatwg.org/multipage/links.html#link-type-preconnect>
+//! <https://html.spec.whatwg.org/multipage/links.html#link-type-prefetch>
+//! <https://html.spec.whatwg.org/multipage/links.html#link-type-preload>
+
+use std::thread;
+
+use resourceloader::{Priority, RESOURCE_LOADER};
+use url::{Host, URL};
+
+use crate::dom::dom_objects::HtmlLinkElement;
+
+use super::links::{Destination, Relationship};
+
+/// Act on the hint given by a `<link>` element, if it is one
+pub(crate) fn process(link_element: &HtmlLinkElement) {
+    let relationship = link_element.relationship();
+    if !matches!(
+        relationship,
+        Relationship::DnsPrefetch
+            | Relationship::PreConnect
+            | Relationship::PreFetch
+            | Relationship::PreLoad
+    ) {
+        return;
+    }
+
+    let Some(url) = link_element.url() else {
+        return;
+    };
+
+    match relationship {
+        Relationship::DnsPrefetch => resolve_host(&url),
+        Relationship::PreConnect => {
+            // FIXME: Establish the connection (and the TLS session) ahead of time, once
+            //        there is a way to hand it to the request that ends up using it
+            resolve_host(&url);
+        },
+        Relationship::PreFetch => preload(url, Priority::Prefetch),
+        Relationship::PreLoad => {
+            // Preloads without a valid destination are ignored
+            let Some(destination) = link_element.destination() else {
+                log::debug!(
+                    "Ignoring preload of {} without a valid \"as\" attribute",
+                    url.serialize(url::ExcludeFragment::Yes)
+                );
+                return;
+            };
+
+            preload(url, priority_of(destination));
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn preload(url: URL, priority: Priority) {
+    if RESOURCE_LOADER.preload(url, priority).is_err() {
+        log::error!("Failed to preload resource, the resource thread disconnected");
+    }
+}
+
+fn priority_of(destination: Destination) -> Priority {
+    match destination {
+        Destination::Document | Destination::Style => Priority::RenderBlocking,
+        Destination::Font => Priority::Font,
+        Destination::Image => Priority::VisibleImage,
+        Destination::Script | Destination::Worker => Priority::AsyncScript,
+        _ => Priority::Normal,
+    }
+}
+
+/// Resolve the host of the url in the background, so it is already cached when it is needed
+fn resolve_host(url: &URL) {
+    let Some(Host::Domain(host)) = url.host() else {
+        return;
+    };
+
+    let host = host.to_string();
+    let spawned = thread::Builder::new()
+        .name("DNS prefetch".to_string())
+        .spawn(move || {
+            if let Err(error) = dns::Domain::new(&host).lookup() {
+                log::debug!("Failed to prefetch dns record for {host}: {error:?}");
+            }
+        });
+
+    if let Err(error) = spawned {
+        log::warn!("Failed to start dns prefetch: {error}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preload_priority() {
+        let priority = |value| Destination::from_as_attribute(value).map(priority_of);
+
+        assert_eq!(priority("STYLE"), Some(Priority::RenderBlocking));
+        assert_eq!(priority("font"), Some(Priority::Font));
+        assert_eq!(priority("script"), Some(Priority::AsyncScript));
+        assert_eq!(priority("track"), Some(Priority::Normal));
+        assert_eq!(priority("stylesheet"), None);
+    }
+}
@@ -0,0 +1,328 @@
+//! Removes scriptable content from untrusted html
+//!
+//! The sanitizer parses a fragment of html and serializes it again, keeping only
+//! the elements and attributes of a [SanitizerConfig]. Independent of the configuration,
+//! event handler attributes, `<script>` elements and urls with disallowed schemes
+//! (like `javascript:`) never make it into the output.
+
+use std::collections::HashSet;
+
+use crate::{
+    dom::{
+        dom_objects::{Document, Element, Node, Text},
+        DomPtr,
+    },
+    html::{self, escape_into, tokenization::IgnoreParseErrors},
+    static_interned, InternedString,
+};
+
+/// Url schemes that cannot run scripts
+pub const DEFAULT_URL_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+const DEFAULT_ELEMENTS: [&str; 48] = [
+    "a",
+    "abbr",
+    "b",
+    "bdi",
+    "bdo",
+    "blockquote",
+    "br",
+    "caption",
+    "cite",
+    "code",
+    "dd",
+    "del",
+    "dfn",
+    "div",
+    "dl",
+    "dt",
+    "em",
+    "figcaption",
+    "figure",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "hr",
+    "i",
+    "img",
+    "ins",
+    "kbd",
+    "li",
+    "mark",
+    "ol",
+    "p",
+    "pre",
+    "q",
+    "s",
+    "small",
+    "span",
+    "strong",
+    "sub",
+    "sup",
+    "table",
+    "tbody",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+const DEFAULT_ATTRIBUTES: [&str; 9] = [
+    "alt", "cite", "colspan", "dir", "href", "lang", "rowspan", "src", "title",
+];
+
+/// Elements that are removed together with their content, instead of being replaced by it
+const DEFAULT_DROPPED_ELEMENTS: [&str; 11] = [
+    "script", "style", "template", "noscript", "iframe", "object", "embed", "svg", "math",
+    "textarea", "title",
+];
+
+/// Attributes whose value is a url
+const URL_ATTRIBUTES: [&str; 6] = ["href", "src", "cite", "action", "formaction", "poster"];
+
+/// <https://html.spec.whatwg.org/multipage/syntax.html#void-elements>
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Decides which parts of a html fragment survive sanitization
+#[derive(Clone, Debug)]
+pub struct SanitizerConfig {
+    /// Elements that are kept
+    ///
+    /// Other elements are replaced by their children, unless they are
+    /// in [dropped_elements](Self::dropped_elements).
+    pub allowed_elements: HashSet<InternedString>,
+
+    /// Attributes that are kept on allowed elements
+    pub allowed_attributes: HashSet<InternedString>,
+
+    /// Elements that are removed together with all of their children
+    pub dropped_elements: HashSet<InternedString>,
+
+    /// Url schemes that are allowed in attributes like `href`
+    ///
+    /// Relative urls are always allowed.
+    pub allowed_url_schemes: Vec<String>,
+}
+
+impl Default for SanitizerConfig {
+    fn default() -> Self {
+        Self {
+            allowed_elements: DEFAULT_ELEMENTS.into_iter().map(Into::into).collect(),
+            allowed_attributes: DEFAULT_ATTRIBUTES.into_iter().map(Into::into).collect(),
+            dropped_elements: DEFAULT_DROPPED_ELEMENTS
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            allowed_url_schemes: DEFAULT_URL_SCHEMES.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl SanitizerConfig {
+    /// Parse `source` as a html fragment and serialize the parts that are allowed
+    #[must_use]
+    pub fn sanitize(&self, source: &str) -> String {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        // FIXME: Use the fragment parsing algorithm once the parser supports it,
+        //        for now the fragment becomes the body of a new document
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let document = parser.parse().0;
+
+        let mut html = String::new();
+        if let Some(body) = find_body(&document.upcast()) {
+            for child in body.borrow().children() {
+                self.write_node(child, &mut html);
+            }
+        }
+        html
+    }
+
+    /// Serialize the allowed parts of a node and its descendants
+    pub fn write_node(&self, node: &DomPtr<Node>, html: &mut String) {
+        if let Some(text) = node.try_into_type::<Text>() {
+            escape_into(html, text.borrow().content());
+            return;
+        }
+
+        // Comments, doctypes and the like are dropped
+        let Some(element) = node.try_into_type::<Element>() else {
+            return;
+        };
+        let element = element.borrow();
+        let local_name = element.local_name();
+
+        if local_name == static_interned!("script") || self.dropped_elements.contains(&local_name) {
+            return;
+        }
+
+        if !self.allowed_elements.contains(&local_name) {
+            for child in element.children() {
+                self.write_node(child, html);
+            }
+            return;
+        }
+
+        html.push('<');
+        html.push_str(local_name.as_str());
+
+        // Sort the attributes to keep the output deterministic
+        let mut attributes: Vec<_> = element
+            .attributes()
+            .iter()
+            .filter(|(name, value)| self.allows_attribute(name, value))
+            .collect();
+        attributes.sort_by(|a, b| a.0.as_str().cmp(b.0.as_str()));
+
+        for (name, value) in attributes {
+            html.push(' ');
+            html.push_str(name.as_str());
+            html.push_str("=\"");
+            escape_into(html, value.as_str());
+            html.push('"');
+        }
+        html.push('>');
+
+        if VOID_ELEMENTS.contains(&local_name.as_str()) {
+            return;
+        }
+
+        for child in element.children() {
+            self.write_node(child, html);
+        }
+
+        html.push_str("</");
+        html.push_str(local_name.as_str());
+        html.push('>');
+    }
+
+    fn allows_attribute(&self, name: &InternedString, value: &InternedString) -> bool {
+        // Event handlers are never allowed
+        if name.as_str().starts_with("on") || !self.allowed_attributes.contains(name) {
+            return false;
+        }
+
+        if URL_ATTRIBUTES.contains(&name.as_str()) {
+            return has_allowed_scheme(value.as_str(), &self.allowed_url_schemes);
+        }
+
+        true
+    }
+}
+
+/// Whether a url is relative or uses one of the given schemes
+///
+/// Browsers ignore whitespace and control characters in urls, so `java\tscript:`
+/// is treated like `javascript:`.
+#[must_use]
+pub fn has_allowed_scheme<S: AsRef<str>>(url: &str, schemes: &[S]) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace() && !c.is_control())
+        .collect();
+
+    // Everything before the first ':' is the scheme, unless the url is relative
+    let Some(scheme_end) = url.find(':') else {
+        return true;
+    };
+    if url[..scheme_end].contains(['/', '?', '#']) {
+        return true;
+    }
+
+    let scheme = &url[..scheme_end];
+    schemes
+        .iter()
+        .any(|allowed| allowed.as_ref().eq_ignore_ascii_case(scheme))
+}
+
+fn find_body(node: &DomPtr<Node>) -> Option<DomPtr<Element>> {
+    if let Some(element) = node.try_into_type::<Element>()
+        && element.borrow().local_name() == static_interned!("body")
+    {
+        return Some(element);
+    }
+
+    node.borrow().children().iter().find_map(find_body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_scripts() {
+        let config = SanitizerConfig::default();
+
+        assert_eq!(
+            config.sanitize("<p onclick=\"steal()\">Hello<script>steal()</script> world</p>"),
+            "<p>Hello world</p>"
+        );
+        assert_eq!(
+            config.sanitize("<style>p { color: red }</style><b>bold</b>"),
+            "<b>bold</b>"
+        );
+        assert_eq!(
+            config
+                .sanitize("<a href=\"java\tscript:steal()\">link</a><a href=\"/about\">about</a>"),
+            "<a>link</a><a href=\"/about\">about</a>"
+        );
+    }
+
+    #[test]
+    fn unwrap_unknown_elements() {
+        let config = SanitizerConfig::default();
+
+        assert_eq!(
+            config.sanitize("<form><button>Click</button> <img src=\"cat.png\" alt=cat></form>"),
+            "Click <img alt=\"cat\" src=\"cat.png\">"
+        );
+    }
+
+    #[test]
+    fn configurable_allowlist() {
+        let mut config = SanitizerConfig::default();
+        config.allowed_elements.remove(&"b".into());
+        config.allowed_attributes.insert("class".into());
+        config.allowed_url_schemes.push("data".to_string());
+
+        assert_eq!(
+            config.sanitize("<p class=\"note\"><b>bold</b> <img src=\"data:image/png,\"></p>"),
+            "<p class=\"note\">bold <img src=\"data:image/png,\"></p>"
+        );
+    }
+
+    #[test]
+    fn url_schemes() {
+        assert!(has_allowed_scheme(
+            "https://example.com",
+            &DEFAULT_URL_SCHEMES
+        ));
+        assert!(has_allowed_scheme(
+            "MAILTO:someone@example.com",
+            &DEFAULT_URL_SCHEMES
+        ));
+        assert!(has_allowed_scheme("/path?query=a:b", &DEFAULT_URL_SCHEMES));
+        assert!(has_allowed_scheme("#fragment", &DEFAULT_URL_SCHEMES));
+        assert!(!has_allowed_scheme(
+            "javascript:alert(1)",
+            &DEFAULT_URL_SCHEMES
+        ));
+        assert!(!has_allowed_scheme(
+            " JavaScript:alert(1)",
+            &DEFAULT_URL_SCHEMES
+        ));
+        assert!(!has_allowed_scheme(
+            "data:text/html,<script>",
+            &DEFAULT_URL_SCHEMES
+        ));
+    }
+}
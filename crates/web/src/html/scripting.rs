@@ -0,0 +1,495 @@
+//! Preparing and running `<script>` elements
+//!
+//! Scripts without `async` or `defer` attributes block the parser until they were fetched
+//! and executed. Deferred scripts run in order once the document was parsed, async scripts
+//! run as soon as they were fetched.
+//!
+//! Only classic scripts are supported.
+//!
+//! <https://html.spec.whatwg.org/multipage/scripting.html#script-processing-model>
+
+use std::{collections::VecDeque, panic};
+
+use resourceloader::{PendingLoad, RESOURCE_LOADER};
+use url::URL;
+
+use crate::{
+    dom::{
+        dom_objects::{Document, HtmlScriptElement, Node},
+        DomPtr,
+    },
+    integrity,
+};
+
+/// <https://html.spec.whatwg.org/multipage/scripting.html#concept-script-type>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ScriptType {
+    Classic,
+    Module,
+}
+
+/// Determine the type of a script from its `type` and `language` attributes
+///
+/// Returns `None` for data blocks, which are never executed.
+fn script_type(element: &HtmlScriptElement) -> Option<ScriptType> {
+    let type_attribute = element.type_attribute();
+    let language_attribute = element.language_attribute();
+
+    // 8. If any of the following are true:
+    //    * el has a type attribute whose value is the empty string;
+    //    * el has no type attribute but it has a language attribute and that attribute's value is the empty string; or
+    //    * el has neither a type attribute nor a language attribute,
+    //    then let the script block's type string for this script element be "text/javascript".
+    // Otherwise, if el has a type attribute, then let the script block's type string be the value of that attribute
+    // with leading and trailing ASCII whitespace stripped.
+    // Otherwise, el has a non-empty language attribute; let the script block's type string be the concatenation of
+    // "text/" and the value of el's language attribute.
+    let type_string = match (type_attribute, language_attribute) {
+        (Some(type_attribute), _) if !type_attribute.is_empty() => type_attribute
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .to_owned(),
+        (None, Some(language_attribute)) if !language_attribute.is_empty() => {
+            format!("text/{language_attribute}")
+        },
+        _ => "text/javascript".to_owned(),
+    };
+
+    // 9. If the script block's type string is a JavaScript MIME type essence match, then set el's type to "classic".
+    let is_javascript_essence = !type_string.contains(';')
+        && type_string
+            .parse::<mime::MIMEType>()
+            .is_ok_and(|mime_type| mime_type.is_javascript());
+    if is_javascript_essence {
+        return Some(ScriptType::Classic);
+    }
+
+    // 10. Otherwise, if the script block's type string is an ASCII case-insensitive match for the string "module",
+    //     then set el's type to "module".
+    if type_string.eq_ignore_ascii_case("module") {
+        return Some(ScriptType::Module);
+    }
+
+    // FIXME: 11. Otherwise, if the script block's type string is an ASCII case-insensitive match for the string "importmap",
+    //            then set el's type to "importmap".
+
+    // 12. Otherwise, return. (No script is executed, and el's type is left as null.)
+    None
+}
+
+/// <https://dom.spec.whatwg.org/#connected>
+fn is_connected(element: &DomPtr<HtmlScriptElement>) -> bool {
+    let mut node: DomPtr<Node> = element.clone().upcast();
+    loop {
+        let parent = node.borrow().parent_node();
+        match parent {
+            Some(parent) => node = parent,
+            None => return node.is_a::<Document>(),
+        }
+    }
+}
+
+/// Where the source text of a script comes from
+enum ScriptSource {
+    Inline(String),
+    External {
+        url: URL,
+        integrity: String,
+        load: PendingLoad,
+    },
+}
+
+/// A script that was prepared, but did not run yet
+pub struct PendingScript {
+    element: DomPtr<HtmlScriptElement>,
+    source: ScriptSource,
+}
+
+impl PendingScript {
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#ready-to-be-parser-executed>
+    #[must_use]
+    fn is_ready(&self) -> bool {
+        match &self.source {
+            ScriptSource::Inline(_) => true,
+            ScriptSource::External { load, .. } => load.is_complete(),
+        }
+    }
+
+    /// Wait until the script was fetched
+    ///
+    /// Returns `None` if fetching the script failed.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/webappapis.html#fetch-a-classic-script>
+    fn into_source_text(self) -> Option<String> {
+        let (url, integrity, load) = match self.source {
+            ScriptSource::Inline(source_text) => return Some(source_text),
+            ScriptSource::External {
+                url,
+                integrity,
+                load,
+            } => (url, integrity, load),
+        };
+
+        let resource = match load.block() {
+            Ok(resource) => resource,
+            Err(error) => {
+                log::error!("Failed to load script {url} ({error:?})");
+                return None;
+            },
+        };
+
+        if !integrity::bytes_match_metadata(resource.data(), &integrity) {
+            log::error!(
+                "Failed to load script: None of the digests in the integrity attribute match {url}, the script was blocked"
+            );
+            return None;
+        }
+
+        // FIXME: Decode the script with the encoding given by the charset attribute or the document
+        Some(String::from_utf8_lossy(resource.data()).into_owned())
+    }
+}
+
+/// Runs the scripts of a document while it is being parsed
+///
+/// Every document has its own runtime, which is shared by all of its scripts.
+#[derive(Default)]
+pub struct ScriptRunner {
+    // FIXME: The runtime has no realm or host objects yet, so scripts can't reach the document
+    //        or any of the web APIs. Once it does, the interfaces implemented in this crate
+    //        (like `window`, `location`, `Intl` and the observers) should be registered on
+    //        its global object.
+    vm: js::Vm,
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#list-of-scripts-that-will-execute-when-the-document-has-finished-parsing>
+    deferred_scripts: VecDeque<PendingScript>,
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#set-of-scripts-that-will-execute-as-soon-as-possible>
+    async_scripts: Vec<PendingScript>,
+}
+
+impl ScriptRunner {
+    /// Returns the script if it should be executed before the parser continues
+    ///
+    /// Deferred and async scripts are remembered and executed later.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#prepare-the-script-element>
+    #[must_use]
+    pub fn prepare(&mut self, element: &DomPtr<HtmlScriptElement>) -> Option<PendingScript> {
+        let script_element = element.borrow();
+
+        // 1. If el's already started is true, then return.
+        if script_element.already_started() {
+            return None;
+        }
+
+        // 2. Let parser document be el's parser document.
+        // 3. Set el's parser document to null.
+        // 4. If parser document is non-null and el does not have an async attribute, then set el's force async to true.
+        // NOTE: These steps only matter if the script is prepared again later, which only
+        //       scripts can do.
+        let is_parser_inserted = script_element.is_parser_inserted();
+
+        // 5. Let source text be el's child text content.
+        let source_text = script_element.child_text_content();
+
+        // 6. If el has no src attribute, and source text is the empty string, then return.
+        let src = script_element.src();
+        if src.is_none() && source_text.is_empty() {
+            return None;
+        }
+
+        // 7. If el is not connected, then return.
+        drop(script_element);
+        if !is_connected(element) {
+            return None;
+        }
+        let mut script_element = element.borrow_mut();
+
+        // 8. - 12. Determine the type of the script
+        let script_type = script_type(&script_element)?;
+
+        // 13. If parser document is non-null, then set el's parser document back to parser document and set el's force async to false.
+        // 14. Set el's already started to true.
+        script_element.set_already_started();
+
+        // 15. Set el's preparation-time document to its node document.
+        // 16. If parser document is non-null, and parser document is not equal to el's preparation-time document, then return.
+        // 17. If scripting is disabled for el, then return.
+        // NOTE: The parser only creates a ScriptRunner if scripting is enabled
+
+        // 18. If el has a nomodule content attribute and its type is "classic", then return.
+        // NOTE: Module scripts are not supported, so their nomodule fallbacks are executed instead
+
+        if script_type == ScriptType::Module {
+            // FIXME: Support module scripts
+            log::warn!("Module scripts are not supported, skipping script");
+            return None;
+        }
+
+        // 19. - 31. Content security policy, event handler attributes, fetch options
+        let integrity = script_element.integrity();
+        let has_async_attribute = script_element.has_async_attribute();
+        let force_async = script_element.force_async();
+        let has_defer_attribute = script_element.has_defer_attribute();
+        drop(script_element);
+
+        // 32. If el has a src content attribute, then:
+        let Some(src) = src else {
+            // 33. If el does not have a src content attribute:
+            // FIXME: 35.1 Inline scripts should block the parser while there is a style sheet that is blocking scripts
+            // 35.2 Otherwise, immediately execute the script element el, even if other scripts are already executing.
+            return Some(PendingScript {
+                element: element.clone(),
+                source: ScriptSource::Inline(source_text),
+            });
+        };
+
+        // 3. If src is the empty string, then queue an element task on the DOM manipulation task source given el
+        //    to fire an event named error at el, and return.
+        // 5. Let url be the result of encoding-parsing a URL given src, relative to el's node document.
+        let document = element.borrow().owning_document()?;
+        let url = URL::parse_with_base(&src, Some(document.borrow().url()), None).ok();
+        let Some(url) = url.filter(|_| !src.is_empty()) else {
+            // 6. If url is failure, then queue an element task on the DOM manipulation task source given el to
+            //    fire an event named error at el, and return.
+            // FIXME: Fire the event at the element once the DOM supports event listeners
+            log::debug!("error on <script>");
+            return None;
+        };
+
+        // 9. Switch on el's type:
+        //    "classic": Fetch a classic script given url, settings object, options, classic script CORS setting, encoding, and onComplete.
+        let load = RESOURCE_LOADER.schedule_load(url.clone());
+        let script = PendingScript {
+            element: element.clone(),
+            source: ScriptSource::External {
+                url,
+                integrity,
+                load,
+            },
+        };
+
+        // 34. If el's type is "classic" and el has a src attribute, or el's type is "module":
+        // 2. If el has an async attribute or el's force async is true:
+        //    3. Otherwise, if el is not parser-inserted:
+        if has_async_attribute || force_async || !is_parser_inserted {
+            // Append el to its preparation-time document's set of scripts that will execute as soon as possible.
+            self.async_scripts.push(script);
+            None
+        }
+        // 4. Otherwise, if el has a defer attribute or el's type is "module":
+        else if has_defer_attribute {
+            // Append el to its parser document's list of scripts that will execute when the document has finished parsing.
+            self.deferred_scripts.push_back(script);
+            None
+        }
+        // 5. Otherwise:
+        else {
+            // Set el's parser document's pending parsing-blocking script to el.
+            Some(script)
+        }
+    }
+
+    /// Execute all async scripts that were fetched in the meantime
+    pub fn execute_ready_async_scripts(&mut self) {
+        if self.async_scripts.is_empty() {
+            return;
+        }
+
+        let (ready, pending) = std::mem::take(&mut self.async_scripts)
+            .into_iter()
+            .partition(PendingScript::is_ready);
+        self.async_scripts = pending;
+
+        for script in ready {
+            self.execute(script);
+        }
+    }
+
+    /// Execute the scripts that were deferred until the document finished parsing
+    ///
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#the-end>
+    pub fn execute_deferred_scripts(&mut self) {
+        // 5. While the list of scripts that will execute when the document has finished parsing is not empty:
+        while let Some(script) = self.deferred_scripts.pop_front() {
+            // 1. Spin the event loop until the first script in the list of scripts that will execute when the
+            //    document has finished parsing has its ready to be parser-executed set to true and the parser's
+            //    Document has no style sheet that is blocking scripts.
+            // 2. Execute the script element given by the first script in the list of scripts that will execute
+            //    when the document has finished parsing.
+            // 3. Remove the first script element from the list of scripts that will execute when the document has
+            //    finished parsing (i.e. shift out the first entry in the list).
+            self.execute(script);
+        }
+    }
+
+    /// Execute all async scripts, waiting for the ones that are still being fetched
+    ///
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#the-end>
+    pub fn execute_remaining_async_scripts(&mut self) {
+        // 7. Spin the event loop until the set of scripts that will execute as soon as possible and the list of
+        //    scripts that will execute in order as soon as possible are empty.
+        for script in std::mem::take(&mut self.async_scripts) {
+            self.execute(script);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/scripting.html#execute-the-script-element>
+    pub fn execute(&mut self, script: PendingScript) {
+        let element = script.element.clone();
+        let is_from_external_file = matches!(script.source, ScriptSource::External { .. });
+
+        // 1. Let document be el's node document.
+        let Some(document) = element.borrow().owning_document() else {
+            return;
+        };
+
+        // 4. If el's result is null, then fire an event named error at el, and return.
+        let Some(source_text) = script.into_source_text() else {
+            // FIXME: Fire the event at the element once the DOM supports event listeners
+            log::debug!("error on <script>");
+            return;
+        };
+
+        // 6. Switch on el's type:
+        //    "classic"
+        //     1. Let oldCurrentScript be the value to which document's currentScript object was most recently set.
+        let old_current_script = document.borrow().current_script();
+
+        // 2. If el's root is not a shadow root, then set document's currentScript attribute to el.
+        //    Otherwise, set it to null.
+        document
+            .borrow_mut()
+            .set_current_script(Some(element.clone()));
+
+        // 3. Run the classic script given by el's result.
+        self.run_classic_script(&source_text);
+
+        // 4. Set document's currentScript attribute to oldCurrentScript.
+        document.borrow_mut().set_current_script(old_current_script);
+
+        // 8. If el's from an external file is true, then fire an event named load at el.
+        if is_from_external_file {
+            // FIXME: Fire the event at the element once the DOM supports event listeners
+            log::debug!("load on <script>");
+        }
+    }
+
+    /// Run a script that the user added to the page
+    ///
+    /// User scripts share the runtime with the scripts of the page, but they don't belong
+    /// to any element.
+    pub fn execute_user_script(&mut self, source_text: &str) {
+        self.run_classic_script(source_text);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/webappapis.html#run-a-classic-script>
+    fn run_classic_script(&mut self, source_text: &str) {
+        // The runtime still panics on a lot of valid javascript, a single
+        // unsupported script must not take down the whole page
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let executable: js::Executable = source_text.parse().map_err(|error| {
+                log::error!("Failed to compile script: {error:?}");
+            })?;
+
+            self.vm.execute(executable);
+            Ok::<(), ()>(())
+        }));
+
+        if result.is_err() {
+            log::error!("Script execution panicked, resetting the runtime");
+            self.vm = js::Vm::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dom::dom_objects::DocumentReadyState,
+        html::{self, tokenization::IgnoreParseErrors},
+        static_interned,
+    };
+
+    fn find_scripts(node: &DomPtr<Node>, scripts: &mut Vec<DomPtr<HtmlScriptElement>>) {
+        if let Some(script) = node.try_into_type::<HtmlScriptElement>() {
+            scripts.push(script);
+        }
+
+        for child in node.borrow().children() {
+            find_scripts(child, scripts);
+        }
+    }
+
+    #[test]
+    fn determine_script_type() {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let source = "<script>a</script>
+            <script type=\" text/javascript \">b</script>
+            <script language=\"javascript\">c</script>
+            <script type=\"module\">d</script>
+            <script type=\"text/javascript; charset=utf-8\">e</script>
+            <script type=\"application/json\">{}</script>";
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        let (document, _) = parser.parse();
+
+        let mut scripts = vec![];
+        find_scripts(&document.upcast(), &mut scripts);
+
+        let types: Vec<Option<ScriptType>> = scripts
+            .iter()
+            .map(|script| script_type(&script.borrow()))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                Some(ScriptType::Classic),
+                Some(ScriptType::Classic),
+                Some(ScriptType::Classic),
+                Some(ScriptType::Module),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn run_parser_inserted_scripts() {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let source = "<script id=\"a\">let a = 1;</script><script type=\"application/json\" id=\"b\">{}</script>";
+        let parser: html::Parser<IgnoreParseErrors> =
+            html::Parser::new(source, document).with_script_execution();
+        let (document, _) = parser.parse();
+
+        let mut scripts = vec![];
+        find_scripts(&DomPtr::clone(&document).upcast(), &mut scripts);
+        assert_eq!(scripts.len(), 2);
+
+        // Data blocks are never started
+        assert!(scripts[0].borrow().already_started());
+        assert!(!scripts[1].borrow().already_started());
+        assert_eq!(
+            scripts[0]
+                .borrow()
+                .attributes()
+                .get(&static_interned!("id"))
+                .map(ToString::to_string)
+                .as_deref(),
+            Some("a")
+        );
+        assert!(document.borrow().current_script().is_none());
+        assert_eq!(
+            document.borrow().ready_state(),
+            DocumentReadyState::Interactive
+        );
+    }
+}
@@ -0,0 +1,443 @@
+//! Choosing between the images in the `srcset` attribute of an `<img>` element
+//!
+//! <https://html.spec.whatwg.org/multipage/images.html>
+
+use crate::css::{
+    layout::Pixels,
+    style::{specified::Length, StyleContext, ToComputedStyle},
+    CSSParse, MediaEnvironment, MediaQueryList, Origin, Parser,
+};
+
+/// <https://html.spec.whatwg.org/multipage/images.html#image-candidate-string>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Descriptor {
+    /// `x` descriptor
+    Density(f32),
+
+    /// `w` descriptor, the width of the image in pixels
+    Width(u32),
+}
+
+/// <https://html.spec.whatwg.org/multipage/images.html#image-candidate-string>
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageCandidate {
+    pub url: String,
+
+    /// `None` if the candidate has no descriptor, which is the same as `1x`
+    pub descriptor: Option<Descriptor>,
+}
+
+/// An image that was selected from a source set
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageSource {
+    pub url: String,
+
+    /// <https://html.spec.whatwg.org/multipage/images.html#current-pixel-density>
+    pub density: f32,
+}
+
+/// A single entry in the `sizes` attribute
+///
+/// <https://html.spec.whatwg.org/multipage/images.html#sizes-attributes>
+#[derive(Clone, Debug)]
+struct SourceSize {
+    /// `None` if the size applies unconditionally
+    condition: Option<MediaQueryList>,
+    size: Length,
+}
+
+/// <https://html.spec.whatwg.org/multipage/images.html#parse-a-srcset-attribute>
+#[must_use]
+pub fn parse_srcset(input: &str) -> Vec<ImageCandidate> {
+    let mut candidates = vec![];
+    let mut remaining = input;
+
+    loop {
+        // 4. Splitting loop: Collect a sequence of code points that are ASCII whitespace or U+002C COMMA
+        //    characters from input given position.
+        remaining = remaining.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+
+        // 5. If position is past the end of input, return candidates.
+        if remaining.is_empty() {
+            return candidates;
+        }
+
+        // 6. Collect a sequence of code points that are not ASCII whitespace from input given position,
+        //    and let that be url.
+        let url_end = remaining
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(remaining.len());
+        let mut url = &remaining[..url_end];
+        remaining = &remaining[url_end..];
+
+        // 8. If url ends with U+002C (,), then remove all trailing U+002C COMMA characters from url.
+        //    If this removed more than one character, that is a parse error.
+        let descriptors = if url.ends_with(',') {
+            url = url.trim_end_matches(',');
+            vec![]
+        } else {
+            // Otherwise: Descriptor tokenizer
+            let descriptors_end = end_of_descriptors(remaining);
+            let descriptors = remaining[..descriptors_end]
+                .split_ascii_whitespace()
+                .collect();
+            remaining = &remaining[descriptors_end..];
+            descriptors
+        };
+
+        // 9. Descriptor parser
+        if let Some(descriptor) = parse_descriptors(&descriptors) {
+            // 15. If error is still no, then append a new image source to candidates whose URL is url,
+            //     associated with a width width if not absent and a pixel density density if not absent.
+            candidates.push(ImageCandidate {
+                url: url.to_owned(),
+                descriptor,
+            });
+        }
+    }
+}
+
+/// The position of the comma that ends the descriptors of a candidate, ignoring commas in parentheses
+fn end_of_descriptors(input: &str) -> usize {
+    let mut is_in_parens = false;
+    for (index, c) in input.char_indices() {
+        match c {
+            '(' => is_in_parens = true,
+            ')' => is_in_parens = false,
+            ',' if !is_in_parens => return index,
+            _ => {},
+        }
+    }
+
+    input.len()
+}
+
+/// Returns `None` if the descriptors are invalid, in which case the candidate is dropped
+///
+/// <https://html.spec.whatwg.org/multipage/images.html#parse-a-srcset-attribute>
+fn parse_descriptors(descriptors: &[&str]) -> Option<Option<Descriptor>> {
+    // 10. Let error be no.
+    // 11. Let width be absent.
+    // 12. Let density be absent.
+    // 13. Let future-compat-h be absent.
+    let mut width = None;
+    let mut density = None;
+    let mut has_height = false;
+
+    // 14. For each descriptor in descriptors, run the appropriate set of steps from the following list:
+    for descriptor in descriptors {
+        let Some(kind) = descriptor.chars().last() else {
+            continue;
+        };
+        let value = &descriptor[..descriptor.len() - kind.len_utf8()];
+
+        match kind {
+            // If the descriptor consists of a valid non-negative integer followed by a U+0077 LATIN SMALL LETTER W character
+            'w' => {
+                // 1. If the user agent does not support the sizes attribute, let error be yes.
+                // 2. If width and density are not both absent, then let error be yes.
+                if width.is_some() || density.is_some() {
+                    return None;
+                }
+
+                // 3. Apply the rules for parsing non-negative integers to the descriptor.
+                //    If the result is 0, let error be yes. Otherwise, let width be the result.
+                width = Some(parse_non_negative_integer(value).filter(|&width| width != 0)?);
+            },
+            // If the descriptor consists of a valid floating-point number followed by a U+0078 LATIN SMALL LETTER X character
+            'x' => {
+                // 1. If width, density and future-compat-h are not all absent, then let error be yes.
+                if width.is_some() || density.is_some() || has_height {
+                    return None;
+                }
+
+                // 2. Apply the rules for parsing floating-point number values to the descriptor.
+                //    If the result is less than 0, let error be yes. Otherwise, let density be the result.
+                density =
+                    Some(parse_floating_point_number(value).filter(|&density| density >= 0.)?);
+            },
+            // If the descriptor consists of a valid non-negative integer followed by a U+0068 LATIN SMALL LETTER H character
+            'h' => {
+                // 1. If future-compat-h and density are not both absent, then let error be yes.
+                if has_height || density.is_some() {
+                    return None;
+                }
+
+                // 2. Apply the rules for parsing non-negative integers to the descriptor.
+                //    If the result is 0, let error be yes. Otherwise, let future-compat-h be the result.
+                parse_non_negative_integer(value).filter(|&height| height != 0)?;
+                has_height = true;
+            },
+            // Anything else: Let error be yes.
+            _ => return None,
+        }
+    }
+
+    // 15. If future-compat-h is not absent and width is absent, let error be yes.
+    if has_height && width.is_none() {
+        return None;
+    }
+
+    Some(
+        width
+            .map(Descriptor::Width)
+            .or(density.map(Descriptor::Density)),
+    )
+}
+
+/// <https://html.spec.whatwg.org/multipage/common-microsyntaxes.html#valid-non-negative-integer>
+fn parse_non_negative_integer(value: &str) -> Option<u32> {
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    value.parse().ok()
+}
+
+/// <https://html.spec.whatwg.org/multipage/common-microsyntaxes.html#valid-floating-point-number>
+fn parse_floating_point_number(value: &str) -> Option<f32> {
+    // Rust accepts some values (like "inf" or "+1") that are not valid floating point numbers in html
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if !digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|number| number.is_finite())
+}
+
+/// <https://html.spec.whatwg.org/multipage/images.html#parse-a-sizes-attribute>
+fn parse_sizes(input: &str) -> Vec<SourceSize> {
+    // 2. Let unparsed sizes list be the result of parsing a comma-separated list of component values from
+    //    the value of element's sizes attribute (or the empty string, if the attribute is absent).
+    // 3. For each unparsed size in unparsed sizes list:
+    input
+        .split(',')
+        .filter_map(|unparsed_size| {
+            // 1. Remove all consecutive <whitespace-token>s from the end of unparsed size.
+            //    If unparsed size is now empty, that is a parse error; continue.
+            let unparsed_size = unparsed_size.trim();
+            if unparsed_size.is_empty() {
+                return None;
+            }
+
+            // 2. If the last component value in unparsed size is a valid non-negative <source-size-value>,
+            //    then set size to its value and remove the component value from unparsed size.
+            //    Otherwise, continue.
+            let (condition, size) = unparsed_size
+                .rsplit_once(|c: char| c.is_ascii_whitespace())
+                .map_or(("", unparsed_size), |(condition, size)| {
+                    (condition.trim(), size)
+                });
+            let size = Length::parse_complete(&mut Parser::new(size, Origin::Author)).ok()?;
+
+            // 3. Remove all consecutive <whitespace-token>s from the end of unparsed size.
+            //    If unparsed size is now empty, then return size.
+            if condition.is_empty() {
+                return Some(SourceSize {
+                    condition: None,
+                    size,
+                });
+            }
+
+            // 4. Parse the remaining component values in unparsed size as a <media-condition>.
+            //    If it does not parse correctly, or it does parse correctly but the <media-condition>
+            //    evaluates to false, continue.
+            let condition =
+                MediaQueryList::parse_complete(&mut Parser::new(condition, Origin::Author)).ok()?;
+            Some(SourceSize {
+                condition: Some(condition),
+                size,
+            })
+        })
+        .collect()
+}
+
+/// The width that the image is expected to be displayed at
+///
+/// <https://html.spec.whatwg.org/multipage/images.html#parse-a-sizes-attribute>
+fn source_size(sizes: &str, environment: &MediaEnvironment) -> Pixels {
+    let context = StyleContext::for_media_environment(environment);
+
+    parse_sizes(sizes)
+        .into_iter()
+        .filter(|source_size| {
+            source_size
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.matches(environment))
+        })
+        .map(|source_size| source_size.size.to_computed_style(&context))
+        .find(|size| *size >= Pixels::ZERO)
+        // 4. Return 100vw.
+        .unwrap_or(environment.viewport.width)
+}
+
+/// Pick the image that best matches the device, `None` if there are no images to choose from
+///
+/// <https://html.spec.whatwg.org/multipage/images.html#select-an-image-source>
+pub(crate) fn select_image_source(
+    src: Option<&str>,
+    srcset: Option<&str>,
+    sizes: Option<&str>,
+    environment: &MediaEnvironment,
+    device_pixel_ratio: f32,
+) -> Option<ImageSource> {
+    let candidates = srcset.map(parse_srcset).unwrap_or_default();
+    let has_width_descriptors = candidates
+        .iter()
+        .any(|candidate| matches!(candidate.descriptor, Some(Descriptor::Width(_))));
+
+    // <https://html.spec.whatwg.org/multipage/images.html#normalise-the-source-densities>
+    // 1. Let source size be source set's source size.
+    let source_size = source_size(sizes.unwrap_or_default(), environment);
+
+    // 2. For each image source in source set:
+    let mut source_set: Vec<ImageSource> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let density = match candidate.descriptor {
+                // 1. If the image source has a pixel density descriptor, continue to the next image source.
+                Some(Descriptor::Density(density)) => density,
+
+                // 2. Otherwise, if the image source has a width descriptor, replace the width descriptor with a
+                //    pixel density descriptor with a value of the width descriptor value divided by source size
+                //    and a unit of x.
+                Some(Descriptor::Width(width)) => width as f32 / source_size.0,
+
+                // 3. Otherwise, give the image source a pixel density descriptor of 1x.
+                None => 1.,
+            };
+
+            (density.is_finite() && density > 0.).then_some(ImageSource {
+                url: candidate.url,
+                density,
+            })
+        })
+        .collect();
+
+    // <https://html.spec.whatwg.org/multipage/images.html#update-the-source-set>
+    // If child has a src attribute whose value is not the empty string and source set does not contain an
+    // image source with a pixel density descriptor value of 1, and no image source with a width descriptor,
+    // append child's src attribute value to source set.
+    if let Some(src) = src.filter(|src| !src.is_empty())
+        && !has_width_descriptors
+        && !source_set.iter().any(|source| source.density == 1.)
+    {
+        source_set.push(ImageSource {
+            url: src.to_owned(),
+            density: 1.,
+        });
+    }
+
+    // Prefer the smallest image that is still sharp on the device, otherwise the sharpest one there is
+    source_set.sort_by(|a, b| a.density.total_cmp(&b.density));
+    let sharp_enough = source_set
+        .iter()
+        .position(|source| source.density >= device_pixel_ratio);
+
+    match sharp_enough {
+        Some(index) => Some(source_set.swap_remove(index)),
+        None => source_set.pop(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::layout::Size;
+
+    fn environment(width: f32) -> MediaEnvironment {
+        MediaEnvironment::new(Size {
+            width: Pixels(width),
+            height: Pixels(600.),
+        })
+    }
+
+    fn select(srcset: &str, sizes: Option<&str>, width: f32, device_pixel_ratio: f32) -> String {
+        select_image_source(
+            Some("fallback.png"),
+            Some(srcset),
+            sizes,
+            &environment(width),
+            device_pixel_ratio,
+        )
+        .unwrap()
+        .url
+    }
+
+    #[test]
+    fn parse_candidates() {
+        assert_eq!(
+            parse_srcset(
+                " a.png, b.png 2x,c.png 300w, ,d.png,, e.png 1.5x 2x, f.png 0w, g.png 100w 50h"
+            ),
+            [
+                ImageCandidate {
+                    url: "a.png".to_string(),
+                    descriptor: None,
+                },
+                ImageCandidate {
+                    url: "b.png".to_string(),
+                    descriptor: Some(Descriptor::Density(2.)),
+                },
+                ImageCandidate {
+                    url: "c.png".to_string(),
+                    descriptor: Some(Descriptor::Width(300)),
+                },
+                ImageCandidate {
+                    url: "d.png".to_string(),
+                    descriptor: None,
+                },
+                ImageCandidate {
+                    url: "g.png".to_string(),
+                    descriptor: Some(Descriptor::Width(100)),
+                },
+            ]
+        );
+
+        // Urls may contain commas
+        assert_eq!(
+            parse_srcset("data:image/png;base64,abc 2x")[0].url,
+            "data:image/png;base64,abc"
+        );
+        assert!(parse_srcset("a.png infx, b.png -1x").is_empty());
+    }
+
+    #[test]
+    fn evaluate_sizes() {
+        let sizes = "(max-width: 600px) 100vw, (max-width: 1000px) 50vw, 300px";
+        assert_eq!(source_size(sizes, &environment(500.)), Pixels(500.));
+        assert_eq!(source_size(sizes, &environment(800.)), Pixels(400.));
+        assert_eq!(source_size(sizes, &environment(1200.)), Pixels(300.));
+
+        // Invalid entries are skipped, without any valid entry the image fills the viewport
+        assert_eq!(source_size("(foo, 200px", &environment(800.)), Pixels(200.));
+        assert_eq!(source_size("", &environment(800.)), Pixels(800.));
+    }
+
+    #[test]
+    fn select_by_density() {
+        let srcset = "small.png, large.png 2x, huge.png 3x";
+        assert_eq!(select(srcset, None, 800., 1.), "small.png");
+        assert_eq!(select(srcset, None, 800., 1.5), "large.png");
+        assert_eq!(select(srcset, None, 800., 4.), "huge.png");
+
+        // The src attribute is used as a 1x candidate
+        assert_eq!(select("large.png 2x", None, 800., 1.), "fallback.png");
+    }
+
+    #[test]
+    fn select_by_width() {
+        let srcset = "400.png 400w, 800.png 800w, 1600.png 1600w";
+        assert_eq!(select(srcset, None, 800., 1.), "800.png");
+        assert_eq!(select(srcset, None, 800., 2.), "1600.png");
+        assert_eq!(select(srcset, Some("50vw"), 800., 1.), "400.png");
+
+        let source =
+            select_image_source(None, Some(srcset), Some("200px"), &environment(800.), 1.).unwrap();
+        assert_eq!(source.density, 2.);
+    }
+}
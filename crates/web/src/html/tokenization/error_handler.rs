@@ -151,7 +151,8 @@ pub enum HtmlParseError {
     UnknownNamedCharacterReference,
 }
 
-pub trait ParseErrorHandler {
+/// Error handlers must be [Send], so the tokenizer can run on a separate thread
+pub trait ParseErrorHandler: Send + 'static {
     fn handle(error: HtmlParseError);
 }
 
@@ -0,0 +1,265 @@
+//! Tokenization of large documents on a separate thread
+//!
+//! The tree builder has to run on the main thread, because DOM nodes can't be shared
+//! between threads. Tokens however are plain data, so the tokenizer runs ahead of the
+//! tree builder on its own thread and sends the tokens over in batches.
+//!
+//! The tree builder switches the tokenizer to a different state after some start tags,
+//! like `<script>` or `<title>`. After emitting one of these, the parser thread waits
+//! for the tree builder to process the tag and tell it which state to continue in.
+
+use std::{io, mem, sync::mpsc, thread, vec};
+
+use super::{ParseErrorHandler, Token, Tokenizer, TokenizerState};
+use crate::static_interned;
+
+/// Documents that are smaller than this (in bytes) are tokenized on the main thread,
+/// because spawning a thread would take longer than tokenizing them
+pub const THREADED_TOKENIZATION_THRESHOLD: usize = 64 * 1024;
+
+/// The number of tokens that are sent to the tree builder at once
+const BATCH_SIZE: usize = 1024;
+
+/// Whether the tree builder might switch the state of the tokenizer after processing `token`
+///
+/// This must include every start tag after which the tree builder calls
+/// [Tokenizer::switch_to].
+fn is_synchronization_point(token: &Token) -> bool {
+    let Token::StartTag(tag) = token else {
+        return false;
+    };
+
+    matches!(
+        tag.name,
+        static_interned!("title")
+            | static_interned!("textarea")
+            | static_interned!("style")
+            | static_interned!("xmp")
+            | static_interned!("iframe")
+            | static_interned!("noembed")
+            | static_interned!("noframes")
+            | static_interned!("noscript")
+            | static_interned!("script")
+            | static_interned!("plaintext")
+    )
+}
+
+/// The tree builder side of a tokenizer that runs on a separate thread
+#[derive(Debug)]
+pub struct ThreadedTokenizer {
+    batches: mpsc::Receiver<Vec<Token>>,
+
+    /// Tells the parser thread which state to continue in after a synchronization point,
+    /// `None` if the state should not change
+    states: mpsc::Sender<Option<TokenizerState>>,
+    current_batch: vec::IntoIter<Token>,
+
+    /// Whether the parser thread waits for the state after the most recently returned token
+    is_awaiting_state: bool,
+
+    /// The state that the tree builder switched to while processing the most recent token
+    pending_state: Option<TokenizerState>,
+    done: bool,
+}
+
+impl ThreadedTokenizer {
+    /// Start tokenizing `source` on a new thread
+    ///
+    /// The thread exits once the whole source was tokenized or the [ThreadedTokenizer] is dropped.
+    pub fn spawn<P: ParseErrorHandler>(source: &str) -> io::Result<Self> {
+        let (batch_sender, batches) = mpsc::channel();
+        let (states, state_receiver) = mpsc::channel();
+
+        let source = source.to_owned();
+        thread::Builder::new()
+            .name("HTML Parser".to_string())
+            .spawn(move || {
+                let tokenizer = Tokenizer::<P>::new(&source);
+                drop(source);
+                run(tokenizer, &batch_sender, &state_receiver);
+            })?;
+
+        Ok(Self {
+            batches,
+            states,
+            current_batch: vec![].into_iter(),
+            is_awaiting_state: false,
+            pending_state: None,
+            done: false,
+        })
+    }
+
+    /// Switch the tokenizer to `state`, starting after the most recently returned token
+    pub fn switch_to(&mut self, state: TokenizerState) {
+        if !self.is_awaiting_state {
+            log::error!("Cannot switch the parser thread to {state:?}, it is not waiting for the tree builder");
+            return;
+        }
+
+        self.pending_state = Some(state);
+    }
+}
+
+impl Iterator for ThreadedTokenizer {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        // The tree builder is done processing the previous token, so the parser thread can continue
+        if mem::take(&mut self.is_awaiting_state) {
+            // If this fails then the thread has already exited, which is reported below
+            _ = self.states.send(self.pending_state.take());
+        }
+
+        let token = match self.current_batch.next() {
+            Some(token) => token,
+            None => {
+                let Ok(batch) = self.batches.recv() else {
+                    log::error!("Parser thread exited before the end of the document");
+                    self.done = true;
+                    return None;
+                };
+
+                self.current_batch = batch.into_iter();
+                self.current_batch.next()?
+            },
+        };
+
+        self.is_awaiting_state = is_synchronization_point(&token);
+        self.done = matches!(token, Token::EOF);
+        Some(token)
+    }
+}
+
+/// The main loop of the parser thread
+fn run<P: ParseErrorHandler>(
+    mut tokenizer: Tokenizer<P>,
+    batches: &mpsc::Sender<Vec<Token>>,
+    states: &mpsc::Receiver<Option<TokenizerState>>,
+) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    while let Some(token) = tokenizer.next() {
+        let is_synchronization_point = is_synchronization_point(&token);
+        batch.push(token);
+
+        if !is_synchronization_point && batch.len() < BATCH_SIZE {
+            continue;
+        }
+
+        // The tree builder was dropped, there is no point in continuing
+        if batches.send(mem::take(&mut batch)).is_err() {
+            return;
+        }
+
+        if is_synchronization_point {
+            match states.recv() {
+                Ok(Some(state)) => tokenizer.switch_to(state),
+                Ok(None) => {},
+                Err(_) => return,
+            }
+        }
+    }
+
+    if !batch.is_empty() {
+        _ = batches.send(batch);
+    }
+}
+
+/// The tokens that the tree builder consumes
+///
+/// Small documents are tokenized inline, large ones on a separate thread.
+#[derive(Debug)]
+pub enum TokenStream<P: ParseErrorHandler> {
+    Inline(Box<Tokenizer<P>>),
+    Threaded(ThreadedTokenizer),
+}
+
+impl<P: ParseErrorHandler> TokenStream<P> {
+    #[must_use]
+    pub fn new(source: &str) -> Self {
+        if source.len() < THREADED_TOKENIZATION_THRESHOLD {
+            return Self::Inline(Box::new(Tokenizer::new(source)));
+        }
+
+        match ThreadedTokenizer::spawn::<P>(source) {
+            Ok(tokenizer) => Self::Threaded(tokenizer),
+            Err(error) => {
+                log::warn!(
+                    "Failed to spawn parser thread ({error}), tokenizing on the main thread"
+                );
+                Self::Inline(Box::new(Tokenizer::new(source)))
+            },
+        }
+    }
+
+    pub fn switch_to(&mut self, state: TokenizerState) {
+        match self {
+            Self::Inline(tokenizer) => tokenizer.switch_to(state),
+            Self::Threaded(tokenizer) => tokenizer.switch_to(state),
+        }
+    }
+}
+
+impl<P: ParseErrorHandler> Iterator for TokenStream<P> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Inline(tokenizer) => tokenizer.next(),
+            Self::Threaded(tokenizer) => tokenizer.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::tokenization::IgnoreParseErrors;
+
+    /// Collect all tokens, switching states like the tree builder would
+    fn tokenize<I>(mut tokens: I, switch_to: impl Fn(&mut I, TokenizerState)) -> Vec<String>
+    where
+        I: Iterator<Item = Token>,
+    {
+        let mut result = vec![];
+        while let Some(token) = tokens.next() {
+            if let Token::StartTag(tag) = &token {
+                match tag.name {
+                    static_interned!("script") => {
+                        switch_to(&mut tokens, TokenizerState::ScriptData)
+                    },
+                    static_interned!("title") => switch_to(&mut tokens, TokenizerState::RCDATA),
+                    _ => {},
+                }
+            }
+            result.push(format!("{token:?}"));
+        }
+        result
+    }
+
+    #[test]
+    fn same_tokens_as_inline_tokenizer() {
+        let mut source = String::from("<!DOCTYPE html><title>a <b> &amp; c</title>");
+        for i in 0..BATCH_SIZE {
+            source.push_str(&format!(
+                "<p class=\"{i}\">text<script>if (1 < 2) {{}}</script>"
+            ));
+        }
+
+        let inline = tokenize(
+            Tokenizer::<IgnoreParseErrors>::new(&source),
+            |tokenizer, state| tokenizer.switch_to(state),
+        );
+        let threaded = tokenize(
+            ThreadedTokenizer::spawn::<IgnoreParseErrors>(&source).unwrap(),
+            |tokenizer, state| tokenizer.switch_to(state),
+        );
+
+        assert_eq!(inline, threaded);
+        assert!(inline.iter().any(|token| token.contains("Character('<')")));
+    }
+}
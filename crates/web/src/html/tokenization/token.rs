@@ -160,7 +160,7 @@ impl TagData {
     pub fn lookup_attribute(&self, want: InternedString) -> Option<InternedString> {
         for (key, value) in &self.attributes {
             if *key == want {
-                return Some(*value);
+                return Some(value.clone());
             }
         }
         None
@@ -7,20 +7,23 @@ use crate::{
     dom::{
         self,
         dom_objects::{
-            Comment, Document, DocumentType, Element, HtmlBodyElement, HtmlDdElement,
-            HtmlDivElement, HtmlElement, HtmlFormElement, HtmlHeadElement, HtmlHtmlElement,
+            Comment, Document, DocumentReadyState, DocumentType, Element, HtmlBodyElement,
+            HtmlDdElement, HtmlDivElement, HtmlElement, HtmlFormElement, HtmlHeadElement,
             HtmlLiElement, HtmlLinkElement, HtmlParagraphElement, HtmlScriptElement,
             HtmlTableElement, HtmlTemplateElement, Node, Text,
         },
         DomPtr, DomType, DomTyped,
     },
     html::{
-        links,
-        tokenization::{ParseErrorHandler, TagData, Token, Tokenizer, TokenizerState},
+        links, resource_hints,
+        scripting::ScriptRunner,
+        tokenization::{ParseErrorHandler, TagData, Token, TokenStream, TokenizerState},
         treebuilding::{ActiveFormattingElement, ActiveFormattingElements, FormatEntry},
     },
     infra::Namespace,
-    static_interned, InternedString,
+    integrity, static_interned,
+    user_content::USER_CONTENT,
+    InternedString,
 };
 
 use html_treebuilding_match::html_treebuilding_match;
@@ -58,6 +61,56 @@ const DEFAULT_SCOPE: &[InternedString] = &[
     static_interned!("title"),
 ];
 
+/// Start tags that close all open foreign elements when they are found in foreign content
+///
+/// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign>
+const BREAKOUT_ELEMENTS: &[InternedString] = &[
+    static_interned!("b"),
+    static_interned!("big"),
+    static_interned!("blockquote"),
+    static_interned!("body"),
+    static_interned!("br"),
+    static_interned!("center"),
+    static_interned!("code"),
+    static_interned!("dd"),
+    static_interned!("div"),
+    static_interned!("dl"),
+    static_interned!("dt"),
+    static_interned!("em"),
+    static_interned!("embed"),
+    static_interned!("h1"),
+    static_interned!("h2"),
+    static_interned!("h3"),
+    static_interned!("h4"),
+    static_interned!("h5"),
+    static_interned!("h6"),
+    static_interned!("head"),
+    static_interned!("hr"),
+    static_interned!("i"),
+    static_interned!("img"),
+    static_interned!("li"),
+    static_interned!("listing"),
+    static_interned!("menu"),
+    static_interned!("meta"),
+    static_interned!("nobr"),
+    static_interned!("ol"),
+    static_interned!("p"),
+    static_interned!("pre"),
+    static_interned!("ruby"),
+    static_interned!("s"),
+    static_interned!("small"),
+    static_interned!("span"),
+    static_interned!("strong"),
+    static_interned!("strike"),
+    static_interned!("sub"),
+    static_interned!("sup"),
+    static_interned!("table"),
+    static_interned!("tt"),
+    static_interned!("u"),
+    static_interned!("ul"),
+    static_interned!("var"),
+];
+
 /// <https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-button-scope>
 const BUTTON_SCOPE: &[InternedString] = &[
     static_interned!("html"),
@@ -122,7 +175,7 @@ pub enum FramesetOkFlag {
 }
 
 pub struct Parser<P: ParseErrorHandler> {
-    tokenizer: Tokenizer<P>,
+    tokenizer: TokenStream<P>,
     document: DomPtr<Document>,
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#original-insertion-mode>
@@ -152,14 +205,21 @@ pub struct Parser<P: ParseErrorHandler> {
     /// <https://html.spec.whatwg.org/multipage/parsing.html#scripting-flag>
     execute_script: bool,
 
+    /// Runs the scripts of the document, if it is displayed in a browsing context
+    scripts: Option<ScriptRunner>,
+
     /// <https://html.spec.whatwg.org/multipage/parsing.html#concept-pending-table-char-tokens>
     pending_table_character_tokens: Vec<char>,
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#foster-parent>
     is_foster_parenting_enabled: bool,
 
-    // Stylesheets that are asynchronously loaded during parsing
-    pending_stylesheets: Vec<(URL, PendingLoad)>,
+    /// Set after `<pre>`, `<listing>` and `<textarea>` start tags, whose leading newline
+    /// is ignored as an authoring convenience
+    ignore_next_line_feed: bool,
+
+    // Stylesheets that are asynchronously loaded during parsing, together with their integrity metadata
+    pending_stylesheets: Vec<(URL, String, PendingLoad)>,
 
     done: bool,
 
@@ -168,8 +228,13 @@ pub struct Parser<P: ParseErrorHandler> {
 
 impl<P: ParseErrorHandler> Parser<P> {
     pub fn new(source: &str, document: DomPtr<Document>) -> Self {
+        let mut stylesheets = vec![Stylesheet::user_agent_rules()];
+        if SETTINGS.preferences().force_dark_mode {
+            stylesheets.push(Stylesheet::forced_dark_user_agent_rules());
+        }
+
         Self {
-            tokenizer: Tokenizer::new(source),
+            tokenizer: TokenStream::new(source),
             document,
             original_insertion_mode: None,
             template_insertion_modes: vec![],
@@ -179,11 +244,13 @@ impl<P: ParseErrorHandler> Parser<P> {
             form: None,
             frameset_ok: FramesetOkFlag::default(),
             active_formatting_elements: ActiveFormattingElements::default(),
-            execute_script: !SETTINGS.disable_javascript,
+            execute_script: SETTINGS.javascript_enabled(),
+            scripts: None,
             pending_table_character_tokens: vec![],
             is_foster_parenting_enabled: false,
+            ignore_next_line_feed: false,
             done: false,
-            stylesheets: vec![Stylesheet::user_agent_rules()],
+            stylesheets,
             pending_stylesheets: vec![],
         }
     }
@@ -223,15 +290,24 @@ impl<P: ParseErrorHandler> Parser<P> {
     }
 
     fn finish_loading_stylesheets(&mut self) {
-        for (url, pending_stylesheet) in mem::take(&mut self.pending_stylesheets) {
+        for (url, integrity, pending_stylesheet) in mem::take(&mut self.pending_stylesheets) {
             match pending_stylesheet.block() {
                 Ok(resource) => {
+                    // FIXME: Integrity checks on cross-origin resources require a CORS request
+                    if !integrity::bytes_match_metadata(resource.data(), &integrity) {
+                        log::error!(
+                            "Failed to load stylesheet: None of the digests in the integrity attribute match {url}, the stylesheet was blocked"
+                        );
+                        continue;
+                    }
+
                     // FIXME: Check mime type here
                     let css = String::from_utf8_lossy(&resource.data());
                     let stylesheet = css::Parser::new(&css, css::Origin::Author)
                         .parse_stylesheet(self.stylesheets.len());
 
-                    if !stylesheet.rules().is_empty() {
+                    // Stylesheets without rules are still kept if they contain errors, so authors can find out why
+                    if !stylesheet.rules().is_empty() || !stylesheet.errors().is_empty() {
                         self.stylesheets.push(stylesheet);
                     } else {
                         log::debug!("Dropping empty stylesheet");
@@ -260,7 +336,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                         css::Parser::new(text_node.borrow().content(), css::Origin::Author)
                             .parse_stylesheet(self.stylesheets.len());
 
-                    if !stylesheet.rules().is_empty() {
+                    if !stylesheet.rules().is_empty() || !stylesheet.errors().is_empty() {
                         self.stylesheets.push(stylesheet);
                     } else {
                         log::debug!("Dropping empty stylesheet");
@@ -274,18 +350,44 @@ impl<P: ParseErrorHandler> Parser<P> {
             if link_element.relationship() == links::Relationship::Stylesheet {
                 if let Some(url) = link_element.url() {
                     let handle = RESOURCE_LOADER.schedule_load(url.clone());
-                    self.pending_stylesheets.push((url, handle));
+                    self.pending_stylesheets
+                        .push((url, link_element.integrity(), handle));
                 }
             }
+
+            resource_hints::process(&link_element);
         }
 
         element
     }
 
+    /// Run the scripts of the document while it is parsed
+    ///
+    /// Only documents that are displayed in a browsing context run scripts. In all other
+    /// documents (like sanitized fragments) scripts are inert.
+    #[must_use]
+    pub fn with_script_execution(mut self) -> Self {
+        if self.execute_script {
+            self.scripts = Some(ScriptRunner::default());
+        }
+        self
+    }
+
     pub fn parse(mut self) -> (DomPtr<Document>, Vec<Stylesheet>) {
+        let _span = trace::span!(Parse, "parse html");
+
         while let Some(token) = self.tokenizer.next() {
+            if mem::take(&mut self.ignore_next_line_feed) && matches!(token, Token::Character('\n'))
+            {
+                continue;
+            }
+
             self.consume(token);
 
+            if let Some(scripts) = &mut self.scripts {
+                scripts.execute_ready_async_scripts();
+            }
+
             if self.done {
                 break;
             }
@@ -311,8 +413,176 @@ impl<P: ParseErrorHandler> Parser<P> {
             .expect("Stack of template insertion modes cannot be empty")
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#adjusted-current-node>
+    #[must_use]
+    fn adjusted_current_node(&self) -> Option<DomPtr<Element>> {
+        // NOTE: The context element only exists in the fragment case, which is not implemented
+        self.open_elements_bottommost_node()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#tree-construction-dispatcher>
     fn consume(&mut self, token: Token) {
-        self.consume_in_mode(self.insertion_mode, token);
+        let is_in_html_content = match self.adjusted_current_node() {
+            None => true,
+            Some(node) => {
+                let is_start_tag = matches!(token, Token::StartTag(_));
+                let is_character = matches!(token, Token::Character(_));
+
+                node.borrow().namespace() == Namespace::HTML
+                    || (is_mathml_text_integration_point(&node)
+                        && (is_character
+                            || matches!(&token, Token::StartTag(tagdata)
+                                if tagdata.name != static_interned!("mglyph")
+                                    && tagdata.name != static_interned!("malignmark"))))
+                    || (is_mathml_element(&node, static_interned!("annotation-xml"))
+                        && matches!(&token, Token::StartTag(tagdata)
+                            if tagdata.name == static_interned!("svg")))
+                    || (is_html_integration_point(&node) && (is_start_tag || is_character))
+                    || matches!(token, Token::EOF)
+            },
+        };
+
+        if is_in_html_content {
+            self.consume_in_mode(self.insertion_mode, token);
+        } else {
+            self.consume_in_foreign_content(token);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inforeign>
+    fn consume_in_foreign_content(&mut self, token: Token) {
+        let breaks_out_of_foreign_content = match &token {
+            Token::StartTag(tagdata) => {
+                BREAKOUT_ELEMENTS.contains(&tagdata.name)
+                    || (tagdata.name == static_interned!("font")
+                        && [
+                            static_interned!("color"),
+                            static_interned!("face"),
+                            static_interned!("size"),
+                        ]
+                        .into_iter()
+                        .any(|attribute| tagdata.lookup_attribute(attribute).is_some()))
+            },
+            Token::EndTag(tagdata) => {
+                tagdata.name == static_interned!("br") || tagdata.name == static_interned!("p")
+            },
+            _ => false,
+        };
+
+        if breaks_out_of_foreign_content {
+            // Parse error.
+
+            // While the current node is not a MathML text integration point, an HTML integration point,
+            // or an element in the HTML namespace, pop elements from the stack of open elements.
+            loop {
+                let current_node = self.current_node();
+                if is_mathml_text_integration_point(&current_node)
+                    || is_html_integration_point(&current_node)
+                    || current_node.borrow().namespace() == Namespace::HTML
+                {
+                    break;
+                }
+                self.pop_from_open_elements();
+            }
+
+            // Reprocess the token according to the rules given in the section corresponding to the
+            // current insertion mode in HTML content.
+            self.consume_in_mode(self.insertion_mode, token);
+            return;
+        }
+
+        match token {
+            Token::Character('\0') => {
+                // Parse error. Insert a U+FFFD REPLACEMENT CHARACTER character.
+                self.insert_character('\u{FFFD}');
+            },
+            Token::Character(c @ (TAB | LINE_FEED | FORM_FEED | CARRIAGE_RETURN | WHITESPACE)) => {
+                // Insert the token's character.
+                self.insert_character(c);
+            },
+            Token::Character(c) => {
+                // Insert the token's character.
+                self.insert_character(c);
+
+                // Set the frameset-ok flag to "not ok".
+                self.frameset_ok = FramesetOkFlag::NotOk;
+            },
+            Token::Comment(data) => {
+                // Insert a comment.
+                self.insert_comment(data);
+            },
+            Token::DOCTYPE(_) => {}, // Parse error. Ignore the token.
+            Token::StartTag(mut tagdata) => {
+                let namespace = self.current_node().borrow().namespace();
+
+                // If the adjusted current node is an element in the MathML namespace, adjust MathML attributes for the token.
+                // (This fixes the case of MathML attributes that are not all lowercase.)
+                if namespace == Namespace::MathML {
+                    tagdata.adjust_mathml_attributes();
+                }
+
+                // FIXME: If the adjusted current node is an element in the SVG namespace, and the token's tag name is
+                //        one of the ones in the first column of the following table, change the tag name to the name
+                //        given in the corresponding cell in the second column.
+
+                // If the adjusted current node is an element in the SVG namespace, adjust SVG attributes for the token.
+                // (This fixes the case of SVG attributes that are not all lowercase.)
+                if namespace == Namespace::SVG {
+                    tagdata.adjust_svg_attributes();
+                }
+
+                // Adjust foreign attributes for the token. (This fixes the use of namespaced attributes, in particular XLink in SVG.)
+                tagdata.adjust_foreign_attributes();
+
+                // Insert a foreign element for the token, with adjusted current node's namespace and false.
+                self.insert_foreign_element(&tagdata, namespace, false);
+
+                // If the token has its self-closing flag set, then run the appropriate steps from the following list:
+                // FIXME: If the token's tag name is "script", and the new current node is in the SVG namespace,
+                //        then the script should be processed.
+                if tagdata.self_closing {
+                    // Otherwise: Pop the current node off the stack of open elements and acknowledge the token's self-closing flag.
+                    self.pop_from_open_elements();
+                    self.acknowledge_self_closing_flag_if_set(&tagdata);
+                }
+            },
+            Token::EndTag(tagdata) => {
+                // 1. Initialize node to be the current node (the bottommost node of the stack).
+                let mut index = self.open_elements.len() - 1;
+
+                // 2. If node's tag name, converted to ASCII lowercase, is not the same as the tag name of the token,
+                //    then this is a parse error.
+
+                loop {
+                    // 3. Loop: If node is the topmost element in the stack of open elements, then return. (fragment case)
+                    if index == 0 {
+                        return;
+                    }
+
+                    // 4. If node's tag name, converted to ASCII lowercase, is the same as the tag name of the token,
+                    //    pop elements from the stack of open elements until node has been popped from the stack, and then return.
+                    let node = self.open_elements[index].clone();
+                    if node.borrow().local_name().to_string().to_ascii_lowercase()
+                        == tagdata.name.to_string()
+                    {
+                        self.pop_from_open_elements_until(|element| element.ptr_eq(&node));
+                        return;
+                    }
+
+                    // 5. Set node to the previous entry in the stack of open elements.
+                    index -= 1;
+
+                    // 6. If node is not an element in the HTML namespace, return to the step labeled loop.
+                    if self.open_elements[index].borrow().namespace() == Namespace::HTML {
+                        // 7. Otherwise, process the token according to the rules given in the section corresponding
+                        //    to the current insertion mode in HTML content.
+                        self.consume_in_mode(self.insertion_mode, Token::EndTag(tagdata));
+                        return;
+                    }
+                }
+            },
+            Token::EOF => unreachable!("end-of-file tokens are always processed in HTML content"),
+        }
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#insert-a-character>
@@ -460,6 +730,25 @@ impl<P: ParseErrorHandler> Parser<P> {
         self.is_element_in_specific_scope(element_name, TABLE_SCOPE)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/parsing.html#has-an-element-in-select-scope>
+    fn is_element_in_select_scope(&self, element_name: InternedString) -> bool {
+        // The select scope consists of all element types except optgroup and option
+        for node in self.open_elements.iter().rev() {
+            let local_name = node.borrow().local_name();
+            if local_name == element_name {
+                return true;
+            }
+
+            if !matches!(
+                local_name,
+                static_interned!("optgroup") | static_interned!("option")
+            ) {
+                return false;
+            }
+        }
+        false
+    }
+
     fn elements_in_scope<'open_elements, 'scope, 'iterator>(
         &'open_elements self,
         scope: &'scope [InternedString],
@@ -540,7 +829,7 @@ impl<P: ParseErrorHandler> Parser<P> {
             let element_name = self.current_node().borrow().local_name();
 
             if !LOOP_WHILE_ELEMENTS.contains(&element_name)
-                || exclude.is_some_and(|e| e == element_name)
+                || exclude.as_ref().is_some_and(|e| *e == element_name)
             {
                 break;
             }
@@ -587,19 +876,43 @@ impl<P: ParseErrorHandler> Parser<P> {
 
         self.finish_loading_stylesheets();
 
-        // FIXME: I assume this must be done at some point, but i can't find it in the spec
-        let html_element = self
-            .open_elements
-            .first()
-            .expect("no root element found")
-            .clone()
-            .upcast();
-        Node::append_child(self.document.clone().upcast(), html_element);
+        // 3. Update the current document readiness to "interactive".
+        self.document
+            .borrow_mut()
+            .set_ready_state(DocumentReadyState::Interactive);
 
         // 4. Pop all the nodes off the stack of open elements.
         while !self.open_elements.is_empty() {
             self.pop_from_open_elements();
         }
+
+        // 5. While the list of scripts that will execute when the document has finished parsing is not empty,
+        //    execute the first one.
+        if let Some(scripts) = &mut self.scripts {
+            scripts.execute_deferred_scripts();
+
+            // User scripts run once the document was parsed, after the deferred scripts of the page
+            let url = self.document.borrow().url().clone();
+            for source_text in USER_CONTENT.scripts_for(&url) {
+                scripts.execute_user_script(source_text);
+            }
+        }
+
+        // 6. Queue a global task on the DOM manipulation task source given the Document's relevant global object
+        //    to run the following substeps:
+        //    ...
+        //    3. Fire an event named DOMContentLoaded at the Document object, with its bubbles attribute initialized to true.
+        // FIXME: Fire the event at the document once the DOM supports event listeners
+        log::debug!("DOMContentLoaded on document");
+
+        // 7. Spin the event loop until the set of scripts that will execute as soon as possible and the list of
+        //    scripts that will execute in order as soon as possible are empty.
+        if let Some(scripts) = &mut self.scripts {
+            scripts.execute_remaining_async_scripts();
+        }
+
+        // NOTE: The remaining steps fire the load event at the window, which is done by the
+        //       browsing context once the document is displayed
     }
 
     /// <https://html.spec.whatwg.org/multipage/parsing.html#reset-the-insertion-mode-appropriately>
@@ -640,6 +953,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                     // 6. If ancestor is a table node, switch the insertion mode to "in select in table" and return.
                     if ancestor_name == static_interned!("table") {
                         self.insertion_mode = InsertionMode::InSelectInTable;
+                        return;
                     }
 
                     // 7. Jump back to the step labeled loop.
@@ -760,13 +1074,14 @@ impl<P: ParseErrorHandler> Parser<P> {
             .expect("Intended Parent does not belong to a document");
 
         // Let local name be the tag name of the token.
-        let local_name = tagdata.name;
+        let local_name = tagdata.name.clone();
 
         // Let is be the value of the "is" attribute in the given token, if such an attribute exists, or null otherwise.
         let is = tagdata.lookup_attribute(static_interned!("is"));
 
         // Let definition be the result of looking up a custom element definition given document, given namespace, local name, and is.
-        let _definition = dom::lookup_custom_element_definition(namespace, local_name, is);
+        let _definition =
+            dom::lookup_custom_element_definition(namespace, local_name.clone(), is.clone());
 
         // FIXME: If definition is non-null and the parser was not created as part of the HTML fragment parsing algorithm, then let will execute script be true. Otherwise, let it be false.
 
@@ -788,7 +1103,9 @@ impl<P: ParseErrorHandler> Parser<P> {
 
         // Append each attribute in the given token to element.
         for (key, value) in tagdata.attributes() {
-            element.borrow_mut().append_attribute(*key, *value);
+            element
+                .borrow_mut()
+                .append_attribute(key.clone(), value.clone());
         }
 
         // FIXME: If will execute script is true, then:
@@ -963,7 +1280,7 @@ impl<P: ParseErrorHandler> Parser<P> {
     /// <https://html.spec.whatwg.org/multipage/parsing.html#adoption-agency-algorithm>
     fn run_adoption_agency_algorithm(&mut self, tagdata: &TagData) {
         // 1. Let subject be token's tag name.
-        let subject = tagdata.name;
+        let subject = tagdata.name.clone();
 
         // 2. If the current node is an HTML element whose tag name is subject, and the current node is not
         //    in the list of active formatting elements, then pop the current node off the stack of open elements and return.
@@ -1260,9 +1577,14 @@ impl<P: ParseErrorHandler> Parser<P> {
                     },
                     other => {
                         // Create an html element whose node document is the Document object.
-                        let mut html_element = HtmlHtmlElement::default();
-                        html_element.set_owning_document(DomPtr::clone(&self.document).downgrade());
-                        let new_element: DomPtr<Element> = DomPtr::new(html_element).upcast();
+                        let new_element = dom::create_element(
+                            DomPtr::clone(&self.document).downgrade(),
+                            static_interned!("html"),
+                            Namespace::HTML,
+                            None,
+                            None,
+                            false,
+                        );
 
                         // Append it to the Document object.
                         Node::append_child(
@@ -1429,7 +1751,11 @@ impl<P: ParseErrorHandler> Parser<P> {
                         );
 
                         // 3. Set the element's parser document to the Document, and set the element's force async to false.
-                        log::warn!("FIXME: Set script element attributes");
+                        element
+                            .try_into_type::<HtmlScriptElement>()
+                            .expect("element must be a script element")
+                            .borrow_mut()
+                            .set_parser_inserted();
 
                         // 4. If the parser was created as part of the HTML fragment parsing algorithm, then set the script element's already started to true. (fragment case)
 
@@ -1729,8 +2055,8 @@ impl<P: ParseErrorHandler> Parser<P> {
                                 html_element
                                     .borrow_mut()
                                     .attributes_mut()
-                                    .entry(*key)
-                                    .or_insert(*value);
+                                    .entry(key.clone())
+                                    .or_insert(value.clone());
                             }
                         }
                     },
@@ -1783,7 +2109,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                             let mut previous_body = previous_body.borrow_mut();
                             let attributes = previous_body.attributes_mut();
                             for (key, value) in tagdata.attributes() {
-                                attributes.entry(*key).or_insert(*value);
+                                attributes.entry(key.clone()).or_insert(value.clone());
                             }
                         }
                     },
@@ -1921,8 +2247,9 @@ impl<P: ParseErrorHandler> Parser<P> {
                         // Insert an HTML element for the token.
                         self.insert_html_element_for_token(&tagdata);
 
-                        // FIXME: If the next token is a U+000A LINE FEED (LF) character token, then ignore that token
+                        // If the next token is a U+000A LINE FEED (LF) character token, then ignore that token
                         // and move on to the next one. (Newlines at the start of pre blocks are ignored as an authoring convenience.)
+                        self.ignore_next_line_feed = true;
 
                         // Set the frameset-ok flag to "not ok".
                         self.frameset_ok = FramesetOkFlag::NotOk;
@@ -2099,7 +2426,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                     {
                         // If the stack of open elements does not have an element in scope that is an HTML element with
                         // the same tag name as that of the token, then this is a parse error; ignore the token.
-                        if self.is_element_in_scope(tagdata.name) {
+                        if self.is_element_in_scope(tagdata.name.clone()) {
                             return;
                         }
 
@@ -2199,13 +2526,13 @@ impl<P: ParseErrorHandler> Parser<P> {
                     {
                         // If the stack of open elements does not have an element in scope that is an HTML element with the
                         // same tag name as that of the token, then this is a parse error; ignore the token.
-                        if !self.is_element_in_scope(tagdata.name) {
+                        if !self.is_element_in_scope(tagdata.name.clone()) {
                             return;
                         }
 
                         // Otherwise, run these steps:
                         // 1. Generate implied end tags, except for HTML elements with the same tag name as the token.
-                        self.generate_implied_end_tags_excluding(Some(tagdata.name));
+                        self.generate_implied_end_tags_excluding(Some(tagdata.name.clone()));
 
                         // 2. If the current node is not an HTML element with the same tag name as that of the token, then this is a parse error.
 
@@ -2388,7 +2715,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                         // If the stack of open elements does not have an element in scope that is an HTML element
                         // with the same tag name as that of the token,
                         // then this is a parse error; ignore the token.
-                        if !self.is_element_in_scope(tagdata.name) {
+                        if !self.is_element_in_scope(tagdata.name.clone()) {
                             return;
                         }
 
@@ -2485,7 +2812,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                         // then: set the frameset-ok flag to "not ok".
                         if !tagdata.attributes.iter().any(|(key, value)| {
                             *key == static_interned!("type")
-                                && value.to_string().eq_ignore_ascii_case("hidden")
+                                && value.eq_ignore_ascii_case(&static_interned!("hidden"))
                         }) {
                             self.frameset_ok = FramesetOkFlag::NotOk;
                         }
@@ -2535,7 +2862,7 @@ impl<P: ParseErrorHandler> Parser<P> {
 
                         // 2. If the next token is a U+000A LINE FEED (LF) character token, then ignore that token and move on to the next one.
                         //    (Newlines at the start of textarea elements are ignored as an authoring convenience.)
-                        log::warn!("FIXME: ignore newlines at start of <textarea>");
+                        self.ignore_next_line_feed = true;
 
                         // 3. Switch the tokenizer to the RCDATA state.
                         self.tokenizer.switch_to(TokenizerState::RCDATA);
@@ -2722,7 +3049,12 @@ impl<P: ParseErrorHandler> Parser<P> {
                     Token::EOF => {
                         // Parse error.
 
-                        // FIXME: If the current node is a script element, then set its already started to true.
+                        // If the current node is a script element, then set its already started to true.
+                        if let Some(script) =
+                            self.current_node().try_into_type::<HtmlScriptElement>()
+                        {
+                            script.borrow_mut().set_already_started();
+                        }
 
                         // Pop the current node off the stack of open elements.
                         self.pop_from_open_elements();
@@ -2733,14 +3065,12 @@ impl<P: ParseErrorHandler> Parser<P> {
                         self.consume(token);
                     },
                     Token::EndTag(ref tagdata) if tagdata.name == static_interned!("script") => {
-                        log::warn!("FIXME: implement closing script tag in text mode");
-
                         // FIXME: If the active speculative HTML parser is null and the JavaScript execution context stack is empty,
                         // then perform a microtask checkpoint.
 
                         // Let script be the current node (which will be a script element).
-                        let script = self.current_node();
-                        script
+                        let script = self
+                            .current_node()
                             .try_into_type::<HtmlScriptElement>()
                             .expect("current node must be a script element");
 
@@ -2750,7 +3080,24 @@ impl<P: ParseErrorHandler> Parser<P> {
                         // Switch the insertion mode to the original insertion mode.
                         self.switch_back_to_original_insertion_mode();
 
-                        // FIXME: the rest of this method is concerned with scripting, which we don't support yet.
+                        // Let the old insertion point have the same value as the current insertion point.
+                        // Let the insertion point be just before the next input character.
+                        // Increment the parser's script nesting level by one.
+                        // NOTE: The insertion point only matters for document.write(), which is not supported
+
+                        // If the active speculative HTML parser is null, then prepare the script element script.
+                        // This might cause some script to execute, which might cause new characters to be inserted
+                        // into the tokenizer, and might cause the tokenizer to output more tokens, resulting in a
+                        // reentrant invocation of the parser.
+                        let Some(scripts) = &mut self.scripts else {
+                            return;
+                        };
+
+                        // If there is a pending parsing-blocking script, then block the tokenizer until it
+                        // was fetched and execute it.
+                        if let Some(pending_parsing_blocking_script) = scripts.prepare(&script) {
+                            scripts.execute(pending_parsing_blocking_script);
+                        }
                     },
                     Token::EndTag(_) => {
                         // Pop the current node off the stack of open elements.
@@ -3140,7 +3487,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                     {
                         // If the stack of open elements does not have an element in table scope that is an
                         // HTML element with the same tag name as the token, this is a parse error; ignore the token.
-                        if !self.is_element_in_table_scope(tag.name) {
+                        if !self.is_element_in_table_scope(tag.name.clone()) {
                             return;
                         }
 
@@ -3315,7 +3662,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                     {
                         // If the stack of open elements does not have an element in table scope that is an
                         // HTML element with the same tag name as the token, this is a parse error; ignore the token.
-                        if !self.is_element_in_table_scope(tag.name) {
+                        if !self.is_element_in_table_scope(tag.name.clone()) {
                             return;
                         }
 
@@ -3366,7 +3713,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                         // If the stack of open elements does not have an element in table scope that is an
                         // HTML element with the same tag name as that of the token,
                         // then this is a parse error; ignore the token.
-                        if !self.is_element_in_table_scope(tag.name) {
+                        if !self.is_element_in_table_scope(tag.name.clone()) {
                             return;
                         }
 
@@ -3437,7 +3784,7 @@ impl<P: ParseErrorHandler> Parser<P> {
                         // HTML element with the same tag name as that of the token,
                         // then this is a parse error; ignore the token.
                         // FIXME: only consider html elements
-                        if !self.is_element_in_table_scope(tag.name) {
+                        if !self.is_element_in_table_scope(tag.name.clone()) {
                             return;
                         }
 
@@ -3453,10 +3800,219 @@ impl<P: ParseErrorHandler> Parser<P> {
             },
 
             // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inselect
-            InsertionMode::InSelect => todo!("implement InSelect state"),
+            InsertionMode::InSelect => {
+                match token {
+                    Token::Character('\0') => {
+                        // Parse error. Ignore the token.
+                    },
+                    Token::Character(c) => {
+                        // Insert the token's character.
+                        self.insert_character(c);
+                    },
+                    Token::Comment(data) => {
+                        // Insert a comment.
+                        self.insert_comment(data);
+                    },
+                    Token::DOCTYPE(_) => {
+                        // Parse error. Ignore the token.
+                    },
+                    Token::StartTag(ref tag) if tag.name == static_interned!("html") => {
+                        // Process the token using the rules for the "in body" insertion mode.
+                        self.consume_in_mode(InsertionMode::InBody, token);
+                    },
+                    Token::StartTag(tagdata) if tagdata.name == static_interned!("option") => {
+                        // If the current node is an option element, pop that node from the stack of open elements.
+                        if self.current_node().borrow().local_name() == static_interned!("option") {
+                            self.pop_from_open_elements();
+                        }
+
+                        // Insert an HTML element for the token.
+                        self.insert_html_element_for_token(&tagdata);
+                    },
+                    Token::StartTag(tagdata) if tagdata.name == static_interned!("optgroup") => {
+                        // If the current node is an option element, pop that node from the stack of open elements.
+                        if self.current_node().borrow().local_name() == static_interned!("option") {
+                            self.pop_from_open_elements();
+                        }
+
+                        // If the current node is an optgroup element, pop that node from the stack of open elements.
+                        if self.current_node().borrow().local_name() == static_interned!("optgroup")
+                        {
+                            self.pop_from_open_elements();
+                        }
+
+                        // Insert an HTML element for the token.
+                        self.insert_html_element_for_token(&tagdata);
+                    },
+                    Token::EndTag(tag) if tag.name == static_interned!("optgroup") => {
+                        // First, if the current node is an option element, and the node immediately before it in
+                        // the stack of open elements is an optgroup element, then pop the current node from the
+                        // stack of open elements.
+                        let is_option_in_optgroup = self.current_node().borrow().local_name()
+                            == static_interned!("option")
+                            && self.open_elements.iter().rev().nth(1).is_some_and(|node| {
+                                node.borrow().local_name() == static_interned!("optgroup")
+                            });
+                        if is_option_in_optgroup {
+                            self.pop_from_open_elements();
+                        }
+
+                        // If the current node is an optgroup element, then pop that node from the stack of
+                        // open elements. Otherwise, this is a parse error; ignore the token.
+                        if self.current_node().borrow().local_name() == static_interned!("optgroup")
+                        {
+                            self.pop_from_open_elements();
+                        }
+                    },
+                    Token::EndTag(tag) if tag.name == static_interned!("option") => {
+                        // If the current node is an option element, then pop that node from the stack of
+                        // open elements. Otherwise, this is a parse error; ignore the token.
+                        if self.current_node().borrow().local_name() == static_interned!("option") {
+                            self.pop_from_open_elements();
+                        }
+                    },
+                    Token::EndTag(TagData {
+                        name: static_interned!("select"),
+                        ..
+                    })
+                    | Token::StartTag(TagData {
+                        name: static_interned!("select"),
+                        ..
+                    }) => {
+                        // NOTE: A select start tag is a parse error, but is otherwise treated like an end tag
+
+                        // If the stack of open elements does not have a select element in select scope,
+                        // this is a parse error; ignore the token. (fragment case)
+                        if !self.is_element_in_select_scope(static_interned!("select")) {
+                            return;
+                        }
+
+                        // Otherwise:
+                        // Pop elements from the stack of open elements until a select element has been popped from the stack.
+                        self.pop_from_open_elements_until(|e| {
+                            e.borrow().local_name() == static_interned!("select")
+                        });
+
+                        // Reset the insertion mode appropriately.
+                        self.reset_insertion_mode_appropriately();
+                    },
+                    Token::StartTag(ref tag)
+                        if matches!(
+                            tag.name,
+                            static_interned!("input")
+                                | static_interned!("keygen")
+                                | static_interned!("textarea")
+                        ) =>
+                    {
+                        // Parse error.
+
+                        // If the stack of open elements does not have a select element in select scope, ignore the token. (fragment case)
+                        if !self.is_element_in_select_scope(static_interned!("select")) {
+                            return;
+                        }
+
+                        // Pop elements from the stack of open elements until a select element has been popped from the stack.
+                        self.pop_from_open_elements_until(|e| {
+                            e.borrow().local_name() == static_interned!("select")
+                        });
+
+                        // Reset the insertion mode appropriately.
+                        self.reset_insertion_mode_appropriately();
+
+                        // Reprocess the token.
+                        self.consume(token);
+                    },
+                    Token::StartTag(ref tag)
+                        if matches!(
+                            tag.name,
+                            static_interned!("script") | static_interned!("template")
+                        ) =>
+                    {
+                        // Process the token using the rules for the "in head" insertion mode.
+                        self.consume_in_mode(InsertionMode::InHead, token);
+                    },
+                    Token::EndTag(ref tag) if tag.name == static_interned!("template") => {
+                        // Process the token using the rules for the "in head" insertion mode.
+                        self.consume_in_mode(InsertionMode::InHead, token);
+                    },
+                    Token::EOF => {
+                        // Process the token using the rules for the "in body" insertion mode.
+                        self.consume_in_mode(InsertionMode::InBody, token);
+                    },
+                    _ => {
+                        // Parse error. Ignore the token.
+                    },
+                }
+            },
 
             // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-inselectintable
-            InsertionMode::InSelectInTable => todo!("implement InSelectInTable mode"),
+            InsertionMode::InSelectInTable => {
+                match token {
+                    Token::StartTag(ref tag)
+                        if matches!(
+                            tag.name,
+                            static_interned!("caption")
+                                | static_interned!("table")
+                                | static_interned!("tbody")
+                                | static_interned!("tfoot")
+                                | static_interned!("thead")
+                                | static_interned!("tr")
+                                | static_interned!("td")
+                                | static_interned!("th")
+                        ) =>
+                    {
+                        // Parse error.
+
+                        // Pop elements from the stack of open elements until a select element has been popped from the stack.
+                        self.pop_from_open_elements_until(|e| {
+                            e.borrow().local_name() == static_interned!("select")
+                        });
+
+                        // Reset the insertion mode appropriately.
+                        self.reset_insertion_mode_appropriately();
+
+                        // Reprocess the token.
+                        self.consume(token);
+                    },
+                    Token::EndTag(ref tag)
+                        if matches!(
+                            tag.name,
+                            static_interned!("caption")
+                                | static_interned!("table")
+                                | static_interned!("tbody")
+                                | static_interned!("tfoot")
+                                | static_interned!("thead")
+                                | static_interned!("tr")
+                                | static_interned!("td")
+                                | static_interned!("th")
+                        ) =>
+                    {
+                        // Parse error.
+
+                        // If the stack of open elements does not have an element in table scope that is an
+                        // HTML element with the same tag name as that of the token, then ignore the token.
+                        if !self.is_element_in_table_scope(tag.name.clone()) {
+                            return;
+                        }
+
+                        // Otherwise:
+                        // Pop elements from the stack of open elements until a select element has been popped from the stack.
+                        self.pop_from_open_elements_until(|e| {
+                            e.borrow().local_name() == static_interned!("select")
+                        });
+
+                        // Reset the insertion mode appropriately.
+                        self.reset_insertion_mode_appropriately();
+
+                        // Reprocess the token.
+                        self.consume(token);
+                    },
+                    _ => {
+                        // Process the token using the rules for the "in select" insertion mode.
+                        self.consume_in_mode(InsertionMode::InSelect, token);
+                    },
+                }
+            },
 
             // https://html.spec.whatwg.org/multipage/parsing.html#parsing-main-intemplate
             InsertionMode::InTemplate => {
@@ -3813,3 +4369,80 @@ fn is_element_in_special_category(tagname: InternedString) -> bool {
             | static_interned!("desc")
     )
 }
+
+#[must_use]
+fn is_mathml_element(element: &DomPtr<Element>, local_name: InternedString) -> bool {
+    let element = element.borrow();
+    element.namespace() == Namespace::MathML && element.local_name() == local_name
+}
+
+/// <https://html.spec.whatwg.org/multipage/parsing.html#mathml-text-integration-point>
+#[must_use]
+fn is_mathml_text_integration_point(element: &DomPtr<Element>) -> bool {
+    [
+        static_interned!("mi"),
+        static_interned!("mo"),
+        static_interned!("mn"),
+        static_interned!("ms"),
+        static_interned!("mtext"),
+    ]
+    .into_iter()
+    .any(|local_name| is_mathml_element(element, local_name))
+}
+
+/// <https://html.spec.whatwg.org/multipage/parsing.html#html-integration-point>
+#[must_use]
+fn is_html_integration_point(element: &DomPtr<Element>) -> bool {
+    if is_mathml_element(element, static_interned!("annotation-xml")) {
+        let encoding = element
+            .borrow()
+            .attributes()
+            .get(&static_interned!("encoding"))
+            .map(ToString::to_string)
+            .unwrap_or_default();
+
+        return encoding.eq_ignore_ascii_case("text/html")
+            || encoding.eq_ignore_ascii_case("application/xhtml+xml");
+    }
+
+    // FIXME: SVG tag names are not adjusted yet, so "foreignObject" is never found here
+    let element = element.borrow();
+    element.namespace() == Namespace::SVG
+        && [
+            static_interned!("foreignObject"),
+            static_interned!("desc"),
+            static_interned!("title"),
+        ]
+        .contains(&element.local_name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::tokenization::IgnoreParseErrors;
+
+    #[test]
+    fn document_has_one_root_element() {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: Parser<IgnoreParseErrors> = Parser::new("<p>Hello world</p>", document);
+        let (document, _) = parser.parse();
+
+        let document: DomPtr<Node> = document.upcast();
+        let root_elements: Vec<DomPtr<Element>> = document
+            .borrow()
+            .children()
+            .iter()
+            .filter_map(|child| child.try_into_type::<Element>())
+            .collect();
+
+        assert_eq!(root_elements.len(), 1);
+        assert_eq!(
+            root_elements[0].borrow().local_name(),
+            static_interned!("html")
+        );
+    }
+}
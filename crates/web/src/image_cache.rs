@@ -0,0 +1,299 @@
+//! Decoded images that are shared between all elements and documents
+//!
+//! The encoded response of an image is kept around even after its decoded
+//! bitmap was evicted, so it can be decoded again without hitting the network.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use error_derive::Error;
+use image::Texture;
+use resourceloader::{Priority, Resource, ResourceLoadError, RESOURCE_LOADER};
+use sl_std::memory::{MemoryReport, MemoryReporter};
+use url::URL;
+
+/// The number of bytes that images may occupy before the least recently used ones are evicted
+const DEFAULT_BUDGET: usize = 256 * 1024 * 1024;
+
+pub(crate) static IMAGE_CACHE: LazyLock<ImageCache> =
+    LazyLock::new(|| ImageCache::new(DEFAULT_BUDGET));
+
+#[derive(Debug, Error)]
+pub(crate) enum ImageError {
+    #[msg = "failed to load image"]
+    Loading(ResourceLoadError),
+
+    #[msg = "resource is not an image"]
+    NotAnImage,
+
+    #[msg = "failed to decode image"]
+    Decoding,
+}
+
+/// Images that were loaded previously, evicted in least-recently-used order
+/// once they exceed a byte budget
+pub(crate) struct ImageCache {
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<URL, CacheEntry>,
+
+    /// The maximum number of bytes occupied by all entries
+    budget: usize,
+
+    /// Incremented on every access, used to find the least recently used entry
+    clock: u64,
+}
+
+struct CacheEntry {
+    /// The encoded image, as it was received from the network
+    response: Arc<Resource>,
+
+    /// `None` if the image was not decoded yet or its bitmap was evicted
+    decoded: Option<Arc<Texture>>,
+    last_used: u64,
+}
+
+impl CacheEntry {
+    fn encoded_size(&self) -> usize {
+        self.response.data().len()
+    }
+
+    fn decoded_size(&self) -> usize {
+        self.decoded
+            .as_ref()
+            .map_or(0, |texture| texture.size_in_bytes())
+    }
+}
+
+impl ImageCache {
+    #[must_use]
+    pub(crate) fn new(budget: usize) -> Self {
+        let state = CacheState {
+            budget,
+            ..Default::default()
+        };
+
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Get the decoded image at the given url, loading and decoding it if necessary
+    pub(crate) fn get(&self, url: &URL) -> Result<Arc<Texture>, ImageError> {
+        let cached_response = {
+            let mut state = self.lock();
+            state.clock += 1;
+            let now = state.clock;
+
+            match state.entries.get_mut(url) {
+                Some(entry) => {
+                    entry.last_used = now;
+                    if let Some(texture) = &entry.decoded {
+                        return Ok(texture.clone());
+                    }
+                    Some(entry.response.clone())
+                },
+                None => None,
+            }
+        };
+
+        // The lock is not held while the image is loaded and decoded, duplicate loads
+        // of the same url are merged by the resource loader
+        //
+        // FIXME: Images outside the viewport should be less important, but images are
+        //        loaded before their position is known
+        let response = match cached_response {
+            Some(response) => response,
+            None => RESOURCE_LOADER
+                .schedule_load_with_priority(url.clone(), Priority::VisibleImage)
+                .block()
+                .map_err(ImageError::Loading)?,
+        };
+
+        let mime_type = response
+            .mime_metadata()
+            .computed_mime_type_in(mime::SniffingContext::Image);
+        if !mime_type.as_ref().is_some_and(mime::MIMEType::is_image) {
+            log::error!(
+                "Expected {} to be an image, found {mime_type:?}",
+                url.serialize(url::ExcludeFragment::Yes)
+            );
+            return Err(ImageError::NotAnImage);
+        }
+
+        let texture = Texture::from_bytes(response.data()).map_err(|error| {
+            log::error!(
+                "Failed to decode {}: {error:?}",
+                url.serialize(url::ExcludeFragment::Yes)
+            );
+            ImageError::Decoding
+        })?;
+        let texture = Arc::new(texture);
+
+        let mut state = self.lock();
+        state.clock += 1;
+        let entry = CacheEntry {
+            response,
+            decoded: Some(texture.clone()),
+            last_used: state.clock,
+        };
+        state.entries.insert(url.clone(), entry);
+        state.evict(url);
+
+        Ok(texture)
+    }
+
+    /// Whether the given bitmap is the one that is cached for `url`
+    #[must_use]
+    pub(crate) fn contains(&self, url: &URL, texture: &Arc<Texture>) -> bool {
+        self.lock()
+            .entries
+            .get(url)
+            .and_then(|entry| entry.decoded.as_ref())
+            .is_some_and(|decoded| Arc::ptr_eq(decoded, texture))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state.lock().expect("image cache lock was poisoned")
+    }
+}
+
+impl CacheState {
+    fn size(&self) -> usize {
+        self.entries
+            .values()
+            .map(|entry| entry.encoded_size() + entry.decoded_size())
+            .sum()
+    }
+
+    /// Evict entries until the cache fits into its budget again
+    ///
+    /// Decoded bitmaps are dropped before encoded responses, because they are larger
+    /// and can be recreated without loading the image again. The entry at `keep` is
+    /// never evicted, since it is about to be used.
+    fn evict(&mut self, keep: &URL) {
+        let mut size = self.size();
+
+        while size > self.budget {
+            let least_recently_decoded = self
+                .entries
+                .iter_mut()
+                .filter(|(url, entry)| *url != keep && entry.decoded.is_some())
+                .min_by_key(|(_, entry)| entry.last_used);
+
+            if let Some((_, entry)) = least_recently_decoded {
+                size -= entry.decoded_size();
+                entry.decoded = None;
+                continue;
+            }
+
+            let least_recently_used = self
+                .entries
+                .iter()
+                .filter(|(url, _)| *url != keep)
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+
+            let Some(url) = least_recently_used else {
+                break;
+            };
+
+            if let Some(entry) = self.entries.remove(&url) {
+                size -= entry.encoded_size();
+            }
+        }
+    }
+}
+
+impl MemoryReporter for ImageCache {
+    fn report_memory(&self, report: &mut MemoryReport) {
+        let state = self.lock();
+
+        for entry in state.entries.values() {
+            report.record("images/decoded", entry.decoded_size());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use resourceloader::protocol::{self, ProtocolHandler};
+
+    use super::*;
+
+    /// Serves a black bitmap whose width is given by the path of the url
+    struct TestImages;
+
+    impl ProtocolHandler for TestImages {
+        fn load(&self, url: &URL) -> Result<Resource, ResourceLoadError> {
+            let width: u32 = url.path().as_str().parse().unwrap();
+            let mime_type = mime::MIMEType::new("image", "bmp");
+            Ok(Resource::new(bitmap(width, 1), Some(mime_type)))
+        }
+    }
+
+    /// An uncompressed 24-bit bitmap file
+    fn bitmap(width: u32, height: u32) -> Vec<u8> {
+        let row_size = (width * 3).next_multiple_of(4);
+        let pixel_data_size = row_size * height;
+
+        let mut bytes = b"BM".to_vec();
+        bytes.extend((54 + pixel_data_size).to_le_bytes());
+        bytes.extend([0; 4]);
+        bytes.extend(54_u32.to_le_bytes());
+
+        bytes.extend(40_u32.to_le_bytes());
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        bytes.extend(1_u16.to_le_bytes());
+        bytes.extend(24_u16.to_le_bytes());
+        bytes.extend(0_u32.to_le_bytes());
+        bytes.extend(pixel_data_size.to_le_bytes());
+        bytes.extend([0; 16]);
+
+        bytes.resize(bytes.len() + pixel_data_size as usize, 0);
+        bytes
+    }
+
+    fn image_url(width: usize) -> URL {
+        format!("stormlicht-image-test:{width}").parse().unwrap()
+    }
+
+    #[test]
+    fn share_decoded_images() {
+        protocol::register_protocol_handler("stormlicht-image-test", TestImages);
+
+        let cache = ImageCache::new(DEFAULT_BUDGET);
+        let first = cache.get(&image_url(2)).unwrap();
+        let second = cache.get(&image_url(2)).unwrap();
+
+        assert_eq!(first.width(), 2);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(cache.contains(&image_url(2), &first));
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        protocol::register_protocol_handler("stormlicht-image-test", TestImages);
+
+        // Enough space for both responses, but only for one of the decoded images
+        let cache = ImageCache::new(400);
+        let first = cache.get(&image_url(10)).unwrap();
+        let second = cache.get(&image_url(11)).unwrap();
+
+        // The bitmap of the first image was evicted, but the response is still cached
+        assert!(!cache.contains(&image_url(10), &first));
+        assert!(cache.contains(&image_url(11), &second));
+        assert!(cache.lock().entries.contains_key(&image_url(10)));
+
+        // Using the first image again decodes it again and evicts the second one
+        let first = cache.get(&image_url(10)).unwrap();
+        assert!(cache.contains(&image_url(10), &first));
+        assert!(!cache.contains(&image_url(11), &second));
+    }
+}
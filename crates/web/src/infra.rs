@@ -21,6 +21,27 @@ pub enum Namespace {
 
     /// <https://infra.spec.whatwg.org/#xmlns-namespace>
     XMLNS,
+
+    /// Any other namespace, including the null namespace
+    ///
+    /// Elements in these namespaces have no special meaning to the browser.
+    Other,
+}
+
+impl Namespace {
+    /// The namespace that is identified by `url`
+    #[must_use]
+    pub fn from_url(url: &str) -> Self {
+        match url {
+            "http://www.w3.org/1999/xhtml" => Self::HTML,
+            "http://www.w3.org/1998/Math/MathML" => Self::MathML,
+            "http://www.w3.org/2000/svg" => Self::SVG,
+            "http://www.w3.org/1999/xlink" => Self::XLink,
+            "http://www.w3.org/XML/1998/namespace" => Self::XML,
+            "http://www.w3.org/2000/xmlns/" => Self::XMLNS,
+            _ => Self::Other,
+        }
+    }
 }
 
 /// <https://infra.spec.whatwg.org/#normalize-newlines>
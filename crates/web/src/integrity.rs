@@ -0,0 +1,158 @@
+//! Subresource Integrity
+//!
+//! Documents can pin the exact contents of a script or stylesheet by listing
+//! digests of it in the `integrity` attribute. Resources that do not match any of
+//! the digests are not used.
+//!
+//! <https://w3c.github.io/webappsec-subresource-integrity/>
+
+use hash::{Hasher, Sha256, Sha384, Sha512};
+use sl_std::base64;
+
+/// The hash functions that can be used in integrity metadata
+///
+/// Variants are ordered by their strength.
+///
+/// <https://w3c.github.io/webappsec-subresource-integrity/#hash-functions>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Algorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Algorithm {
+    #[must_use]
+    fn from_token(token: &str) -> Option<Self> {
+        let algorithm = match token {
+            "sha256" => Self::Sha256,
+            "sha384" => Self::Sha384,
+            "sha512" => Self::Sha512,
+            _ => return None,
+        };
+
+        Some(algorithm)
+    }
+
+    /// Compute the base64-encoded digest of the given bytes
+    #[must_use]
+    fn digest(&self, bytes: &[u8]) -> String {
+        let mut hasher: Box<dyn Hasher> = match self {
+            Self::Sha256 => Box::<Sha256>::default(),
+            Self::Sha384 => Box::<Sha384>::default(),
+            Self::Sha512 => Box::<Sha512>::default(),
+        };
+        hasher.update(bytes);
+
+        base64::b64encode(&hasher.finalize()).to_string()
+    }
+}
+
+/// A single expected digest from an `integrity` attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Metadata {
+    pub algorithm: Algorithm,
+
+    /// The base64-encoded digest
+    pub value: String,
+}
+
+/// Parse the value of an `integrity` attribute
+///
+/// Items with unknown hash functions are ignored, as required by the spec. Options
+/// (everything after a `?`) are currently not used by any hash function and are dropped.
+///
+/// <https://w3c.github.io/webappsec-subresource-integrity/#parse-metadata>
+#[must_use]
+pub fn parse_metadata(metadata: &str) -> Vec<Metadata> {
+    metadata
+        .split_ascii_whitespace()
+        .filter_map(|item| {
+            let expression = item.split('?').next().unwrap_or_default();
+            let (algorithm, value) = expression.split_once('-')?;
+
+            let Some(algorithm) = Algorithm::from_token(&algorithm.to_ascii_lowercase()) else {
+                log::warn!("Ignoring integrity metadata with unknown hash function: {item:?}");
+                return None;
+            };
+
+            Some(Metadata {
+                algorithm,
+                value: value.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Check whether the bytes of a resource match the value of an `integrity` attribute
+///
+/// Only the digests that use the strongest hash function are taken into account.
+/// A resource matches if there are no usable digests at all.
+///
+/// <https://w3c.github.io/webappsec-subresource-integrity/#does-response-match-metadatalist>
+#[must_use]
+pub fn bytes_match_metadata(bytes: &[u8], metadata: &str) -> bool {
+    let metadata = parse_metadata(metadata);
+
+    // https://w3c.github.io/webappsec-subresource-integrity/#get-the-strongest-metadata
+    let Some(strongest_algorithm) = metadata.iter().map(|item| item.algorithm).max() else {
+        return true;
+    };
+
+    let actual_value = strongest_algorithm.digest(bytes);
+    metadata
+        .iter()
+        .filter(|item| item.algorithm == strongest_algorithm)
+        .any(|item| item.value == actual_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Digests of the string "alert('Hello, world.');", taken from the specification
+    const SHA256: &str = "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng=";
+    const SHA384: &str = "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO";
+
+    #[test]
+    fn parse() {
+        let metadata = parse_metadata(&format!("  md5-abc {SHA256}?foo\tSHA512-def  "));
+
+        assert_eq!(
+            metadata,
+            [
+                Metadata {
+                    algorithm: Algorithm::Sha256,
+                    value: "qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng=".to_string(),
+                },
+                Metadata {
+                    algorithm: Algorithm::Sha512,
+                    value: "def".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn verify() {
+        let script = b"alert('Hello, world.');";
+
+        assert!(bytes_match_metadata(script, SHA256));
+        assert!(bytes_match_metadata(script, SHA384));
+        assert!(!bytes_match_metadata(b"alert('Hacked!');", SHA384));
+
+        // Only the strongest hash function is used
+        assert!(bytes_match_metadata(
+            script,
+            &format!("sha256-bogus {SHA384}")
+        ));
+        assert!(!bytes_match_metadata(
+            script,
+            &format!("{SHA256} sha384-bogus")
+        ));
+
+        // Metadata without known hash functions does not restrict anything
+        assert!(bytes_match_metadata(script, ""));
+        assert!(bytes_match_metadata(script, "md5-xyz"));
+    }
+}
@@ -2,18 +2,17 @@
 // #![feature(const_trait_impl, const_for, const_mut_refs, array_chunks)]
 
 use std::{
-    collections::HashMap,
-    fmt,
-    sync::{LazyLock, Mutex},
+    borrow::Borrow,
+    collections::HashSet,
+    fmt, hash,
+    sync::{Arc, LazyLock, Mutex},
 };
 
-use perfect_hash::str_hash;
-
 mod autogenerated_code {
     include!(concat!(env!("OUT_DIR"), "/identifiers.rs"));
 }
 pub(crate) use autogenerated_code::static_str;
-use autogenerated_code::STATIC_SET;
+use autogenerated_code::{ascii_lowercase, STATIC_SET};
 
 #[macro_export]
 macro_rules! static_interned {
@@ -22,33 +21,59 @@ macro_rules! static_interned {
     };
 }
 
+/// The interner is swept once it holds this many atoms, even if it never grew
+/// to twice its size after the last sweep
+const MIN_ATOMS_BEFORE_SWEEP: usize = 1024;
+
 static INTERNER: LazyLock<Mutex<StringInterner>> =
     LazyLock::new(|| Mutex::new(StringInterner::new()));
 
-/// Like a [String] that is `Copy` and supports comparison in `O(1)`.
+/// Like a [String] that is cheap to clone and supports comparison in `O(1)`.
 ///
-/// [InternedStrings](InternedString) hold references into an [StringInterner],
-/// which actually stores the strings.
+/// [InternedStrings](InternedString) either refer to the static set of identifiers
+/// that is generated at build time or to a reference-counted atom that is shared
+/// with all other strings of the same value.
 /// This has a few implications:
 /// * [InternedStrings](InternedString) are immutable
-/// * No deallocation (for now)
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// * Atoms are freed during the next [sweep](InternedString::collect_garbage) after
+///   their last reference was dropped
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub enum InternedString {
     Static(u32),
-    Dynamic(u32),
+    Dynamic(Atom),
+}
+
+/// A string that is not part of the static set
+///
+/// There is only ever one atom for every value, so atoms are compared by address.
+#[derive(Clone)]
+pub struct Atom(Arc<AtomData>);
+
+struct AtomData {
+    value: Box<str>,
+
+    /// The ASCII lowercase version of the value, `None` if the value is lowercase already
+    lowercase: Option<InternedString>,
 }
 
 // https://github.com/servo/servo/issues/2217
 #[derive(Debug)]
 pub struct StringInterner {
-    internal_map: HashMap<String, u32>,
+    atoms: HashSet<InternerEntry>,
+
+    /// The number of atoms that survived the last sweep
+    atoms_after_last_sweep: usize,
 }
 
+/// An atom owned by the interner, which is looked up by value instead of by address
+struct InternerEntry(Atom);
+
 impl StringInterner {
     #[must_use]
     fn new() -> Self {
         Self {
-            internal_map: HashMap::new(),
+            atoms: HashSet::new(),
+            atoms_after_last_sweep: 0,
         }
     }
 
@@ -57,23 +82,90 @@ impl StringInterner {
             return InternedString::Static(symbol);
         }
 
-        let symbol = self.internal_map.get(&value).copied().unwrap_or_else(|| {
-            let hash = str_hash(&value);
-            self.internal_map.insert(value, hash);
-            hash
-        });
+        if let Some(entry) = self.atoms.get(value.as_str()) {
+            return InternedString::Dynamic(entry.0.clone());
+        }
 
-        InternedString::Dynamic(symbol)
+        if self.atoms.len() >= MIN_ATOMS_BEFORE_SWEEP.max(2 * self.atoms_after_last_sweep) {
+            self.sweep();
+        }
+
+        let lowercase = value
+            .bytes()
+            .any(|byte| byte.is_ascii_uppercase())
+            .then(|| self.get_or_insert(value.to_ascii_lowercase()));
+
+        let atom = Atom(Arc::new(AtomData {
+            value: value.into_boxed_str(),
+            lowercase,
+        }));
+        self.atoms.insert(InternerEntry(atom.clone()));
+
+        InternedString::Dynamic(atom)
+    }
+
+    /// Free all atoms that are only referenced by the interner itself
+    ///
+    /// Returns the number of atoms that were freed.
+    fn sweep(&mut self) -> usize {
+        let atoms_before_sweep = self.atoms.len();
+
+        // Freeing an atom can drop the last reference to its lowercase version,
+        // so keep going until nothing changes
+        loop {
+            let num_atoms = self.atoms.len();
+            self.atoms
+                .retain(|entry| Arc::strong_count(&(entry.0).0) > 1);
+
+            if self.atoms.len() == num_atoms {
+                break;
+            }
+        }
+
+        self.atoms_after_last_sweep = self.atoms.len();
+        atoms_before_sweep - self.atoms.len()
     }
 }
 
 impl InternedString {
     pub fn new(from: String) -> Self {
-        INTERNER
-            .lock()
-            .expect("String interner was poisoned")
-            .get_or_insert(from)
+        lock_interner().get_or_insert(from)
+    }
+
+    /// Free the memory of all strings that are no longer in use
+    ///
+    /// This also happens automatically from time to time as new strings are interned.
+    /// Returns the number of strings that were freed.
+    pub fn collect_garbage() -> usize {
+        lock_interner().sweep()
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Static(symbol) => STATIC_SET.lookup(*symbol),
+            Self::Dynamic(atom) => &atom.0.value,
+        }
+    }
+
+    /// Convert the string to ASCII lowercase in `O(1)`
+    #[must_use]
+    pub fn to_ascii_lowercase(&self) -> Self {
+        match self {
+            Self::Static(symbol) => Self::Static(ascii_lowercase(*symbol)),
+            Self::Dynamic(atom) => atom.0.lowercase.clone().unwrap_or_else(|| self.clone()),
+        }
     }
+
+    /// Compare two strings, ignoring the case of ASCII characters, in `O(1)`
+    #[must_use]
+    pub fn eq_ignore_ascii_case(&self, other: &Self) -> bool {
+        self == other || self.to_ascii_lowercase() == other.to_ascii_lowercase()
+    }
+}
+
+fn lock_interner() -> std::sync::MutexGuard<'static, StringInterner> {
+    INTERNER.lock().expect("String interner was poisoned")
 }
 
 impl Default for InternedString {
@@ -85,48 +177,15 @@ impl Default for InternedString {
 impl fmt::Debug for InternedString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            InternedString::Static(symbol) => {
-                write!(f, "{:?}_s", STATIC_SET.lookup(*symbol))
-            },
-            InternedString::Dynamic(symbol) => {
-                let map = &INTERNER
-                    .lock()
-                    .expect("String interner was poisoned")
-                    .internal_map;
-
-                let string = map
-                    .iter()
-                    .find(|(_, &v)| v == *symbol)
-                    .expect("InternedString not present in Interner")
-                    .0;
-
-                write!(f, "{string:?}_d")
-            },
+            InternedString::Static(_) => write!(f, "{:?}_s", self.as_str()),
+            InternedString::Dynamic(_) => write!(f, "{:?}_d", self.as_str()),
         }
     }
 }
 
 impl fmt::Display for InternedString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            InternedString::Static(symbol) => {
-                write!(f, "{}", STATIC_SET.lookup(*symbol))
-            },
-            InternedString::Dynamic(symbol) => {
-                let map = &INTERNER
-                    .lock()
-                    .expect("String interner was poisoned")
-                    .internal_map;
-
-                let string = map
-                    .iter()
-                    .find(|(_, &v)| v == *symbol)
-                    .expect("InternedString not present in Interner")
-                    .0;
-
-                write!(f, "{string}")
-            },
-        }
+        f.write_str(self.as_str())
     }
 }
 
@@ -142,6 +201,52 @@ impl From<&str> for InternedString {
     }
 }
 
+impl PartialEq for Atom {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Atom {}
+
+impl hash::Hash for Atom {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+impl fmt::Debug for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.value.fmt(f)
+    }
+}
+
+impl PartialEq for InternerEntry {
+    fn eq(&self, other: &Self) -> bool {
+        (self.0).0.value == (other.0).0.value
+    }
+}
+
+impl Eq for InternerEntry {}
+
+impl hash::Hash for InternerEntry {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        (self.0).0.value.hash(state);
+    }
+}
+
+impl Borrow<str> for InternerEntry {
+    fn borrow(&self) -> &str {
+        &(self.0).0.value
+    }
+}
+
+impl fmt::Debug for InternerEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::InternedString;
@@ -154,4 +259,42 @@ mod tests {
         let backtrace = InternedString::new("Bar".to_string());
         assert_eq!(&backtrace.to_string(), "Bar");
     }
+
+    #[test]
+    fn ignore_ascii_case() {
+        // Static strings with mixed case
+        assert!(static_interned!("viewBox").eq_ignore_ascii_case(&InternedString::from("VIEWBOX")));
+        assert_eq!(
+            static_interned!("viewBox").to_ascii_lowercase(),
+            static_interned!("viewbox")
+        );
+
+        // Dynamic strings
+        let upper = InternedString::from("SOME-UNKNOWN-TAG");
+        let mixed = InternedString::from("Some-Unknown-Tag");
+        assert!(upper.eq_ignore_ascii_case(&mixed));
+        assert!(!upper.eq_ignore_ascii_case(&InternedString::from("some-other-tag")));
+
+        // Dynamic strings whose lowercase version is static
+        assert!(InternedString::from("DIV").eq_ignore_ascii_case(&static_interned!("div")));
+    }
+
+    #[test]
+    fn free_unused_strings() {
+        let used = InternedString::from("A-string-that-is-still-used");
+        let value = "A-string-that-is-no-longer-used";
+        let unused = InternedString::from(value);
+        let InternedString::Dynamic(atom) = &unused else {
+            panic!("expected a dynamic string");
+        };
+        let weak = std::sync::Arc::downgrade(&atom.0);
+        drop(unused);
+
+        InternedString::collect_garbage();
+        assert!(weak.upgrade().is_none());
+        assert_eq!(used.as_str(), "A-string-that-is-still-used");
+
+        // Interning the same value again creates a new atom
+        assert_eq!(InternedString::from(value).as_str(), value);
+    }
 }
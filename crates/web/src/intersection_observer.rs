@@ -0,0 +1,454 @@
+//! Observe when elements enter or leave the viewport
+//!
+//! Observations are updated once per rendering update, after the page was laid out.
+//! Changes are queued as [IntersectionObserverEntry] records.
+//!
+//! Observers have no callback, the queued records are taken with
+//! [IntersectionObserver::take_records] instead.
+//!
+//! <https://w3c.github.io/IntersectionObserver/>
+
+use error_derive::Error;
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::layout::{Pixels, Size},
+    dom::{dom_objects, DomPtr},
+    geometry::DomRect,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum IntersectionObserverError {
+    /// Corresponds to a `SyntaxError` `DOMException`
+    #[msg = "invalid root margin"]
+    InvalidRootMargin,
+
+    /// Corresponds to a `RangeError`
+    #[msg = "thresholds must be between 0 and 1"]
+    ThresholdOutOfRange,
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#dictdef-intersectionobserverinit>
+#[derive(Clone, Debug)]
+pub struct IntersectionObserverInit {
+    /// Grows (or shrinks) the viewport before computing intersections, like `"10px 20%"`
+    pub root_margin: String,
+
+    /// The intersection ratios at which a record is queued
+    pub threshold: Vec<f64>,
+}
+
+impl Default for IntersectionObserverInit {
+    fn default() -> Self {
+        Self {
+            root_margin: "0px".to_string(),
+            threshold: vec![0.],
+        }
+    }
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#intersectionobserverentry>
+#[derive(Clone)]
+pub struct IntersectionObserverEntry {
+    /// The time (in milliseconds since the page was displayed) at which the change happened
+    pub time: f64,
+
+    pub root_bounds: DomRect,
+    pub bounding_client_rect: DomRect,
+    pub intersection_rect: DomRect,
+    pub is_intersecting: bool,
+    pub intersection_ratio: f64,
+    pub target: DomPtr<dom_objects::Element>,
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#intersectionobserver-root-margin>
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Margin {
+    Pixels(f32),
+    Percentage(f32),
+}
+
+impl Margin {
+    fn resolve(&self, reference: Pixels) -> Pixels {
+        match self {
+            Self::Pixels(pixels) => Pixels(*pixels),
+            Self::Percentage(percentage) => reference * (percentage / 100.),
+        }
+    }
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#intersectionobserverregistration>
+#[derive(Clone)]
+struct Registration {
+    target: DomPtr<dom_objects::Element>,
+
+    /// `None` before the first observation
+    previous_threshold_index: Option<usize>,
+    previous_is_intersecting: bool,
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#intersectionobserver>
+#[derive(Clone)]
+pub struct IntersectionObserver {
+    /// In the order top, right, bottom, left
+    root_margin: [Margin; 4],
+
+    /// Sorted in ascending order
+    thresholds: Vec<f64>,
+    registrations: Vec<Registration>,
+    queued_entries: Vec<IntersectionObserverEntry>,
+}
+
+impl IntersectionObserver {
+    /// Create an observer whose root is the viewport
+    ///
+    /// <https://w3c.github.io/IntersectionObserver/#initialize-new-intersection-observer>
+    pub fn new(options: IntersectionObserverInit) -> Result<Self, IntersectionObserverError> {
+        // FIXME: Support elements as the root of the observer
+
+        // 3. Attempt to parse a margin from options.rootMargin.
+        //    If a list is returned, set this’s internal [[rootMargin]] slot to that.
+        //    Otherwise, throw a SyntaxError exception.
+        let root_margin = parse_root_margin(&options.root_margin)?;
+
+        // 4. Let thresholds be a list equal to options.threshold.
+        let mut thresholds = options.threshold;
+
+        // 5. If any value in thresholds is less than 0.0 or greater than 1.0, throw a RangeError exception.
+        if thresholds
+            .iter()
+            .any(|threshold| !(0.0..=1.0).contains(threshold))
+        {
+            return Err(IntersectionObserverError::ThresholdOutOfRange);
+        }
+
+        // 6. Sort thresholds in ascending order.
+        thresholds.sort_by(f64::total_cmp);
+
+        // 7. If thresholds is empty, append 0 to thresholds.
+        if thresholds.is_empty() {
+            thresholds.push(0.);
+        }
+
+        Ok(Self {
+            root_margin,
+            thresholds,
+            registrations: vec![],
+            queued_entries: vec![],
+        })
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-rootmargin>
+    #[must_use]
+    pub fn root_margin(&self) -> String {
+        self.root_margin
+            .iter()
+            .map(|margin| match margin {
+                Margin::Pixels(pixels) => format!("{pixels}px"),
+                Margin::Percentage(percentage) => format!("{percentage}%"),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-thresholds>
+    #[must_use]
+    pub fn thresholds(&self) -> &[f64] {
+        &self.thresholds
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-observe>
+    pub fn observe(&mut self, target: DomPtr<dom_objects::Element>) {
+        // 1. If target is in observer’s internal [[ObservationTargets]] slot, return.
+        if self
+            .registrations
+            .iter()
+            .any(|registration| registration.target.ptr_eq(&target))
+        {
+            return;
+        }
+
+        // 2. Let intersectionObserverRegistration be an IntersectionObserverRegistration record with an
+        //    observer property set to observer, a previousThresholdIndex property set to -1,
+        //    and a previousIsIntersecting property set to false.
+        self.registrations.push(Registration {
+            target,
+            previous_threshold_index: None,
+            previous_is_intersecting: false,
+        });
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-unobserve>
+    pub fn unobserve(&mut self, target: &DomPtr<dom_objects::Element>) {
+        self.registrations
+            .retain(|registration| !registration.target.ptr_eq(target));
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-disconnect>
+    pub fn disconnect(&mut self) {
+        self.registrations.clear();
+    }
+
+    /// <https://w3c.github.io/IntersectionObserver/#dom-intersectionobserver-takerecords>
+    pub fn take_records(&mut self) -> Vec<IntersectionObserverEntry> {
+        std::mem::take(&mut self.queued_entries)
+    }
+
+    /// The area of the viewport, grown by the root margin
+    ///
+    /// <https://w3c.github.io/IntersectionObserver/#intersectionobserver-root-intersection-rectangle>
+    fn root_intersection_rectangle(&self, viewport: Size<Pixels>) -> Rectangle<Pixels> {
+        let [top, right, bottom, left] = self.root_margin;
+
+        Rectangle::from_corners(
+            Vec2D::new(
+                Pixels::ZERO - left.resolve(viewport.width),
+                Pixels::ZERO - top.resolve(viewport.height),
+            ),
+            Vec2D::new(
+                viewport.width + right.resolve(viewport.width),
+                viewport.height + bottom.resolve(viewport.height),
+            ),
+        )
+    }
+
+    /// Queue an entry for every target whose intersection with the viewport crossed a threshold
+    ///
+    /// `bounding_client_rect` returns the area of a target relative to the viewport, or `None` if the
+    /// target did not generate any boxes.
+    ///
+    /// <https://w3c.github.io/IntersectionObserver/#update-intersection-observations-algo>
+    pub(crate) fn update_observations<F>(
+        &mut self,
+        viewport: Size<Pixels>,
+        time: f64,
+        mut bounding_client_rect: F,
+    ) where
+        F: FnMut(&DomPtr<dom_objects::Element>) -> Option<DomRect>,
+    {
+        let root_bounds = self.root_intersection_rectangle(viewport);
+
+        for registration in &mut self.registrations {
+            let target_rect = bounding_client_rect(&registration.target);
+            let intersection = compute_intersection(target_rect, root_bounds);
+
+            // Let thresholdIndex be the index of the first entry in observer.thresholds whose value
+            // is greater than intersectionRatio, or the length of observer.thresholds if
+            // intersectionRatio is greater than or equal to the last entry in observer.thresholds.
+            let threshold_index = if intersection.is_intersecting {
+                self.thresholds
+                    .iter()
+                    .position(|&threshold| threshold > intersection.ratio)
+                    .unwrap_or(self.thresholds.len())
+            } else {
+                0
+            };
+
+            // If thresholdIndex does not equal previousThresholdIndex or isIntersecting does not
+            // equal previousIsIntersecting, queue an IntersectionObserverEntry
+            if registration.previous_threshold_index == Some(threshold_index)
+                && registration.previous_is_intersecting == intersection.is_intersecting
+            {
+                continue;
+            }
+
+            self.queued_entries.push(IntersectionObserverEntry {
+                time,
+                root_bounds: DomRect::from_rectangle(root_bounds),
+                bounding_client_rect: target_rect.unwrap_or_default(),
+                intersection_rect: intersection.rect,
+                is_intersecting: intersection.is_intersecting,
+                intersection_ratio: intersection.ratio,
+                target: registration.target.clone(),
+            });
+
+            registration.previous_threshold_index = Some(threshold_index);
+            registration.previous_is_intersecting = intersection.is_intersecting;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Intersection {
+    rect: DomRect,
+    is_intersecting: bool,
+    ratio: f64,
+}
+
+/// <https://w3c.github.io/IntersectionObserver/#calculate-intersection-rect-algo>
+fn compute_intersection(
+    target_rect: Option<DomRect>,
+    root_bounds: Rectangle<Pixels>,
+) -> Intersection {
+    // Targets without boxes never intersect
+    let Some(target_rect) = target_rect else {
+        return Intersection {
+            rect: DomRect::default(),
+            is_intersecting: false,
+            ratio: 0.,
+        };
+    };
+    let target = target_rect.to_rectangle();
+
+    // Targets that touch the edge of the root are intersecting, even if they have no area.
+    let top_left = Vec2D::new(
+        target.top_left().x.max(root_bounds.top_left().x),
+        target.top_left().y.max(root_bounds.top_left().y),
+    );
+    let bottom_right = Vec2D::new(
+        target.bottom_right().x.min(root_bounds.bottom_right().x),
+        target.bottom_right().y.min(root_bounds.bottom_right().y),
+    );
+    let is_intersecting = top_left.x <= bottom_right.x && top_left.y <= bottom_right.y;
+    if !is_intersecting {
+        return Intersection {
+            rect: DomRect::default(),
+            is_intersecting: false,
+            ratio: 0.,
+        };
+    }
+
+    let rect = DomRect::from_rectangle(Rectangle::from_corners(top_left, bottom_right));
+
+    // If targetArea is non-zero, set intersectionRatio to intersectionArea divided by targetArea.
+    // Otherwise, set intersectionRatio to 1 if isIntersecting is true, or 0 if isIntersecting is false.
+    let target_area = target_rect.area();
+    let ratio = if target_area == 0. {
+        1.
+    } else {
+        f64::from(rect.area() / target_area)
+    };
+
+    Intersection {
+        rect,
+        is_intersecting,
+        ratio,
+    }
+}
+
+/// Parse one to four margins, which are either pixel lengths or percentages
+///
+/// <https://w3c.github.io/IntersectionObserver/#parse-a-margin>
+fn parse_root_margin(input: &str) -> Result<[Margin; 4], IntersectionObserverError> {
+    let margins = input
+        .split_ascii_whitespace()
+        .map(|margin| {
+            let (value, is_percentage) = if let Some(value) = margin.strip_suffix('%') {
+                (value, true)
+            } else if let Some(value) = margin.strip_suffix("px") {
+                (value, false)
+            } else if margin == "0" {
+                (margin, false)
+            } else {
+                return Err(IntersectionObserverError::InvalidRootMargin);
+            };
+
+            let value: f32 = value
+                .parse()
+                .map_err(|_| IntersectionObserverError::InvalidRootMargin)?;
+
+            if is_percentage {
+                Ok(Margin::Percentage(value))
+            } else {
+                Ok(Margin::Pixels(value))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Missing margins are taken from the opposite side, like with the css margin shorthand
+    let margins = match margins[..] {
+        [] => [Margin::Pixels(0.); 4],
+        [all] => [all; 4],
+        [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+        [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+        [top, right, bottom, left] => [top, right, bottom, left],
+        _ => return Err(IntersectionObserverError::InvalidRootMargin),
+    };
+
+    Ok(margins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: Size<Pixels> = Size {
+        width: Pixels(800.),
+        height: Pixels(600.),
+    };
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> DomRect {
+        DomRect {
+            x: Pixels(x),
+            y: Pixels(y),
+            width: Pixels(width),
+            height: Pixels(height),
+        }
+    }
+
+    #[test]
+    fn parse_options() {
+        let observer = IntersectionObserver::new(IntersectionObserverInit {
+            root_margin: "10px 5%".to_string(),
+            threshold: vec![1., 0.5],
+        })
+        .unwrap();
+        assert_eq!(observer.root_margin(), "10px 5% 10px 5%");
+        assert_eq!(observer.thresholds(), [0.5, 1.]);
+
+        let invalid_margin = IntersectionObserverInit {
+            root_margin: "10em".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            IntersectionObserver::new(invalid_margin).err(),
+            Some(IntersectionObserverError::InvalidRootMargin)
+        );
+
+        let invalid_threshold = IntersectionObserverInit {
+            threshold: vec![1.5],
+            ..Default::default()
+        };
+        assert_eq!(
+            IntersectionObserver::new(invalid_threshold).err(),
+            Some(IntersectionObserverError::ThresholdOutOfRange)
+        );
+    }
+
+    #[test]
+    fn intersection_ratio() {
+        let root = Rectangle::from_corners(
+            Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+            Vec2D::new(VIEWPORT.width, VIEWPORT.height),
+        );
+
+        // Half of the target is below the viewport
+        let intersection = compute_intersection(Some(rect(0., 500., 100., 200.)), root);
+        assert!(intersection.is_intersecting);
+        assert_eq!(intersection.ratio, 0.5);
+        assert_eq!(intersection.rect, rect(0., 500., 100., 100.));
+
+        // Touching the edge counts as intersecting
+        let intersection = compute_intersection(Some(rect(0., 600., 100., 200.)), root);
+        assert!(intersection.is_intersecting);
+        assert_eq!(intersection.ratio, 0.);
+
+        let intersection = compute_intersection(Some(rect(0., 700., 100., 200.)), root);
+        assert!(!intersection.is_intersecting);
+
+        assert!(!compute_intersection(None, root).is_intersecting);
+    }
+
+    #[test]
+    fn root_margin_grows_viewport() {
+        let observer = IntersectionObserver::new(IntersectionObserverInit {
+            root_margin: "50% 0px".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let root = observer.root_intersection_rectangle(VIEWPORT);
+        assert_eq!(root.top_left(), Vec2D::new(Pixels::ZERO, Pixels(-300.)));
+        assert_eq!(root.bottom_right(), Vec2D::new(Pixels(800.), Pixels(900.)));
+    }
+}
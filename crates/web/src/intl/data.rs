@@ -0,0 +1,283 @@
+//! Formatting data for the bundled locales, taken from the [CLDR](https://cldr.unicode.org/)
+//!
+//! Date patterns use the [LDML syntax](https://unicode.org/reports/tr35/tr35-dates.html#Date_Field_Symbol_Table),
+//! see [DateTimeFormat](super::DateTimeFormat) for the supported fields.
+
+#[derive(Debug)]
+pub(super) struct LocaleData {
+    pub language: &'static str,
+    pub region: &'static str,
+
+    pub decimal_separator: &'static str,
+    pub group_separator: &'static str,
+
+    /// Numbers need at least this many digits in front of the first group to be grouped,
+    /// so `1234` is not grouped in spanish but `12345` is
+    pub minimum_grouping_digits: usize,
+
+    /// Appended to percentages, including any space in front of the percent sign
+    pub percent_suffix: &'static str,
+
+    pub months: [&'static str; 12],
+    pub short_months: [&'static str; 12],
+
+    /// Starting with sunday
+    pub weekdays: [&'static str; 7],
+    pub short_weekdays: [&'static str; 7],
+
+    /// The names for the hours before and after noon
+    pub day_periods: [&'static str; 2],
+
+    /// The date pattern when no style is given, which shows the numeric year, month and day
+    pub numeric_date: &'static str,
+
+    /// Date patterns for the short, medium, long and full style
+    pub date_patterns: [&'static str; 4],
+
+    /// Time patterns without and with seconds
+    pub time_patterns: [&'static str; 2],
+
+    /// Placed between the date and the time if both are shown
+    pub date_time_separator: &'static str,
+}
+
+impl LocaleData {
+    #[must_use]
+    pub fn tag(&self) -> String {
+        format!("{}-{}", self.language, self.region)
+    }
+}
+
+/// The first locale is used if no other locale matches
+pub(super) static LOCALES: [LocaleData; 6] = [
+    LocaleData {
+        language: "en",
+        region: "US",
+        decimal_separator: ".",
+        group_separator: ",",
+        minimum_grouping_digits: 1,
+        percent_suffix: "%",
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        short_months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+        weekdays: [
+            "Sunday",
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+        ],
+        short_weekdays: ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+        day_periods: ["AM", "PM"],
+        numeric_date: "M/d/y",
+        date_patterns: ["M/d/yy", "MMM d, y", "MMMM d, y", "EEEE, MMMM d, y"],
+        time_patterns: ["h:mm a", "h:mm:ss a"],
+        date_time_separator: ", ",
+    },
+    LocaleData {
+        language: "en",
+        region: "GB",
+        decimal_separator: ".",
+        group_separator: ",",
+        minimum_grouping_digits: 1,
+        percent_suffix: "%",
+        months: [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        short_months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sept", "Oct", "Nov", "Dec",
+        ],
+        weekdays: [
+            "Sunday",
+            "Monday",
+            "Tuesday",
+            "Wednesday",
+            "Thursday",
+            "Friday",
+            "Saturday",
+        ],
+        short_weekdays: ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+        day_periods: ["am", "pm"],
+        numeric_date: "dd/MM/y",
+        date_patterns: ["dd/MM/y", "d MMM y", "d MMMM y", "EEEE d MMMM y"],
+        time_patterns: ["HH:mm", "HH:mm:ss"],
+        date_time_separator: ", ",
+    },
+    LocaleData {
+        language: "de",
+        region: "DE",
+        decimal_separator: ",",
+        group_separator: ".",
+        minimum_grouping_digits: 1,
+        percent_suffix: "\u{A0}%",
+        months: [
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+        short_months: [
+            "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+            "Dez.",
+        ],
+        weekdays: [
+            "Sonntag",
+            "Montag",
+            "Dienstag",
+            "Mittwoch",
+            "Donnerstag",
+            "Freitag",
+            "Samstag",
+        ],
+        short_weekdays: ["So.", "Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa."],
+        day_periods: ["AM", "PM"],
+        numeric_date: "d.M.y",
+        date_patterns: ["dd.MM.yy", "dd.MM.y", "d. MMMM y", "EEEE, d. MMMM y"],
+        time_patterns: ["HH:mm", "HH:mm:ss"],
+        date_time_separator: ", ",
+    },
+    LocaleData {
+        language: "fr",
+        region: "FR",
+        decimal_separator: ",",
+        group_separator: "\u{202F}",
+        minimum_grouping_digits: 1,
+        percent_suffix: "\u{202F}%",
+        months: [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        short_months: [
+            "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+            "nov.", "déc.",
+        ],
+        weekdays: [
+            "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+        ],
+        short_weekdays: ["dim.", "lun.", "mar.", "mer.", "jeu.", "ven.", "sam."],
+        day_periods: ["AM", "PM"],
+        numeric_date: "dd/MM/y",
+        date_patterns: ["dd/MM/y", "d MMM y", "d MMMM y", "EEEE d MMMM y"],
+        time_patterns: ["HH:mm", "HH:mm:ss"],
+        date_time_separator: " ",
+    },
+    LocaleData {
+        language: "es",
+        region: "ES",
+        decimal_separator: ",",
+        group_separator: ".",
+        minimum_grouping_digits: 2,
+        percent_suffix: "\u{A0}%",
+        months: [
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+        short_months: [
+            "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+        ],
+        weekdays: [
+            "domingo",
+            "lunes",
+            "martes",
+            "miércoles",
+            "jueves",
+            "viernes",
+            "sábado",
+        ],
+        short_weekdays: ["dom", "lun", "mar", "mié", "jue", "vie", "sáb"],
+        day_periods: ["a. m.", "p. m."],
+        numeric_date: "d/M/y",
+        date_patterns: [
+            "d/M/yy",
+            "d MMM y",
+            "d 'de' MMMM 'de' y",
+            "EEEE, d 'de' MMMM 'de' y",
+        ],
+        time_patterns: ["H:mm", "H:mm:ss"],
+        date_time_separator: ", ",
+    },
+    LocaleData {
+        language: "ja",
+        region: "JP",
+        decimal_separator: ".",
+        group_separator: ",",
+        minimum_grouping_digits: 1,
+        percent_suffix: "%",
+        months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        short_months: [
+            "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+        ],
+        weekdays: [
+            "日曜日",
+            "月曜日",
+            "火曜日",
+            "水曜日",
+            "木曜日",
+            "金曜日",
+            "土曜日",
+        ],
+        short_weekdays: ["日", "月", "火", "水", "木", "金", "土"],
+        day_periods: ["午前", "午後"],
+        numeric_date: "y/M/d",
+        date_patterns: ["y/MM/dd", "y/MM/dd", "y年M月d日", "y年M月d日EEEE"],
+        time_patterns: ["H:mm", "H:mm:ss"],
+        date_time_separator: " ",
+    },
+];
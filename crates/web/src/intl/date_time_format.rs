@@ -0,0 +1,261 @@
+use js::{Exception, ThrowCompletionOr};
+use sl_std::datetime::DateTime;
+
+use super::{data::LocaleData, Locale};
+
+/// The largest distance from the epoch that a time value can have
+///
+/// <https://262.ecma-international.org/14.0/#sec-time-values-and-time-range>
+const MAX_TIME_VALUE: f64 = 8.64e15;
+
+/// <https://tc39.es/ecma402/#sec-properties-of-intl-datetimeformat-instances>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateTimeStyle {
+    Full,
+    Long,
+    Medium,
+    Short,
+}
+
+/// <https://tc39.es/ecma402/#sec-intl-datetimeformat-constructor>
+///
+/// If neither style is set then only the numeric date is shown.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DateTimeFormatOptions {
+    pub date_style: Option<DateTimeStyle>,
+    pub time_style: Option<DateTimeStyle>,
+}
+
+/// Formats points in time according to the conventions of a locale
+///
+/// FIXME: There is no time zone database, so all times are formatted in UTC
+///
+/// [Specification](https://tc39.es/ecma402/#datetimeformat-objects)
+#[derive(Clone, Copy, Debug)]
+pub struct DateTimeFormat {
+    locale: &'static LocaleData,
+    date_pattern: Option<&'static str>,
+    time_pattern: Option<&'static str>,
+
+    /// Whether the time zone name is shown after the time
+    show_time_zone: bool,
+}
+
+impl DateTimeFormat {
+    /// [Specification](https://tc39.es/ecma402/#sec-createdatetimeformat)
+    #[must_use]
+    pub fn new(locale: &Locale, options: DateTimeFormatOptions) -> Self {
+        let locale = locale.lookup();
+
+        let date_pattern = match options.date_style {
+            Some(DateTimeStyle::Short) => Some(locale.date_patterns[0]),
+            Some(DateTimeStyle::Medium) => Some(locale.date_patterns[1]),
+            Some(DateTimeStyle::Long) => Some(locale.date_patterns[2]),
+            Some(DateTimeStyle::Full) => Some(locale.date_patterns[3]),
+            None if options.time_style.is_none() => Some(locale.numeric_date),
+            None => None,
+        };
+
+        let time_pattern = options.time_style.map(|style| match style {
+            DateTimeStyle::Short => locale.time_patterns[0],
+            _ => locale.time_patterns[1],
+        });
+
+        Self {
+            locale,
+            date_pattern,
+            time_pattern,
+            show_time_zone: matches!(
+                options.time_style,
+                Some(DateTimeStyle::Long | DateTimeStyle::Full)
+            ),
+        }
+    }
+
+    /// The bundled locale that is used for formatting
+    ///
+    /// [Specification](https://tc39.es/ecma402/#sec-intl.datetimeformat.prototype.resolvedoptions)
+    #[must_use]
+    pub fn locale(&self) -> String {
+        self.locale.tag()
+    }
+
+    /// Format a time value, in milliseconds since the epoch
+    ///
+    /// Throws a `RangeError` if the time value is not valid.
+    ///
+    /// [Specification](https://tc39.es/ecma402/#sec-formatdatetime)
+    pub fn format(&self, time_value: f64) -> ThrowCompletionOr<String> {
+        // https://262.ecma-international.org/14.0/#sec-timeclip
+        if !time_value.is_finite() || time_value.abs() > MAX_TIME_VALUE {
+            return Err(Exception::range_error());
+        }
+
+        // Sub-second precision is not displayed
+        let date_time = DateTime::from_signed_unix_timestamp((time_value / 1000.).floor() as i64);
+        let mut result = String::new();
+
+        if let Some(date_pattern) = self.date_pattern {
+            self.format_pattern(date_pattern, date_time, &mut result);
+        }
+
+        if let Some(time_pattern) = self.time_pattern {
+            if self.date_pattern.is_some() {
+                result.push_str(self.locale.date_time_separator);
+            }
+            self.format_pattern(time_pattern, date_time, &mut result);
+
+            if self.show_time_zone {
+                result.push_str(" UTC");
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// <https://unicode.org/reports/tr35/tr35-dates.html#Date_Format_Patterns>
+    fn format_pattern(&self, pattern: &str, date_time: DateTime, result: &mut String) {
+        let (date, time) = (date_time.date(), date_time.time());
+        let year = i64::from(date.year().value());
+        let month = usize::from(date.month().index());
+        let weekday = usize::from(date.weekday().index());
+
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            // Text in single quotes is copied literally, two single quotes are a quote
+            if c == '\'' {
+                if chars.next_if_eq(&'\'').is_some() {
+                    result.push('\'');
+                    continue;
+                }
+
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    result.push(c);
+                }
+                continue;
+            }
+
+            if !c.is_ascii_alphabetic() {
+                result.push(c);
+                continue;
+            }
+
+            // Repeating a field symbol selects a longer form of the field
+            let mut width = 1;
+            while chars.next_if_eq(&c).is_some() {
+                width += 1;
+            }
+
+            match (c, width) {
+                ('y', 2) => push_padded(result, year.rem_euclid(100), 2),
+                ('y', _) => result.push_str(&year.to_string()),
+                ('M', 1 | 2) => push_padded(result, month as i64 + 1, width),
+                ('M', 3) => result.push_str(self.locale.short_months[month]),
+                ('M', _) => result.push_str(self.locale.months[month]),
+                ('d', _) => push_padded(result, i64::from(date.day()), width),
+                ('E', 1..=3) => result.push_str(self.locale.short_weekdays[weekday]),
+                ('E', _) => result.push_str(self.locale.weekdays[weekday]),
+                ('H', _) => push_padded(result, time.hour() as i64, width),
+                ('h', _) => {
+                    let hour = match time.hour() % 12 {
+                        0 => 12,
+                        hour => hour,
+                    };
+                    push_padded(result, hour as i64, width);
+                },
+                ('m', _) => push_padded(result, time.minute() as i64, width),
+                ('s', _) => push_padded(result, time.second() as i64, width),
+                ('a', _) => result.push_str(self.locale.day_periods[time.hour() as usize / 12]),
+                _ => log::warn!("Unsupported date pattern field: {c:?}"),
+            }
+        }
+    }
+}
+
+fn push_padded(result: &mut String, value: i64, width: usize) {
+    result.push_str(&format!("{value:0width$}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-02T15:04:05Z
+    const TIME_VALUE: f64 = 1_704_207_845_000.;
+
+    fn format(
+        tag: &str,
+        date_style: Option<DateTimeStyle>,
+        time_style: Option<DateTimeStyle>,
+    ) -> String {
+        let options = DateTimeFormatOptions {
+            date_style,
+            time_style,
+        };
+
+        DateTimeFormat::new(&tag.parse().unwrap(), options)
+            .format(TIME_VALUE)
+            .unwrap()
+    }
+
+    #[test]
+    fn format_dates() {
+        assert_eq!(format("en-US", None, None), "1/2/2024");
+        assert_eq!(format("en-GB", None, None), "02/01/2024");
+        assert_eq!(
+            format("de-DE", Some(DateTimeStyle::Short), None),
+            "02.01.24"
+        );
+        assert_eq!(
+            format("en-US", Some(DateTimeStyle::Full), None),
+            "Tuesday, January 2, 2024"
+        );
+        assert_eq!(
+            format("es", Some(DateTimeStyle::Long), None),
+            "2 de enero de 2024"
+        );
+        assert_eq!(
+            format("ja", Some(DateTimeStyle::Full), None),
+            "2024年1月2日火曜日"
+        );
+    }
+
+    #[test]
+    fn format_times() {
+        assert_eq!(format("en-US", None, Some(DateTimeStyle::Short)), "3:04 PM");
+        assert_eq!(
+            format(
+                "fr",
+                Some(DateTimeStyle::Medium),
+                Some(DateTimeStyle::Medium)
+            ),
+            "2 janv. 2024 15:04:05"
+        );
+        assert_eq!(
+            format("de", None, Some(DateTimeStyle::Long)),
+            "15:04:05 UTC"
+        );
+    }
+
+    #[test]
+    fn dates_before_the_epoch() {
+        let format = DateTimeFormat::new(
+            &Locale::default(),
+            DateTimeFormatOptions {
+                date_style: Some(DateTimeStyle::Medium),
+                time_style: Some(DateTimeStyle::Short),
+            },
+        );
+
+        // 1969-07-20T20:17:40Z
+        assert_eq!(
+            format.format(-14_182_940_000.).unwrap(),
+            "Jul 20, 1969, 8:17 PM"
+        );
+        assert!(format.format(f64::NAN).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use std::{fmt, str::FromStr};
+
+use error_derive::Error;
+
+use super::data::{self, LocaleData};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum InvalidLocale {
+    #[msg = "invalid language subtag"]
+    Language,
+
+    #[msg = "invalid subtag"]
+    Subtag,
+}
+
+/// A language tag like `en-US` or `zh-Hant-TW`
+///
+/// Variants and extensions are accepted but ignored, since no bundled locale uses them.
+///
+/// [Specification](https://unicode.org/reports/tr35/#Unicode_locale_identifier)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale {
+    /// Lowercase, like `en`
+    language: String,
+
+    /// Titlecase, like `Latn`
+    script: Option<String>,
+
+    /// Uppercase, like `US` or `419`
+    region: Option<String>,
+}
+
+impl Locale {
+    #[must_use]
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    #[must_use]
+    pub fn script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    #[must_use]
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    /// Find the bundled locale data that should be used for this locale
+    ///
+    /// [Specification](https://tc39.es/ecma402/#sec-lookupmatchinglocalebybestfit)
+    pub(super) fn lookup(&self) -> &'static LocaleData {
+        // Prefer an exact match, then any locale with the same language
+        let exact_match = data::LOCALES.iter().find(|data| {
+            data.language == self.language && Some(data.region) == self.region.as_deref()
+        });
+
+        exact_match
+            .or_else(|| {
+                data::LOCALES
+                    .iter()
+                    .find(|data| data.language == self.language)
+            })
+            .unwrap_or(&data::LOCALES[0])
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            language: "en".to_string(),
+            script: None,
+            region: Some("US".to_string()),
+        }
+    }
+}
+
+impl FromStr for Locale {
+    type Err = InvalidLocale;
+
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        // Both "-" and "_" are accepted as separators
+        let mut subtags = tag.split(['-', '_']).peekable();
+
+        // unicode_language_subtag = alpha{2,3} | alpha{5,8}
+        let language = subtags.next().unwrap_or_default();
+        if !matches!(language.len(), 2..=3 | 5..=8)
+            || !language.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return Err(InvalidLocale::Language);
+        }
+
+        // unicode_script_subtag = alpha{4}
+        let script = subtags
+            .next_if(|subtag| subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(|script| {
+                let mut script = script.to_ascii_lowercase();
+                script[..1].make_ascii_uppercase();
+                script
+            });
+
+        // unicode_region_subtag = alpha{2} | digit{3}
+        let region = subtags
+            .next_if(|subtag| {
+                (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+                    || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+            })
+            .map(str::to_ascii_uppercase);
+
+        // Variants and extensions must still be well-formed
+        for subtag in subtags {
+            if !matches!(subtag.len(), 1..=8) || !subtag.chars().all(|c| c.is_ascii_alphanumeric())
+            {
+                return Err(InvalidLocale::Subtag);
+            }
+        }
+
+        let locale = Self {
+            language: language.to_ascii_lowercase(),
+            script,
+            region,
+        };
+
+        Ok(locale)
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.language)?;
+
+        if let Some(script) = &self.script {
+            write!(f, "-{script}")?;
+        }
+
+        if let Some(region) = &self.region {
+            write!(f, "-{region}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_locale() {
+        let locale: Locale = "ZH_hant-tw".parse().unwrap();
+        assert_eq!(locale.language(), "zh");
+        assert_eq!(locale.script(), Some("Hant"));
+        assert_eq!(locale.region(), Some("TW"));
+        assert_eq!(locale.to_string(), "zh-Hant-TW");
+
+        let locale: Locale = "es-419-u-nu-latn".parse().unwrap();
+        assert_eq!(locale.region(), Some("419"));
+
+        assert_eq!("e".parse::<Locale>(), Err(InvalidLocale::Language));
+        assert_eq!("en-US-".parse::<Locale>(), Err(InvalidLocale::Subtag));
+    }
+
+    #[test]
+    fn lookup_bundled_locale() {
+        let lookup = |tag: &str| tag.parse::<Locale>().unwrap().lookup().tag();
+
+        assert_eq!(lookup("en-GB"), "en-GB");
+        assert_eq!(lookup("de-AT"), "de-DE");
+        assert_eq!(lookup("en"), "en-US");
+        assert_eq!(lookup("tlh"), "en-US");
+    }
+}
@@ -0,0 +1,15 @@
+//! A small subset of the ECMAScript internationalization API
+//!
+//! Only a handful of locales are bundled, see [data]. Requests for other locales fall
+//! back to the closest bundled locale with the same language, and then to `en-US`.
+//!
+//! <https://tc39.es/ecma402/>
+
+mod data;
+mod date_time_format;
+mod locale;
+mod number_format;
+
+pub use date_time_format::{DateTimeFormat, DateTimeFormatOptions, DateTimeStyle};
+pub use locale::{InvalidLocale, Locale};
+pub use number_format::{NumberFormat, NumberFormatOptions, NumberStyle};
@@ -0,0 +1,218 @@
+use js::{Exception, ThrowCompletionOr};
+
+use super::{data::LocaleData, Locale};
+
+/// The largest number of fraction digits that can be requested
+const MAX_FRACTION_DIGITS: usize = 100;
+
+/// <https://tc39.es/ecma402/#sec-properties-of-intl-numberformat-instances>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NumberStyle {
+    #[default]
+    Decimal,
+
+    /// The number is multiplied by 100 and followed by a percent sign
+    Percent,
+}
+
+/// <https://tc39.es/ecma402/#sec-intl-numberformat-constructor>
+#[derive(Clone, Copy, Debug)]
+pub struct NumberFormatOptions {
+    pub style: NumberStyle,
+
+    /// Defaults to `0`
+    pub minimum_fraction_digits: Option<usize>,
+
+    /// Defaults to `3` for decimals and `0` for percentages,
+    /// but never less than the minimum
+    pub maximum_fraction_digits: Option<usize>,
+
+    /// Whether to use group separators, like in `1,000`
+    pub use_grouping: bool,
+}
+
+impl Default for NumberFormatOptions {
+    fn default() -> Self {
+        Self {
+            style: NumberStyle::default(),
+            minimum_fraction_digits: None,
+            maximum_fraction_digits: None,
+            use_grouping: true,
+        }
+    }
+}
+
+/// Formats numbers according to the conventions of a locale
+///
+/// [Specification](https://tc39.es/ecma402/#numberformat-objects)
+#[derive(Clone, Copy, Debug)]
+pub struct NumberFormat {
+    locale: &'static LocaleData,
+    style: NumberStyle,
+    minimum_fraction_digits: usize,
+    maximum_fraction_digits: usize,
+    use_grouping: bool,
+}
+
+impl NumberFormat {
+    /// Throws a `RangeError` if the fraction digits are out of range
+    ///
+    /// [Specification](https://tc39.es/ecma402/#sec-initializenumberformat)
+    pub fn new(locale: &Locale, options: NumberFormatOptions) -> ThrowCompletionOr<Self> {
+        // https://tc39.es/ecma402/#sec-setnfdigitoptions
+        let default_maximum_fraction_digits = match options.style {
+            NumberStyle::Decimal => 3,
+            NumberStyle::Percent => 0,
+        };
+
+        let minimum_fraction_digits = options.minimum_fraction_digits.unwrap_or(0);
+        let maximum_fraction_digits = options
+            .maximum_fraction_digits
+            .unwrap_or(default_maximum_fraction_digits.max(minimum_fraction_digits));
+
+        if minimum_fraction_digits > maximum_fraction_digits
+            || maximum_fraction_digits > MAX_FRACTION_DIGITS
+        {
+            return Err(Exception::range_error());
+        }
+
+        let number_format = Self {
+            locale: locale.lookup(),
+            style: options.style,
+            minimum_fraction_digits,
+            maximum_fraction_digits,
+            use_grouping: options.use_grouping,
+        };
+
+        Ok(number_format)
+    }
+
+    /// The bundled locale that is used for formatting
+    ///
+    /// [Specification](https://tc39.es/ecma402/#sec-intl.numberformat.prototype.resolvedoptions)
+    #[must_use]
+    pub fn locale(&self) -> String {
+        self.locale.tag()
+    }
+
+    /// [Specification](https://tc39.es/ecma402/#sec-formatnumber)
+    #[must_use]
+    pub fn format(&self, mut number: f64) -> String {
+        if number.is_nan() {
+            return "NaN".to_string();
+        }
+
+        if self.style == NumberStyle::Percent {
+            number *= 100.;
+        }
+
+        let mut result = String::new();
+        if number.is_sign_negative() {
+            result.push('-');
+        }
+
+        if number.is_infinite() {
+            result.push('∞');
+        } else {
+            self.format_digits(number.abs(), &mut result);
+        }
+
+        if self.style == NumberStyle::Percent {
+            result.push_str(self.locale.percent_suffix);
+        }
+
+        result
+    }
+
+    fn format_digits(&self, magnitude: f64, result: &mut String) {
+        let rounded = format!("{magnitude:.*}", self.maximum_fraction_digits);
+        let (integer, fraction) = rounded.split_once('.').unwrap_or((&rounded, ""));
+
+        // Remove trailing zeros, as long as there are enough fraction digits left
+        let significant_fraction_digits = fraction.trim_end_matches('0').len();
+        let fraction = &fraction[..significant_fraction_digits.max(self.minimum_fraction_digits)];
+
+        let should_group =
+            self.use_grouping && integer.len() >= 3 + self.locale.minimum_grouping_digits;
+        if should_group {
+            // The first group might have less than three digits
+            let first_group_length = match integer.len() % 3 {
+                0 => 3,
+                n => n,
+            };
+            result.push_str(&integer[..first_group_length]);
+
+            for group in integer.as_bytes()[first_group_length..].chunks(3) {
+                result.push_str(self.locale.group_separator);
+                result.push_str(std::str::from_utf8(group).expect("digits are ascii"));
+            }
+        } else {
+            result.push_str(integer);
+        }
+
+        if !fraction.is_empty() {
+            result.push_str(self.locale.decimal_separator);
+            result.push_str(fraction);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number_format(tag: &str, options: NumberFormatOptions) -> NumberFormat {
+        NumberFormat::new(&tag.parse().unwrap(), options).unwrap()
+    }
+
+    #[test]
+    fn format_decimal() {
+        let en = number_format("en-US", NumberFormatOptions::default());
+        assert_eq!(en.format(1234567.891), "1,234,567.891");
+        assert_eq!(en.format(-0.12345), "-0.123");
+        assert_eq!(en.format(100.), "100");
+        assert_eq!(en.format(f64::NEG_INFINITY), "-∞");
+        assert_eq!(en.format(f64::NAN), "NaN");
+
+        let de = number_format("de", NumberFormatOptions::default());
+        assert_eq!(de.format(1234.5), "1.234,5");
+
+        // Spanish does not group numbers with four digits
+        let es = number_format("es", NumberFormatOptions::default());
+        assert_eq!(es.format(1234.), "1234");
+        assert_eq!(es.format(12345.), "12.345");
+    }
+
+    #[test]
+    fn fraction_digits() {
+        let options = NumberFormatOptions {
+            minimum_fraction_digits: Some(2),
+            use_grouping: false,
+            ..Default::default()
+        };
+        let en = number_format("en-US", options);
+        assert_eq!(en.format(1234.), "1234.00");
+        assert_eq!(en.format(0.1234), "0.123");
+
+        let options = NumberFormatOptions {
+            minimum_fraction_digits: Some(2),
+            maximum_fraction_digits: Some(1),
+            ..Default::default()
+        };
+        assert!(NumberFormat::new(&Locale::default(), options).is_err());
+    }
+
+    #[test]
+    fn format_percent() {
+        let options = NumberFormatOptions {
+            style: NumberStyle::Percent,
+            ..Default::default()
+        };
+
+        assert_eq!(number_format("en-US", options).format(0.256), "26%");
+        assert_eq!(
+            number_format("fr-FR", options).format(12.5),
+            "1\u{202F}250\u{202F}%"
+        );
+    }
+}
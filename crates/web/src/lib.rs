@@ -8,18 +8,55 @@
     assert_matches
 )]
 
+mod about;
 mod browsing_context;
+mod editing;
+mod error_page;
+mod feed;
+mod file_chooser;
+mod focus;
+mod image_cache;
+mod integrity;
 mod interned_string;
+mod reader_mode;
+pub mod remote_control;
+mod scroll;
+mod select_popup;
 mod selection;
+mod text_iterator;
 mod tree_debug;
+mod user_content;
+mod view_source;
+mod viewport;
 
+pub mod accessibility;
+pub mod canvas;
 pub mod css;
 pub mod dom;
+pub mod drag_and_drop;
+pub mod encoding;
 pub mod event;
+pub mod file_api;
+pub mod geometry;
 pub mod html;
 pub mod infra;
+pub mod intersection_observer;
+pub mod intl;
+pub mod location;
+pub mod media;
+pub mod messaging;
+pub mod permissions;
+pub mod resize_observer;
+pub mod session_history;
+pub mod spellcheck;
+pub mod window;
+pub mod worker;
+pub mod xml;
 
 pub use browsing_context::{BrowsingContext, BrowsingContextError};
+pub use file_chooser::FileChooserRequest;
+pub use focus::FocusDirection;
 pub use interned_string::InternedString;
 pub use selection::Selection;
 pub use tree_debug::{TreeDebug, TreeFormatter};
+pub use viewport::Device;
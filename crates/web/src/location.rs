@@ -0,0 +1,268 @@
+//! The `location` object, which reads and changes the url of the current page
+//!
+//! Changing any part of the url navigates the browsing context to the new url. Navigations
+//! that only change the fragment do not refetch the page.
+//!
+//! <https://html.spec.whatwg.org/multipage/nav-history-apis.html#the-location-interface>
+
+use error_derive::Error;
+use url::{ExcludeFragment, URL};
+
+use crate::{session_history::HistoryHandling, BrowsingContext};
+
+#[derive(Clone, Copy, Debug, Error)]
+pub enum LocationError {
+    /// Corresponds to a `SyntaxError` `DOMException`
+    #[msg = "invalid url"]
+    Syntax,
+}
+
+/// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#location>
+pub struct Location<'a> {
+    browsing_context: &'a mut BrowsingContext,
+}
+
+impl<'a> Location<'a> {
+    #[must_use]
+    pub(crate) fn new(browsing_context: &'a mut BrowsingContext) -> Self {
+        Self { browsing_context }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#concept-location-url>
+    fn url(&self) -> URL {
+        self.browsing_context
+            .url()
+            .unwrap_or_else(|| "about:blank".parse().expect("about:blank is a valid url"))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#location-object-navigate>
+    fn navigate(&mut self, url: &URL) {
+        // Failed navigations display an error page, they are not reported to the script
+        if let Err(error) = self.browsing_context.navigate(url) {
+            log::error!("Failed to navigate to {url}: {error}");
+        }
+    }
+
+    /// Modify a copy of the url and navigate to the result
+    fn navigate_with(&mut self, modify: impl FnOnce(&mut URL)) {
+        let mut url = self.url();
+        modify(&mut url);
+        self.navigate(&url);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-href>
+    #[must_use]
+    pub fn href(&self) -> String {
+        self.url().to_string()
+    }
+
+    /// Navigate to `value`, which is parsed relative to the current url
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-href>
+    pub fn set_href(&mut self, value: &str) -> Result<(), LocationError> {
+        self.assign(value)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-assign>
+    pub fn assign(&mut self, url: &str) -> Result<(), LocationError> {
+        let url = URL::parse_with_base(url, Some(&self.url()), None)
+            .map_err(|_| LocationError::Syntax)?;
+        self.navigate(&url);
+        Ok(())
+    }
+
+    /// Navigate to `url`, replacing the current entry in the session history
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-replace>
+    pub fn replace(&mut self, url: &str) -> Result<(), LocationError> {
+        let url = URL::parse_with_base(url, Some(&self.url()), None)
+            .map_err(|_| LocationError::Syntax)?;
+
+        if let Err(error) = self
+            .browsing_context
+            .navigate_with_history_handling(&url, HistoryHandling::Replace)
+        {
+            log::error!("Failed to navigate to {url}: {error}");
+        }
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-reload>
+    pub fn reload(&mut self) {
+        if let Err(error) = self.browsing_context.reload() {
+            log::error!("Failed to reload {}: {error}", self.url());
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-origin>
+    #[must_use]
+    pub fn origin(&self) -> String {
+        self.url().origin().to_string()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-protocol>
+    #[must_use]
+    pub fn protocol(&self) -> String {
+        format!("{}:", self.url().scheme())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-protocol>
+    pub fn set_protocol(&mut self, value: &str) -> Result<(), LocationError> {
+        let mut url = self.url();
+        url.set_protocol(value).map_err(|_| LocationError::Syntax)?;
+
+        // Only http(s) urls can be navigated to by changing the protocol
+        if !matches!(url.scheme().as_str(), "http" | "https") {
+            return Ok(());
+        }
+
+        self.navigate(&url);
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-host>
+    #[must_use]
+    pub fn host(&self) -> String {
+        let url = self.url();
+        let Some(host) = url.host() else {
+            return String::new();
+        };
+
+        match url.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-host>
+    pub fn set_host(&mut self, value: &str) {
+        self.navigate_with(|url| url.set_host(value));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-hostname>
+    #[must_use]
+    pub fn hostname(&self) -> String {
+        self.url()
+            .host()
+            .map(ToString::to_string)
+            .unwrap_or_default()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-hostname>
+    pub fn set_hostname(&mut self, value: &str) {
+        self.navigate_with(|url| url.set_hostname(value));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-port>
+    #[must_use]
+    pub fn port(&self) -> String {
+        self.url()
+            .port()
+            .map(|port| port.to_string())
+            .unwrap_or_default()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-port>
+    pub fn set_port(&mut self, value: &str) {
+        self.navigate_with(|url| url.set_port(value));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-pathname>
+    #[must_use]
+    pub fn pathname(&self) -> String {
+        self.url().path().to_string()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-pathname>
+    pub fn set_pathname(&mut self, value: &str) {
+        self.navigate_with(|url| url.set_pathname(value));
+    }
+
+    /// The query of the url, including the leading `?` unless it is empty
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-search>
+    #[must_use]
+    pub fn search(&self) -> String {
+        match self.url().query() {
+            Some(query) if !query.is_empty() => format!("?{query}"),
+            _ => String::new(),
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-search>
+    pub fn set_search(&mut self, value: &str) {
+        self.navigate_with(|url| url.set_search(value));
+    }
+
+    /// The fragment of the url, including the leading `#` unless it is empty
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-hash>
+    #[must_use]
+    pub fn hash(&self) -> String {
+        match self.url().fragment() {
+            Some(fragment) if !fragment.is_empty() => format!("#{fragment}"),
+            _ => String::new(),
+        }
+    }
+
+    /// Scroll to a different part of the page, without refetching it
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-location-hash>
+    pub fn set_hash(&mut self, value: &str) {
+        let mut url = self.url();
+        let value = value.strip_prefix('#').unwrap_or(value);
+
+        // Unlike the url setter, an empty value keeps an empty fragment, so this is still
+        // a fragment navigation
+        if value.is_empty() {
+            let mut serialization = url.serialize(ExcludeFragment::Yes).to_string();
+            serialization.push('#');
+            if let Ok(new_url) = serialization.parse() {
+                url = new_url;
+            }
+        } else {
+            url.set_hash(value);
+        }
+
+        self.navigate(&url);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragment_navigation() {
+        let mut browsing_context = BrowsingContext::default();
+        browsing_context
+            .load(&"about:version".parse().unwrap())
+            .unwrap();
+
+        let mut location = browsing_context.location();
+        assert_eq!(location.href(), "about:version");
+        assert_eq!(location.protocol(), "about:");
+        assert_eq!(location.hash(), "");
+
+        location.set_hash("top");
+        assert_eq!(location.href(), "about:version#top");
+        assert_eq!(location.hash(), "#top");
+
+        location.assign("#bottom").unwrap();
+        assert_eq!(location.hash(), "#bottom");
+
+        location.set_search("?refresh");
+        assert_eq!(location.href(), "about:version?refresh#bottom");
+        assert_eq!(location.search(), "?refresh");
+    }
+
+    #[test]
+    fn invalid_urls() {
+        let mut browsing_context = BrowsingContext::default();
+        let mut location = browsing_context.location();
+
+        assert!(location.assign("http://").is_err());
+        assert!(location.set_protocol("1http").is_err());
+        assert_eq!(location.href(), "about:blank");
+    }
+}
@@ -0,0 +1,162 @@
+//! Playback of audio and video
+//!
+//! The engine does not decode any media formats itself. Decoding backends implement
+//! [MediaPlayer] and make themselves available with [register_backend]. As long as no
+//! backend accepts a resource, media elements still go through their usual state changes
+//! but fail to load with [MediaErrorCode::SourceNotSupported].
+//!
+//! <https://html.spec.whatwg.org/multipage/media.html#media-elements>
+
+use std::{sync::RwLock, time::Duration};
+
+use image::Texture;
+
+/// <https://html.spec.whatwg.org/multipage/media.html#error-codes>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaErrorCode {
+    /// The user agent stopped fetching the resource
+    Aborted = 1,
+
+    /// The resource could not be fetched
+    Network = 2,
+
+    /// The resource could be fetched but not decoded
+    Decode = 3,
+
+    /// There is no backend that can play the resource
+    SourceNotSupported = 4,
+}
+
+/// Information that is available once a backend started decoding a resource
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MediaMetadata {
+    /// `None` for resources that do not have a known length, like live streams
+    pub duration: Option<Duration>,
+
+    /// The size of the video frames in pixels, `None` for audio
+    pub video_size: Option<(usize, usize)>,
+}
+
+/// The data could not be decoded
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeError;
+
+/// A decoder for one media resource
+pub trait MediaPlayer {
+    /// Start decoding the data of the resource
+    fn load(&mut self, data: &[u8]) -> Result<MediaMetadata, DecodeError>;
+
+    fn play(&mut self);
+
+    fn pause(&mut self);
+
+    fn seek(&mut self, position: Duration);
+
+    /// The current playback position
+    #[must_use]
+    fn current_time(&self) -> Duration;
+
+    /// The frame that should be displayed at the current playback position, `None` for audio
+    #[must_use]
+    fn current_frame(&self) -> Option<Texture>;
+
+    /// Whether the playback position reached the end of the resource
+    #[must_use]
+    fn has_ended(&self) -> bool;
+}
+
+/// Creates a [MediaPlayer] for resources of the given MIME type, if the backend supports it
+pub type MediaBackend = fn(&mime::MIMEType) -> Option<Box<dyn MediaPlayer>>;
+
+static BACKENDS: RwLock<Vec<MediaBackend>> = RwLock::new(Vec::new());
+
+/// Make a decoding backend available to media elements
+///
+/// Backends are asked in the order that they were registered.
+pub fn register_backend(backend: MediaBackend) {
+    BACKENDS
+        .write()
+        .expect("media backend registry was poisoned")
+        .push(backend);
+}
+
+/// Find a backend that can play resources of the given type
+#[must_use]
+pub(crate) fn create_player(mime_type: &mime::MIMEType) -> Option<Box<dyn MediaPlayer>> {
+    BACKENDS
+        .read()
+        .expect("media backend registry was poisoned")
+        .iter()
+        .find_map(|backend| backend(mime_type))
+}
+
+/// <https://html.spec.whatwg.org/multipage/media.html#dom-media-networkstate>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NetworkState {
+    /// The element has not yet been initialized
+    #[default]
+    Empty = 0,
+
+    /// A resource was selected, but it is not being fetched right now
+    Idle = 1,
+
+    /// The resource is being fetched
+    Loading = 2,
+
+    /// No resource was found that can be played
+    NoSource = 3,
+}
+
+/// <https://html.spec.whatwg.org/multipage/media.html#dom-media-readystate>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReadyState {
+    #[default]
+    HaveNothing = 0,
+    HaveMetadata = 1,
+    HaveCurrentData = 2,
+    HaveFutureData = 3,
+    HaveEnoughData = 4,
+}
+
+/// <https://html.spec.whatwg.org/multipage/media.html#mediaevents>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaEvent {
+    LoadStart,
+    Suspend,
+    Abort,
+    Error,
+    Emptied,
+    LoadedMetadata,
+    LoadedData,
+    CanPlay,
+    CanPlayThrough,
+    Playing,
+    Waiting,
+    Ended,
+    DurationChange,
+    Play,
+    Pause,
+}
+
+impl MediaEvent {
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::LoadStart => "loadstart",
+            Self::Suspend => "suspend",
+            Self::Abort => "abort",
+            Self::Error => "error",
+            Self::Emptied => "emptied",
+            Self::LoadedMetadata => "loadedmetadata",
+            Self::LoadedData => "loadeddata",
+            Self::CanPlay => "canplay",
+            Self::CanPlayThrough => "canplaythrough",
+            Self::Playing => "playing",
+            Self::Waiting => "waiting",
+            Self::Ended => "ended",
+            Self::DurationChange => "durationchange",
+            Self::Play => "play",
+            Self::Pause => "pause",
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Cross-document messaging with `postMessage`
+//!
+//! Messages are serialized with [structured_clone] when they are posted and
+//! delivered in a later task, once the origin of the receiving document is known.
+//!
+//! <https://html.spec.whatwg.org/multipage/web-messaging.html>
+
+use error_derive::Error;
+use url::{Origin, URL};
+
+use crate::worker::structured_clone::{self, DataCloneError, SerializedValue};
+
+#[derive(Debug, Error)]
+pub enum PostMessageError {
+    /// Corresponds to a "SyntaxError" DOMException
+    #[msg = "target origin is not a valid url"]
+    InvalidTargetOrigin,
+
+    #[msg = "message cannot be cloned"]
+    DataClone(DataCloneError),
+}
+
+/// A message that was posted to a window, but not delivered yet
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/web-messaging.html#window-post-message-steps)
+#[derive(Clone, Debug, PartialEq)]
+pub struct PostedMessage {
+    data: SerializedValue,
+
+    /// The origin of the document that posted the message
+    source_origin: Origin,
+
+    /// The origin that the receiving document must have, `None` if the message
+    /// may be delivered to any document
+    target_origin: Option<Origin>,
+}
+
+/// <https://html.spec.whatwg.org/multipage/comms.html#the-messageevent-interface>
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageEvent {
+    /// <https://html.spec.whatwg.org/multipage/comms.html#dom-messageevent-data>
+    pub data: js::Value,
+
+    /// The serialized origin of the document that posted the message
+    ///
+    /// <https://html.spec.whatwg.org/multipage/comms.html#dom-messageevent-origin>
+    pub origin: String,
+}
+
+impl PostedMessage {
+    /// Prepare a message from a document with the given origin
+    ///
+    /// <https://html.spec.whatwg.org/multipage/web-messaging.html#window-post-message-steps>
+    pub fn new(
+        message: &js::Value,
+        target_origin: &str,
+        source_origin: Origin,
+    ) -> Result<Self, PostMessageError> {
+        // 3. Let targetOrigin be options["targetOrigin"].
+        let target_origin = match target_origin {
+            // 4. If targetOrigin is a single U+002F SOLIDUS character (/), then set targetOrigin
+            //    to incumbentSettings's origin.
+            "/" => Some(source_origin.clone()),
+            "*" => None,
+
+            // 5. Otherwise, if targetOrigin is not a single U+002A ASTERISK character (*), then:
+            target_origin => {
+                // 1. Let parsedURL be the result of running the URL parser on targetOrigin.
+                // 2. If parsedURL is failure, then throw a "SyntaxError" DOMException.
+                let parsed_url: URL = target_origin
+                    .parse()
+                    .map_err(|_| PostMessageError::InvalidTargetOrigin)?;
+
+                // 3. Set targetOrigin to parsedURL's origin.
+                Some(parsed_url.origin())
+            },
+        };
+
+        // 6. Let transfer be options["transfer"].
+        // 7. Let serializeWithTransferResult be StructuredSerializeWithTransfer(message, transfer).
+        //    Rethrow any exceptions.
+        // FIXME: Transferable objects
+        let data = structured_clone::serialize(message).map_err(PostMessageError::DataClone)?;
+
+        Ok(Self {
+            data,
+            source_origin,
+            target_origin,
+        })
+    }
+
+    /// Deliver the message to a document with the given origin
+    ///
+    /// Returns `None` if the message was not meant for documents of that origin.
+    ///
+    /// These are the steps of the task that is queued by the
+    /// [window post message steps](https://html.spec.whatwg.org/multipage/web-messaging.html#window-post-message-steps).
+    #[must_use]
+    pub fn deliver_to(self, document_origin: &Origin) -> Option<MessageEvent> {
+        // 1. If the targetOrigin argument is not a single literal U+002A ASTERISK character (*)
+        //    and targetWindow's associated Document's origin is not same origin with targetOrigin,
+        //    then return.
+        if self
+            .target_origin
+            .is_some_and(|target_origin| !target_origin.is_same_origin(document_origin))
+        {
+            return None;
+        }
+
+        // 2. Let origin be the serialization of incumbentSettings's origin.
+        let origin = self.source_origin.to_string();
+
+        // 3. Let source be the WindowProxy object corresponding to incumbentSettings's global object.
+        // NOTE: There are no WindowProxy objects yet
+
+        // 4. Let deserializeRecord be StructuredDeserializeWithTransfer(serializeWithTransferResult, targetRealm).
+        // NOTE: Deserializing primitive values cannot fail, so there is no need for a messageerror event
+        let data = structured_clone::deserialize(self.data);
+
+        // 5. Let messageClone be deserializeRecord.[[Deserialized]].
+        // 6. Let newPorts be a new frozen array consisting of all MessagePort objects in
+        //    deserializeRecord.[[TransferredValues]], if any, maintaining their relative order.
+        // 7. Fire an event named message at targetWindow, using MessageEvent, with the origin
+        //    attribute initialized to origin, the source attribute initialized to source, the
+        //    data attribute initialized to messageClone, and the ports attribute initialized to newPorts.
+        Some(MessageEvent { data, origin })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin(url: &str) -> Origin {
+        url.parse::<URL>().unwrap().origin()
+    }
+
+    #[test]
+    fn check_target_origin() {
+        let source = origin("https://example.com/page");
+        let message = js::Value::from(true);
+
+        let posted = PostedMessage::new(&message, "https://example.com", source.clone()).unwrap();
+        let event = posted
+            .clone()
+            .deliver_to(&origin("https://example.com/other"))
+            .unwrap();
+        assert_eq!(event.data, message);
+        assert_eq!(event.origin, "https://example.com");
+        assert!(posted.deliver_to(&origin("https://other.com/")).is_none());
+
+        // "/" restricts delivery to the origin of the sender
+        let posted = PostedMessage::new(&message, "/", source.clone()).unwrap();
+        assert!(posted.deliver_to(&origin("http://example.com/")).is_none());
+
+        let posted = PostedMessage::new(&message, "*", source).unwrap();
+        assert!(posted.deliver_to(&origin("https://other.com/")).is_some());
+    }
+
+    #[test]
+    fn invalid_messages() {
+        let source = origin("https://example.com/");
+
+        assert!(matches!(
+            PostedMessage::new(&js::Value::Null, "example", source.clone()),
+            Err(PostMessageError::InvalidTargetOrigin)
+        ));
+
+        let symbol = js::Value::Symbol(js::Symbol::new(None));
+        assert!(matches!(
+            PostedMessage::new(&symbol, "*", source),
+            Err(PostMessageError::DataClone(DataCloneError::Symbol))
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+//! The `permissions` object, which tells pages whether the user allows them to use powerful features
+//!
+//! The decisions of the user are stored in [PERMISSIONS]. APIs that require a permission,
+//! like clipboard access, should call [Permissions::request] before doing anything.
+//!
+//! <https://w3c.github.io/permissions/#permissions-interface>
+
+use error_derive::Error;
+use settings::{PermissionName, PermissionState, PERMISSIONS};
+use url::Origin;
+
+#[derive(Clone, Debug, Error)]
+pub enum PermissionsError {
+    /// Corresponds to a `TypeError`
+    #[msg = "unknown permission name"]
+    UnknownPermission,
+}
+
+/// <https://w3c.github.io/permissions/#dom-permissiondescriptor>
+#[derive(Clone, Debug)]
+pub struct PermissionDescriptor {
+    pub name: String,
+}
+
+/// <https://w3c.github.io/permissions/#dom-permissionstatus>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PermissionStatus {
+    name: PermissionName,
+    state: PermissionState,
+}
+
+impl PermissionStatus {
+    /// <https://w3c.github.io/permissions/#dom-permissionstatus-name>
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        self.name.name()
+    }
+
+    /// <https://w3c.github.io/permissions/#dom-permissionstatus-state>
+    #[must_use]
+    pub const fn state(&self) -> PermissionState {
+        self.state
+    }
+}
+
+/// <https://w3c.github.io/permissions/#dom-permissions>
+#[derive(Clone, Debug)]
+pub struct Permissions {
+    /// The origin of the page that uses the permissions
+    origin: Origin,
+}
+
+impl Permissions {
+    #[must_use]
+    pub(crate) const fn new(origin: Origin) -> Self {
+        Self { origin }
+    }
+
+    /// <https://w3c.github.io/permissions/#dom-permissions-query>
+    pub fn query(
+        &self,
+        descriptor: &PermissionDescriptor,
+    ) -> Result<PermissionStatus, PermissionsError> {
+        // 2. Let rootDesc be the object permissionDesc refers to, converted to an IDL value of type PermissionDescriptor.
+        // 3. If the conversion throws an exception, return a promise rejected with that exception.
+        let name = PermissionName::from_name(&descriptor.name)
+            .ok_or(PermissionsError::UnknownPermission)?;
+
+        // 7. Let status be create a PermissionStatus with typedDescriptor.
+        // 8. Query the permission state given status.
+        let state = PERMISSIONS.state(&self.origin, name);
+
+        // 9. Resolve promise with status.
+        Ok(PermissionStatus { name, state })
+    }
+
+    /// Ask the user whether the page may use the feature, unless they already decided
+    ///
+    /// While the user has not answered yet, this returns [PermissionState::Prompt]
+    /// and the feature must not be used.
+    ///
+    /// <https://w3c.github.io/permissions/#dfn-request-permission-to-use>
+    pub fn request(&self, name: PermissionName) -> PermissionState {
+        PERMISSIONS.request(&self.origin, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use url::URL;
+
+    use super::*;
+
+    #[test]
+    fn query_permissions() {
+        // Pages from file urls have opaque origins, which are never granted any permissions
+        let url: URL = "file:///index.html".parse().unwrap();
+        let permissions = Permissions::new(url.origin());
+
+        let status = permissions
+            .query(&PermissionDescriptor {
+                name: "clipboard-read".to_string(),
+            })
+            .unwrap();
+        assert_eq!(status.name(), "clipboard-read");
+        assert_eq!(status.state(), PermissionState::Denied);
+
+        assert!(matches!(
+            permissions.query(&PermissionDescriptor {
+                name: "teleportation".to_string(),
+            }),
+            Err(PermissionsError::UnknownPermission)
+        ));
+    }
+}
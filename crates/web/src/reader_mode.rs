@@ -0,0 +1,468 @@
+//! Reader mode, which displays only the main content of a page
+//!
+//! The article is found with heuristics similar to those of Mozillas
+//! [readability](https://github.com/mozilla/readability) library: Every paragraph adds a score
+//! to its parent and grandparent, based on how much text it contains. The element with the highest
+//! score, adjusted for how much of its text is part of links, is assumed to contain the article.
+//!
+//! The article is then rendered with a clean stylesheet, without any of the original styling.
+
+use crate::{
+    dom::{
+        dom_objects::{Document, Element, Node, Text},
+        DomPtr,
+    },
+    html::{escape_into, sanitizer},
+    static_interned,
+};
+
+/// Paragraphs with less text than this are ignored when scoring
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+/// Pages with less text than this are not considered to be articles
+const MIN_ARTICLE_LENGTH: usize = 250;
+
+/// Elements whose content is never part of an article
+const IGNORED_ELEMENTS: [&str; 17] = [
+    "head", "script", "style", "noscript", "template", "nav", "aside", "footer", "header", "form",
+    "button", "iframe", "svg", "select", "input", "textarea", "menu",
+];
+
+/// Class names or ids that indicate content
+const POSITIVE_HINTS: [&str; 8] = [
+    "article", "content", "main", "post", "entry", "text", "story", "blog",
+];
+
+/// Class names or ids that indicate boilerplate
+const NEGATIVE_HINTS: [&str; 14] = [
+    "comment", "sidebar", "footer", "nav", "menu", "share", "social", "advert", "banner", "cookie",
+    "related", "promo", "widget", "popup",
+];
+
+const STYLESHEET: &str = "
+body { background-color: #fdfaf4; color: #333333; margin: 48px; }
+article { font-family: serif; font-size: 20px; line-height: 1.6; max-width: 700px; }
+h1, h2, h3, h4, h5, h6 { font-family: sans-serif; line-height: 1.25; }
+h1 { font-size: 2em; margin-bottom: 32px; }
+a { color: #1a5fb4; }
+pre, code { font-family: monospace; font-size: 0.8em; }
+pre { background-color: #f0ece2; padding: 12px; }
+blockquote { border-left: 4px solid #d0c9b8; color: #555555; margin-left: 0; padding-left: 16px; }
+img { max-width: 100%; }
+";
+
+/// The main content of a page
+#[derive(Clone, Debug)]
+pub struct Article {
+    pub title: String,
+
+    /// The cleaned up html of the article, without the title
+    content: String,
+}
+
+/// Scoring state for an element that might contain the article
+struct Candidate {
+    element: DomPtr<Element>,
+    score: f64,
+}
+
+/// How much text a subtree contains
+#[derive(Clone, Copy, Debug, Default)]
+struct TextStatistics {
+    length: usize,
+    link_length: usize,
+    commas: usize,
+}
+
+impl TextStatistics {
+    fn link_density(&self) -> f64 {
+        if self.length == 0 {
+            return 0.;
+        }
+
+        self.link_length as f64 / self.length as f64
+    }
+}
+
+/// Find the main content of the document, if it has any
+#[must_use]
+pub fn extract(document: &DomPtr<Document>) -> Option<Article> {
+    let mut paragraphs = vec![];
+    for child in document.borrow().children() {
+        collect_paragraphs(child, &mut paragraphs);
+    }
+
+    let mut candidates: Vec<Candidate> = vec![];
+    for paragraph in paragraphs {
+        let statistics = text_statistics(&paragraph.clone().upcast());
+        if statistics.length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+
+        let score = 1. + statistics.commas as f64 + (statistics.length as f64 / 100.).min(3.);
+
+        let parent = parent_element(&paragraph);
+        let grandparent = parent.as_ref().and_then(parent_element);
+
+        let ancestors = [(parent, score), (grandparent, score / 2.)];
+        for (ancestor, score) in ancestors {
+            let Some(ancestor) = ancestor else {
+                continue;
+            };
+
+            match candidates
+                .iter_mut()
+                .find(|candidate| candidate.element.ptr_eq(&ancestor))
+            {
+                Some(candidate) => candidate.score += score,
+                None => {
+                    let initial_score = initial_score(&ancestor.borrow());
+                    candidates.push(Candidate {
+                        element: ancestor,
+                        score: initial_score + score,
+                    });
+                },
+            }
+        }
+    }
+
+    let best_candidate = candidates
+        .into_iter()
+        .map(|candidate| {
+            let statistics = text_statistics(&candidate.element.clone().upcast());
+            let score = candidate.score * (1. - statistics.link_density());
+            (score, candidate.element, statistics)
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0));
+
+    let (_, article_element, statistics) = best_candidate?;
+    if statistics.length < MIN_ARTICLE_LENGTH {
+        return None;
+    }
+
+    let mut content = String::new();
+    for child in article_element.borrow().children() {
+        write_content(child, &mut content);
+    }
+
+    let title = document.borrow().title();
+
+    Some(Article { title, content })
+}
+
+/// Create a html document that displays the article
+#[must_use]
+pub fn render(article: &Article) -> String {
+    let mut html = String::from("<!DOCTYPE html><html><head><title>");
+    escape_into(&mut html, &article.title);
+    html.push_str("</title><style>");
+    html.push_str(STYLESHEET);
+    html.push_str("</style></head><body><article>");
+
+    if !article.title.is_empty() {
+        html.push_str("<h1>");
+        escape_into(&mut html, &article.title);
+        html.push_str("</h1>");
+    }
+
+    html.push_str(&article.content);
+    html.push_str("</article></body></html>");
+    html
+}
+
+fn collect_paragraphs(node: &DomPtr<Node>, paragraphs: &mut Vec<DomPtr<Element>>) {
+    let Some(element) = node.try_into_type::<Element>() else {
+        return;
+    };
+
+    if is_boilerplate(&element.borrow()) {
+        return;
+    }
+
+    let local_name = element.borrow().local_name();
+    if matches!(
+        local_name,
+        static_interned!("p")
+            | static_interned!("pre")
+            | static_interned!("td")
+            | static_interned!("blockquote")
+    ) {
+        paragraphs.push(element);
+        return;
+    }
+
+    for child in node.borrow().children() {
+        collect_paragraphs(child, paragraphs);
+    }
+}
+
+fn parent_element(element: &DomPtr<Element>) -> Option<DomPtr<Element>> {
+    element.borrow().parent_node()?.try_into_type()
+}
+
+/// Whether the element (and all of its children) should never be part of an article
+fn is_boilerplate(element: &Element) -> bool {
+    let local_name = element.local_name().to_string();
+    if IGNORED_ELEMENTS.contains(&local_name.as_str()) {
+        return true;
+    }
+
+    // The root elements and explicitly marked up articles are never boilerplate
+    let is_container = matches!(
+        element.local_name(),
+        static_interned!("html")
+            | static_interned!("body")
+            | static_interned!("article")
+            | static_interned!("main")
+    );
+
+    !is_container && class_weight(element) < 0.
+}
+
+/// Score an element based on its class names and id
+fn class_weight(element: &Element) -> f64 {
+    let mut weight = 0.;
+
+    for attribute in [static_interned!("class"), static_interned!("id")] {
+        let Some(value) = element.attributes().get(&attribute) else {
+            continue;
+        };
+        let value = value.to_string().to_ascii_lowercase();
+
+        if NEGATIVE_HINTS.iter().any(|hint| value.contains(hint)) {
+            weight -= 25.;
+        }
+
+        if POSITIVE_HINTS.iter().any(|hint| value.contains(hint)) {
+            weight += 25.;
+        }
+    }
+
+    weight
+}
+
+fn initial_score(element: &Element) -> f64 {
+    let tag_weight = match element.local_name() {
+        static_interned!("article") | static_interned!("main") => 10.,
+        static_interned!("div") => 5.,
+        static_interned!("pre") | static_interned!("td") | static_interned!("blockquote") => 3.,
+        static_interned!("ol")
+        | static_interned!("ul")
+        | static_interned!("dl")
+        | static_interned!("dd")
+        | static_interned!("dt")
+        | static_interned!("li")
+        | static_interned!("form") => -3.,
+        static_interned!("h1")
+        | static_interned!("h2")
+        | static_interned!("h3")
+        | static_interned!("h4")
+        | static_interned!("h5")
+        | static_interned!("h6") => -5.,
+        _ => 0.,
+    };
+
+    tag_weight + class_weight(element)
+}
+
+fn text_statistics(node: &DomPtr<Node>) -> TextStatistics {
+    fn visit(node: &DomPtr<Node>, is_link: bool, statistics: &mut TextStatistics) {
+        if let Some(text) = node.try_into_type::<Text>() {
+            let text = text.borrow();
+            let length: usize = text.content().split_ascii_whitespace().map(str::len).sum();
+
+            statistics.length += length;
+            statistics.commas += text.content().matches(',').count();
+            if is_link {
+                statistics.link_length += length;
+            }
+            return;
+        }
+
+        let Some(element) = node.try_into_type::<Element>() else {
+            return;
+        };
+
+        if is_boilerplate(&element.borrow()) {
+            return;
+        }
+
+        let is_link = is_link || element.borrow().local_name() == static_interned!("a");
+        for child in node.borrow().children() {
+            visit(child, is_link, statistics);
+        }
+    }
+
+    let mut statistics = TextStatistics::default();
+    visit(node, false, &mut statistics);
+    statistics
+}
+
+/// Serialize the node as clean html
+///
+/// Only elements that describe the structure of the text are kept, everything else
+/// is replaced by its children.
+fn write_content(node: &DomPtr<Node>, html: &mut String) {
+    if let Some(text) = node.try_into_type::<Text>() {
+        escape_into(html, text.borrow().content());
+        return;
+    }
+
+    let Some(element) = node.try_into_type::<Element>() else {
+        return;
+    };
+    let element_ref = element.borrow();
+
+    if is_boilerplate(&element_ref) {
+        return;
+    }
+
+    let local_name = element_ref.local_name();
+
+    // Collections of links are most likely navigation
+    let is_container = matches!(
+        local_name,
+        static_interned!("div")
+            | static_interned!("section")
+            | static_interned!("ul")
+            | static_interned!("ol")
+            | static_interned!("table")
+    );
+    if is_container && text_statistics(node).link_density() > 0.5 {
+        return;
+    }
+
+    let tag = match local_name {
+        // The article title is the only top level heading
+        static_interned!("h1") => Some("h2"),
+        static_interned!("h2") => Some("h2"),
+        static_interned!("h3") => Some("h3"),
+        static_interned!("h4") => Some("h4"),
+        static_interned!("h5") => Some("h5"),
+        static_interned!("h6") => Some("h6"),
+        static_interned!("p") => Some("p"),
+        static_interned!("div") | static_interned!("section") => Some("div"),
+        static_interned!("ul") => Some("ul"),
+        static_interned!("ol") => Some("ol"),
+        static_interned!("li") => Some("li"),
+        static_interned!("dl") => Some("dl"),
+        static_interned!("dt") => Some("dt"),
+        static_interned!("dd") => Some("dd"),
+        static_interned!("blockquote") => Some("blockquote"),
+        static_interned!("pre") => Some("pre"),
+        static_interned!("code") => Some("code"),
+        static_interned!("em") | static_interned!("i") => Some("em"),
+        static_interned!("strong") | static_interned!("b") => Some("strong"),
+        static_interned!("sub") => Some("sub"),
+        static_interned!("figure") => Some("figure"),
+        static_interned!("figcaption") => Some("figcaption"),
+        static_interned!("a") => Some("a"),
+        static_interned!("img") => Some("img"),
+        static_interned!("br") => Some("br"),
+        static_interned!("hr") => Some("hr"),
+        _ => None,
+    };
+
+    let Some(tag) = tag else {
+        for child in element_ref.children() {
+            write_content(child, html);
+        }
+        return;
+    };
+
+    html.push('<');
+    html.push_str(tag);
+    for (attribute, name) in [
+        (static_interned!("href"), "href"),
+        (static_interned!("src"), "src"),
+        (static_interned!("alt"), "alt"),
+    ] {
+        if let Some(value) = element_ref.attributes().get(&attribute) {
+            // Links to javascript: urls and the like would run scripts
+            if attribute != static_interned!("alt")
+                && !sanitizer::has_allowed_scheme(value.as_str(), &sanitizer::DEFAULT_URL_SCHEMES)
+            {
+                continue;
+            }
+
+            html.push(' ');
+            html.push_str(name);
+            html.push_str("=\"");
+            escape_into(html, &value.to_string());
+            html.push('"');
+        }
+    }
+    html.push('>');
+
+    // Void elements have neither children nor a closing tag
+    if matches!(tag, "img" | "br" | "hr") {
+        return;
+    }
+
+    for child in element_ref.children() {
+        write_content(child, html);
+    }
+
+    html.push_str("</");
+    html.push_str(tag);
+    html.push('>');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::{self, tokenization::IgnoreParseErrors};
+
+    fn parse(source: &str) -> DomPtr<Document> {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        let parser: html::Parser<IgnoreParseErrors> = html::Parser::new(source, document);
+        parser.parse().0
+    }
+
+    const PARAGRAPH: &str = "The quick brown fox jumps over the lazy dog, again and again, \
+        until the dog finally wakes up and chases the fox out of the garden.";
+
+    #[test]
+    fn extract_article() {
+        let source = format!(
+            "<html><head><title>A story</title></head><body>
+            <nav><a href=\"/\">Home</a><a href=\"/about\">About</a></nav>
+            <div class=\"sidebar\"><p>{PARAGRAPH}</p></div>
+            <div id=\"main-content\">
+                <h1>A story</h1>
+                <p>{PARAGRAPH}</p>
+                <p>{PARAGRAPH} <a href=\"/more\">Read more</a> <a href=\"javascript:track()\">Like</a></p>
+                <p>{PARAGRAPH}</p>
+                <ul class=\"links\"><li><a href=\"/a\">First</a></li><li><a href=\"/b\">Second</a></li></ul>
+                <script>track();</script>
+            </div>
+            <footer>Copyright</footer>
+            </body></html>"
+        );
+
+        let article = extract(&parse(&source)).expect("no article found");
+
+        assert_eq!(article.title, "A story");
+        assert_eq!(article.content.matches("<p>").count(), 3);
+        assert!(article.content.contains("<h2>A story</h2>"));
+        assert!(article.content.contains("<a href=\"/more\">Read more</a>"));
+        assert!(!article.content.contains("Home"));
+        assert!(!article.content.contains("First"));
+        assert!(!article.content.contains("track"));
+        assert!(article.content.contains("<a>Like</a>"));
+        assert!(!article.content.contains("Copyright"));
+
+        let html = render(&article);
+        assert!(html.contains("<article><h1>A story</h1>"));
+    }
+
+    #[test]
+    fn pages_without_articles() {
+        let source = "<html><body><nav><a href=\"/\">Home</a></nav><p>Short text</p></body></html>";
+        assert!(extract(&parse(source)).is_none());
+    }
+}
@@ -0,0 +1,505 @@
+//! Lets other programs drive a [BrowsingContext] without a user interface
+//!
+//! Commands are JSON objects with a `command` member and the arguments of the command,
+//! for example `{"command": "click", "selector": "#submit"}`. Every command produces
+//! exactly one response, which is either `{"status": "ok", "value": ...}` or
+//! `{"status": "error", "message": ...}`.
+//!
+//! The following commands are supported:
+//! * `navigate` (`url`): Load a page
+//! * `query-element` (`selector`): Describe the first element that matches a CSS selector
+//! * `click` (`selector`): Click on the center of an element
+//! * `type` (`text`, optionally `selector`): Type text into the focused element,
+//!   after clicking on the element with the given selector
+//! * `screenshot`: Capture the pixels of the viewport, as base64-encoded RGBA bytes
+//! * `dump-layout`: List the area of every box on the page
+//! * `css-errors`: List the parts of the page's stylesheets that were ignored because
+//!   they could not be parsed
+//! * `css-text`: Serialize the rules of the page's stylesheets
+
+use std::collections::HashMap;
+
+use error_derive::Error;
+use image::Texture;
+use render::Composition;
+use serialize::Deserialize;
+use serialize_json::{JsonDeserializer, JsonSerializer, Value};
+use sl_std::base64;
+use url::URL;
+
+use crate::{
+    accessibility,
+    css::{self, layout::Pixels, CSSParse, CSSSerialize, Selector},
+    dom::{
+        dom_objects::{Element, Node},
+        DomPtr,
+    },
+    event, BrowsingContext, BrowsingContextError, Device,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Navigate(URL),
+    QueryElement {
+        selector: String,
+    },
+    Click {
+        selector: String,
+    },
+    Type {
+        selector: Option<String>,
+        text: String,
+    },
+    Screenshot,
+    DumpLayout,
+    CSSErrors,
+    CSSText,
+}
+
+#[derive(Debug, Error)]
+pub enum RemoteControlError {
+    #[msg = "command is not a json object"]
+    InvalidMessage,
+
+    #[msg = "unknown command"]
+    UnknownCommand,
+
+    #[msg = "missing argument"]
+    MissingArgument,
+
+    #[msg = "invalid url"]
+    InvalidURL,
+
+    #[msg = "invalid selector"]
+    InvalidSelector,
+
+    #[msg = "no element matches the selector"]
+    NoSuchElement,
+
+    #[msg = "element is not displayed"]
+    ElementNotDisplayed,
+
+    #[msg = "navigation failed"]
+    Navigation(BrowsingContextError),
+}
+
+impl Command {
+    pub fn parse(message: &str) -> Result<Self, RemoteControlError> {
+        let mut deserializer = JsonDeserializer::new(message);
+        let message = Value::deserialize(&mut deserializer)
+            .map_err(|_| RemoteControlError::InvalidMessage)?;
+        let arguments = message.as_map().ok_or(RemoteControlError::InvalidMessage)?;
+
+        let optional_argument = |name: &str| {
+            arguments
+                .get(name)
+                .and_then(Value::as_str)
+                .map(ToOwned::to_owned)
+        };
+        let argument =
+            |name: &str| optional_argument(name).ok_or(RemoteControlError::MissingArgument);
+
+        let command = match argument("command")?.as_str() {
+            "navigate" => Self::Navigate(
+                argument("url")?
+                    .parse()
+                    .map_err(|_| RemoteControlError::InvalidURL)?,
+            ),
+            "query-element" => Self::QueryElement {
+                selector: argument("selector")?,
+            },
+            "click" => Self::Click {
+                selector: argument("selector")?,
+            },
+            "type" => Self::Type {
+                selector: optional_argument("selector"),
+                text: argument("text")?,
+            },
+            "screenshot" => Self::Screenshot,
+            "dump-layout" => Self::DumpLayout,
+            "css-errors" => Self::CSSErrors,
+            "css-text" => Self::CSSText,
+            _ => return Err(RemoteControlError::UnknownCommand),
+        };
+
+        Ok(command)
+    }
+}
+
+/// A [BrowsingContext] that is rendered offscreen and controlled through [Commands](Command)
+pub struct RemoteControl {
+    browsing_context: BrowsingContext,
+    device: Device,
+    composition: Composition,
+    backend: Box<dyn render::Backend>,
+    view_buffer: Texture,
+}
+
+impl RemoteControl {
+    #[must_use]
+    pub fn new(device: Device) -> Self {
+        let width = (device.size.0 as f32 * device.device_pixel_ratio).ceil() as usize;
+        let height = (device.size.1 as f32 * device.device_pixel_ratio).ceil() as usize;
+
+        Self {
+            browsing_context: BrowsingContext::default(),
+            device,
+            composition: Composition::default(),
+            backend: render::create_backend(false),
+            view_buffer: Texture::new(width, height),
+        }
+    }
+
+    /// Execute a command given as JSON and return the JSON response
+    #[must_use]
+    pub fn handle_message(&mut self, message: &str) -> String {
+        let response = match Command::parse(message).and_then(|command| self.execute(command)) {
+            Ok(value) => object([("status", string("ok")), ("value", value)]),
+            Err(error) => {
+                log::warn!("Remote control command {message:?} failed: {error}");
+                object([
+                    ("status", string("error")),
+                    ("message", string(error.to_string())),
+                ])
+            },
+        };
+
+        JsonSerializer::serialize_to_string(response).expect("Writing to a string cannot fail")
+    }
+
+    pub fn execute(&mut self, command: Command) -> Result<Value, RemoteControlError> {
+        // Queries need to see the current layout of the page
+        if !matches!(command, Command::Navigate(_)) {
+            self.update_rendering();
+        }
+
+        let value = match command {
+            Command::Navigate(url) => {
+                self.browsing_context.load(&url)?;
+                self.update_rendering();
+                Value::Null
+            },
+            Command::QueryElement { selector } => self
+                .query_selector(&selector)?
+                .map_or(Value::Null, |element| self.describe(&element)),
+            Command::Click { selector } => {
+                let element = self.find_element(&selector)?;
+                if !self.browsing_context.click_element(&element) {
+                    return Err(RemoteControlError::ElementNotDisplayed);
+                }
+                self.update_rendering();
+                Value::Null
+            },
+            Command::Type { selector, text } => {
+                if let Some(selector) = selector {
+                    let element = self.find_element(&selector)?;
+                    if !self.browsing_context.click_element(&element) {
+                        return Err(RemoteControlError::ElementNotDisplayed);
+                    }
+                }
+
+                let mut handled = true;
+                for c in text.chars() {
+                    let key = match c {
+                        '\n' => event::Key::Enter,
+                        _ => event::Key::Character(c),
+                    };
+                    handled &= self
+                        .browsing_context
+                        .handle_key_press(event::KeyEvent::new(key));
+                }
+                self.update_rendering();
+                Value::Boolean(handled)
+            },
+            Command::Screenshot => self.screenshot(),
+            Command::DumpLayout => self.dump_layout(),
+            Command::CSSErrors => self.css_errors(),
+            Command::CSSText => self.css_text(),
+        };
+
+        Ok(value)
+    }
+
+    fn update_rendering(&mut self) {
+        self.composition.clear();
+        self.browsing_context
+            .paint(&mut self.composition, self.device);
+        self.backend
+            .render(&mut self.composition, &mut self.view_buffer);
+    }
+
+    /// Find the first element in tree order that matches the given list of selectors
+    fn query_selector(
+        &self,
+        selector: &str,
+    ) -> Result<Option<DomPtr<Element>>, RemoteControlError> {
+        fn find(node: &DomPtr<Node>, selectors: &[Selector]) -> Option<DomPtr<Element>> {
+            if let Some(element) = node.try_into_type::<Element>()
+                && selectors.iter().any(|selector| selector.matches(&element))
+            {
+                return Some(element);
+            }
+
+            node.borrow()
+                .children()
+                .iter()
+                .find_map(|child| find(child, selectors))
+        }
+
+        let mut parser = css::Parser::new(selector, css::Origin::Author);
+        let selectors = parser.parse_comma_seperated_list(Selector::parse);
+        if selectors.is_empty() || parser.next_token_ignoring_whitespace().is_some() {
+            return Err(RemoteControlError::InvalidSelector);
+        }
+
+        let Some(document) = self.browsing_context.document() else {
+            return Ok(None);
+        };
+
+        Ok(find(&document.upcast(), &selectors))
+    }
+
+    fn find_element(&self, selector: &str) -> Result<DomPtr<Element>, RemoteControlError> {
+        self.query_selector(selector)?
+            .ok_or(RemoteControlError::NoSuchElement)
+    }
+
+    fn describe(&self, element: &DomPtr<Element>) -> Value {
+        let node: DomPtr<Node> = element.clone().upcast();
+
+        let attributes = element
+            .borrow()
+            .attributes()
+            .iter()
+            .map(|(name, value)| (name.to_string(), string(value.to_string())))
+            .collect();
+
+        let area = self
+            .browsing_context
+            .area_of(&node)
+            .map_or(Value::Null, rectangle);
+
+        object([
+            ("tag", string(element.borrow().local_name().to_string())),
+            ("attributes", Value::Map(attributes)),
+            ("text", string(accessibility::text_content(&node))),
+            ("area", area),
+        ])
+    }
+
+    /// The pixels of the viewport, row by row
+    fn screenshot(&self) -> Value {
+        let pixels: Vec<u8> = self
+            .view_buffer
+            .data()
+            .iter()
+            .flat_map(|color| {
+                [color.red(), color.green(), color.blue(), color.alpha()]
+                    .map(|channel| (channel * 255.).round() as u8)
+            })
+            .collect();
+
+        object([
+            ("width", Value::Integer(self.view_buffer.width())),
+            ("height", Value::Integer(self.view_buffer.height())),
+            ("format", string("rgba8")),
+            ("data", string(base64::b64encode(&pixels).to_string())),
+        ])
+    }
+
+    /// Every box on the page in paint order, together with the element that generated it
+    fn dump_layout(&self) -> Value {
+        let mut boxes = vec![];
+
+        if let Some(fragment_tree) = self.browsing_context.fragment_tree() {
+            fragment_tree.for_each_box(|fragment, area| {
+                let node = fragment
+                    .dom_node()
+                    .and_then(|node| node.try_into_type::<Element>())
+                    .map_or(Value::Null, |element| {
+                        string(element.borrow().local_name().to_string())
+                    });
+
+                boxes.push(object([("node", node), ("area", rectangle(area))]));
+            });
+        }
+
+        Value::List(boxes)
+    }
+
+    /// Syntax errors in the stylesheets of the page, in the order they were found
+    fn css_errors(&self) -> Value {
+        let errors = self
+            .browsing_context
+            .stylesheets()
+            .iter()
+            .filter(|stylesheet| stylesheet.origin() == css::Origin::Author)
+            .flat_map(|stylesheet| {
+                stylesheet.errors().iter().map(|error| {
+                    object([
+                        ("stylesheet", Value::Integer(stylesheet.index())),
+                        ("line", Value::Integer(error.location.line)),
+                        ("column", Value::Integer(error.location.column)),
+                        ("message", string(error.kind.to_string())),
+                    ])
+                })
+            })
+            .collect();
+
+        Value::List(errors)
+    }
+
+    /// The serialized rules of every stylesheet on the page
+    ///
+    /// Parts of the stylesheets that could not be parsed are not included.
+    fn css_text(&self) -> Value {
+        let stylesheets = self
+            .browsing_context
+            .stylesheets()
+            .iter()
+            .filter(|stylesheet| stylesheet.origin() == css::Origin::Author)
+            .map(|stylesheet| string(stylesheet.to_css_string()))
+            .collect();
+
+        Value::List(stylesheets)
+    }
+}
+
+fn object<const N: usize>(members: [(&str, Value); N]) -> Value {
+    Value::Map(
+        members
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect::<HashMap<_, _>>(),
+    )
+}
+
+fn string(value: impl Into<String>) -> Value {
+    Value::String(value.into())
+}
+
+fn rectangle(area: math::Rectangle<Pixels>) -> Value {
+    object([
+        ("x", Value::Float(area.top_left().x.0.into())),
+        ("y", Value::Float(area.top_left().y.0.into())),
+        ("width", Value::Float(area.width().0.into())),
+        ("height", Value::Float(area.height().0.into())),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use resourceloader::{
+        protocol::{self, ProtocolHandler},
+        Resource, ResourceLoadError,
+    };
+
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            Command::parse(r#"{"command": "type", "text": "hi"}"#).unwrap(),
+            Command::Type {
+                selector: None,
+                text: "hi".to_string()
+            }
+        );
+        assert_eq!(
+            Command::parse(r##"{"command": "click", "selector": "#ok"}"##).unwrap(),
+            Command::Click {
+                selector: "#ok".to_string()
+            }
+        );
+
+        assert!(matches!(
+            Command::parse("[]"),
+            Err(RemoteControlError::InvalidMessage)
+        ));
+        assert!(matches!(
+            Command::parse(r#"{"command": "fly"}"#),
+            Err(RemoteControlError::UnknownCommand)
+        ));
+        assert!(matches!(
+            Command::parse(r#"{"command": "navigate"}"#),
+            Err(RemoteControlError::MissingArgument)
+        ));
+    }
+
+    /// Serves a fixed document, file urls cannot be parsed yet
+    struct TestPage;
+
+    impl ProtocolHandler for TestPage {
+        fn load(&self, _url: &URL) -> Result<Resource, ResourceLoadError> {
+            let html = "<style>div { colour: red; color: green }</style>\
+                <div id=box style='width: 100px; height: 50px' data-kind=example></div>";
+            let mime_type = mime::MIMEType::new("text", "html");
+            Ok(Resource::new(html.as_bytes().to_vec(), Some(mime_type)))
+        }
+    }
+
+    #[test]
+    fn drive_page() {
+        protocol::register_protocol_handler("stormlicht-remote-test", TestPage);
+
+        let mut remote_control = RemoteControl::new(Device {
+            size: (200, 100),
+            device_pixel_ratio: 2.,
+        });
+        let url = "stormlicht-remote-test://page/".parse().unwrap();
+        remote_control.execute(Command::Navigate(url)).unwrap();
+
+        let element = remote_control
+            .execute(Command::QueryElement {
+                selector: "div".to_string(),
+            })
+            .unwrap();
+        let element = element.as_map().unwrap();
+        assert_eq!(element["tag"].as_str(), Some("div"));
+        assert_eq!(
+            element["attributes"].as_map().unwrap()["data-kind"].as_str(),
+            Some("example")
+        );
+        assert!(matches!(
+            element["area"].as_map().unwrap()["width"],
+            Value::Float(width) if width == 100.
+        ));
+
+        assert!(matches!(
+            remote_control.execute(Command::Click {
+                selector: "#missing".to_string()
+            }),
+            Err(RemoteControlError::NoSuchElement)
+        ));
+        assert!(matches!(
+            remote_control.execute(Command::QueryElement {
+                selector: "{".to_string()
+            }),
+            Err(RemoteControlError::InvalidSelector)
+        ));
+
+        let layout = remote_control.execute(Command::DumpLayout).unwrap();
+        assert!(layout
+            .as_list()
+            .unwrap()
+            .any(|fragment| fragment.as_map().unwrap()["node"].as_str() == Some("div")));
+
+        let errors = remote_control.execute(Command::CSSErrors).unwrap();
+        let errors: Vec<&Value> = errors.as_list().unwrap().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].as_map().unwrap()["message"].as_str(),
+            Some("Unknown property 'colour'")
+        );
+
+        let css_text = remote_control.execute(Command::CSSText).unwrap();
+        let css_text: Vec<&Value> = css_text.as_list().unwrap().collect();
+        assert_eq!(css_text.len(), 1);
+        assert_eq!(css_text[0].as_str(), Some("div { color: rgb(0, 128, 0); }"));
+
+        let screenshot = remote_control.execute(Command::Screenshot).unwrap();
+        let screenshot = screenshot.as_map().unwrap();
+        assert!(matches!(screenshot["width"], Value::Integer(400)));
+        assert!(matches!(screenshot["height"], Value::Integer(200)));
+    }
+}
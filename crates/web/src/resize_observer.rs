@@ -0,0 +1,414 @@
+//! Observe changes to the size of elements
+//!
+//! Observations are gathered and broadcast during every rendering update, after the page
+//! was laid out. Callbacks might change the size of other elements, so the page is laid out
+//! again and observations are gathered again, but only for elements that are deeper in the
+//! tree than the ones that were just broadcast. This guarantees that the loop terminates.
+//!
+//! The callbacks of observers are rust closures.
+//!
+//! <https://drafts.csswg.org/resize-observer/>
+
+use std::{cell::RefCell, fmt, mem, rc::Rc};
+
+use crate::{
+    css::layout::{Pixels, Size},
+    dom::{dom_objects, DomPtr},
+    geometry::DomRect,
+};
+
+type Callback = Box<dyn FnMut(Vec<ResizeObserverEntry>)>;
+
+/// The box whose size is observed
+///
+/// <https://drafts.csswg.org/resize-observer/#enumdef-resizeobserverboxoptions>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizeObserverBoxOptions {
+    /// The content area, which does not include padding or borders
+    #[default]
+    ContentBox,
+
+    /// The content area, padding and borders
+    BorderBox,
+
+    /// The content area, in device pixels
+    DevicePixelContentBox,
+}
+
+/// <https://drafts.csswg.org/resize-observer/#dictdef-resizeobserveroptions>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResizeObserverOptions {
+    pub observed_box: ResizeObserverBoxOptions,
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resizeobserversize>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ResizeObserverSize {
+    pub inline_size: Pixels,
+    pub block_size: Pixels,
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resize-observer-entry-interface>
+#[derive(Clone)]
+pub struct ResizeObserverEntry {
+    pub target: DomPtr<dom_objects::Element>,
+
+    /// The content area relative to the padding area
+    pub content_rect: DomRect,
+    pub border_box_size: Vec<ResizeObserverSize>,
+    pub content_box_size: Vec<ResizeObserverSize>,
+    pub device_pixel_content_box_size: Vec<ResizeObserverSize>,
+}
+
+/// The boxes of an element, as computed during the most recent layout
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Measurement {
+    pub border_box: Size<Pixels>,
+
+    /// The content area relative to the padding area
+    pub content_rect: DomRect,
+    pub device_pixel_ratio: f32,
+}
+
+impl Measurement {
+    /// <https://drafts.csswg.org/resize-observer/#calculate-box-size>
+    fn size(&self, observed_box: ResizeObserverBoxOptions) -> ResizeObserverSize {
+        // FIXME: The inline size is the height for vertical writing modes
+        match observed_box {
+            ResizeObserverBoxOptions::ContentBox => ResizeObserverSize {
+                inline_size: self.content_rect.width,
+                block_size: self.content_rect.height,
+            },
+            ResizeObserverBoxOptions::BorderBox => ResizeObserverSize {
+                inline_size: self.border_box.width,
+                block_size: self.border_box.height,
+            },
+            ResizeObserverBoxOptions::DevicePixelContentBox => ResizeObserverSize {
+                inline_size: self.content_rect.width * self.device_pixel_ratio,
+                block_size: self.content_rect.height * self.device_pixel_ratio,
+            },
+        }
+    }
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resizeobservation>
+#[derive(Clone)]
+struct ResizeObservation {
+    target: DomPtr<dom_objects::Element>,
+    observed_box: ResizeObserverBoxOptions,
+
+    /// Starts out as `0x0`, so elements that are displayed at all are reported during the first update
+    last_reported_size: ResizeObserverSize,
+
+    /// Whether the size changed and will be reported during the next broadcast
+    is_active: bool,
+
+    /// Whether the size changed, but the target was too shallow in the tree to be reported
+    is_skipped: bool,
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resize-observer-interface>
+pub struct ResizeObserver {
+    callback: Callback,
+    observation_targets: Vec<ResizeObservation>,
+}
+
+impl ResizeObserver {
+    #[must_use]
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: FnMut(Vec<ResizeObserverEntry>) + 'static,
+    {
+        Self {
+            callback: Box::new(callback),
+            observation_targets: vec![],
+        }
+    }
+
+    /// Report changes to the size of `target`, replacing any previous observation of the same target
+    ///
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observe>
+    pub fn observe(
+        &mut self,
+        target: DomPtr<dom_objects::Element>,
+        options: ResizeObserverOptions,
+    ) {
+        // 1. If target is in [[observationTargets]] slot, call unobserve() with argument target.
+        self.unobserve(&target);
+
+        // 2. Let observedBox be the value of the box dictionary member of options.
+        // 3. Let resizeObservation be new ResizeObservation(target, observedBox).
+        // 4. Add the resizeObservation to the [[observationTargets]] slot.
+        self.observation_targets.push(ResizeObservation {
+            target,
+            observed_box: options.observed_box,
+            last_reported_size: ResizeObserverSize::default(),
+            is_active: false,
+            is_skipped: false,
+        });
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-unobserve>
+    pub fn unobserve(&mut self, target: &DomPtr<dom_objects::Element>) {
+        self.observation_targets
+            .retain(|observation| !observation.target.ptr_eq(target));
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-disconnect>
+    pub fn disconnect(&mut self) {
+        self.observation_targets.clear();
+    }
+}
+
+impl fmt::Debug for ResizeObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResizeObserver")
+            .field("observation_targets", &self.observation_targets.len())
+            .finish()
+    }
+}
+
+/// The number of ancestors of the element
+///
+/// <https://drafts.csswg.org/resize-observer/#calculate-depth-for-node>
+fn depth_of(element: &DomPtr<dom_objects::Element>) -> usize {
+    let mut depth = 0;
+    let mut node = element.borrow().parent_node();
+    while let Some(parent) = node {
+        depth += 1;
+        node = parent.borrow().parent_node();
+    }
+    depth
+}
+
+/// Mark every observation whose size changed as either active or skipped
+///
+/// Only targets that are deeper than `depth` become active.
+///
+/// <https://drafts.csswg.org/resize-observer/#gather-active-observations-h>
+pub(crate) fn gather_active_observations<F>(
+    observers: &[Rc<RefCell<ResizeObserver>>],
+    depth: usize,
+    mut measure: F,
+) where
+    F: FnMut(&DomPtr<dom_objects::Element>) -> Measurement,
+{
+    for observer in observers {
+        // 1. Clear observer’s [[activeTargets]], and [[skippedTargets]].
+        // 2. For each observation in observer.[[observationTargets]] run this step:
+        for observation in &mut observer.borrow_mut().observation_targets {
+            // 1. If observation.isActive() is true
+            let current_size = measure(&observation.target).size(observation.observed_box);
+            let has_changed = current_size != observation.last_reported_size;
+
+            // 1. Let targetDepth be result of calculate depth for node for observation.target.
+            // 2. If targetDepth is greater than depth then add observation to [[activeTargets]].
+            // 3. Else add observation to [[skippedTargets]].
+            let is_deep_enough = has_changed && depth_of(&observation.target) > depth;
+            observation.is_active = is_deep_enough;
+            observation.is_skipped = has_changed && !is_deep_enough;
+        }
+    }
+}
+
+/// <https://drafts.csswg.org/resize-observer/#has-active-observations-h>
+#[must_use]
+pub(crate) fn has_active_observations(observers: &[Rc<RefCell<ResizeObserver>>]) -> bool {
+    observers.iter().any(|observer| {
+        observer
+            .borrow()
+            .observation_targets
+            .iter()
+            .any(|observation| observation.is_active)
+    })
+}
+
+/// <https://drafts.csswg.org/resize-observer/#has-skipped-observations-h>
+#[must_use]
+pub(crate) fn has_skipped_observations(observers: &[Rc<RefCell<ResizeObserver>>]) -> bool {
+    observers.iter().any(|observer| {
+        observer
+            .borrow()
+            .observation_targets
+            .iter()
+            .any(|observation| observation.is_skipped)
+    })
+}
+
+/// Invoke the callbacks of all observers with active observations
+///
+/// Returns the depth of the shallowest target that was reported.
+///
+/// <https://drafts.csswg.org/resize-observer/#broadcast-active-resize-observations>
+pub(crate) fn broadcast_active_observations<F>(
+    observers: &[Rc<RefCell<ResizeObserver>>],
+    mut measure: F,
+) -> usize
+where
+    F: FnMut(&DomPtr<dom_objects::Element>) -> Measurement,
+{
+    // 1. Let shallowestTargetDepth be ∞
+    let mut shallowest_target_depth = usize::MAX;
+
+    // 2. For each observer in document.[[resizeObservers]] run these steps:
+    for observer in observers {
+        let mut entries = vec![];
+
+        {
+            let mut observer = observer.borrow_mut();
+
+            // 2. Let entries be an empty list of ResizeObserverEntryies.
+            // 3. For each observation in [[activeTargets]] perform these steps:
+            for observation in &mut observer.observation_targets {
+                if !mem::take(&mut observation.is_active) {
+                    continue;
+                }
+
+                // 1. Let entry be the result of running create and populate a ResizeObserverEntry given observation.target.
+                let measurement = measure(&observation.target);
+                entries.push(ResizeObserverEntry {
+                    target: observation.target.clone(),
+                    content_rect: measurement.content_rect,
+                    border_box_size: vec![measurement.size(ResizeObserverBoxOptions::BorderBox)],
+                    content_box_size: vec![measurement.size(ResizeObserverBoxOptions::ContentBox)],
+                    device_pixel_content_box_size: vec![
+                        measurement.size(ResizeObserverBoxOptions::DevicePixelContentBox)
+                    ],
+                });
+
+                // 2. Set observation.lastReportedSizes to matching entry sizes.
+                observation.last_reported_size = measurement.size(observation.observed_box);
+
+                // 3. Set targetDepth to the result of calculate depth for node for observation.target.
+                // 4. Set shallowestTargetDepth to targetDepth if targetDepth < shallowestTargetDepth
+                shallowest_target_depth =
+                    shallowest_target_depth.min(depth_of(&observation.target));
+            }
+        }
+
+        // 1. If observer.[[activeTargets]] slot is empty, continue.
+        if entries.is_empty() {
+            continue;
+        }
+
+        // 4. Invoke observer.[[callback]] with entries as the first argument and observer as the second argument.
+        // The callback might access the observer, so it must not be borrowed while the callback runs
+        let mut callback = mem::replace(&mut observer.borrow_mut().callback, Box::new(|_| {}));
+        callback(entries);
+        observer.borrow_mut().callback = callback;
+    }
+
+    shallowest_target_depth
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{dom::ElementCustomState, infra::Namespace, static_interned};
+
+    fn measurement(width: f32, height: f32) -> Measurement {
+        Measurement {
+            border_box: Size {
+                width: Pixels(width + 10.),
+                height: Pixels(height + 10.),
+            },
+            content_rect: DomRect {
+                x: Pixels(5.),
+                y: Pixels(5.),
+                width: Pixels(width),
+                height: Pixels(height),
+            },
+            device_pixel_ratio: 2.,
+        }
+    }
+
+    fn element() -> DomPtr<dom_objects::Element> {
+        let document = DomPtr::new(dom_objects::Document::default());
+        let element = DomPtr::new(dom_objects::Element::new(
+            Namespace::HTML,
+            None,
+            static_interned!("div"),
+            ElementCustomState::Uncustomized,
+            None,
+            None,
+        ));
+        dom_objects::Node::append_child(document.upcast(), element.clone().upcast());
+        element
+    }
+
+    #[test]
+    fn report_size_changes() {
+        let reported_sizes = Rc::new(RefCell::new(vec![]));
+        let observer = {
+            let reported_sizes = reported_sizes.clone();
+            ResizeObserver::new(move |entries| {
+                for entry in entries {
+                    reported_sizes.borrow_mut().push(entry.content_box_size[0]);
+                }
+            })
+        };
+        let observers = [Rc::new(RefCell::new(observer))];
+
+        let target = element();
+        observers[0]
+            .borrow_mut()
+            .observe(target.clone(), ResizeObserverOptions::default());
+
+        let mut size = measurement(100., 50.);
+        let update = |size: Measurement| {
+            gather_active_observations(&observers, 0, |_| size);
+            while has_active_observations(&observers) {
+                let depth = broadcast_active_observations(&observers, |_| size);
+                gather_active_observations(&observers, depth, |_| size);
+            }
+        };
+
+        update(size);
+        update(size);
+        assert_eq!(
+            *reported_sizes.borrow(),
+            [ResizeObserverSize {
+                inline_size: Pixels(100.),
+                block_size: Pixels(50.),
+            }]
+        );
+
+        size.content_rect.width = Pixels(200.);
+        update(size);
+        assert_eq!(reported_sizes.borrow().len(), 2);
+        assert_eq!(reported_sizes.borrow()[1].inline_size, Pixels(200.));
+    }
+
+    #[test]
+    fn skip_shallow_targets() {
+        let observer = ResizeObserver::new(|_| {});
+        let observers = [Rc::new(RefCell::new(observer))];
+
+        let target = element();
+        observers[0]
+            .borrow_mut()
+            .observe(target.clone(), ResizeObserverOptions::default());
+
+        // The target is a child of the document, so it is at depth 1
+        gather_active_observations(&observers, 1, |_| measurement(10., 10.));
+        assert!(!has_active_observations(&observers));
+        assert!(has_skipped_observations(&observers));
+    }
+
+    #[test]
+    fn device_pixel_content_box() {
+        let size = measurement(100., 50.);
+        assert_eq!(
+            size.size(ResizeObserverBoxOptions::DevicePixelContentBox),
+            ResizeObserverSize {
+                inline_size: Pixels(200.),
+                block_size: Pixels(100.),
+            }
+        );
+        assert_eq!(
+            size.size(ResizeObserverBoxOptions::BorderBox).inline_size,
+            Pixels(110.)
+        );
+    }
+}
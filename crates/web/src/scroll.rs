@@ -0,0 +1,127 @@
+//! Scrolling of the viewport
+//!
+//! The scroll position is the top left corner of the viewport within the page. It is
+//! always kept within the scrollable overflow of the page, so the viewport never shows
+//! anything past the end of the content.
+//!
+//! <https://drafts.csswg.org/cssom-view/#scrolling>
+
+use math::Vec2D;
+
+use crate::{
+    css::layout::{Pixels, Size},
+    event::Key,
+};
+
+/// The distance that the viewport moves per line of a mouse wheel or per press of an arrow key
+pub const LINE_HEIGHT: Pixels = Pixels(40.);
+
+/// The fraction of the viewport that is kept visible when scrolling by a page
+const PAGE_OVERLAP: f32 = 0.125;
+
+/// Keep the scroll position within the scrollable overflow of the page
+///
+/// Pages that are smaller than the viewport cannot be scrolled at all.
+#[must_use]
+pub fn clamp(
+    position: Vec2D<Pixels>,
+    overflow: Size<Pixels>,
+    viewport: Size<Pixels>,
+) -> Vec2D<Pixels> {
+    let max_x = (overflow.width - viewport.width).max(Pixels::ZERO);
+    let max_y = (overflow.height - viewport.height).max(Pixels::ZERO);
+
+    Vec2D::new(
+        position.x.clamp(Pixels::ZERO, max_x),
+        position.y.clamp(Pixels::ZERO, max_y),
+    )
+}
+
+/// The scroll position after pressing `key`, if the key scrolls the viewport
+///
+/// The returned position is not clamped.
+#[must_use]
+pub fn position_after_key_press(
+    key: Key,
+    position: Vec2D<Pixels>,
+    overflow: Size<Pixels>,
+    viewport: Size<Pixels>,
+) -> Option<Vec2D<Pixels>> {
+    let page_height = viewport.height * (1. - PAGE_OVERLAP);
+
+    let position = match key {
+        Key::ArrowUp => Vec2D::new(position.x, position.y - LINE_HEIGHT),
+        Key::ArrowDown => Vec2D::new(position.x, position.y + LINE_HEIGHT),
+        Key::ArrowLeft => Vec2D::new(position.x - LINE_HEIGHT, position.y),
+        Key::ArrowRight => Vec2D::new(position.x + LINE_HEIGHT, position.y),
+        Key::Space => Vec2D::new(position.x, position.y + page_height),
+        Key::Home => Vec2D::new(position.x, Pixels::ZERO),
+        Key::End => Vec2D::new(position.x, overflow.height),
+        _ => return None,
+    };
+
+    Some(position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIEWPORT: Size<Pixels> = Size {
+        width: Pixels(800.),
+        height: Pixels(600.),
+    };
+
+    #[test]
+    fn clamp_to_overflow() {
+        let overflow = Size {
+            width: Pixels(800.),
+            height: Pixels(2000.),
+        };
+
+        assert_eq!(
+            clamp(Vec2D::new(Pixels(50.), Pixels(-10.)), overflow, VIEWPORT),
+            Vec2D::new(Pixels::ZERO, Pixels::ZERO)
+        );
+        assert_eq!(
+            clamp(Vec2D::new(Pixels::ZERO, Pixels(5000.)), overflow, VIEWPORT),
+            Vec2D::new(Pixels::ZERO, Pixels(1400.))
+        );
+
+        // Small pages can't be scrolled
+        let overflow = Size {
+            width: Pixels(100.),
+            height: Pixels(100.),
+        };
+        assert_eq!(
+            clamp(Vec2D::new(Pixels(10.), Pixels(10.)), overflow, VIEWPORT),
+            Vec2D::new(Pixels::ZERO, Pixels::ZERO)
+        );
+    }
+
+    #[test]
+    fn scroll_with_keys() {
+        let overflow = Size {
+            width: Pixels(800.),
+            height: Pixels(2000.),
+        };
+        let position = Vec2D::new(Pixels::ZERO, Pixels(100.));
+
+        assert_eq!(
+            position_after_key_press(Key::ArrowDown, position, overflow, VIEWPORT),
+            Some(Vec2D::new(Pixels::ZERO, Pixels(140.)))
+        );
+        assert_eq!(
+            position_after_key_press(Key::Space, position, overflow, VIEWPORT),
+            Some(Vec2D::new(Pixels::ZERO, Pixels(625.)))
+        );
+        assert_eq!(
+            position_after_key_press(Key::Home, position, overflow, VIEWPORT),
+            Some(Vec2D::new(Pixels::ZERO, Pixels::ZERO))
+        );
+        assert_eq!(
+            position_after_key_press(Key::Enter, position, overflow, VIEWPORT),
+            None
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! The list of options that is displayed when a drop-down `<select>` is opened
+//!
+//! The popup is not part of the page content, it is painted on top of the page by the
+//! browsing context and closed once an option was picked.
+
+use math::{Rectangle, Vec2D};
+
+use crate::{
+    css::{
+        display_list::Painter,
+        layout::{select, Pixels},
+        Color, ComputedStyle, FontMetrics,
+    },
+    dom::{dom_objects::HtmlSelectElement, DomPtr},
+};
+
+/// Horizontal space between the border of the popup and the option labels
+const PADDING: Pixels = Pixels(4.);
+
+const BORDER_WIDTH: Pixels = Pixels(1.);
+
+const BORDER_COLOR: Color = Color::rgb(0x76, 0x76, 0x76);
+
+const BACKGROUND_COLOR: Color = Color::WHITE;
+
+/// How the highlighted option changes in response to keyboard input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Movement {
+    Previous,
+    Next,
+    First,
+    Last,
+}
+
+/// Find the option that is reached by moving from `current` in the given direction
+///
+/// Disabled options are skipped. If there is no enabled option in that direction,
+/// the current option stays the same.
+#[must_use]
+pub(crate) fn move_within(
+    is_disabled: &[bool],
+    current: Option<usize>,
+    movement: Movement,
+) -> Option<usize> {
+    let mut enabled = (0..is_disabled.len()).filter(|&index| !is_disabled[index]);
+
+    let target = match (movement, current) {
+        (Movement::First, _) | (Movement::Next, None) => enabled.next(),
+        (Movement::Last, _) | (Movement::Previous, None) => enabled.next_back(),
+        (Movement::Next, Some(current)) => enabled.find(|&index| index > current),
+        (Movement::Previous, Some(current)) => enabled.rfind(|&index| index < current),
+    };
+
+    target.or(current)
+}
+
+#[derive(Clone, Debug)]
+struct PopupOption {
+    label: String,
+    is_disabled: bool,
+}
+
+/// An opened drop-down box
+pub(crate) struct SelectPopup {
+    select: DomPtr<HtmlSelectElement>,
+    options: Vec<PopupOption>,
+    area: Rectangle<Pixels>,
+    row_height: Pixels,
+    font_metrics: FontMetrics,
+
+    /// The option that is picked when the user presses enter
+    highlighted: Option<usize>,
+}
+
+impl SelectPopup {
+    /// Open the popup for a `<select>` element whose border box is at `anchor`
+    ///
+    /// The popup is placed directly below the element and is at least as wide as it.
+    #[must_use]
+    pub fn open(select: DomPtr<HtmlSelectElement>, anchor: Rectangle<Pixels>) -> Self {
+        let style = ComputedStyle::default();
+        let font_metrics = FontMetrics::for_style(&style);
+        let row_height = style.line_height().used_value(*style.font_size());
+
+        let (options, highlighted) = {
+            let select = select.borrow();
+            let options: Vec<PopupOption> = select
+                .list_of_options()
+                .iter()
+                .map(|option| {
+                    let option = option.borrow();
+                    PopupOption {
+                        label: option.label(),
+                        is_disabled: option.is_disabled(),
+                    }
+                })
+                .collect();
+
+            (options, select.selected_index())
+        };
+
+        let label_width = options
+            .iter()
+            .map(|option| font_metrics.width_of(&option.label))
+            .fold(Pixels::ZERO, Pixels::max);
+
+        let width = (label_width + PADDING * 2. + BORDER_WIDTH * 2.).max(anchor.width());
+        let height = row_height * options.len() as f32 + BORDER_WIDTH * 2.;
+        let area = Rectangle::from_position_and_size(anchor.bottom_left(), width, height);
+
+        Self {
+            select,
+            options,
+            area,
+            row_height,
+            font_metrics,
+            highlighted,
+        }
+    }
+
+    #[must_use]
+    pub fn select(&self) -> &DomPtr<HtmlSelectElement> {
+        &self.select
+    }
+
+    #[must_use]
+    pub fn highlighted(&self) -> Option<usize> {
+        self.highlighted
+    }
+
+    #[must_use]
+    pub fn contains(&self, position: Vec2D<Pixels>) -> bool {
+        self.area.contains_point(position)
+    }
+
+    /// The enabled option at the given position, if any
+    #[must_use]
+    pub fn option_at(&self, position: Vec2D<Pixels>) -> Option<usize> {
+        if !self.contains(position) {
+            return None;
+        }
+
+        let offset = position.y - self.area.top_left().y - BORDER_WIDTH;
+        let index = (offset.0 / self.row_height.0).floor();
+        if index < 0. {
+            return None;
+        }
+
+        let index = index as usize;
+        self.options
+            .get(index)
+            .is_some_and(|option| !option.is_disabled)
+            .then_some(index)
+    }
+
+    /// Highlight the option below the mouse pointer
+    pub fn hover(&mut self, position: Vec2D<Pixels>) {
+        if let Some(index) = self.option_at(position) {
+            self.highlighted = Some(index);
+        }
+    }
+
+    pub fn move_highlight(&mut self, movement: Movement) {
+        let is_disabled: Vec<bool> = self
+            .options
+            .iter()
+            .map(|option| option.is_disabled)
+            .collect();
+        self.highlighted = move_within(&is_disabled, self.highlighted, movement);
+    }
+
+    pub fn paint(&self, painter: &mut Painter) {
+        let select_node = self.select.clone().upcast();
+        painter.begin_fragment(Some(&select_node));
+
+        painter.rect(self.area, BORDER_COLOR.into());
+        let inner_area = Rectangle::from_corners(
+            self.area.top_left() + Vec2D::new(BORDER_WIDTH, BORDER_WIDTH),
+            self.area.bottom_right() - Vec2D::new(BORDER_WIDTH, BORDER_WIDTH),
+        );
+        painter.rect(inner_area, BACKGROUND_COLOR.into());
+
+        for (index, option) in self.options.iter().enumerate() {
+            let row_position =
+                inner_area.top_left() + Vec2D::new(Pixels::ZERO, self.row_height * index as f32);
+
+            let text_color = if self.highlighted == Some(index) {
+                let row_area = Rectangle::from_position_and_size(
+                    row_position,
+                    inner_area.width(),
+                    self.row_height,
+                );
+                painter.rect(row_area, select::SELECTED_OPTION_BACKGROUND.into());
+                select::SELECTED_OPTION_COLOR
+            } else if option.is_disabled {
+                select::DISABLED_OPTION_COLOR
+            } else {
+                Color::BLACK
+            };
+
+            let text_area = Rectangle::from_position_and_size(
+                row_position + Vec2D::new(PADDING, Pixels::ZERO),
+                self.font_metrics.width_of(&option.label),
+                self.row_height,
+            );
+            painter.text(
+                option.label.clone(),
+                text_area,
+                text_color.into(),
+                self.font_metrics.clone(),
+            );
+        }
+
+        painter.end_fragment();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyboard_movement_skips_disabled_options() {
+        let is_disabled = [true, false, true, false, false, true];
+
+        assert_eq!(move_within(&is_disabled, None, Movement::Next), Some(1));
+        assert_eq!(move_within(&is_disabled, Some(1), Movement::Next), Some(3));
+        assert_eq!(move_within(&is_disabled, Some(4), Movement::Next), Some(4));
+        assert_eq!(
+            move_within(&is_disabled, Some(3), Movement::Previous),
+            Some(1)
+        );
+        assert_eq!(
+            move_within(&is_disabled, Some(1), Movement::Previous),
+            Some(1)
+        );
+        assert_eq!(move_within(&is_disabled, None, Movement::Previous), Some(4));
+        assert_eq!(move_within(&is_disabled, Some(4), Movement::First), Some(1));
+        assert_eq!(move_within(&is_disabled, Some(1), Movement::Last), Some(4));
+
+        assert_eq!(move_within(&[true, true], None, Movement::Next), None);
+    }
+}
@@ -0,0 +1,383 @@
+//! The list of pages that were visited in a browsing context, and the `history` object
+//! that lets scripts add to it
+//!
+//! Single-page applications use [History::push_state] to give each of their views a url,
+//! without loading a new document. Traversing between entries that belong to the same
+//! document only fires a `popstate` event at the window, which the application uses to
+//! restore the view.
+//!
+//! <https://html.spec.whatwg.org/multipage/nav-history-apis.html#the-history-interface>
+
+use error_derive::Error;
+use js::Value;
+use url::URL;
+
+use crate::{
+    worker::structured_clone::{self, DataCloneError, SerializedValue},
+    BrowsingContext,
+};
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    /// Corresponds to a `SecurityError` `DOMException`
+    #[msg = "url cannot be used to rewrite the url of the document"]
+    Security,
+
+    #[msg = "state cannot be serialized"]
+    DataClone(DataCloneError),
+}
+
+/// Whether a navigation adds a new entry to the session history
+///
+/// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#navigationhistorybehavior>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HistoryHandling {
+    Push,
+    Replace,
+}
+
+/// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#session-history-entry>
+#[derive(Clone, Debug)]
+pub(crate) struct SessionHistoryEntry {
+    pub url: URL,
+
+    /// <https://html.spec.whatwg.org/multipage/browsing-the-web.html#she-serialized-state>
+    pub serialized_state: Option<SerializedValue>,
+
+    /// Entries with the same document id can be traversed without loading a new document
+    pub document_id: usize,
+}
+
+/// <https://html.spec.whatwg.org/multipage/document-sequences.html#tn-session-history-entries>
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SessionHistory {
+    entries: Vec<SessionHistoryEntry>,
+
+    /// The index of the entry that is currently displayed
+    current: usize,
+    next_document_id: usize,
+}
+
+impl SessionHistory {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn current_entry(&self) -> Option<&SessionHistoryEntry> {
+        self.entries.get(self.current)
+    }
+
+    /// Add an entry for a newly loaded document
+    pub fn add_document(&mut self, url: URL, history_handling: HistoryHandling) {
+        let document_id = self.next_document_id;
+        self.next_document_id += 1;
+
+        self.add_entry(
+            SessionHistoryEntry {
+                url,
+                serialized_state: None,
+                document_id,
+            },
+            history_handling,
+        );
+    }
+
+    /// Add an entry that belongs to the document of the current entry
+    pub fn add_same_document_entry(
+        &mut self,
+        url: URL,
+        serialized_state: Option<SerializedValue>,
+        history_handling: HistoryHandling,
+    ) {
+        let Some(current_entry) = self.current_entry() else {
+            self.add_document(url, history_handling);
+            return;
+        };
+
+        let entry = SessionHistoryEntry {
+            url,
+            serialized_state,
+            document_id: current_entry.document_id,
+        };
+        self.add_entry(entry, history_handling);
+    }
+
+    fn add_entry(&mut self, entry: SessionHistoryEntry, history_handling: HistoryHandling) {
+        if self.entries.is_empty() {
+            self.entries.push(entry);
+            return;
+        }
+
+        match history_handling {
+            HistoryHandling::Push => {
+                // Entries after the current one can't be traversed to anymore
+                self.entries.truncate(self.current + 1);
+                self.entries.push(entry);
+                self.current = self.entries.len() - 1;
+            },
+            HistoryHandling::Replace => self.entries[self.current] = entry,
+        }
+    }
+
+    /// Make the entry that is `delta` steps away from the current one the current entry
+    ///
+    /// Returns the previous and the new entry, or `None` if there is no such entry.
+    pub fn traverse(
+        &mut self,
+        delta: isize,
+    ) -> Option<(&SessionHistoryEntry, &SessionHistoryEntry)> {
+        let target = self
+            .current
+            .checked_add_signed(delta)
+            .filter(|&target| target < self.entries.len() && delta != 0)?;
+
+        let previous = self.current;
+        self.current = target;
+        Some((&self.entries[previous], &self.entries[target]))
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#can-have-its-url-rewritten>
+#[must_use]
+fn can_have_url_rewritten(document_url: &URL, target_url: &URL) -> bool {
+    // 1. If targetURL and documentURL differ in their scheme, username, password, host, or port components, then return false.
+    if document_url.scheme() != target_url.scheme()
+        || document_url.username() != target_url.username()
+        || document_url.password() != target_url.password()
+        || document_url.host() != target_url.host()
+        || document_url.port() != target_url.port()
+    {
+        return false;
+    }
+
+    // 2. If targetURL’s scheme is an HTTP(S) scheme, then return true.
+    if matches!(target_url.scheme().as_str(), "http" | "https") {
+        return true;
+    }
+
+    // 3. If targetURL’s scheme is "file", then:
+    if target_url.scheme().as_str() == "file" {
+        // 1. If targetURL and documentURL differ in their path component, then return false.
+        // 2. Return true.
+        return document_url.path() == target_url.path();
+    }
+
+    // 4. If targetURL and documentURL differ in their path component or query components, then return false.
+    // 5. Return true.
+    document_url.path() == target_url.path() && document_url.query() == target_url.query()
+}
+
+/// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#history-3>
+pub struct History<'a> {
+    browsing_context: &'a mut BrowsingContext,
+}
+
+impl<'a> History<'a> {
+    #[must_use]
+    pub(crate) fn new(browsing_context: &'a mut BrowsingContext) -> Self {
+        Self { browsing_context }
+    }
+
+    /// The number of entries in the session history
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-length>
+    #[must_use]
+    pub fn length(&self) -> usize {
+        self.browsing_context.session_history().len()
+    }
+
+    /// The state that was stored with the current entry, or `null` if there is none
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-state>
+    #[must_use]
+    pub fn state(&self) -> Value {
+        self.browsing_context
+            .session_history()
+            .current_entry()
+            .and_then(|entry| entry.serialized_state.clone())
+            .map_or(Value::Null, structured_clone::deserialize)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-go>
+    pub fn go(&mut self, delta: isize) {
+        // 3. If delta is 0, then reload this's relevant global object's navigable.
+        if delta == 0 {
+            if let Err(error) = self.browsing_context.reload() {
+                log::error!("Failed to reload page: {error}");
+            }
+            return;
+        }
+
+        // 4. Traverse the history by a delta given this's relevant global object's navigable's traversable navigable, delta, and document.
+        self.browsing_context.traverse_history(delta);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-back>
+    pub fn back(&mut self) {
+        self.go(-1);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-forward>
+    pub fn forward(&mut self) {
+        self.go(1);
+    }
+
+    /// Add an entry with the given state to the session history, without loading a new document
+    ///
+    /// The unused `title` argument of the specification is omitted.
+    ///
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-pushstate>
+    pub fn push_state(&mut self, data: &Value, url: Option<&str>) -> Result<(), HistoryError> {
+        self.shared_push_or_replace_state(data, url, HistoryHandling::Push)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-history-replacestate>
+    pub fn replace_state(&mut self, data: &Value, url: Option<&str>) -> Result<(), HistoryError> {
+        self.shared_push_or_replace_state(data, url, HistoryHandling::Replace)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#shared-history-push/replace-state-steps>
+    fn shared_push_or_replace_state(
+        &mut self,
+        data: &Value,
+        url: Option<&str>,
+        history_handling: HistoryHandling,
+    ) -> Result<(), HistoryError> {
+        // 1. Let document be history's associated Document.
+        // 2. If document is not fully active, then throw a "SecurityError" DOMException.
+        let Some(document_url) = self.browsing_context.url() else {
+            return Err(HistoryError::Security);
+        };
+
+        // 3. Optionally, return.
+        // 4. Let serializedData be StructuredSerializeForStorage(data).
+        let serialized_data = structured_clone::serialize(data)?;
+
+        // 5. Let newURL be document's URL.
+        // 6. If url is not null or the empty string, then:
+        let new_url = match url {
+            Some(url) if !url.is_empty() => {
+                // 1. Set newURL to the result of encoding-parsing a URL given url, relative to the relevant settings object of history.
+                // 2. If newURL is failure, then throw a "SecurityError" DOMException.
+                let new_url = URL::parse_with_base(url, Some(&document_url), None)
+                    .map_err(|_| HistoryError::Security)?;
+
+                // 3. If document cannot have its URL rewritten to newURL, then throw a "SecurityError" DOMException.
+                if !can_have_url_rewritten(&document_url, &new_url) {
+                    return Err(HistoryError::Security);
+                }
+
+                new_url
+            },
+            _ => document_url,
+        };
+
+        // 7. Let navigation be history's relevant global object's navigation API.
+        // 8. Let continue be the result of firing a push/replace/reload navigate event at navigation with navigationType set to historyHandling, isSameDocument set to true, destinationURL set to newURL, and classicHistoryAPIState set to serializedData.
+        // 9. If continue is false, then return.
+        // NOTE: There is no navigation API yet
+
+        // 10. Run the URL and history update steps given document and newURL, with serializedData set to serializedData and historyHandling set to historyHandling.
+        self.browsing_context.update_url_and_history(
+            new_url,
+            Some(serialized_data),
+            history_handling,
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js::Number;
+
+    use super::*;
+
+    #[test]
+    fn push_and_traverse() {
+        let mut browsing_context = BrowsingContext::default();
+        browsing_context
+            .load(&"about:version".parse().unwrap())
+            .unwrap();
+
+        let mut history = browsing_context.history();
+        assert_eq!(history.length(), 1);
+        assert_eq!(history.state(), Value::Null);
+
+        history
+            .push_state(&Value::Number(Number::new(2.)), Some("#page-2"))
+            .unwrap();
+        assert_eq!(history.length(), 2);
+        assert_eq!(history.state(), Value::Number(Number::new(2.)));
+        assert_eq!(
+            browsing_context.url().unwrap().to_string(),
+            "about:version#page-2"
+        );
+
+        let mut history = browsing_context.history();
+        history.back();
+        assert_eq!(history.state(), Value::Null);
+        assert_eq!(browsing_context.url().unwrap().to_string(), "about:version");
+
+        let mut history = browsing_context.history();
+        history.forward();
+        history.replace_state(&Value::Boolean(true), None).unwrap();
+        assert_eq!(history.length(), 2);
+        assert_eq!(history.state(), Value::Boolean(true));
+
+        // Pushing an entry discards all entries after the current one
+        history.go(-1);
+        history.push_state(&Value::Null, Some("#top")).unwrap();
+        assert_eq!(history.length(), 2);
+        assert_eq!(
+            browsing_context.url().unwrap().to_string(),
+            "about:version#top"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_state() {
+        let mut browsing_context = BrowsingContext::default();
+        browsing_context
+            .load(&"about:version".parse().unwrap())
+            .unwrap();
+        let mut history = browsing_context.history();
+
+        assert!(matches!(
+            history.push_state(&Value::Null, Some("http://example.com")),
+            Err(HistoryError::Security)
+        ));
+        assert!(matches!(
+            history.push_state(&Value::Null, Some("about:blank")),
+            Err(HistoryError::Security)
+        ));
+
+        // Only http(s) and file urls can change more than their fragment
+        assert!(matches!(
+            history.push_state(&Value::Null, Some("?page=2")),
+            Err(HistoryError::Security)
+        ));
+        assert_eq!(history.length(), 1);
+    }
+
+    #[test]
+    fn rewrite_urls() {
+        let document_url: URL = "https://example.com/a?b".parse().unwrap();
+
+        assert!(can_have_url_rewritten(
+            &document_url,
+            &"https://example.com/c".parse().unwrap()
+        ));
+        assert!(!can_have_url_rewritten(
+            &document_url,
+            &"https://example.com:8080/a".parse().unwrap()
+        ));
+        assert!(!can_have_url_rewritten(
+            &document_url,
+            &"http://example.com/a".parse().unwrap()
+        ));
+    }
+}
@@ -0,0 +1,467 @@
+//! Parsing of hunspell affix files (`.aff`)
+//!
+//! <https://man.archlinux.org/man/hunspell.5>
+
+use std::collections::HashMap;
+
+use super::DictionaryError;
+
+/// A flag that is attached to words in the dictionary, usually to allow an affix
+pub(super) type Flag = u32;
+
+/// How flags are written in the affix and dictionary files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(super) enum FlagType {
+    /// Every character is a flag
+    #[default]
+    Short,
+
+    /// Every two characters are a flag
+    Long,
+
+    /// Flags are decimal numbers, separated by commas
+    Numeric,
+}
+
+impl FlagType {
+    /// Parse a sequence of flags, like `ABC` or `12,34`
+    pub fn parse_flags(&self, flags: &str) -> Result<Vec<Flag>, DictionaryError> {
+        let mut flags: Vec<Flag> = match self {
+            Self::Short => flags.chars().map(Flag::from).collect(),
+            Self::Long => {
+                let chars: Vec<char> = flags.chars().collect();
+                if chars.len() % 2 != 0 {
+                    return Err(DictionaryError::InvalidFlag);
+                }
+
+                chars
+                    .chunks(2)
+                    .map(|pair| (Flag::from(pair[0]) << 16) | Flag::from(pair[1]))
+                    .collect()
+            },
+            Self::Numeric => flags
+                .split(',')
+                .map(|flag| {
+                    flag.trim()
+                        .parse()
+                        .map_err(|_| DictionaryError::InvalidFlag)
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        flags.sort_unstable();
+        flags.dedup();
+        Ok(flags)
+    }
+
+    pub fn parse_flag(&self, flag: &str) -> Result<Flag, DictionaryError> {
+        match self.parse_flags(flag)?.as_slice() {
+            [flag] => Ok(*flag),
+            _ => Err(DictionaryError::InvalidFlag),
+        }
+    }
+}
+
+/// One character of an affix condition
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ConditionPart {
+    /// `.`, matches any character
+    Any,
+
+    /// A literal character
+    Char(char),
+
+    /// `[abc]` or `[^abc]`
+    Set { chars: Vec<char>, is_negated: bool },
+}
+
+impl ConditionPart {
+    #[must_use]
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Char(expected) => c == *expected,
+            Self::Set { chars, is_negated } => chars.contains(&c) != *is_negated,
+        }
+    }
+}
+
+/// The characters that the stem must start (for prefixes) or end (for suffixes) with,
+/// written like a simplified regular expression
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(super) struct Condition {
+    parts: Vec<ConditionPart>,
+}
+
+impl Condition {
+    fn parse(condition: &str) -> Result<Self, DictionaryError> {
+        let mut parts = vec![];
+        let mut chars = condition.chars();
+
+        while let Some(c) = chars.next() {
+            let part = match c {
+                '.' => ConditionPart::Any,
+                '[' => {
+                    let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                    let is_negated = set.first() == Some(&'^');
+                    if is_negated {
+                        set.remove(0);
+                    }
+
+                    ConditionPart::Set {
+                        chars: set,
+                        is_negated,
+                    }
+                },
+                ']' => return Err(DictionaryError::InvalidCondition),
+                c => ConditionPart::Char(c),
+            };
+            parts.push(part);
+        }
+
+        // A condition of "." matches every stem, including empty ones
+        if parts == [ConditionPart::Any] {
+            parts.clear();
+        }
+
+        Ok(Self { parts })
+    }
+
+    #[must_use]
+    fn matches_start(&self, stem: &str) -> bool {
+        let mut chars = stem.chars();
+        self.parts
+            .iter()
+            .all(|part| chars.next().is_some_and(|c| part.matches(c)))
+    }
+
+    #[must_use]
+    fn matches_end(&self, stem: &str) -> bool {
+        let mut chars = stem.chars().rev();
+        self.parts
+            .iter()
+            .rev()
+            .all(|part| chars.next().is_some_and(|c| part.matches(c)))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum AffixKind {
+    Prefix,
+    Suffix,
+}
+
+/// A rule that derives words from a stem, like adding "s" to form the plural
+#[derive(Clone, Debug)]
+pub(super) struct Affix {
+    pub kind: AffixKind,
+
+    /// The flag that stems must have for the rule to apply to them
+    pub flag: Flag,
+
+    /// Whether the rule can be combined with an affix of the other kind
+    pub cross_product: bool,
+
+    /// The characters that are removed from the stem before the affix is added
+    strip: String,
+
+    /// The characters that are added to the stem
+    pub affix: String,
+    condition: Condition,
+}
+
+impl Affix {
+    /// Undo the rule, returning the stem that the word was derived from
+    ///
+    /// Returns `None` if the word could not have been derived with this rule.
+    #[must_use]
+    pub fn remove_from(&self, word: &str) -> Option<String> {
+        let stem = match self.kind {
+            AffixKind::Prefix => {
+                let remainder = word.strip_prefix(&self.affix)?;
+                format!("{}{remainder}", self.strip)
+            },
+            AffixKind::Suffix => {
+                let remainder = word.strip_suffix(&self.affix)?;
+                format!("{remainder}{}", self.strip)
+            },
+        };
+
+        // Affixes can't form words on their own
+        if stem.is_empty() {
+            return None;
+        }
+
+        let matches_condition = match self.kind {
+            AffixKind::Prefix => self.condition.matches_start(&stem),
+            AffixKind::Suffix => self.condition.matches_end(&stem),
+        };
+        matches_condition.then_some(stem)
+    }
+}
+
+/// Replacements that fix common typing errors, used for suggestions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct Replacement {
+    pub from: String,
+    pub to: String,
+}
+
+/// The contents of an affix file
+#[derive(Clone, Debug, Default)]
+pub(super) struct AffixFile {
+    pub flag_type: FlagType,
+
+    /// Flag sets that are referred to by their (1-based) index, set with `AF`
+    pub flag_aliases: Vec<Vec<Flag>>,
+    pub prefixes: Vec<Affix>,
+    pub suffixes: Vec<Affix>,
+
+    /// The characters that are tried when generating suggestions, most common first
+    pub try_chars: String,
+
+    /// Groups of neighboring keys on the keyboard, separated by `|`
+    pub keyboard: String,
+    pub replacements: Vec<Replacement>,
+
+    /// Words with this flag are not correct in any form
+    pub forbidden_word: Option<Flag>,
+
+    /// Words with this flag are never suggested
+    pub no_suggest: Option<Flag>,
+
+    /// Words with this flag are only correct with an affix
+    pub need_affix: Option<Flag>,
+
+    /// Words with this flag must be written exactly as in the dictionary, even at the start of a sentence
+    pub keep_case: Option<Flag>,
+}
+
+impl AffixFile {
+    /// Find the value of the `SET` option, which determines how the files should be decoded
+    ///
+    /// This can be used before the file is decoded, since the option itself is always ascii.
+    #[must_use]
+    pub fn declared_encoding(bytes: &[u8]) -> Option<String> {
+        bytes
+            .split(|&byte| byte == b'\n')
+            .filter_map(|line| line.strip_prefix(b"SET"))
+            .find_map(|value| {
+                let value = String::from_utf8_lossy(value);
+                let value = value.trim();
+                (!value.is_empty()).then(|| value.to_string())
+            })
+    }
+
+    pub fn parse(source: &str) -> Result<Self, DictionaryError> {
+        let mut affix_file = Self::default();
+        let mut lines = source.lines().map(|line| {
+            // Everything after a "#" at the start of a line is a comment
+            if line.trim_start().starts_with('#') {
+                ""
+            } else {
+                line
+            }
+        });
+
+        // Options like FLAG change how the following lines are parsed, so the file is read in order
+        while let Some(line) = lines.next() {
+            let mut fields = line.split_whitespace();
+            let Some(option) = fields.next() else {
+                continue;
+            };
+            let mut value = || fields.next().ok_or(DictionaryError::MissingValue);
+
+            match option {
+                // The encoding was already determined by [Self::declared_encoding]
+                "SET" => {},
+                "FLAG" => {
+                    affix_file.flag_type = match value()? {
+                        "long" => FlagType::Long,
+                        "num" => FlagType::Numeric,
+                        "UTF-8" => FlagType::Short,
+                        _ => return Err(DictionaryError::InvalidFlag),
+                    }
+                },
+                "TRY" => affix_file.try_chars = value()?.to_string(),
+                "KEY" => affix_file.keyboard = value()?.to_string(),
+                "FORBIDDENWORD" => {
+                    affix_file.forbidden_word = Some(affix_file.flag_type.parse_flag(value()?)?)
+                },
+                "NOSUGGEST" => {
+                    affix_file.no_suggest = Some(affix_file.flag_type.parse_flag(value()?)?)
+                },
+                "NEEDAFFIX" | "PSEUDOROOT" => {
+                    affix_file.need_affix = Some(affix_file.flag_type.parse_flag(value()?)?)
+                },
+                "KEEPCASE" => {
+                    affix_file.keep_case = Some(affix_file.flag_type.parse_flag(value()?)?)
+                },
+                "AF" => {
+                    // The first AF line contains the number of aliases, the following ones the aliases
+                    let count: usize = value()?
+                        .parse()
+                        .map_err(|_| DictionaryError::InvalidCount)?;
+                    for _ in 0..count {
+                        let line = lines.next().ok_or(DictionaryError::MissingValue)?;
+                        let flags = line
+                            .split_whitespace()
+                            .nth(1)
+                            .ok_or(DictionaryError::MissingValue)?;
+                        let flags = affix_file.flag_type.parse_flags(flags)?;
+                        affix_file.flag_aliases.push(flags);
+                    }
+                },
+                "REP" => {
+                    let count: usize = value()?
+                        .parse()
+                        .map_err(|_| DictionaryError::InvalidCount)?;
+                    for _ in 0..count {
+                        let line = lines.next().ok_or(DictionaryError::MissingValue)?;
+                        let mut fields = line.split_whitespace().skip(1);
+                        let (Some(from), Some(to)) = (fields.next(), fields.next()) else {
+                            return Err(DictionaryError::MissingValue);
+                        };
+
+                        // Underscores stand for spaces
+                        affix_file.replacements.push(Replacement {
+                            from: from.replace('_', " "),
+                            to: to.replace('_', " "),
+                        });
+                    }
+                },
+                "PFX" | "SFX" => {
+                    let kind = if option == "PFX" {
+                        AffixKind::Prefix
+                    } else {
+                        AffixKind::Suffix
+                    };
+                    let flag = affix_file.flag_type.parse_flag(value()?)?;
+                    let cross_product = value()? == "Y";
+                    let count: usize = value()?
+                        .parse()
+                        .map_err(|_| DictionaryError::InvalidCount)?;
+
+                    for _ in 0..count {
+                        let line = lines.next().ok_or(DictionaryError::MissingValue)?;
+                        let affix = affix_file.parse_affix_rule(line, kind, flag, cross_product)?;
+
+                        match kind {
+                            AffixKind::Prefix => affix_file.prefixes.push(affix),
+                            AffixKind::Suffix => affix_file.suffixes.push(affix),
+                        }
+                    }
+                },
+
+                // Compounding, morphological analysis and the remaining suggestion options are not supported
+                _ => {},
+            }
+        }
+
+        Ok(affix_file)
+    }
+
+    /// Parse a line like `SFX A y ies [^aeiou]y`
+    fn parse_affix_rule(
+        &self,
+        line: &str,
+        kind: AffixKind,
+        flag: Flag,
+        cross_product: bool,
+    ) -> Result<Affix, DictionaryError> {
+        let mut fields = line.split_whitespace().skip(2);
+        let (Some(strip), Some(affix)) = (fields.next(), fields.next()) else {
+            return Err(DictionaryError::MissingValue);
+        };
+        let condition = fields.next().unwrap_or(".");
+
+        // "0" stands for the empty string, continuation flags after a slash are ignored
+        let strip = if strip == "0" { "" } else { strip };
+        let affix = affix.split_once('/').map_or(affix, |(affix, _)| affix);
+        let affix = if affix == "0" { "" } else { affix };
+
+        Ok(Affix {
+            kind,
+            flag,
+            cross_product,
+            strip: strip.to_string(),
+            affix: affix.to_string(),
+            condition: Condition::parse(condition)?,
+        })
+    }
+
+    /// Resolve the flags of a dictionary entry, which might be an alias
+    pub fn resolve_flags(&self, flags: &str) -> Result<Vec<Flag>, DictionaryError> {
+        if self.flag_aliases.is_empty() {
+            return self.flag_type.parse_flags(flags);
+        }
+
+        let index: usize = flags.parse().map_err(|_| DictionaryError::InvalidFlag)?;
+        index
+            .checked_sub(1)
+            .and_then(|index| self.flag_aliases.get(index))
+            .cloned()
+            .ok_or(DictionaryError::InvalidFlag)
+    }
+
+    /// Group the suffixes by the text they add, so they can be looked up by the ending of a word
+    #[must_use]
+    pub fn suffixes_by_ending(&self) -> HashMap<String, Vec<usize>> {
+        let mut suffixes: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, suffix) in self.suffixes.iter().enumerate() {
+            suffixes
+                .entry(suffix.affix.clone())
+                .or_default()
+                .push(index);
+        }
+        suffixes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conditions() {
+        let condition = Condition::parse("[^aeiou]y").unwrap();
+        assert!(condition.matches_end("fly"));
+        assert!(!condition.matches_end("play"));
+        assert!(!condition.matches_end("y"));
+
+        let condition = Condition::parse("re").unwrap();
+        assert!(condition.matches_start("read"));
+        assert!(!condition.matches_start("r"));
+
+        assert!(Condition::parse(".").unwrap().matches_end(""));
+    }
+
+    #[test]
+    fn flag_types() {
+        assert_eq!(FlagType::Short.parse_flags("BA").unwrap(), [65, 66]);
+        assert_eq!(
+            FlagType::Long.parse_flags("AaBb").unwrap(),
+            [(65 << 16) | 97, (66 << 16) | 98]
+        );
+        assert_eq!(FlagType::Numeric.parse_flags("12,3").unwrap(), [3, 12]);
+        assert!(FlagType::Long.parse_flags("A").is_err());
+    }
+
+    #[test]
+    fn remove_affix() {
+        let affix_file = AffixFile::parse(
+            "SFX S Y 2
+SFX S y ies [^aeiou]y
+SFX S 0 s [aeiou]y",
+        )
+        .unwrap();
+
+        let [consonant, vowel] = &affix_file.suffixes[..] else {
+            panic!("expected two suffix rules");
+        };
+        assert_eq!(consonant.remove_from("flies").as_deref(), Some("fly"));
+        assert_eq!(consonant.remove_from("plaies"), None);
+        assert_eq!(vowel.remove_from("plays").as_deref(), Some("play"));
+        assert_eq!(vowel.remove_from("flys"), None);
+    }
+}
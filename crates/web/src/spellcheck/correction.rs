@@ -0,0 +1,70 @@
+use std::ops::Range;
+
+use settings::SETTINGS;
+
+use crate::dom::{dom_objects::HtmlTextAreaElement, DomPtr};
+
+/// A misspelled word in an editable element, together with the words that could replace it
+///
+/// Embedders show the suggestions in a context menu and pass the one that the user picked to
+/// [BrowsingContext::apply_spelling_correction](crate::BrowsingContext::apply_spelling_correction).
+#[derive(Clone)]
+pub struct SpellingCorrection {
+    textarea: DomPtr<HtmlTextAreaElement>,
+
+    /// The byte range of the word within the value of the textarea
+    range: Range<usize>,
+    word: String,
+    suggestions: Vec<String>,
+}
+
+impl SpellingCorrection {
+    /// The misspelled word at the given byte offset of the textarea, if any
+    #[must_use]
+    pub(crate) fn at_offset(textarea: DomPtr<HtmlTextAreaElement>, offset: usize) -> Option<Self> {
+        if !textarea.borrow().is_mutable() || !super::is_enabled_for(&textarea.clone().upcast()) {
+            return None;
+        }
+
+        let language = SETTINGS.preferences().language.clone();
+        let dictionary = super::dictionary_for_language(&language)?;
+
+        let text = textarea.borrow().editor().text().to_owned();
+        let range = super::misspelled_words(&text, &dictionary)
+            .into_iter()
+            .find(|range| (range.start..=range.end).contains(&offset))?;
+        let word = text[range.clone()].to_owned();
+        let suggestions = dictionary.suggest(&word);
+
+        Some(Self {
+            textarea,
+            range,
+            word,
+            suggestions,
+        })
+    }
+
+    #[must_use]
+    pub(crate) fn textarea(&self) -> &DomPtr<HtmlTextAreaElement> {
+        &self.textarea
+    }
+
+    #[must_use]
+    pub(crate) fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The word as it was written
+    #[must_use]
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    /// Words that the user might have meant, best guesses first
+    ///
+    /// This is empty if the dictionary does not know any similar words.
+    #[must_use]
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+}
@@ -0,0 +1,323 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use encodings::Encoding;
+
+use super::{
+    affix::{Affix, AffixFile, Flag},
+    DictionaryError,
+};
+
+/// A hunspell dictionary
+///
+/// <https://man.archlinux.org/man/hunspell.5>
+#[derive(Clone, Debug)]
+pub struct Dictionary {
+    pub(super) affixes: AffixFile,
+
+    /// Indices into the suffixes of the affix file, by the text that they add
+    suffixes_by_ending: HashMap<String, Vec<usize>>,
+
+    /// The flags of every word in the dictionary
+    ///
+    /// A word can appear more than once with different flags (homonyms).
+    words: HashMap<String, Vec<Vec<Flag>>>,
+}
+
+/// How the casing of a word was changed before looking it up
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CaseFolding {
+    /// The word is looked up as it was written
+    None,
+
+    /// The word was converted to lowercase, for example because it appears at the start of a sentence
+    Folded,
+}
+
+/// Decode an affix or dictionary file with the encoding from the `SET` option
+fn decode(bytes: &[u8], label: Option<&str>) -> Result<String, DictionaryError> {
+    let Some(label) = label else {
+        // Dictionaries without a SET option are ISO-8859-1 encoded
+        return Ok(bytes.iter().copied().map(char::from).collect());
+    };
+
+    let encoding: Encoding = label
+        .parse()
+        .map_err(|_| DictionaryError::UnsupportedEncoding)?;
+
+    match encodings::decode(bytes, encoding) {
+        Ok(text) => Ok(text),
+
+        // FIXME: Decoders for most legacy encodings are still missing, ISO-8859-1 (which
+        //        maps to windows-1252) is simple enough to decode here.
+        Err(_) if encoding == Encoding::windows_1252 => {
+            Ok(bytes.iter().copied().map(char::from).collect())
+        },
+        Err(_) => Err(DictionaryError::UnsupportedEncoding),
+    }
+}
+
+impl Dictionary {
+    /// Read a dictionary from an affix file and a word list
+    pub fn load(aff_path: &Path, dic_path: &Path) -> Result<Self, DictionaryError> {
+        let affixes = fs::read(aff_path)?;
+        let words = fs::read(dic_path)?;
+        Self::parse(&affixes, &words)
+    }
+
+    pub fn parse(affixes: &[u8], words: &[u8]) -> Result<Self, DictionaryError> {
+        let encoding = AffixFile::declared_encoding(affixes);
+        let affixes = AffixFile::parse(&decode(affixes, encoding.as_deref())?)?;
+        let source = decode(words, encoding.as_deref())?;
+
+        // The first line contains the (approximate) number of words
+        let mut words: HashMap<String, Vec<Vec<Flag>>> = HashMap::new();
+        for line in source.lines().skip(1) {
+            // Everything after the word and its flags is morphological information
+            let Some(entry) = line.split_whitespace().next() else {
+                continue;
+            };
+
+            let (word, flags) = match entry.split_once('/') {
+                Some((word, flags)) => (word, affixes.resolve_flags(flags)?),
+                None => (entry, vec![]),
+            };
+
+            words.entry(word.to_string()).or_default().push(flags);
+        }
+
+        Ok(Self {
+            suffixes_by_ending: affixes.suffixes_by_ending(),
+            affixes,
+            words,
+        })
+    }
+
+    /// Whether the word is spelled correctly
+    ///
+    /// Words that start with an uppercase letter or are written in all caps are also accepted
+    /// if the dictionary only contains their lowercase form.
+    #[must_use]
+    pub fn check(&self, word: &str) -> bool {
+        // Dictionaries use ascii apostrophes
+        let word = word.replace('’', "'");
+
+        if self.is_forbidden(&word) {
+            return false;
+        }
+
+        if self.check_form(&word, CaseFolding::None) {
+            return true;
+        }
+
+        let mut chars = word.chars();
+        let Some(first) = chars.next() else {
+            return false;
+        };
+        let rest = chars.as_str();
+
+        let is_capitalized = first.is_uppercase() && !rest.chars().any(char::is_uppercase);
+        let is_all_caps = !word.chars().any(char::is_lowercase);
+
+        if is_all_caps {
+            // "HELLO" might be "Hello" or "hello"
+            let capitalized: String = first
+                .to_uppercase()
+                .chain(rest.to_lowercase().chars())
+                .collect();
+            if self.check_form(&capitalized, CaseFolding::Folded) {
+                return true;
+            }
+        }
+
+        if is_capitalized || is_all_caps {
+            let lowercase = word.to_lowercase();
+            return !self.is_forbidden(&lowercase)
+                && self.check_form(&lowercase, CaseFolding::Folded);
+        }
+
+        false
+    }
+
+    /// Whether the word is marked with the `FORBIDDENWORD` flag
+    #[must_use]
+    fn is_forbidden(&self, word: &str) -> bool {
+        let Some(forbidden_word) = self.affixes.forbidden_word else {
+            return false;
+        };
+
+        self.words.get(word).is_some_and(|homonyms| {
+            homonyms
+                .iter()
+                .any(|flags| flags.binary_search(&forbidden_word).is_ok())
+        })
+    }
+
+    /// Whether the word is marked with the `NOSUGGEST` flag
+    #[must_use]
+    pub(super) fn is_never_suggested(&self, word: &str) -> bool {
+        let Some(no_suggest) = self.affixes.no_suggest else {
+            return false;
+        };
+
+        self.words.get(word).is_some_and(|homonyms| {
+            homonyms
+                .iter()
+                .any(|flags| flags.binary_search(&no_suggest).is_ok())
+        })
+    }
+
+    /// The flag sets of the entries for `stem` that can be used with the given casing
+    fn entries<'a>(
+        &'a self,
+        stem: &str,
+        case_folding: CaseFolding,
+    ) -> impl Iterator<Item = &'a [Flag]> + 'a {
+        let forbidden_word = self.affixes.forbidden_word;
+        let keep_case = self.affixes.keep_case;
+        let has_flag = |flags: &[Flag], flag: Option<Flag>| {
+            flag.is_some_and(|flag| flags.binary_search(&flag).is_ok())
+        };
+
+        self.words
+            .get(stem)
+            .into_iter()
+            .flatten()
+            .map(Vec::as_slice)
+            .filter(move |flags| {
+                !has_flag(flags, forbidden_word)
+                    && (case_folding == CaseFolding::None || !has_flag(flags, keep_case))
+            })
+    }
+
+    /// Whether `stem` is in the dictionary and can be combined with all of the given affixes
+    #[must_use]
+    fn has_stem(&self, stem: &str, affixes: &[&Affix], case_folding: CaseFolding) -> bool {
+        let need_affix = self.affixes.need_affix;
+
+        self.entries(stem, case_folding).any(|flags| {
+            if affixes.is_empty() {
+                // Stems with the NEEDAFFIX flag are not words on their own
+                return need_affix.is_none_or(|flag| flags.binary_search(&flag).is_err());
+            }
+
+            affixes
+                .iter()
+                .all(|affix| flags.binary_search(&affix.flag).is_ok())
+        })
+    }
+
+    /// Whether the word is in the dictionary or can be derived from a word in the dictionary
+    #[must_use]
+    fn check_form(&self, word: &str, case_folding: CaseFolding) -> bool {
+        if self.has_stem(word, &[], case_folding) {
+            return true;
+        }
+
+        // Words with a prefix
+        for prefix in &self.affixes.prefixes {
+            if let Some(stem) = prefix.remove_from(word)
+                && self.has_stem(&stem, &[prefix], case_folding)
+            {
+                return true;
+            }
+        }
+
+        // Words with a suffix, and possibly a prefix too
+        let endings = word
+            .char_indices()
+            .map(|(index, _)| &word[index..])
+            .chain(std::iter::once(""));
+        for ending in endings {
+            let Some(suffixes) = self.suffixes_by_ending.get(ending) else {
+                continue;
+            };
+
+            for suffix in suffixes.iter().map(|&index| &self.affixes.suffixes[index]) {
+                let Some(stem) = suffix.remove_from(word) else {
+                    continue;
+                };
+
+                if self.has_stem(&stem, &[suffix], case_folding) {
+                    return true;
+                }
+
+                if !suffix.cross_product {
+                    continue;
+                }
+
+                for prefix in self
+                    .affixes
+                    .prefixes
+                    .iter()
+                    .filter(|prefix| prefix.cross_product)
+                {
+                    if let Some(stem) = prefix.remove_from(&stem)
+                        && self.has_stem(&stem, &[prefix, suffix], case_folding)
+                    {
+                        return true;
+                    }
+                }
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::dictionary;
+
+    #[test]
+    fn words_and_affixes() {
+        let dictionary = dictionary();
+
+        assert!(dictionary.check("hello"));
+        assert!(dictionary.check("flies"));
+        assert!(dictionary.check("plays"));
+        assert!(dictionary.check("played"));
+        assert!(dictionary.check("believed"));
+        assert!(dictionary.check("unbelieved"));
+        assert!(dictionary.check("undo"));
+
+        assert!(!dictionary.check("flys"));
+        assert!(!dictionary.check("helloed"));
+        assert!(!dictionary.check("unhello"));
+        assert!(!dictionary.check("undone"));
+    }
+
+    #[test]
+    fn casing() {
+        let dictionary = dictionary();
+
+        assert!(dictionary.check("Hello"));
+        assert!(dictionary.check("HELLO"));
+        assert!(!dictionary.check("hELLO"));
+        assert!(dictionary.check("I"));
+        assert!(!dictionary.check("i"));
+    }
+
+    #[test]
+    fn forbidden_words() {
+        let dictionary = dictionary();
+
+        assert!(!dictionary.check("wellness"));
+        assert!(!dictionary.check("Wellness"));
+
+        // Words that are never suggested are still correct
+        assert!(dictionary.check("damn"));
+    }
+
+    #[test]
+    fn apostrophes() {
+        let dictionary = super::super::Dictionary::parse(b"", b"1\ndon't").unwrap();
+        assert!(dictionary.check("don't"));
+        assert!(dictionary.check("don’t"));
+    }
+
+    #[test]
+    fn latin1() {
+        let dictionary = super::super::Dictionary::parse(b"SET ISO8859-1", b"1\nna\xefve").unwrap();
+        assert!(dictionary.check("naïve"));
+    }
+}
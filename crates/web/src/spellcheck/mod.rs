@@ -0,0 +1,210 @@
+//! Spellchecking for editable text
+//!
+//! Dictionaries use the hunspell format, which consists of an affix file (`.aff`)
+//! and a list of words (`.dic`). They are looked up in the directories from `$DICPATH`
+//! and in the locations that linux distributions install them to.
+//!
+//! Compounding, twofold affixes and morphological analysis are not supported, words that
+//! rely on them are reported as misspelled.
+//!
+//! <https://html.spec.whatwg.org/multipage/interaction.html#spelling-and-grammar-checking>
+
+mod affix;
+mod correction;
+mod dictionary;
+mod suggest;
+
+pub use correction::SpellingCorrection;
+pub use dictionary::Dictionary;
+
+use std::{
+    collections::HashMap,
+    env, io,
+    ops::Range,
+    path::PathBuf,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use error_derive::Error;
+
+use crate::{
+    dom::{
+        dom_objects::{HtmlElement, Node},
+        DomPtr,
+    },
+    text_iterator::WordIterator,
+};
+
+#[derive(Debug, Error)]
+pub enum DictionaryError {
+    #[msg = "failed to read dictionary"]
+    Io(io::Error),
+
+    #[msg = "unsupported dictionary encoding"]
+    UnsupportedEncoding,
+
+    #[msg = "option is missing a value"]
+    MissingValue,
+
+    #[msg = "invalid count"]
+    InvalidCount,
+
+    #[msg = "invalid flag"]
+    InvalidFlag,
+
+    #[msg = "invalid affix condition"]
+    InvalidCondition,
+}
+
+/// Directories that dictionaries are installed to, in addition to the ones from `$DICPATH`
+const SYSTEM_DICTIONARY_DIRECTORIES: [&str; 3] = [
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+];
+
+/// Dictionaries that were already loaded, by language
+///
+/// Languages without a dictionary are remembered too, so the directories are only searched once.
+static DICTIONARIES: LazyLock<Mutex<HashMap<String, Option<Arc<Dictionary>>>>> =
+    LazyLock::new(Mutex::default);
+
+/// The dictionary for a language tag like `en-US`
+///
+/// If there is no dictionary for the region, the dictionary of the language is used.
+#[must_use]
+pub fn dictionary_for_language(language: &str) -> Option<Arc<Dictionary>> {
+    let mut dictionaries = DICTIONARIES.lock().expect("dictionary cache was poisoned");
+
+    dictionaries
+        .entry(language.to_string())
+        .or_insert_with(|| {
+            // Dictionaries are named like "en_US.dic"
+            let name = language.replace('-', "_");
+            let fallback = name.split('_').next().map(str::to_string);
+
+            std::iter::once(name)
+                .chain(fallback)
+                .find_map(|name| load_dictionary(&name))
+                .map(Arc::new)
+        })
+        .clone()
+}
+
+fn dictionary_directories() -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = env::var_os("DICPATH")
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default();
+
+    directories.extend(SYSTEM_DICTIONARY_DIRECTORIES.iter().map(PathBuf::from));
+    directories
+}
+
+fn load_dictionary(name: &str) -> Option<Dictionary> {
+    for directory in dictionary_directories() {
+        let aff_path = directory.join(format!("{name}.aff"));
+        let dic_path = directory.join(format!("{name}.dic"));
+        if !aff_path.is_file() || !dic_path.is_file() {
+            continue;
+        }
+
+        match Dictionary::load(&aff_path, &dic_path) {
+            Ok(dictionary) => {
+                log::info!("Loaded dictionary from {}", dic_path.display());
+                return Some(dictionary);
+            },
+            Err(error) => {
+                log::error!("Failed to load dictionary {}: {error}", dic_path.display());
+            },
+        }
+    }
+
+    None
+}
+
+/// Whether the text of `node` should be checked for spelling errors
+///
+/// Elements whose `spellcheck` attribute is in the default state inherit the state of
+/// their parent. Spellchecking is enabled by default.
+///
+/// <https://html.spec.whatwg.org/multipage/interaction.html#concept-spellcheck-default>
+#[must_use]
+pub(crate) fn is_enabled_for(node: &DomPtr<Node>) -> bool {
+    let mut current = Some(node.clone());
+    while let Some(node) = current {
+        if let Some(element) = node.try_into_type::<HtmlElement>()
+            && let Some(spellcheck) = element.borrow().spellcheck_attribute()
+        {
+            return spellcheck;
+        }
+        current = node.borrow().parent_node();
+    }
+
+    true
+}
+
+/// The byte ranges of the words in `text` that are not in the dictionary
+///
+/// Words that contain digits, like "3rd" or "mp3", are never reported.
+#[must_use]
+pub fn misspelled_words(text: &str, dictionary: &Dictionary) -> Vec<Range<usize>> {
+    WordIterator::new(text)
+        .filter(|range| {
+            let word = &text[range.clone()];
+            !word.chars().any(char::is_numeric) && !dictionary.check(word)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) const AFFIXES: &str = "SET UTF-8
+TRY esianrtolcdugmphbyfvkwz'
+KEY qwertyuiop|asdfghjkl|zxcvbnm
+NOSUGGEST !
+FORBIDDENWORD *
+REP 1
+REP f ph
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX S Y 3
+SFX S y ies [^aeiou]y
+SFX S 0 s [aeiou]y
+SFX S 0 s [^y]
+
+SFX D Y 2
+SFX D 0 d e
+SFX D 0 ed [^e]";
+
+    pub(super) const WORDS: &str = "11
+I
+the
+hello
+world
+fly/S
+play/SD
+do/U
+phone/S
+believe/DU
+damn/!
+wellness/*";
+
+    pub(super) fn dictionary() -> Dictionary {
+        Dictionary::parse(AFFIXES.as_bytes(), WORDS.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn find_misspelled_words() {
+        let text = "Helo world, I beleive the 3rd fly plays!";
+        let misspelled: Vec<&str> = misspelled_words(text, &dictionary())
+            .into_iter()
+            .map(|range| &text[range])
+            .collect();
+
+        assert_eq!(misspelled, ["Helo", "beleive"]);
+    }
+}
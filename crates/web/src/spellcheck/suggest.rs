@@ -0,0 +1,227 @@
+//! Suggestions for misspelled words
+//!
+//! Candidates are generated by undoing common typing errors, like swapped or missing
+//! letters, and kept if they are spelled correctly.
+
+use super::Dictionary;
+
+/// The maximum number of suggestions for a single word
+const MAX_SUGGESTIONS: usize = 5;
+
+/// The casing of the misspelled word, which is applied to the suggestions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Casing {
+    Lowercase,
+    Capitalized,
+    AllCaps,
+}
+
+impl Casing {
+    #[must_use]
+    fn of(word: &str) -> Self {
+        let first = word.chars().next().unwrap_or_default();
+
+        if word.chars().count() > 1 && !word.chars().any(char::is_lowercase) {
+            Self::AllCaps
+        } else if first.is_uppercase() {
+            Self::Capitalized
+        } else {
+            Self::Lowercase
+        }
+    }
+
+    #[must_use]
+    fn apply(&self, word: &str) -> String {
+        match self {
+            Self::Lowercase => word.to_string(),
+            Self::Capitalized => {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .into_iter()
+                    .flat_map(char::to_uppercase)
+                    .chain(chars)
+                    .collect()
+            },
+            Self::AllCaps => word.to_uppercase(),
+        }
+    }
+}
+
+/// Collects the correctly spelled candidates, in the order they were found
+struct Suggestions<'a> {
+    dictionary: &'a Dictionary,
+    suggestions: Vec<String>,
+}
+
+impl Suggestions<'_> {
+    fn is_full(&self) -> bool {
+        self.suggestions.len() >= MAX_SUGGESTIONS
+    }
+
+    fn try_candidate(&mut self, candidate: String) {
+        if self.is_full() || self.suggestions.contains(&candidate) {
+            return;
+        }
+
+        // Phrases like "a lot" are suggested if all of their words are correct
+        let is_correct = candidate.split(' ').all(|word| {
+            !word.is_empty()
+                && self.dictionary.check(word)
+                && !self.dictionary.is_never_suggested(word)
+        });
+
+        if is_correct {
+            self.suggestions.push(candidate);
+        }
+    }
+}
+
+impl Dictionary {
+    /// Words that the user might have meant to type instead of `word`, best guesses first
+    #[must_use]
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let casing = Casing::of(word);
+        let word = word.replace('’', "'").to_lowercase();
+        let chars: Vec<char> = word.chars().collect();
+
+        let mut suggestions = Suggestions {
+            dictionary: self,
+            suggestions: vec![],
+        };
+
+        // Only the casing was wrong, like "hELLO"
+        suggestions.try_candidate(word.clone());
+
+        // Replacements from the dictionary, like "f" -> "ph"
+        for replacement in &self.affixes.replacements {
+            for (index, _) in word.match_indices(&replacement.from) {
+                let candidate = format!(
+                    "{}{}{}",
+                    &word[..index],
+                    replacement.to,
+                    &word[index + replacement.from.len()..]
+                );
+                suggestions.try_candidate(candidate);
+            }
+        }
+
+        // A key next to the intended one was pressed
+        for (index, &c) in chars.iter().enumerate() {
+            for neighbour in self.keyboard_neighbours(c) {
+                let mut candidate = chars.clone();
+                candidate[index] = neighbour;
+                suggestions.try_candidate(candidate.into_iter().collect());
+            }
+        }
+
+        // Two letters were swapped
+        for index in 1..chars.len() {
+            let mut candidate = chars.clone();
+            candidate.swap(index - 1, index);
+            suggestions.try_candidate(candidate.into_iter().collect());
+        }
+
+        // A letter too much was typed
+        for index in 0..chars.len() {
+            let mut candidate = chars.clone();
+            candidate.remove(index);
+            suggestions.try_candidate(candidate.into_iter().collect());
+        }
+
+        // A letter is missing
+        let try_chars: Vec<char> = self.affixes.try_chars.to_lowercase().chars().collect();
+        for index in 0..=chars.len() {
+            for &c in &try_chars {
+                let mut candidate = chars.clone();
+                candidate.insert(index, c);
+                suggestions.try_candidate(candidate.into_iter().collect());
+            }
+        }
+
+        // A wrong letter was typed
+        for index in 0..chars.len() {
+            for &c in try_chars.iter().filter(|&&c| c != chars[index]) {
+                let mut candidate = chars.clone();
+                candidate[index] = c;
+                suggestions.try_candidate(candidate.into_iter().collect());
+            }
+        }
+
+        // The space between two words is missing
+        for index in 1..chars.len() {
+            let (first, second) = chars.split_at(index);
+            let candidate = format!(
+                "{} {}",
+                first.iter().collect::<String>(),
+                second.iter().collect::<String>()
+            );
+            suggestions.try_candidate(candidate);
+        }
+
+        let mut suggestions: Vec<String> = suggestions
+            .suggestions
+            .into_iter()
+            .map(|suggestion| casing.apply(&suggestion))
+            .collect();
+        suggestions.dedup();
+        suggestions
+    }
+
+    /// The keys that are next to `c` on the keyboard, from the `KEY` option
+    fn keyboard_neighbours(&self, c: char) -> Vec<char> {
+        let mut neighbours = vec![];
+
+        for row in self.affixes.keyboard.split('|') {
+            let keys: Vec<char> = row.chars().collect();
+            for (index, &key) in keys.iter().enumerate() {
+                if key != c {
+                    continue;
+                }
+
+                if let Some(&previous) = index.checked_sub(1).and_then(|index| keys.get(index)) {
+                    neighbours.push(previous);
+                }
+                if let Some(&next) = keys.get(index + 1) {
+                    neighbours.push(next);
+                }
+            }
+        }
+
+        neighbours
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::dictionary;
+
+    #[test]
+    fn suggestions() {
+        let dictionary = dictionary();
+
+        assert_eq!(dictionary.suggest("helo"), ["hello"]);
+        assert_eq!(dictionary.suggest("wprld"), ["world"]);
+        assert_eq!(dictionary.suggest("beleive"), ["believe"]);
+        assert_eq!(dictionary.suggest("fone"), ["phone"]);
+        assert_eq!(dictionary.suggest("helloworld"), ["hello world"]);
+        assert!(dictionary.suggest("xyzzy").is_empty());
+    }
+
+    #[test]
+    fn casing_is_kept() {
+        let dictionary = dictionary();
+
+        assert_eq!(dictionary.suggest("Helo"), ["Hello"]);
+        assert_eq!(dictionary.suggest("WROLD"), ["WORLD"]);
+        assert_eq!(dictionary.suggest("hELLO"), ["hello"]);
+    }
+
+    #[test]
+    fn never_suggested_words() {
+        let dictionary = dictionary();
+
+        assert!(dictionary.check("damn"));
+        assert!(dictionary.suggest("dammn").is_empty());
+    }
+}
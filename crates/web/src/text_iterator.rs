@@ -0,0 +1,111 @@
+//! Splitting text into words
+//!
+//! This follows the [word boundary rules](https://www.unicode.org/reports/tr29/#Word_Boundary_Rules)
+//! of UAX #29 for alphabetic scripts: Letters and digits form words, apostrophes may
+//! appear between letters and periods or commas between digits. Everything else separates words.
+//!
+//! FIXME: Scripts without spaces between words, like Thai or Chinese, need a dictionary to be segmented
+
+use std::ops::Range;
+
+/// Iterates over the byte ranges of the words in a text
+///
+/// Whitespace and punctuation between the words is skipped.
+#[derive(Clone, Debug)]
+pub(crate) struct WordIterator<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> WordIterator<'a> {
+    #[must_use]
+    pub const fn new(text: &'a str) -> Self {
+        Self { text, position: 0 }
+    }
+}
+
+/// Whether the character can be part of a word, like `ALetter` or `Numeric` in UAX #29
+#[must_use]
+fn is_word_character(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Characters that don't break a word if they appear between two letters (`MidLetter` and `MidNumLet`)
+///
+/// Periods are excluded, otherwise sentences that lack a space after the period would be joined.
+#[must_use]
+fn is_mid_letter(c: char) -> bool {
+    matches!(c, '\'' | '’' | '·' | '\u{05F4}' | '\u{2027}')
+}
+
+/// Characters that don't break a word if they appear between two digits (`MidNum` and `MidNumLet`)
+#[must_use]
+fn is_mid_number(c: char) -> bool {
+    matches!(c, ',' | ';' | '.' | '\'' | '’' | '\u{066C}')
+}
+
+impl<'a> Iterator for WordIterator<'a> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = &self.text[self.position..];
+        let start = self.position + remaining.find(is_word_character)?;
+
+        let mut end = start;
+        let mut chars = self.text[start..].chars().peekable();
+        let mut previous = None;
+        while let Some(c) = chars.next() {
+            if is_word_character(c) {
+                end += c.len_utf8();
+                previous = Some(c);
+                continue;
+            }
+
+            // WB6/WB7: Don't break letters across certain punctuation
+            // WB11/WB12: Don't break digits across certain punctuation
+            let Some((previous, next)) = previous.zip(chars.peek().copied()) else {
+                break;
+            };
+            let joins_letters =
+                previous.is_alphabetic() && next.is_alphabetic() && is_mid_letter(c);
+            let joins_digits = previous.is_numeric() && next.is_numeric() && is_mid_number(c);
+            if !joins_letters && !joins_digits {
+                break;
+            }
+
+            end += c.len_utf8();
+        }
+
+        self.position = end;
+        Some(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        WordIterator::new(text).map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn split_at_whitespace_and_punctuation() {
+        assert_eq!(
+            words("  Hello, wörld! How's it going?"),
+            ["Hello", "wörld", "How's", "it", "going"]
+        );
+        assert_eq!(words("well-known"), ["well", "known"]);
+        assert_eq!(words("end.Start"), ["end", "Start"]);
+        assert!(words(" ... ").is_empty());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            words("pi is 3.14159, e is 2,718"),
+            ["pi", "is", "3.14159", "e", "is", "2,718"]
+        );
+        assert_eq!(words("don’t 'quote'"), ["don’t", "quote"]);
+    }
+}
@@ -0,0 +1,301 @@
+//! Stylesheets and scripts that the user added to websites
+//!
+//! They are read from the user content directory of the profile. `.css` files are applied
+//! at the user origin of the cascade, `.js` files run once the document was parsed, right
+//! before `DOMContentLoaded`.
+//!
+//! Both kinds of files can limit the sites they apply to with a metadata block:
+//! ```text
+//! // ==UserScript==
+//! // @include https://*.example.com/*
+//! // @exclude https://example.com/private/*
+//! // ==/UserScript==
+//! ```
+//! Stylesheets use `==UserStyle==` inside a css comment instead. Patterns are matched against
+//! the whole url, `*` matches any number of characters. Files without `@include` patterns
+//! apply to all sites.
+
+use std::{fs, io, path::Path, sync::LazyLock};
+
+use settings::SETTINGS;
+use url::URL;
+
+use crate::css::{self, Stylesheet};
+
+/// The user content of the profile in [SETTINGS]
+pub(crate) static USER_CONTENT: LazyLock<UserContent> = LazyLock::new(|| {
+    let Some(profile) = SETTINGS.profile() else {
+        return UserContent::default();
+    };
+
+    let directory = profile.user_content_directory();
+    UserContent::load(&directory).unwrap_or_else(|error| {
+        log::error!(
+            "Failed to load user content from {}: {error}",
+            directory.display()
+        );
+        UserContent::default()
+    })
+});
+
+/// The sites that a user content file applies to
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct SitePatterns {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl SitePatterns {
+    /// Read the `@include` and `@exclude` patterns from the metadata block at the start of a file
+    fn parse(source: &str) -> Self {
+        let mut patterns = Self::default();
+
+        let mut lines = source.lines();
+        if !lines
+            .by_ref()
+            .take_while(|line| line.trim().is_empty() || is_comment(line))
+            .any(is_metadata_start)
+        {
+            return patterns;
+        }
+
+        for line in lines {
+            if is_metadata_end(line) {
+                break;
+            }
+
+            let line = line.trim_start_matches(|c: char| c.is_whitespace() || c == '/' || c == '*');
+            let Some((key, value)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+
+            let value = value.trim().trim_end_matches("*/").trim().to_owned();
+            match key {
+                "@include" | "@match" => patterns.include.push(value),
+                "@exclude" => patterns.exclude.push(value),
+                _ => {},
+            }
+        }
+
+        patterns
+    }
+
+    fn matches(&self, url: &URL) -> bool {
+        let url = url.to_string();
+
+        let is_included =
+            self.include.is_empty() || self.include.iter().any(|pattern| glob_match(pattern, &url));
+        is_included && !self.exclude.iter().any(|pattern| glob_match(pattern, &url))
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+}
+
+fn is_metadata_start(line: &str) -> bool {
+    line.contains("==UserScript==") || line.contains("==UserStyle==")
+}
+
+fn is_metadata_end(line: &str) -> bool {
+    line.contains("==/UserScript==") || line.contains("==/UserStyle==")
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any (possibly empty) sequence of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+
+    for part in parts {
+        let Some(index) = remaining.find(part) else {
+            return false;
+        };
+        remaining = &remaining[index + part.len()..];
+    }
+
+    remaining.ends_with(last)
+}
+
+/// A single stylesheet or script
+#[derive(Clone, Debug)]
+struct UserContentFile {
+    /// The name of the file, used in error messages
+    name: String,
+    patterns: SitePatterns,
+    source: String,
+}
+
+impl UserContentFile {
+    fn new(name: String, source: String) -> Self {
+        Self {
+            name,
+            patterns: SitePatterns::parse(&source),
+            source,
+        }
+    }
+}
+
+/// The stylesheets and scripts in the user content directory
+#[derive(Clone, Debug, Default)]
+pub(crate) struct UserContent {
+    stylesheets: Vec<UserContentFile>,
+    scripts: Vec<UserContentFile>,
+}
+
+impl UserContent {
+    /// Read all stylesheets and scripts in the directory, in alphabetical order
+    ///
+    /// A directory that does not exist yields no user content.
+    pub(crate) fn load(directory: &Path) -> io::Result<Self> {
+        let mut user_content = Self::default();
+
+        let mut paths = match fs::read_dir(directory) {
+            Ok(entries) => entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<io::Result<Vec<_>>>()?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(user_content),
+            Err(error) => return Err(error),
+        };
+        paths.sort();
+
+        for path in paths {
+            let files = match path.extension().and_then(|extension| extension.to_str()) {
+                Some("css") => &mut user_content.stylesheets,
+                Some("js") => &mut user_content.scripts,
+                _ => continue,
+            };
+
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            files.push(UserContentFile::new(name, fs::read_to_string(&path)?));
+        }
+
+        Ok(user_content)
+    }
+
+    /// The user stylesheets that apply to the given url
+    ///
+    /// The stylesheets are numbered in order, starting at `first_index`.
+    pub(crate) fn stylesheets_for(&self, url: &URL, first_index: usize) -> Vec<Stylesheet> {
+        self.stylesheets
+            .iter()
+            .filter(|file| file.patterns.matches(url))
+            .enumerate()
+            .map(|(index, file)| {
+                let stylesheet = css::Parser::new(&file.source, css::Origin::User)
+                    .parse_stylesheet(first_index + index);
+
+                if !stylesheet.errors().is_empty() {
+                    log::warn!(
+                        "User stylesheet {} contains {} errors",
+                        file.name,
+                        stylesheet.errors().len()
+                    );
+                }
+
+                stylesheet
+            })
+            .collect()
+    }
+
+    /// The sources of the user scripts that should run on the given url
+    pub(crate) fn scripts_for<'a>(&'a self, url: &'a URL) -> impl Iterator<Item = &'a str> {
+        self.scripts
+            .iter()
+            .filter(|file| file.patterns.matches(url))
+            .map(|file| file.source.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn url(url: &str) -> URL {
+        url.parse().unwrap()
+    }
+
+    #[test]
+    fn match_glob_patterns() {
+        assert!(glob_match("https://example.com/", "https://example.com/"));
+        assert!(glob_match("*", "https://example.com/"));
+        assert!(glob_match(
+            "https://*.example.com/*",
+            "https://www.example.com/a/b"
+        ));
+        assert!(!glob_match(
+            "https://*.example.com/*",
+            "https://example.com/"
+        ));
+        assert!(glob_match(
+            "*://example.com/*.html",
+            "http://example.com/index.html"
+        ));
+        assert!(!glob_match("*.html", "index.htm"));
+        assert!(!glob_match("a*a", "a"));
+    }
+
+    #[test]
+    fn load_user_content() {
+        let directory =
+            env::temp_dir().join(format!("stormlicht-user-content-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+
+        fs::write(directory.join("all.css"), "p { color: red }").unwrap();
+        fs::write(
+            directory.join("example.css"),
+            "/* ==UserStyle==\n * @include https://example.com/*\n * ==/UserStyle== */\np { color: blue }",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("script.js"),
+            "// ==UserScript==\n// @include *://example.com/*\n// @exclude *://example.com/private/*\n// ==/UserScript==\nlet a = 1;",
+        )
+        .unwrap();
+        fs::write(directory.join("notes.txt"), "not user content").unwrap();
+
+        let user_content = UserContent::load(&directory).unwrap();
+        assert_eq!(
+            user_content.scripts[0].patterns,
+            SitePatterns {
+                include: vec!["*://example.com/*".to_string()],
+                exclude: vec!["*://example.com/private/*".to_string()],
+            }
+        );
+
+        let stylesheets = user_content.stylesheets_for(&url("https://example.com/index.html"), 3);
+        assert_eq!(stylesheets.len(), 2);
+        assert_eq!(stylesheets[0].origin(), css::Origin::User);
+        assert_eq!(stylesheets[1].index(), 4);
+        assert_eq!(
+            user_content
+                .stylesheets_for(&url("https://stormlicht.rs"), 0)
+                .len(),
+            1
+        );
+
+        let example = url("https://example.com");
+        assert_eq!(user_content.scripts_for(&example).count(), 1);
+        let private = url("https://example.com/private/page");
+        assert_eq!(user_content.scripts_for(&private).count(), 0);
+
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(UserContent::load(&directory)
+            .unwrap()
+            .stylesheets
+            .is_empty());
+    }
+}
@@ -0,0 +1,384 @@
+//! Displays the source of a document for `view-source:` URLs
+//!
+//! The source is converted to a html document that is displayed like any other
+//! page. Since the layout engine does not support `white-space: pre` yet, every line
+//! is its own block and indentation is emulated with margins. Whitespace in the middle
+//! of a line is collapsed.
+
+use std::fmt::Write;
+
+use url::URL;
+
+use crate::html::escape_into;
+
+/// The scheme used for viewing the source of a page
+pub const VIEW_SOURCE_SCHEME: &str = "view-source";
+
+/// Width of a tab character, in columns
+const TAB_WIDTH: usize = 4;
+
+/// Width of the line number column, in columns
+const LINE_NUMBER_WIDTH: usize = 6;
+
+const STYLESHEET: &str = "
+body { font-family: monospace; margin: 8px; }
+.line { clear: left; }
+.line-number { float: left; width: 5ch; color: #999999; }
+.tag { color: #881280; }
+.attribute-name { color: #994500; }
+.attribute-value { color: #1a1aa6; }
+.comment { color: #236e25; }
+.doctype { color: #4f4f4f; }
+.entity { color: #c4770d; }
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Highlight {
+    Text,
+    Tag,
+    AttributeName,
+    AttributeValue,
+    Comment,
+    Doctype,
+    Entity,
+}
+
+impl Highlight {
+    #[must_use]
+    const fn class_name(&self) -> Option<&'static str> {
+        match self {
+            Self::Text => None,
+            Self::Tag => Some("tag"),
+            Self::AttributeName => Some("attribute-name"),
+            Self::AttributeValue => Some("attribute-value"),
+            Self::Comment => Some("comment"),
+            Self::Doctype => Some("doctype"),
+            Self::Entity => Some("entity"),
+        }
+    }
+}
+
+/// Return the [URL] whose source should be displayed, or `None` if the URL is
+/// not a valid `view-source:` URL
+#[must_use]
+pub fn source_url(url: &URL) -> Option<URL> {
+    if url.scheme().as_str() != VIEW_SOURCE_SCHEME {
+        return None;
+    }
+
+    // The query (but not the fragment) belongs to the inner URL
+    let inner_url: URL = url
+        .serialize(url::ExcludeFragment::Yes)
+        .as_str()
+        .strip_prefix(VIEW_SOURCE_SCHEME)?
+        .strip_prefix(':')?
+        .parse()
+        .ok()?;
+
+    // Nested view-source URLs are not allowed
+    if inner_url.scheme().as_str() == VIEW_SOURCE_SCHEME {
+        return None;
+    }
+
+    Some(inner_url)
+}
+
+/// Create a html document that displays the given source code with line numbers
+/// and syntax highlighting
+#[must_use]
+pub fn render(url: &URL, source: &str) -> String {
+    let mut lines = vec![Line::default()];
+
+    for (highlight, text) in highlight(source) {
+        for (index, part) in text.split('\n').enumerate() {
+            if index != 0 {
+                lines.push(Line::default());
+            }
+
+            let line = lines.last_mut().expect("there is always at least one line");
+            line.push(highlight, part.strip_suffix('\r').unwrap_or(part));
+        }
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html><html><head><title>");
+    escape_into(&mut html, url.serialize(url::ExcludeFragment::No).as_str());
+    html.push_str("</title><style>");
+    html.push_str(STYLESHEET);
+    html.push_str("</style></head><body>");
+
+    for (index, line) in lines.iter().enumerate() {
+        let _ = write!(
+            html,
+            "<div class=\"line\"><div class=\"line-number\">{}</div><div style=\"margin-left: {}ch\">{}</div></div>",
+            index + 1,
+            LINE_NUMBER_WIDTH + line.indentation,
+            line.content
+        );
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+/// A single line of output
+#[derive(Default)]
+struct Line {
+    /// Width of the leading whitespace, in columns
+    indentation: usize,
+
+    /// Highlighted html content of the line, without leading whitespace
+    content: String,
+}
+
+impl Line {
+    fn push(&mut self, highlight: Highlight, mut text: &str) {
+        if self.content.is_empty() {
+            let trimmed = text.trim_start_matches([' ', '\t']);
+            for c in text[..text.len() - trimmed.len()].chars() {
+                self.indentation += if c == '\t' { TAB_WIDTH } else { 1 };
+            }
+            text = trimmed;
+        }
+
+        if text.is_empty() {
+            return;
+        }
+
+        match highlight.class_name() {
+            Some(class_name) => {
+                let _ = write!(self.content, "<span class=\"{class_name}\">");
+                escape_into(&mut self.content, text);
+                self.content.push_str("</span>");
+            },
+            None => escape_into(&mut self.content, text),
+        }
+    }
+}
+
+/// Split html source code into highlighted segments
+///
+/// This is a lot more forgiving than the actual tokenizer, it only needs to
+/// produce output that looks reasonable.
+fn highlight(source: &str) -> Vec<(Highlight, &str)> {
+    let mut segments = vec![];
+    let mut remaining = source;
+
+    while !remaining.is_empty() {
+        if remaining.starts_with("<!--") {
+            let length = remaining[4..]
+                .find("-->")
+                .map_or(remaining.len(), |end| end + 4 + 3);
+            segments.push((Highlight::Comment, take(&mut remaining, length)));
+        } else if remaining.starts_with("<!") || remaining.starts_with("<?") {
+            let length = remaining.find('>').map_or(remaining.len(), |end| end + 1);
+            segments.push((Highlight::Doctype, take(&mut remaining, length)));
+        } else if remaining.strip_prefix('<').is_some_and(|rest| {
+            rest.trim_start_matches('/')
+                .starts_with(|c: char| c.is_ascii_alphabetic())
+        }) {
+            let tag_name = highlight_tag(&mut remaining, &mut segments);
+
+            // The contents of these elements are not parsed as html
+            if tag_name.is_some_and(|name| {
+                name.eq_ignore_ascii_case("script") || name.eq_ignore_ascii_case("style")
+            }) {
+                let tag_name = tag_name.expect("checked above");
+                let closing_tag = format!("</{}", tag_name.to_ascii_lowercase());
+                let length = remaining
+                    .to_ascii_lowercase()
+                    .find(&closing_tag)
+                    .unwrap_or(remaining.len());
+                segments.push((Highlight::Text, take(&mut remaining, length)));
+            }
+        } else if let Some(reference) = remaining
+            .strip_prefix('&')
+            .filter(|rest| rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '#'))
+        {
+            let name_length = reference
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '#'))
+                .unwrap_or(reference.len());
+            let length = if reference[name_length..].starts_with(';') {
+                name_length + 2
+            } else {
+                name_length + 1
+            };
+            segments.push((Highlight::Entity, take(&mut remaining, length)));
+        } else {
+            // The text always contains at least the first character, which might take up more than one byte
+            let first_char_length = remaining.chars().next().map_or(0, char::len_utf8);
+            let length = remaining[first_char_length..]
+                .find(['<', '&'])
+                .map_or(remaining.len(), |end| end + first_char_length);
+            segments.push((Highlight::Text, take(&mut remaining, length)));
+        }
+    }
+
+    segments
+}
+
+fn take<'a>(remaining: &mut &'a str, length: usize) -> &'a str {
+    let (taken, rest) = remaining.split_at(length);
+    *remaining = rest;
+    taken
+}
+
+/// Highlight a start or end tag, returning the tag name if it was a start tag
+fn highlight_tag<'a>(
+    remaining: &mut &'a str,
+    segments: &mut Vec<(Highlight, &'a str)>,
+) -> Option<&'a str> {
+    let is_end_tag = remaining.starts_with("</");
+    let prefix_length = if is_end_tag { 2 } else { 1 };
+
+    let name_length = remaining[prefix_length..]
+        .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .unwrap_or(remaining.len() - prefix_length);
+    let (tag, rest) = remaining.split_at(prefix_length + name_length);
+    let tag_name = &tag[prefix_length..];
+    segments.push((Highlight::Tag, tag));
+    *remaining = rest;
+
+    while let Some(c) = remaining.chars().next() {
+        let (highlight, length) = match c {
+            '>' => {
+                segments.push((Highlight::Tag, &remaining[..1]));
+                *remaining = &remaining[1..];
+                break;
+            },
+            _ if remaining.starts_with("/>") => {
+                segments.push((Highlight::Tag, &remaining[..2]));
+                *remaining = &remaining[2..];
+                return None;
+            },
+            _ if c.is_ascii_whitespace() || c == '=' || c == '/' => (Highlight::Text, 1),
+            '"' | '\'' => {
+                let length = remaining[1..]
+                    .find(c)
+                    .map_or(remaining.len(), |end| end + 2);
+                (Highlight::AttributeValue, length)
+            },
+            _ => {
+                let length = remaining
+                    .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+                    .unwrap_or(remaining.len());
+
+                // Anything that directly follows a '=' is an unquoted value
+                let follows_equals_sign = segments.last().is_some_and(|(_, text)| *text == "=");
+                if follows_equals_sign {
+                    (Highlight::AttributeValue, length)
+                } else {
+                    (Highlight::AttributeName, length)
+                }
+            },
+        };
+
+        let (text, rest) = remaining.split_at(length);
+        segments.push((highlight, text));
+        *remaining = rest;
+    }
+
+    if is_end_tag {
+        None
+    } else {
+        Some(tag_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_html() {
+        let segments =
+            highlight("<!DOCTYPE html><!-- a -->\n<a href=\"/\" hidden class=foo>x &amp; y</a>");
+
+        assert_eq!(
+            segments,
+            [
+                (Highlight::Doctype, "<!DOCTYPE html>"),
+                (Highlight::Comment, "<!-- a -->"),
+                (Highlight::Text, "\n"),
+                (Highlight::Tag, "<a"),
+                (Highlight::Text, " "),
+                (Highlight::AttributeName, "href"),
+                (Highlight::Text, "="),
+                (Highlight::AttributeValue, "\"/\""),
+                (Highlight::Text, " "),
+                (Highlight::AttributeName, "hidden"),
+                (Highlight::Text, " "),
+                (Highlight::AttributeName, "class"),
+                (Highlight::Text, "="),
+                (Highlight::AttributeValue, "foo"),
+                (Highlight::Tag, ">"),
+                (Highlight::Text, "x "),
+                (Highlight::Entity, "&amp;"),
+                (Highlight::Text, " y"),
+                (Highlight::Tag, "</a"),
+                (Highlight::Tag, ">"),
+            ]
+        );
+    }
+
+    #[test]
+    fn script_contents_are_not_highlighted() {
+        let segments = highlight("<script>if (a < b) {}</SCRIPT>");
+
+        assert_eq!(
+            segments,
+            [
+                (Highlight::Tag, "<script"),
+                (Highlight::Tag, ">"),
+                (Highlight::Text, "if (a < b) {}"),
+                (Highlight::Tag, "</SCRIPT"),
+                (Highlight::Tag, ">"),
+            ]
+        );
+    }
+
+    #[test]
+    fn highlight_multibyte_text() {
+        let segments = highlight("<p>été &amp;é</p>ü");
+
+        assert_eq!(
+            segments,
+            [
+                (Highlight::Tag, "<p"),
+                (Highlight::Tag, ">"),
+                (Highlight::Text, "été "),
+                (Highlight::Entity, "&amp;"),
+                (Highlight::Text, "é"),
+                (Highlight::Tag, "</p"),
+                (Highlight::Tag, ">"),
+                (Highlight::Text, "ü"),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_lines() {
+        let url: URL = "view-source:https://example.com".parse().unwrap();
+        let html = render(&url, "<p>\n\tA & B\r\n</p>");
+
+        assert!(html.contains(
+            "<div class=\"line-number\">2</div><div style=\"margin-left: 10ch\">A &amp; B</div>"
+        ));
+        assert!(html.contains("<div class=\"line-number\">3</div>"));
+        assert!(!html.contains("<div class=\"line-number\">4</div>"));
+    }
+
+    #[test]
+    fn inner_url() {
+        let url: URL = "view-source:https://example.com/a?b=c".parse().unwrap();
+        assert_eq!(
+            source_url(&url).map(|url| url.serialize(url::ExcludeFragment::No).to_string()),
+            Some("https://example.com/a?b=c".to_string())
+        );
+
+        let nested: URL = "view-source:view-source:https://example.com"
+            .parse()
+            .unwrap();
+        assert!(source_url(&nested).is_none());
+    }
+}
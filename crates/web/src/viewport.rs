@@ -0,0 +1,269 @@
+//! The [viewport `<meta>` element](https://drafts.csswg.org/css-viewport/#viewport-meta)
+//!
+//! Pages that are designed for small screens use `<meta name=viewport>` to control the size
+//! of the initial containing block and the initial zoom level.
+
+use crate::{
+    css::layout::{Pixels, Size},
+    dom::{
+        dom_objects::{Document, Element, HtmlMetaElement, Node},
+        DomPtr,
+    },
+    static_interned,
+};
+
+/// Describes the output device that web pages are displayed on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Device {
+    /// The size of the screen area, in display points
+    pub size: (u16, u16),
+
+    /// The number of device pixels per display point
+    pub device_pixel_ratio: f32,
+}
+
+/// The width requested by the `width` property of a viewport `<meta>` element
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ViewportWidth {
+    /// `width=device-width`
+    DeviceWidth,
+    Fixed(Pixels),
+}
+
+/// The properties of a viewport `<meta>` element that affect layout
+///
+/// <https://drafts.csswg.org/css-viewport/#viewport-meta>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct ViewportDescription {
+    width: Option<ViewportWidth>,
+    initial_scale: Option<f32>,
+}
+
+/// The viewport that a page is laid out in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Viewport {
+    /// The size of the initial containing block
+    pub size: Size<Pixels>,
+
+    /// The factor by which the page is zoomed in
+    pub scale: f32,
+}
+
+impl ViewportDescription {
+    /// Parse the `content` attribute of a viewport `<meta>` element
+    ///
+    /// Unknown properties and invalid values are ignored.
+    ///
+    /// <https://drafts.csswg.org/css-viewport/#parsing-algorithm>
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let mut description = Self::default();
+
+        for (key, value) in properties(content) {
+            match key.to_ascii_lowercase().as_str() {
+                "width" => {
+                    if value.eq_ignore_ascii_case("device-width") {
+                        description.width = Some(ViewportWidth::DeviceWidth);
+                    } else if let Some(width) = parse_leading_number(value) {
+                        description.width =
+                            Some(ViewportWidth::Fixed(Pixels(width.clamp(1., 10000.))));
+                    }
+                },
+                "initial-scale" => {
+                    if let Some(scale) = parse_leading_number(value) {
+                        description.initial_scale = Some(scale.clamp(0.1, 10.));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        description
+    }
+
+    /// Find the viewport description of a document
+    ///
+    /// If there are multiple viewport `<meta>` elements, the last one wins.
+    #[must_use]
+    pub fn from_document(document: &DomPtr<Document>) -> Self {
+        fn find_last_viewport_meta(node: &DomPtr<Node>, content: &mut Option<String>) {
+            if node.is_a::<HtmlMetaElement>()
+                && let Some(element) = node.try_into_type::<Element>()
+            {
+                let element = element.borrow();
+                let attributes = element.attributes();
+                let is_viewport_meta = attributes
+                    .get(&static_interned!("name"))
+                    .is_some_and(|name| name.eq_ignore_ascii_case(&static_interned!("viewport")));
+
+                if is_viewport_meta
+                    && let Some(value) = attributes.get(&static_interned!("content"))
+                {
+                    *content = Some(value.to_string());
+                }
+            }
+
+            for child in node.borrow().children() {
+                find_last_viewport_meta(child, content);
+            }
+        }
+
+        let mut content = None;
+        for child in document.borrow().children() {
+            find_last_viewport_meta(child, &mut content);
+        }
+
+        content.as_deref().map(Self::parse).unwrap_or_default()
+    }
+
+    /// Determine the viewport that a page is laid out in on a screen of the given size
+    ///
+    /// The viewport is never narrower than the visible area of the screen.
+    #[must_use]
+    pub fn resolve(&self, screen_size: Size<Pixels>) -> Viewport {
+        let requested_width = self.width.map(|width| match width {
+            ViewportWidth::DeviceWidth => screen_size.width,
+            ViewportWidth::Fixed(width) => width,
+        });
+
+        let scale = match (self.initial_scale, requested_width) {
+            (Some(scale), _) => scale,
+            (None, Some(width)) => screen_size.width.0 / width.0,
+            (None, None) => 1.,
+        };
+
+        let visible_width = screen_size.width / scale;
+        let width = requested_width.map_or(visible_width, |width| width.max(visible_width));
+
+        Viewport {
+            size: Size {
+                width,
+                height: screen_size.height / scale,
+            },
+            scale,
+        }
+    }
+}
+
+/// Split the `content` attribute into key-value pairs
+///
+/// Properties are separated by commas, semicolons or whitespace and there
+/// may be whitespace around the `=` sign.
+fn properties(content: &str) -> Vec<(&str, &str)> {
+    let is_separator = |c: char| c.is_ascii_whitespace() || c == ',' || c == ';';
+    let is_whitespace = |c: char| c.is_ascii_whitespace();
+    let ends_word = |c: char| is_separator(c) || c == '=';
+
+    let mut properties = vec![];
+    let mut remaining = content;
+    loop {
+        remaining = remaining.trim_start_matches(is_separator);
+        if remaining.is_empty() {
+            break;
+        }
+
+        let key_end = remaining.find(ends_word).unwrap_or(remaining.len());
+        let key = &remaining[..key_end];
+        remaining = remaining[key_end..].trim_start_matches(is_whitespace);
+
+        let mut value = "";
+        if let Some(after_equals) = remaining.strip_prefix('=') {
+            remaining = after_equals.trim_start_matches(is_whitespace);
+            let value_end = remaining.find(ends_word).unwrap_or(remaining.len());
+            value = &remaining[..value_end];
+            remaining = &remaining[value_end..];
+        }
+
+        // A lone "=" without a key is skipped
+        if key.is_empty() {
+            remaining = remaining.trim_start_matches('=');
+        } else {
+            properties.push((key, value));
+        }
+    }
+
+    properties
+}
+
+/// Parse the number at the start of a property value, ignoring anything that follows it
+fn parse_leading_number(value: &str) -> Option<f32> {
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+
+    value[..end].parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN_SIZE: Size<Pixels> = Size {
+        width: Pixels(400.),
+        height: Pixels(800.),
+    };
+
+    #[test]
+    fn parse_viewport_description() {
+        assert_eq!(
+            ViewportDescription::parse("width=device-width, initial-scale=1"),
+            ViewportDescription {
+                width: Some(ViewportWidth::DeviceWidth),
+                initial_scale: Some(1.),
+            }
+        );
+
+        assert_eq!(
+            ViewportDescription::parse("WIDTH = 600px;user-scalable=no initial-scale=abc"),
+            ViewportDescription {
+                width: Some(ViewportWidth::Fixed(Pixels(600.))),
+                initial_scale: None,
+            }
+        );
+
+        assert_eq!(
+            ViewportDescription::parse("initial-scale=100"),
+            ViewportDescription {
+                width: None,
+                initial_scale: Some(10.),
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_viewport() {
+        // Without a viewport description, the viewport covers the screen
+        assert_eq!(
+            ViewportDescription::default().resolve(SCREEN_SIZE),
+            Viewport {
+                size: SCREEN_SIZE,
+                scale: 1.,
+            }
+        );
+
+        // Fixed widths are scaled to fit the screen
+        assert_eq!(
+            ViewportDescription::parse("width=800").resolve(SCREEN_SIZE),
+            Viewport {
+                size: Size {
+                    width: Pixels(800.),
+                    height: Pixels(1600.),
+                },
+                scale: 0.5,
+            }
+        );
+
+        // The viewport grows to cover the visible area
+        assert_eq!(
+            ViewportDescription::parse("width=device-width, initial-scale=0.5")
+                .resolve(SCREEN_SIZE),
+            Viewport {
+                size: Size {
+                    width: Pixels(800.),
+                    height: Pixels(1600.),
+                },
+                scale: 0.5,
+            }
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! The parts of the `window` global that describe the browser, the screen and the viewport
+//!
+//! Scripts commonly probe these to adapt to the environment they run in.
+//!
+//! <https://html.spec.whatwg.org/multipage/nav-history-apis.html#the-window-object>
+
+use math::Vec2D;
+use settings::SETTINGS;
+
+use crate::{css::layout::Pixels, viewport::Viewport, Device};
+
+/// Events that are fired at the window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowEvent {
+    /// <https://drafts.csswg.org/cssom-view/#eventdef-window-resize>
+    Resize,
+
+    /// <https://html.spec.whatwg.org/multipage/indices.html#event-hashchange>
+    HashChange,
+
+    /// <https://html.spec.whatwg.org/multipage/indices.html#event-popstate>
+    PopState,
+
+    /// <https://html.spec.whatwg.org/multipage/indices.html#event-load>
+    Load,
+
+    /// <https://html.spec.whatwg.org/multipage/indices.html#event-beforeunload>
+    BeforeUnload,
+
+    /// <https://html.spec.whatwg.org/multipage/indices.html#event-pagehide>
+    PageHide,
+
+    /// <https://html.spec.whatwg.org/multipage/indices.html#event-unload>
+    Unload,
+}
+
+impl WindowEvent {
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Resize => "resize",
+            Self::HashChange => "hashchange",
+            Self::PopState => "popstate",
+            Self::Load => "load",
+            Self::BeforeUnload => "beforeunload",
+            Self::PageHide => "pagehide",
+            Self::Unload => "unload",
+        }
+    }
+}
+
+/// <https://html.spec.whatwg.org/multipage/system-state.html#the-navigator-object>
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Navigator;
+
+impl Navigator {
+    /// <https://html.spec.whatwg.org/multipage/system-state.html#dom-navigator-useragent>
+    #[must_use]
+    pub const fn user_agent(&self) -> &'static str {
+        http::request::USER_AGENT
+    }
+
+    /// The preferred language of the user
+    ///
+    /// <https://html.spec.whatwg.org/multipage/system-state.html#dom-navigator-language>
+    #[must_use]
+    pub fn language(&self) -> String {
+        SETTINGS.preferences().language.clone()
+    }
+
+    /// The operating system and architecture, in the format that other browsers use
+    ///
+    /// <https://html.spec.whatwg.org/multipage/system-state.html#dom-navigator-platform>
+    #[must_use]
+    pub fn platform(&self) -> String {
+        platform(std::env::consts::OS, std::env::consts::ARCH)
+    }
+}
+
+/// <https://drafts.csswg.org/cssom-view/#the-screen-interface>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Screen {
+    device: Device,
+}
+
+impl Screen {
+    /// <https://drafts.csswg.org/cssom-view/#dom-screen-width>
+    #[must_use]
+    pub fn width(&self) -> Pixels {
+        Pixels(f32::from(self.device.size.0))
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-screen-height>
+    #[must_use]
+    pub fn height(&self) -> Pixels {
+        Pixels(f32::from(self.device.size.1))
+    }
+
+    /// The width that is available to windows
+    ///
+    /// The chrome does not know about panels of the desktop environment, so this
+    /// is the same as [width](Self::width).
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-screen-availwidth>
+    #[must_use]
+    pub fn avail_width(&self) -> Pixels {
+        self.width()
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-screen-availheight>
+    #[must_use]
+    pub fn avail_height(&self) -> Pixels {
+        self.height()
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-screen-colordepth>
+    #[must_use]
+    pub const fn color_depth(&self) -> usize {
+        24
+    }
+}
+
+/// The state of the window that a page is displayed in
+#[derive(Clone, Debug)]
+pub struct Window {
+    device: Device,
+    viewport: Viewport,
+
+    /// The top left corner of the viewport within the page
+    scroll_position: Vec2D<Pixels>,
+
+    /// Events that were not dispatched yet
+    events: Vec<WindowEvent>,
+}
+
+impl Window {
+    #[must_use]
+    pub(crate) const fn new(device: Device, viewport: Viewport) -> Self {
+        Self {
+            device,
+            viewport,
+            scroll_position: Vec2D::new(Pixels::ZERO, Pixels::ZERO),
+            events: vec![],
+        }
+    }
+
+    /// Called before every paint, queues a resize event if the viewport changed size
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#run-the-resize-steps>
+    pub(crate) fn update(
+        &mut self,
+        device: Device,
+        viewport: Viewport,
+        scroll_position: Vec2D<Pixels>,
+    ) {
+        if viewport.size != self.viewport.size {
+            self.events.push(WindowEvent::Resize);
+        }
+
+        self.device = device;
+        self.viewport = viewport;
+        self.scroll_position = scroll_position;
+    }
+
+    pub(crate) fn queue_event(&mut self, event: WindowEvent) {
+        self.events.push(event);
+    }
+
+    pub(crate) fn take_events(&mut self) -> Vec<WindowEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/nav-history-apis.html#dom-navigator>
+    #[must_use]
+    pub const fn navigator(&self) -> Navigator {
+        Navigator
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-screen>
+    #[must_use]
+    pub const fn screen(&self) -> Screen {
+        Screen {
+            device: self.device,
+        }
+    }
+
+    /// The width of the viewport, which is the width of the initial containing block
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-innerwidth>
+    #[must_use]
+    pub fn inner_width(&self) -> Pixels {
+        self.viewport.size.width
+    }
+
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-innerheight>
+    #[must_use]
+    pub fn inner_height(&self) -> Pixels {
+        self.viewport.size.height
+    }
+
+    /// How far the page is scrolled to the right
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-scrollx>
+    #[must_use]
+    pub fn scroll_x(&self) -> Pixels {
+        self.scroll_position.x
+    }
+
+    /// How far the page is scrolled down
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-scrolly>
+    #[must_use]
+    pub fn scroll_y(&self) -> Pixels {
+        self.scroll_position.y
+    }
+
+    /// The number of device pixels per CSS pixel, which includes the zoom level
+    ///
+    /// <https://drafts.csswg.org/cssom-view/#dom-window-devicepixelratio>
+    #[must_use]
+    pub fn device_pixel_ratio(&self) -> f32 {
+        self.device.device_pixel_ratio * self.viewport.scale
+    }
+}
+
+fn platform(os: &str, arch: &str) -> String {
+    match os {
+        "linux" => format!("Linux {arch}"),
+        "macos" => "MacIntel".to_string(),
+        "windows" => "Win32".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::css::layout::Size;
+
+    fn viewport(width: f32, height: f32, scale: f32) -> Viewport {
+        Viewport {
+            size: Size {
+                width: Pixels(width),
+                height: Pixels(height),
+            },
+            scale,
+        }
+    }
+
+    #[test]
+    fn resize_events() {
+        let device = Device {
+            size: (800, 600),
+            device_pixel_ratio: 2.,
+        };
+        let mut window = Window::new(device, viewport(800., 600., 1.));
+        let origin = Vec2D::new(Pixels::ZERO, Pixels::ZERO);
+
+        // Zooming does not resize the viewport
+        window.update(device, viewport(800., 600., 1.5), origin);
+        assert!(window.take_events().is_empty());
+        assert_eq!(window.device_pixel_ratio(), 3.);
+
+        let device = Device {
+            size: (1024, 600),
+            device_pixel_ratio: 2.,
+        };
+        window.update(
+            device,
+            viewport(1024., 600., 1.),
+            Vec2D::new(Pixels::ZERO, Pixels(120.)),
+        );
+        assert_eq!(window.take_events(), vec![WindowEvent::Resize]);
+        assert_eq!(window.inner_width(), Pixels(1024.));
+        assert_eq!(window.scroll_y(), Pixels(120.));
+        assert_eq!(window.screen().width(), Pixels(1024.));
+        assert!(window.take_events().is_empty());
+    }
+
+    #[test]
+    fn platform_names() {
+        assert_eq!(platform("linux", "x86_64"), "Linux x86_64");
+        assert_eq!(platform("macos", "aarch64"), "MacIntel");
+        assert_eq!(platform("windows", "x86_64"), "Win32");
+    }
+}
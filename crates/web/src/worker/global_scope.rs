@@ -0,0 +1,102 @@
+//! The worker side of a dedicated worker
+//!
+//! <https://html.spec.whatwg.org/multipage/workers.html#the-workerglobalscope-common-interface>
+
+use std::sync::mpsc;
+
+use super::{
+    structured_clone::{self, SerializedValue},
+    WorkerError,
+};
+
+/// Work that is queued on the event loop of a worker
+#[derive(Debug)]
+pub(super) enum Task {
+    Message(SerializedValue),
+    Terminate,
+}
+
+/// Owns the javascript runtime of a worker and runs its event loop
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/workers.html#dedicatedworkerglobalscope)
+pub(super) struct WorkerGlobalScope {
+    vm: js::Vm,
+    tasks: mpsc::Receiver<Task>,
+    errors: mpsc::Sender<WorkerError>,
+
+    /// Set by the page when the worker is terminated, which also interrupts running scripts
+    ///
+    /// <https://html.spec.whatwg.org/multipage/workers.html#dom-workerglobalscope-closing>
+    closing: js::InterruptHandle,
+}
+
+impl WorkerGlobalScope {
+    pub(super) fn new(
+        tasks: mpsc::Receiver<Task>,
+        errors: mpsc::Sender<WorkerError>,
+        closing: js::InterruptHandle,
+    ) -> Self {
+        Self {
+            vm: js::Vm::with_interrupt_handle(closing.clone()),
+            tasks,
+            errors,
+            closing,
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/workers.html#run-a-worker>
+    pub(super) fn run<F>(mut self, fetch_script: F)
+    where
+        F: FnOnce() -> Result<String, WorkerError>,
+    {
+        if let Err(error) = self.run_script(fetch_script) {
+            // The page might already have dropped the worker, in which case nobody cares about the error
+            _ = self.errors.send(error);
+            return;
+        }
+
+        self.run_event_loop();
+    }
+
+    fn run_script<F>(&mut self, fetch_script: F) -> Result<(), WorkerError>
+    where
+        F: FnOnce() -> Result<String, WorkerError>,
+    {
+        let source = fetch_script()?;
+
+        // The worker might have been terminated while the script was loading
+        if self.closing.is_interrupted() {
+            return Ok(());
+        }
+        let executable: js::Executable = source.parse().map_err(|error| {
+            log::error!("Failed to compile worker script: {error:?}");
+            WorkerError::InvalidScript
+        })?;
+
+        self.vm.execute(executable);
+        Ok(())
+    }
+
+    /// Process tasks until the worker is terminated or the page drops its handle
+    ///
+    /// <https://html.spec.whatwg.org/multipage/webappapis.html#event-loop-processing-model>
+    fn run_event_loop(&mut self) {
+        while let Ok(task) = self.tasks.recv() {
+            // Tasks that were queued before the worker was terminated are discarded
+            if self.closing.is_interrupted() {
+                break;
+            }
+
+            match task {
+                Task::Message(message) => {
+                    let message = structured_clone::deserialize(message);
+
+                    // FIXME: Fire a MessageEvent at the global scope once the runtime
+                    //        supports event handlers
+                    log::debug!("Worker received message: {message:?}");
+                },
+                Task::Terminate => break,
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Dedicated workers, which run scripts on a separate thread
+//!
+//! Every worker owns its own javascript runtime. Values are passed between the
+//! page and the worker using [structured_clone].
+//!
+//! <https://html.spec.whatwg.org/multipage/workers.html>
+
+mod global_scope;
+pub mod structured_clone;
+
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+use error_derive::Error;
+use resourceloader::ResourceLoadError;
+use url::URL;
+
+use self::{
+    global_scope::{Task, WorkerGlobalScope},
+    structured_clone::DataCloneError,
+};
+
+/// How long [Worker::terminate] waits for the worker thread to exit
+const TERMINATION_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// <https://html.spec.whatwg.org/multipage/workers.html#workertype>
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WorkerType {
+    #[default]
+    Classic,
+    Module,
+}
+
+#[derive(Debug, Error)]
+pub enum WorkerError {
+    #[msg = "failed to load worker script"]
+    Loading(ResourceLoadError),
+
+    #[msg = "worker script is not valid utf-8"]
+    InvalidEncoding,
+
+    #[msg = "failed to compile worker script"]
+    InvalidScript,
+
+    #[msg = "module workers are not supported"]
+    ModuleScriptsUnsupported,
+
+    #[msg = "failed to spawn worker thread"]
+    Spawn(std::io::Error),
+}
+
+/// The page side of a dedicated worker
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/workers.html#dedicated-workers-and-the-worker-interface)
+#[derive(Debug)]
+pub struct Worker {
+    tasks: mpsc::Sender<Task>,
+    errors: mpsc::Receiver<WorkerError>,
+    closing: js::InterruptHandle,
+
+    /// Disconnected once the worker thread exits
+    exited: mpsc::Receiver<()>,
+
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Worker {
+    /// Start running the script at `url` on a new thread
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/workers.html#dom-worker)
+    pub fn new(url: URL, worker_type: WorkerType) -> Result<Self, WorkerError> {
+        // FIXME: Module scripts require the parser to support the Module goal symbol
+        if worker_type == WorkerType::Module {
+            return Err(WorkerError::ModuleScriptsUnsupported);
+        }
+
+        let name = format!("Worker {}", url.serialize(url::ExcludeFragment::Yes));
+        Self::spawn(name, move || {
+            let resource = resourceloader::RESOURCE_LOADER
+                .schedule_load_with_priority(url, resourceloader::Priority::AsyncScript)
+                .block()
+                .map_err(WorkerError::Loading)?;
+
+            String::from_utf8(resource.data().to_vec()).map_err(|_| WorkerError::InvalidEncoding)
+        })
+    }
+
+    /// Spawn a worker thread that runs the script returned by `fetch_script`
+    fn spawn<F>(name: String, fetch_script: F) -> Result<Self, WorkerError>
+    where
+        F: FnOnce() -> Result<String, WorkerError> + Send + 'static,
+    {
+        let (task_sender, task_receiver) = mpsc::channel();
+        let (error_sender, error_receiver) = mpsc::channel();
+        let (exit_sender, exit_receiver) = mpsc::channel();
+        let closing = js::InterruptHandle::default();
+
+        let worker_closing = closing.clone();
+        let thread = thread::Builder::new()
+            .name(name)
+            .spawn(move || {
+                // Dropped when the thread exits, even if it panics
+                let _exit_sender = exit_sender;
+
+                let global_scope =
+                    WorkerGlobalScope::new(task_receiver, error_sender, worker_closing);
+                global_scope.run(fetch_script);
+            })
+            .map_err(WorkerError::Spawn)?;
+
+        let worker = Self {
+            tasks: task_sender,
+            errors: error_receiver,
+            closing,
+            exited: exit_receiver,
+            thread: Some(thread),
+        };
+
+        Ok(worker)
+    }
+
+    /// Send a message to the worker
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/workers.html#dom-worker-postmessage)
+    pub fn post_message(&self, message: &js::Value) -> Result<(), DataCloneError> {
+        let serialized = structured_clone::serialize(message)?;
+
+        // Messages to a worker that has already exited are silently dropped
+        _ = self.tasks.send(Task::Message(serialized));
+
+        Ok(())
+    }
+
+    /// Return an error that occurred inside the worker, if any
+    ///
+    /// This does not block.
+    #[must_use]
+    pub fn take_error(&self) -> Option<WorkerError> {
+        self.errors.try_recv().ok()
+    }
+
+    /// Stop the worker and wait a short time for its thread to exit
+    ///
+    /// Running scripts are interrupted. If the thread is blocked on something else,
+    /// like loading the worker script, it is detached and exits on its own later.
+    ///
+    /// [Specification](https://html.spec.whatwg.org/multipage/workers.html#dom-worker-terminate)
+    pub fn terminate(&mut self) {
+        self.closing.interrupt();
+
+        // Wakes up the event loop if it is waiting for tasks
+        _ = self.tasks.send(Task::Terminate);
+
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+
+        match self.exited.recv_timeout(TERMINATION_TIMEOUT) {
+            Err(RecvTimeoutError::Timeout) => {
+                log::warn!("Worker thread did not exit in time, detaching it");
+            },
+            _ => {
+                if thread.join().is_err() {
+                    log::error!("Worker thread panicked");
+                }
+            },
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.terminate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_and_terminate() {
+        let mut worker =
+            Worker::spawn("Test worker".to_string(), || Ok("let x = 1;".to_string())).unwrap();
+
+        worker.post_message(&js::Value::Boolean(true)).unwrap();
+        worker.terminate();
+
+        assert!(worker.take_error().is_none());
+    }
+
+    #[test]
+    fn report_load_errors() {
+        let mut worker = Worker::spawn("Test worker".to_string(), || {
+            Err(WorkerError::InvalidEncoding)
+        })
+        .unwrap();
+        worker.terminate();
+
+        assert!(matches!(
+            worker.take_error(),
+            Some(WorkerError::InvalidEncoding)
+        ));
+    }
+
+    #[test]
+    fn terminate_blocked_workers() {
+        let mut worker = Worker::spawn("Test worker".to_string(), || {
+            thread::sleep(Duration::from_secs(10));
+            Ok("let x = 1;".to_string())
+        })
+        .unwrap();
+
+        let start = std::time::Instant::now();
+        worker.terminate();
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn module_workers() {
+        let url = "https://example.com/worker.js".parse().unwrap();
+
+        assert!(matches!(
+            Worker::new(url, WorkerType::Module),
+            Err(WorkerError::ModuleScriptsUnsupported)
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+//! <https://html.spec.whatwg.org/multipage/structured-data.html#safe-passing-of-structured-data>
+//!
+//! Values can't be shared between javascript runtimes, so they are serialized into a
+//! runtime-independent representation before being sent to another thread.
+
+use error_derive::Error;
+use js::{Number, Value};
+
+/// A javascript value that was serialized by [serialize]
+///
+/// [Specification](https://html.spec.whatwg.org/multipage/structured-data.html#structuredserializeinternal)
+#[derive(Clone, Debug, PartialEq)]
+pub enum SerializedValue {
+    Undefined,
+    Null,
+    Boolean(bool),
+    Number(Number),
+    String(String),
+}
+
+/// <https://webidl.spec.whatwg.org/#datacloneerror>
+#[derive(Debug, Error)]
+pub enum DataCloneError {
+    #[msg = "symbols cannot be cloned"]
+    Symbol,
+
+    #[msg = "bigints cannot be cloned"]
+    BigInt,
+
+    #[msg = "objects cannot be cloned"]
+    Object,
+}
+
+/// <https://html.spec.whatwg.org/multipage/structured-data.html#structuredserialize>
+pub fn serialize(value: &Value) -> Result<SerializedValue, DataCloneError> {
+    // NOTE: The memory map is only necessary to preserve the identity of objects,
+    //       which can't be serialized yet.
+    let serialized = match value {
+        Value::Undefined => SerializedValue::Undefined,
+        Value::Null => SerializedValue::Null,
+        Value::Boolean(boolean) => SerializedValue::Boolean(*boolean),
+        Value::Number(number) => SerializedValue::Number(*number),
+        Value::String(string) => SerializedValue::String(string.clone()),
+
+        // If value is a Symbol, then throw a "DataCloneError" DOMException.
+        Value::Symbol(_) => return Err(DataCloneError::Symbol),
+
+        // FIXME: BigInts can be cloned once the runtime supports them
+        Value::BigInt => return Err(DataCloneError::BigInt),
+
+        // FIXME: Serialize ordinary objects once the runtime can enumerate their properties
+        Value::Object(_) => return Err(DataCloneError::Object),
+    };
+
+    Ok(serialized)
+}
+
+/// <https://html.spec.whatwg.org/multipage/structured-data.html#structureddeserialize>
+#[must_use]
+pub fn deserialize(serialized: SerializedValue) -> Value {
+    match serialized {
+        SerializedValue::Undefined => Value::Undefined,
+        SerializedValue::Null => Value::Null,
+        SerializedValue::Boolean(boolean) => boolean.into(),
+        SerializedValue::Number(number) => number.into(),
+        SerializedValue::String(string) => string.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clone_primitives() {
+        let values = [
+            Value::Undefined,
+            Value::Null,
+            Value::Boolean(true),
+            Value::Number(Number::new(1.5)),
+            Value::String("hello".to_string()),
+        ];
+
+        for value in values {
+            let clone = deserialize(serialize(&value).unwrap());
+            assert_eq!(clone, value);
+        }
+    }
+
+    #[test]
+    fn uncloneable_values() {
+        let symbol = Value::Symbol(js::Symbol::new(None));
+        assert!(matches!(serialize(&symbol), Err(DataCloneError::Symbol)));
+    }
+}
@@ -0,0 +1,78 @@
+use std::{error, fmt};
+
+use error_derive::Error;
+
+/// <https://www.w3.org/TR/xml/#dt-wellformed>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Error)]
+pub enum WellFormednessError {
+    #[msg = "unexpected end of document"]
+    UnexpectedEndOfDocument,
+
+    #[msg = "unexpected character"]
+    UnexpectedCharacter,
+
+    #[msg = "expected a name"]
+    ExpectedName,
+
+    #[msg = "invalid qualified name"]
+    InvalidQualifiedName,
+
+    #[msg = "the document does not contain an element"]
+    MissingRootElement,
+
+    #[msg = "content after the root element"]
+    ContentAfterRootElement,
+
+    #[msg = "end tag does not match the start tag"]
+    MismatchedEndTag,
+
+    #[msg = "attribute is specified more than once"]
+    DuplicateAttribute,
+
+    #[msg = "attribute values must be quoted"]
+    UnquotedAttributeValue,
+
+    #[msg = "'<' is not allowed in attribute values"]
+    LessThanInAttributeValue,
+
+    #[msg = "namespace prefix is not declared"]
+    UndeclaredNamespacePrefix,
+
+    #[msg = "unknown entity"]
+    UnknownEntity,
+
+    #[msg = "character reference does not refer to a valid character"]
+    InvalidCharacterReference,
+
+    #[msg = "'--' is not allowed in comments"]
+    DoubleHyphenInComment,
+
+    #[msg = "']]>' is not allowed in text"]
+    CDataEndInText,
+}
+
+/// The first well-formedness error in a xml document
+///
+/// Unlike html parsing, xml parsing stops at the first error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct XmlError {
+    pub kind: WellFormednessError,
+
+    /// Starts at `1`
+    pub line: usize,
+
+    /// Starts at `1`, counts characters
+    pub column: usize,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (line {}, column {})",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl error::Error for XmlError {}
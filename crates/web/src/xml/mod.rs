@@ -0,0 +1,7 @@
+//! <https://www.w3.org/TR/xml/>
+
+mod error;
+mod parser;
+
+pub use error::{WellFormednessError, XmlError};
+pub use parser::Parser;
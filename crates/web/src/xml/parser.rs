@@ -0,0 +1,881 @@
+use std::mem;
+
+use resourceloader::{PendingLoad, RESOURCE_LOADER};
+use settings::SETTINGS;
+use url::URL;
+
+use super::{WellFormednessError, XmlError};
+use crate::{
+    css::{self, Stylesheet},
+    dom::{
+        self,
+        dom_objects::{
+            Comment, Document, DocumentReadyState, DocumentType, Element, HtmlLinkElement,
+            HtmlStyleElement, Node, Text,
+        },
+        DomPtr,
+    },
+    html::{links, tokenization::lookup_character_reference},
+    infra::{self, Namespace},
+    integrity,
+};
+
+/// <https://www.w3.org/TR/xml-names/#ns-decl>
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// <https://www.w3.org/TR/xml-names/#ns-decl>
+const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+/// Documents with one of these public identifiers may use the named character
+/// references of html
+///
+/// <https://html.spec.whatwg.org/multipage/xhtml.html#parsing-xhtml-documents>
+const XHTML_PUBLIC_IDENTIFIERS: [&str; 8] = [
+    "-//W3C//DTD XHTML 1.0 Transitional//EN",
+    "-//W3C//DTD XHTML 1.1//EN",
+    "-//W3C//DTD XHTML 1.0 Strict//EN",
+    "-//W3C//DTD XHTML 1.0 Frameset//EN",
+    "-//W3C//DTD XHTML Basic 1.0//EN",
+    "-//W3C//DTD XHTML 1.1 plus MathML 2.0//EN",
+    "-//W3C//DTD XHTML 1.1 plus MathML 2.0 plus SVG 1.1//EN",
+    "-//W3C//DTD MathML 2.0//EN",
+];
+
+/// Builds the DOM of a xml document
+///
+/// Elements in the html namespace become the same DOM objects that the [html parser](crate::html::Parser)
+/// creates, which makes it possible to display `application/xhtml+xml` documents.
+///
+/// Entity declarations in the internal subset of the doctype are not supported.
+pub struct Parser {
+    source: String,
+
+    /// Byte offset of the next character in [source](Self::source)
+    position: usize,
+
+    document: DomPtr<Document>,
+    open_elements: Vec<OpenElement>,
+
+    /// Whether the doctype allows using the named character references of html
+    allows_html_entities: bool,
+
+    stylesheets: Vec<Stylesheet>,
+
+    /// Stylesheets that are asynchronously loaded during parsing, together with their integrity metadata
+    pending_stylesheets: Vec<(URL, String, PendingLoad)>,
+}
+
+struct OpenElement {
+    element: DomPtr<Element>,
+
+    /// The name in the start tag, which the end tag has to repeat
+    qualified_name: String,
+
+    /// The `xmlns` attributes of the element
+    namespace_declarations: Vec<NamespaceDeclaration>,
+}
+
+/// <https://www.w3.org/TR/xml-names/#ns-decl>
+struct NamespaceDeclaration {
+    /// `None` for the default namespace
+    prefix: Option<String>,
+
+    /// The empty string undeclares the default namespace
+    namespace: String,
+}
+
+impl Parser {
+    #[must_use]
+    pub fn new(source: &str, document: DomPtr<Document>) -> Self {
+        let mut stylesheets = vec![Stylesheet::user_agent_rules()];
+        if SETTINGS.preferences().force_dark_mode {
+            stylesheets.push(Stylesheet::forced_dark_user_agent_rules());
+        }
+
+        let source = source.strip_prefix('\u{FEFF}').unwrap_or(source);
+
+        Self {
+            source: infra::normalize_newlines(source),
+            position: 0,
+            document,
+            open_elements: vec![],
+            allows_html_entities: false,
+            stylesheets,
+            pending_stylesheets: vec![],
+        }
+    }
+
+    /// <https://www.w3.org/TR/xml/#sec-documents>
+    pub fn parse(mut self) -> Result<(DomPtr<Document>, Vec<Stylesheet>), XmlError> {
+        let _span = trace::span!(Parse, "parse xml");
+
+        self.consume_prolog()?;
+
+        let mut chars = self.remaining().chars();
+        if chars.next() != Some('<') || !chars.next().is_some_and(is_name_start_char) {
+            return Err(self.error(WellFormednessError::MissingRootElement));
+        }
+        self.consume_root_element()?;
+
+        self.consume_misc()?;
+        if !self.remaining().is_empty() {
+            return Err(self.error(WellFormednessError::ContentAfterRootElement));
+        }
+
+        self.finish_loading_stylesheets();
+
+        // https://html.spec.whatwg.org/multipage/xhtml.html#parsing-xhtml-documents
+        // Once the user agent stops parsing the document, the user agent must run the steps of "the end"
+        self.document
+            .borrow_mut()
+            .set_ready_state(DocumentReadyState::Interactive);
+
+        // FIXME: Fire the event at the document once the DOM supports event listeners
+        log::debug!("DOMContentLoaded on document");
+
+        Ok((self.document, self.stylesheets))
+    }
+
+    #[must_use]
+    fn remaining(&self) -> &str {
+        &self.source[self.position..]
+    }
+
+    fn advance(&mut self, bytes: usize) {
+        self.position += bytes;
+    }
+
+    fn error(&self, kind: WellFormednessError) -> XmlError {
+        let preceding_source = &self.source[..self.position];
+
+        let line = preceding_source.matches('\n').count() + 1;
+        let line_start = preceding_source.rfind('\n').map_or(0, |index| index + 1);
+        let column = preceding_source[line_start..].chars().count() + 1;
+
+        XmlError { kind, line, column }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), XmlError> {
+        match self.remaining().chars().next() {
+            Some(c) if c == expected => {
+                self.advance(c.len_utf8());
+                Ok(())
+            },
+            Some(_) => Err(self.error(WellFormednessError::UnexpectedCharacter)),
+            None => Err(self.error(WellFormednessError::UnexpectedEndOfDocument)),
+        }
+    }
+
+    /// Returns whether any whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let whitespace_length =
+            self.remaining().len() - self.remaining().trim_start_matches([' ', '\t', '\n']).len();
+        self.advance(whitespace_length);
+
+        whitespace_length != 0
+    }
+
+    /// Skip everything up to and including `terminator`
+    ///
+    /// Returns the skipped text, without the terminator.
+    fn consume_until(&mut self, terminator: &str) -> Result<String, XmlError> {
+        let Some(end) = self.remaining().find(terminator) else {
+            self.position = self.source.len();
+            return Err(self.error(WellFormednessError::UnexpectedEndOfDocument));
+        };
+
+        let skipped = self.remaining()[..end].to_owned();
+        self.advance(end + terminator.len());
+        Ok(skipped)
+    }
+
+    /// <https://www.w3.org/TR/xml/#NT-Name>
+    fn consume_name(&mut self) -> Result<String, XmlError> {
+        let remaining = self.remaining();
+        if !remaining.chars().next().is_some_and(is_name_start_char) {
+            return Err(self.error(WellFormednessError::ExpectedName));
+        }
+
+        let length = remaining
+            .find(|c| !is_name_char(c))
+            .unwrap_or(remaining.len());
+        let name = remaining[..length].to_owned();
+        self.advance(length);
+
+        Ok(name)
+    }
+
+    /// <https://www.w3.org/TR/xml/#NT-prolog>
+    fn consume_prolog(&mut self) -> Result<(), XmlError> {
+        // The xml declaration is a processing instruction for our purposes
+        self.consume_misc()?;
+
+        if self.remaining().starts_with("<!DOCTYPE") {
+            self.consume_doctype()?;
+            self.consume_misc()?;
+        }
+
+        Ok(())
+    }
+
+    /// Consume any comments, processing instructions and whitespace
+    ///
+    /// <https://www.w3.org/TR/xml/#NT-Misc>
+    fn consume_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+
+            if self.remaining().starts_with("<!--") {
+                self.consume_comment()?;
+            } else if self.remaining().starts_with("<?") {
+                self.consume_processing_instruction()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// <https://www.w3.org/TR/xml/#NT-doctypedecl>
+    fn consume_doctype(&mut self) -> Result<(), XmlError> {
+        self.advance("<!DOCTYPE".len());
+        if !self.skip_whitespace() {
+            return Err(self.error(WellFormednessError::UnexpectedCharacter));
+        }
+
+        let name = self.consume_name()?;
+        self.skip_whitespace();
+
+        let mut public_id = String::new();
+        let mut system_id = String::new();
+        if self.remaining().starts_with("PUBLIC") {
+            self.advance("PUBLIC".len());
+            self.skip_whitespace();
+            public_id = self.consume_quoted_literal()?;
+            self.skip_whitespace();
+            system_id = self.consume_quoted_literal()?;
+        } else if self.remaining().starts_with("SYSTEM") {
+            self.advance("SYSTEM".len());
+            self.skip_whitespace();
+            system_id = self.consume_quoted_literal()?;
+        }
+        self.skip_whitespace();
+
+        // FIXME: Process the declarations in the internal subset
+        if self.remaining().starts_with('[') {
+            self.skip_internal_subset()?;
+            self.skip_whitespace();
+        }
+        self.expect('>')?;
+
+        self.allows_html_entities = XHTML_PUBLIC_IDENTIFIERS.contains(&public_id.as_str());
+
+        let mut doctype = DocumentType::default();
+        doctype.set_name(name.as_str().into());
+        doctype.set_public_id(public_id.as_str().into());
+        doctype.set_system_id(system_id.as_str().into());
+        doctype.set_owning_document(DomPtr::clone(&self.document).downgrade());
+        Node::append_child(
+            DomPtr::clone(&self.document).upcast(),
+            DomPtr::new(doctype).upcast(),
+        );
+
+        Ok(())
+    }
+
+    /// Skip the declarations between `[` and `]` in a doctype
+    fn skip_internal_subset(&mut self) -> Result<(), XmlError> {
+        self.advance(1);
+
+        loop {
+            let Some(c) = self.remaining().chars().next() else {
+                return Err(self.error(WellFormednessError::UnexpectedEndOfDocument));
+            };
+
+            match c {
+                ']' => {
+                    self.advance(1);
+                    return Ok(());
+                },
+                // Quoted strings can contain ']'
+                '"' | '\'' => {
+                    self.consume_quoted_literal()?;
+                },
+                _ if self.remaining().starts_with("<!--") => self.consume_comment()?,
+                _ => self.advance(c.len_utf8()),
+            }
+        }
+    }
+
+    fn consume_quoted_literal(&mut self) -> Result<String, XmlError> {
+        let quote = match self.remaining().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            Some(_) => return Err(self.error(WellFormednessError::UnexpectedCharacter)),
+            None => return Err(self.error(WellFormednessError::UnexpectedEndOfDocument)),
+        };
+        self.advance(1);
+
+        self.consume_until(quote.encode_utf8(&mut [0; 1]))
+    }
+
+    /// <https://www.w3.org/TR/xml/#sec-comments>
+    fn consume_comment(&mut self) -> Result<(), XmlError> {
+        self.advance("<!--".len());
+
+        let content = self.consume_until("--")?;
+        if !self.remaining().starts_with('>') {
+            return Err(self.error(WellFormednessError::DoubleHyphenInComment));
+        }
+        self.advance(1);
+
+        let mut comment = Comment::default();
+        comment.content_mut().push_str(&content);
+        comment.set_owning_document(DomPtr::clone(&self.document).downgrade());
+        Node::append_child(self.current_node(), DomPtr::new(comment).upcast());
+
+        Ok(())
+    }
+
+    /// <https://www.w3.org/TR/xml/#sec-pi>
+    fn consume_processing_instruction(&mut self) -> Result<(), XmlError> {
+        self.advance("<?".len());
+        self.consume_name()?;
+
+        // FIXME: Honor <?xml-stylesheet?>
+        self.consume_until("?>")?;
+        Ok(())
+    }
+
+    /// Consume the root element, including all of its descendants
+    ///
+    /// <https://www.w3.org/TR/xml/#NT-element>
+    fn consume_root_element(&mut self) -> Result<(), XmlError> {
+        loop {
+            let remaining = self.remaining();
+
+            if remaining.starts_with("</") {
+                self.consume_end_tag()?;
+            } else if remaining.starts_with("<!--") {
+                self.consume_comment()?;
+            } else if remaining.starts_with("<![CDATA[") {
+                self.advance("<![CDATA[".len());
+                let text = self.consume_until("]]>")?;
+                self.insert_text(&text);
+            } else if remaining.starts_with("<?") {
+                self.consume_processing_instruction()?;
+            } else if remaining.starts_with('<') {
+                self.consume_start_tag()?;
+            } else if remaining.is_empty() {
+                return Err(self.error(WellFormednessError::UnexpectedEndOfDocument));
+            } else {
+                self.consume_character_data()?;
+            }
+
+            if self.open_elements.is_empty() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// <https://www.w3.org/TR/xml/#sec-starttags>
+    fn consume_start_tag(&mut self) -> Result<(), XmlError> {
+        self.advance(1);
+        let qualified_name = self.consume_name()?;
+
+        let mut attributes: Vec<(String, String)> = vec![];
+        let is_empty_element = loop {
+            let is_separated = self.skip_whitespace();
+
+            if self.remaining().starts_with("/>") {
+                self.advance(2);
+                break true;
+            }
+            if self.remaining().starts_with('>') {
+                self.advance(1);
+                break false;
+            }
+            if self.remaining().is_empty() {
+                return Err(self.error(WellFormednessError::UnexpectedEndOfDocument));
+            }
+            if !is_separated {
+                return Err(self.error(WellFormednessError::UnexpectedCharacter));
+            }
+
+            let name = self.consume_name()?;
+            self.skip_whitespace();
+            self.expect('=')?;
+            self.skip_whitespace();
+            let value = self.consume_attribute_value()?;
+
+            if attributes.iter().any(|(other_name, _)| *other_name == name) {
+                return Err(self.error(WellFormednessError::DuplicateAttribute));
+            }
+            attributes.push((name, value));
+        };
+
+        let namespace_declarations: Vec<NamespaceDeclaration> = attributes
+            .iter()
+            .filter_map(|(name, value)| {
+                let prefix = if name == "xmlns" {
+                    None
+                } else {
+                    Some(name.strip_prefix("xmlns:")?.to_owned())
+                };
+
+                Some(NamespaceDeclaration {
+                    prefix,
+                    namespace: value.clone(),
+                })
+            })
+            .collect();
+
+        let (prefix, local_name) = self.split_qualified_name(&qualified_name)?;
+        let namespace = match self.lookup_namespace(prefix, &namespace_declarations) {
+            Some(namespace) => Namespace::from_url(namespace),
+            None if prefix.is_some() => {
+                return Err(self.error(WellFormednessError::UndeclaredNamespacePrefix))
+            },
+            None => Namespace::Other,
+        };
+
+        // FIXME: The DOM does not support namespaced attributes, so prefixed attributes
+        //        keep their qualified name
+        for (name, _) in &attributes {
+            let (prefix, _) = self.split_qualified_name(name)?;
+            if prefix.is_some_and(|prefix| prefix != "xmlns")
+                && self
+                    .lookup_namespace(prefix, &namespace_declarations)
+                    .is_none()
+            {
+                return Err(self.error(WellFormednessError::UndeclaredNamespacePrefix));
+            }
+        }
+
+        let element = dom::create_element(
+            DomPtr::clone(&self.document).downgrade(),
+            local_name.into(),
+            namespace,
+            prefix.map(Into::into),
+            None,
+            false,
+        );
+        for (name, value) in &attributes {
+            element
+                .borrow_mut()
+                .append_attribute(name.as_str().into(), value.as_str().into());
+        }
+        Node::append_child(self.current_node(), element.clone().upcast());
+
+        if is_empty_element {
+            self.finish_element(&element);
+        } else {
+            self.open_elements.push(OpenElement {
+                element,
+                qualified_name,
+                namespace_declarations,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// <https://www.w3.org/TR/xml/#sec-starttags>
+    fn consume_end_tag(&mut self) -> Result<(), XmlError> {
+        self.advance("</".len());
+        let name = self.consume_name()?;
+        self.skip_whitespace();
+        self.expect('>')?;
+
+        let open_element = self
+            .open_elements
+            .pop()
+            .expect("end tags are only consumed inside the root element");
+        if open_element.qualified_name != name {
+            return Err(self.error(WellFormednessError::MismatchedEndTag));
+        }
+
+        self.finish_element(&open_element.element);
+        Ok(())
+    }
+
+    /// <https://www.w3.org/TR/xml/#NT-AttValue>
+    fn consume_attribute_value(&mut self) -> Result<String, XmlError> {
+        let quote = match self.remaining().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            Some(_) => return Err(self.error(WellFormednessError::UnquotedAttributeValue)),
+            None => return Err(self.error(WellFormednessError::UnexpectedEndOfDocument)),
+        };
+        self.advance(1);
+
+        let mut value = String::new();
+        loop {
+            match self.remaining().chars().next() {
+                Some(c) if c == quote => {
+                    self.advance(1);
+                    return Ok(value);
+                },
+                Some('<') => return Err(self.error(WellFormednessError::LessThanInAttributeValue)),
+                Some('&') => value.push_str(&self.consume_reference()?),
+
+                // <https://www.w3.org/TR/xml/#AVNormalize>
+                Some('\t' | '\n') => {
+                    value.push(' ');
+                    self.advance(1);
+                },
+                Some(c) => {
+                    value.push(c);
+                    self.advance(c.len_utf8());
+                },
+                None => return Err(self.error(WellFormednessError::UnexpectedEndOfDocument)),
+            }
+        }
+    }
+
+    /// <https://www.w3.org/TR/xml/#syntax>
+    fn consume_character_data(&mut self) -> Result<(), XmlError> {
+        let mut text = String::new();
+
+        loop {
+            let remaining = self.remaining();
+            let length = remaining.find(['<', '&']).unwrap_or(remaining.len());
+
+            if let Some(offset) = remaining[..length].find("]]>") {
+                self.advance(offset);
+                return Err(self.error(WellFormednessError::CDataEndInText));
+            }
+            text.push_str(&remaining[..length]);
+            self.advance(length);
+
+            if self.remaining().starts_with('&') {
+                text.push_str(&self.consume_reference()?);
+            } else {
+                break;
+            }
+        }
+
+        self.insert_text(&text);
+        Ok(())
+    }
+
+    /// Consume an entity or character reference, starting at the `&`
+    ///
+    /// <https://www.w3.org/TR/xml/#sec-references>
+    fn consume_reference(&mut self) -> Result<String, XmlError> {
+        self.advance(1);
+
+        let remaining = self.remaining();
+        let Some(length) = remaining.find(';') else {
+            return Err(self.error(WellFormednessError::UnknownEntity));
+        };
+        let name = &remaining[..length];
+
+        let character_reference = match name.strip_prefix("#x") {
+            Some(hexadecimal) => Some(u32::from_str_radix(hexadecimal, 16).ok()),
+            None => name
+                .strip_prefix('#')
+                .map(|decimal| decimal.parse::<u32>().ok()),
+        };
+
+        let resolved = match character_reference {
+            Some(code_point) => {
+                let c = code_point
+                    .filter(|_| !name.contains('+'))
+                    .and_then(char::from_u32)
+                    .filter(|c| is_xml_char(*c))
+                    .ok_or_else(|| self.error(WellFormednessError::InvalidCharacterReference))?;
+                c.to_string()
+            },
+            None => match name {
+                "lt" => "<".to_owned(),
+                "gt" => ">".to_owned(),
+                "amp" => "&".to_owned(),
+                "apos" => "'".to_owned(),
+                "quot" => "\"".to_owned(),
+                _ => lookup_character_reference(&remaining[..=length])
+                    .filter(|(matched, _)| self.allows_html_entities && matched.len() == length + 1)
+                    .map(|(_, resolved)| resolved.to_owned())
+                    .ok_or_else(|| self.error(WellFormednessError::UnknownEntity))?,
+            },
+        };
+
+        self.advance(length + 1);
+        Ok(resolved)
+    }
+
+    /// Split a name into its prefix and local name
+    ///
+    /// <https://www.w3.org/TR/xml-names/#ns-qualnames>
+    fn split_qualified_name<'b>(
+        &self,
+        name: &'b str,
+    ) -> Result<(Option<&'b str>, &'b str), XmlError> {
+        match name.split_once(':') {
+            None => Ok((None, name)),
+            Some((prefix, local_name))
+                if !prefix.is_empty() && !local_name.is_empty() && !local_name.contains(':') =>
+            {
+                Ok((Some(prefix), local_name))
+            },
+            Some(_) => Err(self.error(WellFormednessError::InvalidQualifiedName)),
+        }
+    }
+
+    /// Find the namespace that a prefix refers to, given the declarations of the current element
+    ///
+    /// Returns `None` if the prefix is not declared, or if the default namespace is empty.
+    fn lookup_namespace<'b>(
+        &'b self,
+        prefix: Option<&str>,
+        declarations: &'b [NamespaceDeclaration],
+    ) -> Option<&'b str> {
+        match prefix {
+            Some("xml") => return Some(XML_NAMESPACE),
+            Some("xmlns") => return Some(XMLNS_NAMESPACE),
+            _ => {},
+        }
+
+        let outer_declarations = self
+            .open_elements
+            .iter()
+            .rev()
+            .flat_map(|open_element| &open_element.namespace_declarations);
+
+        declarations
+            .iter()
+            .chain(outer_declarations)
+            .find(|declaration| declaration.prefix.as_deref() == prefix)
+            .map(|declaration| declaration.namespace.as_str())
+            .filter(|namespace| !namespace.is_empty())
+    }
+
+    fn current_node(&self) -> DomPtr<Node> {
+        match self.open_elements.last() {
+            Some(open_element) => open_element.element.clone().upcast(),
+            None => DomPtr::clone(&self.document).upcast(),
+        }
+    }
+
+    /// Append text to the current node, merging it with a preceding text node
+    fn insert_text(&self, text: &str) {
+        let parent = self.current_node();
+
+        if let Some(last_child) = parent.borrow().last_child()
+            && let Some(last_text) = last_child.try_into_type::<Text>()
+        {
+            last_text.borrow_mut().content_mut().push_str(text);
+            return;
+        }
+
+        let mut new_text = Text::default();
+        new_text.content_mut().push_str(text);
+        new_text.set_owning_document(DomPtr::clone(&self.document).downgrade());
+        Node::append_child(parent, DomPtr::new(new_text).upcast());
+    }
+
+    /// Register the stylesheets of `<style>` and `<link>` elements once their content is known
+    fn finish_element(&mut self, element: &DomPtr<Element>) {
+        if element.is_a::<HtmlStyleElement>() {
+            let mut css = String::new();
+            for child in element.borrow().children() {
+                if let Some(text) = child.try_into_type::<Text>() {
+                    css.push_str(text.borrow().content());
+                }
+            }
+
+            let stylesheet = css::Parser::new(&css, css::Origin::Author)
+                .parse_stylesheet(self.stylesheets.len());
+            if !stylesheet.rules().is_empty() || !stylesheet.errors().is_empty() {
+                self.stylesheets.push(stylesheet);
+            }
+        }
+
+        if let Some(link_element) = element.try_into_type::<HtmlLinkElement>() {
+            let link_element = link_element.borrow();
+            if link_element.relationship() == links::Relationship::Stylesheet
+                && let Some(url) = link_element.url()
+            {
+                let handle = RESOURCE_LOADER.schedule_load(url.clone());
+                self.pending_stylesheets
+                    .push((url, link_element.integrity(), handle));
+            }
+        }
+    }
+
+    fn finish_loading_stylesheets(&mut self) {
+        for (url, integrity, pending_stylesheet) in mem::take(&mut self.pending_stylesheets) {
+            let resource = match pending_stylesheet.block() {
+                Ok(resource) => resource,
+                Err(error) => {
+                    log::error!("Failed to load stylesheet: {url} could not be loaded ({error:?})");
+                    continue;
+                },
+            };
+
+            if !integrity::bytes_match_metadata(resource.data(), &integrity) {
+                log::error!(
+                    "Failed to load stylesheet: None of the digests in the integrity attribute match {url}, the stylesheet was blocked"
+                );
+                continue;
+            }
+
+            let css = String::from_utf8_lossy(resource.data());
+            let stylesheet = css::Parser::new(&css, css::Origin::Author)
+                .parse_stylesheet(self.stylesheets.len());
+            self.stylesheets.push(stylesheet);
+        }
+    }
+}
+
+/// <https://www.w3.org/TR/xml/#NT-NameStartChar>
+///
+/// All non-ascii characters are accepted, which is more lenient than the specification.
+fn is_name_start_char(c: char) -> bool {
+    c.is_ascii_alphabetic() || matches!(c, ':' | '_') || !c.is_ascii()
+}
+
+/// <https://www.w3.org/TR/xml/#NT-NameChar>
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c) || c.is_ascii_digit() || matches!(c, '-' | '.')
+}
+
+/// <https://www.w3.org/TR/xml/#NT-Char>
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::dom_objects::HtmlParagraphElement, static_interned};
+
+    fn parse(source: &str) -> Result<DomPtr<Document>, XmlError> {
+        let document = DomPtr::new(Document::default());
+        document
+            .borrow_mut()
+            .set_owning_document(DomPtr::clone(&document).downgrade());
+
+        Parser::new(source, document)
+            .parse()
+            .map(|(document, _)| document)
+    }
+
+    fn root_element(document: &DomPtr<Document>) -> DomPtr<Element> {
+        document
+            .borrow()
+            .children()
+            .iter()
+            .find_map(|child| child.try_into_type::<Element>())
+            .expect("document has no root element")
+    }
+
+    fn text_content(node: &DomPtr<Node>) -> String {
+        if let Some(text) = node.try_into_type::<Text>() {
+            return text.borrow().content().to_owned();
+        }
+
+        node.borrow().children().iter().map(text_content).collect()
+    }
+
+    #[test]
+    fn parse_elements_and_text() {
+        let document = parse(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>
+            <!-- A comment -->
+            <root id=\"main\" title='a\tb'>Fish &amp; chips<![CDATA[ <raw> ]]>&#x21;<empty/></root>",
+        )
+        .unwrap();
+
+        let root = root_element(&document);
+        assert_eq!(root.borrow().local_name(), "root".into());
+        assert_eq!(root.borrow().namespace(), Namespace::Other);
+        assert_eq!(
+            root.borrow().attributes().get(&static_interned!("title")),
+            Some(&"a b".into())
+        );
+        assert_eq!(text_content(&root.clone().upcast()), "Fish & chips <raw> !");
+        assert_eq!(root.borrow().children().len(), 2);
+    }
+
+    #[test]
+    fn resolve_namespaces() {
+        let document = parse(
+            "<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:svg=\"http://www.w3.org/2000/svg\">
+                <body><p>Text</p><svg:svg/><other xmlns=\"\"/></body>
+            </html>",
+        )
+        .unwrap();
+
+        let root = root_element(&document);
+        assert_eq!(root.borrow().namespace(), Namespace::HTML);
+
+        let body = root.borrow().children()[1]
+            .try_into_type::<Element>()
+            .unwrap();
+        let body = body.borrow();
+        let namespaces: Vec<Namespace> = body
+            .children()
+            .iter()
+            .map(|child| {
+                child
+                    .try_into_type::<Element>()
+                    .unwrap()
+                    .borrow()
+                    .namespace()
+            })
+            .collect();
+        assert_eq!(
+            namespaces,
+            [Namespace::HTML, Namespace::SVG, Namespace::Other]
+        );
+
+        // Elements in the html namespace get their html interface
+        assert!(body.children()[0].is_a::<HtmlParagraphElement>());
+    }
+
+    #[test]
+    fn reject_malformed_documents() {
+        let error = |source| {
+            parse(source)
+                .err()
+                .expect("document should not be well-formed")
+                .kind
+        };
+
+        assert_eq!(error(""), WellFormednessError::MissingRootElement);
+        assert_eq!(error("<a></b>"), WellFormednessError::MismatchedEndTag);
+        assert_eq!(error("<a>"), WellFormednessError::UnexpectedEndOfDocument);
+        assert_eq!(
+            error("<a/><b/>"),
+            WellFormednessError::ContentAfterRootElement
+        );
+        assert_eq!(
+            error("<a b='1' b='2'/>"),
+            WellFormednessError::DuplicateAttribute
+        );
+        assert_eq!(
+            error("<a b=1/>"),
+            WellFormednessError::UnquotedAttributeValue
+        );
+        assert_eq!(
+            error("<x:a/>"),
+            WellFormednessError::UndeclaredNamespacePrefix
+        );
+        assert_eq!(error("<a>&nbsp;</a>"), WellFormednessError::UnknownEntity);
+        assert_eq!(
+            error("<a>&#0;</a>"),
+            WellFormednessError::InvalidCharacterReference
+        );
+        assert_eq!(error("<a>]]></a>"), WellFormednessError::CDataEndInText);
+        assert_eq!(
+            error("<!-- a -- b --><a/>"),
+            WellFormednessError::DoubleHyphenInComment
+        );
+    }
+
+    #[test]
+    fn report_error_location() {
+        let error = parse("<a>\n  <b>\n</a>").err().unwrap();
+
+        assert_eq!(error.kind, WellFormednessError::MismatchedEndTag);
+        assert_eq!((error.line, error.column), (3, 5));
+    }
+}
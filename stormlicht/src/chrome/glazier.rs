@@ -1,4 +1,4 @@
-use image::{Rgbaf32, Texture};
+use image::Texture;
 use url::URL;
 use web::BrowsingContext;
 
@@ -20,6 +20,7 @@ pub struct BrowserApplication {
 
     repaint_required: RepaintRequired,
     composition: render::Composition,
+    backend: Box<dyn render::Backend>,
     window_handle: glazier::WindowHandle,
     browsing_context: BrowsingContext,
 }
@@ -38,8 +39,8 @@ impl glazier::WinHandler for BrowserApplication {
         }
     }
 
-    fn paint(&mut self, _invalid: &glazier::Region) {
-        self.view_buffer.clear(Rgbaf32::rgb(1., 1., 1.));
+    fn paint(&mut self, invalid: &glazier::Region) {
+        // The view buffer is not cleared, the composition only redraws the parts that changed
         self.composition.clear();
 
         let dpi = self
@@ -47,11 +48,22 @@ impl glazier::WinHandler for BrowserApplication {
             .get_scale()
             .expect("Could not access dpi scale");
 
-        self.composition.set_dpi((dpi.x() as f32, dpi.y() as f32));
+        // Areas invalidated by the windowing system (for example after being obscured)
+        // need to be redrawn too
+        for rect in invalid.rects() {
+            self.composition.invalidate(math::Rectangle::from_corners(
+                math::Vec2D::new(rect.x0 as f32, rect.y0 as f32),
+                math::Vec2D::new(rect.x1 as f32, rect.y1 as f32),
+            ));
+        }
 
-        self.browsing_context
-            .paint(&mut self.composition, self.viewport_size);
-        self.composition.render_to(&mut self.view_buffer);
+        let device = web::Device {
+            size: self.viewport_size,
+            device_pixel_ratio: dpi.x() as f32,
+        };
+        self.browsing_context.paint(&mut self.composition, device);
+        self.backend
+            .render(&mut self.composition, &mut self.view_buffer);
 
         if let Some(graphics_context) = &mut self.graphics_context {
             // Convert the RGBA slice (of u8) into 0RGB (of u32)
@@ -74,6 +86,12 @@ impl glazier::WinHandler for BrowserApplication {
             );
         }
         self.repaint_required = RepaintRequired::No;
+
+        // Running animations need to be updated in the next frame
+        if self.browsing_context.has_running_animations() {
+            self.repaint_required = RepaintRequired::Yes;
+            self.window_handle.request_anim_frame();
+        }
     }
 
     fn as_any(&mut self) -> &mut dyn std::any::Any {
@@ -108,8 +126,8 @@ pub fn run() -> ExitCode {
 
     let mut browsing_context = BrowsingContext::default();
     if let Err(error) = browsing_context.load(&url) {
+        // An error page is displayed instead
         log::error!("Failed to load {}: {error:?}", url.to_string());
-        return ExitCode::FAILURE;
     };
 
     // The view buffer is initialized once the window size method is called on startup.
@@ -123,6 +141,7 @@ pub fn run() -> ExitCode {
         viewport_size: (super::INITIAL_WIDTH, super::INITIAL_HEIGHT),
         repaint_required: RepaintRequired::Yes,
         composition: render::Composition::default(),
+        backend: render::create_backend(settings::SETTINGS.use_gpu),
         window_handle: glazier::WindowHandle::default(),
         browsing_context,
     };
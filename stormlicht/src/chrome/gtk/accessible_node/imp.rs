@@ -0,0 +1,90 @@
+use std::cell::{Cell, RefCell};
+
+use gtk::{gdk, glib, prelude::*, subclass::prelude::*};
+
+#[derive(glib::Properties)]
+#[properties(wrapper_type = super::AccessibleNode)]
+pub struct AccessibleNode {
+    #[property(name = "accessible-role", get, set, override_interface = gtk::Accessible)]
+    accessible_role: Cell<gtk::AccessibleRole>,
+
+    pub focusable: Cell<bool>,
+    pub focused: Cell<bool>,
+
+    /// Position and size relative to the parent
+    pub bounds: Cell<Option<(i32, i32, i32, i32)>>,
+
+    pub parent: glib::WeakRef<gtk::Accessible>,
+    pub next_sibling: glib::WeakRef<super::AccessibleNode>,
+
+    /// Children are owned by their parent, the root node is owned by the web view
+    pub children: RefCell<Vec<super::AccessibleNode>>,
+
+    at_context: RefCell<Option<gtk::ATContext>>,
+}
+
+impl Default for AccessibleNode {
+    fn default() -> Self {
+        Self {
+            accessible_role: Cell::new(gtk::AccessibleRole::Generic),
+            focusable: Cell::default(),
+            focused: Cell::default(),
+            bounds: Cell::default(),
+            parent: glib::WeakRef::default(),
+            next_sibling: glib::WeakRef::default(),
+            children: RefCell::default(),
+            at_context: RefCell::default(),
+        }
+    }
+}
+
+#[glib::object_subclass]
+impl ObjectSubclass for AccessibleNode {
+    const NAME: &'static str = "StormlichtAccessibleNode";
+    type Type = super::AccessibleNode;
+    type Interfaces = (gtk::Accessible,);
+}
+
+#[glib::derived_properties]
+impl ObjectImpl for AccessibleNode {}
+
+impl AccessibleImpl for AccessibleNode {
+    fn at_context(&self) -> Option<gtk::ATContext> {
+        // Objects that are not widgets have to create their own context
+        let mut at_context = self.at_context.borrow_mut();
+        if at_context.is_none() {
+            let display = gdk::Display::default()?;
+            *at_context =
+                gtk::ATContext::create(self.accessible_role.get(), &*self.obj(), &display);
+        }
+
+        at_context.clone()
+    }
+
+    fn platform_state(&self, state: gtk::AccessiblePlatformState) -> bool {
+        match state {
+            gtk::AccessiblePlatformState::Focusable => self.focusable.get(),
+            gtk::AccessiblePlatformState::Focused => self.focused.get(),
+            _ => false,
+        }
+    }
+
+    fn bounds(&self) -> Option<(i32, i32, i32, i32)> {
+        self.bounds.get()
+    }
+
+    fn accessible_parent(&self) -> Option<gtk::Accessible> {
+        self.parent.upgrade()
+    }
+
+    fn first_accessible_child(&self) -> Option<gtk::Accessible> {
+        self.children
+            .borrow()
+            .first()
+            .map(|child| child.clone().upcast())
+    }
+
+    fn next_accessible_sibling(&self) -> Option<gtk::Accessible> {
+        self.next_sibling.upgrade().map(|sibling| sibling.upcast())
+    }
+}
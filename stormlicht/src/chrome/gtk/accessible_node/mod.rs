@@ -0,0 +1,93 @@
+//! Exposes the accessibility tree of a page to assistive technologies
+//!
+//! GTK forwards accessible objects to AT-SPI, which is what screen readers on linux talk to.
+
+mod imp;
+
+use glib::Object;
+use gtk::{accessible::Property, glib, prelude::*, subclass::prelude::*};
+use web::accessibility::{self, Role};
+
+glib::wrapper! {
+    pub struct AccessibleNode(ObjectSubclass<imp::AccessibleNode>)
+        @implements gtk::Accessible;
+}
+
+impl AccessibleNode {
+    /// Create accessible objects for a node of the accessibility tree and all of its descendants
+    ///
+    /// `parent_origin` is the position of the parent within the page, in css pixels.
+    pub fn new(
+        node: &accessibility::AccessibleNode,
+        parent: &impl IsA<gtk::Accessible>,
+        parent_origin: (f32, f32),
+    ) -> Self {
+        let accessible: Self = Object::builder()
+            .property("accessible-role", gtk_role(node.role))
+            .build();
+
+        if !node.name.is_empty() {
+            accessible.update_property(&[Property::Label(&node.name)]);
+        }
+
+        if let Role::Heading { level } = node.role {
+            accessible.update_property(&[Property::Level(i32::from(level))]);
+        }
+
+        let imp = accessible.imp();
+        imp.focusable.set(node.states.focusable);
+        imp.focused.set(node.states.focused);
+        imp.parent.set(Some(parent.upcast_ref()));
+
+        // Bounds are relative to the parent, children of nodes without bounds are positioned
+        // relative to the closest ancestor that has bounds
+        let mut origin = parent_origin;
+        if let Some(bounds) = node.bounds {
+            let top_left = bounds.top_left();
+            imp.bounds.set(Some((
+                (top_left.x - parent_origin.0) as i32,
+                (top_left.y - parent_origin.1) as i32,
+                bounds.width() as i32,
+                bounds.height() as i32,
+            )));
+            origin = (top_left.x, top_left.y);
+        }
+
+        let children: Vec<Self> = node
+            .children
+            .iter()
+            .map(|child| Self::new(child, &accessible, origin))
+            .collect();
+
+        for siblings in children.windows(2) {
+            siblings[0].imp().next_sibling.set(Some(&siblings[1]));
+        }
+
+        imp.children.replace(children);
+        accessible
+    }
+}
+
+fn gtk_role(role: Role) -> gtk::AccessibleRole {
+    match role {
+        Role::Document => gtk::AccessibleRole::Document,
+        Role::Article => gtk::AccessibleRole::Article,
+        Role::Banner => gtk::AccessibleRole::Banner,
+        Role::Button => gtk::AccessibleRole::Button,
+        Role::Complementary => gtk::AccessibleRole::Complementary,
+        Role::ContentInfo => gtk::AccessibleRole::ContentInfo,
+        Role::Form => gtk::AccessibleRole::Form,
+        Role::Generic => gtk::AccessibleRole::Generic,
+        Role::Heading { .. } => gtk::AccessibleRole::Heading,
+        Role::Image => gtk::AccessibleRole::Img,
+        Role::Link => gtk::AccessibleRole::Link,
+        Role::List => gtk::AccessibleRole::List,
+        Role::ListItem => gtk::AccessibleRole::ListItem,
+        Role::Main => gtk::AccessibleRole::Main,
+        Role::Navigation => gtk::AccessibleRole::Navigation,
+        // GTK 4.10 does not have a paragraph role yet
+        Role::Paragraph => gtk::AccessibleRole::Group,
+        Role::Table => gtk::AccessibleRole::Table,
+        Role::Text => gtk::AccessibleRole::Label,
+    }
+}
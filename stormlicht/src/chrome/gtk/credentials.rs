@@ -0,0 +1,141 @@
+//! Asks the user for credentials when a server or proxy requires authentication
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{LazyLock, Mutex},
+};
+
+use adw::prelude::*;
+use gtk::{gio, glib};
+use http::authentication::{
+    AuthenticationRequest, AuthenticationTarget, CredentialPrompt, Credentials,
+};
+use settings::Command;
+
+const CANCEL: &str = "cancel";
+const LOG_IN: &str = "log-in";
+
+/// The credentials that the user entered during this session
+static CREDENTIALS: LazyLock<Mutex<HashMap<ProtectionSpace, Credentials>>> =
+    LazyLock::new(Mutex::default);
+
+/// Protection spaces for which a dialog is currently shown
+static PENDING_PROMPTS: LazyLock<Mutex<HashSet<ProtectionSpace>>> = LazyLock::new(Mutex::default);
+
+/// The set of resources that share the same credentials
+///
+/// <https://httpwg.org/specs/rfc9110.html#protection.space>
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct ProtectionSpace {
+    target: AuthenticationTarget,
+
+    /// The origin of the server, proxies don't have one
+    origin: Option<String>,
+    realm: Option<String>,
+}
+
+impl ProtectionSpace {
+    fn new(request: &AuthenticationRequest) -> Self {
+        let origin = match request.target {
+            AuthenticationTarget::Server => Some(request.url.origin().to_string()),
+            AuthenticationTarget::Proxy => None,
+        };
+
+        Self {
+            target: request.target,
+            origin,
+            realm: request.realm.clone(),
+        }
+    }
+}
+
+/// Show a dialog whenever a server or proxy asks for credentials
+pub fn install_prompt() {
+    resourceloader::set_credential_prompt(CredentialPrompt::new(|request| {
+        let protection_space = ProtectionSpace::new(request);
+        let mut credentials = CREDENTIALS.lock().expect("credentials lock was poisoned");
+
+        if request.previous_attempt_failed {
+            credentials.remove(&protection_space);
+        } else if let Some(credentials) = credentials.get(&protection_space) {
+            return Some(credentials.clone());
+        }
+
+        // The prompt is called from the threads that load resources, while the main thread
+        // may be waiting for the very same load. Instead of blocking until the user answered,
+        // the load fails and the page is reloaded once the credentials are known.
+        // Subresources from the same protection space share a single dialog
+        let is_first_prompt = PENDING_PROMPTS
+            .lock()
+            .expect("pending prompts lock was poisoned")
+            .insert(protection_space.clone());
+        if is_first_prompt {
+            let request = request.clone();
+            glib::idle_add_once(move || prompt(request, protection_space));
+        }
+        None
+    }));
+}
+
+fn prompt(request: AuthenticationRequest, protection_space: ProtectionSpace) {
+    let window = gio::Application::default()
+        .and_downcast::<gtk::Application>()
+        .and_then(|application| application.active_window());
+
+    let who = match request.target {
+        AuthenticationTarget::Server => request.url.origin().to_string(),
+        AuthenticationTarget::Proxy => "The proxy".to_string(),
+    };
+    let body = match &request.realm {
+        Some(realm) => format!("{who} asks for a username and password for \"{realm}\"."),
+        None => format!("{who} asks for a username and password."),
+    };
+
+    let dialog = adw::MessageDialog::new(
+        window.as_ref(),
+        Some("Authentication required"),
+        Some(&body),
+    );
+
+    let username = gtk::Entry::builder().placeholder_text("Username").build();
+    let password = gtk::PasswordEntry::builder()
+        .placeholder_text("Password")
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+    let entries = gtk::Box::new(gtk::Orientation::Vertical, 6);
+    entries.append(&username);
+    entries.append(&password);
+    dialog.set_extra_child(Some(&entries));
+
+    dialog.add_responses(&[(CANCEL, "Cancel"), (LOG_IN, "Log In")]);
+    dialog.set_response_appearance(LOG_IN, adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some(LOG_IN));
+    dialog.set_close_response(CANCEL);
+
+    dialog.connect_response(None, move |_, response| {
+        PENDING_PROMPTS
+            .lock()
+            .expect("pending prompts lock was poisoned")
+            .remove(&protection_space);
+
+        if response != LOG_IN {
+            return;
+        }
+
+        let credentials = Credentials::new(username.text().into(), password.text().into());
+        CREDENTIALS
+            .lock()
+            .expect("credentials lock was poisoned")
+            .insert(protection_space.clone(), credentials);
+
+        // Load the page again, this time the credentials are sent along
+        if let Some(window) = &window {
+            if let Err(error) = window.activate_action(Command::Reload.name(), None) {
+                log::error!("Failed to reload the page: {error}");
+            }
+        }
+    });
+
+    dialog.present();
+}
@@ -0,0 +1,191 @@
+//! Shows the progress of all downloads in the current session
+
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use gtk::{glib, pango, prelude::*};
+use resourceloader::{
+    download::{Download, DownloadId, DownloadState, DownloadStatus},
+    DOWNLOAD_MANAGER,
+};
+
+thread_local! {
+    /// All download lists that are currently shown, only accessed from the main thread
+    static LISTS: RefCell<Vec<DownloadList>> = const { RefCell::new(Vec::new()) };
+}
+
+struct DownloadList {
+    list: glib::WeakRef<gtk::ListBox>,
+    rows: HashMap<DownloadId, DownloadRow>,
+}
+
+struct DownloadRow {
+    file_name: gtk::Label,
+    details: gtk::Label,
+    progress: gtk::ProgressBar,
+    action: gtk::Button,
+}
+
+/// Fill the given list with all downloads and keep it up to date
+pub fn attach(list: &gtk::ListBox) {
+    let is_first_list = LISTS.with_borrow_mut(|lists| {
+        lists.push(DownloadList {
+            list: list.downgrade(),
+            rows: HashMap::new(),
+        });
+        lists.len() == 1
+    });
+
+    // Downloads report their progress from their own thread, the widgets must be updated on the main thread
+    if is_first_list {
+        DOWNLOAD_MANAGER.on_update(|download| {
+            let download = download.clone();
+            glib::idle_add_once(move || update(&download));
+        });
+    }
+
+    for download in DOWNLOAD_MANAGER.all() {
+        update(&download);
+    }
+}
+
+fn update(download: &Arc<Download>) {
+    let state = download.state();
+
+    LISTS.with_borrow_mut(|lists| {
+        // Forget about lists whose window was closed
+        lists.retain(|list| list.list.upgrade().is_some());
+
+        for download_list in lists.iter_mut() {
+            let Some(list) = download_list.list.upgrade() else {
+                continue;
+            };
+
+            download_list
+                .rows
+                .entry(download.id())
+                .or_insert_with(|| DownloadRow::new(&list, download.id()))
+                .update(&state);
+        }
+    });
+}
+
+impl DownloadRow {
+    fn new(list: &gtk::ListBox, id: DownloadId) -> Self {
+        let file_name = gtk::Label::builder()
+            .xalign(0.)
+            .ellipsize(pango::EllipsizeMode::Middle)
+            .build();
+        let details = gtk::Label::builder()
+            .xalign(0.)
+            .css_classes(["dim-label", "caption"])
+            .build();
+        let progress = gtk::ProgressBar::new();
+
+        let labels = gtk::Box::new(gtk::Orientation::Vertical, 4);
+        labels.set_hexpand(true);
+        labels.append(&file_name);
+        labels.append(&progress);
+        labels.append(&details);
+
+        let action = gtk::Button::builder()
+            .valign(gtk::Align::Center)
+            .css_classes(["flat", "circular"])
+            .build();
+        action.connect_clicked(move |_| {
+            let Some(download) = DOWNLOAD_MANAGER.get(id) else {
+                return;
+            };
+
+            match download.state().status {
+                DownloadStatus::InProgress => download.cancel(),
+                DownloadStatus::Cancelled | DownloadStatus::Failed => {
+                    download.resume();
+                },
+                DownloadStatus::Completed => {},
+            }
+        });
+
+        let row = gtk::Box::builder()
+            .orientation(gtk::Orientation::Horizontal)
+            .spacing(12)
+            .margin_top(6)
+            .margin_bottom(6)
+            .margin_start(6)
+            .margin_end(6)
+            .build();
+        row.append(&labels);
+        row.append(&action);
+
+        // Show the most recent download first
+        list.prepend(&row);
+
+        Self {
+            file_name,
+            details,
+            progress,
+            action,
+        }
+    }
+
+    fn update(&self, state: &DownloadState) {
+        self.file_name
+            .set_label(state.file_name.as_deref().unwrap_or("Starting download…"));
+
+        match state.total {
+            Some(total) if total != 0 => self
+                .progress
+                .set_fraction(state.received as f64 / total as f64),
+            _ if state.status == DownloadStatus::Completed => self.progress.set_fraction(1.),
+            _ => self.progress.pulse(),
+        }
+
+        let size = match state.total {
+            Some(total) => format!("{} of {}", format_size(state.received), format_size(total)),
+            None => format_size(state.received),
+        };
+
+        let (details, icon) = match state.status {
+            DownloadStatus::InProgress => (size, Some(("process-stop-symbolic", "Cancel"))),
+            DownloadStatus::Completed => (format_size(state.received), None),
+            DownloadStatus::Cancelled => (
+                format!("Cancelled — {size}"),
+                Some(("view-refresh-symbolic", "Resume")),
+            ),
+            DownloadStatus::Failed => (
+                format!("Failed — {size}"),
+                Some(("view-refresh-symbolic", "Retry")),
+            ),
+        };
+
+        self.details.set_label(&details);
+        self.progress
+            .set_visible(state.status == DownloadStatus::InProgress);
+
+        match icon {
+            Some((icon_name, tooltip)) => {
+                self.action.set_icon_name(icon_name);
+                self.action.set_tooltip_text(Some(tooltip));
+                self.action.set_visible(true);
+            },
+            None => self.action.set_visible(false),
+        }
+    }
+}
+
+/// Format a number of bytes for humans, like `1.5 MB`
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+
+    if bytes < 1000 {
+        return format!("{bytes} bytes");
+    }
+
+    let mut size = bytes as f64 / 1000.;
+    let mut unit = 0;
+    while size >= 1000. && unit < UNITS.len() - 1 {
+        size /= 1000.;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
@@ -1,8 +1,15 @@
+mod accessible_node;
+mod credentials;
+mod downloads;
+mod permissions;
 mod run;
+mod session;
+mod shortcuts;
 mod web_view;
 mod window;
 
 pub use run::run;
 
+use accessible_node::AccessibleNode;
 use web_view::WebView;
 use window::Window;
@@ -0,0 +1,56 @@
+//! Asks the user whether websites may use powerful features, like geolocation
+
+use adw::prelude::*;
+use gtk::{gio, glib};
+use settings::{PermissionRequest, PermissionState, PERMISSIONS};
+
+const ALLOW: &str = "allow";
+const BLOCK: &str = "block";
+
+/// Closing the dialog without answering denies the permission only this once
+const DISMISS: &str = "dismiss";
+
+/// Show a dialog whenever a website asks for a permission
+pub fn install_prompt_handler() {
+    // Permissions may be requested from any thread, but the dialog must be shown on the main thread
+    PERMISSIONS.on_prompt(|request| {
+        let request = request.clone();
+        glib::idle_add_once(move || prompt(request));
+    });
+}
+
+fn prompt(request: PermissionRequest) {
+    let window = gio::Application::default()
+        .and_downcast::<gtk::Application>()
+        .and_then(|application| application.active_window());
+
+    let dialog = adw::MessageDialog::new(
+        window.as_ref(),
+        Some("Allow this site?"),
+        Some(&format!(
+            "{} wants to {}.",
+            request.origin,
+            request.name.description()
+        )),
+    );
+    dialog.add_responses(&[(BLOCK, "Block"), (ALLOW, "Allow")]);
+    dialog.set_response_appearance(ALLOW, adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some(BLOCK));
+    dialog.set_close_response(DISMISS);
+
+    dialog.connect_response(None, move |_, response| {
+        let state = match response {
+            ALLOW => PermissionState::Granted,
+            BLOCK => PermissionState::Denied,
+
+            // The feature stays unusable, but the user is asked again the next time
+            _ => return,
+        };
+
+        if let Err(error) = PERMISSIONS.set(&request.origin, request.name, state) {
+            log::error!("Failed to save permission: {error}");
+        }
+    });
+
+    dialog.present();
+}
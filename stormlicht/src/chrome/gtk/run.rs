@@ -1,10 +1,9 @@
-use crate::chrome::{INITIAL_HEIGHT, INITIAL_WIDTH};
-
-use super::Window;
+use super::{credentials, permissions, session, shortcuts, Window};
 
 use std::process::ExitCode;
 
 use gtk::{gio, glib, prelude::*};
+use settings::{Theme, SETTINGS};
 
 const APP_ID: &str = "rs.stormlicht.browser";
 
@@ -20,20 +19,52 @@ pub fn run() -> ExitCode {
             application.quit();
         }),
     );
-    application.set_accels_for_action("app.quit", &["<Primary>Q"]);
     application.add_action(&quit);
 
-    application.set_accels_for_action("open-file", &["<Ctrl>O"]);
-
+    application.connect_startup(|application| {
+        shortcuts::install(application);
+        apply_theme(SETTINGS.preferences().theme);
+        track_system_color_scheme();
+        permissions::install_prompt_handler();
+        credentials::install_prompt();
+        session::install(application);
+    });
     application.connect_activate(build_ui);
 
+    // Preferences may be changed from any thread, but the theme must be updated on the main thread
+    SETTINGS.on_change(|preferences| {
+        let theme = preferences.theme;
+        glib::idle_add_once(move || apply_theme(theme));
+    });
+
     let glib_exit_code = application.run_with_args::<&'static str>(&[]);
     ExitCode::from(glib_exit_code.value() as u8)
 }
 
 fn build_ui(app: &adw::Application) {
     let window = Window::new(app);
-    window.set_default_width(INITIAL_WIDTH as i32);
-    window.set_default_height(INITIAL_HEIGHT as i32);
     window.present();
+
+    session::restore(app, &window);
+}
+
+/// Keep the settings informed about the color scheme of the desktop environment,
+/// which web pages can query with `prefers-color-scheme`
+fn track_system_color_scheme() {
+    let style_manager = adw::StyleManager::default();
+    SETTINGS.set_system_prefers_dark(style_manager.is_dark());
+
+    style_manager.connect_dark_notify(|style_manager| {
+        SETTINGS.set_system_prefers_dark(style_manager.is_dark());
+    });
+}
+
+fn apply_theme(theme: Theme) {
+    let color_scheme = match theme {
+        Theme::System => adw::ColorScheme::Default,
+        Theme::Light => adw::ColorScheme::ForceLight,
+        Theme::Dark => adw::ColorScheme::ForceDark,
+    };
+
+    adw::StyleManager::default().set_color_scheme(color_scheme);
 }
@@ -0,0 +1,97 @@
+//! Saves the open pages periodically and restores them when the browser starts again
+
+use adw::prelude::*;
+use gtk::glib;
+use settings::{SessionPage, SESSION};
+
+use super::Window;
+
+/// How often the open pages are saved, in seconds
+const SAVE_INTERVAL: u32 = 15;
+
+const RESTORE: &str = "restore";
+const START_NEW: &str = "start-new";
+
+/// Save the open pages periodically and mark the session as ended when the application quits
+pub fn install(application: &adw::Application) {
+    glib::timeout_add_seconds_local(
+        SAVE_INTERVAL,
+        glib::clone!(@weak application => @default-return glib::ControlFlow::Break, move || {
+            save(&application);
+            glib::ControlFlow::Continue
+        }),
+    );
+
+    application.connect_shutdown(|_| {
+        if let Err(error) = SESSION.end() {
+            log::error!("Failed to end session: {error}");
+        }
+    });
+}
+
+fn save(application: &adw::Application) {
+    let pages = application
+        .windows()
+        .iter()
+        .filter_map(|window| window.downcast_ref::<Window>())
+        .filter_map(Window::session_page)
+        .collect();
+
+    if let Err(error) = SESSION.save(pages) {
+        log::error!("Failed to save session: {error}");
+    }
+}
+
+/// Restore the pages of the previous session, starting with the given window
+///
+/// After a crash the pages are restored right away, otherwise the user is asked first.
+pub fn restore(application: &adw::Application, window: &Window) {
+    let pages = SESSION.take_previous_pages();
+    if pages.is_empty() {
+        return;
+    }
+
+    if SESSION.previous_session_crashed() {
+        log::info!(
+            "The previous session ended unexpectedly, restoring {} pages",
+            pages.len()
+        );
+        restore_pages(application, window, &pages);
+        return;
+    }
+
+    let body = match pages.len() {
+        1 => "A page was open when the browser was closed.".to_string(),
+        n => format!("{n} pages were open when the browser was closed."),
+    };
+    let dialog =
+        adw::MessageDialog::new(Some(window), Some("Restore previous session?"), Some(&body));
+    dialog.add_responses(&[(START_NEW, "Start New"), (RESTORE, "Restore")]);
+    dialog.set_response_appearance(RESTORE, adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some(RESTORE));
+    dialog.set_close_response(START_NEW);
+
+    dialog.connect_response(
+        None,
+        glib::clone!(@weak application, @weak window => move |_, response| {
+            if response == RESTORE {
+                restore_pages(&application, &window, &pages);
+            }
+        }),
+    );
+
+    dialog.present();
+}
+
+fn restore_pages(application: &adw::Application, window: &Window, pages: &[SessionPage]) {
+    let Some((first, others)) = pages.split_first() else {
+        return;
+    };
+
+    window.restore(first);
+    for page in others {
+        let window = Window::new(application);
+        window.present();
+        window.restore(page);
+    }
+}
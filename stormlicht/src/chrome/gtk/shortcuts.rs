@@ -0,0 +1,104 @@
+//! Binds the keyboard shortcuts from the settings to the actions of the browser interface
+
+use adw::prelude::*;
+use gtk::{gio, glib};
+use settings::{Command, Keybindings, SETTINGS};
+
+/// The action that is activated by the given command
+///
+/// Quitting is an action of the application, all other commands are actions of the window.
+#[must_use]
+pub fn action_name(command: Command) -> String {
+    match command {
+        Command::Quit => "app.quit".to_string(),
+        _ => command.name().to_string(),
+    }
+}
+
+/// Bind all keyboard shortcuts and keep them up to date when the preferences change
+pub fn install(application: &adw::Application) {
+    apply(application, &SETTINGS.preferences().keybindings());
+
+    // Preferences may be changed from any thread, but accelerators must be updated on the main thread
+    SETTINGS.on_change(|preferences| {
+        let keybindings = preferences.keybindings();
+        glib::idle_add_once(move || {
+            if let Some(application) =
+                gio::Application::default().and_downcast::<gtk::Application>()
+            {
+                apply(&application, &keybindings);
+            }
+        });
+    });
+}
+
+fn apply(application: &impl IsA<gtk::Application>, keybindings: &Keybindings) {
+    for command in Command::ALL {
+        // GTK rejects the whole list if one accelerator is invalid
+        let accelerators: Vec<&str> = keybindings
+            .accelerators(command)
+            .iter()
+            .map(String::as_str)
+            .filter(|accelerator| {
+                let is_valid = gtk::accelerator_parse(accelerator).is_some();
+                if !is_valid {
+                    log::warn!(
+                        "Ignoring invalid accelerator {accelerator:?} for {:?}",
+                        command.name()
+                    );
+                }
+                is_valid
+            })
+            .collect();
+
+        application.set_accels_for_action(&action_name(command), &accelerators);
+    }
+}
+
+/// Show a window that lists all commands together with their keyboard shortcuts
+pub fn show_overlay(parent: &impl IsA<gtk::Window>) {
+    let keybindings = SETTINGS.preferences().keybindings();
+
+    let list = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .margin_top(12)
+        .margin_bottom(12)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+
+    for command in Command::ALL {
+        let accelerators = keybindings.accelerators(command);
+        if accelerators.is_empty() {
+            continue;
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(command.description())
+            .build();
+        row.add_suffix(&gtk::ShortcutLabel::new(&accelerators.join(" ")));
+        list.append(&row);
+    }
+
+    let scrolled_window = gtk::ScrolledWindow::builder()
+        .hscrollbar_policy(gtk::PolicyType::Never)
+        .propagate_natural_height(true)
+        .vexpand(true)
+        .child(&list)
+        .build();
+
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    content.append(&adw::HeaderBar::new());
+    content.append(&scrolled_window);
+
+    adw::Window::builder()
+        .title("Keyboard Shortcuts")
+        .modal(true)
+        .transient_for(parent)
+        .default_width(420)
+        .default_height(520)
+        .content(&content)
+        .build()
+        .present();
+}
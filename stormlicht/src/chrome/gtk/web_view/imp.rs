@@ -1,34 +1,87 @@
 use image::{Rgbaf32, Texture};
 use sl_std::safe_casts::cast_slice;
 use url::URL;
-use web::{BrowsingContext, BrowsingContextError};
+use web::{
+    accessibility::AccessibilityTree, drag_and_drop::DropEffect, spellcheck::SpellingCorrection,
+    BrowsingContext, BrowsingContextError, FileChooserRequest, FocusDirection,
+};
 
-use std::{cell::RefCell, mem};
+use std::{
+    cell::{Cell, RefCell},
+    mem,
+    path::PathBuf,
+    sync::Arc,
+};
 
 use adw::subclass::prelude::*;
-use gtk::{gdk, glib, prelude::*, CompositeTemplate};
+use gtk::{gdk, gio, glib, prelude::*, CompositeTemplate};
+
+use crate::chrome::gtk::AccessibleNode;
+
+/// The zoom factors that the page can be displayed at
+const ZOOM_LEVELS: [f32; 13] = [
+    0.3, 0.5, 0.67, 0.8, 0.9, 1., 1.1, 1.25, 1.5, 1.75, 2., 2.5, 3.,
+];
+
+/// The index of the zoom factor `1` in [ZOOM_LEVELS]
+const DEFAULT_ZOOM_LEVEL: usize = 5;
 
 #[derive(CompositeTemplate, Default)]
 #[template(resource = "/rs/stormlicht/ui/web_view.ui")]
 pub struct WebView {
     state: RefCell<State>,
+
+    /// Whether a paint is scheduled to run once the main loop is idle
+    paint_scheduled: Cell<bool>,
+
+    /// The most recent frame of the compositor, drawn until the next one is ready
+    frame: RefCell<Option<gdk::MemoryTexture>>,
+
+    /// The accessibility tree that is currently exposed to assistive technologies
+    accessibility_tree: RefCell<Option<AccessibilityTree>>,
+
+    /// The accessible object for the root of [Self::accessibility_tree]
+    accessible_root: RefCell<Option<AccessibleNode>>,
+
+    /// The popover that was most recently opened with a right click
+    context_menu: RefCell<Option<gtk::PopoverMenu>>,
+
+    /// The misspelled word that the context menu offers corrections for
+    spelling_correction: RefCell<Option<SpellingCorrection>>,
 }
 
+#[derive(Default)]
 struct State {
-    view_buffer: Texture,
     browsing_context: BrowsingContext,
-    composition: render::Composition,
+
+    /// Rasterizes the page, `None` if the compositor thread could not be started
+    compositor: Option<render::Compositor>,
+
+    /// The device that the page was most recently painted for
+    painted_device: Option<web::Device>,
+
+    /// Whether the page might have changed since it was last painted
+    needs_paint: bool,
     url: Option<URL>,
+    zoom_level: ZoomLevel,
+
+    /// Where to scroll the page once it was laid out, in CSS pixels
+    pending_scroll_position: Option<math::Vec2D>,
 }
 
-impl Default for State {
+/// An index into [ZOOM_LEVELS]
+#[derive(Clone, Copy, Debug)]
+struct ZoomLevel(usize);
+
+impl ZoomLevel {
+    fn factor(self) -> f32 {
+        ZOOM_LEVELS[self.0]
+    }
+}
+
+impl Default for ZoomLevel {
     fn default() -> Self {
-        Self {
-            view_buffer: Texture::new(0, 0),
-            browsing_context: BrowsingContext::default(),
-            composition: render::Composition::default(),
-            url: None,
-        }
+        Self(DEFAULT_ZOOM_LEVEL)
     }
 }
 
@@ -37,64 +90,170 @@ impl ObjectSubclass for WebView {
     const NAME: &'static str = "WebView";
     type Type = super::WebView;
     type ParentType = gtk::Widget;
+    type Interfaces = (gtk::Accessible,);
 }
 
 impl ObjectImpl for WebView {
     fn constructed(&self) {
         self.parent_constructed();
+
+        // Frames are rendered on the compositor thread, but must be drawn on the main thread
+        let widget: glib::SendWeakRef<super::WebView> = self.obj().downgrade().into();
+        let compositor = render::Compositor::spawn(settings::SETTINGS.use_gpu, move || {
+            let widget = widget.clone();
+            glib::idle_add_once(move || {
+                if let Some(widget) = widget.upgrade() {
+                    widget.queue_draw();
+                }
+            });
+        });
+
+        match compositor {
+            Ok(compositor) => self.state.borrow_mut().compositor = Some(compositor),
+            Err(error) => log::error!("Failed to start compositor thread: {error}"),
+        }
+
+        self.attach_drop_target();
+        self.attach_context_menu();
+    }
+
+    fn dispose(&self) {
+        if let Some(context_menu) = self.context_menu.take() {
+            context_menu.unparent();
+        }
     }
 }
 
 impl WidgetImpl for WebView {
-    fn snapshot(&self, snapshot: &gtk::Snapshot) {
-        let widget = self.obj();
+    fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
+        self.parent_size_allocate(width, height, baseline);
 
-        let device_width = widget.width();
-        let device_height = widget.height();
+        // Popovers need to be positioned again when their parent changes size
+        if let Some(context_menu) = self.context_menu.borrow().as_ref() {
+            context_menu.present();
+        }
+    }
 
-        if device_width <= 0 || device_height <= 0 {
+    fn snapshot(&self, snapshot: &gtk::Snapshot) {
+        let Some((device, _)) = self.device() else {
             return;
+        };
+
+        // The page is painted again for the new size, until then the previous frame is stretched
+        if self.state.borrow().painted_device != Some(device) {
+            self.schedule_paint();
         }
 
-        let scale_factor = widget.scale_factor();
-        self.state
-            .borrow_mut()
-            .composition
-            .set_dpi((scale_factor as f32, scale_factor as f32));
+        if let Some(frame) = self
+            .state
+            .borrow()
+            .compositor
+            .as_ref()
+            .and_then(render::Compositor::take_frame)
+        {
+            let (width, height) = (frame.texture.width(), frame.texture.height());
+            let texture = gdk::MemoryTexture::new(
+                width as i32,
+                height as i32,
+                gdk::MemoryFormat::R32g32b32a32FloatPremultiplied,
+                &glib::Bytes::from_owned(FrameData(frame.texture)),
+                mem::size_of::<Rgbaf32>() * width,
+            );
+            self.frame.replace(Some(texture));
+        }
 
-        let window_width = (device_width * scale_factor) as usize;
-        let window_height = (device_height * scale_factor) as usize;
+        // Until the compositor finished the first frame, there is nothing to draw
+        if let Some(frame) = &*self.frame.borrow() {
+            let zoom = self.state.borrow().zoom_level.factor();
+            let (device_width, device_height) = device.size;
+            frame.snapshot(
+                snapshot,
+                f64::from(device_width as f32 * zoom),
+                f64::from(device_height as f32 * zoom),
+            );
+        }
 
-        self.state
-            .borrow_mut()
-            .view_buffer
-            .resize_buffer(window_width as usize, window_height as usize);
+        // Running animations need to be updated in the next frame
+        if self
+            .state
+            .borrow()
+            .browsing_context
+            .has_running_animations()
+        {
+            self.schedule_paint();
+        }
+    }
+}
 
-        self.state
-            .borrow_mut()
-            .paint(device_width as u16, device_height as u16);
+/// Lets GTK read the pixels of a frame without copying them
+///
+/// The compositor renders into the texture again once GTK released it.
+struct FrameData(Arc<Texture>);
 
-        let state = self.state.borrow();
-        let buffer_bytes: &[u8] = cast_slice(state.view_buffer.data());
+impl AsRef<[u8]> for FrameData {
+    fn as_ref(&self) -> &[u8] {
+        cast_slice(self.0.data())
+    }
+}
 
-        gdk::MemoryTexture::new(
-            window_width as i32,
-            window_height as i32,
-            gdk::MemoryFormat::R32g32b32a32FloatPremultiplied,
-            &glib::Bytes::from(buffer_bytes),
-            mem::size_of::<Rgbaf32>() * window_width as usize,
-        )
-        .snapshot(snapshot, device_width as f64, device_height as f64);
+impl AccessibleImpl for WebView {
+    fn first_accessible_child(&self) -> Option<gtk::Accessible> {
+        self.accessible_root
+            .borrow()
+            .as_ref()
+            .map(|root| root.clone().upcast())
     }
 }
 
 impl WebView {
     pub fn load_url(&self, url: &URL) -> Result<(), BrowsingContextError> {
         let mut state = self.state.borrow_mut();
-        state.browsing_context.load(url)?;
+
+        // A failed load still displays an error page, reloading should retry the failed url
+        let result = state.browsing_context.load(url);
         state.url = Some(url.clone());
-        self.obj().queue_draw();
-        Ok(())
+        drop(state);
+
+        self.schedule_paint();
+        result
+    }
+
+    /// Follow a link on the current page
+    ///
+    /// Unlike [load_url](Self::load_url), the navigation is made on behalf of the page,
+    /// so urls that only the user may open are refused.
+    pub fn navigate_url(&self, url: &URL) -> Result<(), BrowsingContextError> {
+        let mut state = self.state.borrow_mut();
+
+        let result = state.browsing_context.navigate(url);
+        state.url = Some(url.clone());
+        drop(state);
+
+        self.schedule_paint();
+        result
+    }
+
+    /// Load the given url and scroll to `scroll_position` once the page is displayed
+    pub fn restore(
+        &self,
+        url: &URL,
+        scroll_position: math::Vec2D,
+    ) -> Result<(), BrowsingContextError> {
+        let result = self.load_url(url);
+        self.state.borrow_mut().pending_scroll_position = Some(scroll_position);
+        result
+    }
+
+    pub fn url(&self) -> Option<URL> {
+        self.state.borrow().url.clone()
+    }
+
+    /// The top left corner of the viewport within the page, in CSS pixels
+    pub fn scroll_position(&self) -> math::Vec2D {
+        let mut state = self.state.borrow_mut();
+        state.sync_scroll_position();
+        let position = state.browsing_context.scroll_position();
+        math::Vec2D::new(f32::from(position.x), f32::from(position.y))
     }
 
     pub fn reload(&self) -> Result<(), BrowsingContextError> {
@@ -105,30 +264,523 @@ impl WebView {
         self.load_url(&url)
     }
 
-    pub fn handle_mouse_move(&self, x: f64, y: f64) {
-        let event_location = math::Vec2D {
-            x: x as i32,
-            y: y as i32,
+    /// Print the current page to a PDF document, returns `None` if there is no page
+    pub fn print_to_pdf(&self) -> Option<Vec<u8>> {
+        let document = self.state.borrow_mut().browsing_context.print_to_pdf();
+
+        // The page needs to be laid out for the screen again
+        self.schedule_paint();
+        document
+    }
+
+    pub fn set_reader_mode(&self, enabled: bool) -> bool {
+        let is_active = self
+            .state
+            .borrow_mut()
+            .browsing_context
+            .set_reader_mode(enabled);
+        self.schedule_paint();
+        is_active
+    }
+
+    pub fn move_focus(&self, direction: FocusDirection) -> bool {
+        let has_focus = self
+            .state
+            .borrow_mut()
+            .browsing_context
+            .move_focus(direction);
+        self.schedule_paint();
+        has_focus
+    }
+
+    pub fn traverse_history(&self, delta: isize) {
+        let mut state = self.state.borrow_mut();
+        state.browsing_context.traverse_history(delta);
+        state.url = state.browsing_context.url();
+        drop(state);
+
+        self.schedule_paint();
+    }
+
+    /// Move `steps` zoom levels up (or down)
+    pub fn zoom_by(&self, steps: isize) {
+        let mut state = self.state.borrow_mut();
+        let level = state
+            .zoom_level
+            .0
+            .saturating_add_signed(steps)
+            .min(ZOOM_LEVELS.len() - 1);
+        state.zoom_level = ZoomLevel(level);
+        drop(state);
+
+        self.schedule_paint();
+    }
+
+    pub fn reset_zoom(&self) {
+        self.state.borrow_mut().zoom_level = ZoomLevel::default();
+        self.schedule_paint();
+    }
+
+    /// Convert a position within the widget to CSS pixels on the page
+    fn to_page_coordinates(&self, x: f64, y: f64) -> math::Vec2D<i32> {
+        let zoom = f64::from(self.state.borrow().zoom_level.factor());
+
+        math::Vec2D {
+            x: (x / zoom) as i32,
+            y: (y / zoom) as i32,
+        }
+    }
+
+    /// The device that the page is displayed on and the size of the frames for it, in pixels
+    ///
+    /// Returns `None` if the widget has no size yet.
+    fn device(&self) -> Option<(web::Device, (usize, usize))> {
+        let widget = self.obj();
+        let zoom = self.state.borrow().zoom_level.factor();
+
+        // Zooming makes every CSS pixel cover more pixels on the screen
+        let device = match settings::SETTINGS.device_emulation {
+            Some(emulated_device) => web::Device {
+                size: (
+                    (emulated_device.width as f32 / zoom) as u16,
+                    (emulated_device.height as f32 / zoom) as u16,
+                ),
+                device_pixel_ratio: emulated_device.device_pixel_ratio * zoom,
+            },
+            None => {
+                let (width, height) = (widget.width(), widget.height());
+                if width <= 0 || height <= 0 {
+                    return None;
+                }
+
+                web::Device {
+                    size: ((width as f32 / zoom) as u16, (height as f32 / zoom) as u16),
+                    device_pixel_ratio: widget.scale_factor() as f32 * zoom,
+                }
+            },
+        };
+
+        let (device_width, device_height) = device.size;
+        let window_width = (device_width as f32 * device.device_pixel_ratio).ceil() as usize;
+        let window_height = (device_height as f32 * device.device_pixel_ratio).ceil() as usize;
+        Some((device, (window_width, window_height)))
+    }
+
+    /// Paint the page again once the main loop is idle
+    ///
+    /// Painting runs scripts and layout. Waiting until GTK handled all pending input and
+    /// drew the frames that the compositor finished keeps scrolling smooth while the page is busy.
+    fn schedule_paint(&self) {
+        self.state.borrow_mut().needs_paint = true;
+        if self.paint_scheduled.replace(true) {
+            return;
+        }
+
+        let web_view = self.obj().downgrade();
+        glib::idle_add_local_full(glib::Priority::DEFAULT_IDLE, move || {
+            if let Some(web_view) = web_view.upgrade() {
+                web_view.imp().paint();
+            }
+            glib::ControlFlow::Break
+        });
+    }
+
+    /// Paint the page and hand the layers to the compositor, which asks for the frame to be drawn
+    fn paint(&self) {
+        self.paint_scheduled.set(false);
+        let Some((device, size)) = self.device() else {
+            return;
         };
 
+        let mut state = self.state.borrow_mut();
+
+        // Scrolling that was handled by the compositor needs to be seen by the page
+        state.sync_scroll_position();
+
+        if state.needs_paint || state.painted_device != Some(device) {
+            state.paint(device, size);
+        }
+
+        // Restored pages can only be scrolled after their first layout
+        if let Some(position) = state.pending_scroll_position.take() {
+            let current_position = state.browsing_context.scroll_position();
+            let current_position =
+                math::Vec2D::new(f32::from(current_position.x), f32::from(current_position.y));
+            state
+                .browsing_context
+                .apply_scroll_delta(position - current_position);
+            drop(state);
+            self.schedule_paint();
+        } else {
+            drop(state);
+        }
+
+        // Painting might have caused a relayout, which rebuilds the accessibility tree
+        self.update_accessibility_tree();
+    }
+
+    pub fn focused_link(&self) -> Option<URL> {
+        self.state.borrow().browsing_context.focused_link()
+    }
+
+    /// Expose the current accessibility tree of the page, if it changed since the last update
+    fn update_accessibility_tree(&self) {
+        let state = self.state.borrow();
+        let tree = state.browsing_context.accessibility_tree();
+
+        if tree == self.accessibility_tree.borrow().as_ref() {
+            return;
+        }
+
+        let root = tree.map(|tree| AccessibleNode::new(tree.root(), &*self.obj(), (0., 0.)));
+        self.accessibility_tree.replace(tree.cloned());
+        self.accessible_root.replace(root);
+    }
+
+    pub fn handle_mouse_move(&self, x: f64, y: f64) {
+        let event_location = self.to_page_coordinates(x, y);
+
         let mouse_event = web::event::MouseEvent::move_to(event_location);
 
+        let mut state = self.state.borrow_mut();
+        state.sync_scroll_position();
+        state.browsing_context.handle_mouse_event(mouse_event);
+        drop(state);
+
+        self.schedule_paint();
+    }
+
+    pub fn handle_mouse_press(&self, x: f64, y: f64) {
+        let event_location = self.to_page_coordinates(x, y);
+
+        let mouse_event =
+            web::event::MouseEvent::button_down(event_location, web::event::MouseButton::Left);
+
+        let mut state = self.state.borrow_mut();
+        state.sync_scroll_position();
+        state.browsing_context.handle_mouse_event(mouse_event);
+        let file_chooser_request = state.browsing_context.take_file_chooser_request();
+        drop(state);
+
+        if let Some(request) = file_chooser_request {
+            self.show_file_chooser(request);
+        }
+
+        self.schedule_paint();
+    }
+
+    /// Let the user pick files for a `<input type=file>` element
+    fn show_file_chooser(&self, request: FileChooserRequest) {
+        let allows_multiple_files = request.allows_multiple_files();
+
+        let dialog = gtk::FileDialog::builder()
+            .title(if allows_multiple_files {
+                "Choose Files"
+            } else {
+                "Choose File"
+            })
+            .accept_label("Select")
+            .modal(true)
+            .build();
+
+        // The page suggests which types of files to pick, but any file may be chosen
+        let accepted_types = request.accepted_types();
+        if !accepted_types.is_empty() {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(&accepted_types.join(", ")));
+            for accepted_type in &accepted_types {
+                match accepted_type.strip_prefix('.') {
+                    Some(suffix) => filter.add_suffix(suffix),
+                    None => filter.add_mime_type(accepted_type),
+                }
+            }
+
+            let all_files = gtk::FileFilter::new();
+            all_files.set_name(Some("All files"));
+            all_files.add_pattern("*");
+
+            let filters = gio::ListStore::new::<gtk::FileFilter>();
+            filters.append(&filter);
+            filters.append(&all_files);
+            dialog.set_filters(Some(&filters));
+            dialog.set_default_filter(Some(&filter));
+        }
+
+        let window = self.obj().root().and_downcast::<gtk::Window>();
+        let web_view = self.obj().downgrade();
+        glib::spawn_future_local(async move {
+            let result = if allows_multiple_files {
+                dialog
+                    .open_multiple_future(window.as_ref())
+                    .await
+                    .map(|files| {
+                        files
+                            .iter::<gio::File>()
+                            .filter_map(Result::ok)
+                            .filter_map(|file| file.path())
+                            .collect()
+                    })
+            } else {
+                dialog
+                    .open_future(window.as_ref())
+                    .await
+                    .map(|file| file.path().into_iter().collect())
+            };
+
+            let paths: Vec<PathBuf> = match result {
+                Ok(paths) => paths,
+                Err(error) => {
+                    if !error.matches(gtk::DialogError::Dismissed) {
+                        log::error!("Failed to choose files: {error}");
+                    }
+                    vec![]
+                },
+            };
+
+            let Some(web_view) = web_view.upgrade() else {
+                return;
+            };
+            web_view
+                .imp()
+                .state
+                .borrow_mut()
+                .browsing_context
+                .complete_file_chooser(request, &paths);
+            web_view.imp().schedule_paint();
+        });
+    }
+
+    /// Accept files that are dragged onto the page from other applications
+    fn attach_drop_target(&self) {
+        let drop_target = gtk::DropTarget::new(gdk::FileList::static_type(), gdk::DragAction::COPY);
+
+        let web_view = self.obj();
+        drop_target.connect_enter(glib::clone!(@weak web_view => @default-return gdk::DragAction::empty(), move |_, x, y| {
+            web_view.imp().handle_drag_motion(x, y)
+        }));
+        drop_target.connect_motion(glib::clone!(@weak web_view => @default-return gdk::DragAction::empty(), move |_, x, y| {
+            web_view.imp().handle_drag_motion(x, y)
+        }));
+        drop_target.connect_leave(glib::clone!(@weak web_view => move |_| {
+            web_view.imp().handle_drag_event(web::event::DragEvent::Leave);
+        }));
+        drop_target.connect_drop(
+            glib::clone!(@weak web_view => @default-return false, move |_, value, x, y| {
+                let Ok(files) = value.get::<gdk::FileList>() else {
+                    return false;
+                };
+
+                let paths = files.files().iter().filter_map(|file| file.path()).collect();
+                let drag_event = web::event::DragEvent::Drop {
+                    position: web_view.imp().to_page_coordinates(x, y),
+                    paths,
+                };
+                web_view.imp().handle_drag_event(drag_event) != DropEffect::None
+            }),
+        );
+
+        web_view.add_controller(drop_target);
+    }
+
+    /// Offer corrections for misspelled words when the page is right-clicked
+    fn attach_context_menu(&self) {
+        let web_view = self.obj();
+
+        let gesture = gtk::GestureClick::builder()
+            .button(gdk::BUTTON_SECONDARY)
+            .build();
+        gesture.connect_pressed(glib::clone!(@weak web_view => move |gesture, _, x, y| {
+            if web_view.imp().show_context_menu(x, y) {
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+            }
+        }));
+        web_view.add_controller(gesture);
+
+        let replace = gio::SimpleAction::new("replace", Some(glib::VariantTy::STRING));
+        replace.connect_activate(glib::clone!(@weak web_view => move |_, parameter| {
+            let Some(suggestion) = parameter.and_then(|parameter| parameter.get::<String>()) else {
+                return;
+            };
+            web_view.imp().apply_spelling_correction(&suggestion);
+        }));
+
+        // Shown instead of the suggestions if there are none
+        let no_suggestions = gio::SimpleAction::new("no-suggestions", None);
+        no_suggestions.set_enabled(false);
+
+        let actions = gio::SimpleActionGroup::new();
+        actions.add_action(&replace);
+        actions.add_action(&no_suggestions);
+        web_view.insert_action_group("spelling", Some(&actions));
+    }
+
+    /// Open the context menu at the given position, returns `false` if there is nothing to show
+    fn show_context_menu(&self, x: f64, y: f64) -> bool {
+        let position = self.to_page_coordinates(x, y);
+
+        let mut state = self.state.borrow_mut();
+        state.sync_scroll_position();
+        let correction = state.browsing_context.spelling_correction_at(position);
+        drop(state);
+
+        let Some(correction) = correction else {
+            return false;
+        };
+
+        let menu = gio::Menu::new();
+        for suggestion in correction.suggestions() {
+            let item = gio::MenuItem::new(Some(suggestion), None);
+            item.set_action_and_target_value(
+                Some("spelling.replace"),
+                Some(&suggestion.to_variant()),
+            );
+            menu.append_item(&item);
+        }
+        if correction.suggestions().is_empty() {
+            menu.append(Some("No Suggestions"), Some("spelling.no-suggestions"));
+        }
+        self.spelling_correction.replace(Some(correction));
+
+        if let Some(previous_menu) = self.context_menu.take() {
+            previous_menu.unparent();
+        }
+
+        let context_menu = gtk::PopoverMenu::from_model(Some(&menu));
+        context_menu.set_parent(&*self.obj());
+        context_menu.set_has_arrow(false);
+        context_menu.set_halign(gtk::Align::Start);
+        context_menu.set_pointing_to(Some(&gdk::Rectangle::new(x as i32, y as i32, 1, 1)));
+        context_menu.popup();
+        self.context_menu.replace(Some(context_menu));
+
+        true
+    }
+
+    fn apply_spelling_correction(&self, suggestion: &str) {
+        let Some(correction) = self.spelling_correction.take() else {
+            return;
+        };
+
         self.state
             .borrow_mut()
             .browsing_context
-            .handle_mouse_event(mouse_event);
+            .apply_spelling_correction(correction, suggestion);
+        self.schedule_paint();
+    }
+
+    fn handle_drag_motion(&self, x: f64, y: f64) -> gdk::DragAction {
+        let drag_event = web::event::DragEvent::Move(self.to_page_coordinates(x, y));
+
+        match self.handle_drag_event(drag_event) {
+            DropEffect::None => gdk::DragAction::empty(),
+            DropEffect::Copy => gdk::DragAction::COPY,
+            DropEffect::Link => gdk::DragAction::LINK,
+            DropEffect::Move => gdk::DragAction::MOVE,
+        }
+    }
+
+    fn handle_drag_event(&self, drag_event: web::event::DragEvent) -> DropEffect {
+        let mut state = self.state.borrow_mut();
+        state.sync_scroll_position();
+        let drop_effect = state.browsing_context.handle_drag_event(drag_event);
+        drop(state);
+
+        self.schedule_paint();
+        drop_effect
+    }
+
+    pub fn handle_scroll(&self, dx: f64, dy: f64) {
+        let wheel_event = web::event::WheelEvent {
+            delta: math::Vec2D {
+                x: dx as f32,
+                y: dy as f32,
+            },
+        };
+
+        let mut state = self.state.borrow_mut();
+        match &state.compositor {
+            // The compositor scrolls without waiting for the page to be painted again,
+            // the page learns about it once the main loop is idle
+            Some(compositor) => compositor.scroll_by(wheel_event.distance()),
+            None => state.browsing_context.handle_wheel_event(wheel_event),
+        }
+        drop(state);
+
+        self.schedule_paint();
+    }
+
+    pub fn handle_key_press(&self, key_event: web::event::KeyEvent) -> bool {
+        let mut state = self.state.borrow_mut();
+        state.sync_scroll_position();
+        let was_handled = state.browsing_context.handle_key_press(key_event);
+        drop(state);
+
+        if was_handled {
+            self.schedule_paint();
+        }
+        was_handled
+    }
+
+    pub fn handle_composition_event(
+        &self,
+        composition_event: web::event::CompositionEvent,
+    ) -> bool {
+        let was_handled = self
+            .state
+            .borrow_mut()
+            .browsing_context
+            .handle_composition_event(composition_event);
+
+        if was_handled {
+            self.schedule_paint();
+        }
+        was_handled
+    }
 
-        self.obj().queue_draw();
+    pub fn accepts_text_input(&self) -> bool {
+        self.state.borrow().browsing_context.accepts_text_input()
+    }
+
+    pub fn text_input_area(&self) -> Option<gdk::Rectangle> {
+        let state = self.state.borrow();
+        let area = state.browsing_context.text_input_area()?;
+        let zoom = state.zoom_level.factor();
+
+        Some(gdk::Rectangle::new(
+            (area.top_left().x * zoom) as i32,
+            (area.top_left().y * zoom) as i32,
+            (area.width() * zoom) as i32,
+            (area.height() * zoom) as i32,
+        ))
     }
 }
 
 impl State {
-    fn paint(&mut self, width: u16, height: u16) {
-        self.view_buffer.clear(Rgbaf32::rgb(1., 1., 1.));
-        self.composition.clear();
+    /// Paint the page and hand the layers to the compositor
+    ///
+    /// `size` is the size of the frame in pixels.
+    fn paint(&mut self, device: web::Device, size: (usize, usize)) {
+        let layer_tree = self.browsing_context.paint_layers(device);
+        if let Some(compositor) = &self.compositor {
+            compositor.commit(layer_tree, size);
+        }
 
-        self.browsing_context
-            .paint(&mut self.composition, (width, height));
-        self.composition.render_to(&mut self.view_buffer);
+        self.painted_device = Some(device);
+        self.needs_paint = false;
+    }
+
+    /// Let the page know about scrolling that was handled by the compositor
+    fn sync_scroll_position(&mut self) {
+        let Some(compositor) = &mut self.compositor else {
+            return;
+        };
+
+        let delta = compositor.take_scroll_delta();
+        if !delta.is_origin() {
+            self.browsing_context.apply_scroll_delta(delta);
+
+            // Scroll events are dispatched during the next paint
+            self.needs_paint = true;
+        }
     }
 }
@@ -1,8 +1,10 @@
-use std::error::Report;
+use std::{error::Report, fs, path::Path};
 
 use glib::Object;
-use gtk::{glib, subclass::prelude::*};
+use gtk::{gdk, glib, subclass::prelude::*};
+use settings::SessionPage;
 use url::URL;
+use web::FocusDirection;
 
 mod imp;
 
@@ -25,6 +27,47 @@ impl WebView {
         }
     }
 
+    /// Follow a link on the current page
+    pub fn navigate(&self, url: &URL) {
+        if let Err(error) = self.imp().navigate_url(url) {
+            log::error!(
+                "Failed to navigate to {url}:\n{}",
+                Report::new(error).pretty(true).show_backtrace(true)
+            );
+        }
+    }
+
+    /// Load a page that was open in a previous session
+    pub fn restore(&self, page: &SessionPage) {
+        let url = match page.url.parse::<URL>() {
+            Ok(url) => url,
+            Err(error) => {
+                log::error!("Failed to parse {:?} as a URL: {error:?}", page.url);
+                return;
+            },
+        };
+
+        let scroll_position = math::Vec2D::new(page.scroll_x, page.scroll_y);
+        if let Err(error) = self.imp().restore(&url, scroll_position) {
+            log::error!(
+                "Failed to load {url}:\n{}",
+                Report::new(error).pretty(true).show_backtrace(true)
+            );
+        }
+    }
+
+    /// The page that is currently displayed, as it should be restored in the next session
+    pub fn session_page(&self) -> Option<SessionPage> {
+        let url = self.imp().url()?;
+        let scroll_position = self.imp().scroll_position();
+
+        Some(SessionPage {
+            url: url.to_string(),
+            scroll_x: scroll_position.x,
+            scroll_y: scroll_position.y,
+        })
+    }
+
     pub fn reload(&self) {
         if let Err(error) = self.imp().reload() {
             log::error!(
@@ -34,9 +77,88 @@ impl WebView {
         }
     }
 
+    /// Save the current page as a PDF document
+    pub fn print_to_pdf(&self, path: &Path) {
+        let Some(document) = self.imp().print_to_pdf() else {
+            log::warn!("There is no page to print");
+            return;
+        };
+
+        match fs::write(path, document) {
+            Ok(()) => log::info!("Saved page to {}", path.display()),
+            Err(error) => log::error!("Failed to write {}: {error}", path.display()),
+        }
+    }
+
+    /// Enable or disable reader mode, returns whether reader mode is active afterwards
+    pub fn set_reader_mode(&self, enabled: bool) -> bool {
+        self.imp().set_reader_mode(enabled)
+    }
+
+    /// Move `delta` entries back (or forward) through the session history
+    pub fn traverse_history(&self, delta: isize) {
+        self.imp().traverse_history(delta);
+    }
+
+    /// Display the page one zoom level larger
+    pub fn zoom_in(&self) {
+        self.imp().zoom_by(1);
+    }
+
+    /// Display the page one zoom level smaller
+    pub fn zoom_out(&self) {
+        self.imp().zoom_by(-1);
+    }
+
+    pub fn reset_zoom(&self) {
+        self.imp().reset_zoom();
+    }
+
+    /// Move the keyboard focus within the page, returns `false` if there is nothing left to focus
+    pub fn move_focus(&self, direction: FocusDirection) -> bool {
+        self.imp().move_focus(direction)
+    }
+
+    /// The url of the focused link, if any
+    pub fn focused_link(&self) -> Option<URL> {
+        self.imp().focused_link()
+    }
+
     pub fn handle_mouse_move(&self, x: f64, y: f64) {
         self.imp().handle_mouse_move(x, y);
     }
+
+    pub fn handle_mouse_press(&self, x: f64, y: f64) {
+        self.imp().handle_mouse_press(x, y);
+    }
+
+    /// Scroll the page by the given number of lines
+    pub fn handle_scroll(&self, dx: f64, dy: f64) {
+        self.imp().handle_scroll(dx, dy);
+    }
+
+    /// Let the page react to a key press, returns `false` if the page did not handle it
+    pub fn handle_key_press(&self, key_event: web::event::KeyEvent) -> bool {
+        self.imp().handle_key_press(key_event)
+    }
+
+    /// Let the page react to text from an input method, returns `false` if the page did not handle it
+    pub fn handle_composition_event(
+        &self,
+        composition_event: web::event::CompositionEvent,
+    ) -> bool {
+        self.imp().handle_composition_event(composition_event)
+    }
+
+    /// Whether key presses should be passed to an input method before handling them
+    pub fn accepts_text_input(&self) -> bool {
+        self.imp().accepts_text_input()
+    }
+
+    /// The area of the focused text control, relative to the widget
+    pub fn text_input_area(&self) -> Option<gdk::Rectangle> {
+        self.imp().text_input_area()
+    }
 }
 
 impl Default for WebView {
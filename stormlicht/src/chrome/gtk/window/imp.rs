@@ -1,11 +1,18 @@
+use std::cell::{Cell, RefCell};
+
 use adw::subclass::prelude::*;
 use gtk::prelude::*;
-use gtk::{glib, CompositeTemplate};
+use gtk::{gdk, glib, CompositeTemplate};
 
 use glib::subclass::InitializingObject;
+use settings::Command;
 use url::URL;
+use web::{
+    event::{CompositionEvent, Key, KeyEvent},
+    FocusDirection,
+};
 
-use crate::chrome::gtk::WebView;
+use crate::chrome::gtk::{downloads, shortcuts, WebView};
 
 #[derive(CompositeTemplate, Default)]
 #[template(resource = "/rs/stormlicht/ui/window.ui")]
@@ -13,11 +20,32 @@ pub struct Window {
     #[template_child]
     pub reload_button: TemplateChild<gtk::Button>,
 
+    #[template_child]
+    pub reader_mode_button: TemplateChild<gtk::ToggleButton>,
+
     #[template_child]
     pub search_bar: TemplateChild<gtk::Entry>,
 
     #[template_child]
     pub web_view: TemplateChild<WebView>,
+
+    #[template_child]
+    pub downloads_list: TemplateChild<gtk::ListBox>,
+
+    #[template_child]
+    pub key_controller: TemplateChild<gtk::EventControllerKey>,
+
+    /// Turns key presses into text that cannot be typed directly, like CJK characters
+    input_method: gtk::IMMulticontext,
+
+    /// Whether the input method is currently composing text in the web view
+    is_composing: Cell<bool>,
+
+    /// The text that the user typed into the url bar, without the completion from the history
+    typed_url: RefCell<String>,
+
+    /// Set while the text of the url bar is changed by the browser instead of the user
+    is_setting_url: Cell<bool>,
 }
 
 #[glib::object_subclass]
@@ -32,13 +60,13 @@ impl ObjectSubclass for Window {
         klass.bind_template_callbacks();
 
         klass.install_action_async(
-            "open-file",
+            Command::OpenFile.name(),
             None,
             |win, _action_name, _action_target| async move {
                 match win.open_file_dialog().await {
                     Ok(file_path) => match URL::try_from(file_path.as_path()) {
                         Ok(url) => {
-                            win.imp().web_view.load(&url);
+                            win.imp().load(&url);
                         },
                         Err(e) => {
                             log::error!("Failed to parse path as url: {e:?}");
@@ -48,6 +76,48 @@ impl ObjectSubclass for Window {
                 }
             },
         );
+
+        klass.install_action_async(
+            Command::PrintToPdf.name(),
+            None,
+            |win, _action_name, _action_target| async move {
+                match win.print_to_pdf_dialog().await {
+                    Ok(file_path) => win.imp().web_view.print_to_pdf(&file_path),
+                    Err(error) => log::error!("Error choosing where to save the PDF: {error}"),
+                }
+            },
+        );
+
+        klass.install_action(Command::Reload.name(), None, |win, _, _| {
+            win.imp().handle_reload_page();
+        });
+        klass.install_action(Command::Back.name(), None, |win, _, _| {
+            win.imp().web_view.traverse_history(-1);
+        });
+        klass.install_action(Command::Forward.name(), None, |win, _, _| {
+            win.imp().web_view.traverse_history(1);
+        });
+        klass.install_action(Command::ZoomIn.name(), None, |win, _, _| {
+            win.imp().web_view.zoom_in();
+        });
+        klass.install_action(Command::ZoomOut.name(), None, |win, _, _| {
+            win.imp().web_view.zoom_out();
+        });
+        klass.install_action(Command::ResetZoom.name(), None, |win, _, _| {
+            win.imp().web_view.reset_zoom();
+        });
+        klass.install_action(Command::ToggleReaderMode.name(), None, |win, _, _| {
+            let button = &win.imp().reader_mode_button;
+            button.set_active(!button.is_active());
+        });
+        klass.install_action(Command::FocusAddressBar.name(), None, |win, _, _| {
+            let search_bar = &win.imp().search_bar;
+            search_bar.grab_focus();
+            search_bar.select_region(0, -1);
+        });
+        klass.install_action(Command::ShowShortcuts.name(), None, |win, _, _| {
+            shortcuts::show_overlay(win);
+        });
     }
 
     fn instance_init(obj: &InitializingObject<Self>) {
@@ -58,6 +128,9 @@ impl ObjectSubclass for Window {
 impl ObjectImpl for Window {
     fn constructed(&self) {
         self.parent_constructed();
+
+        downloads::attach(&self.downloads_list);
+        self.attach_input_method();
     }
 }
 
@@ -66,12 +139,141 @@ impl WidgetImpl for Window {}
 impl WindowImpl for Window {}
 impl ApplicationWindowImpl for Window {}
 
+impl Window {
+    fn load(&self, url: &URL) {
+        self.web_view.load(url);
+
+        // Navigating always leaves reader mode
+        self.reader_mode_button.set_active(false);
+    }
+
+    /// Replace the text of the url bar without completing it
+    pub(super) fn set_url_text(&self, text: &str) {
+        self.is_setting_url.set(true);
+        self.search_bar.set_text(text);
+        self.is_setting_url.set(false);
+    }
+
+    /// Complete the url that the user typed from the history, selecting the completed part
+    ///
+    /// Typing on replaces the selection, so the completion never gets in the way.
+    fn complete_url(&self, typed: &str) {
+        // The user kept typing in the meantime
+        if self.search_bar.text().as_str() != typed {
+            return;
+        }
+
+        let Some(completion) = settings::HISTORY.complete(typed) else {
+            return;
+        };
+
+        self.set_url_text(&completion);
+        self.search_bar
+            .select_region(typed.chars().count() as i32, -1);
+    }
+
+    /// Follow a link on the current page, on behalf of the page
+    fn follow_link(&self, url: &URL) {
+        self.web_view.navigate(url);
+        self.reader_mode_button.set_active(false);
+    }
+
+    fn attach_input_method(&self) {
+        let input_method = &self.input_method;
+        input_method.set_client_widget(Some(&*self.web_view));
+
+        let window = self.obj();
+        input_method.connect_preedit_start(glib::clone!(@weak window => move |_| {
+            window.imp().start_composition();
+        }));
+        input_method.connect_preedit_changed(glib::clone!(@weak window => move |input_method| {
+            let (text, _, _) = input_method.preedit_string();
+            window.imp().update_composition(text.as_str());
+        }));
+        input_method.connect_preedit_end(glib::clone!(@weak window => move |_| {
+            window.imp().end_composition(String::new());
+        }));
+        input_method.connect_commit(glib::clone!(@weak window => move |_, text| {
+            window.imp().commit_text(text);
+        }));
+    }
+
+    /// Give the input method a chance to handle the current key event
+    ///
+    /// Returns `true` if the input method consumed the event.
+    fn filter_through_input_method(&self) -> bool {
+        if !self.web_view.accepts_text_input() {
+            return false;
+        }
+
+        self.key_controller
+            .current_event()
+            .is_some_and(|event| self.input_method.filter_keypress(&event))
+    }
+
+    /// Tell the input method where to display its candidates
+    fn update_input_method_cursor(&self) {
+        if let Some(area) = self.web_view.text_input_area() {
+            self.input_method.set_cursor_location(&area);
+        }
+    }
+
+    fn start_composition(&self) {
+        if self.is_composing.replace(true) {
+            return;
+        }
+
+        self.web_view
+            .handle_composition_event(CompositionEvent::Start);
+        self.update_input_method_cursor();
+    }
+
+    fn update_composition(&self, text: &str) {
+        // Some input methods change the preedit string without announcing the start of a composition
+        self.start_composition();
+
+        self.web_view
+            .handle_composition_event(CompositionEvent::Update(text.to_owned()));
+        self.update_input_method_cursor();
+    }
+
+    fn end_composition(&self, text: String) {
+        if self.is_composing.replace(false) {
+            self.web_view
+                .handle_composition_event(CompositionEvent::End(text));
+        }
+    }
+
+    fn commit_text(&self, text: &str) {
+        if self.is_composing.get() {
+            self.end_composition(text.to_owned());
+            return;
+        }
+
+        // Text that was committed without composing it first is typed like regular key presses
+        for c in text.chars() {
+            self.web_view
+                .handle_key_press(KeyEvent::new(Key::Character(c)));
+        }
+    }
+}
+
 #[gtk::template_callbacks]
 impl Window {
     #[template_callback]
     fn handle_url_entered(&self) {
         let text = self.search_bar.buffer().text();
-        let url = match URL::from_user_input(text.as_str()) {
+        let text = text.trim();
+
+        if is_search(text) {
+            match settings::SETTINGS.preferences().search_url(text) {
+                Some(url) => self.load(&url),
+                None => log::error!("Invalid search engine, cannot search for {text:?}"),
+            }
+            return;
+        }
+
+        let url = match URL::from_user_input(text) {
             Ok(parsed_url) => parsed_url,
             Err(error) => {
                 log::error!("Failed to parse {text:?} as a URL: {error:?}");
@@ -79,16 +281,154 @@ impl Window {
             },
         };
 
-        self.web_view.load(&url);
+        // Typed urls are preferred when autocompleting
+        if let Err(error) = settings::HISTORY.record_typed(&url) {
+            log::error!("Failed to update history: {error}");
+        }
+
+        self.load(&url);
+    }
+
+    #[template_callback]
+    fn handle_url_changed(&self) {
+        if self.is_setting_url.get() {
+            return;
+        }
+
+        let text = self.search_bar.text().to_string();
+        let previous = self.typed_url.replace(text.clone());
+
+        // Deleting the completion must not bring it back
+        if text.len() <= previous.len() || !text.starts_with(previous.as_str()) {
+            return;
+        }
+
+        // The entry updates its cursor after notifying about the change
+        let window = self.obj();
+        glib::idle_add_local_once(glib::clone!(@weak window => move || {
+            window.imp().complete_url(&text);
+        }));
     }
 
     #[template_callback]
     fn handle_reload_page(&self) {
-        self.web_view.reload()
+        self.web_view.reload();
+        self.reader_mode_button.set_active(false);
+    }
+
+    #[template_callback]
+    fn handle_toggle_reader_mode(&self) {
+        let requested = self.reader_mode_button.is_active();
+        let is_active = self.web_view.set_reader_mode(requested);
+
+        // Not every page has an article that can be displayed in reader mode
+        if is_active != requested {
+            self.reader_mode_button.set_active(is_active);
+        }
+    }
+
+    #[template_callback]
+    fn on_key_pressed(&self, key: gdk::Key, _keycode: u32, modifiers: gdk::ModifierType) -> bool {
+        // Returning true stops the key press from being handled by other widgets
+        if self.filter_through_input_method() {
+            return true;
+        }
+
+        let shift = modifiers.contains(gdk::ModifierType::SHIFT_MASK);
+        let press = |key| {
+            self.web_view
+                .handle_key_press(KeyEvent::new(key).with_shift(shift))
+        };
+
+        match key {
+            gdk::Key::Tab | gdk::Key::ISO_Left_Tab => {
+                let direction = if key == gdk::Key::ISO_Left_Tab
+                    || modifiers.contains(gdk::ModifierType::SHIFT_MASK)
+                {
+                    FocusDirection::Backward
+                } else {
+                    FocusDirection::Forward
+                };
+
+                // Once the last element of the page was passed, focus moves on to the browser interface
+                self.web_view.move_focus(direction)
+            },
+            gdk::Key::Return | gdk::Key::KP_Enter => {
+                if press(Key::Enter) {
+                    return true;
+                }
+
+                match self.web_view.focused_link() {
+                    Some(url) => {
+                        self.follow_link(&url);
+                        true
+                    },
+                    None => false,
+                }
+            },
+            gdk::Key::Up => press(Key::ArrowUp),
+            gdk::Key::Down => press(Key::ArrowDown),
+            gdk::Key::Left => press(Key::ArrowLeft),
+            gdk::Key::Right => press(Key::ArrowRight),
+            gdk::Key::Home => press(Key::Home),
+            gdk::Key::End => press(Key::End),
+            gdk::Key::space => press(Key::Space),
+            gdk::Key::Escape => press(Key::Escape),
+            gdk::Key::BackSpace => press(Key::Backspace),
+            gdk::Key::Delete => press(Key::Delete),
+            _ => {
+                // Keys that are part of a shortcut never produce text
+                let is_shortcut = modifiers
+                    .intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK);
+
+                match key.to_unicode() {
+                    Some(c) if !c.is_control() && !is_shortcut => press(Key::Character(c)),
+                    _ => false,
+                }
+            },
+        }
+    }
+
+    #[template_callback]
+    fn on_key_released(&self, _key: gdk::Key, _keycode: u32, _modifiers: gdk::ModifierType) {
+        // Some input methods react to key releases as well
+        self.filter_through_input_method();
+    }
+
+    #[template_callback]
+    fn on_focus_enter(&self) {
+        self.input_method.focus_in();
+    }
+
+    #[template_callback]
+    fn on_focus_leave(&self) {
+        self.input_method.focus_out();
     }
 
     #[template_callback]
     fn on_mouse_move(&self, x: f64, y: f64) {
         self.web_view.handle_mouse_move(x, y);
     }
+
+    #[template_callback]
+    fn on_mouse_pressed(&self, _n_press: i32, x: f64, y: f64) {
+        self.web_view.grab_focus();
+
+        // Clicking somewhere else ends the current composition
+        self.input_method.reset();
+        self.web_view.handle_mouse_press(x, y);
+    }
+
+    #[template_callback]
+    fn on_scroll(&self, dx: f64, dy: f64) -> bool {
+        self.web_view.handle_scroll(dx, dy);
+        true
+    }
+}
+
+/// Whether text that was entered into the url bar should be searched for instead of loaded
+///
+/// Anything that does not look like a host name or a path is treated as search terms.
+fn is_search(text: &str) -> bool {
+    text.contains(char::is_whitespace) || (!text.contains(['.', ':', '/']) && text != "localhost")
 }
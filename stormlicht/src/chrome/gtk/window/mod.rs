@@ -3,7 +3,10 @@ mod imp;
 use std::path::PathBuf;
 
 use glib::Object;
-use gtk::{gio, glib, prelude::*};
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use settings::SessionPage;
+
+use crate::chrome::{INITIAL_HEIGHT, INITIAL_WIDTH};
 
 glib::wrapper! {
     pub struct Window(ObjectSubclass<imp::Window>)
@@ -14,8 +17,22 @@ glib::wrapper! {
 
 impl Window {
     pub fn new(app: &adw::Application) -> Self {
-        // Create new window
-        Object::builder().property("application", app).build()
+        Object::builder()
+            .property("application", app)
+            .property("default-width", INITIAL_WIDTH as i32)
+            .property("default-height", INITIAL_HEIGHT as i32)
+            .build()
+    }
+
+    /// The page that is displayed in the window, as it should be restored in the next session
+    pub fn session_page(&self) -> Option<SessionPage> {
+        self.imp().web_view.session_page()
+    }
+
+    /// Display a page that was open in a previous session
+    pub fn restore(&self, page: &SessionPage) {
+        self.imp().set_url_text(&page.url);
+        self.imp().web_view.restore(page);
     }
 
     async fn open_file_dialog(&self) -> Result<PathBuf, glib::Error> {
@@ -38,4 +55,26 @@ impl Window {
 
         Ok(path)
     }
+
+    async fn print_to_pdf_dialog(&self) -> Result<PathBuf, glib::Error> {
+        let filter = gtk::FileFilter::new();
+        filter.add_mime_type("application/pdf");
+        filter.set_name(Some(".pdf"));
+
+        let filters = gio::ListStore::new::<gtk::FileFilter>();
+        filters.append(&filter);
+
+        let dialog = gtk::FileDialog::builder()
+            .title("Print to PDF")
+            .accept_label("Save")
+            .initial_name("page.pdf")
+            .modal(true)
+            .filters(&filters)
+            .build();
+
+        let file = dialog.save_future(Some(self)).await?;
+        let path = file.path().expect("Path should always exist");
+
+        Ok(path)
+    }
 }
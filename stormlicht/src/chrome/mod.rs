@@ -13,7 +13,7 @@ cfg_match! {
 }
 
 /// Initial viewport width, in display points
-const INITIAL_WIDTH: u16 = 800;
+pub(crate) const INITIAL_WIDTH: u16 = 800;
 
 /// Initial viewport height, in display points
-const INITIAL_HEIGHT: u16 = 600;
+pub(crate) const INITIAL_HEIGHT: u16 = 600;
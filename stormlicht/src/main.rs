@@ -1,6 +1,9 @@
 #![feature(panic_update_hook, cfg_match, error_reporter)]
 
 mod chrome;
+mod print;
+#[cfg(unix)]
+mod remote_control;
 
 use std::{process::ExitCode, sync::LazyLock};
 
@@ -33,5 +36,31 @@ pub fn main() -> ExitCode {
     // Initialize settings object
     LazyLock::force(&SETTINGS);
 
+    if SETTINGS.trace_file.is_some() {
+        trace::enable();
+    }
+
+    let exit_code = run();
+
+    if let Some(trace_file) = &SETTINGS.trace_file {
+        match trace::save(trace_file) {
+            Ok(()) => log::info!("Saved trace to {}", trace_file.display()),
+            Err(error) => log::error!("Failed to save trace to {}: {error}", trace_file.display()),
+        }
+    }
+
+    exit_code
+}
+
+fn run() -> ExitCode {
+    if let Some(output) = &SETTINGS.print_to_pdf {
+        return print::run(output);
+    }
+
+    #[cfg(unix)]
+    if let Some(socket) = &SETTINGS.remote_control {
+        return remote_control::run(socket);
+    }
+
     chrome::run()
 }
@@ -0,0 +1,34 @@
+//! Prints the initial page to a PDF file instead of opening a window
+
+use std::{error::Report, fs, path::Path, process::ExitCode};
+
+use settings::SETTINGS;
+use web::BrowsingContext;
+
+pub fn run(output: &Path) -> ExitCode {
+    let url = &SETTINGS.url;
+    let mut browsing_context = BrowsingContext::default();
+    if let Err(error) = browsing_context.load(url) {
+        // An error page is printed instead
+        log::error!(
+            "Failed to load {url}:\n{}",
+            Report::new(error).pretty(true).show_backtrace(true)
+        );
+    }
+
+    let Some(document) = browsing_context.print_to_pdf() else {
+        log::error!("There is no page to print");
+        return ExitCode::FAILURE;
+    };
+
+    match fs::write(output, document) {
+        Ok(()) => {
+            log::info!("Saved {url} to {}", output.display());
+            ExitCode::SUCCESS
+        },
+        Err(error) => {
+            log::error!("Failed to write {}: {error}", output.display());
+            ExitCode::FAILURE
+        },
+    }
+}
@@ -0,0 +1,86 @@
+//! Serves remote control commands over a unix socket instead of opening a window
+//!
+//! Every line that is received on a connection is a command, and every command is answered
+//! with a single line. See [web::remote_control] for the available commands.
+
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::Path,
+    process::ExitCode,
+};
+
+use web::remote_control::{Command, RemoteControl};
+
+pub fn run(socket: &Path) -> ExitCode {
+    // A socket that was left behind by a previous session would make binding fail
+    if fs::metadata(socket).is_ok_and(|metadata| metadata.file_type().is_socket()) {
+        if let Err(error) = fs::remove_file(socket) {
+            log::error!(
+                "Failed to remove stale socket {}: {error}",
+                socket.display()
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let listener = match UnixListener::bind(socket) {
+        Ok(listener) => listener,
+        Err(error) => {
+            log::error!("Failed to listen on {}: {error}", socket.display());
+            return ExitCode::FAILURE;
+        },
+    };
+
+    let device = match settings::SETTINGS.device_emulation {
+        Some(emulated_device) => web::Device {
+            size: (emulated_device.width, emulated_device.height),
+            device_pixel_ratio: emulated_device.device_pixel_ratio,
+        },
+        None => web::Device {
+            size: (crate::chrome::INITIAL_WIDTH, crate::chrome::INITIAL_HEIGHT),
+            device_pixel_ratio: 1.,
+        },
+    };
+
+    let mut remote_control = RemoteControl::new(device);
+    let url = settings::SETTINGS.url.clone();
+    if let Err(error) = remote_control.execute(Command::Navigate(url.clone())) {
+        // An error page is displayed instead
+        log::error!("Failed to load {}: {error:?}", url.to_string());
+    }
+
+    log::info!("Accepting remote control commands on {}", socket.display());
+
+    // The browsing context cannot be shared between threads, so connections
+    // are served one after another
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| serve(&mut remote_control, stream));
+
+        if let Err(error) = result {
+            log::warn!("Remote control connection failed: {error}");
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn serve(remote_control: &mut RemoteControl, stream: UnixStream) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = remote_control.handle_message(&line);
+        writeln!(writer, "{response}")?;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use std::{fmt::Write, str::Chars};
+use std::str::Chars;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum UnescapeStringError {
@@ -58,21 +58,3 @@ pub fn unescape_str(text: &str) -> Result<String, UnescapeStringError> {
     }
     Ok(result)
 }
-
-/// unlike the [std version](https://doc.rust-lang.org/std/primitive.str.html#method.escape_unicode),
-/// this doesn't use braces and hex codes are uppercase(`\uABCD` instead of `\u{abcd}`).
-/// Ascii characters are not escaped
-pub fn unicode_escape(text: &str) -> String {
-    let mut result = String::new();
-    for c in text.chars() {
-        match c {
-            '\x20'..='\x7e' => result.push(c),
-            ..='\u{FFFF}' => {
-                let code = c as u32;
-                let _ = write!(result, "\\u{code:0>4X}");
-            },
-            _ => result.push(c),
-        }
-    }
-    result
-}
@@ -3,11 +3,12 @@
 mod escape;
 
 use clap::Parser;
+use serialize_json::{JsonSerializer, Value};
 use web::html::tokenization::{
     IgnoreParseErrors, ParseErrorHandler, Token, Tokenizer, TokenizerState,
 };
 
-use crate::escape::{unescape_str, unicode_escape};
+use crate::escape::unescape_str;
 
 #[derive(Debug, Default, Parser)]
 #[command(version, about, long_about = None)]
@@ -54,7 +55,8 @@ fn main() -> Result<(), Error> {
         }
     }
 
-    let result = format!("[{}]", serialized_tokens.join(","));
+    let result = JsonSerializer::serialize_to_string(Value::List(serialized_tokens))
+        .expect("Writing to a string cannot fail");
     println!("{result}");
 
     Ok(())
@@ -72,69 +74,54 @@ fn parse_initial_state(initial_state: &str) -> Result<TokenizerState, Error> {
     }
 }
 
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn optional_string(s: Option<impl ToString>) -> Value {
+    s.map_or(Value::Null, |s| Value::String(s.to_string()))
+}
+
 fn serialize_token<P: ParseErrorHandler>(
     token: Token,
     tokenizer: &mut Tokenizer<P>,
-    serialized_tokens: &mut Vec<String>,
+    serialized_tokens: &mut Vec<Value>,
 ) -> bool {
     match token {
         Token::DOCTYPE(doctype) => {
-            let name = doctype
-                .name
-                .map(|s| format!("\"{s}\""))
-                .unwrap_or("null".to_string());
-            let public_id = doctype
-                .public_ident
-                .map(|s| format!("\"{s}\""))
-                .unwrap_or("null".to_string());
-            let system_id = doctype
-                .system_ident
-                .map(|s| format!("\"{s}\""))
-                .unwrap_or("null".to_string());
-            let force_quirks = doctype.force_quirks;
-
-            serialized_tokens.push(format!(
-                "[\"DOCTYPE\", {}, {}, {}, {:?}]",
-                unicode_escape(&name),
-                unicode_escape(&public_id),
-                unicode_escape(&system_id),
-                !force_quirks,
-            ));
+            serialized_tokens.push(Value::List(vec![
+                string("DOCTYPE"),
+                optional_string(doctype.name),
+                optional_string(doctype.public_ident),
+                optional_string(doctype.system_ident),
+                Value::Boolean(!doctype.force_quirks),
+            ]));
         },
         Token::StartTag(tagdata) => {
             let attributes = tagdata
                 .attributes
                 .iter()
-                .map(|(key, value)| {
-                    format!(
-                        "\"{}\": \"{}\"",
-                        unicode_escape(&key.to_string()),
-                        unicode_escape(&value.to_string())
-                    )
-                })
-                .collect::<Vec<String>>()
-                .join(",");
-            let serialized_token = if tagdata.self_closing {
-                format!(
-                    "[\"StartTag\", \"{}\", {{{attributes}}}, true]",
-                    unicode_escape(&tagdata.name.to_string()),
-                )
-            } else {
-                format!(
-                    "[\"StartTag\", \"{}\", {{{attributes}}}]",
-                    unicode_escape(&tagdata.name.to_string()),
-                )
-            };
-            serialized_tokens.push(serialized_token);
+                .map(|(key, value)| (key.to_string(), Value::String(value.to_string())))
+                .collect();
+
+            let mut serialized_token = vec![
+                string("StartTag"),
+                Value::String(tagdata.name.to_string()),
+                Value::Map(attributes),
+            ];
+            if tagdata.self_closing {
+                serialized_token.push(Value::Boolean(true));
+            }
+            serialized_tokens.push(Value::List(serialized_token));
         },
         Token::EndTag(tagdata) => {
-            serialized_tokens.push(format!(
-                "[\"EndTag\", \"{}\"]",
-                unicode_escape(&tagdata.name.to_string()),
-            ));
+            serialized_tokens.push(Value::List(vec![
+                string("EndTag"),
+                Value::String(tagdata.name.to_string()),
+            ]));
         },
         Token::Comment(comment) => {
-            serialized_tokens.push(format!("[\"Comment\", \"{}\"]", unicode_escape(&comment)));
+            serialized_tokens.push(Value::List(vec![string("Comment"), Value::String(comment)]));
         },
         Token::EOF => {
             return true;
@@ -147,7 +134,7 @@ fn serialize_token<P: ParseErrorHandler>(
                     Some(Token::Character(c)) => data.push(c),
                     Some(other) => {
                         serialized_tokens
-                            .push(format!("[\"Character\", \"{}\"]", unicode_escape(&data)));
+                            .push(Value::List(vec![string("Character"), Value::String(data)]));
                         return serialize_token(other, tokenizer, serialized_tokens);
                     },
                     None => {